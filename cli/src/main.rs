@@ -24,18 +24,30 @@
 
 use anyhow::{anyhow, Result};
 use async_channel::Sender;
+use endbasic_client::TokenStore;
 use endbasic_core::exec::Signal;
+use endbasic_repl::autosave::{print_recovery_notice, Autosave, AutosaveCommand};
+use endbasic_repl::history::{FileHistoryStore, History, DEFAULT_MAX_ENTRIES};
+use endbasic_std::audio::Audio;
 use endbasic_std::console::{Console, ConsoleSpec};
+use endbasic_std::program::ExternalEditor;
+use endbasic_std::shell::Shell;
 use endbasic_std::storage::Storage;
 use getopts::Options;
 use std::cell::RefCell;
 use std::env;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io;
 use std::path::Path;
 use std::process;
 use std::rc::Rc;
 
+/// Exit status to return when execution fails with an error.
+///
+/// This is outside of the range of the codes that `END` accepts so that callers can tell errors
+/// apart from explicit exits.
+const ERROR_EXIT_CODE: i32 = 128;
+
 /// Errors caused by the user when invoking this binary (invalid options or arguments).
 #[derive(Debug, thiserror::Error)]
 #[error("{message}")]
@@ -69,7 +81,7 @@ fn program_name(mut args: env::Args, default_name: &'static str) -> (String, env
 
 /// Prints usage information for program `name` with `opts` following the GNU Standards format.
 fn help(name: &str, opts: &Options) {
-    let brief = format!("Usage: {} [options] [program-file]", name);
+    let brief = format!("Usage: {} [options] [program-file [arg1 .. argN]]", name);
     println!("{}", opts.usage(&brief));
     println!("CONSOLE-SPEC can be one of the following:");
     if cfg!(feature = "sdl") {
@@ -82,12 +94,22 @@ fn help(name: &str, opts: &Options) {
         println!("                        'WIDTHxHEIGHT' or 'WIDTHxHEIGHTfs'");
     }
     if cfg!(feature = "rpi") {
+        println!("    ili9341[:SPEC]      enables the ILI9341 LCD console and configures it");
+        println!("                        with the settings in SPEC, which is of the form:");
+        println!("                        fg_color=COLOR,bg_color=COLOR,font=NAME,");
+        println!("                        rotation=DEGREES,backlight_pin=PIN,");
+        println!("                        backlight_pwm=CHANNEL,dc_pin=PIN,rst_pin=PIN");
         println!("    st7735s[:SPEC]      enables the ST7735S LCD console and configures it");
         println!("                        with the settings in SPEC, which is of the form:");
-        println!("                        fg_color=COLOR,bg_color=COLOR,font=NAME");
+        println!("                        fg_color=COLOR,bg_color=COLOR,font=NAME,");
+        println!("                        rotation=DEGREES,backlight_pin=PIN,");
+        println!("                        backlight_pwm=CHANNEL");
     }
     println!("    text                enables the text-based console");
     println!();
+    println!("When running a program-file, the exit status is the code given to END (0 by");
+    println!("default), or {} if the program fails with an error.", ERROR_EXIT_CODE);
+    println!();
     println!("Report bugs to: https://github.com/endbasic/endbasic/issues");
     println!("EndBASIC home page: https://www.endbasic.dev/");
 }
@@ -100,7 +122,11 @@ fn version() {
 }
 
 /// Creates a new EndBASIC machine builder based on the features enabled in this crate.
-fn new_machine_builder(console_spec: Option<&str>) -> io::Result<endbasic_std::MachineBuilder> {
+///
+/// Returns the builder and the external editor supported by the console, if any.
+fn new_machine_builder(
+    console_spec: Option<&str>,
+) -> io::Result<(endbasic_std::MachineBuilder, Option<Rc<dyn ExternalEditor>>)> {
     /// Obtains the default set of pins for a Raspberry Pi.
     #[cfg(feature = "rpi")]
     fn add_gpio_pins(builder: endbasic_std::MachineBuilder) -> endbasic_std::MachineBuilder {
@@ -116,41 +142,156 @@ fn new_machine_builder(console_spec: Option<&str>) -> io::Result<endbasic_std::M
         builder
     }
 
+    /// Obtains the SPI devices and the SPI-driven NeoPixel strips for a Raspberry Pi, keeping
+    /// programs away from the bus used by the LCD consoles if `console_spec` selects one of them.
+    #[cfg(feature = "rpi")]
+    fn add_spi(
+        builder: endbasic_std::MachineBuilder,
+        console_spec: Option<&str>,
+    ) -> endbasic_std::MachineBuilder {
+        use endbasic_std::gpio::Pin;
+        use endbasic_std::neopixel::SpiNeoPixels;
+        use endbasic_std::spi::SpiDevice;
+
+        // NeoPixel strips are driven by the MOSI line of these buses, which are hardwired to
+        // these pins.
+        let mut neopixel_devices =
+            vec![(Pin(10), SpiDevice { bus: 0, cs: 0 }), (Pin(20), SpiDevice { bus: 1, cs: 0 })];
+
+        let mut builder =
+            builder.with_spi(Rc::from(RefCell::from(endbasic_rpi::RppalSpi::default())));
+        let driver = ConsoleSpec::init(console_spec.unwrap_or("text")).driver;
+        if driver == "ili9341" || driver == "st7735s" {
+            let lcd_device = SpiDevice { bus: 0, cs: 0 };
+            builder = builder.with_reserved_spi_device(lcd_device);
+            neopixel_devices.retain(|(_pin, device)| device.bus != lcd_device.bus);
+        }
+
+        let neopixels =
+            SpiNeoPixels::new(Box::from(endbasic_rpi::RppalSpi::default()), neopixel_devices);
+        builder.with_neopixels(Rc::from(RefCell::from(neopixels)))
+    }
+
+    /// Obtains the default SPI devices for a platform without SPI support.
+    #[cfg(not(feature = "rpi"))]
+    fn add_spi(
+        builder: endbasic_std::MachineBuilder,
+        _console_spec: Option<&str>,
+    ) -> endbasic_std::MachineBuilder {
+        builder
+    }
+
+    /// Obtains the serial ports of a Unix system, which are only supported when crossterm support
+    /// is built in.
+    #[cfg(all(unix, feature = "crossterm"))]
+    fn add_serial_ports(builder: endbasic_std::MachineBuilder) -> endbasic_std::MachineBuilder {
+        builder.with_serial_ports(Rc::from(endbasic_terminal::TtySerialPorts::default()))
+    }
+
+    /// Obtains the serial ports for a platform without serial port support.
+    #[cfg(not(all(unix, feature = "crossterm")))]
+    fn add_serial_ports(builder: endbasic_std::MachineBuilder) -> endbasic_std::MachineBuilder {
+        builder
+    }
+
     let signals_chan = async_channel::unbounded();
     let mut builder = endbasic_std::MachineBuilder::default();
-    builder = builder.with_console(setup_console(console_spec, signals_chan.0.clone())?);
+    let (console, editor, audio, shell) = setup_console(console_spec, signals_chan.0.clone())?;
+    builder = builder.with_console(console);
+    if let Some(audio) = audio {
+        builder = builder.with_audio(audio);
+    }
+    if let Some(shell) = shell {
+        builder = builder.with_shell(shell);
+    }
     builder = builder.with_signals_chan(signals_chan);
+    builder = builder
+        .with_environment(Rc::from(RefCell::from(endbasic_std::env::SystemEnvironment::default())));
+    builder = builder.with_http_client(Rc::from(endbasic_client::ReqwestHttpClient::default()));
+    builder = builder.with_socket_connector(Rc::from(endbasic_client::TcpConnector::default()));
     builder = add_gpio_pins(builder);
-    Ok(builder)
+    builder = add_serial_ports(builder);
+    builder = add_spi(builder, console_spec);
+    Ok((builder, editor))
 }
 
 /// Turns a regular machine builder into an interactive builder ensuring common features for all
 /// callers.
+///
+/// If `external_editor` is provided, `EDIT "EXTERNAL"` uses it to edit the stored program.  If
+/// `autosave` is provided, the stored program is automatically saved while it is edited.
 fn make_interactive(
-    builder: endbasic_std::MachineBuilder,
+    mut builder: endbasic_std::InteractiveMachineBuilder,
+    external_editor: Option<Rc<dyn ExternalEditor>>,
+    autosave: Option<Rc<RefCell<Autosave>>>,
 ) -> endbasic_std::InteractiveMachineBuilder {
-    builder
-        .make_interactive()
-        .with_program(Rc::from(RefCell::from(endbasic_repl::editor::Editor::default())))
+    let mut editor = endbasic_repl::editor::Editor::default();
+    if let Some(autosave) = autosave {
+        editor = editor.with_autosave(autosave);
+    }
+    if let Some(external_editor) = external_editor {
+        builder = builder.with_external_editor(external_editor);
+    }
+    builder.with_program(Rc::from(RefCell::from(editor)))
 }
 
 /// Completes the build of an interactive machine by taking a partial builder and running post-build
 /// steps on it.
 ///
-/// `service_url` is the base URL of the cloud service.
+/// `service_url` is the base URL of the cloud service and `token_store` is the optional location
+/// where to remember login sessions.
+///
+/// Returns the new machine and the cloud service it is attached to.  The cloud commands are only
+/// registered if the cloud feature is enabled in the `builder`.
 fn finish_interactive_build(
     mut builder: endbasic_std::InteractiveMachineBuilder,
     service_url: &str,
-) -> Result<endbasic_core::exec::Machine> {
+    token_store: Option<Rc<RefCell<dyn TokenStore>>>,
+) -> Result<(endbasic_core::exec::Machine, Rc<RefCell<endbasic_client::CloudService>>)> {
     let console = builder.get_console();
     let storage = builder.get_storage();
+    let cloud = builder.is_feature_enabled(endbasic_std::Feature::Cloud);
+
+    let service = Rc::from(RefCell::from(endbasic_client::CloudService::new(service_url)?));
+    if cloud {
+        builder = builder.with_doctor_check(endbasic_client::ServiceCheck::new(service.clone()));
+    }
 
     let mut machine = builder.build()?;
 
-    let service = Rc::from(RefCell::from(endbasic_client::CloudService::new(service_url)?));
-    endbasic_client::add_all(&mut machine, service, console, storage, "https://repl.endbasic.dev/");
+    if cloud {
+        endbasic_client::add_all(
+            &mut machine,
+            service.clone(),
+            console,
+            storage,
+            token_store,
+            endbasic_client::RetryPolicy::default(),
+            "https://repl.endbasic.dev/",
+        );
+    }
 
-    Ok(machine)
+    Ok((machine, service))
+}
+
+/// Returns the store where to remember login sessions, if we can compute its location.
+fn get_token_store() -> Option<Rc<RefCell<dyn TokenStore>>> {
+    dirs::config_dir().map(|d| {
+        let store = endbasic_client::FileTokenStore::new(d.join("endbasic/session.json"));
+        Rc::from(RefCell::from(store)) as Rc<RefCell<dyn TokenStore>>
+    })
+}
+
+/// Returns the history of entered lines, persisted if we can compute the location of its file.
+fn get_history() -> Rc<RefCell<History>> {
+    let history = match dirs::config_dir() {
+        Some(d) => {
+            let store = FileHistoryStore::new(d.join("endbasic/history.txt"));
+            History::new(Box::from(store), DEFAULT_MAX_ENTRIES)
+        }
+        None => History::default(),
+    };
+    Rc::from(RefCell::from(history))
 }
 
 /// Returns `flag` if present, or else returns the URI of the default `LOCAL` drive.
@@ -177,29 +318,51 @@ fn get_local_drive_spec(flag: Option<String>) -> Result<String> {
     }
 }
 
+/// A console, the external editor that can run on it, if any, the audio device attached to it, if
+/// any, and the shell to run external commands on it, if any.
+type ConsoleWithExtras = (
+    Rc<RefCell<dyn Console>>,
+    Option<Rc<dyn ExternalEditor>>,
+    Option<Rc<RefCell<dyn Audio>>>,
+    Option<Rc<dyn Shell>>,
+);
+
+/// A graphical console and the audio device attached to it.
+type ConsoleWithAudio = (Rc<RefCell<dyn Console>>, Rc<RefCell<dyn Audio>>);
+
 /// Sets up the console.
+///
+/// Returns the console, the external editor that can run on it, if any, the audio device that
+/// goes with it, if any, and the shell that can run external commands on it, if any.
 fn setup_console(
     console_spec: Option<&str>,
     signals_tx: Sender<Signal>,
-) -> io::Result<Rc<RefCell<dyn Console>>> {
-    /// Creates the textual console when crossterm support is built in.
+) -> io::Result<ConsoleWithExtras> {
+    /// Creates the textual console when crossterm support is built in, along with an external
+    /// editor and a shell that suspend it while running and with the terminal bell as the audio
+    /// device.
     #[cfg(feature = "crossterm")]
-    fn setup_text_console(signals_tx: Sender<Signal>) -> io::Result<Rc<RefCell<dyn Console>>> {
-        Ok(Rc::from(RefCell::from(endbasic_terminal::TerminalConsole::from_stdio(signals_tx)?)))
+    fn setup_text_console(signals_tx: Sender<Signal>) -> io::Result<ConsoleWithExtras> {
+        let console = endbasic_terminal::TerminalConsole::from_stdio(signals_tx)?;
+        let editor = Rc::from(console.external_editor());
+        let audio = Rc::from(RefCell::from(endbasic_terminal::TerminalBell::default()));
+        let shell = Rc::from(console.shell());
+        Ok((Rc::from(RefCell::from(console)), Some(editor), Some(audio), Some(shell)))
     }
 
     /// Creates the textual console with very basic features when crossterm support is not built in.
     #[cfg(not(feature = "crossterm"))]
-    fn setup_text_console(_signals_tx: Sender<Signal>) -> io::Result<Rc<RefCell<dyn Console>>> {
-        Ok(Rc::from(RefCell::from(endbasic_std::console::TrivialConsole::default())))
+    fn setup_text_console(_signals_tx: Sender<Signal>) -> io::Result<ConsoleWithExtras> {
+        let console = Rc::from(RefCell::from(endbasic_std::console::TrivialConsole::default()));
+        Ok((console, None, None, None))
     }
 
-    /// Creates the graphical console when SDL support is built in.
+    /// Creates the graphical console when SDL support is built in, along with its audio device.
     #[cfg(feature = "sdl")]
     pub fn setup_sdl_console(
         signals_tx: Sender<Signal>,
         spec: &mut ConsoleSpec,
-    ) -> io::Result<Rc<RefCell<dyn Console>>> {
+    ) -> io::Result<ConsoleWithAudio> {
         endbasic_sdl::setup(spec, signals_tx)
     }
 
@@ -208,11 +371,35 @@ fn setup_console(
     pub fn setup_sdl_console(
         _signals_tx: Sender<Signal>,
         _spec: &mut ConsoleSpec,
-    ) -> io::Result<Rc<RefCell<dyn Console>>> {
+    ) -> io::Result<ConsoleWithAudio> {
         // TODO(jmmv): Make this io::ErrorKind::Unsupported when our MSRV allows it.
         Err(io::Error::new(io::ErrorKind::InvalidInput, "SDL support not compiled in"))
     }
 
+    #[cfg(feature = "rpi")]
+    fn setup_ili9341_console(
+        signals_tx: Sender<Signal>,
+        spec: &mut ConsoleSpec,
+    ) -> io::Result<Rc<RefCell<dyn Console>>> {
+        let console = endbasic_std::gfx::lcd::ili9341::new_console(
+            endbasic_rpi::RppalPins::default(),
+            endbasic_rpi::spi_bus_open,
+            endbasic_rpi::pwm_open,
+            endbasic_terminal::TerminalConsole::from_stdio(signals_tx)?,
+            spec,
+            &endbasic_std::gfx::lcd::fonts::all_fonts(),
+        )?;
+        Ok(Rc::from(RefCell::from(console)))
+    }
+
+    #[cfg(not(feature = "rpi"))]
+    pub fn setup_ili9341_console(
+        _signals_tx: Sender<Signal>,
+        _spec: &mut ConsoleSpec,
+    ) -> io::Result<Rc<RefCell<dyn Console>>> {
+        Err(io::Error::new(io::ErrorKind::InvalidInput, "ILI9341 support not compiled in"))
+    }
+
     #[cfg(feature = "rpi")]
     fn setup_st7735s_console(
         signals_tx: Sender<Signal>,
@@ -221,6 +408,7 @@ fn setup_console(
         let console = endbasic_st7735s::new_console(
             endbasic_rpi::RppalPins::default(),
             endbasic_rpi::spi_bus_open,
+            endbasic_rpi::pwm_open,
             endbasic_terminal::TerminalConsole::from_stdio(signals_tx)?,
             spec,
             &endbasic_std::gfx::lcd::fonts::all_fonts(),
@@ -237,9 +425,13 @@ fn setup_console(
     }
 
     let mut console_spec = ConsoleSpec::init(console_spec.unwrap_or("text"));
-    let console: Rc<RefCell<dyn Console>> = match console_spec.driver {
-        "sdl" => setup_sdl_console(signals_tx, &mut console_spec)?,
-        "st7735s" => setup_st7735s_console(signals_tx, &mut console_spec)?,
+    let (console, editor, audio, shell) = match console_spec.driver {
+        "sdl" => {
+            let (console, audio) = setup_sdl_console(signals_tx, &mut console_spec)?;
+            (console, None, Some(audio), None)
+        }
+        "ili9341" => (setup_ili9341_console(signals_tx, &mut console_spec)?, None, None, None),
+        "st7735s" => (setup_st7735s_console(signals_tx, &mut console_spec)?, None, None, None),
         "text" => setup_text_console(signals_tx)?,
         driver => {
             return Err(io::Error::new(
@@ -251,56 +443,106 @@ fn setup_console(
     console_spec.finish().map_err(|e| {
         io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid --console flag: {}", e))
     })?;
-    Ok(console)
+    Ok((console, editor, audio, shell))
 }
 
 /// Sets up the common storage drives.
 ///
 /// This instantiates non-optional drives, such as `MEMORY:` and `DEMOS:`, maps `LOCAL` the
 /// location given in `local_drive_spec`.
-pub fn setup_storage(storage: &mut Storage, local_drive_spec: &str) -> io::Result<()> {
+pub async fn setup_storage(storage: &mut Storage, local_drive_spec: &str) -> io::Result<()> {
     storage.register_scheme("demos", Box::from(endbasic_repl::demos::DemoDriveFactory::default()));
-    storage.mount("demos", "demos://").expect("Demos drive shouldn't fail to mount");
+    storage.mount("demos", "demos://", false).await.expect("Demos drive shouldn't fail to mount");
     storage.register_scheme(
         "file",
         Box::from(endbasic_std::storage::DirectoryDriveFactory::default()),
     );
-    storage.mount("local", local_drive_spec)?;
+    storage.mount("local", local_drive_spec, false).await?;
     storage.cd("local:").expect("Local drive was just registered");
     Ok(())
 }
 
+/// Computes the banner to show when entering the interactive interpreter.
+///
+/// `quiet` suppresses the banner altogether and `banner_file` is the optional path to a file with
+/// custom content to show instead of the default welcome message.
+fn get_banner(quiet: bool, banner_file: Option<String>) -> Result<endbasic_repl::Banner> {
+    match (quiet, banner_file) {
+        (true, Some(_)) => {
+            Err(UsageError::new("--banner-file and --quiet are mutually exclusive").into())
+        }
+        (true, None) => Ok(endbasic_repl::Banner::Quiet),
+        (false, Some(path)) => match fs::read_to_string(&path) {
+            Ok(text) => Ok(endbasic_repl::Banner::Custom(text)),
+            Err(e) => Err(anyhow!("Cannot read banner file {}: {}", path, e)),
+        },
+        (false, None) => Ok(endbasic_repl::Banner::Default),
+    }
+}
+
 /// Enters the interactive interpreter.
 ///
 /// `local_drive` is the optional local drive to mount and use as the default location.
 /// `service_url` is the base URL of the cloud service.
+/// `banner` is the message to show before accepting any input.
 async fn run_repl_loop(
     console_spec: Option<&str>,
     local_drive_spec: &str,
     service_url: &str,
+    banner: &endbasic_repl::Banner,
 ) -> Result<i32> {
-    let mut builder = make_interactive(new_machine_builder(console_spec)?);
+    let (builder, external_editor) = new_machine_builder(console_spec)?;
+    let mut builder = builder.make_interactive();
+    let autosave = Rc::from(RefCell::from(Autosave::new(builder.get_storage())));
+    let mut builder = make_interactive(builder, external_editor, Some(autosave.clone()));
 
     let console = builder.get_console();
     let program = builder.get_program();
 
     let storage = builder.get_storage();
-    setup_storage(&mut storage.borrow_mut(), local_drive_spec)?;
+    setup_storage(&mut storage.borrow_mut(), local_drive_spec).await?;
+
+    let token_store = get_token_store();
+    let (mut machine, service) =
+        finish_interactive_build(builder, service_url, token_store.clone())?;
+    machine.add_callable(AutosaveCommand::new(autosave.clone()));
+    if let Some(token_store) = token_store {
+        endbasic_client::restore_session(service, storage.clone(), token_store).await;
+    }
+    endbasic_repl::print_banner(console.clone(), banner)?;
+    print_recovery_notice(console.clone(), storage.clone()).await?;
+    endbasic_repl::try_load_autoexec(&mut machine, console.clone(), storage.clone()).await?;
+    let history = get_history();
+    Ok(endbasic_repl::run_repl_loop(
+        &mut machine,
+        console,
+        storage,
+        program,
+        Some(autosave),
+        Some(history),
+    )
+    .await?)
+}
 
-    let mut machine = finish_interactive_build(builder, service_url)?;
-    endbasic_repl::print_welcome(console.clone())?;
-    endbasic_repl::try_load_autoexec(&mut machine, console.clone(), storage).await?;
-    Ok(endbasic_repl::run_repl_loop(&mut machine, console, program).await?)
+/// Returns the arguments to expose to the `path` program given its command-line `args`.
+fn program_args(path: &str, args: &[String]) -> Vec<String> {
+    let mut all = Vec::with_capacity(args.len() + 1);
+    all.push(path.to_owned());
+    all.extend_from_slice(args);
+    all
 }
 
-/// Executes the `path` program in a fresh machine.
-async fn run_script<P: AsRef<Path>>(path: P, console_spec: Option<&str>) -> Result<i32> {
-    let mut machine = new_machine_builder(console_spec)?.build()?;
+/// Executes the `path` program in a fresh machine and passes `args` to it.
+async fn run_script(path: &str, args: &[String], console_spec: Option<&str>) -> Result<i32> {
+    let (builder, _external_editor) = new_machine_builder(console_spec)?;
+    let mut machine = builder.build()?;
+    machine.set_args(program_args(path, args));
     let mut input = File::open(path)?;
     Ok(machine.exec(&mut input).await?.as_exit_code())
 }
 
-/// Executes the `path` program in a fresh machine allowing any interactive-only calls.
+/// Executes the `path` program in a fresh machine allowing any interactive-only calls and passes
+/// `args` to it.
 ///
 /// `local_drive` is the optional local drive to mount and use as the default location.
 /// `service_url` is the base URL of the cloud service.
@@ -310,19 +552,22 @@ async fn run_script<P: AsRef<Path>>(path: P, console_spec: Option<&str>) -> Resu
 /// just in the web and helps test this feature.
 async fn run_interactive(
     path: &str,
+    args: &[String],
     console_spec: Option<&str>,
     local_drive_spec: &str,
     service_url: &str,
 ) -> Result<i32> {
-    let mut builder = make_interactive(new_machine_builder(console_spec)?);
+    let (builder, external_editor) = new_machine_builder(console_spec)?;
+    let mut builder = make_interactive(builder.make_interactive(), external_editor, None);
 
     let console = builder.get_console();
     let program = builder.get_program();
 
     let storage = builder.get_storage();
-    setup_storage(&mut storage.borrow_mut(), local_drive_spec)?;
+    setup_storage(&mut storage.borrow_mut(), local_drive_spec).await?;
 
-    let mut machine = finish_interactive_build(builder, service_url)?;
+    let (mut machine, _service) = finish_interactive_build(builder, service_url, None)?;
+    machine.set_args(program_args(path, args));
 
     match path.strip_prefix("cloud://") {
         Some(username_path) => {
@@ -349,10 +594,12 @@ async fn safe_main(name: &str, args: env::Args) -> Result<i32> {
     let args: Vec<String> = args.collect();
 
     let mut opts = Options::new();
+    opts.optopt("", "banner-file", "file with the text to show instead of the banner", "PATH");
     opts.optopt("", "console", "type and properties of the console to use", "CONSOLE-SPEC");
     opts.optflag("h", "help", "show command-line usage information and exit");
     opts.optflag("i", "interactive", "force interactive mode when running a script");
     opts.optopt("", "local-drive", "location of the drive to mount as LOCAL", "URI");
+    opts.optflag("q", "quiet", "do not show the banner when entering the interpreter");
     opts.optopt("", "service-url", "base URL of the cloud service", "URL");
     opts.optflag("", "version", "show version information and exit");
     let matches = opts.parse(args)?;
@@ -375,19 +622,19 @@ async fn safe_main(name: &str, args: env::Args) -> Result<i32> {
 
     match matches.free.as_slice() {
         [] => {
+            let banner = get_banner(matches.opt_present("quiet"), matches.opt_str("banner-file"))?;
             let local_drive = get_local_drive_spec(matches.opt_str("local-drive"))?;
-            Ok(run_repl_loop(console_spec.as_deref(), &local_drive, &service_url).await?)
+            Ok(run_repl_loop(console_spec.as_deref(), &local_drive, &service_url, &banner).await?)
         }
-        [file] => {
+        [file, args @ ..] => {
             if matches.opt_present("interactive") {
                 let local_drive = get_local_drive_spec(matches.opt_str("local-drive"))?;
-                Ok(run_interactive(file, console_spec.as_deref(), &local_drive, &service_url)
+                Ok(run_interactive(file, args, console_spec.as_deref(), &local_drive, &service_url)
                     .await?)
             } else {
-                Ok(run_script(file, console_spec.as_deref()).await?)
+                Ok(run_script(file, args, console_spec.as_deref()).await?)
             }
         }
-        [_, ..] => Err(UsageError::new("Too many arguments").into()),
     }
 }
 
@@ -407,7 +654,7 @@ async fn main() {
                 2
             } else {
                 eprintln!("{}: {}", name, e);
-                1
+                ERROR_EXIT_CODE
             }
         }
     };
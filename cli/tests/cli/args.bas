@@ -0,0 +1,24 @@
+' EndBASIC
+' Copyright 2025 Julio Merino
+'
+' Licensed under the Apache License, Version 2.0 (the "License"); you may not
+' use this file except in compliance with the License.  You may obtain a copy
+' of the License at:
+'
+'     http://www.apache.org/licenses/LICENSE-2.0
+'
+' Unless required by applicable law or agreed to in writing, software
+' distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+' WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+' License for the specific language governing permissions and limitations
+' under the License.
+
+' Tests that the arguments given after the script path are exposed to the
+' program.  Argument 0 is the script path, which varies across machines, so
+' only print its file name.
+
+PRINT "argc"; ARGC
+PRINT "name "; RIGHT(ARG(0), 8)
+FOR i = 1 TO ARGC - 1
+    PRINT "arg"; i; ARG(i)
+NEXT
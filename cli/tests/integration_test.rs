@@ -193,7 +193,7 @@ fn test_cli_autoexec_is_ignored() {
             &format!("--local-drive=file://{}", dir.path().to_str().unwrap()),
             &src_str("cli/tests/cli/interactive.bas"),
         ],
-        1,
+        128,
         Behavior::Null,
         Behavior::Null,
         Behavior::File(src_path("cli/tests/cli/interactive.err")),
@@ -230,7 +230,7 @@ fn test_cli_interactive() {
     check(
         bin_path("endbasic"),
         &["--local-drive=memory://", &src_str("cli/tests/cli/interactive.bas")],
-        1,
+        128,
         Behavior::Null,
         Behavior::Null,
         Behavior::File(src_path("cli/tests/cli/interactive.err")),
@@ -292,30 +292,32 @@ fn test_cli_program_name_uses_arg0() {
     fs::copy(&original, &custom).unwrap();
     check(
         &custom,
-        &["one", "two", "three"],
+        &["-Z"],
         2,
         Behavior::Null,
         Behavior::Null,
         Behavior::Literal(
-            "Usage error: Too many arguments\nType custom-name --help for more information\n"
+            "Usage error: Unrecognized option: 'Z'\nType custom-name --help for more information\n"
                 .to_owned(),
         ),
     );
 }
 
 #[test]
-fn test_cli_too_many_args() {
-    check(
-        bin_path("endbasic"),
-        &["foo", "bar"],
-        2,
-        Behavior::Null,
-        Behavior::Null,
-        Behavior::Literal(
-            "Usage error: Too many arguments\nType endbasic --help for more information\n"
-                .to_owned(),
-        ),
-    );
+fn test_cli_script_args() {
+    for flags in [&[][..], &["--local-drive=memory://", "--interactive"][..]] {
+        let mut args = flags.to_vec();
+        let script = src_str("cli/tests/cli/args.bas");
+        args.extend_from_slice(&[&script, "foo", "42", "--", "-x"]);
+        check(
+            bin_path("endbasic"),
+            &args,
+            0,
+            Behavior::Null,
+            Behavior::File(src_path("cli/tests/cli/args.out")),
+            Behavior::Null,
+        );
+    }
 }
 
 #[test]
@@ -409,6 +411,18 @@ fn test_example_palette() {
     );
 }
 
+#[test]
+fn test_example_sprite() {
+    check(
+        bin_path("endbasic"),
+        &["--local-drive=memory://", "--interactive", &src_str("cli/tests/examples/sprite.bas")],
+        0,
+        Behavior::Null,
+        Behavior::File(src_path("cli/tests/examples/sprite.out")),
+        Behavior::Null,
+    );
+}
+
 #[test]
 fn test_example_tour() {
     check(
@@ -462,7 +476,7 @@ fn test_lang_exec_error() {
     check(
         bin_path("endbasic"),
         &["--local-drive=memory://", &src_str("cli/tests/lang/exec-error.bas")],
-        1,
+        128,
         Behavior::Null,
         Behavior::File(src_path("cli/tests/lang/exec-error.out")),
         Behavior::File(src_path("cli/tests/lang/exec-error.err")),
@@ -522,7 +536,7 @@ fn test_lang_lexer_error() {
     check(
         bin_path("endbasic"),
         &["--local-drive=memory://", &src_str("cli/tests/lang/lexer-error.bas")],
-        1,
+        128,
         Behavior::Null,
         Behavior::Null,
         Behavior::File(src_path("cli/tests/lang/lexer-error.err")),
@@ -558,7 +572,7 @@ fn test_lang_parser_error() {
     check(
         bin_path("endbasic"),
         &["--local-drive=memory://", &src_str("cli/tests/lang/parser-error.bas")],
-        1,
+        128,
         Behavior::Null,
         Behavior::Null,
         Behavior::File(src_path("cli/tests/lang/parser-error.err")),
@@ -615,6 +629,33 @@ fn test_repl_autoexec() {
     );
 }
 
+#[test]
+fn test_repl_banner_file() {
+    check(
+        bin_path("endbasic"),
+        &["--local-drive=memory://", "--banner-file", &src_str("cli/tests/repl/banner.txt")],
+        0,
+        Behavior::File(src_path("cli/tests/repl/banner.in")),
+        Behavior::File(src_path("cli/tests/repl/banner.out")),
+        Behavior::Null,
+    );
+}
+
+#[test]
+fn test_repl_banner_file_and_quiet() {
+    check(
+        bin_path("endbasic"),
+        &["--banner-file=foo.txt", "--quiet"],
+        2,
+        Behavior::Null,
+        Behavior::Null,
+        Behavior::Literal(
+            "Usage error: --banner-file and --quiet are mutually exclusive\nType endbasic --help for more information\n"
+                .to_owned(),
+        ),
+    );
+}
+
 #[test]
 fn test_repl_colors() {
     check(
@@ -684,6 +725,18 @@ fn test_repl_exit_nonzero() {
     );
 }
 
+#[test]
+fn test_repl_exit_nested() {
+    check(
+        bin_path("endbasic"),
+        &["--local-drive=memory://", &src_str("cli/tests/repl/exit-nested.bas")],
+        3,
+        Behavior::Null,
+        Behavior::File(src_path("cli/tests/repl/exit-nested.out")),
+        Behavior::Null,
+    );
+}
+
 #[test]
 fn test_repl_exit_saved() {
     check(
@@ -769,6 +822,22 @@ fn test_repl_load_save() {
     assert!(dir.path().join("hello2.bas").exists());
 }
 
+#[test]
+fn test_repl_quiet() {
+    fn check_with_args(args: &[&str]) {
+        check(
+            bin_path("endbasic"),
+            args,
+            0,
+            Behavior::File(src_path("cli/tests/repl/banner.in")),
+            Behavior::File(src_path("cli/tests/repl/quiet.out")),
+            Behavior::Null,
+        );
+    }
+    check_with_args(&["--local-drive=memory://", "-q"]);
+    check_with_args(&["--local-drive=memory://", "--quiet"]);
+}
+
 #[test]
 fn test_repl_state_sharing() {
     check(
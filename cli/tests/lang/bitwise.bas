@@ -26,3 +26,13 @@ PRINT NOT 123456
 
 PRINT 1 << 20
 PRINT 128 >> 2
+
+flags = 5
+IF (flags AND 4) <> 0 AND flags > 0 THEN PRINT "Match 6"
+IF NOT ((flags AND 2) <> 0) THEN PRINT "Match 7"
+WHILE (flags AND 1) <> 0
+    flags = flags >> 1
+WEND
+PRINT flags
+
+PRINT (255 << 16) OR (128 << 8) OR 1
@@ -51,6 +51,13 @@ PRINT a(10, 0): PRINT ERRMSG
 
 PRINT ">>> Simple function calls"
 PRINT PI
-PRINT MAX(5)
+PRINT MAX(5, 2.5)
 PRINT MAX(2, 5, 3)
 PRINT MAX#(2, 5, 3)
+
+PRINT ">>> Conditional expressions"
+n = 0
+PRINT IIF(n <> 0, 100 / n, 0); IIF(n = 0, "zero", "nonzero"), IIF(TRUE, 1, 2.5)
+n = 4
+PRINT IIF(n <> 0, 100 / n, 0); IIF(n = 0, "zero", "nonzero"), IIF(FALSE, 1, 2.5)
+PRINT IIF(n > 2, IIF(n > 3, "big", "medium"), "small")
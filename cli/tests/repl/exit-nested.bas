@@ -0,0 +1,31 @@
+' EndBASIC
+' Copyright 2025 Julio Merino
+'
+' Licensed under the Apache License, Version 2.0 (the "License"); you may not
+' use this file except in compliance with the License.  You may obtain a copy
+' of the License at:
+'
+'     http://www.apache.org/licenses/LICENSE-2.0
+'
+' Unless required by applicable law or agreed to in writing, software
+' distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+' WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+' License for the specific language governing permissions and limitations
+' under the License.
+
+' Exits with an error code from within nested GOSUB and FUNCTION frames.
+
+FUNCTION fail%(code%)
+    PRINT "In function"
+    END code%
+    PRINT "Should not be executed"
+END FUNCTION
+
+GOSUB @sub
+PRINT "Should not be executed either"
+END 1
+
+@sub:
+PRINT "In subroutine"
+dummy% = fail%(3)
+RETURN
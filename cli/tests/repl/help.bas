@@ -32,17 +32,24 @@ LOOP
 
 ' Help topics.
 DATA "ARRAY"
-DATA "CLOUD"
+DATA "AUDIO"
+DATA "CLOUD ACCESS"
 DATA "CONSOLE"
 DATA "DATA"
+DATA "DATE AND TIME"
+DATA "ENVIRONMENT"
+DATA "FILE I/O"
 DATA "FILE SYSTEM"
 DATA "GRAPHICS"
 DATA "HARDWARE"
 DATA "INTERPRETER"
 DATA "LANG"
+DATA "NETWORKING"
 DATA "NUMERICAL"
+DATA "SOCKETS"
 DATA "STORED"
 DATA "STRING"
+DATA "SYSTEM"
 
 ' Language reference.
 DATA "DO"
@@ -52,6 +59,8 @@ DATA "FUNCTIONS"
 DATA "IF"
 DATA "JUMPS"
 DATA "ON ERROR"
+DATA "ON TIMER"
+DATA "RECORDS"
 DATA "SELECT CASE"
 DATA "SUBROUTINES"
 DATA "STYLE"
@@ -60,60 +69,141 @@ DATA "VARIABLES"
 DATA "WHILE"
 
 ' Commands.
+DATA "ACCOUNTDEL"
+DATA "BACKLIGHT"
+DATA "BEEP"
+DATA "BINTODATA"
+DATA "BLOAD"
+DATA "BSAVE"
 DATA "CD"
 DATA "CLEAR"
+DATA "CLOSE#"
+DATA "CLOUDLS"
 DATA "CLS"
 DATA "COLOR"
 DATA "COPY"
+DATA "COPYFILE"
+DATA "CSVLOAD"
 DATA "DEG"
 DATA "DIR"
 DATA "DISASM"
+DATA "DOCTOR"
 DATA "EDIT"
+DATA "EMPTYVALUES"
+DATA "EXECUTE"
 DATA "GFX_CIRCLE"
 DATA "GFX_CIRCLEF"
+DATA "GFX_CLIP"
+DATA "GFX_COPY"
+DATA "GFX_FONT"
 DATA "GFX_LINE"
 DATA "GFX_PIXEL"
 DATA "GFX_RECT"
 DATA "GFX_RECTF"
+DATA "GFX_ROTATE"
+DATA "GFX_SCROLL"
 DATA "GFX_SYNC"
+DATA "GFX_TEXT"
 DATA "GPIO_CLEAR"
 DATA "GPIO_SETUP"
+DATA "GPIO_WAIT"
 DATA "GPIO_WRITE"
 DATA "HELP"
 DATA "INPUT"
 DATA "KILL"
+DATA "LINE INPUT#"
 DATA "LIST"
 DATA "LOAD"
 DATA "LOCATE"
 DATA "LOGIN"
 DATA "LOGOUT"
+DATA "MEMINFO"
+DATA "MERGE"
+DATA "MKDIR"
+DATA "MOTD"
 DATA "MOUNT"
+DATA "NEOPIXEL_FILL"
+DATA "NEOPIXEL_INIT"
+DATA "NEOPIXEL_SET"
+DATA "NEOPIXEL_SHOW"
 DATA "NEW"
+DATA "OPEN"
+DATA "PACKAGE"
+DATA "PLAY"
 DATA "PRINT"
+DATA "PRINT#"
+DATA "PRINTZONE"
+DATA "PROFILE"
 DATA "PWD"
 DATA "RAD"
 DATA "RANDOMIZE"
 DATA "READ"
+DATA "REFACTOR"
+DATA "RENAME"
+DATA "RENUM"
 DATA "RESTORE"
+DATA "RMDIR"
 DATA "RUN"
 DATA "SAVE"
+DATA "SCREENDUMP"
+DATA "SERIALCLOSE"
+DATA "SERIALOPEN"
+DATA "SERIALWRITE"
+DATA "SETENV"
 DATA "SHARE"
+DATA "SHELL"
 DATA "SIGNUP"
 DATA "SLEEP"
+DATA "SOCKCLOSE"
+DATA "SOCKSEND"
+DATA "SOUND"
+DATA "SPI_OPEN"
+DATA "SPI_XFER"
+DATA "STATS"
 DATA "UNMOUNT"
+DATA "VERSION"
+DATA "WAITFRAME"
 
 ' Functions.
+DATA "ARG$"
+DATA "ARGC"
 DATA "ASC"
 DATA "ATN"
+DATA "CDBL"
 DATA "CHR"
 DATA "CINT"
-DATA "COS"
+DATA "CLAMP"
+DATA "COS#"
+DATA "CSVCOLS"
+DATA "CSVFIELD"
+DATA "DATE$"
+DATA "DATEDIFF#"
+DATA "DATEFMT$"
+DATA "DEGREES"
+DATA "EOF"
+DATA "ERL"
+DATA "ERR%"
 DATA "ERRMSG"
+DATA "EULER"
+DATA "EVAL"
+DATA "FIX"
+DATA "FILEDATE"
+DATA "FILEEXISTS"
+DATA "FILEREADB"
+DATA "FILESIZE"
+DATA "FRE"
+DATA "GETENV"
 DATA "GFX_HEIGHT"
 DATA "GFX_WIDTH"
 DATA "GPIO_READ"
+DATA "GPIO_WAITRESULT"
+DATA "HTTPGET"
+DATA "HTTPPOST"
+DATA "HTTPSTATUS"
 DATA "INKEY"
 DATA "INT%"
+DATA "ISDEFINED"
+DATA "ISEMPTY"
 DATA "LBOUND"
 DATA "LEFT"
 DATA "LEN"
@@ -121,16 +211,36 @@ DATA "LTRIM"
 DATA "MAX"
 DATA "MID"
 DATA "MIN"
+DATA "NOW#"
 DATA "PI"
+DATA "RADIANS"
 DATA "RIGHT"
-DATA "RND"
+DATA "RND#"
+DATA "RNDCHOICE"
+DATA "RNDEXP"
+DATA "RNDINT"
+DATA "RNDNORM"
 DATA "RTRIM"
 DATA "SCRCOLS"
+DATA "SCREENCHAR"
 DATA "SCRROWS"
-DATA "SIN"
+DATA "SERIALREAD$"
+DATA "SERIALREADLINE"
+DATA "SGN"
+DATA "SHELLOUT"
+DATA "SHELLSTATUS"
+DATA "SIN#"
+DATA "SOCKOPEN"
+DATA "SOCKREADLINE"
+DATA "SOCKRECV"
 DATA "SQR"
 DATA "STR$"
-DATA "TAN"
+DATA "SYMSYNTAX"
+DATA "SYMTYPE"
+DATA "TAN#"
+DATA "TEXTWIDTH"
+DATA "TIME$"
+DATA "TIMER#"
 DATA "UBOUND"
 
 ' End of data marker.
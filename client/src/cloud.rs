@@ -38,8 +38,10 @@ async fn http_response_to_io_error(response: Response) -> io::Error {
         StatusCode::OK => panic!("Should not have been called on a successful request"),
 
         // Match against the codes we know the server explicitly hands us.
+        StatusCode::BAD_GATEWAY => io::ErrorKind::AddrNotAvailable,
         StatusCode::BAD_REQUEST => io::ErrorKind::InvalidInput,
         StatusCode::FORBIDDEN => io::ErrorKind::PermissionDenied,
+        StatusCode::GATEWAY_TIMEOUT => io::ErrorKind::TimedOut,
         StatusCode::INSUFFICIENT_STORAGE => io::ErrorKind::Other,
         StatusCode::INTERNAL_SERVER_ERROR => io::ErrorKind::Other,
         StatusCode::NOT_FOUND => io::ErrorKind::NotFound,
@@ -77,8 +79,70 @@ async fn http_response_to_io_error(response: Response) -> io::Error {
 }
 
 /// Converts a `reqwest::Error` to an `io::Error`.
-fn reqwest_error_to_io_error(e: reqwest::Error) -> io::Error {
-    io::Error::new(io::ErrorKind::Other, format!("{}", e))
+///
+/// Timeouts and connection failures are mapped to specific error kinds so that they can be
+/// recognized as transient and retried.
+pub(crate) fn reqwest_error_to_io_error(e: reqwest::Error) -> io::Error {
+    #[cfg(not(target_arch = "wasm32"))]
+    let is_connect = e.is_connect();
+    #[cfg(target_arch = "wasm32")]
+    let is_connect = false;
+
+    let kind = if e.is_timeout() {
+        io::ErrorKind::TimedOut
+    } else if is_connect {
+        io::ErrorKind::ConnectionRefused
+    } else {
+        io::ErrorKind::Other
+    };
+    io::Error::new(kind, format!("{}", e))
+}
+
+/// Reads the body of a successful `response`, reporting the bytes received so far to `progress`.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_body(
+    mut response: Response,
+    mut progress: Option<ProgressFn<'_>>,
+) -> io::Result<Vec<u8>> {
+    let total = response.content_length();
+    let mut content = Vec::with_capacity(total.unwrap_or(0) as usize);
+    while let Some(chunk) = response.chunk().await.map_err(reqwest_error_to_io_error)? {
+        content.extend_from_slice(&chunk);
+        if let (Some(progress), Some(total)) = (progress.as_mut(), total) {
+            progress(content.len() as u64, total);
+        }
+    }
+    Ok(content)
+}
+
+/// Reads the body of a successful `response`, reporting the bytes received so far to `progress`.
+///
+/// The response cannot be read in chunks on this target so progress is only reported at the end.
+#[cfg(target_arch = "wasm32")]
+async fn read_body(response: Response, progress: Option<ProgressFn<'_>>) -> io::Result<Vec<u8>> {
+    let content = response.bytes().await.map_err(reqwest_error_to_io_error)?.to_vec();
+    if let Some(progress) = progress {
+        progress(content.len() as u64, content.len() as u64);
+    }
+    Ok(content)
+}
+
+/// Extracts the ACL entries carried in all instances of the `header` of a `response`.  `kind`
+/// names the type of the ACL for error reporting.
+fn parse_acl_headers(response: &Response, header: &str, kind: &str) -> io::Result<Vec<String>> {
+    let mut acls = vec![];
+    for h in response.headers().get_all(header) {
+        match h.to_str() {
+            Ok(value) => acls.push(value.to_owned()),
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Server returned invalid {} ACL: {}", kind, e),
+                ))
+            }
+        }
+    }
+    Ok(acls)
 }
 
 /// Container for authentication data to track after login.
@@ -171,7 +235,7 @@ impl Service for CloudService {
         }
     }
 
-    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResponse> {
+    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResult> {
         // TODO(https://github.com/seanmonstar/reqwest/pull/1096): Replace with a basic_auth()
         // call on the RequestBuilder once it is supported in WASM.
         let basic_auth =
@@ -186,6 +250,43 @@ impl Service for CloudService {
             .send()
             .await
             .map_err(reqwest_error_to_io_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
+                let response: LoginResult = serde_json::from_reader(bytes.reader())?;
+                if let LoginResult::Authenticated(response) = &response {
+                    let auth_data = AuthData {
+                        username: username.to_owned(),
+                        access_token: response.access_token.clone(),
+                    };
+                    *(self.auth_data.borrow_mut()) = Some(auth_data);
+                }
+                Ok(response)
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
+    async fn login_2fa(
+        &mut self,
+        username: &str,
+        token_challenge: &str,
+        code: &str,
+    ) -> io::Result<LoginResponse> {
+        let request = SecondFactorRequest {
+            username: username.to_owned(),
+            token_challenge: token_challenge.to_owned(),
+            code: code.to_owned(),
+        };
+        let response = self
+            .client
+            .post(self.make_url("api/login/2fa"))
+            .headers(self.default_headers())
+            .header("Content-Type", "application/json")
+            .body(serde_json::to_vec(&request)?)
+            .send()
+            .await
+            .map_err(reqwest_error_to_io_error)?;
         match response.status() {
             StatusCode::OK => {
                 let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
@@ -201,6 +302,31 @@ impl Service for CloudService {
         }
     }
 
+    async fn login_with_token(
+        &mut self,
+        username: &str,
+        access_token: AccessToken,
+    ) -> io::Result<()> {
+        // The service does not offer a dedicated endpoint to validate tokens, so issue the
+        // cheapest request that requires authentication and see if it succeeds.
+        let response = self
+            .client
+            .get(self.make_url(&format!("api/users/{}/files", username)))
+            .headers(self.default_headers())
+            .bearer_auth(access_token.as_str())
+            .send()
+            .await
+            .map_err(reqwest_error_to_io_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let auth_data = AuthData { username: username.to_owned(), access_token };
+                *(self.auth_data.borrow_mut()) = Some(auth_data);
+                Ok(())
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
     async fn logout(&mut self) -> io::Result<()> {
         let mut auth_data = self.auth_data.borrow_mut();
         let response = {
@@ -223,6 +349,52 @@ impl Service for CloudService {
         }
     }
 
+    async fn delete_account(&mut self, password: &str) -> io::Result<()> {
+        let mut auth_data = self.auth_data.borrow_mut();
+        let response = {
+            let auth_data = Self::require_auth_data(auth_data.as_ref())?;
+
+            // Deleting an account is irreversible so require the password instead of the access
+            // token, which might have been remembered on a shared computer.
+            let basic_auth = format!(
+                "Basic {}",
+                BASE64_STANDARD.encode(format!("{}:{}", auth_data.username, password))
+            );
+
+            self.client
+                .delete(self.make_url(&format!("api/users/{}", auth_data.username)))
+                .headers(self.default_headers())
+                .header("Authorization", basic_auth)
+                .send()
+                .await
+                .map_err(reqwest_error_to_io_error)?
+        };
+        match response.status() {
+            StatusCode::OK => {
+                *auth_data = None;
+                Ok(())
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
+    async fn get_motd(&mut self) -> io::Result<MotdResponse> {
+        let mut builder =
+            self.client.get(self.make_url("api/motd")).headers(self.default_headers());
+        if let Some(auth_data) = self.auth_data.borrow().as_ref() {
+            builder = builder.bearer_auth(auth_data.access_token.as_str());
+        }
+        let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
+                let response: MotdResponse = serde_json::from_reader(bytes.reader())?;
+                Ok(response)
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
     fn is_logged_in(&self) -> bool {
         self.auth_data.borrow().is_some()
     }
@@ -250,7 +422,57 @@ impl Service for CloudService {
         }
     }
 
-    async fn get_file(&mut self, username: &str, filename: &str) -> io::Result<Vec<u8>> {
+    async fn list_public_users(
+        &mut self,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicUsersResponse> {
+        let mut builder =
+            self.client.get(self.make_url("api/public/users")).headers(self.default_headers());
+        if let Some(page_token) = page_token {
+            builder = builder.query(&[("page_token", page_token)]);
+        }
+        let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
+                let response: PublicUsersResponse = serde_json::from_reader(bytes.reader())?;
+                Ok(response)
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
+    async fn list_public_files(
+        &mut self,
+        username: &str,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicFilesResponse> {
+        // Do not send our credentials even if we have them: the listing must only contain the
+        // files that anyone can read, not the ones that are shared with us.
+        let mut builder = self
+            .client
+            .get(self.make_url(&format!("api/public/users/{}/files", username)))
+            .headers(self.default_headers());
+        if let Some(page_token) = page_token {
+            builder = builder.query(&[("page_token", page_token)]);
+        }
+        let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
+        match response.status() {
+            StatusCode::OK => {
+                let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
+                let response: PublicFilesResponse = serde_json::from_reader(bytes.reader())?;
+                Ok(response)
+            }
+            _ => Err(http_response_to_io_error(response).await),
+        }
+    }
+
+    async fn get_file(
+        &mut self,
+        username: &str,
+        filename: &str,
+        progress: Option<ProgressFn<'_>>,
+    ) -> io::Result<Vec<u8>> {
         let mut builder = self
             .client
             .get(self.make_url(&format!("api/users/{}/files/{}", username, filename)))
@@ -260,9 +482,7 @@ impl Service for CloudService {
         }
         let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
         match response.status() {
-            StatusCode::OK => {
-                Ok(response.bytes().await.map_err(reqwest_error_to_io_error)?.to_vec())
-            }
+            StatusCode::OK => read_body(response, progress).await,
             _ => Err(http_response_to_io_error(response).await),
         }
     }
@@ -271,6 +491,7 @@ impl Service for CloudService {
         let mut headers = self.default_headers();
         headers.insert("X-EndBASIC-GetContent", "false".parse().unwrap());
         headers.insert("X-EndBASIC-GetReaders", "true".parse().unwrap());
+        headers.insert("X-EndBASIC-GetWriters", "true".parse().unwrap());
         let mut builder = self
             .client
             .get(self.make_url(&format!("api/users/{}/files/{}", username, filename)))
@@ -281,23 +502,13 @@ impl Service for CloudService {
         let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
         match response.status() {
             StatusCode::OK => {
-                let mut readers = vec![];
-                for h in response.headers().get_all("X-EndBASIC-Reader") {
-                    match h.to_str() {
-                        Ok(value) => readers.push(value.to_owned()),
-                        Err(e) => {
-                            return Err(io::Error::new(
-                                io::ErrorKind::InvalidData,
-                                format!("Server returned invalid reader ACL: {}", e),
-                            ))
-                        }
-                    }
-                }
+                let readers = parse_acl_headers(&response, "X-EndBASIC-Reader", "reader")?;
+                let writers = parse_acl_headers(&response, "X-EndBASIC-Writer", "writer")?;
 
                 let bytes = response.bytes().await.map_err(reqwest_error_to_io_error)?;
                 debug_assert!(bytes.is_empty(), "Did not expect server to return content");
 
-                Ok(FileAcls::default().with_readers(readers))
+                Ok(FileAcls::default().with_readers(readers).with_writers(writers))
             }
             _ => Err(http_response_to_io_error(response).await),
         }
@@ -308,9 +519,17 @@ impl Service for CloudService {
         username: &str,
         filename: &str,
         content: Vec<u8>,
+        mut progress: Option<ProgressFn<'_>>,
     ) -> io::Result<()> {
         let auth_data = self.auth_data.borrow();
 
+        // The request body is sent in one go because streaming bodies are not available on all
+        // targets, so we can only tell when the upload starts and when it ends.
+        let total = content.len() as u64;
+        if let Some(progress) = progress.as_mut() {
+            progress(0, total);
+        }
+
         let response = self
             .client
             .patch(self.make_url(&format!("api/users/{}/files/{}", username, filename)))
@@ -323,7 +542,12 @@ impl Service for CloudService {
             .await
             .map_err(reqwest_error_to_io_error)?;
         match response.status() {
-            StatusCode::OK | StatusCode::CREATED => Ok(()),
+            StatusCode::OK | StatusCode::CREATED => {
+                if let Some(progress) = progress.as_mut() {
+                    progress(total, total);
+                }
+                Ok(())
+            }
             _ => Err(http_response_to_io_error(response).await),
         }
     }
@@ -351,6 +575,12 @@ impl Service for CloudService {
         for reader in remove.readers() {
             builder = builder.header("X-EndBASIC-RemoveReader", reader);
         }
+        for writer in add.writers() {
+            builder = builder.header("X-EndBASIC-AddWriter", writer);
+        }
+        for writer in remove.writers() {
+            builder = builder.header("X-EndBASIC-RemoveWriter", writer);
+        }
 
         let response = builder
             .bearer_auth(Self::require_auth_data(auth_data.as_ref())?.access_token.as_str())
@@ -380,6 +610,22 @@ impl Service for CloudService {
             _ => Err(http_response_to_io_error(response).await),
         }
     }
+
+    async fn ping(&mut self) -> io::Result<()> {
+        let response = self
+            .client
+            .get(self.make_url("api/"))
+            .headers(self.default_headers())
+            .send()
+            .await
+            .map_err(reqwest_error_to_io_error)?;
+        // Any response proves that the server is reachable, even if it does not serve this path.
+        if response.status().is_server_error() {
+            Err(http_response_to_io_error(response).await)
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -515,6 +761,31 @@ mod tests {
         assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
     }
 
+    #[tokio::test]
+    #[ignore = "Requires environment configuration and is expensive"]
+    async fn test_login_with_token() {
+        let username = env::var("TEST_ACCOUNT_1_USERNAME").expect("Expected env config not found");
+        let password = env::var("TEST_ACCOUNT_1_PASSWORD").expect("Expected env config not found");
+
+        let mut service = new_service_from_env();
+        let access_token = match service.login(&username, &password).await.unwrap() {
+            LoginResult::Authenticated(response) => response.access_token,
+            LoginResult::SecondFactorRequired(_) => panic!("Test account must not require 2FA"),
+        };
+
+        let mut service = new_service_from_env();
+        service.login_with_token(&username, access_token).await.unwrap();
+        assert!(service.is_logged_in());
+
+        let mut service = new_service_from_env();
+        let err = service
+            .login_with_token(&username, AccessToken::new("invalid token"))
+            .await
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert!(!service.is_logged_in());
+    }
+
     #[test]
     #[ignore = "Requires environment configuration and is expensive"]
     fn test_get_files() {
@@ -546,12 +817,15 @@ mod tests {
             assert!(disk_free.files() >= needed_files, "Not enough space for test run");
 
             for (filename, _content) in &filenames_and_contents {
-                let err = service.get_file(&username, filename).await.unwrap_err();
+                let err = service.get_file(&username, filename, None).await.unwrap_err();
                 assert_eq!(io::ErrorKind::NotFound, err.kind(), "{}", err);
             }
 
             for (filename, content) in &filenames_and_contents {
-                service.patch_file_content(&username, filename, content.clone()).await.unwrap();
+                service
+                    .patch_file_content(&username, filename, content.clone(), None)
+                    .await
+                    .unwrap();
             }
 
             let response = service.get_files(&username).await.unwrap();
@@ -571,8 +845,8 @@ mod tests {
         let mut service = context.service();
 
         let content = content.into();
-        service.patch_file_content(&username, filename, content.clone()).await.unwrap();
-        assert_eq!(content, service.get_file(&username, filename).await.unwrap());
+        service.patch_file_content(&username, filename, content.clone(), None).await.unwrap();
+        assert_eq!(content, service.get_file(&username, filename, None).await.unwrap());
     }
 
     #[test]
@@ -618,7 +892,7 @@ mod tests {
             let mut service = context.service();
             let (filename, _content) = context.random_file();
 
-            let err = service.get_file(&username, &filename).await.unwrap_err();
+            let err = service.get_file(&username, &filename, None).await.unwrap_err();
             assert_eq!(io::ErrorKind::NotFound, err.kind(), "{}", err);
         }
         run(&mut TestContext::new_from_env());
@@ -637,7 +911,7 @@ mod tests {
 
             context.do_logout().await;
             let err = service
-                .patch_file_content(&username, &filename, b"foo".to_vec())
+                .patch_file_content(&username, &filename, b"foo".to_vec(), None)
                 .await
                 .unwrap_err();
             assert_eq!(io::ErrorKind::PermissionDenied, err.kind(), "{}", err);
@@ -660,11 +934,11 @@ mod tests {
 
             // Share username1's file with username2.
             context.do_login(1).await;
-            service.patch_file_content(&username1, &filename, content.clone()).await.unwrap();
+            service.patch_file_content(&username1, &filename, content.clone(), None).await.unwrap();
 
             // Read username1's file as username2 before it is shared.
             context.do_login(2).await;
-            let err = service.get_file(&username1, &filename).await.unwrap_err();
+            let err = service.get_file(&username1, &filename, None).await.unwrap_err();
             assert_eq!(io::ErrorKind::NotFound, err.kind(), "{}", err);
 
             // Share username1's file with username2.
@@ -681,12 +955,54 @@ mod tests {
 
             // Read username1's file as username2 again, now that it is shared.
             context.do_login(2).await;
-            let response = service.get_file(&username1, &filename).await.unwrap();
+            let response = service.get_file(&username1, &filename, None).await.unwrap();
             assert_eq!(content, response);
         }
         run(&mut TestContext::new_from_env());
     }
 
+    #[test]
+    #[ignore = "Requires environment configuration and is expensive"]
+    fn test_acls_writers() {
+        #[tokio::main]
+        async fn run(context: &mut TestContext) {
+            let mut service = context.service();
+
+            let (filename, content) = context.random_file();
+
+            let username1 = context.get_username(1);
+            let username2 = context.get_username(2);
+
+            // Create username1's file and let username2 modify it.
+            context.do_login(1).await;
+            service.patch_file_content(&username1, &filename, content.clone(), None).await.unwrap();
+            service
+                .patch_file_acls(
+                    &username1,
+                    &filename,
+                    &FileAcls::default().with_writers([username2.clone()]),
+                    &FileAcls::default(),
+                )
+                .await
+                .unwrap();
+            let acls = service.get_file_acls(&username1, &filename).await.unwrap();
+            assert_eq!(&[username2], acls.writers());
+
+            // Modify username1's file as username2.
+            context.do_login(2).await;
+            service
+                .patch_file_content(&username1, &filename, b"changed".to_vec(), None)
+                .await
+                .unwrap();
+
+            // Read username1's file as username1 to see the modification.
+            context.do_login(1).await;
+            let response = service.get_file(&username1, &filename, None).await.unwrap();
+            assert_eq!(b"changed", response.as_slice());
+        }
+        run(&mut TestContext::new_from_env());
+    }
+
     #[test]
     #[ignore = "Requires environment configuration and is expensive"]
     fn test_acls_public() {
@@ -700,11 +1016,11 @@ mod tests {
 
             // Share username1's file with the public.
             context.do_login(1).await;
-            service.patch_file_content(&username1, &filename, content.clone()).await.unwrap();
+            service.patch_file_content(&username1, &filename, content.clone(), None).await.unwrap();
 
             // Read username1's file as a guest before it is shared.
             context.do_logout().await;
-            let err = service.get_file(&username1, &filename).await.unwrap_err();
+            let err = service.get_file(&username1, &filename, None).await.unwrap_err();
             assert_eq!(io::ErrorKind::NotFound, err.kind(), "{}", err);
 
             // Share username1's file with the public.
@@ -721,7 +1037,7 @@ mod tests {
 
             // Read username1's file as a guest again, now that it is shared.
             context.do_logout().await;
-            let response = service.get_file(&username1, &filename).await.unwrap();
+            let response = service.get_file(&username1, &filename, None).await.unwrap();
             assert_eq!(content, response);
         }
         run(&mut TestContext::new_from_env());
@@ -736,11 +1052,11 @@ mod tests {
             let mut service = context.service();
             let (filename, content) = context.random_file();
 
-            service.patch_file_content(&username, &filename, content).await.unwrap();
+            service.patch_file_content(&username, &filename, content, None).await.unwrap();
 
             service.delete_file(&username, &filename).await.unwrap();
 
-            let err = service.get_file(&username, &filename).await.unwrap_err();
+            let err = service.get_file(&username, &filename, None).await.unwrap_err();
             assert_eq!(io::ErrorKind::NotFound, err.kind(), "{}", err);
             assert!(format!("{}", err).contains("(server code: 404"));
         }
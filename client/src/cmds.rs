@@ -15,6 +15,7 @@
 
 //! Commands to interact with the cloud service.
 
+use crate::token::save_session;
 use crate::*;
 use async_trait::async_trait;
 use endbasic_core::ast::{ArgSep, ExprType};
@@ -25,7 +26,7 @@ use endbasic_core::exec::{Error, Machine, Result, Scope};
 use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
 use endbasic_core::LineCol;
 use endbasic_std::console::{is_narrow, read_line, read_line_secure, refill_and_print, Console};
-use endbasic_std::storage::{FileAcls, Storage};
+use endbasic_std::storage::{format_size, FileAcls, Storage};
 use endbasic_std::strings::parse_boolean;
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -47,20 +48,357 @@ those people will be able to see them by mounting your drive.
 If you have any questions or experience any problems while interacting with the cloud service, \
 please contact support@endbasic.dev.";
 
+/// The `ACCOUNTDEL` command.
+pub struct AccountDelCommand {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+    token_store: Option<Rc<RefCell<dyn TokenStore>>>,
+}
+
+impl AccountDelCommand {
+    /// Creates a new `ACCOUNTDEL` command.
+    ///
+    /// If `token_store` is provided, any session remembered in it is forgotten.
+    pub fn new(
+        service: Rc<RefCell<dyn Service>>,
+        console: Rc<RefCell<dyn Console>>,
+        storage: Rc<RefCell<Storage>>,
+        token_store: Option<Rc<RefCell<dyn TokenStore>>>,
+    ) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ACCOUNTDEL")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Permanently deletes the account of the logged in user.
+All of your files are deleted along with the account, including those you shared with others, and \
+this cannot be undone.  You will be asked to retype your username and your password to confirm \
+the operation.
+On success, you are logged out and the CLOUD drive is unmounted.  As a consequence of this, \
+running ACCOUNTDEL from within the CLOUD drive will fail.",
+                )
+                .build(),
+            service,
+            console,
+            storage,
+            token_store,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for AccountDelCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let username = match self.service.borrow().logged_in_username() {
+            Some(username) => username,
+            None => return Err(scope.internal_error("Must LOGIN first")),
+        };
+
+        // Check this upfront because failing to unmount the drive once the account is gone would
+        // leave it pointing to nowhere.
+        if self.storage.borrow().cwd().starts_with("CLOUD:") {
+            return Err(
+                scope.internal_error("Cannot delete the account while the CLOUD drive is active")
+            );
+        }
+
+        let console = &mut *self.console.borrow_mut();
+        console.print("").map_err(|e| scope.io_error(e))?;
+        refill_and_print(
+            console,
+            [
+                "WARNING: You are about to PERMANENTLY delete your cloud account.",
+                "All of your files will be deleted along with it, including those you shared with \
+others, and there is no way to recover them afterwards.  Make sure you have copies of anything \
+you want to keep before proceeding.",
+                "To confirm, retype your username and enter your password.  Enter anything else \
+or hit Ctrl+C to abort.",
+            ],
+            "    ",
+        )
+        .map_err(|e| scope.io_error(e))?;
+        console.print("").map_err(|e| scope.io_error(e))?;
+
+        let confirmation =
+            read_line(console, "Username: ", "", None).await.map_err(|e| scope.io_error(e))?;
+        if confirmation != username {
+            console
+                .print("Username does not match; your account was not deleted.")
+                .map_err(|e| scope.io_error(e))?;
+            return Ok(());
+        }
+        let password =
+            read_line_secure(console, "Password: ").await.map_err(|e| scope.io_error(e))?;
+
+        self.service.borrow_mut().delete_account(&password).await.map_err(|e| scope.io_error(e))?;
+
+        let unmounted = match self.storage.borrow_mut().unmount("CLOUD") {
+            Ok(()) => true,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => false,
+            Err(e) => return Err(scope.io_error(e)),
+        };
+        if let Some(token_store) = self.token_store.as_ref() {
+            token_store.borrow_mut().delete().map_err(|e| scope.io_error(e))?;
+        }
+
+        console.print("").map_err(|e| scope.io_error(e))?;
+        if unmounted {
+            console.print("    Unmounted CLOUD drive").map_err(|e| scope.io_error(e))?;
+        }
+        console
+            .print("    Your account has been deleted.  Good bye!")
+            .map_err(|e| scope.io_error(e))?;
+        console.print("").map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `CLOUDLS` command.
+pub struct CloudLsCommand {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl CloudLsCommand {
+    /// Creates a new `CLOUDLS` command.
+    pub fn new(service: Rc<RefCell<dyn Service>>, console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CLOUDLS")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("username"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Lists public content in the cloud service.
+Without arguments, lists the users that have shared files with the public.  With a username, \
+lists the files that the user has shared with the public.  This does not require an account.
+To access the files, mount the user's drive with the cloud:// file system scheme.  For example:
+    MOUNT \"X\", \"cloud://user-123\": DIR \"X:\"",
+                )
+                .build(),
+            service,
+            console,
+        })
+    }
+
+    /// Fetches all pages of the list of users with public files.
+    async fn fetch_users(&self) -> io::Result<Vec<String>> {
+        let mut users = vec![];
+        let mut page_token = None;
+        loop {
+            let response =
+                self.service.borrow_mut().list_public_users(page_token.as_deref()).await?;
+            users.extend(response.users);
+            match response.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+        Ok(users)
+    }
+
+    /// Fetches all pages of the list of public files owned by `username`.
+    async fn fetch_files(&self, username: &str) -> io::Result<Vec<DirectoryEntry>> {
+        let mut files = vec![];
+        let mut page_token = None;
+        loop {
+            let response = self
+                .service
+                .borrow_mut()
+                .list_public_files(username, page_token.as_deref())
+                .await?;
+            files.extend(response.files);
+            match response.next_page_token {
+                Some(next) => page_token = Some(next),
+                None => break,
+            }
+        }
+        Ok(files)
+    }
+
+    /// Prints the list of users with public files.
+    async fn show_users(&self) -> io::Result<()> {
+        let mut users = self.fetch_users().await?;
+        users.sort();
+
+        let console = &mut *self.console.borrow_mut();
+        console.print("")?;
+        console.print("    Users with public files")?;
+        console.print("")?;
+        for user in &users {
+            console.print(&format!("    {}", user))?;
+        }
+        if !users.is_empty() {
+            console.print("")?;
+        }
+        console.print(&format!("    {} user(s)", users.len()))?;
+        console.print("")?;
+        Ok(())
+    }
+
+    /// Prints the list of public files owned by `username` in the same format as `DIR`.
+    async fn show_files(&self, username: &str) -> io::Result<()> {
+        let mut files = self.fetch_files(username).await?;
+        files.sort_by(|a, b| a.filename.cmp(&b.filename));
+
+        let console = &mut *self.console.borrow_mut();
+        console.print("")?;
+        console.print(&format!("    Public files of {}", username))?;
+        console.print("")?;
+        console.print("    Modified              Size    Name")?;
+        let mut total_bytes = 0;
+        for file in &files {
+            let date = match time::OffsetDateTime::from_unix_timestamp(file.mtime as i64) {
+                Ok(date) => date,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}", e))),
+            };
+            console.print(&format!(
+                "    {:04}-{:02}-{:02} {:02}:{:02}    {:>6}    {}",
+                date.year(),
+                date.month() as u8,
+                date.day(),
+                date.hour(),
+                date.minute(),
+                format_size(file.length),
+                file.filename,
+            ))?;
+            total_bytes += file.length;
+        }
+        if !files.is_empty() {
+            console.print("")?;
+        }
+        console.print(&format!("    {} file(s), {} bytes", files.len(), total_bytes))?;
+        console.print("")?;
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CloudLsCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        if scope.nargs() == 0 {
+            self.show_users().await.map_err(|e| scope.io_error(e))
+        } else {
+            debug_assert_eq!(1, scope.nargs());
+            let (username, pos) = scope.pop_string_with_pos();
+            if username.is_empty() {
+                return Err(Error::SyntaxError(pos, "Username cannot be empty".to_owned()));
+            }
+            self.show_files(&username).await.map_err(|e| scope.io_error(e))
+        }
+    }
+}
+
+/// The `LOGGEDIN` function.
+pub struct LoggedInFunction {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+}
+
+impl LoggedInFunction {
+    /// Creates a new `LOGGEDIN` function.
+    pub fn new(service: Rc<RefCell<dyn Service>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("LOGGEDIN")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Checks if the user is logged into their account.
+Programs can use this to check for an active session before accessing the CLOUD drive.",
+                )
+                .build(),
+            service,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for LoggedInFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let logged_in = self.service.borrow().is_logged_in();
+        scope.return_boolean(logged_in)
+    }
+}
+
+/// Tries to read a boolean value until it is valid.  Returns `default` if the user hits enter.
+async fn read_bool(console: &mut dyn Console, prompt: &str, default: bool) -> io::Result<bool> {
+    loop {
+        match read_line(console, prompt, "", None).await? {
+            s if s.is_empty() => return Ok(default),
+            s => match parse_boolean(s.trim_end()) {
+                Ok(b) => return Ok(b),
+                Err(_) => {
+                    console.print("Invalid input; try again.")?;
+                    continue;
+                }
+            },
+        }
+    }
+}
+
+/// Prints the server-provided `motd` lines to the `console` within a frame.
+fn print_motd(console: &mut dyn Console, motd: Vec<String>) -> io::Result<()> {
+    console.print("")?;
+    console.print("----- BEGIN SERVER MOTD -----")?;
+    for line in motd {
+        refill_and_print(console, [line], "")?;
+    }
+    console.print("-----  END SERVER MOTD  -----")?;
+    console.print("")?;
+    Ok(())
+}
+
+/// Maximum number of times the user can type a two-factor authentication code during `LOGIN`.
+const MAX_2FA_ATTEMPTS: usize = 3;
+
 /// The `LOGIN` command.
 pub struct LoginCommand {
     metadata: CallableMetadata,
     service: Rc<RefCell<dyn Service>>,
     console: Rc<RefCell<dyn Console>>,
     storage: Rc<RefCell<Storage>>,
+    token_store: Option<Rc<RefCell<dyn TokenStore>>>,
 }
 
 impl LoginCommand {
     /// Creates a new `LOGIN` command.
+    ///
+    /// If `token_store` is provided, interactive logins offer to remember the session in it.
     pub fn new(
         service: Rc<RefCell<dyn Service>>,
         console: Rc<RefCell<dyn Console>>,
         storage: Rc<RefCell<Storage>>,
+        token_store: Option<Rc<RefCell<dyn TokenStore>>>,
     ) -> Rc<Self> {
         Rc::from(Self {
             metadata: CallableMetadataBuilder::new("LOGIN")
@@ -101,34 +439,93 @@ impl LoginCommand {
 On a successful login, this mounts your personal drive under the CLOUD:/ location, which you can \
 access with any other file-related commands.  Using the cloud:// file system scheme, you can mount \
 other people's drives with the MOUNT command.
+When run interactively, LOGIN offers to remember the session on this computer so that you are \
+logged in automatically the next time you start EndBASIC.  Use LOGOUT to forget it.
+If your account has two-factor authentication enabled, do not pass the password$ so that LOGIN \
+asks for it interactively, followed by the one-time code from your authenticator.
 To create an account, use the SIGNUP command.",
                 )
                 .build(),
             service,
             console,
             storage,
+            token_store,
         })
     }
 
+    /// Asks the user for the one-time code required to complete the login of `username` that
+    /// returned `challenge`, giving them a few attempts to type it correctly.
+    ///
+    /// This is only possible if `can_prompt` is true and the console is interactive.
+    async fn do_second_factor(
+        &self,
+        username: &str,
+        challenge: &SecondFactorChallenge,
+        can_prompt: bool,
+    ) -> io::Result<LoginResponse> {
+        let console = &mut *self.console.borrow_mut();
+        if !can_prompt || !console.is_interactive() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "Account requires a two-factor authentication code; LOGIN interactively \
+without passing the password to enter it",
+            ));
+        }
+
+        let mut attempt = 1;
+        loop {
+            let code = read_line(console, "Two-factor authentication code: ", "", None).await?;
+            let result = self
+                .service
+                .borrow_mut()
+                .login_2fa(username, &challenge.token_challenge, code.trim())
+                .await;
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e)
+                    if e.kind() == io::ErrorKind::PermissionDenied
+                        && attempt < MAX_2FA_ATTEMPTS =>
+                {
+                    console.print(&format!("{}; try again", e))?;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
     /// Performs the login workflow against the server.
-    async fn do_login(&self, username: &str, password: &str) -> io::Result<()> {
-        let response = self.service.borrow_mut().login(username, password).await?;
+    ///
+    /// `can_prompt` indicates whether the user can be asked for a second factor if necessary.
+    async fn do_login(&self, username: &str, password: &str, can_prompt: bool) -> io::Result<()> {
+        let result = self.service.borrow_mut().login(username, password).await?;
+        let response = match result {
+            LoginResult::Authenticated(response) => response,
+            LoginResult::SecondFactorRequired(challenge) => {
+                self.do_second_factor(username, &challenge, can_prompt).await?
+            }
+        };
 
         {
             let console = &mut *self.console.borrow_mut();
             if !is_narrow(&*console) && !response.motd.is_empty() {
-                console.print("")?;
-                console.print("----- BEGIN SERVER MOTD -----")?;
-                for line in response.motd {
-                    refill_and_print(console, [line], "")?;
-                }
-                console.print("-----  END SERVER MOTD  -----")?;
-                console.print("")?;
+                print_motd(console, response.motd)?;
             }
         }
 
-        let mut storage = self.storage.borrow_mut();
-        storage.mount("CLOUD", &format!("cloud://{}", username))?;
+        {
+            let mut storage = self.storage.borrow_mut();
+            storage.mount("CLOUD", &format!("cloud://{}", username), false).await?;
+        }
+
+        if let Some(token_store) = self.token_store.as_ref() {
+            let console = &mut *self.console.borrow_mut();
+            if console.is_interactive()
+                && read_bool(console, "Remember this login on this computer (y/N)? ", false).await?
+            {
+                save_session(&mut *token_store.borrow_mut(), username, &response.access_token)?;
+            }
+        }
 
         Ok(())
     }
@@ -146,16 +543,17 @@ impl Callable for LoginCommand {
         }
 
         let username = scope.pop_string();
-        let password = if scope.nargs() == 0 {
-            read_line_secure(&mut *self.console.borrow_mut(), "Password: ")
+        let (password, can_prompt) = if scope.nargs() == 0 {
+            let password = read_line_secure(&mut *self.console.borrow_mut(), "Password: ")
                 .await
-                .map_err(|e| scope.io_error(e))?
+                .map_err(|e| scope.io_error(e))?;
+            (password, true)
         } else {
             debug_assert_eq!(1, scope.nargs());
-            scope.pop_string()
+            (scope.pop_string(), false)
         };
 
-        self.do_login(&username, &password).await.map_err(|e| scope.io_error(e))
+        self.do_login(&username, &password, can_prompt).await.map_err(|e| scope.io_error(e))
     }
 }
 
@@ -165,14 +563,18 @@ pub struct LogoutCommand {
     service: Rc<RefCell<dyn Service>>,
     console: Rc<RefCell<dyn Console>>,
     storage: Rc<RefCell<Storage>>,
+    token_store: Option<Rc<RefCell<dyn TokenStore>>>,
 }
 
 impl LogoutCommand {
     /// Creates a new `LOGOUT` command.
+    ///
+    /// If `token_store` is provided, any session remembered in it is forgotten.
     pub fn new(
         service: Rc<RefCell<dyn Service>>,
         console: Rc<RefCell<dyn Console>>,
         storage: Rc<RefCell<Storage>>,
+        token_store: Option<Rc<RefCell<dyn TokenStore>>>,
     ) -> Rc<Self> {
         Rc::from(Self {
             metadata: CallableMetadataBuilder::new("LOGOUT")
@@ -181,12 +583,14 @@ impl LogoutCommand {
                 .with_description(
                     "Logs the user out of their account.
 Unmounts the CLOUD drive that was mounted by the LOGIN command.  As a consequence of this, running \
-LOGOUT from within the CLOUD drive will fail.",
+LOGOUT from within the CLOUD drive will fail.
+If LOGIN was asked to remember the session on this computer, LOGOUT forgets it.",
                 )
                 .build(),
             service,
             console,
             storage,
+            token_store,
         })
     }
 }
@@ -221,6 +625,9 @@ impl Callable for LogoutCommand {
         };
 
         self.service.borrow_mut().logout().await.map_err(|e| scope.io_error(e))?;
+        if let Some(token_store) = self.token_store.as_ref() {
+            token_store.borrow_mut().delete().map_err(|e| scope.io_error(e))?;
+        }
 
         {
             let mut console = self.console.borrow_mut();
@@ -236,6 +643,66 @@ impl Callable for LogoutCommand {
     }
 }
 
+/// The `MOTD` command.
+pub struct MotdCommand {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl MotdCommand {
+    /// Creates a new `MOTD` command.
+    pub fn new(service: Rc<RefCell<dyn Service>>, console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MOTD")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Shows the server's message of the day.
+LOGIN shows this message on a successful login.  Use MOTD to read it again at any time.  The \
+server may require you to be logged in to see it.",
+                )
+                .build(),
+            service,
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MotdCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let logged_in = self.service.borrow().is_logged_in();
+        let response = self.service.borrow_mut().get_motd().await;
+
+        let console = &mut *self.console.borrow_mut();
+        match response {
+            Ok(response) if response.motd.is_empty() => {
+                console.print("").map_err(|e| scope.io_error(e))?;
+                console
+                    .print("    There is no message of the day")
+                    .map_err(|e| scope.io_error(e))?;
+                console.print("").map_err(|e| scope.io_error(e))?;
+            }
+            Ok(response) => print_motd(console, response.motd).map_err(|e| scope.io_error(e))?,
+            Err(e) if !logged_in && e.kind() == io::ErrorKind::PermissionDenied => {
+                console.print("").map_err(|e| scope.io_error(e))?;
+                console.print("    Log in to see the MOTD").map_err(|e| scope.io_error(e))?;
+                console.print("").map_err(|e| scope.io_error(e))?;
+            }
+            Err(e) => return Err(scope.io_error(e)),
+        }
+
+        Ok(())
+    }
+}
+
 /// The `SHARE` command.
 ///
 /// Note that this command is not exclusively for use by the cloud drive as this interacts with the
@@ -283,9 +750,12 @@ If given only a filename$, this command prints out the ACLs of the file.
 Otherwise, when given a list of ACL changes, applies those changes to the file.  The acl1$ to \
 aclN$ arguments are strings of the form \"username+r\" or \"username-r\", where the former adds \
 \"username\" to the users allowed to read the file, and the latter removes \"username\" from the \
-list of users allowed to read the file.
+list of users allowed to read the file.  Similarly, \"username+w\" and \"username-w\" add or \
+remove \"username\" from the list of users allowed to modify the file.  Read and write access \
+must be changed with separate ACLs.
 You can use the special \"public+r\" ACL to share a file with everyone.  These files can be \
-auto-run via the web interface using the special URL that the command prints on success.
+auto-run via the web interface using the special URL that the command prints on success.  Files \
+can never be made writable by everyone.
 Note that this command only works for cloud-based drives as it is designed to share files \
 among users of the EndBASIC service.",
                 )
@@ -306,6 +776,18 @@ impl ShareCommand {
         add: &mut FileAcls,
         remove: &mut FileAcls,
     ) -> Result<()> {
+        let lowercase_acl = acl.to_lowercase();
+        if ["+rw", "+wr", "-rw", "-wr"].iter().any(|suffix| lowercase_acl.ends_with(suffix)) {
+            return Err(Error::SyntaxError(
+                acl_pos,
+                format!(
+                    "Invalid ACL '{}': read and write access must be changed separately, as in \
+\"username+r\" and \"username+w\"",
+                    acl
+                ),
+            ));
+        }
+
         let change = if acl.len() < 3 { String::new() } else { acl.split_off(acl.len() - 2) };
         let username = acl; // For clarity after splitting off the ACL change request.
         match (username, change.as_str()) {
@@ -313,11 +795,25 @@ impl ShareCommand {
             (username, "+R") if !username.is_empty() => add.add_reader(username),
             (username, "-r") if !username.is_empty() => remove.add_reader(username),
             (username, "-R") if !username.is_empty() => remove.add_reader(username),
+            (username, "+w" | "+W") if username.to_lowercase() == "public" => {
+                return Err(Error::SyntaxError(
+                    acl_pos,
+                    format!(
+                        "Invalid ACL '{}{}': files cannot be writable by everyone",
+                        username, change
+                    ),
+                ))
+            }
+            (username, "+w") if !username.is_empty() => add.add_writer(username),
+            (username, "+W") if !username.is_empty() => add.add_writer(username),
+            (username, "-w") if !username.is_empty() => remove.add_writer(username),
+            (username, "-W") if !username.is_empty() => remove.add_writer(username),
             (username, change) => {
                 return Err(Error::SyntaxError(
                     acl_pos,
                     format!(
-                        "Invalid ACL '{}{}': must be of the form \"username+r\" or \"username-r\"",
+                        "Invalid ACL '{}{}': must be of the form \"username+r\", \"username-r\", \
+\"username+w\" or \"username-w\"",
                         username, change
                     ),
                 ))
@@ -342,14 +838,24 @@ impl ShareCommand {
 
         let mut console = self.console.borrow_mut();
         console.print("")?;
-        if acls.readers().is_empty() {
+        if acls.is_empty() {
             console.print(&format!("    No ACLs on {}", filename))?;
-        } else {
+        }
+        if !acls.readers().is_empty() {
             console.print(&format!("    Reader ACLs on {}:", filename))?;
             for acl in acls.readers() {
                 console.print(&format!("    {}", acl))?;
             }
         }
+        if !acls.writers().is_empty() {
+            if !acls.readers().is_empty() {
+                console.print("")?;
+            }
+            console.print(&format!("    Writer ACLs on {}:", filename))?;
+            for acl in acls.writers() {
+                console.print(&format!("    {}", acl))?;
+            }
+        }
         console.print("")
     }
 }
@@ -462,24 +968,8 @@ cloud service.  You will be asked for confirmation before proceeding.",
         })
     }
 
-    /// Tries to read a boolean value until it is valid.  Returns `default` if the user hits enter.
-    async fn read_bool(console: &mut dyn Console, prompt: &str, default: bool) -> io::Result<bool> {
-        loop {
-            match read_line(console, prompt, "", None).await? {
-                s if s.is_empty() => return Ok(default),
-                s => match parse_boolean(s.trim_end()) {
-                    Ok(b) => return Ok(b),
-                    Err(_) => {
-                        console.print("Invalid input; try again.")?;
-                        continue;
-                    }
-                },
-            }
-        }
-    }
-
-    /// Tries to get a password from the user until it is valid.
-    async fn read_password(console: &mut dyn Console) -> io::Result<String> {
+    /// Tries to get a password from the user until it is valid.
+    async fn read_password(console: &mut dyn Console) -> io::Result<String> {
         loop {
             let password = read_line_secure(console, "Password: ").await?;
             match validate_password_complexity(&password) {
@@ -542,10 +1032,9 @@ have no adverse impact in the service you receive.",
 
         let email =
             read_line(console, "Email address: ", "", None).await.map_err(|e| scope.io_error(e))?;
-        let promotional_email =
-            Self::read_bool(console, "Receive promotional email (y/N)? ", false)
-                .await
-                .map_err(|e| scope.io_error(e))?;
+        let promotional_email = read_bool(console, "Receive promotional email (y/N)? ", false)
+            .await
+            .map_err(|e| scope.io_error(e))?;
 
         console.print("").map_err(|e| scope.io_error(e))?;
         refill_and_print(
@@ -561,9 +1050,8 @@ have no adverse impact in the service you receive.",
         console
             .print(&format!("Promotional email: {}", if promotional_email { "yes" } else { "no" }))
             .map_err(|e| scope.io_error(e))?;
-        let proceed = Self::read_bool(console, "Continue (y/N)? ", false)
-            .await
-            .map_err(|e| scope.io_error(e))?;
+        let proceed =
+            read_bool(console, "Continue (y/N)? ", false).await.map_err(|e| scope.io_error(e))?;
         if !proceed {
             // TODO(jmmv): This should return an error of some form once we have error handling in
             // the language.
@@ -589,28 +1077,169 @@ in it to activate your account.  Make sure to check your spam folder.",
     }
 }
 
+/// The `USERNAME` function.
+pub struct UsernameFunction {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+}
+
+impl UsernameFunction {
+    /// Creates a new `USERNAME` function.
+    pub fn new(service: Rc<RefCell<dyn Service>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("USERNAME")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the name of the user that is logged in.
+Returns the empty string if there is no active session.",
+                )
+                .build(),
+            service,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for UsernameFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let username = self.service.borrow().logged_in_username();
+        scope.return_string(username.unwrap_or_default())
+    }
+}
+
+/// The `WHOAMI` command.
+pub struct WhoamiCommand {
+    metadata: CallableMetadata,
+    service: Rc<RefCell<dyn Service>>,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl WhoamiCommand {
+    /// Creates a new `WHOAMI` command.
+    pub fn new(
+        service: Rc<RefCell<dyn Service>>,
+        console: Rc<RefCell<dyn Console>>,
+        storage: Rc<RefCell<Storage>>,
+    ) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("WHOAMI")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Shows the user that is logged in.
+Also shows whether the CLOUD drive is mounted and where it points to.  To query the logged in \
+user from a program, use the LOGGEDIN? and USERNAME$ functions.",
+                )
+                .build(),
+            service,
+            console,
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for WhoamiCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let username = self.service.borrow().logged_in_username();
+        let cloud_target =
+            self.storage.borrow().mounted().get("CLOUD").map(|target| (*target).to_owned());
+
+        let mut console = self.console.borrow_mut();
+        console.print("").map_err(|e| scope.io_error(e))?;
+        match username {
+            Some(username) => console
+                .print(&format!("    Logged in as {}", username))
+                .map_err(|e| scope.io_error(e))?,
+            None => console.print("    Not logged in").map_err(|e| scope.io_error(e))?,
+        }
+        match cloud_target {
+            Some(target) => console
+                .print(&format!("    CLOUD drive mounted from {}", target))
+                .map_err(|e| scope.io_error(e))?,
+            None => console.print("    CLOUD drive not mounted").map_err(|e| scope.io_error(e))?,
+        }
+        console.print("").map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
 /// Adds all remote manipulation commands for `service` to the `machine`, using `console` to
 /// display information and `storage` to manipulate the remote drives.
+///
+/// If `token_store` is provided, the login commands use it to remember sessions across runs.
+/// `retry_policy` configures how cloud drives retry requests that fail due to transient errors.
 pub fn add_all<S: Into<String>>(
     machine: &mut Machine,
     service: Rc<RefCell<dyn Service>>,
     console: Rc<RefCell<dyn Console>>,
     storage: Rc<RefCell<Storage>>,
+    token_store: Option<Rc<RefCell<dyn TokenStore>>>,
+    retry_policy: RetryPolicy,
     exec_base_url: S,
 ) {
+    storage.borrow_mut().register_scheme(
+        "cloud",
+        Box::from(CloudDriveFactory::new(
+            service.clone(),
+            retry_policy,
+            Some(console.clone()),
+            DEFAULT_CACHE_TTL,
+        )),
+    );
+    let transport = Rc::from(ReqwestTransport::default());
     storage
         .borrow_mut()
-        .register_scheme("cloud", Box::from(CloudDriveFactory::new(service.clone())));
+        .register_scheme("http", Box::from(HttpDriveFactory::new("http", transport.clone())));
+    storage
+        .borrow_mut()
+        .register_scheme("https", Box::from(HttpDriveFactory::new("https", transport)));
 
-    machine.add_callable(LoginCommand::new(service.clone(), console.clone(), storage.clone()));
-    machine.add_callable(LogoutCommand::new(service.clone(), console.clone(), storage.clone()));
+    machine.add_callable(AccountDelCommand::new(
+        service.clone(),
+        console.clone(),
+        storage.clone(),
+        token_store.clone(),
+    ));
+    machine.add_callable(CloudLsCommand::new(service.clone(), console.clone()));
+    machine.add_callable(LoggedInFunction::new(service.clone()));
+    machine.add_callable(LoginCommand::new(
+        service.clone(),
+        console.clone(),
+        storage.clone(),
+        token_store.clone(),
+    ));
+    machine.add_callable(LogoutCommand::new(
+        service.clone(),
+        console.clone(),
+        storage.clone(),
+        token_store,
+    ));
+    machine.add_callable(MotdCommand::new(service.clone(), console.clone()));
     machine.add_callable(ShareCommand::new(
         service.clone(),
         console.clone(),
-        storage,
+        storage.clone(),
         exec_base_url,
     ));
-    machine.add_callable(SignupCommand::new(service, console));
+    machine.add_callable(SignupCommand::new(service.clone(), console.clone()));
+    machine.add_callable(UsernameFunction::new(service.clone()));
+    machine.add_callable(WhoamiCommand::new(service, console, storage));
 }
 
 #[cfg(test)]
@@ -619,12 +1248,101 @@ mod tests {
     use crate::testutils::*;
     use endbasic_std::{console::CharsXY, testutils::*};
 
+    #[tokio::test]
+    async fn test_accountdel_ok() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_service().borrow_mut().add_mock_delete_account("the-password", Ok(()));
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
+        t.get_token_store().borrow_mut().data = Some("saved session".to_owned());
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t.add_input_chars("logged-in-username\n").add_input_chars("the-password\n");
+        let mut c = t.run("ACCOUNTDEL");
+        let output = flatten_output(c.take_captured_out());
+        c.check();
+
+        assert!(output.contains("PERMANENTLY delete your cloud account"));
+        assert!(output.contains("Unmounted CLOUD drive"));
+        assert!(output.contains("Your account has been deleted.  Good bye!"));
+        assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
+        assert!(t.get_token_store().borrow().data.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_accountdel_username_mismatch() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
+        t.get_token_store().borrow_mut().data = Some("saved session".to_owned());
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t.add_input_chars("someone-else\n");
+        let mut c = t.run("ACCOUNTDEL");
+        let output = flatten_output(c.take_captured_out());
+        c.expect_access_token("$").check();
+
+        assert!(output.contains("Username does not match; your account was not deleted."));
+        assert!(!output.contains("Password: "));
+        assert!(t.get_storage().borrow().mounted().contains_key("CLOUD"));
+        assert!(t.get_token_store().borrow().data.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_accountdel_cloud_mounted_and_active() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
+        t.get_storage().borrow_mut().cd("CLOUD:/").unwrap();
+        t.run("ACCOUNTDEL")
+            .expect_err("1:1: Cannot delete the account while the CLOUD drive is active")
+            .expect_access_token("$")
+            .check();
+        assert!(t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[tokio::test]
+    async fn test_accountdel_service_error() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_service().borrow_mut().add_mock_delete_account(
+            "bad-password",
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Invalid password")),
+        );
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t.add_input_chars("logged-in-username\n").add_input_chars("bad-password\n");
+        let mut c = t.run("ACCOUNTDEL");
+        let output = flatten_output(c.take_captured_out());
+        c.expect_err("1:1: Invalid password").expect_access_token("$").check();
+
+        assert!(!output.contains("Good bye!"));
+        assert!(t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[test]
+    fn test_accountdel_errors() {
+        client_check_stmt_compilation_err(
+            "1:1: ACCOUNTDEL expected no arguments",
+            r#"ACCOUNTDEL "a""#,
+        );
+        client_check_stmt_err("1:1: Must LOGIN first", "ACCOUNTDEL");
+    }
+
     #[test]
     fn test_cloud_scheme_always_available() {
         let t = ClientTester::default();
         assert!(t.get_storage().borrow().has_scheme("cloud"));
     }
 
+    #[test]
+    fn test_http_schemes_always_available() {
+        let t = ClientTester::default();
+        assert!(t.get_storage().borrow().has_scheme("http"));
+        assert!(t.get_storage().borrow().has_scheme("https"));
+    }
+
     #[test]
     fn test_login_ok_with_password() {
         let mut t = ClientTester::default();
@@ -659,7 +1377,13 @@ mod tests {
         }
         exp_output.push(CapturedOut::Print("".to_owned()));
 
+        exp_output
+            .push(CapturedOut::Write("Remember this login on this computer (y/N)? ".to_string()));
+        exp_output.push(CapturedOut::SyncNow);
+        exp_output.push(CapturedOut::Print("".to_owned()));
+
         t.add_input_chars("the-password")
+            .add_input_chars("\n")
             .add_input_chars("\n")
             .run(format!(r#"LOGIN "{}""#, "the-username"))
             .expect_access_token("random token")
@@ -669,6 +1393,48 @@ mod tests {
         assert!(storage.borrow().mounted().contains_key("CLOUD"));
     }
 
+    #[test]
+    fn test_login_remember_session() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login(
+            "the-username",
+            "the-password",
+            Ok(LoginResponse { access_token: AccessToken::new("random token"), motd: vec![] }),
+        );
+        t.get_console().borrow_mut().set_interactive(true);
+        let token_store = t.get_token_store();
+
+        let mut t = t.add_input_chars("y\n");
+        let mut c = t.run(r#"LOGIN "the-username", "the-password""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_access_token("random token").check();
+
+        assert!(output.contains("Remember this login on this computer (y/N)? "));
+        let data = token_store.borrow().data.clone().expect("Session should have been saved");
+        assert!(data.contains("the-username"));
+        assert!(data.contains("random token"));
+    }
+
+    #[test]
+    fn test_login_do_not_remember_session() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login(
+            "the-username",
+            "the-password",
+            Ok(LoginResponse { access_token: AccessToken::new("random token"), motd: vec![] }),
+        );
+        t.get_console().borrow_mut().set_interactive(true);
+        let token_store = t.get_token_store();
+
+        let mut t = t.add_input_chars("n\n");
+        let mut c = t.run(r#"LOGIN "the-username", "the-password""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_access_token("random token").check();
+
+        assert!(output.contains("Remember this login on this computer (y/N)? "));
+        assert!(token_store.borrow().data.is_none());
+    }
+
     #[test]
     fn test_login_skip_motd_on_narrow_console() {
         let mut t = ClientTester::default();
@@ -710,6 +1476,121 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_login_2fa_ok() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login_second_factor("u", "p", "the challenge");
+        t.get_service().borrow_mut().add_mock_login_2fa(
+            "u",
+            "the challenge",
+            "123456",
+            Ok(LoginResponse { access_token: AccessToken::new("random token"), motd: vec![] }),
+        );
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t.add_input_chars("p\n").add_input_chars("123456\n").add_input_chars("\n");
+        let mut c = t.run(r#"LOGIN "u""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_access_token("random token").check();
+
+        assert!(output.contains("Two-factor authentication code: "));
+        assert!(t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[test]
+    fn test_login_2fa_retry() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login_second_factor("u", "p", "the challenge");
+        t.get_service().borrow_mut().add_mock_login_2fa(
+            "u",
+            "the challenge",
+            "111111",
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Invalid code")),
+        );
+        t.get_service().borrow_mut().add_mock_login_2fa(
+            "u",
+            "the challenge",
+            "222222",
+            Ok(LoginResponse { access_token: AccessToken::new("random token"), motd: vec![] }),
+        );
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t
+            .add_input_chars("p\n")
+            .add_input_chars("111111\n")
+            .add_input_chars(" 222222 \n")
+            .add_input_chars("\n");
+        let mut c = t.run(r#"LOGIN "u""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_access_token("random token").check();
+
+        assert!(output.contains("Invalid code; try again"));
+        assert!(t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[test]
+    fn test_login_2fa_exhausted() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login_second_factor("u", "p", "the challenge");
+        for code in ["111111", "222222", "333333"] {
+            t.get_service().borrow_mut().add_mock_login_2fa(
+                "u",
+                "the challenge",
+                code,
+                Err(io::Error::new(io::ErrorKind::PermissionDenied, "Invalid code")),
+            );
+        }
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t
+            .add_input_chars("p\n")
+            .add_input_chars("111111\n")
+            .add_input_chars("222222\n")
+            .add_input_chars("333333\n");
+        let mut c = t.run(r#"LOGIN "u""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_err("1:1: Invalid code").check();
+
+        assert_eq!(2, output.matches("Invalid code; try again").count());
+        assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[test]
+    fn test_login_2fa_no_retry_on_other_errors() {
+        let t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login_second_factor("u", "p", "the challenge");
+        t.get_service().borrow_mut().add_mock_login_2fa(
+            "u",
+            "the challenge",
+            "111111",
+            Err(io::Error::new(io::ErrorKind::Other, "Server is down")),
+        );
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut t = t.add_input_chars("p\n").add_input_chars("111111\n");
+        let mut c = t.run(r#"LOGIN "u""#);
+        let output = flatten_output(c.take_captured_out());
+        c.expect_err("1:1: Server is down").check();
+
+        assert!(!output.contains("try again"));
+    }
+
+    #[test]
+    fn test_login_2fa_requires_interactive_login() {
+        for interactive in [false, true] {
+            let mut t = ClientTester::default();
+            t.get_service().borrow_mut().add_mock_login_second_factor("u", "p", "the challenge");
+            t.get_console().borrow_mut().set_interactive(interactive);
+            t.run(r#"LOGIN "u", "p""#)
+                .expect_err(
+                    "1:1: Account requires a two-factor authentication code; LOGIN interactively \
+without passing the password to enter it",
+                )
+                .check();
+            assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
+        }
+    }
+
     #[test]
     fn test_login_bad_credentials() {
         let mut t = ClientTester::default();
@@ -789,7 +1670,7 @@ mod tests {
     async fn test_logout_ok_unmount_cloud() {
         let mut t = ClientTester::default();
         t.get_service().borrow_mut().do_login().await;
-        t.get_storage().borrow_mut().mount("CLOUD", "memory://").unwrap();
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
         t.run(r#"LOGOUT"#)
             .expect_prints(["", "    Unmounted CLOUD drive", "    Good bye!", ""])
             .check();
@@ -800,7 +1681,7 @@ mod tests {
     async fn test_logout_cloud_mounted_and_active() {
         let mut t = ClientTester::default();
         t.get_service().borrow_mut().do_login().await;
-        t.get_storage().borrow_mut().mount("CLOUD", "memory://").unwrap();
+        t.get_storage().borrow_mut().mount("CLOUD", "memory://", false).await.unwrap();
         t.get_storage().borrow_mut().cd("CLOUD:/").unwrap();
         t.run(r#"LOGOUT"#)
             .expect_err("1:1: Cannot log out while the CLOUD drive is active")
@@ -815,6 +1696,304 @@ mod tests {
         client_check_stmt_err("1:1: Must LOGIN first", r#"LOGOUT"#);
     }
 
+    #[tokio::test]
+    async fn test_motd_logged_in() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 0));
+        t.get_service().borrow_mut().add_mock_get_motd(Ok(MotdResponse {
+            motd: vec!["first line".to_owned(), "second line".to_owned()],
+        }));
+        t.run("MOTD")
+            .expect_prints([
+                "",
+                "----- BEGIN SERVER MOTD -----",
+                "first",
+                "line",
+                "second",
+                "line",
+                "-----  END SERVER MOTD  -----",
+                "",
+            ])
+            .expect_access_token("$")
+            .check();
+    }
+
+    #[test]
+    fn test_motd_logged_out_public() {
+        let mut t = ClientTester::default();
+        t.get_service()
+            .borrow_mut()
+            .add_mock_get_motd(Ok(MotdResponse { motd: vec!["public news".to_owned()] }));
+        t.run("MOTD")
+            .expect_prints([
+                "",
+                "----- BEGIN SERVER MOTD -----",
+                "public news",
+                "-----  END SERVER MOTD  -----",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_motd_logged_out_requires_login() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_get_motd(Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Not logged in yet",
+        )));
+        t.run("MOTD").expect_prints(["", "    Log in to see the MOTD", ""]).check();
+    }
+
+    #[tokio::test]
+    async fn test_motd_empty() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_service().borrow_mut().add_mock_get_motd(Ok(MotdResponse { motd: vec![] }));
+        t.run("MOTD")
+            .expect_prints(["", "    There is no message of the day", ""])
+            .expect_access_token("$")
+            .check();
+    }
+
+    #[tokio::test]
+    async fn test_motd_logged_in_permission_denied() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_service().borrow_mut().add_mock_get_motd(Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "Token expired",
+        )));
+        t.run("MOTD").expect_err("1:1: Token expired").expect_access_token("$").check();
+    }
+
+    #[test]
+    fn test_motd_errors() {
+        client_check_stmt_compilation_err("1:1: MOTD expected no arguments", r#"MOTD "a""#);
+
+        let mut t = ClientTester::default();
+        t.get_service()
+            .borrow_mut()
+            .add_mock_get_motd(Err(io::Error::new(io::ErrorKind::Other, "Server is down")));
+        t.run("MOTD").expect_err("1:1: Server is down").check();
+    }
+
+    #[test]
+    fn test_cloudls_users_empty() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_users(
+            None,
+            Ok(PublicUsersResponse { users: vec![], next_page_token: None }),
+        );
+        t.run("CLOUDLS")
+            .expect_prints(["", "    Users with public files", "", "    0 user(s)", ""])
+            .check();
+    }
+
+    #[test]
+    fn test_cloudls_users_multiple_pages() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_users(
+            None,
+            Ok(PublicUsersResponse {
+                users: vec!["user-b".to_owned(), "user-a".to_owned()],
+                next_page_token: Some("page-2".to_owned()),
+            }),
+        );
+        t.get_service().borrow_mut().add_mock_list_public_users(
+            Some("page-2"),
+            Ok(PublicUsersResponse { users: vec!["user-c".to_owned()], next_page_token: None }),
+        );
+        t.run("CLOUDLS")
+            .expect_prints([
+                "",
+                "    Users with public files",
+                "",
+                "    user-a",
+                "    user-b",
+                "    user-c",
+                "",
+                "    3 user(s)",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_cloudls_files_empty() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_files(
+            "someone",
+            None,
+            Ok(PublicFilesResponse { files: vec![], next_page_token: None }),
+        );
+        t.run(r#"CLOUDLS "someone""#)
+            .expect_prints([
+                "",
+                "    Public files of someone",
+                "",
+                "    Modified              Size    Name",
+                "    0 file(s), 0 bytes",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_cloudls_files_multiple_pages() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_files(
+            "someone",
+            None,
+            Ok(PublicFilesResponse {
+                files: vec![DirectoryEntry {
+                    filename: "game.bas".to_owned(),
+                    mtime: 1622556024,
+                    length: 15,
+                }],
+                next_page_token: Some("token".to_owned()),
+            }),
+        );
+        t.get_service().borrow_mut().add_mock_list_public_files(
+            "someone",
+            Some("token"),
+            Ok(PublicFilesResponse {
+                files: vec![DirectoryEntry {
+                    filename: "demo.bas".to_owned(),
+                    mtime: 1622642424,
+                    length: 12345,
+                }],
+                next_page_token: None,
+            }),
+        );
+        t.run(r#"CLOUDLS "someone""#)
+            .expect_prints([
+                "",
+                "    Public files of someone",
+                "",
+                "    Modified              Size    Name",
+                "    2021-06-02 14:00       12K    demo.bas",
+                "    2021-06-01 14:00        15    game.bas",
+                "",
+                "    2 file(s), 12360 bytes",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_cloudls_server_errors() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_users(
+            None,
+            Ok(PublicUsersResponse {
+                users: vec!["user-a".to_owned()],
+                next_page_token: Some("page-2".to_owned()),
+            }),
+        );
+        t.get_service().borrow_mut().add_mock_list_public_users(
+            Some("page-2"),
+            Err(io::Error::new(io::ErrorKind::Other, "Server is down")),
+        );
+        t.run("CLOUDLS").expect_err("1:1: Server is down").check();
+
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_list_public_files(
+            "someone",
+            None,
+            Err(io::Error::new(io::ErrorKind::NotFound, "User not found")),
+        );
+        t.run(r#"PRINT "x": CLOUDLS "someone""#)
+            .expect_prints(["x"])
+            .expect_err("1:12: User not found")
+            .check();
+    }
+
+    #[test]
+    fn test_cloudls_errors() {
+        client_check_stmt_compilation_err(
+            "1:1: CLOUDLS expected <> | <username$>",
+            r#"CLOUDLS "a", "b""#,
+        );
+        client_check_stmt_compilation_err("1:9: expected STRING but found INTEGER", "CLOUDLS 3");
+        client_check_stmt_err("1:9: Username cannot be empty", r#"CLOUDLS """#);
+    }
+
+    #[tokio::test]
+    async fn test_loggedin_and_username() {
+        let mut t = ClientTester::default();
+        t.run("l = LOGGEDIN?: u = USERNAME$").expect_var("l", false).expect_var("u", "").check();
+
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.run("l = LOGGEDIN?: u = USERNAME$")
+            .expect_access_token("$")
+            .expect_var("l", true)
+            .expect_var("u", "logged-in-username")
+            .check();
+    }
+
+    #[test]
+    fn test_loggedin_and_username_errors() {
+        client_check_stmt_compilation_err(
+            "1:5: LOGGEDIN expected no arguments",
+            "l = LOGGEDIN?(1)",
+        );
+        client_check_stmt_compilation_err(
+            "1:5: USERNAME expected no arguments",
+            "u = USERNAME$(1)",
+        );
+    }
+
+    #[test]
+    fn test_whoami_logged_out() {
+        let mut t = ClientTester::default();
+        t.run("WHOAMI")
+            .expect_prints(["", "    Not logged in", "    CLOUD drive not mounted", ""])
+            .check();
+    }
+
+    #[tokio::test]
+    async fn test_whoami_logged_in() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.get_storage()
+            .borrow_mut()
+            .mount("CLOUD", "cloud://logged-in-username", false)
+            .await
+            .unwrap();
+        t.run("WHOAMI")
+            .expect_prints([
+                "",
+                "    Logged in as logged-in-username",
+                "    CLOUD drive mounted from cloud://logged-in-username",
+                "",
+            ])
+            .expect_access_token("$")
+            .check();
+    }
+
+    #[tokio::test]
+    async fn test_whoami_logged_in_without_cloud_drive() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().do_login().await;
+        t.run("WHOAMI")
+            .expect_prints([
+                "",
+                "    Logged in as logged-in-username",
+                "    CLOUD drive not mounted",
+                "",
+            ])
+            .expect_access_token("$")
+            .check();
+    }
+
+    #[test]
+    fn test_whoami_errors() {
+        client_check_stmt_compilation_err("1:1: WHOAMI expected no arguments", r#"WHOAMI "a""#);
+    }
+
     #[test]
     fn test_login_logout_flow_once() {
         let mut t = ClientTester::default();
@@ -830,6 +2009,21 @@ mod tests {
         assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
     }
 
+    #[test]
+    fn test_logout_forgets_session() {
+        let mut t = ClientTester::default();
+        t.get_service().borrow_mut().add_mock_login(
+            "u1",
+            "p1",
+            Ok(LoginResponse { access_token: AccessToken::new("token 1"), motd: vec![] }),
+        );
+        t.get_token_store().borrow_mut().data = Some("saved session".to_owned());
+        t.run(r#"LOGIN "u1", "p1": LOGOUT"#)
+            .expect_prints(["", "    Unmounted CLOUD drive", "    Good bye!", ""])
+            .check();
+        assert!(t.get_token_store().borrow().data.is_none());
+    }
+
     #[test]
     fn test_login_logout_flow_multiple() {
         let mut t = ClientTester::default();
@@ -864,6 +2058,26 @@ mod tests {
         ShareCommand::parse_acl("Y-R".to_owned(), lc, &mut add, &mut remove).unwrap();
         assert_eq!(&["user1".to_owned(), "user2".to_owned()], add.readers());
         assert_eq!(&["X".to_owned(), "Y".to_owned()], remove.readers());
+        assert!(add.writers().is_empty());
+        assert!(remove.writers().is_empty());
+    }
+
+    #[test]
+    fn test_share_parse_acl_writers_ok() {
+        let mut add = FileAcls::default();
+        let mut remove = FileAcls::default();
+
+        let lc = LineCol { line: 0, col: 0 };
+
+        ShareCommand::parse_acl("user1+w".to_owned(), lc, &mut add, &mut remove).unwrap();
+        ShareCommand::parse_acl("user2+W".to_owned(), lc, &mut add, &mut remove).unwrap();
+        ShareCommand::parse_acl("X-w".to_owned(), lc, &mut add, &mut remove).unwrap();
+        ShareCommand::parse_acl("public-W".to_owned(), lc, &mut add, &mut remove).unwrap();
+        ShareCommand::parse_acl("user3+r".to_owned(), lc, &mut add, &mut remove).unwrap();
+        assert_eq!(&["user1".to_owned(), "user2".to_owned()], add.writers());
+        assert_eq!(&["X".to_owned(), "public".to_owned()], remove.writers());
+        assert_eq!(&["user3".to_owned()], add.readers());
+        assert!(remove.readers().is_empty());
     }
 
     #[test]
@@ -898,6 +2112,38 @@ mod tests {
         assert_eq!(&["before2".to_owned()], remove.readers());
     }
 
+    #[test]
+    fn test_share_parse_acl_writers_errors() {
+        let mut add = FileAcls::default().with_writers(["before1".to_owned()]);
+        let mut remove = FileAcls::default().with_writers(["before2".to_owned()]);
+
+        for (acl, exp_message) in &[
+            ("w", "must be of the form"),
+            ("+w", "must be of the form"),
+            ("foo+rw", "must be changed separately"),
+            ("foo-WR", "must be changed separately"),
+            ("public+w", "cannot be writable by everyone"),
+            ("PUBLIC+W", "cannot be writable by everyone"),
+        ] {
+            let err = ShareCommand::parse_acl(
+                acl.to_string(),
+                LineCol { line: 12, col: 34 },
+                &mut add,
+                &mut remove,
+            )
+            .unwrap_err();
+            let message = format!("12:34: {:?}", err);
+            assert!(message.contains("Invalid ACL"));
+            assert!(message.contains(acl));
+            assert!(message.contains(exp_message), "{}", message);
+        }
+
+        assert_eq!(&["before1".to_owned()], add.writers());
+        assert_eq!(&["before2".to_owned()], remove.writers());
+        assert!(add.readers().is_empty());
+        assert!(remove.readers().is_empty());
+    }
+
     #[tokio::test]
     async fn test_share_print_no_acls() {
         let mut t = ClientTester::default();
@@ -941,7 +2187,50 @@ mod tests {
         assert!(output.contains("https://repl.example.com/?run=logged-in-username/FOO.BAS"));
     }
 
-    // TODO(jmmv): Add forgotten tests for SHARE modifying ACLs.
+    #[tokio::test]
+    async fn test_share_print_writer_acls() {
+        let mut t = ClientTester::default();
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            storage.put("MEMORY:/FOO", b"").await.unwrap();
+            storage
+                .update_acls(
+                    "MEMORY:/FOO",
+                    &FileAcls::default().with_writers(["friend".to_owned()]),
+                    &FileAcls::default(),
+                )
+                .await
+                .unwrap();
+        }
+        t.run(r#"SHARE "MEMORY:/FOO""#)
+            .expect_prints(["", "    Writer ACLs on MEMORY:/FOO:", "    friend", ""])
+            .expect_file("MEMORY:/FOO", "")
+            .check();
+    }
+
+    #[tokio::test]
+    async fn test_share_modify_acls() {
+        let mut t = ClientTester::default();
+        t.get_storage().borrow_mut().put("MEMORY:/FOO", b"").await.unwrap();
+        t.run(
+            r#"SHARE "MEMORY:/FOO", "some+r", "friend+w", "other+w": SHARE "MEMORY:/FOO", "other-w""#,
+        )
+        .expect_file("MEMORY:/FOO", "")
+        .check();
+        t.run(r#"SHARE "MEMORY:/FOO""#)
+            .expect_prints([
+                "",
+                "    Reader ACLs on MEMORY:/FOO:",
+                "    some",
+                "",
+                "    Writer ACLs on MEMORY:/FOO:",
+                "    friend",
+                "",
+            ])
+            .expect_file("MEMORY:/FOO", "")
+            .check();
+    }
 
     #[test]
     fn test_share_errors() {
@@ -971,9 +2260,13 @@ mod tests {
             r#"SHARE "a", 3, "b""#,
         );
         client_check_stmt_err(
-            r#"1:12: Invalid ACL 'foobar': must be of the form "username+r" or "username-r""#,
+            r#"1:12: Invalid ACL 'foobar': must be of the form "username+r", "username-r", "username+w" or "username-w""#,
             r#"SHARE "a", "foobar""#,
         );
+        client_check_stmt_err(
+            r#"1:12: Invalid ACL 'public+w': files cannot be writable by everyone"#,
+            r#"SHARE "a", "public+w""#,
+        );
     }
 
     #[test]
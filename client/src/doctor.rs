@@ -0,0 +1,86 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Environment checks related to the cloud service.
+
+use crate::Service;
+use async_trait::async_trait;
+use endbasic_std::doctor::{Check, CheckResult};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Checks that the cloud service is reachable.
+pub struct ServiceCheck {
+    service: Rc<RefCell<dyn Service>>,
+}
+
+impl ServiceCheck {
+    /// Creates a new check that pings `service`.
+    pub fn new(service: Rc<RefCell<dyn Service>>) -> Rc<Self> {
+        Rc::from(Self { service })
+    }
+}
+
+#[async_trait(?Send)]
+impl Check for ServiceCheck {
+    fn name(&self) -> &'static str {
+        "Cloud service"
+    }
+
+    async fn run(&self) -> CheckResult {
+        match self.service.borrow_mut().ping().await {
+            Ok(()) => CheckResult::Pass("Reachable".to_owned()),
+            Err(e) => CheckResult::Fail(
+                e.to_string(),
+                "Check your network connection; LOGIN, SHARE and the CLOUD drive need it"
+                    .to_owned(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use std::io;
+
+    #[tokio::test]
+    async fn test_service_check_pass() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().add_mock_ping(Ok(()));
+        let check = ServiceCheck::new(service.clone());
+        assert_eq!(CheckResult::Pass("Reachable".to_owned()), check.run().await);
+        service.borrow_mut().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_service_check_fail() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service
+            .borrow_mut()
+            .add_mock_ping(Err(io::Error::new(io::ErrorKind::Other, "Connection refused")));
+        let check = ServiceCheck::new(service.clone());
+        assert_eq!(
+            CheckResult::Fail(
+                "Connection refused".to_owned(),
+                "Check your network connection; LOGIN, SHARE and the CLOUD drive need it"
+                    .to_owned()
+            ),
+            check.run().await
+        );
+        service.borrow_mut().verify_all_used();
+    }
+}
@@ -17,63 +17,282 @@
 
 use crate::*;
 use async_trait::async_trait;
-use endbasic_std::storage::{Drive, DriveFactory, DriveFiles, FileAcls, Metadata};
+use endbasic_std::console::{ClearType, Console};
+use endbasic_std::storage::{Drive, DriveFactory, DriveFiles, FileAcls, Metadata, Storage};
 use std::cell::RefCell;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::io;
 use std::rc::Rc;
 use std::str;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+/// Default amount of time during which the results of queries to the service are reused.
+pub(crate) const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Minimum size of a file transfer, in bytes, for which to show progress.
+const PROGRESS_THRESHOLD: u64 = 32 * 1024;
+
+/// Type of the function used to query the current time.
+type NowFn = Box<dyn Fn() -> OffsetDateTime>;
+
+/// A value obtained from the service along with the time when it was requested.
+struct CacheEntry<T> {
+    value: T,
+    fetched: OffsetDateTime,
+}
+
+/// Results of previous queries to the service that can be reused for a while.
+///
+/// The service does not expose version identifiers for files, so cached entries are only
+/// validated by their age.
+#[derive(Default)]
+struct Cache {
+    files: Option<CacheEntry<DriveFiles>>,
+    contents: HashMap<String, CacheEntry<Vec<u8>>>,
+}
+
+/// Shows the progress of a file transfer on a single console line that is updated in place.
+///
+/// Nothing is shown if there is no console, if the console is not interactive, or if the transfer
+/// is not larger than `PROGRESS_THRESHOLD`.  Failures to update the console are ignored because
+/// they should not cause the transfer to fail.
+struct ProgressLine {
+    console: Option<Rc<RefCell<dyn Console>>>,
+    label: String,
+    shown: usize,
+    percent: Option<u64>,
+}
+
+impl ProgressLine {
+    /// Creates a new progress line for a transfer of `filename` described by `verb`.
+    fn new(console: Option<&Rc<RefCell<dyn Console>>>, verb: &str, filename: &str) -> Self {
+        // The console may already be in use by the command that triggered the transfer, in which
+        // case we cannot safely draw on it.
+        let console = console
+            .filter(|c| c.try_borrow().map(|c| c.is_interactive()).unwrap_or(false))
+            .cloned();
+        Self { console, label: format!("{} {}...", verb, filename), shown: 0, percent: None }
+    }
+
+    /// Erases the text shown by a previous update, if any.
+    fn erase(&mut self, console: &mut dyn Console) -> io::Result<()> {
+        if self.shown > 0 {
+            console.move_within_line(-(self.shown.min(i16::MAX as usize) as i16))?;
+            console.clear(ClearType::UntilNewLine)?;
+            self.shown = 0;
+        }
+        Ok(())
+    }
+
+    /// Updates the line to show that `done` out of `total` bytes have been transferred.
+    fn update(&mut self, done: u64, total: u64) {
+        if total <= PROGRESS_THRESHOLD {
+            return;
+        }
+        let console = match self.console.clone() {
+            Some(console) => console,
+            None => return,
+        };
+        let percent = done.min(total) * 100 / total;
+        if self.percent == Some(percent) {
+            return;
+        }
+        self.percent = Some(percent);
+
+        let text = format!("{} {}%", self.label, percent);
+        let mut console = match console.try_borrow_mut() {
+            Ok(console) => console,
+            Err(_) => return,
+        };
+        let _ = self.erase(&mut *console).and_then(|()| {
+            console.write(&text)?;
+            console.sync_now()
+        });
+        self.shown = text.chars().count();
+    }
+
+    /// Erases the line, if it was ever shown.
+    fn finish(&mut self) {
+        if self.shown == 0 {
+            return;
+        }
+        if let Some(console) = self.console.clone() {
+            if let Ok(mut console) = console.try_borrow_mut() {
+                let _ = self.erase(&mut *console).and_then(|()| console.sync_now());
+            }
+        }
+    }
+}
 
 /// A drive backed by a remote EndBASIC service.
+///
+/// Idempotent operations are retried according to a `RetryPolicy` when they fail due to transient
+/// errors.  Deletions are not retried because a retry after a lost response would fail.
+///
+/// Directory listings and file contents are cached for a while to avoid round trips to the
+/// service when nothing changed.  Any modification made through the drive invalidates the cached
+/// data it affects, but modifications made elsewhere go unnoticed until the cache expires.
 struct CloudDrive {
     service: Rc<RefCell<dyn Service>>,
+    retry_policy: Rc<RetryPolicy>,
+    console: Option<Rc<RefCell<dyn Console>>>,
     username: String,
+    cache: RefCell<Cache>,
+    cache_ttl: Duration,
+    now_fn: NowFn,
 }
 
 impl CloudDrive {
-    /// Creates a new cloud drive against `service` to access the files owned by `username`.
-    fn new<S: Into<String>>(service: Rc<RefCell<dyn Service>>, username: S) -> Self {
+    /// Creates a new cloud drive against `service` to access the files owned by `username`,
+    /// retrying failed requests as specified by `retry_policy`, showing the progress of large
+    /// transfers on `console`, and reusing query results for `cache_ttl`.
+    fn new<S: Into<String>>(
+        service: Rc<RefCell<dyn Service>>,
+        retry_policy: Rc<RetryPolicy>,
+        console: Option<Rc<RefCell<dyn Console>>>,
+        cache_ttl: Duration,
+        username: S,
+    ) -> Self {
         let username = username.into();
-        Self { service, username }
+        Self {
+            service,
+            retry_policy,
+            console,
+            username,
+            cache: RefCell::from(Cache::default()),
+            cache_ttl,
+            now_fn: Box::from(OffsetDateTime::now_utc),
+        }
+    }
+
+    /// Overrides the function used to query the current time when checking cached entries.
+    #[cfg(test)]
+    fn with_now_fn(mut self, now_fn: NowFn) -> Self {
+        self.now_fn = now_fn;
+        self
+    }
+
+    /// Returns true if a cache entry `fetched` at the given time can still be used.
+    fn is_fresh(&self, fetched: OffsetDateTime) -> bool {
+        let age = (self.now_fn)() - fetched;
+        !age.is_negative() && age < self.cache_ttl
+    }
+
+    /// Invalidates the cached data affected by a modification to `filename`.
+    fn invalidate(&mut self, filename: &str) {
+        let key = self.canonicalize_name(filename);
+        let cache = self.cache.get_mut();
+        cache.files = None;
+        cache.contents.remove(&key);
     }
 }
 
 #[async_trait(?Send)]
 impl Drive for CloudDrive {
     async fn delete(&mut self, filename: &str) -> io::Result<()> {
-        self.service.borrow_mut().delete_file(&self.username, filename).await
+        let result = self.service.borrow_mut().delete_file(&self.username, filename).await;
+        self.invalidate(filename);
+        result
+    }
+
+    fn discard_cache(&mut self) {
+        *self.cache.get_mut() = Cache::default();
     }
 
     async fn enumerate(&self) -> io::Result<DriveFiles> {
-        let response = self.service.borrow_mut().get_files(&self.username).await?;
+        if let Some(entry) = self.cache.borrow().files.as_ref() {
+            if self.is_fresh(entry.fetched) {
+                return Ok(entry.value.clone());
+            }
+        }
+
+        let fetched = (self.now_fn)();
+        let response = self
+            .retry_policy
+            .run(|| async { self.service.borrow_mut().get_files(&self.username).await })
+            .await?;
         let mut entries = BTreeMap::default();
         for e in response.files {
-            let date = match time::OffsetDateTime::from_unix_timestamp(e.mtime as i64) {
+            let date = match OffsetDateTime::from_unix_timestamp(e.mtime as i64) {
                 Ok(date) => date,
                 Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}", e))),
             };
             entries.insert(e.filename, Metadata { date, length: e.length });
         }
-        Ok(DriveFiles::new(
+        let files = DriveFiles::new(
             entries,
             response.disk_quota.map(|x| x.into()),
             response.disk_free.map(|x| x.into()),
-        ))
+        );
+        self.cache.borrow_mut().files = Some(CacheEntry { value: files.clone(), fetched });
+        Ok(files)
     }
 
     async fn get(&self, filename: &str) -> io::Result<Vec<u8>> {
-        self.service.borrow_mut().get_file(&self.username, filename).await
+        let key = self.canonicalize_name(filename);
+        if let Some(entry) = self.cache.borrow().contents.get(&key) {
+            if self.is_fresh(entry.fetched) {
+                return Ok(entry.value.clone());
+            }
+        }
+
+        let fetched = (self.now_fn)();
+        let progress =
+            RefCell::from(ProgressLine::new(self.console.as_ref(), "Downloading", filename));
+        let result = self
+            .retry_policy
+            .run(|| async {
+                let mut progress = progress.borrow_mut();
+                self.service
+                    .borrow_mut()
+                    .get_file(
+                        &self.username,
+                        filename,
+                        Some(&mut |done, total| progress.update(done, total)),
+                    )
+                    .await
+            })
+            .await;
+        progress.borrow_mut().finish();
+
+        let content = result?;
+        let mut cache = self.cache.borrow_mut();
+        cache.contents.retain(|_, entry| self.is_fresh(entry.fetched));
+        cache.contents.insert(key, CacheEntry { value: content.clone(), fetched });
+        Ok(content)
     }
 
     async fn get_acls(&self, filename: &str) -> io::Result<FileAcls> {
-        self.service.borrow_mut().get_file_acls(&self.username, filename).await
+        self.retry_policy
+            .run(|| async {
+                self.service.borrow_mut().get_file_acls(&self.username, filename).await
+            })
+            .await
     }
 
     async fn put(&mut self, filename: &str, content: &[u8]) -> io::Result<()> {
-        self.service
-            .borrow_mut()
-            .patch_file_content(&self.username, filename, content.to_vec())
-            .await
+        // Replacing the whole content of a file is idempotent so the request can be replayed.
+        let progress =
+            RefCell::from(ProgressLine::new(self.console.as_ref(), "Uploading", filename));
+        let result = self
+            .retry_policy
+            .run(|| async {
+                let mut progress = progress.borrow_mut();
+                self.service
+                    .borrow_mut()
+                    .patch_file_content(
+                        &self.username,
+                        filename,
+                        content.to_vec(),
+                        Some(&mut |done, total| progress.update(done, total)),
+                    )
+                    .await
+            })
+            .await;
+        progress.borrow_mut().finish();
+        self.invalidate(filename);
+        result
     }
 
     async fn update_acls(
@@ -82,26 +301,53 @@ impl Drive for CloudDrive {
         add: &FileAcls,
         remove: &FileAcls,
     ) -> io::Result<()> {
-        self.service.borrow_mut().patch_file_acls(&self.username, filename, add, remove).await
+        let result = self
+            .retry_policy
+            .run(|| async {
+                self.service
+                    .borrow_mut()
+                    .patch_file_acls(&self.username, filename, add, remove)
+                    .await
+            })
+            .await;
+        self.invalidate(filename);
+        result
     }
 }
 
 /// Factory for cloud drives.
 pub struct CloudDriveFactory {
     service: Rc<RefCell<dyn Service>>,
+    retry_policy: Rc<RetryPolicy>,
+    console: Option<Rc<RefCell<dyn Console>>>,
+    cache_ttl: Duration,
 }
 
 impl CloudDriveFactory {
-    /// Creates a new cloud drive factory that uses `service` to connect to the remote service.
-    pub(crate) fn new(service: Rc<RefCell<dyn Service>>) -> Self {
-        Self { service }
+    /// Creates a new cloud drive factory that uses `service` to connect to the remote service,
+    /// that retries failed requests as specified by `retry_policy`, that shows the progress of
+    /// large transfers on `console`, and whose drives reuse query results for `cache_ttl`.
+    pub(crate) fn new(
+        service: Rc<RefCell<dyn Service>>,
+        retry_policy: RetryPolicy,
+        console: Option<Rc<RefCell<dyn Console>>>,
+        cache_ttl: Duration,
+    ) -> Self {
+        Self { service, retry_policy: Rc::from(retry_policy), console, cache_ttl }
     }
 }
 
+#[async_trait(?Send)]
 impl DriveFactory for CloudDriveFactory {
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
         if !target.is_empty() {
-            Ok(Box::from(CloudDrive::new(self.service.clone(), target)))
+            Ok(Box::from(CloudDrive::new(
+                self.service.clone(),
+                self.retry_policy.clone(),
+                self.console.clone(),
+                self.cache_ttl,
+                target,
+            )))
         } else {
             Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -115,12 +361,25 @@ impl DriveFactory for CloudDriveFactory {
 mod tests {
     use super::*;
     use crate::testutils::*;
+    use endbasic_std::testutils::{CapturedOut, MockConsole};
+    use std::cell::Cell;
+
+    /// Creates a retry policy that does not wait between attempts.
+    fn no_delay_retry_policy() -> Rc<RetryPolicy> {
+        Rc::from(RetryPolicy::new(3, Duration::ZERO, Duration::ZERO))
+    }
 
     #[tokio::test]
     async fn test_clouddrive_delete() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let mut drive = CloudDrive::new(service.clone(), "the-user");
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_delete_file("the-user", "the-filename", Ok(()));
         drive.delete("the-filename").await.unwrap();
@@ -132,7 +391,13 @@ mod tests {
     async fn test_clouddrive_enumerate() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_get_files(
             "the-user",
@@ -148,17 +413,11 @@ mod tests {
         let result = drive.enumerate().await.unwrap();
         assert_eq!(2, result.dirents().len());
         assert_eq!(
-            &Metadata {
-                date: time::OffsetDateTime::from_unix_timestamp(9000).unwrap(),
-                length: 15
-            },
+            &Metadata { date: OffsetDateTime::from_unix_timestamp(9000).unwrap(), length: 15 },
             result.dirents().get("one").unwrap()
         );
         assert_eq!(
-            &Metadata {
-                date: time::OffsetDateTime::from_unix_timestamp(8000).unwrap(),
-                length: 17
-            },
+            &Metadata { date: OffsetDateTime::from_unix_timestamp(8000).unwrap(), length: 17 },
             result.dirents().get("two").unwrap()
         );
         assert_eq!(&DiskSpace::new(10000, 100), result.disk_quota().as_ref().unwrap());
@@ -171,7 +430,13 @@ mod tests {
     async fn test_clouddrive_get() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_get_file(
             "the-user",
@@ -188,7 +453,13 @@ mod tests {
     async fn test_clouddrive_get_no_content() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_get_file("the-user", "the-filename", Ok(""));
         let result = drive.get("the-filename").await.unwrap();
@@ -205,7 +476,13 @@ mod tests {
 
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_get_file("the-user", "the-filename", Ok(BAD_UTF8));
         let result = drive.get("the-filename").await.unwrap();
@@ -218,12 +495,26 @@ mod tests {
     async fn test_clouddrive_get_acls() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
-        let response = FileAcls { readers: vec!["r1".to_owned(), "r2".to_owned()] };
+        let response = FileAcls {
+            readers: vec!["r1".to_owned(), "r2".to_owned()],
+            writers: vec!["w1".to_owned()],
+        };
         service.borrow_mut().add_mock_get_file_acls("the-user", "the-filename", Ok(response));
         let result = drive.get_acls("the-filename").await.unwrap();
-        assert_eq!(FileAcls::default().with_readers(["r1".to_owned(), "r2".to_owned()]), result);
+        assert_eq!(
+            FileAcls::default()
+                .with_readers(["r1".to_owned(), "r2".to_owned()])
+                .with_writers(["w1".to_owned()]),
+            result
+        );
 
         service.take().verify_all_used();
     }
@@ -232,7 +523,13 @@ mod tests {
     async fn test_clouddrive_get_acls_no_readers() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let drive = CloudDrive::new(service.clone(), "the-user");
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_get_file_acls(
             "the-user",
@@ -249,7 +546,13 @@ mod tests {
     async fn test_clouddrive_put_new() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let mut drive = CloudDrive::new(service.clone(), "the-user");
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_patch_file_content(
             "the-user",
@@ -266,7 +569,13 @@ mod tests {
     async fn test_clouddrive_put_existing() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let mut drive = CloudDrive::new(service.clone(), "the-user");
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
         service.borrow_mut().add_mock_patch_file_content(
             "the-user",
@@ -291,23 +600,479 @@ mod tests {
     async fn test_clouddrive_put_acls() {
         let service = Rc::from(RefCell::from(MockService::default()));
         service.borrow_mut().do_login().await;
-        let mut drive = CloudDrive::new(service.clone(), "the-user");
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
 
+        let add = FileAcls::default()
+            .with_readers(["r1".to_owned(), "r2".to_owned()])
+            .with_writers(["w1".to_owned()]);
+        let remove = FileAcls::default()
+            .with_readers(["r2".to_owned(), "r3".to_owned()])
+            .with_writers(["w2".to_owned()]);
         service.borrow_mut().add_mock_patch_file_acls(
             "the-user",
             "the-filename",
-            ["r1".to_owned(), "r2".to_owned()],
-            ["r2".to_owned(), "r3".to_owned()],
+            add.clone(),
+            remove.clone(),
+            Ok(()),
+        );
+        drive.update_acls("the-filename", &add, &remove).await.unwrap();
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_retry_eventual_success() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
+
+        service.borrow_mut().add_mock_get_file::<&[u8]>(
+            "the-user",
+            "the-filename",
+            Err(io::Error::new(io::ErrorKind::TimedOut, "Timed out")),
+        );
+        service.borrow_mut().add_mock_get_file::<&[u8]>(
+            "the-user",
+            "the-filename",
+            Err(io::Error::new(io::ErrorKind::AddrNotAvailable, "Service unavailable")),
+        );
+        service.borrow_mut().add_mock_get_file("the-user", "the-filename", Ok("some content"));
+        let result = drive.get("the-filename").await.unwrap();
+        assert_eq!(b"some content", result.as_slice());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_retry_put_replays_content() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
+
+        service.borrow_mut().add_mock_patch_file_content(
+            "the-user",
+            "the-filename",
+            "some content",
+            Err(io::Error::new(io::ErrorKind::TimedOut, "Timed out")),
+        );
+        service.borrow_mut().add_mock_patch_file_content(
+            "the-user",
+            "the-filename",
+            "some content",
             Ok(()),
         );
-        drive
-            .update_acls(
-                "the-filename",
-                &FileAcls::default().with_readers(["r1".to_owned(), "r2".to_owned()]),
-                &FileAcls::default().with_readers(["r2".to_owned(), "r3".to_owned()]),
+        drive.put("the-filename", b"some content").await.unwrap();
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_retry_exhausted() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
+
+        for i in 0..3 {
+            service.borrow_mut().add_mock_get_files(
+                "the-user",
+                Err(io::Error::new(io::ErrorKind::TimedOut, format!("Attempt {}", i))),
+            );
+        }
+        let err = drive.enumerate().await.unwrap_err();
+        assert_eq!(io::ErrorKind::TimedOut, err.kind());
+        assert_eq!("Attempt 2", format!("{}", err));
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_no_retry_on_permission_denied() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
+
+        service.borrow_mut().add_mock_get_file_acls(
+            "the-user",
+            "the-filename",
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Not allowed")),
+        );
+        let err = drive.get_acls("the-filename").await.unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_no_retry_on_delete() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            None,
+            Duration::ZERO,
+            "the-user",
+        );
+
+        service.borrow_mut().add_mock_delete_file(
+            "the-user",
+            "the-filename",
+            Err(io::Error::new(io::ErrorKind::TimedOut, "Timed out")),
+        );
+        let err = drive.delete("the-filename").await.unwrap_err();
+        assert_eq!(io::ErrorKind::TimedOut, err.kind());
+
+        service.take().verify_all_used();
+    }
+
+    /// Creates a mock console to capture the progress of transfers.
+    fn progress_console(interactive: bool) -> Rc<RefCell<MockConsole>> {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        console.borrow_mut().set_interactive(interactive);
+        console
+    }
+
+    /// Computes the console output expected for the progress of a transfer of `filename`
+    /// described by `verb` that goes through the given `percents`.
+    fn exp_progress(verb: &str, filename: &str, percents: &[u64]) -> Vec<CapturedOut> {
+        let mut exp_out = vec![];
+        let mut shown = 0;
+        for percent in percents {
+            if shown > 0 {
+                exp_out.push(CapturedOut::MoveWithinLine(-shown));
+                exp_out.push(CapturedOut::Clear(ClearType::UntilNewLine));
+            }
+            let text = format!("{} {}... {}%", verb, filename, percent);
+            shown = text.len() as i16;
+            exp_out.push(CapturedOut::Write(text));
+            exp_out.push(CapturedOut::SyncNow);
+        }
+        exp_out.push(CapturedOut::MoveWithinLine(-shown));
+        exp_out.push(CapturedOut::Clear(ClearType::UntilNewLine));
+        exp_out.push(CapturedOut::SyncNow);
+        exp_out
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_get_progress() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let console = progress_console(true);
+        let drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            Some(console.clone()),
+            Duration::ZERO,
+            "u",
+        );
+
+        let content = vec![b'x'; MOCK_CHUNK_SIZE * 2 + 5000];
+        service.borrow_mut().add_mock_get_file("u", "big.bas", Ok(content.clone()));
+        assert_eq!(content, drive.get("big.bas").await.unwrap());
+
+        assert_eq!(
+            exp_progress("Downloading", "big.bas", &[43, 86, 100]),
+            console.borrow_mut().take_captured_out()
+        );
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_put_progress() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let console = progress_console(true);
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            Some(console.clone()),
+            Duration::ZERO,
+            "u",
+        );
+
+        let content = vec![b'x'; MOCK_CHUNK_SIZE * 3];
+        service.borrow_mut().add_mock_patch_file_content("u", "big.bas", content.clone(), Ok(()));
+        drive.put("big.bas", &content).await.unwrap();
+
+        assert_eq!(
+            exp_progress("Uploading", "big.bas", &[33, 66, 100]),
+            console.borrow_mut().take_captured_out()
+        );
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_progress_silent_for_small_files() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let console = progress_console(true);
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            Some(console.clone()),
+            Duration::ZERO,
+            "u",
+        );
+
+        let content = vec![b'x'; PROGRESS_THRESHOLD as usize];
+        service.borrow_mut().add_mock_patch_file_content("u", "a.bas", content.clone(), Ok(()));
+        drive.put("a.bas", &content).await.unwrap();
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok(content.clone()));
+        drive.get("a.bas").await.unwrap();
+
+        assert!(console.borrow().captured_out().is_empty());
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_progress_silent_if_not_interactive() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let console = progress_console(false);
+        let mut drive = CloudDrive::new(
+            service.clone(),
+            no_delay_retry_policy(),
+            Some(console.clone()),
+            Duration::ZERO,
+            "u",
+        );
+
+        let content = vec![b'x'; MOCK_CHUNK_SIZE * 3];
+        service.borrow_mut().add_mock_patch_file_content("u", "big.bas", content.clone(), Ok(()));
+        drive.put("big.bas", &content).await.unwrap();
+        service.borrow_mut().add_mock_get_file("u", "big.bas", Ok(content.clone()));
+        drive.get("big.bas").await.unwrap();
+
+        assert!(console.borrow().captured_out().is_empty());
+        service.take().verify_all_used();
+    }
+
+    /// Creates a cloud drive for `username` that caches results for 10 seconds and whose clock
+    /// is controlled by the returned cell.
+    fn caching_drive(
+        service: Rc<RefCell<MockService>>,
+        username: &str,
+    ) -> (CloudDrive, Rc<Cell<OffsetDateTime>>) {
+        let now = Rc::from(Cell::new(OffsetDateTime::from_unix_timestamp(1000).unwrap()));
+        let drive = {
+            let now = now.clone();
+            CloudDrive::new(
+                service,
+                no_delay_retry_policy(),
+                None,
+                Duration::from_secs(10),
+                username,
             )
-            .await
-            .unwrap();
+            .with_now_fn(Box::from(move || now.get()))
+        };
+        (drive, now)
+    }
+
+    /// Records a `get_files` response for `username` that contains a single `filename`.
+    fn add_mock_get_files_one(service: &Rc<RefCell<MockService>>, username: &str, filename: &str) {
+        service.borrow_mut().add_mock_get_files(
+            username,
+            Ok(GetFilesResponse {
+                files: vec![DirectoryEntry { filename: filename.to_owned(), mtime: 0, length: 1 }],
+                disk_quota: None,
+                disk_free: None,
+            }),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_enumerate_ttl() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (drive, now) = caching_drive(service.clone(), "u");
+
+        add_mock_get_files_one(&service, "u", "first.bas");
+        for _ in 0..3 {
+            let files = drive.enumerate().await.unwrap();
+            assert!(files.dirents().contains_key("first.bas"));
+        }
+        service.borrow_mut().verify_all_used();
+
+        now.set(now.get() + Duration::from_secs(9));
+        drive.enumerate().await.unwrap();
+
+        now.set(now.get() + Duration::from_secs(1));
+        add_mock_get_files_one(&service, "u", "second.bas");
+        let files = drive.enumerate().await.unwrap();
+        assert!(files.dirents().contains_key("second.bas"));
+        drive.enumerate().await.unwrap();
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_get_ttl() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (drive, now) = caching_drive(service.clone(), "u");
+
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("old"));
+        service.borrow_mut().add_mock_get_file("u", "b.bas", Ok("other"));
+        assert_eq!(b"old", drive.get("a.bas").await.unwrap().as_slice());
+        assert_eq!(b"other", drive.get("b.bas").await.unwrap().as_slice());
+        assert_eq!(b"old", drive.get("a.bas").await.unwrap().as_slice());
+        assert_eq!(b"other", drive.get("b.bas").await.unwrap().as_slice());
+        service.borrow_mut().verify_all_used();
+
+        now.set(now.get() + Duration::from_secs(10));
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("new"));
+        assert_eq!(b"new", drive.get("a.bas").await.unwrap().as_slice());
+        assert_eq!(b"new", drive.get("a.bas").await.unwrap().as_slice());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_errors_not_cached() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (drive, _now) = caching_drive(service.clone(), "u");
+
+        service.borrow_mut().add_mock_get_file::<&[u8]>(
+            "u",
+            "a.bas",
+            Err(io::Error::from(io::ErrorKind::NotFound)),
+        );
+        assert_eq!(io::ErrorKind::NotFound, drive.get("a.bas").await.unwrap_err().kind());
+
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("content"));
+        assert_eq!(b"content", drive.get("a.bas").await.unwrap().as_slice());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_invalidated_by_put() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (mut drive, _now) = caching_drive(service.clone(), "u");
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("old"));
+        service.borrow_mut().add_mock_get_file("u", "b.bas", Ok("other"));
+        drive.enumerate().await.unwrap();
+        drive.get("a.bas").await.unwrap();
+        drive.get("b.bas").await.unwrap();
+
+        service.borrow_mut().add_mock_patch_file_content("u", "a.bas", "new", Ok(()));
+        drive.put("a.bas", b"new").await.unwrap();
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("new"));
+        drive.enumerate().await.unwrap();
+        assert_eq!(b"new", drive.get("a.bas").await.unwrap().as_slice());
+        assert_eq!(b"other", drive.get("b.bas").await.unwrap().as_slice());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_invalidated_by_delete() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (mut drive, _now) = caching_drive(service.clone(), "u");
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("content"));
+        drive.enumerate().await.unwrap();
+        drive.get("a.bas").await.unwrap();
+
+        service.borrow_mut().add_mock_delete_file("u", "a.bas", Ok(()));
+        drive.delete("a.bas").await.unwrap();
+
+        service.borrow_mut().add_mock_get_files(
+            "u",
+            Ok(GetFilesResponse { files: vec![], disk_quota: None, disk_free: None }),
+        );
+        service.borrow_mut().add_mock_get_file::<&[u8]>(
+            "u",
+            "a.bas",
+            Err(io::Error::from(io::ErrorKind::NotFound)),
+        );
+        assert!(drive.enumerate().await.unwrap().dirents().is_empty());
+        assert_eq!(io::ErrorKind::NotFound, drive.get("a.bas").await.unwrap_err().kind());
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_invalidated_by_update_acls() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (mut drive, _now) = caching_drive(service.clone(), "u");
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        drive.enumerate().await.unwrap();
+
+        let add = FileAcls::default().with_readers(["r".to_owned()]);
+        service.borrow_mut().add_mock_patch_file_acls(
+            "u",
+            "a.bas",
+            add.clone(),
+            FileAcls::default(),
+            Ok(()),
+        );
+        drive.update_acls("a.bas", &add, &FileAcls::default()).await.unwrap();
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        drive.enumerate().await.unwrap();
+        drive.enumerate().await.unwrap();
+
+        service.take().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_clouddrive_cache_discard() {
+        let service = Rc::from(RefCell::from(MockService::default()));
+        service.borrow_mut().do_login().await;
+        let (mut drive, _now) = caching_drive(service.clone(), "u");
+
+        add_mock_get_files_one(&service, "u", "a.bas");
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("old"));
+        drive.enumerate().await.unwrap();
+        drive.get("a.bas").await.unwrap();
+
+        drive.discard_cache();
+
+        add_mock_get_files_one(&service, "u", "b.bas");
+        service.borrow_mut().add_mock_get_file("u", "a.bas", Ok("new"));
+        assert!(drive.enumerate().await.unwrap().dirents().contains_key("b.bas"));
+        assert_eq!(b"new", drive.get("a.bas").await.unwrap().as_slice());
 
         service.take().verify_all_used();
     }
@@ -315,7 +1080,7 @@ mod tests {
     #[test]
     fn test_clouddrive_system_path() {
         let service = Rc::from(RefCell::from(MockService::default()));
-        let drive = CloudDrive::new(service, "");
+        let drive = CloudDrive::new(service, no_delay_retry_policy(), None, Duration::ZERO, "");
         assert!(drive.system_path("foo").is_none());
     }
 
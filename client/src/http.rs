@@ -0,0 +1,541 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Read-only drive backed by files served by an arbitrary web server.
+
+use crate::cloud::reqwest_error_to_io_error;
+use async_trait::async_trait;
+use endbasic_std::console::remove_control_chars;
+use endbasic_std::storage::{Drive, DriveFactory, DriveFiles, Metadata, Storage};
+use reqwest::StatusCode;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::rc::Rc;
+use url::Url;
+
+/// Maximum number of bytes that a single response may carry.
+const MAX_RESPONSE_LENGTH: u64 = 4 * 1024 * 1024;
+
+/// Name of the optional manifest at the base URL that lists the files served by the drive.
+const INDEX_NAME: &str = "index.json";
+
+/// Constructs the error returned by all operations that would modify the drive.
+fn read_only_error() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "HTTP drives are read-only")
+}
+
+/// Response to an HTTP request.
+#[async_trait(?Send)]
+pub(crate) trait HttpResponse {
+    /// Returns the status code of the response.
+    fn status(&self) -> StatusCode;
+
+    /// Returns the length of the body as advertised by the server, if known.
+    fn content_length(&self) -> Option<u64>;
+
+    /// Consumes the response and returns its body.
+    async fn body(self: Box<Self>) -> io::Result<Vec<u8>>;
+}
+
+#[async_trait(?Send)]
+impl HttpResponse for reqwest::Response {
+    fn status(&self) -> StatusCode {
+        reqwest::Response::status(self)
+    }
+
+    fn content_length(&self) -> Option<u64> {
+        reqwest::Response::content_length(self)
+    }
+
+    async fn body(self: Box<Self>) -> io::Result<Vec<u8>> {
+        Ok(self.bytes().await.map_err(reqwest_error_to_io_error)?.to_vec())
+    }
+}
+
+/// Abstract interface to issue HTTP requests.
+#[async_trait(?Send)]
+pub(crate) trait HttpTransport {
+    /// Issues a GET request for `url`.
+    async fn get(&self, url: &Url) -> io::Result<Box<dyn HttpResponse>>;
+}
+
+/// HTTP transport backed by the same client library used to talk to the cloud service.
+#[derive(Default)]
+pub(crate) struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[async_trait(?Send)]
+impl HttpTransport for ReqwestTransport {
+    async fn get(&self, url: &Url) -> io::Result<Box<dyn HttpResponse>> {
+        let response =
+            self.client.get(url.clone()).send().await.map_err(reqwest_error_to_io_error)?;
+        Ok(Box::from(response))
+    }
+}
+
+/// Representation of a single file in the `index.json` manifest.
+#[derive(Deserialize)]
+struct IndexEntry {
+    name: String,
+    #[serde(default)]
+    length: u64,
+    #[serde(default)]
+    mtime: u64,
+}
+
+/// Representation of the `index.json` manifest.
+#[derive(Deserialize)]
+struct Index {
+    files: Vec<IndexEntry>,
+}
+
+/// A read-only drive that fetches files from a web server.
+///
+/// Files are fetched from `<base>/<name>`.  Web servers offer no standard way of listing their
+/// contents, so the drive enumerates the files listed in an optional `index.json` manifest at the
+/// base URL, which looks like `{"files": [{"name": "GAME.BAS", "length": 123, "mtime": 0}]}` with
+/// optional `length` and `mtime` fields.  The listing is empty if the manifest does not exist.
+struct HttpDrive {
+    transport: Rc<dyn HttpTransport>,
+    base: Url,
+    max_length: u64,
+}
+
+impl HttpDrive {
+    /// Creates a new drive that fetches files under `base` via `transport`, rejecting any response
+    /// longer than `max_length` bytes.
+    ///
+    /// The path of `base` must end with a slash.
+    fn new(transport: Rc<dyn HttpTransport>, base: Url, max_length: u64) -> Self {
+        debug_assert!(base.path().ends_with('/'));
+        Self { transport, base, max_length }
+    }
+
+    /// Computes the URL of the file `name`.
+    fn make_url(&self, name: &str) -> Url {
+        let mut url = self.base.clone();
+        url.path_segments_mut()
+            .expect("Base URL was validated at creation time")
+            .pop_if_empty()
+            .extend(name.split('/'));
+        url
+    }
+
+    /// Fetches the file `name` and returns its contents.
+    async fn fetch(&self, name: &str) -> io::Result<Vec<u8>> {
+        let url = self.make_url(name);
+        let response = self.transport.get(&url).await?;
+
+        let status = response.status();
+        if status != StatusCode::OK {
+            let kind = match status {
+                StatusCode::NOT_FOUND => io::ErrorKind::NotFound,
+                StatusCode::FORBIDDEN | StatusCode::UNAUTHORIZED => io::ErrorKind::PermissionDenied,
+                _ => io::ErrorKind::Other,
+            };
+            return Err(io::Error::new(
+                kind,
+                format!("HTTP request for {} returned status {}", url, status),
+            ));
+        }
+
+        let too_big = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Response for {} exceeds the maximum of {} bytes", url, self.max_length),
+            )
+        };
+        if response.content_length().unwrap_or(0) > self.max_length {
+            return Err(too_big());
+        }
+        let body = response.body().await?;
+        if body.len() as u64 > self.max_length {
+            return Err(too_big());
+        }
+        Ok(body)
+    }
+}
+
+#[async_trait(?Send)]
+impl Drive for HttpDrive {
+    async fn delete(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn enumerate(&self) -> io::Result<DriveFiles> {
+        let content = match self.fetch(INDEX_NAME).await {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(DriveFiles::new(BTreeMap::default(), None, None))
+            }
+            Err(e) => return Err(e),
+        };
+
+        let index: Index = serde_json::from_slice(&content).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid {}: {}", INDEX_NAME, remove_control_chars(e.to_string())),
+            )
+        })?;
+        let mut entries = BTreeMap::default();
+        for e in index.files {
+            let date = match time::OffsetDateTime::from_unix_timestamp(e.mtime as i64) {
+                Ok(date) => date,
+                Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("{}", e))),
+            };
+            entries.insert(e.name, Metadata { date, length: e.length });
+        }
+        Ok(DriveFiles::new(entries, None, None))
+    }
+
+    async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        self.fetch(name).await
+    }
+
+    async fn mkdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rename(&mut self, _old: &str, _new: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rmdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+}
+
+/// Factory for HTTP drives.
+///
+/// The same factory type handles both the `http` and `https` schemes, so each instance remembers
+/// the scheme it was registered for to reconstruct the URL from the mount target.
+pub(crate) struct HttpDriveFactory {
+    scheme: &'static str,
+    transport: Rc<dyn HttpTransport>,
+}
+
+impl HttpDriveFactory {
+    /// Creates a new factory for `scheme` that uses `transport` to issue requests.
+    pub(crate) fn new(scheme: &'static str, transport: Rc<dyn HttpTransport>) -> Self {
+        Self { scheme, transport }
+    }
+}
+
+#[async_trait(?Send)]
+impl DriveFactory for HttpDriveFactory {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
+        if target.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Must specify a server to mount an HTTP drive",
+            ));
+        }
+
+        let mut base = match Url::parse(&format!("{}://{}", self.scheme, target)) {
+            Ok(url) if !url.cannot_be_a_base() => url,
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid HTTP drive URL '{}'", target),
+                ))
+            }
+            Err(e) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid HTTP drive URL '{}': {}", target, e),
+                ))
+            }
+        };
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+
+        Ok(Box::from(HttpDrive::new(self.transport.clone(), base, MAX_RESPONSE_LENGTH)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+
+    /// Canned response returned by `MockTransport`.
+    struct MockResponse {
+        status: StatusCode,
+        content_length: Option<u64>,
+        body: Vec<u8>,
+    }
+
+    #[async_trait(?Send)]
+    impl HttpResponse for MockResponse {
+        fn status(&self) -> StatusCode {
+            self.status
+        }
+
+        fn content_length(&self) -> Option<u64> {
+            self.content_length
+        }
+
+        async fn body(self: Box<Self>) -> io::Result<Vec<u8>> {
+            Ok(self.body)
+        }
+    }
+
+    /// HTTP transport that allows specifying expectations on requests and yields the responses
+    /// previously recorded into it.
+    #[derive(Default)]
+    struct MockTransport {
+        mock_get: RefCell<VecDeque<(String, MockResponse)>>,
+    }
+
+    impl MockTransport {
+        /// Records an upcoming request for `url` that returns `status` with `body`.
+        fn add_mock_get<B: Into<Vec<u8>>>(&self, url: &str, status: StatusCode, body: B) {
+            let body = body.into();
+            let response = MockResponse { status, content_length: Some(body.len() as u64), body };
+            self.mock_get.borrow_mut().push_back((url.to_owned(), response));
+        }
+
+        /// Records an upcoming request for `url` that returns `response`.
+        fn add_mock_get_raw(&self, url: &str, response: MockResponse) {
+            self.mock_get.borrow_mut().push_back((url.to_owned(), response));
+        }
+
+        /// Ensures that all requests and responses have been consumed.
+        fn verify_all_used(&self) {
+            assert!(self.mock_get.borrow().is_empty(), "Mock requests not fully consumed");
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl HttpTransport for MockTransport {
+        async fn get(&self, url: &Url) -> io::Result<Box<dyn HttpResponse>> {
+            let (exp_url, response) =
+                self.mock_get.borrow_mut().pop_front().expect("No mock requests available");
+            assert_eq!(exp_url, url.as_str());
+            Ok(Box::from(response))
+        }
+    }
+
+    /// Mounts an HTTP drive for `uri` using `transport`.
+    async fn mount(transport: Rc<MockTransport>, uri: &str) -> io::Result<Box<dyn Drive>> {
+        let (scheme, target) = uri.split_once("://").unwrap();
+        let scheme = if scheme == "http" { "http" } else { "https" };
+        let factory = HttpDriveFactory::new(scheme, transport);
+        factory.create(target, &Storage::default()).await
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_get_ok() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "https://example.com/basic").await.unwrap();
+
+        transport.add_mock_get(
+            "https://example.com/basic/GAME.BAS",
+            StatusCode::OK,
+            "PRINT \"Hello\"",
+        );
+        assert_eq!(b"PRINT \"Hello\"", drive.get("GAME.BAS").await.unwrap().as_slice());
+
+        transport.add_mock_get("https://example.com/basic/a%20b%23c.bas", StatusCode::OK, "");
+        assert!(drive.get("a b#c.bas").await.unwrap().is_empty());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_get_binary() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "http://example.com/").await.unwrap();
+
+        transport.add_mock_get("http://example.com/data.bin", StatusCode::OK, [0xff, 0x00, 0xfe]);
+        assert_eq!(&[0xff, 0x00, 0xfe], drive.get("data.bin").await.unwrap().as_slice());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_get_errors() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "https://example.com/").await.unwrap();
+
+        transport.add_mock_get("https://example.com/missing.bas", StatusCode::NOT_FOUND, "");
+        let err = drive.get("missing.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        assert_eq!(
+            "HTTP request for https://example.com/missing.bas returned status 404 Not Found",
+            format!("{}", err)
+        );
+
+        transport.add_mock_get("https://example.com/secret.bas", StatusCode::FORBIDDEN, "");
+        let err = drive.get("secret.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+
+        transport.add_mock_get(
+            "https://example.com/bad.bas",
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "",
+        );
+        let err = drive.get("bad.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::Other, err.kind());
+        assert_eq!(
+            "HTTP request for https://example.com/bad.bas returned status 500 Internal Server Error",
+            format!("{}", err)
+        );
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_get_too_big() {
+        let transport = Rc::from(MockTransport::default());
+        let drive =
+            HttpDrive::new(transport.clone(), Url::parse("https://example.com/").unwrap(), 5);
+
+        transport.add_mock_get("https://example.com/ok.bas", StatusCode::OK, "12345");
+        assert_eq!(b"12345", drive.get("ok.bas").await.unwrap().as_slice());
+
+        transport.add_mock_get("https://example.com/big.bas", StatusCode::OK, "123456");
+        let err = drive.get("big.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "Response for https://example.com/big.bas exceeds the maximum of 5 bytes",
+            format!("{}", err)
+        );
+
+        // Servers may not advertise the length of the response, or may lie about it.
+        transport.add_mock_get_raw(
+            "https://example.com/big.bas",
+            MockResponse { status: StatusCode::OK, content_length: None, body: b"123456".to_vec() },
+        );
+        let err = drive.get("big.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        transport.add_mock_get_raw(
+            "https://example.com/big.bas",
+            MockResponse { status: StatusCode::OK, content_length: Some(1000), body: vec![] },
+        );
+        let err = drive.get("big.bas").await.unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_enumerate_with_index() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "https://example.com/basic/").await.unwrap();
+
+        transport.add_mock_get(
+            "https://example.com/basic/index.json",
+            StatusCode::OK,
+            r#"{"files": [{"name": "GAME.BAS", "length": 15, "mtime": 9000}, {"name": "x"}]}"#,
+        );
+        let result = drive.enumerate().await.unwrap();
+        assert_eq!(2, result.dirents().len());
+        assert_eq!(
+            &Metadata {
+                date: time::OffsetDateTime::from_unix_timestamp(9000).unwrap(),
+                length: 15
+            },
+            result.dirents().get("GAME.BAS").unwrap()
+        );
+        assert_eq!(
+            &Metadata { date: time::OffsetDateTime::from_unix_timestamp(0).unwrap(), length: 0 },
+            result.dirents().get("x").unwrap()
+        );
+        assert!(result.disk_quota().is_none());
+        assert!(result.disk_free().is_none());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_enumerate_without_index() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "https://example.com/basic/").await.unwrap();
+
+        transport.add_mock_get(
+            "https://example.com/basic/index.json",
+            StatusCode::NOT_FOUND,
+            "Not found",
+        );
+        let result = drive.enumerate().await.unwrap();
+        assert!(result.dirents().is_empty());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_enumerate_errors() {
+        let transport = Rc::from(MockTransport::default());
+        let drive = mount(transport.clone(), "https://example.com/").await.unwrap();
+
+        transport.add_mock_get("https://example.com/index.json", StatusCode::OK, "not json");
+        let err = drive.enumerate().await.unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(format!("{}", err).starts_with("Invalid index.json: "));
+
+        transport.add_mock_get(
+            "https://example.com/index.json",
+            StatusCode::SERVICE_UNAVAILABLE,
+            "",
+        );
+        let err = drive.enumerate().await.unwrap_err();
+        assert_eq!(io::ErrorKind::Other, err.kind());
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_writes() {
+        let transport = Rc::from(MockTransport::default());
+        let mut drive = mount(transport.clone(), "https://example.com/").await.unwrap();
+
+        let check = |r: io::Result<()>| {
+            let err = r.unwrap_err();
+            assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+            assert_eq!("HTTP drives are read-only", format!("{}", err));
+        };
+        check(drive.delete("a.bas").await);
+        check(drive.mkdir("dir").await);
+        check(drive.put("a.bas", b"").await);
+        check(drive.rename("a.bas", "b.bas").await);
+        check(drive.rmdir("dir").await);
+
+        transport.verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_httpdrive_factory_errors() {
+        let transport = Rc::from(MockTransport::default());
+
+        let err = mount(transport.clone(), "https://").await.map(|_| ()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("Must specify a server to mount an HTTP drive", format!("{}", err));
+
+        let err = mount(transport.clone(), "https://:80/").await.map(|_| ()).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert!(format!("{}", err).starts_with("Invalid HTTP drive URL ':80/': "));
+
+        transport.verify_all_used();
+    }
+}
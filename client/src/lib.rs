@@ -31,10 +31,24 @@ mod cloud;
 pub use cloud::CloudService;
 mod cmds;
 pub use cmds::add_all;
+mod doctor;
+pub use doctor::ServiceCheck;
 mod drive;
-pub(crate) use drive::CloudDriveFactory;
+pub(crate) use drive::{CloudDriveFactory, DEFAULT_CACHE_TTL};
+mod http;
+pub(crate) use http::{HttpDriveFactory, ReqwestTransport};
+mod net;
+pub use net::ReqwestHttpClient;
+mod retry;
+pub use retry::{RetryPolicy, RetrySleepFn};
+#[cfg(not(target_arch = "wasm32"))]
+mod sockets;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sockets::TcpConnector;
 #[cfg(test)]
 pub(crate) mod testutils;
+mod token;
+pub use token::{restore_session, FileTokenStore, TokenStore};
 
 /// Base address of the production REST API.
 pub const PROD_API_ADDRESS: &str = "https://service.endbasic.dev/";
@@ -61,8 +75,7 @@ impl From<SerdeDiskSpace> for DiskSpace {
 
 /// An opaque access token obtained during authentication and used for all subsequent requests
 /// against the server.
-#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
-#[cfg_attr(test, derive(Serialize))]
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct AccessToken(String);
 
 impl AccessToken {
@@ -93,6 +106,42 @@ pub struct LoginResponse {
     motd: Vec<String>,
 }
 
+/// Representation of the challenge issued by the server when a login needs a second factor.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug, Serialize))]
+pub struct SecondFactorChallenge {
+    pub(crate) token_challenge: String,
+}
+
+/// Representation of the possible outcomes of a login request.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug, Serialize))]
+#[serde(untagged)]
+pub enum LoginResult {
+    /// The credentials were accepted and the session is established.
+    Authenticated(LoginResponse),
+
+    /// The credentials were accepted but the account requires a second factor to complete the
+    /// login via `Service::login_2fa`.
+    SecondFactorRequired(SecondFactorChallenge),
+}
+
+/// Representation of a request to complete a login with a second factor.
+#[derive(Debug, Default, Eq, PartialEq, Serialize)]
+#[cfg_attr(test, derive(Deserialize))]
+pub struct SecondFactorRequest {
+    username: String,
+    token_challenge: String,
+    code: String,
+}
+
+/// Representation of a message of the day response.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug, Serialize))]
+pub struct MotdResponse {
+    motd: Vec<String>,
+}
+
 /// Representation of a single directory entry as returned by the server.
 #[derive(Deserialize)]
 #[cfg_attr(test, derive(Debug, Serialize))]
@@ -111,6 +160,22 @@ pub struct GetFilesResponse {
     disk_free: Option<SerdeDiskSpace>,
 }
 
+/// Representation of one page of the list of users that have public files.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug, Serialize))]
+pub struct PublicUsersResponse {
+    users: Vec<String>,
+    next_page_token: Option<String>,
+}
+
+/// Representation of one page of the list of public files owned by a user.
+#[derive(Deserialize)]
+#[cfg_attr(test, derive(Debug, Serialize))]
+pub struct PublicFilesResponse {
+    files: Vec<DirectoryEntry>,
+    next_page_token: Option<String>,
+}
+
 /// Representation of a signup request.
 #[derive(Debug, Default, Eq, PartialEq, Serialize)]
 #[cfg_attr(test, derive(Deserialize))]
@@ -121,6 +186,10 @@ pub struct SignupRequest {
     promotional_email: bool,
 }
 
+/// Callback to report the progress of a file transfer, which receives the number of bytes
+/// transferred so far and the total number of bytes to transfer.
+pub type ProgressFn<'a> = &'a mut dyn FnMut(u64, u64);
+
 /// Abstract interface to interact with an EndBASIC service server.
 #[async_trait(?Send)]
 pub trait Service {
@@ -130,12 +199,46 @@ pub trait Service {
     /// Sends an authentication request to the service with `username` and `password` to obtain an
     /// access token for the session.
     ///
+    /// If logging is successful, the access token is cached for future retrieval.  If the account
+    /// requires a second factor, the returned challenge must be passed to `login_2fa`.
+    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResult>;
+
+    /// Completes a login for `username` that required a second factor by sending the
+    /// `token_challenge` returned by `login` along with the one-time `code` provided by the user.
+    ///
     /// If logging is successful, the access token is cached for future retrieval.
-    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResponse>;
+    async fn login_2fa(
+        &mut self,
+        username: &str,
+        token_challenge: &str,
+        code: &str,
+    ) -> io::Result<LoginResponse>;
+
+    /// Resumes a session for `username` with an `access_token` obtained by a previous login,
+    /// checking that the service still accepts it.
+    ///
+    /// If the token is valid, it is cached for future retrieval as if `login` had been called.
+    async fn login_with_token(
+        &mut self,
+        username: &str,
+        access_token: AccessToken,
+    ) -> io::Result<()>;
 
     /// Logs out from the service and clears the access token from this object.
     async fn logout(&mut self) -> io::Result<()>;
 
+    /// Permanently deletes the account of the logged in user, along with all of their files,
+    /// after confirming their identity with `password`.
+    ///
+    /// On success, the access token is cleared from this object as if `logout` had been called.
+    async fn delete_account(&mut self, password: &str) -> io::Result<()>;
+
+    /// Sends a request to the server to obtain the current message of the day.
+    ///
+    /// This uses the active session if there is one.  Otherwise, the request is sent anonymously,
+    /// which the server may reject with a permission denied error.
+    async fn get_motd(&mut self) -> io::Result<MotdResponse>;
+
     /// Checks if there is an active session against the service.
     fn is_logged_in(&self) -> bool;
 
@@ -146,9 +249,36 @@ pub trait Service {
     /// previously-acquired `access_token`.
     async fn get_files(&mut self, username: &str) -> io::Result<GetFilesResponse>;
 
+    /// Sends a request to the server to obtain one page of the list of users that have public files.
+    ///
+    /// `page_token` is the continuation token returned by a previous call, or none to fetch the
+    /// first page.  This does not require an active session.
+    async fn list_public_users(
+        &mut self,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicUsersResponse>;
+
+    /// Sends a request to the server to obtain one page of the list of files owned by `username`
+    /// that everyone can read.
+    ///
+    /// `page_token` is the continuation token returned by a previous call, or none to fetch the
+    /// first page.  This does not require an active session.
+    async fn list_public_files(
+        &mut self,
+        username: &str,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicFilesResponse>;
+
     /// Sends a request to the server to obtain the contents of `filename` owned by `username` with a
     /// previously-acquired `access_token`.
-    async fn get_file(&mut self, username: &str, filename: &str) -> io::Result<Vec<u8>>;
+    ///
+    /// If provided, `progress` is invoked as the contents are received.
+    async fn get_file(
+        &mut self,
+        username: &str,
+        filename: &str,
+        progress: Option<ProgressFn<'_>>,
+    ) -> io::Result<Vec<u8>>;
 
     /// Sends a request to the server to obtain the ACLs of `filename` owned by `username` with a
     /// previously-acquired `access_token`.
@@ -156,11 +286,14 @@ pub trait Service {
 
     /// Sends a request to the server to update the contents of `filename` owned by `username` as
     /// specified in `content` with a previously-acquired `access_token`.
+    ///
+    /// If provided, `progress` is invoked as the contents are sent.
     async fn patch_file_content(
         &mut self,
         username: &str,
         filename: &str,
         content: Vec<u8>,
+        progress: Option<ProgressFn<'_>>,
     ) -> io::Result<()>;
 
     /// Sends a request to the server to update the ACLs of `filename` owned by `username` as
@@ -176,4 +309,8 @@ pub trait Service {
     /// Sends a request to the server to delete `filename` owned by `username` with a
     /// previously-acquired `access_token`.
     async fn delete_file(&mut self, username: &str, filename: &str) -> io::Result<()>;
+
+    /// Sends a request to the server to check that it is reachable and healthy.  This does not
+    /// require an active session.
+    async fn ping(&mut self) -> io::Result<()>;
 }
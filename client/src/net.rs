@@ -0,0 +1,91 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! HTTP client for the networking functions of programs.
+
+use crate::cloud::reqwest_error_to_io_error;
+use async_trait::async_trait;
+use endbasic_std::net::{HttpClient, HttpRequest, HttpResponse};
+use reqwest::header::CONTENT_TYPE;
+use reqwest::Response;
+use std::io;
+
+/// Maximum time that a request may take before it is abandoned.
+#[cfg(not(target_arch = "wasm32"))]
+const REQUEST_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// HTTP client backed by the same client library used to talk to the cloud service.
+pub struct ReqwestHttpClient {
+    client: reqwest::Client,
+}
+
+impl Default for ReqwestHttpClient {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn default() -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Client configuration is static and must be valid");
+        Self { client }
+    }
+
+    /// Requests are subject to the timeouts of the browser on this target.
+    #[cfg(target_arch = "wasm32")]
+    fn default() -> Self {
+        Self { client: reqwest::Client::default() }
+    }
+}
+
+/// Reads the body of `response`, stopping as soon as it grows beyond `max_length` bytes.
+#[cfg(not(target_arch = "wasm32"))]
+async fn read_body(mut response: Response, max_length: usize) -> io::Result<Vec<u8>> {
+    let mut content = vec![];
+    while let Some(chunk) = response.chunk().await.map_err(reqwest_error_to_io_error)? {
+        content.extend_from_slice(&chunk);
+        if content.len() > max_length {
+            break;
+        }
+    }
+    Ok(content)
+}
+
+/// Reads the body of `response`.
+///
+/// The response cannot be read in chunks on this target so it is always read in full.
+#[cfg(target_arch = "wasm32")]
+async fn read_body(response: Response, _max_length: usize) -> io::Result<Vec<u8>> {
+    Ok(response.bytes().await.map_err(reqwest_error_to_io_error)?.to_vec())
+}
+
+#[async_trait(?Send)]
+impl HttpClient for ReqwestHttpClient {
+    async fn request(
+        &self,
+        request: HttpRequest<'_>,
+        max_length: usize,
+    ) -> io::Result<HttpResponse> {
+        let builder = match request {
+            HttpRequest::Get { url } => self.client.get(url),
+            HttpRequest::Post { url, body, content_type } => {
+                self.client.post(url).header(CONTENT_TYPE, content_type).body(body.to_owned())
+            }
+        };
+        let response = builder.send().await.map_err(reqwest_error_to_io_error)?;
+
+        let status = response.status().as_u16();
+        let body = read_body(response, max_length).await?;
+        Ok(HttpResponse { status, body })
+    }
+}
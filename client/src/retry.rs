@@ -0,0 +1,206 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Retries of service requests that fail due to transient errors.
+
+use rand::Rng;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::thread;
+use std::time::Duration;
+
+/// Type of the function used to wait between retries.
+pub type RetrySleepFn = Box<dyn Fn(Duration) -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// An implementation of a `RetrySleepFn` that stops the current thread.
+fn system_sleep(d: Duration) -> Pin<Box<dyn Future<Output = ()>>> {
+    Box::pin(async move { thread::sleep(d) })
+}
+
+/// Returns true if `e` represents an error that may go away by issuing the same request again,
+/// such as a timeout or an unavailable server.
+///
+/// Errors that indicate a problem with the request itself, such as authentication failures or
+/// missing files, are never transient.
+pub(crate) fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::AddrNotAvailable
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::TimedOut
+    )
+}
+
+/// Policy that describes how to retry idempotent requests that fail due to transient errors.
+///
+/// Retries are delayed using exponential backoff with jitter: the delay before retry `n` is a
+/// random value between half and the full value of `initial_delay * 2^n`, capped at `max_delay`.
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    sleep_fn: RetrySleepFn,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(4, Duration::from_millis(250), Duration::from_secs(4))
+    }
+}
+
+impl RetryPolicy {
+    /// Creates a new policy that issues a request at most `max_attempts` times, which must be at
+    /// least 1, and waits between `initial_delay` and `max_delay` between attempts.
+    pub fn new(max_attempts: u32, initial_delay: Duration, max_delay: Duration) -> Self {
+        assert!(max_attempts > 0, "Must issue requests at least once");
+        Self { max_attempts, initial_delay, max_delay, sleep_fn: Box::from(system_sleep) }
+    }
+
+    /// Overrides the function used to wait between retries, which by default blocks the current
+    /// thread.  Must be set for targets that cannot block, like the web.
+    pub fn with_sleep_fn(mut self, sleep_fn: RetrySleepFn) -> Self {
+        self.sleep_fn = sleep_fn;
+        self
+    }
+
+    /// Computes the delay to wait for before the retry that follows the failed `attempt`, where
+    /// the first attempt is 0.
+    fn delay_for(&self, attempt: u32) -> Duration {
+        let factor = 1u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        let delay = self.initial_delay.saturating_mul(factor).min(self.max_delay);
+        let half = delay / 2;
+        if half.is_zero() {
+            return delay;
+        }
+        half + rand::thread_rng().gen_range(Duration::ZERO..=half)
+    }
+
+    /// Runs `op` until it succeeds, until it fails with a non-transient error, or until it has
+    /// been attempted as many times as the policy allows, and returns its last result.
+    ///
+    /// `op` must build a new request on each invocation so that the request can be replayed.
+    pub(crate) async fn run<T, F, Fut>(&self, mut op: F) -> io::Result<T>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = io::Result<T>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match op().await {
+                Err(e) if is_transient(&e) && attempt + 1 < self.max_attempts => {
+                    (self.sleep_fn)(self.delay_for(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Creates a policy that records the delays it waits for into the returned vector instead of
+    /// actually sleeping.
+    fn recording_policy(max_attempts: u32) -> (RetryPolicy, Rc<RefCell<Vec<Duration>>>) {
+        let delays = Rc::from(RefCell::from(vec![]));
+        let policy =
+            RetryPolicy::new(max_attempts, Duration::from_millis(100), Duration::from_millis(300))
+                .with_sleep_fn({
+                    let delays = delays.clone();
+                    Box::from(move |d| {
+                        delays.borrow_mut().push(d);
+                        Box::pin(async {}) as Pin<Box<dyn Future<Output = ()>>>
+                    })
+                });
+        (policy, delays)
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(is_transient(&io::Error::new(io::ErrorKind::TimedOut, "")));
+        assert!(is_transient(&io::Error::new(io::ErrorKind::AddrNotAvailable, "")));
+        assert!(!is_transient(&io::Error::new(io::ErrorKind::PermissionDenied, "")));
+        assert!(!is_transient(&io::Error::new(io::ErrorKind::NotFound, "")));
+        assert!(!is_transient(&io::Error::new(io::ErrorKind::InvalidInput, "")));
+        assert!(!is_transient(&io::Error::new(io::ErrorKind::Other, "")));
+    }
+
+    #[test]
+    fn test_delay_for_grows_and_is_capped() {
+        let (policy, _delays) = recording_policy(10);
+        for (attempt, max_ms) in [(0, 100), (1, 200), (2, 300), (3, 300), (40, 300)] {
+            let delay = policy.delay_for(attempt);
+            assert!(delay >= Duration::from_millis(max_ms / 2), "Too short: {:?}", delay);
+            assert!(delay <= Duration::from_millis(max_ms), "Too long: {:?}", delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_eventual_success() {
+        let (policy, delays) = recording_policy(3);
+        let mut calls = 0;
+        let result = policy
+            .run(|| {
+                calls += 1;
+                let result = if calls < 3 {
+                    Err(io::Error::new(io::ErrorKind::TimedOut, "Slow"))
+                } else {
+                    Ok(calls)
+                };
+                async move { result }
+            })
+            .await;
+        assert_eq!(3, result.unwrap());
+        assert_eq!(2, delays.borrow().len());
+    }
+
+    #[tokio::test]
+    async fn test_run_exhausted() {
+        let (policy, delays) = recording_policy(3);
+        let mut calls = 0;
+        let result: io::Result<()> = policy
+            .run(|| {
+                calls += 1;
+                async move { Err(io::Error::new(io::ErrorKind::AddrNotAvailable, "Down")) }
+            })
+            .await;
+        assert_eq!(io::ErrorKind::AddrNotAvailable, result.unwrap_err().kind());
+        assert_eq!(3, calls);
+        assert_eq!(2, delays.borrow().len());
+    }
+
+    #[tokio::test]
+    async fn test_run_no_retry_on_permanent_error() {
+        let (policy, delays) = recording_policy(3);
+        let mut calls = 0;
+        let result: io::Result<()> = policy
+            .run(|| {
+                calls += 1;
+                async move { Err(io::Error::new(io::ErrorKind::PermissionDenied, "No")) }
+            })
+            .await;
+        assert_eq!(io::ErrorKind::PermissionDenied, result.unwrap_err().kind());
+        assert_eq!(1, calls);
+        assert!(delays.borrow().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! TCP sockets for the socket functions of programs.
+
+use async_trait::async_trait;
+use endbasic_std::sockets::{Connector, Socket};
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Maximum time that establishing a connection may take before it is abandoned.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Socket backed by a Tokio TCP stream.
+struct TcpSocket {
+    stream: TcpStream,
+}
+
+#[async_trait(?Send)]
+impl Socket for TcpSocket {
+    async fn send(&mut self, data: &[u8]) -> io::Result<()> {
+        self.stream.write_all(data).await
+    }
+
+    async fn recv(&mut self, max_length: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; max_length];
+        let n = self.stream.read(&mut buffer).await?;
+        buffer.truncate(n);
+        Ok(buffer)
+    }
+}
+
+/// Connector that opens real TCP connections.
+///
+/// This requires the program to run within a Tokio runtime with I/O and time support.
+#[derive(Default)]
+pub struct TcpConnector {}
+
+#[async_trait(?Send)]
+impl Connector for TcpConnector {
+    async fn connect(&self, host: &str, port: u16) -> io::Result<Box<dyn Socket>> {
+        let stream =
+            match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port))).await {
+                Ok(result) => result?,
+                Err(_) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        format!("Connection timed out after {:?}", CONNECT_TIMEOUT),
+                    ))
+                }
+            };
+        stream.set_nodelay(true)?;
+        Ok(Box::from(TcpSocket { stream }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    /// Starts a listener on a random local port and returns it along with the port number.
+    async fn listen() -> (TcpListener, u16) {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        (listener, port)
+    }
+
+    #[tokio::test]
+    async fn test_echo_round_trip() {
+        let (listener, port) = listen().await;
+        let server = tokio::spawn(async move {
+            let (mut stream, _addr) = listener.accept().await.unwrap();
+            let mut buffer = [0; 16];
+            loop {
+                let n = stream.read(&mut buffer).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                stream.write_all(&buffer[..n]).await.unwrap();
+            }
+        });
+
+        let mut socket = TcpConnector::default().connect("127.0.0.1", port).await.unwrap();
+        socket.send(b"hello").await.unwrap();
+        let mut received = vec![];
+        while received.len() < 5 {
+            received.extend(socket.recv(100).await.unwrap());
+        }
+        assert_eq!(b"hello", received.as_slice());
+
+        socket.send(b"0123456789").await.unwrap();
+        let mut received = vec![];
+        while received.len() < 10 {
+            let data = socket.recv(3).await.unwrap();
+            assert!(!data.is_empty() && data.len() <= 3);
+            received.extend(data);
+        }
+        assert_eq!(b"0123456789", received.as_slice());
+
+        drop(socket);
+        server.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_close_by_peer() {
+        let (listener, port) = listen().await;
+        let server = tokio::spawn(async move {
+            let (mut stream, _addr) = listener.accept().await.unwrap();
+            stream.write_all(b"bye").await.unwrap();
+        });
+
+        let mut socket = TcpConnector::default().connect("127.0.0.1", port).await.unwrap();
+        server.await.unwrap();
+        assert_eq!(b"bye", socket.recv(100).await.unwrap().as_slice());
+        assert!(socket.recv(100).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_connection_refused() {
+        let (listener, port) = listen().await;
+        drop(listener);
+
+        let err = TcpConnector::default().connect("127.0.0.1", port).await.err().unwrap();
+        assert_eq!(io::ErrorKind::ConnectionRefused, err.kind());
+    }
+}
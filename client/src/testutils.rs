@@ -15,14 +15,35 @@
 
 //! Test utilities for the cloud service.
 
-use crate::{add_all, AccessToken, GetFilesResponse, LoginResponse, Service, SignupRequest};
+use crate::{
+    add_all, AccessToken, GetFilesResponse, LoginResponse, LoginResult, MotdResponse, ProgressFn,
+    PublicFilesResponse, PublicUsersResponse, RetryPolicy, SecondFactorChallenge, Service,
+    SignupRequest, TokenStore,
+};
 use async_trait::async_trait;
+use endbasic_core::ast::Value;
 use endbasic_std::storage::{FileAcls, Storage};
 use endbasic_std::testutils::*;
 use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::io;
 use std::rc::Rc;
+use std::time::Duration;
+
+/// Size of the chunks in which the mock service pretends to transfer file contents.
+pub(crate) const MOCK_CHUNK_SIZE: usize = 16 * 1024;
+
+/// Reports the progress of a simulated transfer of `total` bytes to `progress`, if any, in chunks
+/// of `MOCK_CHUNK_SIZE`.
+fn simulate_transfer(total: usize, progress: Option<ProgressFn<'_>>) {
+    if let Some(progress) = progress {
+        let mut done = 0;
+        while done < total {
+            done = (done + MOCK_CHUNK_SIZE).min(total);
+            progress(done as u64, total as u64);
+        }
+    }
+}
 
 /// Service client implementation that allows specifying expectations on requests and yields the
 /// responses previously recorded into it.
@@ -31,13 +52,20 @@ pub struct MockService {
     access_token: Option<AccessToken>,
 
     mock_signup: VecDeque<(SignupRequest, io::Result<()>)>,
-    mock_login: VecDeque<((String, String), io::Result<LoginResponse>)>,
+    mock_login: VecDeque<((String, String), io::Result<LoginResult>)>,
+    mock_login_2fa: VecDeque<((String, String, String), io::Result<LoginResponse>)>,
+    mock_login_with_token: VecDeque<((String, AccessToken), io::Result<()>)>,
+    mock_delete_account: VecDeque<(String, io::Result<()>)>,
+    mock_get_motd: VecDeque<io::Result<MotdResponse>>,
     mock_get_files: VecDeque<(String, io::Result<GetFilesResponse>)>,
+    mock_list_public_users: VecDeque<(Option<String>, io::Result<PublicUsersResponse>)>,
+    mock_list_public_files: VecDeque<((String, Option<String>), io::Result<PublicFilesResponse>)>,
     mock_get_file: VecDeque<((String, String), io::Result<Vec<u8>>)>,
     mock_get_file_acls: VecDeque<((String, String), io::Result<FileAcls>)>,
     mock_patch_file_content: VecDeque<((String, String, Vec<u8>), io::Result<()>)>,
     mock_patch_file_acls: VecDeque<((String, String, FileAcls, FileAcls), io::Result<()>)>,
     mock_delete_file: VecDeque<((String, String), io::Result<()>)>,
+    mock_ping: VecDeque<io::Result<()>>,
 }
 
 impl MockService {
@@ -69,7 +97,61 @@ impl MockService {
         result: io::Result<LoginResponse>,
     ) {
         let exp_request = (username.to_owned(), password.to_owned());
-        self.mock_login.push_back((exp_request, result));
+        self.mock_login.push_back((exp_request, result.map(LoginResult::Authenticated)));
+    }
+
+    /// Records the behavior of an upcoming login operation with `username` and `password`
+    /// credentials that asks for a second factor with `token_challenge`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_login_second_factor(
+        &mut self,
+        username: &str,
+        password: &str,
+        token_challenge: &str,
+    ) {
+        let exp_request = (username.to_owned(), password.to_owned());
+        let challenge = SecondFactorChallenge { token_challenge: token_challenge.to_owned() };
+        self.mock_login.push_back((exp_request, Ok(LoginResult::SecondFactorRequired(challenge))));
+    }
+
+    /// Records the behavior of an upcoming second factor login operation for `username` with
+    /// `token_challenge` and `code` and that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_login_2fa(
+        &mut self,
+        username: &str,
+        token_challenge: &str,
+        code: &str,
+        result: io::Result<LoginResponse>,
+    ) {
+        let exp_request = (username.to_owned(), token_challenge.to_owned(), code.to_owned());
+        self.mock_login_2fa.push_back((exp_request, result));
+    }
+
+    /// Records the behavior of an upcoming login operation with `username` and a previously-saved
+    /// access `token` and that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_login_with_token(
+        &mut self,
+        username: &str,
+        token: &str,
+        result: io::Result<()>,
+    ) {
+        let exp_request = (username.to_owned(), AccessToken::new(token));
+        self.mock_login_with_token.push_back((exp_request, result));
+    }
+
+    /// Records the behavior of an upcoming account deletion confirmed with `password` and that
+    /// returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_delete_account(&mut self, password: &str, result: io::Result<()>) {
+        self.mock_delete_account.push_back((password.to_owned(), result));
+    }
+
+    /// Records the behavior of an upcoming "get MOTD" operation that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_get_motd(&mut self, result: io::Result<MotdResponse>) {
+        self.mock_get_motd.push_back(result);
     }
 
     /// Records the behavior of an upcoming "get files" operation for `username` and that returns
@@ -84,6 +166,31 @@ impl MockService {
         self.mock_get_files.push_back((exp_request, result));
     }
 
+    /// Records the behavior of an upcoming "list public users" operation for the page given by
+    /// `page_token` that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_list_public_users(
+        &mut self,
+        page_token: Option<&str>,
+        result: io::Result<PublicUsersResponse>,
+    ) {
+        let exp_request = page_token.map(str::to_owned);
+        self.mock_list_public_users.push_back((exp_request, result));
+    }
+
+    /// Records the behavior of an upcoming "list public files" operation for the `username`
+    /// and the page given by `page_token` that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_list_public_files(
+        &mut self,
+        username: &str,
+        page_token: Option<&str>,
+        result: io::Result<PublicFilesResponse>,
+    ) {
+        let exp_request = (username.to_owned(), page_token.map(str::to_owned));
+        self.mock_list_public_files.push_back((exp_request, result));
+    }
+
     /// Records the behavior of an upcoming "get file" operation for the `username`/`filename`
     /// pair that returns `result`.
     #[cfg(test)]
@@ -127,22 +234,15 @@ impl MockService {
     /// Records the behavior of an upcoming "patch file ACLS" operation for the
     /// `username`/`filename` pair with `exp_add` and `exp_remove` and that returns `result`.
     #[cfg(test)]
-    pub(crate) fn add_mock_patch_file_acls<S: Into<String>, V: Into<Vec<S>>>(
+    pub(crate) fn add_mock_patch_file_acls(
         &mut self,
         username: &str,
         filename: &str,
-        exp_add: V,
-        exp_remove: V,
+        exp_add: FileAcls,
+        exp_remove: FileAcls,
         result: io::Result<()>,
     ) {
-        let exp_add = FileAcls {
-            readers: exp_add.into().into_iter().map(|v| v.into()).collect::<Vec<String>>(),
-        };
-        let exp_remove = FileAcls {
-            readers: exp_remove.into().into_iter().map(|v| v.into()).collect::<Vec<String>>(),
-        };
-        let exp_request =
-            (username.to_owned(), filename.to_owned(), exp_add.into(), exp_remove.into());
+        let exp_request = (username.to_owned(), filename.to_owned(), exp_add, exp_remove);
         self.mock_patch_file_acls.push_back((exp_request, result));
     }
 
@@ -159,16 +259,29 @@ impl MockService {
         self.mock_delete_file.push_back((exp_request, result));
     }
 
+    /// Records the behavior of an upcoming "ping" operation that returns `result`.
+    #[cfg(test)]
+    pub(crate) fn add_mock_ping(&mut self, result: io::Result<()>) {
+        self.mock_ping.push_back(result);
+    }
+
     /// Ensures that all requests and responses have been consumed.
     pub(crate) fn verify_all_used(&mut self) {
         assert!(self.mock_signup.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_login.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_login_2fa.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_login_with_token.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_delete_account.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_get_motd.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_get_files.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_list_public_users.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_list_public_files.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_get_file.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_get_file_acls.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_patch_file_content.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_patch_file_acls.is_empty(), "Mock requests not fully consumed");
         assert!(self.mock_delete_file.is_empty(), "Mock requests not fully consumed");
+        assert!(self.mock_ping.is_empty(), "Mock requests not fully consumed");
     }
 }
 
@@ -180,11 +293,29 @@ impl Service for MockService {
         mock.1
     }
 
-    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResponse> {
+    async fn login(&mut self, username: &str, password: &str) -> io::Result<LoginResult> {
         let mock = self.mock_login.pop_front().expect("No mock requests available");
         assert_eq!(&mock.0 .0, username);
         assert_eq!(&mock.0 .1, password);
 
+        if let Ok(LoginResult::Authenticated(response)) = &mock.1 {
+            self.access_token = Some(response.access_token.clone());
+        }
+
+        mock.1
+    }
+
+    async fn login_2fa(
+        &mut self,
+        username: &str,
+        token_challenge: &str,
+        code: &str,
+    ) -> io::Result<LoginResponse> {
+        let mock = self.mock_login_2fa.pop_front().expect("No mock requests available");
+        assert_eq!(&mock.0 .0, username);
+        assert_eq!(&mock.0 .1, token_challenge);
+        assert_eq!(&mock.0 .2, code);
+
         if let Ok(response) = &mock.1 {
             self.access_token = Some(response.access_token.clone());
         }
@@ -192,12 +323,44 @@ impl Service for MockService {
         mock.1
     }
 
+    async fn login_with_token(
+        &mut self,
+        username: &str,
+        access_token: AccessToken,
+    ) -> io::Result<()> {
+        let mock = self.mock_login_with_token.pop_front().expect("No mock requests available");
+        assert_eq!(&mock.0 .0, username);
+        assert_eq!(mock.0 .1, access_token);
+
+        if mock.1.is_ok() {
+            self.access_token = Some(access_token);
+        }
+
+        mock.1
+    }
+
     async fn logout(&mut self) -> io::Result<()> {
         self.access_token.as_ref().expect("login not called yet");
         self.access_token = None;
         Ok(())
     }
 
+    async fn delete_account(&mut self, password: &str) -> io::Result<()> {
+        self.access_token.as_ref().expect("login not called yet");
+        let mock = self.mock_delete_account.pop_front().expect("No mock requests available");
+        assert_eq!(&mock.0, password);
+
+        if mock.1.is_ok() {
+            self.access_token = None;
+        }
+
+        mock.1
+    }
+
+    async fn get_motd(&mut self) -> io::Result<MotdResponse> {
+        self.mock_get_motd.pop_front().expect("No mock requests available")
+    }
+
     fn is_logged_in(&self) -> bool {
         self.access_token.is_some()
     }
@@ -216,12 +379,40 @@ impl Service for MockService {
         mock.1
     }
 
-    async fn get_file(&mut self, username: &str, filename: &str) -> io::Result<Vec<u8>> {
+    async fn list_public_users(
+        &mut self,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicUsersResponse> {
+        let mock = self.mock_list_public_users.pop_front().expect("No mock requests available");
+        assert_eq!(mock.0.as_deref(), page_token);
+        mock.1
+    }
+
+    async fn list_public_files(
+        &mut self,
+        username: &str,
+        page_token: Option<&str>,
+    ) -> io::Result<PublicFilesResponse> {
+        let mock = self.mock_list_public_files.pop_front().expect("No mock requests available");
+        assert_eq!(&mock.0 .0, username);
+        assert_eq!(mock.0 .1.as_deref(), page_token);
+        mock.1
+    }
+
+    async fn get_file(
+        &mut self,
+        username: &str,
+        filename: &str,
+        progress: Option<ProgressFn<'_>>,
+    ) -> io::Result<Vec<u8>> {
         self.access_token.as_ref().expect("login not called yet");
 
         let mock = self.mock_get_file.pop_front().expect("No mock requests available");
         assert_eq!(&mock.0 .0, username);
         assert_eq!(&mock.0 .1, filename);
+        if let Ok(content) = &mock.1 {
+            simulate_transfer(content.len(), progress);
+        }
         mock.1
     }
 
@@ -239,6 +430,7 @@ impl Service for MockService {
         username: &str,
         filename: &str,
         content: Vec<u8>,
+        progress: Option<ProgressFn<'_>>,
     ) -> io::Result<()> {
         self.access_token.as_ref().expect("login not called yet");
 
@@ -246,6 +438,9 @@ impl Service for MockService {
         assert_eq!(&mock.0 .0, username);
         assert_eq!(&mock.0 .1, filename);
         assert_eq!(&mock.0 .2, &content);
+        if mock.1.is_ok() {
+            simulate_transfer(content.len(), progress);
+        }
         mock.1
     }
 
@@ -274,6 +469,33 @@ impl Service for MockService {
         assert_eq!(&mock.0 .1, filename);
         mock.1
     }
+
+    async fn ping(&mut self) -> io::Result<()> {
+        self.mock_ping.pop_front().expect("No mock requests available")
+    }
+}
+
+/// Token store that keeps the saved session in memory.
+#[derive(Default)]
+pub(crate) struct MockTokenStore {
+    /// The saved session, if any.
+    pub(crate) data: Option<String>,
+}
+
+impl TokenStore for MockTokenStore {
+    fn load(&self) -> io::Result<Option<String>> {
+        Ok(self.data.clone())
+    }
+
+    fn save(&mut self, data: &str) -> io::Result<()> {
+        self.data = Some(data.to_owned());
+        Ok(())
+    }
+
+    fn delete(&mut self) -> io::Result<()> {
+        self.data = None;
+        Ok(())
+    }
 }
 
 /// Wrapper over the generic `Tester` to validate features related to the cloud service.
@@ -281,6 +503,7 @@ impl Service for MockService {
 pub(crate) struct ClientTester {
     tester: Tester,
     service: Rc<RefCell<MockService>>,
+    token_store: Rc<RefCell<MockTokenStore>>,
 }
 
 impl Default for ClientTester {
@@ -289,21 +512,28 @@ impl Default for ClientTester {
         let console = tester.get_console();
         let storage = tester.get_storage();
         let service = Rc::from(RefCell::from(MockService::default()));
+        let token_store = Rc::from(RefCell::from(MockTokenStore::default()));
         add_all(
             tester.get_machine(),
             service.clone(),
             console,
             storage,
+            Some(token_store.clone()),
+            RetryPolicy::new(3, Duration::ZERO, Duration::ZERO),
             "https://repl.example.com/",
         );
-        ClientTester { tester, service }
+        ClientTester { tester, service, token_store }
     }
 }
 
 impl ClientTester {
     /// See the wrapped `Tester::add_input_chars` function for details.
     pub fn add_input_chars(self, golden_in: &str) -> Self {
-        ClientTester { tester: self.tester.add_input_chars(golden_in), service: self.service }
+        ClientTester {
+            tester: self.tester.add_input_chars(golden_in),
+            service: self.service,
+            token_store: self.token_store,
+        }
     }
 
     /// See the wrapped `Tester::get_console` function for details.
@@ -319,6 +549,11 @@ impl ClientTester {
         self.service.clone()
     }
 
+    /// Gets the mock token store from the tester.
+    pub(crate) fn get_token_store(&self) -> Rc<RefCell<MockTokenStore>> {
+        self.token_store.clone()
+    }
+
     /// See the wrapped `Tester::get_storage` function for details.
     pub fn get_storage(&self) -> Rc<RefCell<Storage>> {
         self.tester.get_storage()
@@ -394,6 +629,15 @@ impl<'a> ClientChecker<'a> {
         }
     }
 
+    /// See the wrapped `Checker::expect_var` function for details.
+    pub fn expect_var<S: AsRef<str>, V: Into<Value>>(self, name: S, value: V) -> Self {
+        Self {
+            checker: self.checker.expect_var(name, value),
+            service: self.service,
+            exp_access_token: self.exp_access_token,
+        }
+    }
+
     /// See the wrapped `Checker::take_captured_out` function for details.
     #[must_use]
     pub fn take_captured_out(&mut self) -> Vec<CapturedOut> {
@@ -0,0 +1,250 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Persistence of login sessions across interpreter runs.
+
+use crate::{AccessToken, Service};
+use endbasic_std::storage::Storage;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::rc::Rc;
+
+/// Abstract storage for the login session that the user asked to remember.
+///
+/// The session is handed to the store as an opaque string so that implementations do not need to
+/// know anything about its contents.
+pub trait TokenStore {
+    /// Loads the saved session, if any.
+    fn load(&self) -> io::Result<Option<String>>;
+
+    /// Saves the session `data`, replacing any previously-saved session.
+    fn save(&mut self, data: &str) -> io::Result<()>;
+
+    /// Deletes the saved session.  Succeeds if there was no session.
+    fn delete(&mut self) -> io::Result<()>;
+}
+
+/// Token store backed by a file in the local file system.
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Creates a new token store that keeps the session in the file at `path`.  The file and its
+    /// parent directory need not exist yet.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self, data: &str) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut options = OpenOptions::new();
+        options.create(true).write(true).truncate(true);
+        #[cfg(unix)]
+        {
+            // The access token grants full access to the user's account so keep it private.
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut output = options.open(&self.path)?;
+        output.write_all(data.as_bytes())?;
+        output.sync_all()
+    }
+
+    fn delete(&mut self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// Representation of the login session kept in a `TokenStore`.
+#[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
+struct SavedSession {
+    username: String,
+    access_token: AccessToken,
+}
+
+/// Saves the session of `username` with `access_token` into `token_store`.
+pub(crate) fn save_session(
+    token_store: &mut dyn TokenStore,
+    username: &str,
+    access_token: &AccessToken,
+) -> io::Result<()> {
+    let session =
+        SavedSession { username: username.to_owned(), access_token: access_token.clone() };
+    token_store.save(&serde_json::to_string(&session)?)
+}
+
+/// Attempts to restore the login session saved in `token_store` against `service` and, on success,
+/// mounts the user's drive as `CLOUD` in `storage`.
+///
+/// Returns true if the session was restored.  Failures are not reported because the user can
+/// always fall back to logging in explicitly.  Sessions that cannot be parsed or that the service
+/// rejects are deleted so that they are not retried on every startup, but sessions that could not
+/// be checked due to other errors, such as network problems, are kept.
+pub async fn restore_session(
+    service: Rc<RefCell<dyn Service>>,
+    storage: Rc<RefCell<Storage>>,
+    token_store: Rc<RefCell<dyn TokenStore>>,
+) -> bool {
+    let data = match token_store.borrow().load() {
+        Ok(Some(data)) => data,
+        Ok(None) | Err(_) => return false,
+    };
+
+    let session: SavedSession = match serde_json::from_str(&data) {
+        Ok(session) => session,
+        Err(_) => {
+            let _ = token_store.borrow_mut().delete();
+            return false;
+        }
+    };
+
+    match service.borrow_mut().login_with_token(&session.username, session.access_token).await {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+            let _ = token_store.borrow_mut().delete();
+            return false;
+        }
+        Err(_) => return false,
+    }
+
+    let mut storage = storage.borrow_mut();
+    storage.mount("CLOUD", &format!("cloud://{}", session.username), false).await.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    /// Creates a mock token store that contains the session for `username` with `token`.
+    fn store_with_session(username: &str, token: &str) -> Rc<RefCell<MockTokenStore>> {
+        let mut store = MockTokenStore::default();
+        save_session(&mut store, username, &AccessToken::new(token)).unwrap();
+        Rc::from(RefCell::from(store))
+    }
+
+    #[test]
+    fn test_file_token_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("subdir/session");
+        let mut store = FileTokenStore::new(&path);
+
+        assert_eq!(None, store.load().unwrap());
+        store.delete().unwrap();
+
+        store.save("first").unwrap();
+        assert_eq!(Some("first".to_owned()), store.load().unwrap());
+        store.save("second").unwrap();
+        assert_eq!(Some("second".to_owned()), store.load().unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(0o600, fs::metadata(&path).unwrap().permissions().mode() & 0o777);
+        }
+
+        store.delete().unwrap();
+        assert_eq!(None, store.load().unwrap());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_ok() {
+        let t = ClientTester::default();
+        let token_store = store_with_session("the-user", "the-token");
+        t.get_service().borrow_mut().add_mock_login_with_token("the-user", "the-token", Ok(()));
+
+        assert!(restore_session(t.get_service(), t.get_storage(), token_store.clone()).await);
+        assert!(t.get_service().borrow().is_logged_in());
+        assert_eq!(Some(&"cloud://the-user"), t.get_storage().borrow().mounted().get("CLOUD"));
+        assert!(token_store.borrow().data.is_some());
+        t.get_service().borrow_mut().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_nothing_saved() {
+        let t = ClientTester::default();
+        let token_store = Rc::from(RefCell::from(MockTokenStore::default()));
+
+        assert!(!restore_session(t.get_service(), t.get_storage(), token_store).await);
+        assert!(!t.get_service().borrow().is_logged_in());
+        assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_expired() {
+        let t = ClientTester::default();
+        let token_store = store_with_session("the-user", "old-token");
+        t.get_service().borrow_mut().add_mock_login_with_token(
+            "the-user",
+            "old-token",
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Expired")),
+        );
+
+        assert!(!restore_session(t.get_service(), t.get_storage(), token_store.clone()).await);
+        assert!(!t.get_service().borrow().is_logged_in());
+        assert!(!t.get_storage().borrow().mounted().contains_key("CLOUD"));
+        assert!(token_store.borrow().data.is_none());
+        t.get_service().borrow_mut().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_other_error_keeps_token() {
+        let t = ClientTester::default();
+        let token_store = store_with_session("the-user", "the-token");
+        t.get_service().borrow_mut().add_mock_login_with_token(
+            "the-user",
+            "the-token",
+            Err(io::Error::new(io::ErrorKind::Other, "Network is down")),
+        );
+
+        assert!(!restore_session(t.get_service(), t.get_storage(), token_store.clone()).await);
+        assert!(!t.get_service().borrow().is_logged_in());
+        assert!(token_store.borrow().data.is_some());
+        t.get_service().borrow_mut().verify_all_used();
+    }
+
+    #[tokio::test]
+    async fn test_restore_session_corrupted() {
+        let t = ClientTester::default();
+        let token_store = Rc::from(RefCell::from(MockTokenStore::default()));
+        token_store.borrow_mut().save("not json").unwrap();
+
+        assert!(!restore_session(t.get_service(), t.get_storage(), token_store.clone()).await);
+        assert!(!t.get_service().borrow().is_logged_in());
+        assert!(token_store.borrow().data.is_none());
+    }
+}
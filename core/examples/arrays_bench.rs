@@ -0,0 +1,79 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Micro-benchmark for the storage of large numeric arrays.
+//!
+//! This example fills and sums a one-million element array, first from EndBASIC code and then
+//! from Rust code that accesses the array's elements as a slice, and prints how long each phase
+//! took.  Run it with `cargo run --release --example arrays_bench`.
+
+use endbasic_core::{
+    ast::{ExprType, Value},
+    exec::{Machine, StopReason},
+    syms::{Array, Symbol},
+};
+use futures_lite::future::block_on;
+use std::time::{Duration, Instant};
+
+/// Number of elements in the array to benchmark.
+const N: usize = 1_000_000;
+
+/// Runs `op` and returns how long it took along with its result.
+fn timed<T, F: FnOnce() -> T>(op: F) -> (Duration, T) {
+    let start = Instant::now();
+    let result = op();
+    (start.elapsed(), result)
+}
+
+/// Executes the `code` snippet in `machine`, panicking on errors.
+fn exec(machine: &mut Machine, code: &str) {
+    let stop_reason = block_on(machine.exec(&mut code.as_bytes())).expect("Execution error");
+    assert_eq!(StopReason::Eof, stop_reason);
+}
+
+fn main() {
+    let mut machine = Machine::default();
+    exec(&mut machine, &format!("DIM a({}) AS INTEGER", N));
+
+    let (elapsed, ()) = timed(|| {
+        exec(&mut machine, &format!("FOR i = 0 TO {}: a(i) = i MOD 256: NEXT", N - 1));
+    });
+    println!("Interpreted fill: {:?}", elapsed);
+
+    let (elapsed, ()) = timed(|| {
+        exec(&mut machine, &format!("s# = 0: FOR i = 0 TO {}: s# = s# + a(i): NEXT", N - 1));
+    });
+    match machine.get_symbols().get_auto("s") {
+        Some(Symbol::Variable(Value::Double(d))) => {
+            println!("Interpreted sum: {:?} ({})", elapsed, d)
+        }
+        _ => panic!("Sum not computed"),
+    }
+
+    let mut array = Array::new(ExprType::Integer, vec![N]);
+    let (elapsed, ()) = timed(|| {
+        let values = array.as_integers_mut().expect("Array is INTEGER");
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = (i % 256) as i32;
+        }
+    });
+    println!("Native fill: {:?}", elapsed);
+
+    let (elapsed, sum) = timed(|| {
+        let values = array.as_integers().expect("Array is INTEGER");
+        values.iter().map(|v| f64::from(*v)).sum::<f64>()
+    });
+    println!("Native sum: {:?} ({})", elapsed, sum);
+}
@@ -0,0 +1,83 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Micro-benchmark for the per-statement overhead of the interpreter.
+//!
+//! This example runs a numeric workload made of nested loops that constantly load and store
+//! variables, both at the top level and within a function, and prints the fastest and the median
+//! times of several runs.  Run it with `cargo run --release --example loops_bench`.
+//!
+//! To compare two revisions of the interpreter, copy this file into a checkout of each revision
+//! and run it in both on the same otherwise-idle machine.
+
+use endbasic_core::{
+    ast::Value,
+    exec::{Machine, StopReason},
+    syms::Symbol,
+};
+use futures_lite::future::block_on;
+use std::time::{Duration, Instant};
+
+/// Numeric workload that runs in the top-level scope.
+const TOP_LEVEL: &str = r#"
+total = 0
+FOR i = 1 TO 1000
+    FOR j = 1 TO 1000
+        total = total + (i * j) MOD 7
+    NEXT
+NEXT
+"#;
+
+/// Numeric workload that runs within a function.
+const IN_FUNCTION: &str = r#"
+FUNCTION work
+    acc = 0
+    FOR i = 1 TO 1000
+        FOR j = 1 TO 1000
+            acc = acc + (i * j) MOD 7
+        NEXT
+    NEXT
+    work = acc
+END FUNCTION
+total = work
+"#;
+
+/// Number of times to run each workload.
+const RUNS: usize = 7;
+
+/// Runs the `code` workload in a fresh machine and returns how long it took.
+fn run_once(code: &str) -> Duration {
+    let mut machine = Machine::default();
+    let start = Instant::now();
+    let stop_reason = block_on(machine.exec(&mut code.as_bytes())).expect("Execution error");
+    let elapsed = start.elapsed();
+    assert_eq!(StopReason::Eof, stop_reason);
+    match machine.get_symbols().get_auto("total") {
+        Some(Symbol::Variable(Value::Integer(2576574))) => elapsed,
+        _ => panic!("Total not computed"),
+    }
+}
+
+/// Runs the `code` workload `RUNS` times and prints the fastest and the median times.
+fn bench(name: &str, code: &str) {
+    let mut times = (0..RUNS).map(|_| run_once(code)).collect::<Vec<Duration>>();
+    times.sort();
+    println!("{}: best {:?}, median {:?}", name, times[0], times[RUNS / 2]);
+}
+
+fn main() {
+    bench("Top-level loops", TOP_LEVEL);
+    bench("Function loops", IN_FUNCTION);
+}
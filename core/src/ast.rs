@@ -333,6 +333,9 @@ pub enum Value {
 
     /// A reference to a variable.
     VarRef(SymbolKey, ExprType),
+
+    /// A missing value of the given type, which can only exist when EMPTY values are enabled.
+    Empty(ExprType),
 }
 
 impl From<bool> for Value {
@@ -374,6 +377,7 @@ impl fmt::Display for Value {
             Value::Integer(i) => write!(f, "{}", i),
             Value::Text(s) => write!(f, "\"{}\"", s),
             Value::VarRef(key, etype) => write!(f, "{}{}", key, etype),
+            Value::Empty(_etype) => write!(f, "EMPTY"),
         }
     }
 }
@@ -387,6 +391,7 @@ impl Value {
             Value::Integer(_) => ExprType::Integer,
             Value::Text(_) => ExprType::Text,
             Value::VarRef(_key, etype) => *etype,
+            Value::Empty(etype) => *etype,
         }
     }
 }
@@ -493,6 +498,21 @@ pub struct CallSpan {
     pub args: Vec<ArgSpan>,
 }
 
+/// Components of a parameter in a `FUNCTION` or `SUB` definition.
+#[derive(Debug, PartialEq)]
+pub struct ParamSpan {
+    /// Name and type of the parameter.
+    pub vref: VarRef,
+
+    /// Whether the parameter was declared with `BYREF` so that assignments to it are propagated
+    /// to the variable or array element given by the caller.
+    pub byref: bool,
+
+    /// Expression for the value of the parameter when the caller omits it, if the parameter is
+    /// optional.
+    pub default: Option<Expr>,
+}
+
 /// Components of a `FUNCTION` or `SUB` definition.
 #[derive(Debug, PartialEq)]
 pub struct CallableSpan {
@@ -504,7 +524,7 @@ pub struct CallableSpan {
     pub name_pos: LineCol,
 
     /// Definition of the callable parameters.
-    pub params: Vec<VarRef>,
+    pub params: Vec<ParamSpan>,
 
     /// Statements within the callable's body.
     pub body: Vec<Statement>,
@@ -570,6 +590,29 @@ pub struct DimArraySpan {
     pub subtype_pos: LineCol,
 }
 
+/// Components of a record variable or array definition.
+#[derive(Debug, PartialEq)]
+pub struct DimRecordSpan {
+    /// Name of the record variable or array to define.  Type annotations are not allowed, hence
+    /// why this is not a `VarRef`.
+    pub name: String,
+
+    /// Position of the name.
+    pub name_pos: LineCol,
+
+    /// Whether the record is global or not.
+    pub shared: bool,
+
+    /// Expressions to compute the dimensions of the array, or empty if this is not an array.
+    pub dimensions: Vec<Expr>,
+
+    /// Name of the record type.
+    pub type_name: String,
+
+    /// Position of the record type name.
+    pub type_pos: LineCol,
+}
+
 /// Type of the `DO` loop.
 #[derive(Debug, PartialEq)]
 pub enum DoGuard {
@@ -689,6 +732,25 @@ pub struct LabelSpan {
     pub name_pos: LineCol,
 }
 
+/// Components of a `MID$` statement, which overwrites a portion of a string variable.
+#[derive(Debug, PartialEq)]
+pub struct MidAssignmentSpan {
+    /// Reference to the string variable to modify.
+    pub vref: VarRef,
+
+    /// Position of the `vref`.
+    pub vref_pos: LineCol,
+
+    /// Expression to compute the 1-based position of the first character to overwrite.
+    pub start: Expr,
+
+    /// Expression to compute the maximum number of characters to overwrite, if any.
+    pub length: Option<Expr>,
+
+    /// Expression to compute the replacement string.
+    pub expr: Expr,
+}
+
 /// Components of an `ON ERROR` statement.
 #[derive(Debug, Eq, PartialEq)]
 pub enum OnErrorSpan {
@@ -702,6 +764,46 @@ pub enum OnErrorSpan {
     ResumeNext,
 }
 
+/// Components of an `ON expr GOTO` or an `ON expr GOSUB` statement.
+#[derive(Debug, PartialEq)]
+pub struct OnGotoSpan {
+    /// Expression that selects the target to jump to, starting at 1.
+    pub expr: Expr,
+
+    /// Whether the jump is a `GOSUB` instead of a `GOTO`.
+    pub gosub: bool,
+
+    /// Labels to jump to, in order.
+    pub targets: Vec<GotoSpan>,
+
+    /// Position of the statement.
+    pub pos: LineCol,
+}
+
+/// Components of an `ON TIMER` statement.
+#[derive(Debug, PartialEq)]
+pub struct OnTimerSpan {
+    /// Expression to compute the number of seconds between timer events.
+    pub interval: Expr,
+
+    /// Label of the subroutine to call on every timer event.
+    pub target: GotoSpan,
+
+    /// Position of the statement.
+    pub pos: LineCol,
+}
+
+/// Components of a `RESUME` statement.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ResumeSpan {
+    /// Whether execution resumes at the statement after the one that failed (`RESUME NEXT`)
+    /// instead of at the failed statement itself (`RESUME`).
+    pub next: bool,
+
+    /// Position of the statement.
+    pub pos: LineCol,
+}
+
 /// Components of a `RETURN` statement.
 #[derive(Debug, Eq, PartialEq)]
 pub struct ReturnSpan {
@@ -765,6 +867,81 @@ pub struct SelectSpan {
     pub end_pos: LineCol,
 }
 
+/// Definition of the variable declared by a `STATIC` statement.
+#[derive(Debug, PartialEq)]
+pub enum StaticDecl {
+    /// A variable initialized to the default value of its type.
+    Dim(DimSpan),
+
+    /// An array initialized to the default values of its subtype.
+    DimArray(DimArraySpan),
+
+    /// A variable initialized to the result of an expression.
+    Assignment(AssignmentSpan),
+}
+
+/// Components of a `STATIC` statement.
+#[derive(Debug, PartialEq)]
+pub struct StaticSpan {
+    /// Definition of the static variable.
+    pub decl: StaticDecl,
+
+    /// Position of the statement.
+    pub pos: LineCol,
+}
+
+/// Components of a field definition within a `TYPE` statement.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FieldSpan {
+    /// Name of the field.  Type annotations are not allowed, hence why this is not a `VarRef`.
+    pub name: String,
+
+    /// Position of the name.
+    pub name_pos: LineCol,
+
+    /// Type of the field.
+    pub vtype: ExprType,
+
+    /// Position of the type.
+    pub vtype_pos: LineCol,
+}
+
+/// Collection of state changes that a `TIMER` statement can request.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TimerAction {
+    /// Enables the dispatching of timer events.
+    On,
+
+    /// Disables timer events and discards any that are pending.
+    Off,
+
+    /// Suspends the dispatching of timer events, which are remembered until `TIMER ON`.
+    Stop,
+}
+
+/// Components of a `TIMER` statement.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TimerSpan {
+    /// State change requested by the statement.
+    pub action: TimerAction,
+
+    /// Position of the statement.
+    pub pos: LineCol,
+}
+
+/// Components of a `TYPE` statement.
+#[derive(Debug, Eq, PartialEq)]
+pub struct TypeSpan {
+    /// Name of the record type to define.
+    pub name: String,
+
+    /// Position of the name.
+    pub name_pos: LineCol,
+
+    /// Definitions of the fields of the record, in order.
+    pub fields: Vec<FieldSpan>,
+}
+
 /// Components of a `WHILE` statement.
 #[derive(Debug, PartialEq)]
 pub struct WhileSpan {
@@ -800,6 +977,9 @@ pub enum Statement {
     /// Represents an array definition.
     DimArray(DimArraySpan),
 
+    /// Represents a record variable or array definition.
+    DimRecord(DimRecordSpan),
+
     /// Represents a `DO` statement.
     Do(DoSpan),
 
@@ -824,15 +1004,36 @@ pub enum Statement {
     /// Represents a label "statement".
     Label(LabelSpan),
 
+    /// Represents a `MID$` statement.
+    MidAssignment(MidAssignmentSpan),
+
     /// Represents an `ON ERROR` statement.
     OnError(OnErrorSpan),
 
+    /// Represents an `ON expr GOTO` or an `ON expr GOSUB` statement.
+    OnGoto(OnGotoSpan),
+
+    /// Represents an `ON TIMER` statement.
+    OnTimer(OnTimerSpan),
+
+    /// Represents a `RESUME` statement.
+    Resume(ResumeSpan),
+
     /// Represents a `RETURN` statement.
     Return(ReturnSpan),
 
     /// Represents a `SELECT` statement.
     Select(SelectSpan),
 
+    /// Represents a `STATIC` statement.
+    Static(StaticSpan),
+
+    /// Represents a `TIMER` statement.
+    Timer(TimerSpan),
+
+    /// Represents a `TYPE` definition.
+    Type(TypeSpan),
+
     /// Represents a `WHILE` statement.
     While(WhileSpan),
 }
@@ -15,16 +15,16 @@
 
 //! Low-level representation of an EndBASIC program for execution.
 
-use crate::ast::{ExprType, Value};
+use crate::ast::{ExprType, TimerAction, Value};
 use crate::reader::LineCol;
-use crate::syms::SymbolKey;
+use crate::syms::{CallableMetadata, SymbolKey};
+use std::collections::HashMap;
 
 /// Convenience type to represent a program address.
 pub type Address = usize;
 
 /// Components of a variable definition.
-#[derive(Debug, PartialEq)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DimISpan {
     /// Name of the variable to define.
     pub name: SymbolKey,
@@ -36,9 +36,21 @@ pub struct DimISpan {
     pub vtype: ExprType,
 }
 
+/// Components of a conversion of an array subscript from a double to an integer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DoubleToSubscriptISpan {
+    /// Name of the array being indexed.
+    pub name: SymbolKey,
+
+    /// Position of the name.
+    pub name_pos: LineCol,
+
+    /// Zero-based index of the dimension addressed by the subscript.
+    pub dimension: usize,
+}
+
 /// Components of an array definition.
-#[derive(Debug, PartialEq)]
-#[cfg_attr(test, derive(Clone))]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DimArrayISpan {
     /// Name of the array to define.
     pub name: SymbolKey,
@@ -60,13 +72,14 @@ pub struct DimArrayISpan {
 }
 
 /// Components of an unconditional jump instruction.
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq)]
 pub struct JumpISpan {
     /// The address to jump to.
     pub addr: Address,
 }
 
 /// Components of a conditional jump that depends on whether a variable is defined.
+#[derive(Clone)]
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct JumpIfDefinedISpan {
     /// The variable to check for nonexistence.
@@ -76,6 +89,31 @@ pub struct JumpIfDefinedISpan {
     pub addr: Address,
 }
 
+/// Components of a request to make a static variable visible in the current scope.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct LinkStaticISpan {
+    /// Name of the callable that owns the static variable.
+    pub callable: SymbolKey,
+
+    /// Name of the static variable.
+    pub name: SymbolKey,
+
+    /// The address to jump to if the static variable is already initialized.
+    pub addr: Address,
+}
+
+/// Components of a request to turn a local variable into a static variable.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct SaveStaticISpan {
+    /// Name of the callable that owns the static variable.
+    pub callable: SymbolKey,
+
+    /// Name of the local variable to turn into a static variable.
+    pub name: SymbolKey,
+}
+
 /// Components of a change to the error handler.
 #[derive(Clone, Copy)]
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
@@ -90,7 +128,19 @@ pub enum ErrorHandlerISpan {
     ResumeNext,
 }
 
+/// Components of a change to the timer event handler.
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug, Eq, PartialEq))]
+pub struct TimerHandlerISpan {
+    /// Address of the subroutine to call on every timer event.
+    pub addr: Address,
+
+    /// Position of the expression that computes the interval between events.
+    pub pos: LineCol,
+}
+
 /// Components of a request to unset a variable.
+#[derive(Clone)]
 #[cfg_attr(test, derive(Debug, Eq, PartialEq))]
 pub struct UnsetISpan {
     /// Name of the variable to unset.
@@ -101,6 +151,7 @@ pub struct UnsetISpan {
 }
 
 /// Representation of all possible instructions in the bytecode.
+#[derive(Clone)]
 #[cfg_attr(test, derive(Debug, PartialEq))]
 pub enum Instruction {
     /// Represents a binary logical "and" operation.
@@ -275,9 +326,24 @@ pub enum Instruction {
     /// Represents a conversion of a float to an integer with rounding.
     DoubleToInteger,
 
+    /// Represents a conversion of a float to an integer with rounding for use as an array
+    /// subscript.
+    DoubleToSubscript(DoubleToSubscriptISpan),
+
     /// Represents a conversion of an integer to a float.
     IntegerToDouble,
 
+    /// Represents an unconditional jump within the evaluation of an expression.
+    ///
+    /// Unlike `Jump`, this does not terminate a statement, which matters to `ON ERROR`.
+    ExprJump(Address),
+
+    /// Represents a conditional jump within the evaluation of an expression that jumps if the
+    /// condition is not met.
+    ///
+    /// Unlike `JumpIfNotTrue`, this does not terminate a statement, which matters to `ON ERROR`.
+    ExprJumpIfNotTrue(Address),
+
     /// Represents an unconditional jump.
     Jump(JumpISpan),
 
@@ -293,6 +359,10 @@ pub enum Instruction {
     /// Represents a request to leave the current scope for symbols.
     LeaveScope,
 
+    /// Represents a request to make a static variable visible in the current scope, which jumps
+    /// over its initialization if the variable already exists.
+    LinkStatic(LinkStaticISpan),
+
     /// Represents a load of a boolean variable's value from main memory into the stack.
     LoadBoolean(SymbolKey, LineCol),
 
@@ -311,6 +381,10 @@ pub enum Instruction {
     /// Represents an instruction that does nothing.
     Nop,
 
+    /// Represents the replacement of a portion of a string as done by the `MID$` statement.  The
+    /// boolean indicates whether the maximum number of characters to replace is in the stack.
+    OverwriteString(LineCol, bool),
+
     /// Represents a load of a literal boolean value into the top of the stack.
     PushBoolean(bool, LineCol),
 
@@ -323,17 +397,61 @@ pub enum Instruction {
     /// Represents a load of a literal string value into the top of the stack.
     PushString(String, LineCol),
 
+    /// Represents a return from an error handler to the statement that failed, or to the one after
+    /// it if the boolean is true.
+    Resume(bool, LineCol),
+
     /// Represents a return after a call.
     Return(LineCol),
 
+    /// Represents a request to turn a freshly-initialized local variable into a static variable.
+    SaveStatic(SaveStaticISpan),
+
     /// Represents a change in the error handler state.
     SetErrorHandler(ErrorHandlerISpan),
 
+    /// Represents a change in the timer event handler.  The interval between events, in seconds,
+    /// is at the top of the stack.
+    SetTimerHandler(TimerHandlerISpan),
+
+    /// Represents a change in the dispatching state of timer events.
+    SetTimerState(TimerAction),
+
     /// Represents a request to unset a variable.
     Unset(UnsetISpan),
 }
 
 impl Instruction {
+    /// Calls `f` on every symbol key referenced by the instruction.
+    pub(crate) fn for_each_key_mut<F: FnMut(&mut SymbolKey)>(&mut self, mut f: F) {
+        match self {
+            Instruction::ArrayAssignment(key, ..)
+            | Instruction::ArrayLoad(key, ..)
+            | Instruction::Assign(key)
+            | Instruction::BuiltinCall(key, ..)
+            | Instruction::FunctionCall(key, ..)
+            | Instruction::LoadBoolean(key, _)
+            | Instruction::LoadDouble(key, _)
+            | Instruction::LoadInteger(key, _)
+            | Instruction::LoadString(key, _)
+            | Instruction::LoadRef(key, ..) => f(key),
+
+            Instruction::Dim(span) => f(&mut span.name),
+            Instruction::DimArray(span) => f(&mut span.name),
+            Instruction::DoubleToSubscript(span) => f(&mut span.name),
+            Instruction::JumpIfDefined(span) => f(&mut span.var),
+            Instruction::Unset(span) => f(&mut span.name),
+
+            Instruction::LinkStatic(LinkStaticISpan { callable, name, .. })
+            | Instruction::SaveStatic(SaveStaticISpan { callable, name }) => {
+                f(callable);
+                f(name);
+            }
+
+            _ => (),
+        }
+    }
+
     /// Returns the textual representation of the instruction.
     pub fn repr(&self) -> (&'static str, Option<String>) {
         match self {
@@ -451,8 +569,14 @@ impl Instruction {
             Instruction::EnterScope => ("ENTER", None),
 
             Instruction::DoubleToInteger => ("#TO%", None),
+            Instruction::DoubleToSubscript(span) => {
+                ("#TOSUB%", Some(format!("{}, {}", span.name, span.dimension)))
+            }
             Instruction::IntegerToDouble => ("%TO#", None),
 
+            Instruction::ExprJump(addr) => ("EJMP", Some(format!("{:04x}", addr))),
+            Instruction::ExprJumpIfNotTrue(addr) => ("EJMPNT", Some(format!("{:04x}", addr))),
+
             Instruction::Jump(span) => ("JMP", Some(format!("{:04x}", span.addr))),
             Instruction::JumpIfDefined(span) => {
                 ("JMPVD", Some(format!("{}, {:04x}", span.var, span.addr)))
@@ -462,6 +586,10 @@ impl Instruction {
 
             Instruction::LeaveScope => ("LEAVE", None),
 
+            Instruction::LinkStatic(span) => {
+                ("LINKST", Some(format!("{}, {}, {:04x}", span.callable, span.name, span.addr)))
+            }
+
             Instruction::LoadBoolean(key, _pos) => ("LOAD?", Some(key.to_string())),
             Instruction::LoadDouble(key, _pos) => ("LOAD#", Some(key.to_string())),
             Instruction::LoadInteger(key, _pos) => ("LOAD%", Some(key.to_string())),
@@ -471,19 +599,35 @@ impl Instruction {
 
             Instruction::Nop => ("NOP", None),
 
+            Instruction::OverwriteString(_pos, has_length) => {
+                ("OVERWRITE$", Some(format!("{}", has_length)))
+            }
+
             Instruction::PushBoolean(b, _pos) => ("PUSH?", Some(format!("{}", b))),
             Instruction::PushDouble(d, _pos) => ("PUSH#", Some(format!("{}", d))),
             Instruction::PushInteger(i, _pos) => ("PUSH%", Some(format!("{}", i))),
             Instruction::PushString(s, _pos) => ("PUSH$", Some(format!("\"{}\"", s))),
 
+            Instruction::Resume(next, _pos) => ("RESUME", Some(format!("{}", next))),
             Instruction::Return(_pos) => ("RET", None),
 
+            Instruction::SaveStatic(span) => {
+                ("SAVEST", Some(format!("{}, {}", span.callable, span.name)))
+            }
+
             Instruction::SetErrorHandler(span) => match span {
                 ErrorHandlerISpan::Jump(addr) => ("SEHA", Some(format!("{:04x}", addr))),
                 ErrorHandlerISpan::None => ("SEHN", None),
                 ErrorHandlerISpan::ResumeNext => ("SEHRN", None),
             },
 
+            Instruction::SetTimerHandler(span) => ("STH", Some(format!("{:04x}", span.addr))),
+            Instruction::SetTimerState(action) => match action {
+                TimerAction::On => ("STSON", None),
+                TimerAction::Off => ("STSOFF", None),
+                TimerAction::Stop => ("STSSTOP", None),
+            },
+
             Instruction::Unset(span) => ("UNSETV", Some(format!("{}", span.name))),
         }
     }
@@ -559,24 +703,33 @@ impl Instruction {
             Instruction::End(_) => None,
             Instruction::EnterScope => None,
             Instruction::DoubleToInteger => None,
+            Instruction::DoubleToSubscript(span) => Some(span.name_pos),
             Instruction::IntegerToDouble => None,
+            Instruction::ExprJump(_) => None,
+            Instruction::ExprJumpIfNotTrue(_) => None,
             Instruction::Jump(_) => None,
             Instruction::JumpIfDefined(_) => None,
             Instruction::JumpIfTrue(_) => None,
             Instruction::JumpIfNotTrue(_) => None,
             Instruction::LeaveScope => None,
+            Instruction::LinkStatic(_) => None,
             Instruction::LoadBoolean(_, pos) => Some(*pos),
             Instruction::LoadDouble(_, pos) => Some(*pos),
             Instruction::LoadInteger(_, pos) => Some(*pos),
             Instruction::LoadString(_, pos) => Some(*pos),
             Instruction::LoadRef(_, _, pos) => Some(*pos),
             Instruction::Nop => None,
+            Instruction::OverwriteString(pos, _) => Some(*pos),
             Instruction::PushBoolean(_, pos) => Some(*pos),
             Instruction::PushDouble(_, pos) => Some(*pos),
             Instruction::PushInteger(_, pos) => Some(*pos),
             Instruction::PushString(_, pos) => Some(*pos),
+            Instruction::Resume(_, pos) => Some(*pos),
             Instruction::Return(pos) => Some(*pos),
+            Instruction::SaveStatic(_) => None,
             Instruction::SetErrorHandler(_) => None,
+            Instruction::SetTimerHandler(span) => Some(span.pos),
+            Instruction::SetTimerState(_) => None,
             Instruction::Unset(span) => Some(span.pos),
         }
     }
@@ -636,18 +789,23 @@ impl Instruction {
             | Instruction::ConcatStrings(_)
             | Instruction::FunctionCall(_, _, _, _)
             | Instruction::DoubleToInteger
+            | Instruction::DoubleToSubscript(_)
             | Instruction::IntegerToDouble
+            | Instruction::ExprJump(_)
+            | Instruction::ExprJumpIfNotTrue(_)
             | Instruction::LoadBoolean(_, _)
             | Instruction::LoadDouble(_, _)
             | Instruction::LoadInteger(_, _)
             | Instruction::LoadString(_, _)
             | Instruction::LoadRef(_, _, _)
+            | Instruction::OverwriteString(_, _)
             | Instruction::PushBoolean(_, _)
             | Instruction::PushDouble(_, _)
             | Instruction::PushInteger(_, _)
             | Instruction::PushString(_, _)
             | Instruction::EnterScope
-            | Instruction::LeaveScope => false,
+            | Instruction::LeaveScope
+            | Instruction::LinkStatic(_) => false,
 
             Instruction::ArrayAssignment(_, _, _)
             | Instruction::Assign(_)
@@ -661,15 +819,127 @@ impl Instruction {
             | Instruction::JumpIfTrue(_)
             | Instruction::JumpIfNotTrue(_)
             | Instruction::Nop
+            | Instruction::Resume(_, _)
             | Instruction::Return(_)
+            | Instruction::SaveStatic(_)
             | Instruction::SetErrorHandler(_)
+            | Instruction::SetTimerHandler(_)
+            | Instruction::SetTimerState(_)
             | Instruction::Unset(_) => true,
         }
     }
+
+    /// Returns the number of values at the top of the stack that this instruction consumes as
+    /// operands of a computation, which excludes values that are just moved around.
+    pub(crate) fn noperands(&self) -> usize {
+        match self {
+            Instruction::LogicalAnd(_)
+            | Instruction::LogicalOr(_)
+            | Instruction::LogicalXor(_)
+            | Instruction::BitwiseAnd(_)
+            | Instruction::BitwiseOr(_)
+            | Instruction::BitwiseXor(_)
+            | Instruction::ShiftLeft(_)
+            | Instruction::ShiftRight(_)
+            | Instruction::EqualBooleans(_)
+            | Instruction::NotEqualBooleans(_)
+            | Instruction::EqualDoubles(_)
+            | Instruction::NotEqualDoubles(_)
+            | Instruction::LessDoubles(_)
+            | Instruction::LessEqualDoubles(_)
+            | Instruction::GreaterDoubles(_)
+            | Instruction::GreaterEqualDoubles(_)
+            | Instruction::EqualIntegers(_)
+            | Instruction::NotEqualIntegers(_)
+            | Instruction::LessIntegers(_)
+            | Instruction::LessEqualIntegers(_)
+            | Instruction::GreaterIntegers(_)
+            | Instruction::GreaterEqualIntegers(_)
+            | Instruction::EqualStrings(_)
+            | Instruction::NotEqualStrings(_)
+            | Instruction::LessStrings(_)
+            | Instruction::LessEqualStrings(_)
+            | Instruction::GreaterStrings(_)
+            | Instruction::GreaterEqualStrings(_)
+            | Instruction::AddDoubles(_)
+            | Instruction::SubtractDoubles(_)
+            | Instruction::MultiplyDoubles(_)
+            | Instruction::DivideDoubles(_)
+            | Instruction::ModuloDoubles(_)
+            | Instruction::PowerDoubles(_)
+            | Instruction::AddIntegers(_)
+            | Instruction::SubtractIntegers(_)
+            | Instruction::MultiplyIntegers(_)
+            | Instruction::DivideIntegers(_)
+            | Instruction::ModuloIntegers(_)
+            | Instruction::PowerIntegers(_)
+            | Instruction::ConcatStrings(_) => 2,
+
+            Instruction::LogicalNot(_)
+            | Instruction::BitwiseNot(_)
+            | Instruction::NegateDouble(_)
+            | Instruction::NegateInteger(_)
+            | Instruction::End(true)
+            | Instruction::ExprJumpIfNotTrue(_)
+            | Instruction::JumpIfTrue(_)
+            | Instruction::JumpIfNotTrue(_)
+            | Instruction::SetTimerHandler(_) => 1,
+
+            // Only the subscripts are operands: the value to assign sits below them.
+            Instruction::ArrayAssignment(_, _, nargs) | Instruction::ArrayLoad(_, _, nargs) => {
+                *nargs
+            }
+
+            Instruction::DimArray(span) => span.dimensions,
+
+            Instruction::OverwriteString(_, has_length) => {
+                if *has_length {
+                    4
+                } else {
+                    3
+                }
+            }
+
+            // Arguments to callables are validated right before invoking them because some
+            // callables accept EMPTY values, and all other instructions just move values around.
+            Instruction::BuiltinCall(_, _, _)
+            | Instruction::FunctionCall(_, _, _, _)
+            | Instruction::Assign(_)
+            | Instruction::Call(_)
+            | Instruction::Dim(_)
+            | Instruction::DoubleToInteger
+            | Instruction::DoubleToSubscript(_)
+            | Instruction::IntegerToDouble
+            | Instruction::ExprJump(_)
+            | Instruction::End(false)
+            | Instruction::EnterScope
+            | Instruction::Jump(_)
+            | Instruction::JumpIfDefined(_)
+            | Instruction::LeaveScope
+            | Instruction::LinkStatic(_)
+            | Instruction::LoadBoolean(_, _)
+            | Instruction::LoadDouble(_, _)
+            | Instruction::LoadInteger(_, _)
+            | Instruction::LoadString(_, _)
+            | Instruction::LoadRef(_, _, _)
+            | Instruction::Nop
+            | Instruction::PushBoolean(_, _)
+            | Instruction::PushDouble(_, _)
+            | Instruction::PushInteger(_, _)
+            | Instruction::PushString(_, _)
+            | Instruction::Resume(_, _)
+            | Instruction::Return(_)
+            | Instruction::SaveStatic(_)
+            | Instruction::SetErrorHandler(_)
+            | Instruction::SetTimerState(_)
+            | Instruction::Unset(_) => 0,
+        }
+    }
 }
 
 /// Representation of a compiled program.
-#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+#[cfg_attr(test, derive(Debug))]
 pub struct Image {
     /// Collection of instructions in the program.
     ///
@@ -678,4 +948,7 @@ pub struct Image {
 
     /// Collection of data values in the program.
     pub data: Vec<Option<Value>>,
+
+    /// Metadata of the callables defined by the program.
+    pub callables: HashMap<SymbolKey, CallableMetadata>,
 }
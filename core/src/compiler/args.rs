@@ -17,9 +17,14 @@
 
 use crate::ast::*;
 use crate::bytecode::*;
-use crate::compiler::exprs::{compile_expr, compile_expr_as_type};
-use crate::compiler::{Error, ExprType, Result, SymbolPrototype, SymbolsTable};
+use crate::compiler::exprs::{
+    compile_array_indices, compile_expr, compile_expr_as_type, insert_instr,
+};
+use crate::compiler::{
+    check_implicit_definition, Error, ExprType, Result, SymbolPrototype, SymbolsTable,
+};
 use crate::exec::ValueTag;
+use crate::parser::argspans_to_exprs;
 use crate::reader::LineCol;
 use crate::syms::CallableMetadata;
 use crate::syms::SymbolKey;
@@ -51,6 +56,20 @@ pub struct RequiredRefSyntax {
     pub define_undefined: bool,
 }
 
+/// Details to compile a required scalar parameter that is passed by reference.
+///
+/// The caller must provide a variable or an array element of exactly `vtype`, which the callable
+/// receives by value and which is updated with the value that the callable leaves on the stack
+/// upon return.  Only supported in user-defined callables.
+#[derive(Clone, Debug)]
+pub struct RequiredByRefSyntax {
+    /// The name of the parameter for help purposes.
+    pub name: Cow<'static, str>,
+
+    /// The type of the expected parameter.
+    pub vtype: ExprType,
+}
+
 /// Details to compile an optional scalar parameter.
 ///
 /// Optional parameters are only supported in commands.
@@ -70,6 +89,23 @@ pub struct OptionalValueSyntax {
     pub present_value: i32,
 }
 
+/// Details to compile an optional scalar parameter that takes a default value when missing.
+///
+/// The callable always receives the parameter: if the caller omits it, the compiler pushes the
+/// default value in its place.  Only supported in user-defined callables, where these parameters
+/// must appear after all other parameters.
+#[derive(Clone, Debug)]
+pub struct DefaultValueSyntax {
+    /// The name of the parameter for help purposes.
+    pub name: Cow<'static, str>,
+
+    /// The type of the expected parameter.
+    pub vtype: ExprType,
+
+    /// Value to push onto the stack when the parameter is missing.  Must be of type `vtype`.
+    pub default: Value,
+}
+
 /// Details to describe the type of a repeated parameter.
 #[derive(Clone, Debug)]
 pub enum RepeatedTypeSyntax {
@@ -80,8 +116,16 @@ pub enum RepeatedTypeSyntax {
     /// Expects a value of the given type.
     TypedValue(ExprType),
 
+    /// Expects a numeric value, which can be an integer or a double.  The values pushed onto the
+    /// stack are followed by their `ValueTag` so that the callable can tell them apart.
+    NumericValue,
+
     /// Expects a reference to a variable (not an array) and allows the variables to not be defined.
     VariableRef,
+
+    /// Expects a reference to a variable, which is allowed to not be defined, or a reference to an
+    /// array, which must be defined and be written as `name()`.
+    VariableOrArrayRef,
 }
 
 /// Details to compile a repeated parameter.
@@ -154,6 +198,16 @@ pub struct AnyValueSyntax {
     pub allow_missing: bool,
 }
 
+/// Details to compile a parameter of any numeric type.
+///
+/// The value is pushed onto the stack as is, followed by its `ValueTag` so that the callable can
+/// tell whether it received an integer or a double.
+#[derive(Clone, Debug)]
+pub struct NumericValueSyntax {
+    /// The name of the parameter for help purposes.
+    pub name: Cow<'static, str>,
+}
+
 /// Details to process an argument separator.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ArgSepSyntax {
@@ -209,11 +263,20 @@ pub enum SingularArgSyntax {
     /// A required reference.
     RequiredRef(RequiredRefSyntax, ArgSepSyntax),
 
+    /// A required scalar value passed by reference.
+    RequiredByRef(RequiredByRefSyntax, ArgSepSyntax),
+
     /// An optional scalar value.
     OptionalValue(OptionalValueSyntax, ArgSepSyntax),
 
+    /// An optional scalar value that takes a default value when missing.
+    DefaultValue(DefaultValueSyntax, ArgSepSyntax),
+
     /// A required scalar value of any type.
     AnyValue(AnyValueSyntax, ArgSepSyntax),
+
+    /// A required numeric value.
+    NumericValue(NumericValueSyntax, ArgSepSyntax),
 }
 
 /// Details to process the arguments of a callable.
@@ -253,7 +316,14 @@ impl CallableSyntax {
 
     /// Computes the range of the expected number of parameters for this syntax.
     fn expected_nargs(&self) -> RangeInclusive<usize> {
-        let mut min = self.singular.len();
+        let ndefaults = self
+            .singular
+            .iter()
+            .rev()
+            .take_while(|syn| matches!(syn, SingularArgSyntax::DefaultValue(..)))
+            .count();
+        debug_assert!(ndefaults == 0 || self.repeated.is_none());
+        let mut min = self.singular.len() - ndefaults;
         let mut max = self.singular.len();
         if let Some(syn) = self.repeated.as_ref() {
             if syn.require_one {
@@ -286,6 +356,13 @@ impl CallableSyntax {
                     sep
                 }
 
+                SingularArgSyntax::RequiredByRef(details, sep) => {
+                    description.push_str("BYREF ");
+                    description.push_str(&details.name);
+                    description.push(details.vtype.annotation());
+                    sep
+                }
+
                 SingularArgSyntax::OptionalValue(details, sep) => {
                     description.push('[');
                     description.push_str(&details.name);
@@ -294,6 +371,14 @@ impl CallableSyntax {
                     sep
                 }
 
+                SingularArgSyntax::DefaultValue(details, sep) => {
+                    description.push('[');
+                    description.push_str(&details.name);
+                    description.push(details.vtype.annotation());
+                    description.push(']');
+                    sep
+                }
+
                 SingularArgSyntax::AnyValue(details, sep) => {
                     if details.allow_missing {
                         description.push('[');
@@ -304,6 +389,11 @@ impl CallableSyntax {
                     }
                     sep
                 }
+
+                SingularArgSyntax::NumericValue(details, sep) => {
+                    description.push_str(&details.name);
+                    sep
+                }
             };
 
             if self.repeated.is_none() || i < self.singular.len() - 1 {
@@ -345,6 +435,7 @@ fn compile_required_ref(
                         return Err(Error::UndefinedSymbol(span.pos, key));
                     }
                     debug_assert!(!require_array);
+                    check_implicit_definition(&span.vref, span.pos)?;
 
                     let vtype = span.vref.ref_type().unwrap_or(ExprType::Integer);
 
@@ -401,7 +492,125 @@ fn compile_required_ref(
 
                     Err(Error::NotArrayOrFunction(span.pos, key))
                 }
+
+                Some(SymbolPrototype::Record(..)) => Err(Error::NotAReference(span.pos)),
+            }
+        }
+
+        Some(expr) => Err(Error::NotAReference(expr.start_pos())),
+
+        None => Err(Error::CallableSyntaxError(pos, md.clone())),
+    }
+}
+
+/// Generates the name of the hidden variable that holds the `index`th subscript of an array
+/// element passed by reference at `pos`.
+///
+/// The subscripts are saved so that the element written back when the callable returns is the same
+/// one that was passed in, even if the callable changes the variables used in the subscripts.
+fn byref_subscript_key(pos: LineCol, index: usize) -> SymbolKey {
+    SymbolKey::from(format!("0byref{}_{}_{}", pos.line, pos.col, index))
+}
+
+/// Compiles an argument that is passed by reference to a user-defined callable.
+///
+/// The value of the argument is pushed to the stack like any other value and the instructions to
+/// store the updated value back into the referenced variable or array element are appended to
+/// `writeback` for the caller to emit after the call.
+///
+/// Commands can receive references to undefined variables, in which case this returns the details
+/// for the symbol to insert into the symbols table as `compile_required_ref` does.
+fn compile_required_byref(
+    instrs: &mut Vec<Instruction>,
+    writeback: &mut Vec<Instruction>,
+    md: &CallableMetadata,
+    pos: LineCol,
+    symtable: &SymbolsTable,
+    vtype: ExprType,
+    expr: Option<Expr>,
+) -> Result<Option<(SymbolKey, SymbolPrototype)>> {
+    match expr {
+        Some(Expr::Symbol(span)) => {
+            let key = SymbolKey::from(span.vref.name());
+            let span_pos = span.pos;
+            match symtable.get(&key) {
+                Some(SymbolPrototype::Variable(etype)) => {
+                    let etype = *etype;
+                    compile_expr(instrs, symtable, Expr::Symbol(span), false)?;
+                    if etype != vtype {
+                        return Err(Error::TypeMismatch(span_pos, etype, vtype));
+                    }
+                    writeback.push(Instruction::Assign(key));
+                    Ok(None)
+                }
+
+                Some(SymbolPrototype::Array(..))
+                | Some(SymbolPrototype::Callable(..))
+                | Some(SymbolPrototype::Record(..)) => Err(Error::NotAReference(span_pos)),
+
+                None => {
+                    if md.is_function() {
+                        return Err(Error::UndefinedSymbol(span_pos, key));
+                    }
+                    check_implicit_definition(&span.vref, span_pos)?;
+
+                    let etype = span.vref.ref_type().unwrap_or(vtype);
+                    if etype != vtype {
+                        return Err(Error::TypeMismatch(span_pos, etype, vtype));
+                    }
+
+                    instrs.push(match vtype {
+                        ExprType::Boolean => Instruction::PushBoolean(false, span_pos),
+                        ExprType::Double => Instruction::PushDouble(0.0, span_pos),
+                        ExprType::Integer => Instruction::PushInteger(0, span_pos),
+                        ExprType::Text => Instruction::PushString(String::new(), span_pos),
+                    });
+                    writeback.push(Instruction::Assign(key.clone()));
+                    Ok(Some((key, SymbolPrototype::Variable(vtype))))
+                }
+            }
+        }
+
+        Some(Expr::Call(span)) => {
+            let key = SymbolKey::from(span.vref.name());
+            let (etype, dims) = match symtable.get(&key) {
+                Some(SymbolPrototype::Array(etype, dims)) => (*etype, *dims),
+                _ => return Err(Error::NotAReference(span.vref_pos)),
+            };
+
+            if !span.vref.accepts(etype) {
+                return Err(Error::IncompatibleTypeAnnotationInReference(span.vref_pos, span.vref));
+            }
+            if etype != vtype {
+                return Err(Error::TypeMismatch(span.vref_pos, etype, vtype));
+            }
+
+            let nargs = span.args.len();
+            compile_array_indices(
+                instrs,
+                symtable,
+                dims,
+                argspans_to_exprs(span.args),
+                &key,
+                span.vref_pos,
+            )?;
+            let vref_pos = span.vref_pos;
+            let keys: Vec<SymbolKey> =
+                (0..nargs).map(|i| byref_subscript_key(vref_pos, i)).collect();
+            for key in &keys {
+                instrs.push(Instruction::Assign(key.clone()));
+            }
+
+            for key in keys.iter().rev() {
+                instrs.push(Instruction::LoadInteger(key.clone(), span.vref_pos));
+                writeback.push(Instruction::LoadInteger(key.clone(), span.vref_pos));
             }
+            instrs.push(Instruction::ArrayLoad(key.clone(), span.vref_pos, nargs));
+            writeback.push(Instruction::ArrayAssignment(key, span.vref_pos, nargs));
+            for key in keys {
+                writeback.push(Instruction::Unset(UnsetISpan { name: key, pos: span.vref_pos }));
+            }
+            Ok(None)
         }
 
         Some(expr) => Err(Error::NotAReference(expr.start_pos())),
@@ -410,6 +619,31 @@ fn compile_required_ref(
     }
 }
 
+/// Compiles a numeric argument given in `expr`, pushing its value followed by its tag, and returns
+/// the type of the value.
+fn compile_numeric_value(
+    instrs: &mut Vec<Instruction>,
+    symtable: &SymbolsTable,
+    expr: Expr,
+) -> Result<ExprType> {
+    let pos = expr.start_pos();
+    let etype = compile_expr(instrs, symtable, expr, false)?;
+    if !etype.is_numerical() {
+        return Err(Error::NotANumber(pos, etype));
+    }
+    instrs.push(Instruction::PushInteger(ValueTag::from(etype) as i32, pos));
+    Ok(etype)
+}
+
+/// Computes the type that results from promoting the numeric types `current` and `etype`, where
+/// `current` is unknown if no numeric arguments have been seen yet.
+fn promote_numeric(current: Option<ExprType>, etype: ExprType) -> Option<ExprType> {
+    match (current, etype) {
+        (Some(ExprType::Double), _) | (_, ExprType::Double) => Some(ExprType::Double),
+        (_, etype) => Some(etype),
+    }
+}
+
 /// Locates the syntax definition that can parse the given number of arguments.
 ///
 /// Panics if more than one syntax definition applies.
@@ -470,7 +704,7 @@ fn compile_syn_argsep(
                 if sep != *exp_sep1 && sep != *exp_sep2 {
                     return Err(Error::CallableSyntaxError(pos, md.clone()));
                 }
-                instrs.insert(sep_tag_pc, Instruction::PushInteger(sep as i32, sep_pos));
+                insert_instr(instrs, sep_tag_pc, Instruction::PushInteger(sep as i32, sep_pos));
                 Ok(1)
             }
         }
@@ -482,17 +716,31 @@ fn compile_syn_argsep(
     }
 }
 
+/// Generates the instruction to push the `default` value of an omitted argument at `pos`.
+fn push_default_value(default: &Value, pos: LineCol) -> Instruction {
+    match default {
+        Value::Boolean(b) => Instruction::PushBoolean(*b, pos),
+        Value::Double(d) => Instruction::PushDouble(*d, pos),
+        Value::Integer(i) => Instruction::PushInteger(*i, pos),
+        Value::Text(s) => Instruction::PushString(s.clone(), pos),
+        Value::VarRef(..) | Value::Empty(..) => unreachable!("Default values must be scalars"),
+    }
+}
+
 /// Parses the arguments to a command or a function and generates expressions to compute them.
 ///
 /// Returns the number of arguments that the instructions added to `instrs` will push into the
-/// stack and returns the list of new symbols that need to be inserted into `symtable`.
+/// stack, the list of new symbols that need to be inserted into `symtable`, the instructions
+/// that must be emitted right after the call to store the values of arguments passed by reference,
+/// and the type that results from promoting all numeric arguments, if there were any.
+#[allow(clippy::type_complexity)]
 fn compile_args(
     md: &CallableMetadata,
     instrs: &mut Vec<Instruction>,
     symtable: &SymbolsTable,
     pos: LineCol,
     args: Vec<ArgSpan>,
-) -> Result<(usize, Vec<(SymbolKey, SymbolPrototype)>)> {
+) -> Result<(usize, Vec<(SymbolKey, SymbolPrototype)>, Vec<Instruction>, Option<ExprType>)> {
     let syntax = find_syntax(md, pos, args.len())?;
 
     let input_nargs = args.len();
@@ -500,6 +748,8 @@ fn compile_args(
 
     let mut nargs = 0;
     let mut to_insert = vec![];
+    let mut writebacks = vec![];
+    let mut numeric_type = None;
 
     let mut remaining;
     if let Some(syn) = syntax.repeated.as_ref() {
@@ -511,7 +761,11 @@ fn compile_args(
             return Err(Error::CallableSyntaxError(pos, md.clone()));
         }
 
-        let need_tags = syn.allow_missing || matches!(syn.type_syn, RepeatedTypeSyntax::AnyValue);
+        let need_tags = syn.allow_missing
+            || matches!(
+                syn.type_syn,
+                RepeatedTypeSyntax::AnyValue | RepeatedTypeSyntax::NumericValue
+            );
 
         remaining = input_nargs;
         while remaining > syntax.singular.len() {
@@ -547,6 +801,32 @@ fn compile_args(
                             nargs += 1;
                         }
 
+                        RepeatedTypeSyntax::VariableOrArrayRef => {
+                            let (expr, require_array) = match expr {
+                                Expr::Call(span) if span.args.is_empty() => (
+                                    Expr::Symbol(SymbolSpan {
+                                        vref: span.vref,
+                                        pos: span.vref_pos,
+                                    }),
+                                    true,
+                                ),
+                                expr => (expr, false),
+                            };
+                            let to_insert_one = compile_required_ref(
+                                instrs,
+                                md,
+                                pos,
+                                symtable,
+                                require_array,
+                                !require_array,
+                                Some(expr),
+                            )?;
+                            if let Some(to_insert_one) = to_insert_one {
+                                to_insert.push(to_insert_one);
+                            }
+                            nargs += 1;
+                        }
+
                         RepeatedTypeSyntax::TypedValue(vtype) => {
                             compile_expr_as_type(instrs, symtable, expr, vtype)?;
                             if need_tags {
@@ -559,6 +839,13 @@ fn compile_args(
                                 nargs += 1;
                             }
                         }
+
+                        RepeatedTypeSyntax::NumericValue => {
+                            debug_assert!(need_tags);
+                            let etype = compile_numeric_value(instrs, symtable, expr)?;
+                            numeric_type = promote_numeric(numeric_type, etype);
+                            nargs += 2;
+                        }
                     }
                 }
                 None => {
@@ -588,6 +875,18 @@ fn compile_args(
     }
 
     for syn in syntax.singular.iter().rev() {
+        if remaining > input_nargs {
+            match syn {
+                SingularArgSyntax::DefaultValue(details, _sep) => {
+                    instrs.push(push_default_value(&details.default, pos));
+                    nargs += 1;
+                }
+                _ => unreachable!("Only trailing parameters can be missing"),
+            }
+            remaining -= 1;
+            continue;
+        }
+
         let span = aiter.next().expect("Args and their syntax must advance in unison");
 
         let sep_tag_pc = instrs.len();
@@ -621,6 +920,25 @@ fn compile_args(
                 sep
             }
 
+            SingularArgSyntax::RequiredByRef(details, sep) => {
+                let mut one_writeback = vec![];
+                let to_insert_one = compile_required_byref(
+                    instrs,
+                    &mut one_writeback,
+                    md,
+                    pos,
+                    symtable,
+                    details.vtype,
+                    span.expr,
+                )?;
+                if let Some(to_insert_one) = to_insert_one {
+                    to_insert.push(to_insert_one);
+                }
+                writebacks.push(one_writeback);
+                nargs += 1;
+                sep
+            }
+
             SingularArgSyntax::OptionalValue(details, sep) => {
                 let (tag, pos) = match span.expr {
                     Some(expr) => {
@@ -636,6 +954,17 @@ fn compile_args(
                 sep
             }
 
+            SingularArgSyntax::DefaultValue(details, sep) => {
+                match span.expr {
+                    Some(expr) => {
+                        compile_expr_as_type(instrs, symtable, expr, details.vtype)?;
+                    }
+                    None => instrs.push(push_default_value(&details.default, span.sep_pos)),
+                }
+                nargs += 1;
+                sep
+            }
+
             SingularArgSyntax::AnyValue(details, sep) => {
                 let (tag, pos) = match span.expr {
                     Some(expr) => {
@@ -655,6 +984,18 @@ fn compile_args(
                 instrs.push(Instruction::PushInteger(tag as i32, pos));
                 sep
             }
+
+            SingularArgSyntax::NumericValue(_details, sep) => {
+                match span.expr {
+                    Some(expr) => {
+                        let etype = compile_numeric_value(instrs, symtable, expr)?;
+                        numeric_type = promote_numeric(numeric_type, etype);
+                        nargs += 2;
+                    }
+                    None => return Err(Error::CallableSyntaxError(pos, md.clone())),
+                }
+                sep
+            }
         };
 
         nargs += compile_syn_argsep(
@@ -671,43 +1012,56 @@ fn compile_args(
         remaining -= 1;
     }
 
-    Ok((nargs, to_insert))
+    // The callable returns the values of by-reference parameters with the first one at the top of
+    // the stack, but we compiled the arguments from last to first.
+    let writeback = writebacks.into_iter().rev().flatten().collect();
+
+    Ok((nargs, to_insert, writeback, numeric_type))
 }
 
 /// Parses the arguments to a buitin command and generates expressions to compute them.
 ///
 /// This can be used to help the runtime by doing type checking during compilation and then
 /// allowing the runtime to assume that the values on the stack are correctly typed.
+///
+/// Returns the number of arguments pushed to the stack and the instructions to emit after the call
+/// to store the values of arguments passed by reference.
 pub(super) fn compile_command_args(
     md: &CallableMetadata,
     instrs: &mut Vec<Instruction>,
     symtable: &mut SymbolsTable,
     pos: LineCol,
     args: Vec<ArgSpan>,
-) -> Result<usize> {
-    let (nargs, to_insert) = compile_args(md, instrs, symtable, pos, args)?;
+) -> Result<(usize, Vec<Instruction>)> {
+    let (nargs, to_insert, writeback, _numeric_type) =
+        compile_args(md, instrs, symtable, pos, args)?;
     for (key, proto) in to_insert {
         if !symtable.contains_key(&key) {
             symtable.insert(key, proto);
         }
     }
-    Ok(nargs)
+    Ok((nargs, writeback))
 }
 
 /// Parses the arguments to a function and generates expressions to compute them.
 ///
 /// This can be used to help the runtime by doing type checking during compilation and then
 /// allowing the runtime to assume that the values on the stack are correctly typed.
+///
+/// Returns the number of arguments pushed to the stack, the instructions to emit after the call
+/// to store the values of arguments passed by reference, and the type that results from promoting
+/// all numeric arguments, if there were any.
 pub(super) fn compile_function_args(
     md: &CallableMetadata,
     instrs: &mut Vec<Instruction>,
     symtable: &SymbolsTable,
     pos: LineCol,
     args: Vec<ArgSpan>,
-) -> Result<usize> {
-    let (nargs, to_insert) = compile_args(md, instrs, symtable, pos, args)?;
+) -> Result<(usize, Vec<Instruction>, Option<ExprType>)> {
+    let (nargs, to_insert, writeback, numeric_type) =
+        compile_args(md, instrs, symtable, pos, args)?;
     debug_assert!(to_insert.is_empty());
-    Ok(nargs)
+    Ok((nargs, writeback, numeric_type))
 }
 
 #[cfg(test)]
@@ -762,7 +1116,7 @@ mod testutils {
                 result,
                 instrs,
                 symtable: self.symtable,
-                exp_result: Ok(0),
+                exp_result: Ok((0, vec![])),
                 exp_instrs: vec![Instruction::Nop],
                 exp_vars: HashMap::default(),
             }
@@ -772,10 +1126,10 @@ mod testutils {
     /// Builder pattern to validate expectations in a test scenario.
     #[must_use]
     pub(super) struct Checker {
-        result: Result<usize>,
+        result: Result<(usize, Vec<Instruction>)>,
         instrs: Vec<Instruction>,
         symtable: SymbolsTable,
-        exp_result: Result<usize>,
+        exp_result: Result<(usize, Vec<Instruction>)>,
         exp_instrs: Vec<Instruction>,
         exp_vars: HashMap<SymbolKey, ExprType>,
     }
@@ -783,7 +1137,18 @@ mod testutils {
     impl Checker {
         /// Expects the compilation to succeeded and produce `nargs` arguments.
         pub(super) fn exp_nargs(mut self, nargs: usize) -> Self {
-            self.exp_result = Ok(nargs);
+            self.exp_result = Ok((nargs, vec![]));
+            self
+        }
+
+        /// Expects the compilation to succeed and produce `nargs` arguments that need the
+        /// `writeback` instructions after the call.
+        pub(super) fn exp_nargs_with_writeback<I: Into<Vec<Instruction>>>(
+            mut self,
+            nargs: usize,
+            writeback: I,
+        ) -> Self {
+            self.exp_result = Ok((nargs, writeback.into()));
             self
         }
 
@@ -886,6 +1251,21 @@ mod description_tests {
         );
     }
 
+    #[test]
+    fn test_singular_required_byref() {
+        assert_eq!(
+            "BYREF the-arg#",
+            CallableSyntax::new_static(
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("the-arg"), vtype: ExprType::Double },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .describe()
+        );
+    }
+
     #[test]
     fn test_singular_optional_value() {
         assert_eq!(
@@ -1082,6 +1462,27 @@ mod description_tests {
             .describe()
         );
     }
+
+    #[test]
+    fn test_singular_and_repeated_numeric() {
+        assert_eq!(
+            "x, rep1[, .., repN]",
+            CallableSyntax::new_static(
+                &[SingularArgSyntax::NumericValue(
+                    NumericValueSyntax { name: Cow::Borrowed("x") },
+                    ArgSepSyntax::Exactly(ArgSep::Long),
+                )],
+                Some(&RepeatedSyntax {
+                    name: Cow::Borrowed("rep"),
+                    type_syn: RepeatedTypeSyntax::NumericValue,
+                    sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                    require_one: true,
+                    allow_missing: false,
+                }),
+            )
+            .describe()
+        );
+    }
 }
 
 #[cfg(test)]
@@ -1526,75 +1927,346 @@ mod compile_tests {
     }
 
     #[test]
-    fn test_one_optional_value_ok_is_present() {
+    fn test_one_required_byref_variable_ok() {
         Tester::default()
+            .symbol("foo", SymbolPrototype::Variable(ExprType::Double))
             .syntax(
-                &[SingularArgSyntax::OptionalValue(
-                    OptionalValueSyntax {
-                        name: Cow::Borrowed("ref"),
-                        vtype: ExprType::Double,
-                        missing_value: 10,
-                        present_value: 20,
-                    },
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Double },
                     ArgSepSyntax::End,
                 )],
                 None,
             )
             .compile_command([ArgSpan {
-                expr: Some(Expr::Double(DoubleSpan { value: 3.0, pos: lc(1, 2) })),
+                expr: Some(Expr::Symbol(SymbolSpan {
+                    vref: VarRef::new("foo", None),
+                    pos: lc(1, 2),
+                })),
                 sep: ArgSep::End,
                 sep_pos: lc(1, 5),
             }])
-            .exp_instr(Instruction::PushDouble(3.0, lc(1, 2)))
-            .exp_instr(Instruction::PushInteger(20, lc(1, 2)))
-            .exp_nargs(2)
+            .exp_instr(Instruction::LoadDouble(SymbolKey::from("foo"), lc(1, 2)))
+            .exp_nargs_with_writeback(1, [Instruction::Assign(SymbolKey::from("foo"))])
             .check();
     }
 
     #[test]
-    fn test_one_optional_value_ok_is_missing() {
+    fn test_one_required_byref_variable_define_undefined() {
         Tester::default()
             .syntax(
-                &[SingularArgSyntax::OptionalValue(
-                    OptionalValueSyntax {
-                        name: Cow::Borrowed("ref"),
-                        vtype: ExprType::Double,
-                        missing_value: 10,
-                        present_value: 20,
-                    },
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Text },
                     ArgSepSyntax::End,
                 )],
                 None,
             )
-            .compile_command([ArgSpan { expr: None, sep: ArgSep::End, sep_pos: lc(1, 2) }])
-            .exp_instr(Instruction::PushInteger(10, lc(1, 2)))
-            .exp_nargs(1)
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Symbol(SymbolSpan {
+                    vref: VarRef::new("foo", None),
+                    pos: lc(1, 2),
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_instr(Instruction::PushString(String::new(), lc(1, 2)))
+            .exp_nargs_with_writeback(1, [Instruction::Assign(SymbolKey::from("foo"))])
+            .exp_symbol("foo", ExprType::Text)
             .check();
     }
 
     #[test]
-    fn test_multiple_any_value_ok() {
+    fn test_one_required_byref_array_element_ok() {
         Tester::default()
+            .symbol("foo", SymbolPrototype::Array(ExprType::Integer, 2))
+            .symbol("i", SymbolPrototype::Variable(ExprType::Integer))
             .syntax(
-                &[
-                    SingularArgSyntax::AnyValue(
-                        AnyValueSyntax { name: Cow::Borrowed("arg1"), allow_missing: false },
-                        ArgSepSyntax::Exactly(ArgSep::Long),
-                    ),
-                    SingularArgSyntax::AnyValue(
-                        AnyValueSyntax { name: Cow::Borrowed("arg2"), allow_missing: false },
-                        ArgSepSyntax::Exactly(ArgSep::Long),
-                    ),
-                    SingularArgSyntax::AnyValue(
-                        AnyValueSyntax { name: Cow::Borrowed("arg3"), allow_missing: false },
-                        ArgSepSyntax::Exactly(ArgSep::Long),
-                    ),
-                    SingularArgSyntax::AnyValue(
-                        AnyValueSyntax { name: Cow::Borrowed("arg4"), allow_missing: false },
-                        ArgSepSyntax::End,
-                    ),
-                ],
-                None,
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Call(CallSpan {
+                    vref: VarRef::new("foo", None),
+                    vref_pos: lc(1, 2),
+                    args: vec![
+                        ArgSpan {
+                            expr: Some(Expr::Symbol(SymbolSpan {
+                                vref: VarRef::new("i", None),
+                                pos: lc(1, 6),
+                            })),
+                            sep: ArgSep::Long,
+                            sep_pos: lc(1, 7),
+                        },
+                        ArgSpan {
+                            expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 9) })),
+                            sep: ArgSep::End,
+                            sep_pos: lc(1, 10),
+                        },
+                    ],
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 11),
+            }])
+            .exp_instr(Instruction::PushInteger(3, lc(1, 9)))
+            .exp_instr(Instruction::LoadInteger(SymbolKey::from("i"), lc(1, 6)))
+            .exp_instr(Instruction::Assign(SymbolKey::from("0byref1_2_0")))
+            .exp_instr(Instruction::Assign(SymbolKey::from("0byref1_2_1")))
+            .exp_instr(Instruction::LoadInteger(SymbolKey::from("0byref1_2_1"), lc(1, 2)))
+            .exp_instr(Instruction::LoadInteger(SymbolKey::from("0byref1_2_0"), lc(1, 2)))
+            .exp_instr(Instruction::ArrayLoad(SymbolKey::from("foo"), lc(1, 2), 2))
+            .exp_nargs_with_writeback(
+                1,
+                [
+                    Instruction::LoadInteger(SymbolKey::from("0byref1_2_1"), lc(1, 2)),
+                    Instruction::LoadInteger(SymbolKey::from("0byref1_2_0"), lc(1, 2)),
+                    Instruction::ArrayAssignment(SymbolKey::from("foo"), lc(1, 2), 2),
+                    Instruction::Unset(UnsetISpan {
+                        name: SymbolKey::from("0byref1_2_0"),
+                        pos: lc(1, 2),
+                    }),
+                    Instruction::Unset(UnsetISpan {
+                        name: SymbolKey::from("0byref1_2_1"),
+                        pos: lc(1, 2),
+                    }),
+                ],
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_one_required_byref_disallow_value() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Integer(IntegerSpan { value: 5, pos: lc(1, 2) })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_error(Error::NotAReference(lc(1, 2)))
+            .check();
+    }
+
+    #[test]
+    fn test_one_required_byref_disallow_whole_array() {
+        Tester::default()
+            .symbol("foo", SymbolPrototype::Array(ExprType::Integer, 1))
+            .syntax(
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Symbol(SymbolSpan {
+                    vref: VarRef::new("foo", None),
+                    pos: lc(1, 2),
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_error(Error::NotAReference(lc(1, 2)))
+            .check();
+    }
+
+    #[test]
+    fn test_one_required_byref_wrong_type() {
+        Tester::default()
+            .symbol("foo", SymbolPrototype::Variable(ExprType::Integer))
+            .syntax(
+                &[SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name: Cow::Borrowed("ref"), vtype: ExprType::Double },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Symbol(SymbolSpan {
+                    vref: VarRef::new("foo", None),
+                    pos: lc(1, 2),
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_error(Error::TypeMismatch(lc(1, 2), ExprType::Integer, ExprType::Double))
+            .check();
+    }
+
+    #[test]
+    fn test_one_optional_value_ok_is_present() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::OptionalValue(
+                    OptionalValueSyntax {
+                        name: Cow::Borrowed("ref"),
+                        vtype: ExprType::Double,
+                        missing_value: 10,
+                        present_value: 20,
+                    },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Double(DoubleSpan { value: 3.0, pos: lc(1, 2) })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_instr(Instruction::PushDouble(3.0, lc(1, 2)))
+            .exp_instr(Instruction::PushInteger(20, lc(1, 2)))
+            .exp_nargs(2)
+            .check();
+    }
+
+    #[test]
+    fn test_one_optional_value_ok_is_missing() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::OptionalValue(
+                    OptionalValueSyntax {
+                        name: Cow::Borrowed("ref"),
+                        vtype: ExprType::Double,
+                        missing_value: 10,
+                        present_value: 20,
+                    },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan { expr: None, sep: ArgSep::End, sep_pos: lc(1, 2) }])
+            .exp_instr(Instruction::PushInteger(10, lc(1, 2)))
+            .exp_nargs(1)
+            .check();
+    }
+
+    #[test]
+    fn test_default_value_ok_is_present() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::DefaultValue(
+                    DefaultValueSyntax {
+                        name: Cow::Borrowed("arg"),
+                        vtype: ExprType::Double,
+                        default: Value::Double(1.5),
+                    },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 2) })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_instr(Instruction::PushInteger(3, lc(1, 2)))
+            .exp_instr(Instruction::IntegerToDouble)
+            .exp_nargs(1)
+            .check();
+    }
+
+    #[test]
+    fn test_default_value_ok_is_missing() {
+        Tester::default()
+            .syntax(
+                &[
+                    SingularArgSyntax::DefaultValue(
+                        DefaultValueSyntax {
+                            name: Cow::Borrowed("arg1"),
+                            vtype: ExprType::Integer,
+                            default: Value::Integer(7),
+                        },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    ),
+                    SingularArgSyntax::DefaultValue(
+                        DefaultValueSyntax {
+                            name: Cow::Borrowed("arg2"),
+                            vtype: ExprType::Boolean,
+                            default: Value::Boolean(true),
+                        },
+                        ArgSepSyntax::End,
+                    ),
+                ],
+                None,
+            )
+            .compile_command([
+                ArgSpan { expr: None, sep: ArgSep::Long, sep_pos: lc(1, 2) },
+                ArgSpan {
+                    expr: Some(Expr::Boolean(BooleanSpan { value: false, pos: lc(1, 4) })),
+                    sep: ArgSep::End,
+                    sep_pos: lc(1, 9),
+                },
+            ])
+            .exp_instr(Instruction::PushBoolean(false, lc(1, 4)))
+            .exp_instr(Instruction::PushInteger(7, lc(1, 2)))
+            .exp_nargs(2)
+            .check();
+    }
+
+    #[test]
+    fn test_default_value_ok_is_omitted() {
+        Tester::default()
+            .syntax(
+                &[
+                    SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("arg1"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    ),
+                    SingularArgSyntax::DefaultValue(
+                        DefaultValueSyntax {
+                            name: Cow::Borrowed("arg2"),
+                            vtype: ExprType::Integer,
+                            default: Value::Integer(7),
+                        },
+                        ArgSepSyntax::End,
+                    ),
+                ],
+                None,
+            )
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 2) })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_instr(Instruction::PushInteger(7, lc(1000, 2000)))
+            .exp_instr(Instruction::PushInteger(3, lc(1, 2)))
+            .exp_nargs(2)
+            .check();
+    }
+
+    #[test]
+    fn test_multiple_any_value_ok() {
+        Tester::default()
+            .syntax(
+                &[
+                    SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("arg1"), allow_missing: false },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    ),
+                    SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("arg2"), allow_missing: false },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    ),
+                    SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("arg3"), allow_missing: false },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    ),
+                    SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("arg4"), allow_missing: false },
+                        ArgSepSyntax::End,
+                    ),
+                ],
+                None,
             )
             .compile_command([
                 ArgSpan {
@@ -1974,6 +2646,100 @@ mod compile_tests {
             .check();
     }
 
+    #[test]
+    fn test_repeated_variable_or_array_ref_ok() {
+        Tester::default()
+            .symbol("arr", SymbolPrototype::Array(ExprType::Double, 2))
+            .syntax(
+                &[],
+                Some(&RepeatedSyntax {
+                    name: Cow::Borrowed("arg"),
+                    type_syn: RepeatedTypeSyntax::VariableOrArrayRef,
+                    sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                    allow_missing: false,
+                    require_one: true,
+                }),
+            )
+            .compile_command([
+                ArgSpan {
+                    expr: Some(Expr::Symbol(SymbolSpan {
+                        vref: VarRef::new("foo", Some(ExprType::Text)),
+                        pos: lc(1, 2),
+                    })),
+                    sep: ArgSep::Long,
+                    sep_pos: lc(1, 6),
+                },
+                ArgSpan {
+                    expr: Some(Expr::Call(CallSpan {
+                        vref: VarRef::new("arr", None),
+                        vref_pos: lc(1, 8),
+                        args: vec![],
+                    })),
+                    sep: ArgSep::End,
+                    sep_pos: lc(1, 13),
+                },
+            ])
+            .exp_instr(Instruction::LoadRef(SymbolKey::from("arr"), ExprType::Double, lc(1, 8)))
+            .exp_instr(Instruction::LoadRef(SymbolKey::from("foo"), ExprType::Text, lc(1, 2)))
+            .exp_nargs(2)
+            .exp_symbol("foo", ExprType::Text)
+            .check();
+    }
+
+    #[test]
+    fn test_repeated_variable_or_array_ref_errors() {
+        const SYN: RepeatedSyntax = RepeatedSyntax {
+            name: Cow::Borrowed("arg"),
+            type_syn: RepeatedTypeSyntax::VariableOrArrayRef,
+            sep: ArgSepSyntax::Exactly(ArgSep::Long),
+            allow_missing: false,
+            require_one: true,
+        };
+
+        Tester::default()
+            .syntax(&[], Some(&SYN))
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Call(CallSpan {
+                    vref: VarRef::new("arr", None),
+                    vref_pos: lc(1, 2),
+                    args: vec![],
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 7),
+            }])
+            .exp_error(Error::UndefinedSymbol(lc(1, 2), SymbolKey::from("arr")))
+            .check();
+
+        Tester::default()
+            .symbol("arr", SymbolPrototype::Array(ExprType::Double, 1))
+            .syntax(&[], Some(&SYN))
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Symbol(SymbolSpan {
+                    vref: VarRef::new("arr", None),
+                    pos: lc(1, 2),
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 5),
+            }])
+            .exp_error(Error::NotAReference(lc(1, 2)))
+            .check();
+
+        Tester::default()
+            .symbol("foo", SymbolPrototype::Variable(ExprType::Integer))
+            .syntax(&[], Some(&SYN))
+            .compile_command([ArgSpan {
+                expr: Some(Expr::Call(CallSpan {
+                    vref: VarRef::new("foo", None),
+                    vref_pos: lc(1, 2),
+                    args: vec![],
+                })),
+                sep: ArgSep::End,
+                sep_pos: lc(1, 7),
+            }])
+            .exp_error(Error::NotAReference(lc(1, 2)))
+            .check();
+    }
+
     #[test]
     fn test_repeated_oneof_separator() {
         Tester::default()
@@ -2013,6 +2779,63 @@ mod compile_tests {
             .check();
     }
 
+    #[test]
+    fn test_repeated_oneof_separator_relocates_expr_jumps() {
+        let iif_arg = |expr, sep, sep_pos| ArgSpan { expr: Some(expr), sep, sep_pos };
+        Tester::default()
+            .syntax(
+                &[],
+                Some(&RepeatedSyntax {
+                    name: Cow::Borrowed("arg"),
+                    type_syn: RepeatedTypeSyntax::TypedValue(ExprType::Integer),
+                    sep: ArgSepSyntax::OneOf(ArgSep::Long, ArgSep::Short),
+                    allow_missing: false,
+                    require_one: false,
+                }),
+            )
+            .compile_command([
+                ArgSpan {
+                    expr: Some(Expr::Call(CallSpan {
+                        vref: VarRef::new("IIF", None),
+                        vref_pos: lc(1, 2),
+                        args: vec![
+                            iif_arg(
+                                Expr::Boolean(BooleanSpan { value: true, pos: lc(1, 3) }),
+                                ArgSep::Long,
+                                lc(1, 4),
+                            ),
+                            iif_arg(
+                                Expr::Integer(IntegerSpan { value: 1, pos: lc(1, 5) }),
+                                ArgSep::Long,
+                                lc(1, 6),
+                            ),
+                            iif_arg(
+                                Expr::Integer(IntegerSpan { value: 2, pos: lc(1, 7) }),
+                                ArgSep::End,
+                                lc(1, 8),
+                            ),
+                        ],
+                    })),
+                    sep: ArgSep::Short,
+                    sep_pos: lc(1, 9),
+                },
+                ArgSpan {
+                    expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 10) })),
+                    sep: ArgSep::End,
+                    sep_pos: lc(1, 11),
+                },
+            ])
+            .exp_instr(Instruction::PushInteger(3, lc(1, 10)))
+            .exp_instr(Instruction::PushInteger(ArgSep::Short as i32, lc(1, 9)))
+            .exp_instr(Instruction::PushBoolean(true, lc(1, 3)))
+            .exp_instr(Instruction::ExprJumpIfNotTrue(7))
+            .exp_instr(Instruction::PushInteger(1, lc(1, 5)))
+            .exp_instr(Instruction::ExprJump(8))
+            .exp_instr(Instruction::PushInteger(2, lc(1, 7)))
+            .exp_nargs(3)
+            .check();
+    }
+
     #[test]
     fn test_repeated_oneof_separator_and_missing_in_last_position() {
         Tester::default()
@@ -2130,4 +2953,71 @@ mod compile_tests {
             .exp_instr(Instruction::PushDouble(4.0, lc(1, 2)))
             .check();
     }
+    #[test]
+    fn test_singular_and_repeated_numeric_ok() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::NumericValue(
+                    NumericValueSyntax { name: Cow::Borrowed("arg") },
+                    ArgSepSyntax::Exactly(ArgSep::Long),
+                )],
+                Some(&RepeatedSyntax {
+                    name: Cow::Borrowed("rep"),
+                    type_syn: RepeatedTypeSyntax::NumericValue,
+                    sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                    allow_missing: false,
+                    require_one: true,
+                }),
+            )
+            .compile_command([
+                ArgSpan {
+                    expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 2) })),
+                    sep: ArgSep::Long,
+                    sep_pos: lc(1, 3),
+                },
+                ArgSpan {
+                    expr: Some(Expr::Double(DoubleSpan { value: 2.0, pos: lc(1, 4) })),
+                    sep: ArgSep::End,
+                    sep_pos: lc(1, 5),
+                },
+            ])
+            .exp_instr(Instruction::PushDouble(2.0, lc(1, 4)))
+            .exp_instr(Instruction::PushInteger(ValueTag::Double as i32, lc(1, 4)))
+            .exp_instr(Instruction::PushInteger(3, lc(1, 2)))
+            .exp_instr(Instruction::PushInteger(ValueTag::Integer as i32, lc(1, 2)))
+            .exp_nargs(4)
+            .check();
+    }
+
+    #[test]
+    fn test_singular_and_repeated_numeric_not_a_number() {
+        Tester::default()
+            .syntax(
+                &[SingularArgSyntax::NumericValue(
+                    NumericValueSyntax { name: Cow::Borrowed("arg") },
+                    ArgSepSyntax::Exactly(ArgSep::Long),
+                )],
+                Some(&RepeatedSyntax {
+                    name: Cow::Borrowed("rep"),
+                    type_syn: RepeatedTypeSyntax::NumericValue,
+                    sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                    allow_missing: false,
+                    require_one: true,
+                }),
+            )
+            .compile_command([
+                ArgSpan {
+                    expr: Some(Expr::Integer(IntegerSpan { value: 3, pos: lc(1, 2) })),
+                    sep: ArgSep::Long,
+                    sep_pos: lc(1, 3),
+                },
+                ArgSpan {
+                    expr: Some(Expr::Text(TextSpan { value: "foo".to_owned(), pos: lc(1, 4) })),
+                    sep: ArgSep::End,
+                    sep_pos: lc(1, 5),
+                },
+            ])
+            .exp_error(Error::NotANumber(lc(1, 4), ExprType::Text))
+            .check();
+    }
 }
@@ -15,32 +15,55 @@
 
 //! Functions to convert expressions into bytecode.
 
-use super::{Error, ExprType, Result, SymbolPrototype, SymbolsTable};
+use super::{check_implicit_definition, Error, ExprType, Result, SymbolPrototype, SymbolsTable};
 use crate::ast::*;
 use crate::bytecode::*;
-use crate::compiler::compile_function_args;
+use crate::compiler::{
+    compile_function_args, AnyValueSyntax, ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax,
+};
 use crate::parser::argspans_to_exprs;
 use crate::reader::LineCol;
-use crate::syms::SymbolKey;
+use crate::syms::{CallableMetadata, CallableMetadataBuilder, SymbolKey};
+use std::borrow::Cow;
 
-/// Compiles the indices used to address an array.
+/// Inserts `instr` into `instrs` at `pc` and adjusts the targets of the expression jumps that
+/// follow it.
+///
+/// `pc` must be the address where the compilation of an expression started, because then all
+/// jumps within that expression target addresses past `pc`.
+pub(super) fn insert_instr(instrs: &mut Vec<Instruction>, pc: Address, instr: Instruction) {
+    instrs.insert(pc, instr);
+    for instr in &mut instrs[pc + 1..] {
+        match instr {
+            Instruction::ExprJump(addr) | Instruction::ExprJumpIfNotTrue(addr) => *addr += 1,
+            _ => (),
+        }
+    }
+}
+
+/// Compiles the indices used to address the array `name`.
 pub(super) fn compile_array_indices(
     instrs: &mut Vec<Instruction>,
     symtable: &SymbolsTable,
     exp_nargs: usize,
     args: Vec<Expr>,
+    name: &SymbolKey,
     name_pos: LineCol,
 ) -> Result<()> {
     if exp_nargs != args.len() {
         return Err(Error::ArrayIndexSubscriptsError(name_pos, args.len(), exp_nargs));
     }
 
-    for arg in args.into_iter().rev() {
+    for (dimension, arg) in args.into_iter().enumerate().rev() {
         let arg_pos = arg.start_pos();
         match compile_expr(instrs, symtable, arg, false)? {
             ExprType::Integer => (),
             ExprType::Double => {
-                instrs.push(Instruction::DoubleToInteger);
+                instrs.push(Instruction::DoubleToSubscript(DoubleToSubscriptISpan {
+                    name: name.clone(),
+                    name_pos,
+                    dimension,
+                }));
             }
             itype => {
                 return Err(Error::NotANumber(arg_pos, itype));
@@ -346,6 +369,8 @@ fn compile_expr_symbol(
             }
         }
 
+        Some(SymbolPrototype::Record(..)) => return Err(Error::RecordAsValue(span.pos, key)),
+
         Some(SymbolPrototype::Callable(md)) => {
             let etype = match md.return_type() {
                 Some(etype) => etype,
@@ -358,8 +383,11 @@ fn compile_expr_symbol(
                 return Err(Error::CallableSyntaxError(span.pos, md.clone()));
             }
 
-            let nargs = compile_function_args(md, instrs, symtable, span.pos, vec![])?;
+            let (nargs, writeback, numeric_type) =
+                compile_function_args(md, instrs, symtable, span.pos, vec![])?;
             debug_assert_eq!(0, nargs, "Argless compiler must have returned zero arguments");
+            debug_assert!(writeback.is_empty());
+            debug_assert!(numeric_type.is_none());
             (Instruction::FunctionCall(key, etype, span.pos, 0), etype)
         }
     };
@@ -379,6 +407,8 @@ fn compile_expr_symbol_ref(
     let key = SymbolKey::from(span.vref.name());
     match symtable.get(&key) {
         None => {
+            check_implicit_definition(&span.vref, span.pos)?;
+
             let vtype = span.vref.ref_type().unwrap_or(ExprType::Integer);
 
             if !span.vref.accepts(vtype) {
@@ -408,6 +438,8 @@ fn compile_expr_symbol_ref(
 
             Err(Error::NotArrayOrFunction(span.pos, key))
         }
+
+        Some(SymbolPrototype::Record(..)) => Err(Error::NotAReference(span.pos)),
     }
 }
 
@@ -422,7 +454,7 @@ fn compile_array_ref(
 ) -> Result<ExprType> {
     let exprs = argspans_to_exprs(span.args);
     let nargs = exprs.len();
-    compile_array_indices(instrs, symtable, dimensions, exprs, span.vref_pos)?;
+    compile_array_indices(instrs, symtable, dimensions, exprs, &key, span.vref_pos)?;
 
     if !span.vref.accepts(vtype) {
         return Err(Error::IncompatibleTypeAnnotationInReference(span.vref_pos, span.vref));
@@ -431,6 +463,90 @@ fn compile_array_ref(
     Ok(vtype)
 }
 
+/// Returns the metadata of the `IIF` conditional expression for use in syntax errors.
+fn iif_metadata() -> CallableMetadata {
+    CallableMetadataBuilder::new("IIF")
+        .with_syntax(&[(
+            &[
+                SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name: Cow::Borrowed("cond"), vtype: ExprType::Boolean },
+                    ArgSepSyntax::Exactly(ArgSep::Long),
+                ),
+                SingularArgSyntax::AnyValue(
+                    AnyValueSyntax { name: Cow::Borrowed("iftrue"), allow_missing: false },
+                    ArgSepSyntax::Exactly(ArgSep::Long),
+                ),
+                SingularArgSyntax::AnyValue(
+                    AnyValueSyntax { name: Cow::Borrowed("iffalse"), allow_missing: false },
+                    ArgSepSyntax::End,
+                ),
+            ],
+            None,
+        )])
+        .test_build()
+}
+
+/// Compiles a call to the `IIF` conditional expression and appends its instructions to `instrs`.
+///
+/// Only the argument selected by the condition is evaluated at runtime.  The two arguments must
+/// have the same type, except that an integer is promoted to a double if the other one is a double.
+fn compile_iif(
+    instrs: &mut Vec<Instruction>,
+    symtable: &SymbolsTable,
+    span: CallSpan,
+) -> Result<ExprType> {
+    if span.args.len() != 3 {
+        return Err(Error::CallableSyntaxError(span.vref_pos, iif_metadata()));
+    }
+    let mut args = argspans_to_exprs(span.args).into_iter();
+    let (cond, iftrue, iffalse) =
+        (args.next().unwrap(), args.next().unwrap(), args.next().unwrap());
+
+    let cond_pos = cond.start_pos();
+    if compile_expr(instrs, symtable, cond, false)? != ExprType::Boolean {
+        return Err(Error::NotABooleanCondition(cond_pos, "IIF".to_owned()));
+    }
+    let jump_false_pc = instrs.len();
+    instrs.push(Instruction::Nop);
+
+    let true_type = compile_expr(instrs, symtable, iftrue, false)?;
+    let jump_end_pc = instrs.len();
+    instrs.push(Instruction::Nop);
+
+    instrs[jump_false_pc] = Instruction::ExprJumpIfNotTrue(instrs.len());
+    let false_pos = iffalse.start_pos();
+    let false_type = compile_expr(instrs, symtable, iffalse, false)?;
+
+    let vtype = match (true_type, false_type) {
+        (ExprType::Integer, ExprType::Double) => {
+            // The value of the true branch needs a cast, so place it after the false branch and
+            // make the false branch skip it.
+            let end_pc = instrs.len() + 2;
+            instrs.push(Instruction::ExprJump(end_pc));
+            instrs[jump_end_pc] = Instruction::ExprJump(instrs.len());
+            instrs.push(Instruction::IntegerToDouble);
+            ExprType::Double
+        }
+        (ExprType::Double, ExprType::Integer) => {
+            instrs.push(Instruction::IntegerToDouble);
+            instrs[jump_end_pc] = Instruction::ExprJump(instrs.len());
+            ExprType::Double
+        }
+        (true_type, false_type) if true_type == false_type => {
+            instrs[jump_end_pc] = Instruction::ExprJump(instrs.len());
+            true_type
+        }
+        (true_type, false_type) => {
+            return Err(Error::TypeMismatch(false_pos, false_type, true_type));
+        }
+    };
+
+    if !span.vref.accepts(vtype) {
+        return Err(Error::IncompatibleTypeAnnotationInReference(span.vref_pos, span.vref));
+    }
+    Ok(vtype)
+}
+
 /// Compiles the evaluation of an expression, appends its instructions to `instrs`, and returns
 /// the type of the compiled expression.
 ///
@@ -678,15 +794,33 @@ pub(super) fn compile_expr(
                     }
 
                     let span_pos = span.vref_pos;
-                    let nargs = compile_function_args(md, instrs, symtable, span_pos, span.args)?;
-                    instrs.push(Instruction::FunctionCall(key, vtype, span_pos, nargs));
-                    Ok(vtype)
+                    let ref_type = span.vref.ref_type();
+                    let (nargs, writeback, numeric_type) =
+                        compile_function_args(md, instrs, symtable, span_pos, span.args)?;
+                    let call_type = match numeric_type {
+                        Some(numeric_type) if md.numeric_promotion() => numeric_type,
+                        _ => vtype,
+                    };
+                    instrs.push(Instruction::FunctionCall(key, call_type, span_pos, nargs));
+                    instrs.extend(writeback);
+                    if ref_type.is_some() && call_type != vtype {
+                        // The caller explicitly asked for the declared return type via an
+                        // annotation, so honor it even if the arguments yielded a narrower one.
+                        debug_assert_eq!((ExprType::Integer, ExprType::Double), (call_type, vtype));
+                        instrs.push(Instruction::IntegerToDouble);
+                        return Ok(vtype);
+                    }
+                    Ok(call_type)
                 }
 
                 Some(SymbolPrototype::Variable(_)) => {
                     Err(Error::NotArrayOrFunction(span.vref_pos, key))
                 }
 
+                Some(SymbolPrototype::Record(..)) => Err(Error::RecordAsValue(span.vref_pos, key)),
+
+                None if key == SymbolKey::from("IIF") => compile_iif(instrs, symtable, span),
+
                 None => Err(Error::UndefinedSymbol(span.vref_pos, key)),
             }
         }
@@ -748,6 +882,7 @@ mod tests {
         testutils::*, ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredRefSyntax,
         RequiredValueSyntax, SingularArgSyntax,
     };
+    use crate::exec::ValueTag;
     use crate::syms::CallableMetadataBuilder;
     use std::borrow::Cow;
 
@@ -1181,7 +1316,14 @@ mod tests {
             .parse("i = FOO(3.8)")
             .compile()
             .expect_instr(0, Instruction::PushDouble(3.8, lc(1, 9)))
-            .expect_instr(1, Instruction::DoubleToInteger)
+            .expect_instr(
+                1,
+                Instruction::DoubleToSubscript(DoubleToSubscriptISpan {
+                    name: SymbolKey::from("foo"),
+                    name_pos: lc(1, 5),
+                    dimension: 0,
+                }),
+            )
             .expect_instr(2, Instruction::ArrayLoad(SymbolKey::from("foo"), lc(1, 5), 1))
             .expect_instr(3, Instruction::Assign(SymbolKey::from("i")))
             .check();
@@ -1258,6 +1400,162 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_expr_function_call_numeric_promotion() {
+        const SYNTAX: &[(&[SingularArgSyntax], Option<&RepeatedSyntax>)] = &[(
+            &[],
+            Some(&RepeatedSyntax {
+                name: Cow::Borrowed("expr"),
+                type_syn: RepeatedTypeSyntax::NumericValue,
+                sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                require_one: true,
+                allow_missing: false,
+            }),
+        )];
+
+        Tester::default()
+            .define_callable(
+                CallableMetadataBuilder::new("FOO")
+                    .with_return_type(ExprType::Double)
+                    .with_syntax(SYNTAX)
+                    .with_numeric_promotion(),
+            )
+            .parse("i = FOO(3)\nd = FOO(3, 2.5)\ne = FOO#(3)")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(3, lc(1, 9)))
+            .expect_instr(1, Instruction::PushInteger(ValueTag::Integer as i32, lc(1, 9)))
+            .expect_instr(
+                2,
+                Instruction::FunctionCall(SymbolKey::from("FOO"), ExprType::Integer, lc(1, 5), 2),
+            )
+            .expect_instr(3, Instruction::Assign(SymbolKey::from("i")))
+            .expect_instr(4, Instruction::PushDouble(2.5, lc(2, 12)))
+            .expect_instr(5, Instruction::PushInteger(ValueTag::Double as i32, lc(2, 12)))
+            .expect_instr(6, Instruction::PushInteger(3, lc(2, 9)))
+            .expect_instr(7, Instruction::PushInteger(ValueTag::Integer as i32, lc(2, 9)))
+            .expect_instr(
+                8,
+                Instruction::FunctionCall(SymbolKey::from("FOO"), ExprType::Double, lc(2, 5), 4),
+            )
+            .expect_instr(9, Instruction::Assign(SymbolKey::from("d")))
+            .expect_instr(10, Instruction::PushInteger(3, lc(3, 10)))
+            .expect_instr(11, Instruction::PushInteger(ValueTag::Integer as i32, lc(3, 10)))
+            .expect_instr(
+                12,
+                Instruction::FunctionCall(SymbolKey::from("FOO"), ExprType::Integer, lc(3, 5), 2),
+            )
+            .expect_instr(13, Instruction::IntegerToDouble)
+            .expect_instr(14, Instruction::Assign(SymbolKey::from("e")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_same_types() {
+        Tester::default()
+            .define("c", SymbolPrototype::Variable(ExprType::Boolean))
+            .parse("s = IIF(c, \"yes\", \"no\")")
+            .compile()
+            .expect_instr(0, Instruction::LoadBoolean(SymbolKey::from("c"), lc(1, 9)))
+            .expect_instr(1, Instruction::ExprJumpIfNotTrue(4))
+            .expect_instr(2, Instruction::PushString("yes".to_owned(), lc(1, 12)))
+            .expect_instr(3, Instruction::ExprJump(5))
+            .expect_instr(4, Instruction::PushString("no".to_owned(), lc(1, 19)))
+            .expect_instr(5, Instruction::Assign(SymbolKey::from("s")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_promotes_true_branch() {
+        Tester::default()
+            .parse("d = IIF(TRUE, 1, 2.5)")
+            .compile()
+            .expect_instr(0, Instruction::PushBoolean(true, lc(1, 9)))
+            .expect_instr(1, Instruction::ExprJumpIfNotTrue(4))
+            .expect_instr(2, Instruction::PushInteger(1, lc(1, 15)))
+            .expect_instr(3, Instruction::ExprJump(6))
+            .expect_instr(4, Instruction::PushDouble(2.5, lc(1, 18)))
+            .expect_instr(5, Instruction::ExprJump(7))
+            .expect_instr(6, Instruction::IntegerToDouble)
+            .expect_instr(7, Instruction::Assign(SymbolKey::from("d")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_promotes_false_branch() {
+        Tester::default()
+            .parse("d = IIF(FALSE, 1.5, 2)")
+            .compile()
+            .expect_instr(0, Instruction::PushBoolean(false, lc(1, 9)))
+            .expect_instr(1, Instruction::ExprJumpIfNotTrue(4))
+            .expect_instr(2, Instruction::PushDouble(1.5, lc(1, 16)))
+            .expect_instr(3, Instruction::ExprJump(6))
+            .expect_instr(4, Instruction::PushInteger(2, lc(1, 21)))
+            .expect_instr(5, Instruction::IntegerToDouble)
+            .expect_instr(6, Instruction::Assign(SymbolKey::from("d")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_nested() {
+        Tester::default()
+            .parse("i = IIF(TRUE, IIF(FALSE, 1, 2), 3)")
+            .compile()
+            .expect_instr(0, Instruction::PushBoolean(true, lc(1, 9)))
+            .expect_instr(1, Instruction::ExprJumpIfNotTrue(8))
+            .expect_instr(2, Instruction::PushBoolean(false, lc(1, 19)))
+            .expect_instr(3, Instruction::ExprJumpIfNotTrue(6))
+            .expect_instr(4, Instruction::PushInteger(1, lc(1, 26)))
+            .expect_instr(5, Instruction::ExprJump(7))
+            .expect_instr(6, Instruction::PushInteger(2, lc(1, 29)))
+            .expect_instr(7, Instruction::ExprJump(9))
+            .expect_instr(8, Instruction::PushInteger(3, lc(1, 33)))
+            .expect_instr(9, Instruction::Assign(SymbolKey::from("i")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_errors() {
+        Tester::default()
+            .parse("i = IIF(TRUE, 1)")
+            .compile()
+            .expect_err("1:5: IIF expected cond?, iftrue, iffalse")
+            .check();
+
+        Tester::default()
+            .parse("i = IIF(1, 2, 3)")
+            .compile()
+            .expect_err("1:9: IIF requires a boolean condition")
+            .check();
+
+        Tester::default()
+            .parse("i = IIF(TRUE, 2, \"3\")")
+            .compile()
+            .expect_err("1:18: expected INTEGER but found STRING")
+            .check();
+
+        Tester::default()
+            .parse("i = IIF(TRUE, FALSE, 3.0)")
+            .compile()
+            .expect_err("1:22: expected BOOLEAN but found DOUBLE")
+            .check();
+
+        Tester::default()
+            .parse("i = IIF$(TRUE, 2, 3)")
+            .compile()
+            .expect_err("1:5: Incompatible type annotation in IIF$ reference")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_expr_iif_shadowed_by_user_symbol() {
+        Tester::default()
+            .define("iif", SymbolPrototype::Variable(ExprType::Integer))
+            .parse("i = IIF(TRUE, 2, 3)")
+            .compile()
+            .expect_err("1:5: IIF is not an array nor a function")
+            .check();
+    }
+
     #[test]
     fn test_compile_expr_array_or_function_not_defined() {
         Tester::default()
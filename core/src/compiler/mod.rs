@@ -19,11 +19,11 @@ use crate::ast::*;
 use crate::bytecode::*;
 use crate::parser;
 use crate::reader::LineCol;
-use crate::syms::{CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey, Symbols};
+use crate::syms::{
+    survives_clear, CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey, Symbols,
+};
 use std::borrow::Cow;
-use std::collections::HashMap;
-#[cfg(test)]
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::io;
 
 mod args;
@@ -44,9 +44,18 @@ pub enum Error {
     #[error("{0}: {} expected {}", .1.name(), .1.syntax())]
     CallableSyntaxError(LineCol, CallableMetadata),
 
+    #[error("{0}: Duplicate field {1}")]
+    DuplicateField(LineCol, String),
+
     #[error("{0}: Duplicate label {1}")]
     DuplicateLabel(LineCol, String),
 
+    #[error("{0}: Duplicate type {1}")]
+    DuplicateType(LineCol, String),
+
+    #[error("{0}: Expected a record of type {1}")]
+    ExpectedRecord(LineCol, SymbolKey),
+
     #[error("{0}: Cannot assign value of type {1} to variable of type {2}")]
     IncompatibleTypesInAssignment(LineCol, ExprType, ExprType),
 
@@ -62,12 +71,18 @@ pub enum Error {
     #[error("{0}: EXIT DO outside of DO loop")]
     MisplacedExitDo(LineCol),
 
+    #[error("{0}: STATIC outside of FUNCTION or SUB")]
+    MisplacedStatic(LineCol),
+
     #[error("{0}: {1} requires a boolean condition")]
     NotABooleanCondition(LineCol, String),
 
     #[error("{0}: {1} is not a command")]
     NotACommand(LineCol, VarRef),
 
+    #[error("{0}: Default value must be a literal")]
+    NotALiteral(LineCol),
+
     #[error("{0}: {1} is not a number")]
     NotANumber(LineCol, ExprType),
 
@@ -83,6 +98,9 @@ pub enum Error {
     #[error("{0}: {1}")]
     ParseError(LineCol, String),
 
+    #[error("{0}: Cannot use record {1} as a value")]
+    RecordAsValue(LineCol, SymbolKey),
+
     #[error("{0}: Cannot define already-defined symbol {1}")]
     RedefinitionError(LineCol, SymbolKey),
 
@@ -95,6 +113,9 @@ pub enum Error {
     #[error("{0}: Undefined symbol {1}")]
     UndefinedSymbol(LineCol, SymbolKey),
 
+    #[error("{0}: Undefined type {1}")]
+    UndefinedType(LineCol, String),
+
     #[error("{0}: Unknown label {1}")]
     UnknownLabel(LineCol, String),
 }
@@ -120,6 +141,11 @@ enum SymbolPrototype {
     /// Information about a callable.
     Callable(CallableMetadata),
 
+    /// Information about a record or an array of records.  The key indicates the name of the
+    /// record type and the integer indicates the number of dimensions in the array, which is zero
+    /// for a single record.
+    Record(SymbolKey, usize),
+
     /// Information about a variable.
     Variable(ExprType),
 }
@@ -166,7 +192,7 @@ impl From<&Symbols> for SymbolsTable {
         }
 
         let mut scope = HashMap::default();
-        for (name, symbol) in syms.locals() {
+        for (name, symbol) in syms.locals().iter() {
             let proto = match symbol {
                 Symbol::Array(array) => {
                     SymbolPrototype::Array(array.subtype(), array.dimensions().len())
@@ -247,6 +273,7 @@ enum FixupType {
     Gosub,
     Goto,
     OnError,
+    OnTimer(LineCol),
 }
 
 /// Describes a location in the code needs fixing up after all addresses have been laid out.
@@ -276,6 +303,11 @@ impl Fixup {
     fn from_on_error(span: GotoSpan) -> Self {
         Self { target: span.target, target_pos: span.target_pos, ftype: FixupType::OnError }
     }
+
+    /// Constructs a `Fixup` for a `ON TIMER GOSUB` instruction whose interval is at `pos`.
+    fn from_on_timer(span: GotoSpan, pos: LineCol) -> Self {
+        Self { target: span.target, target_pos: span.target_pos, ftype: FixupType::OnTimer(pos) }
+    }
 }
 
 /// Compilation context to accumulate the results of the translation of various translation units.
@@ -294,6 +326,13 @@ struct Compiler {
     /// Current number of `SELECT` statements, needed to assign internal variable names.
     selects: usize,
 
+    /// Current number of `ON expr GOTO/GOSUB` statements, needed to assign internal variable names.
+    on_gotos: usize,
+
+    /// Current number of `DIM` statements for arrays of records, needed to assign internal
+    /// variable names.
+    dim_records: usize,
+
     /// Mapping of discovered labels to the addresses where they are.
     labels: HashMap<String, Address>,
 
@@ -312,8 +351,17 @@ struct Compiler {
     /// Name of the function being compiled, needed to set the return value in assignment operators.
     current_function: Option<SymbolKey>,
 
+    /// Name of the function or subroutine being compiled, needed to own its static variables.
+    current_callable: Option<SymbolKey>,
+
     /// Callables to be compiled.
     callable_spans: Vec<CallableSpan>,
+
+    /// Metadata of the callables defined by the program.
+    callables: HashMap<SymbolKey, CallableMetadata>,
+
+    /// Fields of the record types defined by the program, in definition order.
+    types: HashMap<SymbolKey, Vec<(String, ExprType)>>,
 }
 
 impl Compiler {
@@ -349,6 +397,27 @@ impl Compiler {
         format!("0select{}", selects)
     }
 
+    /// Generates an internal variable name to hold the result of an `ON expr GOTO/GOSUB`
+    /// selector evaluation.
+    ///
+    /// See `select_test_var_name` for details on why this is safe.
+    fn on_goto_var_name(on_gotos: usize) -> String {
+        format!("0on{}", on_gotos)
+    }
+
+    /// Generates the name of the internal variable that holds the `i`th dimension of an array of
+    /// records while the arrays for its fields are being defined.
+    ///
+    /// See `select_test_var_name` for details on why this is safe.
+    fn dim_record_var_name(dim_records: usize, i: usize) -> String {
+        format!("0dim{}_{}", dim_records, i)
+    }
+
+    /// Generates the name of the variable or array that holds the `field` of the record `name`.
+    fn record_field_name(name: &str, field: &str) -> String {
+        format!("{}.{}", name, field)
+    }
+
     /// Emits the necessary casts to convert the value at the top of the stack from its type `from`
     /// to the new type `target`.
     ///
@@ -367,22 +436,24 @@ impl Compiler {
         }
     }
 
-    /// Compiles the indices used to address an array.
+    /// Compiles the indices used to address the array `name`.
     fn compile_array_indices(
         &mut self,
         exp_nargs: usize,
         args: Vec<Expr>,
+        name: &SymbolKey,
         name_pos: LineCol,
     ) -> Result<()> {
-        let mut instrs = vec![];
-        match exprs::compile_array_indices(&mut instrs, &self.symtable, exp_nargs, args, name_pos) {
-            Ok(result) => {
-                self.next_pc += instrs.len();
-                self.instrs.append(&mut instrs);
-                Ok(result)
-            }
-            Err(e) => Err(e),
-        }
+        exprs::compile_array_indices(
+            &mut self.instrs,
+            &self.symtable,
+            exp_nargs,
+            args,
+            name,
+            name_pos,
+        )?;
+        self.next_pc = self.instrs.len();
+        Ok(())
     }
 
     /// Compiles an assignment to an array position.
@@ -390,6 +461,16 @@ impl Compiler {
         let key = SymbolKey::from(span.vref.name());
         let (atype, dims) = match self.symtable.get(&key) {
             Some(SymbolPrototype::Array(atype, dims)) => (*atype, *dims),
+            Some(SymbolPrototype::Record(type_key, dims)) if *dims > 0 => {
+                let type_key = type_key.clone();
+                return self.compile_record_assignment(
+                    span.vref,
+                    span.vref_pos,
+                    span.subscripts,
+                    type_key,
+                    span.expr,
+                );
+            }
             Some(_) => {
                 return Err(Error::IndexNonArray(span.vref_pos, span.vref.take_name()));
             }
@@ -409,7 +490,7 @@ impl Compiler {
         }
 
         let nargs = span.subscripts.len();
-        self.compile_array_indices(dims, span.subscripts, span.vref_pos)?;
+        self.compile_array_indices(dims, span.subscripts, &key, span.vref_pos)?;
 
         self.emit(Instruction::ArrayAssignment(key, span.vref_pos, nargs));
 
@@ -422,6 +503,11 @@ impl Compiler {
     /// instructions to ensure consistent handling of the symbols table.
     fn compile_assignment(&mut self, vref: VarRef, vref_pos: LineCol, expr: Expr) -> Result<()> {
         let mut key = SymbolKey::from(&vref.name());
+        if let Some(SymbolPrototype::Record(type_key, 0)) = self.symtable.get(&key) {
+            let type_key = type_key.clone();
+            return self.compile_record_assignment(vref, vref_pos, vec![], type_key, expr);
+        }
+
         let etype = self.compile_expr(expr, false)?;
 
         if let Some(current_function) = self.current_function.as_ref() {
@@ -434,6 +520,8 @@ impl Compiler {
             Some(SymbolPrototype::Variable(vtype)) => *vtype,
             Some(_) => return Err(Error::RedefinitionError(vref_pos, key)),
             None => {
+                check_implicit_definition(&vref, vref_pos)?;
+
                 // TODO(jmmv): Compile separate Dim instructions for new variables instead of
                 // checking this every time.
                 let key = key.clone();
@@ -459,6 +547,128 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a `MID$` statement, which replaces a portion of a string variable in place.
+    fn compile_mid_assignment(&mut self, span: MidAssignmentSpan) -> Result<()> {
+        let mut key = SymbolKey::from(&span.vref.name());
+        if let Some(current_function) = self.current_function.as_ref() {
+            if &key == current_function {
+                key = Compiler::return_key(current_function);
+            }
+        }
+
+        match self.symtable.get(&key) {
+            Some(SymbolPrototype::Variable(ExprType::Text)) => (),
+            Some(SymbolPrototype::Variable(vtype)) => {
+                return Err(Error::TypeMismatch(span.vref_pos, *vtype, ExprType::Text));
+            }
+            Some(_) => return Err(Error::NotAVariable(span.vref_pos, span.vref)),
+            None => return Err(Error::UndefinedSymbol(span.vref_pos, key)),
+        }
+        if !span.vref.accepts(ExprType::Text) {
+            return Err(Error::IncompatibleTypeAnnotationInReference(span.vref_pos, span.vref));
+        }
+
+        self.emit(Instruction::LoadString(key.clone(), span.vref_pos));
+        self.compile_expr_as_type(span.start, ExprType::Integer)?;
+        let has_length = match span.length {
+            Some(length) => {
+                self.compile_expr_as_type(length, ExprType::Integer)?;
+                true
+            }
+            None => false,
+        };
+        self.compile_expr_as_type(span.expr, ExprType::Text)?;
+        self.emit(Instruction::OverwriteString(span.vref_pos, has_length));
+        self.emit(Instruction::Assign(key));
+
+        Ok(())
+    }
+
+    /// Compiles the copy of the record in `expr` into the record `vref` or, if `subscripts` is not
+    /// empty, into the element of the array of records `vref`.  The copy happens field by field
+    /// and requires the source and the target to have the same `type_key`.
+    fn compile_record_assignment(
+        &mut self,
+        vref: VarRef,
+        vref_pos: LineCol,
+        subscripts: Vec<Expr>,
+        type_key: SymbolKey,
+        expr: Expr,
+    ) -> Result<()> {
+        if vref.ref_type().is_some() {
+            return Err(Error::IncompatibleTypeAnnotationInReference(vref_pos, vref));
+        }
+
+        let epos = expr.start_pos();
+        let (src_vref, src_args) = match expr {
+            Expr::Symbol(span) => (span.vref, None),
+            Expr::Call(span) => (span.vref, Some(span.args)),
+            _ => return Err(Error::ExpectedRecord(epos, type_key)),
+        };
+        match self.symtable.get(&SymbolKey::from(src_vref.name())) {
+            Some(SymbolPrototype::Record(src_type_key, dims))
+                if *src_type_key == type_key && (*dims == 0) == src_args.is_none() =>
+            {
+                if src_vref.ref_type().is_some() {
+                    return Err(Error::IncompatibleTypeAnnotationInReference(epos, src_vref));
+                }
+            }
+            _ => return Err(Error::ExpectedRecord(epos, type_key)),
+        }
+
+        let fields = self.types.get(&type_key).expect("Records must have a defined type").clone();
+        for (field, _vtype) in fields {
+            let src_field = VarRef::new(Compiler::record_field_name(src_vref.name(), &field), None);
+            let expr = match &src_args {
+                None => Expr::Symbol(SymbolSpan { vref: src_field, pos: epos }),
+                Some(args) => {
+                    Expr::Call(CallSpan { vref: src_field, vref_pos: epos, args: args.clone() })
+                }
+            };
+
+            let vref = VarRef::new(Compiler::record_field_name(vref.name(), &field), None);
+            if subscripts.is_empty() {
+                self.compile_assignment(vref, vref_pos, expr)?;
+            } else {
+                self.compile_array_assignment(ArrayAssignmentSpan {
+                    vref,
+                    vref_pos,
+                    subscripts: subscripts.clone(),
+                    expr,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Computes the value of the `default` expression for a parameter of type `vtype`.
+    fn default_value(default: &Expr, vtype: ExprType) -> Result<Value> {
+        let value = match default {
+            Expr::Boolean(span) => Value::Boolean(span.value),
+            Expr::Double(span) => Value::Double(span.value),
+            Expr::Integer(span) => Value::Integer(span.value),
+            Expr::Text(span) => Value::Text(span.value.clone()),
+            Expr::Negate(span) => match &span.expr {
+                Expr::Double(span) => Value::Double(-span.value),
+                Expr::Integer(span) => Value::Integer(-span.value),
+                _ => return Err(Error::NotALiteral(default.start_pos())),
+            },
+            _ => return Err(Error::NotALiteral(default.start_pos())),
+        };
+
+        match (value, vtype) {
+            (Value::Integer(i), ExprType::Double) => Ok(Value::Double(i as f64)),
+            (value, vtype) => {
+                let etype = value.as_exprtype();
+                if etype != vtype {
+                    return Err(Error::TypeMismatch(default.start_pos(), etype, vtype));
+                }
+                Ok(value)
+            }
+        }
+    }
+
     /// Compiles a `FUNCTION` or `SUB` definition.
     fn compile_callable(&mut self, span: CallableSpan) -> Result<()> {
         let key = SymbolKey::from(span.name.name());
@@ -473,13 +683,25 @@ impl Compiler {
             } else {
                 ArgSepSyntax::Exactly(ArgSep::Long)
             };
-            syntax.push(SingularArgSyntax::RequiredValue(
-                RequiredValueSyntax {
-                    name: Cow::Owned(param.name().to_owned()),
-                    vtype: param.ref_type().unwrap_or(ExprType::Integer),
-                },
-                sep,
-            ));
+            let name = Cow::Owned(param.vref.name().to_owned());
+            let vtype = param.vref.ref_type().unwrap_or(ExprType::Integer);
+            if let Some(default) = param.default.as_ref() {
+                let default = Compiler::default_value(default, vtype)?;
+                syntax.push(SingularArgSyntax::DefaultValue(
+                    DefaultValueSyntax { name, vtype, default },
+                    sep,
+                ));
+            } else if param.byref {
+                syntax.push(SingularArgSyntax::RequiredByRef(
+                    RequiredByRefSyntax { name, vtype },
+                    sep,
+                ));
+            } else {
+                syntax.push(SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name, vtype },
+                    sep,
+                ));
+            }
         }
 
         let mut builder = CallableMetadataBuilder::new_dynamic(span.name.name().to_owned())
@@ -489,7 +711,9 @@ impl Compiler {
         if let Some(ctype) = span.name.ref_type() {
             builder = builder.with_return_type(ctype);
         }
-        self.symtable.insert_global(key, SymbolPrototype::Callable(builder.build()));
+        let metadata = builder.build();
+        self.callables.insert(key.clone(), metadata.clone());
+        self.symtable.insert_global(key, SymbolPrototype::Callable(metadata));
         self.callable_spans.push(span);
 
         Ok(())
@@ -516,6 +740,114 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a `DIM` statement for an array.
+    fn compile_dim_array(&mut self, span: DimArraySpan) -> Result<()> {
+        let key = SymbolKey::from(&span.name);
+        if self.symtable.contains_key(&key) {
+            return Err(Error::RedefinitionError(span.name_pos, key));
+        }
+
+        let nargs = span.dimensions.len();
+        for arg in span.dimensions.into_iter().rev() {
+            self.compile_expr_as_type(arg, ExprType::Integer)?;
+        }
+        self.emit(Instruction::DimArray(DimArrayISpan {
+            name: key.clone(),
+            name_pos: span.name_pos,
+            shared: span.shared,
+            dimensions: nargs,
+            subtype: span.subtype,
+            subtype_pos: span.subtype_pos,
+        }));
+
+        if span.shared {
+            self.symtable.insert_global(key, SymbolPrototype::Array(span.subtype, nargs));
+        } else {
+            self.symtable.insert(key, SymbolPrototype::Array(span.subtype, nargs));
+        }
+
+        Ok(())
+    }
+
+    /// Compiles a `DIM` statement for a record or an array of records.
+    ///
+    /// Records do not exist at runtime: each field is backed by its own variable or array, named
+    /// after the record and the field.
+    fn compile_dim_record(&mut self, span: DimRecordSpan) -> Result<()> {
+        let key = SymbolKey::from(&span.name);
+        if self.symtable.contains_key(&key) {
+            return Err(Error::RedefinitionError(span.name_pos, key));
+        }
+
+        let type_key = SymbolKey::from(&span.type_name);
+        let fields = match self.types.get(&type_key) {
+            Some(fields) => fields.clone(),
+            None => return Err(Error::UndefinedType(span.type_pos, span.type_name)),
+        };
+        for (field, _vtype) in &fields {
+            let field_key = SymbolKey::from(Compiler::record_field_name(&span.name, field));
+            if self.symtable.contains_key(&field_key) {
+                return Err(Error::RedefinitionError(span.name_pos, field_key));
+            }
+        }
+
+        let nargs = span.dimensions.len();
+        let mut dim_keys = vec![];
+        if nargs > 0 {
+            self.dim_records += 1;
+            for (i, arg) in span.dimensions.into_iter().enumerate() {
+                let dim_key = SymbolKey::from(Compiler::dim_record_var_name(self.dim_records, i));
+                self.compile_expr_as_type(arg, ExprType::Integer)?;
+                self.symtable.insert(dim_key.clone(), SymbolPrototype::Variable(ExprType::Integer));
+                self.emit(Instruction::Assign(dim_key.clone()));
+                dim_keys.push(dim_key);
+            }
+        }
+
+        for (field, vtype) in fields {
+            let field_key = SymbolKey::from(Compiler::record_field_name(&span.name, &field));
+            let proto = if nargs == 0 {
+                self.emit(Instruction::Dim(DimISpan {
+                    name: field_key.clone(),
+                    shared: span.shared,
+                    vtype,
+                }));
+                SymbolPrototype::Variable(vtype)
+            } else {
+                for dim_key in dim_keys.iter().rev() {
+                    self.emit(Instruction::LoadInteger(dim_key.clone(), span.name_pos));
+                }
+                self.emit(Instruction::DimArray(DimArrayISpan {
+                    name: field_key.clone(),
+                    name_pos: span.name_pos,
+                    shared: span.shared,
+                    dimensions: nargs,
+                    subtype: vtype,
+                    subtype_pos: span.type_pos,
+                }));
+                SymbolPrototype::Array(vtype, nargs)
+            };
+            if span.shared {
+                self.symtable.insert_global(field_key, proto);
+            } else {
+                self.symtable.insert(field_key, proto);
+            }
+        }
+
+        for dim_key in dim_keys {
+            self.emit(Instruction::Unset(UnsetISpan { name: dim_key.clone(), pos: span.name_pos }));
+            self.symtable.remove(dim_key);
+        }
+
+        if span.shared {
+            self.symtable.insert_global(key, SymbolPrototype::Record(type_key, nargs));
+        } else {
+            self.symtable.insert(key, SymbolPrototype::Record(type_key, nargs));
+        }
+
+        Ok(())
+    }
+
     /// Compiles a `DO` loop and appends its instructions to the compilation context.
     fn compile_do(&mut self, span: DoSpan) -> Result<()> {
         self.exit_do_level.1 += 1;
@@ -666,6 +998,62 @@ impl Compiler {
         }
     }
 
+    /// Compiles an `ON TIMER` statement and appends its instructions to the compilation context.
+    fn compile_on_timer(&mut self, span: OnTimerSpan) -> Result<()> {
+        let pos = span.interval.start_pos();
+        self.compile_expr_as_type(span.interval, ExprType::Double)?;
+        let timer_pc = self.emit(Instruction::Nop);
+        self.fixups.insert(timer_pc, Fixup::from_on_timer(span.target, pos));
+        Ok(())
+    }
+
+    /// Compiles an `ON expr GOTO` or an `ON expr GOSUB` statement and appends its instructions to
+    /// the compilation context.
+    ///
+    /// The selector is stored in an internal variable, which is unset before transferring control
+    /// to any of the targets so that it does not leak when the jump does not come back.
+    fn compile_on_goto(&mut self, span: OnGotoSpan) -> Result<()> {
+        let mut end_pcs = vec![];
+
+        self.on_gotos += 1;
+        let vref = VarRef::new(Compiler::on_goto_var_name(self.on_gotos), Some(ExprType::Integer));
+        let key = SymbolKey::from(vref.name());
+        self.compile_expr_as_type(span.expr, ExprType::Integer)?;
+        self.symtable.insert(key.clone(), SymbolPrototype::Variable(ExprType::Integer));
+        self.emit(Instruction::Assign(key.clone()));
+
+        for (i, target) in span.targets.into_iter().enumerate() {
+            let pos = target.target_pos;
+            let guard = Expr::Equal(Box::from(BinaryOpSpan {
+                lhs: Expr::Symbol(SymbolSpan { vref: vref.clone(), pos }),
+                rhs: Expr::Integer(IntegerSpan { value: (i + 1) as i32, pos }),
+                pos,
+            }));
+            self.compile_expr_guard(guard, "ON")?;
+            let jump_pc = self.emit(Instruction::Nop);
+
+            self.emit(Instruction::Unset(UnsetISpan { name: key.clone(), pos: span.pos }));
+            let target_pc = self.emit(Instruction::Nop);
+            if span.gosub {
+                self.fixups.insert(target_pc, Fixup::from_gosub(target));
+                end_pcs.push(self.emit(Instruction::Nop));
+            } else {
+                self.fixups.insert(target_pc, Fixup::from_goto(target));
+            }
+
+            self.instrs[jump_pc] = Instruction::JumpIfNotTrue(self.next_pc);
+        }
+
+        self.emit(Instruction::Unset(UnsetISpan { name: key.clone(), pos: span.pos }));
+        self.symtable.remove(key);
+
+        for end_pc in end_pcs {
+            self.instrs[end_pc] = Instruction::Jump(JumpISpan { addr: self.next_pc });
+        }
+
+        Ok(())
+    }
+
     /// Generates the expression to evaluate a list of `guards`, which are compared against the
     /// test expression stored in `test_vref`.
     fn compile_case_guards(test_vref: &VarRef, guards: Vec<CaseGuardSpan>) -> Option<Expr> {
@@ -718,6 +1106,45 @@ impl Compiler {
         expr
     }
 
+    /// Compiles a `STATIC` statement.
+    ///
+    /// The variable is first defined as a local variable of the callable and then moved to the
+    /// callable's static storage.  Subsequent executions of the statement link the existing static
+    /// variable into the current scope and skip its initialization.
+    fn compile_static(&mut self, span: StaticSpan) -> Result<()> {
+        let callable = match self.current_callable.as_ref() {
+            Some(callable) => callable.clone(),
+            None => return Err(Error::MisplacedStatic(span.pos)),
+        };
+
+        let (key, key_pos) = match &span.decl {
+            StaticDecl::Dim(span) => (SymbolKey::from(&span.name), span.name_pos),
+            StaticDecl::DimArray(span) => (SymbolKey::from(&span.name), span.name_pos),
+            StaticDecl::Assignment(span) => (SymbolKey::from(span.vref.name()), span.vref_pos),
+        };
+        if self.symtable.contains_key(&key) {
+            return Err(Error::RedefinitionError(key_pos, key));
+        }
+
+        let link_pc = self.emit(Instruction::Nop);
+        match span.decl {
+            StaticDecl::Dim(span) => self.compile_dim(span)?,
+            StaticDecl::DimArray(span) => self.compile_dim_array(span)?,
+            StaticDecl::Assignment(span) => {
+                self.compile_assignment(span.vref, span.vref_pos, span.expr)?
+            }
+        }
+        self.emit(Instruction::SaveStatic(SaveStaticISpan {
+            callable: callable.clone(),
+            name: key.clone(),
+        }));
+
+        self.instrs[link_pc] =
+            Instruction::LinkStatic(LinkStaticISpan { callable, name: key, addr: self.next_pc });
+
+        Ok(())
+    }
+
     /// Compiles a `SELECT` statement and appends its instructions to the compilation context.
     fn compile_select(&mut self, span: SelectSpan) -> Result<()> {
         let mut end_pcs = vec![];
@@ -763,6 +1190,26 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles a `TYPE` definition.  Types only exist at compilation time so this does not emit
+    /// any instructions.
+    fn compile_type(&mut self, span: TypeSpan) -> Result<()> {
+        let key = SymbolKey::from(&span.name);
+        if self.types.contains_key(&key) {
+            return Err(Error::DuplicateType(span.name_pos, span.name));
+        }
+
+        let mut fields: Vec<(String, ExprType)> = vec![];
+        for field in span.fields {
+            if fields.iter().any(|(name, _)| name.eq_ignore_ascii_case(&field.name)) {
+                return Err(Error::DuplicateField(field.name_pos, field.name));
+            }
+            fields.push((field.name, field.vtype));
+        }
+        self.types.insert(key, fields);
+
+        Ok(())
+    }
+
     /// Compiles a `WHILE` loop and appends its instructions to the compilation context.
     fn compile_while(&mut self, span: WhileSpan) -> Result<()> {
         let start_pc = self.next_pc;
@@ -829,6 +1276,10 @@ impl Compiler {
                 self.compile_assignment(span.vref, span.vref_pos, span.expr)?;
             }
 
+            Statement::MidAssignment(span) => {
+                self.compile_mid_assignment(span)?;
+            }
+
             Statement::Call(span) => {
                 let key = SymbolKey::from(&span.vref.name());
                 let md = match self.symtable.get(&key) {
@@ -847,7 +1298,7 @@ impl Compiler {
                 };
 
                 let name_pos = span.vref_pos;
-                let nargs = compile_command_args(
+                let (nargs, writeback) = compile_command_args(
                     &md,
                     &mut self.instrs,
                     &mut self.symtable,
@@ -856,6 +1307,9 @@ impl Compiler {
                 )?;
                 self.next_pc = self.instrs.len();
                 self.emit(Instruction::BuiltinCall(key, span.vref_pos, nargs));
+                for instr in writeback {
+                    self.emit(instr);
+                }
             }
 
             Statement::Callable(span) => {
@@ -871,29 +1325,11 @@ impl Compiler {
             }
 
             Statement::DimArray(span) => {
-                let key = SymbolKey::from(&span.name);
-                if self.symtable.contains_key(&key) {
-                    return Err(Error::RedefinitionError(span.name_pos, key));
-                }
-
-                let nargs = span.dimensions.len();
-                for arg in span.dimensions.into_iter().rev() {
-                    self.compile_expr_as_type(arg, ExprType::Integer)?;
-                }
-                self.emit(Instruction::DimArray(DimArrayISpan {
-                    name: key.clone(),
-                    name_pos: span.name_pos,
-                    shared: span.shared,
-                    dimensions: nargs,
-                    subtype: span.subtype,
-                    subtype_pos: span.subtype_pos,
-                }));
+                self.compile_dim_array(span)?;
+            }
 
-                if span.shared {
-                    self.symtable.insert_global(key, SymbolPrototype::Array(span.subtype, nargs));
-                } else {
-                    self.symtable.insert(key, SymbolPrototype::Array(span.subtype, nargs));
-                }
+            Statement::DimRecord(span) => {
+                self.compile_dim_record(span)?;
             }
 
             Statement::Do(span) => {
@@ -949,6 +1385,18 @@ impl Compiler {
                 self.compile_on_error(span);
             }
 
+            Statement::OnGoto(span) => {
+                self.compile_on_goto(span)?;
+            }
+
+            Statement::OnTimer(span) => {
+                self.compile_on_timer(span)?;
+            }
+
+            Statement::Resume(span) => {
+                self.emit(Instruction::Resume(span.next, span.pos));
+            }
+
             Statement::Return(span) => {
                 self.emit(Instruction::Return(span.pos));
             }
@@ -957,6 +1405,18 @@ impl Compiler {
                 self.compile_select(span)?;
             }
 
+            Statement::Static(span) => {
+                self.compile_static(span)?;
+            }
+
+            Statement::Timer(span) => {
+                self.emit(Instruction::SetTimerState(span.action));
+            }
+
+            Statement::Type(span) => {
+                self.compile_type(span)?;
+            }
+
             Statement::While(span) => {
                 self.compile_while(span)?;
             }
@@ -975,6 +1435,38 @@ impl Compiler {
         Ok(())
     }
 
+    /// Compiles the assignment of the arguments on the stack to the `params` of a callable.
+    ///
+    /// Returns the names and types of the parameters passed by reference.
+    fn compile_params(&mut self, params: Vec<ParamSpan>) -> Vec<(SymbolKey, ExprType)> {
+        let mut byrefs = vec![];
+        for param in params {
+            let key = SymbolKey::from(param.vref.name());
+            let ptype = param.vref.ref_type().unwrap_or(ExprType::Integer);
+            self.emit(Instruction::Assign(key.clone()));
+            self.symtable.insert(key.clone(), SymbolPrototype::Variable(ptype));
+            if param.byref {
+                byrefs.push((key, ptype));
+            }
+        }
+        byrefs
+    }
+
+    /// Compiles the push of the final values of the parameters passed by reference, in `byrefs`,
+    /// so that the caller can store them back into its variables.  The value of the first
+    /// parameter ends up at the top of the stack.
+    fn compile_byref_returns(&mut self, byrefs: Vec<(SymbolKey, ExprType)>, pos: LineCol) {
+        for (key, ptype) in byrefs.into_iter().rev() {
+            let load_inst = match ptype {
+                ExprType::Boolean => Instruction::LoadBoolean,
+                ExprType::Double => Instruction::LoadDouble,
+                ExprType::Integer => Instruction::LoadInteger,
+                ExprType::Text => Instruction::LoadString,
+            };
+            self.emit(load_inst(key, pos));
+        }
+    }
+
     /// Compiles all callables discovered during the first phase and fixes up all call sites to
     /// point to the compiled code.
     fn compile_callables(&mut self) -> Result<()> {
@@ -1001,16 +1493,13 @@ impl Compiler {
                     self.symtable
                         .insert(return_value.clone(), SymbolPrototype::Variable(return_type));
 
-                    for param in span.params {
-                        let key = SymbolKey::from(param.name());
-                        let ptype = param.ref_type().unwrap_or(ExprType::Integer);
-                        self.emit(Instruction::Assign(key.clone()));
-                        self.symtable.insert(key, SymbolPrototype::Variable(ptype));
-                    }
+                    let byrefs = self.compile_params(span.params);
 
                     self.current_function = Some(key.clone());
+                    self.current_callable = Some(key.clone());
                     self.compile_many(span.body)?;
                     self.current_function = None;
+                    self.current_callable = None;
 
                     let load_inst = match return_type {
                         ExprType::Boolean => Instruction::LoadBoolean,
@@ -1019,6 +1508,7 @@ impl Compiler {
                         ExprType::Text => Instruction::LoadString,
                     };
                     self.emit(load_inst(return_value.clone(), span.end_pos));
+                    self.compile_byref_returns(byrefs, span.end_pos);
 
                     self.emit(Instruction::LeaveScope);
                     self.symtable.leave_scope();
@@ -1030,14 +1520,12 @@ impl Compiler {
                     self.emit(Instruction::EnterScope);
                     self.symtable.enter_scope();
 
-                    for param in span.params {
-                        let key = SymbolKey::from(param.name());
-                        let ptype = param.ref_type().unwrap_or(ExprType::Integer);
-                        self.emit(Instruction::Assign(key.clone()));
-                        self.symtable.insert(key, SymbolPrototype::Variable(ptype));
-                    }
+                    let byrefs = self.compile_params(span.params);
 
+                    self.current_callable = Some(key.clone());
                     self.compile_many(span.body)?;
+                    self.current_callable = None;
+                    self.compile_byref_returns(byrefs, span.end_pos);
 
                     self.emit(Instruction::LeaveScope);
                     self.symtable.leave_scope();
@@ -1092,9 +1580,12 @@ impl Compiler {
                 FixupType::OnError => {
                     self.instrs[pc] = Instruction::SetErrorHandler(ErrorHandlerISpan::Jump(addr))
                 }
+                FixupType::OnTimer(pos) => {
+                    self.instrs[pc] = Instruction::SetTimerHandler(TimerHandlerISpan { addr, pos })
+                }
             }
         }
-        let image = Image { instrs: self.instrs, data: self.data };
+        let image = Image { instrs: self.instrs, data: self.data, callables: self.callables };
         Ok((image, self.symtable))
     }
 }
@@ -1103,6 +1594,16 @@ impl Compiler {
 ///
 /// `symtable` is the symbols table as used by the compiler and should be prepopulated with any
 /// callables that the compiled program should recognize.
+/// Checks if the variable `vref` at `pos` can be defined implicitly on first use.
+///
+/// Names with dots refer to the fields of records, which can only be defined via `DIM`.
+fn check_implicit_definition(vref: &VarRef, pos: LineCol) -> Result<()> {
+    if vref.name().contains('.') {
+        return Err(Error::UndefinedSymbol(pos, SymbolKey::from(vref.name())));
+    }
+    Ok(())
+}
+
 fn compile_aux(input: &mut dyn io::Read, symtable: SymbolsTable) -> Result<(Image, SymbolsTable)> {
     let mut compiler = Compiler { symtable, ..Default::default() };
     for stmt in parser::parse(input) {
@@ -1118,7 +1619,40 @@ fn compile_aux(input: &mut dyn io::Read, symtable: SymbolsTable) -> Result<(Imag
 // TODO(jmmv): This is ugly.  Now that we have a symbols table in here, we should not _also_ have a
 // Symbols object to maintain runtime state (or if we do, we shouldn't be getting it here).
 pub fn compile(input: &mut dyn io::Read, syms: &Symbols) -> Result<Image> {
-    compile_aux(input, SymbolsTable::from(syms)).map(|(image, _symtable)| image)
+    let (mut image, _symtable) = compile_aux(input, SymbolsTable::from(syms))?;
+    syms.resolve_names(&mut image.instrs);
+    Ok(image)
+}
+
+/// Compiles a collection of statements into an image ready for execution after the machine is
+/// cleared with `Machine::clear_except`.
+///
+/// This is the same as `compile` but the only user-defined symbols from `syms` that are visible to
+/// the program are those named in `keep`, which are the ones that survive the clear operation.
+pub fn compile_preserving(
+    input: &mut dyn io::Read,
+    syms: &Symbols,
+    keep: &HashSet<SymbolKey>,
+) -> Result<Image> {
+    let mut symtable = SymbolsTable::from(syms);
+    for scope in &mut symtable.scopes {
+        scope.retain(|key, _proto| survives_clear(key, keep));
+    }
+    let (mut image, _symtable) = compile_aux(input, symtable)?;
+    syms.resolve_names(&mut image.instrs);
+    Ok(image)
+}
+
+/// Compiles a single expression into an image that leaves the value of the expression on the top
+/// of the stack.
+///
+/// `syms` is a reference to the execution symbols, as in `compile`.
+pub(crate) fn compile_standalone_expr(input: &mut dyn io::Read, syms: &Symbols) -> Result<Image> {
+    let expr = parser::parse_expr(input)?;
+    let mut instrs = vec![];
+    compile_expr(&mut instrs, &SymbolsTable::from(syms), expr, false)?;
+    syms.resolve_names(&mut instrs);
+    Ok(Image { instrs, data: vec![], callables: HashMap::default() })
 }
 
 #[cfg(test)]
@@ -1318,7 +1852,14 @@ mod tests {
             .compile()
             .expect_instr(0, Instruction::PushInteger(1, lc(1, 10)))
             .expect_instr(1, Instruction::PushDouble(1.2, lc(1, 3)))
-            .expect_instr(2, Instruction::DoubleToInteger)
+            .expect_instr(
+                2,
+                Instruction::DoubleToSubscript(DoubleToSubscriptISpan {
+                    name: SymbolKey::from("a"),
+                    name_pos: lc(1, 1),
+                    dimension: 0,
+                }),
+            )
             .expect_instr(3, Instruction::ArrayAssignment(SymbolKey::from("a"), lc(1, 1), 1))
             .check();
     }
@@ -1413,45 +1954,113 @@ mod tests {
     }
 
     #[test]
-    fn test_compile_assignment_literal() {
+    fn test_compile_mid_assignment() {
         Tester::default()
-            .parse("foo = 5")
+            .define("s", SymbolPrototype::Variable(ExprType::Text))
+            .define("n", SymbolPrototype::Variable(ExprType::Double))
+            .parse("MID$(s$, 2, n) = \"xy\"")
             .compile()
-            .expect_instr(0, Instruction::PushInteger(5, lc(1, 7)))
-            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
+            .expect_instr(0, Instruction::LoadString(SymbolKey::from("s"), lc(1, 6)))
+            .expect_instr(1, Instruction::PushInteger(2, lc(1, 10)))
+            .expect_instr(2, Instruction::LoadDouble(SymbolKey::from("n"), lc(1, 13)))
+            .expect_instr(3, Instruction::DoubleToInteger)
+            .expect_instr(4, Instruction::PushString("xy".to_owned(), lc(1, 18)))
+            .expect_instr(5, Instruction::OverwriteString(lc(1, 6), true))
+            .expect_instr(6, Instruction::Assign(SymbolKey::from("s")))
             .check();
     }
 
     #[test]
-    fn test_compile_assignment_varref_is_evaluated() {
+    fn test_compile_mid_assignment_without_length() {
         Tester::default()
-            .define("i", SymbolPrototype::Variable(ExprType::Integer))
-            .parse("foo = i")
+            .define("s", SymbolPrototype::Variable(ExprType::Text))
+            .parse("MID$(s, 2) = \"xy\"")
             .compile()
-            .expect_instr(0, Instruction::LoadInteger(SymbolKey::from("i"), lc(1, 7)))
-            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
+            .expect_instr(0, Instruction::LoadString(SymbolKey::from("s"), lc(1, 6)))
+            .expect_instr(1, Instruction::PushInteger(2, lc(1, 9)))
+            .expect_instr(2, Instruction::PushString("xy".to_owned(), lc(1, 14)))
+            .expect_instr(3, Instruction::OverwriteString(lc(1, 6), false))
+            .expect_instr(4, Instruction::Assign(SymbolKey::from("s")))
             .check();
     }
 
     #[test]
-    fn test_compile_assignment_new_var_auto_ref_expr_determines_type() {
+    fn test_compile_mid_assignment_errors() {
         Tester::default()
-            .parse("foo = 2.3")
+            .parse("MID$(s$, 1) = \"x\"")
             .compile()
-            .expect_instr(0, Instruction::PushDouble(2.3, lc(1, 7)))
-            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
-            .expect_symtable(SymbolKey::from("foo"), SymbolPrototype::Variable(ExprType::Double))
+            .expect_err("1:6: Undefined symbol S")
             .check();
-    }
 
-    #[test]
-    fn test_compile_assignment_new_var_explicit_ref_determines_type() {
         Tester::default()
-            .parse("foo# = 2")
+            .define("i", SymbolPrototype::Variable(ExprType::Integer))
+            .parse("MID$(i, 1) = \"x\"")
             .compile()
-            .expect_instr(0, Instruction::PushInteger(2, lc(1, 8)))
-            .expect_instr(1, Instruction::IntegerToDouble)
-            .expect_instr(2, Instruction::Assign(SymbolKey::from("foo")))
+            .expect_err("1:6: expected STRING but found INTEGER")
+            .check();
+
+        Tester::default()
+            .define("a", SymbolPrototype::Array(ExprType::Text, 1))
+            .parse("MID$(a, 1) = \"x\"")
+            .compile()
+            .expect_err("1:6: a is not a variable")
+            .check();
+
+        Tester::default()
+            .define("s", SymbolPrototype::Variable(ExprType::Text))
+            .parse("MID$(s, TRUE) = \"x\"")
+            .compile()
+            .expect_err("1:9: BOOLEAN is not a number")
+            .check();
+
+        Tester::default()
+            .define("s", SymbolPrototype::Variable(ExprType::Text))
+            .parse("MID$(s, 1) = 3")
+            .compile()
+            .expect_err("1:14: expected STRING but found INTEGER")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_assignment_literal() {
+        Tester::default()
+            .parse("foo = 5")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(5, lc(1, 7)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_assignment_varref_is_evaluated() {
+        Tester::default()
+            .define("i", SymbolPrototype::Variable(ExprType::Integer))
+            .parse("foo = i")
+            .compile()
+            .expect_instr(0, Instruction::LoadInteger(SymbolKey::from("i"), lc(1, 7)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_assignment_new_var_auto_ref_expr_determines_type() {
+        Tester::default()
+            .parse("foo = 2.3")
+            .compile()
+            .expect_instr(0, Instruction::PushDouble(2.3, lc(1, 7)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("foo")))
+            .expect_symtable(SymbolKey::from("foo"), SymbolPrototype::Variable(ExprType::Double))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_assignment_new_var_explicit_ref_determines_type() {
+        Tester::default()
+            .parse("foo# = 2")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(2, lc(1, 8)))
+            .expect_instr(1, Instruction::IntegerToDouble)
+            .expect_instr(2, Instruction::Assign(SymbolKey::from("foo")))
             .expect_symtable(SymbolKey::from("foo"), SymbolPrototype::Variable(ExprType::Double))
             .check();
     }
@@ -1762,6 +2371,276 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_dim_record_ok() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\ny AS STRING\nEND TYPE\nDIM p AS t")
+            .compile()
+            .expect_instr(
+                0,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("p.x"),
+                    shared: false,
+                    vtype: ExprType::Integer,
+                }),
+            )
+            .expect_instr(
+                1,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("p.y"),
+                    shared: false,
+                    vtype: ExprType::Text,
+                }),
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_compile_dim_record_array() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\ny AS STRING\nEND TYPE\nDIM SHARED a(2, 3.5) AS t")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(2, lc(5, 14)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("0dim1_0")))
+            .expect_instr(2, Instruction::PushDouble(3.5, lc(5, 17)))
+            .expect_instr(3, Instruction::DoubleToInteger)
+            .expect_instr(4, Instruction::Assign(SymbolKey::from("0dim1_1")))
+            .expect_instr(5, Instruction::LoadInteger(SymbolKey::from("0dim1_1"), lc(5, 12)))
+            .expect_instr(6, Instruction::LoadInteger(SymbolKey::from("0dim1_0"), lc(5, 12)))
+            .expect_instr(
+                7,
+                Instruction::DimArray(DimArrayISpan {
+                    name: SymbolKey::from("a.x"),
+                    name_pos: lc(5, 12),
+                    shared: true,
+                    dimensions: 2,
+                    subtype: ExprType::Integer,
+                    subtype_pos: lc(5, 25),
+                }),
+            )
+            .expect_instr(8, Instruction::LoadInteger(SymbolKey::from("0dim1_1"), lc(5, 12)))
+            .expect_instr(9, Instruction::LoadInteger(SymbolKey::from("0dim1_0"), lc(5, 12)))
+            .expect_instr(
+                10,
+                Instruction::DimArray(DimArrayISpan {
+                    name: SymbolKey::from("a.y"),
+                    name_pos: lc(5, 12),
+                    shared: true,
+                    dimensions: 2,
+                    subtype: ExprType::Text,
+                    subtype_pos: lc(5, 25),
+                }),
+            )
+            .expect_instr(
+                11,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0dim1_0"), pos: lc(5, 12) }),
+            )
+            .expect_instr(
+                12,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0dim1_1"), pos: lc(5, 12) }),
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_compile_dim_record_errors() {
+        Tester::default()
+            .parse("DIM p AS t")
+            .compile()
+            .expect_err("1:10: Undefined type t")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\nDIM P AS t")
+            .compile()
+            .expect_err("5:5: Cannot define already-defined symbol P")
+            .check();
+
+        Tester::default()
+            .define("p.x", SymbolPrototype::Variable(ExprType::Integer))
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t")
+            .compile()
+            .expect_err("4:5: Cannot define already-defined symbol P.X")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM a(\"1\") AS t")
+            .compile()
+            .expect_err("4:7: STRING is not a number")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_record_field_assignment() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\np.x = 2.5")
+            .compile()
+            .expect_instr(
+                0,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("p.x"),
+                    shared: false,
+                    vtype: ExprType::Integer,
+                }),
+            )
+            .expect_instr(1, Instruction::PushDouble(2.5, lc(5, 7)))
+            .expect_instr(2, Instruction::DoubleToInteger)
+            .expect_instr(3, Instruction::Assign(SymbolKey::from("p.x")))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_record_field_errors() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\np.x = \"a\"")
+            .compile()
+            .expect_err("5:1: Cannot assign value of type STRING to variable of type INTEGER")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\np.y = 1")
+            .compile()
+            .expect_err("5:1: Undefined symbol P.Y")
+            .check();
+
+        Tester::default()
+            .define_callable(CallableMetadataBuilder::new("OUT").with_syntax(&[(
+                &[SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name: Cow::Borrowed("arg"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )]))
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\nOUT p")
+            .compile()
+            .expect_err("5:5: Cannot use record P as a value")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_record_assignment() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\ny AS STRING\nEND TYPE\nDIM p AS t\nDIM q AS t\np = q")
+            .compile()
+            .expect_instr(
+                0,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("p.x"),
+                    shared: false,
+                    vtype: ExprType::Integer,
+                }),
+            )
+            .expect_instr(
+                1,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("p.y"),
+                    shared: false,
+                    vtype: ExprType::Text,
+                }),
+            )
+            .expect_instr(
+                2,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("q.x"),
+                    shared: false,
+                    vtype: ExprType::Integer,
+                }),
+            )
+            .expect_instr(
+                3,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("q.y"),
+                    shared: false,
+                    vtype: ExprType::Text,
+                }),
+            )
+            .expect_instr(4, Instruction::LoadInteger(SymbolKey::from("q.x"), lc(7, 5)))
+            .expect_instr(5, Instruction::Assign(SymbolKey::from("p.x")))
+            .expect_instr(6, Instruction::LoadString(SymbolKey::from("q.y"), lc(7, 5)))
+            .expect_instr(7, Instruction::Assign(SymbolKey::from("p.y")))
+            .expect_symtable(SymbolKey::from("p"), SymbolPrototype::Record(SymbolKey::from("t"), 0))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_record_array_assignment() {
+        Tester::default()
+            .define("i", SymbolPrototype::Variable(ExprType::Integer))
+            .define("a", SymbolPrototype::Array(ExprType::Integer, 1))
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM b(3) AS t\nb(i) = b(2)\na(1) = b(i).x")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(3, lc(4, 7)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("0dim1_0")))
+            .expect_instr(2, Instruction::LoadInteger(SymbolKey::from("0dim1_0"), lc(4, 5)))
+            .expect_instr(
+                3,
+                Instruction::DimArray(DimArrayISpan {
+                    name: SymbolKey::from("b.x"),
+                    name_pos: lc(4, 5),
+                    shared: false,
+                    dimensions: 1,
+                    subtype: ExprType::Integer,
+                    subtype_pos: lc(4, 13),
+                }),
+            )
+            .expect_instr(
+                4,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0dim1_0"), pos: lc(4, 5) }),
+            )
+            .expect_instr(5, Instruction::PushInteger(2, lc(5, 10)))
+            .expect_instr(6, Instruction::ArrayLoad(SymbolKey::from("b.x"), lc(5, 8), 1))
+            .expect_instr(7, Instruction::LoadInteger(SymbolKey::from("i"), lc(5, 3)))
+            .expect_instr(8, Instruction::ArrayAssignment(SymbolKey::from("b.x"), lc(5, 1), 1))
+            .expect_instr(9, Instruction::LoadInteger(SymbolKey::from("i"), lc(6, 10)))
+            .expect_instr(10, Instruction::ArrayLoad(SymbolKey::from("b.x"), lc(6, 8), 1))
+            .expect_instr(11, Instruction::PushInteger(1, lc(6, 3)))
+            .expect_instr(12, Instruction::ArrayAssignment(SymbolKey::from("a"), lc(6, 1), 1))
+            .expect_symtable(SymbolKey::from("b"), SymbolPrototype::Record(SymbolKey::from("t"), 1))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_record_assignment_errors() {
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\np = 3")
+            .compile()
+            .expect_err("5:5: Expected a record of type T")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nTYPE u\nx AS INTEGER\nEND TYPE\nDIM p AS t\nDIM q AS u\np = q")
+            .compile()
+            .expect_err("9:5: Expected a record of type T")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\nDIM a(2) AS t\np = a")
+            .compile()
+            .expect_err("6:5: Expected a record of type T")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nEND TYPE\nDIM p AS t\nDIM q AS t\np$ = q")
+            .compile()
+            .expect_err("6:1: Incompatible type annotation in p$ reference")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_type_errors() {
+        Tester::default()
+            .parse("TYPE t\nEND TYPE\nTYPE T\nEND TYPE")
+            .compile()
+            .expect_err("3:6: Duplicate type T")
+            .check();
+
+        Tester::default()
+            .parse("TYPE t\nx AS INTEGER\nX AS STRING\nEND TYPE")
+            .compile()
+            .expect_err("3:1: Duplicate field X")
+            .check();
+    }
+
     #[test]
     fn test_compile_do_infinite() {
         Tester::default()
@@ -2134,6 +3013,68 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_sub_default_params() {
+        Tester::default()
+            .parse("SUB foo(a, b$ = \"x\", c# = 3): END SUB\nfoo 1: foo 2, \"b\"")
+            .compile()
+            .expect_instr(0, Instruction::PushDouble(3.0, lc(2, 1)))
+            .expect_instr(1, Instruction::PushString("x".to_owned(), lc(2, 1)))
+            .expect_instr(2, Instruction::PushInteger(1, lc(2, 5)))
+            .expect_instr(3, Instruction::Call(JumpISpan { addr: 9 }))
+            .expect_instr(4, Instruction::PushDouble(3.0, lc(2, 8)))
+            .expect_instr(5, Instruction::PushString("b".to_owned(), lc(2, 15)))
+            .expect_instr(6, Instruction::PushInteger(2, lc(2, 12)))
+            .expect_instr(7, Instruction::Call(JumpISpan { addr: 9 }))
+            .expect_instr(8, Instruction::Jump(JumpISpan { addr: 15 }))
+            .expect_instr(9, Instruction::EnterScope)
+            .expect_instr(10, Instruction::Assign(SymbolKey::from("a")))
+            .expect_instr(11, Instruction::Assign(SymbolKey::from("b")))
+            .expect_instr(12, Instruction::Assign(SymbolKey::from("c")))
+            .expect_instr(13, Instruction::LeaveScope)
+            .expect_instr(14, Instruction::Return(lc(1, 31)))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_sub_default_params_errors() {
+        Tester::default()
+            .parse("SUB foo(a, b = \"x\"): END SUB")
+            .compile()
+            .expect_err("1:16: expected INTEGER but found STRING")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(a, b = 1.5): END SUB")
+            .compile()
+            .expect_err("1:16: expected INTEGER but found DOUBLE")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(a, b = -a): END SUB")
+            .compile()
+            .expect_err("1:16: Default value must be a literal")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(a, b = 2 + 3): END SUB")
+            .compile()
+            .expect_err("1:16: Default value must be a literal")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(a, b = 2, c = 3): END SUB\nfoo")
+            .compile()
+            .expect_err("2:1: FOO expected a%, [b%], [c%]")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(a, b = 2, c = 3): END SUB\nfoo 1, 2, 3, 4")
+            .compile()
+            .expect_err("2:1: FOO expected a%, [b%], [c%]")
+            .check();
+    }
+
     #[test]
     fn test_compile_sub_redefined_was_variable() {
         Tester::default()
@@ -2161,6 +3102,116 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_static_assignment() {
+        Tester::default()
+            .parse("SUB foo\nSTATIC n = 3\nEND SUB")
+            .compile()
+            .expect_instr(0, Instruction::Jump(JumpISpan { addr: 8 }))
+            .expect_instr(1, Instruction::EnterScope)
+            .expect_instr(
+                2,
+                Instruction::LinkStatic(LinkStaticISpan {
+                    callable: SymbolKey::from("foo"),
+                    name: SymbolKey::from("n"),
+                    addr: 6,
+                }),
+            )
+            .expect_instr(3, Instruction::PushInteger(3, lc(2, 12)))
+            .expect_instr(4, Instruction::Assign(SymbolKey::from("n")))
+            .expect_instr(
+                5,
+                Instruction::SaveStatic(SaveStaticISpan {
+                    callable: SymbolKey::from("foo"),
+                    name: SymbolKey::from("n"),
+                }),
+            )
+            .expect_instr(6, Instruction::LeaveScope)
+            .expect_instr(7, Instruction::Return(lc(3, 1)))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_static_array() {
+        Tester::default()
+            .parse("FUNCTION foo$\nSTATIC a(2) AS STRING\nEND FUNCTION")
+            .compile()
+            .expect_instr(0, Instruction::Jump(JumpISpan { addr: 10 }))
+            .expect_instr(1, Instruction::EnterScope)
+            .expect_instr(
+                2,
+                Instruction::Dim(DimISpan {
+                    name: SymbolKey::from("0return_foo"),
+                    shared: false,
+                    vtype: ExprType::Text,
+                }),
+            )
+            .expect_instr(
+                3,
+                Instruction::LinkStatic(LinkStaticISpan {
+                    callable: SymbolKey::from("foo"),
+                    name: SymbolKey::from("a"),
+                    addr: 7,
+                }),
+            )
+            .expect_instr(4, Instruction::PushInteger(2, lc(2, 10)))
+            .expect_instr(
+                5,
+                Instruction::DimArray(DimArrayISpan {
+                    name: SymbolKey::from("a"),
+                    name_pos: lc(2, 8),
+                    shared: false,
+                    dimensions: 1,
+                    subtype: ExprType::Text,
+                    subtype_pos: lc(2, 16),
+                }),
+            )
+            .expect_instr(
+                6,
+                Instruction::SaveStatic(SaveStaticISpan {
+                    callable: SymbolKey::from("foo"),
+                    name: SymbolKey::from("a"),
+                }),
+            )
+            .expect_instr(7, Instruction::LoadString(SymbolKey::from("0return_foo"), lc(3, 1)))
+            .expect_instr(8, Instruction::LeaveScope)
+            .expect_instr(9, Instruction::Return(lc(3, 1)))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_static_errors() {
+        Tester::default()
+            .parse("STATIC n")
+            .compile()
+            .expect_err("1:1: STATIC outside of FUNCTION or SUB")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo(n)\nSTATIC n\nEND SUB")
+            .compile()
+            .expect_err("2:8: Cannot define already-defined symbol N")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo\nSTATIC n\nSTATIC n(3)\nEND SUB")
+            .compile()
+            .expect_err("3:8: Cannot define already-defined symbol N")
+            .check();
+
+        Tester::default()
+            .parse("FUNCTION foo\nSTATIC foo = 1\nEND FUNCTION")
+            .compile()
+            .expect_err("2:8: Cannot define already-defined symbol FOO")
+            .check();
+
+        Tester::default()
+            .parse("SUB foo\nSTATIC n$ = 3\nEND SUB")
+            .compile()
+            .expect_err("2:8: Cannot assign value of type INTEGER to variable of type STRING")
+            .check();
+    }
+
     #[test]
     fn test_compile_goto() {
         Tester::default()
@@ -2254,6 +3305,46 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_on_timer() {
+        Tester::default()
+            .parse("ON TIMER(2) GOSUB @foo\n@foo")
+            .compile()
+            .expect_instr(0, Instruction::PushInteger(2, lc(1, 10)))
+            .expect_instr(1, Instruction::IntegerToDouble)
+            .expect_instr(
+                2,
+                Instruction::SetTimerHandler(TimerHandlerISpan { addr: 3, pos: lc(1, 10) }),
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_compile_on_timer_errors() {
+        Tester::default()
+            .parse("ON TIMER(2) GOSUB @foo")
+            .compile()
+            .expect_err("1:19: Unknown label foo")
+            .check();
+
+        Tester::default()
+            .parse("ON TIMER(\"2\") GOSUB @foo\n@foo")
+            .compile()
+            .expect_err("1:10: STRING is not a number")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_timer() {
+        Tester::default()
+            .parse("TIMER ON: TIMER STOP: TIMER OFF")
+            .compile()
+            .expect_instr(0, Instruction::SetTimerState(TimerAction::On))
+            .expect_instr(1, Instruction::SetTimerState(TimerAction::Stop))
+            .expect_instr(2, Instruction::SetTimerState(TimerAction::Off))
+            .check();
+    }
+
     #[test]
     fn test_compile_on_error_resume_next() {
         Tester::default()
@@ -2263,6 +3354,94 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_compile_on_goto_double_selector() {
+        Tester::default()
+            .parse("ON 2.5 GOTO @a, @b\n@a\n@b")
+            .compile()
+            .expect_instr(0, Instruction::PushDouble(2.5, lc(1, 4)))
+            .expect_instr(1, Instruction::DoubleToInteger)
+            .expect_instr(2, Instruction::Assign(SymbolKey::from("0on1")))
+            .expect_instr(3, Instruction::LoadInteger(SymbolKey::from("0on1"), lc(1, 13)))
+            .expect_instr(4, Instruction::PushInteger(1, lc(1, 13)))
+            .expect_instr(5, Instruction::EqualIntegers(lc(1, 13)))
+            .expect_instr(6, Instruction::JumpIfNotTrue(9))
+            .expect_instr(
+                7,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0on1"), pos: lc(1, 1) }),
+            )
+            .expect_instr(8, Instruction::Jump(JumpISpan { addr: 16 }))
+            .expect_instr(9, Instruction::LoadInteger(SymbolKey::from("0on1"), lc(1, 17)))
+            .expect_instr(10, Instruction::PushInteger(2, lc(1, 17)))
+            .expect_instr(11, Instruction::EqualIntegers(lc(1, 17)))
+            .expect_instr(12, Instruction::JumpIfNotTrue(15))
+            .expect_instr(
+                13,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0on1"), pos: lc(1, 1) }),
+            )
+            .expect_instr(14, Instruction::Jump(JumpISpan { addr: 16 }))
+            .expect_instr(
+                15,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0on1"), pos: lc(1, 1) }),
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_compile_on_gosub() {
+        Tester::default()
+            .define("i", SymbolPrototype::Variable(ExprType::Integer))
+            .define_callable(CallableMetadataBuilder::new("FOO"))
+            .parse("ON i GOSUB @a\nFOO\n@a")
+            .compile()
+            .expect_instr(0, Instruction::LoadInteger(SymbolKey::from("i"), lc(1, 4)))
+            .expect_instr(1, Instruction::Assign(SymbolKey::from("0on1")))
+            .expect_instr(2, Instruction::LoadInteger(SymbolKey::from("0on1"), lc(1, 12)))
+            .expect_instr(3, Instruction::PushInteger(1, lc(1, 12)))
+            .expect_instr(4, Instruction::EqualIntegers(lc(1, 12)))
+            .expect_instr(5, Instruction::JumpIfNotTrue(9))
+            .expect_instr(
+                6,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0on1"), pos: lc(1, 1) }),
+            )
+            .expect_instr(7, Instruction::Call(JumpISpan { addr: 11 }))
+            .expect_instr(8, Instruction::Jump(JumpISpan { addr: 10 }))
+            .expect_instr(
+                9,
+                Instruction::Unset(UnsetISpan { name: SymbolKey::from("0on1"), pos: lc(1, 1) }),
+            )
+            .expect_instr(10, Instruction::BuiltinCall(SymbolKey::from("FOO"), lc(2, 1), 0))
+            .check();
+    }
+
+    #[test]
+    fn test_compile_on_goto_unknown_label() {
+        Tester::default()
+            .parse("@a\nON 1 GOTO @a, @b")
+            .compile()
+            .expect_err("2:15: Unknown label b")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_on_goto_not_a_number() {
+        Tester::default()
+            .parse("ON \"a\" GOSUB @a\n@a")
+            .compile()
+            .expect_err("1:4: STRING is not a number")
+            .check();
+    }
+
+    #[test]
+    fn test_compile_resume() {
+        Tester::default()
+            .parse("RESUME\nRESUME NEXT")
+            .compile()
+            .expect_instr(0, Instruction::Resume(false, lc(1, 1)))
+            .expect_instr(1, Instruction::Resume(true, lc(2, 1)))
+            .check();
+    }
+
     /// Tests that parsing one or more `guards` as supplied after `CASE` yields the expected
     /// expression in `exp_expr`.
     ///
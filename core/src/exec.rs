@@ -19,14 +19,19 @@ use crate::ast::*;
 use crate::bytecode::*;
 use crate::compiler;
 use crate::reader::LineCol;
-use crate::syms::{Callable, Symbol, SymbolKey, Symbols};
+use crate::snapshot::{SavedSymbol, Snapshot};
+use crate::syms::{text_size, Array, Callable, CallableMetadata, Symbol, SymbolKey, Symbols};
 use crate::value;
 use crate::value::double_to_integer;
 use async_channel::{Receiver, Sender, TryRecvError};
+use async_trait::async_trait;
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::io;
 use std::pin::Pin;
 use std::rc::Rc;
+use std::time::Duration;
 
 /// Execution errors.
 #[derive(Debug, thiserror::Error)]
@@ -47,6 +52,10 @@ pub enum Error {
     #[error("{0}: {1}")]
     IoError(LineCol, io::Error),
 
+    /// Execution aborted because the program exceeded a resource limit set by the embedder.
+    #[error("{0}: {1}")]
+    LimitExceeded(LineCol, String),
+
     /// Syntax error.
     #[error("{0}: {1}")]
     SyntaxError(LineCol, String),
@@ -58,13 +67,78 @@ impl Error {
         Self::EvalError(pos, e.message)
     }
 
+    /// Annotates an error `e` caused by an invalid subscript with the name of the array `key`
+    /// that was being indexed at `pos`.
+    fn from_subscript_error(e: value::Error, key: &SymbolKey, pos: LineCol) -> Self {
+        Self::EvalError(pos, format!("{} of {}", e.message, key))
+    }
+
+    /// Returns the position where the error happened, if known.
+    fn pos(&self) -> Option<LineCol> {
+        match self {
+            Error::CompilerError(_) => None,
+            Error::EvalError(pos, _) => Some(*pos),
+            Error::InternalError(pos, _) => Some(*pos),
+            Error::IoError(pos, _) => Some(*pos),
+            Error::LimitExceeded(pos, _) => Some(*pos),
+            Error::SyntaxError(pos, _) => Some(*pos),
+        }
+    }
+
+    /// Returns the numeric code that identifies the kind of this error, as exposed by `ERR`.
+    ///
+    /// The codes of the common error conditions are stable and are modeled after the ones used
+    /// by other BASIC dialects.  Errors that do not fall into any of those conditions get a generic
+    /// code based on their type.
+    pub fn code(&self) -> i32 {
+        let message = match self {
+            Error::CompilerError(_) => return ERR_SYNTAX,
+            Error::IoError(_, e) => {
+                return match e.kind() {
+                    io::ErrorKind::AlreadyExists => ERR_FILE_ALREADY_EXISTS,
+                    io::ErrorKind::NotFound => ERR_FILE_NOT_FOUND,
+                    io::ErrorKind::PermissionDenied => ERR_PERMISSION_DENIED,
+                    _ => ERR_IO,
+                }
+            }
+            Error::EvalError(_, message) => message,
+            Error::InternalError(_, message) => message,
+            Error::LimitExceeded(_, message) => message,
+            Error::SyntaxError(_, message) => message,
+        };
+
+        if message == "Division by zero" || message == "Modulo by zero" {
+            ERR_DIVISION_BY_ZERO
+        } else if message.starts_with("Out of data") {
+            ERR_OUT_OF_DATA
+        } else if message.starts_with("Out of memory") {
+            ERR_OUT_OF_MEMORY
+        } else if message.ends_with(" overflow") || message.ends_with(" underflow") {
+            ERR_OVERFLOW
+        } else if message.starts_with("Subscript ") && message.contains(" out of range ") {
+            ERR_SUBSCRIPT_OUT_OF_RANGE
+        } else if message.starts_with("Cannot assign value of type ")
+            || message.starts_with("Incompatible type")
+            || message.ends_with(" is not a number")
+        {
+            ERR_TYPE_MISMATCH
+        } else if let Error::InternalError(..) = self {
+            ERR_INTERNAL
+        } else {
+            ERR_ILLEGAL_FUNCTION_CALL
+        }
+    }
+
     /// Returns true if this type of error can be caught by `ON ERROR`.
+    ///
+    /// Exceeding a resource limit is not catchable so that programs cannot ignore the limit.
     fn is_catchable(&self) -> bool {
         match self {
             Error::CompilerError(_) => false,
             Error::EvalError(..) => true,
             Error::InternalError(..) => true,
             Error::IoError(..) => true,
+            Error::LimitExceeded(..) => false,
             Error::SyntaxError(..) => true,
         }
     }
@@ -73,6 +147,45 @@ impl Error {
 /// Result for execution return values.
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Error code for a syntax or compilation error.
+pub const ERR_SYNTAX: i32 = 2;
+
+/// Error code for reading past the end of the `DATA` values.
+pub const ERR_OUT_OF_DATA: i32 = 4;
+
+/// Error code for errors that do not fall into any other category.
+pub const ERR_ILLEGAL_FUNCTION_CALL: i32 = 5;
+
+/// Error code for a numeric overflow or underflow.
+pub const ERR_OVERFLOW: i32 = 6;
+
+/// Error code for an allocation that exceeds the memory limit.
+pub const ERR_OUT_OF_MEMORY: i32 = 7;
+
+/// Error code for an array subscript that is out of range.
+pub const ERR_SUBSCRIPT_OUT_OF_RANGE: i32 = 9;
+
+/// Error code for a division or modulo by zero.
+pub const ERR_DIVISION_BY_ZERO: i32 = 11;
+
+/// Error code for a value of an unexpected type.
+pub const ERR_TYPE_MISMATCH: i32 = 13;
+
+/// Error code for an internal error that does not fall into any other category.
+pub const ERR_INTERNAL: i32 = 51;
+
+/// Error code for a file that does not exist.
+pub const ERR_FILE_NOT_FOUND: i32 = 53;
+
+/// Error code for an I/O error that does not fall into any other category.
+pub const ERR_IO: i32 = 57;
+
+/// Error code for a file that already exists.
+pub const ERR_FILE_ALREADY_EXISTS: i32 = 58;
+
+/// Error code for an operation that is not permitted.
+pub const ERR_PERMISSION_DENIED: i32 = 70;
+
 /// Instantiates a new `Err(Error::SyntaxError(...))` from a message.  Syntactic sugar.
 fn new_syntax_error<T, S: Into<String>>(pos: LineCol, message: S) -> Result<T> {
     Err(Error::SyntaxError(pos, message.into()))
@@ -116,6 +229,10 @@ enum InternalStopReason {
     /// interruption, if any, should be processed.
     CheckStop,
 
+    /// Execution terminated because the statement budget has to be processed before executing the
+    /// instruction at the current program counter.
+    CheckBudget,
+
     /// Execution terminated because the machine reached the end of the input.
     Eof,
 
@@ -164,8 +281,55 @@ pub trait Clearable {
     /// Resets any state held by the object to default values.  `syms` contain the symbols of the
     /// machine before they are cleared, in case some state is held in them too.
     fn reset_state(&self, syms: &mut Symbols);
+
+    /// Returns the key under which this object saves its state in machine snapshots, or `None`
+    /// if the object has no state to save.  Keys must be unique among the clearables of a machine.
+    fn state_key(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Serializes the state held by the object for inclusion in a machine snapshot.  Only called
+    /// if `state_key` returns a key.
+    fn save_state(&self) -> Vec<u8> {
+        vec![]
+    }
+
+    /// Restores the `state` previously serialized by `save_state`.  The object has already been
+    /// reset by the time this is called.
+    fn restore_state(&self, _state: &[u8]) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Supervisor of long-running executions.
+///
+/// The watchdog is consulted every time the machine checks for stop conditions, which happens on
+/// every iteration of a loop and on every return from a `GOSUB`, so it can count these checks to
+/// bound the amount of work a program does and ask the user what to do when the bound is reached.
+#[async_trait(?Send)]
+pub trait Watchdog {
+    /// Returns true if the execution should stop.
+    async fn should_stop(&mut self) -> bool;
+}
+
+/// Observer of the source lines that a program executes.
+///
+/// The profiler is notified before the machine executes every instruction that can be attributed
+/// to a source line, so implementations must be cheap when the line does not change.
+pub trait Profiler {
+    /// Records that the machine is about to execute an instruction from `line`.
+    fn enter_line(&mut self, line: usize);
+
+    /// Records that the machine finished executing a program.
+    fn leave(&mut self);
 }
 
+/// Type of the function used by the execution loop to measure the intervals between timer events.
+///
+/// The function returns the time elapsed since an arbitrary point in the past, which must not
+/// change during the lifetime of the machine.
+pub type ClockFn = Box<dyn Fn() -> Duration>;
+
 /// Type of the function used by the execution loop to yield execution.
 pub type YieldNowFn = Box<dyn Fn() -> Pin<Box<dyn Future<Output = ()> + 'static>>>;
 
@@ -350,6 +514,27 @@ impl Stack {
     fn top(&self) -> Option<&(Value, LineCol)> {
         self.values.last()
     }
+
+    /// Returns the position of the first EMPTY value within the top `n` elements of the stack.
+    fn find_empty(&self, n: usize) -> Option<LineCol> {
+        debug_assert!(n <= self.values.len());
+        self.values[self.values.len() - n..].iter().find_map(|(value, pos)| match value {
+            Value::Empty(_) => Some(*pos),
+            _ => None,
+        })
+    }
+
+    /// Replaces the EMPTY value at the top of the stack, if any, with an EMPTY value of `etype`
+    /// and returns true if it did so.
+    fn cast_empty(&mut self, etype: ExprType) -> bool {
+        match self.values.last_mut() {
+            Some((Value::Empty(vtype), _pos)) => {
+                *vtype = etype;
+                true
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Provides controlled access to the parameters passed to a callable.
@@ -378,6 +563,11 @@ impl<'s> Scope<'s> {
         self.nargs = 0;
     }
 
+    /// Returns the position of the callable that owns this scope.
+    pub(crate) fn pos(&self) -> LineCol {
+        self.fref_pos
+    }
+
     /// Annotates an I/O error with the position of the callable that generated it.
     pub fn io_error(&self, e: io::Error) -> Error {
         Error::IoError(self.fref_pos, e)
@@ -493,6 +683,21 @@ impl<'s> Scope<'s> {
         (key, etype)
     }
 
+    /// Pops the top of the stack if it is an EMPTY value and returns true if it did so.
+    ///
+    /// Callables that accept EMPTY values must call this before popping each typed argument.
+    pub fn pop_if_empty(&mut self) -> bool {
+        debug_assert!(self.nargs > 0, "Not enough arguments in scope");
+        match self.stack.top() {
+            Some((Value::Empty(_), _pos)) => {
+                self.nargs -= 1;
+                self.stack.pop();
+                true
+            }
+            _ => false,
+        }
+    }
+
     /// Pops the top of the stack as a variable reference.
     //
     // TODO(jmmv): Remove this variant once the stack values do not carry position
@@ -539,12 +744,54 @@ impl<'s> Scope<'s> {
     }
 }
 
+/// State of the timer events configured by `ON TIMER` and `TIMER`.
+struct TimerEvents {
+    /// Address of the handler and interval between events, or `None` if there is no handler.
+    handler: Option<(Address, Duration)>,
+
+    /// Dispatching state requested by the last `TIMER` statement.
+    state: TimerAction,
+
+    /// Time at which the next event is due, as returned by the machine's clock.
+    next_due: Duration,
+
+    /// Whether an event happened but has not been dispatched yet.
+    pending: bool,
+
+    /// Depth of the address stack right before calling the handler that is running, or `None` if
+    /// no handler is running.
+    running: Option<usize>,
+
+    /// Depth of the symbol scopes where the handler was set.  Events are only dispatched while
+    /// executing at this depth so that the handler does not run within a `FUNCTION` or `SUB`.
+    scope_depth: usize,
+}
+
+impl Default for TimerEvents {
+    fn default() -> Self {
+        Self {
+            handler: None,
+            state: TimerAction::Off,
+            next_due: Duration::ZERO,
+            pending: false,
+            running: None,
+            scope_depth: 0,
+        }
+    }
+}
+
 /// Machine state for the execution of an individual chunk of code.
 struct Context {
     pc: Address,
     addr_stack: Vec<Address>,
     value_stack: Stack,
     err_handler: ErrorHandlerISpan,
+
+    /// Address of the instruction that raised the error being processed by an `ON ERROR GOTO`
+    /// handler, or `None` if no handler is running.
+    resume_pc: Option<Address>,
+
+    timer: TimerEvents,
 }
 
 impl Default for Context {
@@ -554,18 +801,151 @@ impl Default for Context {
             addr_stack: vec![],
             value_stack: Stack::default(),
             err_handler: ErrorHandlerISpan::None,
+            resume_pc: None,
+            timer: TimerEvents::default(),
+        }
+    }
+}
+
+/// Details about the last error caught during execution.
+struct LastError {
+    message: String,
+    code: i32,
+    line: usize,
+}
+
+/// Returns the address of the first instruction of the statement that follows the one that
+/// contains the instruction at `pc`.
+fn next_statement(instrs: &[Instruction], pc: Address) -> Address {
+    match instrs[pc..].iter().position(Instruction::is_statement) {
+        Some(offset) => pc + offset + 1,
+        None => instrs.len(),
+    }
+}
+
+/// Returns the address of the first instruction of the statement that contains the instruction at
+/// `pc`.
+fn statement_start(instrs: &[Instruction], pc: Address) -> Address {
+    match instrs[..pc].iter().rposition(Instruction::is_statement) {
+        Some(addr) => addr + 1,
+        None => 0,
+    }
+}
+
+/// Counter of the statements executed by the machine to support periodic yielding and a limit on
+/// the amount of work that a program can do.
+///
+/// Statements are only counted when any of these features is enabled so that the tight inner loop
+/// of the interpreter pays nothing for them otherwise.
+#[derive(Default)]
+struct StatementBudget {
+    /// Whether statements have to be counted, which happens if any of the features is enabled.
+    enabled: bool,
+
+    /// Number of statements to execute between calls to the yielding function, if any.
+    yield_interval: Option<u64>,
+
+    /// Maximum number of statements that a program can execute, if any.
+    limit: Option<u64>,
+
+    /// Number of statements executed since the outermost execution started.
+    executed: u64,
+
+    /// Value of `executed` at which the next yield must happen.
+    next_yield: u64,
+
+    /// Value of `executed` at which the inner loop must stop to process the budget.
+    next_check: u64,
+}
+
+impl StatementBudget {
+    /// Resets the counters at the beginning of an execution.
+    fn reset(&mut self) {
+        self.enabled = self.yield_interval.is_some() || self.limit.is_some();
+        self.executed = 0;
+        self.next_yield = self.yield_interval.unwrap_or(u64::MAX);
+        self.update_next_check();
+    }
+
+    /// Computes the point at which the inner loop must stop next.
+    fn update_next_check(&mut self) {
+        self.next_check = self.next_yield.min(self.limit.unwrap_or(u64::MAX));
+    }
+
+    /// Accounts for the execution of `instr` and returns true if the inner loop has to stop to
+    /// process the budget before executing it.
+    fn count(&mut self, instr: &Instruction) -> bool {
+        if !instr.is_statement() {
+            return false;
+        }
+        if self.executed == self.next_check {
+            return true;
+        }
+        self.executed += 1;
+        false
+    }
+
+    /// Returns true if the periodic yield is due and schedules the next one.
+    fn take_yield(&mut self) -> bool {
+        match self.yield_interval {
+            Some(interval) if self.executed >= self.next_yield => {
+                self.next_yield = self.executed.saturating_add(interval);
+                self.update_next_check();
+                true
+            }
+            _ => false,
         }
     }
 }
 
+/// Tracks an active execution by incrementing a nesting counter for as long as it is alive.
+///
+/// This decrements the counter even if the execution future is dropped before completion.
+struct DepthGuard(Rc<Cell<usize>>);
+
+impl DepthGuard {
+    /// Increments `depth` until the returned guard is dropped.
+    fn new(depth: Rc<Cell<usize>>) -> Self {
+        depth.set(depth.get() + 1);
+        Self(depth)
+    }
+}
+
+impl Drop for DepthGuard {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+/// Returns the position of the statement that contains the instruction at `pc`, or of the closest
+/// instruction that has a position.
+fn statement_pos(instrs: &[Instruction], pc: Address) -> LineCol {
+    let start = statement_start(instrs, pc);
+    instrs[start..]
+        .iter()
+        .find_map(Instruction::pos)
+        .or_else(|| instrs[..start].iter().rev().find_map(Instruction::pos))
+        .unwrap_or(LineCol { line: 0, col: 0 })
+}
+
 /// Executes an EndBASIC program and tracks its state.
 pub struct Machine {
     symbols: Symbols,
     clearables: Vec<Box<dyn Clearable>>,
     yield_now_fn: Option<YieldNowFn>,
+    clock_fn: Option<ClockFn>,
     signals_chan: (Sender<Signal>, Receiver<Signal>),
-    last_error: Option<String>,
+    watchdog: Option<Box<dyn Watchdog>>,
+    profiler: Option<Box<dyn Profiler>>,
+    args: Vec<String>,
+    last_error: Option<LastError>,
     data: Vec<Option<Value>>,
+    user_callables: HashMap<SymbolKey, CallableMetadata>,
+    poisoned: bool,
+    empty_values: bool,
+    stop_request: Option<StopReason>,
+    budget: StatementBudget,
+    exec_depth: Rc<Cell<usize>>,
 }
 
 impl Default for Machine {
@@ -590,9 +970,19 @@ impl Machine {
             symbols: Symbols::default(),
             clearables: vec![],
             yield_now_fn,
+            clock_fn: None,
             signals_chan: signals,
+            watchdog: None,
+            profiler: None,
+            args: vec![],
             last_error: None,
             data: vec![],
+            user_callables: HashMap::default(),
+            poisoned: false,
+            empty_values: false,
+            stop_request: None,
+            budget: StatementBudget::default(),
+            exec_depth: Rc::from(Cell::new(0)),
         }
     }
 
@@ -616,18 +1006,228 @@ impl Machine {
         self.signals_chan.0.clone()
     }
 
+    /// Obtains a channel via which to receive the signals sent to the machine.
+    ///
+    /// This is intended for callables that block for long periods of time so that they can react
+    /// to signals, such as a break request, while they wait.  Any signal received this way is
+    /// consumed and thus will not be seen by the machine.
+    pub fn get_signals_rx(&self) -> Receiver<Signal> {
+        self.signals_chan.1.clone()
+    }
+
+    /// Installs `watchdog` to supervise subsequent executions, or removes the current one if
+    /// `None`, and returns the previously-installed watchdog.
+    pub fn set_watchdog(
+        &mut self,
+        watchdog: Option<Box<dyn Watchdog>>,
+    ) -> Option<Box<dyn Watchdog>> {
+        std::mem::replace(&mut self.watchdog, watchdog)
+    }
+
+    /// Installs `clock_fn` to measure the intervals between timer events in subsequent executions,
+    /// or removes the current one if `None`, and returns the previously-installed clock.
+    ///
+    /// Programs cannot use `ON TIMER` unless a clock is installed.
+    pub fn set_clock_fn(&mut self, clock_fn: Option<ClockFn>) -> Option<ClockFn> {
+        std::mem::replace(&mut self.clock_fn, clock_fn)
+    }
+
+    /// Installs `profiler` to observe subsequent executions, or removes the current one if `None`,
+    /// and returns the previously-installed profiler.
+    pub fn set_profiler(
+        &mut self,
+        profiler: Option<Box<dyn Profiler>>,
+    ) -> Option<Box<dyn Profiler>> {
+        std::mem::replace(&mut self.profiler, profiler)
+    }
+
+    /// Configures the machine to call the yielding function once every `interval` executed
+    /// statements, or at every loop iteration if `None`, and returns the previous setting.
+    ///
+    /// The default of yielding at every loop iteration is cheap for native builds, where the
+    /// yielding function is usually not set, but hosts with an expensive yielding function may
+    /// want to do it less often.  Break signals and the watchdog are still checked at every loop
+    /// iteration.
+    pub fn set_yield_interval(&mut self, interval: Option<u64>) -> Option<u64> {
+        let interval = interval.map(|n| n.max(1));
+        std::mem::replace(&mut self.budget.yield_interval, interval)
+    }
+
+    /// Limits the number of statements that a single execution can run to `limit`, or removes
+    /// the limit if `None`, and returns the previous setting.
+    ///
+    /// Exceeding the limit aborts execution with an `Error::LimitExceeded` error that points at
+    /// the statement that could not run.  Programs cannot catch this error via `ON ERROR`.
+    pub fn set_statement_limit(&mut self, limit: Option<u64>) -> Option<u64> {
+        std::mem::replace(&mut self.budget.limit, limit)
+    }
+
+    /// Returns the arguments passed to the program, where the first one is the program name.
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    /// Sets the arguments passed to the program to `args`, where the first one should be the
+    /// program name.
+    ///
+    /// The arguments are not program state so they survive `clear` operations.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
     /// Resets the state of the machine by clearing all variable.
     pub fn clear(&mut self) {
+        self.clear_except(&HashSet::default())
+    }
+
+    /// Resets the state of the machine by clearing all variables except for the variables and
+    /// arrays named in `keep`, which retain their values.
+    pub fn clear_except(&mut self, keep: &HashSet<SymbolKey>) {
+        for clearable in self.clearables.as_slice() {
+            clearable.reset_state(&mut self.symbols);
+        }
+        self.symbols.clear_except(keep);
+        self.last_error = None;
+        self.empty_values = false;
+    }
+
+    /// Captures the variables and arrays of the machine, along with the state of its clearable
+    /// objects, into a snapshot that can be applied later on with `restore`.
+    ///
+    /// Only the symbols defined at the top level of the program and the shared symbols are
+    /// captured, so this should be called while the machine is not executing any callable.
+    pub fn snapshot(&self) -> Snapshot {
+        let saved = |symbols: Vec<(&SymbolKey, &Symbol)>| {
+            symbols
+                .into_iter()
+                .map(|(key, symbol)| (key.clone(), SavedSymbol::from_symbol(symbol)))
+                .collect::<Vec<_>>()
+        };
+        let mut states = vec![];
+        for clearable in self.clearables.as_slice() {
+            if let Some(key) = clearable.state_key() {
+                states.push((key.to_owned(), clearable.save_state()));
+            }
+        }
+
+        Snapshot {
+            globals: saved(self.symbols.user_globals()),
+            locals: saved(self.symbols.user_top_locals()),
+            empty_values: self.empty_values,
+            states,
+        }
+    }
+
+    /// Replaces the variables and arrays of the machine, along with the state of its clearable
+    /// objects, with those captured in `snapshot`.
+    ///
+    /// Clearable objects whose state is not in the snapshot are reset as `clear` does.  If the
+    /// snapshot defines a symbol that clashes with a callable of this machine, the machine is left
+    /// untouched; if the state of a clearable object cannot be restored, the machine is left
+    /// cleared.
+    pub fn restore(&mut self, snapshot: Snapshot) -> io::Result<()> {
+        let live = |symbols: Vec<(SymbolKey, SavedSymbol)>| {
+            symbols.into_iter().map(|(key, symbol)| (key, symbol.into_symbol())).collect::<Vec<_>>()
+        };
+        let keys = snapshot.globals.iter().chain(snapshot.locals.iter()).map(|(key, _)| key);
+        self.symbols
+            .check_not_callables(keys)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.message))?;
+
         for clearable in self.clearables.as_slice() {
             clearable.reset_state(&mut self.symbols);
         }
-        self.symbols.clear();
+        self.symbols.replace_top_level(live(snapshot.globals), live(snapshot.locals));
         self.last_error = None;
+        self.empty_values = snapshot.empty_values;
+
+        for clearable in self.clearables.as_slice() {
+            let key = match clearable.state_key() {
+                Some(key) => key,
+                None => continue,
+            };
+            if let Some((_key, state)) = snapshot.states.iter().find(|(k, _state)| k == key) {
+                if let Err(e) = clearable.restore_state(state) {
+                    self.clear();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Asks the machine to stop the execution of the current program with `reason` as soon as the
+    /// callable that is currently running returns.
+    ///
+    /// This is intended for callables that take over the execution of the program, such as one
+    /// that runs a different program in its place.
+    pub fn request_stop(&mut self, reason: StopReason) {
+        self.stop_request = Some(reason);
+    }
+
+    /// Returns true if EMPTY values are enabled.
+    pub fn empty_values(&self) -> bool {
+        self.empty_values
+    }
+
+    /// Enables EMPTY values until the machine is cleared.
+    ///
+    /// Once enabled, callables may store `Value::Empty` in variables (such as `READ` does for
+    /// missing `DATA` values) and the machine validates that these never reach an operator or a
+    /// callable that does not accept them.
+    pub fn enable_empty_values(&mut self) {
+        self.empty_values = true;
+    }
+
+    /// Fails if any of the `nargs` arguments at the top of the stack is an EMPTY value that the
+    /// callable described by `metadata` does not accept.
+    fn check_empty_args(
+        &self,
+        context: &Context,
+        metadata: &CallableMetadata,
+        nargs: usize,
+    ) -> Result<()> {
+        if self.empty_values && !metadata.accepts_empty() {
+            if let Some(pos) = context.value_stack.find_empty(nargs) {
+                return Err(Error::EvalError(
+                    pos,
+                    format!("{} does not accept EMPTY values", metadata.name()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Marks the machine as poisoned after an execution was abruptly interrupted (e.g. by a panic
+    /// in a callable) and discards any per-execution state that was left behind.
+    ///
+    /// The symbols are left untouched so that the user can still inspect them, but their contents
+    /// may be inconsistent.  Callers should refuse to run new programs on a poisoned machine.
+    pub fn poison(&mut self) {
+        self.poisoned = true;
+        self.data.clear();
+        self.symbols.leave_all_scopes();
+    }
+
+    /// Returns true if the machine was poisoned by a previous call to `poison`.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned
     }
 
     /// Returns the last execution error.
     pub fn last_error(&self) -> Option<&str> {
-        self.last_error.as_deref()
+        self.last_error.as_ref().map(|e| e.message.as_str())
+    }
+
+    /// Returns the code of the last execution error, or 0 if there has been none.
+    pub fn last_error_code(&self) -> i32 {
+        self.last_error.as_ref().map(|e| e.code).unwrap_or(0)
+    }
+
+    /// Returns the line where the last execution error happened, or 0 if there has been none or if
+    /// its location is not known.
+    pub fn last_error_line(&self) -> usize {
+        self.last_error.as_ref().map(|e| e.line).unwrap_or(0)
     }
 
     /// Obtains immutable access to the data values available during the *current* execution.
@@ -645,17 +1245,104 @@ impl Machine {
         &mut self.symbols
     }
 
+    /// Obtains the metadata of the user-defined callable `key` in the program being executed, or
+    /// `None` if it is not defined.
+    pub fn get_user_callable(&self, key: &SymbolKey) -> Option<&CallableMetadata> {
+        self.user_callables.get(key)
+    }
+
     /// Returns true if execution should stop because we have hit a stop condition.
     async fn should_stop(&mut self) -> bool {
-        if let Some(yield_now) = self.yield_now_fn.as_ref() {
-            (yield_now)().await;
+        let yield_due = self.budget.yield_interval.is_none() || self.budget.take_yield();
+        if yield_due {
+            if let Some(yield_now) = self.yield_now_fn.as_ref() {
+                (yield_now)().await;
+            }
         }
 
         match self.signals_chan.1.try_recv() {
-            Ok(Signal::Break) => true,
-            Err(TryRecvError::Empty) => false,
+            Ok(Signal::Break) => return true,
+            Err(TryRecvError::Empty) => (),
             Err(TryRecvError::Closed) => panic!("Channel unexpectedly closed"),
         }
+
+        match self.watchdog.as_mut() {
+            Some(watchdog) => watchdog.should_stop().await,
+            None => false,
+        }
+    }
+
+    /// Handles an `ON TIMER` statement that sets the handler at `addr` to run every `interval`
+    /// seconds, where `interval` was computed by the expression at `pos`.
+    fn set_timer_handler(
+        &self,
+        context: &mut Context,
+        addr: Address,
+        interval: f64,
+        pos: LineCol,
+    ) -> Result<()> {
+        let clock_fn = match self.clock_fn.as_ref() {
+            Some(clock_fn) => clock_fn,
+            None => return new_syntax_error(pos, "Timer events are not available"),
+        };
+        if !interval.is_finite() || interval <= 0.0 {
+            return new_syntax_error(pos, format!("Timer interval {} must be positive", interval));
+        }
+        let interval = Duration::from_secs_f64(interval);
+
+        context.timer.handler = Some((addr, interval));
+        context.timer.next_due = clock_fn() + interval;
+        context.timer.pending = false;
+        context.timer.scope_depth = self.symbols.scopes_depth();
+        Ok(())
+    }
+
+    /// Handles a `TIMER` statement that requests the given `action`.
+    fn set_timer_state(&self, context: &mut Context, action: TimerAction) {
+        let timer = &mut context.timer;
+        if timer.state == TimerAction::Off && action != TimerAction::Off {
+            if let (Some(clock_fn), Some((_addr, interval))) = (&self.clock_fn, timer.handler) {
+                timer.next_due = clock_fn() + interval;
+            }
+        }
+        if action == TimerAction::Off {
+            timer.pending = false;
+        }
+        timer.state = action;
+    }
+
+    /// Checks if a timer event is due and, if the handler can run, calls it.
+    ///
+    /// This must only be called between statements, as the handler interrupts the current
+    /// statement stream and resumes it at `context.pc` once it returns.
+    fn poll_timer(&self, context: &mut Context) {
+        let timer = &mut context.timer;
+        let (addr, interval) = match timer.handler {
+            Some(handler) => handler,
+            None => return,
+        };
+
+        let now = (self.clock_fn.as_ref().expect("Handler can only be set with a clock"))();
+        if now >= timer.next_due {
+            timer.pending = true;
+            // Keep events on their original schedule unless we fell behind by a whole interval,
+            // in which case missed events are coalesced into the pending one.
+            timer.next_due += interval;
+            if timer.next_due <= now {
+                timer.next_due = now + interval;
+            }
+        }
+
+        if timer.pending
+            && timer.state == TimerAction::On
+            && timer.running.is_none()
+            && self.symbols.scopes_depth() == timer.scope_depth
+        {
+            timer.pending = false;
+            timer.running = Some(context.addr_stack.len());
+            context.addr_stack.push(context.pc);
+            context.pc = addr;
+        }
     }
 
     /// Handles an array assignment.
@@ -672,11 +1359,23 @@ impl Machine {
             ds.push(i);
         }
 
-        let (value, _pos) = context.value_stack.pop().unwrap();
+        let (value, pos) = context.value_stack.pop().unwrap();
+
+        let old_size = match self.symbols.load(key) {
+            Some(Symbol::Array(array)) => array
+                .text_size_at(&ds)
+                .map_err(|e| Error::from_subscript_error(e, key, vref_pos))?,
+            _ => unreachable!("Array existence and type checking has been done at compile time"),
+        };
+        self.symbols
+            .resize(old_size, text_size(&value))
+            .map_err(|e| Error::from_value_error(e, pos))?;
 
         match self.symbols.load_mut(key) {
             Some(Symbol::Array(array)) => {
-                array.assign(&ds, value).map_err(|e| Error::from_value_error(e, vref_pos))?;
+                array
+                    .assign(&ds, value)
+                    .map_err(|e| Error::from_subscript_error(e, key, vref_pos))?;
                 Ok(())
             }
             _ => unreachable!("Array existence and type checking has been done at compile time"),
@@ -698,6 +1397,7 @@ impl Machine {
 
         let metadata = b.metadata();
         debug_assert!(!metadata.is_function());
+        self.check_empty_args(context, metadata, nargs)?;
 
         let scope = Scope::new(&mut context.value_stack, nargs, bref_pos);
 
@@ -716,6 +1416,9 @@ impl Machine {
             }
             ds.push(i as usize);
         }
+        self.symbols
+            .check_allocation(Array::storage_size(span.subtype, &ds))
+            .map_err(|e| Error::from_value_error(e, span.name_pos))?;
         if span.shared {
             self.symbols.dim_shared_array(span.name.clone(), span.subtype, ds);
         } else {
@@ -793,6 +1496,46 @@ impl Machine {
         Ok(())
     }
 
+    /// Handles the replacement of a portion of a string for the `MID$` statement at `pos`.
+    fn exec_overwrite_string(context: &mut Context, pos: LineCol, has_length: bool) -> Result<()> {
+        let replacement = context.value_stack.pop_string();
+        let length =
+            if has_length { Some(context.value_stack.pop_integer_with_pos()) } else { None };
+        let (start, start_pos) = context.value_stack.pop_integer_with_pos();
+        let mut chars = context.value_stack.pop_string().chars().collect::<Vec<char>>();
+
+        if start < 1 {
+            return new_syntax_error(
+                start_pos,
+                format!("Start position {} must be positive", start),
+            );
+        }
+        let start = (start - 1) as usize;
+        if start >= chars.len() {
+            return new_syntax_error(
+                start_pos,
+                format!("Start position {} exceeds string length {}", start + 1, chars.len()),
+            );
+        }
+
+        let mut n = chars.len() - start;
+        if let Some((length, length_pos)) = length {
+            if length < 0 {
+                return new_syntax_error(
+                    length_pos,
+                    format!("Length {} cannot be negative", length),
+                );
+            }
+            n = n.min(length as usize);
+        }
+        for (i, ch) in replacement.chars().take(n).enumerate() {
+            chars[start + i] = ch;
+        }
+
+        context.value_stack.push_string(chars.into_iter().collect(), pos);
+        Ok(())
+    }
+
     /// Handles a binary equality operator for booleans that cannot fail.
     fn exec_equality_boolean_op2<F: Fn(bool, bool) -> bool>(
         context: &mut Context,
@@ -879,15 +1622,17 @@ impl Machine {
         Ok(())
     }
 
-    /// Handles a binary arithmetic operator for doubles that cannot fail.
-    fn exec_arithmetic_string_op2<F: Fn(&str, &str) -> String>(
-        context: &mut Context,
-        op: F,
-        pos: LineCol,
-    ) {
+    /// Concatenates the two strings at the top of the stack if the result fits within the memory
+    /// limit.
+    fn concat_strings(&self, context: &mut Context, pos: LineCol) -> Result<()> {
         let rhs = context.value_stack.pop_string();
-        let lhs = context.value_stack.pop_string();
-        context.value_stack.push_string(op(&lhs, &rhs), pos);
+        let mut lhs = context.value_stack.pop_string();
+        self.symbols
+            .check_allocation(lhs.len() + rhs.len())
+            .map_err(|e| Error::from_value_error(e, pos))?;
+        lhs.push_str(&rhs);
+        context.value_stack.push_string(lhs, pos);
+        Ok(())
     }
 
     /// Evaluates the subscripts of an array reference.
@@ -910,7 +1655,10 @@ impl Machine {
         f: Rc<dyn Callable>,
     ) -> Result<()> {
         let metadata = f.metadata();
-        debug_assert_eq!(return_type, metadata.return_type().unwrap());
+        debug_assert!(
+            return_type == metadata.return_type().unwrap() || metadata.numeric_promotion()
+        );
+        self.check_empty_args(context, metadata, nargs)?;
 
         let scope = Scope::new(&mut context.value_stack, nargs, fref_pos);
         f.exec(scope, self).await?;
@@ -942,8 +1690,7 @@ impl Machine {
             Some(Symbol::Array(array)) => {
                 let value = array
                     .index(&subscripts)
-                    .cloned()
-                    .map_err(|e| Error::from_value_error(e, vref_pos))?;
+                    .map_err(|e| Error::from_subscript_error(e, key, vref_pos))?;
                 context.value_stack.push((value, vref_pos));
                 Ok(())
             }
@@ -1023,7 +1770,30 @@ impl Machine {
         instrs: &[Instruction],
     ) -> Result<InternalStopReason> {
         while context.pc < instrs.len() {
+            if context.timer.state != TimerAction::Off
+                && (context.pc == 0 || instrs[context.pc - 1].is_statement())
+            {
+                self.poll_timer(context);
+            }
+
             let instr = &instrs[context.pc];
+            if self.budget.enabled && self.budget.count(instr) {
+                return Ok(InternalStopReason::CheckBudget);
+            }
+            if let Some(profiler) = self.profiler.as_mut() {
+                if let Some(pos) = instr.pos() {
+                    profiler.enter_line(pos.line);
+                }
+            }
+            if self.empty_values {
+                let noperands = instr.noperands();
+                if let Some(pos) = context.value_stack.find_empty(noperands) {
+                    return Err(Error::EvalError(
+                        pos,
+                        "Cannot operate on an EMPTY value".to_owned(),
+                    ));
+                }
+            }
             match instr {
                 Instruction::LogicalAnd(pos) => {
                     Machine::exec_logical_op2(context, |lhs, rhs| lhs && rhs, *pos);
@@ -1246,17 +2016,13 @@ impl Machine {
                 }
 
                 Instruction::ConcatStrings(pos) => {
-                    Machine::exec_arithmetic_string_op2(
-                        context,
-                        |lhs, rhs| lhs.to_owned() + rhs,
-                        *pos,
-                    );
+                    self.concat_strings(context, *pos)?;
                     context.pc += 1;
                 }
 
                 Instruction::Assign(key) => {
-                    let (value, _pos) = context.value_stack.pop().unwrap();
-                    self.symbols.assign(key, value);
+                    let (value, pos) = context.value_stack.pop().unwrap();
+                    self.symbols.assign(key, value).map_err(|e| Error::from_value_error(e, pos))?;
                     context.pc += 1;
                 }
 
@@ -1285,6 +2051,10 @@ impl Machine {
                 }
 
                 Instruction::DoubleToInteger => {
+                    if context.value_stack.cast_empty(ExprType::Integer) {
+                        context.pc += 1;
+                        continue;
+                    }
                     let (d, pos) = context.value_stack.pop_double_with_pos();
                     let i = double_to_integer(d.round())
                         .map_err(|e| Error::from_value_error(e, pos))?;
@@ -1292,10 +2062,32 @@ impl Machine {
                     context.pc += 1;
                 }
 
-                Instruction::FunctionCall(name, return_type, pos, nargs) => {
-                    return Ok(InternalStopReason::Upcall(UpcallData {
-                        name: name.clone(),
-                        return_type: Some(*return_type),
+                Instruction::DoubleToSubscript(span) => {
+                    if context.value_stack.cast_empty(ExprType::Integer) {
+                        context.pc += 1;
+                        continue;
+                    }
+                    let (d, pos) = context.value_stack.pop_double_with_pos();
+                    let i = match double_to_integer(d) {
+                        Ok(i) => i,
+                        Err(e) => {
+                            let e = match self.symbols.load(&span.name) {
+                                Some(Symbol::Array(array)) => {
+                                    array.subscript_error(d.round(), span.dimension)
+                                }
+                                _ => e,
+                            };
+                            return Err(Error::from_subscript_error(e, &span.name, span.name_pos));
+                        }
+                    };
+                    context.value_stack.push_integer(i, pos);
+                    context.pc += 1;
+                }
+
+                Instruction::FunctionCall(name, return_type, pos, nargs) => {
+                    return Ok(InternalStopReason::Upcall(UpcallData {
+                        name: name.clone(),
+                        return_type: Some(*return_type),
                         pos: *pos,
                         nargs: *nargs,
                     }));
@@ -1326,11 +2118,30 @@ impl Machine {
                 }
 
                 Instruction::IntegerToDouble => {
+                    if context.value_stack.cast_empty(ExprType::Double) {
+                        context.pc += 1;
+                        continue;
+                    }
                     let (i, pos) = context.value_stack.pop_integer_with_pos();
                     context.value_stack.push_double(i as f64, pos);
                     context.pc += 1;
                 }
 
+                Instruction::ExprJump(addr) => {
+                    debug_assert!(*addr > context.pc, "Expression jumps only go forward");
+                    context.pc = *addr;
+                }
+
+                Instruction::ExprJumpIfNotTrue(addr) => {
+                    debug_assert!(*addr > context.pc, "Expression jumps only go forward");
+                    let cond = context.value_stack.pop_boolean();
+                    if cond {
+                        context.pc += 1;
+                    } else {
+                        context.pc = *addr;
+                    }
+                }
+
                 Instruction::Jump(span) => {
                     let old_pc = context.pc;
                     context.pc = span.addr;
@@ -1382,39 +2193,47 @@ impl Machine {
                     context.pc += 1;
                 }
 
+                Instruction::LinkStatic(span) => {
+                    if self.symbols.link_static(&span.callable, &span.name) {
+                        context.pc = span.addr;
+                    } else {
+                        context.pc += 1;
+                    }
+                }
+
                 Instruction::LoadBoolean(key, pos) => {
-                    let b = match self.load(key, *pos)? {
-                        Value::Boolean(b) => b,
+                    match self.load(key, *pos)? {
+                        Value::Boolean(b) => context.value_stack.push_boolean(*b, *pos),
+                        value @ Value::Empty(_) => context.value_stack.push((value.clone(), *pos)),
                         _ => unreachable!("Types are validated at compilation time"),
-                    };
-                    context.value_stack.push_boolean(*b, *pos);
+                    }
                     context.pc += 1;
                 }
 
                 Instruction::LoadDouble(key, pos) => {
-                    let d = match self.load(key, *pos)? {
-                        Value::Double(d) => d,
+                    match self.load(key, *pos)? {
+                        Value::Double(d) => context.value_stack.push_double(*d, *pos),
+                        value @ Value::Empty(_) => context.value_stack.push((value.clone(), *pos)),
                         _ => unreachable!("Types are validated at compilation time"),
-                    };
-                    context.value_stack.push_double(*d, *pos);
+                    }
                     context.pc += 1;
                 }
 
                 Instruction::LoadInteger(key, pos) => {
-                    let i = match self.load(key, *pos)? {
-                        Value::Integer(i) => i,
+                    match self.load(key, *pos)? {
+                        Value::Integer(i) => context.value_stack.push_integer(*i, *pos),
+                        value @ Value::Empty(_) => context.value_stack.push((value.clone(), *pos)),
                         _ => unreachable!("Types are validated at compilation time"),
-                    };
-                    context.value_stack.push_integer(*i, *pos);
+                    }
                     context.pc += 1;
                 }
 
                 Instruction::LoadString(key, pos) => {
-                    let s = match self.load(key, *pos)? {
-                        Value::Text(s) => s,
+                    match self.load(key, *pos)? {
+                        Value::Text(s) => context.value_stack.push_string(s.clone(), *pos),
+                        value @ Value::Empty(_) => context.value_stack.push((value.clone(), *pos)),
                         _ => unreachable!("Types are validated at compilation time"),
-                    };
-                    context.value_stack.push_string(s.clone(), *pos);
+                    }
                     context.pc += 1;
                 }
 
@@ -1439,6 +2258,11 @@ impl Machine {
                     context.pc += 1;
                 }
 
+                Instruction::OverwriteString(pos, has_length) => {
+                    Machine::exec_overwrite_string(context, *pos, *has_length)?;
+                    context.pc += 1;
+                }
+
                 Instruction::PushBoolean(value, pos) => {
                     context.value_stack.push((Value::Boolean(*value), *pos));
                     context.pc += 1;
@@ -1459,19 +2283,55 @@ impl Machine {
                     context.pc += 1;
                 }
 
+                Instruction::Resume(next, pos) => match context.resume_pc.take() {
+                    Some(pc) => {
+                        context.pc = if *next {
+                            next_statement(instrs, pc)
+                        } else {
+                            statement_start(instrs, pc)
+                        };
+                        return Ok(InternalStopReason::CheckStop);
+                    }
+                    None => {
+                        return new_syntax_error(
+                            *pos,
+                            "RESUME without an error to handle".to_owned(),
+                        )
+                    }
+                },
+
                 Instruction::Return(pos) => match context.addr_stack.pop() {
                     Some(addr) => {
                         context.pc = addr;
+                        if context.timer.running == Some(context.addr_stack.len()) {
+                            context.timer.running = None;
+                        }
                         return Ok(InternalStopReason::CheckStop);
                     }
                     None => return new_syntax_error(*pos, "No address to return to".to_owned()),
                 },
 
+                Instruction::SaveStatic(span) => {
+                    self.symbols.save_static(&span.callable, &span.name);
+                    context.pc += 1;
+                }
+
                 Instruction::SetErrorHandler(span) => {
                     context.err_handler = *span;
                     context.pc += 1;
                 }
 
+                Instruction::SetTimerHandler(span) => {
+                    let (interval, pos) = context.value_stack.pop_double_with_pos();
+                    self.set_timer_handler(context, span.addr, interval, pos)?;
+                    context.pc += 1;
+                }
+
+                Instruction::SetTimerState(action) => {
+                    self.set_timer_state(context, *action);
+                    context.pc += 1;
+                }
+
                 Instruction::Unset(span) => {
                     self.symbols
                         .unset(&span.name)
@@ -1497,65 +2357,95 @@ impl Machine {
             return Err(e);
         }
 
-        self.last_error = Some(format!("{}", e));
+        self.last_error = Some(LastError {
+            message: format!("{}", e),
+            code: e.code(),
+            line: e.pos().map(|pos| pos.line).unwrap_or(0),
+        });
 
         match context.err_handler {
             ErrorHandlerISpan::Jump(addr) => {
+                if context.resume_pc.is_some() {
+                    // Errors raised by the handler itself are not caught again to prevent the
+                    // handler from looping forever.
+                    return Err(e);
+                }
+                context.resume_pc = Some(context.pc);
                 context.pc = addr;
                 Ok(())
             }
             ErrorHandlerISpan::None => Err(e),
             ErrorHandlerISpan::ResumeNext => {
-                if instrs[context.pc].is_statement() {
-                    context.pc += 1;
-                } else {
-                    loop {
-                        context.pc += 1;
-                        if context.pc >= instrs.len() {
-                            break;
-                        } else if instrs[context.pc].is_statement() {
-                            context.pc += 1;
-                            break;
-                        }
-                    }
-                }
+                context.pc = next_statement(instrs, context.pc);
                 Ok(())
             }
         }
     }
 
-    /// Executes the instructions given in `instr`.
+    /// Executes the instructions given in `instr` within `context`.
     ///
-    /// This is a helper to `exec`, which prepares the machine with the program's data upfront.
-    async fn exec_with_data(&mut self, instrs: &[Instruction]) -> Result<StopReason> {
-        let mut context = Context::default();
+    /// This is a helper to `exec` and `eval`, which prepare the machine with the program's data
+    /// upfront.
+    async fn exec_with_context(
+        &mut self,
+        context: &mut Context,
+        instrs: &[Instruction],
+    ) -> Result<StopReason> {
+        // Nested executions, such as those started by callables, share the budget of the outermost
+        // one so that they cannot be used to escape its limits.
+        if self.exec_depth.get() == 0 {
+            self.budget.reset();
+        }
+        let _guard = DepthGuard::new(self.exec_depth.clone());
+        self.exec_with_context_and_budget(context, instrs).await
+    }
+
+    /// Executes the instructions given in `instr` within `context` after the statement budget has
+    /// been prepared by `exec_with_context`.
+    async fn exec_with_context_and_budget(
+        &mut self,
+        context: &mut Context,
+        instrs: &[Instruction],
+    ) -> Result<StopReason> {
+        let depth = self.symbols.scopes_depth();
         while context.pc < instrs.len() {
-            match self.exec_until_stop(&mut context, instrs) {
+            match self.exec_until_stop(context, instrs) {
                 Ok(InternalStopReason::CheckStop) => {
                     if self.should_stop().await {
                         return Ok(StopReason::Break);
                     }
                 }
 
+                Ok(InternalStopReason::CheckBudget) => {
+                    if Some(self.budget.executed) == self.budget.limit {
+                        let e = Error::LimitExceeded(
+                            statement_pos(instrs, context.pc),
+                            format!("Statement limit of {} exceeded", self.budget.executed),
+                        );
+                        self.handle_error(instrs, context, e)?;
+                    }
+                    if self.should_stop().await {
+                        return Ok(StopReason::Break);
+                    }
+                }
+
                 Ok(InternalStopReason::Upcall(data)) => {
                     let result;
                     if let Some(return_type) = data.return_type {
                         result = self
-                            .function_call(
-                                &mut context,
-                                &data.name,
-                                return_type,
-                                data.pos,
-                                data.nargs,
-                            )
+                            .function_call(context, &data.name, return_type, data.pos, data.nargs)
                             .await;
                     } else {
-                        result =
-                            self.builtin_call(&mut context, &data.name, data.pos, data.nargs).await;
+                        result = self.builtin_call(context, &data.name, data.pos, data.nargs).await;
                     }
                     match result {
                         Ok(()) => context.pc += 1,
-                        Err(e) => self.handle_error(instrs, &mut context, e)?,
+                        Err(e) => self.handle_error(instrs, context, e)?,
+                    }
+
+                    if let Some(reason) = self.stop_request.take() {
+                        self.symbols.leave_scopes_until(depth);
+                        return Ok(reason);
                     }
                 }
 
@@ -1564,10 +2454,13 @@ impl Machine {
                 }
 
                 Ok(InternalStopReason::Exited(code)) => {
+                    // END may be reached from within a function or subroutine, so discard the
+                    // scopes of all the calls that are still active.
+                    self.symbols.leave_scopes_until(depth);
                     return Ok(StopReason::Exited(code));
                 }
 
-                Err(e) => self.handle_error(instrs, &mut context, e)?,
+                Err(e) => self.handle_error(instrs, context, e)?,
             }
         }
         Ok(StopReason::Eof)
@@ -1577,15 +2470,48 @@ impl Machine {
     ///
     /// Note that this does not consume `self`.  As a result, it is possible to execute multiple
     /// different programs on the same machine, all sharing state.
+    ///
+    /// This can be invoked from within a callable to execute code nested in the running program,
+    /// in which case the nested code has its own data and callables.
     pub async fn exec(&mut self, input: &mut dyn io::Read) -> Result<StopReason> {
         let image = compiler::compile(input, &self.symbols)?;
+        self.exec_image(image).await
+    }
 
-        assert!(self.data.is_empty());
-        self.data = image.data;
-        let result = self.exec_with_data(&image.instrs).await;
-        self.data.clear();
+    /// Executes a program that has already been compiled into `image`.
+    ///
+    /// The image must have been compiled against the current symbols of the machine, as `exec`
+    /// does, or against the symbols that survive a `clear_except` call issued before this, as
+    /// `compiler::compile_preserving` does.
+    pub async fn exec_image(&mut self, image: Image) -> Result<StopReason> {
+        let data = std::mem::replace(&mut self.data, image.data);
+        let user_callables = std::mem::replace(&mut self.user_callables, image.callables);
+        let mut context = Context::default();
+        let result = self.exec_with_context(&mut context, &image.instrs).await;
+        if let Some(profiler) = self.profiler.as_mut() {
+            profiler.leave();
+        }
+        self.user_callables = user_callables;
+        self.data = data;
         result
     }
+
+    /// Evaluates the single expression extracted from the `input` readable and returns its value.
+    ///
+    /// The expression has access to all symbols visible in the current scope.
+    pub async fn eval(&mut self, input: &mut dyn io::Read) -> Result<Value> {
+        let image = compiler::compile_standalone_expr(input, &self.symbols)?;
+
+        let mut context = Context::default();
+        match self.exec_with_context(&mut context, &image.instrs).await? {
+            StopReason::Eof => (),
+            StopReason::Break | StopReason::Exited(_) => {
+                unreachable!("Expressions have no backward jumps nor END statements")
+            }
+        }
+        let (value, _pos) = context.value_stack.pop().expect("Expression must leave a value");
+        Ok(value)
+    }
 }
 
 #[cfg(test)]
@@ -1593,7 +2519,7 @@ mod tests {
     use super::*;
     use crate::testutils::*;
     use futures_lite::future::block_on;
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
     use std::rc::Rc;
 
     /// A clearable that tracks whether it has been called.
@@ -1829,6 +2755,230 @@ mod tests {
         assert!(*cleared.borrow());
     }
 
+    #[test]
+    fn test_clear_except() {
+        let mut machine = Machine::default();
+
+        let cleared = Rc::from(RefCell::from(false));
+        let clearable = Box::from(MockClearable { cleared: cleared.clone() });
+        machine.add_clearable(clearable);
+
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"a = TRUE: b = 1: DIM c(2): c(1) = 5".as_ref()))
+                .expect("Execution failed")
+        );
+        machine.clear_except(&HashSet::from([SymbolKey::from("a"), SymbolKey::from("c")]));
+        match machine.get_symbols().get_auto("a") {
+            Some(Symbol::Variable(Value::Boolean(true))) => (),
+            e => panic!("a was not preserved: {:?}", e),
+        }
+        assert!(machine.get_symbols().get_auto("b").is_none());
+        match machine.get_symbols().get_auto("c") {
+            Some(Symbol::Array(array)) => {
+                assert_eq!(Value::Integer(5), array.index(&[1]).unwrap())
+            }
+            e => panic!("c was not preserved: {:?}", e),
+        }
+        assert!(*cleared.borrow());
+    }
+
+    #[test]
+    fn test_clear_then_redefine_names() {
+        let mut machine = Machine::default();
+        let code = b"a = TRUE: DIM c(2): c(1) = 5: DIM SHARED d: d = 3";
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_ref())).unwrap());
+        machine.clear();
+        assert!(machine.get_symbols().get_auto("a").is_none());
+        assert!(machine.get_symbols().get_auto("c").is_none());
+        assert!(machine.get_symbols().get_auto("d").is_none());
+
+        let code = b"a = \"x\": c = 2.5: DIM d(3) AS BOOLEAN: d(2) = TRUE";
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_ref())).unwrap());
+        match machine.get_symbols().get_auto("a") {
+            Some(Symbol::Variable(Value::Text(s))) => assert_eq!("x", s),
+            e => panic!("a was not redefined: {:?}", e),
+        }
+        match machine.get_symbols().get_auto("c") {
+            Some(Symbol::Variable(Value::Double(d))) => assert_eq!(2.5, *d),
+            e => panic!("c was not redefined: {:?}", e),
+        }
+        match machine.get_symbols().get_auto("d") {
+            Some(Symbol::Array(array)) => {
+                assert_eq!(Value::Boolean(true), array.index(&[2]).unwrap())
+            }
+            e => panic!("d was not redefined: {:?}", e),
+        }
+    }
+
+    /// Clearable that holds a counter and saves it in snapshots.
+    struct CounterClearable {
+        counter: Rc<Cell<u8>>,
+    }
+
+    impl Clearable for CounterClearable {
+        fn reset_state(&self, _syms: &mut Symbols) {
+            self.counter.set(0);
+        }
+
+        fn state_key(&self) -> Option<&'static str> {
+            Some("counter")
+        }
+
+        fn save_state(&self) -> Vec<u8> {
+            vec![self.counter.get()]
+        }
+
+        fn restore_state(&self, state: &[u8]) -> io::Result<()> {
+            match state {
+                [counter] if *counter < 100 => {
+                    self.counter.set(*counter);
+                    Ok(())
+                }
+                _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Bad counter")),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_symbols() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(captured_out.clone()));
+
+        let code = r#"
+            DIM SHARED s AS STRING: s = "shared"
+            b = TRUE: d# = 2.5: i = 7: t$ = "text"
+            DIM a(2, 3) AS INTEGER: a(1, 2) = 12
+            DIM n(2) AS STRING: n(1) = "x"
+        "#;
+        assert_eq!(StopReason::Eof, machine.exec(&mut code.as_bytes()).await.unwrap());
+        let snapshot = machine.snapshot();
+        let restored = Snapshot::from_bytes(&snapshot.to_bytes()).unwrap();
+        assert_eq!(snapshot, restored);
+
+        let code = "s = \"other\": i = 8: DIM a2(1): extra = 1";
+        assert_eq!(StopReason::Eof, machine.exec(&mut code.as_bytes()).await.unwrap());
+        machine.restore(restored).unwrap();
+        assert!(machine.get_symbols().get_auto("a2").is_none());
+        assert!(machine.get_symbols().get_auto("extra").is_none());
+        assert_eq!(machine.get_symbols().memory_usage(), {
+            let mut fresh = Machine::default();
+            fresh.restore(snapshot).unwrap();
+            fresh.get_symbols().memory_usage()
+        });
+
+        match machine.get_symbols().get_auto("s") {
+            Some(Symbol::Variable(Value::Text(s))) => assert_eq!("shared", s),
+            e => panic!("Unexpected symbol {:?}", e),
+        }
+
+        let code = "OUT b; d#; i; t$; a(1, 2); n(1)";
+        assert_eq!(StopReason::Eof, machine.exec(&mut code.as_bytes()).await.unwrap());
+        assert_eq!(&["TRUE 2.5 7 text 12 x"], captured_out.borrow().as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_clearables() {
+        let counter = Rc::from(Cell::new(5));
+        let mut machine = Machine::default();
+        machine.add_clearable(Box::from(CounterClearable { counter: counter.clone() }));
+
+        let snapshot = machine.snapshot();
+        counter.set(9);
+        machine.restore(snapshot.clone()).unwrap();
+        assert_eq!(5, counter.get());
+
+        let mut missing = snapshot.clone();
+        missing.states.clear();
+        counter.set(9);
+        machine.restore(missing).unwrap();
+        assert_eq!(0, counter.get());
+
+        let mut bad = snapshot;
+        bad.states[0].1 = vec![200];
+        assert_eq!(StopReason::Eof, machine.exec(&mut b"a = 3".as_ref()).await.unwrap());
+        counter.set(9);
+        let err = machine.restore(bad).unwrap_err();
+        assert_eq!("Bad counter", format!("{}", err));
+        assert_eq!(0, counter.get());
+        assert!(machine.get_symbols().get_auto("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore_clash_with_callable() {
+        let mut machine = Machine::default();
+        assert_eq!(StopReason::Eof, machine.exec(&mut b"out = 3: a = 4".as_ref()).await.unwrap());
+        let snapshot = machine.snapshot();
+
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(Rc::default()));
+        assert_eq!(StopReason::Eof, machine.exec(&mut b"b = 5".as_ref()).await.unwrap());
+        let err = machine.restore(snapshot).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("Cannot redefine OUT as a variable", format!("{}", err));
+        assert!(machine.get_symbols().get_auto("a").is_none());
+        assert!(machine.get_symbols().get_auto("b").is_some());
+    }
+
+    #[test]
+    fn test_exec_image_preserving() {
+        let mut machine = Machine::default();
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"a = 1: b = 2".as_ref())).expect("Execution failed")
+        );
+
+        let keep = HashSet::from([SymbolKey::from("a")]);
+        let image = compiler::compile_preserving(
+            &mut b"b = \"text\": a = a + 1".as_ref(),
+            machine.get_symbols(),
+            &keep,
+        )
+        .expect("Compilation failed");
+        machine.clear_except(&keep);
+        assert_eq!(StopReason::Eof, block_on(machine.exec_image(image)).expect("Execution failed"));
+        match machine.get_symbols().get_auto("a") {
+            Some(Symbol::Variable(Value::Integer(2))) => (),
+            e => panic!("a was not preserved: {:?}", e),
+        }
+        match machine.get_symbols().get_auto("b") {
+            Some(Symbol::Variable(Value::Text(text))) => assert_eq!("text", text),
+            e => panic!("b was not redefined: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_args_survive_clear() {
+        let mut machine = Machine::default();
+        assert!(machine.args().is_empty());
+        machine.set_args(vec!["prog.bas".to_owned(), "foo".to_owned()]);
+        machine.clear();
+        assert_eq!(["prog.bas", "foo"], machine.args());
+    }
+
+    #[test]
+    fn test_poison() {
+        let mut machine = Machine::default();
+        assert!(!machine.is_poisoned());
+
+        machine.get_mut_symbols().enter_scope();
+        machine.data.push(Some(Value::Integer(3)));
+        machine.poison();
+        assert!(machine.is_poisoned());
+        assert!(machine.get_data().is_empty());
+
+        // The machine must still be usable for simple statements after poisoning, and the flag
+        // must survive a clear.
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"DATA 1: a = 3".as_ref())).expect("Execution failed")
+        );
+        assert!(machine.get_symbols().get_auto("a").is_some());
+        machine.clear();
+        assert!(machine.is_poisoned());
+    }
+
     #[test]
     fn test_get_data() {
         let captured_data = Rc::from(RefCell::from(vec![]));
@@ -1885,6 +3035,52 @@ mod tests {
         assert!(machine.get_data().is_empty());
     }
 
+    #[test]
+    fn test_exec_nested_keeps_data() {
+        let mut machine = Machine::default();
+        machine.data.push(Some(Value::Integer(3)));
+
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"DATA 5, 6".as_ref())).expect("Execution failed")
+        );
+        assert_eq!(&[Some(Value::Integer(3))], machine.get_data());
+    }
+
+    #[test]
+    fn test_eval_ok() {
+        let mut machine = Machine::default();
+        machine.add_callable(SumFunction::new());
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"a = 3: b$ = \"x\"".as_ref())).expect("Execution failed")
+        );
+
+        assert_eq!(Value::Integer(8), block_on(machine.eval(&mut b"SUM(a, 5)".as_ref())).unwrap());
+        assert_eq!(Value::Double(1.5), block_on(machine.eval(&mut b"a / 2.0".as_ref())).unwrap());
+        assert_eq!(
+            Value::Text("xy".to_owned()),
+            block_on(machine.eval(&mut b"b$ + \"y\"".as_ref())).unwrap()
+        );
+        assert_eq!(Value::Boolean(true), block_on(machine.eval(&mut b"a > 2".as_ref())).unwrap());
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let mut machine = Machine::default();
+
+        for (input, exp_error) in [
+            ("", "1:1: Missing expression"),
+            ("3 4", "1:3: Unexpected value in expression"),
+            ("3: a = 4", "1:2: Unexpected <<NEWLINE>> in expression"),
+            ("a = 4", "1:1: Undefined symbol A"),
+            ("1 / 0", "1:3: Division by zero"),
+        ] {
+            let err = block_on(machine.eval(&mut input.as_bytes())).unwrap_err();
+            assert_eq!(exp_error, format!("{}", err), "Input: {}", input);
+        }
+    }
+
     /// Runs the `input` code on a new test machine.
     ///
     /// `golden_in` is the sequence of values to yield by `IN`.
@@ -1904,6 +3100,7 @@ mod tests {
         machine.add_callable(OutfFunction::new(captured_out));
         machine.add_callable(RaiseCommand::new());
         machine.add_callable(RaisefFunction::new());
+        machine.add_callable(StopCommand::new());
         machine.add_callable(SumFunction::new());
         machine.add_callable(TypeCheckFunction::new(Value::Integer(5)));
         block_on(machine.exec(&mut input.as_bytes()))
@@ -1981,7 +3178,10 @@ mod tests {
             "DIM a(2)\na() = 3\n",
             "2:1: Cannot index array with 0 subscripts; need 1",
         );
-        do_simple_error_test("DIM a(1)\na(-1) = 3\n", "2:1: Subscript -1 cannot be negative");
+        do_simple_error_test(
+            "DIM a(1)\na(-1) = 3\n",
+            "2:1: Subscript -1 out of range 0..0 for dimension 1 of A",
+        );
         do_simple_error_test("DIM a(1, 2)\na(1, TRUE) = 3\n", "2:6: BOOLEAN is not a number");
         do_simple_error_test(
             "DIM a(2)\na$(1) = 3",
@@ -1989,6 +3189,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_array_subscript_errors() {
+        do_simple_error_test(
+            "DIM a(10)\ni = 3\nOUT a(i - 6)\n",
+            "3:5: Subscript -3 out of range 0..9 for dimension 1 of A",
+        );
+        do_simple_error_test(
+            "DIM a(10)\nOUT a(10)\n",
+            "2:5: Subscript 10 out of range 0..9 for dimension 1 of A",
+        );
+        do_simple_error_test(
+            "DIM a(3, 4)\na(2, 4) = 1\n",
+            "2:1: Subscript 4 out of range 0..3 for dimension 2 of A",
+        );
+        do_simple_error_test(
+            "DIM a(3, 4)\nOUT a(2.6, 1)\n",
+            "2:5: Subscript 3 out of range 0..2 for dimension 1 of A",
+        );
+        do_simple_error_test(
+            "DIM a(3, 4)\nd = 0.0\nOUT a(1, d / d)\n",
+            "3:5: Subscript NaN out of range 0..3 for dimension 2 of A",
+        );
+        do_simple_error_test(
+            "DIM a(3)\nd = 0.0\na(1.0 / d) = 5\n",
+            "3:1: Subscript inf out of range 0..2 for dimension 1 of A",
+        );
+        do_simple_error_test(
+            "DIM a(3)\nOUT a(-10000000000.0)\n",
+            "2:5: Subscript -10000000000 out of range 0..2 for dimension 1 of A",
+        );
+    }
+
     #[test]
     fn test_assignment_ok_types() {
         do_ok_test("a = TRUE\nOUT a; a?", &[], &["TRUE TRUE"]);
@@ -2085,23 +3317,132 @@ mod tests {
     }
 
     #[test]
-    fn test_end_no_code() {
-        let captured_out = Rc::from(RefCell::from(vec![]));
-        assert_eq!(
-            StopReason::Exited(5),
-            run("OUT 1\nEND 5\nOUT 2", &[], captured_out.clone()).expect("Execution failed")
-        );
-        assert_eq!(&["1"], captured_out.borrow().as_slice());
+    fn test_dim_record_ok() {
+        let code = r#"
+            TYPE Point
+                x AS INTEGER
+                y AS DOUBLE
+                label AS STRING
+            END TYPE
+            DIM p AS Point
+            OUT p.x; p.y; p.label; "."
+            p.x = 3
+            p.y = p.x / 2
+            p.label$ = "origin"
+            OUT p.x; p.y; p.label
+        "#;
+        do_ok_test(code, &[], &["0 0  .", "3 1 origin"]);
     }
 
-    fn do_end_with_code_test(code: u8) {
-        let captured_out = Rc::from(RefCell::from(vec![]));
-        assert_eq!(
-            StopReason::Exited(code),
-            run(&format!("OUT 1: END {}: OUT 2", code), &[], captured_out.clone())
-                .expect("Execution failed")
-        );
-        assert_eq!(&["1"], captured_out.borrow().as_slice());
+    #[test]
+    fn test_dim_record_array_ok() {
+        let code = r#"
+            TYPE Point
+                x AS INTEGER
+                visible AS BOOLEAN
+            END TYPE
+            n = 2
+            DIM a(n, 3) AS Point
+            FOR i = 0 TO 1
+                a(i, i + 1).x = i + 10
+                a(i, i + 1).visible = TRUE
+            NEXT
+            OUT a(0, 1).x; a(0, 1).visible; a(1, 2).x; a(1, 0).x; a(1, 0).visible
+        "#;
+        do_ok_test(code, &[], &["10 TRUE 11 0 FALSE"]);
+    }
+
+    #[test]
+    fn test_record_assignment_copies_fields() {
+        let code = r#"
+            TYPE Item
+                name AS STRING
+                count AS INTEGER
+            END TYPE
+            DIM a AS Item
+            DIM b AS Item
+            DIM list(2) AS Item
+            a.name = "apple": a.count = 3
+            b = a
+            a.count = 4
+            list(1) = b
+            list(0) = list(1)
+            list(0).count = 5
+            OUT a.name; a.count; b.name; b.count
+            OUT list(0).name; list(0).count; list(1).name; list(1).count
+        "#;
+        do_ok_test(code, &[], &["apple 4 apple 3", "apple 5 apple 3"]);
+    }
+
+    #[test]
+    fn test_record_in_sub() {
+        let code = r#"
+            TYPE Point
+                x AS INTEGER
+            END TYPE
+            DIM SHARED origin AS Point
+            SUB show
+                DIM p AS Point
+                p = origin
+                p.x = p.x + 1
+                OUT origin.x; p.x
+            END SUB
+            origin.x = 5
+            show
+            show
+        "#;
+        do_ok_test(code, &[], &["5 6", "5 6"]);
+    }
+
+    #[test]
+    fn test_record_errors() {
+        let types = "TYPE Point\nx AS INTEGER\nEND TYPE\n";
+        do_simple_error_test(&format!("{}DIM p AS Pt", types), "4:10: Undefined type Pt");
+        do_simple_error_test(
+            &format!("{}DIM p AS Point\np.x = \"a\"", types),
+            "5:1: Cannot assign value of type STRING to variable of type INTEGER",
+        );
+        do_simple_error_test(
+            &format!("{}DIM p AS Point\np.x$ = 3", types),
+            "5:1: Incompatible type annotation in p.x$ reference",
+        );
+        do_simple_error_test(
+            &format!("{}DIM p AS Point\nOUT p.z", types),
+            "5:5: Undefined symbol P.Z",
+        );
+        do_simple_error_test(
+            &format!("{}DIM p AS Point\nOUT p", types),
+            "5:5: Cannot use record P as a value",
+        );
+        do_simple_error_test(
+            &format!("{}DIM p AS Point\np = 1", types),
+            "5:5: Expected a record of type POINT",
+        );
+        do_simple_error_test(
+            &format!("{}DIM p(2) AS Point\np(5).x = 1", types),
+            "5:1: Subscript 5 out of range 0..1 for dimension 1 of P.X",
+        );
+        do_simple_error_test("q.x = 1", "1:1: Undefined symbol Q.X");
+    }
+
+    #[test]
+    fn test_end_no_code() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        assert_eq!(
+            StopReason::Exited(5),
+            run("OUT 1\nEND 5\nOUT 2", &[], captured_out.clone()).expect("Execution failed")
+        );
+        assert_eq!(&["1"], captured_out.borrow().as_slice());
+    }
+
+    fn do_end_with_code_test(code: u8) {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        assert_eq!(
+            StopReason::Exited(code),
+            run(&format!("OUT 1: END {}: OUT 2", code), &[], captured_out.clone())
+                .expect("Execution failed")
+        );
+        assert_eq!(&["1"], captured_out.borrow().as_slice());
 
         let captured_out = Rc::from(RefCell::from(vec![]));
         assert_eq!(
@@ -2177,6 +3518,37 @@ mod tests {
         assert_eq!(&["0", "1", "2", "3"], captured_out.borrow().as_slice());
     }
 
+    #[test]
+    fn test_end_unwinds_gosub_and_function_frames() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let input = r#"
+            FUNCTION f(n)
+                OUT "f"; n
+                IF n = 2 THEN END 3
+                f = f(n + 1)
+                OUT "not reached"
+            END FUNCTION
+
+            GOSUB @sub
+            OUT "not reached"
+            END 1
+
+            @sub:
+            OUT "sub"
+            a = f(0)
+            RETURN
+            "#;
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(captured_out.clone()));
+        assert_eq!(
+            StopReason::Exited(3),
+            block_on(machine.exec(&mut input.as_bytes())).expect("Execution failed")
+        );
+        assert_eq!(&["sub", "f 0", "f 1", "f 2"], captured_out.borrow().as_slice());
+        assert_eq!(1, machine.get_symbols().scopes_depth());
+        assert!(machine.get_symbols().get_auto("0return_f").is_none());
+    }
+
     #[test]
     fn test_end_can_resume() {
         let captured_out = Rc::from(RefCell::from(vec![]));
@@ -2200,6 +3572,39 @@ mod tests {
         assert_eq!(&["2"], captured_out.borrow().as_slice());
     }
 
+    #[test]
+    fn test_request_stop() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        assert_eq!(
+            StopReason::Exited(4),
+            run("OUT 1: STOP 4: OUT 2", &[], captured_out.clone()).expect("Execution failed")
+        );
+        assert_eq!(&["1"], captured_out.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_request_stop_unwinds_scopes() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let input =
+            "SUB s\nOUT \"s\"\nSTOP 7\nOUT \"not reached\"\nEND SUB\ns\nOUT \"not reached\"";
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(captured_out.clone()));
+        machine.add_callable(StopCommand::new());
+        assert_eq!(
+            StopReason::Exited(7),
+            block_on(machine.exec(&mut input.as_bytes())).expect("Execution failed")
+        );
+        assert_eq!(&["s"], captured_out.borrow().as_slice());
+        assert_eq!(1, machine.get_symbols().scopes_depth());
+
+        captured_out.borrow_mut().clear();
+        assert_eq!(
+            StopReason::Eof,
+            block_on(machine.exec(&mut b"OUT 3".as_ref())).expect("Execution failed")
+        );
+        assert_eq!(&["3"], captured_out.borrow().as_slice());
+    }
+
     #[tokio::test]
     async fn test_signals_stop() {
         let mut machine = Machine::default();
@@ -2225,6 +3630,316 @@ mod tests {
         }
     }
 
+    /// Watchdog that requests a stop after being consulted `limit` times.
+    struct CountingWatchdog {
+        checks: Rc<Cell<usize>>,
+        limit: usize,
+    }
+
+    #[async_trait(?Send)]
+    impl Watchdog for CountingWatchdog {
+        async fn should_stop(&mut self) -> bool {
+            self.checks.set(self.checks.get() + 1);
+            self.checks.get() >= self.limit
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watchdog() {
+        let mut machine = Machine::default();
+        let checks = Rc::from(Cell::new(0));
+        let watchdog = CountingWatchdog { checks: checks.clone(), limit: 5 };
+        assert!(machine.set_watchdog(Some(Box::from(watchdog))).is_none());
+
+        let input = &mut "i = 0: WHILE TRUE: i = i + 1: WEND".as_bytes();
+        assert_eq!(StopReason::Break, machine.exec(input).await.unwrap());
+        assert_eq!(5, checks.get());
+        assert_eq!(
+            Value::Integer(5),
+            *machine.get_symbols().get_var(&VarRef::new("i", None)).unwrap()
+        );
+
+        let input = &mut "FOR j = 1 TO 3: NEXT".as_bytes();
+        assert_eq!(StopReason::Break, machine.exec(input).await.unwrap());
+        assert_eq!(6, checks.get());
+
+        assert!(machine.set_watchdog(None).is_some());
+        let input = &mut "FOR j = 1 TO 3: NEXT".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(6, checks.get());
+    }
+
+    /// Creates a machine whose yielding function counts how many times it is called in `yields`.
+    fn machine_with_counting_yield(yields: Rc<Cell<usize>>) -> Machine {
+        let yield_now_fn: YieldNowFn = Box::from(move || {
+            yields.set(yields.get() + 1);
+            Box::pin(async {}) as Pin<Box<dyn Future<Output = ()>>>
+        });
+        Machine::with_signals_chan_and_yield_now_fn(async_channel::unbounded(), Some(yield_now_fn))
+    }
+
+    #[tokio::test]
+    async fn test_yield_interval_default_yields_per_iteration() {
+        let yields = Rc::from(Cell::new(0));
+        let mut machine = machine_with_counting_yield(yields.clone());
+
+        let input = &mut "a = 1\nb = 2\nc = 3".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(0, yields.get());
+
+        let input = &mut "FOR i = 1 TO 4: NEXT".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(4, yields.get());
+    }
+
+    #[tokio::test]
+    async fn test_yield_interval_counts_statements() {
+        let yields = Rc::from(Cell::new(0));
+        let mut machine = machine_with_counting_yield(yields.clone());
+        assert!(machine.set_yield_interval(Some(3)).is_none());
+
+        let input =
+            &mut "a = 1: a = 2: a = 3: a = 4: a = 5: a = 6: a = 7: a = 8: a = 9: a = 10".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(3, yields.get());
+
+        assert_eq!(Some(3), machine.set_yield_interval(None));
+        let input = &mut "FOR i = 1 TO 4: NEXT".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(3 + 4, yields.get());
+    }
+
+    #[tokio::test]
+    async fn test_statement_limit_aborts_at_statement() {
+        let mut machine = Machine::default();
+        assert!(machine.set_statement_limit(Some(2)).is_none());
+
+        let input = &mut "a = 1\nb = 2\nc = 3".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("3:5: Statement limit of 2 exceeded", format!("{}", err));
+        assert!(!err.is_catchable());
+        let syms = machine.get_symbols();
+        assert_eq!(Value::Integer(1), *syms.get_var(&VarRef::new("a", None)).unwrap());
+        assert_eq!(Value::Integer(2), *syms.get_var(&VarRef::new("b", None)).unwrap());
+        assert!(syms.get_var(&VarRef::new("c", None)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_statement_limit_not_catchable() {
+        let mut machine = Machine::default();
+        machine.set_statement_limit(Some(100));
+
+        let input = &mut "ON ERROR RESUME NEXT\nWHILE TRUE: WEND".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("2:7: Statement limit of 100 exceeded", format!("{}", err));
+    }
+
+    #[tokio::test]
+    async fn test_statement_limit_resets_per_exec() {
+        let mut machine = Machine::default();
+        machine.set_statement_limit(Some(3));
+
+        for _ in 0..3 {
+            let input = &mut "a = 1: b = 2: c = 3".as_bytes();
+            assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        }
+
+        assert_eq!(Some(3), machine.set_statement_limit(None));
+        let input = &mut "a = 1: b = 2: c = 3: d = 4".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+    }
+
+    /// Creates a machine with a memory limit of `limit` bytes and the test callables.
+    fn machine_with_memory_limit(limit: usize, captured_out: Rc<RefCell<Vec<String>>>) -> Machine {
+        let mut machine = Machine::default();
+        machine.add_callable(LastErrorFunction::new());
+        machine.add_callable(OutCommand::new(captured_out));
+        assert!(machine.get_mut_symbols().set_memory_limit(Some(limit)).is_none());
+        machine
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_dim() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(1000, captured_out);
+
+        let input = &mut "DIM a(200) AS INTEGER".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(800, machine.get_symbols().allocated());
+
+        let input = &mut "DIM b(30) AS DOUBLE".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("1:5: Out of memory: limit is 1000 bytes", format!("{}", err));
+        assert_eq!(ERR_OUT_OF_MEMORY, err.code());
+        assert_eq!(800, machine.get_symbols().allocated());
+
+        let input = &mut "DIM c(2147483647, 2147483647, 2147483647)".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("1:5: Out of memory: limit is 1000 bytes", format!("{}", err));
+
+        let input = &mut "DIM d(50) AS INTEGER".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(1000, machine.get_symbols().allocated());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_concat() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(100, captured_out);
+
+        let input = &mut "a$ = \"x\"\nWHILE TRUE: a$ = a$ + a$: WEND".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("2:21: Out of memory: limit is 100 bytes", format!("{}", err));
+        assert_eq!(
+            Value::Text("x".repeat(64)),
+            *machine.get_symbols().get_var(&VarRef::new("a", None)).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_string_assignment() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(10, captured_out);
+
+        let input = &mut "a$ = \"12345\"".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(5, machine.get_symbols().allocated());
+
+        let input = &mut "b$ = \"123456\"".as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("1:6: Out of memory: limit is 10 bytes", format!("{}", err));
+        assert_eq!(5, machine.get_symbols().allocated());
+
+        let input = &mut "a$ = \"1234567890\"".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(10, machine.get_symbols().allocated());
+
+        let input = &mut "a$ = \"\"".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(0, machine.get_symbols().allocated());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_string_array_elements() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let storage = 2 * size_of::<String>();
+        let mut machine = machine_with_memory_limit(storage + 50, captured_out);
+
+        let input = &mut "DIM a(2) AS STRING".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(storage, machine.get_symbols().allocated());
+
+        let code = format!("a(0) = \"{}\"", "x".repeat(40));
+        assert_eq!(StopReason::Eof, machine.exec(&mut code.as_bytes()).await.unwrap());
+        assert_eq!(storage + 40, machine.get_symbols().allocated());
+
+        let code = format!("a(1) = \"{}\"", "x".repeat(20));
+        let err = machine.exec(&mut code.as_bytes()).await.unwrap_err();
+        assert_eq!(
+            format!("1:8: Out of memory: limit is {} bytes", storage + 50),
+            format!("{}", err)
+        );
+        assert_eq!(storage + 40, machine.get_symbols().allocated());
+
+        let code = format!("a(0) = \"\": a(1) = \"{}\"", "x".repeat(20));
+        assert_eq!(StopReason::Eof, machine.exec(&mut code.as_bytes()).await.unwrap());
+        assert_eq!(storage + 20, machine.get_symbols().allocated());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_strings_released_on_return() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(100, captured_out);
+
+        let code = r#"
+            a$ = "hello"
+            SUB fill
+                DIM local(1) AS STRING
+                local(0) = "some text"
+                text$ = local(0) + local(0)
+            END SUB
+            fill
+            fill
+        "#;
+        let input = &mut code.as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(5, machine.get_symbols().allocated());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_is_catchable() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(100, captured_out.clone());
+
+        let input = &mut "ON ERROR RESUME NEXT\nDIM a(1000)\nOUT LAST_ERROR".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(&["2:5: Out of memory: limit is 100 bytes"], captured_out.borrow().as_slice());
+        assert_eq!(0, machine.get_symbols().allocated());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit_accounting() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with_memory_limit(1000, captured_out);
+
+        let code = r#"
+            DIM SHARED a(100) AS INTEGER
+            SUB fill(n%)
+                DIM local(n) AS DOUBLE
+            END SUB
+            fill 50
+            fill 60
+            fill 20
+            fill 100
+        "#;
+        let input = &mut code.as_bytes();
+        let err = machine.exec(input).await.unwrap_err();
+        assert_eq!("4:21: Out of memory: limit is 1000 bytes", format!("{}", err));
+        assert_eq!(400, machine.get_symbols().allocated());
+
+        machine.clear();
+        assert_eq!(0, machine.get_symbols().allocated());
+        let input = &mut "DIM b(250) AS INTEGER".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(1000, machine.get_symbols().allocated());
+    }
+
+    /// Profiler that records the lines it is told about, skipping consecutive repeats, and that
+    /// records a 0 when execution finishes.
+    struct RecordingProfiler {
+        lines: Rc<RefCell<Vec<usize>>>,
+    }
+
+    impl Profiler for RecordingProfiler {
+        fn enter_line(&mut self, line: usize) {
+            let mut lines = self.lines.borrow_mut();
+            if lines.last() != Some(&line) {
+                lines.push(line);
+            }
+        }
+
+        fn leave(&mut self) {
+            self.lines.borrow_mut().push(0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_profiler() {
+        let mut machine = Machine::default();
+        let lines = Rc::from(RefCell::from(vec![]));
+        let profiler = RecordingProfiler { lines: lines.clone() };
+        assert!(machine.set_profiler(Some(Box::from(profiler))).is_none());
+
+        let input = &mut "a = 1\nFOR i = 1 TO 2\na = a + i\nNEXT\nb = a".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(vec![1, 2, 3, 2, 3, 2, 5, 0], *lines.borrow());
+
+        assert!(machine.set_profiler(None).is_some());
+        let input = &mut "a = 1".as_bytes();
+        assert_eq!(StopReason::Eof, machine.exec(input).await.unwrap());
+        assert_eq!(8, lines.borrow().len());
+    }
+
     async fn do_no_check_stop_test(code: &str) {
         let (tx, rx) = async_channel::unbounded();
         let mut machine = Machine::with_signals_chan_and_yield_now_fn((tx.clone(), rx), None);
@@ -2548,36 +4263,110 @@ mod tests {
     }
 
     #[test]
-    fn test_for_incrementing() {
-        do_ok_test("FOR a = 0 TO 0: OUT a: NEXT", &[], &["0"]);
-        do_ok_test("FOR a = 0 TO 3: OUT a: NEXT", &[], &["0", "1", "2", "3"]);
-        do_ok_test("FOR a = 1.3 TO 3: OUT a: NEXT", &[], &["1.3", "2.3"]);
-        do_ok_test("FOR a = 1.3 TO 3.3: OUT a: NEXT", &[], &["1.3", "2.3", "3.3"]);
-        do_ok_test("FOR a = 1 TO 3.3: OUT a: NEXT", &[], &["1", "2", "3"]);
-        do_ok_test("FOR a = 1 TO 3.7: OUT a: NEXT", &[], &["1", "2", "3"]);
-        do_ok_test("FOR a# = 1 TO 2: OUT a: NEXT", &[], &["1", "2"]);
-        do_ok_test("FOR a = 1.1 TO 2.1: b% = (a * 10): OUT b: NEXT", &[], &["11", "21"]);
-        do_ok_test("FOR a# = 1.1 TO 2.1: b% = (a * 10): OUT b: NEXT", &[], &["11", "21"]);
+    fn test_iif_types() {
+        do_ok_test("OUT IIF(TRUE, FALSE, TRUE); IIF(FALSE, FALSE, TRUE)", &[], &["FALSE TRUE"]);
+        do_ok_test("OUT IIF(TRUE, 1.5, 2.5); IIF(FALSE, 1.5, 2.5)", &[], &["1.5 2.5"]);
+        do_ok_test("OUT IIF(TRUE, 1, 2); IIF(FALSE, 1, 2)", &[], &["1 2"]);
+        do_ok_test(r#"OUT IIF(TRUE, "a", "b"); IIF(FALSE, "a", "b")"#, &[], &["a b"]);
+        do_ok_test("OUT IIF(TRUE, 1, 2.5) / 2; IIF(FALSE, 1.5, 2) / 4", &[], &["0.5 0.5"]);
     }
 
     #[test]
-    fn test_for_incrementing_with_step() {
-        do_ok_test("FOR a = 0 TO 0 STEP 3: OUT a: NEXT", &[], &["0"]);
-        do_ok_test("FOR a = 0 TO 2 STEP 3: OUT a: NEXT", &[], &["0"]);
-        do_ok_test("FOR a = 0 TO 3 STEP 3: OUT a: NEXT", &[], &["0", "3"]);
-        do_ok_test("FOR a = 0 TO 10 STEP 3: OUT a: NEXT", &[], &["0", "3", "6", "9"]);
-        do_ok_test("FOR a = 1 TO 2 STEP 0.4: b% = (a * 10): OUT b: NEXT", &[], &["10", "14", "18"]);
+    fn test_iif_nested() {
+        let code = r#"
+            FOR i = 1 TO 3
+                OUT IIF(i = 1, "one", IIF(i = 2, "two", "many"))
+            NEXT
+        "#;
+        do_ok_test(code, &[], &["one", "two", "many"]);
+    }
+
+    #[test]
+    fn test_iif_only_evaluates_selected_argument() {
+        do_ok_test("n = 0\nOUT IIF(n <> 0, 100 / n, 0)", &[], &["0"]);
+        do_ok_test("n = 4\nOUT IIF(n <> 0, 100 / n, 0)", &[], &["25"]);
+        do_ok_test(r#"OUT IIF(FALSE, RAISEF("internal"), TRUE)"#, &[], &["TRUE"]);
+        do_simple_error_test("n = 0\nOUT IIF(n = 0, 100 / n, 0)", "2:20: Division by zero");
+    }
+
+    #[test]
+    fn test_iif_with_on_error_resume_next() {
         do_ok_test(
-            "FOR a# = 1 TO 2 STEP 0.4: b% = (a * 10): OUT b: NEXT",
+            r#"
+            ON ERROR RESUME NEXT
+            OUT IIF(TRUE, RAISEF("internal"), FALSE) OR IIF(FALSE, TRUE, FALSE): OUT LAST_ERROR
+            "#,
             &[],
-            &["10", "14", "18"],
+            &["3:34: Some internal error"],
         );
     }
 
     #[test]
-    fn test_for_decrementing_with_step() {
-        do_ok_test("FOR a = 0 TO 0 STEP -2: OUT a: NEXT", &[], &["0"]);
-        do_ok_test("FOR a = 2 TO 0 STEP -2: OUT a: NEXT", &[], &["2", "0"]);
+    fn test_iif_errors() {
+        do_simple_error_test("OUT IIF(TRUE, 1)", "1:5: IIF expected cond?, iftrue, iffalse");
+        do_simple_error_test("OUT IIF(1, 2, 3)", "1:9: IIF requires a boolean condition");
+        do_simple_error_test(r#"OUT IIF(TRUE, 2, "3")"#, "1:18: expected INTEGER but found STRING");
+    }
+
+    #[test]
+    fn test_mid_assignment() {
+        do_ok_test(r#"s = "abcdef": MID$(s, 3) = "XY": OUT s"#, &[], &["abXYef"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 1) = "__": OUT s"#, &[], &["__cdef"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 5) = "XYZ": OUT s"#, &[], &["abcdXY"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 2, 2) = "XYZ": OUT s"#, &[], &["aXYdef"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 2, 10) = "XYZ": OUT s"#, &[], &["aXYZef"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 2, 0) = "XYZ": OUT s"#, &[], &["abcdef"]);
+        do_ok_test(r#"s = "abcdef": MID$(s, 2) = "": OUT s"#, &[], &["abcdef"]);
+        do_ok_test(r#"s = "añbc": MID$(s, 2, 2) = "€€€": OUT s"#, &[], &["a€€c"]);
+    }
+
+    #[test]
+    fn test_mid_assignment_errors() {
+        do_simple_error_test(
+            r#"s = "abc": MID$(s, 0) = "x""#,
+            "1:20: Start position 0 must be positive",
+        );
+        do_simple_error_test(
+            r#"s = "abc": MID$(s, 4) = "x""#,
+            "1:20: Start position 4 exceeds string length 3",
+        );
+        do_simple_error_test(
+            r#"s = "abc": MID$(s, 1, -1) = "x""#,
+            "1:23: Length -1 cannot be negative",
+        );
+    }
+
+    #[test]
+    fn test_for_incrementing() {
+        do_ok_test("FOR a = 0 TO 0: OUT a: NEXT", &[], &["0"]);
+        do_ok_test("FOR a = 0 TO 3: OUT a: NEXT", &[], &["0", "1", "2", "3"]);
+        do_ok_test("FOR a = 1.3 TO 3: OUT a: NEXT", &[], &["1.3", "2.3"]);
+        do_ok_test("FOR a = 1.3 TO 3.3: OUT a: NEXT", &[], &["1.3", "2.3", "3.3"]);
+        do_ok_test("FOR a = 1 TO 3.3: OUT a: NEXT", &[], &["1", "2", "3"]);
+        do_ok_test("FOR a = 1 TO 3.7: OUT a: NEXT", &[], &["1", "2", "3"]);
+        do_ok_test("FOR a# = 1 TO 2: OUT a: NEXT", &[], &["1", "2"]);
+        do_ok_test("FOR a = 1.1 TO 2.1: b% = (a * 10): OUT b: NEXT", &[], &["11", "21"]);
+        do_ok_test("FOR a# = 1.1 TO 2.1: b% = (a * 10): OUT b: NEXT", &[], &["11", "21"]);
+    }
+
+    #[test]
+    fn test_for_incrementing_with_step() {
+        do_ok_test("FOR a = 0 TO 0 STEP 3: OUT a: NEXT", &[], &["0"]);
+        do_ok_test("FOR a = 0 TO 2 STEP 3: OUT a: NEXT", &[], &["0"]);
+        do_ok_test("FOR a = 0 TO 3 STEP 3: OUT a: NEXT", &[], &["0", "3"]);
+        do_ok_test("FOR a = 0 TO 10 STEP 3: OUT a: NEXT", &[], &["0", "3", "6", "9"]);
+        do_ok_test("FOR a = 1 TO 2 STEP 0.4: b% = (a * 10): OUT b: NEXT", &[], &["10", "14", "18"]);
+        do_ok_test(
+            "FOR a# = 1 TO 2 STEP 0.4: b% = (a * 10): OUT b: NEXT",
+            &[],
+            &["10", "14", "18"],
+        );
+    }
+
+    #[test]
+    fn test_for_decrementing_with_step() {
+        do_ok_test("FOR a = 0 TO 0 STEP -2: OUT a: NEXT", &[], &["0"]);
+        do_ok_test("FOR a = 2 TO 0 STEP -2: OUT a: NEXT", &[], &["2", "0"]);
         do_ok_test("FOR a = -2 TO -6 STEP -2: OUT a: NEXT", &[], &["-2", "-4", "-6"]);
         do_ok_test("FOR a = 10 TO 1 STEP -2: OUT a: NEXT", &[], &["10", "8", "6", "4", "2"]);
         do_ok_test(
@@ -2841,6 +4630,202 @@ mod tests {
         );
     }
 
+    /// Runs the `input` code on a new test machine whose clock only advances via `ADVANCE`.
+    ///
+    /// `expected_out` is updated with the sequence of calls to `OUT`.
+    fn run_with_clock(input: &str, captured_out: Rc<RefCell<Vec<String>>>) -> Result<StopReason> {
+        let now = Rc::from(Cell::new(Duration::ZERO));
+        let mut machine = Machine::default();
+        machine.set_clock_fn(Some(Box::from({
+            let now = now.clone();
+            move || now.get()
+        })));
+        machine.add_callable(AdvanceCommand::new(now));
+        machine.add_callable(OutCommand::new(captured_out));
+        block_on(machine.exec(&mut input.as_bytes()))
+    }
+
+    /// Runs the `input` code on a new test machine with a fake clock and verifies its output.
+    ///
+    /// `expected_out` is the sequence of expected calls to `OUT`.
+    fn do_timer_ok_test(input: &str, expected_out: &'static [&'static str]) {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        assert_eq!(
+            StopReason::Eof,
+            run_with_clock(input, captured_out.clone()).expect("Execution failed")
+        );
+        assert_eq!(expected_out, captured_out.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_on_timer_dispatch_timing() {
+        do_timer_ok_test(
+            r#"
+            GOTO @main
+            @tick
+            OUT "tick"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER ON
+            FOR i = 1 TO 5
+                OUT "work"; i
+                ADVANCE 0.5
+            NEXT
+            TIMER OFF
+            "#,
+            &["work 1", "work 2", "tick", "work 3", "work 4", "tick", "work 5"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_requires_timer_on() {
+        do_timer_ok_test(
+            r#"
+            GOTO @main
+            @tick
+            OUT "tick"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            ADVANCE 5
+            OUT "done"
+            "#,
+            &["done"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_stop_buffers_events() {
+        do_timer_ok_test(
+            r#"
+            GOTO @main
+            @tick
+            OUT "tick"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER STOP
+            ADVANCE 3
+            OUT "stopped"
+            TIMER ON
+            OUT "resumed"
+            TIMER OFF
+            "#,
+            &["stopped", "tick", "resumed"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_off_discards_events() {
+        do_timer_ok_test(
+            r#"
+            GOTO @main
+            @tick
+            OUT "tick"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER STOP
+            ADVANCE 1.5
+            TIMER OFF
+            TIMER ON
+            OUT "on"
+            ADVANCE 0.5
+            OUT "still"
+            ADVANCE 0.5
+            OUT "done"
+            TIMER OFF
+            "#,
+            &["on", "still", "tick", "done"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_return_resumes_interrupted_code() {
+        do_timer_ok_test(
+            r#"
+            n = 10
+            GOTO @main
+            @tick
+            OUT "tick"; n
+            n = n + 1
+            RETURN
+            @work
+            ADVANCE 1: OUT "in work"; n
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER ON
+            GOSUB @work
+            OUT "after"; n
+            TIMER OFF
+            "#,
+            &["tick 10", "in work 11", "after 11"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_no_reentrancy() {
+        do_timer_ok_test(
+            r#"
+            d = 1.5
+            GOTO @main
+            @tick
+            OUT "tick start"
+            ADVANCE d
+            d = 0
+            OUT "tick end"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER ON
+            ADVANCE 1
+            OUT "main 1"
+            OUT "main 2"
+            TIMER OFF
+            "#,
+            &["tick start", "tick end", "tick start", "tick end", "main 1", "main 2"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_not_dispatched_within_callables() {
+        do_timer_ok_test(
+            r#"
+            SUB work
+                ADVANCE 1
+                OUT "in sub"
+            END SUB
+            GOTO @main
+            @tick
+            OUT "tick"
+            RETURN
+            @main
+            ON TIMER(1) GOSUB @tick
+            TIMER ON
+            work
+            OUT "after"
+            TIMER OFF
+            "#,
+            &["in sub", "tick", "after"],
+        );
+    }
+
+    #[test]
+    fn test_on_timer_errors() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        for (input, exp_error) in [
+            ("ON TIMER(0) GOSUB @a\n@a", "1:10: Timer interval 0 must be positive"),
+            ("ON TIMER(-1.5) GOSUB @a\n@a", "1:10: Timer interval -1.5 must be positive"),
+        ] {
+            let err = run_with_clock(input, captured_out.clone()).unwrap_err();
+            assert_eq!(exp_error, format!("{}", err), "Input: {}", input);
+        }
+
+        do_simple_error_test("ON TIMER(1) GOSUB @a\n@a", "1:10: Timer events are not available");
+    }
+
     #[test]
     fn test_on_error_goto_line() {
         do_ok_test(
@@ -2905,67 +4890,429 @@ mod tests {
     }
 
     #[test]
-    fn test_on_error_resume_next_line_command_failure() {
-        do_ok_test(
-            r#"
-            ON ERROR RESUME NEXT
-            OUT 1
-            RAISE "internal"
-            OUT LAST_ERROR
-            "#,
-            &[],
-            &["1", "4:19: Some internal error"],
-        );
+    fn test_on_error_resume_next_line_command_failure() {
+        do_ok_test(
+            r#"
+            ON ERROR RESUME NEXT
+            OUT 1
+            RAISE "internal"
+            OUT LAST_ERROR
+            "#,
+            &[],
+            &["1", "4:19: Some internal error"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_next_statement_function_failure() {
+        do_ok_test(
+            r#"
+            ON ERROR RESUME NEXT
+            OUT 1: OUT RAISEF("internal"): OUT LAST_ERROR
+            "#,
+            &[],
+            &["1", "3:31: Some internal error"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_next_statement_command_failure() {
+        do_ok_test(
+            r#"
+            ON ERROR RESUME NEXT
+            OUT 1: RAISE "internal": OUT LAST_ERROR
+            "#,
+            &[],
+            &["1", "3:26: Some internal error"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_types() {
+        do_ok_test(
+            r#"ON ERROR RESUME NEXT: OUT RAISEF("argument"): OUT LAST_ERROR"#,
+            &[],
+            &["1:34: Bad argument"],
+        );
+
+        do_ok_test(
+            r#"ON ERROR RESUME NEXT: OUT RAISEF("eval"): OUT LAST_ERROR"#,
+            &[],
+            &["1:34: Some eval error"],
+        );
+
+        do_ok_test(
+            r#"ON ERROR RESUME NEXT: OUT RAISEF("internal"): OUT LAST_ERROR"#,
+            &[],
+            &["1:34: Some internal error"],
+        );
+
+        do_ok_test(
+            r#"ON ERROR RESUME NEXT: OUT RAISEF("io"): OUT LAST_ERROR"#,
+            &[],
+            &["1:34: Some I/O error"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_retries_statement() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @handler
+            i = 0
+            OUT 1: OUT 10 / i: OUT 3
+            OUT 4
+            GOTO @end
+            @handler
+            OUT LAST_ERROR
+            i = 2
+            RESUME
+            @end
+            "#,
+            &[],
+            &["1", "4:27: Division by zero", "5", "3", "4"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_retries_command() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @handler
+            n = 0
+            OUT "before"; SUM(1, 2): RAISE "internal": OUT "after"
+            GOTO @end
+            @handler
+            n = n + 1
+            OUT "handled"; n
+            IF n < 3 THEN RESUME ELSE RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["before 3", "handled 1", "handled 2", "handled 3", "after"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_next_skips_statement() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @handler
+            OUT 1: OUT RAISEF("internal"): OUT 2
+            OUT RAISEF("io")
+            OUT 3
+            GOTO @end
+            @handler
+            OUT LAST_ERROR
+            RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["1", "3:31: Some internal error", "2", "4:24: Some I/O error", "3"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_resume_within_gosub() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @handler
+            GOSUB @sub
+            OUT "back"
+            GOTO @end
+            @sub
+            OUT RAISEF("eval")
+            OUT "sub"
+            RETURN
+            @handler
+            OUT LAST_ERROR
+            RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["7:24: Some eval error", "sub", "back"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_nested_handlers() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @first
+            RAISE "internal"
+            RAISE "io"
+            OUT "done"
+            GOTO @end
+            @first
+            OUT "first"
+            ON ERROR GOTO @second
+            RESUME NEXT
+            @second
+            OUT "second"
+            RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["first", "second", "done"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_error_in_handler_aborts() {
+        do_error_test(
+            r#"
+            ON ERROR GOTO @handler
+            OUT 1
+            RAISE "internal"
+            OUT 2
+            @handler
+            OUT "handler"
+            RAISE "io"
+            RESUME NEXT
+            "#,
+            &[],
+            &["1", "handler"],
+            "8:19: Some I/O error",
+        );
+    }
+
+    #[test]
+    fn test_on_error_handler_can_catch_again_after_resume() {
+        do_ok_test(
+            r#"
+            ON ERROR GOTO @handler
+            FOR i = 1 TO 3
+                RAISE "internal"
+            NEXT
+            GOTO @end
+            @handler
+            OUT "caught"; i
+            RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["caught 1", "caught 2", "caught 3"],
+        );
+    }
+
+    #[test]
+    fn test_on_error_without_handler_aborts() {
+        do_error_test(
+            "OUT 1\nOUT RAISEF(\"internal\")\nOUT 2",
+            &[],
+            &["1"],
+            "2:12: Some internal error",
+        );
+    }
+
+    #[test]
+    fn test_resume_without_error() {
+        do_error_test("OUT 1\nRESUME", &[], &["1"], "2:1: RESUME without an error to handle");
+        do_simple_error_test("RESUME NEXT", "1:1: RESUME without an error to handle");
+        do_error_test(
+            r#"
+            ON ERROR GOTO @handler
+            RAISE "internal"
+            ON ERROR GOTO 0
+            RESUME
+            GOTO @end
+            @handler
+            OUT "handled"
+            RESUME NEXT
+            @end
+            "#,
+            &[],
+            &["handled"],
+            "5:13: RESUME without an error to handle",
+        );
+    }
+
+    #[test]
+    fn test_on_error_records_code_and_line() {
+        let mut machine = Machine::default();
+        assert_eq!(0, machine.last_error_code());
+        assert_eq!(0, machine.last_error_line());
+
+        let code = "ON ERROR RESUME NEXT\na = 0\n\nb = 5 / a";
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_bytes())).unwrap());
+        assert_eq!(Some("4:7: Division by zero"), machine.last_error());
+        assert_eq!(ERR_DIVISION_BY_ZERO, machine.last_error_code());
+        assert_eq!(4, machine.last_error_line());
+
+        machine.clear();
+        assert_eq!(None, machine.last_error());
+        assert_eq!(0, machine.last_error_code());
+        assert_eq!(0, machine.last_error_line());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let pos = LineCol { line: 1, col: 1 };
+        let eval = |message: &str| Error::EvalError(pos, message.to_owned()).code();
+        let io = |kind| Error::IoError(pos, io::Error::new(kind, "foo")).code();
+
+        assert_eq!(ERR_DIVISION_BY_ZERO, eval("Division by zero"));
+        assert_eq!(ERR_DIVISION_BY_ZERO, eval("Modulo by zero"));
+        assert_eq!(ERR_OVERFLOW, eval("Integer overflow"));
+        assert_eq!(ERR_OVERFLOW, eval("Integer underflow"));
+        assert_eq!(ERR_OUT_OF_MEMORY, eval("Out of memory: limit is 100 bytes"));
+        assert_eq!(
+            ERR_SUBSCRIPT_OUT_OF_RANGE,
+            eval("Subscript 5 out of range 0..4 for dimension 1")
+        );
+        assert_eq!(
+            ERR_TYPE_MISMATCH,
+            eval("Cannot assign value of type STRING to variable of type INTEGER")
+        );
+        assert_eq!(ERR_TYPE_MISMATCH, eval("STRING is not a number"));
+        assert_eq!(ERR_ILLEGAL_FUNCTION_CALL, eval("Something else"));
+        assert_eq!(
+            ERR_OUT_OF_DATA,
+            Error::InternalError(pos, "Out of data reading into A".to_owned()).code()
+        );
+        assert_eq!(ERR_INTERNAL, Error::InternalError(pos, "Oops".to_owned()).code());
+        assert_eq!(ERR_ILLEGAL_FUNCTION_CALL, Error::SyntaxError(pos, "Bad".to_owned()).code());
+
+        assert_eq!(ERR_FILE_NOT_FOUND, io(io::ErrorKind::NotFound));
+        assert_eq!(ERR_FILE_ALREADY_EXISTS, io(io::ErrorKind::AlreadyExists));
+        assert_eq!(ERR_PERMISSION_DENIED, io(io::ErrorKind::PermissionDenied));
+        assert_eq!(ERR_IO, io(io::ErrorKind::Other));
+    }
+
+    #[test]
+    fn test_on_goto_in_range() {
+        let code = r#"
+            IN n
+            ON n GOTO @one, 20, @three
+            OUT "fallthrough"
+            GOTO @end
+            @one: OUT 1: GOTO @end
+            20 OUT 2: GOTO @end
+            @three: OUT 3
+            @end
+        "#;
+        do_ok_test(code, &["1"], &["1"]);
+        do_ok_test(code, &["2"], &["2"]);
+        do_ok_test(code, &["3"], &["3"]);
+    }
+
+    #[test]
+    fn test_on_goto_out_of_range_falls_through() {
+        let code = r#"
+            IN n
+            ON n GOTO @one, @two
+            OUT "fallthrough"
+            GOTO @end
+            @one: OUT 1: GOTO @end
+            @two: OUT 2
+            @end
+        "#;
+        do_ok_test(code, &["0"], &["fallthrough"]);
+        do_ok_test(code, &["3"], &["fallthrough"]);
+        do_ok_test(code, &["-1"], &["fallthrough"]);
+        do_ok_test(code, &["-2147483648"], &["fallthrough"]);
+    }
+
+    #[test]
+    fn test_on_goto_double_to_integer() {
+        let code = r#"
+            IN n#
+            ON n# GOTO @one, @two
+            OUT "fallthrough"
+            GOTO @end
+            @one: OUT 1: GOTO @end
+            @two: OUT 2
+            @end
+        "#;
+        do_ok_test(code, &["0.4"], &["fallthrough"]);
+        do_ok_test(code, &["0.5"], &["1"]);
+        do_ok_test(code, &["1.4"], &["1"]);
+        do_ok_test(code, &["1.5"], &["2"]);
+        do_ok_test(code, &["2.5"], &["fallthrough"]);
+        do_ok_test(code, &["-0.4"], &["fallthrough"]);
     }
 
     #[test]
-    fn test_on_error_resume_next_statement_function_failure() {
-        do_ok_test(
-            r#"
-            ON ERROR RESUME NEXT
-            OUT 1: OUT RAISEF("internal"): OUT LAST_ERROR
-            "#,
-            &[],
-            &["1", "3:31: Some internal error"],
-        );
+    fn test_on_goto_expression_evaluated_only_once() {
+        let code = r#"
+            ON COUNT GOTO @one, @two
+            @one: OUT "one"
+            @two: OUT COUNT
+        "#;
+        do_ok_test(code, &[], &["one", "2"]);
     }
 
     #[test]
-    fn test_on_error_resume_next_statement_command_failure() {
-        do_ok_test(
-            r#"
-            ON ERROR RESUME NEXT
-            OUT 1: RAISE "internal": OUT LAST_ERROR
-            "#,
-            &[],
-            &["1", "3:26: Some internal error"],
-        );
+    fn test_on_gosub_and_return() {
+        let code = r#"
+            IN n
+            ON n GOSUB @one, @two
+            OUT "back"
+            GOTO @end
+            @one: OUT 1: RETURN
+            @two: OUT 2: RETURN
+            @end
+        "#;
+        do_ok_test(code, &["1"], &["1", "back"]);
+        do_ok_test(code, &["2"], &["2", "back"]);
+        do_ok_test(code, &["0"], &["back"]);
+        do_ok_test(code, &["3"], &["back"]);
+        do_ok_test(code, &["-5"], &["back"]);
     }
 
     #[test]
-    fn test_on_error_types() {
+    fn test_on_gosub_nested() {
+        let code = r#"
+            depth = 1
+            GOTO @main
+            @outer: depth = depth + 1: OUT depth
+                ON depth GOSUB @inner, @outer
+                OUT "outer done": RETURN
+            @inner: OUT "inner": GOSUB @leaf: RETURN
+            @leaf: OUT "leaf": RETURN
+            @main
+            GOSUB @outer
+            ON 2 GOSUB @leaf, @inner
+            OUT "main done"
+        "#;
         do_ok_test(
-            r#"ON ERROR RESUME NEXT: OUT RAISEF("argument"): OUT LAST_ERROR"#,
+            code,
             &[],
-            &["1:34: Bad argument"],
+            &["2", "3", "outer done", "outer done", "inner", "leaf", "main done"],
         );
+    }
 
-        do_ok_test(
-            r#"ON ERROR RESUME NEXT: OUT RAISEF("eval"): OUT LAST_ERROR"#,
-            &[],
-            &["1:34: Some eval error"],
-        );
+    #[test]
+    fn test_on_goto_no_selector_var_leaks() {
+        let code = r#"
+            i = 0
+            ON 1 GOTO @a
+            @a
+            ON 1 GOSUB @b
+            ON 5 GOTO @a, @b
+            GOTO @end
+            @b: i = i + 1: RETURN
+            @end
+        "#;
 
-        do_ok_test(
-            r#"ON ERROR RESUME NEXT: OUT RAISEF("internal"): OUT LAST_ERROR"#,
-            &[],
-            &["1:34: Some internal error"],
-        );
+        let mut machine = Machine::default();
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_bytes())).unwrap());
+        assert_eq!(1, machine.get_symbols().locals().len());
+        match machine.get_symbols().get_auto("I") {
+            Some(Symbol::Variable(Value::Integer(i))) => assert_eq!(1, *i),
+            e => panic!("I is not an integer: {:?}", e),
+        }
+    }
 
-        do_ok_test(
-            r#"ON ERROR RESUME NEXT: OUT RAISEF("io"): OUT LAST_ERROR"#,
-            &[],
-            &["1:34: Some I/O error"],
+    #[test]
+    fn test_on_goto_errors() {
+        do_simple_error_test("ON 1 GOTO @foo", "1:11: Unknown label foo");
+        do_simple_error_test("@a: ON 1 GOSUB @a, 10", "1:20: Unknown label 10");
+        do_simple_error_test("ON \"x\" GOTO @a: @a", "1:4: STRING is not a number");
+        do_simple_error_test("ON 1 GOSUB @a: @a: RETURN: RETURN", "1:20: No address to return to");
+        do_simple_error_test(
+            "ON 10000000000.0 GOTO @a: @a",
+            "1:4: Cannot cast 10000000000 to integer due to overflow",
         );
     }
 
@@ -3160,6 +5507,26 @@ mod tests {
         do_ok_test(code, &[], &["OK 1", "OK 2"]);
     }
 
+    #[test]
+    fn test_select_in_loops() {
+        let code = r#"
+            FOR i = 1 TO 4
+                SELECT CASE i
+                    CASE 1, 3: OUT "Odd"; i
+                    CASE ELSE: OUT "Even"; i
+                END SELECT
+            NEXT
+            WHILE i > 2
+                i = i - 1
+                SELECT CASE i
+                    CASE IS >= 3: OUT "Big"; i
+                    CASE ELSE: OUT "Small"; i
+                END SELECT
+            WEND
+        "#;
+        do_ok_test(code, &[], &["Odd 1", "Even 2", "Odd 3", "Even 4", "Big 4", "Big 3", "Small 2"]);
+    }
+
     #[test]
     fn test_select_errors() {
         do_simple_error_test(
@@ -3173,6 +5540,18 @@ mod tests {
             "SELECT CASE 2\nCASE FALSE\nEND SELECT",
             "2:6: Cannot = INTEGER and BOOLEAN",
         );
+        do_simple_error_test(
+            "SELECT CASE \"a\"\nCASE \"b\", 3\nEND SELECT",
+            "2:11: Cannot = STRING and INTEGER",
+        );
+        do_simple_error_test(
+            "SELECT CASE 2\nCASE IS >= \"x\"\nEND SELECT",
+            "2:12: Cannot >= INTEGER and STRING",
+        );
+        do_simple_error_test(
+            "SELECT CASE 2.5\nCASE 1 TO \"x\"\nEND SELECT",
+            "2:11: Cannot <= DOUBLE and STRING",
+        );
     }
 
     #[test]
@@ -3505,4 +5884,353 @@ mod tests {
         "#;
         do_error_test(code, &[], &[], "5:13: FOO expected n%");
     }
+
+    #[test]
+    fn test_user_subs_default_params() {
+        let code = r#"
+            SUB show(n%, label$ = "none", ratio# = -0.5, flag? = TRUE, times% = 3)
+                OUT n; label; ratio; flag; times
+            END SUB
+            show 1
+            show 2, "two"
+            show 3, "three", 1
+            show 4, , , FALSE
+            show 5, "five", 2.5, FALSE, 7
+        "#;
+        do_ok_test(
+            code,
+            &[],
+            &[
+                "1 none -0.5 TRUE 3",
+                "2 two -0.5 TRUE 3",
+                "3 three 1 TRUE 3",
+                "4 none -0.5 FALSE 3",
+                "5 five 2.5 FALSE 7",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_user_functions_default_params() {
+        let code = r#"
+            FUNCTION scale#(x#, factor# = 2)
+                scale = x * factor
+            END FUNCTION
+            FUNCTION greeting$(name$ = "world")
+                greeting = "hello " + name
+            END FUNCTION
+            OUT scale(3); scale(3, 0.5); greeting(); greeting("you")
+        "#;
+        do_ok_test(code, &[], &["6 1.5 hello world hello you"]);
+    }
+
+    #[test]
+    fn test_user_subs_default_params_errors() {
+        let code = r#"
+            SUB foo(a, b = 1)
+            END SUB
+            foo
+        "#;
+        do_error_test(code, &[], &[], "4:13: FOO expected a%, [b%]");
+
+        let code = r#"
+            SUB foo(a, b = 1)
+            END SUB
+            foo 1, 2, 3
+        "#;
+        do_error_test(code, &[], &[], "4:13: FOO expected a%, [b%]");
+
+        let code = r#"
+            SUB foo(a = 1, b)
+            END SUB
+        "#;
+        do_error_test(
+            code,
+            &[],
+            &[],
+            "2:28: Parameter b after optional parameters needs a default",
+        );
+
+        let code = r#"
+            SUB foo(a, b$ = 1)
+            END SUB
+        "#;
+        do_error_test(code, &[], &[], "2:29: expected STRING but found INTEGER");
+    }
+
+    #[test]
+    fn test_user_subs_byref_scalars() {
+        let code = r#"
+            SUB polar_to_xy(r#, a#, BYREF x#, BYREF y#)
+                x = r * a
+                y = r + a
+                r = 0
+            END SUB
+            DIM r AS DOUBLE
+            r = 2
+            polar_to_xy r, 3, px#, py#
+            OUT r; px; py
+        "#;
+        do_ok_test(code, &[], &["2 6 5"]);
+    }
+
+    #[test]
+    fn test_user_subs_byref_nested_calls() {
+        let code = r#"
+            SUB inc(BYREF n)
+                n = n + 1
+            END SUB
+            SUB inc_twice(BYREF n)
+                inc n
+                inc n
+            END SUB
+            i = 5
+            inc_twice i
+            OUT i
+        "#;
+        do_ok_test(code, &[], &["7"]);
+    }
+
+    #[test]
+    fn test_user_subs_byref_array_elements() {
+        let code = r#"
+            DIM SHARED i
+            SUB swap(BYREF a$, BYREF b$)
+                t$ = a
+                a = b
+                b = t$
+                i = 2
+            END SUB
+            DIM names(3) AS STRING
+            names(0) = "a": names(1) = "b": names(2) = "c"
+            i = 0
+            swap names(i), names(i + 1)
+            OUT names(0); names(1); names(2); i
+        "#;
+        do_ok_test(code, &[], &["b a c 2"]);
+    }
+
+    #[test]
+    fn test_user_functions_byref() {
+        let code = r#"
+            FUNCTION next_id(BYREF counter)
+                counter = counter + 1
+                next_id = counter * 10
+            END FUNCTION
+            c = 0
+            a = next_id(c)
+            b = next_id(c) + next_id(c)
+            OUT a; b; c
+        "#;
+        do_ok_test(code, &[], &["10 50 3"]);
+    }
+
+    #[test]
+    fn test_user_subs_byref_errors() {
+        let code = r#"
+            SUB foo(BYREF n)
+            END SUB
+            foo 3
+        "#;
+        do_error_test(code, &[], &[], "4:17: Requires a reference, not a value");
+
+        let code = r#"
+            SUB foo(BYREF n)
+            END SUB
+            i = 3
+            foo i + 1
+        "#;
+        do_error_test(code, &[], &[], "5:17: Requires a reference, not a value");
+
+        let code = r#"
+            FUNCTION foo(BYREF n)
+            END FUNCTION
+            OUT foo(i)
+        "#;
+        do_error_test(code, &[], &[], "4:21: Undefined symbol I");
+
+        let code = r#"
+            SUB foo(BYREF n)
+            END SUB
+            foo s$
+        "#;
+        do_error_test(code, &[], &[], "4:17: expected INTEGER but found STRING");
+
+        let code = r#"
+            SUB foo(BYREF n)
+            END SUB
+            d# = 3
+            foo d#
+        "#;
+        do_error_test(code, &[], &[], "5:17: expected INTEGER but found DOUBLE");
+
+        let code = r#"
+            SUB foo(BYREF n, m)
+            END SUB
+            foo 1, 2
+        "#;
+        do_error_test(code, &[], &[], "4:17: Requires a reference, not a value");
+    }
+
+    #[test]
+    fn test_user_functions_static_counter() {
+        let code = r#"
+            FUNCTION counter
+                STATIC n = 10
+                n = n + 1
+                counter = n
+            END FUNCTION
+            n = 100
+            OUT counter
+            OUT counter
+            OUT counter
+            OUT n
+        "#;
+        do_ok_test(code, &[], &["11", "12", "13", "100"]);
+    }
+
+    #[test]
+    fn test_user_subs_static_arrays() {
+        let code = r#"
+            SUB append(s$)
+                STATIC items(3) AS STRING
+                STATIC total AS INTEGER
+                items(total) = s
+                total = total + 1
+                FOR i = 0 TO total - 1
+                    OUT i; items(i)
+                NEXT
+            END SUB
+            append "a"
+            append "b"
+        "#;
+        do_ok_test(code, &[], &["0 a", "0 a", "1 b"]);
+    }
+
+    #[test]
+    fn test_user_functions_static_shared_by_recursion() {
+        let code = r#"
+            FUNCTION depth(n)
+                STATIC calls
+                calls = calls + 1
+                IF n > 0 THEN depth = depth(n - 1) ELSE depth = calls
+            END FUNCTION
+            OUT depth(3)
+        "#;
+        do_ok_test(code, &[], &["4"]);
+    }
+
+    #[test]
+    fn test_user_subs_static_reset_by_clear() {
+        let code = r#"
+            SUB bump
+                STATIC n
+                n = n + 1
+                OUT n
+            END SUB
+            bump: bump
+            CLEAR
+            bump
+        "#;
+        do_ok_test(code, &[], &["1", "2", "1"]);
+    }
+
+    #[test]
+    fn test_user_subs_static_errors() {
+        let code = r#"
+            SUB foo(n)
+                STATIC n
+            END SUB
+        "#;
+        do_error_test(code, &[], &[], "3:24: Cannot define already-defined symbol N");
+
+        let code = r#"
+            STATIC n
+        "#;
+        do_error_test(code, &[], &[], "2:13: STATIC outside of FUNCTION or SUB");
+    }
+
+    /// Creates a test machine with EMPTY values enabled and with the `e` integer and `s` string
+    /// variables set to EMPTY.
+    fn new_machine_with_empty_values(captured_out: Rc<RefCell<Vec<String>>>) -> Machine {
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(captured_out));
+        machine.add_callable(SumFunction::new());
+        machine.enable_empty_values();
+        let symbols = machine.get_mut_symbols();
+        symbols.set_var(&VarRef::new("e", None), Value::Empty(ExprType::Integer)).unwrap();
+        symbols.set_var(&VarRef::new("s", None), Value::Empty(ExprType::Text)).unwrap();
+        machine
+    }
+
+    #[test]
+    fn test_empty_values_copy() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = new_machine_with_empty_values(captured_out.clone());
+        let code = r#"
+            DIM a(2)
+            i = e
+            d# = e
+            a(1) = e
+            t = s
+            i2 = a(1)
+            OUT 1
+        "#;
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_bytes())).unwrap());
+        assert_eq!(&["1"], captured_out.borrow().as_slice());
+
+        let symbols = machine.get_symbols();
+        for (name, exp_value) in [
+            ("i", Value::Empty(ExprType::Integer)),
+            ("d", Value::Empty(ExprType::Double)),
+            ("t", Value::Empty(ExprType::Text)),
+            ("i2", Value::Empty(ExprType::Integer)),
+        ] {
+            match symbols.get_auto(name) {
+                Some(Symbol::Variable(value)) => assert_eq!(&exp_value, value),
+                e => panic!("{} is not a variable: {:?}", name, e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_empty_values_errors() {
+        for (code, exp_error) in [
+            ("x = e + 1", "1:5: Cannot operate on an EMPTY value"),
+            ("x = 1 - e", "1:9: Cannot operate on an EMPTY value"),
+            ("x = -e", "1:6: Cannot operate on an EMPTY value"),
+            ("x# = e * 2.5", "1:6: Cannot operate on an EMPTY value"),
+            ("x$ = s + \"a\"", "1:6: Cannot operate on an EMPTY value"),
+            ("IF e = 0 THEN OUT 1", "1:4: Cannot operate on an EMPTY value"),
+            ("WHILE s = \"\": WEND", "1:7: Cannot operate on an EMPTY value"),
+            ("DIM a(e)", "1:7: Cannot operate on an EMPTY value"),
+            ("DIM a(3): OUT a(e)", "1:17: Cannot operate on an EMPTY value"),
+            ("OUT e", "1:5: OUT does not accept EMPTY values"),
+            ("x = SUM(1, e)", "1:12: SUM does not accept EMPTY values"),
+        ] {
+            let captured_out = Rc::from(RefCell::from(vec![]));
+            let mut machine = new_machine_with_empty_values(captured_out);
+            let err = block_on(machine.exec(&mut code.as_bytes())).unwrap_err();
+            assert_eq!(exp_error, format!("{}", err), "Code: {}", code);
+        }
+    }
+
+    #[test]
+    fn test_empty_values_errors_are_catchable() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = new_machine_with_empty_values(captured_out.clone());
+        let code = "ON ERROR RESUME NEXT: x = e + 1: OUT 5";
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut code.as_bytes())).unwrap());
+        assert_eq!(&["5"], captured_out.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_empty_values_disabled_by_clear() {
+        let mut machine = Machine::default();
+        assert!(!machine.empty_values());
+        machine.enable_empty_values();
+        assert!(machine.empty_values());
+        machine.clear();
+        assert!(!machine.empty_values());
+    }
 }
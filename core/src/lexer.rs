@@ -45,6 +45,8 @@ pub enum Token {
 
     Comma,
     Semicolon,
+    Hash,
+    Dot,
     LeftParen,
     RightParen,
 
@@ -94,12 +96,15 @@ pub enum Token {
     Step,
     Then,
     To,
+    Type,
     Until,
     Wend,
     While,
 
     Dim,
     Shared,
+    Static,
+    ByRef,
     As,
     BooleanName,
     DoubleName,
@@ -129,6 +134,8 @@ impl fmt::Display for Token {
 
             Token::Comma => write!(f, ","),
             Token::Semicolon => write!(f, ";"),
+            Token::Hash => write!(f, "#"),
+            Token::Dot => write!(f, "."),
             Token::LeftParen => write!(f, "("),
             Token::RightParen => write!(f, ")"),
 
@@ -178,12 +185,15 @@ impl fmt::Display for Token {
             Token::Step => write!(f, "STEP"),
             Token::Then => write!(f, "THEN"),
             Token::To => write!(f, "TO"),
+            Token::Type => write!(f, "TYPE"),
             Token::Until => write!(f, "UNTIL"),
             Token::Wend => write!(f, "WEND"),
             Token::While => write!(f, "WHILE"),
 
             Token::Dim => write!(f, "DIM"),
             Token::Shared => write!(f, "SHARED"),
+            Token::Static => write!(f, "STATIC"),
+            Token::ByRef => write!(f, "BYREF"),
             Token::As => write!(f, "AS"),
             Token::BooleanName => write!(f, "BOOLEAN"),
             Token::DoubleName => write!(f, "DOUBLE"),
@@ -245,8 +255,7 @@ pub struct TokenSpan {
     pub(crate) pos: LineCol,
 
     /// Length of the token in characters.
-    #[allow(unused)] // TODO(jmmv): Use this in the parser.
-    length: usize,
+    pub(crate) length: usize,
 }
 
 impl TokenSpan {
@@ -462,7 +471,8 @@ impl<'a> Lexer<'a> {
 
     /// Consumes the symbol or keyword at the current position, whose first letter is `first`.
     ///
-    /// The symbol may be a bare name, but it may also contain an optional type annotation.
+    /// The symbol may be a bare name, but it may also contain an optional type annotation.  Dots
+    /// within the name separate a record variable from the name of one of its fields.
     fn consume_symbol(&mut self, first: CharSpan) -> io::Result<TokenSpan> {
         let mut s = String::new();
         s.push(first.ch);
@@ -471,7 +481,7 @@ impl<'a> Lexer<'a> {
         loop {
             match self.input.peek() {
                 Some(Ok(ch_span)) => match ch_span.ch {
-                    ch if ch.is_word() => s.push(self.input.next().unwrap()?.ch),
+                    ch if ch.is_word() || ch == '.' => s.push(self.input.next().unwrap()?.ch),
                     ch if ch.is_separator() => break,
                     '?' => {
                         vtype = Some(ExprType::Boolean);
@@ -510,10 +520,15 @@ impl<'a> Lexer<'a> {
         debug_assert!(token_len <= 1);
 
         token_len += s.len();
+        if s.ends_with('.') || s.contains("..") {
+            let msg = format!("Missing field name in symbol: {}", s);
+            return Ok(TokenSpan::new(Token::Bad(msg), first.pos, token_len));
+        }
         let token = match s.to_uppercase().as_str() {
             "AND" => Token::And,
             "AS" => Token::As,
             "BOOLEAN" => Token::BooleanName,
+            "BYREF" => Token::ByRef,
             "CASE" => Token::Case,
             "DATA" => Token::Data,
             "DIM" => Token::Dim,
@@ -543,11 +558,13 @@ impl<'a> Lexer<'a> {
             "RETURN" => Token::Return,
             "SELECT" => Token::Select,
             "SHARED" => Token::Shared,
+            "STATIC" => Token::Static,
             "STEP" => Token::Step,
             "STRING" => Token::TextName,
             "SUB" => Token::Sub,
             "THEN" => Token::Then,
             "TO" => Token::To,
+            "TYPE" => Token::Type,
             "TRUE" => Token::Boolean(true),
             "UNTIL" => Token::Until,
             "WEND" => Token::Wend,
@@ -679,6 +696,7 @@ impl<'a> Lexer<'a> {
 
             ';' => Ok(TokenSpan::new(Token::Semicolon, ch_span.pos, 1)),
             ',' => Ok(TokenSpan::new(Token::Comma, ch_span.pos, 1)),
+            '#' => Ok(TokenSpan::new(Token::Hash, ch_span.pos, 1)),
 
             '(' => Ok(TokenSpan::new(Token::LeftParen, ch_span.pos, 1)),
             ')' => Ok(TokenSpan::new(Token::RightParen, ch_span.pos, 1)),
@@ -696,6 +714,10 @@ impl<'a> Lexer<'a> {
 
             '&' => self.consume_integer_with_base(ch_span.pos),
 
+            '.' if matches!(self.input.peek(), Some(Ok(next)) if next.ch.is_alphabetic()) => {
+                Ok(TokenSpan::new(Token::Dot, ch_span.pos, 1))
+            }
+
             ch if ch.is_ascii_digit() => self.consume_number(ch_span),
             ch if ch.is_word() => self.consume_symbol(ch_span),
             ch => self.handle_bad_read(format!("Unknown character: {}", ch), ch_span.pos),
@@ -1105,6 +1127,16 @@ mod tests {
             ],
         );
 
+        do_ok_test(
+            "BYREF byref",
+            &[ts(Token::ByRef, 1, 1, 5), ts(Token::ByRef, 1, 7, 5), ts(Token::Eof, 1, 12, 0)],
+        );
+
+        do_ok_test(
+            "STATIC static",
+            &[ts(Token::Static, 1, 1, 6), ts(Token::Static, 1, 8, 6), ts(Token::Eof, 1, 14, 0)],
+        );
+
         do_ok_test(
             "dim shared as",
             &[
@@ -1348,6 +1380,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_type() {
+        do_ok_test(
+            "TYPE Point END TYPE",
+            &[
+                ts(Token::Type, 1, 1, 4),
+                ts(new_auto_symbol("Point"), 1, 6, 5),
+                ts(Token::End, 1, 12, 3),
+                ts(Token::Type, 1, 16, 4),
+                ts(Token::Eof, 1, 20, 0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_record_fields() {
+        do_ok_test(
+            "p.x p.name$ a(1).y%",
+            &[
+                ts(new_auto_symbol("p.x"), 1, 1, 3),
+                ts(Token::Symbol(VarRef::new("p.name", Some(ExprType::Text))), 1, 5, 7),
+                ts(new_auto_symbol("a"), 1, 13, 1),
+                ts(Token::LeftParen, 1, 14, 1),
+                ts(Token::Integer(1), 1, 15, 1),
+                ts(Token::RightParen, 1, 16, 1),
+                ts(Token::Dot, 1, 17, 1),
+                ts(Token::Symbol(VarRef::new("y", Some(ExprType::Integer))), 1, 18, 2),
+                ts(Token::Eof, 1, 20, 0),
+            ],
+        );
+
+        do_ok_test(
+            "p. q..r$ 5",
+            &[
+                ts(Token::Bad("Missing field name in symbol: p.".to_owned()), 1, 1, 2),
+                ts(Token::Bad("Missing field name in symbol: q..r".to_owned()), 1, 4, 5),
+                ts(Token::Integer(5), 1, 10, 1),
+                ts(Token::Eof, 1, 11, 0),
+            ],
+        );
+    }
+
     #[test]
     fn test_while() {
         do_ok_test(
@@ -1441,6 +1515,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_hash() {
+        do_ok_test(
+            "PRINT #1, a# #ch",
+            &[
+                ts(new_auto_symbol("PRINT"), 1, 1, 5),
+                ts(Token::Hash, 1, 7, 1),
+                ts(Token::Integer(1), 1, 8, 1),
+                ts(Token::Comma, 1, 9, 1),
+                ts(Token::Symbol(VarRef::new("a", Some(ExprType::Double))), 1, 11, 2),
+                ts(Token::Hash, 1, 14, 1),
+                ts(new_auto_symbol("ch"), 1, 15, 2),
+                ts(Token::Eof, 1, 17, 0),
+            ],
+        );
+    }
+
     #[test]
     fn test_parenthesis() {
         do_ok_test(
@@ -28,8 +28,14 @@ pub mod bytecode;
 pub mod compiler;
 pub mod exec;
 mod lexer;
+pub mod merger;
+pub mod minifier;
 pub mod parser;
 mod reader;
+pub mod refactor;
+pub mod simple;
+pub mod snapshot;
+pub mod stats;
 pub mod syms;
 #[cfg(test)]
 mod testutils;
@@ -0,0 +1,263 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Source code merger to combine two programs into one.
+
+use crate::lexer::{Lexer, Token};
+use crate::reader::LineCol;
+use std::collections::HashMap;
+use std::io;
+
+/// A top-level construct of a program that cannot appear twice in the merged program.
+struct Definition {
+    /// Identifier of the construct shared by all constructs that clash with each other.
+    key: String,
+
+    /// Description of the construct for error messages.
+    what: String,
+
+    /// Position of the construct in its program.
+    pos: LineCol,
+}
+
+/// Summary of the parts of a program that matter when merging it with another one.
+#[derive(Default)]
+struct Outline {
+    /// Callables and line numbers defined by the program, in order of appearance.
+    definitions: Vec<Definition>,
+
+    /// Line number at the beginning of each physical line that contains code, indexed by the
+    /// physical line number (1-based).  Lines without code are not present.
+    numbers: HashMap<usize, Option<i32>>,
+}
+
+impl Outline {
+    /// Returns true if all the lines that contain code start with a line number.
+    fn is_numbered(&self) -> bool {
+        !self.numbers.is_empty() && self.numbers.values().all(Option::is_some)
+    }
+}
+
+/// Scans the program in `text` to compute its outline.
+fn outline(text: &str) -> io::Result<Outline> {
+    let mut outline = Outline::default();
+
+    let mut bytes = text.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    let mut previous = Token::Eol;
+    let mut previous_line = 0;
+    loop {
+        let span = lexer.read()?;
+        match span.token {
+            Token::Eof => break,
+            Token::Eol => {
+                previous = span.token;
+                continue;
+            }
+            Token::Bad(msg) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", span.pos, msg),
+                ))
+            }
+            _ => (),
+        }
+
+        if span.pos.line != previous_line {
+            let number = match span.token {
+                Token::Integer(i) => {
+                    outline.definitions.push(Definition {
+                        key: format!("line {}", i),
+                        what: format!("line {}", i),
+                        pos: span.pos,
+                    });
+                    Some(i)
+                }
+                _ => None,
+            };
+            outline.numbers.insert(span.pos.line, number);
+            previous_line = span.pos.line;
+        }
+
+        let kind = match (&previous, &span.token) {
+            (Token::End | Token::Exit, _) => None,
+            (_, Token::Function) => Some("FUNCTION"),
+            (_, Token::Sub) => Some("SUB"),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let name_span = lexer.read()?;
+            if let Token::Symbol(vref) = &name_span.token {
+                let name = vref.name().to_uppercase();
+                outline.definitions.push(Definition {
+                    key: format!("callable {}", name),
+                    what: format!("{} {}", kind, name),
+                    pos: name_span.pos,
+                });
+            }
+            previous = name_span.token;
+        } else {
+            previous = span.token;
+        }
+    }
+
+    Ok(outline)
+}
+
+/// Splits `text` into chunks of physical lines that each start with a line number, as given by
+/// `numbers`.  Lines that precede the first line number are returned in a separate header.
+fn split_numbered(
+    text: &str,
+    numbers: &HashMap<usize, Option<i32>>,
+) -> (String, Vec<(i32, String)>) {
+    let mut header = String::new();
+    let mut chunks: Vec<(i32, String)> = vec![];
+    for (i, line) in text.lines().enumerate() {
+        let chunk = match numbers.get(&(i + 1)) {
+            Some(Some(number)) => {
+                chunks.push((*number, String::new()));
+                &mut chunks.last_mut().expect("Just pushed a chunk").1
+            }
+            _ => match chunks.last_mut() {
+                Some((_, chunk)) => chunk,
+                None => &mut header,
+            },
+        };
+        chunk.push_str(line);
+        chunk.push('\n');
+    }
+    (header, chunks)
+}
+
+/// Merges the program in `other` into the program in `base` and returns the new source code.
+///
+/// If both programs are fully numbered (every line with code starts with a line number), their
+/// lines are interleaved by line number.  Otherwise, `other` is appended to the end of `base`.
+///
+/// Fails if both programs define the same callable or the same line number, reporting all clashes
+/// with their positions.  `base_name` and `other_name` are used to qualify these positions.
+pub fn merge(base: &str, base_name: &str, other: &str, other_name: &str) -> io::Result<String> {
+    let base_outline = outline(base)?;
+    let other_outline = outline(other)?;
+
+    let mut known = HashMap::new();
+    for def in &base_outline.definitions {
+        known.entry(def.key.as_str()).or_insert(def);
+    }
+    let mut conflicts = vec![];
+    for def in &other_outline.definitions {
+        if let Some(previous) = known.get(def.key.as_str()) {
+            conflicts.push(format!(
+                "{} at {}:{} conflicts with {} at {}:{}",
+                def.what, other_name, def.pos, previous.what, base_name, previous.pos
+            ));
+        }
+    }
+    if !conflicts.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::AlreadyExists, conflicts.join("; ")));
+    }
+
+    let mut output = String::with_capacity(base.len() + other.len() + 1);
+    if base_outline.is_numbered() && other_outline.is_numbered() {
+        let (base_header, mut chunks) = split_numbered(base, &base_outline.numbers);
+        let (other_header, other_chunks) = split_numbered(other, &other_outline.numbers);
+        chunks.extend(other_chunks);
+        chunks.sort_by_key(|(number, _)| *number);
+
+        output.push_str(&base_header);
+        output.push_str(&other_header);
+        for (_, chunk) in chunks {
+            output.push_str(&chunk);
+        }
+    } else {
+        output.push_str(base);
+        if !output.is_empty() && !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output.push_str(other);
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_empty() {
+        assert_eq!("", merge("", "a", "", "b").unwrap());
+        assert_eq!("PRINT 1\n", merge("PRINT 1\n", "a", "", "b").unwrap());
+        assert_eq!("PRINT 1\n", merge("", "a", "PRINT 1\n", "b").unwrap());
+    }
+
+    #[test]
+    fn test_merge_appends() {
+        assert_eq!(
+            "PRINT 1\nFUNCTION f\n    f = 2\nEND FUNCTION\n",
+            merge("PRINT 1", "a", "FUNCTION f\n    f = 2\nEND FUNCTION\n", "b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_appends_partially_numbered() {
+        assert_eq!(
+            "10 PRINT 1\nPRINT 2\n5 PRINT 3\n",
+            merge("10 PRINT 1\nPRINT 2\n", "a", "5 PRINT 3\n", "b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_interleaves_numbered() {
+        let base = "' Main program\n10 PRINT 1\n30 PRINT 3\n' After 30\n50 GOTO 10\n";
+        let other = "' Library\n20 PRINT 2\n\n40 PRINT 4\n";
+        assert_eq!(
+            "' Main program\n' Library\n10 PRINT 1\n20 PRINT 2\n\n30 PRINT 3\n' After 30\n40 PRINT 4\n50 GOTO 10\n",
+            merge(base, "a", other, "b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_merge_ignores_callable_ends() {
+        let base = "SUB s\n    EXIT SUB\nEND SUB\nFUNCTION f\n    EXIT FUNCTION\nEND FUNCTION\n";
+        let other = "SUB t\nEND SUB\nFUNCTION g\nEND FUNCTION\n";
+        assert_eq!(format!("{}{}", base, other), merge(base, "a", other, "b").unwrap());
+    }
+
+    #[test]
+    fn test_merge_conflicts() {
+        let base = "10 SUB foo\n20 END SUB\n30 PRINT 1\n";
+        let other = "10 FUNCTION Foo\n20 END FUNCTION\n25 PRINT 2\n";
+        let err = merge(base, "MAIN.BAS", other, "LIB.BAS").unwrap_err();
+        assert_eq!(io::ErrorKind::AlreadyExists, err.kind());
+        assert_eq!(
+            "line 10 at LIB.BAS:1:1 conflicts with line 10 at MAIN.BAS:1:1; \
+FUNCTION FOO at LIB.BAS:1:13 conflicts with SUB FOO at MAIN.BAS:1:8; \
+line 20 at LIB.BAS:2:1 conflicts with line 20 at MAIN.BAS:2:1",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_merge_errors() {
+        assert_eq!(
+            "1:5: Unknown character: .",
+            merge("a = .3", "a", "", "b").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "2:1: Unknown character: .",
+            merge("", "a", "\n.", "b").unwrap_err().to_string()
+        );
+    }
+}
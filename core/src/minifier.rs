@@ -0,0 +1,291 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Source code minifier to make programs hard to read.
+
+use crate::ast::VarRef;
+use crate::lexer::{Lexer, Token};
+use std::collections::HashMap;
+use std::io;
+
+/// Returns true if the character `ch` separates two tokens without the need for whitespace.
+///
+/// This must be kept in sync with the logic in the lexer.
+fn is_separator(ch: char) -> bool {
+    matches!(
+        ch,
+        '\n' | ':' | '(' | ')' | '\'' | '=' | '<' | '>' | ';' | ',' | '+' | '-' | '*' | '/' | '^'
+    )
+}
+
+/// Returns true if the text `prev` needs a space before `next` so that the two don't merge into a
+/// different token when lexed again.
+fn needs_space(prev: &str, next: &str) -> bool {
+    let (last, first) = match (prev.chars().last(), next.chars().next()) {
+        (Some(last), Some(first)) => (last, first),
+        _ => return false,
+    };
+    if (last == '<' || last == '>') && matches!(first, '=' | '<' | '>') {
+        return true;
+    }
+    !is_separator(last) && !is_separator(first)
+}
+
+/// Returns true if `name` would be lexed as a keyword instead of as a symbol.
+pub(crate) fn is_keyword(name: &str) -> bool {
+    let mut input = name.as_bytes();
+    let mut lexer = Lexer::from(&mut input);
+    !matches!(lexer.read(), Ok(span) if matches!(span.token, Token::Symbol(_)))
+}
+
+/// Generator of short names to replace the original names of a program.
+struct Renamer<'a> {
+    /// Mapping of original names, in uppercase, to their replacements.
+    names: HashMap<String, String>,
+
+    /// Counter to generate the next name.
+    next: usize,
+
+    /// Predicate to determine which names must not be renamed nor be used as replacements.
+    is_reserved: &'a dyn Fn(&str) -> bool,
+}
+
+impl<'a> Renamer<'a> {
+    /// Creates a new renamer that leaves names that match `is_reserved` alone.
+    fn new(is_reserved: &'a dyn Fn(&str) -> bool) -> Self {
+        Self { names: HashMap::default(), next: 0, is_reserved }
+    }
+
+    /// Generates a new name that does not clash with any reserved name nor keyword.
+    fn generate(&mut self) -> String {
+        loop {
+            let mut name = String::new();
+            let mut n = self.next;
+            loop {
+                name.insert(0, char::from(b'a' + (n % 26) as u8));
+                if n < 26 {
+                    break;
+                }
+                n = n / 26 - 1;
+            }
+            self.next += 1;
+
+            if !(self.is_reserved)(&name) && !is_keyword(&name) {
+                return name;
+            }
+        }
+    }
+
+    /// Returns the replacement for `name`, or `name` itself if it is reserved.
+    fn rename(&mut self, name: &str) -> String {
+        if (self.is_reserved)(name) {
+            return name.to_owned();
+        }
+        let key = name.to_uppercase();
+        if let Some(replacement) = self.names.get(&key) {
+            return replacement.clone();
+        }
+        let replacement = self.generate();
+        self.names.insert(key, replacement.clone());
+        replacement
+    }
+}
+
+/// Formats a text literal so that it can be lexed back into the same value.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for ch in text.chars() {
+        if ch == '"' || ch == '\\' {
+            quoted.push('\\');
+        }
+        quoted.push(ch);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Minifies the program in `input` and returns its new source code.
+///
+/// The minified program has all comments and redundant whitespace removed, and all identifiers and
+/// labels renamed to short meaningless names, except for those names for which `is_reserved`
+/// returns true (which should cover all built-in callables).  Statement separators are kept as
+/// they were to preserve the semantics of single-line `IF` statements.
+///
+/// Note that names referenced from within strings (for example, in code given to `EXECUTE`) are not
+/// renamed so such programs will break after minification.
+pub fn minify(input: &mut dyn io::Read, is_reserved: &dyn Fn(&str) -> bool) -> io::Result<String> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+    let lines: Vec<Vec<char>> = text.split('\n').map(|l| l.chars().collect()).collect();
+
+    let mut symbols = Renamer::new(is_reserved);
+    let never_reserved = |_: &str| false;
+    let mut labels = Renamer::new(&never_reserved);
+
+    let mut output = String::new();
+    let mut line = String::new();
+    let mut last = String::new();
+    let mut bytes = text.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    loop {
+        let span = lexer.read()?;
+        let next = match span.token {
+            Token::Eof => break,
+
+            Token::Eol => {
+                let is_colon = lines
+                    .get(span.pos.line - 1)
+                    .and_then(|l| l.get(span.pos.col - 1))
+                    .map(|ch| *ch == ':')
+                    .unwrap_or(false);
+                if is_colon {
+                    if !line.is_empty() && !line.ends_with(':') {
+                        line.push(':');
+                        last = ":".to_owned();
+                    }
+                } else if !line.is_empty() {
+                    output.push_str(&line);
+                    output.push('\n');
+                    line.clear();
+                    last.clear();
+                }
+                continue;
+            }
+
+            Token::Bad(msg) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", span.pos, msg),
+                ))
+            }
+
+            Token::Double(d) => {
+                let s = d.to_string();
+                if s.contains('.') {
+                    s
+                } else {
+                    format!("{}.0", s)
+                }
+            }
+            Token::Integer(i) if i < 0 => format!("&x{:x}", i),
+            Token::Text(t) => quote(&t),
+            Token::Symbol(vref) => {
+                VarRef::new(symbols.rename(vref.name()), vref.ref_type()).to_string()
+            }
+            Token::Label(l) => format!("@{}", labels.rename(&l)),
+
+            token => token.to_string(),
+        };
+
+        if needs_space(&last, &next) {
+            line.push(' ');
+        }
+        line.push_str(&next);
+        last = next;
+    }
+    if !line.is_empty() {
+        output.push_str(&line);
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Minifies `input` treating `PRINT` and `LEN` as the only reserved names.
+    fn do_minify(input: &str) -> io::Result<String> {
+        let is_reserved = |name: &str| matches!(name.to_uppercase().as_str(), "PRINT" | "LEN");
+        minify(&mut input.as_bytes(), &is_reserved)
+    }
+
+    #[test]
+    fn test_minify_empty() {
+        assert_eq!("", do_minify("").unwrap());
+        assert_eq!("", do_minify("\n\n' Only a comment\nREM another one\n").unwrap());
+    }
+
+    #[test]
+    fn test_minify_removes_comments_and_whitespace() {
+        assert_eq!(
+            "PRINT 1+2\nPRINT \"a\";3\n",
+            do_minify("  ' Header\nPRINT 1 + 2  ' Sum\n\n   PRINT \"a\" ; 3\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minify_renames_consistently() {
+        assert_eq!(
+            "a=3:b$=\"x\"\nPRINT a;b$;LEN(b$)\n",
+            do_minify("counter = 3: name$ = \"x\"\nPRINT COUNTER; Name$; LEN(name$)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minify_renames_callables_and_labels() {
+        let input = "FUNCTION square(n%)\n    square = n * n\nEND FUNCTION\n@top\nPRINT square(2)\nGOTO @top\n";
+        assert_eq!(
+            "FUNCTION a(b%)\na=b*b\nEND FUNCTION\n@a\nPRINT a(2)\nGOTO @a\n",
+            do_minify(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minify_skips_keywords_and_reserved() {
+        let mut input = String::new();
+        for i in 0..400 {
+            input.push_str(&format!("v{} = {}\n", i, i));
+        }
+        let output = do_minify(&input).unwrap();
+        for line in output.lines() {
+            let name = line.split('=').next().unwrap();
+            assert!(!is_keyword(name), "{} is a keyword", name);
+        }
+        assert!(output.contains("\ndp=")); // "do" is a keyword so it must be skipped.
+    }
+
+    #[test]
+    fn test_minify_literals_roundtrip() {
+        assert_eq!(
+            "a=1.0+2.5+&xffffffff+7\nb$=\"say \\\"hi\\\" \\\\ bye\"\n",
+            do_minify("x = 1.0 + 2.5 + &xFFFFFFFF + &b111\ny$ = \"say \\\"hi\\\" \\\\ bye\"\n")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minify_keeps_operators_apart() {
+        assert_eq!(
+            "IF a<-1 OR a> =2 THEN PRINT\n",
+            do_minify("IF x < -1 OR x> =2 THEN PRINT").unwrap()
+        );
+        assert_eq!("a=b<<1\n", do_minify("x = y << 1").unwrap());
+    }
+
+    #[test]
+    fn test_minify_keeps_statement_separators() {
+        assert_eq!(
+            "IF a THEN PRINT 1:PRINT 2\nPRINT 3\n",
+            do_minify("IF x THEN PRINT 1 : PRINT 2\n: PRINT 3").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minify_errors() {
+        assert_eq!("1:5: Unknown character: .", format!("{}", do_minify("a = .3").unwrap_err()));
+    }
+}
@@ -20,6 +20,7 @@ use crate::lexer::{Lexer, PeekableLexer, Token, TokenSpan};
 use crate::reader::LineCol;
 use std::cmp::Ordering;
 use std::io;
+use std::mem;
 
 /// Parser errors.
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +53,50 @@ fn vref_to_unannotated_string(vref: VarRef, pos: LineCol) -> Result<String> {
     Ok(vref.take_name())
 }
 
+/// Checks that the name in `vref` is valid for the definition of a new symbol.
+///
+/// Dots are only valid in references to the fields of records, so they are not allowed here.
+fn check_definition_name(vref: &VarRef, pos: LineCol) -> Result<()> {
+    if vref.name().contains('.') {
+        return Err(Error::Bad(
+            pos,
+            format!("Invalid name {}: dots are reserved for record fields", vref),
+        ));
+    }
+    Ok(())
+}
+
+/// Returns true if `token` is the unannotated `TIMER` symbol that starts the `ON TIMER` statement.
+fn is_timer_symbol(token: &Token) -> bool {
+    match token {
+        Token::Symbol(vref) => {
+            vref.ref_type().is_none() && vref.name().eq_ignore_ascii_case("TIMER")
+        }
+        _ => false,
+    }
+}
+
+/// Returns the action requested by a `TIMER` statement if `vref` and `next` are the first two
+/// tokens of one, or `None` otherwise.
+fn timer_action(vref: &VarRef, next: &Token) -> Option<TimerAction> {
+    if vref.ref_type().is_some() || !vref.name().eq_ignore_ascii_case("TIMER") {
+        return None;
+    }
+    match next {
+        Token::On => Some(TimerAction::On),
+        Token::Symbol(action) if action.ref_type().is_none() => {
+            if action.name().eq_ignore_ascii_case("OFF") {
+                Some(TimerAction::Off)
+            } else if action.name().eq_ignore_ascii_case("STOP") {
+                Some(TimerAction::Stop)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
 /// Converts a collection of `ArgSpan`s passed to a function or array reference to a collection
 /// of expressions with proper validation.
 pub(crate) fn argspans_to_exprs(spans: Vec<ArgSpan>) -> Vec<Expr> {
@@ -239,6 +284,15 @@ impl ExprOpSpan {
     }
 }
 
+/// Type of the symbol defined by a `DIM` statement.
+enum DimType {
+    /// A variable or array of a primitive type.
+    Scalar(ExprType),
+
+    /// A record variable or array of records of the named type.
+    Record(String),
+}
+
 /// Iterator over the statements of the language.
 pub struct Parser<'a> {
     lexer: PeekableLexer<'a>,
@@ -326,6 +380,54 @@ impl<'a> Parser<'a> {
         Ok(Statement::ArrayAssignment(ArrayAssignmentSpan { vref, vref_pos, subscripts, expr }))
     }
 
+    /// Parses a `MID$` statement whose `args` within parenthesis, which start at `left_paren_pos`,
+    /// have already been read.
+    fn parse_mid_assignment(
+        &mut self,
+        vref: VarRef,
+        vref_pos: LineCol,
+        left_paren_pos: LineCol,
+        args: Vec<Expr>,
+    ) -> Result<Statement> {
+        if !vref.accepts(ExprType::Text) {
+            return Err(Error::Bad(vref_pos, format!("Invalid type annotation in {}", vref)));
+        }
+
+        if args.len() != 2 && args.len() != 3 {
+            return Err(Error::Bad(
+                left_paren_pos,
+                "MID$ requires a variable, a start position, and an optional length".to_owned(),
+            ));
+        }
+        let mut args = args.into_iter();
+        let (target, target_pos) = match args.next().unwrap() {
+            Expr::Symbol(span) => (span.vref, span.pos),
+            expr => {
+                return Err(Error::Bad(
+                    expr.start_pos(),
+                    "MID$ requires a variable reference as its target".to_owned(),
+                ))
+            }
+        };
+        let start = args.next().unwrap();
+        let length = args.next();
+
+        let expr = self.parse_required_expr("Missing expression in MID$ assignment")?;
+
+        let next = self.lexer.peek()?;
+        match &next.token {
+            Token::Eof | Token::Eol | Token::Else => (),
+            t => return Err(Error::Bad(next.pos, format!("Unexpected {} in MID$ assignment", t))),
+        }
+        Ok(Statement::MidAssignment(MidAssignmentSpan {
+            vref: target,
+            vref_pos: target_pos,
+            start,
+            length,
+            expr,
+        }))
+    }
+
     /// Parses a builtin call (things of the form `INPUT a`).
     fn parse_builtin_call(
         &mut self,
@@ -336,9 +438,30 @@ impl<'a> Parser<'a> {
         let mut name = vref_to_unannotated_string(vref, vref_pos)?;
         name.make_ascii_uppercase();
 
+        // `LINE INPUT` is the only builtin whose name spans two words.
+        if name == "LINE" && first.is_none() {
+            if let Token::Symbol(vref) = &self.lexer.peek()?.token {
+                if vref.ref_type().is_none() && vref.name().eq_ignore_ascii_case("INPUT") {
+                    self.lexer.consume_peeked();
+                    name.push_str(" INPUT");
+                }
+            }
+        }
+
+        // A first argument of the form `#channel` selects the variant of the builtin that
+        // operates on a file channel, which we name after the builtin with a `#` suffix.
+        if first.is_none() && self.lexer.peek()?.token == Token::Hash {
+            self.lexer.consume_peeked();
+            first = Some(self.parse_required_expr("Missing channel number after #")?);
+            name.push('#');
+        }
+
+        // `OPEN` is the only builtin that takes a `FOR` clause, right after its first argument.
+        let mut delims: &[Token] = if name == "OPEN" { &[Token::For] } else { &[] };
+
         let mut args = vec![];
         loop {
-            let expr = self.parse_expr(first.take())?;
+            let expr = self.parse_expr_until(first.take(), mem::take(&mut delims))?;
 
             let peeked = self.lexer.peek()?;
             match peeked.token {
@@ -359,6 +482,29 @@ impl<'a> Parser<'a> {
                 Token::As => {
                     let peeked = self.lexer.consume_peeked();
                     args.push(ArgSpan { expr, sep: ArgSep::As, sep_pos: peeked.pos });
+
+                    // Allow channel numbers to be written as `#channel`, as in `OPEN ... AS #1`.
+                    if self.lexer.peek()?.token == Token::Hash {
+                        self.lexer.consume_peeked();
+                        first = Some(self.parse_required_expr("Missing channel number after #")?);
+                    }
+                }
+                Token::For => {
+                    // `OPEN file$ FOR mode AS ...` takes the mode as a bare word, which we turn
+                    // into a string argument.
+                    let peeked = self.lexer.consume_peeked();
+                    args.push(ArgSpan { expr, sep: ArgSep::Long, sep_pos: peeked.pos });
+
+                    let mode = self.lexer.read()?;
+                    match mode.token {
+                        Token::Symbol(vref) if vref.ref_type().is_none() => {
+                            first = Some(Expr::Text(TextSpan {
+                                value: vref.take_name().to_ascii_uppercase(),
+                                pos: mode.pos,
+                            }));
+                        }
+                        _ => return Err(Error::Bad(mode.pos, "Expected file mode".to_owned())),
+                    }
                 }
                 _ => {
                     return Err(Error::Bad(
@@ -383,7 +529,16 @@ impl<'a> Parser<'a> {
                 let left_paren = self.lexer.consume_peeked();
                 let spans = self.parse_comma_separated_exprs()?;
                 let mut exprs = spans.into_iter().map(|span| span.expr.unwrap()).collect();
+                if self.lexer.peek()?.token == Token::Dot {
+                    let vref = self.maybe_parse_record_field(vref, vref_pos)?;
+                    self.expect_and_consume(Token::Equal, "Expected = after record field")?;
+                    return self.parse_array_assignment(vref, vref_pos, exprs);
+                }
                 match self.lexer.peek()?.token {
+                    Token::Equal if vref.name().eq_ignore_ascii_case("MID") => {
+                        self.lexer.consume_peeked();
+                        self.parse_mid_assignment(vref, vref_pos, left_paren.pos, exprs)
+                    }
                     Token::Equal => {
                         self.lexer.consume_peeked();
                         self.parse_array_assignment(vref, vref_pos, exprs)
@@ -403,6 +558,25 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses the `.field` suffix that may follow a reference to an element of an array of records
+    /// and returns the reference to the array that holds the values of that field.  If there is no
+    /// suffix, returns the original `vref` unmodified.
+    fn maybe_parse_record_field(&mut self, vref: VarRef, vref_pos: LineCol) -> Result<VarRef> {
+        if self.lexer.peek()?.token != Token::Dot {
+            return Ok(vref);
+        }
+        self.lexer.consume_peeked();
+
+        let name = vref_to_unannotated_string(vref, vref_pos)?;
+        let token_span = self.lexer.read()?;
+        match token_span.token {
+            Token::Symbol(field) if !field.name().contains('.') => {
+                Ok(VarRef::new(format!("{}.{}", name, field.name()), field.ref_type()))
+            }
+            _ => Err(Error::Bad(token_span.pos, "Expected field name after .".to_owned())),
+        }
+    }
+
     /// Parses the type name of an `AS` type definition.
     ///
     /// The `AS` token has already been consumed, so all this does is read a literal type name and
@@ -493,15 +667,26 @@ impl<'a> Parser<'a> {
         Ok(Statement::Data(DataSpan { values }))
     }
 
-    /// Parses the `AS typename` clause of a `DIM` statement.  The caller has already consumed the
-    /// `AS` token.
-    fn parse_dim_as(&mut self) -> Result<(ExprType, LineCol)> {
+    /// Parses the `AS typename` clause of a `DIM` or `STATIC` statement, as indicated by `stmt`.
+    /// The caller has already consumed the `AS` token.
+    fn parse_dim_as(&mut self, stmt: &str) -> Result<(DimType, LineCol)> {
         let peeked = self.lexer.peek()?;
         let (vtype, vtype_pos) = match peeked.token {
-            Token::Eof | Token::Eol => (ExprType::Integer, peeked.pos),
+            Token::Eof | Token::Eol => (DimType::Scalar(ExprType::Integer), peeked.pos),
             Token::As => {
                 self.lexer.consume_peeked();
-                self.parse_as_type()?
+                let peeked = self.lexer.peek()?;
+                if let Token::Symbol(vref) = &peeked.token {
+                    let vref = vref.clone();
+                    let pos = peeked.pos;
+                    self.lexer.consume_peeked();
+                    check_definition_name(&vref, pos)?;
+                    let name = vref_to_unannotated_string(vref, pos)?;
+                    (DimType::Record(name), pos)
+                } else {
+                    let (vtype, vtype_pos) = self.parse_as_type()?;
+                    (DimType::Scalar(vtype), vtype_pos)
+                }
             }
             _ => return Err(Error::Bad(peeked.pos, "Expected AS or end of statement".to_owned())),
         };
@@ -509,7 +694,9 @@ impl<'a> Parser<'a> {
         let next = self.lexer.peek()?;
         match &next.token {
             Token::Eof | Token::Eol => (),
-            t => return Err(Error::Bad(next.pos, format!("Unexpected {} in DIM statement", t))),
+            t => {
+                return Err(Error::Bad(next.pos, format!("Unexpected {} in {} statement", t, stmt)))
+            }
         }
 
         Ok((vtype, vtype_pos))
@@ -534,6 +721,7 @@ impl<'a> Parser<'a> {
                 ))
             }
         };
+        check_definition_name(&vref, token_span.pos)?;
         let name = vref_to_unannotated_string(vref, token_span.pos)?;
         let name_pos = token_span.pos;
 
@@ -547,21 +735,112 @@ impl<'a> Parser<'a> {
                         "Arrays require at least one dimension".to_owned(),
                     ));
                 }
-                let (subtype, subtype_pos) = self.parse_dim_as()?;
-                Ok(Statement::DimArray(DimArraySpan {
+                let dimensions = argspans_to_exprs(dimensions);
+                match self.parse_dim_as("DIM")? {
+                    (DimType::Scalar(subtype), subtype_pos) => {
+                        Ok(Statement::DimArray(DimArraySpan {
+                            name,
+                            name_pos,
+                            shared,
+                            dimensions,
+                            subtype,
+                            subtype_pos,
+                        }))
+                    }
+                    (DimType::Record(type_name), type_pos) => {
+                        Ok(Statement::DimRecord(DimRecordSpan {
+                            name,
+                            name_pos,
+                            shared,
+                            dimensions,
+                            type_name,
+                            type_pos,
+                        }))
+                    }
+                }
+            }
+            _ => match self.parse_dim_as("DIM")? {
+                (DimType::Scalar(vtype), vtype_pos) => {
+                    Ok(Statement::Dim(DimSpan { name, name_pos, shared, vtype, vtype_pos }))
+                }
+                (DimType::Record(type_name), type_pos) => Ok(Statement::DimRecord(DimRecordSpan {
                     name,
                     name_pos,
                     shared,
-                    dimensions: argspans_to_exprs(dimensions),
-                    subtype,
-                    subtype_pos,
-                }))
+                    dimensions: vec![],
+                    type_name,
+                    type_pos,
+                })),
+            },
+        }
+    }
+
+    /// Parses a `STATIC` statement.
+    fn parse_static(&mut self, pos: LineCol) -> Result<Statement> {
+        let token_span = self.lexer.read()?;
+        let vref = match token_span.token {
+            Token::Symbol(vref) => vref,
+            _ => {
+                return Err(Error::Bad(
+                    token_span.pos,
+                    "Expected variable name after STATIC".to_owned(),
+                ))
+            }
+        };
+        check_definition_name(&vref, token_span.pos)?;
+        let name_pos = token_span.pos;
+
+        let decl = match self.lexer.peek()?.token {
+            Token::Equal => {
+                self.lexer.consume_peeked();
+                let expr = self.parse_required_expr("Missing expression in STATIC statement")?;
+                StaticDecl::Assignment(AssignmentSpan { vref, vref_pos: name_pos, expr })
+            }
+            Token::LeftParen => {
+                let name = vref_to_unannotated_string(vref, name_pos)?;
+                let peeked = self.lexer.consume_peeked();
+                let dimensions = self.parse_comma_separated_exprs()?;
+                if dimensions.is_empty() {
+                    return Err(Error::Bad(
+                        peeked.pos,
+                        "Arrays require at least one dimension".to_owned(),
+                    ));
+                }
+                let dimensions = argspans_to_exprs(dimensions);
+                match self.parse_dim_as("STATIC")? {
+                    (DimType::Scalar(subtype), subtype_pos) => StaticDecl::DimArray(DimArraySpan {
+                        name,
+                        name_pos,
+                        shared: false,
+                        dimensions,
+                        subtype,
+                        subtype_pos,
+                    }),
+                    (DimType::Record(_), type_pos) => {
+                        return Err(Error::Bad(
+                            type_pos,
+                            "STATIC does not support records".to_owned(),
+                        ))
+                    }
+                }
             }
             _ => {
-                let (vtype, vtype_pos) = self.parse_dim_as()?;
-                Ok(Statement::Dim(DimSpan { name, name_pos, shared, vtype, vtype_pos }))
+                let name = vref_to_unannotated_string(vref, name_pos)?;
+                match self.parse_dim_as("STATIC")? {
+                    (DimType::Scalar(vtype), vtype_pos) => {
+                        StaticDecl::Dim(DimSpan { name, name_pos, shared: false, vtype, vtype_pos })
+                    }
+                    (DimType::Record(_), type_pos) => {
+                        return Err(Error::Bad(
+                            type_pos,
+                            "STATIC does not support records".to_owned(),
+                        ))
+                    }
+                }
             }
-        }
+        };
+
+        Ok(Statement::Static(StaticSpan { decl, pos }))
     }
 
     /// Parses the `UNTIL` or `WHILE` clause of a `DO` loop.
@@ -654,6 +933,7 @@ impl<'a> Parser<'a> {
             Token::If => Ok(Err(Token::If)),
             Token::Select => Ok(Err(Token::Select)),
             Token::Sub => Ok(Err(Token::Sub)),
+            Token::Type => Ok(Err(Token::Type)),
             _ => {
                 let code = self.parse_expr(None)?;
                 Ok(Ok(Statement::End(EndSpan { code })))
@@ -734,6 +1014,12 @@ impl<'a> Parser<'a> {
     ///
     /// This is an implementation of the Shunting Yard Algorithm by Edgar Dijkstra.
     fn parse_expr(&mut self, first: Option<Expr>) -> Result<Option<Expr>> {
+        self.parse_expr_until(first, &[])
+    }
+
+    /// Same as `parse_expr` but also treats any of the keywords in `delims` as the end of the
+    /// expression.
+    fn parse_expr_until(&mut self, first: Option<Expr>, delims: &[Token]) -> Result<Option<Expr>> {
         let mut exprs: Vec<Expr> = vec![];
         let mut op_spans: Vec<ExprOpSpan> = vec![];
 
@@ -765,6 +1051,7 @@ impl<'a> Parser<'a> {
                 | Token::Then
                 | Token::To
                 | Token::Step => break,
+                ref t if delims.contains(t) => break,
                 Token::RightParen => {
                     if !op_spans.iter().any(|eos| eos.op == ExprOp::LeftParen) {
                         // We encountered an unbalanced parenthesis but we don't know if this is
@@ -802,11 +1089,9 @@ impl<'a> Parser<'a> {
                     match exprs.pop() {
                         Some(Expr::Symbol(span)) => {
                             if !need_operand {
-                                exprs.push(Expr::Call(CallSpan {
-                                    vref: span.vref,
-                                    vref_pos: span.pos,
-                                    args: self.parse_comma_separated_exprs()?,
-                                }));
+                                let args = self.parse_comma_separated_exprs()?;
+                                let vref = self.maybe_parse_record_field(span.vref, span.pos)?;
+                                exprs.push(Expr::Call(CallSpan { vref, vref_pos: span.pos, args }));
                                 need_operand = false;
                             } else {
                                 // We popped out the last expression to see if it this left
@@ -901,6 +1186,14 @@ impl<'a> Parser<'a> {
 
                 Token::Bad(e) => return Err(Error::Bad(ts.pos, e)),
 
+                Token::Hash => {
+                    return Err(Error::Bad(ts.pos, "Unexpected # in expression".to_owned()));
+                }
+
+                Token::Dot => {
+                    return Err(Error::Bad(ts.pos, "Unexpected . in expression".to_owned()));
+                }
+
                 Token::Eof
                 | Token::Eol
                 | Token::As
@@ -914,6 +1207,7 @@ impl<'a> Parser<'a> {
                 }
 
                 Token::BooleanName
+                | Token::ByRef
                 | Token::Case
                 | Token::Data
                 | Token::Do
@@ -938,8 +1232,10 @@ impl<'a> Parser<'a> {
                 | Token::Return
                 | Token::Select
                 | Token::Shared
+                | Token::Static
                 | Token::Sub
                 | Token::TextName
+                | Token::Type
                 | Token::Until
                 | Token::Wend
                 | Token::While => {
@@ -1293,15 +1589,21 @@ impl<'a> Parser<'a> {
 
     /// Parses the optional parameter list that may appear after a `FUNCTION` or `SUB` definition,
     /// including the opening and closing parenthesis.
-    fn parse_callable_args(&mut self) -> Result<Vec<VarRef>> {
+    fn parse_callable_args(&mut self) -> Result<Vec<ParamSpan>> {
         let mut params = vec![];
         let peeked = self.lexer.peek()?;
         if peeked.token == Token::LeftParen {
             self.lexer.consume_peeked();
 
             loop {
-                let token_span = self.lexer.read()?;
-                match token_span.token {
+                let mut token_span = self.lexer.read()?;
+                let byref = token_span.token == Token::ByRef;
+                if byref {
+                    token_span = self.lexer.read()?;
+                }
+
+                let param_pos = token_span.pos;
+                let vref = match token_span.token {
                     Token::Symbol(param) => {
                         let peeked = self.lexer.peek()?;
                         if peeked.token == Token::As {
@@ -1309,9 +1611,9 @@ impl<'a> Parser<'a> {
 
                             let name = vref_to_unannotated_string(param, token_span.pos)?;
                             let (vtype, _pos) = self.parse_as_type()?;
-                            params.push(VarRef::new(name, Some(vtype)));
+                            VarRef::new(name, Some(vtype))
                         } else {
-                            params.push(param);
+                            param
                         }
                     }
                     _ => {
@@ -1320,7 +1622,28 @@ impl<'a> Parser<'a> {
                             "Expected a parameter name".to_owned(),
                         ));
                     }
-                }
+                };
+
+                let peeked = self.lexer.peek()?;
+                let default = if peeked.token == Token::Equal {
+                    if byref {
+                        return Err(Error::Bad(
+                            peeked.pos,
+                            "BYREF parameters cannot have default values".to_owned(),
+                        ));
+                    }
+                    self.lexer.consume_peeked();
+                    Some(self.parse_required_expr("Missing default value")?)
+                } else {
+                    if params.iter().any(|p: &ParamSpan| p.default.is_some()) {
+                        return Err(Error::Bad(
+                            param_pos,
+                            format!("Parameter {} after optional parameters needs a default", vref),
+                        ));
+                    }
+                    None
+                };
+                params.push(ParamSpan { vref, byref, default });
 
                 let token_span = self.lexer.read()?;
                 match token_span.token {
@@ -1329,7 +1652,7 @@ impl<'a> Parser<'a> {
                     _ => {
                         return Err(Error::Bad(
                             token_span.pos,
-                            "Expected comma, AS, or end of parameters list".to_owned(),
+                            "Expected comma, AS, =, or end of parameters list".to_owned(),
                         ));
                     }
                 }
@@ -1488,9 +1811,94 @@ impl<'a> Parser<'a> {
         self.reset()
     }
 
-    /// Parses an `ON ERROR` statement.  Only `ON` has been consumed so far.
-    fn parse_on(&mut self) -> Result<Statement> {
-        self.expect_and_consume(Token::Error, "Expected ERROR after ON")?;
+    /// Parses a `TYPE` definition.  Only `TYPE` has been consumed so far.
+    fn parse_type(&mut self, type_pos: LineCol) -> Result<Statement> {
+        let token_span = self.lexer.read()?;
+        let vref = match token_span.token {
+            Token::Symbol(vref) => vref,
+            _ => {
+                return Err(Error::Bad(
+                    token_span.pos,
+                    "Expected a type name after TYPE".to_owned(),
+                ))
+            }
+        };
+        check_definition_name(&vref, token_span.pos)?;
+        let name = vref_to_unannotated_string(vref, token_span.pos)?;
+        let name_pos = token_span.pos;
+        self.expect_and_consume(Token::Eol, "Expected newline after TYPE name")?;
+
+        let mut fields = vec![];
+        loop {
+            let token_span = self.lexer.read()?;
+            match token_span.token {
+                Token::Eol => (),
+                Token::Eof => {
+                    return Err(Error::Bad(type_pos, "TYPE without END TYPE".to_owned()));
+                }
+                Token::End => {
+                    self.expect_and_consume(Token::Type, "Expected TYPE after END")?;
+                    break;
+                }
+                Token::Symbol(vref) => {
+                    check_definition_name(&vref, token_span.pos)?;
+                    let name = vref_to_unannotated_string(vref, token_span.pos)?;
+                    self.expect_and_consume(Token::As, "Expected AS after field name")?;
+                    let (vtype, vtype_pos) = self.parse_as_type()?;
+                    fields.push(FieldSpan { name, name_pos: token_span.pos, vtype, vtype_pos });
+
+                    let peeked = self.lexer.peek()?;
+                    if peeked.token != Token::Eol {
+                        return Err(Error::Bad(
+                            peeked.pos,
+                            format!("Expected newline but found {}", peeked.token),
+                        ));
+                    }
+                }
+                t => {
+                    return Err(Error::Bad(
+                        token_span.pos,
+                        format!("Unexpected {} in TYPE definition", t),
+                    ));
+                }
+            }
+        }
+
+        Ok(Statement::Type(TypeSpan { name, name_pos, fields }))
+    }
+
+    /// Advances until the next statement after failing to parse a `TYPE` definition.
+    fn reset_type(&mut self) -> Result<()> {
+        loop {
+            match self.lexer.peek()?.token {
+                Token::Eof => break,
+                Token::End => {
+                    self.lexer.consume_peeked();
+
+                    let token_span = self.lexer.read()?;
+                    if token_span.token == Token::Type {
+                        break;
+                    }
+                }
+                _ => {
+                    self.lexer.consume_peeked();
+                }
+            }
+        }
+        self.reset()
+    }
+
+    /// Parses an `ON ERROR`, `ON TIMER`, `ON expr GOTO` or `ON expr GOSUB` statement.  Only `ON`
+    /// has been consumed so far.
+    fn parse_on(&mut self, pos: LineCol) -> Result<Statement> {
+        if is_timer_symbol(&self.lexer.peek()?.token) {
+            self.lexer.consume_peeked();
+            return self.parse_on_timer(pos);
+        }
+        if self.lexer.peek()?.token != Token::Error {
+            return self.parse_on_goto(pos);
+        }
+        self.lexer.consume_peeked();
 
         let token_span = self.lexer.read()?;
         match token_span.token {
@@ -1522,6 +1930,99 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// Parses an `ON expr GOTO` or an `ON expr GOSUB` statement.  Only `ON` has been consumed so
+    /// far.
+    fn parse_on_goto(&mut self, pos: LineCol) -> Result<Statement> {
+        let next_pos = self.lexer.peek()?.pos;
+        let expr = match self.parse_expr_until(None, &[Token::Gosub, Token::Goto])? {
+            Some(expr) => expr,
+            None => {
+                return Err(Error::Bad(
+                    next_pos,
+                    "Expected ERROR or an expression after ON".to_owned(),
+                ))
+            }
+        };
+
+        let token_span = self.lexer.read()?;
+        let (gosub, name) = match token_span.token {
+            Token::Gosub => (true, "GOSUB"),
+            Token::Goto => (false, "GOTO"),
+            _ => {
+                return Err(Error::Bad(
+                    token_span.pos,
+                    "Expected ERROR, GOTO or GOSUB in ON statement".to_owned(),
+                ))
+            }
+        };
+
+        let mut targets = vec![];
+        loop {
+            let token_span = self.lexer.read()?;
+            let target = match token_span.token {
+                Token::Integer(i) => format!("{}", i),
+                Token::Label(target) => target,
+                _ => {
+                    return Err(Error::Bad(
+                        token_span.pos,
+                        format!("Expected label name after {}", name),
+                    ))
+                }
+            };
+            targets.push(GotoSpan { target, target_pos: token_span.pos });
+
+            if self.lexer.peek()?.token != Token::Comma {
+                break;
+            }
+            self.lexer.consume_peeked();
+        }
+
+        Ok(Statement::OnGoto(OnGotoSpan { expr, gosub, targets, pos }))
+    }
+
+    /// Parses an `ON TIMER` statement.  Only `ON TIMER` has been consumed so far.
+    fn parse_on_timer(&mut self, pos: LineCol) -> Result<Statement> {
+        let peeked = self.lexer.peek()?;
+        if peeked.token != Token::LeftParen {
+            return Err(Error::Bad(peeked.pos, "Expected ( after ON TIMER".to_owned()));
+        }
+        let interval = self
+            .parse_expr_until(None, &[Token::Gosub, Token::Goto])?
+            .expect("Must have parsed at least the parenthesized expression");
+
+        let token_span = self.lexer.read()?;
+        if token_span.token != Token::Gosub {
+            return Err(Error::Bad(token_span.pos, "Expected GOSUB after ON TIMER".to_owned()));
+        }
+
+        let token_span = self.lexer.read()?;
+        let target = match token_span.token {
+            Token::Integer(i) => format!("{}", i),
+            Token::Label(target) => target,
+            _ => {
+                return Err(Error::Bad(
+                    token_span.pos,
+                    "Expected label name after GOSUB".to_owned(),
+                ))
+            }
+        };
+
+        Ok(Statement::OnTimer(OnTimerSpan {
+            interval,
+            target: GotoSpan { target, target_pos: token_span.pos },
+            pos,
+        }))
+    }
+
+    /// Parses a `RESUME` statement.  Only `RESUME` has been consumed so far.
+    fn parse_resume(&mut self, pos: LineCol) -> Result<Statement> {
+        let next = self.lexer.peek()?.token == Token::Next;
+        if next {
+            self.lexer.consume_peeked();
+        }
+        Ok(Statement::Resume(ResumeSpan { next, pos }))
+    }
+
     /// Parses the guards after a `CASE` keyword.
     fn parse_case_guards(&mut self) -> Result<Vec<CaseGuardSpan>> {
         let mut guards = vec![];
@@ -1788,13 +2289,17 @@ impl<'a> Parser<'a> {
             Token::Exit => Ok(Some(self.parse_exit_do(token_span.pos)?)),
             Token::Gosub => Ok(Some(self.parse_gosub()?)),
             Token::Goto => Ok(Some(self.parse_goto()?)),
-            Token::On => Ok(Some(self.parse_on()?)),
+            Token::On => Ok(Some(self.parse_on(token_span.pos)?)),
+            Token::Resume => Ok(Some(self.parse_resume(token_span.pos)?)),
             Token::Return => Ok(Some(Statement::Return(ReturnSpan { pos: token_span.pos }))),
             Token::Symbol(vref) => {
                 let peeked = self.lexer.peek()?;
                 if peeked.token == Token::Equal {
                     self.lexer.consume_peeked();
                     Ok(Some(self.parse_assignment(vref, token_span.pos)?))
+                } else if let Some(action) = timer_action(&vref, &peeked.token) {
+                    self.lexer.consume_peeked();
+                    Ok(Some(Statement::Timer(TimerSpan { action, pos: token_span.pos })))
                 } else {
                     Ok(Some(self.parse_array_or_builtin_call(vref, token_span.pos)?))
                 }
@@ -1873,7 +2378,8 @@ impl<'a> Parser<'a> {
                 // ending given that the next statement may start after the label we found.
                 return Ok(Some(Statement::Label(LabelSpan { name, name_pos: token_span.pos })));
             }
-            Token::On => Ok(Some(self.parse_on()?)),
+            Token::On => Ok(Some(self.parse_on(token_span.pos)?)),
+            Token::Resume => Ok(Some(self.parse_resume(token_span.pos)?)),
             Token::Return => Ok(Some(Statement::Return(ReturnSpan { pos: token_span.pos }))),
             Token::Select => {
                 let result = self.parse_select(token_span.pos);
@@ -1882,6 +2388,7 @@ impl<'a> Parser<'a> {
                 }
                 Ok(Some(result?))
             }
+            Token::Static => Ok(Some(self.parse_static(token_span.pos)?)),
             Token::Sub => {
                 let result = self.parse_sub(token_span.pos);
                 if result.is_err() {
@@ -1889,11 +2396,21 @@ impl<'a> Parser<'a> {
                 }
                 Ok(Some(result?))
             }
+            Token::Type => {
+                let result = self.parse_type(token_span.pos);
+                if result.is_err() {
+                    self.reset_type()?;
+                }
+                Ok(Some(result?))
+            }
             Token::Symbol(vref) => {
                 let peeked = self.lexer.peek()?;
                 if peeked.token == Token::Equal {
                     self.lexer.consume_peeked();
                     Ok(Some(self.parse_assignment(vref, token_span.pos)?))
+                } else if let Some(action) = timer_action(&vref, &peeked.token) {
+                    self.lexer.consume_peeked();
+                    Ok(Some(Statement::Timer(TimerSpan { action, pos: token_span.pos })))
                 } else {
                     Ok(Some(self.parse_array_or_builtin_call(vref, token_span.pos)?))
                 }
@@ -1972,6 +2489,18 @@ pub(crate) fn parse(input: &mut dyn io::Read) -> StatementIter {
     StatementIter { parser: Parser::from(input) }
 }
 
+/// Extracts a single expression from the input stream, which must not contain anything else.
+pub(crate) fn parse_expr(input: &mut dyn io::Read) -> Result<Expr> {
+    let mut parser = Parser::from(input);
+    let expr = parser.parse_required_expr("Missing expression")?;
+
+    let next = parser.lexer.peek()?;
+    match &next.token {
+        Token::Eof => Ok(expr),
+        t => Err(Error::Bad(next.pos, format!("Unexpected {} in expression", t))),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2149,6 +2678,105 @@ mod tests {
         do_error_test("(2) = 3", "1:1: Unexpected ( in statement");
     }
 
+    #[test]
+    fn test_mid_assignments() {
+        do_ok_test(
+            "MID$(a$, 2) = \"xy\"\nmid(b, 3, n + 1) = c$",
+            &[
+                Statement::MidAssignment(MidAssignmentSpan {
+                    vref: VarRef::new("a", Some(ExprType::Text)),
+                    vref_pos: lc(1, 6),
+                    start: expr_integer(2, 1, 10),
+                    length: None,
+                    expr: expr_text("xy", 1, 15),
+                }),
+                Statement::MidAssignment(MidAssignmentSpan {
+                    vref: VarRef::new("b", None),
+                    vref_pos: lc(2, 5),
+                    start: expr_integer(3, 2, 8),
+                    length: Some(Expr::Add(Box::from(BinaryOpSpan {
+                        lhs: expr_symbol(VarRef::new("n", None), 2, 11),
+                        rhs: expr_integer(1, 2, 15),
+                        pos: lc(2, 13),
+                    }))),
+                    expr: expr_symbol(VarRef::new("c", Some(ExprType::Text)), 2, 20),
+                }),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_mid_assignment_errors() {
+        do_error_test("MID%(a$, 1) = \"x\"", "1:1: Invalid type annotation in MID%");
+        do_error_test(
+            "MID$(a$) = \"x\"",
+            "1:5: MID$ requires a variable, a start position, and an optional length",
+        );
+        do_error_test(
+            "MID$(a$, 1, 2, 3) = \"x\"",
+            "1:5: MID$ requires a variable, a start position, and an optional length",
+        );
+        do_error_test(
+            "MID$(\"abc\", 1) = \"x\"",
+            "1:6: MID$ requires a variable reference as its target",
+        );
+        do_error_test(
+            "MID$(a$(1), 1) = \"x\"",
+            "1:6: MID$ requires a variable reference as its target",
+        );
+        do_error_test("MID$(a$, 1) =", "1:14: Missing expression in MID$ assignment");
+        do_error_test("MID$(a$, 1) = \"x\" THEN", "1:19: Unexpected THEN in MID$ assignment");
+    }
+
+    #[test]
+    fn test_record_fields() {
+        do_ok_test(
+            "p.x = q.y + 1\na(1).name$ = b(2, 3).name$",
+            &[
+                Statement::Assignment(AssignmentSpan {
+                    vref: VarRef::new("p.x", None),
+                    vref_pos: lc(1, 1),
+                    expr: Expr::Add(Box::from(BinaryOpSpan {
+                        lhs: expr_symbol(VarRef::new("q.y", None), 1, 7),
+                        rhs: expr_integer(1, 1, 13),
+                        pos: lc(1, 11),
+                    })),
+                }),
+                Statement::ArrayAssignment(ArrayAssignmentSpan {
+                    vref: VarRef::new("a.name", Some(ExprType::Text)),
+                    vref_pos: lc(2, 1),
+                    subscripts: vec![expr_integer(1, 2, 3)],
+                    expr: Expr::Call(CallSpan {
+                        vref: VarRef::new("b.name", Some(ExprType::Text)),
+                        vref_pos: lc(2, 14),
+                        args: vec![
+                            ArgSpan {
+                                expr: Some(expr_integer(2, 2, 16)),
+                                sep: ArgSep::Long,
+                                sep_pos: lc(2, 17),
+                            },
+                            ArgSpan {
+                                expr: Some(expr_integer(3, 2, 19)),
+                                sep: ArgSep::End,
+                                sep_pos: lc(2, 20),
+                            },
+                        ],
+                    }),
+                }),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_record_field_errors() {
+        do_error_test("p. = 3", "1:1: Missing field name in symbol: p.");
+        do_error_test("a(1). = 3", "1:5: Unknown character: .");
+        do_error_test("a(1).3 = 3", "1:5: Unknown character: .");
+        do_error_test("a(1).x", "1:7: Expected = after record field");
+        do_error_test("a(1).x.y = 3", "1:6: Expected field name after .");
+        do_error_test("PRINT 3 .x", "1:9: Unexpected . in expression");
+    }
+
     #[test]
     fn test_assignments() {
         do_ok_test(
@@ -2189,59 +2817,145 @@ mod tests {
     #[test]
     fn test_builtin_calls() {
         do_ok_test(
-            "PRINT a\nPRINT ; 3 , c$\nNOARGS\nNAME 3 AS 4",
+            "PRINT a\nPRINT ; 3 , c$\nNOARGS\nNAME 3 AS 4",
+            &[
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("PRINT", None),
+                    vref_pos: lc(1, 1),
+                    args: vec![ArgSpan {
+                        expr: Some(expr_symbol(VarRef::new("a", None), 1, 7)),
+                        sep: ArgSep::End,
+                        sep_pos: lc(1, 8),
+                    }],
+                }),
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("PRINT", None),
+                    vref_pos: lc(2, 1),
+                    args: vec![
+                        ArgSpan { expr: None, sep: ArgSep::Short, sep_pos: lc(2, 7) },
+                        ArgSpan {
+                            expr: Some(expr_integer(3, 2, 9)),
+                            sep: ArgSep::Long,
+                            sep_pos: lc(2, 11),
+                        },
+                        ArgSpan {
+                            expr: Some(expr_symbol(VarRef::new("c", Some(ExprType::Text)), 2, 13)),
+                            sep: ArgSep::End,
+                            sep_pos: lc(2, 15),
+                        },
+                    ],
+                }),
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("NOARGS", None),
+                    vref_pos: lc(3, 1),
+                    args: vec![],
+                }),
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("NAME", None),
+                    vref_pos: lc(4, 1),
+                    args: vec![
+                        ArgSpan {
+                            expr: Some(expr_integer(3, 4, 6)),
+                            sep: ArgSep::As,
+                            sep_pos: lc(4, 8),
+                        },
+                        ArgSpan {
+                            expr: Some(expr_integer(4, 4, 11)),
+                            sep: ArgSep::End,
+                            sep_pos: lc(4, 12),
+                        },
+                    ],
+                }),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_builtin_calls_with_channels() {
+        do_ok_test(
+            "OPEN f$ FOR input AS #3 + 1\nPRINT #n, 5;\nLINE INPUT #1, l$\nCLOSE #1",
             &[
                 Statement::Call(CallSpan {
-                    vref: VarRef::new("PRINT", None),
+                    vref: VarRef::new("OPEN", None),
                     vref_pos: lc(1, 1),
-                    args: vec![ArgSpan {
-                        expr: Some(expr_symbol(VarRef::new("a", None), 1, 7)),
-                        sep: ArgSep::End,
-                        sep_pos: lc(1, 8),
-                    }],
+                    args: vec![
+                        ArgSpan {
+                            expr: Some(expr_symbol(VarRef::new("f", Some(ExprType::Text)), 1, 6)),
+                            sep: ArgSep::Long,
+                            sep_pos: lc(1, 9),
+                        },
+                        ArgSpan {
+                            expr: Some(expr_text("INPUT", 1, 13)),
+                            sep: ArgSep::As,
+                            sep_pos: lc(1, 19),
+                        },
+                        ArgSpan {
+                            expr: Some(Expr::Add(Box::from(BinaryOpSpan {
+                                lhs: expr_integer(3, 1, 23),
+                                rhs: expr_integer(1, 1, 27),
+                                pos: lc(1, 25),
+                            }))),
+                            sep: ArgSep::End,
+                            sep_pos: lc(1, 28),
+                        },
+                    ],
                 }),
                 Statement::Call(CallSpan {
-                    vref: VarRef::new("PRINT", None),
+                    vref: VarRef::new("PRINT#", None),
                     vref_pos: lc(2, 1),
                     args: vec![
-                        ArgSpan { expr: None, sep: ArgSep::Short, sep_pos: lc(2, 7) },
                         ArgSpan {
-                            expr: Some(expr_integer(3, 2, 9)),
+                            expr: Some(expr_symbol(VarRef::new("n", None), 2, 8)),
                             sep: ArgSep::Long,
-                            sep_pos: lc(2, 11),
+                            sep_pos: lc(2, 9),
                         },
                         ArgSpan {
-                            expr: Some(expr_symbol(VarRef::new("c", Some(ExprType::Text)), 2, 13)),
-                            sep: ArgSep::End,
-                            sep_pos: lc(2, 15),
+                            expr: Some(expr_integer(5, 2, 11)),
+                            sep: ArgSep::Short,
+                            sep_pos: lc(2, 12),
                         },
+                        ArgSpan { expr: None, sep: ArgSep::End, sep_pos: lc(2, 13) },
                     ],
                 }),
                 Statement::Call(CallSpan {
-                    vref: VarRef::new("NOARGS", None),
+                    vref: VarRef::new("LINE INPUT#", None),
                     vref_pos: lc(3, 1),
-                    args: vec![],
-                }),
-                Statement::Call(CallSpan {
-                    vref: VarRef::new("NAME", None),
-                    vref_pos: lc(4, 1),
                     args: vec![
                         ArgSpan {
-                            expr: Some(expr_integer(3, 4, 6)),
-                            sep: ArgSep::As,
-                            sep_pos: lc(4, 8),
+                            expr: Some(expr_integer(1, 3, 13)),
+                            sep: ArgSep::Long,
+                            sep_pos: lc(3, 14),
                         },
                         ArgSpan {
-                            expr: Some(expr_integer(4, 4, 11)),
+                            expr: Some(expr_symbol(VarRef::new("l", Some(ExprType::Text)), 3, 16)),
                             sep: ArgSep::End,
-                            sep_pos: lc(4, 12),
+                            sep_pos: lc(3, 18),
                         },
                     ],
                 }),
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("CLOSE#", None),
+                    vref_pos: lc(4, 1),
+                    args: vec![ArgSpan {
+                        expr: Some(expr_integer(1, 4, 8)),
+                        sep: ArgSep::End,
+                        sep_pos: lc(4, 9),
+                    }],
+                }),
             ],
         );
     }
 
+    #[test]
+    fn test_builtin_calls_with_channels_errors() {
+        do_error_test("PRINT #", "1:8: Missing channel number after #");
+        do_error_test("OPEN f AS #", "1:12: Missing channel number after #");
+        do_error_test("OPEN f FOR 3 AS 1", "1:12: Expected file mode");
+        do_error_test("OPEN f FOR m$ AS 1", "1:12: Expected file mode");
+        do_error_test("PRINT f FOR 3", "1:9: Unexpected keyword in expression");
+        do_error_test("PRINT 1, #2", "1:10: Unexpected # in expression");
+    }
+
     #[test]
     fn test_builtin_calls_and_array_references_disambiguation() {
         use Expr::*;
@@ -2617,6 +3331,47 @@ mod tests {
         do_error_test("DIM a(1) AS INTEGER 3", "1:21: Unexpected 3 in DIM statement");
     }
 
+    #[test]
+    fn test_dim_records() {
+        do_ok_test(
+            "DIM p AS Point",
+            &[Statement::DimRecord(DimRecordSpan {
+                name: "p".to_owned(),
+                name_pos: lc(1, 5),
+                shared: false,
+                dimensions: vec![],
+                type_name: "Point".to_owned(),
+                type_pos: lc(1, 10),
+            })],
+        );
+
+        do_ok_test(
+            "DIM SHARED a(3, n) AS Point",
+            &[Statement::DimRecord(DimRecordSpan {
+                name: "a".to_owned(),
+                name_pos: lc(1, 12),
+                shared: true,
+                dimensions: vec![
+                    expr_integer(3, 1, 14),
+                    expr_symbol(VarRef::new("n", None), 1, 17),
+                ],
+                type_name: "Point".to_owned(),
+                type_pos: lc(1, 23),
+            })],
+        );
+    }
+
+    #[test]
+    fn test_dim_record_errors() {
+        do_error_test("DIM p.x", "1:5: Invalid name p.x: dots are reserved for record fields");
+        do_error_test(
+            "DIM p AS a.b",
+            "1:10: Invalid name a.b: dots are reserved for record fields",
+        );
+        do_error_test("DIM p AS Point$", "1:10: Type annotation not allowed in Point$");
+        do_error_test("DIM p AS Point 3", "1:16: Unexpected 3 in DIM statement");
+    }
+
     #[test]
     fn test_do_until_empty() {
         do_ok_test(
@@ -3865,7 +4620,52 @@ mod tests {
             Statement::OnError(OnErrorSpan::ResumeNext),
         );
 
-        do_error_test("IF 1 THEN ON", "1:13: Expected ERROR after ON");
+        do_error_test("IF 1 THEN ON", "1:13: Expected ERROR or an expression after ON");
+    }
+
+    #[test]
+    fn test_if_uniline_allowed_on_goto() {
+        do_if_uniline_allowed_test(
+            "ON x GOTO @a",
+            Statement::OnGoto(OnGotoSpan {
+                expr: expr_symbol(VarRef::new("x", None), 1, 14),
+                gosub: false,
+                targets: vec![GotoSpan { target: "a".to_owned(), target_pos: lc(1, 21) }],
+                pos: lc(1, 11),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_if_uniline_allowed_on_timer() {
+        do_if_uniline_allowed_test(
+            "ON TIMER(1) GOSUB @a",
+            Statement::OnTimer(OnTimerSpan {
+                interval: expr_integer(1, 1, 20),
+                target: GotoSpan { target: "a".to_owned(), target_pos: lc(1, 29) },
+                pos: lc(1, 11),
+            }),
+        );
+    }
+
+    #[test]
+    fn test_if_uniline_allowed_resume() {
+        do_if_uniline_allowed_test(
+            "RESUME",
+            Statement::Resume(ResumeSpan { next: false, pos: lc(1, 11) }),
+        );
+        do_if_uniline_allowed_test(
+            "RESUME NEXT",
+            Statement::Resume(ResumeSpan { next: true, pos: lc(1, 11) }),
+        );
+    }
+
+    #[test]
+    fn test_if_uniline_allowed_timer() {
+        do_if_uniline_allowed_test(
+            "TIMER STOP",
+            Statement::Timer(TimerSpan { action: TimerAction::Stop, pos: lc(1, 11) }),
+        );
     }
 
     #[test]
@@ -4170,7 +4970,11 @@ mod tests {
             &[Statement::Callable(CallableSpan {
                 name: VarRef::new("foo", Some(ExprType::Text)),
                 name_pos: lc(1, 10),
-                params: vec![VarRef::new("x", None)],
+                params: vec![ParamSpan {
+                    vref: VarRef::new("x", None),
+                    byref: false,
+                    default: None,
+                }],
                 body: vec![],
                 end_pos: lc(2, 1),
             })],
@@ -4185,9 +4989,17 @@ mod tests {
                 name: VarRef::new("foo", Some(ExprType::Text)),
                 name_pos: lc(1, 10),
                 params: vec![
-                    VarRef::new("x", Some(ExprType::Text)),
-                    VarRef::new("y", None),
-                    VarRef::new("z", Some(ExprType::Boolean)),
+                    ParamSpan {
+                        vref: VarRef::new("x", Some(ExprType::Text)),
+                        byref: false,
+                        default: None,
+                    },
+                    ParamSpan { vref: VarRef::new("y", None), byref: false, default: None },
+                    ParamSpan {
+                        vref: VarRef::new("z", Some(ExprType::Boolean)),
+                        byref: false,
+                        default: None,
+                    },
                 ],
                 body: vec![],
                 end_pos: lc(2, 1),
@@ -4219,7 +5031,7 @@ mod tests {
         do_error_test("FUNCTION foo (a AS)", "1:19: Invalid type name ) in AS type definition");
         do_error_test(
             "FUNCTION foo (a INTEGER)",
-            "1:17: Expected comma, AS, or end of parameters list",
+            "1:17: Expected comma, AS, =, or end of parameters list",
         );
         do_error_test("FUNCTION foo (a? AS BOOLEAN)", "1:15: Type annotation not allowed in a?");
     }
@@ -4329,10 +5141,158 @@ mod tests {
         do_ok_test("ON ERROR RESUME NEXT", &[Statement::OnError(OnErrorSpan::ResumeNext)]);
     }
 
+    #[test]
+    fn test_parse_on_goto_ok() {
+        do_ok_test(
+            "ON i GOTO 10",
+            &[Statement::OnGoto(OnGotoSpan {
+                expr: expr_symbol(VarRef::new("i", None), 1, 4),
+                gosub: false,
+                targets: vec![GotoSpan { target: "10".to_owned(), target_pos: lc(1, 11) }],
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "ON 1 + 2 GOSUB @a, 20,@c",
+            &[Statement::OnGoto(OnGotoSpan {
+                expr: Expr::Add(Box::from(BinaryOpSpan {
+                    lhs: expr_integer(1, 1, 4),
+                    rhs: expr_integer(2, 1, 8),
+                    pos: lc(1, 6),
+                })),
+                gosub: true,
+                targets: vec![
+                    GotoSpan { target: "a".to_owned(), target_pos: lc(1, 16) },
+                    GotoSpan { target: "20".to_owned(), target_pos: lc(1, 20) },
+                    GotoSpan { target: "c".to_owned(), target_pos: lc(1, 23) },
+                ],
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "PRINT: ON n GOTO @a: PRINT",
+            &[
+                make_bare_builtin_call("PRINT", 1, 1),
+                Statement::OnGoto(OnGotoSpan {
+                    expr: expr_symbol(VarRef::new("n", None), 1, 11),
+                    gosub: false,
+                    targets: vec![GotoSpan { target: "a".to_owned(), target_pos: lc(1, 18) }],
+                    pos: lc(1, 8),
+                }),
+                make_bare_builtin_call("PRINT", 1, 22),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_on_goto_errors() {
+        do_error_test("ON 3", "1:5: Expected ERROR, GOTO or GOSUB in ON statement");
+        do_error_test("ON 3 RETURN", "1:6: Unexpected keyword in expression");
+        do_error_test("ON 3, 4 GOTO @a", "1:5: Expected ERROR, GOTO or GOSUB in ON statement");
+        do_error_test("ON 3 GOTO", "1:10: Expected label name after GOTO");
+        do_error_test("ON 3 GOSUB", "1:11: Expected label name after GOSUB");
+        do_error_test("ON 3 GOTO @a,", "1:14: Expected label name after GOTO");
+        do_error_test("ON 3 GOTO @a, foo", "1:15: Expected label name after GOTO");
+        do_error_test("ON 3 GOSUB @a @b", "1:15: Expected newline but found @b");
+    }
+
+    #[test]
+    fn test_parse_on_timer_ok() {
+        do_ok_test(
+            "ON TIMER(5) GOSUB 100",
+            &[Statement::OnTimer(OnTimerSpan {
+                interval: expr_integer(5, 1, 10),
+                target: GotoSpan { target: "100".to_owned(), target_pos: lc(1, 19) },
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "on timer (n * 0.5) gosub @tick",
+            &[Statement::OnTimer(OnTimerSpan {
+                interval: Expr::Multiply(Box::from(BinaryOpSpan {
+                    lhs: expr_symbol(VarRef::new("n", None), 1, 11),
+                    rhs: expr_double(0.5, 1, 15),
+                    pos: lc(1, 13),
+                })),
+                target: GotoSpan { target: "tick".to_owned(), target_pos: lc(1, 26) },
+                pos: lc(1, 1),
+            })],
+        );
+    }
+
+    #[test]
+    fn test_parse_on_timer_errors() {
+        do_error_test("ON TIMER", "1:9: Expected ( after ON TIMER");
+        do_error_test("ON TIMER 5 GOSUB @a", "1:10: Expected ( after ON TIMER");
+        do_error_test("ON TIMER(5)", "1:12: Expected GOSUB after ON TIMER");
+        do_error_test("ON TIMER(5) GOTO @a", "1:13: Expected GOSUB after ON TIMER");
+        do_error_test("ON TIMER(5) GOSUB", "1:18: Expected label name after GOSUB");
+        do_error_test("ON TIMER(5) GOSUB @a, @b", "1:21: Expected newline but found ,");
+    }
+
+    #[test]
+    fn test_parse_timer_ok() {
+        do_ok_test(
+            "TIMER ON\ntimer off\nTimer Stop",
+            &[
+                Statement::Timer(TimerSpan { action: TimerAction::On, pos: lc(1, 1) }),
+                Statement::Timer(TimerSpan { action: TimerAction::Off, pos: lc(2, 1) }),
+                Statement::Timer(TimerSpan { action: TimerAction::Stop, pos: lc(3, 1) }),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_timer_other_uses() {
+        do_ok_test(
+            "TIMER OFF$\nTIMER",
+            &[
+                Statement::Call(CallSpan {
+                    vref: VarRef::new("TIMER", None),
+                    vref_pos: lc(1, 1),
+                    args: vec![ArgSpan {
+                        expr: Some(expr_symbol(VarRef::new("OFF", Some(ExprType::Text)), 1, 7)),
+                        sep: ArgSep::End,
+                        sep_pos: lc(1, 11),
+                    }],
+                }),
+                make_bare_builtin_call("TIMER", 2, 1),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_timer_errors() {
+        do_error_test("TIMER ON 3", "1:10: Expected newline but found 3");
+        do_error_test("TIMER STOP NOW", "1:12: Expected newline but found NOW");
+    }
+
+    #[test]
+    fn test_parse_resume_ok() {
+        do_ok_test("RESUME", &[Statement::Resume(ResumeSpan { next: false, pos: lc(1, 1) })]);
+        do_ok_test("RESUME NEXT", &[Statement::Resume(ResumeSpan { next: true, pos: lc(1, 1) })]);
+        do_ok_test(
+            "RESUME: RESUME NEXT",
+            &[
+                Statement::Resume(ResumeSpan { next: false, pos: lc(1, 1) }),
+                Statement::Resume(ResumeSpan { next: true, pos: lc(1, 9) }),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_resume_errors() {
+        do_error_test("RESUME 3", "1:8: Expected newline but found 3");
+        do_error_test("RESUME NEXT @a", "1:13: Expected newline but found @a");
+    }
+
     #[test]
     fn test_parse_on_error_errors() {
-        do_error_test("ON", "1:3: Expected ERROR after ON");
-        do_error_test("ON NEXT", "1:4: Expected ERROR after ON");
+        do_error_test("ON", "1:3: Expected ERROR or an expression after ON");
+        do_error_test("ON NEXT", "1:4: Unexpected keyword in expression");
         do_error_test("ON ERROR", "1:9: Expected GOTO or RESUME after ON ERROR");
         do_error_test("ON ERROR FOR", "1:10: Expected GOTO or RESUME after ON ERROR");
 
@@ -4626,6 +5586,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_static() {
+        do_ok_test(
+            "STATIC n",
+            &[Statement::Static(StaticSpan {
+                decl: StaticDecl::Dim(DimSpan {
+                    name: "n".to_owned(),
+                    name_pos: lc(1, 8),
+                    shared: false,
+                    vtype: ExprType::Integer,
+                    vtype_pos: lc(1, 9),
+                }),
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "STATIC n AS STRING",
+            &[Statement::Static(StaticSpan {
+                decl: StaticDecl::Dim(DimSpan {
+                    name: "n".to_owned(),
+                    name_pos: lc(1, 8),
+                    shared: false,
+                    vtype: ExprType::Text,
+                    vtype_pos: lc(1, 13),
+                }),
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "STATIC a(3, 4) AS DOUBLE",
+            &[Statement::Static(StaticSpan {
+                decl: StaticDecl::DimArray(DimArraySpan {
+                    name: "a".to_owned(),
+                    name_pos: lc(1, 8),
+                    shared: false,
+                    dimensions: vec![expr_integer(3, 1, 10), expr_integer(4, 1, 13)],
+                    subtype: ExprType::Double,
+                    subtype_pos: lc(1, 19),
+                }),
+                pos: lc(1, 1),
+            })],
+        );
+
+        do_ok_test(
+            "STATIC n$ = \"foo\"",
+            &[Statement::Static(StaticSpan {
+                decl: StaticDecl::Assignment(AssignmentSpan {
+                    vref: VarRef::new("n", Some(ExprType::Text)),
+                    vref_pos: lc(1, 8),
+                    expr: expr_text("foo", 1, 13),
+                }),
+                pos: lc(1, 1),
+            })],
+        );
+    }
+
+    #[test]
+    fn test_static_errors() {
+        do_error_test("STATIC", "1:7: Expected variable name after STATIC");
+        do_error_test("STATIC 3", "1:8: Expected variable name after STATIC");
+        do_error_test("STATIC p.x", "1:8: Invalid name p.x: dots are reserved for record fields");
+        do_error_test("STATIC n$ AS STRING", "1:8: Type annotation not allowed in n$");
+        do_error_test("STATIC n AS INTEGER 3", "1:21: Unexpected 3 in STATIC statement");
+        do_error_test("STATIC n =", "1:11: Missing expression in STATIC statement");
+        do_error_test("STATIC a()", "1:9: Arrays require at least one dimension");
+        do_error_test("STATIC p AS Point", "1:13: STATIC does not support records");
+        do_error_test("STATIC a(2) AS Point", "1:16: STATIC does not support records");
+    }
+
     #[test]
     fn test_sub_empty() {
         do_ok_test(
@@ -4675,7 +5706,11 @@ mod tests {
             &[Statement::Callable(CallableSpan {
                 name: VarRef::new("foo", None),
                 name_pos: lc(1, 5),
-                params: vec![VarRef::new("x", None)],
+                params: vec![ParamSpan {
+                    vref: VarRef::new("x", None),
+                    byref: false,
+                    default: None,
+                }],
                 body: vec![],
                 end_pos: lc(2, 1),
             })],
@@ -4690,9 +5725,77 @@ mod tests {
                 name: VarRef::new("foo", None),
                 name_pos: lc(1, 5),
                 params: vec![
-                    VarRef::new("x", Some(ExprType::Text)),
-                    VarRef::new("y", None),
-                    VarRef::new("z", Some(ExprType::Boolean)),
+                    ParamSpan {
+                        vref: VarRef::new("x", Some(ExprType::Text)),
+                        byref: false,
+                        default: None,
+                    },
+                    ParamSpan { vref: VarRef::new("y", None), byref: false, default: None },
+                    ParamSpan {
+                        vref: VarRef::new("z", Some(ExprType::Boolean)),
+                        byref: false,
+                        default: None,
+                    },
+                ],
+                body: vec![],
+                end_pos: lc(2, 1),
+            })],
+        );
+    }
+
+    #[test]
+    fn test_sub_byref_params() {
+        do_ok_test(
+            "SUB foo(x, BYREF y#, byref z AS STRING)\nEND SUB",
+            &[Statement::Callable(CallableSpan {
+                name: VarRef::new("foo", None),
+                name_pos: lc(1, 5),
+                params: vec![
+                    ParamSpan { vref: VarRef::new("x", None), byref: false, default: None },
+                    ParamSpan {
+                        vref: VarRef::new("y", Some(ExprType::Double)),
+                        byref: true,
+                        default: None,
+                    },
+                    ParamSpan {
+                        vref: VarRef::new("z", Some(ExprType::Text)),
+                        byref: true,
+                        default: None,
+                    },
+                ],
+                body: vec![],
+                end_pos: lc(2, 1),
+            })],
+        );
+    }
+
+    #[test]
+    fn test_sub_default_params() {
+        do_ok_test(
+            "SUB foo(x, y$ = \"a\", z AS DOUBLE = -1.5, w = v)\nEND SUB",
+            &[Statement::Callable(CallableSpan {
+                name: VarRef::new("foo", None),
+                name_pos: lc(1, 5),
+                params: vec![
+                    ParamSpan { vref: VarRef::new("x", None), byref: false, default: None },
+                    ParamSpan {
+                        vref: VarRef::new("y", Some(ExprType::Text)),
+                        byref: false,
+                        default: Some(expr_text("a", 1, 17)),
+                    },
+                    ParamSpan {
+                        vref: VarRef::new("z", Some(ExprType::Double)),
+                        byref: false,
+                        default: Some(Expr::Negate(Box::from(UnaryOpSpan {
+                            expr: expr_double(1.5, 1, 37),
+                            pos: lc(1, 36),
+                        }))),
+                    },
+                    ParamSpan {
+                        vref: VarRef::new("w", None),
+                        byref: false,
+                        default: Some(expr_symbol(VarRef::new("v", None), 1, 46)),
+                    },
                 ],
                 body: vec![],
                 end_pos: lc(2, 1),
@@ -4722,8 +5825,24 @@ mod tests {
         do_error_test("SUB foo (a,)", "1:12: Expected a parameter name");
         do_error_test("SUB foo (,b)", "1:10: Expected a parameter name");
         do_error_test("SUB foo (a AS)", "1:14: Invalid type name ) in AS type definition");
-        do_error_test("SUB foo (a INTEGER)", "1:12: Expected comma, AS, or end of parameters list");
+        do_error_test(
+            "SUB foo (a INTEGER)",
+            "1:12: Expected comma, AS, =, or end of parameters list",
+        );
         do_error_test("SUB foo (a? AS BOOLEAN)", "1:10: Type annotation not allowed in a?");
+        do_error_test("SUB foo (BYREF)", "1:15: Expected a parameter name");
+        do_error_test("SUB foo (BYREF BYREF a)", "1:16: Expected a parameter name");
+        do_error_test(
+            "SUB foo (a BYREF)",
+            "1:12: Expected comma, AS, =, or end of parameters list",
+        );
+        do_error_test("SUB foo (a =)", "1:13: Missing default value");
+        do_error_test("SUB foo (a = 1 b)", "1:16: Unexpected value in expression");
+        do_error_test("SUB foo (BYREF a = 1)", "1:18: BYREF parameters cannot have default values");
+        do_error_test(
+            "SUB foo (a = 1, b AS STRING)",
+            "1:17: Parameter b$ after optional parameters needs a default",
+        );
         do_error_test(
             "SUB foo$",
             "1:5: SUBs cannot return a value so type annotations are not allowed",
@@ -4734,6 +5853,65 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_type_ok() {
+        do_ok_test(
+            "TYPE Point\nEND TYPE",
+            &[Statement::Type(TypeSpan {
+                name: "Point".to_owned(),
+                name_pos: lc(1, 6),
+                fields: vec![],
+            })],
+        );
+
+        do_ok_test(
+            "TYPE Point\n  x AS INTEGER\n\n  label AS STRING\nEND TYPE\nPRINT",
+            &[
+                Statement::Type(TypeSpan {
+                    name: "Point".to_owned(),
+                    name_pos: lc(1, 6),
+                    fields: vec![
+                        FieldSpan {
+                            name: "x".to_owned(),
+                            name_pos: lc(2, 3),
+                            vtype: ExprType::Integer,
+                            vtype_pos: lc(2, 8),
+                        },
+                        FieldSpan {
+                            name: "label".to_owned(),
+                            name_pos: lc(4, 3),
+                            vtype: ExprType::Text,
+                            vtype_pos: lc(4, 12),
+                        },
+                    ],
+                }),
+                make_bare_builtin_call("PRINT", 6, 1),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_type_errors() {
+        do_error_test("TYPE", "1:5: Expected a type name after TYPE");
+        do_error_test("TYPE 3", "1:6: Expected a type name after TYPE");
+        do_error_test("TYPE a$", "1:6: Type annotation not allowed in a$");
+        do_error_test("TYPE a.b", "1:6: Invalid name a.b: dots are reserved for record fields");
+        do_error_test("TYPE a b", "1:8: Expected newline after TYPE name");
+        do_error_test("TYPE a\nx AS INTEGER\n", "1:1: TYPE without END TYPE");
+        do_error_test("TYPE a\nEND", "2:4: Expected TYPE after END");
+        do_error_test("TYPE a\nx\nEND TYPE", "2:2: Expected AS after field name");
+        do_error_test(
+            "TYPE a\nx AS\nEND TYPE",
+            "2:5: Invalid type name <<NEWLINE>> in AS type definition",
+        );
+        do_error_test("TYPE a\nx AS a\nEND TYPE", "2:6: Invalid type name a in AS type definition");
+        do_error_test("TYPE a\nx% AS INTEGER\nEND TYPE", "2:1: Type annotation not allowed in x%");
+        do_error_test("TYPE a\nx AS INTEGER 3\nEND TYPE", "2:14: Expected newline but found 3");
+        do_error_test("TYPE a\nPRINT 3\nEND TYPE", "2:7: Expected AS after field name");
+        do_error_test("TYPE a\nIF\nEND TYPE", "2:1: Unexpected IF in TYPE definition");
+        do_error_test("IF TRUE THEN TYPE a", "1:14: Unexpected TYPE in uniline IF branch");
+    }
+
     #[test]
     fn test_while_empty() {
         do_ok_test(
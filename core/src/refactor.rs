@@ -0,0 +1,577 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Source code transformations that preserve the formatting of a program.
+
+use crate::lexer::{Lexer, Token};
+use crate::minifier::is_keyword;
+use crate::reader::{CharReader, CharSpan};
+use crate::LineCol;
+use std::collections::{HashMap, HashSet};
+use std::io;
+
+/// Scope in which a symbol reference is resolved.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Scope {
+    /// The symbol is global: a variable or array defined outside of any callable, a shared
+    /// variable, or a user-defined callable.
+    Global,
+
+    /// The symbol is local to the user-defined callable with the given name, in uppercase.
+    Local(String),
+}
+
+/// A reference to a symbol in the source code.
+struct SymbolRef {
+    /// Position of the first character of the reference.
+    pos: LineCol,
+
+    /// Name of the symbol in uppercase, without its type annotation.
+    key: String,
+
+    /// Number of characters in the name of the symbol as written, without its type annotation.
+    len: usize,
+
+    /// Scope in which the reference resolves.
+    scope: Scope,
+}
+
+/// All the symbols referenced by a program.
+struct Symbols {
+    /// References to symbols in the order in which they appear.
+    refs: Vec<SymbolRef>,
+
+    /// Names of the user-defined callables, in uppercase.
+    callables: HashSet<String>,
+
+    /// Names of the variables and arrays defined via `DIM SHARED`, in uppercase.
+    shared: HashSet<String>,
+}
+
+impl Symbols {
+    /// Returns true if the global symbol `key` is visible from within callables.
+    fn is_visible_in_locals(&self, key: &str) -> bool {
+        self.callables.contains(key) || self.shared.contains(key)
+    }
+}
+
+/// Collects all references to symbols in `text` and resolves their scopes, ignoring names for
+/// which `is_reserved` returns true.
+fn scan(text: &str, is_reserved: &dyn Fn(&str) -> bool) -> io::Result<Symbols> {
+    let mut callables = HashSet::new();
+    let mut shared = HashSet::new();
+    let mut raw_refs = vec![];
+
+    let mut current: Option<String> = None;
+    let mut prev = Token::Eol;
+    let mut prev2 = Token::Eol;
+    let mut bytes = text.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    loop {
+        let span = lexer.read()?;
+        match &span.token {
+            Token::Eof => break,
+
+            Token::Bad(msg) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", span.pos, msg),
+                ))
+            }
+
+            Token::Function | Token::Sub if prev == Token::End => current = None,
+
+            Token::Symbol(vref) if !is_reserved(vref.name()) => {
+                let key = vref.name().to_uppercase();
+                let len = vref.name().chars().count();
+                if matches!(prev, Token::Function | Token::Sub) && prev2 == Token::Eol {
+                    callables.insert(key.clone());
+                    raw_refs.push((span.pos, key.clone(), len, None));
+                    current = Some(key);
+                } else {
+                    if prev == Token::Shared && prev2 == Token::Dim && current.is_none() {
+                        shared.insert(key.clone());
+                    }
+                    raw_refs.push((span.pos, key, len, current.clone()));
+                }
+            }
+
+            _ => (),
+        }
+        prev2 = prev;
+        prev = span.token;
+    }
+
+    let refs = raw_refs
+        .into_iter()
+        .map(|(pos, key, len, owner)| {
+            let scope = match owner {
+                Some(owner) if !callables.contains(&key) && !shared.contains(&key) => {
+                    Scope::Local(owner)
+                }
+                _ => Scope::Global,
+            };
+            SymbolRef { pos, key, len, scope }
+        })
+        .collect();
+    Ok(Symbols { refs, callables, shared })
+}
+
+/// Validates that `name` is a plain symbol name and returns it in uppercase.
+fn parse_name(name: &str) -> io::Result<String> {
+    let invalid =
+        || io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid symbol name '{}'", name));
+
+    let mut bytes = name.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    match lexer.read()?.token {
+        Token::Symbol(vref) if vref.ref_type().is_none() && vref.name() == name => (),
+        _ => return Err(invalid()),
+    }
+    match lexer.read()?.token {
+        Token::Eof => Ok(name.to_uppercase()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Renames all references to the symbol `old` in the program in `input` to `new` and returns the
+/// new source code along with the number of references that were changed.
+///
+/// `old` names a global variable, array, or callable, or a symbol local to a callable if given in
+/// the `callable.name` form.  Only references that resolve to that symbol are renamed: a local
+/// variable in a `FUNCTION` or `SUB` is not conflated with a global variable of the same name
+/// unless the latter is defined via `DIM SHARED`.  Type annotations in the references are kept.
+///
+/// The rename is refused if `new` is a keyword, a name for which `is_reserved` returns true (which
+/// should cover all built-in callables), or a name that is already in use where `old` is visible.
+///
+/// The source code is modified in place so all comments and formatting are preserved.  Note that
+/// names referenced from within strings (for example, in code given to `EXECUTE`) are not renamed.
+pub fn rename(
+    input: &mut dyn io::Read,
+    old: &str,
+    new: &str,
+    is_reserved: &dyn Fn(&str) -> bool,
+) -> io::Result<(String, usize)> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    let (target_scope, old_key) = match old.split_once('.') {
+        Some((owner, name)) => (Scope::Local(parse_name(owner)?), parse_name(name)?),
+        None => (Scope::Global, parse_name(old)?),
+    };
+    if is_reserved(old) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot rename built-in '{}'", old),
+        ));
+    }
+    let looks_like_word = new.chars().next().map(char::is_alphabetic).unwrap_or(false)
+        && new.chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+    if looks_like_word && is_keyword(new) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Cannot rename to '{}' because it is a keyword", new),
+        ));
+    }
+    let new_key = parse_name(new)?;
+    if is_reserved(new) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("Cannot rename to '{}' because it is a built-in name", new),
+        ));
+    }
+
+    let symbols = scan(&text, is_reserved)?;
+    if let (Scope::Local(owner), Some((name, _))) = (&target_scope, old.split_once('.')) {
+        if !symbols.callables.contains(owner) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Unknown FUNCTION or SUB '{}'", name),
+            ));
+        }
+    }
+    let matches: Vec<&SymbolRef> =
+        symbols.refs.iter().filter(|r| r.key == old_key && r.scope == target_scope).collect();
+    if matches.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::NotFound, format!("Symbol '{}' not found", old)));
+    }
+
+    if new_key != old_key {
+        let collides = match &target_scope {
+            // Callables and shared variables are visible from within all callables, so renaming
+            // one of them must not capture any local with the new name.
+            Scope::Global if symbols.is_visible_in_locals(&old_key) => {
+                symbols.refs.iter().any(|r| r.key == new_key)
+            }
+            Scope::Global => {
+                symbols.refs.iter().any(|r| r.key == new_key && r.scope == Scope::Global)
+            }
+            Scope::Local(_) => {
+                symbols.is_visible_in_locals(&new_key)
+                    || symbols.refs.iter().any(|r| r.key == new_key && r.scope == target_scope)
+            }
+        };
+        if collides {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Cannot rename to '{}' because it is already in use", new),
+            ));
+        }
+    }
+
+    let replacements = matches.iter().map(|r| (r.pos, r.len, new.to_owned())).collect();
+    let output = replace(&text, replacements)?;
+    Ok((output, matches.len()))
+}
+
+/// Replaces the `(pos, len, new)` spans of `text` that start at `pos` and are `len` characters long
+/// with their `new` contents, leaving everything else untouched.
+fn replace(text: &str, mut replacements: Vec<(LineCol, usize, String)>) -> io::Result<String> {
+    let chars = CharReader::from(&mut text.as_bytes()).collect::<io::Result<Vec<CharSpan>>>()?;
+    let mut starts = Vec::with_capacity(replacements.len());
+    for (pos, len, new) in replacements.drain(..) {
+        let index = chars
+            .binary_search_by_key(&(pos.line, pos.col), |c| (c.pos.line, c.pos.col))
+            .expect("Token positions must map to characters in the input");
+        starts.push((index, len, new));
+    }
+    starts.sort_unstable();
+
+    let mut output = String::with_capacity(text.len());
+    let mut pending = starts.iter().peekable();
+    let mut i = 0;
+    while i < chars.len() {
+        match pending.peek() {
+            Some((start, len, new)) if *start == i => {
+                output.push_str(new);
+                i += len;
+                pending.next();
+            }
+            _ => {
+                output.push(chars[i].ch);
+                i += 1;
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Renumbers all numbered lines in the program in `input` so that they start at `start` and
+/// increase by `step`, and returns the new source code along with the number of renumbered lines.
+///
+/// A numbered line is a statement that starts with an integer label, optionally preceded by
+/// other labels.  All `GOTO`, `GOSUB`, and `ON ERROR GOTO` statements that target a line number are
+/// updated to match.  The renumbering is refused if any of these statements targets a line number
+/// that does not exist, if a line number is defined more than once, or if the new line numbers do
+/// not fit in an integer.
+///
+/// The source code is modified in place so all comments and formatting are preserved.
+pub fn renumber(input: &mut dyn io::Read, start: i32, step: i32) -> io::Result<(String, usize)> {
+    let mut text = String::new();
+    input.read_to_string(&mut text)?;
+
+    if start < 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Start line number {} must be positive", start),
+        ));
+    }
+    if step <= 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Line number step {} must be positive", step),
+        ));
+    }
+
+    let mut lines = vec![];
+    let mut targets = vec![];
+    let mut prev = Token::Eol;
+    let mut prev2 = Token::Eol;
+    let mut bytes = text.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    loop {
+        let span = lexer.read()?;
+        match &span.token {
+            Token::Eof => break,
+
+            Token::Bad(msg) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", span.pos, msg),
+                ))
+            }
+
+            Token::Integer(0) if prev == Token::Goto && prev2 == Token::Error => (),
+
+            Token::Integer(i) if matches!(prev, Token::Goto | Token::Gosub) => {
+                targets.push((*i, span.pos, span.length));
+            }
+
+            Token::Integer(i) if matches!(prev, Token::Eol | Token::Label(_)) => {
+                lines.push((*i, span.pos, span.length));
+            }
+
+            _ => (),
+        }
+        prev2 = prev;
+        prev = span.token;
+    }
+
+    let mut mapping = HashMap::with_capacity(lines.len());
+    let mut replacements = Vec::with_capacity(lines.len() + targets.len());
+    let mut next = Some(start);
+    for (old, pos, len) in lines.iter() {
+        let new = next.ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{}: Line numbers would exceed the maximum integer", pos),
+            )
+        })?;
+        if mapping.insert(*old, new).is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: Duplicate line number {}", pos, old),
+            ));
+        }
+        replacements.push((*pos, *len, new.to_string()));
+        next = new.checked_add(step);
+    }
+    for (old, pos, len) in targets {
+        match mapping.get(&old) {
+            Some(new) => replacements.push((pos, len, new.to_string())),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("{}: Reference to unknown line number {}", pos, old),
+                ))
+            }
+        }
+    }
+
+    let output = replace(&text, replacements)?;
+    Ok((output, lines.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Renames `old` to `new` in `input` treating `PRINT` and `LEN` as the only reserved names.
+    fn do_rename(input: &str, old: &str, new: &str) -> io::Result<(String, usize)> {
+        let is_reserved = |name: &str| matches!(name.to_uppercase().as_str(), "PRINT" | "LEN");
+        rename(&mut input.as_bytes(), old, new, &is_reserved)
+    }
+
+    /// Renames `old` to `new` in `input` and expects an error with `message`.
+    fn check_error(input: &str, old: &str, new: &str, message: &str) {
+        assert_eq!(message, format!("{}", do_rename(input, old, new).unwrap_err()));
+    }
+
+    /// Renumbers `input` and expects an error with `message`.
+    fn check_renumber_error(input: &str, start: i32, step: i32, message: &str) {
+        let err = renumber(&mut input.as_bytes(), start, step).unwrap_err();
+        assert_eq!(message, format!("{}", err));
+    }
+
+    #[test]
+    fn test_renumber_preserves_formatting() {
+        let input =
+            "' Countdown\n5 i = 3   ' Start\n7   PRINT i: i = i - 1\n\tIF i > 0 THEN GOTO 7\n";
+        assert_eq!(
+            (
+                "' Countdown\n100 i = 3   ' Start\n110   PRINT i: i = i - 1\n\tIF i > 0 THEN GOTO 110\n"
+                    .to_owned(),
+                2
+            ),
+            renumber(&mut input.as_bytes(), 100, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_renumber_all_reference_kinds() {
+        let input = "1 ON ERROR GOTO 900\nGOSUB 50: GOTO @end\n50 PRINT 50: RETURN\n@end 60 ON ERROR GOTO 0\n900 END 1\n";
+        assert_eq!(
+            (
+                "10 ON ERROR GOTO 40\nGOSUB 20: GOTO @end\n20 PRINT 50: RETURN\n@end 30 ON ERROR GOTO 0\n40 END 1\n"
+                    .to_owned(),
+                4
+            ),
+            renumber(&mut input.as_bytes(), 10, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_renumber_after_colon_and_varying_widths() {
+        let input = "a = 1: 0005 PRINT a\nGOTO 5\n";
+        assert_eq!(
+            ("a = 1: 1000 PRINT a\nGOTO 1000\n".to_owned(), 1),
+            renumber(&mut input.as_bytes(), 1000, 1).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_renumber_nothing() {
+        assert_eq!(
+            ("PRINT 3\nGOTO @a\n@a\n".to_owned(), 0),
+            renumber(&mut "PRINT 3\nGOTO @a\n@a\n".as_bytes(), 10, 10).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_renumber_errors() {
+        check_renumber_error("10 GOTO 20\n", 10, 10, "1:9: Reference to unknown line number 20");
+        check_renumber_error(
+            "10 PRINT\nGOSUB 15\n",
+            10,
+            10,
+            "2:7: Reference to unknown line number 15",
+        );
+        check_renumber_error("10 PRINT\n10 PRINT\n", 10, 10, "2:1: Duplicate line number 10");
+        check_renumber_error(
+            "1 PRINT\n2 PRINT\n",
+            2147483647,
+            1,
+            "2:1: Line numbers would exceed the maximum integer",
+        );
+        check_renumber_error("10 PRINT\n", -1, 10, "Start line number -1 must be positive");
+        check_renumber_error("10 PRINT\n", 10, 0, "Line number step 0 must be positive");
+        check_renumber_error("10 a = .3\n", 10, 10, "1:8: Unknown character: .");
+    }
+
+    #[test]
+    fn test_rename_global_preserves_formatting() {
+        let input = "' Counter demo\ncount% = 3   ' Initial value\n\tPRINT Count; \"count\"\nIF count > 1 THEN count = count - 1: PRINT LEN(\"x\")\n";
+        assert_eq!(
+            (
+                "' Counter demo\ntotal% = 3   ' Initial value\n\tPRINT total; \"count\"\nIF total > 1 THEN total = total - 1: PRINT LEN(\"x\")\n"
+                    .to_owned(),
+                5
+            ),
+            do_rename(input, "count", "total").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_global_skips_locals_with_same_name() {
+        let input = "x = 1\nFUNCTION f(x)\n    f = x * 2\nEND FUNCTION\nSUB s\n    x = 5\nEND SUB\nPRINT f(x)\n";
+        assert_eq!(
+            (
+                "y = 1\nFUNCTION f(x)\n    f = x * 2\nEND FUNCTION\nSUB s\n    x = 5\nEND SUB\nPRINT f(y)\n"
+                    .to_owned(),
+                2
+            ),
+            do_rename(input, "x", "y").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_shared_global_and_callable() {
+        let input = "DIM SHARED x AS INTEGER\nFUNCTION f(n)\n    x = x + n\n    f = x\nEND FUNCTION\nPRINT f(2): x = 0\n";
+        assert_eq!(
+            (
+                "DIM SHARED total AS INTEGER\nFUNCTION f(n)\n    total = total + n\n    f = total\nEND FUNCTION\nPRINT f(2): total = 0\n"
+                    .to_owned(),
+                5
+            ),
+            do_rename(input, "x", "total").unwrap()
+        );
+        assert_eq!(
+            (
+                "DIM SHARED x AS INTEGER\nFUNCTION add%(n)\n    x = x + n\n    add = x\nEND FUNCTION\nPRINT add(2): x = 0\n"
+                    .to_owned(),
+                3
+            ),
+            do_rename(&input.replace("f(n)", "f%(n)"), "f", "add").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_local() {
+        let input = "i = 10\nSUB s(n)\n    FOR i = 1 TO n: PRINT i: NEXT\nEND SUB\nSUB t\n    i = 3\nEND SUB\ns i\n";
+        assert_eq!(
+            (
+                "i = 10\nSUB s(n)\n    FOR idx = 1 TO n: PRINT idx: NEXT\nEND SUB\nSUB t\n    i = 3\nEND SUB\ns i\n"
+                    .to_owned(),
+                2
+            ),
+            do_rename(input, "s.i", "idx").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_case_only() {
+        assert_eq!(
+            ("Foo = 1: PRINT Foo\n".to_owned(), 2),
+            do_rename("foo = 1: PRINT FOO\n", "foo", "Foo").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_refuses_collisions() {
+        check_error(
+            "a = 1: b = 2\n",
+            "a",
+            "b",
+            "Cannot rename to 'b' because it is already in use",
+        );
+        check_error(
+            "a = 1\nSUB s\n    b = 2\nEND SUB\n",
+            "s",
+            "b",
+            "Cannot rename to 'b' because it is already in use",
+        );
+        check_error(
+            "SUB s(a)\n    b = a\nEND SUB\n",
+            "s.a",
+            "B",
+            "Cannot rename to 'B' because it is already in use",
+        );
+        check_error(
+            "DIM SHARED g\nSUB s(a)\n    PRINT a\nEND SUB\n",
+            "s.a",
+            "g",
+            "Cannot rename to 'g' because it is already in use",
+        );
+        check_error("a = 1\n", "a", "len", "Cannot rename to 'len' because it is a built-in name");
+    }
+
+    #[test]
+    fn test_rename_allows_unrelated_scopes() {
+        assert_eq!(
+            ("b = 1\nSUB s\n    b = 2\nEND SUB\n".to_owned(), 1),
+            do_rename("a = 1\nSUB s\n    b = 2\nEND SUB\n", "a", "b").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_refuses_keywords() {
+        check_error("a = 1\n", "a", "while", "Cannot rename to 'while' because it is a keyword");
+        check_error("a = 1\n", "a", "TRUE", "Cannot rename to 'TRUE' because it is a keyword");
+    }
+
+    #[test]
+    fn test_rename_errors() {
+        check_error("a = 1\n", "print", "b", "Cannot rename built-in 'print'");
+        check_error("a = 1\n", "a$", "b", "Invalid symbol name 'a$'");
+        check_error("a = 1\n", "a", "b c", "Invalid symbol name 'b c'");
+        check_error("a = 1\n", "a", "", "Invalid symbol name ''");
+        check_error("a = 1\n", "a", "1b", "Invalid symbol name '1b'");
+        check_error("a = 1\n", "b", "c", "Symbol 'b' not found");
+        check_error("a = 1\n", "s.a", "c", "Unknown FUNCTION or SUB 's'");
+        check_error("SUB s\nEND SUB\na = 1\n", "s.a", "c", "Symbol 's.a' not found");
+        check_error("a = .3\n", "a", "b", "1:5: Unknown character: .");
+    }
+}
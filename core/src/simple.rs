@@ -0,0 +1,740 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Callables backed by Rust closures.
+//!
+//! Implementing the `Callable` trait gives full control over how arguments are compiled and
+//! consumed, but requires a fair amount of boilerplate for callables that only take a fixed list
+//! of typed values.  `SimpleCallable` derives the syntax definitions, the argument handling and
+//! the return type from a typed signature and delegates the actual work to a closure:
+//!
+//! ```
+//! use endbasic_core::ast::ExprType;
+//! use endbasic_core::simple::{SimpleCallable, SimpleError};
+//! use std::convert::TryFrom;
+//!
+//! let repeat = SimpleCallable::function("REPEAT")
+//!     .with_category("Demonstration")
+//!     .with_description("Repeats a string a given number of times.")
+//!     .arg("s", ExprType::Text)
+//!     .arg("n", ExprType::Integer)
+//!     .handler(|args, _machine| {
+//!         match usize::try_from(args.integer(1)) {
+//!             Ok(n) => Ok(args.text(0).repeat(n)),
+//!             Err(_) => Err(SimpleError::Argument(1, "n% cannot be negative".to_owned())),
+//!         }
+//!     });
+//! ```
+
+use crate::ast::{ArgSep, ExprType, Value};
+use crate::compiler::{
+    ArgSepSyntax, OptionalValueSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredValueSyntax,
+    SingularArgSyntax,
+};
+use crate::exec::{Error, Machine, Result, Scope};
+use crate::reader::LineCol;
+use crate::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use crate::value;
+use async_trait::async_trait;
+use std::borrow::Cow;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::rc::Rc;
+
+/// Tag pushed by the compiler when an optional argument is missing.
+const MISSING: i32 = 0;
+
+/// Tag pushed by the compiler when an optional argument is present.
+const PRESENT: i32 = 1;
+
+/// Errors returned by the handlers of simple callables.
+///
+/// Handlers do not have access to the position of the call, so these errors are annotated with
+/// the right position once the handler returns.
+#[derive(Debug)]
+pub enum SimpleError {
+    /// The argument at the given index, counting repeated arguments after all singular ones, has
+    /// an invalid value.  Reported at the position of the argument.
+    Argument(usize, String),
+
+    /// The callable failed to compute its result.  Reported at the position of the call.
+    Eval(String),
+
+    /// Any other error not representable by other values.  Reported at the position of the call.
+    Internal(String),
+
+    /// I/O error.  Reported at the position of the call.
+    Io(io::Error),
+
+    /// An error that already carries a position, such as one returned by the machine.
+    Exec(Error),
+}
+
+impl From<Error> for SimpleError {
+    fn from(e: Error) -> Self {
+        Self::Exec(e)
+    }
+}
+
+impl From<io::Error> for SimpleError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<value::Error> for SimpleError {
+    fn from(e: value::Error) -> Self {
+        Self::Eval(e.message)
+    }
+}
+
+impl SimpleError {
+    /// Annotates this error with the positions of the call described by `args`.
+    fn into_error(self, args: &SimpleArgs) -> Error {
+        match self {
+            Self::Argument(i, message) => Error::SyntaxError(args.arg_pos(i), message),
+            Self::Eval(message) => Error::EvalError(args.pos, message),
+            Self::Internal(message) => Error::InternalError(args.pos, message),
+            Self::Io(e) => Error::IoError(args.pos, e),
+            Self::Exec(e) => e,
+        }
+    }
+}
+
+/// Result type for the handlers of simple callables.
+pub type SimpleResult<T> = std::result::Result<T, SimpleError>;
+
+/// Future returned by the asynchronous handlers of simple callables.
+pub type SimpleFuture<'a, T> = Pin<Box<dyn Future<Output = SimpleResult<T>> + 'a>>;
+
+/// Types that the handlers of simple callables can return.
+///
+/// The unit type is reserved for commands and the scalar types define the return type of functions.
+pub trait SimpleReturn {
+    /// The return type of the callable, or `None` for commands.
+    const TYPE: Option<ExprType>;
+
+    /// Converts the handler's result into the callable's return value, if any.
+    fn into_value(self) -> Option<Value>;
+}
+
+impl SimpleReturn for () {
+    const TYPE: Option<ExprType> = None;
+
+    fn into_value(self) -> Option<Value> {
+        None
+    }
+}
+
+impl SimpleReturn for bool {
+    const TYPE: Option<ExprType> = Some(ExprType::Boolean);
+
+    fn into_value(self) -> Option<Value> {
+        Some(Value::Boolean(self))
+    }
+}
+
+impl SimpleReturn for f64 {
+    const TYPE: Option<ExprType> = Some(ExprType::Double);
+
+    fn into_value(self) -> Option<Value> {
+        Some(Value::Double(self))
+    }
+}
+
+impl SimpleReturn for i32 {
+    const TYPE: Option<ExprType> = Some(ExprType::Integer);
+
+    fn into_value(self) -> Option<Value> {
+        Some(Value::Integer(self))
+    }
+}
+
+impl SimpleReturn for String {
+    const TYPE: Option<ExprType> = Some(ExprType::Text);
+
+    fn into_value(self) -> Option<Value> {
+        Some(Value::Text(self))
+    }
+}
+
+/// Arguments passed to the handler of a simple callable.
+///
+/// Arguments are indexed in the order in which they were declared.  The typed accessors panic if
+/// the requested argument does not match its declaration, as the compiler guarantees that the
+/// values always match the signature.
+pub struct SimpleArgs {
+    /// Position of the call.
+    pos: LineCol,
+
+    /// Values and positions of the singular arguments, with `None` for missing optional arguments.
+    values: Vec<Option<(Value, LineCol)>>,
+
+    /// Values and positions of the repeated arguments.
+    repeated: Vec<(Value, LineCol)>,
+}
+
+impl SimpleArgs {
+    /// Returns the position of the call.
+    pub fn pos(&self) -> LineCol {
+        self.pos
+    }
+
+    /// Returns the position of the argument at index `i`, or the position of the call if the
+    /// argument is missing.
+    pub fn arg_pos(&self, i: usize) -> LineCol {
+        let value = match i.checked_sub(self.values.len()) {
+            None => self.values[i].as_ref(),
+            Some(i) => self.repeated.get(i),
+        };
+        value.map(|(_value, pos)| *pos).unwrap_or(self.pos)
+    }
+
+    /// Returns the value of the singular argument at index `i`, or `None` if it is an optional
+    /// argument that was not provided.
+    pub fn get(&self, i: usize) -> Option<&Value> {
+        self.values[i].as_ref().map(|(value, _pos)| value)
+    }
+
+    /// Returns the value of the required argument at index `i`.
+    fn required(&self, i: usize) -> &Value {
+        self.get(i).expect("Argument is optional and was not provided")
+    }
+
+    /// Returns the boolean value of the required argument at index `i`.
+    pub fn boolean(&self, i: usize) -> bool {
+        match self.required(i) {
+            Value::Boolean(b) => *b,
+            _ => panic!("Argument is not a boolean"),
+        }
+    }
+
+    /// Returns the double value of the required argument at index `i`.
+    pub fn double(&self, i: usize) -> f64 {
+        match self.required(i) {
+            Value::Double(d) => *d,
+            _ => panic!("Argument is not a double"),
+        }
+    }
+
+    /// Returns the integer value of the required argument at index `i`.
+    pub fn integer(&self, i: usize) -> i32 {
+        match self.required(i) {
+            Value::Integer(i) => *i,
+            _ => panic!("Argument is not an integer"),
+        }
+    }
+
+    /// Returns the string value of the required argument at index `i`.
+    pub fn text(&self, i: usize) -> &str {
+        match self.required(i) {
+            Value::Text(s) => s,
+            _ => panic!("Argument is not a string"),
+        }
+    }
+
+    /// Returns the values of the repeated arguments.
+    pub fn repeated(&self) -> impl Iterator<Item = &Value> {
+        self.repeated.iter().map(|(value, _pos)| value)
+    }
+}
+
+/// Type of the handlers that compute their result without suspending execution.
+type SyncHandler = Box<dyn Fn(&SimpleArgs, &mut Machine) -> SimpleResult<Option<Value>>>;
+
+/// Type of the handlers that can suspend execution.
+type AsyncHandler =
+    Box<dyn for<'a> Fn(&'a SimpleArgs, &'a mut Machine) -> SimpleFuture<'a, Option<Value>>>;
+
+/// Forces the type of the closure `f` to be an `AsyncHandler` so that the compiler can infer the
+/// lifetimes of its arguments.
+fn async_handler_fn<F>(f: F) -> F
+where
+    F: for<'a> Fn(&'a SimpleArgs, &'a mut Machine) -> SimpleFuture<'a, Option<Value>>,
+{
+    f
+}
+
+/// The closure that implements a simple callable.
+enum Handler {
+    Sync(SyncHandler),
+    Async(AsyncHandler),
+}
+
+/// Builder pattern for a simple callable.
+pub struct SimpleCallableBuilder {
+    name: &'static str,
+    is_function: bool,
+    category: Option<&'static str>,
+    description: Option<&'static str>,
+    required: Vec<(&'static str, ExprType)>,
+    optional: Vec<(&'static str, ExprType)>,
+    repeated: Option<(&'static str, ExprType, bool)>,
+}
+
+impl SimpleCallableBuilder {
+    /// Sets the category for this callable.  See `CallableMetadataBuilder::with_category`.
+    pub fn with_category(mut self, category: &'static str) -> Self {
+        self.category = Some(category);
+        self
+    }
+
+    /// Sets the description for this callable.  See `CallableMetadataBuilder::with_description`.
+    pub fn with_description(mut self, description: &'static str) -> Self {
+        self.description = Some(description);
+        self
+    }
+
+    /// Adds a required argument called `name` of type `vtype`.
+    ///
+    /// Required arguments must come before any optional or repeated arguments.
+    pub fn arg(mut self, name: &'static str, vtype: ExprType) -> Self {
+        assert!(self.optional.is_empty(), "Required arguments must precede optional arguments");
+        assert!(self.repeated.is_none(), "Required arguments must precede repeated arguments");
+        self.required.push((name, vtype));
+        self
+    }
+
+    /// Adds an optional argument called `name` of type `vtype`.
+    ///
+    /// Optional arguments are only supported in commands and cannot be combined with repeated
+    /// arguments.  Callers can omit any number of trailing optional arguments or leave any of
+    /// them empty.
+    pub fn optional_arg(mut self, name: &'static str, vtype: ExprType) -> Self {
+        assert!(!self.is_function, "Functions cannot have optional arguments");
+        assert!(self.repeated.is_none(), "Optional and repeated arguments cannot be combined");
+        self.optional.push((name, vtype));
+        self
+    }
+
+    /// Adds a repeated argument called `name` of type `vtype` after all singular arguments,
+    /// which must be given at least once if `require_one` is true.
+    pub fn repeated_arg(mut self, name: &'static str, vtype: ExprType, require_one: bool) -> Self {
+        assert!(self.optional.is_empty(), "Optional and repeated arguments cannot be combined");
+        assert!(self.repeated.is_none(), "Only one repeated argument is allowed");
+        self.repeated = Some((name, vtype, require_one));
+        self
+    }
+
+    /// Computes the syntax definitions for the declared arguments, with one definition for every
+    /// number of trailing optional arguments that can be provided.
+    fn syntaxes(&self) -> Vec<(Vec<SingularArgSyntax>, Option<RepeatedSyntax>)> {
+        let repeated = self.repeated.map(|(name, vtype, require_one)| RepeatedSyntax {
+            name: Cow::Borrowed(name),
+            type_syn: RepeatedTypeSyntax::TypedValue(vtype),
+            sep: ArgSepSyntax::Exactly(ArgSep::Long),
+            require_one,
+            allow_missing: false,
+        });
+
+        let mut syntaxes = vec![];
+        for noptional in 0..=self.optional.len() {
+            let nargs = self.required.len() + noptional;
+            let sep = |i: usize| {
+                if i + 1 < nargs || repeated.is_some() {
+                    ArgSepSyntax::Exactly(ArgSep::Long)
+                } else {
+                    ArgSepSyntax::End
+                }
+            };
+
+            let mut singular = vec![];
+            for (i, (name, vtype)) in self.required.iter().enumerate() {
+                singular.push(SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name: Cow::Borrowed(name), vtype: *vtype },
+                    sep(i),
+                ));
+            }
+            for (i, (name, vtype)) in self.optional[..noptional].iter().enumerate() {
+                singular.push(SingularArgSyntax::OptionalValue(
+                    OptionalValueSyntax {
+                        name: Cow::Borrowed(name),
+                        vtype: *vtype,
+                        missing_value: MISSING,
+                        present_value: PRESENT,
+                    },
+                    sep(self.required.len() + i),
+                ));
+            }
+            syntaxes.push((singular, repeated.clone()));
+        }
+        syntaxes
+    }
+
+    /// Generates the final callable with `handler` as its implementation.
+    fn build<R: SimpleReturn>(self, handler: Handler) -> Rc<SimpleCallable> {
+        if self.is_function {
+            assert!(R::TYPE.is_some(), "Function handlers must return a value");
+        } else {
+            assert!(R::TYPE.is_none(), "Command handlers cannot return a value");
+        }
+
+        let mut builder =
+            CallableMetadataBuilder::new(self.name).with_dynamic_syntax(self.syntaxes());
+        if let Some(return_type) = R::TYPE {
+            builder = builder.with_return_type(return_type);
+        }
+        if let Some(category) = self.category {
+            builder = builder.with_category(category);
+        }
+        if let Some(description) = self.description {
+            builder = builder.with_description(description);
+        }
+
+        Rc::from(SimpleCallable {
+            metadata: builder.build(),
+            required: self.required.iter().map(|(_name, vtype)| *vtype).collect(),
+            optional: self.optional.iter().map(|(_name, vtype)| *vtype).collect(),
+            repeated: self.repeated.map(|(_name, vtype, _require_one)| vtype),
+            handler,
+        })
+    }
+
+    /// Generates the final callable, which runs the `handler` closure when invoked.
+    ///
+    /// The return type of the handler determines the return type of functions.
+    pub fn handler<R, F>(self, handler: F) -> Rc<SimpleCallable>
+    where
+        R: SimpleReturn,
+        F: Fn(&SimpleArgs, &mut Machine) -> SimpleResult<R> + 'static,
+    {
+        let handler = Handler::Sync(Box::from(move |args: &SimpleArgs, machine: &mut Machine| {
+            handler(args, machine).map(R::into_value)
+        }));
+        self.build::<R>(handler)
+    }
+
+    /// Generates the final callable, which awaits the future returned by the `handler` closure
+    /// when invoked.
+    ///
+    /// The return type of the handler determines the return type of functions.
+    pub fn async_handler<R, F>(self, handler: F) -> Rc<SimpleCallable>
+    where
+        R: SimpleReturn + 'static,
+        F: for<'a> Fn(&'a SimpleArgs, &'a mut Machine) -> SimpleFuture<'a, R> + 'static,
+    {
+        let handler = Handler::Async(Box::from(async_handler_fn(move |args, machine| {
+            let future = handler(args, machine);
+            Box::pin(async move { future.await.map(R::into_value) })
+        })));
+        self.build::<R>(handler)
+    }
+}
+
+/// A callable whose implementation is provided by a closure.
+pub struct SimpleCallable {
+    metadata: CallableMetadata,
+    required: Vec<ExprType>,
+    optional: Vec<ExprType>,
+    repeated: Option<ExprType>,
+    handler: Handler,
+}
+
+impl SimpleCallable {
+    /// Starts building a command called `name`.
+    pub fn command(name: &'static str) -> SimpleCallableBuilder {
+        SimpleCallable::builder(name, false)
+    }
+
+    /// Starts building a function called `name`.
+    pub fn function(name: &'static str) -> SimpleCallableBuilder {
+        SimpleCallable::builder(name, true)
+    }
+
+    /// Starts building a callable called `name`.
+    fn builder(name: &'static str, is_function: bool) -> SimpleCallableBuilder {
+        SimpleCallableBuilder {
+            name,
+            is_function,
+            category: None,
+            description: None,
+            required: vec![],
+            optional: vec![],
+            repeated: None,
+        }
+    }
+}
+
+/// Pops a value of type `vtype` from the `scope` along with its position.
+fn pop_value(scope: &mut Scope<'_>, vtype: ExprType) -> (Value, LineCol) {
+    match vtype {
+        ExprType::Boolean => {
+            let (b, pos) = scope.pop_boolean_with_pos();
+            (Value::Boolean(b), pos)
+        }
+        ExprType::Double => {
+            let (d, pos) = scope.pop_double_with_pos();
+            (Value::Double(d), pos)
+        }
+        ExprType::Integer => {
+            let (i, pos) = scope.pop_integer_with_pos();
+            (Value::Integer(i), pos)
+        }
+        ExprType::Text => {
+            let (s, pos) = scope.pop_string_with_pos();
+            (Value::Text(s), pos)
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SimpleCallable {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        let mut args = SimpleArgs {
+            pos: scope.pos(),
+            values: Vec::with_capacity(self.required.len() + self.optional.len()),
+            repeated: vec![],
+        };
+        for vtype in &self.required {
+            args.values.push(Some(pop_value(&mut scope, *vtype)));
+        }
+        for vtype in &self.optional {
+            if scope.nargs() > 0 && scope.pop_integer() == PRESENT {
+                args.values.push(Some(pop_value(&mut scope, *vtype)));
+            } else {
+                args.values.push(None);
+            }
+        }
+        if let Some(vtype) = self.repeated {
+            while scope.nargs() > 0 {
+                args.repeated.push(pop_value(&mut scope, vtype));
+            }
+        }
+        debug_assert_eq!(0, scope.nargs());
+
+        let result = match &self.handler {
+            Handler::Sync(handler) => handler(&args, machine),
+            Handler::Async(handler) => handler(&args, machine).await,
+        };
+        match result {
+            Ok(Some(value)) => scope.return_any(value),
+            Ok(None) => Ok(()),
+            Err(e) => Err(e.into_error(&args)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exec::StopReason;
+    use crate::testutils::*;
+    use std::cell::RefCell;
+
+    /// Creates a machine with the `OUT` command and the given `callables`.
+    fn machine_with(
+        callables: Vec<Rc<dyn Callable>>,
+        captured_out: Rc<RefCell<Vec<String>>>,
+    ) -> Machine {
+        let mut machine = Machine::default();
+        machine.add_callable(OutCommand::new(captured_out));
+        for callable in callables {
+            machine.add_callable(callable);
+        }
+        machine
+    }
+
+    /// Runs `code` in `machine` and returns the formatted error, if any.
+    async fn run(machine: &mut Machine, code: &str) -> std::result::Result<(), String> {
+        match machine.exec(&mut code.as_bytes()).await {
+            Ok(stop_reason) => {
+                assert_eq!(StopReason::Eof, stop_reason);
+                Ok(())
+            }
+            Err(e) => Err(format!("{}", e)),
+        }
+    }
+
+    /// Creates a `GREET` command with one required and two optional arguments that records the
+    /// values it receives in `calls`.
+    fn greet_command(calls: Rc<RefCell<Vec<String>>>) -> Rc<SimpleCallable> {
+        SimpleCallable::command("GREET")
+            .with_category("Testing")
+            .with_description("Greets someone.")
+            .arg("name", ExprType::Text)
+            .optional_arg("count", ExprType::Integer)
+            .optional_arg("loud", ExprType::Boolean)
+            .handler(move |args, _machine| {
+                let count = args.get(1).map(|v| format!("{:?}", v)).unwrap_or_default();
+                let loud = args.get(2).map(|v| format!("{:?}", v)).unwrap_or_default();
+                calls.borrow_mut().push(format!("{}|{}|{}", args.text(0), count, loud));
+                Ok(())
+            })
+    }
+
+    #[test]
+    fn test_simple_callable_metadata() {
+        let calls = Rc::from(RefCell::from(vec![]));
+        let greet = greet_command(calls);
+        assert_eq!("GREET", greet.metadata().name());
+        assert_eq!(None, greet.metadata().return_type());
+        assert_eq!(
+            "<name$> | <name$, [count%]> | <name$, [count%], [loud?]>",
+            greet.metadata().syntax()
+        );
+
+        let sum = SimpleCallable::function("SUM")
+            .with_category("Testing")
+            .with_description("Adds numbers.")
+            .arg("first", ExprType::Double)
+            .repeated_arg("rest", ExprType::Double, false)
+            .handler(|args, _machine| {
+                Ok(args.repeated().fold(args.double(0), |acc, v| match v {
+                    Value::Double(d) => acc + d,
+                    _ => unreachable!(),
+                }))
+            });
+        assert_eq!(Some(ExprType::Double), sum.metadata().return_type());
+        assert_eq!("first#[, rest1#, .., restN#]", sum.metadata().syntax());
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_command_optional_args() {
+        let calls = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with(vec![greet_command(calls.clone())], Rc::default());
+
+        run(&mut machine, r#"GREET "a""#).await.unwrap();
+        run(&mut machine, r#"GREET "b", 3"#).await.unwrap();
+        run(&mut machine, r#"GREET "c", , TRUE"#).await.unwrap();
+        run(&mut machine, r#"GREET "d", 2.6, FALSE"#).await.unwrap();
+        assert_eq!(
+            &["a||", "b|Integer(3)|", "c||Boolean(true)", "d|Integer(3)|Boolean(false)"],
+            calls.borrow().as_slice()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_function_repeated_args() {
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let concat = SimpleCallable::function("CONCAT")
+            .with_category("Testing")
+            .with_description("Concatenates strings.")
+            .repeated_arg("s", ExprType::Text, true)
+            .handler(|args, _machine| {
+                Ok(args
+                    .repeated()
+                    .map(|v| match v {
+                        Value::Text(s) => s.as_str(),
+                        _ => unreachable!(),
+                    })
+                    .collect::<Vec<&str>>()
+                    .join("+"))
+            });
+        let mut machine = machine_with(vec![concat], captured_out.clone());
+
+        run(&mut machine, r#"OUT CONCAT("x"): OUT CONCAT("a", "b", "c")"#).await.unwrap();
+        assert_eq!(&["x", "a+b+c"], captured_out.borrow().as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_syntax_errors() {
+        let calls = Rc::from(RefCell::from(vec![]));
+        let twice = SimpleCallable::function("TWICE")
+            .with_category("Testing")
+            .with_description("Doubles a number.")
+            .arg("n", ExprType::Integer)
+            .handler(|args, _machine| Ok(args.integer(0) * 2));
+        let mut machine = machine_with(vec![greet_command(calls.clone()), twice], Rc::default());
+
+        assert_eq!(
+            "1:1: GREET expected <name$> | <name$, [count%]> | <name$, [count%], [loud?]>",
+            run(&mut machine, "GREET").await.unwrap_err()
+        );
+        assert_eq!(
+            "1:1: GREET expected <name$> | <name$, [count%]> | <name$, [count%], [loud?]>",
+            run(&mut machine, r#"GREET "a", 1, TRUE, 4"#).await.unwrap_err()
+        );
+        assert_eq!(
+            "1:7: expected STRING but found INTEGER",
+            run(&mut machine, "GREET 1").await.unwrap_err()
+        );
+        assert_eq!("1:5: TWICE expected n%", run(&mut machine, "a = TWICE()").await.unwrap_err());
+        assert!(calls.borrow().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_errors_have_positions() {
+        let check = SimpleCallable::command("CHECK")
+            .with_category("Testing")
+            .with_description("Fails in different ways.")
+            .arg("kind", ExprType::Integer)
+            .repeated_arg("extra", ExprType::Integer, false)
+            .handler(|args, _machine| match args.integer(0) {
+                1 => Err(SimpleError::Argument(0, "Bad kind".to_owned())),
+                2 => Err(SimpleError::Argument(2, "Bad extra".to_owned())),
+                3 => Err(SimpleError::Eval("Failed".to_owned())),
+                4 => Err(io::Error::new(io::ErrorKind::NotFound, "Missing").into()),
+                _ => Ok(()),
+            });
+        let mut machine = machine_with(vec![check], Rc::default());
+
+        assert_eq!("1:9: Bad kind", run(&mut machine, "CHECK   1").await.unwrap_err());
+        assert_eq!("1:16: Bad extra", run(&mut machine, "CHECK 2, 5,    6").await.unwrap_err());
+        assert_eq!("1:3: Failed", run(&mut machine, "  CHECK 3").await.unwrap_err());
+        assert_eq!("2:1: Missing", run(&mut machine, "\nCHECK 4").await.unwrap_err());
+        run(&mut machine, "CHECK 5").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_errors_are_catchable() {
+        let fail = SimpleCallable::command("FAIL")
+            .with_category("Testing")
+            .with_description("Always fails.")
+            .handler(|_args, _machine| Err::<(), _>(SimpleError::Eval("Oops".to_owned())));
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with(vec![fail, LastErrorFunction::new()], captured_out.clone());
+
+        run(&mut machine, "ON ERROR RESUME NEXT: FAIL: OUT LAST_ERROR").await.unwrap();
+        assert_eq!(&["1:23: Oops"], captured_out.borrow().as_slice());
+    }
+
+    #[tokio::test]
+    async fn test_simple_callable_async_handler_accesses_machine() {
+        let count_vars = SimpleCallable::function("COUNT_VARS")
+            .with_category("Testing")
+            .with_description("Counts variables.")
+            .async_handler(|_args, machine| {
+                Box::pin(async move {
+                    let usage = machine.get_symbols().memory_usage();
+                    Ok(usage.variables as i32)
+                })
+            });
+        let captured_out = Rc::from(RefCell::from(vec![]));
+        let mut machine = machine_with(vec![count_vars], captured_out.clone());
+
+        run(&mut machine, "a = 1: b = 2: OUT COUNT_VARS").await.unwrap();
+        assert_eq!(&["2"], captured_out.borrow().as_slice());
+    }
+
+    #[test]
+    #[should_panic(expected = "Functions cannot have optional arguments")]
+    fn test_simple_callable_function_with_optional_arg() {
+        SimpleCallable::function("F").optional_arg("x", ExprType::Integer);
+    }
+
+    #[test]
+    #[should_panic(expected = "Command handlers cannot return a value")]
+    fn test_simple_callable_command_returns_value() {
+        SimpleCallable::command("C")
+            .with_category("Testing")
+            .with_description("Returns a value.")
+            .handler(|_args, _machine| Ok(3));
+    }
+}
@@ -0,0 +1,453 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Snapshots of the data held by a machine.
+//!
+//! A snapshot captures the variables and arrays of a machine along with the state of its
+//! clearable objects, such as the position of the `DATA` reader or the state of the random number
+//! generator, so that the machine can be brought back to the same point later on.  Callables and
+//! any state held outside of the interpreter, like the console or the drives, are not part of
+//! snapshots.
+//!
+//! Snapshots are serialized with a versioned binary encoding so that they can be persisted.  The
+//! encoding is little-endian and starts with the `MAGIC` bytes followed by the `VERSION` number.
+
+use crate::ast::{ExprType, Value};
+use crate::syms::{Array, Symbol, SymbolKey};
+use std::convert::TryFrom;
+use std::io;
+
+/// Bytes that start every serialized snapshot.
+const MAGIC: &[u8] = b"EBSNAP";
+
+/// Version of the encoding of serialized snapshots.  Must be bumped whenever the encoding changes.
+pub const VERSION: u32 = 1;
+
+/// Creates an error that describes a malformed snapshot.
+fn invalid<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+/// A saved symbol.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum SavedSymbol {
+    /// An array and its contents.
+    Array(Array),
+
+    /// A scalar variable and its value.
+    Variable(Value),
+}
+
+impl SavedSymbol {
+    /// Creates a saved symbol from a live `symbol`, which must be user-defined.
+    pub(crate) fn from_symbol(symbol: &Symbol) -> Self {
+        match symbol {
+            Symbol::Array(array) => SavedSymbol::Array(array.clone()),
+            Symbol::Callable(_) => unreachable!("Callables are not part of snapshots"),
+            Symbol::Variable(value) => SavedSymbol::Variable(value.clone()),
+        }
+    }
+
+    /// Converts the saved symbol into a live symbol.
+    pub(crate) fn into_symbol(self) -> Symbol {
+        match self {
+            SavedSymbol::Array(array) => Symbol::Array(array),
+            SavedSymbol::Variable(value) => Symbol::Variable(value),
+        }
+    }
+}
+
+/// The saved state of a machine.
+///
+/// Obtain one with `Machine::snapshot` and apply it with `Machine::restore`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Snapshot {
+    /// Symbols that were defined in the globals map, sorted by name.
+    pub(crate) globals: Vec<(SymbolKey, SavedSymbol)>,
+
+    /// Symbols that were defined in the outermost scope, sorted by name.
+    pub(crate) locals: Vec<(SymbolKey, SavedSymbol)>,
+
+    /// Whether EMPTY values were enabled.
+    pub(crate) empty_values: bool,
+
+    /// Opaque states of the clearable objects, keyed by their state keys.
+    pub(crate) states: Vec<(String, Vec<u8>)>,
+}
+
+impl Snapshot {
+    /// Serializes the snapshot into a sequence of bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.bytes.extend_from_slice(MAGIC);
+        writer.u32(VERSION);
+        writer.bool(self.empty_values);
+        writer.symbols(&self.globals);
+        writer.symbols(&self.locals);
+        writer.len(self.states.len());
+        for (key, state) in &self.states {
+            writer.str(key);
+            writer.len(state.len());
+            writer.bytes.extend_from_slice(state);
+        }
+        writer.bytes
+    }
+
+    /// Deserializes a snapshot previously serialized with `to_bytes`.
+    ///
+    /// Fails if the `bytes` are not a snapshot or if they were produced by an incompatible
+    /// version of the encoding.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len()).ok() != Some(MAGIC) {
+            return Err(invalid("Not a machine snapshot"));
+        }
+        let version = reader.u32()?;
+        if version != VERSION {
+            return Err(invalid(format!(
+                "Incompatible snapshot version {}; expected {}",
+                version, VERSION
+            )));
+        }
+
+        let empty_values = reader.bool()?;
+        let globals = reader.symbols()?;
+        let locals = reader.symbols()?;
+        let nstates = reader.len()?;
+        let mut states = Vec::with_capacity(nstates.min(reader.bytes.len()));
+        for _ in 0..nstates {
+            let key = reader.str()?;
+            let len = reader.len()?;
+            states.push((key, reader.take(len)?.to_vec()));
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(invalid("Trailing bytes after snapshot"));
+        }
+        Ok(Snapshot { globals, locals, empty_values, states })
+    }
+}
+
+/// Encodes `etype` as a single byte.
+fn exprtype_to_u8(etype: ExprType) -> u8 {
+    match etype {
+        ExprType::Boolean => 0,
+        ExprType::Double => 1,
+        ExprType::Integer => 2,
+        ExprType::Text => 3,
+    }
+}
+
+/// Decodes a type previously encoded with `exprtype_to_u8`.
+fn u8_to_exprtype(b: u8) -> io::Result<ExprType> {
+    match b {
+        0 => Ok(ExprType::Boolean),
+        1 => Ok(ExprType::Double),
+        2 => Ok(ExprType::Integer),
+        3 => Ok(ExprType::Text),
+        _ => Err(invalid(format!("Invalid type tag {}", b))),
+    }
+}
+
+/// Serializer of the primitive types that compose a snapshot.
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, b: u8) {
+        self.bytes.push(b);
+    }
+
+    fn bool(&mut self, b: bool) {
+        self.u8(u8::from(b));
+    }
+
+    fn u32(&mut self, n: u32) {
+        self.bytes.extend_from_slice(&n.to_le_bytes());
+    }
+
+    fn len(&mut self, n: usize) {
+        self.u32(u32::try_from(n).expect("Snapshots cannot hold more than 4G items"));
+    }
+
+    fn str(&mut self, s: &str) {
+        self.len(s.len());
+        self.bytes.extend_from_slice(s.as_bytes());
+    }
+
+    fn value(&mut self, value: &Value) {
+        match value {
+            Value::Boolean(b) => {
+                self.u8(0);
+                self.bool(*b);
+            }
+            Value::Double(d) => {
+                self.u8(1);
+                self.bytes.extend_from_slice(&d.to_bits().to_le_bytes());
+            }
+            Value::Integer(i) => {
+                self.u8(2);
+                self.bytes.extend_from_slice(&i.to_le_bytes());
+            }
+            Value::Text(s) => {
+                self.u8(3);
+                self.str(s);
+            }
+            Value::Empty(etype) => {
+                self.u8(4);
+                self.u8(exprtype_to_u8(*etype));
+            }
+            Value::VarRef(..) => unreachable!("References cannot be stored in variables"),
+        }
+    }
+
+    fn array(&mut self, array: &Array) {
+        self.u8(exprtype_to_u8(array.subtype()));
+        self.len(array.dimensions().len());
+        for dim in array.dimensions() {
+            self.len(*dim);
+        }
+        if let Some(values) = array.as_booleans() {
+            values.iter().for_each(|b| self.bool(*b));
+        } else if let Some(values) = array.as_doubles() {
+            values.iter().for_each(|d| self.bytes.extend_from_slice(&d.to_bits().to_le_bytes()));
+        } else if let Some(values) = array.as_integers() {
+            values.iter().for_each(|i| self.bytes.extend_from_slice(&i.to_le_bytes()));
+        } else if let Some(values) = array.as_texts() {
+            values.iter().for_each(|s| self.str(s));
+        }
+    }
+
+    fn symbols(&mut self, symbols: &[(SymbolKey, SavedSymbol)]) {
+        self.len(symbols.len());
+        for (key, symbol) in symbols {
+            self.str(key.as_str());
+            match symbol {
+                SavedSymbol::Array(array) => {
+                    self.u8(0);
+                    self.array(array);
+                }
+                SavedSymbol::Variable(value) => {
+                    self.u8(1);
+                    self.value(value);
+                }
+            }
+        }
+    }
+}
+
+/// Deserializer of the primitive types that compose a snapshot.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() < n {
+            return Err(invalid("Truncated snapshot"));
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn bool(&mut self) -> io::Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            b => Err(invalid(format!("Invalid boolean {}", b))),
+        }
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        Ok(self.u32()? as i32)
+    }
+
+    fn f64(&mut self) -> io::Result<f64> {
+        let bytes = self.take(8)?;
+        let mut raw = [0; 8];
+        raw.copy_from_slice(bytes);
+        Ok(f64::from_bits(u64::from_le_bytes(raw)))
+    }
+
+    fn len(&mut self) -> io::Result<usize> {
+        Ok(self.u32()? as usize)
+    }
+
+    fn str(&mut self) -> io::Result<String> {
+        let len = self.len()?;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| invalid("Invalid UTF-8 string"))
+    }
+
+    fn value(&mut self) -> io::Result<Value> {
+        match self.u8()? {
+            0 => Ok(Value::Boolean(self.bool()?)),
+            1 => Ok(Value::Double(self.f64()?)),
+            2 => Ok(Value::Integer(self.i32()?)),
+            3 => Ok(Value::Text(self.str()?)),
+            4 => Ok(Value::Empty(u8_to_exprtype(self.u8()?)?)),
+            b => Err(invalid(format!("Invalid value tag {}", b))),
+        }
+    }
+
+    fn array(&mut self) -> io::Result<Array> {
+        let subtype = u8_to_exprtype(self.u8()?)?;
+        let ndims = self.len()?;
+        let mut dimensions = Vec::with_capacity(ndims.min(self.bytes.len()));
+        let mut nelements = 1usize;
+        for _ in 0..ndims {
+            let dim = self.len()?;
+            nelements = nelements.checked_mul(dim).ok_or_else(|| invalid("Array too large"))?;
+            dimensions.push(dim);
+        }
+        // Every element takes at least one byte, so this rejects bogus dimensions before
+        // allocating the array.
+        if nelements > self.bytes.len() {
+            return Err(invalid("Truncated snapshot"));
+        }
+
+        let mut array = Array::new(subtype, dimensions);
+        if let Some(values) = array.as_booleans_mut() {
+            for b in values {
+                *b = self.bool()?;
+            }
+        } else if let Some(values) = array.as_doubles_mut() {
+            for d in values {
+                *d = self.f64()?;
+            }
+        } else if let Some(values) = array.as_integers_mut() {
+            for i in values {
+                *i = self.i32()?;
+            }
+        } else if let Some(values) = array.as_texts_mut() {
+            for s in values {
+                *s = self.str()?;
+            }
+        }
+        Ok(array)
+    }
+
+    fn symbols(&mut self) -> io::Result<Vec<(SymbolKey, SavedSymbol)>> {
+        let n = self.len()?;
+        let mut symbols = Vec::with_capacity(n.min(self.bytes.len()));
+        for _ in 0..n {
+            let key = SymbolKey::from(self.str()?);
+            let symbol = match self.u8()? {
+                0 => SavedSymbol::Array(self.array()?),
+                1 => SavedSymbol::Variable(self.value()?),
+                b => return Err(invalid(format!("Invalid symbol tag {}", b))),
+            };
+            symbols.push((key, symbol));
+        }
+        Ok(symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a snapshot that holds every type of value and array.
+    fn full_snapshot() -> Snapshot {
+        let mut booleans = Array::new(ExprType::Boolean, vec![2]);
+        booleans.as_booleans_mut().unwrap()[1] = true;
+        let mut doubles = Array::new(ExprType::Double, vec![2, 2]);
+        doubles.as_doubles_mut().unwrap()[3] = -0.125;
+        let mut integers = Array::new(ExprType::Integer, vec![3]);
+        integers.as_integers_mut().unwrap().copy_from_slice(&[i32::MIN, 0, i32::MAX]);
+        let mut texts = Array::new(ExprType::Text, vec![1, 2, 1]);
+        texts.as_texts_mut().unwrap()[0] = "héllo".to_owned();
+
+        Snapshot {
+            globals: vec![
+                (SymbolKey::from("B"), SavedSymbol::Array(booleans)),
+                (SymbolKey::from("S"), SavedSymbol::Variable(Value::Text("shared".to_owned()))),
+            ],
+            locals: vec![
+                (SymbolKey::from("A"), SavedSymbol::Variable(Value::Boolean(true))),
+                (SymbolKey::from("D"), SavedSymbol::Variable(Value::Double(f64::MAX))),
+                (SymbolKey::from("D2"), SavedSymbol::Array(doubles)),
+                (SymbolKey::from("E"), SavedSymbol::Variable(Value::Empty(ExprType::Integer))),
+                (SymbolKey::from("I"), SavedSymbol::Variable(Value::Integer(-5))),
+                (SymbolKey::from("I2"), SavedSymbol::Array(integers)),
+                (SymbolKey::from("T"), SavedSymbol::Variable(Value::Text("".to_owned()))),
+                (SymbolKey::from("T2"), SavedSymbol::Array(texts)),
+            ],
+            empty_values: true,
+            states: vec![("one".to_owned(), vec![1, 2, 3]), ("two".to_owned(), vec![])],
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = full_snapshot();
+        assert_eq!(snapshot, Snapshot::from_bytes(&snapshot.to_bytes()).unwrap());
+
+        let empty =
+            Snapshot { globals: vec![], locals: vec![], empty_values: false, states: vec![] };
+        assert_eq!(empty, Snapshot::from_bytes(&empty.to_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_snapshot_version_mismatch() {
+        let mut bytes = full_snapshot().to_bytes();
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(VERSION + 1).to_le_bytes());
+        let err = Snapshot::from_bytes(&bytes).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            format!("Incompatible snapshot version {}; expected {}", VERSION + 1, VERSION),
+            format!("{}", err)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_malformed() {
+        fn check(exp_error: &str, bytes: &[u8]) {
+            let err = Snapshot::from_bytes(bytes).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+            assert_eq!(exp_error, format!("{}", err));
+        }
+
+        let bytes = full_snapshot().to_bytes();
+        check("Not a machine snapshot", b"");
+        check("Not a machine snapshot", b"EBSNAQ\x01\x00\x00\x00");
+        check("Truncated snapshot", &bytes[..MAGIC.len() + 2]);
+        check("Truncated snapshot", &bytes[..bytes.len() - 1]);
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        check("Trailing bytes after snapshot", &trailing);
+
+        let mut bad_bool = bytes[..MAGIC.len() + 4].to_vec();
+        bad_bool.push(2);
+        check("Invalid boolean 2", &bad_bool);
+
+        let mut huge_array = bytes[..MAGIC.len() + 4].to_vec();
+        huge_array.extend_from_slice(&[0, 1, 0, 0, 0, 1, 0, 0, 0, b'A', 0, 2, 1, 0, 0, 0]);
+        huge_array.extend_from_slice(&u32::MAX.to_le_bytes());
+        check("Truncated snapshot", &huge_array);
+    }
+}
@@ -0,0 +1,315 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Metrics to summarize the size and complexity of a program.
+
+use crate::ast::{CallableSpan, Statement, StaticDecl};
+use crate::lexer::{Lexer, Token};
+use crate::parser::{self, Result};
+use std::collections::HashSet;
+
+/// Size and complexity metrics of a program.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ProgramStats {
+    /// Number of physical lines in the program.
+    pub lines: usize,
+
+    /// Number of physical lines that contain code.
+    pub code_lines: usize,
+
+    /// Number of physical lines that only contain comments.
+    pub comment_lines: usize,
+
+    /// Number of statements, including those nested within other statements.
+    pub statements: usize,
+
+    /// Number of distinct scalar variable names defined by the program, including parameters.
+    pub variables: usize,
+
+    /// Number of distinct array names defined by the program.
+    pub arrays: usize,
+
+    /// Number of `SUB` definitions.
+    pub subs: usize,
+
+    /// Number of `FUNCTION` definitions.
+    pub functions: usize,
+
+    /// Deepest nesting level of control flow statements, or 0 if there are none.
+    pub max_nesting: usize,
+
+    /// Name and length in lines of the longest `FUNCTION` or `SUB`, if any.
+    pub longest_callable: Option<(String, usize)>,
+}
+
+/// State to compute the metrics that derive from the program's AST.
+#[derive(Default)]
+struct Walker {
+    /// Metrics computed so far.
+    stats: ProgramStats,
+
+    /// Names of the scalar variables seen so far, in uppercase.
+    variables: HashSet<String>,
+
+    /// Names of the arrays seen so far, in uppercase.
+    arrays: HashSet<String>,
+}
+
+impl Walker {
+    /// Records the definition of the `span` callable.
+    fn visit_callable(&mut self, span: &CallableSpan, depth: usize) {
+        if span.name.ref_type().is_some() {
+            self.stats.functions += 1;
+        } else {
+            self.stats.subs += 1;
+        }
+
+        for param in &span.params {
+            self.variables.insert(param.vref.name().to_uppercase());
+        }
+
+        let length = span.end_pos.line - span.name_pos.line + 1;
+        match &self.stats.longest_callable {
+            Some((_, longest)) if *longest >= length => (),
+            _ => self.stats.longest_callable = Some((span.name.name().to_uppercase(), length)),
+        }
+
+        let name = span.name.name().to_uppercase();
+        self.walk(&span.body, depth, Some(&name));
+    }
+
+    /// Walks the `body` of a control flow statement found at nesting level `depth`.
+    fn nest(&mut self, body: &[Statement], depth: usize, callable: Option<&str>) {
+        self.stats.max_nesting = self.stats.max_nesting.max(depth + 1);
+        self.walk(body, depth + 1, callable);
+    }
+
+    /// Walks the `stmts` found at nesting level `depth` and within the `callable`, if any.
+    fn walk(&mut self, stmts: &[Statement], depth: usize, callable: Option<&str>) {
+        for stmt in stmts {
+            if !matches!(stmt, Statement::Label(_)) {
+                self.stats.statements += 1;
+            }
+
+            match stmt {
+                Statement::Assignment(span) => {
+                    // Assignments to the name of the enclosing function set its return value.
+                    let name = span.vref.name().to_uppercase();
+                    if callable != Some(name.as_str()) {
+                        self.variables.insert(name);
+                    }
+                }
+
+                Statement::Callable(span) => self.visit_callable(span, depth),
+
+                Statement::Dim(span) => {
+                    self.variables.insert(span.name.to_uppercase());
+                }
+
+                Statement::DimArray(span) => {
+                    self.arrays.insert(span.name.to_uppercase());
+                }
+
+                Statement::DimRecord(span) => {
+                    if span.dimensions.is_empty() {
+                        self.variables.insert(span.name.to_uppercase());
+                    } else {
+                        self.arrays.insert(span.name.to_uppercase());
+                    }
+                }
+
+                Statement::Do(span) => self.nest(&span.body, depth, callable),
+
+                Statement::For(span) => {
+                    self.variables.insert(span.iter.name().to_uppercase());
+                    self.nest(&span.body, depth, callable);
+                }
+
+                Statement::If(span) => {
+                    for branch in &span.branches {
+                        self.nest(&branch.body, depth, callable);
+                    }
+                }
+
+                Statement::Select(span) => {
+                    for case in &span.cases {
+                        self.nest(&case.body, depth, callable);
+                    }
+                }
+
+                Statement::Static(span) => match &span.decl {
+                    StaticDecl::Dim(span) => {
+                        self.variables.insert(span.name.to_uppercase());
+                    }
+                    StaticDecl::DimArray(span) => {
+                        self.arrays.insert(span.name.to_uppercase());
+                    }
+                    StaticDecl::Assignment(span) => {
+                        self.variables.insert(span.vref.name().to_uppercase());
+                    }
+                },
+
+                Statement::While(span) => self.nest(&span.body, depth, callable),
+
+                Statement::ArrayAssignment(_)
+                | Statement::Call(_)
+                | Statement::Data(_)
+                | Statement::End(_)
+                | Statement::ExitDo(_)
+                | Statement::Gosub(_)
+                | Statement::Goto(_)
+                | Statement::Label(_)
+                | Statement::MidAssignment(_)
+                | Statement::OnError(_)
+                | Statement::OnGoto(_)
+                | Statement::OnTimer(_)
+                | Statement::Resume(_)
+                | Statement::Return(_)
+                | Statement::Timer(_)
+                | Statement::Type(_) => (),
+            }
+        }
+    }
+}
+
+/// Computes the metrics of the program in `text`.
+///
+/// All metrics are computed from the parsed program except for the line counts, which come from
+/// the tokens in each physical line because comments are not part of the AST.
+pub fn compute(text: &str) -> Result<ProgramStats> {
+    let mut walker = Walker::default();
+    let mut stmts = vec![];
+    for stmt in parser::parse(&mut text.as_bytes()) {
+        stmts.push(stmt?);
+    }
+    walker.walk(&stmts, 0, None);
+
+    let mut code_lines = HashSet::new();
+    let mut bytes = text.as_bytes();
+    let mut lexer = Lexer::from(&mut bytes);
+    loop {
+        // The parser consumed the same input successfully so the lexer cannot fail here.
+        let span = lexer.read().expect("Input was already parsed");
+        match span.token {
+            Token::Eof => break,
+            Token::Eol => (),
+            _ => {
+                code_lines.insert(span.pos.line);
+            }
+        }
+    }
+
+    let mut stats = walker.stats;
+    stats.variables = walker.variables.len();
+    stats.arrays = walker.arrays.len();
+    for (i, line) in text.lines().enumerate() {
+        stats.lines += 1;
+        if code_lines.contains(&(i + 1)) {
+            stats.code_lines += 1;
+        } else if !line.trim().is_empty() {
+            stats.comment_lines += 1;
+        }
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_empty() {
+        assert_eq!(ProgramStats::default(), compute("").unwrap());
+        assert_eq!(ProgramStats { lines: 2, ..Default::default() }, compute("\n  \n").unwrap());
+    }
+
+    #[test]
+    fn test_compute_all() {
+        let text = "' Computes squares.
+DIM total AS INTEGER
+DIM values(3) AS INTEGER
+
+FUNCTION square(n%)
+    square = n * n
+END FUNCTION
+
+SUB show(v%)
+    IF v > 10 THEN
+        PRINT \"big\"; v
+    ELSE
+        PRINT v
+    END IF
+END SUB
+
+FOR i = 1 TO 3  ' Fill in values.
+    values(i - 1) = square(i)
+    WHILE total < 100
+        total = total + values(i - 1)
+    WEND
+    show values(i - 1)
+NEXT
+@done
+";
+        assert_eq!(
+            ProgramStats {
+                lines: 24,
+                code_lines: 20,
+                comment_lines: 1,
+                statements: 13,
+                variables: 4,
+                arrays: 1,
+                subs: 1,
+                functions: 1,
+                max_nesting: 2,
+                longest_callable: Some(("SHOW".to_owned(), 7)),
+            },
+            compute(text).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compute_statements_on_one_line() {
+        let stats = compute("a = 1: b = 2: IF a THEN c = 3 ELSE d = 4\nREM Done").unwrap();
+        assert_eq!(2, stats.lines);
+        assert_eq!(1, stats.code_lines);
+        assert_eq!(1, stats.comment_lines);
+        assert_eq!(5, stats.statements);
+        assert_eq!(4, stats.variables);
+        assert_eq!(1, stats.max_nesting);
+    }
+
+    #[test]
+    fn test_compute_variables_are_case_insensitive() {
+        let stats =
+            compute("DIM a AS INTEGER\nA = 3\nfor A = 1 TO 2: NEXT\nDIM b(2)\nB(1) = 3").unwrap();
+        assert_eq!(1, stats.variables);
+        assert_eq!(1, stats.arrays);
+    }
+
+    #[test]
+    fn test_compute_longest_callable_keeps_first() {
+        let text = "SUB a\nEND SUB\nSUB b\n    PRINT\nEND SUB\nFUNCTION c\n    c = 1\nEND FUNCTION";
+        let stats = compute(text).unwrap();
+        assert_eq!(2, stats.subs);
+        assert_eq!(1, stats.functions);
+        assert_eq!(0, stats.variables);
+        assert_eq!(Some(("B".to_owned(), 3)), stats.longest_callable);
+    }
+
+    #[test]
+    fn test_compute_errors() {
+        assert_eq!("1:5: Unknown character: .", compute("a = .3").unwrap_err().to_string());
+    }
+}
@@ -16,45 +16,393 @@
 //! Symbol definitions and symbols table representation.
 
 use crate::ast::{ExprType, Value, VarRef};
+use crate::bytecode::Instruction;
 use crate::compiler::{CallableSyntax, RepeatedSyntax, SingularArgSyntax};
 use crate::exec::{self, Machine, Scope};
 use crate::value;
 use async_trait::async_trait;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::convert::TryFrom;
 use std::fmt;
-use std::mem;
+use std::hash::{BuildHasherDefault, Hash, Hasher};
 use std::rc::Rc;
 use std::str::Lines;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::Arc;
 
 /// The key of a symbol in the symbols table.
-#[derive(Clone, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
-pub struct SymbolKey(String);
+///
+/// Keys are cheap to clone, hash and compare: the canonical name is shared among all clones of a
+/// key and its hash is computed only once.  Keys can also be resolved against the `SymbolNames`
+/// table of a machine, which assigns them a slot that indexes the storage of the symbols directly.
+/// This matters because the interpreter looks up symbols by key on every variable load and store,
+/// and resolved keys let it do so without hashing nor comparing names.
+#[derive(Clone)]
+pub struct SymbolKey {
+    /// Hash of the canonical name.
+    hash: u64,
+
+    /// Canonical version of the name.
+    name: Arc<str>,
+
+    /// Identifier of the `SymbolNames` table that resolved this key, or zero if unresolved.
+    table: u64,
+
+    /// Slot assigned to the name by `table`.  Only meaningful if `table` is not zero.
+    slot: u32,
+}
+
+impl SymbolKey {
+    /// Returns the canonical name of the symbol.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+}
 
 impl<R: AsRef<str>> From<R> for SymbolKey {
     fn from(value: R) -> Self {
-        Self(value.as_ref().to_ascii_uppercase())
+        let name = value.as_ref().to_ascii_uppercase();
+        // FNV-1a, which is good enough for the short names that programs use.
+        let hash = name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+            (hash ^ u64::from(b)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        Self { hash, name: Arc::from(name), table: 0, slot: 0 }
+    }
+}
+
+impl PartialEq for SymbolKey {
+    fn eq(&self, other: &Self) -> bool {
+        if self.table != 0 && self.table == other.table {
+            return self.slot == other.slot;
+        }
+        Arc::ptr_eq(&self.name, &other.name) || (self.hash == other.hash && self.name == other.name)
+    }
+}
+
+impl Eq for SymbolKey {}
+
+impl PartialOrd for SymbolKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SymbolKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.name.cmp(&other.name)
+    }
+}
+
+impl Hash for SymbolKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash)
+    }
+}
+
+impl fmt::Debug for SymbolKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SymbolKey").field(&self.name).finish()
     }
 }
 
 impl fmt::Display for SymbolKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Hasher for maps keyed by `SymbolKey`.
+///
+/// Symbol keys hash as their precomputed hash only, so there is no need to pay for a general
+/// purpose hash function when looking them up.
+#[derive(Default)]
+pub struct SymbolKeyHasher(u64);
+
+impl Hasher for SymbolKeyHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.write_u64(u64::from(*b));
+        }
+    }
+
+    fn write_u32(&mut self, i: u32) {
+        self.write_u64(u64::from(i));
+    }
+
+    fn write_u64(&mut self, i: u64) {
+        // Multiply to spread the bits of the input, which the hash map needs to be able to
+        // distribute keys across its buckets.
+        self.0 = (self.0.rotate_left(5) ^ i).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+}
+
+/// Map keyed by symbol names.
+pub type SymbolMap<V> = HashMap<SymbolKey, V, BuildHasherDefault<SymbolKeyHasher>>;
+
+/// Table of slots for the names of the symbols known to a machine.
+///
+/// Each machine owns one of these tables and resolves the names of the symbols it stores, as well
+/// as the names referenced by the programs it compiles, into slots.  Slots are never reused while
+/// the table is alive because compiled images hold on to them, so the table only grows with the
+/// number of distinct names that the machine sees.
+struct SymbolNames {
+    /// Identifier of this table, which is unique within the process and never zero.
+    id: u64,
+
+    /// Resolved keys for all known names, which are looked up by name.
+    keys: HashSet<SymbolKey, BuildHasherDefault<SymbolKeyHasher>>,
+}
+
+impl Default for SymbolNames {
+    fn default() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        Self { id: NEXT_ID.fetch_add(1, AtomicOrdering::Relaxed), keys: HashSet::default() }
+    }
+}
+
+impl SymbolNames {
+    /// Returns the slot assigned to the name of `key`, if any.
+    fn lookup(&self, key: &SymbolKey) -> Option<u32> {
+        self.keys.get(key).map(|known| known.slot)
+    }
+
+    /// Returns the key resolved against this table for the name of `key`, assigning a new slot to
+    /// the name if it did not have one yet.
+    fn resolve(&mut self, key: &SymbolKey) -> SymbolKey {
+        if let Some(known) = self.keys.get(key) {
+            return known.clone();
+        }
+        let slot = u32::try_from(self.keys.len()).expect("Too many symbol names");
+        let known = SymbolKey { table: self.id, slot, ..key.clone() };
+        self.keys.insert(known.clone());
+        known
+    }
+}
+
+/// Map of symbols indexed by the slots of their keys.
+///
+/// All keys stored in a map must have been resolved against the same `SymbolNames` table, which
+/// is the responsibility of `Symbols`.  Lookups index a vector by slot, and iteration only visits
+/// the entries that exist in the map.
+pub struct SlotMap<V> {
+    /// Position plus one of the entry for every slot, or zero if the slot has no entry.
+    index: Vec<u32>,
+
+    /// Keys and values of the entries in the map, in no particular order.
+    entries: Vec<(SymbolKey, V)>,
+}
+
+impl<V> Default for SlotMap<V> {
+    fn default() -> Self {
+        Self { index: vec![], entries: vec![] }
+    }
+}
+
+impl<V> SlotMap<V> {
+    /// Returns the position in `entries` of the entry for `slot`, if any.
+    fn position(&self, slot: u32) -> Option<usize> {
+        match self.index.get(slot as usize) {
+            Some(0) | None => None,
+            Some(pos) => Some((*pos - 1) as usize),
+        }
+    }
+
+    /// Returns the value for `slot`, if any.
+    fn get(&self, slot: u32) -> Option<&V> {
+        self.position(slot).map(|pos| &self.entries[pos].1)
+    }
+
+    /// Returns the value for `slot` for modification, if any.
+    fn get_mut(&mut self, slot: u32) -> Option<&mut V> {
+        self.position(slot).map(move |pos| &mut self.entries[pos].1)
+    }
+
+    /// Returns true if there is a value for `slot`.
+    fn contains(&self, slot: u32) -> bool {
+        self.position(slot).is_some()
+    }
+
+    /// Sets the value for the resolved `key`, replacing any previous value.
+    fn insert(&mut self, key: SymbolKey, value: V) {
+        debug_assert!(key.table != 0, "Keys must be resolved before they are stored");
+        let slot = key.slot as usize;
+        match self.position(key.slot) {
+            Some(pos) => self.entries[pos] = (key, value),
+            None => {
+                if slot >= self.index.len() {
+                    self.index.resize(slot + 1, 0);
+                }
+                self.entries.push((key, value));
+                self.index[slot] = u32::try_from(self.entries.len()).unwrap();
+            }
+        }
+    }
+
+    /// Removes the entry for `slot` and returns its value, if any.
+    fn remove(&mut self, slot: u32) -> Option<V> {
+        let pos = self.position(slot)?;
+        self.index[slot as usize] = 0;
+        let (_key, value) = self.entries.swap_remove(pos);
+        if let Some((moved, _value)) = self.entries.get(pos) {
+            self.index[moved.slot as usize] = u32::try_from(pos + 1).unwrap();
+        }
+        Some(value)
+    }
+
+    /// Keeps only the entries for which `f` returns true.
+    fn retain<F: FnMut(&SymbolKey, &mut V) -> bool>(&mut self, mut f: F) {
+        let index = &mut self.index;
+        self.entries.retain_mut(|(key, value)| {
+            let keep = f(key, value);
+            if !keep {
+                index[key.slot as usize] = 0;
+            }
+            keep
+        });
+        for (pos, (key, _value)) in self.entries.iter().enumerate() {
+            self.index[key.slot as usize] = u32::try_from(pos + 1).unwrap();
+        }
+    }
+
+    /// Removes all entries.
+    fn clear(&mut self) {
+        self.retain(|_key, _value| false);
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the keys and values of all entries, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&SymbolKey, &V)> {
+        self.entries.iter().map(|(key, value)| (key, value))
+    }
+
+    /// Returns an iterator over the values of all entries, in no particular order.
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_key, value)| value)
+    }
+}
+
+/// Returns the estimated number of bytes used by a value of type `etype`, excluding the contents
+/// of strings.
+fn fixed_size(etype: ExprType) -> usize {
+    match etype {
+        ExprType::Boolean => size_of::<bool>(),
+        ExprType::Double => size_of::<f64>(),
+        ExprType::Integer => size_of::<i32>(),
+        ExprType::Text => size_of::<String>(),
+    }
+}
+
+/// Returns the estimated number of bytes used by `value`, including the contents of strings.
+pub fn value_size(value: &Value) -> usize {
+    fixed_size(value.as_exprtype()) + text_size(value)
+}
+
+/// Returns the number of bytes used by the contents of `value` if it is a string, or zero.
+pub(crate) fn text_size(value: &Value) -> usize {
+    match value {
+        Value::Text(s) => s.len(),
+        _ => 0,
+    }
+}
+
+/// Homogeneous storage for the elements of an array, flattened.
+#[derive(Clone, Debug, PartialEq)]
+enum ArrayData {
+    /// Storage for an array of booleans.
+    Boolean(Vec<bool>),
+
+    /// Storage for an array of doubles.
+    Double(Vec<f64>),
+
+    /// Storage for an array of integers.
+    Integer(Vec<i32>),
+
+    /// Storage for an array of strings.
+    Text(Vec<String>),
+}
+
+impl ArrayData {
+    /// Creates storage for `n` elements of type `subtype` set to their default values.
+    fn new(subtype: ExprType, n: usize) -> Self {
+        match subtype {
+            ExprType::Boolean => ArrayData::Boolean(vec![false; n]),
+            ExprType::Double => ArrayData::Double(vec![0.0; n]),
+            ExprType::Integer => ArrayData::Integer(vec![0; n]),
+            ExprType::Text => ArrayData::Text(vec![String::new(); n]),
+        }
+    }
+
+    /// Returns the type of the elements in this storage.
+    fn subtype(&self) -> ExprType {
+        match self {
+            ArrayData::Boolean(_) => ExprType::Boolean,
+            ArrayData::Double(_) => ExprType::Double,
+            ArrayData::Integer(_) => ExprType::Integer,
+            ArrayData::Text(_) => ExprType::Text,
+        }
+    }
+
+    /// Returns the number of elements in this storage.
+    fn len(&self) -> usize {
+        match self {
+            ArrayData::Boolean(values) => values.len(),
+            ArrayData::Double(values) => values.len(),
+            ArrayData::Integer(values) => values.len(),
+            ArrayData::Text(values) => values.len(),
+        }
+    }
+
+    /// Returns the number of bytes used by the contents of the strings in this storage.
+    fn text_size(&self) -> usize {
+        match self {
+            ArrayData::Text(values) => values.iter().map(String::len).sum(),
+            _ => 0,
+        }
+    }
+
+    /// Resets the element at position `i` to its default value.
+    fn reset(&mut self, i: usize) {
+        match self {
+            ArrayData::Boolean(values) => values[i] = false,
+            ArrayData::Double(values) => values[i] = 0.0,
+            ArrayData::Integer(values) => values[i] = 0,
+            ArrayData::Text(values) => values[i].clear(),
+        }
     }
 }
 
 /// Represents a multidimensional array.
+///
+/// The elements of the array are stored in a vector of the native Rust type that corresponds to
+/// the array's subtype, which native callables can access directly via the `as_*` methods.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Array {
-    /// The type of all elements in the array.
-    subtype: ExprType,
-
     /// The dimensions of the array.  At least one must be present.
     dimensions: Vec<usize>,
 
     /// The values in the array, flattened.  Given dimensions `(N, M, O)`, an element `(i, j, k)` is
     /// at position `i * (M * O) + j * O + k`.
-    values: Vec<Value>,
+    data: ArrayData,
+
+    /// Positions of the flattened `data` that hold EMPTY values.  The corresponding elements in
+    /// `data` are set to their default values.
+    empties: BTreeSet<usize>,
 }
 
 impl Array {
@@ -66,12 +414,7 @@ impl Array {
             assert!(n > 0);
             n *= dim;
         }
-        let mut values = Vec::with_capacity(n);
-        let value = subtype.default_value();
-        for _ in 0..n {
-            values.push(value.clone());
-        }
-        Self { subtype, dimensions, values }
+        Self { dimensions, data: ArrayData::new(subtype, n), empties: BTreeSet::default() }
     }
 
     /// Returns the dimensions of the array.
@@ -81,17 +424,143 @@ impl Array {
 
     /// Returns the type of the elements in this array.
     pub fn subtype(&self) -> ExprType {
-        self.subtype
+        self.data.subtype()
     }
 
-    /// Validates that the subscript `i` is in the `[0,max)` range and converts it to an `usize`.
-    fn validate_subscript(i: i32, max: usize) -> value::Result<usize> {
-        if i < 0 {
-            Err(value::Error::new(format!("Subscript {} cannot be negative", i)))
-        } else if (i as usize) >= max {
-            Err(value::Error::new(format!("Subscript {} exceeds limit of {}", i, max)))
-        } else {
-            Ok(i as usize)
+    /// Returns the estimated number of bytes needed to store an array of `subtype` elements with
+    /// `dimensions`, excluding the contents of strings.  Saturates on overflow.
+    pub fn storage_size(subtype: ExprType, dimensions: &[usize]) -> usize {
+        dimensions.iter().fold(fixed_size(subtype), |size, dim| size.saturating_mul(*dim))
+    }
+
+    /// Returns the total number of elements in this array.
+    pub fn num_elements(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the estimated number of bytes used by the elements of this array, including the
+    /// contents of strings.
+    pub fn size(&self) -> usize {
+        self.storage() + self.data.text_size()
+    }
+
+    /// Returns the estimated number of bytes used by the elements of this array, excluding the
+    /// contents of strings.
+    fn storage(&self) -> usize {
+        fixed_size(self.subtype()) * self.data.len()
+    }
+
+    /// Returns the elements of a boolean array, flattened, or `None` if the array is of a different
+    /// type.  Elements that hold EMPTY values appear with their default value.
+    pub fn as_booleans(&self) -> Option<&[bool]> {
+        match &self.data {
+            ArrayData::Boolean(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of a double array, flattened, or `None` if the array is of a different
+    /// type.  Elements that hold EMPTY values appear with their default value.
+    pub fn as_doubles(&self) -> Option<&[f64]> {
+        match &self.data {
+            ArrayData::Double(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of an integer array, flattened, or `None` if the array is of a
+    /// different type.  Elements that hold EMPTY values appear with their default value.
+    pub fn as_integers(&self) -> Option<&[i32]> {
+        match &self.data {
+            ArrayData::Integer(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the elements of a string array, flattened, or `None` if the array is of a different
+    /// type.  Elements that hold EMPTY values appear with their default value.
+    pub fn as_texts(&self) -> Option<&[String]> {
+        match &self.data {
+            ArrayData::Text(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    /// Returns the mutable elements of a boolean array, flattened, or `None` if the array is of a
+    /// different type.  Elements that hold EMPTY values become their default value.
+    pub fn as_booleans_mut(&mut self) -> Option<&mut [bool]> {
+        match &mut self.data {
+            ArrayData::Boolean(values) => {
+                self.empties.clear();
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the mutable elements of a double array, flattened, or `None` if the array is of a
+    /// different type.  Elements that hold EMPTY values become their default value.
+    pub fn as_doubles_mut(&mut self) -> Option<&mut [f64]> {
+        match &mut self.data {
+            ArrayData::Double(values) => {
+                self.empties.clear();
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the mutable elements of an integer array, flattened, or `None` if the array is of a
+    /// different type.  Elements that hold EMPTY values become their default value.
+    pub fn as_integers_mut(&mut self) -> Option<&mut [i32]> {
+        match &mut self.data {
+            ArrayData::Integer(values) => {
+                self.empties.clear();
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the mutable elements of a string array, flattened, or `None` if the array is of a
+    /// different type.  Elements that hold EMPTY values become their default value.
+    pub fn as_texts_mut(&mut self) -> Option<&mut [String]> {
+        match &mut self.data {
+            ArrayData::Text(values) => {
+                self.empties.clear();
+                Some(values)
+            }
+            _ => None,
+        }
+    }
+
+    /// Builds the error for a `subscript` that is out of range for the zero-based `dimension` of
+    /// this array.
+    pub(crate) fn subscript_error<S: fmt::Display>(
+        &self,
+        subscript: S,
+        dimension: usize,
+    ) -> value::Error {
+        Array::out_of_range(subscript, dimension, self.dimensions[dimension])
+    }
+
+    /// Builds the error for a `subscript` that is not in the `[0,max)` range of the zero-based
+    /// `dimension`.
+    fn out_of_range<S: fmt::Display>(subscript: S, dimension: usize, max: usize) -> value::Error {
+        value::Error::new(format!(
+            "Subscript {} out of range 0..{} for dimension {}",
+            subscript,
+            max - 1,
+            dimension + 1
+        ))
+    }
+
+    /// Validates that the subscript `i` is in the `[0,max)` range of the zero-based `dimension` and
+    /// converts it to an `usize`.
+    fn validate_subscript(i: i32, dimension: usize, max: usize) -> value::Result<usize> {
+        match usize::try_from(i) {
+            Ok(i) if i < max => Ok(i),
+            _ => Err(Array::out_of_range(i, dimension, max)),
         }
     }
 
@@ -110,15 +579,25 @@ impl Array {
         let mut multiplier = 1;
         let mut k = dimensions.len() - 1;
         while k > 0 {
-            offset += Array::validate_subscript(subscripts[k], dimensions[k])? * multiplier;
+            offset += Array::validate_subscript(subscripts[k], k, dimensions[k])? * multiplier;
             debug_assert!(dimensions[k] > 0);
             multiplier *= dimensions[k];
             k -= 1;
         }
-        offset += Array::validate_subscript(subscripts[k], dimensions[k])? * multiplier;
+        offset += Array::validate_subscript(subscripts[k], k, dimensions[k])? * multiplier;
         Ok(offset)
     }
 
+    /// Returns the number of bytes used by the contents of the element at `subscripts` if this is
+    /// an array of strings, or zero otherwise.
+    pub(crate) fn text_size_at(&self, subscripts: &[i32]) -> value::Result<usize> {
+        let i = Array::native_index(&self.dimensions, subscripts)?;
+        match &self.data {
+            ArrayData::Text(values) => Ok(values[i].len()),
+            _ => Ok(0),
+        }
+    }
+
     /// Assings the `value` to the array position indicated by the `subscripts`.
     pub fn assign(&mut self, subscripts: &[i32], value: Value) -> value::Result<()> {
         debug_assert_eq!(
@@ -129,20 +608,41 @@ impl Array {
 
         debug_assert_eq!(
             value.as_exprtype(),
-            self.subtype,
+            self.subtype(),
             "Invalid types in assignment; guaranteed valid by the compiler"
         );
 
         let i = Array::native_index(&self.dimensions, subscripts)?;
-        self.values[i] = value;
+        match (&mut self.data, value) {
+            (ArrayData::Boolean(values), Value::Boolean(b)) => values[i] = b,
+            (ArrayData::Double(values), Value::Double(d)) => values[i] = d,
+            (ArrayData::Integer(values), Value::Integer(n)) => values[i] = n,
+            (ArrayData::Text(values), Value::Text(s)) => values[i] = s,
+            (data, Value::Empty(_)) => {
+                data.reset(i);
+                self.empties.insert(i);
+                return Ok(());
+            }
+            (_, _) => unreachable!("Invalid types in assignment; guaranteed valid by the compiler"),
+        }
+        if !self.empties.is_empty() {
+            self.empties.remove(&i);
+        }
         Ok(())
     }
 
     /// Obtains the value contained in the array position indicated by the `subscripts`.
-    pub fn index(&self, subscripts: &[i32]) -> value::Result<&Value> {
+    pub fn index(&self, subscripts: &[i32]) -> value::Result<Value> {
         let i = Array::native_index(&self.dimensions, subscripts)?;
-        let value = &self.values[i];
-        debug_assert!(value.as_exprtype() == self.subtype);
+        if !self.empties.is_empty() && self.empties.contains(&i) {
+            return Ok(Value::Empty(self.subtype()));
+        }
+        let value = match &self.data {
+            ArrayData::Boolean(values) => Value::Boolean(values[i]),
+            ArrayData::Double(values) => Value::Double(values[i]),
+            ArrayData::Integer(values) => Value::Integer(values[i]),
+            ArrayData::Text(values) => Value::Text(values[i].clone()),
+        };
         Ok(value)
     }
 }
@@ -198,6 +698,103 @@ impl fmt::Debug for Symbol {
     }
 }
 
+/// Estimated memory usage of an array.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArrayUsage {
+    /// Name of the array.
+    pub name: SymbolKey,
+
+    /// Type of the elements in the array.
+    pub subtype: ExprType,
+
+    /// Total number of elements in the array.
+    pub elements: usize,
+
+    /// Estimated number of bytes used by the elements, including the contents of strings.
+    pub bytes: usize,
+}
+
+/// Estimated memory usage of the variables and arrays in a symbols table.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryUsage {
+    /// Number of scalar variables.
+    pub variables: usize,
+
+    /// Estimated number of bytes used by the scalar variables, including the contents of strings.
+    pub variables_bytes: usize,
+
+    /// Usage of every array, sorted by name.
+    pub arrays: Vec<ArrayUsage>,
+
+    /// Number of bytes used by the contents of all strings, in both variables and arrays.  These
+    /// bytes are already accounted for in `variables_bytes` and in the usage of each array.
+    pub strings_bytes: usize,
+}
+
+impl MemoryUsage {
+    /// Accounts for the usage of `symbol` named `key`.
+    fn add(&mut self, key: &SymbolKey, symbol: &Symbol) {
+        match symbol {
+            Symbol::Array(array) => {
+                self.arrays.push(ArrayUsage {
+                    name: key.clone(),
+                    subtype: array.subtype(),
+                    elements: array.num_elements(),
+                    bytes: array.size(),
+                });
+                self.strings_bytes += array.data.text_size();
+            }
+            Symbol::Callable(_) => (),
+            Symbol::Variable(value) => {
+                self.variables += 1;
+                self.variables_bytes += value_size(value);
+                self.strings_bytes += text_size(value);
+            }
+        }
+    }
+
+    /// Returns the estimated number of bytes used by all variables and arrays.
+    pub fn total_bytes(&self) -> usize {
+        self.arrays.iter().fold(self.variables_bytes, |total, array| total + array.bytes)
+    }
+}
+
+/// Returns the number of bytes in `symbols` that are accounted against the memory limit, which
+/// are the storage of arrays and the contents of strings.
+fn allocated_size<'a, I: Iterator<Item = &'a Symbol>>(symbols: I) -> usize {
+    symbols
+        .map(|symbol| match symbol {
+            Symbol::Array(array) => array.size(),
+            Symbol::Variable(value) => text_size(value),
+            Symbol::Callable(_) => 0,
+        })
+        .sum()
+}
+
+/// Checks if `bytes` more bytes fit within the memory `limit` given that `allocated` bytes are
+/// already in use.
+fn check_allocation(limit: Option<usize>, allocated: usize, bytes: usize) -> value::Result<()> {
+    match limit {
+        Some(limit) if allocated.saturating_add(bytes) > limit => {
+            Err(value::Error::new(format!("Out of memory: limit is {} bytes", limit)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Returns true if the user-defined symbol `key` must survive a clear operation that preserves
+/// the symbols in `keep`.
+pub(crate) fn survives_clear(key: &SymbolKey, keep: &HashSet<SymbolKey>) -> bool {
+    let is_internal = key.name.starts_with(|c: char| c.is_ascii_digit());
+
+    // TODO(jmmv): Preserving symbols that start with __ is a hack that was added to support
+    // the already-existing GPIO tests when RUN was changed to issue a CLEAR upfront.  This
+    // is undocumented behavior and we should find a nicer way to do this.
+    let is_gpio_hack = key.name.starts_with("__");
+
+    is_internal || is_gpio_hack || keep.contains(key)
+}
+
 /// Storage for all symbols that exist at runtime.
 ///
 /// Symbols are represented as a two-layer map: the globals map contains all symbols that are
@@ -208,20 +805,65 @@ impl fmt::Debug for Symbol {
 /// the global symbols and the symbols in the last scope.
 ///
 /// Scopes are represented as a stack in order to support nested function calls.
+///
+/// Static variables of user-defined callables live outside of the scopes so that they survive
+/// across calls.  Each scope can link some of its local names to these static variables, which
+/// makes the static variables visible within that scope only.
+///
+/// All maps are indexed by the slots that `names` assigns to the symbol names.  The compiler
+/// resolves the names that programs reference ahead of time, which leaves looking up names by
+/// their text only for the keys that are created at runtime.
 pub struct Symbols {
     /// Map of global symbol names to their definitions.
-    globals: HashMap<SymbolKey, Symbol>,
+    globals: SlotMap<Symbol>,
 
     /// Map of local symbol names to their definitions.
-    scopes: Vec<HashMap<SymbolKey, Symbol>>,
+    scopes: Vec<SlotMap<Symbol>>,
+
+    /// Map of callable names to the static variables they own.
+    statics: SymbolMap<SlotMap<Symbol>>,
+
+    /// Map of local symbol names to the callable that owns the static variable they refer to.
+    /// There is one entry in this stack for every entry in `scopes`.
+    links: Vec<SlotMap<SymbolKey>>,
+
+    /// Maximum number of bytes that the program can allocate, if any.
+    memory_limit: Option<usize>,
+
+    /// Number of bytes used by the storage of all arrays and the contents of all strings,
+    /// accounted against `memory_limit`.
+    allocated: usize,
+
+    /// Identifier of the `names` table, cached to check keys without borrowing the table.
+    names_id: u64,
+
+    /// Slots of the names of the symbols known to this machine.
+    names: RefCell<SymbolNames>,
 }
 
 impl Default for Symbols {
     fn default() -> Self {
-        Self { globals: HashMap::default(), scopes: vec![HashMap::default()] }
+        let names = SymbolNames::default();
+        Self {
+            globals: SlotMap::default(),
+            scopes: vec![SlotMap::default()],
+            statics: SymbolMap::default(),
+            links: vec![SlotMap::default()],
+            memory_limit: None,
+            allocated: 0,
+            names_id: names.id,
+            names: RefCell::from(names),
+        }
     }
 }
 
+/// Returns the user-defined symbols in `map`, sorted by name.
+fn user_defined_sorted(map: &SlotMap<Symbol>) -> Vec<(&SymbolKey, &Symbol)> {
+    let mut symbols = map.iter().filter(|(_key, s)| s.user_defined()).collect::<Vec<_>>();
+    symbols.sort_by(|a, b| a.0.cmp(b.0));
+    symbols
+}
+
 impl Symbols {
     /// Constructs a symbols object from a flat map of symbol names to their definitions.
     #[cfg(test)]
@@ -229,12 +871,51 @@ impl Symbols {
         globals: HashMap<SymbolKey, Symbol>,
         scope: HashMap<SymbolKey, Symbol>,
     ) -> Self {
-        Self { globals, scopes: vec![scope] }
+        let allocated = allocated_size(globals.values().chain(scope.values()));
+        let mut syms = Self { allocated, ..Self::default() };
+        for (key, symbol) in globals {
+            let key = syms.resolve(&key);
+            syms.globals.insert(key, symbol);
+        }
+        for (key, symbol) in scope {
+            let key = syms.resolve(&key);
+            syms.scopes[0].insert(key, symbol);
+        }
+        syms
+    }
+
+    /// Returns the slot assigned to the name of `key`, or `None` if the name is not known, in
+    /// which case there is no symbol with that name.
+    fn slot(&self, key: &SymbolKey) -> Option<u32> {
+        if key.table == self.names_id {
+            Some(key.slot)
+        } else {
+            self.names.borrow().lookup(key)
+        }
+    }
+
+    /// Returns `key` resolved against the names known to these symbols, assigning a slot to its
+    /// name if it did not have one yet.
+    fn resolve(&self, key: &SymbolKey) -> SymbolKey {
+        if key.table == self.names_id {
+            key.clone()
+        } else {
+            self.names.borrow_mut().resolve(key)
+        }
+    }
+
+    /// Resolves the symbol names referenced by `instrs` against the names known to these symbols
+    /// so that executing the instructions does not need to look names up.
+    pub(crate) fn resolve_names(&self, instrs: &mut [Instruction]) {
+        for instr in instrs {
+            instr.for_each_key_mut(|key| *key = self.resolve(key));
+        }
     }
 
     /// Enters a new scope.
     pub(crate) fn enter_scope(&mut self) {
-        self.scopes.push(HashMap::default());
+        self.scopes.push(SlotMap::default());
+        self.links.push(SlotMap::default());
     }
 
     /// Leaves the current scope.
@@ -242,6 +923,28 @@ impl Symbols {
         let last = self.scopes.pop();
         assert!(last.is_some(), "Must have at least one scope to pop");
         assert!(!self.scopes.is_empty(), "Cannot pop the global scope");
+        self.links.pop();
+        self.release(allocated_size(last.iter().flat_map(SlotMap::values)));
+    }
+
+    /// Leaves all scopes other than the global one, discarding their contents.
+    pub(crate) fn leave_all_scopes(&mut self) {
+        self.leave_scopes_until(1);
+    }
+
+    /// Returns the number of scopes currently entered, including the global one.
+    pub fn scopes_depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Leaves all scopes entered after the scope at `depth`, discarding their contents.
+    pub(crate) fn leave_scopes_until(&mut self, depth: usize) {
+        assert!(depth > 0, "Cannot pop the global scope");
+        if depth < self.scopes.len() {
+            self.release(allocated_size(self.scopes[depth..].iter().flat_map(SlotMap::values)));
+        }
+        self.scopes.truncate(depth);
+        self.links.truncate(depth);
     }
 
     /// Registers the given builtin callable as a global symbol.
@@ -249,15 +952,15 @@ impl Symbols {
     /// Given that callables cannot be defined at runtime, specifying a non-unique name results in
     /// a panic.
     pub fn add_callable(&mut self, callable: Rc<dyn Callable>) {
-        let key = SymbolKey::from(callable.metadata().name());
-        assert!(!self.globals.contains_key(&key));
-        self.globals.insert(key.to_owned(), Symbol::Callable(callable));
+        let key = self.resolve(&SymbolKey::from(callable.metadata().name()));
+        assert!(!self.globals.contains(key.slot));
+        self.globals.insert(key, Symbol::Callable(callable));
     }
 
     /// Returns the mapping of all callables.
     pub fn callables(&self) -> HashMap<&SymbolKey, Rc<dyn Callable>> {
         let mut callables = HashMap::with_capacity(self.globals.len());
-        for (key, symbol) in &self.globals {
+        for (key, symbol) in self.globals.iter() {
             if let Symbol::Callable(c) = symbol {
                 callables.insert(key, c.clone());
             }
@@ -266,31 +969,161 @@ impl Symbols {
     }
 
     /// Returns the mapping of all symbols in the current scope that are not globals.
-    pub fn locals(&self) -> &HashMap<SymbolKey, Symbol> {
+    pub fn locals(&self) -> &SlotMap<Symbol> {
         self.scopes.last().unwrap()
     }
 
     /// Clears all user-defined symbols.
     pub fn clear(&mut self) {
-        fn filter(key: &SymbolKey, symbol: &mut Symbol) -> bool {
-            let is_internal = key.0.starts_with(|c: char| c.is_ascii_digit());
+        self.clear_except(&HashSet::default())
+    }
 
-            // TODO(jmmv): Preserving symbols that start with __ is a hack that was added to support
-            // the already-existing GPIO tests when RUN was changed to issue a CLEAR upfront.  This
-            // is undocumented behavior and we should find a nicer way to do this.
-            let is_gpio_hack = key.0.starts_with("__");
+    /// Clears all user-defined symbols except for the variables and arrays named in `keep`.
+    ///
+    /// Static variables are always cleared.
+    pub fn clear_except(&mut self, keep: &HashSet<SymbolKey>) {
+        let filter = |key: &SymbolKey, symbol: &mut Symbol| {
+            !symbol.user_defined() || survives_clear(key, keep)
+        };
+        self.globals.retain(filter);
+        self.scopes.last_mut().unwrap().retain(filter);
+        self.statics.clear();
+        self.links.last_mut().unwrap().clear();
+
+        let all = self.globals.values().chain(self.scopes.iter().flat_map(SlotMap::values));
+        self.allocated = allocated_size(all);
+    }
+
+    /// Returns the user-defined symbols in the globals map, sorted by name.
+    pub(crate) fn user_globals(&self) -> Vec<(&SymbolKey, &Symbol)> {
+        user_defined_sorted(&self.globals)
+    }
+
+    /// Returns the user-defined symbols in the outermost scope, sorted by name.
+    pub(crate) fn user_top_locals(&self) -> Vec<(&SymbolKey, &Symbol)> {
+        user_defined_sorted(&self.scopes[0])
+    }
+
+    /// Checks that none of the symbols named in `keys` clashes with a callable.
+    pub(crate) fn check_not_callables<'a, I: Iterator<Item = &'a SymbolKey>>(
+        &self,
+        mut keys: I,
+    ) -> value::Result<()> {
+        let is_callable = |key: &SymbolKey| match self.slot(key) {
+            Some(slot) => matches!(self.globals.get(slot), Some(Symbol::Callable(_))),
+            None => false,
+        };
+        match keys.find(|key| is_callable(key)) {
+            Some(key) => Err(value::Error::new(format!("Cannot redefine {} as a variable", key))),
+            None => Ok(()),
+        }
+    }
 
-            is_internal || is_gpio_hack || !symbol.user_defined()
+    /// Clears all user-defined symbols and replaces them with the given `globals` and `locals`,
+    /// which are defined in the globals map and in the outermost scope respectively.
+    ///
+    /// The caller must have checked that none of the new symbols clashes with a callable.
+    pub(crate) fn replace_top_level(
+        &mut self,
+        globals: Vec<(SymbolKey, Symbol)>,
+        locals: Vec<(SymbolKey, Symbol)>,
+    ) {
+        self.leave_all_scopes();
+        self.clear();
+        for (key, symbol) in globals {
+            let key = self.resolve(&key);
+            self.globals.insert(key, symbol);
+        }
+        for (key, symbol) in locals {
+            let key = self.resolve(&key);
+            self.scopes[0].insert(key, symbol);
         }
 
-        self.globals.retain(filter);
-        self.scopes.last_mut().unwrap().retain(filter);
+        let all = self.globals.values().chain(self.scopes.iter().flat_map(SlotMap::values));
+        self.allocated = allocated_size(all);
+    }
+
+    /// Limits the number of bytes that the program can allocate to `limit`, or removes the limit
+    /// if `None`, and returns the previous setting.
+    pub fn set_memory_limit(&mut self, limit: Option<usize>) -> Option<usize> {
+        std::mem::replace(&mut self.memory_limit, limit)
+    }
+
+    /// Returns the number of bytes accounted against the memory limit, which covers the storage
+    /// of all arrays and the contents of all strings.
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Checks if the program can allocate `bytes` more bytes without exceeding the memory limit.
+    pub fn check_allocation(&self, bytes: usize) -> value::Result<()> {
+        check_allocation(self.memory_limit, self.allocated, bytes)
+    }
+
+    /// Accounts for a symbol whose contents change from `old_size` to `new_size` bytes.
+    ///
+    /// Returns an error without accounting anything if the growth does not fit within the memory
+    /// limit.
+    pub(crate) fn resize(&mut self, old_size: usize, new_size: usize) -> value::Result<()> {
+        if new_size > old_size {
+            self.check_allocation(new_size - old_size)?;
+        }
+        self.release(old_size);
+        self.allocated += new_size;
+        Ok(())
+    }
+
+    /// Stops accounting `bytes` against the memory limit.
+    ///
+    /// Saturates at zero because native callables can modify the contents of arrays in place
+    /// without going through the accounting.
+    fn release(&mut self, bytes: usize) {
+        self.allocated = self.allocated.saturating_sub(bytes);
+    }
+
+    /// Computes an estimate of the memory used by all variables and arrays, including those in
+    /// the scopes of the callables that are running and the static variables.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage::default();
+        let all = self.globals.iter().chain(self.scopes.iter().flat_map(SlotMap::iter));
+        for (key, symbol) in all.chain(self.statics.values().flat_map(SlotMap::iter)) {
+            usage.add(key, symbol);
+        }
+        usage.arrays.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+
+    /// Makes the static variable `key` of the `callable` visible in the current scope.
+    ///
+    /// Returns false if the static variable has not been initialized yet.
+    pub(crate) fn link_static(&mut self, callable: &SymbolKey, key: &SymbolKey) -> bool {
+        let key = self.resolve(key);
+        match self.statics.get(callable) {
+            Some(statics) if statics.contains(key.slot) => (),
+            _ => return false,
+        }
+        self.links.last_mut().unwrap().insert(key, callable.clone());
+        true
+    }
+
+    /// Turns the local symbol `key` into a static variable of the `callable` and makes it visible
+    /// in the current scope.
+    ///
+    /// Does nothing if the local symbol does not exist, which can happen if its initialization
+    /// failed and the error was ignored.  In that case, the next call retries the initialization.
+    pub(crate) fn save_static(&mut self, callable: &SymbolKey, key: &SymbolKey) {
+        let key = self.resolve(key);
+        if let Some(symbol) = self.scopes.last_mut().unwrap().remove(key.slot) {
+            self.statics.entry(callable.clone()).or_default().insert(key.clone(), symbol);
+            self.links.last_mut().unwrap().insert(key, callable.clone());
+        }
     }
 
     /// Defines a new local variable `key` of type `etype`.  The variable must not yet exist.
     pub fn dim(&mut self, key: SymbolKey, etype: ExprType) {
+        let key = self.resolve(&key);
         debug_assert!(
-            !self.globals.contains_key(&key) && !self.scopes.last_mut().unwrap().contains_key(&key),
+            !self.globals.contains(key.slot) && !self.scopes.last().unwrap().contains(key.slot),
             "Pre-existence of variables is checked at compilation time"
         );
         self.scopes.last_mut().unwrap().insert(key, Symbol::Variable(etype.default_value()));
@@ -298,8 +1131,9 @@ impl Symbols {
 
     /// Defines a new global variable `key` of type `etype`.  The variable must not yet exist.
     pub fn dim_shared(&mut self, key: SymbolKey, etype: ExprType) {
+        let key = self.resolve(&key);
         debug_assert!(
-            !self.globals.contains_key(&key) && !self.scopes.last_mut().unwrap().contains_key(&key),
+            !self.globals.contains(key.slot) && !self.scopes.last().unwrap().contains(key.slot),
             "Pre-existence of variables is checked at compilation time"
         );
         self.globals.insert(key, Symbol::Variable(etype.default_value()));
@@ -308,21 +1142,44 @@ impl Symbols {
     /// Defines a new array `key` of type `subtype` with `dimensions`.  The array must not yet
     /// exist, and the name may not overlap function or variable names.
     pub fn dim_array(&mut self, key: SymbolKey, subtype: ExprType, dimensions: Vec<usize>) {
+        let key = self.resolve(&key);
         debug_assert!(
-            !self.globals.contains_key(&key) && !self.scopes.last_mut().unwrap().contains_key(&key),
+            !self.globals.contains(key.slot) && !self.scopes.last().unwrap().contains(key.slot),
             "Pre-existence of variables is checked at compilation time"
         );
-        self.scopes.last_mut().unwrap().insert(key, Symbol::Array(Array::new(subtype, dimensions)));
+        let array = Array::new(subtype, dimensions);
+        self.allocated += array.size();
+        self.scopes.last_mut().unwrap().insert(key, Symbol::Array(array));
     }
 
     /// Defines a new global array `key` of type `subtype` with `dimensions`.  The array must not yet
     /// exist, and the name may not overlap function or variable names.
     pub fn dim_shared_array(&mut self, key: SymbolKey, subtype: ExprType, dimensions: Vec<usize>) {
+        let key = self.resolve(&key);
         debug_assert!(
-            !self.globals.contains_key(&key) && !self.scopes.last_mut().unwrap().contains_key(&key),
+            !self.globals.contains(key.slot) && !self.scopes.last().unwrap().contains(key.slot),
             "Pre-existence of variables is checked at compilation time"
         );
-        self.globals.insert(key, Symbol::Array(Array::new(subtype, dimensions)));
+        let array = Array::new(subtype, dimensions);
+        self.allocated += array.size();
+        self.globals.insert(key, Symbol::Array(array));
+    }
+
+    /// Replaces the contents of the existing array `vref` with `array`, which can have a different
+    /// size, and accounts for the difference in size against the memory limit.
+    ///
+    /// Returns an error if `vref` is not an array or if the new contents do not fit in memory.
+    pub fn replace_array(&mut self, vref: &VarRef, array: Array) -> value::Result<()> {
+        let old_size = match self.get(vref)? {
+            Some(Symbol::Array(old)) => old.size(),
+            _ => return Err(value::Error::new(format!("{} is not an array", vref.name()))),
+        };
+        self.resize(old_size, array.size())?;
+        match self.get_mut(vref)? {
+            Some(Symbol::Array(old)) => *old = array,
+            _ => unreachable!("Array existence checked above"),
+        }
+        Ok(())
     }
 
     /// Obtains the value of a symbol or `None` if it is not defined.
@@ -330,11 +1187,15 @@ impl Symbols {
     /// This is meant to use by the compiler only.  All other users should call `get` instead
     /// to do the necessary runtime validity checks.
     pub(crate) fn load(&self, key: &SymbolKey) -> Option<&Symbol> {
-        let local = self.scopes.last().unwrap().get(key);
+        let slot = self.slot(key)?;
+        let local = self.scopes.last().unwrap().get(slot);
         if local.is_some() {
             return local;
         }
-        self.globals.get(key)
+        if let Some(callable) = self.links.last().unwrap().get(slot) {
+            return self.statics.get(callable).and_then(|statics| statics.get(slot));
+        }
+        self.globals.get(slot)
     }
 
     /// Obtains the value of a symbol or `None` if it is not defined.
@@ -342,11 +1203,15 @@ impl Symbols {
     /// This is meant to use by the compiler only.  All other users should call `get` instead
     /// to do the necessary runtime validity checks.
     pub(crate) fn load_mut(&mut self, key: &SymbolKey) -> Option<&mut Symbol> {
-        let local = self.scopes.last_mut().unwrap().get_mut(key);
+        let slot = self.slot(key)?;
+        let local = self.scopes.last_mut().unwrap().get_mut(slot);
         if local.is_some() {
             return local;
         }
-        self.globals.get_mut(key)
+        if let Some(callable) = self.links.last().unwrap().get(slot) {
+            return self.statics.get_mut(callable).and_then(|statics| statics.get_mut(slot));
+        }
+        self.globals.get_mut(slot)
     }
 
     /// Obtains the value of a symbol or `None` if it is not defined.
@@ -408,28 +1273,46 @@ impl Symbols {
 
     /// Sets the value of a variable without type promotion nor type checking.
     ///
+    /// Returns an error if the contents of the new value do not fit within the memory limit.
+    ///
     /// This is meant to use by the compiler only.  All other users should call `set_var` instead
     /// to do the necessary runtime validity checks.
-    pub(crate) fn assign(&mut self, key: &SymbolKey, value: Value) {
-        let old_value = match self.globals.get_mut(key) {
-            Some(value) => Some(value),
-            None => self.scopes.last_mut().unwrap().get_mut(key),
+    pub(crate) fn assign(&mut self, key: &SymbolKey, value: Value) -> value::Result<()> {
+        let old_value = match self.slot(key) {
+            Some(slot) => match self.globals.get_mut(slot) {
+                Some(value) => Some(value),
+                None => match self.links.last().unwrap().get(slot) {
+                    Some(callable) => {
+                        self.statics.get_mut(callable).and_then(|statics| statics.get_mut(slot))
+                    }
+                    None => self.scopes.last_mut().unwrap().get_mut(slot),
+                },
+            },
+            None => None,
         };
 
         match old_value {
             Some(Symbol::Variable(old_value)) => {
                 debug_assert_eq!(
-                    mem::discriminant(old_value),
-                    mem::discriminant(&value),
+                    old_value.as_exprtype(),
+                    value.as_exprtype(),
                     "Type consistency is validated at compilation time"
                 );
+                let (old_size, new_size) = (text_size(old_value), text_size(&value));
+                if new_size > old_size {
+                    check_allocation(self.memory_limit, self.allocated, new_size - old_size)?;
+                }
+                self.allocated = self.allocated.saturating_sub(old_size) + new_size;
                 *old_value = value;
             }
             Some(_) => unreachable!("Type consistency is validated at compilation time"),
             None => {
-                self.scopes.last_mut().unwrap().insert(key.clone(), Symbol::Variable(value));
+                self.resize(0, text_size(&value))?;
+                let key = self.resolve(key);
+                self.scopes.last_mut().unwrap().insert(key, Symbol::Variable(value));
             }
         }
+        Ok(())
     }
 
     /// Sets the value of a variable.
@@ -445,15 +1328,14 @@ impl Symbols {
         match self.get_mut(vref)? {
             Some(Symbol::Variable(old_value)) => {
                 let value = value.maybe_cast(Some(old_value.as_exprtype()))?;
-                if mem::discriminant(&value) != mem::discriminant(old_value) {
+                if value.as_exprtype() != old_value.as_exprtype() {
                     return Err(value::Error::new(format!(
                         "Cannot assign value of type {} to variable of type {}",
                         value.as_exprtype(),
                         old_value.as_exprtype(),
                     )));
                 }
-                self.assign(&key, value);
-                Ok(())
+                self.assign(&key, value)
             }
             Some(_) => Err(value::Error::new(format!("Cannot redefine {} as a variable", vref))),
             None => {
@@ -466,6 +1348,8 @@ impl Symbols {
                         )));
                     }
                 }
+                self.resize(0, text_size(&value))?;
+                let key = self.resolve(&key);
                 self.scopes.last_mut().unwrap().insert(key, Symbol::Variable(value));
                 Ok(())
             }
@@ -474,8 +1358,15 @@ impl Symbols {
 
     /// Unsets the symbol `key` irrespective of its type.
     pub(crate) fn unset(&mut self, key: &SymbolKey) -> value::Result<()> {
-        match self.scopes.last_mut().unwrap().remove(key) {
-            Some(_) => Ok(()),
+        let removed = match self.slot(key) {
+            Some(slot) => self.scopes.last_mut().unwrap().remove(slot),
+            None => None,
+        };
+        match removed {
+            Some(symbol) => {
+                self.release(allocated_size(std::iter::once(&symbol)));
+                Ok(())
+            }
             None => Err(value::Error::new(format!("{} is not defined", key))),
         }
     }
@@ -488,6 +1379,8 @@ pub struct CallableMetadataBuilder {
     category: Option<&'static str>,
     syntaxes: Vec<CallableSyntax>,
     description: Option<&'static str>,
+    accepts_empty: bool,
+    numeric_promotion: bool,
 }
 
 impl CallableMetadataBuilder {
@@ -505,6 +1398,8 @@ impl CallableMetadataBuilder {
             syntaxes: vec![],
             category: None,
             description: None,
+            accepts_empty: false,
+            numeric_promotion: false,
         }
     }
 
@@ -519,6 +1414,8 @@ impl CallableMetadataBuilder {
             syntaxes: vec![],
             category: None,
             description: None,
+            accepts_empty: false,
+            numeric_promotion: false,
         }
     }
 
@@ -577,6 +1474,20 @@ impl CallableMetadataBuilder {
         self
     }
 
+    /// Marks the callable as accepting EMPTY values in its arguments, which it must then consume
+    /// via `Scope::pop_if_empty`.
+    pub fn with_empty_values(mut self) -> Self {
+        self.accepts_empty = true;
+        self
+    }
+
+    /// Marks the function as returning an integer when all of its numeric arguments are integers,
+    /// in which case the declared return type only applies when any of them is a double.
+    pub fn with_numeric_promotion(mut self) -> Self {
+        self.numeric_promotion = true;
+        self
+    }
+
     /// Generates the final `CallableMetadata` object, ensuring all values are present.
     pub fn build(self) -> CallableMetadata {
         assert!(!self.syntaxes.is_empty(), "All callables must specify a syntax");
@@ -586,6 +1497,8 @@ impl CallableMetadataBuilder {
             syntaxes: self.syntaxes,
             category: self.category.expect("All callables must specify a category"),
             description: self.description.expect("All callables must specify a description"),
+            accepts_empty: self.accepts_empty,
+            numeric_promotion: self.numeric_promotion,
         }
     }
 
@@ -601,6 +1514,8 @@ impl CallableMetadataBuilder {
             syntaxes: self.syntaxes,
             category: self.category.unwrap_or(""),
             description: self.description.unwrap_or(""),
+            accepts_empty: self.accepts_empty,
+            numeric_promotion: self.numeric_promotion,
         }
     }
 }
@@ -616,6 +1531,8 @@ pub struct CallableMetadata {
     syntaxes: Vec<CallableSyntax>,
     category: &'static str,
     description: &'static str,
+    accepts_empty: bool,
+    numeric_promotion: bool,
 }
 
 impl CallableMetadata {
@@ -650,6 +1567,19 @@ impl CallableMetadata {
         }
     }
 
+    /// Gets a one-line summary of how to invoke the callable, including its name and, for
+    /// functions, the type annotation of its return value.
+    pub fn usage(&self) -> String {
+        match (self.return_type, self.is_argless()) {
+            (None, true) => self.name.to_string(),
+            (None, false) => format!("{} {}", self.name, self.syntax()),
+            (Some(return_type), true) => format!("{}{}", self.name, return_type.annotation()),
+            (Some(return_type), false) => {
+                format!("{}{}({})", self.name, return_type.annotation(), self.syntax())
+            }
+        }
+    }
+
     /// Returns the callable's syntax definitions.
     pub(crate) fn syntaxes(&self) -> &[CallableSyntax] {
         &self.syntaxes
@@ -672,6 +1602,16 @@ impl CallableMetadata {
         self.syntaxes.is_empty() || (self.syntaxes.len() == 1 && self.syntaxes[0].is_empty())
     }
 
+    /// Returns true if this callable accepts EMPTY values in its arguments.
+    pub fn accepts_empty(&self) -> bool {
+        self.accepts_empty
+    }
+
+    /// Returns true if this function's return type is promoted from its numeric arguments.
+    pub fn numeric_promotion(&self) -> bool {
+        self.numeric_promotion
+    }
+
     /// Returns true if this callable is a function (not a command).
     pub fn is_function(&self) -> bool {
         self.return_type.is_some()
@@ -706,8 +1646,144 @@ pub trait Callable {
 mod tests {
     use super::*;
     use crate::ast::{ExprType, VarRef};
+    use crate::compiler::{ArgSepSyntax, RequiredValueSyntax};
     use crate::testutils::*;
-    use std::cell::RefCell;
+
+    #[test]
+    fn test_symbol_key_canonical() {
+        let key1 = SymbolKey::from("SomeName");
+        let key2 = SymbolKey::from("SOMENAME");
+        let key3 = SymbolKey::from(String::from("somename"));
+        assert_eq!(key1, key2);
+        assert_eq!(key1, key3);
+        assert_eq!(key1.hash, key3.hash);
+        assert_eq!("SOMENAME", key1.as_str());
+        assert_eq!("SOMENAME", format!("{}", key1));
+        assert_eq!("SymbolKey(\"SOMENAME\")", format!("{:?}", key1));
+
+        let other = SymbolKey::from("OtherName");
+        assert_ne!(key1, other);
+        assert_ne!(key1.hash, other.hash);
+    }
+
+    #[test]
+    fn test_symbol_key_ordering_by_name() {
+        let c = SymbolKey::from("c");
+        let b = SymbolKey::from("b");
+        let a = SymbolKey::from("a");
+        let mut keys = vec![b.clone(), c.clone(), a.clone()];
+        keys.sort();
+        assert_eq!(vec![a, b, c], keys);
+    }
+
+    #[test]
+    fn test_symbol_names_resolve() {
+        let mut names = SymbolNames::default();
+        assert_ne!(0, names.id);
+        assert_ne!(SymbolNames::default().id, names.id);
+
+        let key1 = names.resolve(&SymbolKey::from("SomeName"));
+        assert_eq!((names.id, 0), (key1.table, key1.slot));
+        let key2 = names.resolve(&SymbolKey::from("somename"));
+        assert_eq!((names.id, 0), (key2.table, key2.slot));
+        assert!(Arc::ptr_eq(&key1.name, &key2.name));
+
+        let other = names.resolve(&SymbolKey::from("Other"));
+        assert_eq!(1, other.slot);
+        assert_ne!(key1, other);
+
+        assert_eq!(Some(0), names.lookup(&SymbolKey::from("SOMENAME")));
+        assert_eq!(Some(1), names.lookup(&other));
+        assert_eq!(None, names.lookup(&SymbolKey::from("Unknown")));
+        assert_eq!(2, names.keys.len());
+    }
+
+    #[test]
+    fn test_symbol_key_equality_across_tables() {
+        let mut names1 = SymbolNames::default();
+        let mut names2 = SymbolNames::default();
+        names2.resolve(&SymbolKey::from("first"));
+
+        let unresolved = SymbolKey::from("name");
+        let key1 = names1.resolve(&unresolved);
+        let key2 = names2.resolve(&unresolved);
+        assert_ne!(key1.slot, key2.slot);
+        assert_eq!(key1, key2);
+        assert_eq!(key1, unresolved);
+        assert_eq!(unresolved, key2);
+        assert_ne!(names1.resolve(&SymbolKey::from("first")), key2);
+    }
+
+    #[test]
+    fn test_slot_map_insert_remove_retain() {
+        let mut names = SymbolNames::default();
+        let keys = (0..5).map(|i| names.resolve(&SymbolKey::from(format!("k{}", i))));
+        let keys = keys.collect::<Vec<SymbolKey>>();
+
+        let mut map = SlotMap::default();
+        assert!(map.is_empty());
+        map.insert(keys[3].clone(), 3);
+        map.insert(keys[1].clone(), 1);
+        map.insert(keys[4].clone(), 4);
+        map.insert(keys[0].clone(), 0);
+        map.insert(keys[1].clone(), 10);
+        assert_eq!(4, map.len());
+        assert_eq!(Some(&10), map.get(1));
+        assert_eq!(None, map.get(2));
+        assert_eq!(None, map.get(1000));
+
+        assert_eq!(Some(3), map.remove(3));
+        assert_eq!(None, map.remove(3));
+        assert_eq!(3, map.len());
+        *map.get_mut(0).unwrap() += 100;
+        assert_eq!(Some(&100), map.get(0));
+        assert_eq!(Some(&4), map.get(4));
+
+        map.retain(|key, _value| key.slot != 1);
+        let mut entries = map.iter().map(|(k, v)| (k.as_str(), *v)).collect::<Vec<_>>();
+        entries.sort();
+        assert_eq!(vec![("K0", 100), ("K4", 4)], entries);
+        assert_eq!(Some(&4), map.get(4));
+        assert!(!map.contains(1));
+
+        map.clear();
+        assert!(map.is_empty());
+        assert!(!map.contains(0));
+        map.insert(keys[2].clone(), 2);
+        assert_eq!(vec![&2], map.values().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_symbols_resolve_names_at_runtime() {
+        let mut syms = Symbols::default();
+        syms.set_var(&VarRef::new("foo", None), Value::Integer(3)).unwrap();
+        let resolved = syms.resolve(&SymbolKey::from("FOO"));
+        assert_eq!(syms.names_id, resolved.table);
+
+        assert!(matches!(syms.load(&resolved), Some(Symbol::Variable(Value::Integer(3)))));
+        assert!(matches!(syms.get_auto("Foo"), Some(Symbol::Variable(Value::Integer(3)))));
+
+        // Keys resolved by a different machine must still find symbols by name.
+        let foreign = SymbolNames::default().resolve(&SymbolKey::from("foo"));
+        assert!(matches!(syms.load(&foreign), Some(Symbol::Variable(Value::Integer(3)))));
+
+        // Looking up unknown names must not assign slots to them.
+        assert!(syms.get_auto("bar").is_none());
+        assert!(syms.unset(&SymbolKey::from("bar")).is_err());
+        assert_eq!(None, syms.names.borrow().lookup(&SymbolKey::from("bar")));
+    }
+
+    #[test]
+    fn test_symbol_map_many_keys() {
+        let mut map = SymbolMap::default();
+        for i in 0..1000 {
+            map.insert(SymbolKey::from(format!("key{}", i)), i);
+        }
+        for i in 0..1000 {
+            assert_eq!(Some(&i), map.get(&SymbolKey::from(format!("KEY{}", i))));
+        }
+        assert!(!map.contains_key(&SymbolKey::from("key1000")));
+    }
 
     #[test]
     fn test_array_unidimensional_ok() {
@@ -716,11 +1792,11 @@ mod tests {
 
         array.assign(&[1], 5.into()).unwrap();
         array.assign(&[4], 8.into()).unwrap();
-        assert_eq!(&Value::Integer(0), array.index(&[0]).unwrap());
-        assert_eq!(&Value::Integer(5), array.index(&[1]).unwrap());
-        assert_eq!(&Value::Integer(0), array.index(&[2]).unwrap());
-        assert_eq!(&Value::Integer(0), array.index(&[3]).unwrap());
-        assert_eq!(&Value::Integer(8), array.index(&[4]).unwrap());
+        assert_eq!(Value::Integer(0), array.index(&[0]).unwrap());
+        assert_eq!(Value::Integer(5), array.index(&[1]).unwrap());
+        assert_eq!(Value::Integer(0), array.index(&[2]).unwrap());
+        assert_eq!(Value::Integer(0), array.index(&[3]).unwrap());
+        assert_eq!(Value::Integer(8), array.index(&[4]).unwrap());
     }
 
     #[test]
@@ -728,19 +1804,22 @@ mod tests {
         let mut array = Array::new(ExprType::Integer, vec![5]);
 
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..4 for dimension 1",
             format!("{}", array.assign(&[-1], Value::Integer(1)).unwrap_err())
         );
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..4 for dimension 1",
             format!("{}", array.index(&[-1]).unwrap_err())
         );
 
         assert_eq!(
-            "Subscript 6 exceeds limit of 5",
+            "Subscript 6 out of range 0..4 for dimension 1",
             format!("{}", array.assign(&[6], Value::Integer(1)).unwrap_err())
         );
-        assert_eq!("Subscript 6 exceeds limit of 5", format!("{}", array.index(&[6]).unwrap_err()));
+        assert_eq!(
+            "Subscript 5 out of range 0..4 for dimension 1",
+            format!("{}", array.index(&[5]).unwrap_err())
+        );
     }
 
     #[test]
@@ -751,12 +1830,12 @@ mod tests {
         array.assign(&[0, 1], 9.1.into()).unwrap();
         array.assign(&[1, 0], 8.1.into()).unwrap();
         array.assign(&[1, 2], 7.1.into()).unwrap();
-        assert_eq!(&Value::Double(0.0), array.index(&[0, 0]).unwrap());
-        assert_eq!(&Value::Double(9.1), array.index(&[0, 1]).unwrap());
-        assert_eq!(&Value::Double(0.0), array.index(&[0, 2]).unwrap());
-        assert_eq!(&Value::Double(8.1), array.index(&[1, 0]).unwrap());
-        assert_eq!(&Value::Double(0.0), array.index(&[1, 1]).unwrap());
-        assert_eq!(&Value::Double(7.1), array.index(&[1, 2]).unwrap());
+        assert_eq!(Value::Double(0.0), array.index(&[0, 0]).unwrap());
+        assert_eq!(Value::Double(9.1), array.index(&[0, 1]).unwrap());
+        assert_eq!(Value::Double(0.0), array.index(&[0, 2]).unwrap());
+        assert_eq!(Value::Double(8.1), array.index(&[1, 0]).unwrap());
+        assert_eq!(Value::Double(0.0), array.index(&[1, 1]).unwrap());
+        assert_eq!(Value::Double(7.1), array.index(&[1, 2]).unwrap());
     }
 
     #[test]
@@ -764,33 +1843,42 @@ mod tests {
         let mut array = Array::new(ExprType::Integer, vec![5, 2]);
 
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..4 for dimension 1",
             format!("{}", array.assign(&[-1, 1], Value::Integer(1)).unwrap_err())
         );
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..4 for dimension 1",
             format!("{}", array.index(&[-1, 1]).unwrap_err())
         );
 
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..1 for dimension 2",
             format!("{}", array.assign(&[1, -1], Value::Integer(1)).unwrap_err())
         );
         assert_eq!(
-            "Subscript -1 cannot be negative",
+            "Subscript -1 out of range 0..1 for dimension 2",
             format!("{}", array.index(&[1, -1]).unwrap_err())
         );
 
         assert_eq!(
-            "Subscript 2 exceeds limit of 2",
+            "Subscript 2 out of range 0..1 for dimension 2",
             format!("{}", array.assign(&[-1, 2], Value::Integer(1)).unwrap_err())
         );
         assert_eq!(
-            "Subscript 2 exceeds limit of 2",
+            "Subscript 2 out of range 0..1 for dimension 2",
             format!("{}", array.index(&[-1, 2]).unwrap_err())
         );
     }
 
+    #[test]
+    fn test_array_subscript_error() {
+        let array = Array::new(ExprType::Integer, vec![5, 2]);
+        assert_eq!(
+            "Subscript NaN out of range 0..1 for dimension 2",
+            format!("{}", array.subscript_error(f64::NAN, 1))
+        );
+    }
+
     #[test]
     fn test_array_multidimensional() {
         let mut array = Array::new(ExprType::Integer, vec![2, 4, 3, 5]);
@@ -804,7 +1892,7 @@ mod tests {
                 for k in 0..3 {
                     for l in 0..5 {
                         array.assign(&[i, j, k, l], n.into()).unwrap();
-                        assert_eq!(&Value::Integer(n), array.index(&[i, j, k, l]).unwrap());
+                        assert_eq!(Value::Integer(n), array.index(&[i, j, k, l]).unwrap());
                         n += 1;
                     }
                 }
@@ -817,7 +1905,7 @@ mod tests {
             for j in 0..4 {
                 for k in 0..3 {
                     for l in 0..5 {
-                        assert_eq!(&Value::Integer(n), array.index(&[i, j, k, l]).unwrap());
+                        assert_eq!(Value::Integer(n), array.index(&[i, j, k, l]).unwrap());
                         n += 1;
                     }
                 }
@@ -825,6 +1913,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_array_default_values() {
+        let array = Array::new(ExprType::Boolean, vec![2]);
+        assert_eq!(Some([false, false].as_slice()), array.as_booleans());
+        let array = Array::new(ExprType::Double, vec![2]);
+        assert_eq!(Some([0.0, 0.0].as_slice()), array.as_doubles());
+        let array = Array::new(ExprType::Integer, vec![2]);
+        assert_eq!(Some([0, 0].as_slice()), array.as_integers());
+        let array = Array::new(ExprType::Text, vec![2]);
+        assert_eq!(Some(["".to_owned(), "".to_owned()].as_slice()), array.as_texts());
+    }
+
+    #[test]
+    fn test_array_slices() {
+        let mut array = Array::new(ExprType::Integer, vec![2, 3]);
+        array.assign(&[1, 0], 7.into()).unwrap();
+        assert_eq!(Some([0, 0, 0, 7, 0, 0].as_slice()), array.as_integers());
+        assert_eq!(None, array.as_booleans());
+        assert_eq!(None, array.as_doubles());
+        assert_eq!(None, array.as_texts());
+
+        array.as_integers_mut().unwrap()[5] = 9;
+        assert_eq!(Value::Integer(9), array.index(&[1, 2]).unwrap());
+        assert!(array.as_doubles_mut().is_none());
+
+        let mut array = Array::new(ExprType::Text, vec![2]);
+        array.as_texts_mut().unwrap()[1].push_str("foo");
+        assert_eq!(Value::Text("foo".to_owned()), array.index(&[1]).unwrap());
+    }
+
+    #[test]
+    fn test_array_empty_values() {
+        let mut array = Array::new(ExprType::Double, vec![3]);
+        array.assign(&[0], 1.5.into()).unwrap();
+        array.assign(&[1], 2.5.into()).unwrap();
+        array.assign(&[1], Value::Empty(ExprType::Double)).unwrap();
+        array.assign(&[2], Value::Empty(ExprType::Double)).unwrap();
+        assert_eq!(Value::Double(1.5), array.index(&[0]).unwrap());
+        assert_eq!(Value::Empty(ExprType::Double), array.index(&[1]).unwrap());
+        assert_eq!(Value::Empty(ExprType::Double), array.index(&[2]).unwrap());
+        assert_eq!(Some([1.5, 0.0, 0.0].as_slice()), array.as_doubles());
+
+        array.assign(&[1], 3.5.into()).unwrap();
+        assert_eq!(Value::Double(3.5), array.index(&[1]).unwrap());
+        assert_eq!(Value::Empty(ExprType::Double), array.index(&[2]).unwrap());
+
+        assert!(array.as_booleans_mut().is_none());
+        assert!(array.as_integers_mut().is_none());
+        assert!(array.as_texts_mut().is_none());
+        assert_eq!(Value::Empty(ExprType::Double), array.index(&[2]).unwrap());
+
+        array.as_doubles_mut().unwrap();
+        assert_eq!(Value::Double(0.0), array.index(&[2]).unwrap());
+    }
+
     #[test]
     fn test_symbols_clear() {
         let mut syms = SymbolsBuilder::default()
@@ -854,6 +1997,129 @@ mod tests {
         assert!(syms.get(&VarRef::new("__GLOBAL_SYSTEM_VAR", None)).unwrap().is_some());
     }
 
+    #[test]
+    fn test_array_size() {
+        let array = Array::new(ExprType::Boolean, vec![10]);
+        assert_eq!(10, array.num_elements());
+        assert_eq!(10, array.size());
+
+        let array = Array::new(ExprType::Double, vec![2, 5]);
+        assert_eq!(10, array.num_elements());
+        assert_eq!(80, array.size());
+
+        let mut array = Array::new(ExprType::Text, vec![3]);
+        assert_eq!(3, array.num_elements());
+        assert_eq!(3 * size_of::<String>(), array.size());
+        array.assign(&[1], "hello".into()).unwrap();
+        assert_eq!(3 * size_of::<String>() + 5, array.size());
+    }
+
+    #[test]
+    fn test_symbols_memory_usage() {
+        let mut syms = SymbolsBuilder::default()
+            .add_callable(SumFunction::new())
+            .add_var("A", Value::Integer(3))
+            .add_var("B", Value::Text("abc".to_owned()))
+            .add_global_var("C", Value::Double(1.0))
+            .build();
+        syms.dim_array(SymbolKey::from("Y"), ExprType::Integer, vec![100]);
+        syms.dim_shared_array(SymbolKey::from("X"), ExprType::Text, vec![2, 3]);
+        match syms.get_mut(&VarRef::new("X", None)).unwrap() {
+            Some(Symbol::Array(array)) => array.assign(&[0, 1], "hello".into()).unwrap(),
+            _ => panic!("X must be an array"),
+        }
+
+        let usage = syms.memory_usage();
+        assert_eq!(3, usage.variables);
+        assert_eq!(4 + (size_of::<String>() + 3) + 8, usage.variables_bytes);
+        assert_eq!(
+            vec![
+                ArrayUsage {
+                    name: SymbolKey::from("X"),
+                    subtype: ExprType::Text,
+                    elements: 6,
+                    bytes: 6 * size_of::<String>() + 5,
+                },
+                ArrayUsage {
+                    name: SymbolKey::from("Y"),
+                    subtype: ExprType::Integer,
+                    elements: 100,
+                    bytes: 400,
+                },
+            ],
+            usage.arrays
+        );
+        assert_eq!(3 + 5, usage.strings_bytes);
+        assert_eq!(usage.variables_bytes + 6 * size_of::<String>() + 5 + 400, usage.total_bytes());
+
+        syms.clear();
+        assert_eq!(MemoryUsage::default(), syms.memory_usage());
+    }
+
+    #[test]
+    fn test_symbols_memory_usage_includes_scopes_and_statics() {
+        let mut syms = SymbolsBuilder::default().add_var("A", Value::Integer(3)).build();
+        syms.enter_scope();
+        syms.dim(SymbolKey::from("B"), ExprType::Integer);
+        syms.dim(SymbolKey::from("S"), ExprType::Double);
+        syms.save_static(&SymbolKey::from("FUNC"), &SymbolKey::from("S"));
+
+        let usage = syms.memory_usage();
+        assert_eq!(3, usage.variables);
+        assert_eq!(4 + 4 + 8, usage.total_bytes());
+    }
+
+    #[test]
+    fn test_symbols_clear_except() {
+        let mut syms = SymbolsBuilder::default()
+            .add_array("SOMEARRAY", ExprType::Integer)
+            .add_array("OTHERARRAY", ExprType::Integer)
+            .add_callable(SumFunction::new())
+            .add_var("SOMEVAR", Value::Boolean(true))
+            .add_var("OTHERVAR", Value::Boolean(true))
+            .add_global_var("GLOBAL_VAR", Value::Integer(43))
+            .build();
+
+        let keep = HashSet::from([SymbolKey::from("somearray"), SymbolKey::from("SomeVar")]);
+        syms.clear_except(&keep);
+        assert!(syms.get(&VarRef::new("SOMEARRAY", None)).unwrap().is_some());
+        assert!(syms.get(&VarRef::new("OTHERARRAY", None)).unwrap().is_none());
+        assert!(syms.get(&VarRef::new("SUM", None)).unwrap().is_some());
+        assert!(syms.get(&VarRef::new("SOMEVAR", None)).unwrap().is_some());
+        assert!(syms.get(&VarRef::new("OTHERVAR", None)).unwrap().is_none());
+        assert!(syms.get(&VarRef::new("GLOBAL_VAR", None)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_symbols_statics() {
+        let mut syms = Symbols::default();
+        let callable = SymbolKey::from("f");
+        let key = SymbolKey::from("n");
+
+        syms.enter_scope();
+        assert!(!syms.link_static(&callable, &key));
+        syms.dim(key.clone(), ExprType::Integer);
+        syms.set_var(&VarRef::new("n", None), Value::Integer(5)).unwrap();
+        syms.save_static(&callable, &key);
+        assert!(syms.locals().is_empty());
+        assert_eq!(&Value::Integer(5), syms.get_var(&VarRef::new("n", None)).unwrap());
+        syms.set_var(&VarRef::new("n", None), Value::Integer(6)).unwrap();
+        syms.leave_scope();
+        assert!(syms.get_auto("n").is_none());
+
+        syms.enter_scope();
+        assert!(syms.get_auto("n").is_none());
+        assert!(syms.link_static(&callable, &key));
+        assert!(!syms.link_static(&SymbolKey::from("g"), &key));
+        assert_eq!(&Value::Integer(6), syms.get_var(&VarRef::new("n", None)).unwrap());
+        syms.leave_scope();
+
+        syms.clear();
+        syms.enter_scope();
+        assert!(!syms.link_static(&callable, &key));
+        syms.leave_scope();
+    }
+
     #[test]
     fn test_symbols_dim_ok() {
         let mut syms = Symbols::default();
@@ -1213,30 +2479,30 @@ mod tests {
     #[test]
     fn test_symbols_apply_new() {
         let mut syms = Symbols::default();
-        syms.assign(&SymbolKey::from("a"), Value::Integer(5));
+        syms.assign(&SymbolKey::from("a"), Value::Integer(5)).unwrap();
         check_var(&syms, "a", Value::Integer(5));
     }
 
     #[test]
     fn test_symbols_apply_existing() {
         let mut syms = SymbolsBuilder::default().add_var("A", Value::Double(1.0)).build();
-        syms.assign(&SymbolKey::from("a"), Value::Double(2.0));
+        syms.assign(&SymbolKey::from("a"), Value::Double(2.0)).unwrap();
         check_var(&syms, "a", Value::Double(2.0));
     }
 
     #[test]
     fn test_symbols_get_var_apply_case_insensitivity() {
         let mut syms = Symbols::default();
-        syms.assign(&SymbolKey::from("SomeName"), Value::Integer(6));
+        syms.assign(&SymbolKey::from("SomeName"), Value::Integer(6)).unwrap();
         assert_eq!(Value::Integer(6), *syms.get_var(&VarRef::new("somename", None)).unwrap());
     }
 
     #[test]
     fn test_symbols_get_var_apply_replace_value() {
         let mut syms = Symbols::default();
-        syms.assign(&SymbolKey::from("the_var"), Value::Integer(100));
+        syms.assign(&SymbolKey::from("the_var"), Value::Integer(100)).unwrap();
         assert_eq!(Value::Integer(100), *syms.get_var(&VarRef::new("the_var", None)).unwrap());
-        syms.assign(&SymbolKey::from("the_var"), Value::Integer(200));
+        syms.assign(&SymbolKey::from("the_var"), Value::Integer(200)).unwrap();
         assert_eq!(Value::Integer(200), *syms.get_var(&VarRef::new("the_var", None)).unwrap());
     }
 
@@ -1432,10 +2698,64 @@ mod tests {
         assert_eq!(2, syms.callables().len());
     }
 
+    #[test]
+    fn test_symbols_accounts_strings() {
+        let mut syms =
+            SymbolsBuilder::default().add_var("A", Value::Text("abc".to_owned())).build();
+        assert_eq!(3, syms.allocated());
+        syms.set_memory_limit(Some(10));
+
+        syms.set_var(&VarRef::new("b", None), Value::Text("12345".to_owned())).unwrap();
+        assert_eq!(8, syms.allocated());
+        assert_eq!(
+            "Out of memory: limit is 10 bytes",
+            format!("{}", syms.set_var(&VarRef::new("a", None), "123456".into()).unwrap_err())
+        );
+        assert_eq!(8, syms.allocated());
+        syms.assign(&SymbolKey::from("a"), Value::Text("123456".to_owned())).unwrap_err();
+        syms.assign(&SymbolKey::from("a"), Value::Text("x".to_owned())).unwrap();
+        assert_eq!(6, syms.allocated());
+
+        syms.unset(&SymbolKey::from("b")).unwrap();
+        assert_eq!(1, syms.allocated());
+    }
+
     #[test]
     fn test_symbols_unset_undefined() {
         let mut syms = SymbolsBuilder::default().add_var("SOMETHING", Value::Integer(3)).build();
         syms.unset(&SymbolKey::from("FOO")).unwrap_err();
         assert_eq!(1, syms.locals().len());
     }
+
+    #[test]
+    fn test_callable_metadata_usage() {
+        let md = CallableMetadataBuilder::new("CMD").test_build();
+        assert_eq!("CMD", md.usage());
+
+        let md = CallableMetadataBuilder::new("CMD")
+            .with_syntax(&[(
+                &[SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )])
+            .test_build();
+        assert_eq!("CMD n%", md.usage());
+
+        let md = CallableMetadataBuilder::new("FN").with_return_type(ExprType::Text).test_build();
+        assert_eq!("FN$", md.usage());
+
+        let md = CallableMetadataBuilder::new("FN")
+            .with_return_type(ExprType::Double)
+            .with_syntax(&[(
+                &[SingularArgSyntax::RequiredValue(
+                    RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Integer },
+                    ArgSepSyntax::End,
+                )],
+                None,
+            )])
+            .test_build();
+        assert_eq!("FN#(n%)", md.usage());
+    }
 }
@@ -20,17 +20,59 @@ use crate::compiler::{
     ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredRefSyntax, RequiredValueSyntax,
     SingularArgSyntax,
 };
-use crate::exec::{Error, Machine, Result, Scope, ValueTag};
+use crate::exec::{Error, Machine, Result, Scope, StopReason, ValueTag};
 use crate::syms::{
     Array, Callable, CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey, Symbols,
 };
 use crate::value;
 use async_trait::async_trait;
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::io;
 use std::rc::Rc;
+use std::time::Duration;
+
+/// Advances a fake clock by the given number of seconds.
+pub struct AdvanceCommand {
+    metadata: CallableMetadata,
+    now: Rc<Cell<Duration>>,
+}
+
+impl AdvanceCommand {
+    /// Creates a new command that advances the fake clock held in `now`.
+    pub fn new(now: Rc<Cell<Duration>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ADVANCE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("seconds"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .test_build(),
+            now,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for AdvanceCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        assert_eq!(1, scope.nargs());
+        let seconds = scope.pop_double();
+        self.now.set(self.now.get() + Duration::from_secs_f64(seconds));
+        Ok(())
+    }
+}
 
 /// Returns a constant value.
 pub struct ArglessFunction {
@@ -311,7 +353,7 @@ impl Callable for InCommand {
 
     async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
         debug_assert_eq!(1, scope.nargs());
-        let (vname, vtype) = scope.pop_varref();
+        let (vname, vtype, pos) = scope.pop_varref_with_pos();
 
         let mut data = self.data.borrow_mut();
         let raw_value = data.next().unwrap();
@@ -321,7 +363,10 @@ impl Callable for InCommand {
             ExprType::Text => Value::Text(raw_value.to_string()),
             _ => unreachable!("Unsupported target type"),
         };
-        machine.get_mut_symbols().assign(&vname, value);
+        machine
+            .get_mut_symbols()
+            .assign(&vname, value)
+            .map_err(|e| Error::EvalError(pos, e.message))?;
         Ok(())
     }
 }
@@ -459,6 +504,44 @@ impl Callable for OutfFunction {
     }
 }
 
+/// Asks the machine to stop the program with the exit code given in an argument.
+pub(crate) struct StopCommand {
+    metadata: CallableMetadata,
+}
+
+impl StopCommand {
+    pub(crate) fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("STOP")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("code"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .test_build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for StopCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        assert_eq!(1, scope.nargs());
+        let code = scope.pop_integer();
+        machine.request_stop(StopReason::Exited(code as u8));
+        Ok(())
+    }
+}
+
 /// Sums a collection of integers of arbitrary length.
 pub struct SumFunction {
     metadata: CallableMetadata,
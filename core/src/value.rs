@@ -47,6 +47,11 @@ impl Value {
                 Ok(Value::Integer(double_to_integer(d)?))
             }
             (Some(ExprType::Double), Value::Integer(i)) => Ok(Value::Double(integer_to_double(i))),
+            (Some(etype @ (ExprType::Double | ExprType::Integer)), Value::Empty(vtype))
+                if vtype.is_numerical() =>
+            {
+                Ok(Value::Empty(etype))
+            }
             (_, v) => Ok(v),
         }
     }
@@ -67,35 +72,27 @@ pub(crate) fn integer_to_double(i: i32) -> f64 {
     i as f64
 }
 
+/// Validates the number of bits `rhs` to shift an integer by with the `op` operator.
+fn shift_bits(rhs: i32, op: &str) -> Result<u32> {
+    match u32::try_from(rhs) {
+        Ok(n) if n < i32::BITS => Ok(n),
+        _ => Err(Error::new(format!(
+            "Number of bits to {} ({}) must be between 0 and {}",
+            op,
+            rhs,
+            i32::BITS - 1
+        ))),
+    }
+}
+
 /// Performs a left shift.
 pub(crate) fn bitwise_shl(lhs: i32, rhs: i32) -> Result<i32> {
-    let bits = match u32::try_from(rhs) {
-        Ok(n) => n,
-        Err(_) => {
-            return Err(Error::new(format!("Number of bits to << ({}) must be positive", rhs)))
-        }
-    };
-
-    match lhs.checked_shl(bits) {
-        Some(i) => Ok(i),
-        None => Ok(0),
-    }
+    Ok(lhs << shift_bits(rhs, "<<")?)
 }
 
-/// Performs a right shift.
+/// Performs a right shift, which preserves the sign of `lhs`.
 pub(crate) fn bitwise_shr(lhs: i32, rhs: i32) -> Result<i32> {
-    let bits = match u32::try_from(rhs) {
-        Ok(n) => n,
-        Err(_) => {
-            return Err(Error::new(format!("Number of bits to >> ({}) must be positive", rhs)))
-        }
-    };
-
-    match lhs.checked_shr(bits) {
-        Some(i) => Ok(i),
-        None if lhs < 0 => Ok(-1),
-        None => Ok(0),
-    }
+    Ok(lhs >> shift_bits(rhs, ">>")?)
 }
 
 /// Performs an arithmetic addition of integers.
@@ -235,13 +232,21 @@ mod tests {
 
         assert_eq!(0xe0000000u32 as i32, bitwise_shl(0xf0000000u32 as i32, 1).unwrap());
         assert_eq!(0, bitwise_shl(0x80000000u32 as i32, 1).unwrap());
-        assert_eq!(0, bitwise_shl(1, 32).unwrap());
-        assert_eq!(0, bitwise_shl(1, 64).unwrap());
+        assert_eq!(i32::MIN, bitwise_shl(-1, 31).unwrap());
+        assert_eq!(-2, bitwise_shl(-1, 1).unwrap());
 
         assert_eq!(
-            "Number of bits to << (-1) must be positive",
+            "Number of bits to << (-1) must be between 0 and 31",
             format!("{}", bitwise_shl(3, -1).unwrap_err())
         );
+        assert_eq!(
+            "Number of bits to << (32) must be between 0 and 31",
+            format!("{}", bitwise_shl(1, 32).unwrap_err())
+        );
+        assert_eq!(
+            "Number of bits to << (64) must be between 0 and 31",
+            format!("{}", bitwise_shl(1, 64).unwrap_err())
+        );
     }
 
     #[test]
@@ -255,15 +260,18 @@ mod tests {
         assert_eq!(0xf0000000u32 as i32, bitwise_shr(0xe0000000u32 as i32, 1).unwrap());
         assert_eq!(0xc0000000u32 as i32, bitwise_shr(0x80000000u32 as i32, 1).unwrap());
         assert_eq!(0x38000000, bitwise_shr(0x70000000, 1).unwrap());
-        assert_eq!(0, bitwise_shr(0x70000000u32 as i32, 32).unwrap());
-        assert_eq!(0, bitwise_shr(0x70000000u32 as i32, 32).unwrap());
-        assert_eq!(-1, bitwise_shr(0x80000000u32 as i32, 32).unwrap());
-        assert_eq!(-1, bitwise_shr(0x80000000u32 as i32, 64).unwrap());
+        assert_eq!(-1, bitwise_shr(i32::MIN, 31).unwrap());
+        assert_eq!(-1, bitwise_shr(-1, 31).unwrap());
+        assert_eq!(0, bitwise_shr(i32::MAX, 31).unwrap());
 
         assert_eq!(
-            "Number of bits to >> (-1) must be positive",
+            "Number of bits to >> (-1) must be between 0 and 31",
             format!("{}", bitwise_shr(3, -1).unwrap_err())
         );
+        assert_eq!(
+            "Number of bits to >> (32) must be between 0 and 31",
+            format!("{}", bitwise_shr(0x80000000u32 as i32, 32).unwrap_err())
+        );
     }
 
     #[test]
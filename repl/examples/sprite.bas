@@ -0,0 +1,44 @@
+' EndBASIC
+' Copyright 2025 Julio Merino
+'
+' Licensed under the Apache License, Version 2.0 (the "License"); you may not
+' use this file except in compliance with the License.  You may obtain a copy
+' of the License at:
+'
+'     http://www.apache.org/licenses/LICENSE-2.0
+'
+' Unless required by applicable law or agreed to in writing, software
+' distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+' WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+' License for the specific language governing permissions and limitations
+' under the License.
+
+'
+' Draws a sprite whose bitmap was embedded into the program with BINTODATA.
+'
+' The DATA statement below was generated from an 8-byte binary file in which
+' every byte represents one row of an 8x8 monochrome sprite by running:
+'
+'     BINTODATA "SMILEY.BIN", "SMILEY.BAS"
+'
+
+' Generated by BINTODATA from MEMORY:SMILEY.BIN (8 bytes)
+DATA 60, 66, 165, 129, 165, 153, 66, 60
+
+DIM rows(8)
+FOR i = 0 TO 7
+    READ b
+    rows(i) = b
+NEXT
+
+FOR i = 0 TO 7
+    line$ = ""
+    FOR j = 7 TO 0 STEP -1
+        IF ((rows(i) >> j) AND 1) = 1 THEN
+            line$ = line$ + "##"
+        ELSE
+            line$ = line$ + "  "
+        END IF
+    NEXT
+    PRINT line$
+NEXT
@@ -0,0 +1,465 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Automatic saving of the stored program to recover from unexpected exits.
+
+use async_trait::async_trait;
+use endbasic_core::compiler::{AnyValueSyntax, ArgSepSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Error, Machine, Result, Scope, ValueTag};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_std::console::Console;
+use endbasic_std::program::Program;
+use endbasic_std::storage::Storage;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use time::{Duration, OffsetDateTime};
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Interpreter";
+
+/// Path to the file where the stored program is automatically saved to.
+pub const AUTOSAVE_FILE: &str = "LOCAL:/.AUTOSAVE.BAS";
+
+/// Default interval between periodic saves while the program is being edited.
+pub const DEFAULT_INTERVAL: Duration = Duration::seconds(30);
+
+/// Type of the function used to query the current time.
+type NowFn = Box<dyn Fn() -> OffsetDateTime>;
+
+/// Keeps a recovery copy of the stored program in `AUTOSAVE_FILE` while it has unsaved changes.
+///
+/// The recovery file is written after every edit operation and periodically while the program is
+/// being edited, and it is deleted once the program is saved explicitly.  Its presence on startup
+/// therefore indicates that the previous session ended with unsaved changes.  All operations are
+/// quiet: they never print anything to the console.
+pub struct Autosave {
+    storage: Rc<RefCell<Storage>>,
+    enabled: bool,
+    interval: Duration,
+    now_fn: NowFn,
+    last_save: OffsetDateTime,
+    last_state: (String, bool),
+    wrote: bool,
+}
+
+impl Autosave {
+    /// Creates a new autosave facility that writes to `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Self {
+        Self::with_now_fn(storage, Box::from(OffsetDateTime::now_utc))
+    }
+
+    /// Same as `new` but uses `now_fn` to query the current time.
+    fn with_now_fn(storage: Rc<RefCell<Storage>>, now_fn: NowFn) -> Self {
+        let last_save = now_fn();
+        Self {
+            storage,
+            enabled: true,
+            interval: DEFAULT_INTERVAL,
+            now_fn,
+            last_save,
+            last_state: (String::new(), false),
+            wrote: false,
+        }
+    }
+
+    /// Enables or disables automatic saves.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Sets the `interval` between periodic saves and enables automatic saves.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.enabled = true;
+        self.interval = interval;
+    }
+
+    /// Returns true if enough time has passed since the last save for a periodic save to happen.
+    pub fn is_due(&self) -> bool {
+        self.enabled && (self.now_fn)() - self.last_save >= self.interval
+    }
+
+    /// Records the current state of the `program` in the recovery file.
+    ///
+    /// A program with unsaved changes is written to the recovery file.  A clean program causes the
+    /// recovery file to be deleted if it was written by this session, which preserves the changes
+    /// left behind by a previous session until they are replaced, and unless the program was
+    /// loaded from the recovery file itself.  Nothing happens if the program did not change since
+    /// the last call.
+    pub async fn save(&mut self, program: &dyn Program) -> io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        self.last_save = (self.now_fn)();
+
+        let state = (program.text(), program.is_dirty());
+        if state == self.last_state {
+            return Ok(());
+        }
+
+        let mut storage = self.storage.borrow_mut();
+        if state.1 {
+            storage.put(AUTOSAVE_FILE, state.0.as_bytes()).await?;
+            self.wrote = true;
+        } else if self.wrote && program.name() != Some(AUTOSAVE_FILE) {
+            match storage.delete(AUTOSAVE_FILE).await {
+                Ok(()) => (),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+                Err(e) => return Err(e),
+            }
+            self.wrote = false;
+        }
+        self.last_state = state;
+        Ok(())
+    }
+}
+
+/// Tells the user about a recovery file left behind by a previous session, if any.
+///
+/// Problems querying the recovery file are ignored because they mean there is nothing to recover.
+pub async fn print_recovery_notice(
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+) -> io::Result<()> {
+    if storage.borrow().metadata(AUTOSAVE_FILE).await.is_err() {
+        return Ok(());
+    }
+
+    let mut console = console.borrow_mut();
+    console.print("The previous session ended with unsaved changes to the stored program.")?;
+    console.print(&format!("Use LOAD \"{}\" to recover them.", AUTOSAVE_FILE))?;
+    console.print("")?;
+    Ok(())
+}
+
+/// The `AUTOSAVE` command.
+pub struct AutosaveCommand {
+    metadata: CallableMetadata,
+    autosave: Rc<RefCell<Autosave>>,
+}
+
+impl AutosaveCommand {
+    /// Creates a new `AUTOSAVE` command that controls `autosave`.
+    pub fn new(autosave: Rc<RefCell<Autosave>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("AUTOSAVE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("mode"), allow_missing: false },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Controls the automatic saving of the stored program.
+While enabled, the stored program is saved to LOCAL:/.AUTOSAVE.BAS after every command that \
+modifies it and periodically while it is being edited, as long as it has unsaved changes.  The \
+file is deleted once the program is saved explicitly, and if it is present when the interpreter \
+starts, you are told how to LOAD it to recover your work.
+mode can be \"ON\" or TRUE to enable automatic saves, \"OFF\" or FALSE to disable them, or a \
+positive number of seconds to enable them and set the interval between periodic saves.  \
+Automatic saves are enabled by default every 30 seconds.",
+                )
+                .build(),
+            autosave,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for AutosaveCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let mut autosave = self.autosave.borrow_mut();
+        match scope.pop_value_tag() {
+            ValueTag::Boolean => autosave.set_enabled(scope.pop_boolean()),
+            ValueTag::Integer => {
+                let (seconds, pos) = scope.pop_integer_with_pos();
+                if seconds <= 0 {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        "Autosave interval must be positive".to_owned(),
+                    ));
+                }
+                autosave.set_interval(Duration::seconds(i64::from(seconds)));
+            }
+            ValueTag::Text => {
+                let (mode, pos) = scope.pop_string_with_pos();
+                match mode.to_ascii_uppercase().as_str() {
+                    "ON" => autosave.set_enabled(true),
+                    "OFF" => autosave.set_enabled(false),
+                    _ => {
+                        return Err(Error::SyntaxError(
+                            pos,
+                            format!("Invalid autosave mode '{}'; must be ON or OFF", mode),
+                        ))
+                    }
+                }
+            }
+            ValueTag::Double => {
+                let (_seconds, pos) = scope.pop_double_with_pos();
+                return Err(Error::SyntaxError(
+                    pos,
+                    "Autosave interval must be an INTEGER".to_owned(),
+                ));
+            }
+            ValueTag::Missing => {
+                unreachable!("Missing expressions aren't allowed in AUTOSAVE");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::Editor;
+    use endbasic_std::console::{CharsXY, Key};
+    use endbasic_std::testutils::*;
+    use futures_lite::future::block_on;
+    use std::cell::Cell;
+
+    /// Creates a storage with a `LOCAL` drive backed by memory.
+    fn storage_with_local() -> Rc<RefCell<Storage>> {
+        let storage = Rc::from(RefCell::from(Storage::default()));
+        block_on(storage.borrow_mut().mount("local", "memory://", false)).unwrap();
+        storage
+    }
+
+    /// Creates a fake clock that only advances when the returned cell is updated, along with the
+    /// function to query it.
+    fn fake_clock() -> (Rc<Cell<OffsetDateTime>>, NowFn) {
+        let now = Rc::from(Cell::new(OffsetDateTime::UNIX_EPOCH));
+        let now_fn = {
+            let now = now.clone();
+            Box::from(move || now.get())
+        };
+        (now, now_fn)
+    }
+
+    /// Returns the contents of the recovery file in `storage`, if any.
+    fn recovery_file(storage: &Rc<RefCell<Storage>>) -> Option<String> {
+        match block_on(storage.borrow().get(AUTOSAVE_FILE)) {
+            Ok(content) => Some(String::from_utf8(content).unwrap()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn test_autosave_is_due() {
+        let (now, now_fn) = fake_clock();
+        let mut autosave = Autosave::with_now_fn(storage_with_local(), now_fn);
+        assert!(!autosave.is_due());
+
+        now.set(now.get() + Duration::seconds(29));
+        assert!(!autosave.is_due());
+        now.set(now.get() + Duration::seconds(1));
+        assert!(autosave.is_due());
+
+        block_on(autosave.save(&RecordedProgram::default())).unwrap();
+        assert!(!autosave.is_due());
+
+        autosave.set_interval(Duration::seconds(5));
+        now.set(now.get() + Duration::seconds(5));
+        assert!(autosave.is_due());
+
+        autosave.set_enabled(false);
+        assert!(!autosave.is_due());
+    }
+
+    #[test]
+    fn test_autosave_only_writes_changes() {
+        let storage = storage_with_local();
+        let (_now, now_fn) = fake_clock();
+        let mut autosave = Autosave::with_now_fn(storage.clone(), now_fn);
+        let mut program = RecordedProgram::default();
+
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(None, recovery_file(&storage));
+
+        program.set_text("PRINT 1\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("PRINT 1\n"), recovery_file(&storage).as_deref());
+
+        // Remove the file behind the back of the autosave facility to detect if it gets written
+        // again for an unchanged program.
+        block_on(storage.borrow_mut().delete(AUTOSAVE_FILE)).unwrap();
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(None, recovery_file(&storage));
+
+        program.set_text("PRINT 2\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("PRINT 2\n"), recovery_file(&storage).as_deref());
+    }
+
+    #[test]
+    fn test_autosave_deletes_after_explicit_save() {
+        let storage = storage_with_local();
+        let (_now, now_fn) = fake_clock();
+        let mut autosave = Autosave::with_now_fn(storage.clone(), now_fn);
+        let mut program = RecordedProgram::default();
+
+        program.set_text("PRINT 1\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("PRINT 1\n"), recovery_file(&storage).as_deref());
+
+        program.set_name("LOCAL:/PROG.BAS");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(None, recovery_file(&storage));
+    }
+
+    #[test]
+    fn test_autosave_keeps_previous_session() {
+        let storage = storage_with_local();
+        block_on(storage.borrow_mut().put(AUTOSAVE_FILE, b"OLD\n")).unwrap();
+
+        let (_now, now_fn) = fake_clock();
+        let mut autosave = Autosave::with_now_fn(storage.clone(), now_fn);
+        let mut program = RecordedProgram::default();
+
+        program.load(Some("LOCAL:/OTHER.BAS"), "PRINT 1\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("OLD\n"), recovery_file(&storage).as_deref());
+
+        program.load(Some(AUTOSAVE_FILE), "OLD\n");
+        program.set_text("NEW\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("NEW\n"), recovery_file(&storage).as_deref());
+
+        program.set_name(AUTOSAVE_FILE);
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("NEW\n"), recovery_file(&storage).as_deref());
+    }
+
+    #[test]
+    fn test_autosave_disabled() {
+        let storage = storage_with_local();
+        let (_now, now_fn) = fake_clock();
+        let mut autosave = Autosave::with_now_fn(storage.clone(), now_fn);
+        let mut program = RecordedProgram::default();
+
+        autosave.set_enabled(false);
+        program.set_text("PRINT 1\n");
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(None, recovery_file(&storage));
+
+        autosave.set_enabled(true);
+        block_on(autosave.save(&program)).unwrap();
+        assert_eq!(Some("PRINT 1\n"), recovery_file(&storage).as_deref());
+    }
+
+    #[test]
+    fn test_autosave_periodically_while_editing() {
+        let storage = storage_with_local();
+        let (now, now_fn) = fake_clock();
+        let autosave = Rc::from(RefCell::from(Autosave::with_now_fn(storage.clone(), now_fn)));
+        let mut editor = Editor::default().with_autosave(autosave);
+
+        let mut console = MockConsole::default();
+        console.set_size_chars(CharsXY::new(40, 10));
+        console.add_input_chars("ab");
+        console.add_input_keys(&[Key::Escape]);
+
+        // The clock does not advance while editing, so no periodic saves happen.
+        block_on(editor.edit(&mut console)).unwrap();
+        assert_eq!(None, recovery_file(&storage));
+
+        // Once the interval expires, the next key press triggers a save.
+        now.set(now.get() + DEFAULT_INTERVAL);
+        console.add_input_chars("c");
+        console.add_input_keys(&[Key::Escape]);
+        block_on(editor.edit(&mut console)).unwrap();
+        assert_eq!(Some("ab\n"), recovery_file(&storage).as_deref());
+    }
+
+    #[test]
+    fn test_print_recovery_notice() {
+        let mut t = Tester::default();
+        let (console, storage) = (t.get_console(), t.get_storage());
+        block_on(storage.borrow_mut().mount("local", "memory://", false)).unwrap();
+
+        block_on(print_recovery_notice(console.clone(), storage.clone())).unwrap();
+        t.run("").check();
+
+        let mut t = Tester::default();
+        let (console, storage) = (t.get_console(), t.get_storage());
+        block_on(storage.borrow_mut().mount("local", "memory://", false)).unwrap();
+        block_on(storage.borrow_mut().put(AUTOSAVE_FILE, b"PRINT 1\n")).unwrap();
+
+        block_on(print_recovery_notice(console, storage)).unwrap();
+        t.run("")
+            .expect_prints([
+                "The previous session ended with unsaved changes to the stored program.",
+                "Use LOAD \"LOCAL:/.AUTOSAVE.BAS\" to recover them.",
+                "",
+            ])
+            .expect_file("LOCAL:/.AUTOSAVE.BAS", "PRINT 1\n")
+            .check();
+    }
+
+    #[test]
+    fn test_print_recovery_notice_without_local_drive() {
+        let mut t = Tester::default();
+        block_on(print_recovery_notice(t.get_console(), t.get_storage())).unwrap();
+        t.run("").check();
+    }
+
+    #[test]
+    fn test_autosave_command() {
+        let storage = storage_with_local();
+        let (now, now_fn) = fake_clock();
+        let autosave = Rc::from(RefCell::from(Autosave::with_now_fn(storage, now_fn)));
+        let mut t = Tester::default().add_callable(AutosaveCommand::new(autosave.clone()));
+
+        t.run(r#"AUTOSAVE "off""#).check();
+        now.set(now.get() + DEFAULT_INTERVAL);
+        assert!(!autosave.borrow().is_due());
+
+        t.run("AUTOSAVE TRUE").check();
+        assert!(autosave.borrow().is_due());
+
+        t.run("AUTOSAVE FALSE").check();
+        assert!(!autosave.borrow().is_due());
+
+        t.run(r#"AUTOSAVE "ON""#).check();
+        assert!(autosave.borrow().is_due());
+
+        t.run("AUTOSAVE 60").check();
+        assert!(!autosave.borrow().is_due());
+        now.set(now.get() + DEFAULT_INTERVAL);
+        assert!(autosave.borrow().is_due());
+    }
+
+    #[test]
+    fn test_autosave_command_errors() {
+        let autosave = Rc::from(RefCell::from(Autosave::new(storage_with_local())));
+        let mut t = Tester::default().add_callable(AutosaveCommand::new(autosave));
+
+        t.run("AUTOSAVE").expect_compilation_err("1:1: AUTOSAVE expected mode").check();
+        t.run("AUTOSAVE 0").expect_err("1:10: Autosave interval must be positive").check();
+        t.run("AUTOSAVE 1.5").expect_err("1:10: Autosave interval must be an INTEGER").check();
+        t.run(r#"AUTOSAVE "x""#)
+            .expect_err("1:10: Invalid autosave mode 'x'; must be ON or OFF")
+            .check();
+    }
+}
@@ -0,0 +1,190 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Execution budget for programs that run without the user explicitly asking for them.
+
+use async_trait::async_trait;
+use endbasic_core::exec::Watchdog;
+use endbasic_std::console::{read_line, Console};
+use endbasic_std::strings::parse_boolean;
+use std::cell::RefCell;
+use std::rc::Rc;
+use time::{Duration, OffsetDateTime};
+
+/// Default number of execution steps (loop iterations and `GOSUB` returns) that an auto-run
+/// program can take before the user is asked whether to continue.
+pub const DEFAULT_MAX_STEPS: usize = 1_000_000;
+
+/// Default wall time that an auto-run program can take before the user is asked whether to
+/// continue.
+pub const DEFAULT_MAX_TIME: Duration = Duration::seconds(10);
+
+/// Prompt shown to the user when a program exceeds its budget.
+const PROMPT: &str = "This shared program is still running — continue? (y/n) ";
+
+/// Type of the function used to query the current time.
+type NowFn = Box<dyn Fn() -> OffsetDateTime>;
+
+/// Watchdog that pauses a program once it exceeds a number of execution steps or an amount of wall
+/// time, and asks the user whether to continue running it with a fresh budget or to stop it.
+pub struct AutorunBudget {
+    console: Rc<RefCell<dyn Console>>,
+    max_steps: usize,
+    max_time: Duration,
+    now_fn: NowFn,
+    steps: usize,
+    started: OffsetDateTime,
+}
+
+impl AutorunBudget {
+    /// Creates a new budget that allows `max_steps` execution steps and `max_time` wall time, and
+    /// that interacts with the user via `console` when either is exceeded.
+    pub fn new(console: Rc<RefCell<dyn Console>>, max_steps: usize, max_time: Duration) -> Self {
+        Self::with_now_fn(console, max_steps, max_time, Box::from(OffsetDateTime::now_utc))
+    }
+
+    /// Same as `new` but uses `now_fn` to query the current time.
+    fn with_now_fn(
+        console: Rc<RefCell<dyn Console>>,
+        max_steps: usize,
+        max_time: Duration,
+        now_fn: NowFn,
+    ) -> Self {
+        let started = now_fn();
+        Self { console, max_steps, max_time, now_fn, steps: 0, started }
+    }
+
+    /// Asks the user whether to continue running the program and returns their answer.  Any
+    /// problem reading the answer is treated as a request to stop.
+    async fn ask_continue(&mut self) -> bool {
+        let mut console = self.console.borrow_mut();
+        loop {
+            let answer = match read_line(&mut *console, PROMPT, "", None).await {
+                Ok(answer) => answer,
+                Err(_) => return false,
+            };
+            match parse_boolean(answer.trim()) {
+                Ok(answer) => return answer,
+                Err(_) => {
+                    if console.print("Please answer y or n").is_err() {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Watchdog for AutorunBudget {
+    async fn should_stop(&mut self) -> bool {
+        self.steps += 1;
+        if self.steps < self.max_steps && (self.now_fn)() - self.started < self.max_time {
+            return false;
+        }
+
+        if !self.ask_continue().await {
+            return true;
+        }
+        self.steps = 0;
+        self.started = (self.now_fn)();
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endbasic_core::exec::StopReason;
+    use endbasic_std::console::Key;
+    use endbasic_std::testutils::*;
+    use std::cell::Cell;
+
+    /// Counts the number of times the budget prompt appears in `checker`'s captured output.
+    fn count_prompts(checker: &mut Checker) -> usize {
+        flatten_output(checker.take_captured_out()).matches("still running").count()
+    }
+
+    #[test]
+    fn test_autorun_budget_continue() {
+        let mut t = Tester::default();
+        let budget = AutorunBudget::new(t.get_console(), 10, DEFAULT_MAX_TIME);
+        t.get_machine().set_watchdog(Some(Box::from(budget)));
+        t.get_console().borrow_mut().set_interactive(true);
+        t.get_console().borrow_mut().add_input_chars("y");
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+        t.get_console().borrow_mut().add_input_chars("yes");
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+
+        let mut c = t.run("i = 0: WHILE i < 25: i = i + 1: WEND");
+        assert_eq!(2, count_prompts(&mut c));
+        c.expect_var("i", 25).check();
+    }
+
+    #[test]
+    fn test_autorun_budget_stop() {
+        let mut t = Tester::default();
+        let budget = AutorunBudget::new(t.get_console(), 10, DEFAULT_MAX_TIME);
+        t.get_machine().set_watchdog(Some(Box::from(budget)));
+        t.get_console().borrow_mut().set_interactive(true);
+        t.get_console().borrow_mut().add_input_chars("maybe");
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+        t.get_console().borrow_mut().add_input_chars("n");
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+
+        let mut c = t.run("i = 0: WHILE i < 25: i = i + 1: WEND");
+        let output = flatten_output(c.take_captured_out());
+        assert_eq!(2, output.matches("still running").count());
+        assert!(output.contains("Please answer y or n"));
+        c.expect_ok(StopReason::Break).expect_var("i", 10).check();
+    }
+
+    #[test]
+    fn test_autorun_budget_stop_on_eof() {
+        let mut t = Tester::default();
+        let budget = AutorunBudget::new(t.get_console(), 3, DEFAULT_MAX_TIME);
+        t.get_machine().set_watchdog(Some(Box::from(budget)));
+        t.get_console().borrow_mut().set_interactive(true);
+
+        let mut c = t.run("i = 0: WHILE i < 25: i = i + 1: WEND");
+        assert_eq!(1, count_prompts(&mut c));
+        c.expect_ok(StopReason::Break).expect_var("i", 3).check();
+    }
+
+    #[test]
+    fn test_autorun_budget_time_limit() {
+        let now = Rc::from(Cell::new(OffsetDateTime::UNIX_EPOCH));
+        let now_fn = {
+            let now = now.clone();
+            Box::from(move || {
+                let current = now.get();
+                now.set(current + Duration::seconds(1));
+                current
+            })
+        };
+
+        let mut t = Tester::default();
+        let budget =
+            AutorunBudget::with_now_fn(t.get_console(), usize::MAX, Duration::seconds(5), now_fn);
+        t.get_machine().set_watchdog(Some(Box::from(budget)));
+        t.get_console().borrow_mut().set_interactive(true);
+        t.get_console().borrow_mut().add_input_chars("n");
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+
+        let mut c = t.run("i = 0: WHILE i < 25: i = i + 1: WEND");
+        assert_eq!(1, count_prompts(&mut c));
+        c.expect_ok(StopReason::Break).expect_var("i", 5).check();
+    }
+}
@@ -0,0 +1,256 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Completion of command names and file names in the interactive interpreter.
+
+use async_trait::async_trait;
+use endbasic_core::exec::Machine;
+use endbasic_std::console::{Completer, Completion};
+use endbasic_std::storage::Storage;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// Commands whose first argument is a file name and that get file name completions for it.
+const FILE_COMMANDS: &[&str] = &["DEL", "DIR", "LOAD", "RUN", "SAVE"];
+
+/// Returns true if `candidate` starts with `prefix`, ignoring case.
+fn has_prefix(candidate: &str, prefix: &str) -> bool {
+    candidate.to_ascii_uppercase().starts_with(&prefix.to_ascii_uppercase())
+}
+
+/// Completer for the lines entered in the REPL.
+///
+/// The first word of a statement is completed against the names of the commands registered in
+/// the machine, and the contents of a string literal that follows one of the `FILE_COMMANDS` are
+/// completed against the mounted drives and the files in the storage.
+pub struct ReplCompleter {
+    commands: Vec<String>,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl ReplCompleter {
+    /// Creates a new completer that offers the commands registered in `machine` and the files
+    /// available in `storage`.
+    pub fn new(machine: &Machine, storage: Rc<RefCell<Storage>>) -> Self {
+        let mut commands = machine
+            .get_symbols()
+            .callables()
+            .values()
+            .map(|c| c.metadata())
+            .filter(|md| !md.is_function() && md.name().starts_with(|c: char| c.is_alphabetic()))
+            .map(|md| md.name().to_owned())
+            .collect::<Vec<String>>();
+        commands.sort();
+        Self { commands, storage }
+    }
+
+    /// Returns the command names that start with `prefix`.
+    fn complete_command(&self, prefix: &str) -> Vec<String> {
+        self.commands.iter().filter(|name| has_prefix(name, prefix)).cloned().collect()
+    }
+
+    /// Returns the drive names and the paths of the directories and files that start with `prefix`.
+    async fn complete_file(&self, prefix: &str) -> Vec<String> {
+        // The separators we look for are ASCII so the byte index is always a character boundary.
+        let split = prefix.rfind('/').or_else(|| prefix.find(':')).map(|i| i + 1).unwrap_or(0);
+        let (dir, partial) = prefix.split_at(split);
+
+        let mut candidates = vec![];
+        let storage = self.storage.borrow();
+        if dir.is_empty() {
+            for name in storage.mounted().keys() {
+                let drive = format!("{}:/", name);
+                if has_prefix(&drive, partial) {
+                    candidates.push(drive);
+                }
+            }
+        }
+        if let Ok(files) = storage.enumerate(dir).await {
+            for name in files.subdirs().keys() {
+                if has_prefix(name, partial) {
+                    candidates.push(format!("{}{}/", dir, name));
+                }
+            }
+            for name in files.dirents().keys() {
+                if has_prefix(name, partial) {
+                    candidates.push(format!("{}{}", dir, name));
+                }
+            }
+        }
+        candidates
+    }
+}
+
+#[async_trait(?Send)]
+impl Completer for ReplCompleter {
+    async fn complete(&self, line: &str, pos: usize) -> Completion {
+        let chars = line.chars().take(pos).collect::<Vec<char>>();
+
+        let mut stmt_start = 0;
+        let mut quote = None;
+        for (i, ch) in chars.iter().enumerate() {
+            match ch {
+                '"' if quote.is_none() => quote = Some(i),
+                '"' => quote = None,
+                ':' if quote.is_none() => stmt_start = i + 1,
+                _ => (),
+            }
+        }
+
+        match quote {
+            Some(quote) => {
+                let command = chars[stmt_start..quote].iter().collect::<String>();
+                let command = command.trim().to_ascii_uppercase();
+                if !FILE_COMMANDS.contains(&command.as_str()) {
+                    return Completion { start: pos, candidates: vec![] };
+                }
+
+                let prefix = chars[quote + 1..].iter().collect::<String>();
+                Completion { start: quote + 1, candidates: self.complete_file(&prefix).await }
+            }
+
+            None => {
+                let start = chars
+                    .iter()
+                    .rposition(|ch| !(ch.is_alphanumeric() || *ch == '_'))
+                    .map(|i| i + 1)
+                    .unwrap_or(0);
+                if !chars[stmt_start..start].iter().all(|ch| ch.is_whitespace()) {
+                    return Completion { start: pos, candidates: vec![] };
+                }
+
+                let prefix = chars[start..].iter().collect::<String>();
+                Completion { start, candidates: self.complete_command(&prefix) }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use endbasic_std::testutils::*;
+    use futures_lite::future::block_on;
+
+    /// Creates a completer for a machine with the standard library and with a `MEMORY` drive that
+    /// contains a few files.
+    fn seeded_completer(tester: &mut Tester) -> ReplCompleter {
+        let storage = tester.get_storage();
+        {
+            let mut storage = storage.borrow_mut();
+            block_on(storage.put("MEMORY:/FIBONACCI.BAS", b"")).unwrap();
+            block_on(storage.put("MEMORY:/FILE.BAS", b"")).unwrap();
+            block_on(storage.put("MEMORY:/OTHER.BAS", b"")).unwrap();
+            block_on(storage.mkdir("MEMORY:/GAMES")).unwrap();
+            block_on(storage.put("MEMORY:/GAMES/PONG.BAS", b"")).unwrap();
+            block_on(storage.mount("DEMOS", "memory://", true)).unwrap();
+            block_on(storage.mount("DATA", "memory://", false)).unwrap();
+        }
+        ReplCompleter::new(tester.get_machine(), storage)
+    }
+
+    /// Completes `line` with the cursor at its end using `completer`.
+    fn complete(completer: &ReplCompleter, line: &str) -> Completion {
+        block_on(completer.complete(line, line.chars().count()))
+    }
+
+    /// Constructs a `Completion` that starts at `start` and has the given `candidates`.
+    fn completion(start: usize, candidates: &[&str]) -> Completion {
+        Completion { start, candidates: candidates.iter().map(|c| (*c).to_owned()).collect() }
+    }
+
+    #[test]
+    fn test_complete_command_at_statement_start() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(completion(0, &["PRINT", "PRINT#", "PRINTZONE"]), complete(&completer, "PRIN"));
+        assert_eq!(completion(2, &["PRINT", "PRINT#", "PRINTZONE"]), complete(&completer, "  pri"));
+        assert_eq!(
+            completion(7, &["PRINT", "PRINT#", "PRINTZONE"]),
+            complete(&completer, "a = 1: PRI")
+        );
+        assert_eq!(completion(0, &["CLEAR", "CLOSE#", "CLS"]), complete(&completer, "CL"));
+    }
+
+    #[test]
+    fn test_complete_command_excludes_functions() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(completion(0, &[]), complete(&completer, "LEN"));
+    }
+
+    #[test]
+    fn test_complete_command_not_at_statement_start() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(completion(9, &[]), complete(&completer, "PRINT PRI"));
+        assert_eq!(completion(5, &[]), complete(&completer, "a = P"));
+    }
+
+    #[test]
+    fn test_complete_command_in_middle_of_line() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(
+            completion(0, &["PRINT", "PRINT#", "PRINTZONE"]),
+            block_on(completer.complete("PRI 3", 3))
+        );
+    }
+
+    #[test]
+    fn test_complete_file_in_current_drive() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(
+            completion(6, &["FIBONACCI.BAS", "FILE.BAS"]),
+            complete(&completer, "LOAD \"FI")
+        );
+        assert_eq!(completion(5, &["OTHER.BAS"]), complete(&completer, "RUN \"o"));
+        assert_eq!(completion(6, &["GAMES/"]), complete(&completer, "SAVE \"GA"));
+        assert_eq!(completion(5, &["GAMES/PONG.BAS"]), complete(&completer, "DEL \"GAMES/"));
+    }
+
+    #[test]
+    fn test_complete_file_with_drive_prefix() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(completion(5, &["DATA:/", "DEMOS:/"]), complete(&completer, "DIR \"D"));
+        assert_eq!(
+            completion(6, &["MEMORY:/FIBONACCI.BAS"]),
+            complete(&completer, "LOAD \"MEMORY:/FIB")
+        );
+        assert_eq!(
+            completion(6, &["memory:FIBONACCI.BAS"]),
+            complete(&completer, "LOAD \"memory:fib")
+        );
+        assert_eq!(completion(6, &[]), complete(&completer, "LOAD \"DEMOS:/"));
+    }
+
+    #[test]
+    fn test_complete_file_only_for_file_commands() {
+        let mut tester = Tester::default();
+        let completer = seeded_completer(&mut tester);
+
+        assert_eq!(completion(9, &[]), complete(&completer, "PRINT \"FI"));
+        assert_eq!(completion(15, &[]), complete(&completer, "LOAD \"FILE.BAS\""));
+        assert_eq!(completion(17, &["FILE.BAS"]), complete(&completer, "a$ = \"x:\": LOAD \"FIL"));
+    }
+}
@@ -16,7 +16,7 @@
 //! Exposes EndBASIC demos as a read-only drive.
 
 use async_trait::async_trait;
-use endbasic_std::storage::{DiskSpace, Drive, DriveFactory, DriveFiles, Metadata};
+use endbasic_std::storage::{DiskSpace, Drive, DriveFactory, DriveFiles, Metadata, Storage};
 use std::collections::{BTreeMap, HashMap};
 use std::io;
 use std::str;
@@ -87,6 +87,14 @@ impl Default for DemosDrive {
             };
             demos.insert("PALETTE.BAS", (metadata, content));
         }
+        {
+            let content = process_demo(include_bytes!("../examples/sprite.bas"));
+            let metadata = Metadata {
+                date: time::OffsetDateTime::from_unix_timestamp(1760630400).unwrap(),
+                length: content.len() as u64,
+            };
+            demos.insert("SPRITE.BAS", (metadata, content));
+        }
         {
             let content = process_demo(include_bytes!("../examples/tour.bas"));
             let metadata = Metadata {
@@ -145,8 +153,9 @@ impl Drive for DemosDrive {
 #[derive(Default)]
 pub struct DemoDriveFactory {}
 
+#[async_trait(?Send)]
 impl DriveFactory for DemoDriveFactory {
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
         if target.is_empty() {
             Ok(Box::from(DemosDrive::default()))
         } else {
@@ -161,7 +170,9 @@ impl DriveFactory for DemoDriveFactory {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use endbasic_std::testutils::*;
     use futures_lite::future::block_on;
+    use std::convert::TryFrom;
 
     #[test]
     fn test_demos_drive_delete() {
@@ -192,13 +203,80 @@ mod tests {
         assert!(files.dirents().contains_key("GUESS.BAS"));
         assert!(files.dirents().contains_key("HELLO.BAS"));
         assert!(files.dirents().contains_key("PALETTE.BAS"));
+        assert!(files.dirents().contains_key("SPRITE.BAS"));
         assert!(files.dirents().contains_key("TOUR.BAS"));
 
         assert!(files.disk_quota().unwrap().bytes() > 0);
-        assert_eq!(6, files.disk_quota().unwrap().files());
+        assert_eq!(7, files.disk_quota().unwrap().files());
         assert_eq!(DiskSpace::new(0, 0), files.disk_free().unwrap());
     }
 
+    #[test]
+    fn test_demos_drive_file_queries() {
+        let drive = DemosDrive::default();
+        let files = block_on(drive.enumerate()).unwrap();
+        let hello = files.dirents().get("HELLO.BAS").unwrap();
+        let tour = files.dirents().get("TOUR.BAS").unwrap();
+        assert_eq!(1608646800, hello.date.unix_timestamp());
+        assert_eq!(1608774770, tour.date.unix_timestamp());
+
+        let mut t = Tester::default();
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            storage.register_scheme("demos", Box::from(DemoDriveFactory::default()));
+            block_on(storage.mount("demos", "demos://", false)).unwrap();
+        }
+        let mut c = t
+            .run(
+                r#"a = FILEEXISTS("demos:/hello.bas"): b = FILEEXISTS("demos:/missing.bas")
+CD "demos:/": c% = FILESIZE("Hello.Bas"): d$ = FILEDATE("HELLO.BAS")
+e% = FILESIZE("DEMOS:TOUR.BAS"): f$ = FILEDATE("tour.bas")
+g% = FILESIZE("missing.bas")"#,
+            )
+            .expect_var("a", true)
+            .expect_var("b", false)
+            .expect_var("c", i32::try_from(hello.length).unwrap())
+            .expect_var("d", "2020-12-22 14:20:00")
+            .expect_var("e", i32::try_from(tour.length).unwrap())
+            .expect_var("f", "2020-12-24 01:52:50")
+            .expect_err("4:15: Entry not found");
+        for name in files.dirents().keys() {
+            let content = block_on(drive.get(name)).unwrap();
+            c = c.expect_file_bytes(format!("DEMOS:/{}", name), &content);
+        }
+        c.check();
+    }
+
+    #[test]
+    fn test_demos_drive_disk_space() {
+        let drive = DemosDrive::default();
+        let files = block_on(drive.enumerate()).unwrap();
+        let quota = files.disk_quota().unwrap();
+
+        let mut t = Tester::default();
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            storage.register_scheme("demos", Box::from(DemoDriveFactory::default()));
+            block_on(storage.mount("demos", "demos://", false)).unwrap();
+        }
+        let mut c = t
+            .run(
+                r#"a# = DISKFREE("demos"): b% = DISKFREEFILES("DEMOS:")
+c# = DISKQUOTA("demos:"): d% = DISKQUOTAFILES("Demos")"#,
+            )
+            .expect_var("a", 0.0)
+            .expect_var("b", 0)
+            .expect_var("c", quota.bytes() as f64)
+            .expect_var("d", 7);
+        for name in files.dirents().keys() {
+            let content = block_on(drive.get(name)).unwrap();
+            c = c.expect_file_bytes(format!("DEMOS:/{}", name), &content);
+        }
+        c.check();
+    }
+
     #[test]
     fn test_demos_drive_get() {
         let drive = DemosDrive::default();
@@ -215,6 +293,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_demos_drive_mixed_case_names() {
+        let content = process_demo(include_bytes!("../examples/hello.bas"));
+        let t = Tester::default();
+        t.get_storage()
+            .borrow_mut()
+            .register_scheme("demos", Box::from(DemoDriveFactory::default()));
+        t.write_file("hello.bas", "local copy\n")
+            .run(
+                r#"MOUNT "demos://" AS "demos"
+LOAD "DEMOS:/hello.bas"
+a$ = FILEDATE("demos:/Hello.Bas")
+SAVE "memory:/HELLO.BAS"
+UNMOUNT "demos""#,
+            )
+            .expect_clear()
+            .expect_prints(["Saved as MEMORY:/HELLO.BAS"])
+            .expect_var("a", "2020-12-22 14:20:00")
+            .expect_program(Some("MEMORY:/HELLO.BAS"), &content)
+            .expect_file("MEMORY:/hello.bas", &content)
+            .check();
+    }
+
     #[test]
     fn test_demos_drive_put() {
         let mut drive = DemosDrive::default();
@@ -234,6 +335,32 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_demos_drive_rename_and_copy() {
+        let mut storage = Storage::default();
+        storage.register_scheme("demos", Box::from(DemoDriveFactory::default()));
+        block_on(storage.mount("demos", "demos://", false)).unwrap();
+        block_on(storage.put("mine.bas", b"PRINT 1\n")).unwrap();
+
+        let err = block_on(storage.rename("mine.bas", "demos:/")).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert_eq!(b"PRINT 1\n", block_on(storage.get("mine.bas")).unwrap().as_slice());
+        assert!(!block_on(storage.enumerate("demos:")).unwrap().dirents().contains_key("mine.bas"));
+
+        let err = block_on(storage.rename("demos:/tour.bas", "memory:/")).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert!(!block_on(storage.enumerate("memory:"))
+            .unwrap()
+            .dirents()
+            .contains_key("tour.bas"));
+
+        block_on(storage.copy("DEMOS:/TOUR.BAS", "MEMORY:/TOUR.BAS", false)).unwrap();
+        assert_eq!(
+            process_demo(include_bytes!("../examples/tour.bas")).as_bytes(),
+            block_on(storage.get("MEMORY:/TOUR.BAS")).unwrap().as_slice()
+        );
+    }
+
     #[test]
     fn test_demos_drive_system_path() {
         let drive = DemosDrive::default();
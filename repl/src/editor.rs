@@ -15,13 +15,16 @@
 
 //! Interactive console-based text editor.
 
+use crate::autosave::Autosave;
 use crate::console::{CharsXY, ClearType, Console, Key};
 use async_trait::async_trait;
 use endbasic_std::console::{AnsiColor, LineBuffer};
 use endbasic_std::program::Program;
+use std::cell::RefCell;
 use std::cmp;
 use std::convert::TryFrom;
 use std::io;
+use std::rc::Rc;
 
 /// The color of the main editor window.
 const TEXT_COLOR: (Option<u8>, Option<u8>) = (Some(AnsiColor::White as u8), None);
@@ -94,6 +97,9 @@ pub struct Editor {
     /// Last edited column, used when moving vertically to preserve the insertion point even when
     /// traversing shorter lines.
     insert_col: usize,
+
+    /// Facility to periodically save the contents while they are being edited, if any.
+    autosave: Option<Rc<RefCell<Autosave>>>,
 }
 
 impl Default for Editor {
@@ -106,11 +112,18 @@ impl Default for Editor {
             viewport_pos: FilePos::default(),
             file_pos: FilePos::default(),
             insert_col: 0,
+            autosave: None,
         }
     }
 }
 
 impl Editor {
+    /// Configures the editor to periodically record its contents with `autosave` while editing.
+    pub fn with_autosave(mut self, autosave: Rc<RefCell<Autosave>>) -> Self {
+        self.autosave = Some(autosave);
+        self
+    }
+
     /// Rewrites the status line at the bottom of the `console`, using the previously queried
     /// `console_size`.
     ///
@@ -273,6 +286,14 @@ impl Editor {
             console.show_cursor()?;
             console.sync_now()?;
 
+            if let Some(autosave) = self.autosave.clone() {
+                if autosave.borrow().is_due() {
+                    // Autosave is best-effort and must never disrupt editing, so errors are
+                    // ignored.
+                    let _ = autosave.borrow_mut().save(self).await;
+                }
+            }
+
             match console.read_key().await? {
                 Key::Escape | Key::Eof | Key::Interrupt => break,
 
@@ -425,7 +446,7 @@ impl Editor {
                 }
 
                 // TODO(jmmv): Should do something smarter with unknown keys.
-                Key::Unknown => (),
+                Key::Search | Key::Unknown => (),
             }
         }
 
@@ -466,6 +487,14 @@ impl Program for Editor {
         self.dirty = false;
     }
 
+    fn set_text(&mut self, text: &str) {
+        self.content = text.lines().map(LineBuffer::from).collect();
+        self.dirty = true;
+        self.viewport_pos = FilePos::default();
+        self.file_pos = FilePos::default();
+        self.insert_col = 0;
+    }
+
     fn text(&self) -> String {
         self.content
             .iter()
@@ -652,6 +681,11 @@ mod tests {
 
         editor.set_name("SAVED");
         assert!(!editor.is_dirty());
+
+        editor.set_text("merged\n");
+        assert_eq!("merged\n", editor.text());
+        assert_eq!(Some("SAVED"), editor.name());
+        assert!(editor.is_dirty());
     }
 
     #[test]
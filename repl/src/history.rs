@@ -0,0 +1,216 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Persistent history of the lines entered in the REPL.
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+/// Default maximum number of lines to remember.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Abstraction to persist the history across sessions.
+pub trait HistoryStore {
+    /// Loads the saved history, if any.
+    fn load(&self) -> io::Result<Option<String>>;
+
+    /// Saves the history `data`, replacing any previously-saved history.
+    fn save(&mut self, data: &str) -> io::Result<()>;
+}
+
+/// History store that keeps the history in a file.
+pub struct FileHistoryStore {
+    path: PathBuf,
+}
+
+impl FileHistoryStore {
+    /// Creates a new history store that keeps the history in the file at `path`.  The file and its
+    /// parent directory need not exist yet.
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl HistoryStore for FileHistoryStore {
+    fn load(&self) -> io::Result<Option<String>> {
+        match fs::read_to_string(&self.path) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn save(&mut self, data: &str) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, data)
+    }
+}
+
+/// Lines entered in the REPL, optionally persisted across sessions.
+///
+/// Consecutive repeats of the same line are only recorded once and only the most recent
+/// `max_entries` lines are kept.  Persistence is best-effort: errors while loading or saving the
+/// history are ignored so that they never disrupt the interactive session.
+pub struct History {
+    store: Option<Box<dyn HistoryStore>>,
+    max_entries: usize,
+    entries: Vec<String>,
+}
+
+impl Default for History {
+    /// Creates an in-memory history that is not persisted.
+    fn default() -> Self {
+        Self { store: None, max_entries: DEFAULT_MAX_ENTRIES, entries: vec![] }
+    }
+}
+
+impl History {
+    /// Creates a new history that keeps up to `max_entries` lines and that is persisted in `store`,
+    /// loading any lines previously saved in it.
+    pub fn new(store: Box<dyn HistoryStore>, max_entries: usize) -> Self {
+        let mut entries: Vec<String> = match store.load() {
+            Ok(Some(data)) => data.lines().filter(|l| !l.is_empty()).map(str::to_owned).collect(),
+            Ok(None) | Err(_) => vec![],
+        };
+        if entries.len() > max_entries {
+            entries.drain(0..entries.len() - max_entries);
+        }
+        Self { store: Some(store), max_entries, entries }
+    }
+
+    /// Returns the recorded lines, from oldest to newest.
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Records a newly-entered `line` and persists the updated history.
+    pub fn add(&mut self, line: &str) {
+        if line.is_empty() || self.entries.last().map(String::as_str) == Some(line) {
+            return;
+        }
+
+        self.entries.push(line.to_owned());
+        if self.entries.len() > self.max_entries {
+            self.entries.drain(0..self.entries.len() - self.max_entries);
+        }
+
+        if let Some(store) = self.store.as_mut() {
+            let mut data = self.entries.join("\n");
+            data.push('\n');
+            let _ = store.save(&data);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// History store that keeps the data in memory and that can be shared across histories.
+    #[derive(Clone, Default)]
+    struct MockHistoryStore {
+        data: Rc<RefCell<Option<String>>>,
+    }
+
+    impl HistoryStore for MockHistoryStore {
+        fn load(&self) -> io::Result<Option<String>> {
+            Ok(self.data.borrow().clone())
+        }
+
+        fn save(&mut self, data: &str) -> io::Result<()> {
+            *self.data.borrow_mut() = Some(data.to_owned());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_history_add_and_reload_ordering() {
+        let store = MockHistoryStore::default();
+
+        let mut history = History::new(Box::from(store.clone()), 10);
+        assert!(history.entries().is_empty());
+        history.add("first");
+        history.add("second");
+        history.add("third");
+        assert_eq!(Some("first\nsecond\nthird\n".to_owned()), *store.data.borrow());
+
+        let mut history = History::new(Box::from(store.clone()), 10);
+        assert_eq!(["first", "second", "third"], history.entries());
+        history.add("fourth");
+        assert_eq!(["first", "second", "third", "fourth"], history.entries());
+
+        let history = History::new(Box::from(store), 10);
+        assert_eq!(["first", "second", "third", "fourth"], history.entries());
+    }
+
+    #[test]
+    fn test_history_dedups_consecutive_repeats() {
+        let store = MockHistoryStore::default();
+        let mut history = History::new(Box::from(store.clone()), 10);
+        history.add("a");
+        history.add("a");
+        history.add("b");
+        history.add("a");
+        history.add("a");
+        assert_eq!(["a", "b", "a"], history.entries());
+        assert_eq!(Some("a\nb\na\n".to_owned()), *store.data.borrow());
+    }
+
+    #[test]
+    fn test_history_ignores_empty_lines() {
+        let store = MockHistoryStore::default();
+        let mut history = History::new(Box::from(store.clone()), 10);
+        history.add("");
+        assert!(history.entries().is_empty());
+        assert_eq!(None, *store.data.borrow());
+    }
+
+    #[test]
+    fn test_history_cap() {
+        let store = MockHistoryStore::default();
+        let mut history = History::new(Box::from(store.clone()), 3);
+        for line in ["1", "2", "3", "4", "5"] {
+            history.add(line);
+        }
+        assert_eq!(["3", "4", "5"], history.entries());
+        assert_eq!(Some("3\n4\n5\n".to_owned()), *store.data.borrow());
+    }
+
+    #[test]
+    fn test_history_cap_on_load() {
+        let store = MockHistoryStore::default();
+        *store.data.borrow_mut() = Some("1\n2\n\n3\n4\n5\n".to_owned());
+        let history = History::new(Box::from(store), 2);
+        assert_eq!(["4", "5"], history.entries());
+    }
+
+    #[test]
+    fn test_file_history_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("subdir/history.txt");
+        let mut store = FileHistoryStore::new(&path);
+
+        assert_eq!(None, store.load().unwrap());
+        store.save("first\n").unwrap();
+        assert_eq!(Some("first\n".to_owned()), store.load().unwrap());
+        store.save("second\n").unwrap();
+        assert_eq!(Some("second\n".to_owned()), store.load().unwrap());
+    }
+}
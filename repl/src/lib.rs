@@ -22,16 +22,28 @@
 #![warn(unused, unused_extern_crates, unused_import_braces, unused_qualifications)]
 #![warn(unsafe_code)]
 
+use crate::autosave::Autosave;
+use crate::budget::{AutorunBudget, DEFAULT_MAX_STEPS, DEFAULT_MAX_TIME};
+use crate::completion::ReplCompleter;
+use crate::history::History;
 use endbasic_core::exec::{Machine, StopReason};
 use endbasic_std::console::{self, is_narrow, refill_and_print, Console};
 use endbasic_std::program::{continue_if_modified, Program, BREAK_MSG};
 use endbasic_std::storage::Storage;
+use endbasic_std::strings::parse_boolean;
+use futures_lite::FutureExt;
+use std::any::Any;
 use std::cell::RefCell;
 use std::io;
+use std::panic::AssertUnwindSafe;
 use std::rc::Rc;
 
+pub mod autosave;
+pub mod budget;
+pub mod completion;
 pub mod demos;
 pub mod editor;
+pub mod history;
 
 /// Prints the EndBASIC welcome message to the given console.
 pub fn print_welcome(console: Rc<RefCell<dyn Console>>) -> io::Result<()> {
@@ -51,6 +63,68 @@ pub fn print_welcome(console: Rc<RefCell<dyn Console>>) -> io::Result<()> {
     Ok(())
 }
 
+/// Describes what to show to the user when the interpreter starts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Banner {
+    /// Shows the built-in welcome message as printed by `print_welcome`.
+    Default,
+
+    /// Shows the given text in place of the welcome message.
+    ///
+    /// The text is split into paragraphs at blank lines and each paragraph is refilled to the
+    /// width of the console.  Narrow consoles get the text without indentation, just like the
+    /// default welcome message does.
+    Custom(String),
+
+    /// Shows nothing at all.
+    Quiet,
+}
+
+/// Prints the `banner` to the given console.
+pub fn print_banner(console: Rc<RefCell<dyn Console>>, banner: &Banner) -> io::Result<()> {
+    match banner {
+        Banner::Default => print_welcome(console),
+        Banner::Custom(text) => {
+            let mut paragraphs = vec![];
+            let mut current = String::new();
+            for line in text.lines() {
+                let line = line.trim();
+                if line.is_empty() {
+                    if !current.is_empty() {
+                        paragraphs.push(current);
+                        current = String::new();
+                    }
+                } else {
+                    if !current.is_empty() {
+                        current.push(' ');
+                    }
+                    current.push_str(line);
+                }
+            }
+            if !current.is_empty() {
+                paragraphs.push(current);
+            }
+
+            let mut console = console.borrow_mut();
+            let indent = if is_narrow(&*console) {
+                ""
+            } else {
+                console.print("")?;
+                "    "
+            };
+            for (i, paragraph) in paragraphs.iter().enumerate() {
+                if i > 0 {
+                    console.print("")?;
+                }
+                refill_and_print(&mut *console, [paragraph], indent)?;
+            }
+            console.print("")?;
+            Ok(())
+        }
+        Banner::Quiet => Ok(()),
+    }
+}
+
 /// Loads the `AUTOEXEC.BAS` file if it exists in the `drive`.
 ///
 /// Failures to process the file are logged to the `console` but are ignored.  Other failures are
@@ -102,7 +176,7 @@ pub async fn run_from_cloud(
     };
 
     console.borrow_mut().print(&format!("Mounting {} as AUTORUN...", fs_uri))?;
-    storage.borrow_mut().mount("AUTORUN", &fs_uri)?;
+    storage.borrow_mut().mount("AUTORUN", &fs_uri, false).await?;
     storage.borrow_mut().cd("AUTORUN:/")?;
 
     console.borrow_mut().print(&format!("Loading {}...", path))?;
@@ -120,7 +194,10 @@ pub async fn run_from_cloud(
     console.borrow_mut().print("Starting...")?;
     console.borrow_mut().print("")?;
 
+    let budget = AutorunBudget::new(console.clone(), DEFAULT_MAX_STEPS, DEFAULT_MAX_TIME);
+    let previous = machine.set_watchdog(Some(Box::from(budget)));
     let result = machine.exec(&mut "RUN".as_bytes()).await;
+    machine.set_watchdog(previous);
 
     let mut console = console.borrow_mut();
 
@@ -164,24 +241,104 @@ execute the program again.",
     Ok(code)
 }
 
+/// Name of the file where the stored program is saved to after an internal error.
+const CRASH_FILE: &str = "CRASH.BAS";
+
+/// Extracts a textual description of a panic from its `payload`.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
+/// Handles a panic that happened while executing a statement from the REPL.
+///
+/// The `machine` is marked as poisoned so that it refuses to run new programs, and the user is
+/// offered the chance to save the stored `program` so that their work is not lost.
+async fn recover_from_panic(
+    machine: &mut Machine,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+    message: &str,
+) -> io::Result<()> {
+    machine.poison();
+
+    {
+        let mut console = console.borrow_mut();
+        console.print(&format!("internal error, please report: {}", message))?;
+        if !program.borrow().is_dirty() {
+            return Ok(());
+        }
+        console.print("The stored program has unsaved changes that can be recovered.")?;
+    }
+
+    let answer = {
+        let mut console = console.borrow_mut();
+        let prompt = format!("Save the program as {} (y/N)? ", CRASH_FILE);
+        console::read_line(&mut *console, &prompt, "", None).await?
+    };
+    if parse_boolean(&answer).unwrap_or(false) {
+        let save = format!("SAVE \"{}\"", CRASH_FILE);
+        if let Err(e) = machine.exec(&mut save.as_bytes()).await {
+            console.borrow_mut().print(&format!("ERROR: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
 /// Enters the interactive interpreter.
 ///
 /// The `console` provided here is used for the REPL prompt interaction and should match the
 /// console that's in use by the machine (if any).  They don't necessarily have to match though.
+///
+/// Panics raised while executing a statement do not terminate the loop.  Instead, the machine is
+/// poisoned and the user is given a chance to save the stored program before exiting.
+///
+/// If `autosave` is provided, the stored program is recorded in the recovery file after every
+/// statement that executes successfully.
+///
+/// If `history` is provided, the lines entered at the prompt are recorded in it, which allows
+/// persisting them across sessions.  Otherwise, the history only lives for this loop.
+///
+/// Pressing tab at the prompt completes command names and the file names found in `storage`.
 pub async fn run_repl_loop(
     machine: &mut Machine,
     console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
     program: Rc<RefCell<dyn Program>>,
+    autosave: Option<Rc<RefCell<Autosave>>>,
+    history: Option<Rc<RefCell<History>>>,
 ) -> io::Result<i32> {
+    let history = history.unwrap_or_else(|| Rc::from(RefCell::from(History::default())));
     let mut stop_reason = StopReason::Eof;
-    let mut history = vec![];
     while stop_reason == StopReason::Eof {
         let line = {
             let mut console = console.borrow_mut();
             if console.is_interactive() {
                 console.print("Ready")?;
             }
-            console::read_line(&mut *console, "", "", Some(&mut history)).await
+            let completer = ReplCompleter::new(machine, storage.clone());
+            let mut history = history.borrow_mut();
+            let mut entries = history.entries().to_vec();
+            let line = console::read_line_with_completer(
+                &mut *console,
+                "",
+                "",
+                Some(&mut entries),
+                &completer,
+            )
+            .await;
+            // The line is only appended to the entries when it was accepted interactively.
+            if entries.len() > history.entries().len() {
+                if let Ok(line) = line.as_ref() {
+                    history.add(line);
+                }
+            }
+            line
         };
 
         // Any signals entered during console input should not impact upcoming execution.  Drain
@@ -189,13 +346,29 @@ pub async fn run_repl_loop(
         machine.drain_signals();
 
         match line {
-            Ok(line) => match machine.exec(&mut line.as_bytes()).await {
-                Ok(reason) => stop_reason = reason,
-                Err(e) => {
-                    let mut console = console.borrow_mut();
-                    console.print(format!("ERROR: {}", e).as_str())?;
+            Ok(line) => {
+                let result =
+                    AssertUnwindSafe(machine.exec(&mut line.as_bytes())).catch_unwind().await;
+                match result {
+                    Ok(Ok(reason)) => {
+                        if let Some(autosave) = autosave.as_ref() {
+                            // Autosave is best-effort and must never disrupt the interactive
+                            // session, so errors are ignored.
+                            let _ = autosave.borrow_mut().save(&*program.borrow()).await;
+                        }
+                        stop_reason = reason;
+                    }
+                    Ok(Err(e)) => {
+                        let mut console = console.borrow_mut();
+                        console.print(format!("ERROR: {}", e).as_str())?;
+                    }
+                    Err(payload) => {
+                        let message = panic_message(payload.as_ref());
+                        recover_from_panic(machine, console.clone(), program.clone(), message)
+                            .await?;
+                    }
                 }
-            },
+            }
             Err(e) => {
                 if e.kind() == io::ErrorKind::Interrupted {
                     let mut console = console.borrow_mut();
@@ -233,9 +406,11 @@ pub async fn run_repl_loop(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use endbasic_core::exec::Signal;
+    use async_trait::async_trait;
+    use endbasic_core::exec::{Scope, Signal};
+    use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
     use endbasic_std::console::{CharsXY, Key};
-    use endbasic_std::storage::{Drive, DriveFactory, InMemoryDrive};
+    use endbasic_std::storage::{Drive, DriveFactory, InMemoryDrive, Storage};
     use endbasic_std::testutils::*;
     use futures_lite::future::block_on;
     use std::convert::TryFrom;
@@ -285,6 +460,80 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_print_banner_default() {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        console.borrow_mut().set_size_chars(CharsXY::new(80, 1));
+        print_banner(console.clone(), &Banner::Default).unwrap();
+        let exp_console = Rc::from(RefCell::from(MockConsole::default()));
+        exp_console.borrow_mut().set_size_chars(CharsXY::new(80, 1));
+        print_welcome(exp_console.clone()).unwrap();
+        assert_eq!(
+            exp_console.borrow_mut().take_captured_out(),
+            console.borrow_mut().take_captured_out()
+        );
+    }
+
+    #[test]
+    fn test_print_banner_quiet() {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        print_banner(console.clone(), &Banner::Quiet).unwrap();
+        assert!(console.borrow_mut().take_captured_out().is_empty());
+    }
+
+    #[test]
+    fn test_print_banner_custom_wide_console() {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        console.borrow_mut().set_size_chars(CharsXY::new(80, 1));
+        let banner = Banner::Custom(
+            "Welcome to the lab\nmachine.\n\n\n  Ask for help\nat the desk.\n".to_owned(),
+        );
+        print_banner(console.clone(), &banner).unwrap();
+        assert_eq!(
+            [
+                CapturedOut::Print("".to_owned()),
+                CapturedOut::Print("    Welcome to the lab machine.".to_owned()),
+                CapturedOut::Print("".to_owned()),
+                CapturedOut::Print("    Ask for help at the desk.".to_owned()),
+                CapturedOut::Print("".to_owned()),
+            ],
+            console.borrow_mut().take_captured_out().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_print_banner_custom_narrow_console() {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        console.borrow_mut().set_size_chars(CharsXY::new(16, 1));
+        let banner =
+            Banner::Custom("Welcome to the lab machine.\n\nAsk for help at the desk.".to_owned());
+        print_banner(console.clone(), &banner).unwrap();
+        assert_eq!(
+            [
+                CapturedOut::Print("Welcome to".to_owned()),
+                CapturedOut::Print("the lab".to_owned()),
+                CapturedOut::Print("machine.".to_owned()),
+                CapturedOut::Print("".to_owned()),
+                CapturedOut::Print("Ask for".to_owned()),
+                CapturedOut::Print("help at the".to_owned()),
+                CapturedOut::Print("desk.".to_owned()),
+                CapturedOut::Print("".to_owned()),
+            ],
+            console.borrow_mut().take_captured_out().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_print_banner_custom_empty() {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        console.borrow_mut().set_size_chars(CharsXY::new(16, 1));
+        print_banner(console.clone(), &Banner::Custom("\n  \n".to_owned())).unwrap();
+        assert_eq!(
+            [CapturedOut::Print("".to_owned())],
+            console.borrow_mut().take_captured_out().as_slice()
+        );
+    }
+
     #[test]
     fn test_autoexec_ok() {
         // The code in the autoexec test file should access, in a mutable fashion, all the resources
@@ -326,13 +575,23 @@ mod tests {
             .run("after = 5")
             .expect_var("a", 1)
             .expect_var("after", 5)
-            .expect_prints(["AUTOEXEC.BAS failed: 2:7: Number of bits to >> (-1) must be positive"])
+            .expect_prints([
+                "AUTOEXEC.BAS failed: 2:7: Number of bits to >> (-1) must be between 0 and 31",
+            ])
             .expect_file("MEMORY:/AUTOEXEC.BAS", autoexec)
             .check();
     }
 
     #[test]
-    fn test_autoexec_name_is_case_sensitive() {
+    fn test_autoexec_name_is_case_insensitive() {
+        let mut tester = Tester::default().write_file("autoexec.bas", "a = 2");
+        let (console, storage) = (tester.get_console(), tester.get_storage());
+        block_on(try_load_autoexec(tester.get_machine(), console, storage)).unwrap();
+        tester.run("").expect_var("a", 2).expect_file("MEMORY:/autoexec.bas", "a = 2").check();
+    }
+
+    #[test]
+    fn test_autoexec_name_conflict_uses_first() {
         let mut tester = Tester::default()
             .write_file("AUTOEXEC.BAS", "a = 1")
             .write_file("autoexec.bas", "a = 2");
@@ -365,8 +624,9 @@ mod tests {
         const SCRIPT: &'static str = r#"PRINT "Success""#;
     }
 
+    #[async_trait(?Send)]
     impl DriveFactory for MockDriveFactory {
-        fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+        async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
             let mut drive = InMemoryDrive::default();
             block_on(drive.put(self.exp_file, Self::SCRIPT.as_bytes())).unwrap();
             assert_eq!(self.exp_username, target);
@@ -438,7 +698,8 @@ mod tests {
     #[test]
     fn test_run_repl_loop_signal_before_exec() {
         let mut tester = Tester::default();
-        let (console, program) = (tester.get_console(), tester.get_program());
+        let (console, storage, program) =
+            (tester.get_console(), tester.get_storage(), tester.get_program());
         let signals_tx = tester.get_machine().get_signals_tx();
 
         {
@@ -448,7 +709,194 @@ mod tests {
             console.add_input_chars(" 123");
             console.add_input_keys(&[Key::NewLine, Key::Eof]);
         }
-        block_on(run_repl_loop(tester.get_machine(), console, program)).unwrap();
+        block_on(run_repl_loop(tester.get_machine(), console, storage, program, None, None))
+            .unwrap();
         tester.run("").expect_prints([" 123", "End of input by CTRL-D"]).check();
     }
+
+    #[test]
+    fn test_run_repl_loop_autosave() {
+        let mut tester = Tester::default();
+        let (console, program) = (tester.get_console(), tester.get_program());
+        let storage = tester.get_storage();
+        block_on(storage.borrow_mut().mount("local", "memory://", false)).unwrap();
+        let autosave = Rc::from(RefCell::from(Autosave::new(storage.clone())));
+
+        {
+            let mut console = console.borrow_mut();
+            console.add_input_chars("PRINT FILEEXISTS(\"LOCAL:/.AUTOSAVE.BAS\")\n");
+            console.add_input_chars("EDIT\nPRINT 1\n");
+            console.add_input_chars("PRINT FILESIZE(\"LOCAL:/.AUTOSAVE.BAS\")\n");
+            console.add_input_chars("SAVE \"LOCAL:/PROG.BAS\"\n");
+            console.add_input_chars("PRINT FILEEXISTS(\"LOCAL:/.AUTOSAVE.BAS\")\n");
+            console.add_input_keys(&[Key::Eof]);
+        }
+        block_on(run_repl_loop(
+            tester.get_machine(),
+            console,
+            storage,
+            program,
+            Some(autosave),
+            None,
+        ))
+        .unwrap();
+        tester
+            .run("")
+            .expect_prints([
+                "FALSE",
+                " 8",
+                "Saved as LOCAL:/PROG.BAS",
+                "FALSE",
+                "End of input by CTRL-D",
+            ])
+            .expect_program(Some("LOCAL:/PROG.BAS"), "PRINT 1\n")
+            .expect_file("LOCAL:/PROG.BAS", "PRINT 1\n")
+            .check();
+    }
+
+    /// A command that panics when invoked, used to simulate bugs in command implementations.
+    struct PanicCommand {
+        metadata: CallableMetadata,
+    }
+
+    impl PanicCommand {
+        /// Creates a new instance of the command.
+        fn new() -> Rc<Self> {
+            Rc::from(Self { metadata: CallableMetadataBuilder::new("PANIC").test_build() })
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Callable for PanicCommand {
+        fn metadata(&self) -> &CallableMetadata {
+            &self.metadata
+        }
+
+        async fn exec(
+            &self,
+            _scope: Scope<'_>,
+            _machine: &mut Machine,
+        ) -> endbasic_core::exec::Result<()> {
+            panic!("Oops in PANIC");
+        }
+    }
+
+    /// A command that reads a secret from the console, used to simulate password prompts.
+    struct SecretCommand {
+        metadata: CallableMetadata,
+        console: Rc<RefCell<dyn Console>>,
+    }
+
+    impl SecretCommand {
+        /// Creates a new instance of the command.
+        fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+            Rc::from(Self {
+                metadata: CallableMetadataBuilder::new("SECRET").test_build(),
+                console,
+            })
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Callable for SecretCommand {
+        fn metadata(&self) -> &CallableMetadata {
+            &self.metadata
+        }
+
+        async fn exec(
+            &self,
+            scope: Scope<'_>,
+            _machine: &mut Machine,
+        ) -> endbasic_core::exec::Result<()> {
+            let mut console = self.console.borrow_mut();
+            console::read_line_secure(&mut *console, "Secret: ")
+                .await
+                .map_err(|e| scope.io_error(e))?;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_run_repl_loop_history() {
+        let mut tester = Tester::default();
+        let console = tester.get_console();
+        tester = tester.add_callable(SecretCommand::new(console.clone()));
+        let (storage, program) = (tester.get_storage(), tester.get_program());
+        let history = Rc::from(RefCell::from(History::default()));
+        history.borrow_mut().add("OLD");
+
+        {
+            let mut console = console.borrow_mut();
+            console.set_interactive(true);
+            console.add_input_chars("a = 1\na = 1\n\nSECRET\nthe-password\n");
+            console.add_input_keys(&[Key::ArrowUp, Key::ArrowUp, Key::NewLine]);
+            console.add_input_chars("a");
+            console.add_input_keys(&[Key::Interrupt, Key::Eof]);
+        }
+        block_on(run_repl_loop(
+            tester.get_machine(),
+            console.clone(),
+            storage,
+            program,
+            None,
+            Some(history.clone()),
+        ))
+        .unwrap();
+
+        assert_eq!(["OLD", "a = 1", "SECRET", "a = 1"], history.borrow().entries());
+    }
+
+    #[test]
+    fn test_run_repl_loop_panic_recovery_and_save() {
+        let mut tester = Tester::default().add_callable(PanicCommand::new());
+        let (console, storage, program) =
+            (tester.get_console(), tester.get_storage(), tester.get_program());
+
+        {
+            let mut console = console.borrow_mut();
+            console.add_input_chars("EDIT\nPRINT 1\nPANIC\ny\nRUN\nPRINT 3\n");
+            console.add_input_keys(&[Key::Eof]);
+        }
+        block_on(run_repl_loop(tester.get_machine(), console, storage, program, None, None))
+            .unwrap();
+        assert!(tester.get_machine().is_poisoned());
+        tester
+            .run("")
+            .expect_prints([
+                "internal error, please report: Oops in PANIC",
+                "The stored program has unsaved changes that can be recovered.",
+                "Saved as MEMORY:CRASH.BAS",
+                "ERROR: 1:1: Cannot run programs after an internal error; save your work and restart",
+                " 3",
+                "End of input by CTRL-D",
+            ])
+            .expect_program(Some("MEMORY:CRASH.BAS"), "PRINT 1\n")
+            .expect_file("MEMORY:/CRASH.BAS", "PRINT 1\n")
+            .check();
+    }
+
+    #[test]
+    fn test_run_repl_loop_panic_recovery_clean_program() {
+        let mut tester = Tester::default().add_callable(PanicCommand::new());
+        let (console, storage, program) =
+            (tester.get_console(), tester.get_storage(), tester.get_program());
+
+        {
+            let mut console = console.borrow_mut();
+            console.add_input_chars("a = 1: PANIC: a = 2\nPRINT a\n");
+            console.add_input_keys(&[Key::Eof]);
+        }
+        block_on(run_repl_loop(tester.get_machine(), console, storage, program, None, None))
+            .unwrap();
+        assert!(tester.get_machine().is_poisoned());
+        tester
+            .run("")
+            .expect_var("a", 1)
+            .expect_prints([
+                "internal error, please report: Oops in PANIC",
+                " 1",
+                "End of input by CTRL-D",
+            ])
+            .check();
+    }
 }
@@ -15,10 +15,17 @@
 
 //! GPIO implementation for the Raspberry Pi.
 
-use endbasic_std::gpio::{Pin, PinMode, Pins};
+use async_trait::async_trait;
+use endbasic_std::gpio::{Pin, PinEdge, PinMode, Pins};
+use futures_lite::future;
 use rppal::gpio;
 use std::collections::HashMap;
 use std::io;
+use std::time::{Duration, Instant};
+
+/// Maximum amount of time to block while polling for an edge before yielding to the caller, which
+/// gives the caller a chance to interrupt the wait.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
 /// Implementation of the EndBASIC GPIO operations for a Raspberry Pi using the rppal library.
 #[derive(Default)]
@@ -53,6 +60,7 @@ impl RppalPins {
     }
 }
 
+#[async_trait(?Send)]
 impl Pins for RppalPins {
     fn setup(&mut self, pin: Pin, mode: PinMode) -> io::Result<()> {
         self.clear(pin)?;
@@ -116,4 +124,49 @@ impl Pins for RppalPins {
         }
         Ok(())
     }
+
+    async fn wait(
+        &mut self,
+        pin: Pin,
+        edge: PinEdge,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        if !self.inputs.contains_key(&pin) || self.outputs.contains_key(&pin) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "Pin not configured for wait; use GPIO_SETUP first",
+            ));
+        }
+        let pin = self.inputs.get_mut(&pin).unwrap();
+
+        let trigger = match edge {
+            PinEdge::Rising => gpio::Trigger::RisingEdge,
+            PinEdge::Falling => gpio::Trigger::FallingEdge,
+            PinEdge::Both => gpio::Trigger::Both,
+        };
+        pin.set_interrupt(trigger).map_err(gpio_error_to_io_error)?;
+
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let result = loop {
+            let interval = match deadline {
+                Some(deadline) => {
+                    deadline.saturating_duration_since(Instant::now()).min(WAIT_POLL_INTERVAL)
+                }
+                None => WAIT_POLL_INTERVAL,
+            };
+            match pin.poll_interrupt(false, Some(interval)) {
+                Ok(Some(_level)) => break Ok(true),
+                Ok(None) => {
+                    if deadline.map(|deadline| Instant::now() >= deadline).unwrap_or(false) {
+                        break Ok(false);
+                    }
+                }
+                Err(e) => break Err(gpio_error_to_io_error(e)),
+            }
+            future::yield_now().await;
+        };
+
+        let _ = pin.clear_interrupt();
+        result
+    }
 }
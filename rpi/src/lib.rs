@@ -23,5 +23,7 @@
 
 mod gpio;
 pub use gpio::RppalPins;
+mod pwm;
+pub use pwm::{pwm_open, RppalPwm};
 mod spi;
-pub use spi::{spi_bus_open, RppalSpiBus};
+pub use spi::{spi_bus_open, RppalSpi, RppalSpiBus};
@@ -0,0 +1,59 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! PWM channel implementation using rppal.
+
+use endbasic_std::pwm::PwmChannel;
+use rppal::pwm::{self, Channel, Polarity, Pwm};
+use std::io;
+
+/// Frequency of the PWM signal.  High enough to avoid visible flicker when driving a backlight.
+const PWM_FREQUENCY_HZ: f64 = 1000.0;
+
+/// Converts a PWM error to an IO error.
+fn pwm_error_to_io_error(e: pwm::Error) -> io::Error {
+    match e {
+        pwm::Error::Io(e) => e,
+    }
+}
+
+/// An implementation of a `PwmChannel` using rppal.
+pub struct RppalPwm {
+    pwm: Pwm,
+}
+
+/// Factory function to open an `RppalPwm` on the hardware PWM `channel`.
+pub fn pwm_open(channel: u8) -> io::Result<RppalPwm> {
+    let channel = match channel {
+        0 => Channel::Pwm0,
+        1 => Channel::Pwm1,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Only PWM channels 0 and 1 are supported",
+            ))
+        }
+    };
+
+    let pwm = Pwm::with_frequency(channel, PWM_FREQUENCY_HZ, 0.0, Polarity::Normal, true)
+        .map_err(pwm_error_to_io_error)?;
+    Ok(RppalPwm { pwm })
+}
+
+impl PwmChannel for RppalPwm {
+    fn set_duty_cycle(&mut self, duty: f64) -> io::Result<()> {
+        self.pwm.set_duty_cycle(duty).map_err(pwm_error_to_io_error)
+    }
+}
@@ -15,7 +15,7 @@
 
 //! SPI bus implementation using rppal.
 
-use endbasic_std::spi::{SpiBus, SpiMode};
+use endbasic_std::spi::{self as std_spi, SpiBus, SpiDevice, SpiMode};
 use rppal::spi::{self, Bus, SlaveSelect, Spi};
 use std::io::Write;
 use std::path::Path;
@@ -64,6 +64,16 @@ pub struct RppalSpiBus {
     bufsiz: usize,
 }
 
+/// Converts an EndBASIC SPI mode to an rppal SPI mode.
+fn spi_mode_to_rppal(mode: SpiMode) -> spi::Mode {
+    match mode {
+        SpiMode::Mode0 => spi::Mode::Mode0,
+        SpiMode::Mode1 => spi::Mode::Mode1,
+        SpiMode::Mode2 => spi::Mode::Mode2,
+        SpiMode::Mode3 => spi::Mode::Mode3,
+    }
+}
+
 /// Factory function to open an `RppalSpiBus`.
 pub fn spi_bus_open(bus: u8, slave: u8, clock_hz: u32, mode: SpiMode) -> io::Result<RppalSpiBus> {
     let bus = match bus {
@@ -76,14 +86,8 @@ pub fn spi_bus_open(bus: u8, slave: u8, clock_hz: u32, mode: SpiMode) -> io::Res
         _ => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Only slave 0 is supported")),
     };
 
-    let mode = match mode {
-        SpiMode::Mode0 => spi::Mode::Mode0,
-        SpiMode::Mode1 => spi::Mode::Mode1,
-        SpiMode::Mode2 => spi::Mode::Mode2,
-        SpiMode::Mode3 => spi::Mode::Mode3,
-    };
-
-    let spi = Spi::new(bus, slave, clock_hz, mode).map_err(spi_error_to_io_error)?;
+    let spi =
+        Spi::new(bus, slave, clock_hz, spi_mode_to_rppal(mode)).map_err(spi_error_to_io_error)?;
 
     let bufsiz = query_spi_bufsiz(None)?;
 
@@ -105,3 +109,90 @@ impl SpiBus for RppalSpiBus {
         self.bufsiz
     }
 }
+
+/// An open SPI device along with its maximum transfer size.
+struct OpenSpi {
+    spi: Spi,
+    bufsiz: usize,
+}
+
+/// An implementation of the `SPI_*` commands backend using rppal, which talks to spidev.
+#[derive(Default)]
+pub struct RppalSpi {
+    open: Option<OpenSpi>,
+}
+
+impl std_spi::Spi for RppalSpi {
+    fn open(&mut self, device: SpiDevice, speed_hz: u32, mode: SpiMode) -> io::Result<()> {
+        let bus = match device.bus {
+            0 => Bus::Spi0,
+            1 => Bus::Spi1,
+            2 => Bus::Spi2,
+            3 => Bus::Spi3,
+            4 => Bus::Spi4,
+            5 => Bus::Spi5,
+            6 => Bus::Spi6,
+            i => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown SPI bus {}", i),
+                ))
+            }
+        };
+
+        let slave = match device.cs {
+            0 => SlaveSelect::Ss0,
+            1 => SlaveSelect::Ss1,
+            2 => SlaveSelect::Ss2,
+            3 => SlaveSelect::Ss3,
+            4 => SlaveSelect::Ss4,
+            5 => SlaveSelect::Ss5,
+            6 => SlaveSelect::Ss6,
+            7 => SlaveSelect::Ss7,
+            i => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown SPI chip select {}", i),
+                ))
+            }
+        };
+
+        self.open = None;
+        let spi = Spi::new(bus, slave, speed_hz, spi_mode_to_rppal(mode))
+            .map_err(spi_error_to_io_error)?;
+        let bufsiz = query_spi_bufsiz(None)?;
+        self.open = Some(OpenSpi { spi, bufsiz });
+        Ok(())
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        self.open = None;
+        Ok(())
+    }
+
+    fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> io::Result<()> {
+        debug_assert_eq!(tx.len(), rx.len());
+        let open = match self.open.as_mut() {
+            Some(open) => open,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotConnected,
+                    "No SPI device is open; use SPI_OPEN first",
+                ))
+            }
+        };
+
+        // spidev rejects transfers larger than its buffer size, so split them.  Note that this
+        // may toggle the chip select line between chunks.
+        for (tx, rx) in tx.chunks(open.bufsiz).zip(rx.chunks_mut(open.bufsiz)) {
+            let n = open.spi.transfer(rx, tx).map_err(spi_error_to_io_error)?;
+            if n != tx.len() {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    format!("Short SPI transfer: {} of {} bytes", n, tx.len()),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -20,12 +20,14 @@ use async_channel::Sender;
 use async_trait::async_trait;
 use endbasic_core::exec::Signal;
 use endbasic_std::console::{
-    remove_control_chars, CharsXY, ClearType, Console, Key, PixelsXY, Resolution, SizeInPixels,
+    remove_control_chars, CharsXY, ClearType, ClipRect, Console, Key, PixelsXY, Resolution,
+    ScreenCell, ScreenRotation, SizeInPixels, TextRotation,
 };
 use std::io;
 use std::path::PathBuf;
 use std::sync::mpsc::{self, Receiver, SyncSender, TryRecvError};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 
 /// Implementation of the EndBASIC console on top of an SDL2 window.
 ///
@@ -92,6 +94,16 @@ impl SdlConsole {
         }
     }
 
+    /// Queues a tone of `freq` Hz that lasts for `duration` on the audio device of the console.
+    pub(crate) fn tone(&self, freq: f64, duration: Duration) -> io::Result<()> {
+        self.call(Request::Tone(freq, duration))
+    }
+
+    /// Queues silence that lasts for `duration` on the audio device of the console.
+    pub(crate) fn rest(&self, duration: Duration) -> io::Result<()> {
+        self.call(Request::Rest(duration))
+    }
+
     /// Issues a synchronous call against the console host for a request that returns nothing but
     /// an error (if any).
     fn call(&self, request: Request) -> io::Result<()> {
@@ -220,6 +232,22 @@ impl Console for SdlConsole {
         self.call(Request::Write(text))
     }
 
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        self.request_tx.send(Request::ReadScreen).expect("Channel must be alive");
+        match self.response_rx.recv().expect("Channel must be alive") {
+            Response::ReadScreen(result) => result,
+            _ => panic!("Unexpected response type"),
+        }
+    }
+
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        self.request_tx.send(Request::CursorPos).expect("Channel must be alive");
+        match self.response_rx.recv().expect("Channel must be alive") {
+            Response::CursorPos(result) => result,
+            _ => panic!("Unexpected response type"),
+        }
+    }
+
     fn draw_circle(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
         self.call(Request::DrawCircle(center, radius))
     }
@@ -244,6 +272,44 @@ impl Console for SdlConsole {
         self.call(Request::DrawRectFilled(x1y1, x2y2))
     }
 
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        self.call(Request::DrawText(xy, text.to_owned(), scale, rotation))
+    }
+
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        self.call(Request::CopyPixels(src, size, dst))
+    }
+
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        self.call(Request::ScrollPixels(dx, dy))
+    }
+
+    fn set_clip(&mut self, clip: Option<ClipRect>) -> io::Result<()> {
+        self.call(Request::SetClip(clip))
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<()> {
+        self.call(Request::SetRotation(rotation))
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        self.call(Request::SetTextFont(name.to_owned()))
+    }
+
+    fn text_width(&self, text: &str) -> io::Result<u16> {
+        self.request_tx.send(Request::TextWidth(text.to_owned())).expect("Channel must be alive");
+        match self.response_rx.recv().expect("Channel must be alive") {
+            Response::TextWidth(result) => result,
+            _ => panic!("Unexpected response type"),
+        }
+    }
+
     fn sync_now(&mut self) -> io::Result<()> {
         self.call(Request::SyncNow)
     }
@@ -25,8 +25,10 @@ use endbasic_core::exec::Signal;
 use endbasic_std::console::drawing::{draw_circle, draw_circle_filled};
 use endbasic_std::console::graphics::{ClampedInto, ClampedMul, InputOps, RasterInfo, RasterOps};
 use endbasic_std::console::{
-    CharsXY, ClearType, Console, GraphicsConsole, Key, PixelsXY, Resolution, SizeInPixels, RGB,
+    CharsXY, ClearType, ClipRect, Console, GraphicsConsole, Key, PixelsXY, Resolution, ScreenCell,
+    ScreenRotation, SizeInPixels, TextRotation, RGB,
 };
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
 use sdl2::event::Event;
 use sdl2::keyboard::{Keycode, Mod};
 use sdl2::pixels::{Color, PixelFormatEnum};
@@ -56,6 +58,12 @@ const LOOP_POLL_BUDGET: u16 = 10000;
 /// Delay between loop iterations when the polling budget in `LOOP_POLL_BUDGET` is exceeded.
 const LOOP_DELAY_MS: u64 = 1;
 
+/// Sample rate to request from the audio device, in Hz.
+const AUDIO_SAMPLE_RATE: i32 = 44100;
+
+/// Amplitude of the generated tones as a fraction of the maximum volume.
+const TONE_VOLUME: f64 = 0.25;
+
 /// Converts a `fmt::Error` to an `io::Error`.
 fn fmt_error_to_io_error(e: fmt::Error) -> io::Error {
     io::Error::new(io::ErrorKind::Other, e)
@@ -155,6 +163,7 @@ fn parse_event(event: Event) -> Option<Key> {
             Keycode::P if (keymod == Mod::LCTRLMOD || keymod == Mod::RCTRLMOD) => {
                 Some(Key::ArrowUp)
             }
+            Keycode::R if (keymod == Mod::LCTRLMOD || keymod == Mod::RCTRLMOD) => Some(Key::Search),
 
             Keycode::Backspace => Some(Key::Backspace),
             Keycode::End => Some(Key::End),
@@ -197,6 +206,10 @@ struct Context {
     #[cfg_attr(not(test), allow(unused))]
     sdl: Sdl,
 
+    /// Queue of samples to play through the audio device, or `None` if the device could not be
+    /// opened, in which case tones are silently dropped.
+    audio: Option<AudioQueue<i16>>,
+
     /// Monospaced font to use in the console.
     font: MonospacedFont<'static>,
 
@@ -239,6 +252,7 @@ impl Context {
         let font = MonospacedFont::load(&font_path, font_size)?;
 
         let sdl = sdl2::init().map_err(string_error_to_io_error)?;
+        let audio = open_audio(&sdl).ok();
         let event_pump = sdl.event_pump().map_err(string_error_to_io_error)?;
         let video = sdl.video().map_err(string_error_to_io_error)?;
 
@@ -292,6 +306,7 @@ impl Context {
 
         Ok(Self {
             sdl,
+            audio,
             font,
             event_pump,
             window,
@@ -305,6 +320,51 @@ impl Context {
     }
 }
 
+impl Context {
+    /// Queues a square wave of `freq` Hz that lasts for `duration` on the audio device.
+    fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()> {
+        let audio = match self.audio.as_ref() {
+            Some(audio) => audio,
+            None => return Ok(()),
+        };
+
+        let rate = f64::from(audio.spec().freq);
+        let channels = usize::from(audio.spec().channels);
+        let amplitude = (f64::from(i16::MAX) * TONE_VOLUME) as i16;
+        let nsamples = (duration.as_secs_f64() * rate).round() as usize;
+        let mut data = Vec::with_capacity(nsamples * channels);
+        for i in 0..nsamples {
+            let phase = (i as f64 * freq / rate).fract();
+            let sample = if phase < 0.5 { amplitude } else { -amplitude };
+            data.resize(data.len() + channels, sample);
+        }
+        audio.queue_audio(&data).map_err(string_error_to_io_error)
+    }
+
+    /// Queues silence that lasts for `duration` on the audio device.
+    fn rest(&mut self, duration: Duration) -> io::Result<()> {
+        let audio = match self.audio.as_ref() {
+            Some(audio) => audio,
+            None => return Ok(()),
+        };
+
+        let rate = f64::from(audio.spec().freq);
+        let channels = usize::from(audio.spec().channels);
+        let nsamples = (duration.as_secs_f64() * rate).round() as usize;
+        audio.queue_audio(&vec![0; nsamples * channels]).map_err(string_error_to_io_error)
+    }
+}
+
+/// Opens the default audio device of `sdl` to play tones.
+fn open_audio(sdl: &Sdl) -> io::Result<AudioQueue<i16>> {
+    let subsystem = sdl.audio().map_err(string_error_to_io_error)?;
+    let desired =
+        AudioSpecDesired { freq: Some(AUDIO_SAMPLE_RATE), channels: Some(1), samples: None };
+    let audio = subsystem.open_queue(None, &desired).map_err(string_error_to_io_error)?;
+    audio.resume();
+    Ok(audio)
+}
+
 impl RasterOps for Context {
     type ID = (Vec<u8>, SizeInPixels);
 
@@ -459,6 +519,14 @@ impl RasterOps for Context {
 struct SharedContext(Rc<RefCell<Context>>);
 
 impl SharedContext {
+    fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()> {
+        (*self.0).borrow_mut().tone(freq, duration)
+    }
+
+    fn rest(&mut self, duration: Duration) -> io::Result<()> {
+        (*self.0).borrow_mut().rest(duration)
+    }
+
     fn poll_event(&mut self) -> Option<Event> {
         (*self.0).borrow_mut().event_pump.poll_event()
     }
@@ -563,14 +631,25 @@ pub(crate) enum Request {
     SizeChars,
     SizePixels,
     Write(String),
+    ReadScreen,
+    CursorPos,
     DrawCircle(PixelsXY, u16),
     DrawCircleFilled(PixelsXY, u16),
     DrawLine(PixelsXY, PixelsXY),
     DrawPixel(PixelsXY),
     DrawRect(PixelsXY, PixelsXY),
     DrawRectFilled(PixelsXY, PixelsXY),
+    DrawText(PixelsXY, String, u16, TextRotation),
+    CopyPixels(PixelsXY, SizeInPixels, PixelsXY),
+    ScrollPixels(i16, i16),
+    SetClip(Option<ClipRect>),
+    SetRotation(ScreenRotation),
+    SetTextFont(String),
+    TextWidth(String),
     SyncNow,
     SetSync(bool),
+    Tone(f64, Duration),
+    Rest(Duration),
 
     #[cfg(test)]
     PushEvent(Event),
@@ -587,6 +666,9 @@ pub(crate) enum Response {
     SizeChars(CharsXY),
     SizePixels(SizeInPixels),
     SetSync(io::Result<bool>),
+    TextWidth(io::Result<u16>),
+    ReadScreen(io::Result<Vec<Vec<ScreenCell>>>),
+    CursorPos(io::Result<CharsXY>),
 }
 
 /// Implementation of `InputOps` that should never be used.
@@ -657,6 +739,8 @@ pub(crate) fn run(
                     Request::SizeChars => Response::SizeChars(info.size_chars),
                     Request::SizePixels => Response::SizePixels(info.size_pixels),
                     Request::Write(text) => Response::Empty(console.write(&text)),
+                    Request::ReadScreen => Response::ReadScreen(console.read_screen()),
+                    Request::CursorPos => Response::CursorPos(console.cursor_pos()),
                     Request::DrawCircle(center, radius) => {
                         Response::Empty(console.draw_circle(center, radius))
                     }
@@ -669,8 +753,23 @@ pub(crate) fn run(
                     Request::DrawRectFilled(x1y1, x2y2) => {
                         Response::Empty(console.draw_rect_filled(x1y1, x2y2))
                     }
+                    Request::DrawText(xy, text, scale, rotation) => {
+                        Response::Empty(console.draw_text(xy, &text, scale, rotation))
+                    }
+                    Request::CopyPixels(src, size, dst) => {
+                        Response::Empty(console.copy_pixels(src, size, dst))
+                    }
+                    Request::ScrollPixels(dx, dy) => Response::Empty(console.scroll_pixels(dx, dy)),
+                    Request::SetClip(clip) => Response::Empty(console.set_clip(clip)),
+                    Request::SetRotation(rotation) => {
+                        Response::Empty(console.set_rotation(rotation))
+                    }
+                    Request::SetTextFont(name) => Response::Empty(console.set_text_font(&name)),
+                    Request::TextWidth(text) => Response::TextWidth(console.text_width(&text)),
                     Request::SyncNow => Response::Empty(console.sync_now()),
                     Request::SetSync(enabled) => Response::SetSync(console.set_sync(enabled)),
+                    Request::Tone(freq, duration) => Response::Empty(ctx.tone(freq, duration)),
+                    Request::Rest(duration) => Response::Empty(ctx.rest(duration)),
 
                     #[cfg(test)]
                     Request::PushEvent(ev) => Response::Empty(ctx.push_event(ev)),
@@ -24,6 +24,7 @@
 
 use async_channel::Sender;
 use endbasic_core::exec::Signal;
+use endbasic_std::audio::Audio;
 use endbasic_std::console::{Console, ConsoleSpec, Resolution};
 use std::cell::RefCell;
 use std::fs::File;
@@ -31,6 +32,7 @@ use std::io::{self, Write};
 use std::num::NonZeroU32;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
 use tempfile::TempDir;
 
 mod console;
@@ -71,11 +73,25 @@ impl TempFont {
     }
 }
 
-/// Creates the graphical console based on the given `spec`.
-pub fn setup(
-    spec: &mut ConsoleSpec,
-    signals_tx: Sender<Signal>,
-) -> io::Result<Rc<RefCell<dyn Console>>> {
+/// Implementation of the EndBASIC audio operations that plays tones via the SDL console.
+struct SdlAudio(Rc<RefCell<console::SdlConsole>>);
+
+impl Audio for SdlAudio {
+    fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()> {
+        self.0.borrow().tone(freq, duration)
+    }
+
+    fn rest(&mut self, duration: Duration) -> io::Result<()> {
+        self.0.borrow().rest(duration)
+    }
+}
+
+/// A graphical console and the audio device attached to it.
+pub type ConsoleWithAudio = (Rc<RefCell<dyn Console>>, Rc<RefCell<dyn Audio>>);
+
+/// Creates the graphical console based on the given `spec`, along with the audio device that
+/// plays tones through it.
+pub fn setup(spec: &mut ConsoleSpec, signals_tx: Sender<Signal>) -> io::Result<ConsoleWithAudio> {
     let resolution: Resolution = spec.take_keyed_flag("resolution")?.unwrap_or_else(|| {
         let width = NonZeroU32::new(DEFAULT_RESOLUTION_PIXELS.0).unwrap();
         let height = NonZeroU32::new(DEFAULT_RESOLUTION_PIXELS.1).unwrap();
@@ -112,5 +128,7 @@ pub fn setup(
             signals_tx,
         )?,
     };
-    Ok(Rc::from(RefCell::from(console)))
+    let console = Rc::from(RefCell::from(console));
+    let audio = SdlAudio(console.clone());
+    Ok((console, Rc::from(RefCell::from(audio))))
 }
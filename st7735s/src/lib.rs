@@ -25,15 +25,19 @@
 
 use async_channel::{Receiver, TryRecvError};
 use async_trait::async_trait;
-use endbasic_std::console::graphics::InputOps;
+use endbasic_std::console::graphics::{InputOps, RasterOps};
 use endbasic_std::console::{
-    CharsXY, ClearType, Console, ConsoleSpec, GraphicsConsole, Key, ParseError, PixelsXY,
-    SizeInPixels, RGB,
+    CharsXY, ClearType, ClipRect, Console, ConsoleSpec, GraphicsConsole, Key, PixelsXY, ScreenCell,
+    ScreenRotation, SizeInPixels, TextRotation, RGB,
 };
 use endbasic_std::gfx::lcd::fonts::Fonts;
-use endbasic_std::gfx::lcd::{to_xy_size, BufferedLcd, Lcd, LcdSize, LcdXY, RGB565Pixel};
+use endbasic_std::gfx::lcd::{
+    take_backlight_flags, take_font_flag, take_rotation_flag, to_xy_size, Backlight, BufferedLcd,
+    Lcd, LcdSize, LcdXY, RGB565Pixel,
+};
 use endbasic_std::gpio::{Pin, PinMode, Pins};
-use endbasic_std::spi::{SpiBus, SpiMode};
+use endbasic_std::pwm::PwmChannel;
+use endbasic_std::spi::{write_all, SpiBus, SpiMode};
 use std::io;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
@@ -130,41 +134,26 @@ impl<K: InputOps> InputOps for ST7735SInput<K> {
     }
 }
 
-/// Writes arbitrary data to the SPI bus.
-///
-/// The input data is chunked to respect the maximum write size accepted by the SPI bus.
-fn lcd_write<B: SpiBus>(spi_bus: &mut B, data: &[u8]) -> io::Result<()> {
-    // TODO(jmmv): Do we really need to chunk the data ourselves, or can we try to write it
-    // all to the bus and then expect the write to return partial results?
-    for chunk in data.chunks(spi_bus.max_size()) {
-        let mut i = 0;
-        loop {
-            let n = spi_bus.write(&chunk[i..])?;
-            if n == chunk.len() - i {
-                break;
-            }
-            i += n;
-        }
-    }
-    Ok(())
-}
-
 /// LCD handler for the ST7735S console.
-struct ST7735SLcd<P: Pins, B> {
+struct ST7735SLcd<P: Pins, B, W: PwmChannel> {
     pins: Arc<Mutex<P>>,
     spi_bus: B,
+    backlight: Backlight<W>,
     size_pixels: LcdSize,
 }
 
-impl<P: Pins, B: SpiBus> ST7735SLcd<P, B> {
-    /// Initializes the LCD.
-    pub fn new<F>(pins: Arc<Mutex<P>>, spi_factory: F) -> io::Result<Self>
+impl<P: Pins, B: SpiBus, W: PwmChannel> ST7735SLcd<P, B, W> {
+    /// Initializes the LCD, controlling its brightness via `backlight`.
+    pub fn new<F>(pins: Arc<Mutex<P>>, spi_factory: F, backlight: Backlight<W>) -> io::Result<Self>
     where
         F: FnOnce(u8, u8, u32, SpiMode) -> io::Result<B>,
     {
         {
             let mut pins = pins.lock().unwrap();
-            for pin in [OUTPUT_PIN_CS, OUTPUT_PIN_RST, OUTPUT_PIN_DC, OUTPUT_PIN_BL] {
+            for pin in [OUTPUT_PIN_CS, OUTPUT_PIN_RST, OUTPUT_PIN_DC] {
+                pins.setup(pin, PinMode::Out)?;
+            }
+            if let Backlight::Pin(pin) = backlight {
                 pins.setup(pin, PinMode::Out)?;
             }
         }
@@ -173,7 +162,7 @@ impl<P: Pins, B: SpiBus> ST7735SLcd<P, B> {
 
         let size_pixels = LcdSize { width: 128, height: 128 };
 
-        let mut device = Self { pins, spi_bus, size_pixels };
+        let mut device = Self { pins, spi_bus, backlight, size_pixels };
 
         device.lcd_init()?;
 
@@ -183,13 +172,13 @@ impl<P: Pins, B: SpiBus> ST7735SLcd<P, B> {
     /// Selects the registers to affect by the next data write.
     fn lcd_write_reg(pins: &mut P, spi_bus: &mut B, regs: &[u8]) -> io::Result<()> {
         pins.write(OUTPUT_PIN_DC, false)?;
-        lcd_write(spi_bus, regs)
+        write_all(spi_bus, regs)
     }
 
     /// Writes data to the device.  A register should have been selected before.
     fn lcd_write_data(pins: &mut P, spi_bus: &mut B, data: &[u8]) -> io::Result<()> {
         pins.write(OUTPUT_PIN_DC, true)?;
-        lcd_write(spi_bus, data)
+        write_all(spi_bus, data)
     }
 
     /// Resets the LCD.
@@ -290,7 +279,7 @@ impl<P: Pins, B: SpiBus> ST7735SLcd<P, B> {
         // setting this to high on NetBSD causes the LCD to remain lit up.
         pins.write(OUTPUT_PIN_CS, false)?;
 
-        pins.write(OUTPUT_PIN_BL, true)?;
+        self.backlight.set(&mut *pins, 100)?;
 
         Self::lcd_reset(&mut *pins)?;
         Self::lcd_init_reg(&mut *pins, &mut self.spi_bus)?;
@@ -330,14 +319,14 @@ impl<P: Pins, B: SpiBus> ST7735SLcd<P, B> {
     }
 }
 
-impl<P: Pins, B> Drop for ST7735SLcd<P, B> {
+impl<P: Pins, B, W: PwmChannel> Drop for ST7735SLcd<P, B, W> {
     fn drop(&mut self) {
         let mut pins = self.pins.lock().unwrap();
-        let _result = pins.write(OUTPUT_PIN_BL, false);
+        let _result = self.backlight.set(&mut *pins, 0);
     }
 }
 
-impl<P: Pins, B: SpiBus> Lcd for ST7735SLcd<P, B> {
+impl<P: Pins, B: SpiBus, W: PwmChannel> Lcd for ST7735SLcd<P, B, W> {
     type Pixel = RGB565Pixel;
 
     fn info(&self) -> (LcdSize, usize) {
@@ -345,13 +334,7 @@ impl<P: Pins, B: SpiBus> Lcd for ST7735SLcd<P, B> {
     }
 
     fn encode(&self, rgb: RGB) -> Self::Pixel {
-        let rgb = (u16::from(rgb.0), u16::from(rgb.1), u16::from(rgb.2));
-
-        let pixel: u16 = ((rgb.0 >> 3) << 11) | ((rgb.1 >> 2) << 5) | (rgb.2 >> 3);
-
-        let high = (pixel >> 8) as u8;
-        let low = (pixel & 0xff) as u8;
-        RGB565Pixel([high, low])
+        RGB565Pixel::from_rgb(rgb)
     }
 
     fn set_data(&mut self, x1y1: LcdXY, x2y2: LcdXY, data: &[u8]) -> io::Result<()> {
@@ -360,17 +343,22 @@ impl<P: Pins, B: SpiBus> Lcd for ST7735SLcd<P, B> {
         Self::lcd_set_window(&mut *pins, &mut self.spi_bus, xy, size)?;
         Self::lcd_write_data(&mut *pins, &mut self.spi_bus, data)
     }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        let mut pins = self.pins.lock().unwrap();
+        self.backlight.set(&mut *pins, pct)
+    }
 }
 
 /// Console implementation using an ST7735S LCD.
-pub struct ST7735SConsole<P: Pins + Send, B: SpiBus, K> {
+pub struct ST7735SConsole<P: Pins + Send, B: SpiBus, W: PwmChannel, K> {
     /// The graphical console itself.  We wrap it in a struct to prevent leaking all auxiliary types
     /// outside of this crate.
-    inner: GraphicsConsole<ST7735SInput<K>, BufferedLcd<ST7735SLcd<P, B>>>,
+    inner: GraphicsConsole<ST7735SInput<K>, BufferedLcd<ST7735SLcd<P, B, W>>>,
 }
 
 #[async_trait(?Send)]
-impl<P: Pins + Send, B: SpiBus, K: InputOps> Console for ST7735SConsole<P, B, K> {
+impl<P: Pins + Send, B: SpiBus, W: PwmChannel, K: InputOps> Console for ST7735SConsole<P, B, W, K> {
     fn clear(&mut self, how: ClearType) -> io::Result<()> {
         self.inner.clear(how)
     }
@@ -435,6 +423,14 @@ impl<P: Pins + Send, B: SpiBus, K: InputOps> Console for ST7735SConsole<P, B, K>
         self.inner.write(text)
     }
 
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        self.inner.read_screen()
+    }
+
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        self.inner.cursor_pos()
+    }
+
     fn draw_circle(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
         self.inner.draw_circle(center, radius)
     }
@@ -459,6 +455,44 @@ impl<P: Pins + Send, B: SpiBus, K: InputOps> Console for ST7735SConsole<P, B, K>
         self.inner.draw_rect_filled(x1y1, x2y2)
     }
 
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        self.inner.draw_text(xy, text, scale, rotation)
+    }
+
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        self.inner.copy_pixels(src, size, dst)
+    }
+
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        self.inner.scroll_pixels(dx, dy)
+    }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        self.inner.set_brightness(pct)
+    }
+
+    fn set_clip(&mut self, clip: Option<ClipRect>) -> io::Result<()> {
+        self.inner.set_clip(clip)
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<()> {
+        self.inner.set_rotation(rotation)
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        self.inner.set_text_font(name)
+    }
+
+    fn text_width(&self, text: &str) -> io::Result<u16> {
+        self.inner.text_width(text)
+    }
+
     fn sync_now(&mut self) -> io::Result<()> {
         self.inner.sync_now()
     }
@@ -469,93 +503,38 @@ impl<P: Pins + Send, B: SpiBus, K: InputOps> Console for ST7735SConsole<P, B, K>
 }
 
 /// Initializes a new console on a ST7735S LCD.
-pub fn new_console<P, F, B, K>(
+///
+/// The backlight is controlled by toggling a GPIO pin unless the `spec` requests a PWM channel,
+/// in which case `new_pwm` is used to open it.
+pub fn new_console<P, F, B, G, W, K>(
     pins: P,
     new_spi: F,
+    new_pwm: G,
     keyboard: K,
     spec: &mut ConsoleSpec,
     fonts: &Fonts,
-) -> io::Result<ST7735SConsole<P, B, K>>
+) -> io::Result<ST7735SConsole<P, B, W, K>>
 where
     P: Pins + Send + 'static,
     F: FnOnce(u8, u8, u32, SpiMode) -> io::Result<B>,
     B: SpiBus,
+    G: FnOnce(u8) -> io::Result<W>,
+    W: PwmChannel,
     K: InputOps,
 {
     let default_fg_color = spec.take_keyed_flag::<u8>("fg_color")?;
     let default_bg_color = spec.take_keyed_flag::<u8>("bg_color")?;
 
-    let font_name = spec.take_keyed_flag_str("font").unwrap_or("5x8");
-    let font = match fonts.get(font_name) {
-        Some(font) => font,
-        None => {
-            let mut valid = fonts.keys().copied().collect::<Vec<&'static str>>();
-            valid.sort();
-            return Err(ParseError(format!(
-                "Unknown font: {}; valid names are: {}",
-                font_name,
-                valid.join(", ")
-            ))
-            .into());
-        }
-    };
+    let font = take_font_flag(spec, fonts, "5x8")?;
+    let rotation = take_rotation_flag(spec)?;
+    let backlight = take_backlight_flags(spec, Some(OUTPUT_PIN_BL), new_pwm)?
+        .expect("Must have a backlight because we provided a default pin");
 
     let pins = Arc::from(Mutex::from(pins));
-    let lcd = ST7735SLcd::new(pins.clone(), new_spi)?;
+    let lcd = ST7735SLcd::new(pins.clone(), new_spi, backlight)?;
     let input = ST7735SInput::new(pins, keyboard)?;
-    let lcd = BufferedLcd::new(lcd, font);
+    let mut lcd = BufferedLcd::new(lcd, font)?;
+    lcd.set_rotation(rotation)?;
     let inner = GraphicsConsole::new(input, lcd, default_fg_color, default_bg_color)?;
     Ok(ST7735SConsole { inner })
 }
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::io::Write;
-
-    #[derive(Default)]
-    struct MockSpiBus {
-        max_size: usize,
-
-        writes: Vec<Vec<u8>>,
-    }
-
-    impl Write for MockSpiBus {
-        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-            let partial = if buf.len() < self.max_size { buf } else { &buf[0..self.max_size] };
-            self.writes.push(partial.to_owned());
-            Ok(partial.len())
-        }
-
-        fn flush(&mut self) -> io::Result<()> {
-            Ok(())
-        }
-    }
-
-    impl SpiBus for MockSpiBus {
-        fn max_size(&self) -> usize {
-            self.max_size
-        }
-    }
-
-    #[test]
-    fn test_lcd_write_shorter_than_max_size() {
-        let mut bus = MockSpiBus { max_size: 100, ..Default::default() };
-        lcd_write(&mut bus, &[0, 1, 2, 3, 4]).unwrap();
-        assert_eq!(vec![vec![0, 1, 2, 3, 4]], bus.writes);
-    }
-
-    #[test]
-    fn test_lcd_write_equal_to_max_size() {
-        let mut bus = MockSpiBus { max_size: 3, ..Default::default() };
-        lcd_write(&mut bus, &[0, 1, 2]).unwrap();
-        assert_eq!(vec![vec![0, 1, 2]], bus.writes);
-    }
-
-    #[test]
-    fn test_lcd_write_greater_than_max_size() {
-        let mut bus = MockSpiBus { max_size: 6, ..Default::default() };
-        lcd_write(&mut bus, &[0, 1, 2, 3, 4, 5, 6]).unwrap();
-        assert_eq!(vec![vec![0, 1, 2, 3, 4, 5], vec![6]], bus.writes);
-    }
-}
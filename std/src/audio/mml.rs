@@ -0,0 +1,421 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Parser for the music macro language (MML) accepted by `PLAY`.
+
+use std::iter::Peekable;
+use std::str::Chars;
+use std::time::Duration;
+
+/// Octave used until the string selects a different one.
+const DEFAULT_OCTAVE: i32 = 4;
+
+/// Note length used until the string selects a different one, as a fraction of a whole note.
+const DEFAULT_LENGTH: u32 = 4;
+
+/// Tempo used until the string selects a different one, in quarter notes per minute.
+const DEFAULT_TEMPO: u32 = 120;
+
+/// Highest octave that can be selected.
+const MAX_OCTAVE: i32 = 6;
+
+/// Number of the A4 note, whose frequency is `A4_FREQUENCY`.
+const A4_NOTE: i32 = 4 * 12 + 9;
+
+/// Frequency of the A4 note in Hz, which is the reference for all other notes.
+const A4_FREQUENCY: f64 = 440.0;
+
+/// An event to play, as produced by parsing an MML string.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum MmlEvent {
+    /// Plays the note with the given number for the given duration.  Notes are numbered in
+    /// semitones from C0, which is note 0.
+    Note(i32, Duration),
+
+    /// Stays silent for the given duration.
+    Pause(Duration),
+}
+
+impl MmlEvent {
+    /// Returns the duration of the event.
+    pub(crate) fn duration(&self) -> Duration {
+        match self {
+            MmlEvent::Note(_, duration) => *duration,
+            MmlEvent::Pause(duration) => *duration,
+        }
+    }
+}
+
+/// Computes the frequency in Hz of the `note` number using equal temperament.
+pub(crate) fn note_frequency(note: i32) -> f64 {
+    A4_FREQUENCY * 2.0_f64.powf(f64::from(note - A4_NOTE) / 12.0)
+}
+
+/// Error raised while parsing an MML string.
+#[derive(Debug, PartialEq)]
+pub(crate) struct MmlError {
+    /// Zero-based character offset within the string at which the error was found.
+    pub(crate) offset: usize,
+
+    /// Description of the error.
+    pub(crate) message: String,
+}
+
+/// Result type for MML parsing operations.
+type MmlResult<T> = Result<T, MmlError>;
+
+/// State of the MML parser.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    offset: usize,
+    octave: i32,
+    length: u32,
+    tempo: u32,
+}
+
+impl<'a> Parser<'a> {
+    /// Creates a new parser for the `input` string.
+    fn new(input: &'a str) -> Self {
+        Self {
+            chars: input.chars().peekable(),
+            offset: 0,
+            octave: DEFAULT_OCTAVE,
+            length: DEFAULT_LENGTH,
+            tempo: DEFAULT_TEMPO,
+        }
+    }
+
+    /// Consumes and returns the next character, if any, keeping track of its offset.
+    fn next(&mut self) -> Option<char> {
+        let ch = self.chars.next()?;
+        self.offset += 1;
+        Some(ch)
+    }
+
+    /// Returns the next character without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+
+    /// Consumes an optional number and returns it.  `offset` is the position of the command that
+    /// takes the number, which is used to report overflows.
+    fn parse_number(&mut self, offset: usize) -> MmlResult<Option<u32>> {
+        let mut number: Option<u32> = None;
+        while let Some(ch) = self.peek() {
+            let digit = match ch.to_digit(10) {
+                Some(digit) => digit,
+                None => break,
+            };
+            self.next();
+            number = match number.unwrap_or(0).checked_mul(10).and_then(|n| n.checked_add(digit)) {
+                Some(n) => Some(n),
+                None => return Err(MmlError { offset, message: "Number is too large".to_owned() }),
+            };
+        }
+        Ok(number)
+    }
+
+    /// Consumes a mandatory number for the command `cmd` at `offset` and checks that it is within
+    /// the `min` and `max` limits, where `what` describes the value in error messages.
+    fn parse_arg(
+        &mut self,
+        cmd: char,
+        offset: usize,
+        what: &str,
+        min: u32,
+        max: u32,
+    ) -> MmlResult<u32> {
+        match self.parse_number(offset)? {
+            Some(n) if (min..=max).contains(&n) => Ok(n),
+            Some(n) => Err(MmlError {
+                offset,
+                message: format!("{} {} must be between {} and {}", what, n, min, max),
+            }),
+            None => Err(MmlError { offset, message: format!("Missing number after {}", cmd) }),
+        }
+    }
+
+    /// Consumes the optional length and dots that follow a note or a pause and computes the
+    /// duration of the event.  `offset` is the position of the note or pause.
+    fn parse_duration(&mut self, offset: usize) -> MmlResult<Duration> {
+        let length = match self.parse_number(offset)? {
+            Some(n) if (1..=64).contains(&n) => n,
+            Some(n) => {
+                return Err(MmlError {
+                    offset,
+                    message: format!("Length {} must be between 1 and 64", n),
+                })
+            }
+            None => self.length,
+        };
+
+        // A whole note lasts for four beats at the current tempo.
+        let mut part = 240.0 / f64::from(self.tempo) / f64::from(length);
+        let mut secs = part;
+        while self.peek() == Some('.') {
+            self.next();
+            part /= 2.0;
+            secs += part;
+        }
+        Ok(Duration::from_secs_f64(secs))
+    }
+
+    /// Parses a note whose letter `ch` was found at `offset`.
+    fn parse_note(&mut self, ch: char, offset: usize) -> MmlResult<MmlEvent> {
+        let semitone = match ch {
+            'C' => 0,
+            'D' => 2,
+            'E' => 4,
+            'F' => 5,
+            'G' => 7,
+            'A' => 9,
+            'B' => 11,
+            _ => unreachable!("Caller must only pass note letters"),
+        };
+        let accidental = match self.peek() {
+            Some('#') | Some('+') => {
+                self.next();
+                1
+            }
+            Some('-') => {
+                self.next();
+                -1
+            }
+            _ => 0,
+        };
+
+        let note = self.octave * 12 + semitone + accidental;
+        if !(0..(MAX_OCTAVE + 1) * 12).contains(&note) {
+            return Err(MmlError { offset, message: "Note is out of range".to_owned() });
+        }
+        let duration = self.parse_duration(offset)?;
+        Ok(MmlEvent::Note(note, duration))
+    }
+
+    /// Parses the whole input string.
+    fn parse(mut self) -> MmlResult<Vec<MmlEvent>> {
+        let mut events = vec![];
+        loop {
+            let offset = self.offset;
+            let ch = match self.next() {
+                Some(ch) => ch.to_ascii_uppercase(),
+                None => break,
+            };
+            match ch {
+                ' ' | '\t' => (),
+
+                'A'..='G' => events.push(self.parse_note(ch, offset)?),
+
+                'L' => self.length = self.parse_arg(ch, offset, "Length", 1, 64)?,
+
+                'O' => {
+                    self.octave = self.parse_arg(ch, offset, "Octave", 0, MAX_OCTAVE as u32)? as i32
+                }
+
+                'P' => {
+                    if !self.peek().map(|ch| ch.is_ascii_digit()).unwrap_or(false) {
+                        return Err(MmlError {
+                            offset,
+                            message: "Missing number after P".to_owned(),
+                        });
+                    }
+                    events.push(MmlEvent::Pause(self.parse_duration(offset)?));
+                }
+
+                'T' => self.tempo = self.parse_arg(ch, offset, "Tempo", 32, 255)?,
+
+                '<' => {
+                    if self.octave == 0 {
+                        return Err(MmlError {
+                            offset,
+                            message: "Cannot go below octave 0".to_owned(),
+                        });
+                    }
+                    self.octave -= 1;
+                }
+
+                '>' => {
+                    if self.octave == MAX_OCTAVE {
+                        return Err(MmlError {
+                            offset,
+                            message: format!("Cannot go above octave {}", MAX_OCTAVE),
+                        });
+                    }
+                    self.octave += 1;
+                }
+
+                ch => {
+                    return Err(MmlError {
+                        offset,
+                        message: format!("Unexpected character {}", ch),
+                    })
+                }
+            }
+        }
+        Ok(events)
+    }
+}
+
+/// Parses the MML `input` string into the sequence of events to play.
+///
+/// The supported commands are: the `A` to `G` notes, optionally followed by `#` or `+` for sharps
+/// or by `-` for flats, then by a length and by any number of dots; `O` to select the octave; `<`
+/// and `>` to move one octave down or up; `L` to set the default note length; `T` to set the tempo
+/// in quarter notes per minute; and `P` to pause for a length, which can also be dotted.
+pub(crate) fn parse_mml(input: &str) -> MmlResult<Vec<MmlEvent>> {
+    Parser::new(input).parse()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Shorthand to construct a note event lasting for `ms` milliseconds.
+    fn note(note: i32, ms: u64) -> MmlEvent {
+        MmlEvent::Note(note, Duration::from_millis(ms))
+    }
+
+    /// Shorthand to construct a pause event lasting for `ms` milliseconds.
+    fn pause(ms: u64) -> MmlEvent {
+        MmlEvent::Pause(Duration::from_millis(ms))
+    }
+
+    /// Parses `input` and checks that it yields the `exp_events`.
+    fn do_ok_test(input: &str, exp_events: &[MmlEvent]) {
+        assert_eq!(exp_events, parse_mml(input).unwrap().as_slice());
+    }
+
+    /// Parses `input` and checks that it fails at `exp_offset` with `exp_message`.
+    fn do_error_test(input: &str, exp_offset: usize, exp_message: &str) {
+        assert_eq!(
+            MmlError { offset: exp_offset, message: exp_message.to_owned() },
+            parse_mml(input).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_note_frequency() {
+        assert_eq!(440.0, note_frequency(57));
+        assert_eq!(880.0, note_frequency(69));
+        assert_eq!(220.0, note_frequency(45));
+        assert!((note_frequency(48) - 261.6256).abs() < 0.0001);
+        assert!((note_frequency(0) - 16.3516).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_parse_mml_empty() {
+        do_ok_test("", &[]);
+        do_ok_test("   ", &[]);
+    }
+
+    #[test]
+    fn test_parse_mml_scale() {
+        do_ok_test(
+            "L8 C D E F G A B > C",
+            &[
+                note(48, 250),
+                note(50, 250),
+                note(52, 250),
+                note(53, 250),
+                note(55, 250),
+                note(57, 250),
+                note(59, 250),
+                note(60, 250),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_mml_accidentals() {
+        do_ok_test("C# D+ E- C-", &[note(49, 500), note(51, 500), note(51, 500), note(47, 500)]);
+        do_ok_test("O0 C# O6 B-", &[note(1, 500), note(82, 500)]);
+    }
+
+    #[test]
+    fn test_parse_mml_lengths() {
+        do_ok_test(
+            "C1 C2 C16 L2 C C4",
+            &[note(48, 2000), note(48, 1000), note(48, 125), note(48, 1000), note(48, 500)],
+        );
+    }
+
+    #[test]
+    fn test_parse_mml_dots() {
+        do_ok_test(
+            "C. C.. C8. L2 C. P4.",
+            &[note(48, 750), note(48, 875), note(48, 375), note(48, 1500), pause(750)],
+        );
+    }
+
+    #[test]
+    fn test_parse_mml_tempo() {
+        do_ok_test("T240 C T60 C8 T255 T32", &[note(48, 250), note(48, 500)]);
+    }
+
+    #[test]
+    fn test_parse_mml_octaves() {
+        do_ok_test(
+            "O2 A > A >> A <<< A O0 C O6 B",
+            &[
+                note(33, 500),
+                note(45, 500),
+                note(69, 500),
+                note(33, 500),
+                note(0, 500),
+                note(83, 500),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_parse_mml_pauses() {
+        do_ok_test("C P2 D P16", &[note(48, 500), pause(1000), note(50, 500), pause(125)]);
+    }
+
+    #[test]
+    fn test_parse_mml_case_insensitive() {
+        do_ok_test("t240l8o5c#p8", &[note(61, 125), pause(125)]);
+    }
+
+    #[test]
+    fn test_parse_mml_errors() {
+        do_error_test("C X", 2, "Unexpected character X");
+        do_error_test("C;", 1, "Unexpected character ;");
+        do_error_test("N10", 0, "Unexpected character N");
+
+        do_error_test("C0", 0, "Length 0 must be between 1 and 64");
+        do_error_test("C D65", 2, "Length 65 must be between 1 and 64");
+        do_error_test("P", 0, "Missing number after P");
+        do_error_test("P.", 0, "Missing number after P");
+        do_error_test("P0", 0, "Length 0 must be between 1 and 64");
+
+        do_error_test("L", 0, "Missing number after L");
+        do_error_test("L C", 0, "Missing number after L");
+        do_error_test("L0", 0, "Length 0 must be between 1 and 64");
+        do_error_test("CD L65", 3, "Length 65 must be between 1 and 64");
+
+        do_error_test("O", 0, "Missing number after O");
+        do_error_test("O7", 0, "Octave 7 must be between 0 and 6");
+        do_error_test("O0 <", 3, "Cannot go below octave 0");
+        do_error_test("O6 >", 3, "Cannot go above octave 6");
+        do_error_test("O0 C-", 3, "Note is out of range");
+        do_error_test("O6 B+", 3, "Note is out of range");
+
+        do_error_test("T", 0, "Missing number after T");
+        do_error_test("T31", 0, "Tempo 31 must be between 32 and 255");
+        do_error_test("T256", 0, "Tempo 256 must be between 32 and 255");
+        do_error_test("T99999999999", 0, "Number is too large");
+    }
+}
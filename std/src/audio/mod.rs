@@ -0,0 +1,562 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Sound commands for EndBASIC.
+
+use crate::exec::{interruptible_sleep, seconds_to_duration, SleepFn};
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+mod mml;
+use mml::{note_frequency, parse_mml, MmlEvent};
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Audio
+The sound commands play simple tones through the speaker of the machine.  Platforms without \
+audio support ignore these commands so that programs that make sounds can still run on them, \
+albeit silently.";
+
+/// Frequency of the tone played by `BEEP`, in Hz.
+const BEEP_FREQUENCY: f64 = 800.0;
+
+/// Duration of the tone played by `BEEP`.
+const BEEP_DURATION: Duration = Duration::from_millis(250);
+
+/// Lowest frequency that `SOUND` accepts, in Hz.
+const MIN_FREQUENCY: f64 = 20.0;
+
+/// Highest frequency that `SOUND` accepts, in Hz.
+const MAX_FREQUENCY: f64 = 20000.0;
+
+/// Generic abstraction over an audio device to back all EndBASIC sound commands.
+pub trait Audio {
+    /// Starts playing a tone of `freq` Hz for `duration` and returns without waiting for the tone
+    /// to finish.
+    ///
+    /// Tones requested while others are still playing may be queued up or may replace them,
+    /// depending on the capabilities of the device.
+    fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()>;
+
+    /// Stays silent for `duration` after any tones still playing and returns without waiting for
+    /// the silence to finish.
+    ///
+    /// Devices that do not queue tones can ignore this.
+    fn rest(&mut self, duration: Duration) -> io::Result<()>;
+}
+
+/// Stand-in implementation of the EndBASIC sound operations that silently ignores all requests.
+#[derive(Default)]
+pub(crate) struct NoopAudio {}
+
+impl Audio for NoopAudio {
+    fn tone(&mut self, _freq: f64, _duration: Duration) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn rest(&mut self, _duration: Duration) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The `BEEP` command.
+pub struct BeepCommand {
+    metadata: CallableMetadata,
+    audio: Rc<RefCell<dyn Audio>>,
+}
+
+impl BeepCommand {
+    /// Creates a new instance of the command.
+    pub fn new(audio: Rc<RefCell<dyn Audio>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("BEEP")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Plays the default tone.
+The tone is a quarter of a second long and the program continues running while it plays.",
+                )
+                .build(),
+            audio,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for BeepCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        self.audio.borrow_mut().tone(BEEP_FREQUENCY, BEEP_DURATION).map_err(|e| scope.io_error(e))
+    }
+}
+
+/// The `PLAY` command.
+pub struct PlayCommand {
+    metadata: CallableMetadata,
+    audio: Rc<RefCell<dyn Audio>>,
+    sleep_fn: Rc<SleepFn>,
+}
+
+impl PlayCommand {
+    /// Creates a new instance of the command that plays music via `audio` and that waits for it
+    /// with `sleep_fn` when requested.
+    pub fn new(audio: Rc<RefCell<dyn Audio>>, sleep_fn: Rc<SleepFn>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PLAY")
+                .with_syntax(&[
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("music"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("music"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("wait"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Plays a melody.
+music$ describes the melody in the music macro language, which consists of the following \
+commands, separated by optional spaces:
+A to G plays a note, which can be followed by # or + for a sharp or by - for a flat, then by an \
+optional length and by any number of dots.  The first dot makes the note 50% longer and every \
+other dot adds half as much as the previous one.
+On sets the octave to n, from 0 to 6, and < and > move one octave down or up.  The default \
+octave is 4, which contains the A note at 440 Hz.
+Ln sets the length of the notes that do not specify one to 1/n of a whole note, where n goes \
+from 1 to 64.  The default length is 4 (quarter notes).
+Pn pauses for the length n, which can also be followed by dots.
+Tn sets the tempo to n quarter notes per minute, from 32 to 255.  The default tempo is 120.
+The program continues running while the melody plays unless wait? is TRUE, in which case PLAY \
+returns once the melody is over.  Pressing CTRL+C while waiting stops the program immediately.",
+                )
+                .build(),
+            audio,
+            sleep_fn,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PlayCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert!((1..=2).contains(&scope.nargs()));
+        let has_wait = scope.nargs() == 2;
+        let (music, music_pos) = scope.pop_string_with_pos();
+        let wait = if has_wait { scope.pop_boolean() } else { false };
+
+        let events = parse_mml(&music).map_err(|e| {
+            Error::SyntaxError(
+                music_pos,
+                format!("Invalid music at position {}: {}", e.offset + 1, e.message),
+            )
+        })?;
+
+        let mut total = Duration::ZERO;
+        {
+            let mut audio = self.audio.borrow_mut();
+            for event in events {
+                total += event.duration();
+                match event {
+                    MmlEvent::Note(note, duration) => audio.tone(note_frequency(note), duration),
+                    MmlEvent::Pause(duration) => audio.rest(duration),
+                }
+                .map_err(|e| scope.io_error(e))?;
+            }
+        }
+
+        if wait {
+            interruptible_sleep(&self.sleep_fn, total, music_pos, machine).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The `SOUND` command.
+pub struct SoundCommand {
+    metadata: CallableMetadata,
+    audio: Rc<RefCell<dyn Audio>>,
+    sleep_fn: Rc<SleepFn>,
+}
+
+impl SoundCommand {
+    /// Creates a new instance of the command that plays tones via `audio` and that waits for them
+    /// with `sleep_fn` when requested.
+    pub fn new(audio: Rc<RefCell<dyn Audio>>, sleep_fn: Rc<SleepFn>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOUND")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("frequency"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("duration"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("frequency"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("duration"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("wait"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Plays a tone.
+frequency# is the pitch of the tone in Hz and must be between 20 and 20000.  duration# is the \
+length of the tone in seconds, with millisecond precision.
+The program continues running while the tone plays unless wait? is TRUE, in which case SOUND \
+returns once the tone is over.  Pressing CTRL+C while waiting stops the program immediately.",
+                )
+                .build(),
+            audio,
+            sleep_fn,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SoundCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let has_wait = scope.nargs() == 3;
+        let (freq, freq_pos) = scope.pop_double_with_pos();
+        let (duration, duration_pos) = scope.pop_double_with_pos();
+        let wait = if has_wait { scope.pop_boolean() } else { false };
+
+        if !(MIN_FREQUENCY..=MAX_FREQUENCY).contains(&freq) {
+            return Err(Error::SyntaxError(
+                freq_pos,
+                format!(
+                    "Frequency {} must be between {} and {}",
+                    freq, MIN_FREQUENCY, MAX_FREQUENCY
+                ),
+            ));
+        }
+        let duration = seconds_to_duration(duration, "Sound duration", duration_pos)?;
+
+        self.audio.borrow_mut().tone(freq, duration).map_err(|e| scope.io_error(e))?;
+        if wait {
+            interruptible_sleep(&self.sleep_fn, duration, duration_pos, machine).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds all sound commands to the `machine`, using `audio` to play the tones and `sleep_fn` to
+/// wait for them.
+pub fn add_all(machine: &mut Machine, audio: Rc<RefCell<dyn Audio>>, sleep_fn: Rc<SleepFn>) {
+    machine.add_callable(BeepCommand::new(audio.clone()));
+    machine.add_callable(PlayCommand::new(audio.clone(), sleep_fn.clone()));
+    machine.add_callable(SoundCommand::new(audio, sleep_fn));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use endbasic_core::exec::{Signal, StopReason};
+    use endbasic_core::LineCol;
+    use futures_lite::future::{self, BoxedLocal, FutureExt};
+    use std::cell::Cell;
+
+    /// Audio device that records the tones it is asked to play along with the time at which they
+    /// were requested.  Rests are recorded as tones with a frequency of zero.
+    struct RecordingAudio {
+        now: Rc<Cell<Duration>>,
+        tones: Vec<(f64, Duration, Duration)>,
+    }
+
+    impl Audio for RecordingAudio {
+        fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()> {
+            self.tones.push((freq, duration, self.now.get()));
+            Ok(())
+        }
+
+        fn rest(&mut self, duration: Duration) -> io::Result<()> {
+            self.tones.push((0.0, duration, self.now.get()));
+            Ok(())
+        }
+    }
+
+    /// Audio device that fails all operations.
+    struct BrokenAudio {}
+
+    impl Audio for BrokenAudio {
+        fn tone(&mut self, _freq: f64, _duration: Duration) -> io::Result<()> {
+            Err(io::Error::other("Device unplugged"))
+        }
+
+        fn rest(&mut self, _duration: Duration) -> io::Result<()> {
+            Err(io::Error::other("Device unplugged"))
+        }
+    }
+
+    /// Creates a tester with the sound commands backed by a recording audio device and by a fake
+    /// clock that only advances when sleeping.
+    fn tester_with_fake_clock() -> (Tester, Rc<RefCell<RecordingAudio>>, Rc<Cell<Duration>>) {
+        let now = Rc::from(Cell::new(Duration::ZERO));
+        let audio = Rc::from(RefCell::from(RecordingAudio { now: now.clone(), tones: vec![] }));
+        let sleep_fn: SleepFn = {
+            let now = now.clone();
+            Box::from(move |d: Duration, _pos: LineCol| -> BoxedLocal<Result<()>> {
+                now.set(now.get() + d);
+                async move { Ok(()) }.boxed_local()
+            })
+        };
+
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), audio.clone(), Rc::from(sleep_fn));
+        (t, audio, now)
+    }
+
+    #[test]
+    fn test_beep_ok() {
+        let (mut t, audio, now) = tester_with_fake_clock();
+        t.run("BEEP: BEEP").check();
+        assert_eq!(
+            [
+                (BEEP_FREQUENCY, BEEP_DURATION, Duration::ZERO),
+                (BEEP_FREQUENCY, BEEP_DURATION, Duration::ZERO),
+            ],
+            audio.borrow().tones.as_slice()
+        );
+        assert_eq!(Duration::ZERO, now.get());
+    }
+
+    #[test]
+    fn test_beep_errors() {
+        check_stmt_compilation_err("1:1: BEEP expected no arguments", "BEEP 1");
+
+        let mut t =
+            Tester::empty().add_callable(BeepCommand::new(Rc::from(RefCell::from(BrokenAudio {}))));
+        t.run("BEEP").expect_err("1:1: Device unplugged").check();
+    }
+
+    #[test]
+    fn test_play_does_not_block() {
+        let (mut t, audio, now) = tester_with_fake_clock();
+        t.run(r#"PLAY "L8 C P4 > A.": PLAY "", FALSE"#).check();
+        assert_eq!(
+            [
+                (note_frequency(48), Duration::from_millis(250), Duration::ZERO),
+                (0.0, Duration::from_millis(500), Duration::ZERO),
+                (880.0, Duration::from_millis(375), Duration::ZERO),
+            ],
+            audio.borrow().tones.as_slice()
+        );
+        assert_eq!(Duration::ZERO, now.get());
+    }
+
+    #[test]
+    fn test_play_wait_blocks() {
+        let (mut t, audio, now) = tester_with_fake_clock();
+        t.run(r#"PLAY "T240 A B", TRUE: PLAY "P2", TRUE: PLAY "A", TRUE"#).check();
+        assert_eq!(
+            [
+                (440.0, Duration::from_millis(250), Duration::ZERO),
+                (note_frequency(59), Duration::from_millis(250), Duration::ZERO),
+                (0.0, Duration::from_millis(1000), Duration::from_millis(500)),
+                (440.0, Duration::from_millis(500), Duration::from_millis(1500)),
+            ],
+            audio.borrow().tones.as_slice()
+        );
+        assert_eq!(Duration::from_millis(2000), now.get());
+    }
+
+    #[test]
+    fn test_play_wait_break() {
+        let mut t = Tester::empty();
+        let signals_tx = t.get_machine().get_signals_tx();
+        let sleep_fn: SleepFn = Box::from(move |_d: Duration, _pos: LineCol| {
+            signals_tx.try_send(Signal::Break).unwrap();
+            future::pending().boxed_local()
+        });
+        let audio = Rc::from(RefCell::from(NoopAudio::default()));
+        add_all(t.get_machine(), audio, Rc::from(sleep_fn));
+        t.run(r#"PLAY "L1 CDEFGAB", TRUE: a = 1"#).expect_ok(StopReason::Break).check();
+    }
+
+    #[test]
+    fn test_play_errors() {
+        check_stmt_compilation_err("1:1: PLAY expected <music$> | <music$, wait?>", "PLAY");
+        check_stmt_compilation_err(
+            "1:1: PLAY expected <music$> | <music$, wait?>",
+            r#"PLAY "A", TRUE, 1"#,
+        );
+        check_stmt_compilation_err("1:6: expected STRING but found INTEGER", "PLAY 3");
+
+        check_stmt_err("1:6: Invalid music at position 3: Unexpected character X", r#"PLAY "C X""#);
+        check_stmt_err(
+            "1:6: Invalid music at position 1: Tempo 300 must be between 32 and 255",
+            r#"PLAY "T300 C""#,
+        );
+
+        let (mut t, audio, _now) = tester_with_fake_clock();
+        t.run(r#"PLAY "C D L0 E""#)
+            .expect_err("1:6: Invalid music at position 5: Length 0 must be between 1 and 64")
+            .check();
+        assert!(audio.borrow().tones.is_empty());
+
+        let mut t = Tester::empty().add_callable(PlayCommand::new(
+            Rc::from(RefCell::from(BrokenAudio {})),
+            Rc::from(Box::from(crate::exec::system_sleep) as SleepFn),
+        ));
+        t.run(r#"PLAY "C""#).expect_err("1:1: Device unplugged").check();
+    }
+
+    #[test]
+    fn test_sound_does_not_block() {
+        let (mut t, audio, now) = tester_with_fake_clock();
+        t.run("SOUND 440, 0.5: SOUND 20, 1.25, FALSE: SOUND 20000, 0").check();
+        assert_eq!(
+            [
+                (440.0, Duration::from_millis(500), Duration::ZERO),
+                (20.0, Duration::from_millis(1250), Duration::ZERO),
+                (20000.0, Duration::ZERO, Duration::ZERO),
+            ],
+            audio.borrow().tones.as_slice()
+        );
+        assert_eq!(Duration::ZERO, now.get());
+    }
+
+    #[test]
+    fn test_sound_wait_blocks() {
+        let (mut t, audio, now) = tester_with_fake_clock();
+        t.run("SOUND 440, 0.5, TRUE: SOUND 880.5, 0.0004, TRUE: SOUND 220, 0.125, TRUE").check();
+        assert_eq!(
+            [
+                (440.0, Duration::from_millis(500), Duration::ZERO),
+                (880.5, Duration::ZERO, Duration::from_millis(500)),
+                (220.0, Duration::from_millis(125), Duration::from_millis(500)),
+            ],
+            audio.borrow().tones.as_slice()
+        );
+        assert_eq!(Duration::from_millis(625), now.get());
+    }
+
+    #[test]
+    fn test_sound_wait_break() {
+        let mut t = Tester::empty();
+        let signals_tx = t.get_machine().get_signals_tx();
+        let sleep_fn: SleepFn = Box::from(move |_d: Duration, _pos: LineCol| {
+            signals_tx.try_send(Signal::Break).unwrap();
+            future::pending().boxed_local()
+        });
+        let audio = Rc::from(RefCell::from(NoopAudio::default()));
+        add_all(t.get_machine(), audio, Rc::from(sleep_fn));
+        t.run("SOUND 440, 1000, TRUE: a = 1").expect_ok(StopReason::Break).check();
+    }
+
+    #[test]
+    fn test_sound_silent_by_default() {
+        Tester::default().run("BEEP: SOUND 440, 0.5").check();
+    }
+
+    #[test]
+    fn test_sound_errors() {
+        check_stmt_compilation_err(
+            "1:1: SOUND expected <frequency#, duration#> | <frequency#, duration#, wait?>",
+            "SOUND 440",
+        );
+        check_stmt_compilation_err(
+            "1:1: SOUND expected <frequency#, duration#> | <frequency#, duration#, wait?>",
+            "SOUND 440, 1, TRUE, 2",
+        );
+        check_stmt_compilation_err("1:15: expected BOOLEAN but found INTEGER", "SOUND 440, 1, 2");
+
+        check_stmt_err("1:7: Frequency 19.5 must be between 20 and 20000", "SOUND 19.5, 1");
+        check_stmt_err("1:7: Frequency 20001 must be between 20 and 20000", "SOUND 20001, 1");
+        check_stmt_err("1:12: Sound duration must be positive", "SOUND 440, -1");
+
+        let mut t = Tester::empty().add_callable(SoundCommand::new(
+            Rc::from(RefCell::from(BrokenAudio {})),
+            Rc::from(Box::from(crate::exec::system_sleep) as SleepFn),
+        ));
+        t.run("SOUND 440, 1").expect_err("1:1: Device unplugged").check();
+    }
+}
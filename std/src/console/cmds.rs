@@ -26,8 +26,8 @@ use endbasic_core::compiler::{
     ArgSepSyntax, OptionalValueSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredRefSyntax,
     RequiredValueSyntax, SingularArgSyntax,
 };
-use endbasic_core::exec::{Error, Machine, Result, Scope, ValueTag};
-use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope, ValueTag};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
 use endbasic_core::LineCol;
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -53,6 +53,109 @@ console, you should restrict the script to using only the INPUT and PRINT comman
 Be aware that the console currently reacts poorly to size changes.  Avoid resizing your terminal \
 or web browser.  If you do resize them, however, restart the interpreter.";
 
+/// Default width of the print zones used by the `,` separator in `PRINT`.
+const DEFAULT_ZONE_WIDTH: u16 = 14;
+
+/// Resets the width of the print zones to its default value.
+struct ClearableZoneWidth {
+    zone_width: Rc<RefCell<u16>>,
+}
+
+impl Clearable for ClearableZoneWidth {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        *self.zone_width.borrow_mut() = DEFAULT_ZONE_WIDTH;
+    }
+}
+
+/// Pops a column and a row from `scope` and validates that they fall within the visible area of a
+/// console of `size`.
+fn pop_position(scope: &mut Scope<'_>, size: CharsXY) -> Result<CharsXY> {
+    fn get_coord((i, pos): (i32, LineCol), name: &str) -> Result<(u16, LineCol)> {
+        match u16::try_from(i) {
+            Ok(v) => Ok((v, pos)),
+            Err(_) => Err(Error::SyntaxError(pos, format!("{} out of range", name))),
+        }
+    }
+
+    let (column, column_pos) = get_coord(scope.pop_integer_with_pos(), "Column")?;
+    let (row, row_pos) = get_coord(scope.pop_integer_with_pos(), "Row")?;
+
+    if column >= size.x {
+        return Err(Error::SyntaxError(
+            column_pos,
+            format!("Column {} exceeds visible range of {}", column, size.x - 1),
+        ));
+    }
+    if row >= size.y {
+        return Err(Error::SyntaxError(
+            row_pos,
+            format!("Row {} exceeds visible range of {}", row, size.y - 1),
+        ));
+    }
+
+    Ok(CharsXY::new(column, row))
+}
+
+/// The `BACKLIGHT` command.
+pub struct BacklightCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl BacklightCommand {
+    /// Creates a new `BACKLIGHT` command that controls the display backing the `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("BACKLIGHT")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("pct"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets the brightness of the display's backlight.
+pct% is a percentage between 0 and 100, where 0 turns the backlight off.  Displays whose \
+backlight is wired to a plain GPIO pin can only be switched on or off, so any value other than 0 \
+turns them fully on.
+This is only supported by LCD consoles configured with backlight control.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for BacklightCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (pct, pos) = scope.pop_integer_with_pos();
+
+        let pct = match u8::try_from(pct) {
+            Ok(pct) if pct <= 100 => pct,
+            _ => {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("Brightness {} must be between 0 and 100", pct),
+                ))
+            }
+        };
+
+        self.console.borrow_mut().set_brightness(pct).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
 /// The `CLS` command.
 pub struct ClsCommand {
     metadata: CallableMetadata,
@@ -206,7 +309,7 @@ The returned key matches its name, number, or symbol and maintains case.  In oth
 pressing the X key will return 'x' or 'X' depending on the SHIFT modifier.
 The following special keys are recognized: arrow keys (UP, DOWN, LEFT, RIGHT), backspace (BS), \
 end or CTRL+E (END), enter (ENTER), CTRL+D (EOF), escape (ESC), home or CTRL+A (HOME), \
-CTRL+C (INT), page up (PGUP), page down (PGDOWN), and tab (TAB).
+CTRL+C (INT), page up (PGUP), page down (PGDOWN), CTRL+R (SEARCH), and tab (TAB).
 This function never blocks.  To wait for a key press, you need to explicitly poll the keyboard.  \
 For example, to wait until the escape key is pressed, you could do:
     k$ = \"\": WHILE k$ <> \"ESC\": k = INKEY$: SLEEP 0.01: WEND
@@ -246,6 +349,7 @@ impl Callable for InKeyFunction {
             Some(Key::NewLine) => "ENTER".to_owned(),
             Some(Key::PageDown) => "PGDOWN".to_owned(),
             Some(Key::PageUp) => "PGUP".to_owned(),
+            Some(Key::Search) => "SEARCH".to_owned(),
             Some(Key::Tab) => "TAB".to_owned(),
             Some(Key::Unknown) => "?".to_owned(),
 
@@ -454,34 +558,13 @@ impl Callable for LocateCommand {
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        fn get_coord((i, pos): (i32, LineCol), name: &str) -> Result<(u16, LineCol)> {
-            match u16::try_from(i) {
-                Ok(v) => Ok((v, pos)),
-                Err(_) => Err(Error::SyntaxError(pos, format!("{} out of range", name))),
-            }
-        }
-
         debug_assert_eq!(2, scope.nargs());
-        let (column, column_pos) = get_coord(scope.pop_integer_with_pos(), "Column")?;
-        let (row, row_pos) = get_coord(scope.pop_integer_with_pos(), "Row")?;
 
         let mut console = self.console.borrow_mut();
         let size = console.size_chars().map_err(|e| scope.io_error(e))?;
+        let pos = pop_position(&mut scope, size)?;
 
-        if column >= size.x {
-            return Err(Error::SyntaxError(
-                column_pos,
-                format!("Column {} exceeds visible range of {}", column, size.x - 1),
-            ));
-        }
-        if row >= size.y {
-            return Err(Error::SyntaxError(
-                row_pos,
-                format!("Row {} exceeds visible range of {}", row, size.y - 1),
-            ));
-        }
-
-        console.locate(CharsXY::new(column, row)).map_err(|e| scope.io_error(e))?;
+        console.locate(pos).map_err(|e| scope.io_error(e))?;
         Ok(())
     }
 }
@@ -490,11 +573,13 @@ impl Callable for LocateCommand {
 pub struct PrintCommand {
     metadata: CallableMetadata,
     console: Rc<RefCell<dyn Console>>,
+    zone_width: Rc<RefCell<u16>>,
 }
 
 impl PrintCommand {
-    /// Creates a new `PRINT` command that writes to `console`.
-    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+    /// Creates a new `PRINT` command that writes to `console` and that aligns the fields separated
+    /// by `,` to zones of `zone_width` characters.
+    pub fn new(console: Rc<RefCell<dyn Console>>, zone_width: Rc<RefCell<u16>>) -> Rc<Self> {
         Rc::from(Self {
             metadata: CallableMetadataBuilder::new("PRINT")
                 .with_syntax(&[(
@@ -516,29 +601,42 @@ Using a `;` separator between arguments causes the two adjacent values to be dis
 For strings, this means that no space is added between them; for all other types, a space is added \
 after the value on the left side.
 Using a `,` separator between arguments works the same as `;` except that the fields are \
-left-aligned to 14-character wide fields on the screen.
+left-aligned to print zones on the screen.  Zones are 14 characters wide by default and this can \
+be changed with PRINTZONE.  If the next zone would start past the right edge of the console, \
+printing continues at the beginning of the next line.
 If the last expression is empty (i.e. if the statement ends in a semicolon or a comma), then \
-the cursor position remains on the same line of the message right after what was printed.",
+the cursor position remains on the same line of the message right after what was printed.
+EMPTY values are printed as nothing.",
                 )
+                .with_empty_values()
                 .build(),
             console,
+            zone_width,
         })
     }
-}
 
-#[async_trait(?Send)]
-impl Callable for PrintCommand {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
+    /// Formats and prints the values in `scope`.
+    fn print(&self, scope: &mut Scope<'_>) -> Result<()> {
+        let zone_width = *self.zone_width.borrow();
+        let (mut start, width) = {
+            let console = self.console.borrow();
+            let start = console.cursor_pos().map(|pos| pos.x).unwrap_or(0);
+            let width = console.size_chars().map(|size| size.x).ok();
+            (start, width)
+        };
 
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
         let mut text = String::new();
         let mut nl = true;
         while scope.nargs() > 0 {
             let mut add_space = false;
 
             match scope.pop_value_tag() {
+                ValueTag::Missing => {
+                    nl = false;
+                }
+                _ if scope.pop_if_empty() => {
+                    nl = true;
+                }
                 ValueTag::Boolean => {
                     let b = scope.pop_boolean();
                     add_space = true;
@@ -562,9 +660,6 @@ impl Callable for PrintCommand {
                     nl = true;
                     text += &s;
                 }
-                ValueTag::Missing => {
-                    nl = false;
-                }
             }
 
             if scope.nargs() > 0 {
@@ -575,9 +670,27 @@ impl Callable for PrintCommand {
                         }
                     }
                     ArgSep::Long => {
-                        text += " ";
-                        while text.len() % 14 != 0 {
-                            text += " ";
+                        let mut column = usize::from(start) + text.chars().count();
+                        if let Some(width) = width {
+                            let width = usize::from(width);
+                            if column > width {
+                                column = (column - 1) % width + 1;
+                            }
+                        }
+                        let zone_width = usize::from(zone_width);
+                        let next = (column / zone_width + 1) * zone_width;
+                        match width {
+                            Some(width) if next >= usize::from(width) => {
+                                self.console
+                                    .borrow_mut()
+                                    .print(&text)
+                                    .map_err(|e| scope.io_error(e))?;
+                                text.clear();
+                                start = 0;
+                            }
+                            _ => {
+                                text += &" ".repeat(next - column);
+                            }
                         }
                     }
                     _ => unreachable!(),
@@ -594,6 +707,79 @@ impl Callable for PrintCommand {
     }
 }
 
+#[async_trait(?Send)]
+impl Callable for PrintCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        // Batch the pieces of output that make up this statement so that they reach the console
+        // in a single update.
+        self.console.borrow_mut().start_batch().map_err(|e| scope.io_error(e))?;
+        let result = self.print(&mut scope);
+        let end_result = self.console.borrow_mut().end_batch().map_err(|e| scope.io_error(e));
+        result.and(end_result)
+    }
+}
+
+/// The `PRINTZONE` command.
+pub struct PrintZoneCommand {
+    metadata: CallableMetadata,
+    zone_width: Rc<RefCell<u16>>,
+}
+
+impl PrintZoneCommand {
+    /// Creates a new `PRINTZONE` command that updates `zone_width`.
+    pub fn new(zone_width: Rc<RefCell<u16>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PRINTZONE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("width"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets the width of the print zones used by PRINT.
+When the arguments to PRINT are separated by `,`, each field starts at the beginning of the next \
+print zone, which is a column that is a multiple of width%.  The default width is 14 and CLEAR \
+restores it.",
+                )
+                .build(),
+            zone_width,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PrintZoneCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (width, pos) = scope.pop_integer_with_pos();
+        let width = match u16::try_from(width) {
+            Ok(width) if width > 0 => width,
+            _ => {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("Print zone width {} must be positive", width),
+                ))
+            }
+        };
+        *self.zone_width.borrow_mut() = width;
+        Ok(())
+    }
+}
+
 /// The `SCRCOLS` function.
 pub struct ScrColsFunction {
     metadata: CallableMetadata,
@@ -631,6 +817,73 @@ impl Callable for ScrColsFunction {
     }
 }
 
+/// The `SCREENCHAR` function.
+pub struct ScreenCharFunction {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl ScreenCharFunction {
+    /// Creates a new instance of the function.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SCREENCHAR")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("column"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("row"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the character displayed at the given position of the text console.
+Positions that have never been written to, or that have been cleared, contain a space.  Graphics \
+drawn on the console are not considered text so they do not affect the result.
+See SCREENDUMP to save the whole text console to a file.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ScreenCharFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+
+        let console = self.console.borrow();
+        let size = console.size_chars().map_err(|e| scope.io_error(e))?;
+        let pos = pop_position(&mut scope, size)?;
+
+        let rows = console.read_screen().map_err(|e| scope.io_error(e))?;
+        let ch = rows
+            .get(usize::from(pos.y))
+            .and_then(|row| row.get(usize::from(pos.x)))
+            .map(|cell| cell.ch)
+            .unwrap_or(' ');
+        scope.return_string(ch.to_string())
+    }
+}
+
 /// The `SCRROWS` function.
 pub struct ScrRowsFunction {
     metadata: CallableMetadata,
@@ -671,13 +924,18 @@ impl Callable for ScrRowsFunction {
 /// Adds all console-related commands for the given `console` to the `machine`.
 pub fn add_all(machine: &mut Machine, console: Rc<RefCell<dyn Console>>) {
     machine.add_clearable(ConsoleClearable::new(console.clone()));
+    machine.add_callable(BacklightCommand::new(console.clone()));
     machine.add_callable(ClsCommand::new(console.clone()));
     machine.add_callable(ColorCommand::new(console.clone()));
     machine.add_callable(InKeyFunction::new(console.clone()));
     machine.add_callable(InputCommand::new(console.clone()));
     machine.add_callable(LocateCommand::new(console.clone()));
-    machine.add_callable(PrintCommand::new(console.clone()));
+    let zone_width = Rc::from(RefCell::from(DEFAULT_ZONE_WIDTH));
+    machine.add_clearable(Box::from(ClearableZoneWidth { zone_width: zone_width.clone() }));
+    machine.add_callable(PrintCommand::new(console.clone(), zone_width.clone()));
+    machine.add_callable(PrintZoneCommand::new(zone_width));
     machine.add_callable(ScrColsFunction::new(console.clone()));
+    machine.add_callable(ScreenCharFunction::new(console.clone()));
     machine.add_callable(ScrRowsFunction::new(console));
 }
 
@@ -686,6 +944,28 @@ mod tests {
     use super::*;
     use crate::testutils::*;
 
+    #[test]
+    fn test_backlight_ok() {
+        Tester::default()
+            .run("BACKLIGHT 100: BACKLIGHT 0: BACKLIGHT 35")
+            .expect_output([
+                CapturedOut::SetBrightness(100),
+                CapturedOut::SetBrightness(0),
+                CapturedOut::SetBrightness(35),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_backlight_errors() {
+        check_stmt_compilation_err("1:1: BACKLIGHT expected pct%", "BACKLIGHT");
+        check_stmt_compilation_err("1:1: BACKLIGHT expected pct%", "BACKLIGHT 1, 2");
+        check_stmt_compilation_err("1:11: STRING is not a number", "BACKLIGHT \"50\"");
+        check_stmt_err("1:11: Brightness -1 must be between 0 and 100", "BACKLIGHT -1");
+        check_stmt_err("1:11: Brightness 101 must be between 0 and 100", "BACKLIGHT 101");
+        check_stmt_err("1:11: Brightness 256 must be between 0 and 100", "BACKLIGHT 256");
+    }
+
     #[test]
     fn test_cls_ok() {
         Tester::default().run("CLS").expect_output([CapturedOut::Clear(ClearType::All)]).check();
@@ -1006,6 +1286,58 @@ mod tests {
         check_stmt_err("1:9: Cannot + INTEGER and BOOLEAN", "PRINT 3 + TRUE");
     }
 
+    #[test]
+    fn test_print_zones_follow_cursor() {
+        Tester::default()
+            .run("PRINT \"abc\";: PRINT 1, 2")
+            .expect_output([
+                CapturedOut::Write("abc".to_owned()),
+                CapturedOut::Print(" 1          2".to_owned()),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_print_zones_custom_width() {
+        Tester::default()
+            .run("PRINTZONE 8: PRINT 1, \"abcdefgh\", 3: PRINTZONE 14: PRINT 1, 2")
+            .expect_prints([" 1      abcdefgh         3", " 1             2"])
+            .check();
+    }
+
+    #[test]
+    fn test_print_zones_wrap_on_narrow_console() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(20, 10));
+        t.run("PRINT 1, 2, 3, \"abcdefghijklmnopqrstuvwxyz\", 5")
+            .expect_prints([" 1             2", " 3            abcdefghijklmnopqrstuvwxyz", " 5"])
+            .check();
+
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(20, 10));
+        t.run("PRINTZONE 5: PRINT 1, 2, 3, 4, 5")
+            .expect_prints([" 1    2    3    4", " 5"])
+            .check();
+    }
+
+    #[test]
+    fn test_printzone_reset_by_clear() {
+        Tester::default()
+            .run("PRINTZONE 4: CLEAR: PRINT 1, 2")
+            .expect_clear()
+            .expect_prints([" 1             2"])
+            .check();
+    }
+
+    #[test]
+    fn test_printzone_errors() {
+        check_stmt_compilation_err("1:1: PRINTZONE expected width%", "PRINTZONE");
+        check_stmt_compilation_err("1:1: PRINTZONE expected width%", "PRINTZONE 1, 2");
+        check_stmt_err("1:11: Print zone width 0 must be positive", "PRINTZONE 0");
+        check_stmt_err("1:11: Print zone width -3 must be positive", "PRINTZONE -3");
+        check_stmt_err("1:11: Print zone width 70000 must be positive", "PRINTZONE 70000");
+    }
+
     #[test]
     fn test_scrcols() {
         let mut t = Tester::default();
@@ -1016,6 +1348,45 @@ mod tests {
         check_expr_compilation_error("1:10: SCRCOLS expected no arguments", "SCRCOLS(1)");
     }
 
+    #[test]
+    fn test_screenchar_ok() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY { x: 10, y: 5 });
+        t.run(
+            r#"PRINT "Hello": LOCATE 3, 2: PRINT "x"
+a$ = SCREENCHAR$(1, 0): b$ = SCREENCHAR$(3, 2): c$ = SCREENCHAR$(9, 4)"#,
+        )
+        .expect_output([
+            CapturedOut::Print("Hello".to_owned()),
+            CapturedOut::Locate(CharsXY::new(3, 2)),
+            CapturedOut::Print("x".to_owned()),
+        ])
+        .expect_var("a", "e")
+        .expect_var("b", "x")
+        .expect_var("c", " ")
+        .check();
+    }
+
+    #[test]
+    fn test_screenchar_errors() {
+        check_expr_compilation_error("1:10: SCREENCHAR expected column%, row%", "SCREENCHAR$(1)");
+        check_expr_compilation_error(
+            "1:10: SCREENCHAR expected column%, row%",
+            "SCREENCHAR$(1, 2, 3)",
+        );
+        check_expr_error("1:22: Column out of range", "SCREENCHAR$(-1, 0)");
+        check_expr_error("1:25: Row out of range", "SCREENCHAR$(0, 70000)");
+
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY { x: 30, y: 20 });
+        t.run("a$ = SCREENCHAR$(30, 0)")
+            .expect_err("1:18: Column 30 exceeds visible range of 29")
+            .check();
+        t.run("a$ = SCREENCHAR$(0, 20)")
+            .expect_err("1:21: Row 20 exceeds visible range of 19")
+            .check();
+    }
+
     #[test]
     fn test_scrrows() {
         let mut t = Tester::default();
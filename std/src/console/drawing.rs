@@ -15,8 +15,9 @@
 
 //! Drawing algorithms for consoles that don't provide native rendering primitives.
 
-use crate::console::graphics::{ClampedInto, RasterOps};
-use crate::console::{PixelsXY, SizeInPixels};
+use crate::console::graphics::{ClampedInto, RasterInfo, RasterOps};
+use crate::console::{ClipRect, PixelsXY, SizeInPixels, TextRotation, RGB};
+use std::cmp;
 use std::convert::TryFrom;
 use std::io;
 
@@ -250,6 +251,286 @@ where
     Ok(())
 }
 
+/// Rectangular region with an inclusive top-left corner and an exclusive bottom-right corner.
+///
+/// Coordinates are widened so that we don't have to worry about overflows anywhere.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Bounds {
+    x1: i32,
+    y1: i32,
+    x2: i32,
+    y2: i32,
+}
+
+impl Bounds {
+    /// Constructs the region that starts at `xy` and spans `width` and `height` pixels.
+    fn new(xy: PixelsXY, width: u16, height: u16) -> Self {
+        let x1 = i32::from(xy.x);
+        let y1 = i32::from(xy.y);
+        Self { x1, y1, x2: x1 + i32::from(width), y2: y1 + i32::from(height) }
+    }
+
+    /// Returns true if the region does not contain any pixel.
+    fn is_empty(&self) -> bool {
+        self.x1 >= self.x2 || self.y1 >= self.y2
+    }
+
+    /// Returns true if the pixel at `x` and `y` falls within the region.
+    fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x1 && x < self.x2 && y >= self.y1 && y < self.y2
+    }
+
+    /// Returns true if all pixels of `other` fall within the region.
+    fn contains_bounds(&self, other: &Bounds) -> bool {
+        other.x1 >= self.x1 && other.x2 <= self.x2 && other.y1 >= self.y1 && other.y2 <= self.y2
+    }
+
+    /// Computes the region covered by both `self` and `other`, which may be empty.
+    fn intersect(&self, other: &Bounds) -> Bounds {
+        Bounds {
+            x1: cmp::max(self.x1, other.x1),
+            y1: cmp::max(self.y1, other.y1),
+            x2: cmp::min(self.x2, other.x2),
+            y2: cmp::min(self.y2, other.y2),
+        }
+    }
+
+    /// Computes the up to four strips of the region that fall outside of `other`.  The strips
+    /// returned may be empty.
+    fn subtract(&self, other: &Bounds) -> [Bounds; 4] {
+        let y1 = cmp::min(cmp::max(other.y1, self.y1), self.y2);
+        let y2 = cmp::max(cmp::min(other.y2, self.y2), y1);
+        [
+            Bounds { y2: y1, ..*self },
+            Bounds { y1: y2, ..*self },
+            Bounds { y1, y2, x2: cmp::min(other.x1, self.x2), ..*self },
+            Bounds { y1, y2, x1: cmp::max(other.x2, self.x1), ..*self },
+        ]
+    }
+
+    /// Converts the region to an origin and a size, or returns none if the region is empty.
+    fn to_xy_size(self) -> Option<(PixelsXY, SizeInPixels)> {
+        if self.is_empty() {
+            None
+        } else {
+            Some((
+                PixelsXY::new(self.x1.clamped_into(), self.y1.clamped_into()),
+                SizeInPixels::new(
+                    (self.x2 - self.x1).clamped_into(),
+                    (self.y2 - self.y1).clamped_into(),
+                ),
+            ))
+        }
+    }
+}
+
+/// Adapter over a `RasterOps` that restricts all drawing primitives to a clip region.
+///
+/// Primitives that cannot be clipped by adjusting their coordinates are rasterized via the
+/// algorithms in this module so that backends get clipping support for free.  Operations that are
+/// not drawing primitives, such as `clear` or `copy_pixels`, ignore the clip region.
+pub struct ClippedRasterOps<'a, R: RasterOps> {
+    /// The raster operations to delegate to.
+    inner: &'a mut R,
+
+    /// The region to restrict drawing to, if any.
+    clip: Option<Bounds>,
+}
+
+impl<'a, R: RasterOps> ClippedRasterOps<'a, R> {
+    /// Wraps `inner` so that drawing primitives are restricted to `clip`, if any.
+    pub fn new(inner: &'a mut R, clip: Option<ClipRect>) -> Self {
+        let clip = clip.map(|clip| Bounds::new(clip.xy, clip.width, clip.height));
+        Self { inner, clip }
+    }
+}
+
+impl<R: RasterOps> RasterOps for ClippedRasterOps<'_, R> {
+    type ID = R::ID;
+
+    fn get_info(&self) -> RasterInfo {
+        self.inner.get_info()
+    }
+
+    fn set_draw_color(&mut self, color: RGB) {
+        self.inner.set_draw_color(color)
+    }
+
+    fn clear(&mut self) -> io::Result<()> {
+        self.inner.clear()
+    }
+
+    fn set_sync(&mut self, enabled: bool) {
+        self.inner.set_sync(enabled)
+    }
+
+    fn present_canvas(&mut self) -> io::Result<()> {
+        self.inner.present_canvas()
+    }
+
+    fn read_pixels(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<Self::ID> {
+        self.inner.read_pixels(xy, size)
+    }
+
+    fn put_pixels(&mut self, xy: PixelsXY, data: &Self::ID) -> io::Result<()> {
+        self.inner.put_pixels(xy, data)
+    }
+
+    fn move_pixels(
+        &mut self,
+        x1y1: PixelsXY,
+        x2y2: PixelsXY,
+        size: SizeInPixels,
+    ) -> io::Result<()> {
+        self.inner.move_pixels(x1y1, x2y2, size)
+    }
+
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        self.inner.copy_pixels(src, size, dst)
+    }
+
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        self.inner.scroll_pixels(dx, dy)
+    }
+
+    fn write_text(&mut self, xy: PixelsXY, text: &str) -> io::Result<()> {
+        self.inner.write_text(xy, text)
+    }
+
+    fn draw_circle(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
+        match self.clip {
+            None => self.inner.draw_circle(center, radius),
+            Some(_) => draw_circle(self, center, radius),
+        }
+    }
+
+    fn draw_circle_filled(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
+        match self.clip {
+            None => self.inner.draw_circle_filled(center, radius),
+            Some(_) => draw_circle_filled(self, center, radius),
+        }
+    }
+
+    fn draw_line(&mut self, x1y1: PixelsXY, x2y2: PixelsXY) -> io::Result<()> {
+        let clip = match self.clip {
+            None => return self.inner.draw_line(x1y1, x2y2),
+            Some(clip) => clip,
+        };
+
+        // Horizontal and vertical lines, which are common because rectangles and filled circles
+        // are made of them, are shortened so that the backend can still draw them in one go.
+        // Any other line is drawn pixel by pixel.
+        if x1y1.y == x2y2.y {
+            let y = i32::from(x1y1.y);
+            let x1 = cmp::max(i32::from(cmp::min(x1y1.x, x2y2.x)), clip.x1);
+            let x2 = cmp::min(i32::from(cmp::max(x1y1.x, x2y2.x)), clip.x2 - 1);
+            if y < clip.y1 || y >= clip.y2 || x1 > x2 {
+                return Ok(());
+            }
+            self.inner.draw_line(
+                PixelsXY::new(x1.clamped_into(), x1y1.y),
+                PixelsXY::new(x2.clamped_into(), x1y1.y),
+            )
+        } else if x1y1.x == x2y2.x {
+            let x = i32::from(x1y1.x);
+            let y1 = cmp::max(i32::from(cmp::min(x1y1.y, x2y2.y)), clip.y1);
+            let y2 = cmp::min(i32::from(cmp::max(x1y1.y, x2y2.y)), clip.y2 - 1);
+            if x < clip.x1 || x >= clip.x2 || y1 > y2 {
+                return Ok(());
+            }
+            self.inner.draw_line(
+                PixelsXY::new(x1y1.x, y1.clamped_into()),
+                PixelsXY::new(x1y1.x, y2.clamped_into()),
+            )
+        } else if clip.is_empty() {
+            Ok(())
+        } else {
+            draw_line(self, x1y1, x2y2)
+        }
+    }
+
+    fn draw_pixel(&mut self, xy: PixelsXY) -> io::Result<()> {
+        match self.clip {
+            Some(clip) if !clip.contains(i32::from(xy.x), i32::from(xy.y)) => Ok(()),
+            _ => self.inner.draw_pixel(xy),
+        }
+    }
+
+    fn draw_rect(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<()> {
+        match self.clip {
+            None => self.inner.draw_rect(xy, size),
+            Some(_) => draw_rect(self, xy, size),
+        }
+    }
+
+    fn draw_rect_filled(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<()> {
+        let clip = match self.clip {
+            None => return self.inner.draw_rect_filled(xy, size),
+            Some(clip) => clip,
+        };
+
+        match Bounds::new(xy, size.width, size.height).intersect(&clip).to_xy_size() {
+            Some((xy, size)) => self.inner.draw_rect_filled(xy, size),
+            None => Ok(()),
+        }
+    }
+
+    /// Draws text by letting the backend render it in full and then restoring the pixels that
+    /// fell outside of the clip region, as backends render glyphs in ways we cannot replicate.
+    ///
+    /// Callers should disable syncing while this runs to avoid flickering.
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        let clip = match self.clip {
+            None => return self.inner.draw_text(xy, text, scale, rotation),
+            Some(clip) => clip,
+        };
+
+        // Compute a region that is guaranteed to cover the text for any rotation around `xy`.
+        let length = cmp::max(self.inner.text_width(text), self.inner.text_height());
+        let reach = i32::from(length) * i32::from(scale);
+        let (x, y) = (i32::from(xy.x), i32::from(xy.y));
+        let canvas = self.inner.get_info().size_pixels;
+        let area = Bounds { x1: x - reach, y1: y - reach, x2: x + reach + 1, y2: y + reach + 1 }
+            .intersect(&Bounds::new(PixelsXY::new(0, 0), canvas.width, canvas.height));
+
+        if area.intersect(&clip).is_empty() {
+            return Ok(());
+        } else if clip.contains_bounds(&area) {
+            return self.inner.draw_text(xy, text, scale, rotation);
+        }
+
+        let mut saved = vec![];
+        for strip in area.subtract(&clip) {
+            if let Some((xy, size)) = strip.to_xy_size() {
+                saved.push((xy, self.inner.read_pixels(xy, size)?));
+            }
+        }
+        self.inner.draw_text(xy, text, scale, rotation)?;
+        for (xy, data) in saved {
+            self.inner.put_pixels(xy, &data)?;
+        }
+        Ok(())
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        self.inner.set_text_font(name)
+    }
+
+    fn text_width(&self, text: &str) -> u16 {
+        self.inner.text_width(text)
+    }
+
+    fn text_height(&self) -> u16 {
+        self.inner.text_height()
+    }
+}
+
 #[cfg(test)]
 mod testutils {
     use super::*;
@@ -261,6 +542,7 @@ mod testutils {
     pub(crate) enum CapturedRasop {
         DrawLine(i16, i16, i16, i16),
         DrawPixel(i16, i16),
+        FillRect(i16, i16, u16, u16),
     }
 
     /// An implementation of `RasterOps` that captures calls for later validation.
@@ -335,8 +617,9 @@ mod testutils {
             unimplemented!();
         }
 
-        fn draw_rect_filled(&mut self, _xy: PixelsXY, _size: SizeInPixels) -> io::Result<()> {
-            unimplemented!();
+        fn draw_rect_filled(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<()> {
+            self.ops.push(CapturedRasop::FillRect(xy.x, xy.y, size.width, size.height));
+            Ok(())
         }
     }
 }
@@ -712,4 +995,146 @@ mod tests {
             rasops.ops.as_slice()
         );
     }
+
+    /// Creates a clip region at `x` and `y` with `width` and `height` for testing purposes.
+    fn clip(x: i16, y: i16, width: u16, height: u16) -> Option<ClipRect> {
+        Some(ClipRect::new(PixelsXY::new(x, y), width, height))
+    }
+
+    #[test]
+    fn test_clipped_none_forwards() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, None);
+        clipped.draw_line(PixelsXY::new(-5, 3), PixelsXY::new(10, 8)).unwrap();
+        clipped.draw_pixel(PixelsXY::new(-1, -1)).unwrap();
+        clipped.draw_rect_filled(PixelsXY::new(1, 2), SizeInPixels::new(3, 4)).unwrap();
+        assert_eq!(
+            [
+                CapturedRasop::DrawLine(-5, 3, 10, 8),
+                CapturedRasop::DrawPixel(-1, -1),
+                CapturedRasop::FillRect(1, 2, 3, 4),
+            ],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_line_crossing_boundary() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(2, 2, 4, 3));
+        clipped.draw_line(PixelsXY::new(0, 0), PixelsXY::new(7, 7)).unwrap();
+        clipped.draw_line(PixelsXY::new(7, 0), PixelsXY::new(0, 7)).unwrap();
+        assert_eq!(
+            [
+                CapturedRasop::DrawPixel(2, 2),
+                CapturedRasop::DrawPixel(3, 3),
+                CapturedRasop::DrawPixel(4, 4),
+                CapturedRasop::DrawPixel(5, 2),
+                CapturedRasop::DrawPixel(4, 3),
+                CapturedRasop::DrawPixel(3, 4),
+            ],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_line_horizontal_and_vertical() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(2, 2, 4, 3));
+        clipped.draw_line(PixelsXY::new(10, 3), PixelsXY::new(-5, 3)).unwrap();
+        clipped.draw_line(PixelsXY::new(3, -5), PixelsXY::new(3, 3)).unwrap();
+        clipped.draw_line(PixelsXY::new(4, 4), PixelsXY::new(4, 4)).unwrap();
+        clipped.draw_line(PixelsXY::new(-5, 5), PixelsXY::new(10, 5)).unwrap();
+        clipped.draw_line(PixelsXY::new(6, -5), PixelsXY::new(6, 10)).unwrap();
+        clipped.draw_line(PixelsXY::new(0, 3), PixelsXY::new(1, 3)).unwrap();
+        assert_eq!(
+            [
+                CapturedRasop::DrawLine(2, 3, 5, 3),
+                CapturedRasop::DrawLine(3, 2, 3, 3),
+                CapturedRasop::DrawLine(4, 4, 4, 4),
+            ],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_pixel() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(2, 2, 4, 3));
+        for (x, y) in [(1, 2), (2, 2), (5, 4), (6, 4), (5, 5), (i16::MIN, i16::MAX)] {
+            clipped.draw_pixel(PixelsXY::new(x, y)).unwrap();
+        }
+        assert_eq!(
+            [CapturedRasop::DrawPixel(2, 2), CapturedRasop::DrawPixel(5, 4)],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_rect() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(2, 2, 4, 3));
+        clipped.draw_rect(PixelsXY::new(0, 3), SizeInPixels::new(5, 10)).unwrap();
+        assert_eq!(
+            [CapturedRasop::DrawLine(2, 3, 4, 3), CapturedRasop::DrawLine(4, 3, 4, 4)],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_rect_filled() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(2, 2, 4, 3));
+        clipped.draw_rect_filled(PixelsXY::new(3, -10), SizeInPixels::new(100, 13)).unwrap();
+        clipped.draw_rect_filled(PixelsXY::new(6, 2), SizeInPixels::new(1, 1)).unwrap();
+        clipped.draw_rect_filled(PixelsXY::TOP_LEFT, SizeInPixels::MAX).unwrap();
+        assert_eq!(
+            [CapturedRasop::FillRect(3, 2, 3, 1), CapturedRasop::FillRect(2, 2, 4, 3)],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_draw_circle() {
+        let mut rasops = RecordingRasops::default();
+        let mut clipped = ClippedRasterOps::new(&mut rasops, clip(10, 17, 10, 10));
+        clipped.draw_circle(PixelsXY::new(10, 20), 4).unwrap();
+        rasops.ops.sort();
+        assert_eq!(
+            [
+                CapturedRasop::DrawPixel(10, 17),
+                CapturedRasop::DrawPixel(10, 17),
+                CapturedRasop::DrawPixel(10, 23),
+                CapturedRasop::DrawPixel(10, 23),
+                CapturedRasop::DrawPixel(11, 17),
+                CapturedRasop::DrawPixel(11, 23),
+                CapturedRasop::DrawPixel(12, 17),
+                CapturedRasop::DrawPixel(12, 23),
+                CapturedRasop::DrawPixel(13, 18),
+                CapturedRasop::DrawPixel(13, 19),
+                CapturedRasop::DrawPixel(13, 20),
+                CapturedRasop::DrawPixel(13, 20),
+                CapturedRasop::DrawPixel(13, 21),
+                CapturedRasop::DrawPixel(13, 22),
+            ],
+            rasops.ops.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_clipped_zero_area() {
+        for clip in [clip(2, 2, 0, 3), clip(2, 2, 4, 0)] {
+            let mut rasops = RecordingRasops::default();
+            let mut clipped = ClippedRasterOps::new(&mut rasops, clip);
+            clipped.draw_line(PixelsXY::new(0, 0), PixelsXY::new(7, 7)).unwrap();
+            clipped.draw_line(PixelsXY::new(0, 2), PixelsXY::new(7, 2)).unwrap();
+            clipped.draw_line(PixelsXY::new(2, 0), PixelsXY::new(2, 7)).unwrap();
+            clipped.draw_pixel(PixelsXY::new(2, 2)).unwrap();
+            clipped.draw_rect(PixelsXY::new(0, 0), SizeInPixels::new(5, 5)).unwrap();
+            clipped.draw_rect_filled(PixelsXY::new(0, 0), SizeInPixels::new(5, 5)).unwrap();
+            clipped.draw_circle(PixelsXY::new(2, 2), 3).unwrap();
+            clipped.draw_circle_filled(PixelsXY::new(2, 2), 3).unwrap();
+            assert!(rasops.ops.is_empty());
+        }
+    }
 }
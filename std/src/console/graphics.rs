@@ -15,11 +15,14 @@
 
 //! Support to implement graphical consoles.
 
+use super::drawing::ClippedRasterOps;
 use super::{
-    ansi_color_to_rgb, remove_control_chars, AnsiColor, CharsXY, ClearType, Console, Key,
-    LineBuffer, PixelsXY, SizeInPixels, RGB,
+    ansi_color_to_rgb, remove_control_chars, AnsiColor, CharsXY, ClearType, ClipRect, Console, Key,
+    LineBuffer, PixelsXY, ScreenCell, ScreenRotation, ShadowScreen, SizeInPixels, TextRotation,
+    RGB,
 };
 use async_trait::async_trait;
+use std::cmp;
 use std::convert::TryFrom;
 use std::io;
 
@@ -180,6 +183,43 @@ fn rect_points(x1y1: PixelsXY, x2y2: PixelsXY) -> Option<(PixelsXY, SizeInPixels
     }
 }
 
+/// Clips one axis of a copy of `len` pixels from `src` to `dst` so that both ranges fall within
+/// `0..limit`.  Returns the adjusted `src`, `len` and `dst` or `None` if nothing is left to copy.
+fn clip_copy_axis(limit: u16, src: i16, len: u16, dst: i16) -> Option<(i16, u16, i16)> {
+    let limit = i32::from(limit);
+    let (mut src, mut len, mut dst) = (i32::from(src), i32::from(len), i32::from(dst));
+
+    let shift = cmp::max(0, cmp::max(-src, -dst));
+    src += shift;
+    dst += shift;
+    len -= shift;
+
+    len = cmp::min(len, cmp::min(limit - src, limit - dst));
+    if len <= 0 {
+        None
+    } else {
+        Some((i16::try_from(src).ok()?, len.clamped_into(), i16::try_from(dst).ok()?))
+    }
+}
+
+/// Clips a copy of the rectangle at `src` with `size` to `dst` so that both the source and the
+/// target rectangles fall within a canvas of `canvas` size.  Returns the adjusted source origin,
+/// size, and target origin, or `None` if nothing is left to copy.
+fn clip_copy(
+    canvas: SizeInPixels,
+    src: PixelsXY,
+    size: SizeInPixels,
+    dst: PixelsXY,
+) -> Option<(PixelsXY, SizeInPixels, PixelsXY)> {
+    let (src_x, width, dst_x) = clip_copy_axis(canvas.width, src.x, size.width, dst.x)?;
+    let (src_y, height, dst_y) = clip_copy_axis(canvas.height, src.y, size.height, dst.y)?;
+    Some((
+        PixelsXY::new(src_x, src_y),
+        SizeInPixels::new(width, height),
+        PixelsXY::new(dst_x, dst_y),
+    ))
+}
+
 /// Container for configuration information of the backing surface.
 pub struct RasterInfo {
     /// Size of the console in pixels.
@@ -219,6 +259,22 @@ pub trait RasterOps {
     /// Should ignore any sync values that the backend might have cached via `set_sync`.
     fn present_canvas(&mut self) -> io::Result<()>;
 
+    /// Sets the brightness of the backlight of the device to `_pct` percent.
+    fn set_brightness(&mut self, _pct: u8) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Backlight control not supported by this console",
+        ))
+    }
+
+    /// Rotates the contents of the backing surface by `_rotation` relative to its native
+    /// orientation.  Subsequent calls to `get_info` must report the rotated dimensions.
+    ///
+    /// The contents of the surface are undefined after this call so the caller must clear it.
+    fn set_rotation(&mut self, _rotation: ScreenRotation) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "Screen rotation not supported by this console"))
+    }
+
     /// Reads the raw pixel data for the rectangular region specified by `xy` and `size`.
     fn read_pixels(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<Self::ID>;
 
@@ -230,6 +286,48 @@ pub trait RasterOps {
     fn move_pixels(&mut self, x1y1: PixelsXY, x2y2: PixelsXY, size: SizeInPixels)
         -> io::Result<()>;
 
+    /// Copies the rectangular region specified by `src` and `size` to `dst`.  The two regions may
+    /// overlap, in which case the result is as if the source region was read in full before
+    /// writing the target region.
+    ///
+    /// Unlike `move_pixels`, the coordinates are user-supplied and may fall outside of the canvas.
+    /// Only the parts of the regions that are within the canvas are copied.
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        let canvas = self.get_info().size_pixels;
+        if let Some((src, size, dst)) = clip_copy(canvas, src, size, dst) {
+            let data = self.read_pixels(src, size)?;
+            self.put_pixels(dst, &data)?;
+        }
+        Ok(())
+    }
+
+    /// Shifts the contents of the whole canvas by `dx` and `dy` pixels and fills the areas that
+    /// become exposed with the current drawing color.
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        let canvas = self.get_info().size_pixels;
+        self.copy_pixels(PixelsXY::new(0, 0), canvas, PixelsXY::new(dx, dy))?;
+
+        let exposed_width = cmp::min(dx.unsigned_abs(), canvas.width);
+        if exposed_width > 0 {
+            let x = if dx > 0 { 0 } else { canvas.width - exposed_width };
+            self.draw_rect_filled(
+                PixelsXY::new(x.clamped_into(), 0),
+                SizeInPixels::new(exposed_width, canvas.height),
+            )?;
+        }
+
+        let exposed_height = cmp::min(dy.unsigned_abs(), canvas.height);
+        if exposed_height > 0 {
+            let y = if dy > 0 { 0 } else { canvas.height - exposed_height };
+            self.draw_rect_filled(
+                PixelsXY::new(0, y.clamped_into()),
+                SizeInPixels::new(canvas.width, exposed_height),
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Writes `text` starting at `xy` with the current drawing color.
     fn write_text(&mut self, xy: PixelsXY, text: &str) -> io::Result<()>;
 
@@ -250,6 +348,49 @@ pub trait RasterOps {
 
     /// Draws a filled rectangle from `x1y1` to `x2y2` using the current drawing color.
     fn draw_rect_filled(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<()>;
+
+    /// Draws `text` starting at `xy` with the current drawing color and the font selected by
+    /// `set_text_font`, rendering every pixel of the font as a block of `scale`x`scale` pixels
+    /// and rotating the whole text around `xy` by `rotation`.
+    ///
+    /// Unlike `write_text`, `xy` is user-supplied and may fall outside of the canvas.
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        if scale != 1 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Scaled text not supported by this console",
+            ));
+        }
+        if rotation != TextRotation::Deg0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Rotated text not supported by this console",
+            ));
+        }
+        self.write_text(xy, text)
+    }
+
+    /// Selects the font named `_name` for subsequent `draw_text` calls.
+    fn set_text_font(&mut self, _name: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "Font selection not supported by this console"))
+    }
+
+    /// Computes the width in pixels of `text` when rendered by `draw_text`.
+    fn text_width(&self, text: &str) -> u16 {
+        let glyph_width = usize::from(self.get_info().glyph_size.width);
+        u16::try_from(text.chars().count().saturating_mul(glyph_width)).unwrap_or(u16::MAX)
+    }
+
+    /// Computes the height in pixels of any text when rendered by `draw_text`.
+    fn text_height(&self) -> u16 {
+        self.get_info().glyph_size.height
+    }
 }
 
 /// Primitive graphical console input operations.
@@ -315,6 +456,12 @@ where
 
     /// Whether video syncing is enabled or not.
     sync_enabled: bool,
+
+    /// Region to which drawing primitives are restricted, if any.
+    clip: Option<ClipRect>,
+
+    /// Copy of the characters displayed on the console.
+    shadow: ShadowScreen,
 }
 
 impl<IO, RO> GraphicsConsole<IO, RO>
@@ -351,6 +498,8 @@ where
             fg_color: ansi_color_to_rgb(default_fg_color),
             alt_backup: None,
             sync_enabled: true,
+            clip: None,
+            shadow: ShadowScreen::new(info.size_chars),
         };
 
         console.set_color(console.ansi_fg_color, console.ansi_bg_color)?;
@@ -478,6 +627,7 @@ where
     RO: RasterOps,
 {
     fn clear(&mut self, how: ClearType) -> io::Result<()> {
+        self.shadow.clear(how);
         match how {
             ClearType::All => {
                 self.raster_ops.set_draw_color(self.bg_color);
@@ -526,6 +676,7 @@ where
     }
 
     fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) -> io::Result<()> {
+        self.shadow.set_color(fg, bg);
         self.ansi_fg_color = fg;
         self.fg_color = ansi_color_to_rgb(fg.unwrap_or(self.default_fg_color));
         self.ansi_bg_color = bg;
@@ -551,6 +702,7 @@ where
             self.bg_color,
         ));
 
+        self.shadow.enter_alt();
         self.clear(ClearType::All)
     }
 
@@ -579,6 +731,8 @@ where
         self.clear_cursor()?;
 
         self.raster_ops.put_pixels(PixelsXY::new(0, 0), &pixels)?;
+        self.shadow.leave_alt();
+        self.shadow.set_color(ansi_fg_color, ansi_bg_color);
 
         self.cursor_pos = cursor_pos;
         self.ansi_fg_color = ansi_fg_color;
@@ -599,6 +753,7 @@ where
         let previous = self.set_sync(false)?;
         self.clear_cursor()?;
         self.cursor_pos = pos;
+        self.shadow.locate(pos);
         self.draw_cursor()?;
         self.set_sync(previous)?;
         Ok(())
@@ -612,6 +767,7 @@ where
         } else {
             self.cursor_pos.x += off as u16;
         }
+        self.shadow.move_within_line(off);
         self.draw_cursor()?;
         self.set_sync(previous)?;
         Ok(())
@@ -620,6 +776,8 @@ where
     fn print(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text);
 
+        self.shadow.print(&text);
+
         let previous = self.set_sync(false)?;
         self.clear_cursor()?;
         self.raw_write_wrapped(text)?;
@@ -658,6 +816,7 @@ where
 
     fn write(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text);
+        self.shadow.write(&text);
 
         let previous = self.set_sync(false)?;
         self.clear_cursor()?;
@@ -667,48 +826,133 @@ where
         Ok(())
     }
 
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        Ok(self.shadow.contents())
+    }
+
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        Ok(self.shadow.cursor())
+    }
+
     fn draw_circle(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
-        self.raster_ops.draw_circle(center, radius)?;
+        ClippedRasterOps::new(&mut self.raster_ops, self.clip).draw_circle(center, radius)?;
         self.present_canvas()
     }
 
     fn draw_circle_filled(&mut self, center: PixelsXY, radius: u16) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
-        self.raster_ops.draw_circle_filled(center, radius)?;
+        ClippedRasterOps::new(&mut self.raster_ops, self.clip)
+            .draw_circle_filled(center, radius)?;
         self.present_canvas()
     }
 
     fn draw_line(&mut self, x1y1: PixelsXY, x2y2: PixelsXY) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
-        self.raster_ops.draw_line(x1y1, x2y2)?;
+        ClippedRasterOps::new(&mut self.raster_ops, self.clip).draw_line(x1y1, x2y2)?;
         self.present_canvas()
     }
 
     fn draw_pixel(&mut self, xy: PixelsXY) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
-        self.raster_ops.draw_pixel(xy)?;
+        ClippedRasterOps::new(&mut self.raster_ops, self.clip).draw_pixel(xy)?;
         self.present_canvas()
     }
 
     fn draw_rect(&mut self, x1y1: PixelsXY, x2y2: PixelsXY) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
+        let mut raster_ops = ClippedRasterOps::new(&mut self.raster_ops, self.clip);
         match rect_points(x1y1, x2y2) {
-            Some((xy, size)) => self.raster_ops.draw_rect(xy, size)?,
-            None => self.raster_ops.draw_line(x1y1, x2y2)?,
+            Some((xy, size)) => raster_ops.draw_rect(xy, size)?,
+            None => raster_ops.draw_line(x1y1, x2y2)?,
         }
         self.present_canvas()
     }
 
     fn draw_rect_filled(&mut self, x1y1: PixelsXY, x2y2: PixelsXY) -> io::Result<()> {
         self.raster_ops.set_draw_color(self.fg_color);
+        let mut raster_ops = ClippedRasterOps::new(&mut self.raster_ops, self.clip);
         match rect_points(x1y1, x2y2) {
-            Some((xy, size)) => self.raster_ops.draw_rect_filled(xy, size)?,
-            None => self.raster_ops.draw_line(x1y1, x2y2)?,
+            Some((xy, size)) => raster_ops.draw_rect_filled(xy, size)?,
+            None => raster_ops.draw_line(x1y1, x2y2)?,
+        }
+        self.present_canvas()
+    }
+
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        let text = remove_control_chars(text);
+        if text.is_empty() {
+            return Ok(());
         }
+
+        self.raster_ops.set_draw_color(self.fg_color);
+        if self.clip.is_some() {
+            // Clipped text is drawn in full and then partially erased, so make sure the device
+            // doesn't get to see the intermediate state.
+            self.raster_ops.set_sync(false);
+            let result = ClippedRasterOps::new(&mut self.raster_ops, self.clip)
+                .draw_text(xy, &text, scale, rotation);
+            self.raster_ops.set_sync(self.sync_enabled);
+            result?;
+        } else {
+            self.raster_ops.draw_text(xy, &text, scale, rotation)?;
+        }
+        self.present_canvas()
+    }
+
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        self.raster_ops.copy_pixels(src, size, dst)?;
         self.present_canvas()
     }
 
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        self.raster_ops.set_draw_color(self.bg_color);
+        self.raster_ops.scroll_pixels(dx, dy)?;
+        self.present_canvas()
+    }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        self.raster_ops.set_brightness(pct)
+    }
+
+    fn set_clip(&mut self, clip: Option<ClipRect>) -> io::Result<()> {
+        self.clip = clip;
+        Ok(())
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<()> {
+        if self.alt_backup.is_some() {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                "Cannot rotate the console while in the alternate console",
+            ));
+        }
+
+        self.raster_ops.set_rotation(rotation)?;
+
+        let info = self.raster_ops.get_info();
+        self.size_pixels = info.size_pixels;
+        self.glyph_size = info.glyph_size;
+        self.size_chars = info.size_chars;
+        self.shadow.resize(info.size_chars);
+        self.cursor_backup = None;
+        self.clear(ClearType::All)
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        self.raster_ops.set_text_font(name)
+    }
+
+    fn text_width(&self, text: &str) -> io::Result<u16> {
+        Ok(self.raster_ops.text_width(&remove_control_chars(text)))
+    }
+
     fn sync_now(&mut self) -> io::Result<()> {
         if self.sync_enabled {
             Ok(())
@@ -875,4 +1119,49 @@ mod tests {
         assert_eq!(None, rect_points(PixelsXY { x: 10, y: 10 }, PixelsXY { x: 10, y: 20 }));
         assert_eq!(None, rect_points(PixelsXY { x: 10, y: 10 }, PixelsXY { x: 20, y: 10 }));
     }
+
+    #[test]
+    fn test_clip_copy_axis_within_limits() {
+        assert_eq!(Some((0, 10, 5)), clip_copy_axis(100, 0, 10, 5));
+        assert_eq!(Some((5, 10, 0)), clip_copy_axis(100, 5, 10, 0));
+        assert_eq!(Some((0, 100, 0)), clip_copy_axis(100, 0, 100, 0));
+    }
+
+    #[test]
+    fn test_clip_copy_axis_negative() {
+        assert_eq!(Some((0, 7, 13)), clip_copy_axis(100, -3, 10, 10));
+        assert_eq!(Some((13, 7, 0)), clip_copy_axis(100, 10, 10, -3));
+        assert_eq!(None, clip_copy_axis(100, -10, 10, 0));
+        assert_eq!(None, clip_copy_axis(100, i16::MIN, u16::MAX, 0));
+    }
+
+    #[test]
+    fn test_clip_copy_axis_past_limit() {
+        assert_eq!(Some((95, 5, 0)), clip_copy_axis(100, 95, 10, 0));
+        assert_eq!(Some((0, 3, 97)), clip_copy_axis(100, 0, 10, 97));
+        assert_eq!(None, clip_copy_axis(100, 100, 10, 0));
+        assert_eq!(None, clip_copy_axis(100, 0, 10, i16::MAX));
+    }
+
+    #[test]
+    fn test_clip_copy() {
+        assert_eq!(
+            Some((PixelsXY::new(0, 6), SizeInPixels::new(5, 1), PixelsXY::new(15, 0))),
+            clip_copy(
+                SizeInPixels::new(20, 10),
+                PixelsXY::new(-5, 4),
+                SizeInPixels::new(10, 3),
+                PixelsXY::new(10, -2)
+            )
+        );
+        assert_eq!(
+            None,
+            clip_copy(
+                SizeInPixels::new(20, 10),
+                PixelsXY::new(0, 0),
+                SizeInPixels::new(10, 3),
+                PixelsXY::new(0, 10)
+            )
+        );
+    }
 }
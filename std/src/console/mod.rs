@@ -40,7 +40,9 @@ pub use linebuffer::LineBuffer;
 mod pager;
 pub(crate) use pager::Pager;
 mod readline;
-pub use readline::{read_line, read_line_secure};
+pub use readline::{read_line, read_line_secure, read_line_with_completer, Completer, Completion};
+mod shadow;
+pub use shadow::{ScreenCell, ShadowScreen};
 mod spec;
 pub use spec::{ConsoleSpec, ParseError, Resolution};
 mod trivial;
@@ -97,6 +99,9 @@ pub enum Key {
     /// The Page Up key.
     PageUp,
 
+    /// Requests a search through previously-entered lines (e.g. `Ctrl-R`).
+    Search,
+
     /// The Tab key.
     Tab,
 
@@ -105,7 +110,7 @@ pub enum Key {
 }
 
 /// Indicates what part of the console to clear on a `Console::clear()` call.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum ClearType {
     /// Clears the whole console and moves the cursor to the top left corner.
     All,
@@ -166,7 +171,7 @@ impl PixelsXY {
 }
 
 /// Represents a rectangular size in pixels.
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[non_exhaustive]
 pub struct SizeInPixels {
     /// The width in pixels.
@@ -190,6 +195,78 @@ impl SizeInPixels {
     pub(crate) const MAX: Self = Self { width: u16::MAX, height: u16::MAX };
 }
 
+/// Represents a rectangular region of the console to which graphical operations are restricted.
+///
+/// Unlike `SizeInPixels`, the dimensions can be zero, in which case the region is empty.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ClipRect {
+    /// The top-left corner of the region.
+    pub xy: PixelsXY,
+
+    /// The width of the region in pixels.
+    pub width: u16,
+
+    /// The height of the region in pixels.
+    pub height: u16,
+}
+
+impl ClipRect {
+    /// Constructs a new clip region at `xy` with the given `width` and `height`.
+    pub fn new(xy: PixelsXY, width: u16, height: u16) -> Self {
+        Self { xy, width, height }
+    }
+}
+
+/// Rotation of graphical text around its starting point, counter-clockwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TextRotation {
+    /// Text runs from left to right.
+    Deg0,
+
+    /// Text runs from bottom to top.
+    Deg90,
+
+    /// Text runs from right to left upside down.
+    Deg180,
+
+    /// Text runs from top to bottom.
+    Deg270,
+}
+
+/// Rotation of the whole screen contents with respect to the physical display, clockwise.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScreenRotation {
+    /// The screen is shown as the display renders it natively.
+    Deg0,
+
+    /// The top of the screen is shown on the right side of the display.
+    Deg90,
+
+    /// The screen is shown upside down.
+    Deg180,
+
+    /// The top of the screen is shown on the left side of the display.
+    Deg270,
+}
+
+impl ScreenRotation {
+    /// Converts a rotation given in `degrees` to a screen rotation, if it is a multiple of 90.
+    pub fn from_degrees(degrees: i32) -> Option<Self> {
+        match degrees.rem_euclid(360) {
+            0 => Some(ScreenRotation::Deg0),
+            90 => Some(ScreenRotation::Deg90),
+            180 => Some(ScreenRotation::Deg180),
+            270 => Some(ScreenRotation::Deg270),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the rotation swaps the width and the height of the display.
+    pub fn is_transposed(self) -> bool {
+        matches!(self, ScreenRotation::Deg90 | ScreenRotation::Deg270)
+    }
+}
+
 /// Hooks to implement the commands that manipulate the console.
 #[async_trait(?Send)]
 pub trait Console {
@@ -255,6 +332,18 @@ pub trait Console {
     ///
     fn write(&mut self, text: &str) -> io::Result<()>;
 
+    /// Returns the characters displayed on the console, one vector per row from the top, omitting
+    /// trailing blank rows and trailing blank cells within each row.
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        Err(io::Error::new(io::ErrorKind::Other, "Cannot read the contents of this console"))
+    }
+
+    /// Returns the location of the cursor.  The column can be equal to the width of the console
+    /// when the cursor sits right after the last character of a full line.
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        Err(io::Error::new(io::ErrorKind::Other, "Cannot query the cursor of this console"))
+    }
+
     /// Draws the outline of a circle at `_center` with `_radius` using the current drawing color.
     fn draw_circle(&mut self, _center: PixelsXY, _radius: u16) -> io::Result<()> {
         Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
@@ -285,6 +374,67 @@ pub trait Console {
         Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
     }
 
+    /// Draws `_text` with its top-left corner at `_xy` using the current drawing color and the
+    /// font selected by `set_text_font`.  Every pixel of the font is drawn as a block of
+    /// `_scale`x`_scale` pixels and the whole text is rotated around `_xy` by `_rotation`.
+    fn draw_text(
+        &mut self,
+        _xy: PixelsXY,
+        _text: &str,
+        _scale: u16,
+        _rotation: TextRotation,
+    ) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Copies the rectangular region of `_size` pixels at `_src` to `_dst`.  The regions may
+    /// overlap and any parts of them that fall outside of the console are ignored.
+    fn copy_pixels(
+        &mut self,
+        _src: PixelsXY,
+        _size: SizeInPixels,
+        _dst: PixelsXY,
+    ) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Shifts the contents of the whole console by `_dx` and `_dy` pixels and clears the areas
+    /// that become exposed with the background color.
+    fn scroll_pixels(&mut self, _dx: i16, _dy: i16) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Sets the brightness of the display's backlight to `_pct` percent, where 0 turns the
+    /// backlight off.  Displays that can only switch the backlight on or off treat any non-zero
+    /// value as on.
+    fn set_brightness(&mut self, _pct: u8) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "No backlight control in this console"))
+    }
+
+    /// Restricts all subsequent drawing operations to the region in `_clip`, or removes any
+    /// previous restriction if `_clip` is none.
+    fn set_clip(&mut self, _clip: Option<ClipRect>) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Rotates the contents of the whole console by `_rotation` relative to the physical display,
+    /// which clears the console and may change its size.
+    fn set_rotation(&mut self, _rotation: ScreenRotation) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Selects the font named `_name` for subsequent `draw_text` calls.
+    ///
+    /// Unlike the font used by the text console, this font can be proportional.
+    fn set_text_font(&mut self, _name: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
+    /// Computes the width in pixels of `_text` when rendered by `draw_text`.
+    fn text_width(&self, _text: &str) -> io::Result<u16> {
+        Err(io::Error::new(io::ErrorKind::Other, "No graphics support in this console"))
+    }
+
     /// Causes any buffered output to be synced.
     ///
     /// This is a no-op when video syncing is enabled because output is never buffered in that case.
@@ -301,6 +451,21 @@ pub trait Console {
     ///
     /// Returns the previous status of the video syncing flag.
     fn set_sync(&mut self, _enabled: bool) -> io::Result<bool>;
+
+    /// Starts a batch of operations whose output can be held back until the matching `end_batch`
+    /// call so that it reaches the rendering target all at once.
+    ///
+    /// Batches can be nested.  The default implementation does nothing, which is appropriate for
+    /// consoles that do not benefit from combining their updates.
+    fn start_batch(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// Ends a batch started by `start_batch`, flushing its output if video syncing is enabled and
+    /// this was the outermost batch.
+    fn end_batch(&mut self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
 /// Resets the state of a console in a best-effort manner.
@@ -419,6 +584,19 @@ pub fn is_narrow(console: &dyn Console) -> bool {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_screen_rotation_from_degrees() {
+        assert_eq!(Some(ScreenRotation::Deg0), ScreenRotation::from_degrees(0));
+        assert_eq!(Some(ScreenRotation::Deg90), ScreenRotation::from_degrees(90));
+        assert_eq!(Some(ScreenRotation::Deg180), ScreenRotation::from_degrees(180));
+        assert_eq!(Some(ScreenRotation::Deg270), ScreenRotation::from_degrees(270));
+        assert_eq!(Some(ScreenRotation::Deg0), ScreenRotation::from_degrees(720));
+        assert_eq!(Some(ScreenRotation::Deg270), ScreenRotation::from_degrees(-90));
+
+        assert_eq!(None, ScreenRotation::from_degrees(1));
+        assert_eq!(None, ScreenRotation::from_degrees(-45));
+    }
+
     #[test]
     fn test_has_control_chars() {
         assert!(!has_control_chars(""));
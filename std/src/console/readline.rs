@@ -16,12 +16,31 @@
 //! Interactive line reader.
 
 use crate::console::{Console, Key, LineBuffer};
+use async_trait::async_trait;
 use std::borrow::Cow;
 use std::io;
 
 /// Character to print when typing a secure string.
 const SECURE_CHAR: &str = "*";
 
+/// Candidates to complete the word that precedes the cursor.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Completion {
+    /// Position of the first character of the word to replace, in characters.
+    pub start: usize,
+
+    /// Replacements for the word, in the order in which they are offered.
+    pub candidates: Vec<String>,
+}
+
+/// Provider of completions for interactive line input.
+#[async_trait(?Send)]
+pub trait Completer {
+    /// Returns the candidates to complete the word that ends at the character position `pos` of
+    /// `line`.
+    async fn complete(&self, line: &str, pos: usize) -> Completion;
+}
+
 /// Refreshes the current input line to display `line` assuming that the cursor is currently
 /// offset by `pos` characters from the beginning of the input and that the previous line was
 /// `clear_len` characters long.
@@ -47,14 +66,104 @@ fn update_line(
     console.show_cursor()
 }
 
+/// Returns the index of the most recent entry in `history` before `end` that contains `query`,
+/// ignoring case.
+fn find_in_history(history: &[String], query: &str, end: usize) -> Option<usize> {
+    let query = query.to_lowercase();
+    (0..end).rev().find(|i| history[*i].to_lowercase().contains(&query))
+}
+
+/// Interactively searches backwards through `history` for the most recent entry that contains the
+/// text typed by the user.
+///
+/// The search status replaces the contents of the input `line`, which is assumed to be displayed
+/// with the cursor at `pos`, and is truncated to fit in `width` characters.  The search ends when
+/// the user presses any key other than a printable character, backspace, or another search
+/// request.  On return, the input line displays the selected entry, or the original `line` if the
+/// search was cancelled with escape or if nothing was found.
+///
+/// Returns the index of the selected entry, if any, and the key that terminated the search.
+async fn search_history(
+    console: &mut dyn Console,
+    history: &[String],
+    line: &LineBuffer,
+    pos: usize,
+    width: usize,
+) -> io::Result<(Option<usize>, Key)> {
+    let mut query = String::new();
+    let mut found: Option<usize> = None;
+    let mut failed = false;
+    let mut pos = pos;
+    let mut clear_len = line.len();
+    let key = loop {
+        let status = format!(
+            "{}search '{}': {}",
+            if failed { "failed " } else { "" },
+            query,
+            found.map(|i| history[i].as_str()).unwrap_or("")
+        );
+        let status = LineBuffer::from(status.chars().take(width - 1).collect::<String>());
+        update_line(console, pos, clear_len, &status)?;
+        pos = status.len();
+        clear_len = status.len();
+
+        match console.read_key().await? {
+            Key::Backspace => {
+                query.pop();
+                found = find_in_history(history, &query, history.len());
+                failed = found.is_none() && !query.is_empty();
+            }
+
+            Key::Char(ch) => {
+                query.push(ch);
+                let end = found.map(|i| i + 1).unwrap_or(history.len());
+                match find_in_history(history, &query, end) {
+                    Some(i) => {
+                        found = Some(i);
+                        failed = false;
+                    }
+                    None => failed = true,
+                }
+            }
+
+            Key::Search => {
+                if let Some(i) = found {
+                    match find_in_history(history, &query, i) {
+                        Some(i) => {
+                            found = Some(i);
+                            failed = false;
+                        }
+                        None => failed = true,
+                    }
+                }
+            }
+
+            key => break key,
+        }
+    };
+
+    if key == Key::Escape {
+        found = None;
+    }
+    match found {
+        Some(i) => update_line(console, pos, clear_len, &LineBuffer::from(&history[i]))?,
+        None => update_line(console, pos, clear_len, line)?,
+    }
+    Ok((found, key))
+}
+
 /// Reads a line of text interactively from the console, using the given `prompt` and pre-filling
 /// the input with `previous`.  If `history` is not `None`, then this appends the newly entered line
-/// into the history and allows navigating through it.
+/// into the history and allows navigating and searching through it.  Lines that are not accepted,
+/// such as when the user interrupts the input, are not appended.  If `completer` is not `None`,
+/// then pressing tab replaces the word before the cursor with the candidates it offers, cycling
+/// through them when there is more than one.
 async fn read_line_interactive(
     console: &mut dyn Console,
     prompt: &str,
     previous: &str,
-    mut history: Option<&mut Vec<String>>,
+    history: Option<&mut Vec<String>>,
+    completer: Option<&dyn Completer>,
     echo: bool,
 ) -> io::Result<String> {
     let console_width = {
@@ -93,6 +202,8 @@ async fn read_line_interactive(
     // TODO(zenria): Handle UTF-8 graphemes.
     let mut pos = line.len();
 
+    // Lines entered without echo may contain secrets, so never record them.
+    let mut history = if echo { history } else { None };
     let mut history_pos = match history.as_mut() {
         Some(history) => {
             history.push(line.to_string());
@@ -101,8 +212,18 @@ async fn read_line_interactive(
         None => 0,
     };
 
-    loop {
-        match console.read_key().await? {
+    // Completion being cycled through by consecutive tab presses, and index of the next candidate.
+    let mut cycle: Option<(Completion, usize)> = None;
+
+    let result = loop {
+        let key = match console.read_key().await {
+            Ok(key) => key,
+            Err(e) => break Err(e),
+        };
+        if key != Key::Tab {
+            cycle = None;
+        }
+        match key {
             Key::ArrowUp => {
                 if let Some(history) = history.as_mut() {
                     if history_pos == 0 {
@@ -177,7 +298,7 @@ async fn read_line_interactive(
                 // the golden data we feed to the tests has single-character line endings.
                 if cfg!(not(target_os = "windows")) {
                     console.print("")?;
-                    break;
+                    break Ok(());
                 }
             }
 
@@ -222,7 +343,7 @@ async fn read_line_interactive(
                 }
             }
 
-            Key::Eof => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "EOF")),
+            Key::Eof => break Err(io::Error::new(io::ErrorKind::UnexpectedEof, "EOF")),
 
             Key::Escape => {
                 // Intentionally ignored.
@@ -235,35 +356,106 @@ async fn read_line_interactive(
                 }
             }
 
-            Key::Interrupt => return Err(io::Error::new(io::ErrorKind::Interrupted, "Ctrl+C")),
+            Key::Interrupt => break Err(io::Error::new(io::ErrorKind::Interrupted, "Ctrl+C")),
 
             Key::NewLine => {
                 console.print("")?;
-                break;
+                break Ok(());
             }
 
             Key::PageDown | Key::PageUp => {
                 // Intentionally ignored.
             }
 
+            Key::Search => {
+                if let Some(history) = history.as_mut() {
+                    // The last entry is the line being edited, so exclude it from the search.
+                    let end = history.len() - 1;
+                    let (found, key) =
+                        search_history(console, &history[..end], &line, pos, width).await?;
+                    if let Some(i) = found {
+                        history[history_pos] = line.into_inner();
+                        history_pos = i;
+                        line = LineBuffer::from(&history[history_pos]);
+                    }
+                    pos = line.len();
+
+                    match key {
+                        Key::CarriageReturn | Key::NewLine => {
+                            console.print("")?;
+                            break Ok(());
+                        }
+                        Key::Eof => {
+                            break Err(io::Error::new(io::ErrorKind::UnexpectedEof, "EOF"));
+                        }
+                        Key::Interrupt => {
+                            break Err(io::Error::new(io::ErrorKind::Interrupted, "Ctrl+C"));
+                        }
+                        _ => (),
+                    }
+                }
+            }
+
             Key::Tab => {
-                // TODO(jmmv): Would be nice to have some form of auto-completion.
+                let completer = match completer {
+                    Some(completer) => completer,
+                    None => continue,
+                };
+
+                let (start, candidate) = match cycle.as_mut() {
+                    Some((completion, next)) => {
+                        let candidate = completion.candidates[*next].clone();
+                        *next = (*next + 1) % completion.candidates.len();
+                        (completion.start, candidate)
+                    }
+                    None => {
+                        let completion = completer.complete(&line.to_string(), pos).await;
+                        if completion.candidates.is_empty() || completion.start > pos {
+                            continue;
+                        }
+                        let start = completion.start;
+                        let candidate = completion.candidates[0].clone();
+                        if completion.candidates.len() > 1 {
+                            cycle = Some((completion, 1));
+                        }
+                        (start, candidate)
+                    }
+                };
+
+                let candidate_len = candidate.chars().count();
+                if line.len() - (pos - start) + candidate_len >= width {
+                    // TODO(jmmv): Implement support for lines that exceed the width of the input
+                    // field (the width of the screen).
+                    continue;
+                }
+
+                let clear_len = line.len();
+                let mut new_line = LineBuffer::from(line.start(start));
+                new_line.insert_str(start, &candidate);
+                new_line.insert_str(start + candidate_len, &line.end(pos));
+                line = new_line;
+
+                update_line(console, pos, clear_len, &line)?;
+                pos = start + candidate_len;
+                if pos < line.len() {
+                    console.move_within_line(-((line.len() - pos) as i16))?;
+                }
             }
 
             // TODO(jmmv): Should do something smarter with unknown keys.
             Key::Unknown => (),
         }
-    }
+    };
 
     if let Some(history) = history.as_mut() {
-        if line.is_empty() {
+        if result.is_err() || line.is_empty() {
             history.pop();
         } else {
             let last = history.len() - 1;
             history[last] = line.to_string();
         }
     }
-    Ok(line.into_inner())
+    result.map(|()| line.into_inner())
 }
 
 /// Reads a line of text interactively from the console, which is not expected to be a TTY.
@@ -293,6 +485,7 @@ async fn read_line_raw(console: &mut dyn Console) -> io::Result<String> {
             Key::Interrupt => return Err(io::Error::new(io::ErrorKind::Interrupted, "Ctrl+C")),
             Key::NewLine => break,
             Key::PageDown | Key::PageUp => (),
+            Key::Search => (),
             Key::Tab => (),
             Key::Unknown => line.push('?'),
         }
@@ -309,7 +502,23 @@ pub async fn read_line(
     history: Option<&mut Vec<String>>,
 ) -> io::Result<String> {
     if console.is_interactive() {
-        read_line_interactive(console, prompt, previous, history, true).await
+        read_line_interactive(console, prompt, previous, history, None, true).await
+    } else {
+        read_line_raw(console).await
+    }
+}
+
+/// Same as `read_line` but, if the console is interactive, offers the completions provided by
+/// `completer` when the user presses tab.
+pub async fn read_line_with_completer(
+    console: &mut dyn Console,
+    prompt: &str,
+    previous: &str,
+    history: Option<&mut Vec<String>>,
+    completer: &dyn Completer,
+) -> io::Result<String> {
+    if console.is_interactive() {
+        read_line_interactive(console, prompt, previous, history, Some(completer), true).await
     } else {
         read_line_raw(console).await
     }
@@ -326,7 +535,7 @@ pub async fn read_line_secure(console: &mut dyn Console, prompt: &str) -> io::Re
             "Cannot read secure strings from a raw console".to_owned(),
         ));
     }
-    read_line_interactive(console, prompt, "", None, false).await
+    read_line_interactive(console, prompt, "", None, None, false).await
 }
 
 #[cfg(test)]
@@ -336,6 +545,27 @@ mod tests {
     use crate::testutils::*;
     use futures_lite::future::block_on;
 
+    /// Completer that offers the `words` that start with the word before the cursor.
+    struct WordsCompleter {
+        words: Vec<&'static str>,
+    }
+
+    #[async_trait(?Send)]
+    impl Completer for WordsCompleter {
+        async fn complete(&self, line: &str, pos: usize) -> Completion {
+            let prefix = line.chars().take(pos).collect::<Vec<char>>();
+            let start = prefix.iter().rposition(|ch| *ch == ' ').map(|i| i + 1).unwrap_or(0);
+            let word = prefix[start..].iter().collect::<String>();
+            let candidates = self
+                .words
+                .iter()
+                .filter(|w| w.starts_with(&word))
+                .map(|w| (*w).to_owned())
+                .collect();
+            Completion { start, candidates }
+        }
+    }
+
     /// Builder pattern to construct a test for `read_line_interactive`.
     #[must_use]
     struct ReadLineInteractiveTest {
@@ -344,6 +574,7 @@ mod tests {
         prompt: &'static str,
         previous: &'static str,
         history: Option<Vec<String>>,
+        completer: Option<WordsCompleter>,
         echo: bool,
         exp_line: &'static str,
         exp_output: Vec<CapturedOut>,
@@ -360,6 +591,7 @@ mod tests {
                 prompt: "",
                 previous: "",
                 history: None,
+                completer: None,
                 echo: true,
                 exp_line: "",
                 exp_output: vec![],
@@ -434,6 +666,12 @@ mod tests {
             self
         }
 
+        /// Enables completion and sets the `words` that the completer offers.
+        fn set_completions(mut self, words: Vec<&'static str>) -> Self {
+            self.completer = Some(WordsCompleter { words });
+            self
+        }
+
         /// Sets whether read_line echoes characters or not.
         fn set_echo(mut self, echo: bool) -> Self {
             self.echo = echo;
@@ -449,12 +687,14 @@ mod tests {
             let mut console = MockConsole::default();
             console.add_input_keys(&self.keys);
             console.set_size_chars(self.size_chars);
+            let completer = self.completer.as_ref().map(|c| c as &dyn Completer);
             let line = match self.history.as_mut() {
                 Some(history) => block_on(read_line_interactive(
                     &mut console,
                     self.prompt,
                     self.previous,
                     Some(history),
+                    completer,
                     self.echo,
                 ))
                 .unwrap(),
@@ -463,6 +703,7 @@ mod tests {
                     self.prompt,
                     self.previous,
                     None,
+                    completer,
                     self.echo,
                 ))
                 .unwrap(),
@@ -1107,6 +1348,212 @@ mod tests {
             .accept();
     }
 
+    #[test]
+    fn test_read_line_interactive_history_search_and_accept() {
+        ReadLineInteractiveTest::default()
+            .set_size_chars(CharsXY::new(40, 5))
+            .set_history(
+                vec!["print 1".to_owned(), "goto 10".to_owned(), "PRINT 2".to_owned()],
+                vec![
+                    "print 1".to_owned(),
+                    "goto 10".to_owned(),
+                    "PRINT 2".to_owned(),
+                    "print 1".to_owned(),
+                ],
+            )
+            //
+            .add_key(Key::Search)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::Write("search '': ".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key_chars("p")
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-11))
+            .add_output(CapturedOut::Write("search 'p': PRINT 2".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key_chars("r")
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-19))
+            .add_output(CapturedOut::Write("search 'pr': PRINT 2".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key(Key::Search)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-20))
+            .add_output(CapturedOut::Write("search 'pr': print 1".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-20))
+            .add_output(CapturedOut::Write("print 1".to_string()))
+            .add_output(CapturedOut::Write(" ".repeat(13)))
+            .add_output(CapturedOut::MoveWithinLine(-13))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .set_line("print 1")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_history_search_failed_and_cancelled() {
+        ReadLineInteractiveTest::default()
+            .set_size_chars(CharsXY::new(40, 5))
+            .set_history(vec!["abc".to_owned()], vec!["abc".to_owned(), "ab".to_owned()])
+            //
+            .add_key_chars("ab")
+            .add_output_bytes("ab")
+            //
+            .add_key(Key::Search)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-2))
+            .add_output(CapturedOut::Write("search '': ".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key_chars("z")
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-11))
+            .add_output(CapturedOut::Write("failed search 'z': ".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key(Key::Escape)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-19))
+            .add_output(CapturedOut::Write("ab".to_string()))
+            .add_output(CapturedOut::Write(" ".repeat(17)))
+            .add_output(CapturedOut::MoveWithinLine(-17))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .set_line("ab")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_history_not_recorded_on_error() {
+        let mut console = MockConsole::default();
+        console.add_input_keys(&[Key::Char('a'), Key::Interrupt]);
+        let mut history = vec!["first".to_owned()];
+        let err =
+            block_on(read_line_interactive(&mut console, "", "", Some(&mut history), None, true))
+                .unwrap_err();
+        assert_eq!(io::ErrorKind::Interrupted, err.kind());
+        assert_eq!(vec!["first".to_owned()], history);
+    }
+
+    #[test]
+    fn test_read_line_interactive_history_not_recorded_without_echo() {
+        ReadLineInteractiveTest::default()
+            .set_echo(false)
+            .set_history(vec!["first".to_owned()], vec!["first".to_owned()])
+            //
+            .add_key_chars("secret")
+            .add_output_bytes("******")
+            //
+            .add_key(Key::ArrowUp)
+            .add_key(Key::Search)
+            //
+            .set_line("secret")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_complete_unique() {
+        ReadLineInteractiveTest::default()
+            .set_completions(vec!["hello", "world"])
+            //
+            .add_key_chars("say wo")
+            .add_output_bytes("say wo")
+            //
+            .add_key(Key::Tab)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-6))
+            .add_output(CapturedOut::Write("say world".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .set_line("say world")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_complete_in_middle_of_line() {
+        ReadLineInteractiveTest::default()
+            .set_completions(vec!["hello"])
+            //
+            .add_key_chars("he x")
+            .add_output_bytes("he x")
+            //
+            .add_key(Key::ArrowLeft)
+            .add_output(CapturedOut::MoveWithinLine(-1))
+            .add_key(Key::ArrowLeft)
+            .add_output(CapturedOut::MoveWithinLine(-1))
+            //
+            .add_key(Key::Tab)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-2))
+            .add_output(CapturedOut::Write("hello x".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            .add_output(CapturedOut::MoveWithinLine(-2))
+            //
+            .set_line("hello x")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_complete_cycles_ambiguous() {
+        ReadLineInteractiveTest::default()
+            .set_completions(vec!["abc", "abcdef", "other"])
+            //
+            .add_key_chars("ab")
+            .add_output_bytes("ab")
+            //
+            .add_key(Key::Tab)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-2))
+            .add_output(CapturedOut::Write("abc".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key(Key::Tab)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-3))
+            .add_output(CapturedOut::Write("abcdef".to_string()))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key(Key::Tab)
+            .add_output(CapturedOut::HideCursor)
+            .add_output(CapturedOut::MoveWithinLine(-6))
+            .add_output(CapturedOut::Write("abc".to_string()))
+            .add_output(CapturedOut::Write("   ".to_string()))
+            .add_output(CapturedOut::MoveWithinLine(-3))
+            .add_output(CapturedOut::ShowCursor)
+            //
+            .add_key_chars("!")
+            .add_output_bytes("!")
+            //
+            .set_line("abc!")
+            .accept();
+    }
+
+    #[test]
+    fn test_read_line_interactive_complete_no_candidates_or_too_long() {
+        ReadLineInteractiveTest::default()
+            .set_size_chars(CharsXY::new(10, 5))
+            .set_completions(vec!["abcdefghijkl"])
+            //
+            .add_key_chars("x ab")
+            .add_output_bytes("x ab")
+            //
+            .add_key(Key::Tab)
+            //
+            .add_key_chars(" z")
+            .add_output_bytes(" z")
+            //
+            .add_key(Key::Tab)
+            //
+            .set_line("x ab z")
+            .accept();
+    }
+
     #[test]
     fn test_read_line_ignored_keys() {
         ReadLineInteractiveTest::default()
@@ -1116,6 +1563,7 @@ mod tests {
             .add_key(Key::Escape)
             .add_key(Key::PageDown)
             .add_key(Key::PageUp)
+            .add_key(Key::Search)
             .add_key(Key::Tab)
             // -
             .add_key_chars("affected")
@@ -0,0 +1,360 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! In-memory copy of the characters displayed by a console.
+
+use super::{remove_control_chars, CharsXY, ClearType};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+
+/// A character displayed on the console along with its colors.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ScreenCell {
+    /// The character in the cell.
+    pub ch: char,
+
+    /// The foreground color of the cell, or `None` for the default color.
+    pub fg: Option<u8>,
+
+    /// The background color of the cell, or `None` for the default color.
+    pub bg: Option<u8>,
+}
+
+impl Default for ScreenCell {
+    fn default() -> Self {
+        Self { ch: ' ', fg: None, bg: None }
+    }
+}
+
+impl ScreenCell {
+    /// Returns true if the cell looks empty on the screen.
+    fn is_blank(&self) -> bool {
+        self.ch == ' ' && self.bg.is_none()
+    }
+}
+
+/// Shadow copy of the character grid of a console.
+///
+/// This mirrors the text operations applied to a console, following the same wrapping and
+/// scrolling rules as `GraphicsConsole`, so that consoles that cannot read back their contents can
+/// still report what they display.  Rows and cells are allocated on demand so the screen can be
+/// arbitrarily large as long as it is sparsely populated.
+#[derive(Debug)]
+pub struct ShadowScreen {
+    /// Size of the console in characters.
+    size: CharsXY,
+
+    /// Contents of the rows from the top of the screen.  Missing rows and missing cells at the end
+    /// of each row are blank.
+    rows: VecDeque<Vec<ScreenCell>>,
+
+    /// Location of the cursor.
+    cursor: CharsXY,
+
+    /// Current foreground color.
+    fg: Option<u8>,
+
+    /// Current background color.
+    bg: Option<u8>,
+
+    /// Contents and cursor of the screen right before entering the alternate console.
+    alt_backup: Option<(VecDeque<Vec<ScreenCell>>, CharsXY)>,
+}
+
+impl ShadowScreen {
+    /// Creates a new blank screen of `size` characters.
+    pub fn new(size: CharsXY) -> Self {
+        Self {
+            size,
+            rows: VecDeque::new(),
+            cursor: CharsXY::default(),
+            fg: None,
+            bg: None,
+            alt_backup: None,
+        }
+    }
+
+    /// Changes the size of the screen to `size`, discarding any contents that do not fit.
+    pub fn resize(&mut self, size: CharsXY) {
+        if self.size == size {
+            return;
+        }
+        self.size = size;
+        self.rows.truncate(usize::from(size.y));
+        for row in self.rows.iter_mut() {
+            row.truncate(usize::from(size.x));
+        }
+        self.cursor.x = self.cursor.x.min(size.x);
+        self.cursor.y = self.cursor.y.min(size.y.saturating_sub(1));
+    }
+
+    /// Returns the row at `y`, allocating it if necessary.
+    fn row_mut(&mut self, y: u16) -> &mut Vec<ScreenCell> {
+        let y = usize::from(y);
+        while self.rows.len() <= y {
+            self.rows.push_back(vec![]);
+        }
+        &mut self.rows[y]
+    }
+
+    /// Clears the screen as specified by `how`.
+    pub fn clear(&mut self, how: ClearType) {
+        match how {
+            ClearType::All => {
+                self.rows.clear();
+                self.cursor = CharsXY::default();
+            }
+            ClearType::CurrentLine => {
+                self.row_mut(self.cursor.y).clear();
+                self.cursor.x = 0;
+            }
+            ClearType::PreviousChar => {
+                if self.cursor.x > 0 {
+                    self.cursor.x -= 1;
+                    let x = usize::from(self.cursor.x);
+                    let row = self.row_mut(self.cursor.y);
+                    if x < row.len() {
+                        row[x] = ScreenCell::default();
+                    }
+                }
+            }
+            ClearType::UntilNewLine => {
+                let x = usize::from(self.cursor.x);
+                self.row_mut(self.cursor.y).truncate(x);
+            }
+        }
+    }
+
+    /// Sets the colors for subsequent writes.
+    pub fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) {
+        self.fg = fg;
+        self.bg = bg;
+    }
+
+    /// Saves the current contents and clears the screen.
+    pub fn enter_alt(&mut self) {
+        if self.alt_backup.is_none() {
+            self.alt_backup = Some((self.rows.clone(), self.cursor));
+            self.clear(ClearType::All);
+        }
+    }
+
+    /// Restores the contents saved by `enter_alt`.
+    pub fn leave_alt(&mut self) {
+        if let Some((rows, cursor)) = self.alt_backup.take() {
+            self.rows = rows;
+            self.cursor = cursor;
+        }
+    }
+
+    /// Returns the location of the cursor.
+    ///
+    /// The column can be equal to the width of the screen if the last write filled the line, in
+    /// which case the next write wraps to the next line.
+    pub fn cursor(&self) -> CharsXY {
+        self.cursor
+    }
+
+    /// Moves the cursor to `pos`.
+    pub fn locate(&mut self, pos: CharsXY) {
+        self.cursor = pos;
+    }
+
+    /// Moves the cursor by `off` positions within the current line.
+    pub fn move_within_line(&mut self, off: i16) {
+        let x = i32::from(self.cursor.x) + i32::from(off);
+        self.cursor.x = u16::try_from(x.max(0)).unwrap_or(u16::MAX).min(self.size.x);
+    }
+
+    /// Moves the cursor to the beginning of the next line, scrolling the screen if necessary.
+    fn open_line(&mut self) {
+        if self.cursor.y < self.size.y.saturating_sub(1) {
+            self.cursor.y += 1;
+        } else {
+            self.rows.pop_front();
+        }
+        self.cursor.x = 0;
+    }
+
+    /// Writes `text` at the cursor position with wrapping and scrolling, leaving the cursor right
+    /// after the last character.
+    pub fn write(&mut self, text: &str) {
+        let cell = ScreenCell { ch: ' ', fg: self.fg, bg: self.bg };
+        for ch in remove_control_chars(text).chars() {
+            if self.cursor.x >= self.size.x {
+                self.open_line();
+            }
+            let x = usize::from(self.cursor.x);
+            let row = self.row_mut(self.cursor.y);
+            if row.len() <= x {
+                row.resize(x + 1, ScreenCell::default());
+            }
+            row[x] = ScreenCell { ch, ..cell };
+            self.cursor.x += 1;
+        }
+    }
+
+    /// Writes `text` like `write` does and then moves the cursor to the next line.
+    pub fn print(&mut self, text: &str) {
+        self.write(text);
+        self.open_line();
+    }
+
+    /// Returns the contents of the screen, one vector per row from the top, omitting trailing blank
+    /// rows and trailing blank cells within each row.
+    pub fn contents(&self) -> Vec<Vec<ScreenCell>> {
+        let mut contents: Vec<Vec<ScreenCell>> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let len = row.iter().rposition(|cell| !cell.is_blank()).map_or(0, |i| i + 1);
+                row[..len].to_vec()
+            })
+            .collect();
+        while contents.last().map(Vec::is_empty).unwrap_or(false) {
+            contents.pop();
+        }
+        contents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Syntactic sugar to express the expected contents of a screen as plain text.
+    fn text(screen: &ShadowScreen) -> Vec<String> {
+        screen.contents().iter().map(|row| row.iter().map(|cell| cell.ch).collect()).collect()
+    }
+
+    #[test]
+    fn test_empty() {
+        let screen = ShadowScreen::new(CharsXY::new(10, 5));
+        assert!(screen.contents().is_empty());
+    }
+
+    #[test]
+    fn test_print_and_write() {
+        let mut screen = ShadowScreen::new(CharsXY::new(10, 5));
+        screen.print("Hello");
+        screen.write("a");
+        screen.write("b\n");
+        screen.print("");
+        screen.print("c");
+        assert_eq!(vec!["Hello", "ab", "c"], text(&screen));
+    }
+
+    #[test]
+    fn test_wrap_and_scroll() {
+        let mut screen = ShadowScreen::new(CharsXY::new(4, 3));
+        screen.print("abcdefghij");
+        assert_eq!(vec!["efgh", "ij"], text(&screen));
+        screen.print("12345678");
+        assert_eq!(vec!["1234", "5678"], text(&screen));
+        screen.write("x");
+        assert_eq!(vec!["1234", "5678", "x"], text(&screen));
+    }
+
+    #[test]
+    fn test_exact_fit_does_not_add_blank_line() {
+        let mut screen = ShadowScreen::new(CharsXY::new(4, 3));
+        screen.write("abcd");
+        assert_eq!(CharsXY::new(4, 0), screen.cursor());
+        screen.print("");
+        assert_eq!(CharsXY::new(0, 1), screen.cursor());
+        screen.print("e");
+        assert_eq!(vec!["abcd", "e"], text(&screen));
+    }
+
+    #[test]
+    fn test_colors() {
+        let mut screen = ShadowScreen::new(CharsXY::new(10, 5));
+        screen.set_color(Some(1), None);
+        screen.write("a");
+        screen.set_color(None, Some(2));
+        screen.write(" ");
+        screen.set_color(None, None);
+        screen.write("  ");
+        assert_eq!(
+            vec![vec![
+                ScreenCell { ch: 'a', fg: Some(1), bg: None },
+                ScreenCell { ch: ' ', fg: None, bg: Some(2) },
+            ]],
+            screen.contents()
+        );
+    }
+
+    #[test]
+    fn test_locate_and_clear() {
+        let mut screen = ShadowScreen::new(CharsXY::new(10, 5));
+        screen.print("first");
+        screen.print("second");
+        screen.print("third");
+
+        screen.locate(CharsXY::new(3, 1));
+        screen.write("X");
+        assert_eq!(vec!["first", "secXnd", "third"], text(&screen));
+        assert_eq!(CharsXY::new(4, 1), screen.cursor());
+
+        screen.clear(ClearType::UntilNewLine);
+        assert_eq!(vec!["first", "secX", "third"], text(&screen));
+
+        screen.clear(ClearType::PreviousChar);
+        screen.move_within_line(-1);
+        screen.write("Y");
+        assert_eq!(vec!["first", "seY", "third"], text(&screen));
+
+        screen.locate(CharsXY::new(2, 2));
+        screen.clear(ClearType::CurrentLine);
+        screen.write("Z");
+        assert_eq!(vec!["first", "seY", "Z"], text(&screen));
+
+        screen.clear(ClearType::All);
+        screen.write("W");
+        assert_eq!(vec!["W"], text(&screen));
+    }
+
+    #[test]
+    fn test_alt() {
+        let mut screen = ShadowScreen::new(CharsXY::new(10, 5));
+        screen.print("main");
+        screen.enter_alt();
+        assert!(screen.contents().is_empty());
+        screen.print("alt");
+        screen.leave_alt();
+        screen.write("again");
+        assert_eq!(vec!["main", "again"], text(&screen));
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut screen = ShadowScreen::new(CharsXY::new(10, 5));
+        screen.print("abcdefgh");
+        screen.print("ijkl");
+        screen.print("mnop");
+        screen.resize(CharsXY::new(3, 2));
+        assert_eq!(vec!["abc", "ijk"], text(&screen));
+        screen.write("q");
+        assert_eq!(vec!["abc", "qjk"], text(&screen));
+    }
+
+    #[test]
+    fn test_huge_sparse_screen() {
+        let mut screen = ShadowScreen::new(CharsXY::new(u16::MAX, u16::MAX));
+        screen.locate(CharsXY::new(2, 1));
+        screen.print("a");
+        assert_eq!(vec!["", "  a"], text(&screen));
+    }
+}
@@ -17,11 +17,15 @@
 
 use async_trait::async_trait;
 use endbasic_core::ast::{ArgSep, ExprType, Value, VarRef};
-use endbasic_core::compiler::{ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax};
+use endbasic_core::compiler::{
+    AnyValueSyntax, ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax, SingularArgSyntax,
+};
 use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
 use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
 use std::borrow::Cow;
 use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io;
 use std::rc::Rc;
 
 /// Category description for all symbols provided by this module.
@@ -33,6 +37,105 @@ impl Clearable for ClearableIndex {
     fn reset_state(&self, _syms: &mut endbasic_core::syms::Symbols) {
         *self.0.borrow_mut() = 0;
     }
+
+    fn state_key(&self) -> Option<&'static str> {
+        Some("data.index")
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        (*self.0.borrow() as u64).to_le_bytes().to_vec()
+    }
+
+    fn restore_state(&self, state: &[u8]) -> io::Result<()> {
+        let index = <[u8; 8]>::try_from(state)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid DATA index state"))?;
+        *self.0.borrow_mut() = u64::from_le_bytes(index) as usize;
+        Ok(())
+    }
+}
+
+/// The `EMPTYVALUES` command.
+pub struct EmptyValuesCommand {
+    metadata: CallableMetadata,
+}
+
+impl EmptyValuesCommand {
+    /// Creates a new `EMPTYVALUES` command.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EMPTYVALUES")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Enables EMPTY values to represent missing data.
+By default, READ converts empty values in the data array into the default values for the relevant \
+types, which makes it impossible to tell apart a missing value from an explicit zero.  Once \
+EMPTYVALUES runs, READ stores EMPTY values into the variables instead.  These can be tested with \
+ISEMPTY?, copied into other variables or passed to SUBs and FUNCTIONs, and are displayed as \
+nothing by PRINT.  Any other use of an EMPTY value, such as in arithmetic, raises an error.
+This setting remains in effect until the machine is cleared, which happens when running a new \
+program or when executing CLEAR.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for EmptyValuesCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        machine.enable_empty_values();
+        Ok(())
+    }
+}
+
+/// The `ISEMPTY` function.
+pub struct IsEmptyFunction {
+    metadata: CallableMetadata,
+}
+
+impl IsEmptyFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ISEMPTY")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::AnyValue(
+                        AnyValueSyntax { name: Cow::Borrowed("expr"), allow_missing: false },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_empty_values()
+                .with_category(CATEGORY)
+                .with_description(
+                    "Checks if a value is EMPTY.
+EMPTY values only exist after enabling them with EMPTYVALUES, so this always returns FALSE \
+otherwise.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for IsEmptyFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let _tag = scope.pop_value_tag();
+        let empty = scope.pop_if_empty();
+        scope.return_boolean(empty)
+    }
 }
 
 /// The `READ` command.
@@ -66,7 +169,8 @@ array that gets incremented by the number of provided variables every time it is
 The variable references in the vref1..vrefN list must match the types or be compatible with the \
 values in the corresponding position of the data array.  Empty values in the data array can be \
 specified by DATA, and those are converted into the default values for the relevant types: \
-booleans are false, numbers are 0, and strings are empty.
+booleans are false, numbers are 0, and strings are empty.  If EMPTYVALUES is in effect, empty \
+values are stored as EMPTY instead.
 Attempting to extract more values than are defined by DATA results in an \"out of data\" error.
 The index that READ uses to extract DATA values can be reset by RESTORE and, more generally, by \
 CLEAR.",
@@ -91,6 +195,7 @@ impl Callable for ReadCommand {
             vrefs.push(scope.pop_varref_with_pos());
         }
 
+        let empty_values = machine.empty_values();
         let mut index = self.index.borrow_mut();
         for (vname, vtype, pos) in vrefs {
             let datum = {
@@ -105,6 +210,7 @@ impl Callable for ReadCommand {
 
                 match (vtype, &data[*index]) {
                     (_, Some(datum)) => datum.clone(),
+                    (vtype, None) if empty_values => Value::Empty(vtype),
                     (ExprType::Boolean, None) => Value::Boolean(false),
                     (ExprType::Double, None) => Value::Double(0.0),
                     (ExprType::Integer, None) => Value::Integer(0),
@@ -165,6 +271,8 @@ impl Callable for RestoreCommand {
 pub fn add_all(machine: &mut Machine) {
     let index = Rc::from(RefCell::from(0));
     machine.add_clearable(Box::from(ClearableIndex(index.clone())));
+    machine.add_callable(EmptyValuesCommand::new());
+    machine.add_callable(IsEmptyFunction::new());
     machine.add_callable(ReadCommand::new(index.clone()));
     machine.add_callable(RestoreCommand::new(index));
 }
@@ -172,7 +280,95 @@ pub fn add_all(machine: &mut Machine) {
 #[cfg(test)]
 mod tests {
     use crate::testutils::*;
-    use endbasic_core::ast::Value;
+    use endbasic_core::ast::{ExprType, Value};
+
+    #[test]
+    fn test_emptyvalues_errors() {
+        check_stmt_compilation_err("1:1: EMPTYVALUES expected no arguments", "EMPTYVALUES 1");
+    }
+
+    #[test]
+    fn test_isempty() {
+        check_expr_ok(false, "ISEMPTY(0)");
+        check_expr_ok(false, "ISEMPTY(\"\")");
+
+        Tester::default()
+            .run(
+                r#"
+            EMPTYVALUES
+            DATA 1, , "x", , FALSE
+            READ a, b, c$, d$, e?
+            PRINT ISEMPTY(a); ISEMPTY(b); ISEMPTY(c$); ISEMPTY(d$); ISEMPTY?(e)
+            "#,
+            )
+            .expect_prints(["FALSE TRUE FALSE TRUE FALSE"])
+            .expect_var("a", Value::Integer(1))
+            .expect_var("b", Value::Empty(ExprType::Integer))
+            .expect_var("c", Value::Text("x".to_owned()))
+            .expect_var("d", Value::Empty(ExprType::Text))
+            .expect_var("e", Value::Boolean(false))
+            .check();
+
+        check_expr_compilation_error("1:10: ISEMPTY expected expr", "ISEMPTY()");
+        check_expr_compilation_error("1:10: ISEMPTY expected expr", "ISEMPTY(1, 2)");
+    }
+
+    #[test]
+    fn test_read_empty_values_round_trip() {
+        Tester::default()
+            .run(
+                r#"
+            EMPTYVALUES
+            DATA 5, , 0
+            FOR i = 1 TO 3
+                READ n
+                IF ISEMPTY(n) THEN PRINT "missing" ELSE PRINT n
+            NEXT
+            RESTORE
+            READ a, b#, c
+            other# = b#
+            PRINT a; b#; c; "|"; other#; "|"
+            "#,
+            )
+            .expect_prints([" 5", "missing", " 0", " 5  0 ||"])
+            .expect_var("a", Value::Integer(5))
+            .expect_var("b", Value::Empty(ExprType::Double))
+            .expect_var("c", Value::Integer(0))
+            .expect_var("other", Value::Empty(ExprType::Double))
+            .expect_var("i", Value::Integer(4))
+            .expect_var("n", Value::Integer(0))
+            .check();
+    }
+
+    #[test]
+    fn test_read_empty_values_arithmetic_error() {
+        Tester::default()
+            .run("EMPTYVALUES: DATA , 3: READ a, b: PRINT b * 2: PRINT a + b")
+            .expect_prints([" 6"])
+            .expect_var("a", Value::Empty(ExprType::Integer))
+            .expect_var("b", Value::Integer(3))
+            .expect_err("1:54: Cannot operate on an EMPTY value")
+            .check();
+    }
+
+    #[test]
+    fn test_read_empty_values_not_accepted_by_callable() {
+        Tester::default()
+            .run("EMPTYVALUES: DATA ,: READ a$: PRINT LEN(a$)")
+            .expect_var("a", Value::Empty(ExprType::Text))
+            .expect_err("1:41: LEN does not accept EMPTY values")
+            .check();
+    }
+
+    #[test]
+    fn test_read_empty_values_disabled_by_clear() {
+        Tester::default()
+            .run("EMPTYVALUES: CLEAR: DATA ,: READ a: PRINT ISEMPTY(a); a")
+            .expect_clear()
+            .expect_prints(["FALSE  0"])
+            .expect_var("a", Value::Integer(0))
+            .check();
+    }
 
     #[test]
     fn test_read_simple() {
@@ -300,6 +496,25 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_read_index_survives_snapshot() {
+        let mut t = Tester::default();
+        t.run(r#"DATA 10, 20, 30: READ a"#).expect_var("a", Value::Integer(10)).check();
+        let snapshot = t.get_machine().snapshot();
+
+        t.run(r#"DATA 10, 20, 30: READ b"#)
+            .expect_var("a", Value::Integer(10))
+            .expect_var("b", Value::Integer(20))
+            .check();
+
+        t.get_machine().restore(snapshot).unwrap();
+        t.run(r#"DATA 10, 20, 30: READ c"#)
+            .expect_clear()
+            .expect_var("a", Value::Integer(10))
+            .expect_var("c", Value::Integer(20))
+            .check();
+    }
+
     #[test]
     fn test_read_errors() {
         check_stmt_compilation_err("1:1: READ expected vref1[, .., vrefN]", "READ");
@@ -0,0 +1,488 @@
+// EndBASIC
+// Copyright 2020 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Date and time functions for EndBASIC.
+
+use crate::exec::Clock;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::rc::Rc;
+use time::{OffsetDateTime, UtcOffset};
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Date and time functions
+Dates and times are represented as the number of seconds since the Unix epoch (midnight UTC of \
+January 1st, 1970), which can be obtained with NOW# and operated on with regular arithmetic.
+Unless otherwise specified, dates and times are displayed in the local time zone.";
+
+/// Formats the `date` according to the `format` string, which may contain the `%Y`, `%m`, `%d`,
+/// `%H`, `%M` and `%S` specifiers as well as `%%` to represent a literal percent sign.
+///
+/// Returns an error message if the format string is invalid.
+fn format_date(date: OffsetDateTime, format: &str) -> std::result::Result<String, String> {
+    let mut formatted = String::with_capacity(format.len() * 2);
+    let mut chars = format.chars();
+    while let Some(ch) = chars.next() {
+        if ch != '%' {
+            formatted.push(ch);
+            continue;
+        }
+
+        match chars.next() {
+            Some('Y') => formatted.push_str(&format!("{:04}", date.year())),
+            Some('m') => formatted.push_str(&format!("{:02}", u8::from(date.month()))),
+            Some('d') => formatted.push_str(&format!("{:02}", date.day())),
+            Some('H') => formatted.push_str(&format!("{:02}", date.hour())),
+            Some('M') => formatted.push_str(&format!("{:02}", date.minute())),
+            Some('S') => formatted.push_str(&format!("{:02}", date.second())),
+            Some('%') => formatted.push('%'),
+            Some(other) => return Err(format!("Unknown format specifier %{}", other)),
+            None => return Err("Incomplete format specifier at end of format".to_owned()),
+        }
+    }
+    Ok(formatted)
+}
+
+/// Converts the `epoch` number of seconds since the Unix epoch to a date in the given `offset`.
+fn epoch_to_date(epoch: f64, offset: UtcOffset, pos: LineCol) -> Result<OffsetDateTime> {
+    let secs = epoch.floor();
+    if secs.is_finite() && secs >= i64::MIN as f64 && secs < i64::MAX as f64 {
+        if let Ok(date) = OffsetDateTime::from_unix_timestamp(secs as i64) {
+            return Ok(date.to_offset(offset));
+        }
+    }
+    Err(Error::SyntaxError(pos, format!("Timestamp {} is out of range", epoch)))
+}
+
+/// The `DATE` function.
+pub struct DateFunction {
+    metadata: CallableMetadata,
+    clock: Rc<Clock>,
+}
+
+impl DateFunction {
+    /// Creates a new instance of the function.
+    pub fn new(clock: Rc<Clock>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DATE")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the current date.
+The date is returned in the local time zone in the YYYY-MM-DD format.",
+                )
+                .build(),
+            clock,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DateFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let date = format_date(self.clock.now(), "%Y-%m-%d").expect("Hardcoded format is valid");
+        scope.return_string(date)
+    }
+}
+
+/// The `DATEDIFF` function.
+pub struct DatediffFunction {
+    metadata: CallableMetadata,
+}
+
+impl DatediffFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DATEDIFF")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("a"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("b"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the number of seconds between two dates.
+The result is positive if b# is later than a# and negative otherwise.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DatediffFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let a = scope.pop_double();
+        let b = scope.pop_double();
+        scope.return_double(b - a)
+    }
+}
+
+/// The `DATEFMT` function.
+pub struct DatefmtFunction {
+    metadata: CallableMetadata,
+    clock: Rc<Clock>,
+}
+
+impl DatefmtFunction {
+    /// Creates a new instance of the function.
+    pub fn new(clock: Rc<Clock>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DATEFMT")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("epoch"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("fmt"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("epoch"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("fmt"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("utc"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Formats a date as a string.
+epoch# is the number of seconds since the Unix epoch, such as the value returned by NOW#.  \
+Fractional seconds are discarded.
+fmt$ is the format string, in which the following specifiers are replaced by the corresponding \
+component of the date: %Y for the year, %m for the month (01-12), %d for the day (01-31), %H \
+for the hour (00-23), %M for the minute (00-59), %S for the second (00-59), and %% for a \
+literal percent sign.  Any other specifier is an error.
+The date is formatted in the local time zone unless utc? is TRUE, in which case it is \
+formatted in UTC.",
+                )
+                .build(),
+            clock,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DatefmtFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let (epoch, epochpos) = scope.pop_double_with_pos();
+        let (format, formatpos) = scope.pop_string_with_pos();
+        let utc = if scope.nargs() > 0 { scope.pop_boolean() } else { false };
+        debug_assert_eq!(0, scope.nargs());
+
+        let offset = if utc { UtcOffset::UTC } else { self.clock.now().offset() };
+        let date = epoch_to_date(epoch, offset, epochpos)?;
+        match format_date(date, &format) {
+            Ok(formatted) => scope.return_string(formatted),
+            Err(message) => Err(Error::SyntaxError(formatpos, message)),
+        }
+    }
+}
+
+/// The `NOW` function.
+pub struct NowFunction {
+    metadata: CallableMetadata,
+    clock: Rc<Clock>,
+}
+
+impl NowFunction {
+    /// Creates a new instance of the function.
+    pub fn new(clock: Rc<Clock>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("NOW")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the current date and time.
+The result is the number of seconds since the Unix epoch, including fractional seconds.  Use \
+DATEFMT$ to convert it to a human-readable form.",
+                )
+                .build(),
+            clock,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for NowFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let now = self.clock.now();
+        scope.return_double(now.unix_timestamp() as f64 + f64::from(now.nanosecond()) / 1e9)
+    }
+}
+
+/// The `TIME` function.
+pub struct TimeFunction {
+    metadata: CallableMetadata,
+    clock: Rc<Clock>,
+}
+
+impl TimeFunction {
+    /// Creates a new instance of the function.
+    pub fn new(clock: Rc<Clock>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("TIME")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the current time.
+The time is returned in the local time zone in the HH:MM:SS format, using a 24-hour clock.",
+                )
+                .build(),
+            clock,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for TimeFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let time = format_date(self.clock.now(), "%H:%M:%S").expect("Hardcoded format is valid");
+        scope.return_string(time)
+    }
+}
+
+/// Adds all date and time functions to the `machine`, using `clock` to query the current time.
+pub fn add_all(machine: &mut Machine, clock: Rc<Clock>) {
+    machine.add_callable(DateFunction::new(clock.clone()));
+    machine.add_callable(DatediffFunction::new());
+    machine.add_callable(DatefmtFunction::new(clock.clone()));
+    machine.add_callable(NowFunction::new(clock.clone()));
+    machine.add_callable(TimeFunction::new(clock));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    /// Creates a tester with the date functions backed by a clock fixed at 2024-03-05 07:08:09.25
+    /// in a UTC+02:00 local time zone, which is 1709615289.25 seconds since the Unix epoch.
+    fn tester_with_fake_clock() -> Tester {
+        let now = OffsetDateTime::from_unix_timestamp_nanos(1_709_615_289_250_000_000)
+            .unwrap()
+            .to_offset(UtcOffset::from_hms(2, 0, 0).unwrap());
+        let clock = Clock::new(Box::from(move || now));
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), clock);
+        t
+    }
+
+    #[test]
+    fn test_date_and_time() {
+        let mut t = tester_with_fake_clock();
+        t.run("d = DATE: t = TIME")
+            .expect_var("d", "2024-03-05")
+            .expect_var("t", "07:08:09")
+            .check();
+    }
+
+    #[test]
+    fn test_date_and_time_errors() {
+        check_expr_compilation_error("1:10: DATE expected no arguments", "DATE(1)");
+        check_expr_compilation_error("1:10: TIME expected no arguments", "TIME(1)");
+    }
+
+    #[test]
+    fn test_now() {
+        let mut t = tester_with_fake_clock();
+        t.run("n = NOW").expect_var("n", 1709615289.25).check();
+    }
+
+    #[test]
+    fn test_now_errors() {
+        check_expr_compilation_error("1:10: NOW expected no arguments", "NOW(1)");
+    }
+
+    #[test]
+    fn test_datediff() {
+        check_expr_ok(90.5, "DATEDIFF(1000.0, 1090.5)");
+        check_expr_ok(-90.5, "DATEDIFF(1090.5, 1000.0)");
+        check_expr_ok(0.0, "DATEDIFF(5, 5)");
+    }
+
+    #[test]
+    fn test_datediff_errors() {
+        check_expr_compilation_error("1:10: DATEDIFF expected a#, b#", "DATEDIFF(1)");
+        check_expr_compilation_error("1:10: DATEDIFF expected a#, b#", "DATEDIFF(1, 2, 3)");
+        check_expr_compilation_error("1:22: STRING is not a number", r#"DATEDIFF(1, "2")"#);
+    }
+
+    #[test]
+    fn test_datefmt_each_specifier() {
+        let mut t = tester_with_fake_clock();
+        t.run(
+            r#"
+            y = DATEFMT(NOW, "%Y")
+            m = DATEFMT(NOW, "%m")
+            d = DATEFMT(NOW, "%d")
+            hh = DATEFMT(NOW, "%H")
+            mm = DATEFMT(NOW, "%M")
+            ss = DATEFMT(NOW, "%S")
+            p = DATEFMT(NOW, "100%%")
+            "#,
+        )
+        .expect_var("y", "2024")
+        .expect_var("m", "03")
+        .expect_var("d", "05")
+        .expect_var("hh", "07")
+        .expect_var("mm", "08")
+        .expect_var("ss", "09")
+        .expect_var("p", "100%")
+        .check();
+    }
+
+    #[test]
+    fn test_datefmt_local_and_utc() {
+        let mut t = tester_with_fake_clock();
+        t.run(
+            r#"
+            local1 = DATEFMT(0, "%Y-%m-%d %H:%M:%S")
+            local2 = DATEFMT(0, "%Y-%m-%d %H:%M:%S", FALSE)
+            utc = DATEFMT(0, "%Y-%m-%d %H:%M:%S", TRUE)
+            "#,
+        )
+        .expect_var("local1", "1970-01-01 02:00:00")
+        .expect_var("local2", "1970-01-01 02:00:00")
+        .expect_var("utc", "1970-01-01 00:00:00")
+        .check();
+    }
+
+    #[test]
+    fn test_datefmt_mixed() {
+        let mut t = tester_with_fake_clock();
+        t.run(r#"s = DATEFMT(NOW - 86400 * 5, "On %d/%m/%Y at %H:%M", TRUE)"#)
+            .expect_var("s", "On 29/02/2024 at 05:08")
+            .check();
+    }
+
+    #[test]
+    fn test_datefmt_discards_fractions() {
+        let mut t = tester_with_fake_clock();
+        t.run(
+            r#"
+            a = DATEFMT(59.9, "%M:%S", TRUE)
+            b = DATEFMT(-0.5, "%Y-%m-%d %H:%M:%S", TRUE)
+            "#,
+        )
+        .expect_var("a", "00:59")
+        .expect_var("b", "1969-12-31 23:59:59")
+        .check();
+    }
+
+    #[test]
+    fn test_datefmt_errors() {
+        check_expr_compilation_error(
+            "1:10: DATEFMT expected <epoch#, fmt$> | <epoch#, fmt$, utc?>",
+            "DATEFMT(1)",
+        );
+        check_expr_compilation_error(
+            "1:27: expected BOOLEAN but found INTEGER",
+            r#"DATEFMT(1, "%Y", 3)"#,
+        );
+        check_expr_error("1:21: Unknown format specifier %y", r#"DATEFMT(0, "%Y-%y")"#);
+        check_expr_error("1:21: Unknown format specifier %X", r#"DATEFMT(0, "%X", TRUE)"#);
+        check_expr_error(
+            "1:21: Incomplete format specifier at end of format",
+            r#"DATEFMT(0, "100%")"#,
+        );
+        check_expr_error(
+            "1:18: Timestamp 300000000000 is out of range",
+            r#"DATEFMT(300000000000.0, "%Y")"#,
+        );
+    }
+}
@@ -0,0 +1,449 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Self-test of the environment in which the interpreter runs.
+
+use crate::console::Console;
+use crate::exec::CATEGORY;
+use crate::gpio::Pins;
+use crate::storage::Storage;
+use async_trait::async_trait;
+use endbasic_core::exec::{Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use std::cell::RefCell;
+use std::io;
+use std::rc::Rc;
+
+/// Name of the file used to probe for write access to the current drive.
+const PROBE_FILE: &str = "DOCTOR.TMP";
+
+/// Outcome of a single environment check.
+#[derive(Debug, PartialEq)]
+pub enum CheckResult {
+    /// The check succeeded, with details on what was found.
+    Pass(String),
+
+    /// The check failed, with details on what went wrong and a hint on how to fix it.
+    Fail(String, String),
+}
+
+/// A single environment check run by `DOCTOR`.
+///
+/// Checks must not have side-effects other than the creation of temporary resources that they
+/// clean up before returning.
+#[async_trait(?Send)]
+pub trait Check {
+    /// Returns the name of the check as shown to the user.
+    fn name(&self) -> &'static str;
+
+    /// Runs the check.
+    async fn run(&self) -> CheckResult;
+}
+
+/// Checks the version of the interpreter.
+struct VersionCheck {}
+
+#[async_trait(?Send)]
+impl Check for VersionCheck {
+    fn name(&self) -> &'static str {
+        "Version"
+    }
+
+    async fn run(&self) -> CheckResult {
+        CheckResult::Pass(format!("EndBASIC {}", env!("CARGO_PKG_VERSION")))
+    }
+}
+
+/// Checks that the console can report its size.
+struct ConsoleSizeCheck {
+    console: Rc<RefCell<dyn Console>>,
+}
+
+#[async_trait(?Send)]
+impl Check for ConsoleSizeCheck {
+    fn name(&self) -> &'static str {
+        "Console size"
+    }
+
+    async fn run(&self) -> CheckResult {
+        match self.console.borrow().size_chars() {
+            Ok(size) => CheckResult::Pass(format!("{}x{} characters", size.x, size.y)),
+            Err(e) => CheckResult::Fail(
+                e.to_string(),
+                "Cursor positioning and the full-screen editor will not work".to_owned(),
+            ),
+        }
+    }
+}
+
+/// Checks that the console supports colors.
+struct ConsoleColorCheck {
+    console: Rc<RefCell<dyn Console>>,
+}
+
+#[async_trait(?Send)]
+impl Check for ConsoleColorCheck {
+    fn name(&self) -> &'static str {
+        "Console colors"
+    }
+
+    async fn run(&self) -> CheckResult {
+        let mut console = self.console.borrow_mut();
+        // Reapplying the current colors tells us if colors are supported without changing them.
+        let (fg, bg) = console.color();
+        match console.set_color(fg, bg) {
+            Ok(()) => CheckResult::Pass("Supported".to_owned()),
+            Err(e) => CheckResult::Fail(
+                e.to_string(),
+                "Use a terminal with ANSI color support to see COLOR changes".to_owned(),
+            ),
+        }
+    }
+}
+
+/// Checks that the console accepts interactive input.
+struct ConsoleInputCheck {
+    console: Rc<RefCell<dyn Console>>,
+}
+
+#[async_trait(?Send)]
+impl Check for ConsoleInputCheck {
+    fn name(&self) -> &'static str {
+        "Console input"
+    }
+
+    async fn run(&self) -> CheckResult {
+        if self.console.borrow().is_interactive() {
+            CheckResult::Pass("Interactive".to_owned())
+        } else {
+            CheckResult::Fail(
+                "Console is not interactive".to_owned(),
+                "Run EndBASIC from a terminal so that INPUT and INKEY can read keys".to_owned(),
+            )
+        }
+    }
+}
+
+/// Checks that the console supports graphics.
+struct GraphicsCheck {
+    console: Rc<RefCell<dyn Console>>,
+}
+
+#[async_trait(?Send)]
+impl Check for GraphicsCheck {
+    fn name(&self) -> &'static str {
+        "Graphics"
+    }
+
+    async fn run(&self) -> CheckResult {
+        match self.console.borrow().size_pixels() {
+            Ok(size) => CheckResult::Pass(format!("{}x{} pixels", size.width, size.height)),
+            Err(e) => CheckResult::Fail(
+                e.to_string(),
+                "Start EndBASIC with --console=sdl to use the GFX_* commands".to_owned(),
+            ),
+        }
+    }
+}
+
+/// Checks that files can be written to the current drive.
+struct StorageCheck {
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl StorageCheck {
+    /// Writes, reads back and deletes the probe file in the current directory.
+    async fn probe(&self) -> io::Result<()> {
+        let mut storage = self.storage.borrow_mut();
+        match storage.get(PROBE_FILE).await {
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("Probe file {} already exists", PROBE_FILE),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(e),
+        }
+
+        let content = b"EndBASIC DOCTOR probe";
+        storage.put(PROBE_FILE, content).await?;
+        let result = storage.get(PROBE_FILE).await;
+        storage.delete(PROBE_FILE).await?;
+        if result? != content {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Probe file content does not match what was written",
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Check for StorageCheck {
+    fn name(&self) -> &'static str {
+        "Storage"
+    }
+
+    async fn run(&self) -> CheckResult {
+        let cwd = self.storage.borrow().cwd();
+        match self.probe().await {
+            Ok(()) => CheckResult::Pass(format!("{} is writable", cwd)),
+            Err(e) => CheckResult::Fail(
+                format!("Cannot write to {}: {}", cwd, e),
+                "Use CD to switch to a writable drive or MOUNT one; see HELP \"FILE SYSTEM\""
+                    .to_owned(),
+            ),
+        }
+    }
+}
+
+/// Checks that the GPIO backend is available.
+struct GpioCheck {
+    pins: Rc<RefCell<dyn Pins>>,
+}
+
+#[async_trait(?Send)]
+impl Check for GpioCheck {
+    fn name(&self) -> &'static str {
+        "GPIO"
+    }
+
+    async fn run(&self) -> CheckResult {
+        match self.pins.borrow().probe() {
+            Ok(()) => CheckResult::Pass("Available".to_owned()),
+            Err(e) => CheckResult::Fail(
+                e.to_string(),
+                "GPIO_* commands only work on a Raspberry Pi build of EndBASIC".to_owned(),
+            ),
+        }
+    }
+}
+
+/// The `DOCTOR` command.
+pub struct DoctorCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    checks: Vec<Rc<dyn Check>>,
+}
+
+impl DoctorCommand {
+    /// Creates a new `DOCTOR` command that runs `checks` and reports their results to `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, checks: Vec<Rc<dyn Check>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DOCTOR")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Diagnoses problems with the environment of the interpreter.
+Runs a series of checks on the capabilities of the console, the ability to write files to the \
+current drive, the availability of optional features, and the reachability of the cloud \
+service, and prints whether each check passed or failed along with a hint to fix failures.
+The storage check creates and deletes a DOCTOR.TMP file in the current directory.  None of the \
+checks require being logged in.",
+                )
+                .build(),
+            console,
+            checks,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DoctorCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let mut failed = 0;
+        for check in &self.checks {
+            let result = check.run().await;
+
+            let mut console = self.console.borrow_mut();
+            match result {
+                CheckResult::Pass(details) => {
+                    console
+                        .print(&format!("PASS  {}: {}", check.name(), details))
+                        .map_err(|e| scope.io_error(e))?;
+                }
+                CheckResult::Fail(details, hint) => {
+                    failed += 1;
+                    console
+                        .print(&format!("FAIL  {}: {}", check.name(), details))
+                        .map_err(|e| scope.io_error(e))?;
+                    console
+                        .print(&format!("      Hint: {}", hint))
+                        .map_err(|e| scope.io_error(e))?;
+                }
+            }
+        }
+
+        let mut console = self.console.borrow_mut();
+        console
+            .print(&format!("{} checks passed, {} failed", self.checks.len() - failed, failed))
+            .map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// Adds the `DOCTOR` command to the `machine`.
+///
+/// The command checks the `console`, the `storage` and the GPIO `pins`, and then runs any
+/// `extra_checks` provided by other subsystems.  The storage and GPIO checks are skipped if the
+/// corresponding subsystems are not available to programs.
+pub fn add_all(
+    machine: &mut Machine,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Option<Rc<RefCell<Storage>>>,
+    pins: Option<Rc<RefCell<dyn Pins>>>,
+    extra_checks: Vec<Rc<dyn Check>>,
+) {
+    let mut checks: Vec<Rc<dyn Check>> = vec![
+        Rc::from(VersionCheck {}),
+        Rc::from(ConsoleSizeCheck { console: console.clone() }),
+        Rc::from(ConsoleColorCheck { console: console.clone() }),
+        Rc::from(ConsoleInputCheck { console: console.clone() }),
+        Rc::from(GraphicsCheck { console: console.clone() }),
+    ];
+    if let Some(storage) = storage {
+        checks.push(Rc::from(StorageCheck { storage }));
+    }
+    if let Some(pins) = pins {
+        checks.push(Rc::from(GpioCheck { pins }));
+    }
+    checks.extend(extra_checks);
+    machine.add_callable(DoctorCommand::new(console, checks));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::SizeInPixels;
+    use crate::storage::{Drive, DriveFactory, DriveFiles, InMemoryDrive};
+    use crate::testutils::*;
+    use futures_lite::future::block_on;
+
+    /// A drive that can be read from but that fails all writes.
+    #[derive(Default)]
+    struct BrokenDrive(InMemoryDrive);
+
+    #[async_trait(?Send)]
+    impl Drive for BrokenDrive {
+        async fn delete(&mut self, _name: &str) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Drive is read-only"))
+        }
+
+        async fn enumerate(&self) -> io::Result<DriveFiles> {
+            self.0.enumerate().await
+        }
+
+        async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+            self.0.get(name).await
+        }
+
+        async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Drive is read-only"))
+        }
+    }
+
+    /// Factory for `BrokenDrive`s.
+    struct BrokenDriveFactory {}
+
+    #[async_trait(?Send)]
+    impl DriveFactory for BrokenDriveFactory {
+        async fn create(&self, _target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
+            Ok(Box::from(BrokenDrive::default()))
+        }
+    }
+
+    /// Returns the expected output line for the version check.
+    fn version_line() -> String {
+        format!("PASS  Version: EndBASIC {}", env!("CARGO_PKG_VERSION"))
+    }
+
+    #[test]
+    fn test_doctor_all_pass_but_gpio() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_interactive(true);
+        t.get_console().borrow_mut().set_size_pixels(SizeInPixels::new(800, 600));
+        t.run("DOCTOR")
+            .expect_output([
+                CapturedOut::Print(version_line()),
+                CapturedOut::Print("PASS  Console size: 65535x65535 characters".to_owned()),
+                CapturedOut::SetColor(None, None),
+                CapturedOut::Print("PASS  Console colors: Supported".to_owned()),
+                CapturedOut::Print("PASS  Console input: Interactive".to_owned()),
+                CapturedOut::Print("PASS  Graphics: 800x600 pixels".to_owned()),
+                CapturedOut::Print("PASS  Storage: MEMORY:/ is writable".to_owned()),
+                CapturedOut::Print("FAIL  GPIO: GPIO backend not compiled in".to_owned()),
+                CapturedOut::Print(
+                    "      Hint: GPIO_* commands only work on a Raspberry Pi build of EndBASIC"
+                        .to_owned(),
+                ),
+                CapturedOut::Print("6 checks passed, 1 failed".to_owned()),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_doctor_failures() {
+        let mut t = Tester::default();
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            storage.register_scheme("broken", Box::from(BrokenDriveFactory {}));
+            block_on(storage.mount("ro", "broken://", false)).unwrap();
+            storage.cd("ro:").unwrap();
+        }
+        t.run("DOCTOR")
+            .expect_prints([
+                version_line(),
+                "PASS  Console size: 65535x65535 characters".to_owned(),
+            ])
+            .expect_output([CapturedOut::SetColor(None, None)])
+            .expect_prints([
+                "PASS  Console colors: Supported",
+                "FAIL  Console input: Console is not interactive",
+                "      Hint: Run EndBASIC from a terminal so that INPUT and INKEY can read keys",
+                "FAIL  Graphics: Graphical console size not yet set",
+                "      Hint: Start EndBASIC with --console=sdl to use the GFX_* commands",
+                "FAIL  Storage: Cannot write to RO:/: Drive is read-only",
+                "      Hint: Use CD to switch to a writable drive or MOUNT one; see HELP \"FILE SYSTEM\"",
+                "FAIL  GPIO: GPIO backend not compiled in",
+                "      Hint: GPIO_* commands only work on a Raspberry Pi build of EndBASIC",
+                "3 checks passed, 4 failed",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_doctor_keeps_existing_probe_file() {
+        let mut t = Tester::default().write_file("DOCTOR.TMP", "user data");
+        let output = flatten_output(t.run("DOCTOR").take_captured_out());
+        assert!(output.contains(
+            "FAIL  Storage: Cannot write to MEMORY:/: Probe file DOCTOR.TMP already exists"
+        ));
+        t.run("").expect_file("MEMORY:/DOCTOR.TMP", "user data").check();
+    }
+
+    #[test]
+    fn test_doctor_errors() {
+        check_stmt_compilation_err("1:1: DOCTOR expected no arguments", "DOCTOR 1");
+    }
+}
@@ -0,0 +1,322 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Environment variable manipulation for EndBASIC.
+
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::rc::Rc;
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Environment
+Environment variables are name/value pairs provided by the system that runs the interpreter, \
+and they are useful to configure programs without having to modify them.  Names are case \
+sensitive on most systems.
+Depending on where the interpreter runs, these variables may come from the process environment \
+or from a separate collection managed by the interpreter, such as the parameters of the URL \
+that loaded the web interface.";
+
+/// Abstract interface to access environment variables.
+pub trait Environment {
+    /// Returns the value of the variable `name`, or `None` if it is not set.
+    fn get(&self, name: &str) -> Option<String>;
+
+    /// Sets the variable `name` to `value`.
+    ///
+    /// Callers are responsible for validating that `name` and `value` are valid.
+    fn set(&mut self, name: &str, value: &str) -> io::Result<()>;
+}
+
+/// Environment backed by the variables of the interpreter's process.
+///
+/// Changes to the variables are visible to any child processes spawned afterwards.
+#[derive(Default)]
+pub struct SystemEnvironment {}
+
+impl Environment for SystemEnvironment {
+    fn get(&self, name: &str) -> Option<String> {
+        std::env::var_os(name).map(|v| v.to_string_lossy().into_owned())
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> io::Result<()> {
+        std::env::set_var(name, value);
+        Ok(())
+    }
+}
+
+/// Environment backed by an in-memory collection of variables.
+#[derive(Default)]
+pub struct MemoryEnvironment {
+    vars: HashMap<String, String>,
+}
+
+impl MemoryEnvironment {
+    /// Creates a new environment with an initial set of `vars`.
+    pub fn new(vars: HashMap<String, String>) -> Self {
+        Self { vars }
+    }
+}
+
+impl Environment for MemoryEnvironment {
+    fn get(&self, name: &str) -> Option<String> {
+        self.vars.get(name).cloned()
+    }
+
+    fn set(&mut self, name: &str, value: &str) -> io::Result<()> {
+        self.vars.insert(name.to_owned(), value.to_owned());
+        Ok(())
+    }
+}
+
+/// Checks that `name`, which was specified at `pos`, is a valid name for a variable.
+///
+/// These are the restrictions imposed by the most restrictive system we may run on, and we apply
+/// them to all environments so that programs behave the same everywhere.
+fn validate_name(name: &str, pos: LineCol) -> Result<()> {
+    if name.is_empty() || name.contains('=') || name.contains('\0') {
+        return Err(Error::SyntaxError(
+            pos,
+            format!("Invalid environment variable name \"{}\"", name),
+        ));
+    }
+    Ok(())
+}
+
+/// The `GETENV` function.
+pub struct GetenvFunction {
+    metadata: CallableMetadata,
+    env: Rc<RefCell<dyn Environment>>,
+}
+
+impl GetenvFunction {
+    /// Creates a new instance of the function.
+    pub fn new(env: Rc<RefCell<dyn Environment>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GETENV")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the value of an environment variable.
+Returns an empty string if the variable name$ is not set, so there is no way to distinguish an \
+unset variable from one set to an empty value.",
+                )
+                .build(),
+            env,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GetenvFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        validate_name(&name, pos)?;
+        let value = self.env.borrow().get(&name).unwrap_or_default();
+        scope.return_string(value)
+    }
+}
+
+/// The `SETENV` command.
+pub struct SetenvCommand {
+    metadata: CallableMetadata,
+    env: Rc<RefCell<dyn Environment>>,
+}
+
+impl SetenvCommand {
+    /// Creates a new instance of the command.
+    pub fn new(env: Rc<RefCell<dyn Environment>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SETENV")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("name"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("value"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets the value of an environment variable.
+name$ cannot be empty and cannot contain the = character.  When the variables come from the \
+process environment, the new value is visible to any programs started afterwards.",
+                )
+                .build(),
+            env,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SetenvCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (name, name_pos) = scope.pop_string_with_pos();
+        let (value, value_pos) = scope.pop_string_with_pos();
+
+        validate_name(&name, name_pos)?;
+        if value.contains('\0') {
+            return Err(Error::SyntaxError(
+                value_pos,
+                "Environment variable values cannot contain NUL characters".to_owned(),
+            ));
+        }
+        self.env.borrow_mut().set(&name, &value).map_err(|e| scope.io_error(e))
+    }
+}
+
+/// Adds all environment manipulation symbols to the `machine`, backed by `env`.
+pub fn add_all(machine: &mut Machine, env: Rc<RefCell<dyn Environment>>) {
+    machine.add_callable(GetenvFunction::new(env.clone()));
+    machine.add_callable(SetenvCommand::new(env));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    /// Environment that rejects all modifications.
+    struct ReadOnlyEnvironment {}
+
+    impl Environment for ReadOnlyEnvironment {
+        fn get(&self, _name: &str) -> Option<String> {
+            None
+        }
+
+        fn set(&mut self, _name: &str, _value: &str) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Read-only environment"))
+        }
+    }
+
+    /// Creates a tester backed by a fake environment with the initial `vars`.
+    fn tester_with_env(vars: &[(&str, &str)]) -> (Tester, Rc<RefCell<MemoryEnvironment>>) {
+        let vars = vars.iter().map(|(k, v)| ((*k).to_owned(), (*v).to_owned())).collect();
+        let env = Rc::from(RefCell::from(MemoryEnvironment::new(vars)));
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), env.clone());
+        (t, env)
+    }
+
+    #[test]
+    fn test_getenv_ok() {
+        let (mut t, _env) = tester_with_env(&[("HOME", "/home/me"), ("EMPTY", "")]);
+        t.run(r#"a = GETENV("HOME"): b = GETENV("EMPTY"): c = GETENV("home")"#)
+            .expect_var("a", "/home/me")
+            .expect_var("b", "")
+            .expect_var("c", "")
+            .check();
+    }
+
+    #[test]
+    fn test_getenv_missing() {
+        let (mut t, _env) = tester_with_env(&[]);
+        t.run(r#"a = GETENV("MISSING")"#).expect_var("a", "").check();
+    }
+
+    #[test]
+    fn test_getenv_errors() {
+        let (mut t, _env) = tester_with_env(&[]);
+        t.run(r#"a = GETENV("")"#)
+            .expect_err("1:12: Invalid environment variable name \"\"")
+            .check();
+        t.run(r#"a = GETENV("A=B")"#)
+            .expect_err("1:12: Invalid environment variable name \"A=B\"")
+            .check();
+
+        check_expr_compilation_error("1:10: GETENV expected name$", r#"GETENV()"#);
+        check_expr_compilation_error("1:17: expected STRING but found INTEGER", r#"GETENV(3)"#);
+    }
+
+    #[test]
+    fn test_setenv_ok() {
+        let (mut t, env) = tester_with_env(&[("KEEP", "1"), ("REPLACE", "old")]);
+        t.run(r#"SETENV "REPLACE", "new": SETENV "NEW", "value": a = GETENV("NEW")"#)
+            .expect_var("a", "value")
+            .check();
+        assert_eq!(Some("1".to_owned()), env.borrow().get("KEEP"));
+        assert_eq!(Some("new".to_owned()), env.borrow().get("REPLACE"));
+        assert_eq!(Some("value".to_owned()), env.borrow().get("NEW"));
+    }
+
+    #[test]
+    fn test_setenv_errors() {
+        let (mut t, env) = tester_with_env(&[]);
+        crate::strings::add_all(t.get_machine());
+        t.run(r#"SETENV "", "x""#)
+            .expect_err("1:8: Invalid environment variable name \"\"")
+            .check();
+        t.run(r#"SETENV "A=B", "x""#)
+            .expect_err("1:8: Invalid environment variable name \"A=B\"")
+            .check();
+        t.run(r#"SETENV "A", "x" + CHR(0)"#)
+            .expect_err("1:17: Environment variable values cannot contain NUL characters")
+            .check();
+        assert_eq!(None, env.borrow().get("A"));
+
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), Rc::from(RefCell::from(ReadOnlyEnvironment {})));
+        t.run(r#"SETENV "A", "x""#).expect_err("1:1: Read-only environment").check();
+
+        check_stmt_compilation_err("1:1: SETENV expected name$, value$", r#"SETENV "A""#);
+        check_stmt_compilation_err("1:13: expected STRING but found INTEGER", r#"SETENV "A", 3"#);
+    }
+
+    #[test]
+    fn test_system_environment() {
+        let mut env = SystemEnvironment::default();
+        let name = "ENDBASIC_TEST_SYSTEM_ENVIRONMENT";
+        assert_eq!(None, env.get(name));
+        env.set(name, "some value").unwrap();
+        assert_eq!(Some("some value".to_owned()), env.get(name));
+        assert_eq!("some value", std::env::var(name).unwrap());
+    }
+}
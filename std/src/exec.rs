@@ -16,20 +16,116 @@
 //! Commands that manipulate the machine's state or the program's execution.
 
 use async_trait::async_trait;
-use endbasic_core::ast::ExprType;
+use endbasic_core::ast::{ExprType, Value, VarRef};
 use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
-use endbasic_core::exec::{Error, Machine, Result, Scope};
-use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope, Signal, StopReason};
+use endbasic_core::syms::{
+    Callable, CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey, Symbols,
+};
 use endbasic_core::LineCol;
-use futures_lite::future::{BoxedLocal, FutureExt};
+use futures_lite::future::{self, BoxedLocal, Future, FutureExt};
 use std::borrow::Cow;
+use std::cell::{Cell, RefCell};
+use std::convert::TryFrom;
 use std::rc::Rc;
 use std::thread;
 use std::time::Duration;
+use time::{OffsetDateTime, UtcOffset};
 
 /// Category description for all symbols provided by this module.
 pub(crate) const CATEGORY: &str = "Interpreter";
 
+/// Maximum number of nested `EVAL` and `EXECUTE` invocations.
+///
+/// Keep in sync with the descriptions of these callables.
+pub const MAX_EXECUTE_DEPTH: usize = 16;
+
+/// The `ARG` function.
+pub struct ArgFunction {
+    metadata: CallableMetadata,
+}
+
+impl ArgFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ARG")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Integer },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the n%th argument passed to the program.
+Argument 0 is the name of the program and the rest are the arguments that followed it when \
+the program was started, either from the command line or via RUN.  n% must be in the range 0 to \
+ARGC% - 1.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ArgFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, npos) = scope.pop_integer_with_pos();
+
+        let args = machine.args();
+        match usize::try_from(n).ok().and_then(|n| args.get(n)) {
+            Some(arg) => scope.return_string(arg.clone()),
+            None => Err(Error::SyntaxError(
+                npos,
+                format!("Argument index {} out of range; ARGC% is {}", n, args.len()),
+            )),
+        }
+    }
+}
+
+/// The `ARGC` function.
+pub struct ArgcFunction {
+    metadata: CallableMetadata,
+}
+
+impl ArgcFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ARGC")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the number of arguments passed to the program.
+The count includes the name of the program, which is always argument 0 when the program was \
+started from the command line or via RUN.  Use ARG$() to query the individual arguments.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ArgcFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_integer(machine.args().len() as i32)
+    }
+}
+
 /// The `CLEAR` command.
 pub struct ClearCommand {
     metadata: CallableMetadata,
@@ -43,13 +139,621 @@ impl ClearCommand {
                 .with_syntax(&[(&[], None)])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Restores initial machine state but keeps the stored program.
-This command resets the machine to a semi-pristine state by clearing all user-defined variables \
-and restoring the state of shared resources.  These resources include: the console, whose color \
-and video syncing bit are reset; and the GPIO pins, which are set to their default state.
-The stored program is kept in memory.  To clear that too, use NEW (but don't forget to first \
-SAVE your program!).
-This command is for interactive use only.",
+                    "Restores initial machine state but keeps the stored program.
+This command resets the machine to a semi-pristine state by clearing all user-defined variables \
+and restoring the state of shared resources.  These resources include: the console, whose color \
+and video syncing bit are reset; and the GPIO pins, which are set to their default state.
+The stored program is kept in memory.  To clear that too, use NEW (but don't forget to first \
+SAVE your program!).
+This command is for interactive use only.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ClearCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        machine.clear();
+        Ok(())
+    }
+}
+
+/// The `ERL` function.
+pub struct ErlFunction {
+    metadata: CallableMetadata,
+}
+
+impl ErlFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ERL")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the line number of the last captured error.
+When used in combination of ON ERROR to set an error handler, this function returns the number \
+of the line in the program where the last captured error happened.  If this is called before any \
+error is captured, returns 0.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ErlFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let line = i32::try_from(machine.last_error_line()).unwrap_or(i32::MAX);
+        scope.return_integer(line)
+    }
+}
+
+/// The `ERR` function.
+pub struct ErrFunction {
+    metadata: CallableMetadata,
+}
+
+impl ErrFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ERR")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the code of the last captured error.
+When used in combination of ON ERROR to set an error handler, this function returns a number \
+that identifies the kind of the last captured error.  If this is called before any error is \
+captured, returns 0.
+The codes of common errors are: 2 for syntax errors, 4 for reading past the end of the DATA \
+values, 5 for invalid arguments and other errors, 6 for numeric overflows, 7 for allocations \
+that exceed the memory limit, 9 for array subscripts out of range, 11 for divisions by zero, 13 \
+for type mismatches, 51 for internal errors, 53 for files that do not exist, 57 for other I/O \
+errors, 58 for files that already exist, and 70 for operations that are not permitted.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ErrFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_integer(machine.last_error_code())
+    }
+}
+
+/// The `ERRMSG` function.
+pub struct ErrmsgFunction {
+    metadata: CallableMetadata,
+}
+
+impl ErrmsgFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ERRMSG")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the last captured error message.
+When used in combination of ON ERROR to set an error handler, this function returns the string \
+representation of the last captured error.  If this is called before any error is captured, \
+returns the empty string.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ErrmsgFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        match machine.last_error() {
+            Some(message) => scope.return_string(message),
+            None => scope.return_string("".to_owned()),
+        }
+    }
+}
+
+/// Records entry into a nested `EVAL` or `EXECUTE` invocation, failing if doing so would exceed
+/// `MAX_EXECUTE_DEPTH`.  Callers must decrement `depth` once the nested invocation completes.
+fn enter_nested(depth: &RefCell<usize>, pos: LineCol) -> Result<()> {
+    let mut depth = depth.borrow_mut();
+    if *depth == MAX_EXECUTE_DEPTH {
+        return Err(Error::EvalError(
+            pos,
+            format!("Exceeded maximum EVAL/EXECUTE nesting depth of {}", MAX_EXECUTE_DEPTH),
+        ));
+    }
+    *depth += 1;
+    Ok(())
+}
+
+/// Converts an error from nested code executed by `EVAL` or `EXECUTE` into an error that can be
+/// caught by `ON ERROR` in the caller.
+///
+/// Compilation errors are not catchable, so they are reported as syntax errors at `pos`, which is
+/// the position of the code argument.  The original message keeps the position within the code.
+fn nested_error(e: Error, pos: LineCol) -> Error {
+    match e {
+        Error::CompilerError(e) => Error::SyntaxError(pos, e.to_string()),
+        e => e,
+    }
+}
+
+/// The `EVAL` function.
+pub struct EvalFunction {
+    metadata: CallableMetadata,
+    depth: Rc<RefCell<usize>>,
+}
+
+impl EvalFunction {
+    /// Creates a new instance of the function.
+    ///
+    /// `depth` tracks the nesting of `EVAL` and `EXECUTE` invocations and must be shared with
+    /// `ExecuteCommand`.
+    pub fn new(depth: Rc<RefCell<usize>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EVAL")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("expr"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Evaluates a numeric expression given as a string.
+Compiles and evaluates expr$ as a single expression in the context of the running program, \
+which gives it access to all variables visible at the call site, and returns its value.  The \
+expression must be numeric.
+Errors in expr$ can be caught with ON ERROR and their positions are relative to expr$.
+EVAL and EXECUTE invocations can be nested up to 16 levels deep.",
+                )
+                .build(),
+            depth,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for EvalFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (expr, pos) = scope.pop_string_with_pos();
+
+        enter_nested(&self.depth, pos)?;
+        let result = machine.eval(&mut expr.as_bytes()).await;
+        *self.depth.borrow_mut() -= 1;
+
+        match result.map_err(|e| nested_error(e, pos))? {
+            Value::Double(d) => scope.return_double(d),
+            Value::Integer(i) => scope.return_double(i as f64),
+            Value::Empty(_) => {
+                Err(Error::EvalError(pos, "Cannot operate on an EMPTY value".to_owned()))
+            }
+            v => Err(Error::EvalError(pos, format!("{} is not a number", v.as_exprtype()))),
+        }
+    }
+}
+
+/// The `EXECUTE` command.
+pub struct ExecuteCommand {
+    metadata: CallableMetadata,
+    depth: Rc<RefCell<usize>>,
+}
+
+impl ExecuteCommand {
+    /// Creates a new instance of the command.
+    ///
+    /// `depth` tracks the nesting of `EVAL` and `EXECUTE` invocations and must be shared with
+    /// `EvalFunction`.
+    pub fn new(depth: Rc<RefCell<usize>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EXECUTE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("code"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Executes statements given as a string.
+Compiles and runs code$ as one or more statements in the context of the running program, \
+sharing its variables: any variables that code$ defines or modifies remain visible after EXECUTE \
+returns.  code$ can invoke built-in commands and functions, but it cannot call the FUNCTIONs and \
+SUBs defined by the program.  END within code$ only stops the execution of code$.
+Errors in code$ can be caught with ON ERROR and their positions are relative to code$.
+EVAL and EXECUTE invocations can be nested up to 16 levels deep.",
+                )
+                .build(),
+            depth,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ExecuteCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (code, pos) = scope.pop_string_with_pos();
+
+        enter_nested(&self.depth, pos)?;
+        let result = machine.exec(&mut code.as_bytes()).await;
+        *self.depth.borrow_mut() -= 1;
+
+        match result.map_err(|e| nested_error(e, pos))? {
+            StopReason::Eof | StopReason::Exited(_) => Ok(()),
+            StopReason::Break => {
+                // The nested execution consumed the break signal, so deliver it again to stop
+                // the caller as well.
+                machine
+                    .get_signals_tx()
+                    .try_send(Signal::Break)
+                    .map_err(|e| scope.internal_error(e.to_string()))
+            }
+        }
+    }
+}
+
+/// Kinds of symbols that can be queried by the symbol introspection functions.
+enum SymbolKind<'a> {
+    /// An array.
+    Array,
+
+    /// A built-in command or function.
+    Builtin(&'a CallableMetadata),
+
+    /// A `FUNCTION` or `SUB` defined by the program being executed.
+    User(&'a CallableMetadata),
+
+    /// A variable.
+    Variable,
+}
+
+/// Parses `name` as a reference to a symbol with an optional type annotation.
+fn parse_symbol_name(name: &str, pos: LineCol) -> Result<VarRef> {
+    let (base, ref_type) = match name.chars().last() {
+        Some('?') => (&name[..name.len() - 1], Some(ExprType::Boolean)),
+        Some('#') => (&name[..name.len() - 1], Some(ExprType::Double)),
+        Some('%') => (&name[..name.len() - 1], Some(ExprType::Integer)),
+        Some('$') => (&name[..name.len() - 1], Some(ExprType::Text)),
+        _ => (name, None),
+    };
+
+    let mut chars = base.chars();
+    let valid = match chars.next() {
+        Some(ch) if ch.is_alphabetic() || ch == '_' => {
+            chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+        }
+        _ => false,
+    };
+    if !valid {
+        return Err(Error::SyntaxError(pos, format!("Invalid symbol name '{}'", name)));
+    }
+
+    Ok(VarRef::new(base, ref_type))
+}
+
+/// Looks up the symbol referenced by `vref` following the same rules as the compiler.
+///
+/// References with a type annotation that does not match the type of the symbol are treated as
+/// undefined.
+fn lookup_symbol<'a>(machine: &'a Machine, vref: &VarRef) -> Option<SymbolKind<'a>> {
+    match machine.get_symbols().get(vref) {
+        Ok(Some(Symbol::Array(_))) => Some(SymbolKind::Array),
+        Ok(Some(Symbol::Callable(callable))) => Some(SymbolKind::Builtin(callable.metadata())),
+        Ok(Some(Symbol::Variable(_))) => Some(SymbolKind::Variable),
+        Ok(None) => {
+            let metadata = machine.get_user_callable(&SymbolKey::from(vref.name()))?;
+            if vref.accepts_callable(metadata.return_type()) {
+                Some(SymbolKind::User(metadata))
+            } else {
+                None
+            }
+        }
+        Err(_) => None,
+    }
+}
+
+/// The `ISDEFINED` function.
+pub struct IsDefinedFunction {
+    metadata: CallableMetadata,
+}
+
+impl IsDefinedFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ISDEFINED")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Checks whether a symbol is defined.
+Returns true if name$ refers to a variable, an array, a built-in command or function, or a \
+FUNCTION or SUB defined by the running program.  Symbol names are case-insensitive and may \
+carry a type annotation, in which case the symbol must also be of that type.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for IsDefinedFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let vref = parse_symbol_name(&name, pos)?;
+        scope.return_boolean(lookup_symbol(machine, &vref).is_some())
+    }
+}
+
+/// Type of the sleep function used by the `SLEEP` command to actually suspend execution.
+pub type SleepFn = Box<dyn Fn(Duration, LineCol) -> BoxedLocal<Result<()>>>;
+
+/// An implementation of a `SleepFn` that waits for a helper thread to sleep on our behalf so that
+/// the caller can stop waiting at any time (e.g. when it receives a break signal).
+pub fn system_sleep(d: Duration, _pos: LineCol) -> BoxedLocal<Result<()>> {
+    if d.is_zero() {
+        return async move {
+            future::yield_now().await;
+            Ok(())
+        }
+        .boxed_local();
+    }
+
+    let (done_tx, done_rx) = async_channel::bounded(1);
+    thread::spawn(move || {
+        thread::sleep(d);
+        // The receiver is gone if the sleep was interrupted, in which case there is nobody to
+        // notify anymore.
+        let _ = done_tx.try_send(());
+    });
+    async move {
+        let _ = done_rx.recv().await;
+        Ok(())
+    }
+    .boxed_local()
+}
+
+/// Converts the `n` seconds given to `what` at `pos` to a `Duration` with millisecond precision.
+pub(crate) fn seconds_to_duration(n: f64, what: &str, pos: LineCol) -> Result<Duration> {
+    if n < 0.0 {
+        return Err(Error::SyntaxError(pos, format!("{} must be positive", what)));
+    }
+    let ms = (n * 1000.0).round();
+    if !ms.is_finite() || ms > u64::MAX as f64 {
+        return Err(Error::SyntaxError(pos, format!("{} {} is too large", what, n)));
+    }
+    Ok(Duration::from_millis(ms as u64))
+}
+
+/// Suspends execution for `d` with `sleep_fn`, which was requested at `pos`, unless the `machine`
+/// receives a break signal first, in which case the program is stopped right away.
+pub(crate) async fn interruptible_sleep(
+    sleep_fn: &SleepFn,
+    d: Duration,
+    pos: LineCol,
+    machine: &mut Machine,
+) -> Result<()> {
+    let signals_rx = machine.get_signals_rx();
+    let interrupted = async {
+        match signals_rx.recv().await {
+            Ok(Signal::Break) => Ok(true),
+            Err(_) => future::pending().await,
+        }
+    };
+    let sleep = async { sleep_fn(d, pos).await.map(|()| false) };
+
+    if future::or(sleep, interrupted).await? {
+        // We consumed the break signal to interrupt the sleep, so the machine won't see it: stop
+        // the program ourselves.
+        machine.request_stop(StopReason::Break);
+    }
+    Ok(())
+}
+
+/// Runs `work` until it completes or until the user requests a break via the `machine`'s signals.
+///
+/// Returns `None` if the work was interrupted, in which case the machine has been asked to stop.
+pub(crate) async fn interruptible<T, F: Future<Output = T>>(
+    machine: &mut Machine,
+    work: F,
+) -> Option<T> {
+    let signals_rx = machine.get_signals_rx();
+    let interrupted = async {
+        match signals_rx.recv().await {
+            Ok(Signal::Break) => None,
+            Err(_) => future::pending().await,
+        }
+    };
+    let result = future::or(async { Some(work.await) }, interrupted).await;
+    if result.is_none() {
+        // We consumed the break signal to interrupt the wait, so the machine won't see it: stop
+        // the program ourselves.
+        machine.request_stop(StopReason::Break);
+    }
+    result
+}
+
+/// The `SLEEP` command.
+pub struct SleepCommand {
+    metadata: CallableMetadata,
+    sleep_fn: SleepFn,
+}
+
+impl SleepCommand {
+    /// Creates a new instance of the command.
+    pub fn new(sleep_fn: SleepFn) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SLEEP")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("seconds"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Suspends program execution.
+Pauses program execution for the given number of seconds, which can be specified either as an \
+integer or as a floating point number for finer precision down to the millisecond.  A zero delay \
+gives other tasks a chance to run without waiting.
+Pressing CTRL+C while the program sleeps stops it immediately instead of waiting for the delay \
+to elapse.",
+                )
+                .build(),
+            sleep_fn,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SleepCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, pos) = scope.pop_double_with_pos();
+        let d = seconds_to_duration(n, "Sleep time", pos)?;
+        interruptible_sleep(&self.sleep_fn, d, pos, machine).await
+    }
+}
+
+/// The `SYMSYNTAX` function.
+pub struct SymsyntaxFunction {
+    metadata: CallableMetadata,
+}
+
+impl SymsyntaxFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SYMSYNTAX")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the syntax of a command or function.
+If name$ refers to a built-in command or function, or to a FUNCTION or SUB defined by the \
+running program, returns the same syntax summary that HELP displays for it.  Returns the empty \
+string for any other symbol, including undefined ones.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SymsyntaxFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let vref = parse_symbol_name(&name, pos)?;
+        let usage = match lookup_symbol(machine, &vref) {
+            Some(SymbolKind::Builtin(metadata)) | Some(SymbolKind::User(metadata)) => {
+                metadata.usage()
+            }
+            Some(SymbolKind::Array) | Some(SymbolKind::Variable) | None => "".to_owned(),
+        };
+        scope.return_string(usage)
+    }
+}
+
+/// The `SYMTYPE` function.
+pub struct SymtypeFunction {
+    metadata: CallableMetadata,
+}
+
+impl SymtypeFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SYMTYPE")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the kind of a symbol.
+The returned value is one of VARIABLE, ARRAY, FUNCTION, SUB, or COMMAND, or the empty string if \
+name$ is not defined.  FUNCTION is returned both for built-in functions and for functions \
+defined by the running program, while SUB is only returned for the latter.",
                 )
                 .build(),
         })
@@ -57,86 +761,164 @@ This command is for interactive use only.",
 }
 
 #[async_trait(?Send)]
-impl Callable for ClearCommand {
+impl Callable for SymtypeFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
-        machine.clear();
-        Ok(())
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let vref = parse_symbol_name(&name, pos)?;
+        let kind = match lookup_symbol(machine, &vref) {
+            Some(SymbolKind::Array) => "ARRAY",
+            Some(SymbolKind::Builtin(metadata)) if metadata.is_function() => "FUNCTION",
+            Some(SymbolKind::Builtin(_)) => "COMMAND",
+            Some(SymbolKind::User(metadata)) if metadata.is_function() => "FUNCTION",
+            Some(SymbolKind::User(_)) => "SUB",
+            Some(SymbolKind::Variable) => "VARIABLE",
+            None => "",
+        };
+        scope.return_string(kind.to_owned())
     }
 }
 
-/// The `ERRMSG` function.
-pub struct ErrmsgFunction {
+/// Type of the function used by the timing callables to query the current time.
+///
+/// The offset of the returned time is the local time zone used by the date callables.
+pub type NowFn = Box<dyn Fn() -> OffsetDateTime>;
+
+/// An implementation of a `NowFn` that queries the system clock in the local time zone, or in UTC
+/// if the local time zone cannot be determined.
+pub fn system_now() -> OffsetDateTime {
+    let offset = match UtcOffset::current_local_offset() {
+        Ok(offset) => offset,
+        Err(_) => UtcOffset::UTC,
+    };
+    OffsetDateTime::now_utc().to_offset(offset)
+}
+
+/// Clock shared by the timing callables to measure elapsed time and to pace frames.
+pub struct Clock {
+    /// Function to query the current time.
+    now_fn: NowFn,
+
+    /// Time at which the program started, which `TIMER` measures against.
+    start: Cell<OffsetDateTime>,
+
+    /// Deadline of the most recent frame paced by `WAITFRAME`, if any.
+    last_frame: Cell<Option<OffsetDateTime>>,
+}
+
+impl Clock {
+    /// Creates a new clock that uses `now_fn` to query the current time.
+    pub fn new(now_fn: NowFn) -> Rc<Self> {
+        let start = now_fn();
+        Rc::from(Self { now_fn, start: Cell::new(start), last_frame: Cell::new(None) })
+    }
+
+    /// Returns the current time.
+    pub(crate) fn now(&self) -> OffsetDateTime {
+        (self.now_fn)()
+    }
+
+    /// Returns the time elapsed since the Unix epoch, which never resets and is thus suitable to
+    /// measure the intervals between the machine's timer events.
+    fn since_epoch(&self) -> Duration {
+        Duration::try_from((self.now_fn)() - OffsetDateTime::UNIX_EPOCH).unwrap_or_default()
+    }
+
+    /// Returns the time elapsed since the program started.
+    fn elapsed(&self) -> time::Duration {
+        (self.now_fn)() - self.start.get()
+    }
+
+    /// Computes how long to wait for the next frame to keep frames `period` apart.
+    ///
+    /// Deadlines are computed from the previous deadline, not from the time at which the previous
+    /// wait finished, so that the error of individual waits does not accumulate.  If the deadline
+    /// has already passed, there is no wait and pacing restarts from the current time.
+    fn next_frame_wait(&self, period: Duration) -> Duration {
+        let now = (self.now_fn)();
+        let deadline = match self.last_frame.get() {
+            Some(last) => last + period,
+            None => now,
+        };
+        if deadline > now {
+            self.last_frame.set(Some(deadline));
+            Duration::try_from(deadline - now).expect("Deadline is in the future")
+        } else {
+            self.last_frame.set(Some(now));
+            Duration::ZERO
+        }
+    }
+}
+
+/// Resets the state of the clock when the machine is cleared.
+struct ClockClearable(Rc<Clock>);
+
+impl Clearable for ClockClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.start.set((self.0.now_fn)());
+        self.0.last_frame.set(None);
+    }
+}
+
+/// The `TIMER` function.
+pub struct TimerFunction {
     metadata: CallableMetadata,
+    clock: Rc<Clock>,
 }
 
-impl ErrmsgFunction {
+impl TimerFunction {
     /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
+    pub fn new(clock: Rc<Clock>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("ERRMSG")
-                .with_return_type(ExprType::Text)
+            metadata: CallableMetadataBuilder::new("TIMER")
+                .with_return_type(ExprType::Double)
                 .with_syntax(&[(&[], None)])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Returns the last captured error message.
-When used in combination of ON ERROR to set an error handler, this function returns the string \
-representation of the last captured error.  If this is called before any error is captured, \
-returns the empty string.",
+                    "Returns the number of seconds since the program started.
+The returned value has sub-millisecond precision, which makes it suitable to measure short \
+intervals.  The count restarts when the program is started via RUN and when CLEAR is invoked.",
                 )
                 .build(),
+            clock,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for ErrmsgFunction {
+impl Callable for TimerFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
         debug_assert_eq!(0, scope.nargs());
-
-        match machine.last_error() {
-            Some(message) => scope.return_string(message),
-            None => scope.return_string("".to_owned()),
-        }
-    }
-}
-
-/// Type of the sleep function used by the `SLEEP` command to actually suspend execution.
-pub type SleepFn = Box<dyn Fn(Duration, LineCol) -> BoxedLocal<Result<()>>>;
-
-/// An implementation of a `SleepFn` that stops the current thread.
-fn system_sleep(d: Duration, _pos: LineCol) -> BoxedLocal<Result<()>> {
-    async move {
-        thread::sleep(d);
-        Ok(())
+        scope.return_double(self.clock.elapsed().as_seconds_f64())
     }
-    .boxed_local()
 }
 
-/// The `SLEEP` command.
-pub struct SleepCommand {
+/// The `WAITFRAME` command.
+pub struct WaitframeCommand {
     metadata: CallableMetadata,
-    sleep_fn: SleepFn,
+    clock: Rc<Clock>,
+    sleep_fn: Rc<SleepFn>,
 }
 
-impl SleepCommand {
+impl WaitframeCommand {
     /// Creates a new instance of the command.
-    pub fn new(sleep_fn: SleepFn) -> Rc<Self> {
+    pub fn new(clock: Rc<Clock>, sleep_fn: Rc<SleepFn>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("SLEEP")
+            metadata: CallableMetadataBuilder::new("WAITFRAME")
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
                         RequiredValueSyntax {
-                            name: Cow::Borrowed("seconds"),
-                            vtype: ExprType::Double,
+                            name: Cow::Borrowed("fps"),
+                            vtype: ExprType::Integer,
                         },
                         ArgSepSyntax::End,
                     )],
@@ -144,41 +926,71 @@ impl SleepCommand {
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Suspends program execution.
-Pauses program execution for the given number of seconds, which can be specified either as an \
-integer or as a floating point number for finer precision.",
+                    "Suspends program execution to keep a steady frame rate.
+Pauses program execution until 1/fps% seconds have passed since the previous WAITFRAME call, \
+which lets a loop that invokes WAITFRAME once per iteration run at fps% iterations per second \
+regardless of how long each iteration takes.
+Frames are scheduled relative to each other so that small delays do not accumulate over time.  \
+If an iteration takes longer than its frame, WAITFRAME returns immediately and the frame \
+schedule restarts from that point.",
                 )
                 .build(),
+            clock,
             sleep_fn,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for SleepCommand {
+impl Callable for WaitframeCommand {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
         debug_assert_eq!(1, scope.nargs());
-        let (n, pos) = scope.pop_double_with_pos();
+        let (fps, pos) = scope.pop_integer_with_pos();
 
-        if n < 0.0 {
-            return Err(Error::SyntaxError(pos, "Sleep time must be positive".to_owned()));
+        if fps <= 0 {
+            return Err(Error::SyntaxError(pos, format!("Frame rate {} must be positive", fps)));
         }
 
-        (self.sleep_fn)(Duration::from_secs_f64(n), pos).await
+        let wait = self.clock.next_frame_wait(Duration::from_secs_f64(1.0 / f64::from(fps)));
+        if wait.is_zero() {
+            return Ok(());
+        }
+        (self.sleep_fn)(wait, pos).await
     }
 }
-
 /// Instantiates all REPL commands for the scripting machine and adds them to the `machine`.
 ///
-/// `sleep_fn` is an async function that implements a pause given a `Duration`.  If not provided,
-/// uses the `std::thread::sleep` function.
-pub fn add_scripting(machine: &mut Machine, sleep_fn: Option<SleepFn>) {
+/// `sleep_fn` is an async function that implements a pause given a `Duration`.
+///
+/// `clock` is the clock used to measure elapsed time, including the intervals between the machine's
+/// timer events.
+pub fn add_scripting(machine: &mut Machine, sleep_fn: Rc<SleepFn>, clock: Rc<Clock>) {
+    machine.set_clock_fn(Some(Box::from({
+        let clock = clock.clone();
+        move || clock.since_epoch()
+    })));
+    let depth = Rc::from(RefCell::from(0));
+    machine.add_clearable(Box::from(ClockClearable(clock.clone())));
+    machine.add_callable(ArgFunction::new());
+    machine.add_callable(ArgcFunction::new());
+    machine.add_callable(ErlFunction::new());
+    machine.add_callable(ErrFunction::new());
     machine.add_callable(ErrmsgFunction::new());
-    machine.add_callable(SleepCommand::new(sleep_fn.unwrap_or_else(|| Box::from(system_sleep))));
+    machine.add_callable(EvalFunction::new(depth.clone()));
+    machine.add_callable(ExecuteCommand::new(depth));
+    machine.add_callable(IsDefinedFunction::new());
+    machine.add_callable(SleepCommand::new({
+        let sleep_fn = sleep_fn.clone();
+        Box::from(move |d, pos| sleep_fn(d, pos))
+    }));
+    machine.add_callable(SymsyntaxFunction::new());
+    machine.add_callable(SymtypeFunction::new());
+    machine.add_callable(TimerFunction::new(clock.clone()));
+    machine.add_callable(WaitframeCommand::new(clock, sleep_fn));
 }
 
 /// Instantiates all REPL commands for the interactive machine and adds them to the `machine`.
@@ -192,6 +1004,39 @@ mod tests {
     use crate::testutils::*;
     use std::time::Instant;
 
+    #[test]
+    fn test_arg_and_argc() {
+        let mut t = Tester::default();
+        t.get_machine().set_args(vec!["prog.bas".to_owned(), "foo".to_owned(), "42".to_owned()]);
+        t.run("n = ARGC: a$ = ARG(0): b$ = ARG(1): c$ = ARG(2)")
+            .expect_var("n", 3)
+            .expect_var("a", "prog.bas")
+            .expect_var("b", "foo")
+            .expect_var("c", "42")
+            .check();
+    }
+
+    #[test]
+    fn test_argc_without_args() {
+        check_expr_ok(0, "ARGC");
+    }
+
+    #[test]
+    fn test_arg_errors() {
+        check_expr_error("1:14: Argument index 0 out of range; ARGC% is 0", "ARG(0)");
+
+        let mut t = Tester::default();
+        t.get_machine().set_args(vec!["prog.bas".to_owned()]);
+        t.run("a$ = ARG(1)").expect_err("1:10: Argument index 1 out of range; ARGC% is 1").check();
+        t.run("a$ = ARG(-1)")
+            .expect_err("1:10: Argument index -1 out of range; ARGC% is 1")
+            .check();
+
+        check_expr_compilation_error("1:10: ARG expected n%", "ARG()");
+        check_expr_compilation_error("1:14: STRING is not a number", r#"ARG("a")"#);
+        check_expr_compilation_error("1:10: ARGC expected no arguments", "ARGC(1)");
+    }
+
     #[test]
     fn test_clear_ok() {
         Tester::default().run("a = 1: CLEAR").expect_clear().check();
@@ -207,6 +1052,51 @@ mod tests {
         check_stmt_compilation_err("1:1: CLEAR expected no arguments", "CLEAR 123");
     }
 
+    #[test]
+    fn test_err_and_erl_before_error() {
+        check_expr_ok(0, "ERR");
+        check_expr_ok(0, "ERL");
+    }
+
+    #[test]
+    fn test_err_and_erl_after_error() {
+        let program = r#"
+ON ERROR GOTO @handler
+a = 0
+PRINT 10 / a
+LOAD "missing.bas"
+READ d
+GOTO @end
+@handler
+PRINT ERR; ERL; ERRMSG
+RESUME NEXT
+@end
+"#;
+        Tester::default()
+            .run(program)
+            .expect_prints([
+                " 11  4 4:10: Division by zero",
+                " 53  5 5:1: Entry not found",
+                " 4  6 6:6: Out of data reading into D",
+            ])
+            .expect_var("a", 0)
+            .check();
+    }
+
+    #[test]
+    fn test_err_type_mismatch() {
+        Tester::default()
+            .run("ON ERROR RESUME NEXT: DATA \"x\": READ i%: PRINT ERR; ERL")
+            .expect_prints([" 13  1"])
+            .check();
+    }
+
+    #[test]
+    fn test_err_and_erl_errors() {
+        check_expr_compilation_error("1:10: ERR expected no arguments", r#"ERR()"#);
+        check_expr_compilation_error("1:10: ERL expected no arguments", r#"ERL(3)"#);
+    }
+
     #[test]
     fn test_errmsg_before_error() {
         check_expr_ok("", r#"ERRMSG"#);
@@ -226,6 +1116,192 @@ mod tests {
         check_expr_compilation_error("1:10: ERRMSG expected no arguments", r#"ERRMSG(3)"#);
     }
 
+    #[test]
+    fn test_eval_ok() {
+        check_expr_ok(7.0, r#"EVAL("3 + 4")"#);
+        check_expr_ok(2.5, r#"EVAL("5 / 2.0")"#);
+        check_expr_ok_with_vars(12.0, r#"EVAL("a * 2")"#, [("a", 6.into())]);
+        check_expr_ok(3.0, r#"EVAL("EVAL(\"1 + 2\")")"#);
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        check_expr_compilation_error("1:10: EVAL expected expr$", r#"EVAL()"#);
+        check_expr_compilation_error("1:10: EVAL expected expr$", r#"EVAL("1", "2")"#);
+        check_expr_compilation_error("1:15: expected STRING but found INTEGER", r#"EVAL(3)"#);
+        check_expr_error("1:15: 1:3: Unexpected value in expression", r#"EVAL("1 2")"#);
+        check_expr_error("1:15: 1:1: Undefined symbol X", r#"EVAL("x + 1")"#);
+        check_expr_error("1:15: STRING is not a number", r#"EVAL("\"a\"")"#);
+        check_expr_error("1:3: Division by zero", r#"EVAL("1 / 0")"#);
+    }
+
+    #[test]
+    fn test_eval_nesting_limit() {
+        Tester::default()
+            .run(r#"e$ = "EVAL(e$)": result = EVAL(e$)"#)
+            .expect_err("1:6: Exceeded maximum EVAL/EXECUTE nesting depth of 16")
+            .expect_var("e", "EVAL(e$)")
+            .check();
+    }
+
+    #[test]
+    fn test_execute_assignments_and_prints() {
+        Tester::default()
+            .run(r#"EXECUTE "a = 3: PRINT a + 1""#)
+            .expect_prints([" 4"])
+            .expect_var("a", 3)
+            .check();
+
+        Tester::default()
+            .run(r#"EXECUTE "PRINT 1" + CHR$(10) + "PRINT 2": EXECUTE "PRINT 3""#)
+            .expect_prints([" 1", " 2", " 3"])
+            .check();
+    }
+
+    #[test]
+    fn test_execute_dispatch_by_name() {
+        Tester::default()
+            .run(
+                r#"
+                DATA "PRINT", "COLOR"
+                READ cmd1$, cmd2$
+                EXECUTE cmd1$ + " \"hello\""
+                EXECUTE cmd2$ + " 3"
+                "#,
+            )
+            .expect_prints(["hello"])
+            .expect_output([CapturedOut::SetColor(Some(3), None)])
+            .expect_var("cmd1", "PRINT")
+            .expect_var("cmd2", "COLOR")
+            .check();
+    }
+
+    #[test]
+    fn test_execute_shares_variables() {
+        Tester::default()
+            .run(r#"b = 10: EXECUTE "b = b * 2: DIM c AS STRING": c = "x""#)
+            .expect_var("b", 20)
+            .expect_var("c", "x")
+            .check();
+
+        Tester::default()
+            .run(
+                r#"
+                SUB twice(n%)
+                    EXECUTE "n = n * 2"
+                    PRINT n
+                END SUB
+                twice 21
+                "#,
+            )
+            .expect_prints([" 42"])
+            .check();
+    }
+
+    #[test]
+    fn test_execute_end_only_stops_code() {
+        Tester::default()
+            .run(r#"EXECUTE "PRINT 1: END: PRINT 2": PRINT 3"#)
+            .expect_prints([" 1", " 3"])
+            .check();
+    }
+
+    #[test]
+    fn test_execute_errors() {
+        check_stmt_compilation_err("1:1: EXECUTE expected code$", "EXECUTE");
+        check_stmt_compilation_err("1:1: EXECUTE expected code$", r#"EXECUTE "a", "b""#);
+        check_stmt_compilation_err("1:9: expected STRING but found INTEGER", "EXECUTE 3");
+        check_stmt_err("1:9: 1:7: Not enough values to apply operator", r#"EXECUTE "a = 1 +""#);
+        check_stmt_err("1:9: 1:7: Undefined symbol X", r#"EXECUTE "PRINT x""#);
+        check_stmt_err("1:7: Color out of range", r#"EXECUTE "COLOR -1""#);
+    }
+
+    #[test]
+    fn test_execute_errors_are_catchable() {
+        Tester::default()
+            .run(r#"ON ERROR RESUME NEXT: EXECUTE "PRINT x": PRINT ERRMSG"#)
+            .expect_prints(["1:31: 1:7: Undefined symbol X"])
+            .check();
+
+        Tester::default()
+            .run(
+                r#"a = 0: ON ERROR RESUME NEXT: EXECUTE "a = 1: COLOR -1: a = 2": PRINT a; ERRMSG"#,
+            )
+            .expect_prints([" 1 1:14: Color out of range"])
+            .expect_var("a", 1)
+            .check();
+    }
+
+    #[test]
+    fn test_execute_nesting_limit() {
+        Tester::default()
+            .run(r#"n = 0: c$ = "n = n + 1: EXECUTE c$": EXECUTE c$"#)
+            .expect_err("1:20: Exceeded maximum EVAL/EXECUTE nesting depth of 16")
+            .expect_var("c", "n = n + 1: EXECUTE c$")
+            .expect_var("n", 16)
+            .check();
+
+        // The depth must be restored after errors.
+        Tester::default()
+            .run(r#"c$ = "EXECUTE c$": ON ERROR RESUME NEXT: EXECUTE c$: EXECUTE "PRINT 1""#)
+            .expect_prints([" 1"])
+            .expect_var("c", "EXECUTE c$")
+            .check();
+    }
+
+    #[test]
+    fn test_isdefined_builtins() {
+        check_expr_ok(true, r#"ISDEFINED?("ERRMSG")"#);
+        check_expr_ok(true, r#"ISDEFINED?("errmsg")"#);
+        check_expr_ok(true, r#"ISDEFINED?("ErrMsg$")"#);
+        check_expr_ok(false, r#"ISDEFINED?("ERRMSG%")"#);
+        check_expr_ok(true, r#"ISDEFINED?("SLEEP")"#);
+        check_expr_ok(false, r#"ISDEFINED?("SLEEP$")"#);
+        check_expr_ok(false, r#"ISDEFINED?("UNKNOWN")"#);
+    }
+
+    #[test]
+    fn test_isdefined_user_symbols() {
+        Tester::default()
+            .run(
+                r#"
+                DIM arr(3) AS STRING
+                DIM v AS DOUBLE
+                FUNCTION area#(w#, h#)
+                    area = w * h
+                END FUNCTION
+                SUB greet
+                END SUB
+                PRINT ISDEFINED?("arr"); ISDEFINED?("arr$"); ISDEFINED?("arr%")
+                PRINT ISDEFINED?("V"); ISDEFINED?("v#"); ISDEFINED?("v?")
+                PRINT ISDEFINED?("AREA"); ISDEFINED?("area#"); ISDEFINED?("area$")
+                PRINT ISDEFINED?("greet"); ISDEFINED?("greet%")
+                PRINT ISDEFINED?("undefined")
+                "#,
+            )
+            .expect_array("arr", ExprType::Text, &[3], vec![])
+            .expect_var("v", 0.0)
+            .expect_prints([
+                "TRUE TRUE FALSE",
+                "TRUE TRUE FALSE",
+                "TRUE TRUE FALSE",
+                "TRUE FALSE",
+                "FALSE",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_isdefined_errors() {
+        check_expr_compilation_error("1:10: ISDEFINED expected name$", r#"ISDEFINED()"#);
+        check_expr_compilation_error("1:10: ISDEFINED expected name$", r#"ISDEFINED("a", "b")"#);
+        check_expr_compilation_error("1:20: expected STRING but found INTEGER", r#"ISDEFINED(3)"#);
+        check_expr_error("1:20: Invalid symbol name ''", r#"ISDEFINED("")"#);
+        check_expr_error("1:20: Invalid symbol name '1a'", r#"ISDEFINED("1a")"#);
+        check_expr_error("1:20: Invalid symbol name 'a b'", r#"ISDEFINED("a b")"#);
+        check_expr_error("1:20: Invalid symbol name '$'", r#"ISDEFINED("$")"#);
+    }
+
     #[test]
     fn test_sleep_ok_int() {
         let sleep_fake = |d: Duration, pos: LineCol| -> BoxedLocal<Result<()>> {
@@ -255,6 +1331,37 @@ mod tests {
         t.run("SLEEP 123.1").expect_err("1:7: Good").check();
     }
 
+    #[test]
+    fn test_sleep_subsecond() {
+        let slept = Rc::from(RefCell::from(vec![]));
+        let sleep_fake = {
+            let slept = slept.clone();
+            move |d: Duration, _pos: LineCol| -> BoxedLocal<Result<()>> {
+                slept.borrow_mut().push(d.as_millis());
+                async move { Ok(()) }.boxed_local()
+            }
+        };
+
+        let mut t = Tester::empty().add_callable(SleepCommand::new(Box::from(sleep_fake)));
+        t.run("SLEEP 0.25: SLEEP 0.0004: SLEEP 0.0006: SLEEP 0: SLEEP 1.5").check();
+        assert_eq!(&[250, 0, 1, 0, 1500], slept.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_sleep_break() {
+        let mut t = Tester::empty();
+        let signals_tx = t.get_machine().get_signals_tx();
+        let sleep_fake = move |_d: Duration, _pos: LineCol| -> BoxedLocal<Result<()>> {
+            signals_tx.try_send(Signal::Break).unwrap();
+            future::pending().boxed_local()
+        };
+
+        t.add_callable(SleepCommand::new(Box::from(sleep_fake)))
+            .run("SLEEP 1000: a = 1")
+            .expect_ok(StopReason::Break)
+            .check();
+    }
+
     #[test]
     fn test_sleep_real() {
         let before = Instant::now();
@@ -262,6 +1369,25 @@ mod tests {
         assert!(before.elapsed() >= Duration::from_millis(10));
     }
 
+    #[test]
+    fn test_sleep_real_zero() {
+        Tester::default().run("SLEEP 0").check();
+    }
+
+    #[test]
+    fn test_sleep_real_break() {
+        let mut t = Tester::default();
+        let signals_tx = t.get_machine().get_signals_tx();
+        let before = Instant::now();
+        let breaker = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            signals_tx.try_send(Signal::Break).unwrap();
+        });
+        t.run("SLEEP 60: a = 1").expect_ok(StopReason::Break).check();
+        breaker.join().unwrap();
+        assert!(before.elapsed() < Duration::from_secs(60));
+    }
+
     #[test]
     fn test_sleep_errors() {
         check_stmt_compilation_err("1:1: SLEEP expected seconds#", "SLEEP");
@@ -270,5 +1396,203 @@ mod tests {
         check_stmt_compilation_err("1:7: STRING is not a number", "SLEEP \"foo\"");
         check_stmt_err("1:7: Sleep time must be positive", "SLEEP -1");
         check_stmt_err("1:7: Sleep time must be positive", "SLEEP -0.001");
+        check_stmt_err(
+            "1:7: Sleep time 100000000000000000000 is too large",
+            "SLEEP 100000000000000000000.0",
+        );
+    }
+
+    #[test]
+    fn test_symsyntax_builtins() {
+        check_expr_ok("CLEAR", r#"SYMSYNTAX$("clear")"#);
+        check_expr_ok("ERRMSG$", r#"SYMSYNTAX$("ERRMSG")"#);
+        check_expr_ok("SLEEP seconds#", r#"SYMSYNTAX$("Sleep")"#);
+        check_expr_ok("SYMTYPE$(name$)", r#"SYMSYNTAX$("SYMTYPE$")"#);
+        check_expr_ok("", r#"SYMSYNTAX$("SYMTYPE%")"#);
+        check_expr_ok("", r#"SYMSYNTAX$("UNKNOWN")"#);
+    }
+
+    #[test]
+    fn test_symsyntax_user_symbols() {
+        Tester::default()
+            .run(
+                r#"
+                DIM arr(3)
+                v = 1
+                FUNCTION area#(w#, h#)
+                    area = w * h
+                END FUNCTION
+                SUB greet(name$)
+                END SUB
+                PRINT SYMSYNTAX$("arr"); "|"; SYMSYNTAX$("v"); "|"
+                PRINT SYMSYNTAX$("area")
+                PRINT SYMSYNTAX$("greet")
+                "#,
+            )
+            .expect_array("arr", ExprType::Integer, &[3], vec![])
+            .expect_var("v", 1)
+            .expect_prints(["||", "AREA#(w#, h#)", "GREET name$"])
+            .check();
+    }
+
+    #[test]
+    fn test_symsyntax_errors() {
+        check_expr_compilation_error("1:10: SYMSYNTAX expected name$", r#"SYMSYNTAX()"#);
+        check_expr_compilation_error("1:20: expected STRING but found INTEGER", r#"SYMSYNTAX(3)"#);
+        check_expr_error("1:20: Invalid symbol name '1a'", r#"SYMSYNTAX("1a")"#);
+    }
+
+    #[test]
+    fn test_symtype_builtins() {
+        check_expr_ok("COMMAND", r#"SYMTYPE$("CLEAR")"#);
+        check_expr_ok("FUNCTION", r#"SYMTYPE$("errmsg")"#);
+        check_expr_ok("FUNCTION", r#"SYMTYPE$("errmsg$")"#);
+        check_expr_ok("", r#"SYMTYPE$("errmsg#")"#);
+        check_expr_ok("", r#"SYMTYPE$("UNKNOWN")"#);
+    }
+
+    #[test]
+    fn test_symtype_user_symbols() {
+        Tester::default()
+            .run(
+                r#"
+                DIM arr(3)
+                v = 1
+                FUNCTION area#(w#, h#)
+                    area = w * h
+                END FUNCTION
+                SUB greet
+                END SUB
+                PRINT SYMTYPE$("arr"); "|"; SYMTYPE$("v"); "|"; SYMTYPE$("v$"); "|"
+                PRINT SYMTYPE$("area"); "|"; SYMTYPE$("greet"); "|"; SYMTYPE$("undefined"); "|"
+                "#,
+            )
+            .expect_array("arr", ExprType::Integer, &[3], vec![])
+            .expect_var("v", 1)
+            .expect_prints(["ARRAY|VARIABLE||", "FUNCTION|SUB||"])
+            .check();
+    }
+
+    #[test]
+    fn test_symtype_inside_callable() {
+        Tester::default()
+            .run(
+                r#"
+                SUB check(a%)
+                    PRINT SYMTYPE$("a"); "|"; SYMTYPE$("check")
+                END SUB
+                check 3
+                "#,
+            )
+            .expect_prints(["VARIABLE|SUB"])
+            .check();
+    }
+
+    #[test]
+    fn test_symtype_errors() {
+        check_expr_compilation_error("1:10: SYMTYPE expected name$", r#"SYMTYPE()"#);
+        check_expr_compilation_error("1:18: expected STRING but found INTEGER", r#"SYMTYPE(3)"#);
+        check_expr_error("1:18: Invalid symbol name '1a'", r#"SYMTYPE("1a")"#);
+    }
+
+    /// Creates a tester with the timing callables backed by a fake clock.
+    ///
+    /// The returned tester provides a `SLEEP` command that advances the fake clock without being
+    /// recorded, which simulates work done by the program, and the returned vector records the
+    /// durations of all waits issued by `WAITFRAME`.  `oversleep` is added to the clock after each
+    /// of these waits to simulate an imprecise system sleep.
+    fn tester_with_fake_clock(oversleep: Duration) -> (Tester, Rc<RefCell<Vec<Duration>>>) {
+        let mock_clock = MockClock::default();
+        let clock = Clock::new(mock_clock.now_fn());
+
+        let waits = Rc::from(RefCell::from(vec![]));
+        let wait_fn: SleepFn = {
+            let waits = waits.clone();
+            let mock_clock = mock_clock.clone();
+            Box::from(move |d: Duration, _pos: LineCol| -> BoxedLocal<Result<()>> {
+                waits.borrow_mut().push(d);
+                mock_clock.advance(d + oversleep);
+                async move { Ok(()) }.boxed_local()
+            })
+        };
+
+        let mut t = Tester::empty()
+            .add_callable(SleepCommand::new(mock_clock.sleep_fn()))
+            .add_callable(TimerFunction::new(clock.clone()))
+            .add_callable(WaitframeCommand::new(clock.clone(), Rc::from(wait_fn)));
+        t.get_machine().add_clearable(Box::from(ClockClearable(clock)));
+        (t, waits)
+    }
+
+    #[test]
+    fn test_timer_progression() {
+        let (mut t, _waits) = tester_with_fake_clock(Duration::ZERO);
+        t.run("a = TIMER: SLEEP 0.001: b = TIMER: SLEEP 0.25: c = TIMER: SLEEP 2: d = TIMER")
+            .expect_var("a", 0.0)
+            .expect_var("b", 0.001)
+            .expect_var("c", 0.251)
+            .expect_var("d", 2.251)
+            .check();
+    }
+
+    #[test]
+    fn test_timer_restarts_on_clear() {
+        let (mut t, _waits) = tester_with_fake_clock(Duration::ZERO);
+        t.run("SLEEP 3: a = TIMER").expect_var("a", 3.0).check();
+        t.get_machine().clear();
+        t.run("SLEEP 0.5: b = TIMER").expect_var("b", 0.5).check();
+    }
+
+    #[test]
+    fn test_timer_errors() {
+        check_expr_compilation_error("1:10: TIMER expected no arguments", "TIMER(1)");
+    }
+
+    #[test]
+    fn test_waitframe_paces_frames() {
+        let (mut t, waits) = tester_with_fake_clock(Duration::ZERO);
+        t.run(
+            r#"
+            WAITFRAME 10
+            SLEEP 0.04: WAITFRAME 10
+            SLEEP 0.02: WAITFRAME 10
+            SLEEP 0.25: WAITFRAME 10
+            SLEEP 0.04: WAITFRAME 10
+            t = TIMER
+            "#,
+        )
+        .expect_var("t", 0.55)
+        .check();
+        assert_eq!(
+            [
+                Duration::from_millis(60),
+                Duration::from_millis(80),
+                // The frame that took 0.25 seconds went over budget and caused no wait.
+                Duration::from_millis(60),
+            ],
+            waits.borrow().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_waitframe_corrects_drift() {
+        let (mut t, waits) = tester_with_fake_clock(Duration::from_millis(1));
+        t.run("FOR i = 1 TO 4: WAITFRAME 8: NEXT: t = TIMER")
+            .expect_var("i", 5)
+            .expect_var("t", 0.376)
+            .check();
+        assert_eq!(
+            [Duration::from_millis(125), Duration::from_millis(124), Duration::from_millis(124)],
+            waits.borrow().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_waitframe_errors() {
+        check_stmt_compilation_err("1:1: WAITFRAME expected fps%", "WAITFRAME");
+        check_stmt_compilation_err("1:1: WAITFRAME expected fps%", "WAITFRAME 2, 3");
+        check_stmt_compilation_err("1:11: STRING is not a number", "WAITFRAME \"foo\"");
+        check_stmt_err("1:11: Frame rate 0 must be positive", "WAITFRAME 0");
+        check_stmt_err("1:11: Frame rate -5 must be positive", "WAITFRAME -5");
     }
 }
@@ -17,8 +17,8 @@
 
 use crate::console::drawing;
 use crate::console::graphics::{RasterInfo, RasterOps};
-use crate::console::{CharsXY, PixelsXY, SizeInPixels, RGB};
-use crate::gfx::lcd::fonts::Font;
+use crate::console::{CharsXY, PixelsXY, ScreenRotation, SizeInPixels, TextRotation, RGB};
+use crate::gfx::lcd::fonts::{all_fonts, Font};
 use crate::gfx::lcd::{to_xy_size, AsByteSlice, Lcd, LcdSize, LcdXY};
 use std::convert::TryFrom;
 use std::io;
@@ -28,23 +28,89 @@ mod tests;
 #[cfg(test)]
 mod testutils;
 
+/// Cost, expressed in pixels, that we attribute to every separate transfer to the LCD.  Two damaged
+/// areas are coalesced when sending their bounding box is not more expensive than sending them
+/// separately.
+const TRANSFER_COST_PIXELS: usize = 64;
+
+/// Maximum number of separate damaged areas to track.  Once reached, new areas are coalesced with
+/// the existing area that grows the least.
+const MAX_DAMAGED_AREAS: usize = 16;
+
+/// Computes the number of pixels in the area between `x1y1` and `x2y2` (inclusive).
+fn area_pixels((x1y1, x2y2): (LcdXY, LcdXY)) -> usize {
+    (x2y2.x - x1y1.x + 1) * (x2y2.y - x1y1.y + 1)
+}
+
+/// Computes the bounding box of the areas `a` and `b`.
+fn bounding_box(a: (LcdXY, LcdXY), b: (LcdXY, LcdXY)) -> (LcdXY, LcdXY) {
+    (
+        LcdXY { x: a.0.x.min(b.0.x), y: a.0.y.min(b.0.y) },
+        LcdXY { x: a.1.x.max(b.1.x), y: a.1.y.max(b.1.y) },
+    )
+}
+
+/// Computes the size of the console in characters for an LCD of `size` pixels and `font`.
+fn size_chars(size: LcdSize, font: &Font) -> CharsXY {
+    CharsXY::new(
+        u16::try_from(size.width / font.glyph_size.width).expect("Must fit"),
+        u16::try_from(size.height / font.glyph_size.height).expect("Must fit"),
+    )
+}
+
+/// Converts the `xy` coordinates in a space rotated by `rotation` to the coordinates of the same
+/// pixel in the native space of an LCD of `native` size.
+fn to_native_xy(rotation: ScreenRotation, native: LcdSize, xy: LcdXY) -> LcdXY {
+    match rotation {
+        ScreenRotation::Deg0 => xy,
+        ScreenRotation::Deg90 => LcdXY { x: native.width - 1 - xy.y, y: xy.x },
+        ScreenRotation::Deg180 => LcdXY { x: native.width - 1 - xy.x, y: native.height - 1 - xy.y },
+        ScreenRotation::Deg270 => LcdXY { x: xy.y, y: native.height - 1 - xy.x },
+    }
+}
+
+/// Converts the `xy` coordinates in the native space of an LCD of `native` size to the coordinates
+/// of the same pixel in a space rotated by `rotation`.  This is the inverse of `to_native_xy`.
+fn from_native_xy(rotation: ScreenRotation, native: LcdSize, xy: LcdXY) -> LcdXY {
+    match rotation {
+        ScreenRotation::Deg0 => xy,
+        ScreenRotation::Deg90 => LcdXY { x: xy.y, y: native.width - 1 - xy.x },
+        ScreenRotation::Deg180 => LcdXY { x: native.width - 1 - xy.x, y: native.height - 1 - xy.y },
+        ScreenRotation::Deg270 => LcdXY { x: native.height - 1 - xy.y, y: xy.x },
+    }
+}
+
 /// Implements buffering for a backing slow LCD `L` that renders text with the font `F`.
 ///
 /// All drawing operations are saved to a memory-backed framebuffer.  If syncing is enabled, drawing
 /// primitives are flushed right away to the device; otherwise, they are applied to memory only
 /// until an explicit sync is requested.  The framebuffer is also used to implement all pixel data
 /// reading.
+///
+/// Modified areas of the framebuffer are tracked as a list of damaged rectangles, which are
+/// coalesced when they are close to each other, and only the pixels that differ from what the LCD
+/// already displays are sent to it.  This minimizes the amount of data that goes through the slow
+/// bus that connects to the LCD without affecting what ends up being displayed.
 pub struct BufferedLcd<L: Lcd> {
     lcd: L,
     font: &'static Font,
+    text_font: &'static Font,
 
     fb: Vec<u8>,
     stride: usize,
     sync: bool,
-    damage: Option<(LcdXY, LcdXY)>,
+    damage: Vec<(LcdXY, LcdXY)>,
+
+    /// Copy of the framebuffer as last sent to the LCD.  Only meaningful if `presented_valid`.
+    presented: Vec<u8>,
+
+    /// Whether `presented` matches the contents of the LCD, which we only know after sending the
+    /// whole framebuffer to it at least once.
+    presented_valid: bool,
 
     size_pixels: LcdSize,
     size_chars: CharsXY,
+    rotation: ScreenRotation,
 
     draw_color: L::Pixel,
     row_buffer: Vec<u8>,
@@ -54,8 +120,17 @@ impl<L> BufferedLcd<L>
 where
     L: Lcd,
 {
-    /// Creates a new buffered LCD backed by `lcd`.
-    pub fn new(lcd: L, font: &'static Font) -> Self {
+    /// Creates a new buffered LCD backed by `lcd` that renders the console with `font`.
+    ///
+    /// `font` must be fixed-width because the console lays out text in a grid of characters.
+    pub fn new(lcd: L, font: &'static Font) -> io::Result<Self> {
+        if font.is_proportional() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot use proportional font {} as the console font", font.name),
+            ));
+        }
+
         let (size, stride) = lcd.info();
 
         let fb = {
@@ -63,26 +138,27 @@ where
             vec![0; pixels * stride]
         };
 
-        let size_chars = CharsXY::new(
-            u16::try_from(size.width / font.glyph_size.width).expect("Must fit"),
-            u16::try_from(size.height / font.glyph_size.height).expect("Must fit"),
-        );
+        let size_chars = size_chars(size, font);
 
         let draw_color = lcd.encode((255, 255, 255));
         let row_buffer = Vec::with_capacity(size.width * stride);
 
-        Self {
+        Ok(Self {
             lcd,
             font,
+            text_font: font,
+            presented: fb.clone(),
+            presented_valid: false,
             fb,
             stride,
             sync: true,
-            damage: None,
+            damage: vec![],
             size_pixels: size,
             size_chars,
+            rotation: ScreenRotation::Deg0,
             draw_color,
             row_buffer,
-        }
+        })
     }
 
     /// Executes mutations on the buffered LCD via `ops` while ensuring that syncing is disabled.
@@ -226,37 +302,90 @@ where
         ((y * self.size_pixels.width) + x) * self.stride
     }
 
-    /// Extends the current damage area to include the area between between `x1y1` and `x2y2`
-    /// (inclusive) as damaged.
+    /// Records the area between `x1y1` and `x2y2` (inclusive) as damaged so that the next call to
+    /// `force_present_canvas` sends it to the LCD.
     ///
-    /// This only makes sense when syncing is disabled, as the damage area represents the contents
-    /// that need to be flushed to the LCD once syncing is enabled again.
+    /// The new area is coalesced with any existing damaged areas for which sending their bounding
+    /// box is cheaper than sending them separately, considering `TRANSFER_COST_PIXELS`.
     fn damage(&mut self, x1y1: LcdXY, x2y2: LcdXY) {
-        debug_assert!(!self.sync);
         debug_assert!(x2y2.x >= x1y1.x);
         debug_assert!(x2y2.y >= x1y1.y);
 
-        if self.damage.is_none() {
-            self.damage = Some((x1y1, x2y2));
-            return;
+        let mut area = (x1y1, x2y2);
+        while let Some(i) = self.damage.iter().position(|other| {
+            area_pixels(bounding_box(area, *other))
+                <= area_pixels(area) + area_pixels(*other) + TRANSFER_COST_PIXELS
+        }) {
+            area = bounding_box(area, self.damage.remove(i));
         }
-        let mut damage = self.damage.unwrap();
 
-        if damage.0.x > x1y1.x {
-            damage.0.x = x1y1.x;
+        if self.damage.len() == MAX_DAMAGED_AREAS {
+            let (i, _) = self
+                .damage
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, other)| area_pixels(bounding_box(area, **other)))
+                .expect("Damage list cannot be empty");
+            area = bounding_box(area, self.damage.remove(i));
         }
-        if damage.0.y > x1y1.y {
-            damage.0.y = x1y1.y;
+
+        self.damage.push(area);
+    }
+
+    /// Computes the bounding box of the pixels between `x1y1` and `x2y2` (inclusive) that differ
+    /// from what the LCD displays, or `None` if there are no differences.
+    fn changed_area(&self, x1y1: LcdXY, x2y2: LcdXY) -> Option<(LcdXY, LcdXY)> {
+        if !self.presented_valid {
+            return Some((x1y1, x2y2));
+        }
+
+        let mut changed: Option<(LcdXY, LcdXY)> = None;
+        for y in x1y1.y..(x2y2.y + 1) {
+            let start = self.fb_addr(x1y1.x, y);
+            let end = self.fb_addr(x2y2.x, y) + self.stride;
+            let new_row = self.fb[start..end].chunks(self.stride);
+            let old_row = self.presented[start..end].chunks(self.stride);
+            let mut diffs = new_row.zip(old_row).enumerate().filter(|(_, (new, old))| new != old);
+            let first = match diffs.next() {
+                Some((i, _)) => i,
+                None => continue,
+            };
+            let last = diffs.next_back().map(|(i, _)| i).unwrap_or(first);
+
+            let row = (LcdXY { x: x1y1.x + first, y }, LcdXY { x: x1y1.x + last, y });
+            changed = Some(match changed {
+                Some(changed) => bounding_box(changed, row),
+                None => row,
+            });
         }
+        changed
+    }
 
-        if damage.1.x < x2y2.x {
-            damage.1.x = x2y2.x;
+    /// Sends the pixel `data` of the area contained between `x1y1` and `x2y2` (inclusive) to the
+    /// LCD, transforming the area and the order of the pixels to the native LCD orientation.
+    fn set_lcd_data(&mut self, x1y1: LcdXY, x2y2: LcdXY, data: &[u8]) -> io::Result<()> {
+        if self.rotation == ScreenRotation::Deg0 {
+            return self.lcd.set_data(x1y1, x2y2, data);
         }
-        if damage.1.y < x2y2.y {
-            damage.1.y = x2y2.y;
+
+        let (native, _stride) = self.lcd.info();
+        let corner1 = to_native_xy(self.rotation, native, x1y1);
+        let corner2 = to_native_xy(self.rotation, native, x2y2);
+        let native_x1y1 = LcdXY { x: corner1.x.min(corner2.x), y: corner1.y.min(corner2.y) };
+        let native_x2y2 = LcdXY { x: corner1.x.max(corner2.x), y: corner1.y.max(corner2.y) };
+
+        let width = x2y2.x - x1y1.x + 1;
+        let mut native_data = LcdSize::between(native_x1y1, native_x2y2).new_buffer(self.stride);
+        for y in native_x1y1.y..(native_x2y2.y + 1) {
+            for x in native_x1y1.x..(native_x2y2.x + 1) {
+                let xy = from_native_xy(self.rotation, native, LcdXY { x, y });
+                let offset = ((xy.y - x1y1.y) * width + (xy.x - x1y1.x)) * self.stride;
+                native_data.extend_from_slice(&data[offset..offset + self.stride]);
+            }
         }
+        debug_assert_eq!(data.len(), native_data.len());
 
-        self.damage = Some(damage);
+        self.lcd.set_data(native_x1y1, native_x2y2, &native_data)
     }
 
     /// Fills the area contained between `x1y1` and `x2y2` (inclusive) with the current drawing
@@ -279,38 +408,31 @@ where
             rowlen
         };
 
-        if self.sync {
-            let mut data = LcdSize::between(x1y1, x2y2).new_buffer(self.stride);
-            for y in x1y1.y..(x2y2.y + 1) {
-                let offset = self.fb_addr(x1y1.x, y);
-                self.fb[offset..offset + rowlen].copy_from_slice(&self.row_buffer);
-                data.extend(&self.row_buffer);
-            }
-            self.lcd.set_data(x1y1, x2y2, &data)?;
-        } else {
-            for y in x1y1.y..(x2y2.y + 1) {
-                let offset = self.fb_addr(x1y1.x, y);
-                self.fb[offset..offset + rowlen].copy_from_slice(&self.row_buffer);
-            }
-            self.damage(x1y1, x2y2);
+        for y in x1y1.y..(x2y2.y + 1) {
+            let offset = self.fb_addr(x1y1.x, y);
+            self.fb[offset..offset + rowlen].copy_from_slice(&self.row_buffer);
         }
+        self.damage(x1y1, x2y2);
 
+        if self.sync {
+            self.force_present_canvas()?;
+        }
         Ok(())
     }
 
-    /// Flushes any pending damaged area to the LCD.
-    fn force_present_canvas(&mut self) -> io::Result<()> {
-        let (x1y1, x2y2) = match self.damage {
+    /// Sends the pixels of the framebuffer contained between `x1y1` and `x2y2` (inclusive) to the
+    /// LCD, skipping those that the LCD already displays.
+    fn present_area(&mut self, x1y1: LcdXY, x2y2: LcdXY) -> io::Result<()> {
+        let (x1y1, x2y2) = match self.changed_area(x1y1, x2y2) {
             None => return Ok(()),
-            Some(damage) => damage,
+            Some(changed) => changed,
         };
 
+        let rowlen = (x2y2.x - x1y1.x + 1) * self.stride;
         let mut data = LcdSize::between(x1y1, x2y2).new_buffer(self.stride);
         for y in x1y1.y..(x2y2.y + 1) {
-            for x in x1y1.x..(x2y2.x + 1) {
-                let offset = self.fb_addr(x, y);
-                data.extend_from_slice(&self.fb[offset..offset + self.stride]);
-            }
+            let offset = self.fb_addr(x1y1.x, y);
+            data.extend_from_slice(&self.fb[offset..offset + rowlen]);
         }
         debug_assert_eq!(
             {
@@ -320,38 +442,85 @@ where
             data.len()
         );
 
-        self.lcd.set_data(x1y1, x2y2, &data)?;
+        self.set_lcd_data(x1y1, x2y2, &data)?;
 
-        self.damage = None;
+        for y in x1y1.y..(x2y2.y + 1) {
+            let offset = self.fb_addr(x1y1.x, y);
+            self.presented[offset..offset + rowlen]
+                .copy_from_slice(&self.fb[offset..offset + rowlen]);
+        }
+        if area_pixels((x1y1, x2y2)) == self.size_pixels.width * self.size_pixels.height {
+            self.presented_valid = true;
+        }
 
         Ok(())
     }
 
-    /// Writes a single character `ch` at `pos`.
-    fn write_char(&mut self, pos: LcdXY, ch: char) -> io::Result<()> {
-        let glyph = self.font.glyph(ch);
-        for j in 0..self.font.glyph_size.height {
-            for k in 0..self.font.stride {
-                let row = glyph[j * self.font.stride + k];
-                let mut mask = 0x80;
-                for i in 0..self.font.glyph_size.width {
-                    let bit = row & mask;
-                    if bit != 0 {
-                        let x = pos.x + i + k * 8;
-                        if x >= self.size_pixels.width {
-                            continue;
-                        }
-
-                        let y = pos.y + j;
-                        if y >= self.size_pixels.height {
-                            continue;
-                        }
-
-                        let xy = LcdXY { x, y };
-                        // TODO(jmmv): This is very inefficent on a pixel basis.
-                        self.fill(xy, xy)?;
+    /// Flushes any pending damaged areas to the LCD.
+    fn force_present_canvas(&mut self) -> io::Result<()> {
+        for (x1y1, x2y2) in std::mem::take(&mut self.damage) {
+            self.present_area(x1y1, x2y2)?;
+        }
+        Ok(())
+    }
+
+    /// Fills the rectangle at `x`/`y` of `width`x`height` pixels, which can fall partially or
+    /// completely outside of the LCD, with the current drawing color.
+    fn fill_clipped(&mut self, x: i32, y: i32, width: i32, height: i32) -> io::Result<()> {
+        let max_x = self.size_pixels.width as i32 - 1;
+        let max_y = self.size_pixels.height as i32 - 1;
+
+        let x1 = x.max(0);
+        let y1 = y.max(0);
+        let x2 = (x + width - 1).min(max_x);
+        let y2 = (y + height - 1).min(max_y);
+        if x1 > x2 || y1 > y2 {
+            return Ok(());
+        }
+
+        self.fill(
+            LcdXY { x: x1 as usize, y: y1 as usize },
+            LcdXY { x: x2 as usize, y: y2 as usize },
+        )
+    }
+
+    /// Writes a single character `ch` using `font`, rendering every pixel of the glyph as a block
+    /// of `scale`x`scale` pixels.  Pixels that fall outside of the LCD are skipped.
+    ///
+    /// The glyph is placed `offset` pixels away from `xy` in the direction of the text, and both
+    /// the offset and the glyph bitmap are rotated around `xy` by `rotation`.
+    ///
+    /// Consecutive pixels in a glyph row are drawn as a single rectangle to minimize the number of
+    /// fill operations.
+    fn write_char(
+        &mut self,
+        font: &Font,
+        xy: (i32, i32),
+        offset: i32,
+        ch: char,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        let glyph = font.glyph(ch);
+        let scale = i32::from(scale);
+        for j in 0..font.glyph_size.height {
+            let row = &glyph[j * font.stride..(j + 1) * font.stride];
+            let row_v = j as i32 * scale;
+
+            let mut run_start = None;
+            for i in 0..(font.glyph_size.width + 1) {
+                let bit = i < font.glyph_size.width && (row[i / 8] & (0x80 >> (i % 8))) != 0;
+                match (bit, run_start) {
+                    (true, None) => run_start = Some(i),
+                    (false, Some(start)) => {
+                        let run_u = offset + start as i32 * scale;
+                        let run_length = (i - start) as i32 * scale;
+                        let (dx, dy, width, height) =
+                            rotate_rect(rotation, run_u, row_v, run_length, scale);
+                        self.fill_clipped(xy.0 + dx, xy.1 + dy, width, height)?;
+                        run_start = None;
                     }
-                    mask >>= 1;
+                    _ => (),
                 }
             }
         }
@@ -359,6 +528,26 @@ where
     }
 }
 
+/// Rotates the rectangle of `width`x`height` pixels at `u`/`v` in text coordinates around the
+/// origin by `rotation` and returns its new top-left corner and size in LCD coordinates.
+///
+/// Pixel `(0, 0)` in text coordinates always maps to pixel `(0, 0)` in LCD coordinates so that the
+/// text rotates around the first pixel of its first glyph.
+fn rotate_rect(
+    rotation: TextRotation,
+    u: i32,
+    v: i32,
+    width: i32,
+    height: i32,
+) -> (i32, i32, i32, i32) {
+    match rotation {
+        TextRotation::Deg0 => (u, v, width, height),
+        TextRotation::Deg90 => (v, -(u + width - 1), height, width),
+        TextRotation::Deg180 => (-(u + width - 1), -(v + height - 1), width, height),
+        TextRotation::Deg270 => (-(v + height - 1), u, height, width),
+    }
+}
+
 impl<L> Drop for BufferedLcd<L>
 where
     L: Lcd,
@@ -406,6 +595,35 @@ where
         }
     }
 
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        self.lcd.set_brightness(pct)
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<()> {
+        let size = if self.lcd.set_rotation(rotation)? {
+            // The controller rotates the pixels itself so we can send them as they are.
+            self.rotation = ScreenRotation::Deg0;
+            self.lcd.info().0
+        } else {
+            self.rotation = rotation;
+            let (native, _stride) = self.lcd.info();
+            if rotation.is_transposed() {
+                LcdSize { width: native.height, height: native.width }
+            } else {
+                native
+            }
+        };
+
+        // The framebuffer keeps the same number of pixels so there is no need to reallocate it,
+        // and its contents become meaningless, which is fine because the caller must clear it.
+        self.size_pixels = size;
+        self.size_chars = size_chars(size, self.font);
+        self.damage.clear();
+        self.presented_valid = false;
+        self.row_buffer = Vec::with_capacity(size.width * self.stride);
+        Ok(())
+    }
+
     fn read_pixels(&mut self, xy: PixelsXY, size: SizeInPixels) -> io::Result<Self::ID> {
         self.assert_xy_size_in_range(xy, size);
         let x1y1 = self.clip_xy(xy).expect("Internal ops must receive valid coordinates");
@@ -447,12 +665,11 @@ where
             }
         }
 
+        self.damage(x1y1, x2y2);
+
         if self.sync {
-            self.lcd.set_data(x1y1, x2y2, pixels)?;
-        } else {
-            self.damage(x1y1, x2y2);
+            self.force_present_canvas()?;
         }
-
         Ok(())
     }
 
@@ -481,10 +698,12 @@ where
         let x1y1 = self.clip_xy(xy).expect("Internal ops must receive valid coordinates");
 
         self.without_sync(|self2| {
-            let mut pos = x1y1;
+            let font = self2.font;
+            let xy = (x1y1.x as i32, x1y1.y as i32);
+            let mut offset = 0;
             for ch in text.chars() {
-                self2.write_char(pos, ch)?;
-                pos.x += self2.font.glyph_size.width;
+                self2.write_char(font, xy, offset, ch, 1, TextRotation::Deg0)?;
+                offset += font.glyph_size.width as i32;
             }
             Ok(())
         })
@@ -522,4 +741,62 @@ where
             _ => Ok(()),
         }
     }
+
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        self.without_sync(|self2| {
+            let font = self2.text_font;
+            let xy = (i32::from(xy.x), i32::from(xy.y));
+            let width = self2.size_pixels.width as i32;
+            let height = self2.size_pixels.height as i32;
+            let mut offset = 0;
+            for ch in text.chars() {
+                // Stop as soon as the leading edge of the text leaves the LCD, which depends on
+                // the direction in which the text runs.
+                let past_edge = match rotation {
+                    TextRotation::Deg0 => xy.0 + offset >= width,
+                    TextRotation::Deg90 => xy.1 - offset < 0,
+                    TextRotation::Deg180 => xy.0 - offset < 0,
+                    TextRotation::Deg270 => xy.1 + offset >= height,
+                };
+                if past_edge {
+                    break;
+                }
+                self2.write_char(font, xy, offset, ch, scale, rotation)?;
+                offset += font.advance(ch) as i32 * i32::from(scale);
+            }
+            Ok(())
+        })
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        let fonts = all_fonts();
+        match fonts.get(name) {
+            Some(font) => {
+                self.text_font = font;
+                Ok(())
+            }
+            None => {
+                let mut valid = fonts.keys().copied().collect::<Vec<&'static str>>();
+                valid.sort();
+                Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown font: {}; valid names are: {}", name, valid.join(", ")),
+                ))
+            }
+        }
+    }
+
+    fn text_width(&self, text: &str) -> u16 {
+        u16::try_from(self.text_font.text_width(text)).unwrap_or(u16::MAX)
+    }
+
+    fn text_height(&self) -> u16 {
+        u16::try_from(self.text_font.glyph_size.height).unwrap_or(u16::MAX)
+    }
 }
@@ -17,18 +17,37 @@
 
 use super::testutils::*;
 use super::*;
-use crate::console::graphics::RasterOps;
-use crate::console::{CharsXY, PixelsXY, SizeInPixels};
-use crate::gfx::lcd::fonts::{FONT_16X16, FONT_5X8};
+use crate::console::drawing::ClippedRasterOps;
+use crate::console::graphics::{GraphicsConsole, RasterOps};
+use crate::console::{
+    CharsXY, ClearType, ClipRect, Console, PixelsXY, ScreenRotation, SizeInPixels, TextRotation,
+    RGB,
+};
+use crate::gfx::lcd::fonts::{FONT_16X16, FONT_5X8, FONT_5X8_PROPORTIONAL};
+use std::cell::RefCell;
+use std::cmp;
+use std::io;
+use std::rc::Rc;
 
 #[test]
 fn test_new_does_nothing() {
     Tester::new(size(8, 4)).check()
 }
 
+#[test]
+fn test_new_rejects_proportional_font() {
+    match BufferedLcd::new(LcdRecorder::new(size(20, 30)), &FONT_5X8_PROPORTIONAL) {
+        Ok(_) => panic!("Proportional fonts must be rejected"),
+        Err(e) => {
+            assert_eq!(io::ErrorKind::InvalidInput, e.kind());
+            assert_eq!("Cannot use proportional font 5x8p as the console font", e.to_string());
+        }
+    }
+}
+
 #[test]
 fn test_clip_xy() {
-    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8);
+    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
 
     assert_eq!(Some(xy(0, 0)), lcd.clip_xy(PixelsXY::new(0, 0)));
     assert_eq!(Some(xy(10, 20)), lcd.clip_xy(PixelsXY::new(10, 20)));
@@ -42,7 +61,7 @@ fn test_clip_xy() {
 
 #[test]
 fn test_clamp_xy() {
-    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8);
+    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
 
     assert_eq!(xy(0, 0), lcd.clamp_xy(PixelsXY::new(0, 0)));
     assert_eq!(xy(10, 20), lcd.clamp_xy(PixelsXY::new(10, 20)));
@@ -56,7 +75,7 @@ fn test_clamp_xy() {
 
 #[test]
 fn test_clip_x2y2() {
-    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8);
+    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
 
     assert_eq!(Some(xy(9, 19)), lcd.clip_x2y2(PixelsXY::new(0, 0), SizeInPixels::new(10, 20)));
     assert_eq!(Some(xy(19, 39)), lcd.clip_x2y2(PixelsXY::new(10, 20), SizeInPixels::new(10, 20)));
@@ -80,7 +99,7 @@ fn test_clip_x2y2() {
 
 #[test]
 fn test_fb_addr() {
-    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8);
+    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
 
     assert_eq!(0, lcd.fb_addr(0, 0));
     assert_eq!(3, lcd.fb_addr(1, 0));
@@ -112,6 +131,150 @@ fn test_damage_extend_up_left() {
         .check();
 }
 
+#[test]
+fn test_damage_keeps_distant_areas_separate() {
+    Tester::new(size(40, 30))
+        .op(|l| l.set_sync(false))
+        .op(|l| l.set_draw_color((255, 255, 255)))
+        .op(|l| l.fill(xy(2, 1), xy(3, 2)).unwrap())
+        .op(|l| l.fill(xy(30, 20), xy(35, 25)).unwrap())
+        .op(|l| l.fill(xy(4, 1), xy(5, 2)).unwrap())
+        .expect_damage(xy(30, 20), xy(35, 25))
+        .expect_damage(xy(2, 1), xy(5, 2))
+        .ignore_pixels()
+        .check();
+}
+
+#[test]
+fn test_damage_coalesces_transitively() {
+    Tester::new(size(40, 30))
+        .op(|l| l.set_sync(false))
+        .op(|l| l.set_draw_color((255, 255, 255)))
+        .op(|l| l.fill(xy(0, 0), xy(9, 9)).unwrap())
+        .op(|l| l.fill(xy(20, 0), xy(29, 9)).unwrap())
+        .op(|l| l.fill(xy(10, 0), xy(19, 9)).unwrap())
+        .expect_damage(xy(0, 0), xy(29, 9))
+        .ignore_pixels()
+        .check();
+}
+
+#[test]
+fn test_damage_limits_number_of_areas() {
+    let mut t = Tester::new(size(MAX_DAMAGED_AREAS * 70, 30))
+        .op(|l| l.set_sync(false))
+        .op(|l| l.set_draw_color((255, 255, 255)));
+    for i in 0..MAX_DAMAGED_AREAS {
+        t = t.op(move |l| l.fill(xy(i * 70, 0), xy(i * 70, 0)).unwrap());
+    }
+    t = t.op(|l| l.fill(xy(35, 29), xy(35, 29)).unwrap());
+    for i in 1..MAX_DAMAGED_AREAS {
+        t = t.expect_damage(xy(i * 70, 0), xy(i * 70, 0));
+    }
+    t.expect_damage(xy(0, 0), xy(35, 29)).ignore_pixels().check();
+}
+
+#[test]
+fn test_present_only_changed_pixels() {
+    Tester::new(size(6, 4))
+        .op(|l| {
+            l.set_draw_color((1, 2, 3));
+            l.clear().unwrap();
+            l.set_sync(false);
+            l.fill(xy(0, 0), xy(5, 3)).unwrap();
+            l.set_draw_color((4, 5, 6));
+            l.fill(xy(1, 1), xy(1, 1)).unwrap();
+            l.fill(xy(3, 2), xy(3, 2)).unwrap();
+            l.set_draw_color((1, 2, 3));
+            l.fill(xy(3, 2), xy(3, 2)).unwrap();
+            l.force_present_canvas().unwrap();
+        })
+        .ignore_pixels()
+        .expect_op("set_data: from=(0, 0), to=(5, 3), data=[1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3, 1, 2, 3]")
+        .expect_op("set_data: from=(1, 1), to=(1, 1), data=[4, 5, 6]")
+        .check();
+}
+
+#[test]
+fn test_present_everything_until_lcd_contents_are_known() {
+    Tester::new(size(6, 4))
+        .op(|l| {
+            l.set_draw_color((0, 0, 0));
+            l.fill(xy(0, 0), xy(1, 0)).unwrap();
+            l.fill(xy(0, 0), xy(1, 0)).unwrap();
+        })
+        .expect_op("set_data: from=(0, 0), to=(1, 0), data=[0, 0, 0, 0, 0, 0]")
+        .expect_op("set_data: from=(0, 0), to=(1, 0), data=[0, 0, 0, 0, 0, 0]")
+        .check();
+}
+
+#[test]
+fn test_set_rotation_forgets_lcd_contents() {
+    Tester::new(size(2, 2))
+        .op(|l| {
+            l.set_draw_color((0, 0, 0));
+            l.clear().unwrap();
+            l.set_rotation(ScreenRotation::Deg90).unwrap();
+            l.clear().unwrap();
+        })
+        .expect_op("set_data: from=(0, 0), to=(1, 1), data=[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]")
+        .expect_op("set_data: from=(0, 0), to=(1, 1), data=[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]")
+        .check();
+}
+
+/// Creates a console backed by a 128x128 LCD, like the ST7735S, that counts the data transfers
+/// into the returned stats, which start at zero after the console is initialized.
+fn new_counting_console(
+) -> (GraphicsConsole<NoInput, BufferedLcd<CountingLcd>>, Rc<RefCell<TransferStats>>) {
+    let stats = Rc::from(RefCell::from(TransferStats::default()));
+    let lcd = CountingLcd::new(size(128, 128), stats.clone());
+    let buffered = BufferedLcd::new(lcd, &FONT_5X8).unwrap();
+    let console = GraphicsConsole::new(NoInput {}, buffered, None, None).unwrap();
+    *stats.borrow_mut() = TransferStats::default();
+    (console, stats)
+}
+
+#[test]
+fn test_transfers_print_one_char() {
+    let (mut console, stats) = new_counting_console();
+    console.locate(CharsXY::new(10, 5)).unwrap();
+    *stats.borrow_mut() = TransferStats::default();
+
+    console.print("x").unwrap();
+
+    // Sending the bounding box of all damaged areas would take 1760 bytes because the cursor moves
+    // from the middle of one line to the beginning of the next one.
+    assert_eq!(TransferStats { transfers: 2, bytes: 160 }, *stats.borrow());
+}
+
+#[test]
+fn test_transfers_scroll_one_line() {
+    let (mut console, stats) = new_counting_console();
+    console.locate(CharsXY::new(0, 13)).unwrap();
+    console.print("first").unwrap();
+    console.print("second").unwrap();
+    *stats.borrow_mut() = TransferStats::default();
+
+    console.print("third").unwrap();
+
+    // Sending the bounding box of all damaged areas would take 32768 bytes because the whole screen
+    // scrolls, but only the rows that contain text change.
+    assert_eq!(TransferStats { transfers: 1, bytes: 1320 }, *stats.borrow());
+}
+
+#[test]
+fn test_transfers_cls() {
+    let (mut console, stats) = new_counting_console();
+    console.print("Hello").unwrap();
+    console.print("World").unwrap();
+    *stats.borrow_mut() = TransferStats::default();
+
+    console.clear(ClearType::All).unwrap();
+
+    // Sending all damaged areas would take 32848 bytes for the whole screen and the cursor, but
+    // only the rows that contained text change.
+    assert_eq!(TransferStats { transfers: 2, bytes: 1280 }, *stats.borrow());
+}
+
 #[test]
 fn test_fill_one_pixel_sync() {
     Tester::new(size(8, 4))
@@ -195,13 +358,126 @@ fn test_force_present_canvas_damage() {
 
 #[test]
 fn test_get_info() {
-    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8);
+    let lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
     let info = lcd.get_info();
     assert_eq!(info.size_pixels, SizeInPixels::new(100, 200));
     assert_eq!(info.glyph_size, SizeInPixels::new(5, 8));
     assert_eq!(info.size_chars, CharsXY { x: 20, y: 25 });
 }
 
+#[test]
+fn test_native_xy_roundtrip() {
+    for rotation in [
+        ScreenRotation::Deg0,
+        ScreenRotation::Deg90,
+        ScreenRotation::Deg180,
+        ScreenRotation::Deg270,
+    ] {
+        let native = size(4, 3);
+        let (width, height) = if rotation.is_transposed() { (3, 4) } else { (4, 3) };
+        for y in 0..height {
+            for x in 0..width {
+                let native_xy = to_native_xy(rotation, native, xy(x, y));
+                assert!(native_xy.x < native.width && native_xy.y < native.height);
+                assert_eq!(xy(x, y), from_native_xy(rotation, native, native_xy));
+            }
+        }
+    }
+}
+
+#[test]
+fn test_set_rotation_get_info() {
+    let mut lcd = BufferedLcd::new(LcdRecorder::new(size(100, 200)), &FONT_5X8).unwrap();
+
+    lcd.set_rotation(ScreenRotation::Deg90).unwrap();
+    let info = lcd.get_info();
+    assert_eq!(info.size_pixels, SizeInPixels::new(200, 100));
+    assert_eq!(info.size_chars, CharsXY { x: 40, y: 12 });
+
+    lcd.set_rotation(ScreenRotation::Deg180).unwrap();
+    let info = lcd.get_info();
+    assert_eq!(info.size_pixels, SizeInPixels::new(100, 200));
+    assert_eq!(info.size_chars, CharsXY { x: 20, y: 25 });
+
+    lcd.set_rotation(ScreenRotation::Deg270).unwrap();
+    let info = lcd.get_info();
+    assert_eq!(info.size_pixels, SizeInPixels::new(200, 100));
+    assert_eq!(info.size_chars, CharsXY { x: 40, y: 12 });
+}
+
+/// Puts a 2x2 block of distinct pixels at the top-left corner of a 4x3 LCD rotated by `rotation`
+/// and expects the LCD to receive `exp_op`.
+fn do_set_rotation_put_pixels_test(rotation: ScreenRotation, exp_op: &str) {
+    Tester::new(size(4, 3))
+        .op(|l| {
+            l.set_rotation(rotation).unwrap();
+            let pixels = vec![1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4];
+            l.put_pixels(PixelsXY { x: 0, y: 0 }, &(pixels, SizeInPixels::new(2, 2))).unwrap();
+        })
+        .ignore_pixels()
+        .expect_op(exp_op)
+        .check();
+}
+
+#[test]
+fn test_set_rotation_put_pixels_0() {
+    do_set_rotation_put_pixels_test(
+        ScreenRotation::Deg0,
+        "set_data: from=(0, 0), to=(1, 1), data=[1, 1, 1, 2, 2, 2, 3, 3, 3, 4, 4, 4]",
+    );
+}
+
+#[test]
+fn test_set_rotation_put_pixels_90() {
+    do_set_rotation_put_pixels_test(
+        ScreenRotation::Deg90,
+        "set_data: from=(2, 0), to=(3, 1), data=[3, 3, 3, 1, 1, 1, 4, 4, 4, 2, 2, 2]",
+    );
+}
+
+#[test]
+fn test_set_rotation_put_pixels_180() {
+    do_set_rotation_put_pixels_test(
+        ScreenRotation::Deg180,
+        "set_data: from=(2, 1), to=(3, 2), data=[4, 4, 4, 3, 3, 3, 2, 2, 2, 1, 1, 1]",
+    );
+}
+
+#[test]
+fn test_set_rotation_put_pixels_270() {
+    do_set_rotation_put_pixels_test(
+        ScreenRotation::Deg270,
+        "set_data: from=(0, 1), to=(1, 2), data=[2, 2, 2, 4, 4, 4, 1, 1, 1, 3, 3, 3]",
+    );
+}
+
+#[test]
+fn test_set_rotation_present_canvas() {
+    Tester::new(size(4, 3))
+        .op(|l| {
+            l.set_rotation(ScreenRotation::Deg90).unwrap();
+            l.set_sync(false);
+            l.set_draw_color((7, 8, 9));
+            l.draw_pixel(PixelsXY::new(2, 3)).unwrap();
+            l.present_canvas().unwrap();
+        })
+        .ignore_pixels()
+        .expect_op("set_data: from=(0, 2), to=(0, 2), data=[7, 8, 9]")
+        .check();
+}
+
+#[test]
+fn test_set_brightness() {
+    Tester::new(size(4, 3))
+        .op(|l| {
+            l.set_brightness(80).unwrap();
+            l.set_brightness(0).unwrap();
+        })
+        .expect_op("set_brightness: pct=80")
+        .expect_op("set_brightness: pct=0")
+        .check();
+}
+
 #[test]
 fn test_clear() {
     Tester::new(size(2, 3))
@@ -294,6 +570,157 @@ fn test_put_pixels_no_sync() {
         .check();
 }
 
+/// Computes a distinct color for the pixel at `x` and `y` of a test pattern.
+fn pattern_color(x: usize, y: usize) -> RGB {
+    (10 + x as u8 * 10, 100 + y as u8 * 10, 200)
+}
+
+/// Draws a test pattern of `width`x`height` pixels with its top-left corner at the origin.
+fn draw_pattern(l: &mut BufferedLcd<LcdRecorder>, width: usize, height: usize) {
+    for y in 0..height {
+        for x in 0..width {
+            l.set_draw_color(pattern_color(x, y));
+            l.draw_pixel(PixelsXY::new(x as i16, y as i16)).unwrap();
+        }
+    }
+}
+
+/// Copies a 3x3 test pattern at `2x2` by `dx` and `dy` pixels and checks that the source region
+/// was read in full before writing the overlapping target region.
+fn do_copy_pixels_overlap_test(dx: i16, dy: i16) {
+    let mut t = Tester::new(size(8, 8))
+        .op(|l| {
+            l.set_sync(false);
+            for y in 0..3 {
+                for x in 0..3 {
+                    l.set_draw_color(pattern_color(x, y));
+                    l.draw_pixel(PixelsXY::new(2 + x as i16, 2 + y as i16)).unwrap();
+                }
+            }
+            l.copy_pixels(
+                PixelsXY::new(2, 2),
+                SizeInPixels::new(3, 3),
+                PixelsXY::new(2 + dx, 2 + dy),
+            )
+            .unwrap();
+        })
+        .expect_damage(
+            xy((2 + cmp::min(dx, 0)) as usize, (2 + cmp::min(dy, 0)) as usize),
+            xy((4 + cmp::max(dx, 0)) as usize, (4 + cmp::max(dy, 0)) as usize),
+        );
+    for y in 0..3 {
+        for x in 0..3 {
+            t = t.expect_pixel(xy(2 + x, 2 + y), pattern_color(x, y));
+        }
+    }
+    for y in 0..3 {
+        for x in 0..3 {
+            let target = xy((2 + dx + x as i16) as usize, (2 + dy + y as i16) as usize);
+            t = t.expect_pixel(target, pattern_color(x, y));
+        }
+    }
+    t.check();
+}
+
+#[test]
+fn test_copy_pixels_overlap_right() {
+    do_copy_pixels_overlap_test(1, 0);
+}
+
+#[test]
+fn test_copy_pixels_overlap_left() {
+    do_copy_pixels_overlap_test(-1, 0);
+}
+
+#[test]
+fn test_copy_pixels_overlap_down() {
+    do_copy_pixels_overlap_test(0, 1);
+}
+
+#[test]
+fn test_copy_pixels_overlap_up() {
+    do_copy_pixels_overlap_test(0, -1);
+}
+
+#[test]
+fn test_copy_pixels_sync() {
+    Tester::new(size(4, 1))
+        .op(|l| {
+            let pixels = vec![90, 80, 70, 60, 50, 40];
+            l.put_pixels(PixelsXY::new(0, 0), &(pixels, SizeInPixels::new(2, 1))).unwrap();
+            l.copy_pixels(PixelsXY::new(0, 0), SizeInPixels::new(2, 1), PixelsXY::new(1, 0))
+                .unwrap();
+        })
+        .expect_pixel(xy(0, 0), (90, 80, 70))
+        .expect_pixel(xy(1, 0), (90, 80, 70))
+        .expect_pixel(xy(2, 0), (60, 50, 40))
+        .expect_op("set_data: from=(0, 0), to=(1, 0), data=[90, 80, 70, 60, 50, 40]")
+        .expect_op("set_data: from=(1, 0), to=(2, 0), data=[90, 80, 70, 60, 50, 40]")
+        .check();
+}
+
+#[test]
+fn test_copy_pixels_clip() {
+    let mut t = Tester::new(size(6, 4))
+        .op(|l| {
+            l.set_sync(false);
+            draw_pattern(l, 3, 3);
+            // Only the top-left pixel of the pattern fits in both the source and target regions.
+            l.copy_pixels(PixelsXY::new(-1, -1), SizeInPixels::new(3, 3), PixelsXY::new(4, 2))
+                .unwrap();
+            // Nothing to copy because the source region is fully outside of the LCD.
+            l.copy_pixels(PixelsXY::new(6, 0), SizeInPixels::new(3, 3), PixelsXY::new(0, 0))
+                .unwrap();
+        })
+        .expect_damage(xy(0, 0), xy(5, 3));
+    for y in 0..3 {
+        for x in 0..3 {
+            t = t.expect_pixel(xy(x, y), pattern_color(x, y));
+        }
+    }
+    t.expect_pixel(xy(5, 3), pattern_color(0, 0)).check();
+}
+
+#[test]
+fn test_scroll_pixels() {
+    let mut t = Tester::new(size(4, 3))
+        .op(|l| {
+            l.set_sync(false);
+            draw_pattern(l, 4, 3);
+            l.set_draw_color((1, 2, 3));
+            l.scroll_pixels(1, -1).unwrap();
+        })
+        .expect_damage(xy(0, 0), xy(3, 2));
+    for y in 0..3 {
+        for x in 0..4 {
+            if x == 0 || y == 2 {
+                t = t.expect_pixel(xy(x, y), (1, 2, 3));
+            } else {
+                t = t.expect_pixel(xy(x, y), pattern_color(x - 1, y + 1));
+            }
+        }
+    }
+    t.check();
+}
+
+#[test]
+fn test_scroll_pixels_past_edges() {
+    let mut t = Tester::new(size(4, 3))
+        .op(|l| {
+            l.set_sync(false);
+            draw_pattern(l, 4, 3);
+            l.set_draw_color((1, 2, 3));
+            l.scroll_pixels(-10, 0).unwrap();
+        })
+        .expect_damage(xy(0, 0), xy(3, 2));
+    for y in 0..3 {
+        for x in 0..4 {
+            t = t.expect_pixel(xy(x, y), (1, 2, 3));
+        }
+    }
+    t.check();
+}
+
 #[test]
 fn test_write_text_sync() {
     Tester::with_font(size(20, 30), &FONT_5X8)
@@ -469,6 +896,337 @@ fn test_write_text_wide_font() {
         .check();
 }
 
+#[test]
+fn test_draw_text_default_font() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(2, 3), "!!", 1, TextRotation::Deg0).unwrap()
+        })
+        .expect_damage(xy(4, 3), xy(9, 8))
+        .expect_pixel(xy(4, 3), (250, 251, 252))
+        .expect_pixel(xy(4, 4), (250, 251, 252))
+        .expect_pixel(xy(4, 5), (250, 251, 252))
+        .expect_pixel(xy(4, 6), (250, 251, 252))
+        .expect_pixel(xy(4, 8), (250, 251, 252))
+        .expect_pixel(xy(9, 3), (250, 251, 252))
+        .expect_pixel(xy(9, 4), (250, 251, 252))
+        .expect_pixel(xy(9, 5), (250, 251, 252))
+        .expect_pixel(xy(9, 6), (250, 251, 252))
+        .expect_pixel(xy(9, 8), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_draw_text_proportional_font() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_text_font("5x8p").unwrap();
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(2, 3), "!!", 1, TextRotation::Deg0).unwrap()
+        })
+        .expect_damage(xy(2, 3), xy(4, 8))
+        .expect_pixel(xy(2, 3), (250, 251, 252))
+        .expect_pixel(xy(2, 4), (250, 251, 252))
+        .expect_pixel(xy(2, 5), (250, 251, 252))
+        .expect_pixel(xy(2, 6), (250, 251, 252))
+        .expect_pixel(xy(2, 8), (250, 251, 252))
+        .expect_pixel(xy(4, 3), (250, 251, 252))
+        .expect_pixel(xy(4, 4), (250, 251, 252))
+        .expect_pixel(xy(4, 5), (250, 251, 252))
+        .expect_pixel(xy(4, 6), (250, 251, 252))
+        .expect_pixel(xy(4, 8), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_draw_text_clip() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_text_font("5x8p").unwrap();
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(-1, -3), "!!", 1, TextRotation::Deg0).unwrap();
+            l.draw_text(PixelsXY::new(19, 29), "!!", 1, TextRotation::Deg0).unwrap();
+        })
+        .expect_damage(xy(1, 0), xy(1, 2))
+        .expect_damage(xy(19, 29), xy(19, 29))
+        .expect_pixel(xy(1, 0), (250, 251, 252))
+        .expect_pixel(xy(1, 2), (250, 251, 252))
+        .expect_pixel(xy(19, 29), (250, 251, 252))
+        .check();
+}
+
+/// Expected rendering of the `#` glyph of `FONT_5X8` with a scale of 2, as drawn by hand.
+const HASH_5X8_SCALE_2: &[&str] = &[
+    "    ##  ##",
+    "    ##  ##",
+    "  ##  ##",
+    "  ##  ##",
+    "##########",
+    "##########",
+    "  ##  ##",
+    "  ##  ##",
+    "##########",
+    "##########",
+    "  ##  ##",
+    "  ##  ##",
+    "##  ##",
+    "##  ##",
+];
+
+#[test]
+fn test_draw_text_scale_2() {
+    let mut t = Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(3, 4), "#", 2, TextRotation::Deg0).unwrap()
+        })
+        .expect_damage(xy(3, 4), xy(12, 17));
+    for (y, row) in HASH_5X8_SCALE_2.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            if ch == '#' {
+                t = t.expect_pixel(xy(3 + x, 4 + y), (250, 251, 252));
+            }
+        }
+    }
+    t.check();
+}
+
+#[test]
+fn test_draw_text_scale_proportional_advance() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_text_font("5x8p").unwrap();
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(0, 0), "!!", 3, TextRotation::Deg0).unwrap()
+        })
+        .expect_damage(xy(0, 0), xy(8, 17))
+        .op(|l| {
+            // Both exclamation marks are 3 pixels wide and separated by 3 blank pixels.
+            for y in 0..18 {
+                let on = !(12..15).contains(&y);
+                for x in 0..9 {
+                    let offset = l.fb_addr(x, y);
+                    let expected = on && !(3..6).contains(&x);
+                    assert_eq!(expected, l.fb[offset] != 0, "Pixel at {}x{}", x, y);
+                }
+            }
+        })
+        .ignore_pixels()
+        .check();
+}
+
+#[test]
+fn test_draw_text_scale_clip() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_text_font("5x8p").unwrap();
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(-1, -10), "!", 2, TextRotation::Deg0).unwrap();
+            l.draw_text(PixelsXY::new(19, 28), "!", 4, TextRotation::Deg0).unwrap();
+        })
+        .expect_damage(xy(0, 0), xy(0, 1))
+        .expect_damage(xy(19, 28), xy(19, 29))
+        .expect_pixel(xy(0, 0), (250, 251, 252))
+        .expect_pixel(xy(0, 1), (250, 251, 252))
+        .expect_pixel(xy(19, 28), (250, 251, 252))
+        .expect_pixel(xy(19, 29), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_draw_text_scale_sync() {
+    // The exclamation mark in FONT_5X8 has 4 lit rows, a blank row, and one more lit row.
+    let mut data = vec![];
+    for y in 0..12 {
+        for _ in 0..2 {
+            if y == 8 || y == 9 {
+                data.extend_from_slice(&[0, 0, 0]);
+            } else {
+                data.extend_from_slice(&[250, 251, 252]);
+            }
+        }
+    }
+
+    let mut t = Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(0, 0), "!", 2, TextRotation::Deg0).unwrap()
+        })
+        .expect_op(&format!("set_data: from=(4, 0), to=(5, 11), data={:?}", data));
+    for y in (0..8).chain(10..12) {
+        t = t.expect_pixel(xy(4, y), (250, 251, 252)).expect_pixel(xy(5, y), (250, 251, 252));
+    }
+    t.check();
+}
+
+/// Expected rendering of the `F` glyph of `FONT_5X8` at every rotation, as drawn by hand.
+const F_5X8_DEG0: &[&str] = &["#####", " #  #", " ##", " #", " #", "###"];
+const F_5X8_DEG90: &[&str] = &["##", "#", "# #  #", "######", "#    #"];
+const F_5X8_DEG180: &[&str] = &["  ###", "   #", "   #", "  ##", "#  #", "#####"];
+const F_5X8_DEG270: &[&str] = &["#    #", "######", "#  # #", "     #", "    ##"];
+
+/// Draws the `F` glyph at `10x10` with `rotation` and checks that it matches `expected`, whose
+/// top-left corner must be at `origin`.
+fn do_draw_text_rotation_test(rotation: TextRotation, origin: (usize, usize), expected: &[&str]) {
+    let height = expected.len();
+    let width = expected.iter().map(|row| row.len()).max().unwrap();
+    let mut t = Tester::with_font(size(20, 20), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            l.draw_text(PixelsXY::new(10, 10), "F", 1, rotation).unwrap()
+        })
+        .expect_damage(xy(origin.0, origin.1), xy(origin.0 + width - 1, origin.1 + height - 1));
+    for (y, row) in expected.iter().enumerate() {
+        for (x, ch) in row.chars().enumerate() {
+            if ch == '#' {
+                t = t.expect_pixel(xy(origin.0 + x, origin.1 + y), (250, 251, 252));
+            }
+        }
+    }
+    t.check();
+}
+
+#[test]
+fn test_draw_text_rotation_0() {
+    do_draw_text_rotation_test(TextRotation::Deg0, (10, 10), F_5X8_DEG0);
+}
+
+#[test]
+fn test_draw_text_rotation_90() {
+    do_draw_text_rotation_test(TextRotation::Deg90, (10, 6), F_5X8_DEG90);
+}
+
+#[test]
+fn test_draw_text_rotation_180() {
+    do_draw_text_rotation_test(TextRotation::Deg180, (6, 5), F_5X8_DEG180);
+}
+
+#[test]
+fn test_draw_text_rotation_270() {
+    do_draw_text_rotation_test(TextRotation::Deg270, (5, 10), F_5X8_DEG270);
+}
+
+#[test]
+fn test_draw_text_rotation_clip() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            // The second character starts past the top edge so it is skipped altogether.
+            l.draw_text(PixelsXY::new(0, 3), "!!", 1, TextRotation::Deg90).unwrap();
+            // The second character starts past the bottom edge so it is skipped altogether.
+            l.draw_text(PixelsXY::new(19, 27), "!!", 1, TextRotation::Deg270).unwrap();
+        })
+        .expect_damage(xy(0, 1), xy(5, 1))
+        .expect_damage(xy(14, 29), xy(19, 29))
+        .expect_pixel(xy(0, 1), (250, 251, 252))
+        .expect_pixel(xy(1, 1), (250, 251, 252))
+        .expect_pixel(xy(2, 1), (250, 251, 252))
+        .expect_pixel(xy(3, 1), (250, 251, 252))
+        .expect_pixel(xy(5, 1), (250, 251, 252))
+        .expect_pixel(xy(14, 29), (250, 251, 252))
+        .expect_pixel(xy(16, 29), (250, 251, 252))
+        .expect_pixel(xy(17, 29), (250, 251, 252))
+        .expect_pixel(xy(18, 29), (250, 251, 252))
+        .expect_pixel(xy(19, 29), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_clipped_draw_line() {
+    let clip = ClipRect::new(PixelsXY::new(5, 5), 5, 5);
+    Tester::new(size(20, 20))
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            let mut clipped = ClippedRasterOps::new(l, Some(clip));
+            clipped.draw_line(PixelsXY::new(0, 0), PixelsXY::new(19, 19)).unwrap();
+            clipped.draw_line(PixelsXY::new(0, 7), PixelsXY::new(19, 7)).unwrap();
+        })
+        .expect_damage(xy(5, 5), xy(9, 9))
+        .expect_pixel(xy(5, 5), (250, 251, 252))
+        .expect_pixel(xy(6, 6), (250, 251, 252))
+        .expect_pixel(xy(8, 8), (250, 251, 252))
+        .expect_pixel(xy(9, 9), (250, 251, 252))
+        .expect_pixel(xy(5, 7), (250, 251, 252))
+        .expect_pixel(xy(6, 7), (250, 251, 252))
+        .expect_pixel(xy(7, 7), (250, 251, 252))
+        .expect_pixel(xy(8, 7), (250, 251, 252))
+        .expect_pixel(xy(9, 7), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_clipped_draw_text() {
+    let clip = ClipRect::new(PixelsXY::new(10, 12), 3, 10);
+    Tester::with_font(size(20, 20), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            let mut clipped = ClippedRasterOps::new(l, Some(clip));
+            clipped.draw_text(PixelsXY::new(10, 10), "F", 1, TextRotation::Deg0).unwrap();
+        })
+        .expect_damage(xy(2, 2), xy(18, 18))
+        .expect_pixel(xy(11, 12), (250, 251, 252))
+        .expect_pixel(xy(12, 12), (250, 251, 252))
+        .expect_pixel(xy(11, 13), (250, 251, 252))
+        .expect_pixel(xy(11, 14), (250, 251, 252))
+        .expect_pixel(xy(10, 15), (250, 251, 252))
+        .expect_pixel(xy(11, 15), (250, 251, 252))
+        .expect_pixel(xy(12, 15), (250, 251, 252))
+        .check();
+}
+
+#[test]
+fn test_clipped_draw_text_outside() {
+    let clip = ClipRect::new(PixelsXY::new(0, 0), 5, 5);
+    Tester::with_font(size(20, 20), &FONT_5X8)
+        .op(|l| {
+            l.set_sync(false);
+            l.set_draw_color((250, 251, 252));
+            let mut clipped = ClippedRasterOps::new(l, Some(clip));
+            clipped.draw_text(PixelsXY::new(14, 10), "F", 1, TextRotation::Deg0).unwrap();
+        })
+        .check();
+}
+
+#[test]
+fn test_set_text_font_unknown() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            let e = l.set_text_font("foo").unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidInput, e.kind());
+            assert_eq!("Unknown font: foo; valid names are: 16x16, 5x8, 5x8p", e.to_string());
+            assert_eq!(10, l.text_width("!!"));
+        })
+        .check();
+}
+
+#[test]
+fn test_text_width() {
+    Tester::with_font(size(20, 30), &FONT_5X8)
+        .op(|l| {
+            assert_eq!(0, l.text_width(""));
+            assert_eq!(15, l.text_width("i W"));
+
+            l.set_text_font("5x8p").unwrap();
+            assert_eq!(0, l.text_width(""));
+            assert_eq!(4 + 3 + 6, l.text_width("i W"));
+
+            l.set_text_font("16x16").unwrap();
+            assert_eq!(48, l.text_width("i W"));
+        })
+        .check();
+}
+
 #[test]
 fn test_draw_circle_sync() {
     Tester::new(size(20, 30))
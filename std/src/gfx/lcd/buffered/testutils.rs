@@ -15,10 +15,14 @@
 
 //! Utilities to implement tests for the `BufferedLcd`.
 
-use crate::console::RGB;
+use crate::console::graphics::InputOps;
+use crate::console::{Key, RGB};
 use crate::gfx::lcd::fonts::Font;
-use crate::gfx::lcd::{AsByteSlice, BufferedLcd, Lcd, LcdSize, LcdXY};
+use crate::gfx::lcd::{AsByteSlice, BufferedLcd, Lcd, LcdSize, LcdXY, RGB565Pixel};
+use async_trait::async_trait;
+use std::cell::RefCell;
 use std::io;
+use std::rc::Rc;
 
 /// Data for one pixel encoded as RGB888.
 #[cfg(test)]
@@ -75,11 +79,80 @@ impl Lcd for LcdRecorder {
         ));
         Ok(())
     }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        self.ops.push(format!("set_brightness: pct={}", pct));
+        Ok(())
+    }
+}
+
+/// Counters of the data transfers received by a `CountingLcd`.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct TransferStats {
+    /// Number of separate `set_data` calls.
+    pub(super) transfers: usize,
+
+    /// Total number of bytes of pixel data.
+    pub(super) bytes: usize,
+}
+
+/// Mock LCD that counts the data transfers it receives, which is what a slow bus would carry.
+///
+/// The pixel data is encoded as RGB565 to match the format of real LCDs.
+pub(super) struct CountingLcd {
+    size: LcdSize,
+    stats: Rc<RefCell<TransferStats>>,
+}
+
+impl CountingLcd {
+    /// Creates a mock LCD of the given `size` that updates `stats` on every transfer.
+    pub(super) fn new(size: LcdSize, stats: Rc<RefCell<TransferStats>>) -> Self {
+        Self { size, stats }
+    }
+}
+
+impl Lcd for CountingLcd {
+    type Pixel = RGB565Pixel;
+
+    fn info(&self) -> (LcdSize, usize) {
+        (self.size, 2)
+    }
+
+    fn encode(&self, rgb: RGB) -> Self::Pixel {
+        RGB565Pixel([rgb.0 ^ rgb.1, rgb.2])
+    }
+
+    fn set_data(&mut self, _x1y1: LcdXY, _x2y2: LcdXY, data: &[u8]) -> io::Result<()> {
+        let mut stats = self.stats.borrow_mut();
+        stats.transfers += 1;
+        stats.bytes += data.len();
+        Ok(())
+    }
+}
+
+/// Input operations that never return any key presses.
+pub(super) struct NoInput {}
+
+#[async_trait(?Send)]
+impl InputOps for NoInput {
+    async fn poll_key(&mut self) -> io::Result<Option<Key>> {
+        Ok(None)
+    }
+
+    async fn read_key(&mut self) -> io::Result<Key> {
+        Ok(Key::Eof)
+    }
 }
 
 /// A font with no data.
-const FONT_ZERO: Font =
-    Font { name: "zero", glyph_size: LcdSize { width: 8, height: 8 }, stride: 1, data: &[] };
+const FONT_ZERO: Font = Font {
+    name: "zero",
+    glyph_size: LcdSize { width: 8, height: 8 },
+    stride: 1,
+    segments: &[],
+    advances: None,
+    replacement: None,
+};
 
 /// Builder pattern to define and execute `BufferedLcd` tests.
 #[must_use]
@@ -87,7 +160,7 @@ pub(super) struct Tester {
     size: LcdSize,
     buffered: BufferedLcd<LcdRecorder>,
     exp_fb: Vec<u8>,
-    exp_damage: Option<(LcdXY, LcdXY)>,
+    exp_damage: Vec<(LcdXY, LcdXY)>,
     exp_ops: Vec<String>,
 }
 
@@ -103,9 +176,9 @@ impl Tester {
         let fb_size = size.width * size.height * 3;
         Self {
             size,
-            buffered: BufferedLcd::new(LcdRecorder::new(size), font),
+            buffered: BufferedLcd::new(LcdRecorder::new(size), font).unwrap(),
             exp_fb: vec![0; fb_size],
-            exp_damage: None,
+            exp_damage: vec![],
             exp_ops: vec![],
         }
     }
@@ -129,10 +202,9 @@ impl Tester {
     }
 
     /// Records that the area between `x1y1` and `x2y2` is damaged after all `op`s have been
-    /// executed.
+    /// executed.  Multiple areas must be recorded in the order in which they are tracked.
     pub(super) fn expect_damage(mut self, x1y1: LcdXY, x2y2: LcdXY) -> Self {
-        assert!(self.exp_damage.is_none());
-        self.exp_damage = Some((x1y1, x2y2));
+        self.exp_damage.push((x1y1, x2y2));
         self
     }
 
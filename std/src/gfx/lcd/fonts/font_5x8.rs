@@ -52,7 +52,7 @@
 
 //! Small font for tiny displays.
 
-use crate::gfx::lcd::fonts::Font;
+use crate::gfx::lcd::fonts::{Font, FontSegment};
 use crate::gfx::lcd::LcdSize;
 
 /// Width of the font glyphs in pixels.
@@ -62,7 +62,7 @@ const WIDTH: usize = 5;
 const HEIGHT: usize = 8;
 
 /// Raw font data table for ASCII characters.  Use `glyph` to access.
-const DATA: &[u8] = &[
+const ASCII_DATA: &[u8] = &[
     // @0 ' ' (5 pixels wide)
     0x00, //
     0x00, //
@@ -920,10 +920,3240 @@ const DATA: &[u8] = &[
     0x00, //
 ];
 
+/// Raw font data table for the letters in the Latin-1 supplement (U+00C0 to U+00FF).  Use `glyph` to access.
+const LATIN1_DATA: &[u8] = &[
+    // @0 'À' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @8 'Á' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @16 'Â' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @24 'Ã' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @32 'Ä' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @40 'Å' (5 pixels wide)
+    0x70, //  ###
+    0x50, //  # #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @48 'Æ' (5 pixels wide)
+    0x78, //  ####
+    0xA0, // # #
+    0xB0, // # ##
+    0xF0, // ####
+    0xA0, // # #
+    0xB8, // # ###
+    0x00, //
+    0x00, //
+    // @56 'Ç' (5 pixels wide)
+    0x70, //  ###
+    0x50, //  # #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x30, //   ##
+    0x20, //   #
+    0x60, //  ##
+    // @64 'È' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @72 'É' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @80 'Ê' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @88 'Ë' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @96 'Ì' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @104 'Í' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @112 'Î' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @120 'Ï' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @128 'Ð' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xE8, // ### #
+    0x48, //  #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @136 'Ñ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0xD8, // ## ##
+    0x68, //  ## #
+    0x68, //  ## #
+    0x58, //  # ##
+    0x58, //  # ##
+    0xE8, // ### #
+    // @144 'Ò' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @152 'Ó' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @160 'Ô' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @168 'Õ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @176 'Ö' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @184 '×' (5 pixels wide)
+    0x00, //
+    0x88, // #   #
+    0x50, //  # #
+    0x20, //   #
+    0x50, //  # #
+    0x88, // #   #
+    0x00, //
+    0x00, //
+    // @192 'Ø' (5 pixels wide)
+    0x38, //   ###
+    0x58, //  # ##
+    0x68, //  ## #
+    0x68, //  ## #
+    0x48, //  #  #
+    0xB0, // # ##
+    0x00, //
+    0x00, //
+    // @200 'Ù' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @208 'Ú' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @216 'Û' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @224 'Ü' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @232 'Ý' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x88, // #   #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @240 'Þ' (5 pixels wide)
+    0xE0, // ###
+    0x70, //  ###
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @248 'ß' (5 pixels wide)
+    0x60, //  ##
+    0x48, //  #  #
+    0x50, //  # #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x58, //  # ##
+    0x00, //
+    0x00, //
+    // @256 'à' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @264 'á' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @272 'â' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @280 'ã' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @288 'ä' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @296 'å' (5 pixels wide)
+    0x70, //  ###
+    0x50, //  # #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @304 'æ' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD0, // ## #
+    0x38, //   ###
+    0xF0, // ####
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @312 'ç' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0x40, //  #
+    0x40, //  #
+    0x70, //  ###
+    0x20, //   #
+    0x60, //  ##
+    // @320 'è' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x70, //  ###
+    0x70, //  ###
+    0x40, //  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @328 'é' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x70, //  ###
+    0x70, //  ###
+    0x40, //  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @336 'ê' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x70, //  ###
+    0x40, //  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @344 'ë' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x70, //  ###
+    0x70, //  ###
+    0x40, //  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @352 'ì' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @360 'í' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @368 'î' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x60, //  ##
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @376 'ï' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x60, //  ##
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @384 'ð' (5 pixels wide)
+    0x30, //   ##
+    0x38, //   ###
+    0x68, //  ## #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @392 'ñ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xC8, // ##  #
+    0x00, //
+    0x00, //
+    // @400 'ò' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @408 'ó' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @416 'ô' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @424 'õ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @432 'ö' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @440 '÷' (5 pixels wide)
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x00, //
+    // @448 'ø' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x58, //  # ##
+    0x68, //  ## #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @456 'ù' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @464 'ú' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @472 'û' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @480 'ü' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @488 'ý' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x60, //  ##
+    // @496 'þ' (5 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x70, //  ###
+    0x48, //  #  #
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    // @504 'ÿ' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x60, //  ##
+];
+
+/// Raw font data table for the box-drawing characters (U+2500 to U+257F).  Use `glyph` to access.
+const BOX_DRAWING_DATA: &[u8] = &[
+    // @0 '─' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @8 '━' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @16 '│' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @24 '┃' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @32 '┄' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xA8, // # # #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @40 '┅' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xA8, // # # #
+    0xA8, // # # #
+    0xA8, // # # #
+    0x00, //
+    0x00, //
+    0x00, //
+    // @48 '┆' (5 pixels wide)
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    // @56 '┇' (5 pixels wide)
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    // @64 '┈' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xA8, // # # #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @72 '┉' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xA8, // # # #
+    0xA8, // # # #
+    0xA8, // # # #
+    0x00, //
+    0x00, //
+    0x00, //
+    // @80 '┊' (5 pixels wide)
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x20, //   #
+    0x00, //
+    // @88 '┋' (5 pixels wide)
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    0x70, //  ###
+    0x00, //
+    // @96 '┌' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @104 '┍' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x38, //   ###
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @112 '┎' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @120 '┏' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x78, //  ####
+    0x78, //  ####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @128 '┐' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @136 '┑' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0xE0, // ###
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @144 '┒' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @152 '┓' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0xF0, // ####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @160 '└' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @168 '┕' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x38, //   ###
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @176 '┖' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @184 '┗' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x78, //  ####
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @192 '┘' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @200 '┙' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0xE0, // ###
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @208 '┚' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @216 '┛' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF0, // ####
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @224 '├' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @232 '┝' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x38, //   ###
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @240 '┞' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @248 '┟' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @256 '┠' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @264 '┡' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x78, //  ####
+    0x78, //  ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @272 '┢' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x78, //  ####
+    0x78, //  ####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @280 '┣' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0x78, //  ####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @288 '┤' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @296 '┥' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0xE0, // ###
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @304 '┦' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @312 '┧' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @320 '┨' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @328 '┩' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF0, // ####
+    0xF0, // ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @336 '┪' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF0, // ####
+    0xF0, // ####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @344 '┫' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF0, // ####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @352 '┬' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @360 '┭' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0xF8, // #####
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @368 '┮' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0xF8, // #####
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @376 '┯' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @384 '┰' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @392 '┱' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0xF8, // #####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @400 '┲' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x78, //  ####
+    0xF8, // #####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @408 '┳' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @416 '┴' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @424 '┵' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0xF8, // #####
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @432 '┶' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0xF8, // #####
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @440 '┷' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @448 '┸' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @456 '┹' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF8, // #####
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @464 '┺' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0xF8, // #####
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @472 '┻' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @480 '┼' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @488 '┽' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0xF8, // #####
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @496 '┾' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0xF8, // #####
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @504 '┿' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @512 '╀' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @520 '╁' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @528 '╂' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @536 '╃' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF8, // #####
+    0xF0, // ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @544 '╄' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0xF8, // #####
+    0x78, //  ####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @552 '╅' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF0, // ####
+    0xF8, // #####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @560 '╆' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x78, //  ####
+    0xF8, // #####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @568 '╇' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @576 '╈' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @584 '╉' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF0, // ####
+    0xF8, // #####
+    0xF0, // ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @592 '╊' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x78, //  ####
+    0xF8, // #####
+    0x78, //  ####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @600 '╋' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0xF8, // #####
+    0xF8, // #####
+    0xF8, // #####
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @608 '╌' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xD0, // ## #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @616 '╍' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD0, // ## #
+    0xD0, // ## #
+    0xD0, // ## #
+    0x00, //
+    0x00, //
+    0x00, //
+    // @624 '╎' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x00, //
+    0x00, //
+    0x20, //   #
+    0x20, //   #
+    0x00, //
+    0x00, //
+    // @632 '╏' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @640 '═' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @648 '║' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @656 '╒' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x20, //   #
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @664 '╓' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x78, //  ####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @672 '╔' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x78, //  ####
+    0x40, //  #
+    0x58, //  # ##
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @680 '╕' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x20, //   #
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @688 '╖' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @696 '╗' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x10, //    #
+    0xD0, // ## #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @704 '╘' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x20, //   #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @712 '╙' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @720 '╚' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x58, //  # ##
+    0x40, //  #
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @728 '╛' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0x20, //   #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @736 '╜' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @744 '╝' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0xD0, // ## #
+    0x10, //    #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @752 '╞' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x38, //   ###
+    0x20, //   #
+    0x38, //   ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @760 '╟' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x78, //  ####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @768 '╠' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x58, //  # ##
+    0x40, //  #
+    0x58, //  # ##
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @776 '╡' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    0x20, //   #
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @784 '╢' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0xF0, // ####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @792 '╣' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0xD0, // ## #
+    0x10, //    #
+    0xD0, // ## #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @800 '╤' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @808 '╥' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @816 '╦' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @824 '╧' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @832 '╨' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @840 '╩' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    0x00, //
+    // @848 '╪' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @856 '╫' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0xF8, // #####
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @864 '╬' (5 pixels wide)
+    0x50, //  # #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x00, //
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x50, //  # #
+    // @872 '╭' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x18, //    ##
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @880 '╮' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xC0, // ##
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @888 '╯' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xC0, // ##
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @896 '╰' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x18, //    ##
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @904 '╱' (5 pixels wide)
+    0x08, //     #
+    0x10, //    #
+    0x10, //    #
+    0x20, //   #
+    0x20, //   #
+    0x40, //  #
+    0x40, //  #
+    0x80, // #
+    // @912 '╲' (5 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x40, //  #
+    0x20, //   #
+    0x20, //   #
+    0x10, //    #
+    0x10, //    #
+    0x08, //     #
+    // @920 '╳' (5 pixels wide)
+    0x88, // #   #
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x50, //  # #
+    0x50, //  # #
+    0x88, // #   #
+    // @928 '╴' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @936 '╵' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @944 '╶' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @952 '╷' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    // @960 '╸' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0xE0, // ###
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @968 '╹' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @976 '╺' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0x38, //   ###
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @984 '╻' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @992 '╼' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x38, //   ###
+    0xF8, // #####
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @1000 '╽' (5 pixels wide)
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    // @1008 '╾' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0xF8, // #####
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    // @1016 '╿' (5 pixels wide)
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+];
+
+/// Ranges of characters covered by this font.
+const SEGMENTS: &[FontSegment] = &[
+    FontSegment { first: ' ', count: 95, data: ASCII_DATA },
+    FontSegment { first: '\u{00c0}', count: 64, data: LATIN1_DATA },
+    FontSegment { first: '\u{2500}', count: 128, data: BOX_DRAWING_DATA },
+];
+
 /// Small font for tiny displays.
 pub(crate) const FONT_5X8: Font = Font {
     name: "5x8",
     glyph_size: LcdSize { width: WIDTH, height: HEIGHT },
     stride: 1,
-    data: DATA,
+    segments: SEGMENTS,
+    advances: None,
+    replacement: None,
+};
+
+/// Raw font data table for ASCII characters in the proportional variant of the font, with every
+/// glyph shifted to the left edge.  Use `glyph` to access.
+const PROPORTIONAL_ASCII_DATA: &[u8] = &[
+    // @0 ' ' (0 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @8 '!' (1 pixel wide)
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x00, //
+    0x80, // #
+    0x00, //
+    0x00, //
+    // @16 '"' (3 pixels wide)
+    0xA0, // # #
+    0xA0, // # #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @24 '#' (5 pixels wide)
+    0x28, //   # #
+    0x50, //  # #
+    0xF8, // #####
+    0x50, //  # #
+    0xF8, // #####
+    0x50, //  # #
+    0xA0, // # #
+    0x00, //
+    // @32 '$' (3 pixels wide)
+    0x40, //  #
+    0x60, //  ##
+    0xC0, // ##
+    0x60, //  ##
+    0x20, //   #
+    0xC0, // ##
+    0x40, //  #
+    0x00, //
+    // @40 '%' (4 pixels wide)
+    0x40, //  #
+    0x40, //  #
+    0x30, //   ##
+    0xC0, // ##
+    0x20, //   #
+    0x20, //   #
+    0x00, //
+    0x00, //
+    // @48 '&' (4 pixels wide)
+    0x00, //
+    0x70, //  ###
+    0x40, //  #
+    0xC0, // ##
+    0xA0, // # #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @56 ''' (1 pixel wide)
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @64 '(' (2 pixels wide)
+    0x40, //  #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x40, //  #
+    0x00, //
+    // @72 ')' (2 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x80, // #
+    0x00, //
+    // @80 '*' (3 pixels wide)
+    0x40, //  #
+    0xE0, // ###
+    0x40, //  #
+    0xA0, // # #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @88 '+' (5 pixels wide)
+    0x00, //
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x20, //   #
+    0x20, //   #
+    0x00, //
+    0x00, //
+    // @96 ',' (2 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x40, //  #
+    0x80, // #
+    0x80, // #
+    0x00, //
+    // @104 '-' (3 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @112 '.' (1 pixel wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x80, // #
+    0x00, //
+    0x00, //
+    // @120 '/' (4 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x40, //  #
+    0x40, //  #
+    0x80, // #
+    0x00, //
+    // @128 '0' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0xA0, // # #
+    0xA0, // # #
+    0xA0, // # #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @136 '1' (5 pixels wide)
+    0x60, //  ##
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    // @144 '2' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x40, //  #
+    0x40, //  #
+    0x80, // #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @152 '3' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x20, //   #
+    0x40, //  #
+    0x20, //   #
+    0xC0, // ##
+    0x00, //
+    0x00, //
+    // @160 '4' (4 pixels wide)
+    0x20, //   #
+    0x60, //  ##
+    0xA0, // # #
+    0xF0, // ####
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @168 '5' (3 pixels wide)
+    0xE0, // ###
+    0x80, // #
+    0xC0, // ##
+    0x20, //   #
+    0xA0, // # #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @176 '6' (3 pixels wide)
+    0x60, //  ##
+    0x80, // #
+    0xC0, // ##
+    0xA0, // # #
+    0xA0, // # #
+    0xC0, // ##
+    0x00, //
+    0x00, //
+    // @184 '7' (3 pixels wide)
+    0xE0, // ###
+    0xA0, // # #
+    0x20, //   #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @192 '8' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x40, //  #
+    0xA0, // # #
+    0xA0, // # #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @200 '9' (3 pixels wide)
+    0x60, //  ##
+    0xA0, // # #
+    0xA0, // # #
+    0x60, //  ##
+    0x20, //   #
+    0xC0, // ##
+    0x00, //
+    0x00, //
+    // @208 ':' (1 pixel wide)
+    0x00, //
+    0x00, //
+    0x80, // #
+    0x00, //
+    0x00, //
+    0x80, // #
+    0x00, //
+    0x00, //
+    // @216 ';' (2 pixels wide)
+    0x00, //
+    0x00, //
+    0x40, //  #
+    0x00, //
+    0x40, //  #
+    0x80, // #
+    0x00, //
+    0x00, //
+    // @224 '<' (4 pixels wide)
+    0x00, //
+    0x10, //    #
+    0x20, //   #
+    0xC0, // ##
+    0x20, //   #
+    0x10, //    #
+    0x00, //
+    0x00, //
+    // @232 '=' (3 pixels wide)
+    0x00, //
+    0xE0, // ###
+    0x00, //
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @240 '>' (4 pixels wide)
+    0x00, //
+    0x80, // #
+    0x40, //  #
+    0x30, //   ##
+    0x40, //  #
+    0x80, // #
+    0x00, //
+    0x00, //
+    // @248 '?' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x20, //   #
+    0x40, //  #
+    0x00, //
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @256 '@' (4 pixels wide)
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0xB0, // # ##
+    0x90, // #  #
+    0x80, // #
+    0x70, //  ###
+    0x00, //
+    // @264 'A' (5 pixels wide)
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @272 'B' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x70, //  ###
+    0x48, //  #  #
+    0x48, //  #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @280 'C' (3 pixels wide)
+    0xE0, // ###
+    0xA0, // # #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @288 'D' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @296 'E' (5 pixels wide)
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    // @304 'F' (5 pixels wide)
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @312 'G' (4 pixels wide)
+    0xE0, // ###
+    0x80, // #
+    0x80, // #
+    0xB0, // # ##
+    0xA0, // # #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @320 'H' (5 pixels wide)
+    0xE8, // ### #
+    0x48, //  #  #
+    0x78, //  ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xE8, // ### #
+    0x00, //
+    0x00, //
+    // @328 'I' (3 pixels wide)
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @336 'J' (4 pixels wide)
+    0x70, //  ###
+    0x20, //   #
+    0x20, //   #
+    0xA0, // # #
+    0xA0, // # #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    // @344 'K' (5 pixels wide)
+    0xD8, // ## ##
+    0x50, //  # #
+    0x60, //  ##
+    0x70, //  ###
+    0x50, //  # #
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @352 'L' (5 pixels wide)
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    0x00, //
+    0x00, //
+    // @360 'M' (5 pixels wide)
+    0xD8, // ## ##
+    0xD8, // ## ##
+    0xD8, // ## ##
+    0xA8, // # # #
+    0x88, // #   #
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @368 'N' (5 pixels wide)
+    0xD8, // ## ##
+    0x68, //  ## #
+    0x68, //  ## #
+    0x58, //  # ##
+    0x58, //  # ##
+    0xE8, // ### #
+    0x00, //
+    0x00, //
+    // @376 'O' (4 pixels wide)
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @384 'P' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @392 'Q' (4 pixels wide)
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x30, //   ##
+    0x00, //
+    // @400 'R' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0x70, //  ###
+    0x48, //  #  #
+    0xE8, // ### #
+    0x00, //
+    0x00, //
+    // @408 'S' (3 pixels wide)
+    0xE0, // ###
+    0xA0, // # #
+    0x40, //  #
+    0x20, //   #
+    0xA0, // # #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @416 'T' (5 pixels wide)
+    0xF8, // #####
+    0xA8, // # # #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @424 'U' (5 pixels wide)
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @432 'V' (5 pixels wide)
+    0xD8, // ## ##
+    0x88, // #   #
+    0x48, //  #  #
+    0x50, //  # #
+    0x50, //  # #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @440 'W' (5 pixels wide)
+    0xD8, // ## ##
+    0x88, // #   #
+    0xA8, // # # #
+    0xA8, // # # #
+    0xA8, // # # #
+    0x50, //  # #
+    0x00, //
+    0x00, //
+    // @448 'X' (5 pixels wide)
+    0xD8, // ## ##
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @456 'Y' (5 pixels wide)
+    0xD8, // ## ##
+    0x88, // #   #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @464 'Z' (4 pixels wide)
+    0xF0, // ####
+    0x90, // #  #
+    0x20, //   #
+    0x40, //  #
+    0x90, // #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @472 '[' (2 pixels wide)
+    0xC0, // ##
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0xC0, // ##
+    0x00, //
+    // @480 '\' (4 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x40, //  #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x10, //    #
+    0x00, //
+    // @488 ']' (2 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xC0, // ##
+    0x00, //
+    // @496 '^' (3 pixels wide)
+    0x40, //  #
+    0x40, //  #
+    0xA0, // # #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @504 '_' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0xF8, // #####
+    // @512 '`' (2 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    0x00, //
+    // @520 'a' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @528 'b' (5 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x70, //  ###
+    0x48, //  #  #
+    0x48, //  #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @536 'c' (3 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x80, // #
+    0x80, // #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @544 'd' (4 pixels wide)
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x90, // #  #
+    0x90, // #  #
+    0x70, //  ###
+    0x00, //
+    0x00, //
+    // @552 'e' (3 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0xE0, // ###
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @560 'f' (3 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @568 'g' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0x90, // #  #
+    0x90, // #  #
+    0x70, //  ###
+    0x10, //    #
+    0x60, //  ##
+    // @576 'h' (5 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x70, //  ###
+    0x48, //  #  #
+    0x48, //  #  #
+    0xE8, // ### #
+    0x00, //
+    0x00, //
+    // @584 'i' (3 pixels wide)
+    0x40, //  #
+    0x00, //
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @592 'j' (3 pixels wide)
+    0x40, //  #
+    0x00, //
+    0xE0, // ###
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0x20, //   #
+    0xE0, // ###
+    // @600 'k' (5 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x58, //  # ##
+    0x70, //  ###
+    0x50, //  # #
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @608 'l' (3 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @616 'm' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD0, // ## #
+    0xA8, // # # #
+    0xA8, // # # #
+    0xA8, // # # #
+    0x00, //
+    0x00, //
+    // @624 'n' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xC8, // ##  #
+    0x00, //
+    0x00, //
+    // @632 'o' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @640 'p' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    // @648 'q' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0x90, // #  #
+    0x90, // #  #
+    0x70, //  ###
+    0x10, //    #
+    0x30, //   ##
+    // @656 'r' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @664 's' (3 pixels wide)
+    0x00, //
+    0x00, //
+    0x60, //  ##
+    0x40, //  #
+    0x20, //   #
+    0xC0, // ##
+    0x00, //
+    0x00, //
+    // @672 't' (5 pixels wide)
+    0x00, //
+    0x40, //  #
+    0xF0, // ####
+    0x40, //  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @680 'u' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @688 'v' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xC8, // ##  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @696 'w' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD8, // ## ##
+    0xA8, // # # #
+    0xA8, // # # #
+    0x50, //  # #
+    0x00, //
+    0x00, //
+    // @704 'x' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x90, // #  #
+    0x60, //  ##
+    0x60, //  ##
+    0x90, // #  #
+    0x00, //
+    0x00, //
+    // @712 'y' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x60, //  ##
+    // @720 'z' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0xF0, // ####
+    0xA0, // # #
+    0x50, //  # #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @728 '{' (3 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0x40, //  #
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0x20, //   #
+    0x00, //
+    // @736 '|' (1 pixel wide)
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x00, //
+    // @744 '}' (3 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x40, //  #
+    0x60, //  ##
+    0x40, //  #
+    0x40, //  #
+    0x80, // #
+    0x00, //
+    // @752 '~' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x00, //
+    0x50, //  # #
+    0xA0, // # #
+    0x00, //
+    0x00, //
+    0x00, //
+];
+
+/// Advance widths of the glyphs in `PROPORTIONAL_ASCII_DATA`.
+const PROPORTIONAL_ASCII_ADVANCES: &[u8] = &[
+    3, 2, 4, 6, 4, 5, 5, 2, 3, 3, 4, 6, 3, 4, 2, 5, 4, 6, 4, 4, 5, 4, 4, 4, 4, 4, 2, 3, 5, 4, 5, 4,
+    5, 6, 6, 4, 6, 6, 6, 5, 6, 4, 5, 6, 6, 6, 6, 5, 6, 5, 6, 4, 6, 6, 6, 6, 6, 6, 5, 3, 5, 3, 4, 6,
+    3, 5, 6, 4, 5, 4, 4, 5, 6, 4, 4, 6, 4, 6, 6, 5, 6, 5, 5, 4, 6, 6, 6, 6, 5, 6, 5, 4, 2, 4, 5,
+];
+
+/// Raw font data table for the letters in the Latin-1 supplement (U+00C0 to U+00FF) in the
+/// proportional variant of the font, with every glyph shifted to the left edge.  Use `glyph` to
+/// access.
+const PROPORTIONAL_LATIN1_DATA: &[u8] = &[
+    // @0 'À' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @8 'Á' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @16 'Â' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @24 'Ã' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @32 'Ä' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @40 'Å' (5 pixels wide)
+    0x70, //  ###
+    0x50, //  # #
+    0x60, //  ##
+    0x20, //   #
+    0x50, //  # #
+    0x70, //  ###
+    0x88, // #   #
+    0xD8, // ## ##
+    // @48 'Æ' (5 pixels wide)
+    0x78, //  ####
+    0xA0, // # #
+    0xB0, // # ##
+    0xF0, // ####
+    0xA0, // # #
+    0xB8, // # ###
+    0x00, //
+    0x00, //
+    // @56 'Ç' (3 pixels wide)
+    0xE0, // ###
+    0xA0, // # #
+    0x80, // #
+    0x80, // #
+    0x80, // #
+    0x60, //  ##
+    0x40, //  #
+    0xC0, // ##
+    // @64 'È' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @72 'É' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @80 'Ê' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @88 'Ë' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xF8, // #####
+    0x48, //  #  #
+    0x60, //  ##
+    0x40, //  #
+    0x48, //  #  #
+    0xF8, // #####
+    // @96 'Ì' (3 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    // @104 'Í' (3 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    // @112 'Î' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    // @120 'Ï' (3 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0xE0, // ###
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    // @128 'Ð' (5 pixels wide)
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xE8, // ### #
+    0x48, //  #  #
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @136 'Ñ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0xD8, // ## ##
+    0x68, //  ## #
+    0x68, //  ## #
+    0x58, //  # ##
+    0x58, //  # ##
+    0xE8, // ### #
+    // @144 'Ò' (4 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    // @152 'Ó' (4 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    // @160 'Ô' (4 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    // @168 'Õ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @176 'Ö' (4 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    // @184 '×' (5 pixels wide)
+    0x00, //
+    0x88, // #   #
+    0x50, //  # #
+    0x20, //   #
+    0x50, //  # #
+    0x88, // #   #
+    0x00, //
+    0x00, //
+    // @192 'Ø' (5 pixels wide)
+    0x38, //   ###
+    0x58, //  # ##
+    0x68, //  ## #
+    0x68, //  ## #
+    0x48, //  #  #
+    0xB0, // # ##
+    0x00, //
+    0x00, //
+    // @200 'Ù' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @208 'Ú' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @216 'Û' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @224 'Ü' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    // @232 'Ý' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x88, // #   #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x70, //  ###
+    // @240 'Þ' (5 pixels wide)
+    0xE0, // ###
+    0x70, //  ###
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @248 'ß' (4 pixels wide)
+    0xC0, // ##
+    0x90, // #  #
+    0xA0, // # #
+    0x90, // #  #
+    0x90, // #  #
+    0xB0, // # ##
+    0x00, //
+    0x00, //
+    // @256 'à' (4 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @264 'á' (4 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @272 'â' (4 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @280 'ã' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x10, //    #
+    0x70, //  ###
+    0x78, //  ####
+    0x00, //
+    0x00, //
+    // @288 'ä' (4 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @296 'å' (4 pixels wide)
+    0xE0, // ###
+    0xA0, // # #
+    0x60, //  ##
+    0x20, //   #
+    0xE0, // ###
+    0xF0, // ####
+    0x00, //
+    0x00, //
+    // @304 'æ' (5 pixels wide)
+    0x00, //
+    0x00, //
+    0xD0, // ## #
+    0x38, //   ###
+    0xF0, // ####
+    0xD8, // ## ##
+    0x00, //
+    0x00, //
+    // @312 'ç' (3 pixels wide)
+    0x00, //
+    0x00, //
+    0xE0, // ###
+    0x80, // #
+    0x80, // #
+    0xE0, // ###
+    0x40, //  #
+    0xC0, // ##
+    // @320 'è' (3 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0xE0, // ###
+    0xE0, // ###
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @328 'é' (3 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0xE0, // ###
+    0xE0, // ###
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @336 'ê' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0xE0, // ###
+    0xE0, // ###
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @344 'ë' (3 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0xE0, // ###
+    0xE0, // ###
+    0x80, // #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @352 'ì' (3 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @360 'í' (3 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @368 'î' (3 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @376 'ï' (3 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0xC0, // ##
+    0x40, //  #
+    0x40, //  #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @384 'ð' (4 pixels wide)
+    0x60, //  ##
+    0x70, //  ###
+    0xD0, // ## #
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @392 'ñ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0xF0, // ####
+    0x48, //  #  #
+    0x48, //  #  #
+    0xC8, // ##  #
+    0x00, //
+    0x00, //
+    // @400 'ò' (4 pixels wide)
+    0x80, // #
+    0x40, //  #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @408 'ó' (4 pixels wide)
+    0x20, //   #
+    0x40, //  #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @416 'ô' (4 pixels wide)
+    0x40, //  #
+    0xA0, // # #
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @424 'õ' (5 pixels wide)
+    0x68, //  ## #
+    0x90, // #  #
+    0x30, //   ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x30, //   ##
+    0x00, //
+    0x00, //
+    // @432 'ö' (4 pixels wide)
+    0xA0, // # #
+    0x00, //
+    0x60, //  ##
+    0x90, // #  #
+    0x90, // #  #
+    0x60, //  ##
+    0x00, //
+    0x00, //
+    // @440 '÷' (5 pixels wide)
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0xF8, // #####
+    0x00, //
+    0x20, //   #
+    0x00, //
+    0x00, //
+    // @448 'ø' (4 pixels wide)
+    0x00, //
+    0x00, //
+    0x70, //  ###
+    0xB0, // # ##
+    0xD0, // ## #
+    0xE0, // ###
+    0x00, //
+    0x00, //
+    // @456 'ù' (5 pixels wide)
+    0x40, //  #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @464 'ú' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @472 'û' (5 pixels wide)
+    0x20, //   #
+    0x50, //  # #
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @480 'ü' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x48, //  #  #
+    0x48, //  #  #
+    0x38, //   ###
+    0x00, //
+    0x00, //
+    // @488 'ý' (5 pixels wide)
+    0x10, //    #
+    0x20, //   #
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x60, //  ##
+    // @496 'þ' (5 pixels wide)
+    0xC0, // ##
+    0x40, //  #
+    0x70, //  ###
+    0x48, //  #  #
+    0x48, //  #  #
+    0x70, //  ###
+    0x40, //  #
+    0xE0, // ###
+    // @504 'ÿ' (5 pixels wide)
+    0x50, //  # #
+    0x00, //
+    0xD8, // ## ##
+    0x50, //  # #
+    0x50, //  # #
+    0x20, //   #
+    0x20, //   #
+    0x60, //  ##
+];
+
+/// Advance widths of the glyphs in `PROPORTIONAL_LATIN1_DATA`.
+const PROPORTIONAL_LATIN1_ADVANCES: &[u8] = &[
+    6, 6, 6, 6, 6, 6, 6, 4, 6, 6, 6, 6, 4, 4, 4, 4, 6, 6, 5, 5, 5, 6, 5, 6, 6, 6, 6, 6, 6, 6, 6, 5,
+    5, 5, 5, 6, 5, 5, 6, 4, 4, 4, 4, 4, 4, 4, 4, 4, 5, 6, 5, 5, 5, 6, 5, 6, 5, 6, 6, 6, 6, 6, 6, 6,
+];
+
+/// Advance widths of the glyphs in `BOX_DRAWING_DATA`, which span the whole cell so that they
+/// connect with their neighbors.
+const PROPORTIONAL_BOX_DRAWING_ADVANCES: &[u8] = &[
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+    5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5,
+];
+
+/// Ranges of characters covered by the proportional variant of this font.
+const PROPORTIONAL_SEGMENTS: &[FontSegment] = &[
+    FontSegment { first: ' ', count: 95, data: PROPORTIONAL_ASCII_DATA },
+    FontSegment { first: '\u{00c0}', count: 64, data: PROPORTIONAL_LATIN1_DATA },
+    FontSegment { first: '\u{2500}', count: 128, data: BOX_DRAWING_DATA },
+];
+
+/// Advance widths for each segment in `PROPORTIONAL_SEGMENTS`.
+const PROPORTIONAL_ADVANCES: &[&[u8]] =
+    &[PROPORTIONAL_ASCII_ADVANCES, PROPORTIONAL_LATIN1_ADVANCES, PROPORTIONAL_BOX_DRAWING_ADVANCES];
+
+/// Proportional variant of the small font for graphical text.
+pub(crate) const FONT_5X8_PROPORTIONAL: Font = Font {
+    name: "5x8p",
+    glyph_size: LcdSize { width: WIDTH, height: HEIGHT },
+    stride: 1,
+    segments: PROPORTIONAL_SEGMENTS,
+    advances: Some(PROPORTIONAL_ADVANCES),
+    replacement: None,
 };
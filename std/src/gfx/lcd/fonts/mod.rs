@@ -16,14 +16,27 @@
 //! Support for bitmap fonts directly rendered onto an LCD.
 
 use crate::gfx::lcd::LcdSize;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 mod font_5x8;
-pub(crate) use font_5x8::FONT_5X8;
+pub(crate) use font_5x8::{FONT_5X8, FONT_5X8_PROPORTIONAL};
 
 mod font_16x16;
 pub(crate) use font_16x16::FONT_16X16;
 
+/// Bitmap data for a range of contiguous characters in a font.
+pub struct FontSegment {
+    /// The first character covered by this segment.
+    pub first: char,
+
+    /// The number of glyphs in this segment.
+    pub count: usize,
+
+    /// The bitmap data for the glyphs in this segment, in character order.
+    pub data: &'static [u8],
+}
+
 /// Representation of a font.
 pub struct Font {
     /// The name of the font.
@@ -35,25 +48,98 @@ pub struct Font {
     /// The number of bytes in every glyph row.
     pub stride: usize,
 
-    /// The bitmap data for the font.
-    pub data: &'static [u8],
+    /// The ranges of characters covered by the font.  Must be sorted by their first character and
+    /// must not overlap.
+    pub segments: &'static [FontSegment],
+
+    /// The advance widths of the glyphs, in pixels, for proportional fonts.  If provided, must
+    /// contain one table per entry in `segments`, each with one width per glyph in the segment.
+    /// If not provided, the font is fixed-width and all glyphs advance by `glyph_size.width`.
+    pub advances: Option<&'static [&'static [u8]]>,
+
+    /// The bitmap data for the glyph to display in place of characters not covered by `segments`.
+    /// If not provided, a hollow box matching `glyph_size` is generated.
+    pub replacement: Option<&'static [u8]>,
 }
 
 impl Font {
+    /// Returns true if the glyphs in this font have different widths.
+    ///
+    /// Proportional fonts cannot be used to render the character-based console.
+    pub fn is_proportional(&self) -> bool {
+        self.advances.is_some()
+    }
+
+    /// Locates `ch` in the font and returns the index of its segment and its index within it.
+    fn find(&self, ch: char) -> Option<(usize, usize)> {
+        let pos = self.segments.partition_point(|segment| segment.first <= ch);
+        if pos == 0 {
+            return None;
+        }
+        let segment = &self.segments[pos - 1];
+
+        let index = (ch as usize) - (segment.first as usize);
+        if index >= segment.count {
+            return None;
+        }
+
+        Some((pos - 1, index))
+    }
+
     /// Returns the raw font data for `ch`.
     ///
     /// Each entry in the array corresponds to a row of pixels and is a bitmask indicating which
     /// pixels to turn on.
-    pub(crate) fn glyph(&self, mut ch: char) -> &'static [u8] {
-        if !(' '..='~').contains(&ch) {
-            // TODO(jmmv): Would be nicer to draw an empty box, much like how unknown Unicode
-            // characters are typically displayed.
-            ch = '?';
-        }
+    ///
+    /// Characters not covered by the font are rendered with the `replacement_glyph`.
+    pub(crate) fn glyph(&self, ch: char) -> Cow<'static, [u8]> {
+        let (segment, index) = match self.find(ch) {
+            Some((segment, index)) => (&self.segments[segment], index),
+            None => return self.replacement_glyph(),
+        };
+
         let height = self.glyph_size.height * self.stride;
-        let offset = ((ch as usize) - (' ' as usize)) * height;
-        debug_assert!(offset < (self.data.len() + height));
-        &self.data[offset..offset + height]
+        let offset = index * height;
+        debug_assert!(offset + height <= segment.data.len());
+        Cow::Borrowed(&segment.data[offset..offset + height])
+    }
+
+    /// Returns the number of pixels to advance the pen by after rendering `ch`.
+    ///
+    /// Characters not covered by the font, which are rendered with the `replacement_glyph`, and
+    /// all characters in fixed-width fonts advance by `glyph_size.width`.
+    pub(crate) fn advance(&self, ch: char) -> usize {
+        match (self.advances, self.find(ch)) {
+            (Some(advances), Some((segment, index))) => usize::from(advances[segment][index]),
+            _ => self.glyph_size.width,
+        }
+    }
+
+    /// Returns the width of `text` in pixels when rendered with this font.
+    pub(crate) fn text_width(&self, text: &str) -> usize {
+        text.chars().map(|ch| self.advance(ch)).sum()
+    }
+
+    /// Returns the raw font data for the glyph used to represent characters that have no glyph.
+    ///
+    /// This is the font-provided `replacement` glyph, if any, or a hollow box otherwise.  The box
+    /// leaves the last row and column empty to keep the spacing between consecutive characters.
+    pub(crate) fn replacement_glyph(&self) -> Cow<'static, [u8]> {
+        if let Some(replacement) = self.replacement {
+            return Cow::Borrowed(replacement);
+        }
+
+        let width = self.glyph_size.width - 1;
+        let height = self.glyph_size.height - 1;
+        let mut data = vec![0; self.glyph_size.height * self.stride];
+        for j in 0..height {
+            for i in 0..width {
+                if j == 0 || j == height - 1 || i == 0 || i == width - 1 {
+                    data[j * self.stride + i / 8] |= 0x80 >> (i % 8);
+                }
+            }
+        }
+        Cow::Owned(data)
     }
 }
 
@@ -64,6 +150,7 @@ pub type Fonts = HashMap<&'static str, &'static Font>;
 pub fn all_fonts() -> Fonts {
     let mut fonts = Fonts::default();
     fonts.insert(FONT_5X8.name, &FONT_5X8);
+    fonts.insert(FONT_5X8_PROPORTIONAL.name, &FONT_5X8_PROPORTIONAL);
     fonts.insert(FONT_16X16.name, &FONT_16X16);
     fonts
 }
@@ -77,20 +164,197 @@ mod tests {
         let font = &FONT_5X8;
 
         let offset = (usize::from(b'a') - usize::from(b' ')) * 8;
-        let expected = &font.data[offset..offset + 8];
+        let expected = &font.segments[0].data[offset..offset + 8];
 
         let data = font.glyph('a');
-        assert_eq!(expected, data);
+        assert_eq!(expected, data.as_ref());
     }
 
     #[test]
-    fn test_font_glyph_non_printable() {
+    fn test_font_glyph_question_mark() {
         let font = &FONT_5X8;
 
         let offset = (usize::from(b'?') - usize::from(b' ')) * 8;
-        let expected = &font.data[offset..offset + 8];
+        let expected = &font.segments[0].data[offset..offset + 8];
+
+        let data = font.glyph('?');
+        assert_eq!(expected, data.as_ref());
+    }
+
+    #[test]
+    fn test_font_glyph_non_printable_5x8() {
+        let font = &FONT_5X8;
+
+        let expected: &[u8] = &[
+            0xf0, // ####
+            0x90, // #  #
+            0x90, // #  #
+            0x90, // #  #
+            0x90, // #  #
+            0x90, // #  #
+            0xf0, // ####
+            0x00, //
+        ];
+        assert_eq!(expected, font.glyph(char::from(30)).as_ref());
+        assert_eq!(expected, font.glyph('\u{00a9}').as_ref());
+        assert_eq!(expected, font.glyph('\u{2603}').as_ref());
+        assert_eq!(expected, font.replacement_glyph().as_ref());
+    }
+
+    #[test]
+    fn test_font_glyph_non_printable_16x16() {
+        let font = &FONT_16X16;
+
+        let mut expected = vec![0xff, 0xfe];
+        for _ in 0..13 {
+            expected.extend_from_slice(&[0x80, 0x02]);
+        }
+        expected.extend_from_slice(&[0xff, 0xfe]);
+        expected.extend_from_slice(&[0x00, 0x00]);
+        assert_eq!(expected.as_slice(), font.glyph('\u{2603}').as_ref());
+    }
+
+    #[test]
+    fn test_font_glyph_custom_replacement() {
+        const REPLACEMENT: &[u8] = &[1, 2, 3, 4, 5, 6, 7, 8];
+        let font = Font { replacement: Some(REPLACEMENT), ..FONT_5X8 };
+
+        assert_eq!(REPLACEMENT, font.glyph('\u{2603}').as_ref());
+        assert_ne!(REPLACEMENT, font.glyph('?').as_ref());
+    }
+
+    #[test]
+    fn test_font_segments_are_consistent() {
+        for font in all_fonts().values() {
+            let height = font.glyph_size.height * font.stride;
+            let mut prev_end = 0;
+            for segment in font.segments {
+                assert!(prev_end <= segment.first as usize, "Font {} segments overlap", font.name);
+                assert_eq!(segment.count * height, segment.data.len(), "Font {}", font.name);
+                prev_end = segment.first as usize + segment.count;
+            }
+
+            if let Some(advances) = font.advances {
+                assert_eq!(font.segments.len(), advances.len(), "Font {}", font.name);
+                for (segment, advances) in font.segments.iter().zip(advances.iter()) {
+                    assert_eq!(segment.count, advances.len(), "Font {}", font.name);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_glyph_ascii_offsets() {
+        for font in all_fonts().values() {
+            let height = font.glyph_size.height * font.stride;
+            for (i, ch) in (' '..='~').enumerate() {
+                let expected = &font.segments[0].data[i * height..(i + 1) * height];
+                assert_eq!(expected, font.glyph(ch).as_ref(), "Font {} char {}", font.name, ch);
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_glyph_segment_boundaries() {
+        for font in all_fonts().values() {
+            let height = font.glyph_size.height * font.stride;
+            let replacement = font.replacement_glyph();
+            for segment in font.segments {
+                let first = &segment.data[0..height];
+                let last = &segment.data[segment.data.len() - height..];
+                let before = char::from_u32(segment.first as u32 - 1).unwrap();
+                let end = char::from_u32(segment.first as u32 + segment.count as u32 - 1).unwrap();
+                let after = char::from_u32(segment.first as u32 + segment.count as u32).unwrap();
+
+                assert_eq!(first, font.glyph(segment.first).as_ref());
+                assert_eq!(last, font.glyph(end).as_ref());
+                assert_eq!(replacement, font.glyph(before));
+                assert_eq!(replacement, font.glyph(after));
+            }
+        }
+    }
+
+    #[test]
+    fn test_font_glyph_extended_characters() {
+        let font = &FONT_5X8;
+
+        let expected: &[u8] = &[
+            0x10, //    #
+            0x20, //   #
+            0x70, //  ###
+            0x70, //  ###
+            0x40, //  #
+            0x30, //   ##
+            0x00, //
+            0x00, //
+        ];
+        assert_eq!(expected, font.glyph('\u{00e9}').as_ref());
+
+        let expected: &[u8] = &[
+            0x00, //
+            0x00, //
+            0x00, //
+            0x38, //   ###
+            0x20, //   #
+            0x20, //   #
+            0x20, //   #
+            0x20, //   #
+        ];
+        assert_eq!(expected, font.glyph('\u{250c}').as_ref());
+    }
+
+    #[test]
+    fn test_font_advance_fixed_width() {
+        for font in [&FONT_5X8, &FONT_16X16] {
+            assert!(!font.is_proportional());
+            for ch in ['i', 'W', ' ', '\u{00e9}', '\u{250c}', '\u{2603}'] {
+                assert_eq!(font.glyph_size.width, font.advance(ch), "Font {}", font.name);
+            }
+            assert_eq!(font.glyph_size.width * 5, font.text_width("Hello"), "Font {}", font.name);
+            assert_eq!(0, font.text_width(""));
+        }
+    }
+
+    #[test]
+    fn test_font_advance_proportional() {
+        let font = &FONT_5X8_PROPORTIONAL;
+        assert!(font.is_proportional());
+
+        assert_eq!(3, font.advance(' '));
+        assert_eq!(2, font.advance('!'));
+        assert_eq!(4, font.advance('i'));
+        assert_eq!(6, font.advance('W'));
+        assert_eq!(4, font.advance('\u{00e9}'));
+        assert_eq!(5, font.advance('\u{250c}'));
+        assert_eq!(5, font.advance('\u{2603}'));
+    }
+
+    #[test]
+    fn test_font_text_width_proportional() {
+        let font = &FONT_5X8_PROPORTIONAL;
+
+        assert_eq!(0, font.text_width(""));
+        assert_eq!(2, font.text_width("!"));
+        assert_eq!(2 + 2 + 2, font.text_width("!!!"));
+        assert_eq!(4 + 3 + 6, font.text_width("i W"));
+        assert!(font.text_width("iii") < FONT_5X8.text_width("iii"));
+        assert_eq!(5 + 5 + 6, font.text_width("\u{2500}\u{2603}W"));
+    }
+
+    #[test]
+    fn test_font_glyph_proportional_shifted() {
+        let expected: &[u8] = &[
+            0x80, // #
+            0x80, // #
+            0x80, // #
+            0x80, // #
+            0x00, //
+            0x80, // #
+            0x00, //
+            0x00, //
+        ];
+        assert_eq!(expected, FONT_5X8_PROPORTIONAL.glyph('!').as_ref());
 
-        let data = font.glyph(char::from(30));
-        assert_eq!(expected, data);
+        assert_eq!(FONT_5X8.glyph('\u{250c}'), FONT_5X8_PROPORTIONAL.glyph('\u{250c}'));
     }
 }
@@ -0,0 +1,628 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Driver for 320x240 LCDs based on the ILI9341 controller.
+
+use super::fonts::Fonts;
+use super::{
+    take_backlight_flags, take_font_flag, take_rotation_flag, to_xy_size, Backlight, BufferedLcd,
+    Lcd, LcdSize, LcdXY, RGB565Pixel,
+};
+use crate::console::graphics::{InputOps, RasterOps};
+use crate::console::{ConsoleSpec, GraphicsConsole, ScreenRotation, RGB};
+use crate::gpio::{Pin, PinMode, Pins};
+use crate::pwm::PwmChannel;
+use crate::spi::{write_all, SpiBus, SpiMode};
+use std::io;
+use std::time::Duration;
+
+/// Default GPIO pin that selects between commands (low) and data (high).
+const DEFAULT_PIN_DC: Pin = Pin(25);
+
+/// Default GPIO pin wired to the reset line of the controller.
+const DEFAULT_PIN_RST: Pin = Pin(27);
+
+/// Clock rate of the SPI bus.  The datasheet specifies a 100ns write cycle but all modules we know
+/// of work well at higher rates, which make a big difference when refreshing the whole screen.
+const SPI_CLOCK_HZ: u32 = 32_000_000;
+
+/// Width of the panel in its native (landscape) orientation.
+const NATIVE_WIDTH: usize = 320;
+
+/// Height of the panel in its native (landscape) orientation.
+const NATIVE_HEIGHT: usize = 240;
+
+/// Software reset command.
+const CMD_SWRESET: u8 = 0x01;
+
+/// Sleep out command.
+const CMD_SLPOUT: u8 = 0x11;
+
+/// Display on command.
+const CMD_DISPON: u8 = 0x29;
+
+/// Column address set command, which defines the horizontal extent of the next memory write.
+const CMD_CASET: u8 = 0x2a;
+
+/// Page address set command, which defines the vertical extent of the next memory write.
+const CMD_PASET: u8 = 0x2b;
+
+/// Memory write command, which is followed by the pixel data.
+const CMD_RAMWR: u8 = 0x2c;
+
+/// Memory access control command, which defines the scan direction of the frame memory.
+const CMD_MADCTL: u8 = 0x36;
+
+/// Row address order bit of `CMD_MADCTL`.
+const MADCTL_MY: u8 = 0x80;
+
+/// Column address order bit of `CMD_MADCTL`.
+const MADCTL_MX: u8 = 0x40;
+
+/// Row/column exchange bit of `CMD_MADCTL`.
+const MADCTL_MV: u8 = 0x20;
+
+/// BGR color order bit of `CMD_MADCTL`, which all known modules need.
+const MADCTL_BGR: u8 = 0x08;
+
+/// Commands and their parameters to configure the controller after a software reset.  This omits
+/// the memory access control, which depends on the rotation.
+const INIT_SEQUENCE: &[(u8, &[u8])] = &[
+    (0xef, &[0x03, 0x80, 0x02]),
+    // Power control B.
+    (0xcf, &[0x00, 0xc1, 0x30]),
+    // Power on sequence control.
+    (0xed, &[0x64, 0x03, 0x12, 0x81]),
+    // Driver timing control A.
+    (0xe8, &[0x85, 0x00, 0x78]),
+    // Power control A.
+    (0xcb, &[0x39, 0x2c, 0x00, 0x34, 0x02]),
+    // Pump ratio control.
+    (0xf7, &[0x20]),
+    // Driver timing control B.
+    (0xea, &[0x00, 0x00]),
+    // Power control 1 and 2.
+    (0xc0, &[0x23]),
+    (0xc1, &[0x10]),
+    // VCOM control 1 and 2.
+    (0xc5, &[0x3e, 0x28]),
+    (0xc7, &[0x86]),
+    // Vertical scrolling start address.
+    (0x37, &[0x00]),
+    // 16 bits per pixel (RGB565).
+    (0x3a, &[0x55]),
+    // Frame rate control.
+    (0xb1, &[0x00, 0x18]),
+    // Display function control.
+    (0xb6, &[0x08, 0x82, 0x27]),
+    // Disable 3-gamma and select gamma curve 1.
+    (0xf2, &[0x00]),
+    (0x26, &[0x01]),
+    // Positive and negative gamma correction.
+    (
+        0xe0,
+        &[0x0f, 0x31, 0x2b, 0x0c, 0x0e, 0x08, 0x4e, 0xf1, 0x37, 0x07, 0x10, 0x03, 0x0e, 0x09, 0x00],
+    ),
+    (
+        0xe1,
+        &[0x00, 0x0e, 0x14, 0x03, 0x11, 0x07, 0x31, 0xc1, 0x48, 0x08, 0x0f, 0x0c, 0x31, 0x36, 0x0f],
+    ),
+];
+
+/// Computes the value of the memory access control register to display the screen with
+/// `rotation`, relative to the landscape orientation.
+fn madctl(rotation: ScreenRotation) -> u8 {
+    let scan = match rotation {
+        ScreenRotation::Deg0 => MADCTL_MV,
+        ScreenRotation::Deg90 => MADCTL_MY,
+        ScreenRotation::Deg180 => MADCTL_MY | MADCTL_MX | MADCTL_MV,
+        ScreenRotation::Deg270 => MADCTL_MX,
+    };
+    scan | MADCTL_BGR
+}
+
+/// LCD handler for an ILI9341 controller attached to an SPI bus.
+///
+/// The controller takes care of screen rotations by changing the order in which it scans its
+/// frame memory, so pixel data never has to be rearranged before sending it.
+pub struct Ili9341Lcd<P: Pins, B, W: PwmChannel> {
+    pins: P,
+    spi_bus: B,
+    pin_dc: Pin,
+    pin_rst: Pin,
+    backlight: Option<Backlight<W>>,
+    rotation: ScreenRotation,
+}
+
+impl<P: Pins, B: SpiBus, W: PwmChannel> Ili9341Lcd<P, B, W> {
+    /// Initializes the LCD connected to `spi_bus` and to the `pin_dc` and `pin_rst` GPIO pins,
+    /// controlling its brightness via `backlight` if available.
+    pub fn new(
+        mut pins: P,
+        spi_bus: B,
+        pin_dc: Pin,
+        pin_rst: Pin,
+        backlight: Option<Backlight<W>>,
+    ) -> io::Result<Self> {
+        pins.setup(pin_dc, PinMode::Out)?;
+        pins.setup(pin_rst, PinMode::Out)?;
+        if let Some(Backlight::Pin(pin)) = backlight {
+            pins.setup(pin, PinMode::Out)?;
+        }
+
+        let mut lcd =
+            Self { pins, spi_bus, pin_dc, pin_rst, backlight, rotation: ScreenRotation::Deg0 };
+        lcd.init()?;
+        Ok(lcd)
+    }
+
+    /// Sends the `cmd` command followed by its `params`, if any.
+    fn command(&mut self, cmd: u8, params: &[u8]) -> io::Result<()> {
+        self.pins.write(self.pin_dc, false)?;
+        write_all(&mut self.spi_bus, &[cmd])?;
+        if !params.is_empty() {
+            self.data(params)?;
+        }
+        Ok(())
+    }
+
+    /// Sends `data` as the parameters of the last command.
+    fn data(&mut self, data: &[u8]) -> io::Result<()> {
+        self.pins.write(self.pin_dc, true)?;
+        write_all(&mut self.spi_bus, data)
+    }
+
+    /// Resets and configures the controller, and turns the display on.
+    fn init(&mut self) -> io::Result<()> {
+        if let Some(backlight) = self.backlight.as_mut() {
+            backlight.set(&mut self.pins, 100)?;
+        }
+
+        self.pins.write(self.pin_rst, true)?;
+        std::thread::sleep(Duration::from_millis(5));
+        self.pins.write(self.pin_rst, false)?;
+        std::thread::sleep(Duration::from_millis(20));
+        self.pins.write(self.pin_rst, true)?;
+        std::thread::sleep(Duration::from_millis(150));
+
+        self.command(CMD_SWRESET, &[])?;
+        std::thread::sleep(Duration::from_millis(150));
+
+        for (cmd, params) in INIT_SEQUENCE {
+            self.command(*cmd, params)?;
+        }
+        self.command(CMD_MADCTL, &[madctl(self.rotation)])?;
+
+        self.command(CMD_SLPOUT, &[])?;
+        std::thread::sleep(Duration::from_millis(120));
+        self.command(CMD_DISPON, &[])
+    }
+
+    /// Configures the controller so that the next memory write, which carries pixel data, affects
+    /// the region at `xy` of `size`.
+    fn set_window(&mut self, xy: LcdXY, size: LcdSize) -> io::Result<()> {
+        let x1 = xy.x as u16;
+        let x2 = (xy.x + size.width - 1) as u16;
+        let y1 = xy.y as u16;
+        let y2 = (xy.y + size.height - 1) as u16;
+
+        let mut params = [0; 4];
+        params[0..2].copy_from_slice(&x1.to_be_bytes());
+        params[2..4].copy_from_slice(&x2.to_be_bytes());
+        self.command(CMD_CASET, &params)?;
+
+        params[0..2].copy_from_slice(&y1.to_be_bytes());
+        params[2..4].copy_from_slice(&y2.to_be_bytes());
+        self.command(CMD_PASET, &params)?;
+
+        self.command(CMD_RAMWR, &[])
+    }
+}
+
+impl<P: Pins, B, W: PwmChannel> Drop for Ili9341Lcd<P, B, W> {
+    fn drop(&mut self) {
+        if let Some(backlight) = self.backlight.as_mut() {
+            let _result = backlight.set(&mut self.pins, 0);
+        }
+    }
+}
+
+impl<P: Pins, B: SpiBus, W: PwmChannel> Lcd for Ili9341Lcd<P, B, W> {
+    type Pixel = RGB565Pixel;
+
+    fn info(&self) -> (LcdSize, usize) {
+        let size = if self.rotation.is_transposed() {
+            LcdSize { width: NATIVE_HEIGHT, height: NATIVE_WIDTH }
+        } else {
+            LcdSize { width: NATIVE_WIDTH, height: NATIVE_HEIGHT }
+        };
+        (size, 2)
+    }
+
+    fn encode(&self, rgb: RGB) -> Self::Pixel {
+        RGB565Pixel::from_rgb(rgb)
+    }
+
+    fn set_data(&mut self, x1y1: LcdXY, x2y2: LcdXY, data: &[u8]) -> io::Result<()> {
+        let (xy, size) = to_xy_size(x1y1, x2y2);
+        self.set_window(xy, size)?;
+        self.data(data)
+    }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        match self.backlight.as_mut() {
+            Some(backlight) => backlight.set(&mut self.pins, pct),
+            None => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "No backlight_pin nor backlight_pwm configured for this LCD",
+            )),
+        }
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<bool> {
+        self.command(CMD_MADCTL, &[madctl(rotation)])?;
+        self.rotation = rotation;
+        Ok(true)
+    }
+}
+
+/// Console backed by an ILI9341 LCD.
+pub type Ili9341Console<P, B, W, K> = GraphicsConsole<K, BufferedLcd<Ili9341Lcd<P, B, W>>>;
+
+/// Initializes a new console on an ILI9341 LCD connected to the first SPI bus, reading input from
+/// `keyboard`.
+///
+/// The backlight can only be controlled if the `spec` requests it via a GPIO pin or a PWM channel,
+/// in which case `new_pwm` is used to open the latter.
+pub fn new_console<P, F, B, G, W, K>(
+    pins: P,
+    new_spi: F,
+    new_pwm: G,
+    keyboard: K,
+    spec: &mut ConsoleSpec,
+    fonts: &Fonts,
+) -> io::Result<Ili9341Console<P, B, W, K>>
+where
+    P: Pins,
+    F: FnOnce(u8, u8, u32, SpiMode) -> io::Result<B>,
+    B: SpiBus,
+    G: FnOnce(u8) -> io::Result<W>,
+    W: PwmChannel,
+    K: InputOps,
+{
+    let default_fg_color = spec.take_keyed_flag::<u8>("fg_color")?;
+    let default_bg_color = spec.take_keyed_flag::<u8>("bg_color")?;
+    let font = take_font_flag(spec, fonts, "5x8")?;
+    let rotation = take_rotation_flag(spec)?;
+    let backlight = take_backlight_flags(spec, None, new_pwm)?;
+    let pin_dc = spec.take_keyed_flag::<u8>("dc_pin")?.map(Pin).unwrap_or(DEFAULT_PIN_DC);
+    let pin_rst = spec.take_keyed_flag::<u8>("rst_pin")?.map(Pin).unwrap_or(DEFAULT_PIN_RST);
+
+    let spi_bus = new_spi(0, 0, SPI_CLOCK_HZ, SpiMode::Mode0)?;
+    let lcd = Ili9341Lcd::new(pins, spi_bus, pin_dc, pin_rst, backlight)?;
+    let mut lcd = BufferedLcd::new(lcd, font)?;
+    lcd.set_rotation(rotation)?;
+    GraphicsConsole::new(keyboard, lcd, default_fg_color, default_bg_color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{CharsXY, Console, Key, PixelsXY, SizeInPixels};
+    use async_trait::async_trait;
+    use std::cell::RefCell;
+    use std::io::Write;
+    use std::rc::Rc;
+
+    /// A transfer observed on the wire, decoded according to the state of the DC pin.
+    #[derive(Debug, PartialEq)]
+    enum Transfer {
+        Command(u8),
+        Data(Vec<u8>),
+    }
+
+    /// State of the fake hardware shared by the fake pins and the fake SPI bus.
+    #[derive(Default)]
+    struct Wire {
+        dc: bool,
+        pin_writes: Vec<(Pin, bool)>,
+        transfers: Vec<Transfer>,
+    }
+
+    /// Fake GPIO pins that track the DC pin and record all other writes.
+    struct FakePins(Rc<RefCell<Wire>>);
+
+    impl Pins for FakePins {
+        fn setup(&mut self, _pin: Pin, _mode: PinMode) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear(&mut self, _pin: Pin) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear_all(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn read(&mut self, _pin: Pin) -> io::Result<bool> {
+            Ok(false)
+        }
+
+        fn write(&mut self, pin: Pin, v: bool) -> io::Result<()> {
+            let mut wire = self.0.borrow_mut();
+            if pin == DEFAULT_PIN_DC {
+                wire.dc = v;
+            } else {
+                wire.pin_writes.push((pin, v));
+            }
+            Ok(())
+        }
+    }
+
+    /// Fake SPI bus that decodes the bytes written to it into commands and data, merging
+    /// consecutive data writes as the controller would.
+    struct FakeSpiBus(Rc<RefCell<Wire>>);
+
+    impl Write for FakeSpiBus {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let buf = &buf[0..buf.len().min(self.max_size())];
+            let mut wire = self.0.borrow_mut();
+            if wire.dc {
+                match wire.transfers.last_mut() {
+                    Some(Transfer::Data(data)) => data.extend_from_slice(buf),
+                    _ => wire.transfers.push(Transfer::Data(buf.to_owned())),
+                }
+            } else {
+                for b in buf {
+                    wire.transfers.push(Transfer::Command(*b));
+                }
+            }
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SpiBus for FakeSpiBus {
+        fn max_size(&self) -> usize {
+            64
+        }
+    }
+
+    /// Records the duty cycles set on the PWM channel.
+    #[derive(Default)]
+    struct FakePwm {
+        duties: Rc<RefCell<Vec<f64>>>,
+    }
+
+    impl PwmChannel for FakePwm {
+        fn set_duty_cycle(&mut self, duty: f64) -> io::Result<()> {
+            self.duties.borrow_mut().push(duty);
+            Ok(())
+        }
+    }
+
+    /// Input operations that never return any key presses.
+    struct NoInput {}
+
+    #[async_trait(?Send)]
+    impl InputOps for NoInput {
+        async fn poll_key(&mut self) -> io::Result<Option<Key>> {
+            Ok(None)
+        }
+
+        async fn read_key(&mut self) -> io::Result<Key> {
+            Ok(Key::Eof)
+        }
+    }
+
+    /// Instantiates an LCD attached to a fake wire and returns both.
+    fn new_lcd(
+        backlight: Option<Backlight<FakePwm>>,
+    ) -> (Ili9341Lcd<FakePins, FakeSpiBus, FakePwm>, Rc<RefCell<Wire>>) {
+        let wire = Rc::from(RefCell::from(Wire::default()));
+        let lcd = Ili9341Lcd::new(
+            FakePins(wire.clone()),
+            FakeSpiBus(wire.clone()),
+            DEFAULT_PIN_DC,
+            DEFAULT_PIN_RST,
+            backlight,
+        )
+        .unwrap();
+        (lcd, wire)
+    }
+
+    /// Syntactic sugar to express a command with parameters in the expected transfers.
+    fn cmd(cmd: u8, params: &[u8]) -> Vec<Transfer> {
+        let mut transfers = vec![Transfer::Command(cmd)];
+        if !params.is_empty() {
+            transfers.push(Transfer::Data(params.to_owned()));
+        }
+        transfers
+    }
+
+    #[test]
+    fn test_init() {
+        let (_lcd, wire) = new_lcd(None);
+
+        let mut exp_transfers = vec![];
+        exp_transfers.extend(cmd(0x01, &[]));
+        exp_transfers.extend(cmd(0xef, &[0x03, 0x80, 0x02]));
+        exp_transfers.extend(cmd(0xcf, &[0x00, 0xc1, 0x30]));
+        exp_transfers.extend(cmd(0xed, &[0x64, 0x03, 0x12, 0x81]));
+        exp_transfers.extend(cmd(0xe8, &[0x85, 0x00, 0x78]));
+        exp_transfers.extend(cmd(0xcb, &[0x39, 0x2c, 0x00, 0x34, 0x02]));
+        exp_transfers.extend(cmd(0xf7, &[0x20]));
+        exp_transfers.extend(cmd(0xea, &[0x00, 0x00]));
+        exp_transfers.extend(cmd(0xc0, &[0x23]));
+        exp_transfers.extend(cmd(0xc1, &[0x10]));
+        exp_transfers.extend(cmd(0xc5, &[0x3e, 0x28]));
+        exp_transfers.extend(cmd(0xc7, &[0x86]));
+        exp_transfers.extend(cmd(0x37, &[0x00]));
+        exp_transfers.extend(cmd(0x3a, &[0x55]));
+        exp_transfers.extend(cmd(0xb1, &[0x00, 0x18]));
+        exp_transfers.extend(cmd(0xb6, &[0x08, 0x82, 0x27]));
+        exp_transfers.extend(cmd(0xf2, &[0x00]));
+        exp_transfers.extend(cmd(0x26, &[0x01]));
+        exp_transfers.extend(cmd(
+            0xe0,
+            &[
+                0x0f, 0x31, 0x2b, 0x0c, 0x0e, 0x08, 0x4e, 0xf1, 0x37, 0x07, 0x10, 0x03, 0x0e, 0x09,
+                0x00,
+            ],
+        ));
+        exp_transfers.extend(cmd(
+            0xe1,
+            &[
+                0x00, 0x0e, 0x14, 0x03, 0x11, 0x07, 0x31, 0xc1, 0x48, 0x08, 0x0f, 0x0c, 0x31, 0x36,
+                0x0f,
+            ],
+        ));
+        exp_transfers.extend(cmd(0x36, &[0x28]));
+        exp_transfers.extend(cmd(0x11, &[]));
+        exp_transfers.extend(cmd(0x29, &[]));
+
+        let wire = wire.borrow();
+        assert_eq!(exp_transfers, wire.transfers);
+        assert_eq!(
+            vec![(DEFAULT_PIN_RST, true), (DEFAULT_PIN_RST, false), (DEFAULT_PIN_RST, true)],
+            wire.pin_writes
+        );
+    }
+
+    #[test]
+    fn test_set_data() {
+        let (mut lcd, wire) = new_lcd(None);
+        wire.borrow_mut().transfers.clear();
+
+        let data = (0..200).map(|i| i as u8).collect::<Vec<u8>>();
+        lcd.set_data(LcdXY { x: 300, y: 4 }, LcdXY { x: 309, y: 13 }, &data).unwrap();
+
+        let mut exp_transfers = vec![];
+        exp_transfers.extend(cmd(0x2a, &[0x01, 0x2c, 0x01, 0x35]));
+        exp_transfers.extend(cmd(0x2b, &[0x00, 0x04, 0x00, 0x0d]));
+        exp_transfers.extend(cmd(0x2c, &[]));
+        exp_transfers.push(Transfer::Data(data));
+        assert_eq!(exp_transfers, wire.borrow().transfers);
+    }
+
+    #[test]
+    fn test_set_rotation() {
+        let (mut lcd, wire) = new_lcd(None);
+        wire.borrow_mut().transfers.clear();
+
+        assert_eq!((LcdSize { width: 320, height: 240 }, 2), lcd.info());
+        for (rotation, exp_madctl, exp_size) in [
+            (ScreenRotation::Deg90, 0x88, LcdSize { width: 240, height: 320 }),
+            (ScreenRotation::Deg180, 0xe8, LcdSize { width: 320, height: 240 }),
+            (ScreenRotation::Deg270, 0x48, LcdSize { width: 240, height: 320 }),
+            (ScreenRotation::Deg0, 0x28, LcdSize { width: 320, height: 240 }),
+        ] {
+            assert!(lcd.set_rotation(rotation).unwrap());
+            assert_eq!(cmd(0x36, &[exp_madctl]), wire.borrow().transfers);
+            assert_eq!((exp_size, 2), lcd.info());
+            wire.borrow_mut().transfers.clear();
+        }
+    }
+
+    #[test]
+    fn test_rotation_through_buffered_lcd() {
+        let (lcd, wire) = new_lcd(None);
+        let mut lcd = BufferedLcd::new(lcd, &crate::gfx::lcd::fonts::FONT_5X8).unwrap();
+        wire.borrow_mut().transfers.clear();
+
+        lcd.set_rotation(ScreenRotation::Deg90).unwrap();
+        assert_eq!(240, lcd.get_info().size_pixels.width);
+        assert_eq!(320, lcd.get_info().size_pixels.height);
+        lcd.set_draw_color((255, 0, 0));
+        lcd.draw_pixel(PixelsXY::new(239, 1)).unwrap();
+
+        // The coordinates are sent as given because the controller handles the rotation.
+        let mut exp_transfers = vec![];
+        exp_transfers.extend(cmd(0x36, &[0x88]));
+        exp_transfers.extend(cmd(0x2a, &[0x00, 0xef, 0x00, 0xef]));
+        exp_transfers.extend(cmd(0x2b, &[0x00, 0x01, 0x00, 0x01]));
+        exp_transfers.extend(cmd(0x2c, &[]));
+        exp_transfers.push(Transfer::Data(vec![0xf8, 0x00]));
+        assert_eq!(exp_transfers, wire.borrow().transfers);
+    }
+
+    #[test]
+    fn test_backlight() {
+        let duties = Rc::from(RefCell::from(vec![]));
+        let (mut lcd, _wire) = new_lcd(Some(Backlight::Pwm(FakePwm { duties: duties.clone() })));
+        lcd.set_brightness(40).unwrap();
+        drop(lcd);
+        assert_eq!(vec![1.0, 0.4, 0.0], *duties.borrow());
+    }
+
+    #[test]
+    fn test_backlight_not_configured() {
+        let (mut lcd, _wire) = new_lcd(None);
+        assert_eq!(io::ErrorKind::Unsupported, lcd.set_brightness(40).unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_new_console_flags() {
+        let wire = Rc::from(RefCell::from(Wire::default()));
+        let mut spec = ConsoleSpec::init("ili9341:rotation=270,font=16x16,backlight_pin=18");
+        let console = new_console(
+            FakePins(wire.clone()),
+            |bus, slave, clock, mode| {
+                assert_eq!((0, 0, SPI_CLOCK_HZ, SpiMode::Mode0), (bus, slave, clock, mode));
+                Ok(FakeSpiBus(wire.clone()))
+            },
+            |_channel| -> io::Result<FakePwm> { panic!("PWM not requested") },
+            NoInput {},
+            &mut spec,
+            &crate::gfx::lcd::fonts::all_fonts(),
+        )
+        .unwrap();
+        spec.finish().unwrap();
+        assert_eq!(SizeInPixels::new(240, 320), console.size_pixels().unwrap());
+        assert_eq!(CharsXY::new(15, 20), console.size_chars().unwrap());
+        assert_eq!((Pin(18), true), wire.borrow().pin_writes[0]);
+    }
+
+    #[test]
+    fn test_new_console_errors() {
+        for (spec, exp_error) in [
+            ("ili9341:rotation=45", "Invalid rotation: 45; must be one of 0, 90, 180 or 270"),
+            ("ili9341:font=foo", "Unknown font: foo; valid names are: 16x16, 5x8, 5x8p"),
+            (
+                "ili9341:backlight_pin=1,backlight_pwm=0",
+                "backlight_pin and backlight_pwm are mutually exclusive",
+            ),
+        ] {
+            let wire = Rc::from(RefCell::from(Wire::default()));
+            let mut spec = ConsoleSpec::init(spec);
+            let result = new_console(
+                FakePins(wire.clone()),
+                |_, _, _, _| Ok(FakeSpiBus(wire.clone())),
+                |_channel| Ok(FakePwm::default()),
+                NoInput {},
+                &mut spec,
+                &crate::gfx::lcd::fonts::all_fonts(),
+            );
+            match result {
+                Ok(_) => panic!("Expected an error for {}", exp_error),
+                Err(e) => assert_eq!(exp_error, e.to_string()),
+            }
+        }
+    }
+}
@@ -15,12 +15,16 @@
 
 //! Generic types to represent and manipulate LCDs.
 
-use crate::console::{SizeInPixels, RGB};
+use crate::console::{ConsoleSpec, ParseError, ScreenRotation, SizeInPixels, RGB};
+use crate::gpio::{Pin, Pins};
+use crate::pwm::PwmChannel;
+use fonts::{Font, Fonts};
 use std::convert::TryFrom;
 use std::io;
 
 mod buffered;
 pub mod fonts;
+pub mod ili9341;
 
 pub use buffered::BufferedLcd;
 
@@ -34,6 +38,19 @@ pub trait AsByteSlice {
 #[derive(Clone, Copy)]
 pub struct RGB565Pixel(pub [u8; 2]);
 
+impl RGB565Pixel {
+    /// Encodes an `rgb` color as a big-endian RGB565 value, which is what SPI LCDs expect.
+    pub fn from_rgb(rgb: RGB) -> Self {
+        let rgb = (u16::from(rgb.0), u16::from(rgb.1), u16::from(rgb.2));
+
+        let pixel: u16 = ((rgb.0 >> 3) << 11) | ((rgb.1 >> 2) << 5) | (rgb.2 >> 3);
+
+        let high = (pixel >> 8) as u8;
+        let low = (pixel & 0xff) as u8;
+        Self([high, low])
+    }
+}
+
 impl AsByteSlice for RGB565Pixel {
     fn as_slice(&self) -> &[u8] {
         &self.0
@@ -54,6 +71,108 @@ pub trait Lcd {
     /// Fills the area expressed by `x1y1` to `x2y2` by the pixel `data`.  The length of `data`
     /// should be the size of the window in pixels multiplied by the `Pixel` size.
     fn set_data(&mut self, x1y1: LcdXY, x2y2: LcdXY, data: &[u8]) -> io::Result<()>;
+
+    /// Sets the brightness of the backlight to `_pct` percent, where 0 turns it off.
+    ///
+    /// LCDs whose backlight is not controllable should leave the default implementation, which
+    /// reports the operation as unsupported.
+    fn set_brightness(&mut self, _pct: u8) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Backlight control not supported by this LCD",
+        ))
+    }
+
+    /// Configures the LCD to show its contents rotated by `_rotation` with respect to its native
+    /// orientation.
+    ///
+    /// Returns true if the LCD controller applies the rotation itself, in which case `info` must
+    /// report the rotated dimensions and `set_data` must accept coordinates and pixel data in the
+    /// rotated space.  The default implementation returns false so that callers rotate the pixel
+    /// data themselves.
+    fn set_rotation(&mut self, _rotation: ScreenRotation) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+/// Mechanism to control the backlight of an LCD.
+pub enum Backlight<W> {
+    /// Backlight wired to a GPIO pin, which can only be switched on or off.
+    Pin(Pin),
+
+    /// Backlight driven by a PWM channel, which supports variable brightness.
+    Pwm(W),
+}
+
+impl<W: PwmChannel> Backlight<W> {
+    /// Sets the brightness of the backlight to `pct` percent using `pins` if necessary.
+    pub fn set<P: Pins>(&mut self, pins: &mut P, pct: u8) -> io::Result<()> {
+        match self {
+            Backlight::Pin(pin) => pins.write(*pin, pct > 0),
+            Backlight::Pwm(pwm) => pwm.set_duty_cycle(f64::from(pct) / 100.0),
+        }
+    }
+}
+
+/// Obtains the console font from the `font` flag in `spec`, or uses `default` if not present.
+pub fn take_font_flag(
+    spec: &mut ConsoleSpec,
+    fonts: &Fonts,
+    default: &str,
+) -> io::Result<&'static Font> {
+    let font_name = spec.take_keyed_flag_str("font").unwrap_or(default);
+    match fonts.get(font_name) {
+        Some(font) => Ok(font),
+        None => {
+            let mut valid = fonts.keys().copied().collect::<Vec<&'static str>>();
+            valid.sort();
+            Err(ParseError(format!(
+                "Unknown font: {}; valid names are: {}",
+                font_name,
+                valid.join(", ")
+            ))
+            .into())
+        }
+    }
+}
+
+/// Obtains the screen rotation from the `rotation` flag in `spec`, which defaults to none.
+pub fn take_rotation_flag(spec: &mut ConsoleSpec) -> io::Result<ScreenRotation> {
+    match spec.take_keyed_flag::<i32>("rotation")? {
+        Some(degrees) => match ScreenRotation::from_degrees(degrees) {
+            Some(rotation) => Ok(rotation),
+            None => Err(ParseError(format!(
+                "Invalid rotation: {}; must be one of 0, 90, 180 or 270",
+                degrees
+            ))
+            .into()),
+        },
+        None => Ok(ScreenRotation::Deg0),
+    }
+}
+
+/// Obtains the backlight configuration from the `backlight_pin` and `backlight_pwm` flags in
+/// `spec`, using `new_pwm` to open the PWM channel if requested.  If neither flag is present,
+/// returns the backlight wired to `default_pin`, if any.
+pub fn take_backlight_flags<G, W>(
+    spec: &mut ConsoleSpec,
+    default_pin: Option<Pin>,
+    new_pwm: G,
+) -> io::Result<Option<Backlight<W>>>
+where
+    G: FnOnce(u8) -> io::Result<W>,
+{
+    let backlight_pin = spec.take_keyed_flag::<u8>("backlight_pin")?;
+    let backlight_pwm = spec.take_keyed_flag::<u8>("backlight_pwm")?;
+    match (backlight_pin, backlight_pwm) {
+        (None, None) => Ok(default_pin.map(Backlight::Pin)),
+        (Some(pin), None) => Ok(Some(Backlight::Pin(Pin(pin)))),
+        (None, Some(channel)) => Ok(Some(Backlight::Pwm(new_pwm(channel)?))),
+        (Some(_), Some(_)) => {
+            Err(ParseError("backlight_pin and backlight_pwm are mutually exclusive".to_owned())
+                .into())
+        }
+    }
 }
 
 /// Represents valid coordinates within the LCD space.
@@ -116,6 +235,49 @@ pub fn to_xy_size(x1y1: LcdXY, x2y2: LcdXY) -> (LcdXY, LcdSize) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gpio::PinMode;
+
+    /// Records the values written to the output pins.
+    #[derive(Default)]
+    struct MockPins {
+        writes: Vec<(Pin, bool)>,
+    }
+
+    impl Pins for MockPins {
+        fn setup(&mut self, _pin: Pin, _mode: PinMode) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear(&mut self, _pin: Pin) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn clear_all(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn read(&mut self, _pin: Pin) -> io::Result<bool> {
+            Ok(true)
+        }
+
+        fn write(&mut self, pin: Pin, v: bool) -> io::Result<()> {
+            self.writes.push((pin, v));
+            Ok(())
+        }
+    }
+
+    /// Records the duty cycles set on the PWM channel.
+    #[derive(Default)]
+    struct MockPwm {
+        duties: Vec<f64>,
+    }
+
+    impl PwmChannel for MockPwm {
+        fn set_duty_cycle(&mut self, duty: f64) -> io::Result<()> {
+            self.duties.push(duty);
+            Ok(())
+        }
+    }
 
     /// Syntactic sugar to instantiate a coordinate in the LCD space.
     fn xy(x: usize, y: usize) -> LcdXY {
@@ -158,4 +320,31 @@ mod tests {
             to_xy_size(xy(10, 20), xy(14, 26))
         );
     }
+
+    #[test]
+    fn test_backlight_pin() {
+        let mut pins = MockPins::default();
+        let mut backlight = Backlight::<MockPwm>::Pin(Pin(7));
+        for pct in [100, 0, 30, 1] {
+            backlight.set(&mut pins, pct).unwrap();
+        }
+        assert_eq!(
+            vec![(Pin(7), true), (Pin(7), false), (Pin(7), true), (Pin(7), true)],
+            pins.writes
+        );
+    }
+
+    #[test]
+    fn test_backlight_pwm() {
+        let mut pins = MockPins::default();
+        let mut backlight = Backlight::Pwm(MockPwm::default());
+        for pct in [100, 0, 25, 1] {
+            backlight.set(&mut pins, pct).unwrap();
+        }
+        assert!(pins.writes.is_empty());
+        match backlight {
+            Backlight::Pwm(pwm) => assert_eq!(vec![1.0, 0.0, 0.25, 0.01], pwm.duties),
+            Backlight::Pin(_) => unreachable!(),
+        }
+    }
 }
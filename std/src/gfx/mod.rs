@@ -15,7 +15,7 @@
 
 //! Commands for graphical console interaction.
 
-use crate::console::{Console, PixelsXY};
+use crate::console::{ClipRect, Console, PixelsXY, ScreenRotation, SizeInPixels, TextRotation};
 use async_trait::async_trait;
 use endbasic_core::ast::{ArgSep, ExprType};
 use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
@@ -25,6 +25,7 @@ use endbasic_core::LineCol;
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::convert::TryFrom;
+use std::io;
 use std::rc::Rc;
 
 pub mod lcd;
@@ -49,6 +50,9 @@ fn parse_coordinates(xvalue: i32, xpos: LineCol, yvalue: i32, ypos: LineCol) ->
     Ok(PixelsXY { x: parse_coordinate(xvalue, xpos)?, y: parse_coordinate(yvalue, ypos)? })
 }
 
+/// Maximum scale factor accepted by `GFX_TEXT`.
+pub const MAX_TEXT_SCALE: u16 = 16;
+
 /// Parses an expression that represents a radius.
 fn parse_radius(i: i32, pos: LineCol) -> Result<u16> {
     match u16::try_from(i) {
@@ -58,6 +62,46 @@ fn parse_radius(i: i32, pos: LineCol) -> Result<u16> {
     }
 }
 
+/// Parses an expression that represents the `what` dimension of a rectangle.
+fn parse_dimension(what: &str, i: i32, pos: LineCol) -> Result<u16> {
+    match u16::try_from(i) {
+        Ok(i) if i > 0 => Ok(i),
+        _ if i <= 0 => Err(Error::SyntaxError(pos, format!("{} {} must be positive", what, i))),
+        _ => Err(Error::SyntaxError(pos, format!("{} {} out of range", what, i))),
+    }
+}
+
+/// Parses an expression that represents a dimension that may be zero.
+fn parse_dimension_or_zero(what: &str, i: i32, pos: LineCol) -> Result<u16> {
+    match u16::try_from(i) {
+        Ok(i) => Ok(i),
+        _ if i < 0 => Err(Error::SyntaxError(pos, format!("{} {} must not be negative", what, i))),
+        _ => Err(Error::SyntaxError(pos, format!("{} {} out of range", what, i))),
+    }
+}
+
+/// Parses an expression that represents a text scale factor.
+fn parse_text_scale(i: i32, pos: LineCol) -> Result<u16> {
+    match u16::try_from(i) {
+        Ok(i) if (1..=MAX_TEXT_SCALE).contains(&i) => Ok(i),
+        _ => Err(Error::SyntaxError(
+            pos,
+            format!("Scale {} must be between 1 and {}", i, MAX_TEXT_SCALE),
+        )),
+    }
+}
+
+/// Parses an expression that represents a text rotation in degrees.
+fn parse_text_rotation(i: i32, pos: LineCol) -> Result<TextRotation> {
+    match i.rem_euclid(360) {
+        0 => Ok(TextRotation::Deg0),
+        90 => Ok(TextRotation::Deg90),
+        180 => Ok(TextRotation::Deg180),
+        270 => Ok(TextRotation::Deg270),
+        _ => Err(Error::SyntaxError(pos, format!("Rotation {} must be a multiple of 90", i))),
+    }
+}
+
 /// The `GFX_CIRCLE` command.
 pub struct GfxCircleCommand {
     metadata: CallableMetadata,
@@ -195,6 +239,250 @@ impl Callable for GfxCirclefCommand {
     }
 }
 
+/// The `GFX_CLIP` command.
+pub struct GfxClipCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxClipCommand {
+    /// Creates a new `GFX_CLIP` command that restricts drawing on `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_CLIP")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("x"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("y"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("width"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("height"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Restricts drawing to a rectangular region of the screen.
+With arguments, the graphics commands that draw shapes and text (GFX_CIRCLE, GFX_CIRCLEF, \
+GFX_LINE, GFX_PIXEL, GFX_RECT, GFX_RECTF and GFX_TEXT) only modify the pixels that fall within \
+the region of width% by height% pixels whose top-left corner is at (x%, y%).  Calling this again \
+replaces the previous region, and a region with a zero width% or height% makes those commands \
+draw nothing.
+With no arguments, this removes the restriction so that the whole screen can be drawn on again.
+The region is ignored by CLS, which always clears the whole screen, by GFX_COPY and GFX_SCROLL, \
+and by the text printed with PRINT.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxClipCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        if scope.nargs() == 0 {
+            self.console.borrow_mut().set_clip(None).map_err(|e| scope.io_error(e))?;
+            return Ok(());
+        }
+
+        debug_assert_eq!(4, scope.nargs());
+        let (xvalue, xpos) = scope.pop_integer_with_pos();
+        let (yvalue, ypos) = scope.pop_integer_with_pos();
+        let (widthvalue, widthpos) = scope.pop_integer_with_pos();
+        let (heightvalue, heightpos) = scope.pop_integer_with_pos();
+
+        let xy = parse_coordinates(xvalue, xpos, yvalue, ypos)?;
+        let width = parse_dimension_or_zero("Width", widthvalue, widthpos)?;
+        let height = parse_dimension_or_zero("Height", heightvalue, heightpos)?;
+
+        let clip = ClipRect::new(xy, width, height);
+        self.console.borrow_mut().set_clip(Some(clip)).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// The `GFX_COPY` command.
+pub struct GfxCopyCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxCopyCommand {
+    /// Creates a new `GFX_COPY` command that copies a region of `console` to another location.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_COPY")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("srcx"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("srcy"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("w"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("h"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("dstx"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("dsty"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Copies a rectangular region of the screen to another location.
+The region to copy has its top-left corner at (srcx,srcy) and is w% pixels wide and h% pixels \
+tall.  Its copy is placed with its top-left corner at (dstx,dsty).  The source and target \
+regions may overlap, in which case the result is as if the whole source region had been read \
+before writing any of the target region.
+Any parts of the regions that fall outside of the screen are ignored, and pixels of the source \
+region that are not overwritten are left untouched.  See GFX_SCROLL to shift the whole screen.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxCopyCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(6, scope.nargs());
+        let (srcxvalue, srcxpos) = scope.pop_integer_with_pos();
+        let (srcyvalue, srcypos) = scope.pop_integer_with_pos();
+        let (wvalue, wpos) = scope.pop_integer_with_pos();
+        let (hvalue, hpos) = scope.pop_integer_with_pos();
+        let (dstxvalue, dstxpos) = scope.pop_integer_with_pos();
+        let (dstyvalue, dstypos) = scope.pop_integer_with_pos();
+
+        let src = parse_coordinates(srcxvalue, srcxpos, srcyvalue, srcypos)?;
+        let size = SizeInPixels::new(
+            parse_dimension("Width", wvalue, wpos)?,
+            parse_dimension("Height", hvalue, hpos)?,
+        );
+        let dst = parse_coordinates(dstxvalue, dstxpos, dstyvalue, dstypos)?;
+
+        self.console.borrow_mut().copy_pixels(src, size, dst).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// The `GFX_FONT` command.
+pub struct GfxFontCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxFontCommand {
+    /// Creates a new `GFX_FONT` command that selects the font for `GFX_TEXT` on `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_FONT")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Selects the font used by GFX_TEXT.
+The available fonts depend on the console.  Fonts whose names end in p, such as 5x8p, are \
+proportional: every character takes only as much horizontal space as it needs, which fits more \
+text on small screens.  Use TEXTWIDTH% to compute the width of a string in the selected font.
+This does not affect the font used by the text console, which must be fixed-width.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxFontCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        self.console.borrow_mut().set_text_font(&name).map_err(|e| match e.kind() {
+            io::ErrorKind::InvalidInput => Error::SyntaxError(pos, e.to_string()),
+            _ => scope.io_error(e),
+        })?;
+        Ok(())
+    }
+}
+
 /// The `GFX_HEIGHT` function.
 pub struct GfxHeightFunction {
     metadata: CallableMetadata,
@@ -520,6 +808,129 @@ impl Callable for GfxRectfCommand {
     }
 }
 
+/// The `GFX_ROTATE` command.
+pub struct GfxRotateCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxRotateCommand {
+    /// Creates a new `GFX_ROTATE` command that rotates the contents of `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_ROTATE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("degrees"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Rotates the whole screen with respect to the physical display.
+degrees% indicates the clockwise rotation and must be a multiple of 90.  A rotation of 0 shows \
+the screen in the native orientation of the display.
+Rotating the screen clears it and, for rotations of 90 and 270 degrees, swaps its width and its \
+height: use GFX_WIDTH, GFX_HEIGHT, SCRCOLS, and SCRROWS to query the new dimensions.
+This is only supported by LCD consoles.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxRotateCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (degrees, pos) = scope.pop_integer_with_pos();
+
+        let rotation = match ScreenRotation::from_degrees(degrees) {
+            Some(rotation) => rotation,
+            None => {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("Rotation {} must be a multiple of 90", degrees),
+                ))
+            }
+        };
+
+        self.console.borrow_mut().set_rotation(rotation).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// The `GFX_SCROLL` command.
+pub struct GfxScrollCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxScrollCommand {
+    /// Creates a new `GFX_SCROLL` command that shifts the contents of `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_SCROLL")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("dx"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("dy"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Shifts the contents of the whole screen.
+The screen is moved dx% pixels to the right and dy% pixels down, or to the left and up if the \
+values are negative.  The contents that fall off the edges are lost and the strips that become \
+exposed are cleared with the background color as selected by COLOR.
+This does not move the text cursor.  See GFX_COPY to move only a region of the screen.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxScrollCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (dxvalue, dxpos) = scope.pop_integer_with_pos();
+        let (dyvalue, dypos) = scope.pop_integer_with_pos();
+
+        let delta = parse_coordinates(dxvalue, dxpos, dyvalue, dypos)?;
+
+        self.console.borrow_mut().scroll_pixels(delta.x, delta.y).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
 /// The `GFX_SYNC` command.
 pub struct GfxSyncCommand {
     metadata: CallableMetadata,
@@ -593,6 +1004,170 @@ impl Callable for GfxSyncCommand {
     }
 }
 
+/// The `GFX_TEXT` command.
+pub struct GfxTextCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl GfxTextCommand {
+    /// Creates a new `GFX_TEXT` command that draws text on `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GFX_TEXT")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("x"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("y"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("s"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("x"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("y"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("s"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("scale"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("x"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("y"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("s"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("scale"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("rotation"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Draws text with its top-left corner at (x,y).
+The text is drawn using the foreground color as selected by COLOR and the font selected by \
+GFX_FONT.  Unlike PRINT, this does not move the cursor nor wrap or scroll the text.
+If scale% is specified, every pixel of the font is drawn as a square block of scale% by scale% \
+pixels, which is useful to render titles.  scale% must be between 1 and 16.  TEXTWIDTH% returns \
+the width of the text at scale 1, so multiply it by scale% to compute the width of scaled text.
+If rotation% is specified, the text is rotated counter-clockwise by that many degrees around \
+(x,y), which must be a multiple of 90.  For example, a rotation% of 90 draws text that runs from \
+the bottom to the top of the screen.  Rotation is not available on all consoles.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GfxTextCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((3..=5).contains(&scope.nargs()));
+        let (xvalue, xpos) = scope.pop_integer_with_pos();
+        let (yvalue, ypos) = scope.pop_integer_with_pos();
+        let text = scope.pop_string();
+        let scale = if scope.nargs() == 0 {
+            1
+        } else {
+            let (scalevalue, scalepos) = scope.pop_integer_with_pos();
+            parse_text_scale(scalevalue, scalepos)?
+        };
+        let rotation = if scope.nargs() == 0 {
+            TextRotation::Deg0
+        } else {
+            let (rotationvalue, rotationpos) = scope.pop_integer_with_pos();
+            parse_text_rotation(rotationvalue, rotationpos)?
+        };
+
+        let xy = parse_coordinates(xvalue, xpos, yvalue, ypos)?;
+
+        self.console
+            .borrow_mut()
+            .draw_text(xy, &text, scale, rotation)
+            .map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
 /// The `GFX_WIDTH` function.
 pub struct GfxWidthFunction {
     metadata: CallableMetadata,
@@ -630,23 +1205,75 @@ impl Callable for GfxWidthFunction {
     }
 }
 
+/// The `TEXTWIDTH` function.
+pub struct TextwidthFunction {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl TextwidthFunction {
+    /// Creates a new instance of the function.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("TEXTWIDTH")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("s"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the width in pixels of s$ when drawn with GFX_TEXT.
+The width depends on the font selected by GFX_FONT.  Use this to center or right-align text \
+drawn with proportional fonts.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for TextwidthFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let text = scope.pop_string();
+
+        let width = self.console.borrow().text_width(&text).map_err(|e| scope.io_error(e))?;
+        scope.return_integer(i32::from(width))
+    }
+}
+
 /// Adds all console-related commands for the given `console` to the `machine`.
 pub fn add_all(machine: &mut Machine, console: Rc<RefCell<dyn Console>>) {
     machine.add_callable(GfxCircleCommand::new(console.clone()));
     machine.add_callable(GfxCirclefCommand::new(console.clone()));
+    machine.add_callable(GfxClipCommand::new(console.clone()));
+    machine.add_callable(GfxCopyCommand::new(console.clone()));
+    machine.add_callable(GfxFontCommand::new(console.clone()));
     machine.add_callable(GfxHeightFunction::new(console.clone()));
     machine.add_callable(GfxLineCommand::new(console.clone()));
     machine.add_callable(GfxPixelCommand::new(console.clone()));
     machine.add_callable(GfxRectCommand::new(console.clone()));
     machine.add_callable(GfxRectfCommand::new(console.clone()));
+    machine.add_callable(GfxRotateCommand::new(console.clone()));
+    machine.add_callable(GfxScrollCommand::new(console.clone()));
     machine.add_callable(GfxSyncCommand::new(console.clone()));
-    machine.add_callable(GfxWidthFunction::new(console));
+    machine.add_callable(GfxTextCommand::new(console.clone()));
+    machine.add_callable(GfxWidthFunction::new(console.clone()));
+    machine.add_callable(TextwidthFunction::new(console));
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::console::SizeInPixels;
     use crate::testutils::*;
 
     /// Verifies error conditions for a command named `name` that takes to X/Y pairs.
@@ -775,6 +1402,93 @@ mod tests {
         check_errors_xy_radius("GFX_CIRCLEF");
     }
 
+    #[test]
+    fn test_gfx_clip_ok() {
+        Tester::default()
+            .run("GFX_CLIP 1, 2, 3, 4")
+            .expect_output([CapturedOut::SetClip(Some(ClipRect::new(PixelsXY::new(1, 2), 3, 4)))])
+            .check();
+
+        Tester::default()
+            .run("GFX_CLIP -31000, 32000.7, 0, 65535: GFX_CLIP")
+            .expect_output([
+                CapturedOut::SetClip(Some(ClipRect::new(PixelsXY::new(-31000, 32001), 0, 65535))),
+                CapturedOut::SetClip(None),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_clip_errors() {
+        for args in &["1", "1, 2, 3", "1, 2, 3, 4, 5", "1, 2, 3; 4"] {
+            check_stmt_compilation_err(
+                "1:1: GFX_CLIP expected <> | <x%, y%, width%, height%>",
+                &format!("GFX_CLIP {}", args),
+            );
+        }
+
+        check_stmt_err("1:10: Coordinate -40000 out of range", "GFX_CLIP -40000, 0, 1, 1");
+        check_stmt_err("1:16: Width -1 must not be negative", "GFX_CLIP 0, 0, -1, 1");
+        check_stmt_err("1:19: Height 65536 out of range", "GFX_CLIP 0, 0, 1, 65536");
+        check_stmt_compilation_err("1:13: STRING is not a number", r#"GFX_CLIP 0, "a", 1, 1"#);
+    }
+
+    #[test]
+    fn test_gfx_copy_ok() {
+        Tester::default()
+            .run("GFX_COPY 1, 2, 3, 4, 5, 6")
+            .expect_output([CapturedOut::CopyPixels(
+                PixelsXY { x: 1, y: 2 },
+                SizeInPixels::new(3, 4),
+                PixelsXY { x: 5, y: 6 },
+            )])
+            .check();
+
+        Tester::default()
+            .run("GFX_COPY -31000, 32000, 1, 65535, 31000.2, -32000.7")
+            .expect_output([CapturedOut::CopyPixels(
+                PixelsXY { x: -31000, y: 32000 },
+                SizeInPixels::new(1, 65535),
+                PixelsXY { x: 31000, y: -32001 },
+            )])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_copy_errors() {
+        for args in &["1, 2, 3, 4, 5", "1, 2, 3, 4, 5, 6, 7", "1, 2, 3, 4, 5; 6"] {
+            check_stmt_compilation_err(
+                "1:1: GFX_COPY expected srcx%, srcy%, w%, h%, dstx%, dsty%",
+                &format!("GFX_COPY {}", args),
+            );
+        }
+
+        check_stmt_err("1:10: Coordinate -40000 out of range", "GFX_COPY -40000, 0, 1, 1, 0, 0");
+        check_stmt_err("1:25: Coordinate 40000 out of range", "GFX_COPY 0, 0, 1, 1, 0, 40000");
+        check_stmt_err("1:16: Width -1 must be positive", "GFX_COPY 0, 0, -1, 1, 0, 0");
+        check_stmt_err("1:19: Height 0 must be positive", "GFX_COPY 0, 0, 1, 0, 0, 0");
+        check_stmt_err("1:19: Height 65536 out of range", "GFX_COPY 0, 0, 1, 65536, 0, 0");
+        check_stmt_compilation_err(
+            "1:16: STRING is not a number",
+            r#"GFX_COPY 0, 0, "a", 1, 0, 0"#,
+        );
+    }
+
+    #[test]
+    fn test_gfx_font_ok() {
+        Tester::default()
+            .run(r#"GFX_FONT "5x8p""#)
+            .expect_output([CapturedOut::SetTextFont("5x8p".to_owned())])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_font_errors() {
+        check_stmt_compilation_err("1:1: GFX_FONT expected name$", "GFX_FONT");
+        check_stmt_compilation_err("1:1: GFX_FONT expected name$", r#"GFX_FONT "a", "b""#);
+        check_stmt_compilation_err("1:10: expected STRING but found INTEGER", "GFX_FONT 3");
+    }
+
     #[test]
     fn test_gfx_height() {
         let mut t = Tester::default();
@@ -896,6 +1610,57 @@ mod tests {
         check_errors_two_xy("GFX_RECTF");
     }
 
+    #[test]
+    fn test_gfx_rotate_ok() {
+        Tester::default()
+            .run("GFX_ROTATE 90")
+            .expect_output([CapturedOut::SetRotation(ScreenRotation::Deg90)])
+            .check();
+
+        Tester::default()
+            .run("GFX_ROTATE 0: GFX_ROTATE 180: GFX_ROTATE -90")
+            .expect_output([
+                CapturedOut::SetRotation(ScreenRotation::Deg0),
+                CapturedOut::SetRotation(ScreenRotation::Deg180),
+                CapturedOut::SetRotation(ScreenRotation::Deg270),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_rotate_errors() {
+        for cmd in &["GFX_ROTATE", "GFX_ROTATE 1, 2", "GFX_ROTATE 1; 2"] {
+            check_stmt_compilation_err("1:1: GFX_ROTATE expected degrees%", cmd);
+        }
+
+        check_stmt_err("1:12: Rotation 45 must be a multiple of 90", "GFX_ROTATE 45");
+        check_stmt_compilation_err("1:12: STRING is not a number", r#"GFX_ROTATE "a""#);
+    }
+
+    #[test]
+    fn test_gfx_scroll_ok() {
+        Tester::default()
+            .run("GFX_SCROLL 1, -2")
+            .expect_output([CapturedOut::ScrollPixels(1, -2)])
+            .check();
+
+        Tester::default()
+            .run("GFX_SCROLL -32000.2, 31999.6")
+            .expect_output([CapturedOut::ScrollPixels(-32000, 32000)])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_scroll_errors() {
+        for cmd in &["GFX_SCROLL", "GFX_SCROLL 1", "GFX_SCROLL 1, 2, 3", "GFX_SCROLL 1; 2"] {
+            check_stmt_compilation_err("1:1: GFX_SCROLL expected dx%, dy%", cmd);
+        }
+
+        check_stmt_err("1:12: Coordinate -40000 out of range", "GFX_SCROLL -40000, 0");
+        check_stmt_err("1:15: Coordinate 40000 out of range", "GFX_SCROLL 0, 40000");
+        check_stmt_compilation_err("1:15: STRING is not a number", r#"GFX_SCROLL 1, "a""#);
+    }
+
     #[test]
     fn test_gfx_sync_ok() {
         Tester::default().run("GFX_SYNC").expect_output([CapturedOut::SyncNow]).check();
@@ -915,6 +1680,111 @@ mod tests {
         check_stmt_compilation_err("1:10: expected BOOLEAN but found INTEGER", "GFX_SYNC 2");
     }
 
+    #[test]
+    fn test_gfx_text_ok() {
+        Tester::default()
+            .run(r#"GFX_TEXT 1, 2, "Hello""#)
+            .expect_output([CapturedOut::DrawText(
+                PixelsXY { x: 1, y: 2 },
+                "Hello".to_owned(),
+                1,
+                TextRotation::Deg0,
+            )])
+            .check();
+
+        Tester::default()
+            .run(r#"GFX_TEXT -31000.3, 31999.8, """#)
+            .expect_output([CapturedOut::DrawText(
+                PixelsXY { x: -31000, y: 32000 },
+                "".to_owned(),
+                1,
+                TextRotation::Deg0,
+            )])
+            .check();
+
+        Tester::default()
+            .run(r#"GFX_TEXT 1, 2, "Big", 2: GFX_TEXT 3, 4, "Huge", 16"#)
+            .expect_output([
+                CapturedOut::DrawText(
+                    PixelsXY { x: 1, y: 2 },
+                    "Big".to_owned(),
+                    2,
+                    TextRotation::Deg0,
+                ),
+                CapturedOut::DrawText(
+                    PixelsXY { x: 3, y: 4 },
+                    "Huge".to_owned(),
+                    16,
+                    TextRotation::Deg0,
+                ),
+            ])
+            .check();
+
+        Tester::default()
+            .run(r#"GFX_TEXT 1, 2, "a", 1, 90: GFX_TEXT 1, 2, "b", 1, 180: GFX_TEXT 1, 2, "c", 1, -90: GFX_TEXT 1, 2, "d", 1, 720"#)
+            .expect_output([
+                CapturedOut::DrawText(
+                    PixelsXY { x: 1, y: 2 },
+                    "a".to_owned(),
+                    1,
+                    TextRotation::Deg90,
+                ),
+                CapturedOut::DrawText(
+                    PixelsXY { x: 1, y: 2 },
+                    "b".to_owned(),
+                    1,
+                    TextRotation::Deg180,
+                ),
+                CapturedOut::DrawText(
+                    PixelsXY { x: 1, y: 2 },
+                    "c".to_owned(),
+                    1,
+                    TextRotation::Deg270,
+                ),
+                CapturedOut::DrawText(
+                    PixelsXY { x: 1, y: 2 },
+                    "d".to_owned(),
+                    1,
+                    TextRotation::Deg0,
+                ),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_gfx_text_errors() {
+        for cmd in &["GFX_TEXT 1, 2", r#"GFX_TEXT 1, 2, "a", 3, 4, 5"#, r#"GFX_TEXT 1; 2, "a""#] {
+            check_stmt_compilation_err(
+                "1:1: GFX_TEXT expected <x%, y%, s$> | <x%, y%, s$, scale%> | <x%, y%, s$, scale%, rotation%>",
+                cmd,
+            );
+        }
+
+        for cmd in &[r#"GFX_TEXT -40000, 1, "a""#, r#"GFX_TEXT 1, -40000, "a""#] {
+            check_stmt_err(
+                format!("1:{}: Coordinate -40000 out of range", cmd.find('-').unwrap() + 1),
+                cmd,
+            );
+        }
+
+        check_stmt_compilation_err("1:16: expected STRING but found INTEGER", "GFX_TEXT 1, 2, 3");
+        check_stmt_compilation_err("1:21: STRING is not a number", r#"GFX_TEXT 1, 2, "a", "b""#);
+
+        for scale in &["0", "-1", "17", "100000"] {
+            check_stmt_err(
+                format!("1:21: Scale {} must be between 1 and 16", scale),
+                &format!(r#"GFX_TEXT 1, 2, "a", {}"#, scale),
+            );
+        }
+
+        for rotation in &["1", "45", "-100", "359"] {
+            check_stmt_err(
+                format!("1:24: Rotation {} must be a multiple of 90", rotation),
+                &format!(r#"GFX_TEXT 1, 2, "a", 1, {}"#, rotation),
+            );
+        }
+    }
+
     #[test]
     fn test_gfx_width() {
         let mut t = Tester::default();
@@ -926,4 +1796,14 @@ mod tests {
         check_expr_compilation_error("1:10: GFX_WIDTH expected no arguments", "GFX_WIDTH()");
         check_expr_compilation_error("1:10: GFX_WIDTH expected no arguments", "GFX_WIDTH(1)");
     }
+
+    #[test]
+    fn test_textwidth() {
+        check_expr_ok(0, r#"TEXTWIDTH("")"#);
+        check_expr_ok(40, r#"TEXTWIDTH("Hello")"#);
+
+        check_expr_compilation_error("1:10: TEXTWIDTH expected s$", "TEXTWIDTH()");
+        check_expr_compilation_error("1:10: TEXTWIDTH expected s$", r#"TEXTWIDTH("a", "b")"#);
+        check_expr_compilation_error("1:20: expected STRING but found INTEGER", "TEXTWIDTH(3)");
+    }
 }
@@ -15,15 +15,18 @@
 
 //! Fake implementations of GPIO pins that work on all platforms.
 
-use crate::gpio::{Pin, PinMode, Pins};
+use crate::gpio::{Pin, PinEdge, PinMode, Pins};
+use async_trait::async_trait;
 use endbasic_core::ast::{ExprType, Value, VarRef};
 use endbasic_core::syms::{Array, Symbol, Symbols};
 use std::io;
+use std::time::Duration;
 
 /// Stand-in implementation of the EndBASIC GPIO operations that always returns an error.
 #[derive(Default)]
 pub(crate) struct NoopPins {}
 
+#[async_trait(?Send)]
 impl Pins for NoopPins {
     fn setup(&mut self, _pin: Pin, _mode: PinMode) -> io::Result<()> {
         Err(io::Error::new(io::ErrorKind::Other, "GPIO backend not compiled in"))
@@ -44,6 +47,19 @@ impl Pins for NoopPins {
     fn write(&mut self, _pin: Pin, _v: bool) -> io::Result<()> {
         Err(io::Error::new(io::ErrorKind::Other, "GPIO backend not compiled in"))
     }
+
+    async fn wait(
+        &mut self,
+        _pin: Pin,
+        _edge: PinEdge,
+        _timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        Err(io::Error::other("GPIO backend not compiled in"))
+    }
+
+    fn probe(&self) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "GPIO backend not compiled in"))
+    }
 }
 
 /// Mock GPIO implementation that tracks operations and supplies fake reads.
@@ -69,7 +85,8 @@ impl Pins for NoopPins {
 /// all pins has a special number.
 ///
 /// For read GPIO calls, `data[last]` has to contain the pin number that matches the read operation
-/// and the desired outcome of the operation.
+/// and the desired outcome of the operation.  Wait GPIO calls work the same way, with the outcome
+/// indicating whether the edge happened or the timeout expired, which lets tests schedule edges.
 ///
 /// When a test is complete, the test should inspect the values in `data` up to the `last` position
 /// and ensure they match expectations.
@@ -93,6 +110,9 @@ enum MockOp {
 
     WriteLow = 20,
     WriteHigh = 21,
+
+    WaitEdge = 30,
+    WaitTimeout = 31,
 }
 
 impl MockOp {
@@ -102,12 +122,13 @@ impl MockOp {
         (pin.0 as i32) * 100 + (op as i32)
     }
 
-    /// Decodes a datum from the mock data that is to be used for a read operation.
-    fn decode_read(pos: i32, datum: i32) -> io::Result<(Pin, bool)> {
+    /// Decodes a datum from the mock data that is to be used for a read or wait operation, where
+    /// `high` and `low` are the operations that represent a true and a false outcome respectively.
+    fn decode(pos: i32, datum: i32, high: Self, low: Self) -> io::Result<(Pin, bool)> {
         if datum < 0 {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("Negative read value at __GPIO_MOCK_DATA({})", pos),
+                format!("Negative value at __GPIO_MOCK_DATA({})", pos),
             ));
         }
         let pin = datum / 100;
@@ -119,11 +140,11 @@ impl MockOp {
         }
         let pin = Pin(pin as u8);
         match datum % 100 {
-            i if i == (MockOp::ReadLow as i32) => Ok((pin, false)),
-            i if i == (MockOp::ReadHigh as i32) => Ok((pin, true)),
+            i if i == (low as i32) => Ok((pin, false)),
+            i if i == (high as i32) => Ok((pin, true)),
             i => Err(io::Error::new(
                 io::ErrorKind::InvalidData,
-                format!("Unknown read operation {} at __GPIO_MOCK_DATA({})", i, pos),
+                format!("Unknown operation {} at __GPIO_MOCK_DATA({})", i, pos),
             )),
         }
     }
@@ -162,7 +183,7 @@ impl<'a> MockPins<'a> {
     /// Reads the current value at `data[last]` with proper validation.
     fn raw_get(last: i32, data: &Array) -> io::Result<i32> {
         match data.index(&[last]) {
-            Ok(Value::Integer(v)) => Ok(*v),
+            Ok(Value::Integer(v)) => Ok(v),
             Ok(_) => panic!("We know it's an integer"),
             Err(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
         }
@@ -214,6 +235,21 @@ impl<'a> MockPins<'a> {
     }
 }
 
+/// Ensures that the datum at `pos` is for the `pin` that the `what` operation was invoked on.
+fn check_datum_pin(what: &str, pin: Pin, pos: i32, datum_pin: Pin) -> io::Result<()> {
+    if datum_pin != pin {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "Want to {} pin {} but __GPIO_MOCK_DATA({}) is for pin {}",
+                what, pin.0, pos, datum_pin.0
+            ),
+        ));
+    }
+    Ok(())
+}
+
+#[async_trait(?Send)]
 impl Pins for MockPins<'_> {
     fn setup(&mut self, pin: Pin, mode: PinMode) -> io::Result<()> {
         let datum = match mode {
@@ -237,16 +273,8 @@ impl Pins for MockPins<'_> {
 
     fn read(&mut self, pin: Pin) -> io::Result<bool> {
         let (pos, datum) = self.read_and_advance()?;
-        let (datum_pin, value) = MockOp::decode_read(pos, datum)?;
-        if datum_pin != pin {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!(
-                    "Want to read pin {} but __GPIO_MOCK_DATA({}) is for pin {}",
-                    pin.0, pos, datum_pin.0
-                ),
-            ));
-        }
+        let (datum_pin, value) = MockOp::decode(pos, datum, MockOp::ReadHigh, MockOp::ReadLow)?;
+        check_datum_pin("read", pin, pos, datum_pin)?;
         Ok(value)
     }
 
@@ -257,4 +285,22 @@ impl Pins for MockPins<'_> {
             self.append(MockOp::encode(pin, MockOp::WriteLow))
         }
     }
+
+    async fn wait(
+        &mut self,
+        pin: Pin,
+        _edge: PinEdge,
+        timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        let (pos, datum) = self.read_and_advance()?;
+        let (datum_pin, edge) = MockOp::decode(pos, datum, MockOp::WaitEdge, MockOp::WaitTimeout)?;
+        check_datum_pin("wait for", pin, pos, datum_pin)?;
+        if !edge && timeout.is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot time out a wait without timeout at __GPIO_MOCK_DATA({})", pos),
+            ));
+        }
+        Ok(edge)
+    }
 }
@@ -18,22 +18,27 @@
 use async_trait::async_trait;
 use endbasic_core::ast::{ArgSep, ExprType};
 use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
-use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope, Signal, StopReason};
 use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
 use endbasic_core::LineCol;
+use futures_lite::future;
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::io;
 use std::rc::Rc;
+use std::time::Duration;
 
 mod fakes;
 pub(crate) use fakes::{MockPins, NoopPins};
 
-/// Category description for all symbols provided by this module.
-const CATEGORY: &str = "Hardware interface
-EndBASIC provides features to manipulate external hardware.  These features are currently limited \
-to GPIO interaction on a Raspberry Pi and are only available when EndBASIC has explicitly been \
-built with the --features=rpi option.  Support for other busses and platforms may come later.";
+/// Category description for all symbols provided by this module and by the `spi` and `serial`
+/// modules.
+pub(crate) const CATEGORY: &str = "Hardware interface
+EndBASIC provides features to manipulate external hardware.  GPIO and SPI interaction is \
+currently limited to a Raspberry Pi and is only available when EndBASIC has explicitly been built \
+with the --features=rpi option.  Serial ports are available on desktop systems, which is useful \
+to talk to microcontrollers such as an Arduino.  Support for other busses and platforms may come \
+later.";
 
 /// Pin identifier.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -41,7 +46,7 @@ pub struct Pin(pub u8);
 
 impl Pin {
     /// Creates a new pin number from an EndBASIC integer value.
-    fn from_i32(i: i32, pos: LineCol) -> Result<Self> {
+    pub(crate) fn from_i32(i: i32, pos: LineCol) -> Result<Self> {
         if i < 0 {
             return Err(Error::SyntaxError(pos, format!("Pin number {} must be positive", i)));
         }
@@ -81,7 +86,33 @@ impl PinMode {
     }
 }
 
+/// Signal transitions that can be waited for on an input pin.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PinEdge {
+    /// Transition from low to high.
+    Rising,
+
+    /// Transition from high to low.
+    Falling,
+
+    /// Any transition.
+    Both,
+}
+
+impl PinEdge {
+    /// Obtains a `PinEdge` from a value.
+    fn parse(s: &str, pos: LineCol) -> Result<PinEdge> {
+        match s.to_ascii_uppercase().as_ref() {
+            "RISING" => Ok(PinEdge::Rising),
+            "FALLING" => Ok(PinEdge::Falling),
+            "BOTH" => Ok(PinEdge::Both),
+            s => Err(Error::SyntaxError(pos, format!("Unknown edge type {}", s))),
+        }
+    }
+}
+
 /// Generic abstraction over a GPIO chip to back all EndBASIC commands.
+#[async_trait(?Send)]
 pub trait Pins {
     /// Configures the `pin` as either input or output (per `mode`).
     ///
@@ -101,22 +132,44 @@ pub trait Pins {
 
     /// Writes `v` to the given `pin`, which must have been previously setup as an output pin.
     fn write(&mut self, pin: Pin, v: bool) -> io::Result<()>;
+
+    /// Waits for an `edge` on the given `pin`, which must have been previously setup as an input
+    /// pin.
+    ///
+    /// Returns true if the edge happened and false if the `timeout` expired first.  If there is no
+    /// `timeout`, waits forever.  Implementations that block must yield periodically so that the
+    /// caller can interrupt the wait.
+    async fn wait(
+        &mut self,
+        _pin: Pin,
+        _edge: PinEdge,
+        _timeout: Option<Duration>,
+    ) -> io::Result<bool> {
+        Err(io::Error::other("Edge detection not supported by the GPIO backend"))
+    }
+
+    /// Checks if the GPIO backend is usable without altering the state of any pin.
+    fn probe(&self) -> io::Result<()> {
+        Ok(())
+    }
 }
 
-/// Resets the state of the pins in a best-effort manner.
+/// Resets the state of the pins and the result of the last wait in a best-effort manner.
 pub(crate) struct PinsClearable {
     pins: Rc<RefCell<dyn Pins>>,
+    last_wait: Rc<Cell<bool>>,
 }
 
 impl PinsClearable {
-    /// Creates a new clearable for `pins`.
-    pub(crate) fn new(pins: Rc<RefCell<dyn Pins>>) -> Box<Self> {
-        Box::from(Self { pins })
+    /// Creates a new clearable for `pins` and for the `last_wait` result.
+    pub(crate) fn new(pins: Rc<RefCell<dyn Pins>>, last_wait: Rc<Cell<bool>>) -> Box<Self> {
+        Box::from(Self { pins, last_wait })
     }
 }
 
 impl Clearable for PinsClearable {
     fn reset_state(&self, syms: &mut Symbols) {
+        self.last_wait.set(false);
         let _ = match MockPins::try_new(syms) {
             Some(mut pins) => pins.clear_all(),
             None => self.pins.borrow_mut().clear_all(),
@@ -379,12 +432,186 @@ impl Callable for GpioWriteCommand {
     }
 }
 
+/// The `GPIO_WAIT` command.
+pub struct GpioWaitCommand {
+    metadata: CallableMetadata,
+    pins: Rc<RefCell<dyn Pins>>,
+    last_wait: Rc<Cell<bool>>,
+}
+
+impl GpioWaitCommand {
+    /// Creates a new instance of the command that records its outcome in `last_wait`.
+    pub fn new(pins: Rc<RefCell<dyn Pins>>, last_wait: Rc<Cell<bool>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GPIO_WAIT")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("pin"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("edge"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("pin"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("edge"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("timeout"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Waits for a signal transition on a GPIO pin.
+Suspends program execution until the pin%, which must have been configured as an input pin, \
+sees the requested edge$ transition or until timeout# seconds elapse, whichever happens first.  \
+If no timeout# is given, waits forever.  Pressing CTRL+C interrupts the wait and stops the program.
+The edge$ has to be one of \"RISING\", \"FALLING\", or \"BOTH\".  These values are \
+case-insensitive.
+Use GPIO_WAITRESULT afterwards to tell if the wait completed due to the edge or due to the \
+timeout.",
+                )
+                .build(),
+            pins,
+            last_wait,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GpioWaitCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let has_timeout = scope.nargs() == 3;
+        let pin = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            Pin::from_i32(i, pos)?
+        };
+        let edge = {
+            let (t, pos) = scope.pop_string_with_pos();
+            PinEdge::parse(&t, pos)?
+        };
+        let timeout = if has_timeout {
+            let (n, pos) = scope.pop_double_with_pos();
+            if n < 0.0 {
+                return Err(Error::SyntaxError(pos, "Timeout cannot be negative".to_owned()));
+            }
+            Some(Duration::from_secs_f64(n))
+        } else {
+            None
+        };
+
+        let signals_rx = machine.get_signals_rx();
+        let interrupted = async {
+            match signals_rx.recv().await {
+                Ok(Signal::Break) => None,
+                Err(_) => future::pending().await,
+            }
+        };
+        let wait = async {
+            let result = match MockPins::try_new(machine.get_mut_symbols()) {
+                Some(mut pins) => pins.wait(pin, edge, timeout).await,
+                None => self.pins.borrow_mut().wait(pin, edge, timeout).await,
+            };
+            Some(result)
+        };
+
+        match future::or(wait, interrupted).await {
+            Some(result) => {
+                self.last_wait.set(result.map_err(|e| scope.io_error(e))?);
+                Ok(())
+            }
+            None => {
+                // We consumed the break signal to interrupt the wait, so the machine won't see it:
+                // stop the program ourselves.
+                machine.request_stop(StopReason::Break);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The `GPIO_WAITRESULT` function.
+pub struct GpioWaitresultFunction {
+    metadata: CallableMetadata,
+    last_wait: Rc<Cell<bool>>,
+}
+
+impl GpioWaitresultFunction {
+    /// Creates a new instance of the function that reports the `last_wait` result.
+    pub fn new(last_wait: Rc<Cell<bool>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("GPIO_WAITRESULT")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the outcome of the last GPIO_WAIT.
+Returns TRUE if the last GPIO_WAIT completed because the pin saw the requested edge, and FALSE \
+if it completed because its timeout expired.  Also returns FALSE if there has been no wait yet.",
+                )
+                .build(),
+            last_wait,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for GpioWaitresultFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_boolean(self.last_wait.get())
+    }
+}
+
 /// Adds all symbols provided by this module to the given `machine`.
 pub fn add_all(machine: &mut Machine, pins: Rc<RefCell<dyn Pins>>) {
-    machine.add_clearable(PinsClearable::new(pins.clone()));
+    let last_wait = Rc::from(Cell::new(false));
+    machine.add_clearable(PinsClearable::new(pins.clone(), last_wait.clone()));
     machine.add_callable(GpioClearCommand::new(pins.clone()));
     machine.add_callable(GpioReadFunction::new(pins.clone()));
     machine.add_callable(GpioSetupCommand::new(pins.clone()));
+    machine.add_callable(GpioWaitCommand::new(pins.clone(), last_wait.clone()));
+    machine.add_callable(GpioWaitresultFunction::new(last_wait));
     machine.add_callable(GpioWriteCommand::new(pins));
 }
 
@@ -392,6 +619,7 @@ pub fn add_all(machine: &mut Machine, pins: Rc<RefCell<dyn Pins>>) {
 mod tests {
     use super::*;
     use crate::testutils::*;
+    use async_channel::Sender;
     use endbasic_core::ast::Value;
 
     /// Common checks for pin number validation.
@@ -463,6 +691,7 @@ mod tests {
         check_stmt_err("1:1: GPIO backend not compiled in", "GPIO_CLEAR 0");
         check_expr_error("1:10: GPIO backend not compiled in", "GPIO_READ(0)");
         check_stmt_err("1:1: GPIO backend not compiled in", "GPIO_WRITE 0, TRUE");
+        check_stmt_err("1:1: GPIO backend not compiled in", "GPIO_WAIT 0, \"BOTH\"");
     }
 
     #[test]
@@ -566,4 +795,131 @@ mod tests {
             r#"GPIO_WRITE 1, 5"#,
         );
     }
+
+    #[test]
+    fn test_gpio_wait_edge_before_timeout() {
+        do_mock_test(
+            r#"__GPIO_MOCK_DATA(0) = 430
+            __GPIO_MOCK_DATA(2) = 630
+            GPIO_WAIT 4, "rising", 1.5
+            GPIO_WRITE 5, GPIO_WAITRESULT
+            GPIO_WAIT 6, "BOTH"
+            GPIO_WRITE 7, GPIO_WAITRESULT"#,
+            &[430, 521, 630, 721],
+        );
+    }
+
+    #[test]
+    fn test_gpio_wait_timeout() {
+        do_mock_test(
+            r#"__GPIO_MOCK_DATA(0) = 431
+            GPIO_WAIT 4, "Falling", 0
+            GPIO_WRITE 5, GPIO_WAITRESULT"#,
+            &[431, 520],
+        );
+    }
+
+    #[test]
+    fn test_gpio_waitresult_without_wait() {
+        check_expr_ok(false, "GPIO_WAITRESULT");
+    }
+
+    #[test]
+    fn test_gpio_wait_mock_errors() {
+        Tester::default()
+            .run(
+                r#"DIM __GPIO_MOCK_DATA(5) AS INTEGER: __GPIO_MOCK_LAST = 0
+                __GPIO_MOCK_DATA(0) = 431
+                GPIO_WAIT 4, "BOTH""#,
+            )
+            .expect_err("3:17: Cannot time out a wait without timeout at __GPIO_MOCK_DATA(0)")
+            .expect_var("__GPIO_MOCK_LAST", 1)
+            .expect_array("__GPIO_MOCK_DATA", ExprType::Integer, &[5], vec![(&[0], 431.into())])
+            .check();
+
+        Tester::default()
+            .run(
+                r#"DIM __GPIO_MOCK_DATA(5) AS INTEGER: __GPIO_MOCK_LAST = 0
+                __GPIO_MOCK_DATA(0) = 330
+                GPIO_WAIT 4, "BOTH""#,
+            )
+            .expect_err("3:17: Want to wait for pin 4 but __GPIO_MOCK_DATA(0) is for pin 3")
+            .expect_var("__GPIO_MOCK_LAST", 1)
+            .expect_array("__GPIO_MOCK_DATA", ExprType::Integer, &[5], vec![(&[0], 330.into())])
+            .check();
+    }
+
+    /// Pins implementation whose waits never complete and that request a break when asked to wait.
+    struct HangingPins {
+        signals_tx: Sender<Signal>,
+    }
+
+    #[async_trait(?Send)]
+    impl Pins for HangingPins {
+        fn setup(&mut self, _pin: Pin, _mode: PinMode) -> io::Result<()> {
+            unreachable!();
+        }
+
+        fn clear(&mut self, _pin: Pin) -> io::Result<()> {
+            unreachable!();
+        }
+
+        fn clear_all(&mut self) -> io::Result<()> {
+            unreachable!();
+        }
+
+        fn read(&mut self, _pin: Pin) -> io::Result<bool> {
+            unreachable!();
+        }
+
+        fn write(&mut self, _pin: Pin, _v: bool) -> io::Result<()> {
+            unreachable!();
+        }
+
+        async fn wait(
+            &mut self,
+            _pin: Pin,
+            _edge: PinEdge,
+            _timeout: Option<Duration>,
+        ) -> io::Result<bool> {
+            self.signals_tx.try_send(Signal::Break).unwrap();
+            future::pending().await
+        }
+    }
+
+    #[test]
+    fn test_gpio_wait_cancel() {
+        let mut t = Tester::empty();
+        let signals_tx = t.get_machine().get_signals_tx();
+        let pins = Rc::from(RefCell::from(HangingPins { signals_tx }));
+        let last_wait = Rc::from(Cell::new(true));
+        t.add_callable(GpioWaitCommand::new(pins, last_wait.clone()))
+            .run(r#"GPIO_WAIT 4, "RISING", 10: a = 1"#)
+            .expect_ok(StopReason::Break)
+            .check();
+        assert!(last_wait.get());
+    }
+
+    #[test]
+    fn test_gpio_wait_errors() {
+        check_stmt_compilation_err(
+            "1:1: GPIO_WAIT expected <pin%, edge$> | <pin%, edge$, timeout#>",
+            r#"GPIO_WAIT 1"#,
+        );
+        check_stmt_compilation_err(
+            "1:1: GPIO_WAIT expected <pin%, edge$> | <pin%, edge$, timeout#>",
+            r#"GPIO_WAIT 1, "BOTH", 2, 3"#,
+        );
+        check_stmt_compilation_err("1:14: expected STRING but found INTEGER", r#"GPIO_WAIT 1, 2"#);
+
+        check_pin_validation("1:11: ", "1:11: ", r#"GPIO_WAIT _PIN_, "BOTH""#);
+
+        check_stmt_err(r#"1:14: Unknown edge type UP"#, r#"GPIO_WAIT 1, "up""#);
+        check_stmt_err(r#"1:22: Timeout cannot be negative"#, r#"GPIO_WAIT 1, "BOTH", -1"#);
+
+        check_expr_compilation_error(
+            "1:10: GPIO_WAITRESULT expected no arguments",
+            r#"GPIO_WAITRESULT(1)"#,
+        );
+    }
 }
@@ -90,42 +90,7 @@ impl Topic for CallableTopic {
         pager.print("").await?;
         let previous = pager.color();
         pager.set_color(Some(TITLE_COLOR), previous.1)?;
-        match self.metadata.return_type() {
-            None => {
-                if self.metadata.is_argless() {
-                    refill_and_page(pager, [self.metadata.name()], "    ").await?;
-                } else {
-                    refill_and_page(
-                        pager,
-                        [&format!("{} {}", self.metadata.name(), self.metadata.syntax())],
-                        "    ",
-                    )
-                    .await?;
-                }
-            }
-            Some(return_type) => {
-                if self.metadata.is_argless() {
-                    refill_and_page(
-                        pager,
-                        [&format!("{}{}", self.metadata.name(), return_type.annotation(),)],
-                        "    ",
-                    )
-                    .await?;
-                } else {
-                    refill_and_page(
-                        pager,
-                        [&format!(
-                            "{}{}({})",
-                            self.metadata.name(),
-                            return_type.annotation(),
-                            self.metadata.syntax(),
-                        )],
-                        "    ",
-                    )
-                    .await?;
-                }
-            }
-        }
+        refill_and_page(pager, [&self.metadata.usage()], "    ").await?;
         pager.set_color(previous.0, previous.1)?;
         if !self.metadata.description().count() > 0 {
             pager.print("").await?;
@@ -513,9 +478,50 @@ impl Callable for HelpCommand {
     }
 }
 
+/// The `VERSION` command.
+pub struct VersionCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl VersionCommand {
+    /// Creates a new command that writes the interpreter version to `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("VERSION")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Prints the version of the interpreter.
+This is the same version line shown by the welcome message at startup and remains available \
+even when the welcome message has been suppressed or replaced with custom content.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for VersionCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let mut console = self.console.borrow_mut();
+        console
+            .print(&format!("EndBASIC {}", env!("CARGO_PKG_VERSION")))
+            .map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
 /// Adds all help-related commands to the `machine` and makes them write to `console`.
 pub fn add_all(machine: &mut Machine, console: Rc<RefCell<dyn Console>>) {
-    machine.add_callable(HelpCommand::new(console));
+    machine.add_callable(HelpCommand::new(console.clone()));
+    machine.add_callable(VersionCommand::new(console));
 }
 
 #[cfg(test)]
@@ -1023,4 +1029,26 @@ This is the first and only topic with just one line.
             ])
             .check();
     }
+
+    #[test]
+    fn test_version_ok() {
+        let tester = Tester::empty();
+        let console = tester.get_console();
+        tester
+            .add_callable(VersionCommand::new(console))
+            .run("VERSION")
+            .expect_prints([format!("EndBASIC {}", env!("CARGO_PKG_VERSION"))])
+            .check();
+    }
+
+    #[test]
+    fn test_version_errors() {
+        let tester = Tester::empty();
+        let console = tester.get_console();
+        tester
+            .add_callable(VersionCommand::new(console))
+            .run("VERSION 1")
+            .expect_compilation_err("1:1: VERSION expected no arguments")
+            .check();
+    }
 }
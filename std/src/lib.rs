@@ -25,33 +25,174 @@
 use async_channel::{Receiver, Sender};
 use endbasic_core::exec::{Machine, Result, Signal, YieldNowFn};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::rc::Rc;
 
 // TODO(jmmv): Should narrow the exposed interface by 1.0.0.
 pub mod arrays;
+pub mod audio;
 pub mod console;
 pub mod data;
+pub mod dates;
+pub mod doctor;
+pub mod env;
 pub mod exec;
 pub mod gfx;
 pub mod gpio;
 pub mod help;
+pub mod memory;
+pub mod neopixel;
+pub mod net;
 pub mod numerics;
+pub mod profiler;
 pub mod program;
+pub mod pwm;
+pub mod serial;
+pub mod shell;
+pub mod sockets;
 pub mod spi;
 pub mod storage;
 pub mod strings;
 pub mod testutils;
 
+/// Groups of callables that a `MachineBuilder` can enable or disable.
+///
+/// Most groups match a module of this crate.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Feature {
+    /// Functions to query arrays.
+    Arrays,
+
+    /// Commands to play sounds.
+    Audio,
+
+    /// Commands to interact with the cloud service.  These are not provided by this crate, so
+    /// callers that register them must check this feature on their own.
+    Cloud,
+
+    /// Commands and functions to interact with the console.
+    Console,
+
+    /// Commands to read `DATA` values.
+    Data,
+
+    /// Functions to query the current date and time.
+    Dates,
+
+    /// The `DOCTOR` command.  Only applies to interactive machines.
+    Doctor,
+
+    /// Commands and functions to access environment variables.
+    Env,
+
+    /// Commands to control the execution of the program, including the interactive `CLEAR`.
+    Exec,
+
+    /// Commands to render graphics on the console.
+    Gfx,
+
+    /// Commands to access the GPIO pins.
+    Gpio,
+
+    /// The `HELP` command.  Only applies to interactive machines.
+    Help,
+
+    /// Commands and functions to inspect the memory used by the program.
+    Memory,
+
+    /// Commands to drive NeoPixel LED strips.
+    NeoPixel,
+
+    /// Networking functions.
+    Net,
+
+    /// Numerical functions.
+    Numerics,
+
+    /// Commands to profile the program.
+    Profiler,
+
+    /// Commands to edit and run the stored program.  Only applies to interactive machines.
+    Program,
+
+    /// Commands to access the serial ports.
+    Serial,
+
+    /// Commands to run external processes.
+    Shell,
+
+    /// Commands to use TCP client sockets.
+    Sockets,
+
+    /// Commands to access SPI devices.
+    Spi,
+
+    /// Commands to access files and drives, including the commands of the stored program that
+    /// access files such as `LOAD` and `SAVE`.  Only applies to interactive machines.
+    Storage,
+
+    /// String and character functions.
+    Strings,
+}
+
+/// All groups of callables that a `MachineBuilder` can enable or disable.
+pub const FEATURES: &[Feature] = &[
+    Feature::Arrays,
+    Feature::Audio,
+    Feature::Cloud,
+    Feature::Console,
+    Feature::Data,
+    Feature::Dates,
+    Feature::Doctor,
+    Feature::Env,
+    Feature::Exec,
+    Feature::Gfx,
+    Feature::Gpio,
+    Feature::Help,
+    Feature::Memory,
+    Feature::NeoPixel,
+    Feature::Net,
+    Feature::Numerics,
+    Feature::Profiler,
+    Feature::Program,
+    Feature::Serial,
+    Feature::Shell,
+    Feature::Sockets,
+    Feature::Spi,
+    Feature::Storage,
+    Feature::Strings,
+];
+
+/// Groups of callables that give access to hardware devices.
+const HARDWARE_FEATURES: &[Feature] =
+    &[Feature::Gpio, Feature::NeoPixel, Feature::Serial, Feature::Spi];
+
 /// Builder pattern to construct an EndBASIC interpreter.
 ///
 /// Unless otherwise specified, the interpreter is connected to a terminal-based console.
 #[derive(Default)]
 pub struct MachineBuilder {
+    audio: Option<Rc<RefCell<dyn audio::Audio>>>,
     console: Option<Rc<RefCell<dyn console::Console>>>,
+    environment: Option<Rc<RefCell<dyn env::Environment>>>,
     gpio_pins: Option<Rc<RefCell<dyn gpio::Pins>>>,
+    http_client: Option<Rc<dyn net::HttpClient>>,
+    max_http_response_length: Option<usize>,
+    neopixels: Option<Rc<RefCell<dyn neopixel::NeoPixels>>>,
+    spi: Option<Rc<RefCell<dyn spi::Spi>>>,
+    reserved_spi_devices: Vec<spi::SpiDevice>,
+    serial_ports: Option<Rc<dyn serial::SerialPorts>>,
+    shell: Option<Rc<dyn shell::Shell>>,
+    socket_connector: Option<Rc<dyn sockets::Connector>>,
     sleep_fn: Option<exec::SleepFn>,
+    now_fn: Option<exec::NowFn>,
     yield_now_fn: Option<YieldNowFn>,
+    yield_interval: Option<u64>,
+    statement_limit: Option<u64>,
+    memory_limit: Option<usize>,
+    rng_seed: Option<i32>,
     signals_chan: Option<(Sender<Signal>, Receiver<Signal>)>,
+    disabled_features: HashSet<Feature>,
 }
 
 impl MachineBuilder {
@@ -61,30 +202,162 @@ impl MachineBuilder {
         self
     }
 
+    /// Overrides the default silent audio device with the given one.
+    pub fn with_audio(mut self, audio: Rc<RefCell<dyn audio::Audio>>) -> Self {
+        self.audio = Some(audio);
+        self
+    }
+
+    /// Overrides the default empty in-memory environment with the given one.
+    pub fn with_environment(mut self, environment: Rc<RefCell<dyn env::Environment>>) -> Self {
+        self.environment = Some(environment);
+        self
+    }
+
     /// Overrides the default hardware-based GPIO pins with the given ones.
     pub fn with_gpio_pins(mut self, pins: Rc<RefCell<dyn gpio::Pins>>) -> Self {
         self.gpio_pins = Some(pins);
         self
     }
 
+    /// Enables networking by issuing HTTP requests via the given client.
+    ///
+    /// Networking is disabled unless a client is configured.
+    pub fn with_http_client(mut self, client: Rc<dyn net::HttpClient>) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Overrides the default maximum number of bytes that an HTTP response may carry.
+    pub fn with_max_http_response_length(mut self, length: usize) -> Self {
+        self.max_http_response_length = Some(length);
+        self
+    }
+
+    /// Overrides the default hardware-based NeoPixel strip driver with the given one.
+    pub fn with_neopixels(mut self, neopixels: Rc<RefCell<dyn neopixel::NeoPixels>>) -> Self {
+        self.neopixels = Some(neopixels);
+        self
+    }
+
+    /// Overrides the default hardware-based SPI devices with the given ones.
+    pub fn with_spi(mut self, spi: Rc<RefCell<dyn spi::Spi>>) -> Self {
+        self.spi = Some(spi);
+        self
+    }
+
+    /// Marks the SPI `device` as in use by other parts of the interpreter, such as an LCD console,
+    /// so that programs cannot open it.
+    pub fn with_reserved_spi_device(mut self, device: spi::SpiDevice) -> Self {
+        self.reserved_spi_devices.push(device);
+        self
+    }
+
+    /// Enables access to serial ports via the given factory.
+    ///
+    /// Serial ports are disabled unless a factory is configured.
+    pub fn with_serial_ports(mut self, ports: Rc<dyn serial::SerialPorts>) -> Self {
+        self.serial_ports = Some(ports);
+        self
+    }
+
+    /// Enables running external commands via the given shell.
+    ///
+    /// Running external commands is disabled unless a shell is configured.
+    pub fn with_shell(mut self, shell: Rc<dyn shell::Shell>) -> Self {
+        self.shell = Some(shell);
+        self
+    }
+
+    /// Enables TCP sockets via the given connector.
+    ///
+    /// Sockets are disabled unless a connector is configured.
+    pub fn with_socket_connector(mut self, connector: Rc<dyn sockets::Connector>) -> Self {
+        self.socket_connector = Some(connector);
+        self
+    }
+
     /// Overrides the default sleep function with the given one.
     pub fn with_sleep_fn(mut self, sleep_fn: exec::SleepFn) -> Self {
         self.sleep_fn = Some(sleep_fn);
         self
     }
 
+    /// Overrides the default clock function with the given one.
+    pub fn with_now_fn(mut self, now_fn: exec::NowFn) -> Self {
+        self.now_fn = Some(now_fn);
+        self
+    }
+
     /// Overrides the default yielding function with the given one.
     pub fn with_yield_now_fn(mut self, yield_now_fn: YieldNowFn) -> Self {
         self.yield_now_fn = Some(yield_now_fn);
         self
     }
 
+    /// Configures the machine to call the yielding function once every `interval` statements
+    /// instead of at every loop iteration.
+    pub fn with_yield_interval(mut self, interval: u64) -> Self {
+        self.yield_interval = Some(interval);
+        self
+    }
+
+    /// Limits the number of statements that a single execution can run to `limit`.
+    pub fn with_statement_limit(mut self, limit: u64) -> Self {
+        self.statement_limit = Some(limit);
+        self
+    }
+
+    /// Limits the number of bytes that the program can allocate for arrays and strings to `limit`.
+    pub fn with_memory_limit(mut self, limit: usize) -> Self {
+        self.memory_limit = Some(limit);
+        self
+    }
+
+    /// Seeds the random number generator with `seed` instead of with system entropy so that the
+    /// random numbers returned to programs are reproducible.
+    pub fn with_rng_seed(mut self, seed: i32) -> Self {
+        self.rng_seed = Some(seed);
+        self
+    }
+
     /// Overrides the default signals channel with the given one.
     pub fn with_signals_chan(mut self, chan: (Sender<Signal>, Receiver<Signal>)) -> Self {
         self.signals_chan = Some(chan);
         self
     }
 
+    /// Enables or disables the group of callables `feature`.
+    ///
+    /// All groups are enabled by default.  The callables of disabled groups are not registered in
+    /// the machine at all, so they are not advertised by `HELP` either.
+    pub fn with_feature(mut self, feature: Feature, enabled: bool) -> Self {
+        if enabled {
+            self.disabled_features.remove(&feature);
+        } else {
+            self.disabled_features.insert(feature);
+        }
+        self
+    }
+
+    /// Disables the commands that access files and drives.
+    pub fn without_storage(self) -> Self {
+        self.with_feature(Feature::Storage, false)
+    }
+
+    /// Disables the callables that access hardware devices, such as the GPIO pins or the serial
+    /// ports.
+    pub fn without_hardware(self) -> Self {
+        HARDWARE_FEATURES
+            .iter()
+            .fold(self, |builder, feature| builder.with_feature(*feature, false))
+    }
+
+    /// Returns true if the group of callables `feature` is enabled.
+    pub fn is_feature_enabled(&self, feature: Feature) -> bool {
+        !self.disabled_features.contains(&feature)
+    }
+
     /// Lazily initializes the `console` field with a default value and returns it.
     pub fn get_console(&mut self) -> Rc<RefCell<dyn console::Console>> {
         if self.console.is_none() {
@@ -93,6 +366,22 @@ impl MachineBuilder {
         self.console.clone().unwrap()
     }
 
+    /// Lazily initializes the `audio` field with a default value and returns it.
+    fn get_audio(&mut self) -> Rc<RefCell<dyn audio::Audio>> {
+        if self.audio.is_none() {
+            self.audio = Some(Rc::from(RefCell::from(audio::NoopAudio::default())))
+        }
+        self.audio.as_ref().expect("Must have been initialized above").clone()
+    }
+
+    /// Lazily initializes the `environment` field with a default value and returns it.
+    fn get_environment(&mut self) -> Rc<RefCell<dyn env::Environment>> {
+        if self.environment.is_none() {
+            self.environment = Some(Rc::from(RefCell::from(env::MemoryEnvironment::default())))
+        }
+        self.environment.as_ref().expect("Must have been initialized above").clone()
+    }
+
     /// Lazily initializes the `gpio_pins` field with a default value and returns it.
     fn get_gpio_pins(&mut self) -> Rc<RefCell<dyn gpio::Pins>> {
         if self.gpio_pins.is_none() {
@@ -101,10 +390,37 @@ impl MachineBuilder {
         self.gpio_pins.as_ref().expect("Must have been initialized above").clone()
     }
 
+    /// Lazily initializes the `neopixels` field with a default value and returns it.
+    fn get_neopixels(&mut self) -> Rc<RefCell<dyn neopixel::NeoPixels>> {
+        if self.neopixels.is_none() {
+            self.neopixels = Some(Rc::from(RefCell::from(neopixel::NoopNeoPixels::default())))
+        }
+        self.neopixels.as_ref().expect("Must have been initialized above").clone()
+    }
+
+    /// Lazily initializes the `spi` field with a default value and returns it.
+    fn get_spi(&mut self) -> Rc<RefCell<dyn spi::Spi>> {
+        if self.spi.is_none() {
+            self.spi = Some(Rc::from(RefCell::from(spi::NoopSpi::default())))
+        }
+        self.spi.as_ref().expect("Must have been initialized above").clone()
+    }
+
     /// Builds the interpreter.
     pub fn build(mut self) -> Result<Machine> {
+        let audio = self.get_audio();
         let console = self.get_console();
+        let environment = self.get_environment();
         let gpio_pins = self.get_gpio_pins();
+        let neopixels = self.get_neopixels();
+        let spi = self.get_spi();
+        let network = net::Network::new(
+            self.http_client,
+            self.max_http_response_length.unwrap_or(net::DEFAULT_MAX_RESPONSE_LENGTH),
+        );
+        let clock = exec::Clock::new(self.now_fn.unwrap_or_else(|| Box::from(exec::system_now)));
+        let sleep_fn: Rc<exec::SleepFn> =
+            Rc::from(self.sleep_fn.unwrap_or_else(|| Box::from(exec::system_sleep)));
 
         let signals_chan = match self.signals_chan {
             Some(pair) => pair,
@@ -113,14 +429,72 @@ impl MachineBuilder {
 
         let mut machine =
             Machine::with_signals_chan_and_yield_now_fn(signals_chan, self.yield_now_fn);
-        arrays::add_all(&mut machine);
-        console::add_all(&mut machine, console.clone());
-        data::add_all(&mut machine);
-        gfx::add_all(&mut machine, console);
-        gpio::add_all(&mut machine, gpio_pins);
-        exec::add_scripting(&mut machine, self.sleep_fn);
-        numerics::add_all(&mut machine);
-        strings::add_all(&mut machine);
+        machine.set_yield_interval(self.yield_interval);
+        machine.set_statement_limit(self.statement_limit);
+        machine.get_mut_symbols().set_memory_limit(self.memory_limit);
+        let disabled_features = self.disabled_features;
+        let enabled = |feature| !disabled_features.contains(&feature);
+        if enabled(Feature::Arrays) {
+            arrays::add_all(&mut machine);
+        }
+        if enabled(Feature::Audio) {
+            audio::add_all(&mut machine, audio, sleep_fn.clone());
+        }
+        if enabled(Feature::Console) {
+            console::add_all(&mut machine, console.clone());
+        }
+        if enabled(Feature::Data) {
+            data::add_all(&mut machine);
+        }
+        if enabled(Feature::Dates) {
+            dates::add_all(&mut machine, clock.clone());
+        }
+        if enabled(Feature::Env) {
+            env::add_all(&mut machine, environment);
+        }
+        if enabled(Feature::Gfx) {
+            gfx::add_all(&mut machine, console.clone());
+        }
+        if enabled(Feature::Gpio) {
+            gpio::add_all(&mut machine, gpio_pins);
+        }
+        if enabled(Feature::Exec) {
+            exec::add_scripting(&mut machine, sleep_fn, clock);
+        }
+        if enabled(Feature::Memory) {
+            memory::add_all(&mut machine, console.clone());
+        }
+        if enabled(Feature::NeoPixel) {
+            neopixel::add_all(&mut machine, neopixels);
+        }
+        if enabled(Feature::Net) {
+            net::add_all(&mut machine, network);
+        }
+        if enabled(Feature::Numerics) {
+            let prng = match self.rng_seed {
+                Some(seed) => numerics::Prng::new_from_seed(seed),
+                None => numerics::Prng::new_from_entryopy(),
+            };
+            numerics::add_all_with_prng(&mut machine, prng);
+        }
+        if enabled(Feature::Profiler) {
+            profiler::add_all(&mut machine, console);
+        }
+        if enabled(Feature::Serial) {
+            serial::add_all(&mut machine, self.serial_ports);
+        }
+        if enabled(Feature::Shell) {
+            shell::add_all(&mut machine, self.shell);
+        }
+        if enabled(Feature::Sockets) {
+            sockets::add_all(&mut machine, self.socket_connector);
+        }
+        if enabled(Feature::Spi) {
+            spi::add_all(&mut machine, spi, self.reserved_spi_devices);
+        }
+        if enabled(Feature::Strings) {
+            strings::add_all(&mut machine);
+        }
         Ok(machine)
     }
 
@@ -139,14 +513,22 @@ impl MachineBuilder {
 pub struct InteractiveMachineBuilder {
     builder: MachineBuilder,
     program: Option<Rc<RefCell<dyn program::Program>>>,
+    external_editor: Option<Rc<dyn program::ExternalEditor>>,
     storage: Rc<RefCell<storage::Storage>>,
+    doctor_checks: Vec<Rc<dyn doctor::Check>>,
 }
 
 impl InteractiveMachineBuilder {
     /// Constructs an interactive machine builder from a non-interactive builder.
     fn from(builder: MachineBuilder) -> Self {
         let storage = Rc::from(RefCell::from(storage::Storage::default()));
-        InteractiveMachineBuilder { builder, program: None, storage }
+        InteractiveMachineBuilder {
+            builder,
+            program: None,
+            external_editor: None,
+            storage,
+            doctor_checks: vec![],
+        }
     }
 
     /// Returns the console that will be used for the machine.
@@ -154,6 +536,11 @@ impl InteractiveMachineBuilder {
         self.builder.get_console()
     }
 
+    /// Returns true if the group of callables `feature` is enabled.
+    pub fn is_feature_enabled(&self, feature: Feature) -> bool {
+        self.builder.is_feature_enabled(feature)
+    }
+
     /// Lazily initializes the `program` field with a default value and returns it.
     pub fn get_program(&mut self) -> Rc<RefCell<dyn program::Program>> {
         if self.program.is_none() {
@@ -173,18 +560,234 @@ impl InteractiveMachineBuilder {
         self
     }
 
+    /// Configures the `editor` to use for `EDIT "EXTERNAL"`.  Without one, external editing is not
+    /// supported.
+    pub fn with_external_editor(mut self, editor: Rc<dyn program::ExternalEditor>) -> Self {
+        self.external_editor = Some(editor);
+        self
+    }
+
+    /// Adds a `check` for the `DOCTOR` command to run after all built-in checks.
+    pub fn with_doctor_check(mut self, check: Rc<dyn doctor::Check>) -> Self {
+        self.doctor_checks.push(check);
+        self
+    }
+
     /// Builds the interpreter.
     pub fn build(mut self) -> Result<Machine> {
         let console = self.builder.get_console();
         let program = self.get_program();
         let storage = self.get_storage();
+        let channels = Rc::from(RefCell::from(storage::Channels::default()));
+        let gpio_pins = self.builder.get_gpio_pins();
+        let disabled_features = self.builder.disabled_features.clone();
+        let enabled = |feature| !disabled_features.contains(&feature);
         let mut machine = self.builder.build()?;
 
-        exec::add_interactive(&mut machine);
-        help::add_all(&mut machine, console.clone());
-        program::add_all(&mut machine, program, console.clone(), storage.clone());
-        storage::add_all(&mut machine, console, storage);
+        if enabled(Feature::Doctor) {
+            doctor::add_all(
+                &mut machine,
+                console.clone(),
+                if enabled(Feature::Storage) { Some(storage.clone()) } else { None },
+                if enabled(Feature::Gpio) { Some(gpio_pins) } else { None },
+                self.doctor_checks,
+            );
+        }
+        if enabled(Feature::Exec) {
+            exec::add_interactive(&mut machine);
+        }
+        if enabled(Feature::Help) {
+            help::add_all(&mut machine, console.clone());
+        }
+        if enabled(Feature::Program) {
+            program::add_all(
+                &mut machine,
+                program,
+                console.clone(),
+                self.external_editor,
+                if enabled(Feature::Storage) { Some(storage.clone()) } else { None },
+                channels.clone(),
+            );
+        }
+        if enabled(Feature::Storage) {
+            storage::add_all(&mut machine, console, storage, channels);
+        }
 
         Ok(machine)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{CapturedOut, MockConsole};
+    use endbasic_core::exec::StopReason;
+    use futures_lite::future::block_on;
+
+    /// Builds an interactive machine out of `builder` and returns it with its console.
+    fn build_interactive(builder: MachineBuilder) -> (Machine, Rc<RefCell<MockConsole>>) {
+        let console = Rc::from(RefCell::from(MockConsole::default()));
+        let machine = builder
+            .with_console(console.clone())
+            .with_gpio_pins(Rc::from(RefCell::from(gpio::NoopPins::default())))
+            .make_interactive()
+            .build()
+            .unwrap();
+        (machine, console)
+    }
+
+    /// Returns the sorted names of the callables registered in `machine`.
+    fn callable_names(machine: &Machine) -> Vec<String> {
+        let mut names = machine
+            .get_symbols()
+            .callables()
+            .values()
+            .map(|callable| callable.metadata().name().to_owned())
+            .collect::<Vec<String>>();
+        names.sort();
+        names
+    }
+
+    /// Runs `HELP` in `machine` and returns the titles of the top-level topics it lists.
+    fn help_topics(machine: &mut Machine, console: Rc<RefCell<MockConsole>>) -> Vec<String> {
+        assert_eq!(StopReason::Eof, block_on(machine.exec(&mut "HELP".as_bytes())).unwrap());
+        let output = console.borrow_mut().take_captured_out();
+        let mut topics = vec![];
+        let mut in_link = false;
+        for out in output {
+            match out {
+                CapturedOut::Write(s) if s == "    >> " => in_link = true,
+                CapturedOut::Print(s) if in_link => {
+                    topics.push(s);
+                    in_link = false;
+                }
+                _ => (),
+            }
+        }
+        topics
+    }
+
+    /// Disables all features in `builder` except for those in `keep`.
+    fn only_features(builder: MachineBuilder, keep: &[Feature]) -> MachineBuilder {
+        FEATURES
+            .iter()
+            .filter(|feature| !keep.contains(feature))
+            .fold(builder, |builder, feature| builder.with_feature(*feature, false))
+    }
+
+    #[test]
+    fn test_features_all_enabled_by_default() {
+        let builder = MachineBuilder::default();
+        for feature in FEATURES {
+            assert!(builder.is_feature_enabled(*feature));
+        }
+
+        let (machine, _console) = build_interactive(builder);
+        let names = callable_names(&machine);
+        for name in ["DIR", "GFX_LINE", "GPIO_WRITE", "HELP", "LOAD", "SHELL", "SPI_OPEN"] {
+            assert!(names.iter().any(|n| n == name), "{} not registered", name);
+        }
+    }
+
+    #[test]
+    fn test_features_without_storage() {
+        let builder = MachineBuilder::default().without_storage();
+        assert!(!builder.is_feature_enabled(Feature::Storage));
+        assert!(builder.is_feature_enabled(Feature::Program));
+
+        let (mut machine, console) = build_interactive(builder);
+        let names = callable_names(&machine);
+        for name in ["CHAIN", "DIR", "KILL", "LOAD", "MERGE", "OPEN", "PACKAGE", "SAVE"] {
+            assert!(!names.iter().any(|n| n == name), "{} registered", name);
+        }
+        for name in ["EDIT", "LIST", "NEW", "PRINT", "RUN"] {
+            assert!(names.iter().any(|n| n == name), "{} not registered", name);
+        }
+
+        let topics = help_topics(&mut machine, console);
+        assert!(!topics.iter().any(|t| t == "File I/O" || t == "File system"));
+        assert!(topics.iter().any(|t| t == "Stored program"));
+    }
+
+    #[test]
+    fn test_features_without_hardware() {
+        let builder = MachineBuilder::default().without_hardware();
+        for feature in HARDWARE_FEATURES {
+            assert!(!builder.is_feature_enabled(*feature));
+        }
+
+        let (mut machine, console) = build_interactive(builder);
+        let names = callable_names(&machine);
+        assert!(!names.iter().any(|n| {
+            n.starts_with("GPIO_")
+                || n.starts_with("NEOPIXEL_")
+                || n.starts_with("SERIAL")
+                || n.starts_with("SPI_")
+        }));
+        assert!(names.iter().any(|n| n == "DOCTOR"));
+
+        let topics = help_topics(&mut machine, console);
+        assert!(!topics.iter().any(|t| t == "Hardware interface"));
+        assert!(topics.iter().any(|t| t == "Graphics"));
+    }
+
+    #[test]
+    fn test_features_toggle() {
+        let builder = MachineBuilder::default()
+            .with_feature(Feature::Gfx, false)
+            .with_feature(Feature::Shell, false)
+            .with_feature(Feature::Shell, true);
+        assert!(!builder.is_feature_enabled(Feature::Gfx));
+        assert!(builder.is_feature_enabled(Feature::Shell));
+
+        let machine = builder.build().unwrap();
+        let names = callable_names(&machine);
+        assert!(!names.iter().any(|n| n.starts_with("GFX_")));
+        assert!(names.iter().any(|n| n == "SHELL"));
+    }
+
+    #[test]
+    fn test_features_subset_help_listing() {
+        let (mut machine, console) = build_interactive(only_features(
+            MachineBuilder::default(),
+            &[Feature::Help, Feature::Numerics, Feature::Strings],
+        ));
+        assert_eq!(
+            [
+                "Interpreter",
+                "Language reference",
+                "Numerical functions",
+                "String and character functions"
+            ],
+            help_topics(&mut machine, console).as_slice()
+        );
+
+        let (mut machine, console) = build_interactive(only_features(
+            MachineBuilder::default(),
+            &[Feature::Console, Feature::Help, Feature::Program],
+        ));
+        assert_eq!(
+            ["Console", "Interpreter", "Language reference", "Stored program"],
+            help_topics(&mut machine, console).as_slice()
+        );
+    }
+
+    #[test]
+    fn test_features_none() {
+        let machine = only_features(MachineBuilder::default(), &[]).build().unwrap();
+        assert!(callable_names(&machine).is_empty());
+    }
+
+    #[test]
+    fn test_features_cloud_is_not_registered_here() {
+        let builder = MachineBuilder::default().with_feature(Feature::Cloud, false);
+        assert!(!builder.is_feature_enabled(Feature::Cloud));
+        assert!(!builder.make_interactive().is_feature_enabled(Feature::Cloud));
+
+        let with_cloud = callable_names(&build_interactive(MachineBuilder::default()).0);
+        let without_cloud = callable_names(
+            &build_interactive(MachineBuilder::default().with_feature(Feature::Cloud, false)).0,
+        );
+        assert_eq!(with_cloud, without_cloud);
+    }
+}
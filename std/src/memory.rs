@@ -0,0 +1,241 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Commands to inspect the memory used by the program.
+
+use crate::console::Console;
+use crate::exec::CATEGORY;
+use async_trait::async_trait;
+use endbasic_core::ast::ExprType;
+use endbasic_core::exec::{Machine, Result, Scope};
+use endbasic_core::syms::{value_size, Callable, CallableMetadata, CallableMetadataBuilder};
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io;
+use std::rc::Rc;
+
+/// Formats a row of the memory report with a `label`, an optional number of `items` and `bytes`.
+fn format_row(label: &str, items: Option<usize>, bytes: usize) -> String {
+    let items = items.map(|n| n.to_string()).unwrap_or_default();
+    format!("    {:<12}{:>10}{:>14}", label, items, bytes)
+}
+
+/// Prints the memory usage of the `machine` to the `console`.
+fn show_meminfo(console: &mut dyn Console, machine: &Machine) -> io::Result<()> {
+    let usage = machine.get_symbols().memory_usage();
+    let data = machine.get_data();
+    let data_bytes = data.iter().flatten().map(value_size).sum();
+
+    let arrays_bytes = usage.arrays.iter().map(|array| array.bytes).sum();
+
+    console.print("")?;
+    console.print(&format!("    {:<12}{:>10}{:>14}", "Symbol", "Items", "Bytes"))?;
+    console.print(&format_row("Variables", Some(usage.variables), usage.variables_bytes))?;
+    console.print(&format_row("Arrays", Some(usage.arrays.len()), arrays_bytes))?;
+    for array in &usage.arrays {
+        let name = format!("  {}{}", array.name, array.subtype.annotation());
+        console.print(&format_row(&name, Some(array.elements), array.bytes))?;
+    }
+    console.print(&format_row("Strings", None, usage.strings_bytes))?;
+    console.print(&format_row("Total", None, usage.total_bytes()))?;
+    console.print(&format_row("DATA", Some(data.len()), data_bytes))?;
+    console.print("")?;
+    Ok(())
+}
+
+/// The `FRE` function.
+pub struct FreFunction {
+    metadata: CallableMetadata,
+}
+
+impl FreFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FRE")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the estimated number of bytes used by variables.
+The estimate accounts for all variables and arrays, including the contents of strings, but not \
+for the program itself nor for the DATA values.  Use MEMINFO to see a breakdown of this figure.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for FreFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let bytes = machine.get_symbols().memory_usage().total_bytes();
+        scope.return_integer(i32::try_from(bytes).unwrap_or(i32::MAX))
+    }
+}
+
+/// The `MEMINFO` command.
+pub struct MeminfoCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+}
+
+impl MeminfoCommand {
+    /// Creates a new `MEMINFO` command that prints the memory usage to `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MEMINFO")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Prints a breakdown of the memory used by the program.
+The report shows the number of variables and the bytes they use, followed by the number of arrays \
+and the bytes they use, with the number of elements and bytes of each array.  The Strings row \
+shows how many of these bytes hold the contents of strings, and the Total row matches the value \
+returned by FRE%.  The DATA row shows the number and size of the values in the DATA statements \
+of the program.
+All figures are estimates of the memory held by the interpreter.",
+                )
+                .build(),
+            console,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MeminfoCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let mut console = self.console.borrow_mut();
+        show_meminfo(&mut *console, machine).map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// Adds all memory-related commands to the `machine` and makes them write to `console`.
+pub fn add_all(machine: &mut Machine, console: Rc<RefCell<dyn Console>>) {
+    machine.add_callable(FreFunction::new());
+    machine.add_callable(MeminfoCommand::new(console));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    #[test]
+    fn test_fre_counts_variables_and_arrays() {
+        Tester::default()
+            .run("DIM a(100) AS INTEGER: DIM b(10) AS DOUBLE: c = 5: f = FRE")
+            .expect_array("a", ExprType::Integer, &[100], vec![])
+            .expect_array("b", ExprType::Double, &[10], vec![])
+            .expect_var("c", 5)
+            .expect_var("f", 400 + 80 + 4)
+            .check();
+    }
+
+    #[test]
+    fn test_fre_counts_strings() {
+        let string = size_of::<String>();
+        Tester::default()
+            .run(r#"a$ = "hello": DIM b(2) AS STRING: b(1) = "abc": f = FRE"#)
+            .expect_var("a", "hello")
+            .expect_array("b", ExprType::Text, &[2], vec![(&[1], "abc".into())])
+            .expect_var("f", (string + 5 + 2 * string + 3) as i32)
+            .check();
+    }
+
+    #[test]
+    fn test_fre_after_clear() {
+        let mut t = Tester::default();
+        t.run("DIM a(1000) AS DOUBLE: f1 = FRE")
+            .expect_array("a", ExprType::Double, &[1000], vec![])
+            .expect_var("f1", 8000)
+            .check();
+        t.get_machine().clear();
+        t.run("f2 = FRE").expect_clear().expect_var("f2", 0).check();
+    }
+
+    #[test]
+    fn test_meminfo_breakdown() {
+        let string = size_of::<String>();
+        let program = r#"
+DIM a(10, 10) AS INTEGER
+DIM names(3) AS STRING
+names(0) = "abcd"
+x = 3.5
+s$ = "xy"
+DATA 1, "foo"
+MEMINFO
+"#;
+        Tester::default()
+            .run(program)
+            .expect_array("a", ExprType::Integer, &[10, 10], vec![])
+            .expect_array("names", ExprType::Text, &[3], vec![(&[0], "abcd".into())])
+            .expect_var("x", 3.5)
+            .expect_var("s", "xy")
+            .expect_prints([
+                "".to_owned(),
+                "    Symbol           Items         Bytes".to_owned(),
+                format!("    Variables            2{:>14}", 8 + string + 2),
+                format!("    Arrays               2{:>14}", 400 + 3 * string + 4),
+                "      A%               100           400".to_owned(),
+                format!("      NAMES$             3{:>14}", 3 * string + 4),
+                "    Strings                            6".to_owned(),
+                format!("    Total                 {:>14}", 8 + string + 2 + 400 + 3 * string + 4),
+                format!("    DATA                 2{:>14}", 4 + string + 3),
+                "".to_owned(),
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_meminfo_after_clear() {
+        let mut t = Tester::default();
+        t.run("DIM a(50) AS BOOLEAN: b = 3")
+            .expect_array("a", ExprType::Boolean, &[50], vec![])
+            .expect_var("b", 3)
+            .check();
+        t.get_machine().clear();
+        t.run("MEMINFO")
+            .expect_clear()
+            .expect_prints([
+                "",
+                "    Symbol           Items         Bytes",
+                "    Variables            0             0",
+                "    Arrays               0             0",
+                "    Strings                            0",
+                "    Total                              0",
+                "    DATA                 0             0",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_fre_and_meminfo_errors() {
+        check_expr_compilation_error("1:10: FRE expected no arguments", "FRE(1)");
+        check_stmt_compilation_err("1:1: MEMINFO expected no arguments", "MEMINFO 1");
+    }
+}
@@ -0,0 +1,685 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! NeoPixel (WS2812) LED strip commands for EndBASIC.
+
+use crate::console::RGB;
+use crate::gpio::{Pin, CATEGORY};
+use crate::spi::{Spi, SpiDevice, SpiMode};
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io;
+use std::rc::Rc;
+
+/// Maximum number of LEDs in a strip.
+const MAX_PIXELS: usize = 1024;
+
+/// SPI clock at which every WS2812 bit is encoded as 3 SPI bits, giving pulses of about 0.4us
+/// and 0.8us as required by the WS2812 timings.
+const SPI_CLOCK_HZ: u32 = 2_400_000;
+
+/// Number of zero bytes to send after the pixel data so that the strip latches the new colors.
+///
+/// This amounts to more than the 280us of low signal required by the most recent WS2812B
+/// revisions at `SPI_CLOCK_HZ`.
+const SPI_RESET_BYTES: usize = 90;
+
+/// Generic abstraction over a NeoPixel (WS2812) LED strip to back all EndBASIC commands.
+pub trait NeoPixels {
+    /// Prepares the strip of `count` LEDs attached to `pin` for subsequent `show` calls.
+    ///
+    /// It is OK to call this multiple times to reconfigure the strip.
+    fn open(&mut self, pin: Pin, count: usize) -> io::Result<()>;
+
+    /// Sends the colors in `pixels` to the strip, one per LED in order.
+    fn show(&mut self, pixels: &[RGB]) -> io::Result<()>;
+}
+
+/// Stand-in implementation of the EndBASIC NeoPixel operations that always returns an error.
+#[derive(Default)]
+pub(crate) struct NoopNeoPixels {}
+
+impl NeoPixels for NoopNeoPixels {
+    fn open(&mut self, _pin: Pin, _count: usize) -> io::Result<()> {
+        Err(io::Error::other("NeoPixel backend not compiled in"))
+    }
+
+    fn show(&mut self, _pixels: &[RGB]) -> io::Result<()> {
+        Err(io::Error::other("NeoPixel backend not compiled in"))
+    }
+}
+
+/// Encodes `pixels` as an SPI byte stream to be sent at `SPI_CLOCK_HZ`.
+///
+/// WS2812 LEDs expect their colors in GRB order, and every bit of every color is encoded as the
+/// `110` (for ones) or `100` (for zeros) SPI bit sequences.  The stream is terminated by the
+/// reset period that latches the colors.
+fn spi_encode(pixels: &[RGB]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(pixels.len() * 9 + SPI_RESET_BYTES);
+    for (r, g, b) in pixels {
+        for component in [g, r, b] {
+            let mut bits = 0u32;
+            for i in (0..8).rev() {
+                bits <<= 3;
+                bits |= if component & (1 << i) != 0 { 0b110 } else { 0b100 };
+            }
+            data.push((bits >> 16) as u8);
+            data.push((bits >> 8) as u8);
+            data.push(bits as u8);
+        }
+    }
+    data.resize(data.len() + SPI_RESET_BYTES, 0);
+    data
+}
+
+/// Implementation of the EndBASIC NeoPixel operations that drives the data line of the strip
+/// with the MOSI line of an SPI bus, which takes care of the timing-critical signalling.
+pub struct SpiNeoPixels {
+    spi: Box<dyn Spi>,
+    devices: Vec<(Pin, SpiDevice)>,
+}
+
+impl SpiNeoPixels {
+    /// Creates a new NeoPixel driver that talks to the strip via `spi`.
+    ///
+    /// `devices` maps the pins that can drive a strip to the SPI device whose MOSI line is
+    /// attached to them.
+    pub fn new(spi: Box<dyn Spi>, devices: Vec<(Pin, SpiDevice)>) -> Self {
+        Self { spi, devices }
+    }
+}
+
+impl NeoPixels for SpiNeoPixels {
+    fn open(&mut self, pin: Pin, _count: usize) -> io::Result<()> {
+        let device = match self.devices.iter().find(|(p, _)| *p == pin) {
+            Some((_, device)) => *device,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Pin {} cannot drive a NeoPixel strip", pin.0),
+                ))
+            }
+        };
+        self.spi.open(device, SPI_CLOCK_HZ, SpiMode::Mode0)
+    }
+
+    fn show(&mut self, pixels: &[RGB]) -> io::Result<()> {
+        let tx = spi_encode(pixels);
+        let mut rx = vec![0; tx.len()];
+        self.spi.transfer(&tx, &mut rx)
+    }
+}
+
+/// State of the strip shared by all NeoPixel commands.
+pub struct Strip {
+    /// The hardware backend for the strip.
+    neopixels: Rc<RefCell<dyn NeoPixels>>,
+
+    /// The colors to send to the strip on the next show operation, or `None` if the strip has not
+    /// been initialized yet.
+    pixels: Option<Vec<RGB>>,
+}
+
+impl Strip {
+    /// Creates a new, uninitialized strip backed by `neopixels`.
+    pub fn new(neopixels: Rc<RefCell<dyn NeoPixels>>) -> Self {
+        Self { neopixels, pixels: None }
+    }
+
+    /// Gets mutable access to the pixels buffer, failing if the strip has not been initialized.
+    fn get_mut_pixels(&mut self, scope: &Scope<'_>) -> Result<&mut Vec<RGB>> {
+        match self.pixels.as_mut() {
+            Some(pixels) => Ok(pixels),
+            None => Err(scope.io_error(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "NeoPixel strip not initialized; use NEOPIXEL_INIT first",
+            ))),
+        }
+    }
+}
+
+/// Forgets the state of the strip so that it has to be initialized again.
+struct StripClearable {
+    strip: Rc<RefCell<Strip>>,
+}
+
+impl StripClearable {
+    /// Creates a new clearable for `strip`.
+    fn new(strip: Rc<RefCell<Strip>>) -> Box<Self> {
+        Box::from(Self { strip })
+    }
+}
+
+impl Clearable for StripClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.strip.borrow_mut().pixels = None;
+    }
+}
+
+/// Pops the three color components of an RGB color from the `scope`.
+fn pop_rgb(scope: &mut Scope<'_>) -> Result<RGB> {
+    let mut rgb = [0u8; 3];
+    for c in rgb.iter_mut() {
+        let (i, pos) = scope.pop_integer_with_pos();
+        *c = color_from_i32(i, pos)?;
+    }
+    Ok((rgb[0], rgb[1], rgb[2]))
+}
+
+/// Converts an EndBASIC integer to a color component.
+fn color_from_i32(i: i32, pos: LineCol) -> Result<u8> {
+    match u8::try_from(i) {
+        Ok(c) => Ok(c),
+        Err(_) => {
+            Err(Error::SyntaxError(pos, format!("Color component {} must be between 0 and 255", i)))
+        }
+    }
+}
+
+/// The `NEOPIXEL_FILL` command.
+pub struct NeopixelFillCommand {
+    metadata: CallableMetadata,
+    strip: Rc<RefCell<Strip>>,
+}
+
+impl NeopixelFillCommand {
+    /// Creates a new instance of the command.
+    pub fn new(strip: Rc<RefCell<Strip>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("NEOPIXEL_FILL")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("r"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("g"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("b"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets the color of all LEDs in the NeoPixel strip.
+The r%, g%, and b% color components must be between 0 and 255.  The new colors are not visible \
+until NEOPIXEL_SHOW is used.",
+                )
+                .build(),
+            strip,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for NeopixelFillCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(3, scope.nargs());
+        let rgb = pop_rgb(&mut scope)?;
+
+        let mut strip = self.strip.borrow_mut();
+        let pixels = strip.get_mut_pixels(&scope)?;
+        pixels.fill(rgb);
+        Ok(())
+    }
+}
+
+/// The `NEOPIXEL_INIT` command.
+pub struct NeopixelInitCommand {
+    metadata: CallableMetadata,
+    strip: Rc<RefCell<Strip>>,
+}
+
+impl NeopixelInitCommand {
+    /// Creates a new instance of the command.
+    pub fn new(strip: Rc<RefCell<Strip>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("NEOPIXEL_INIT")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("pin"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("count"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Configures a NeoPixel (WS2812) LED strip.
+Prepares the strip of count% LEDs whose data line is attached to the GPIO pin%.  On a Raspberry \
+Pi, the strip is driven via the MOSI line of an SPI bus so pin% must be 10 (SPI0) or 20 (SPI1).
+All LEDs start off, and NEOPIXEL_SET and NEOPIXEL_FILL change their colors in memory until \
+NEOPIXEL_SHOW sends them to the strip.",
+                )
+                .build(),
+            strip,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for NeopixelInitCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let pin = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            Pin::from_i32(i, pos)?
+        };
+        let count = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            if i <= 0 {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("NeoPixel count {} must be positive", i),
+                ));
+            }
+            if i as usize > MAX_PIXELS {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("NeoPixel count {} is too large; must be at most {}", i, MAX_PIXELS),
+                ));
+            }
+            i as usize
+        };
+
+        let mut strip = self.strip.borrow_mut();
+        strip.pixels = None;
+        strip.neopixels.borrow_mut().open(pin, count).map_err(|e| scope.io_error(e))?;
+        strip.pixels = Some(vec![(0, 0, 0); count]);
+        Ok(())
+    }
+}
+
+/// The `NEOPIXEL_SET` command.
+pub struct NeopixelSetCommand {
+    metadata: CallableMetadata,
+    strip: Rc<RefCell<Strip>>,
+}
+
+impl NeopixelSetCommand {
+    /// Creates a new instance of the command.
+    pub fn new(strip: Rc<RefCell<Strip>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("NEOPIXEL_SET")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("index"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("r"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("g"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("b"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets the color of one LED in the NeoPixel strip.
+The index% of the first LED is 0.  The r%, g%, and b% color components must be between 0 and \
+255.  The new color is not visible until NEOPIXEL_SHOW is used.",
+                )
+                .build(),
+            strip,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for NeopixelSetCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(4, scope.nargs());
+        let (index, index_pos) = scope.pop_integer_with_pos();
+        let rgb = pop_rgb(&mut scope)?;
+
+        let mut strip = self.strip.borrow_mut();
+        let pixels = strip.get_mut_pixels(&scope)?;
+        match usize::try_from(index).ok().and_then(|i| pixels.get_mut(i)) {
+            Some(pixel) => *pixel = rgb,
+            None => {
+                return Err(Error::SyntaxError(
+                    index_pos,
+                    format!("Index {} must be between 0 and {}", index, pixels.len() - 1),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `NEOPIXEL_SHOW` command.
+pub struct NeopixelShowCommand {
+    metadata: CallableMetadata,
+    strip: Rc<RefCell<Strip>>,
+}
+
+impl NeopixelShowCommand {
+    /// Creates a new instance of the command.
+    pub fn new(strip: Rc<RefCell<Strip>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("NEOPIXEL_SHOW")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sends the colors of all LEDs to the NeoPixel strip.
+Makes the changes done with NEOPIXEL_SET and NEOPIXEL_FILL visible.",
+                )
+                .build(),
+            strip,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for NeopixelShowCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        let mut strip = self.strip.borrow_mut();
+        let neopixels = strip.neopixels.clone();
+        let pixels = strip.get_mut_pixels(&scope)?;
+        let result = neopixels.borrow_mut().show(pixels);
+        result.map_err(|e| scope.io_error(e))
+    }
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+pub fn add_all(machine: &mut Machine, neopixels: Rc<RefCell<dyn NeoPixels>>) {
+    let strip = Rc::from(RefCell::from(Strip::new(neopixels)));
+    machine.add_clearable(StripClearable::new(strip.clone()));
+    machine.add_callable(NeopixelFillCommand::new(strip.clone()));
+    machine.add_callable(NeopixelInitCommand::new(strip.clone()));
+    machine.add_callable(NeopixelSetCommand::new(strip.clone()));
+    machine.add_callable(NeopixelShowCommand::new(strip));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    /// Mock NeoPixel backend that records all operations.
+    #[derive(Default)]
+    struct RecordingNeoPixels {
+        /// Parameters of every open operation, in order.
+        opens: Vec<(Pin, usize)>,
+
+        /// Colors sent by every show operation, in order.
+        shows: Vec<Vec<RGB>>,
+    }
+
+    impl NeoPixels for RecordingNeoPixels {
+        fn open(&mut self, pin: Pin, count: usize) -> io::Result<()> {
+            self.opens.push((pin, count));
+            Ok(())
+        }
+
+        fn show(&mut self, pixels: &[RGB]) -> io::Result<()> {
+            self.shows.push(pixels.to_owned());
+            Ok(())
+        }
+    }
+
+    /// Mock SPI bus that records the parameters of the open device and all sent bytes.
+    #[derive(Default)]
+    struct RecordingSpi {
+        open: Rc<RefCell<Option<(SpiDevice, u32, SpiMode)>>>,
+        txs: Rc<RefCell<Vec<Vec<u8>>>>,
+    }
+
+    impl Spi for RecordingSpi {
+        fn open(&mut self, device: SpiDevice, speed_hz: u32, mode: SpiMode) -> io::Result<()> {
+            *self.open.borrow_mut() = Some((device, speed_hz, mode));
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            *self.open.borrow_mut() = None;
+            Ok(())
+        }
+
+        fn transfer(&mut self, tx: &[u8], _rx: &mut [u8]) -> io::Result<()> {
+            self.txs.borrow_mut().push(tx.to_owned());
+            Ok(())
+        }
+    }
+
+    /// Creates a tester with the NeoPixel commands backed by `neopixels`.
+    fn tester(neopixels: Rc<RefCell<dyn NeoPixels>>) -> Tester {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), neopixels);
+        t
+    }
+
+    /// Runs `code` against a recording backend and returns the backend for inspection.
+    fn run_recorded(code: &str) -> RecordingNeoPixels {
+        let neopixels = Rc::from(RefCell::from(RecordingNeoPixels::default()));
+        tester(neopixels.clone()).run(code).check();
+        Rc::try_unwrap(neopixels).ok().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_spi_encode() {
+        let mut exp = vec![
+            0x92, 0x49, 0x24, // G = 0x00.
+            0xdb, 0x6d, 0xb6, // R = 0xff.
+            0xd2, 0x69, 0xb4, // B = 0x96.
+        ];
+        exp.resize(exp.len() + SPI_RESET_BYTES, 0);
+        assert_eq!(exp, spi_encode(&[(0xff, 0x00, 0x96)]));
+
+        assert_eq!(vec![0; SPI_RESET_BYTES], spi_encode(&[]));
+    }
+
+    #[test]
+    fn test_neopixel_init_and_show() {
+        let neopixels = run_recorded("NEOPIXEL_INIT 10, 3: NEOPIXEL_SHOW");
+        assert_eq!(vec![(Pin(10), 3)], neopixels.opens);
+        assert_eq!(vec![vec![(0, 0, 0); 3]], neopixels.shows);
+    }
+
+    #[test]
+    fn test_neopixel_set_and_fill() {
+        let neopixels = run_recorded(
+            "NEOPIXEL_INIT 10, 4
+            NEOPIXEL_SET 1, 255, 0, 0
+            NEOPIXEL_SET 3, 1, 2, 3
+            NEOPIXEL_SHOW
+            NEOPIXEL_FILL 0, 0, 128
+            NEOPIXEL_SET 0, 10, 20, 30
+            NEOPIXEL_SHOW",
+        );
+        assert_eq!(
+            vec![
+                vec![(0, 0, 0), (255, 0, 0), (0, 0, 0), (1, 2, 3)],
+                vec![(10, 20, 30), (0, 0, 128), (0, 0, 128), (0, 0, 128)],
+            ],
+            neopixels.shows
+        );
+    }
+
+    #[test]
+    fn test_neopixel_init_resets_buffer() {
+        let neopixels = run_recorded(
+            "NEOPIXEL_INIT 10, 2: NEOPIXEL_FILL 1, 1, 1: NEOPIXEL_INIT 20, 3: NEOPIXEL_SHOW",
+        );
+        assert_eq!(vec![(Pin(10), 2), (Pin(20), 3)], neopixels.opens);
+        assert_eq!(vec![vec![(0, 0, 0); 3]], neopixels.shows);
+    }
+
+    #[test]
+    fn test_neopixel_show_encodes_on_spi() {
+        let spi = RecordingSpi::default();
+        let open = spi.open.clone();
+        let txs = spi.txs.clone();
+        let devices = vec![(Pin(10), SpiDevice { bus: 0, cs: 0 })];
+        let neopixels = Rc::from(RefCell::from(SpiNeoPixels::new(Box::from(spi), devices)));
+
+        tester(neopixels)
+            .run("NEOPIXEL_INIT 10, 2: NEOPIXEL_SET 1, 255, 0, 150: NEOPIXEL_SHOW")
+            .check();
+
+        assert_eq!(
+            Some((SpiDevice { bus: 0, cs: 0 }, SPI_CLOCK_HZ, SpiMode::Mode0)),
+            *open.borrow()
+        );
+        let mut exp = vec![
+            0x92, 0x49, 0x24, 0x92, 0x49, 0x24, 0x92, 0x49, 0x24, // (0, 0, 0).
+            0x92, 0x49, 0x24, 0xdb, 0x6d, 0xb6, 0xd2, 0x69, 0xb4, // (255, 0, 150).
+        ];
+        exp.resize(exp.len() + SPI_RESET_BYTES, 0);
+        assert_eq!(vec![exp], *txs.borrow());
+    }
+
+    #[test]
+    fn test_neopixel_spi_unknown_pin() {
+        let devices = vec![(Pin(10), SpiDevice { bus: 0, cs: 0 })];
+        let neopixels =
+            Rc::from(RefCell::from(SpiNeoPixels::new(Box::from(RecordingSpi::default()), devices)));
+        tester(neopixels)
+            .run("NEOPIXEL_INIT 11, 2")
+            .expect_err("1:1: Pin 11 cannot drive a NeoPixel strip")
+            .check();
+    }
+
+    #[test]
+    fn test_neopixel_real_backend() {
+        check_stmt_err("1:1: NeoPixel backend not compiled in", "NEOPIXEL_INIT 10, 5");
+    }
+
+    #[test]
+    fn test_neopixel_not_initialized() {
+        let neopixels = Rc::from(RefCell::from(RecordingNeoPixels::default()));
+        for stmt in ["NEOPIXEL_SET 0, 1, 2, 3", "NEOPIXEL_FILL 1, 2, 3", "NEOPIXEL_SHOW"] {
+            tester(neopixels.clone())
+                .run(stmt)
+                .expect_err("1:1: NeoPixel strip not initialized; use NEOPIXEL_INIT first")
+                .check();
+        }
+        assert!(neopixels.borrow().shows.is_empty());
+    }
+
+    #[test]
+    fn test_neopixel_errors() {
+        let neopixels = Rc::from(RefCell::from(RecordingNeoPixels::default()));
+        for (stmt, exp_err) in [
+            ("NEOPIXEL_INIT -1, 5", "1:15: Pin number -1 must be positive"),
+            ("NEOPIXEL_INIT 10, 0", "1:19: NeoPixel count 0 must be positive"),
+            (
+                "NEOPIXEL_INIT 10, 2000",
+                "1:19: NeoPixel count 2000 is too large; must be at most 1024",
+            ),
+            (
+                "NEOPIXEL_INIT 10, 3: NEOPIXEL_SET 3, 0, 0, 0",
+                "1:35: Index 3 must be between 0 and 2",
+            ),
+            (
+                "NEOPIXEL_INIT 10, 3: NEOPIXEL_SET -1, 0, 0, 0",
+                "1:35: Index -1 must be between 0 and 2",
+            ),
+            (
+                "NEOPIXEL_INIT 10, 3: NEOPIXEL_SET 0, 256, 0, 0",
+                "1:38: Color component 256 must be between 0 and 255",
+            ),
+            (
+                "NEOPIXEL_INIT 10, 3: NEOPIXEL_SET 0, 0, -1, 0",
+                "1:41: Color component -1 must be between 0 and 255",
+            ),
+            (
+                "NEOPIXEL_INIT 10, 3: NEOPIXEL_FILL 0, 0, 300",
+                "1:42: Color component 300 must be between 0 and 255",
+            ),
+        ] {
+            tester(neopixels.clone()).run(stmt).expect_err(exp_err).check();
+        }
+        assert!(neopixels.borrow().shows.is_empty());
+
+        check_stmt_compilation_err(
+            "1:1: NEOPIXEL_SET expected index%, r%, g%, b%",
+            "NEOPIXEL_SET 1, 2, 3",
+        );
+        check_stmt_compilation_err("1:1: NEOPIXEL_FILL expected r%, g%, b%", "NEOPIXEL_FILL 1");
+        check_stmt_compilation_err("1:1: NEOPIXEL_INIT expected pin%, count%", "NEOPIXEL_INIT 1");
+        check_stmt_compilation_err("1:1: NEOPIXEL_SHOW expected no arguments", "NEOPIXEL_SHOW 1");
+    }
+}
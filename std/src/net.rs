@@ -0,0 +1,543 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Networking functions for EndBASIC.
+
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io;
+use std::rc::Rc;
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Networking
+The networking functions let programs exchange data with web servers over HTTP and HTTPS.  \
+Requests that fail, either because the server cannot be reached or because it responds with a \
+status other than 2xx, raise an error that can be caught with ON ERROR, and HTTPSTATUS% tells \
+what the status of the last request was.
+Networking may be disabled by the environment in which the interpreter runs, in which case all \
+requests fail.";
+
+/// Default maximum number of bytes that a response may carry.
+pub const DEFAULT_MAX_RESPONSE_LENGTH: usize = 1024 * 1024;
+
+/// An HTTP request issued by a program.
+#[derive(Debug, PartialEq)]
+pub enum HttpRequest<'a> {
+    /// A GET request for a URL.
+    Get {
+        /// The URL to fetch.
+        url: &'a str,
+    },
+
+    /// A POST request to a URL.
+    Post {
+        /// The URL to post to.
+        url: &'a str,
+
+        /// The body of the request.
+        body: &'a str,
+
+        /// The value of the `Content-Type` header that describes the `body`.
+        content_type: &'a str,
+    },
+}
+
+/// Response to an HTTP request.
+pub struct HttpResponse {
+    /// The status code of the response.
+    pub status: u16,
+
+    /// The body of the response.
+    pub body: Vec<u8>,
+}
+
+/// Abstract interface to issue HTTP requests on behalf of programs.
+#[async_trait(?Send)]
+pub trait HttpClient {
+    /// Issues the `request` and returns the response, whatever its status code is.
+    ///
+    /// Implementations should stop reading the body once it grows beyond `max_length` bytes and
+    /// return the data read so far, so that callers can reject oversized responses without
+    /// downloading them in full.
+    async fn request(
+        &self,
+        request: HttpRequest<'_>,
+        max_length: usize,
+    ) -> io::Result<HttpResponse>;
+}
+
+/// State shared by all networking functions.
+pub struct Network {
+    /// The client to issue requests with, or `None` if networking is disabled.
+    client: Option<Rc<dyn HttpClient>>,
+
+    /// Maximum number of bytes that a response may carry.
+    max_response_length: usize,
+
+    /// Status code of the last request, or zero if there was none or if it did not get a response.
+    last_status: Cell<u16>,
+}
+
+impl Network {
+    /// Creates the networking state to issue requests with `client`, or with networking disabled
+    /// if `client` is `None`, rejecting responses longer than `max_response_length` bytes.
+    pub fn new(client: Option<Rc<dyn HttpClient>>, max_response_length: usize) -> Rc<Self> {
+        Rc::from(Self { client, max_response_length, last_status: Cell::new(0) })
+    }
+
+    /// Issues the `request` for the `url` given at `pos` and returns the body of the response.
+    async fn fetch(
+        &self,
+        request: HttpRequest<'_>,
+        url: &str,
+        pos: LineCol,
+        scope: &Scope<'_>,
+    ) -> Result<String> {
+        self.last_status.set(0);
+
+        let client = match self.client.as_ref() {
+            Some(client) => client,
+            None => return Err(Error::SyntaxError(pos, "Networking is disabled".to_owned())),
+        };
+
+        let lower = url.to_ascii_lowercase();
+        if !lower.starts_with("http://") && !lower.starts_with("https://") {
+            return Err(Error::SyntaxError(
+                pos,
+                format!("Invalid URL {}: only http and https are supported", url),
+            ));
+        }
+
+        let response = match client.request(request, self.max_response_length).await {
+            Ok(response) => response,
+            Err(e) => {
+                return Err(scope.io_error(io::Error::new(
+                    e.kind(),
+                    format!("HTTP request failed ({}): {}", e.kind(), e),
+                )))
+            }
+        };
+
+        self.last_status.set(response.status);
+        if !(200..300).contains(&response.status) {
+            return Err(scope.io_error(io::Error::other(format!(
+                "HTTP request failed with status {}",
+                response.status
+            ))));
+        }
+        if response.body.len() > self.max_response_length {
+            return Err(scope.io_error(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("HTTP response exceeds the maximum of {} bytes", self.max_response_length),
+            )));
+        }
+        Ok(String::from_utf8_lossy(&response.body).into_owned())
+    }
+}
+
+/// Resets the status of the last request when the machine is cleared.
+struct NetworkClearable(Rc<Network>);
+
+impl Clearable for NetworkClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.last_status.set(0);
+    }
+}
+
+/// The `HTTPGET` function.
+pub struct HttpgetFunction {
+    metadata: CallableMetadata,
+    network: Rc<Network>,
+}
+
+impl HttpgetFunction {
+    /// Creates a new instance of the function.
+    pub fn new(network: Rc<Network>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("HTTPGET")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("url"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Fetches the contents of a URL.
+Issues a GET request for url$, which must start with http:// or https://, and returns the body \
+of the response.",
+                )
+                .build(),
+            network,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for HttpgetFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (url, pos) = scope.pop_string_with_pos();
+
+        let body = self.network.fetch(HttpRequest::Get { url: &url }, &url, pos, &scope).await?;
+        scope.return_string(body)
+    }
+}
+
+/// The `HTTPPOST` function.
+pub struct HttppostFunction {
+    metadata: CallableMetadata,
+    network: Rc<Network>,
+}
+
+impl HttppostFunction {
+    /// Creates a new instance of the function.
+    pub fn new(network: Rc<Network>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("HTTPPOST")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("url"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("body"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("contenttype"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sends data to a URL.
+Issues a POST request to url$, which must start with http:// or https://, carrying body$ as the \
+payload of the request and contenttype$ as its type (such as application/json), and returns the \
+body of the response.",
+                )
+                .build(),
+            network,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for HttppostFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(3, scope.nargs());
+        let (url, pos) = scope.pop_string_with_pos();
+        let body = scope.pop_string();
+        let content_type = scope.pop_string();
+
+        let request = HttpRequest::Post { url: &url, body: &body, content_type: &content_type };
+        let body = self.network.fetch(request, &url, pos, &scope).await?;
+        scope.return_string(body)
+    }
+}
+
+/// The `HTTPSTATUS` function.
+pub struct HttpstatusFunction {
+    metadata: CallableMetadata,
+    network: Rc<Network>,
+}
+
+impl HttpstatusFunction {
+    /// Creates a new instance of the function.
+    pub fn new(network: Rc<Network>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("HTTPSTATUS")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the status code of the last HTTP request.
+Returns 0 if no request has been issued yet or if the last request did not get a response from \
+the server, such as when the server could not be reached.",
+                )
+                .build(),
+            network,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for HttpstatusFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_integer(i32::from(self.network.last_status.get()))
+    }
+}
+
+/// Adds all networking functions to the `machine`, using `network` to issue the requests.
+pub fn add_all(machine: &mut Machine, network: Rc<Network>) {
+    machine.add_clearable(Box::from(NetworkClearable(network.clone())));
+    machine.add_callable(HttpgetFunction::new(network.clone()));
+    machine.add_callable(HttppostFunction::new(network.clone()));
+    machine.add_callable(HttpstatusFunction::new(network));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// A request recorded by the `MockHttpClient`, with its method, URL, body and content type.
+    type RecordedRequest = (&'static str, String, String, String);
+
+    /// HTTP client that serves canned responses and records all requests it gets.
+    #[derive(Default)]
+    struct MockHttpClient {
+        responses: HashMap<String, io::Result<(u16, Vec<u8>)>>,
+        requests: RefCell<Vec<RecordedRequest>>,
+    }
+
+    impl MockHttpClient {
+        /// Registers a `response` with `status` and `body` for `url`.
+        fn add_response(mut self, url: &str, status: u16, body: &str) -> Self {
+            self.responses.insert(url.to_owned(), Ok((status, body.as_bytes().to_owned())));
+            self
+        }
+
+        /// Registers a failure to get a response of `kind` for `url`.
+        fn add_failure(mut self, url: &str, kind: io::ErrorKind, message: &str) -> Self {
+            self.responses.insert(url.to_owned(), Err(io::Error::new(kind, message)));
+            self
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl HttpClient for MockHttpClient {
+        async fn request(
+            &self,
+            request: HttpRequest<'_>,
+            max_length: usize,
+        ) -> io::Result<HttpResponse> {
+            let url = match request {
+                HttpRequest::Get { url } => {
+                    self.requests.borrow_mut().push((
+                        "GET",
+                        url.to_owned(),
+                        String::new(),
+                        String::new(),
+                    ));
+                    url
+                }
+                HttpRequest::Post { url, body, content_type } => {
+                    self.requests.borrow_mut().push((
+                        "POST",
+                        url.to_owned(),
+                        body.to_owned(),
+                        content_type.to_owned(),
+                    ));
+                    url
+                }
+            };
+
+            match self.responses.get(url) {
+                Some(Ok((status, body))) => {
+                    let mut body = body.clone();
+                    body.truncate(max_length + 1);
+                    Ok(HttpResponse { status: *status, body })
+                }
+                Some(Err(e)) => Err(io::Error::new(e.kind(), e.to_string())),
+                None => panic!("No response registered for {}", url),
+            }
+        }
+    }
+
+    /// Creates a tester with the networking functions backed by `client` and with a maximum
+    /// response length of `max_length`.
+    fn tester_with_client(client: Rc<MockHttpClient>, max_length: usize) -> Tester {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), Network::new(Some(client), max_length));
+        t
+    }
+
+    #[test]
+    fn test_httpget_ok() {
+        let client = Rc::from(
+            MockHttpClient::default()
+                .add_response("http://example.com/", 200, "Hello")
+                .add_response("HTTPS://example.com/empty", 204, ""),
+        );
+        let mut t = tester_with_client(client, 100);
+        t.run(r#"a = HTTPGET("http://example.com/"): b = HTTPGET("HTTPS://example.com/empty")"#)
+            .expect_var("a", "Hello")
+            .expect_var("b", "")
+            .check();
+    }
+
+    #[test]
+    fn test_httpget_status() {
+        let client = Rc::from(
+            MockHttpClient::default()
+                .add_response("http://example.com/", 200, "Hello")
+                .add_response("http://example.com/created", 201, "Done"),
+        );
+        let mut t = tester_with_client(client, 100);
+        t.run(r#"a = HTTPSTATUS: b$ = HTTPGET("http://example.com/created"): c = HTTPSTATUS"#)
+            .expect_var("a", 0)
+            .expect_var("b", "Done")
+            .expect_var("c", 201)
+            .check();
+        t.get_machine().clear();
+        t.run("d = HTTPSTATUS").expect_var("d", 0).check();
+    }
+
+    #[test]
+    fn test_httpget_not_found() {
+        let client =
+            Rc::from(MockHttpClient::default().add_response("http://example.com/x", 404, "Gone"));
+        let mut t = tester_with_client(client, 100);
+        t.run(r#"a = HTTPGET("http://example.com/x")"#)
+            .expect_err("1:5: HTTP request failed with status 404")
+            .check();
+        t.run("b = HTTPSTATUS").expect_var("b", 404).check();
+    }
+
+    #[test]
+    fn test_httpget_timeout() {
+        let client = Rc::from(MockHttpClient::default().add_failure(
+            "http://example.com/slow",
+            io::ErrorKind::TimedOut,
+            "operation timed out",
+        ));
+        let mut t = tester_with_client(client, 100);
+        t.run(r#"a = HTTPGET("http://example.com/slow")"#)
+            .expect_err("1:5: HTTP request failed (timed out): operation timed out")
+            .check();
+        t.run("b = HTTPSTATUS").expect_var("b", 0).check();
+    }
+
+    #[test]
+    fn test_httpget_size_cap() {
+        let client = Rc::from(
+            MockHttpClient::default()
+                .add_response("http://example.com/fits", 200, "12345")
+                .add_response("http://example.com/big", 200, "123456"),
+        );
+        let mut t = tester_with_client(client, 5);
+        t.run(r#"a = HTTPGET("http://example.com/fits")"#).expect_var("a", "12345").check();
+        t.run(r#"b = HTTPGET("http://example.com/big")"#)
+            .expect_var("a", "12345")
+            .expect_err("1:5: HTTP response exceeds the maximum of 5 bytes")
+            .check();
+    }
+
+    #[test]
+    fn test_httpget_disabled() {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), Network::new(None, 100));
+        t.run(r#"a = HTTPGET("http://example.com/")"#)
+            .expect_err("1:13: Networking is disabled")
+            .check();
+
+        check_expr_error("1:18: Networking is disabled", r#"HTTPGET("http://example.com/")"#);
+    }
+
+    #[test]
+    fn test_httpget_errors() {
+        let client = Rc::from(MockHttpClient::default());
+        let mut t = tester_with_client(client.clone(), 100);
+        t.run(r#"a = HTTPGET("file:///etc/passwd")"#)
+            .expect_err("1:13: Invalid URL file:///etc/passwd: only http and https are supported")
+            .check();
+        assert!(client.requests.borrow().is_empty());
+
+        check_expr_compilation_error("1:10: HTTPGET expected url$", r#"HTTPGET()"#);
+        check_expr_compilation_error("1:10: HTTPGET expected url$", r#"HTTPGET("a", "b")"#);
+        check_expr_compilation_error("1:18: expected STRING but found INTEGER", r#"HTTPGET(3)"#);
+    }
+
+    #[test]
+    fn test_httppost_ok() {
+        let client =
+            Rc::from(MockHttpClient::default().add_response("http://example.com/api", 200, "OK"));
+        let mut t = tester_with_client(client.clone(), 100);
+        t.run(r#"a = HTTPPOST("http://example.com/api", "{\"x\": 1}", "application/json")"#)
+            .expect_var("a", "OK")
+            .check();
+        assert_eq!(
+            [(
+                "POST",
+                "http://example.com/api".to_owned(),
+                "{\"x\": 1}".to_owned(),
+                "application/json".to_owned()
+            )],
+            client.requests.borrow().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_httppost_errors() {
+        let client =
+            Rc::from(MockHttpClient::default().add_response("http://example.com/api", 500, "Oops"));
+        let mut t = tester_with_client(client, 100);
+        t.run(r#"a = HTTPPOST("http://example.com/api", "", "text/plain")"#)
+            .expect_err("1:5: HTTP request failed with status 500")
+            .check();
+
+        check_expr_compilation_error(
+            "1:10: HTTPPOST expected url$, body$, contenttype$",
+            r#"HTTPPOST("a", "b")"#,
+        );
+        check_expr_compilation_error(
+            "1:29: expected STRING but found INTEGER",
+            r#"HTTPPOST("a", "b", 3)"#,
+        );
+        check_expr_error("1:19: Networking is disabled", r#"HTTPPOST("http://x", "", "")"#);
+    }
+
+    #[test]
+    fn test_httpstatus_errors() {
+        check_expr_compilation_error("1:10: HTTPSTATUS expected no arguments", "HTTPSTATUS(1)");
+    }
+}
@@ -16,18 +16,21 @@
 //! Numerical functions for EndBASIC.
 
 use async_trait::async_trait;
-use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::ast::{ArgSep, ExprType, VarRef};
 use endbasic_core::compiler::{
-    ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredValueSyntax, SingularArgSyntax,
+    ArgSepSyntax, NumericValueSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredRefSyntax,
+    RequiredValueSyntax, SingularArgSyntax,
 };
-use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
-use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope, ValueTag};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbol, Symbols};
 use endbasic_core::value::double_to_integer;
+use endbasic_core::LineCol;
 use rand::rngs::SmallRng;
 use rand::{RngCore, SeedableRng};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp::Ordering;
+use std::io;
 use std::rc::Rc;
 
 /// Category description for all symbols provided by this module.
@@ -50,6 +53,52 @@ impl Clearable for ClearableAngleMode {
     fn reset_state(&self, _syms: &mut Symbols) {
         *self.angle_mode.borrow_mut() = AngleMode::Radians;
     }
+
+    fn state_key(&self) -> Option<&'static str> {
+        Some("numerics.angle_mode")
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        match *self.angle_mode.borrow() {
+            AngleMode::Degrees => vec![0],
+            AngleMode::Radians => vec![1],
+        }
+    }
+
+    fn restore_state(&self, state: &[u8]) -> io::Result<()> {
+        *self.angle_mode.borrow_mut() = match state {
+            [0] => AngleMode::Degrees,
+            [1] => AngleMode::Radians,
+            _ => {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid angle mode state"))
+            }
+        };
+        Ok(())
+    }
+}
+
+/// Saves and restores the state of the PRNG in machine snapshots.
+///
+/// Clearing the machine does not reseed the PRNG, so this only exists to hook into snapshots.
+struct ClearablePrng {
+    prng: Rc<RefCell<Prng>>,
+}
+
+impl Clearable for ClearablePrng {
+    fn reset_state(&self, _syms: &mut Symbols) {}
+
+    fn state_key(&self) -> Option<&'static str> {
+        Some("numerics.prng")
+    }
+
+    fn save_state(&self) -> Vec<u8> {
+        self.prng.borrow().save()
+    }
+
+    fn restore_state(&self, state: &[u8]) -> io::Result<()> {
+        *self.prng.borrow_mut() = Prng::restore(state)?;
+        Ok(())
+    }
 }
 
 /// Gets the single argument to a trigonometric function, which is its angle.  Applies units
@@ -64,27 +113,100 @@ async fn get_angle(scope: &mut Scope<'_>, angle_mode: &AngleMode) -> Result<f64>
     }
 }
 
+/// Pops a numeric argument pushed along with its tag.  Returns the value as a double, whether the
+/// value was originally a double, and its position.
+fn pop_number_with_pos(scope: &mut Scope<'_>) -> (f64, bool, LineCol) {
+    match scope.pop_value_tag() {
+        ValueTag::Double => {
+            let (n, pos) = scope.pop_double_with_pos();
+            (n, true, pos)
+        }
+        ValueTag::Integer => {
+            let (i, pos) = scope.pop_integer_with_pos();
+            (f64::from(i), false, pos)
+        }
+        _ => unreachable!("Numeric arguments must have been type-checked at compile time"),
+    }
+}
+
+/// Returns `value` from a function with numeric promotion: as a double if any of the arguments was
+/// a double, or as an integer otherwise.
+fn return_number(scope: Scope<'_>, value: f64, is_double: bool) -> Result<()> {
+    if is_double {
+        scope.return_double(value)
+    } else {
+        scope.return_integer(value as i32)
+    }
+}
+
 /// Tracks the state of the PRNG used by the random number manipulation functions and commands.
 ///
 /// The PRNG implemented here is intentionally simplistic and has no cryptographical guarantees.
+///
+/// The PRNG remembers its seed and the number of values it has drawn so that its state can be
+/// saved and later recreated by replaying the same number of draws.
 pub struct Prng {
     prng: SmallRng,
+    seed: u64,
+    draws: u64,
     last: u32,
 }
 
 impl Prng {
     /// Generates a new PRNG based on system entropy.
     pub fn new_from_entryopy() -> Self {
-        let mut prng = SmallRng::from_entropy();
-        let last = prng.next_u32();
-        Self { prng, last }
+        Self::new_from_seed_u64(SmallRng::from_entropy().next_u64())
     }
 
     /// Generates a new PRNG based on the given seed.
     pub fn new_from_seed(seed: i32) -> Self {
-        let mut prng = SmallRng::seed_from_u64(seed as u64);
-        let last = prng.next_u32();
-        Self { prng, last }
+        Self::new_from_seed_u64(seed as u64)
+    }
+
+    /// Generates a new PRNG based on the given raw seed.
+    fn new_from_seed_u64(seed: u64) -> Self {
+        let mut prng = Self { prng: SmallRng::seed_from_u64(seed), seed, draws: 0, last: 0 };
+        prng.last = prng.draw();
+        prng
+    }
+
+    /// Serializes the state of the PRNG.
+    fn save(&self) -> Vec<u8> {
+        let mut state = Vec::with_capacity(16);
+        state.extend_from_slice(&self.seed.to_le_bytes());
+        state.extend_from_slice(&self.draws.to_le_bytes());
+        state
+    }
+
+    /// Recreates a PRNG from the `state` serialized by `save`.
+    ///
+    /// This replays all the draws done by the original PRNG, so it takes time proportional to the
+    /// number of random values that were generated.
+    fn restore(state: &[u8]) -> io::Result<Self> {
+        if state.len() != 16 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "Invalid PRNG state"));
+        }
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&state[..8]);
+        let mut draws = [0; 8];
+        draws.copy_from_slice(&state[8..]);
+
+        let mut prng = Self {
+            prng: SmallRng::seed_from_u64(u64::from_le_bytes(seed)),
+            seed: u64::from_le_bytes(seed),
+            draws: 0,
+            last: 0,
+        };
+        for _ in 0..u64::from_le_bytes(draws) {
+            prng.last = prng.draw();
+        }
+        Ok(prng)
+    }
+
+    /// Draws the next raw value from the generator.
+    fn draw(&mut self) -> u32 {
+        self.draws += 1;
+        self.prng.next_u32()
     }
 
     /// Returns the previously returned random number.
@@ -94,33 +216,110 @@ impl Prng {
 
     /// Computes the next random number and returns it.
     fn next(&mut self) -> f64 {
-        self.last = self.prng.next_u32();
+        self.last = self.draw();
         self.last()
     }
+
+    /// Computes the next random number in the `[0, 1)` range and returns it.
+    fn next_unit(&mut self) -> f64 {
+        self.last = self.draw();
+        f64::from(self.last) / 4294967296.0
+    }
+
+    /// Computes the next random number from a normal distribution with the given `mean` and
+    /// `stddev` using the Box-Muller transform.
+    fn next_normal(&mut self, mean: f64, stddev: f64) -> f64 {
+        let u1 = 1.0 - self.next_unit();
+        let u2 = self.next_unit();
+        let z = (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos();
+        mean + stddev * z
+    }
+
+    /// Computes the next random number from an exponential distribution with rate `lambda`.
+    fn next_exponential(&mut self, lambda: f64) -> f64 {
+        let u = 1.0 - self.next_unit();
+        -u.ln() / lambda
+    }
+
+    /// Picks the index of one of the `weights` with a probability proportional to its value.
+    ///
+    /// The weights must not be negative and at least one of them must be positive.
+    fn next_weighted(&mut self, weights: &[f64]) -> usize {
+        let total: f64 = weights.iter().sum();
+        debug_assert!(total > 0.0);
+        let target = self.next_unit() * total;
+        let mut cumulative = 0.0;
+        let mut last_positive = 0;
+        for (i, weight) in weights.iter().enumerate() {
+            if *weight > 0.0 {
+                cumulative += weight;
+                if target < cumulative {
+                    return i;
+                }
+                last_positive = i;
+            }
+        }
+        // Rounding errors in the cumulative sum may leave `target` just past the end.
+        last_positive
+    }
+
+    /// Computes the next random integer in the `[lo, hi]` range and returns it.
+    ///
+    /// Uses rejection sampling to discard the raw values that would make some of the integers in
+    /// the range more likely than others.
+    fn next_in_range(&mut self, lo: i32, hi: i32) -> i32 {
+        debug_assert!(lo <= hi);
+        let n = (i64::from(hi) - i64::from(lo) + 1) as u64;
+        let zone = (1u64 << 32) / n * n;
+        loop {
+            self.last = self.draw();
+            let raw = u64::from(self.last);
+            if raw < zone {
+                return (i64::from(lo) + (raw % n) as i64) as i32;
+            }
+        }
+    }
 }
 
-/// The `ATN` function.
-pub struct AtnFunction {
+/// The `ATAN2` function.
+pub struct Atan2Function {
     metadata: CallableMetadata,
     angle_mode: Rc<RefCell<AngleMode>>,
 }
 
-impl AtnFunction {
+impl Atan2Function {
     /// Creates a new instance of the function.
     pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("ATN")
+            metadata: CallableMetadataBuilder::new("ATAN2")
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[(
-                    &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
-                        ArgSepSyntax::End,
-                    )],
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("y"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("x"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
                     None,
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Computes the arc-tangent of a number.
+                    "Computes the angle of the point at the given coordinates.
+Returns the angle between the positive X axis and the line that goes from the origin to the \
+(x#, y#) point, in the range [-PI..PI].  Unlike ATN(y# / x#), this takes the signs of both \
+coordinates into account to select the right quadrant and does not fail when x# is zero.
+Points on the positive X axis yield 0, points on the positive Y axis yield PI/2, points on the \
+negative Y axis yield -PI/2, and points on the negative X axis yield PI.  The origin yields 0.
 The resulting angle is measured in degrees or radians depending on the angle mode as selected by \
 the DEG and RAD commands.",
                 )
@@ -131,85 +330,86 @@ the DEG and RAD commands.",
 }
 
 #[async_trait(?Send)]
-impl Callable for AtnFunction {
+impl Callable for Atan2Function {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(1, scope.nargs());
-        let n = scope.pop_double();
+        debug_assert_eq!(2, scope.nargs());
+        let y = scope.pop_double();
+        let x = scope.pop_double();
 
         match *self.angle_mode.borrow() {
-            AngleMode::Degrees => scope.return_double(n.atan().to_degrees()),
-            AngleMode::Radians => scope.return_double(n.atan()),
+            AngleMode::Degrees => scope.return_double(y.atan2(x).to_degrees()),
+            AngleMode::Radians => scope.return_double(y.atan2(x)),
         }
     }
 }
 
-/// The `CINT` function.
-pub struct CintFunction {
+/// The `ATN` function.
+pub struct AtnFunction {
     metadata: CallableMetadata,
+    angle_mode: Rc<RefCell<AngleMode>>,
 }
 
-impl CintFunction {
+impl AtnFunction {
     /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
+    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("CINT")
-                .with_return_type(ExprType::Integer)
+            metadata: CallableMetadataBuilder::new("ATN")
+                .with_return_type(ExprType::Double)
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax {
-                            name: Cow::Borrowed("expr"),
-                            vtype: ExprType::Double,
-                        },
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
                         ArgSepSyntax::End,
                     )],
                     None,
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Casts the given numeric expression to an integer (with rounding).
-When casting a double value to an integer, the double value is first rounded to the closest \
-integer.  For example, 4.4 becomes 4, but both 4.5 and 4.6 become 5.",
+                    "Computes the arc-tangent of a number.
+The resulting angle is measured in degrees or radians depending on the angle mode as selected by \
+the DEG and RAD commands.",
                 )
                 .build(),
+            angle_mode,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for CintFunction {
+impl Callable for AtnFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
         debug_assert_eq!(1, scope.nargs());
-        let (value, pos) = scope.pop_double_with_pos();
+        let n = scope.pop_double();
 
-        let i = double_to_integer(value).map_err(|e| Error::SyntaxError(pos, e.to_string()))?;
-        scope.return_integer(i)
+        match *self.angle_mode.borrow() {
+            AngleMode::Degrees => scope.return_double(n.atan().to_degrees()),
+            AngleMode::Radians => scope.return_double(n.atan()),
+        }
     }
 }
 
-/// The `COS` function.
-pub struct CosFunction {
+/// The `CDBL` function.
+pub struct CdblFunction {
     metadata: CallableMetadata,
-    angle_mode: Rc<RefCell<AngleMode>>,
 }
 
-impl CosFunction {
+impl CdblFunction {
     /// Creates a new instance of the function.
-    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
+    pub fn new() -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("COS")
+            metadata: CallableMetadataBuilder::new("CDBL")
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
                         RequiredValueSyntax {
-                            name: Cow::Borrowed("angle"),
+                            name: Cow::Borrowed("expr"),
                             vtype: ExprType::Double,
                         },
                         ArgSepSyntax::End,
@@ -218,75 +418,82 @@ impl CosFunction {
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Computes the cosine of an angle.
-The input angle% or angle# is measured in degrees or radians depending on the angle mode as \
-selected by the DEG and RAD commands.",
+                    "Casts the given numeric expression to a double.
+Every integer can be represented exactly as a double so this conversion never fails.  Boolean \
+values are not accepted.",
                 )
                 .build(),
-            angle_mode,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for CosFunction {
+impl Callable for CdblFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        let angle = get_angle(&mut scope, &self.angle_mode.borrow()).await?;
-        scope.return_double(angle.cos())
+        debug_assert_eq!(1, scope.nargs());
+        let value = scope.pop_double();
+        scope.return_double(value)
     }
 }
 
-/// The `DEG` command.
-pub struct DegCommand {
+/// The `CEIL` function.
+pub struct CeilFunction {
     metadata: CallableMetadata,
-    angle_mode: Rc<RefCell<AngleMode>>,
 }
 
-impl DegCommand {
-    /// Creates a new instance of the command.
-    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
+impl CeilFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("DEG")
-                .with_syntax(&[(&[], None)])
+            metadata: CallableMetadataBuilder::new("CEIL")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("expr"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Sets degrees mode of calculation.
-The default condition for the trigonometric functions is to use radians.  DEG configures the \
-environment to use degrees until instructed otherwise.",
+                    "Rounds a number up to the closest integral value.
+The result is a double.  For example, both 4.1 and 4.9 become 5, and both -4.1 and -4.9 become -4.",
                 )
                 .build(),
-            angle_mode,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for DegCommand {
+impl Callable for CeilFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
-        *self.angle_mode.borrow_mut() = AngleMode::Degrees;
-        Ok(())
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let value = scope.pop_double();
+        scope.return_double(value.ceil())
     }
 }
 
-/// The `INT` function.
-pub struct IntFunction {
+/// The `CINT` function.
+pub struct CintFunction {
     metadata: CallableMetadata,
 }
 
-impl IntFunction {
+impl CintFunction {
     /// Creates a new instance of the function.
     pub fn new() -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("INT")
+            metadata: CallableMetadataBuilder::new("CINT")
                 .with_return_type(ExprType::Integer)
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
@@ -300,9 +507,11 @@ impl IntFunction {
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Casts the given numeric expression to an integer (with truncation).
-When casting a double value to an integer, the double value is first truncated to the smallest
-integer that is not larger than the double value.  For example, all of 4.4, 4.5 and 4.6 become 4.",
+                    "Casts the given numeric expression to an integer (with rounding).
+When casting a double value to an integer, the double value is first rounded to the closest \
+integer, with ties rounding to the closest even integer.  For example, 4.4 and 4.5 become 4, 4.6 \
+becomes 5, and 5.5 becomes 6.
+It is an error for the rounded value to not fit in an integer.  Boolean values are not accepted.",
                 )
                 .build(),
         })
@@ -310,7 +519,7 @@ integer that is not larger than the double value.  For example, all of 4.4, 4.5
 }
 
 #[async_trait(?Send)]
-impl Callable for IntFunction {
+impl Callable for CintFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
@@ -319,200 +528,1038 @@ impl Callable for IntFunction {
         debug_assert_eq!(1, scope.nargs());
         let (value, pos) = scope.pop_double_with_pos();
 
-        let i =
-            double_to_integer(value.floor()).map_err(|e| Error::SyntaxError(pos, e.to_string()))?;
+        let i = double_to_integer(value.round_ties_even())
+            .map_err(|e| Error::SyntaxError(pos, e.to_string()))?;
         scope.return_integer(i)
     }
 }
 
-/// The `MAX` function.
-pub struct MaxFunction {
+/// The `CLAMP` function.
+pub struct ClampFunction {
     metadata: CallableMetadata,
 }
 
-impl MaxFunction {
+impl ClampFunction {
     /// Creates a new instance of the function.
     pub fn new() -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("MAX")
+            metadata: CallableMetadataBuilder::new("CLAMP")
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[(
-                    &[],
-                    Some(&RepeatedSyntax {
-                        name: Cow::Borrowed("expr"),
-                        type_syn: RepeatedTypeSyntax::TypedValue(ExprType::Double),
-                        sep: ArgSepSyntax::Exactly(ArgSep::Long),
-                        require_one: true,
-                        allow_missing: false,
-                    }),
+                    &[
+                        SingularArgSyntax::NumericValue(
+                            NumericValueSyntax { name: Cow::Borrowed("value") },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::NumericValue(
+                            NumericValueSyntax { name: Cow::Borrowed("lo") },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::NumericValue(
+                            NumericValueSyntax { name: Cow::Borrowed("hi") },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
                 )])
+                .with_numeric_promotion()
                 .with_category(CATEGORY)
-                .with_description("Returns the maximum number out of a set of numbers.")
+                .with_description(
+                    "Limits a number to the given range.
+Returns lo if value is less than lo, hi if value is greater than hi, or value otherwise.  \
+The result is an integer if all arguments are integers, or a double otherwise.
+It is an error for lo to be greater than hi.",
+                )
                 .build(),
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for MaxFunction {
+impl Callable for ClampFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        let mut max = f64::MIN;
-        while scope.nargs() > 0 {
-            let n = scope.pop_double();
-            if n > max {
-                max = n;
-            }
+        debug_assert_eq!(6, scope.nargs());
+        let (value, value_double, _) = pop_number_with_pos(&mut scope);
+        let (lo, lo_double, lopos) = pop_number_with_pos(&mut scope);
+        let (hi, hi_double, _) = pop_number_with_pos(&mut scope);
+        if lo > hi {
+            return Err(Error::SyntaxError(
+                lopos,
+                "lo must be less than or equal to hi".to_owned(),
+            ));
         }
-        scope.return_double(max)
+        let result = if value < lo {
+            lo
+        } else if value > hi {
+            hi
+        } else {
+            value
+        };
+        return_number(scope, result, value_double || lo_double || hi_double)
     }
 }
 
-/// The `MIN` function.
-pub struct MinFunction {
+/// The `COS` function.
+pub struct CosFunction {
     metadata: CallableMetadata,
+    angle_mode: Rc<RefCell<AngleMode>>,
 }
 
-impl MinFunction {
+impl CosFunction {
     /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
+    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("MIN")
+            metadata: CallableMetadataBuilder::new("COS")
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[(
-                    &[],
-                    Some(&RepeatedSyntax {
-                        name: Cow::Borrowed("expr"),
-                        type_syn: RepeatedTypeSyntax::TypedValue(ExprType::Double),
-                        sep: ArgSepSyntax::Exactly(ArgSep::Long),
-                        require_one: true,
-                        allow_missing: false,
-                    }),
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("angle"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
                 )])
                 .with_category(CATEGORY)
-                .with_description("Returns the minimum number out of a set of numbers.")
+                .with_description(
+                    "Computes the cosine of an angle.
+The input angle% or angle# is measured in degrees or radians depending on the angle mode as \
+selected by the DEG and RAD commands.",
+                )
                 .build(),
+            angle_mode,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for MinFunction {
+impl Callable for CosFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        let mut min = f64::MAX;
-        while scope.nargs() > 0 {
-            let n = scope.pop_double();
-            if n < min {
-                min = n;
-            }
-        }
-        scope.return_double(min)
+        let angle = get_angle(&mut scope, &self.angle_mode.borrow()).await?;
+        scope.return_double(angle.cos())
     }
 }
 
-/// The `PI` function.
-pub struct PiFunction {
+/// The `COSH` function.
+pub struct CoshFunction {
     metadata: CallableMetadata,
 }
 
-impl PiFunction {
+impl CoshFunction {
     /// Creates a new instance of the function.
     pub fn new() -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("PI")
+            metadata: CallableMetadataBuilder::new("COSH")
                 .with_return_type(ExprType::Double)
-                .with_syntax(&[(&[], None)])
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
                 .with_category(CATEGORY)
-                .with_description("Returns the Archimedes' constant.")
+                .with_description("Computes the hyperbolic cosine of a number.")
                 .build(),
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for PiFunction {
+impl Callable for CoshFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
-        scope.return_double(std::f64::consts::PI)
-    }
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let n = scope.pop_double();
+        scope.return_double(n.cosh())
+    }
+}
+
+/// The `DEG` command.
+pub struct DegCommand {
+    metadata: CallableMetadata,
+    angle_mode: Rc<RefCell<AngleMode>>,
+}
+
+impl DegCommand {
+    /// Creates a new instance of the command.
+    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DEG")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets degrees mode of calculation.
+The default condition for the trigonometric functions is to use radians.  DEG configures the \
+environment to use degrees until instructed otherwise.",
+                )
+                .build(),
+            angle_mode,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DegCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        *self.angle_mode.borrow_mut() = AngleMode::Degrees;
+        Ok(())
+    }
+}
+
+/// The `DEGREES` function.
+pub struct DegreesFunction {
+    metadata: CallableMetadata,
+}
+
+impl DegreesFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DEGREES")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("radians"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Converts an angle from radians to degrees.
+The conversion does not depend on the current angle mode set by DEG or RAD.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DegreesFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let radians = scope.pop_double();
+        scope.return_double(radians.to_degrees())
+    }
+}
+
+/// The `EULER` function.
+pub struct EulerFunction {
+    metadata: CallableMetadata,
+}
+
+impl EulerFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EULER")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description("Returns Euler's number, the base of natural logarithms.")
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for EulerFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_double(std::f64::consts::E)
+    }
+}
+
+/// The `FIX` function.
+pub struct FixFunction {
+    metadata: CallableMetadata,
+}
+
+impl FixFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FIX")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("expr"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Casts the given numeric expression to an integer (truncating toward zero).
+When casting a double value to an integer, the fractional part of the double value is dropped.  \
+For example, 4.6 becomes 4 and -4.6 becomes -4, whereas INT% returns -5 for the latter.
+It is an error for the truncated value to not fit in an integer.  Boolean values are not accepted.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for FixFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (value, pos) = scope.pop_double_with_pos();
+
+        let i =
+            double_to_integer(value.trunc()).map_err(|e| Error::SyntaxError(pos, e.to_string()))?;
+        scope.return_integer(i)
+    }
+}
+
+/// The `FLOOR` function.
+pub struct FloorFunction {
+    metadata: CallableMetadata,
+}
+
+impl FloorFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FLOOR")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("expr"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Rounds a number down to the closest integral value.
+The result is a double.  For example, both 4.1 and 4.9 become 4, and both -4.1 and -4.9 become -5.
+Unlike INT%, FLOOR# does not overflow on numbers that do not fit in an integer.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for FloorFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let value = scope.pop_double();
+        scope.return_double(value.floor())
+    }
+}
+
+/// The `INT` function.
+pub struct IntFunction {
+    metadata: CallableMetadata,
+}
+
+impl IntFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("INT")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("expr"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Casts the given numeric expression to an integer (with truncation).
+When casting a double value to an integer, the double value is first truncated to the smallest
+integer that is not larger than the double value.  For example, all of 4.4, 4.5 and 4.6 become 4.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for IntFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (value, pos) = scope.pop_double_with_pos();
+
+        let i =
+            double_to_integer(value.floor()).map_err(|e| Error::SyntaxError(pos, e.to_string()))?;
+        scope.return_integer(i)
+    }
+}
+
+/// The `LOG10` function.
+pub struct Log10Function {
+    metadata: CallableMetadata,
+}
+
+impl Log10Function {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("LOG10")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Computes the base-10 logarithm of a number.
+n# must be positive.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for Log10Function {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, npos) = scope.pop_double_with_pos();
+
+        if n <= 0.0 {
+            return Err(Error::SyntaxError(
+                npos,
+                "Cannot take logarithm of a non-positive number".to_owned(),
+            ));
+        }
+        scope.return_double(n.log10())
+    }
+}
+
+/// The `LOG2` function.
+pub struct Log2Function {
+    metadata: CallableMetadata,
+}
+
+impl Log2Function {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("LOG2")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Computes the base-2 logarithm of a number.
+n# must be positive.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for Log2Function {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, npos) = scope.pop_double_with_pos();
+
+        if n <= 0.0 {
+            return Err(Error::SyntaxError(
+                npos,
+                "Cannot take logarithm of a non-positive number".to_owned(),
+            ));
+        }
+        scope.return_double(n.log2())
+    }
+}
+
+/// The `MAX` function.
+pub struct MaxFunction {
+    metadata: CallableMetadata,
+}
+
+impl MaxFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MAX")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::NumericValue(
+                        NumericValueSyntax { name: Cow::Borrowed("expr0") },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    )],
+                    Some(&RepeatedSyntax {
+                        name: Cow::Borrowed("expr"),
+                        type_syn: RepeatedTypeSyntax::NumericValue,
+                        sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                        require_one: true,
+                        allow_missing: false,
+                    }),
+                )])
+                .with_numeric_promotion()
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the maximum number out of a set of numbers.
+Requires at least two arguments.  The result is an integer if all arguments are integers, or a \
+double otherwise.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MaxFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!(scope.nargs() >= 4);
+        let mut max = f64::MIN;
+        let mut any_double = false;
+        while scope.nargs() > 0 {
+            let (n, is_double, _) = pop_number_with_pos(&mut scope);
+            if n > max {
+                max = n;
+            }
+            any_double |= is_double;
+        }
+        return_number(scope, max, any_double)
+    }
+}
+
+/// The `MIN` function.
+pub struct MinFunction {
+    metadata: CallableMetadata,
+}
+
+impl MinFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MIN")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::NumericValue(
+                        NumericValueSyntax { name: Cow::Borrowed("expr0") },
+                        ArgSepSyntax::Exactly(ArgSep::Long),
+                    )],
+                    Some(&RepeatedSyntax {
+                        name: Cow::Borrowed("expr"),
+                        type_syn: RepeatedTypeSyntax::NumericValue,
+                        sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                        require_one: true,
+                        allow_missing: false,
+                    }),
+                )])
+                .with_numeric_promotion()
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the minimum number out of a set of numbers.
+Requires at least two arguments.  The result is an integer if all arguments are integers, or a \
+double otherwise.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MinFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!(scope.nargs() >= 4);
+        let mut min = f64::MAX;
+        let mut any_double = false;
+        while scope.nargs() > 0 {
+            let (n, is_double, _) = pop_number_with_pos(&mut scope);
+            if n < min {
+                min = n;
+            }
+            any_double |= is_double;
+        }
+        return_number(scope, min, any_double)
+    }
+}
+
+/// The `PI` function.
+pub struct PiFunction {
+    metadata: CallableMetadata,
+}
+
+impl PiFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PI")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description("Returns the Archimedes' constant.")
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PiFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_double(std::f64::consts::PI)
+    }
+}
+
+/// The `RAD` command.
+pub struct RadCommand {
+    metadata: CallableMetadata,
+    angle_mode: Rc<RefCell<AngleMode>>,
+}
+
+impl RadCommand {
+    /// Creates a new instance of the command.
+    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RAD")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sets radians mode of calculation.
+The default condition for the trigonometric functions is to use radians but it can be set to \
+degrees with the DEG command.  RAD restores the environment to use radians mode.",
+                )
+                .build(),
+            angle_mode,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RadCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        *self.angle_mode.borrow_mut() = AngleMode::Radians;
+        Ok(())
+    }
+}
+
+/// The `RADIANS` function.
+pub struct RadiansFunction {
+    metadata: CallableMetadata,
+}
+
+impl RadiansFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RADIANS")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("degrees"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Converts an angle from degrees to radians.
+The conversion does not depend on the current angle mode set by DEG or RAD.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RadiansFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let degrees = scope.pop_double();
+        scope.return_double(degrees.to_radians())
+    }
+}
+
+/// The `RANDOMIZE` command.
+pub struct RandomizeCommand {
+    metadata: CallableMetadata,
+    prng: Rc<RefCell<Prng>>,
+}
+
+impl RandomizeCommand {
+    /// Creates a new command that updates `code` with the exit code once called.
+    pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RANDOMIZE")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("seed"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Reinitializes the pseudo-random number generator.
+If no seed is given, uses system entropy to create a new sequence of random numbers.
+WARNING: These random numbers offer no cryptographic guarantees.",
+                )
+                .build(),
+            prng,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RandomizeCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        if scope.nargs() == 0 {
+            *self.prng.borrow_mut() = Prng::new_from_entryopy();
+        } else {
+            debug_assert_eq!(1, scope.nargs());
+            let n = scope.pop_integer();
+            *self.prng.borrow_mut() = Prng::new_from_seed(n);
+        }
+        Ok(())
+    }
+}
+
+/// The `RND` function.
+pub struct RndFunction {
+    metadata: CallableMetadata,
+    prng: Rc<RefCell<Prng>>,
+}
+
+impl RndFunction {
+    /// Creates a new instance of the function.
+    pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RND")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("n"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns a random number in the [0..1] range.
+If n% is zero, returns the previously generated random number.  If n% is positive or is not \
+specified, returns a new random number.
+If you need to generate an integer random number within a specific range, use RNDINT% instead.
+WARNING: These random numbers offer no cryptographic guarantees.",
+                )
+                .build(),
+            prng,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RndFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        if scope.nargs() == 0 {
+            scope.return_double(self.prng.borrow_mut().next())
+        } else {
+            debug_assert_eq!(1, scope.nargs());
+            let (n, npos) = scope.pop_integer_with_pos();
+            match n.cmp(&0) {
+                Ordering::Equal => scope.return_double(self.prng.borrow_mut().last()),
+                Ordering::Greater => scope.return_double(self.prng.borrow_mut().next()),
+                Ordering::Less => Err(Error::SyntaxError(npos, "n% cannot be negative".to_owned())),
+            }
+        }
+    }
+}
+
+/// The `RNDCHOICE` function.
+pub struct RndchoiceFunction {
+    metadata: CallableMetadata,
+    prng: Rc<RefCell<Prng>>,
+}
+
+impl RndchoiceFunction {
+    /// Creates a new instance of the function.
+    pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RNDCHOICE")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredRef(
+                        RequiredRefSyntax {
+                            name: Cow::Borrowed("weights"),
+                            require_array: true,
+                            define_undefined: false,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns a random index into an array of weights.
+The weights must be a one-dimensional numeric array, and the probability of returning each \
+index is proportional to the weight stored at that index.  Weights cannot be negative and at \
+least one of them must be positive.
+WARNING: These random numbers offer no cryptographic guarantees.",
+                )
+                .build(),
+            prng,
+        })
+    }
 }
 
-/// The `RAD` command.
-pub struct RadCommand {
+#[async_trait(?Send)]
+impl Callable for RndchoiceFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (arrayname, arraytype, arraypos) = scope.pop_varref_with_pos();
+
+        let arrayref = VarRef::new(arrayname.to_string(), Some(arraytype));
+        let array = match machine
+            .get_symbols()
+            .get(&arrayref)
+            .map_err(|e| Error::SyntaxError(arraypos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!(),
+        };
+
+        if !matches!(array.subtype(), ExprType::Double | ExprType::Integer) {
+            return Err(Error::SyntaxError(
+                arraypos,
+                format!("{} is not a numeric array", arrayname),
+            ));
+        }
+        if array.dimensions().len() != 1 {
+            return Err(Error::SyntaxError(
+                arraypos,
+                format!("{} must be a one-dimensional array", arrayname),
+            ));
+        }
+
+        let weights = match array.as_doubles() {
+            Some(values) => values.to_vec(),
+            None => {
+                let values = array.as_integers().expect("Array subtype was checked to be numeric");
+                values.iter().map(|i| f64::from(*i)).collect::<Vec<f64>>()
+            }
+        };
+        if let Some(i) = weights.iter().position(|w| *w < 0.0 || w.is_nan()) {
+            return Err(Error::SyntaxError(
+                arraypos,
+                format!("Weight at index {} cannot be negative", i),
+            ));
+        }
+        if !weights.iter().any(|w| *w > 0.0) {
+            return Err(Error::SyntaxError(
+                arraypos,
+                "At least one weight must be positive".to_owned(),
+            ));
+        }
+
+        let i = self.prng.borrow_mut().next_weighted(&weights);
+        scope.return_integer(i as i32)
+    }
+}
+
+/// The `RNDEXP` function.
+pub struct RndexpFunction {
     metadata: CallableMetadata,
-    angle_mode: Rc<RefCell<AngleMode>>,
+    prng: Rc<RefCell<Prng>>,
 }
 
-impl RadCommand {
-    /// Creates a new instance of the command.
-    pub fn new(angle_mode: Rc<RefCell<AngleMode>>) -> Rc<Self> {
+impl RndexpFunction {
+    /// Creates a new instance of the function.
+    pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RAD")
-                .with_syntax(&[(&[], None)])
+            metadata: CallableMetadataBuilder::new("RNDEXP")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("lambda"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Sets radians mode of calculation.
-The default condition for the trigonometric functions is to use radians but it can be set to \
-degrees with the DEG command.  RAD restores the environment to use radians mode.",
+                    "Returns a random number from an exponential distribution.
+lambda# is the rate of the distribution and must be positive.  The mean of the returned numbers \
+is 1 / lambda#.
+WARNING: These random numbers offer no cryptographic guarantees.",
                 )
                 .build(),
-            angle_mode,
+            prng,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for RadCommand {
+impl Callable for RndexpFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
-        *self.angle_mode.borrow_mut() = AngleMode::Radians;
-        Ok(())
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (lambda, lambdapos) = scope.pop_double_with_pos();
+        if lambda <= 0.0 || lambda.is_nan() {
+            return Err(Error::SyntaxError(lambdapos, "lambda# must be positive".to_owned()));
+        }
+        let n = self.prng.borrow_mut().next_exponential(lambda);
+        scope.return_double(n)
     }
 }
 
-/// The `RANDOMIZE` command.
-pub struct RandomizeCommand {
+/// The `RNDINT` function.
+pub struct RndintFunction {
     metadata: CallableMetadata,
     prng: Rc<RefCell<Prng>>,
 }
 
-impl RandomizeCommand {
-    /// Creates a new command that updates `code` with the exit code once called.
+impl RndintFunction {
+    /// Creates a new instance of the function.
     pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RANDOMIZE")
-                .with_syntax(&[
-                    (&[], None),
-                    (
-                        &[SingularArgSyntax::RequiredValue(
+            metadata: CallableMetadataBuilder::new("RNDINT")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
                             RequiredValueSyntax {
-                                name: Cow::Borrowed("seed"),
+                                name: Cow::Borrowed("lo"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("hi"),
                                 vtype: ExprType::Integer,
                             },
                             ArgSepSyntax::End,
-                        )],
-                        None,
-                    ),
-                ])
+                        ),
+                    ],
+                    None,
+                )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Reinitializes the pseudo-random number generator.
-If no seed is given, uses system entropy to create a new sequence of random numbers.
+                    "Returns a random integer in the [lo%..hi%] range.
+All integers in the range, including both bounds, are equally likely.  It is an error for lo% \
+to be greater than hi%.
 WARNING: These random numbers offer no cryptographic guarantees.",
                 )
                 .build(),
@@ -522,81 +1569,253 @@ WARNING: These random numbers offer no cryptographic guarantees.",
 }
 
 #[async_trait(?Send)]
-impl Callable for RandomizeCommand {
+impl Callable for RndintFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        if scope.nargs() == 0 {
-            *self.prng.borrow_mut() = Prng::new_from_entryopy();
-        } else {
-            debug_assert_eq!(1, scope.nargs());
-            let n = scope.pop_integer();
-            *self.prng.borrow_mut() = Prng::new_from_seed(n);
+        debug_assert_eq!(2, scope.nargs());
+        let (lo, lopos) = scope.pop_integer_with_pos();
+        let hi = scope.pop_integer();
+        if lo > hi {
+            return Err(Error::SyntaxError(
+                lopos,
+                "lo% must be less than or equal to hi%".to_owned(),
+            ));
         }
-        Ok(())
+        let n = self.prng.borrow_mut().next_in_range(lo, hi);
+        scope.return_integer(n)
     }
 }
 
-/// The `RND` function.
-pub struct RndFunction {
+/// The `RNDNORM` function.
+pub struct RndnormFunction {
     metadata: CallableMetadata,
     prng: Rc<RefCell<Prng>>,
 }
 
-impl RndFunction {
+impl RndnormFunction {
     /// Creates a new instance of the function.
     pub fn new(prng: Rc<RefCell<Prng>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RND")
+            metadata: CallableMetadataBuilder::new("RNDNORM")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("mean"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("stddev"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns a random number from a normal distribution.
+The distribution is centered at mean# and has a standard deviation of stddev#, which cannot be \
+negative.
+WARNING: These random numbers offer no cryptographic guarantees.",
+                )
+                .build(),
+            prng,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RndnormFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let mean = scope.pop_double();
+        let (stddev, stddevpos) = scope.pop_double_with_pos();
+        if stddev < 0.0 || stddev.is_nan() {
+            return Err(Error::SyntaxError(stddevpos, "stddev# cannot be negative".to_owned()));
+        }
+        let n = self.prng.borrow_mut().next_normal(mean, stddev);
+        scope.return_double(n)
+    }
+}
+
+/// Rounds `value` to the given number of decimal `digits`, with ties rounding away from zero.
+///
+/// Negative `digits` round to the left of the decimal point.  The value is scaled by a power of ten
+/// so that rounding happens at the units, and the result of the scaling is then corrected for the
+/// error introduced by the binary representation of doubles: a scaled value that is within one ULP
+/// of a tie is treated as a tie.  This makes, for example, 1.005 round to 1.01 even though its
+/// closest double is slightly smaller than 1.005.
+fn round_to_digits(value: f64, digits: i32) -> f64 {
+    if digits == 0 {
+        return value.round();
+    } else if digits > 0 && value.fract() == 0.0 {
+        // Also covers values too large to have a fractional part, which could overflow when scaled.
+        return value;
+    }
+
+    let factor = 10f64.powi(digits.abs());
+    let scaled = if digits > 0 { value * factor } else { value / factor };
+    if !scaled.is_finite() {
+        return value;
+    }
+
+    let mut rounded = scaled.round();
+    let ulp = f64::from_bits(scaled.abs().to_bits() + 1) - scaled.abs();
+    if ((rounded - scaled).abs() - 0.5).abs() <= ulp {
+        rounded = scaled.trunc() + scaled.signum();
+    }
+
+    if digits > 0 {
+        rounded / factor
+    } else {
+        rounded * factor
+    }
+}
+
+/// The `ROUND` function.
+pub struct RoundFunction {
+    metadata: CallableMetadata,
+}
+
+impl RoundFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("ROUND")
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[
-                    (&[], None),
                     (
                         &[SingularArgSyntax::RequiredValue(
                             RequiredValueSyntax {
-                                name: Cow::Borrowed("n"),
-                                vtype: ExprType::Integer,
+                                name: Cow::Borrowed("expr"),
+                                vtype: ExprType::Double,
                             },
                             ArgSepSyntax::End,
                         )],
                         None,
                     ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("expr"),
+                                    vtype: ExprType::Double,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("digits"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
                 ])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Returns a random number in the [0..1] range.
-If n% is zero, returns the previously generated random number.  If n% is positive or is not \
-specified, returns a new random number.
-If you need to generate an integer random number within a specific range, say [0..100], compute it \
-with an expression like CINT%(RND#(1) * 100.0).
-WARNING: These random numbers offer no cryptographic guarantees.",
+                    "Rounds a number to the given number of decimal places.
+digits% indicates the number of decimal places to keep and defaults to 0.  Negative values round \
+to the left of the decimal point: for example, a digits% of -2 rounds to the closest hundred.
+Ties round away from zero, so 2.5 becomes 3 and -2.5 becomes -3.
+Rounding scales the number by a power of ten, rounds it to an integral value, and scales it \
+back.  Because doubles cannot represent most decimal fractions exactly, the scaled number is \
+corrected so that values that are within a tiny error of a tie are treated as ties.  This \
+makes ROUND(1.005, 2) return 1.01 even though 1.005 is stored as a slightly smaller number.
+The result is a double.  Use CINT% to round to an integer instead.",
                 )
                 .build(),
-            prng,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for RndFunction {
+impl Callable for RoundFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        if scope.nargs() == 0 {
-            scope.return_double(self.prng.borrow_mut().next())
-        } else {
-            debug_assert_eq!(1, scope.nargs());
-            let (n, npos) = scope.pop_integer_with_pos();
-            match n.cmp(&0) {
-                Ordering::Equal => scope.return_double(self.prng.borrow_mut().last()),
-                Ordering::Greater => scope.return_double(self.prng.borrow_mut().next()),
-                Ordering::Less => Err(Error::SyntaxError(npos, "n% cannot be negative".to_owned())),
+        debug_assert!((1..=2).contains(&scope.nargs()));
+        let value = scope.pop_double();
+        let digits = if scope.nargs() > 0 {
+            let (digits, digitspos) = scope.pop_integer_with_pos();
+            if !(-308..=308).contains(&digits) {
+                return Err(Error::SyntaxError(
+                    digitspos,
+                    "digits% must be between -308 and 308".to_owned(),
+                ));
             }
-        }
+            digits
+        } else {
+            0
+        };
+        debug_assert_eq!(0, scope.nargs());
+
+        scope.return_double(round_to_digits(value, digits))
+    }
+}
+
+/// The `SGN` function.
+pub struct SgnFunction {
+    metadata: CallableMetadata,
+}
+
+impl SgnFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SGN")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("x"), vtype: ExprType::Double },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the sign of a number.
+Returns -1 if x# is negative, 1 if x# is positive, and 0 if x# is zero.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SgnFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let x = scope.pop_double();
+        let sign = if x > 0.0 {
+            1
+        } else if x < 0.0 {
+            -1
+        } else {
+            0
+        };
+        scope.return_integer(sign)
     }
 }
 
@@ -614,35 +1833,73 @@ impl SinFunction {
                 .with_return_type(ExprType::Double)
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax {
-                            name: Cow::Borrowed("angle"),
-                            vtype: ExprType::Double,
-                        },
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("angle"),
+                            vtype: ExprType::Double,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Computes the sine of an angle.
+The input angle% or angle# is measured in degrees or radians depending on the angle mode as \
+selected by the DEG and RAD commands.",
+                )
+                .build(),
+            angle_mode,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SinFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        let angle = get_angle(&mut scope, &self.angle_mode.borrow()).await?;
+        scope.return_double(angle.sin())
+    }
+}
+
+/// The `SINH` function.
+pub struct SinhFunction {
+    metadata: CallableMetadata,
+}
+
+impl SinhFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SINH")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
                         ArgSepSyntax::End,
                     )],
                     None,
                 )])
                 .with_category(CATEGORY)
-                .with_description(
-                    "Computes the sine of an angle.
-The input angle% or angle# is measured in degrees or radians depending on the angle mode as \
-selected by the DEG and RAD commands.",
-                )
+                .with_description("Computes the hyperbolic sine of a number.")
                 .build(),
-            angle_mode,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for SinFunction {
+impl Callable for SinhFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        let angle = get_angle(&mut scope, &self.angle_mode.borrow()).await?;
-        scope.return_double(angle.sin())
+        debug_assert_eq!(1, scope.nargs());
+        let n = scope.pop_double();
+        scope.return_double(n.sinh())
     }
 }
 
@@ -737,30 +1994,163 @@ impl Callable for TanFunction {
     }
 }
 
-/// Adds all symbols provided by this module to the given `machine`.
-pub fn add_all(machine: &mut Machine) {
+/// The `TANH` function.
+pub struct TanhFunction {
+    metadata: CallableMetadata,
+}
+
+impl TanhFunction {
+    /// Creates a new instance of the function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("TANH")
+                .with_return_type(ExprType::Double)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("n"), vtype: ExprType::Double },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description("Computes the hyperbolic tangent of a number.")
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for TanhFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let n = scope.pop_double();
+        scope.return_double(n.tanh())
+    }
+}
+
+/// Same as `add_all` but starts the random number generator with `prng`.
+pub(crate) fn add_all_with_prng(machine: &mut Machine, prng: Prng) {
     let angle_mode = Rc::from(RefCell::from(AngleMode::Radians));
-    let prng = Rc::from(RefCell::from(Prng::new_from_entryopy()));
+    let prng = Rc::from(RefCell::from(prng));
     machine.add_clearable(Box::from(ClearableAngleMode { angle_mode: angle_mode.clone() }));
+    machine.add_clearable(Box::from(ClearablePrng { prng: prng.clone() }));
+    machine.add_callable(Atan2Function::new(angle_mode.clone()));
     machine.add_callable(AtnFunction::new(angle_mode.clone()));
+    machine.add_callable(CdblFunction::new());
+    machine.add_callable(CeilFunction::new());
     machine.add_callable(CintFunction::new());
+    machine.add_callable(ClampFunction::new());
     machine.add_callable(CosFunction::new(angle_mode.clone()));
+    machine.add_callable(CoshFunction::new());
     machine.add_callable(DegCommand::new(angle_mode.clone()));
+    machine.add_callable(DegreesFunction::new());
+    machine.add_callable(EulerFunction::new());
+    machine.add_callable(FixFunction::new());
+    machine.add_callable(FloorFunction::new());
     machine.add_callable(IntFunction::new());
+    machine.add_callable(Log10Function::new());
+    machine.add_callable(Log2Function::new());
     machine.add_callable(MaxFunction::new());
     machine.add_callable(MinFunction::new());
     machine.add_callable(PiFunction::new());
     machine.add_callable(RadCommand::new(angle_mode.clone()));
+    machine.add_callable(RadiansFunction::new());
     machine.add_callable(RandomizeCommand::new(prng.clone()));
-    machine.add_callable(RndFunction::new(prng));
+    machine.add_callable(RndFunction::new(prng.clone()));
+    machine.add_callable(RndchoiceFunction::new(prng.clone()));
+    machine.add_callable(RndexpFunction::new(prng.clone()));
+    machine.add_callable(RndintFunction::new(prng.clone()));
+    machine.add_callable(RndnormFunction::new(prng));
+    machine.add_callable(RoundFunction::new());
+    machine.add_callable(SgnFunction::new());
     machine.add_callable(SinFunction::new(angle_mode.clone()));
+    machine.add_callable(SinhFunction::new());
     machine.add_callable(SqrFunction::new());
     machine.add_callable(TanFunction::new(angle_mode));
+    machine.add_callable(TanhFunction::new());
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+pub fn add_all(machine: &mut Machine) {
+    add_all_with_prng(machine, Prng::new_from_entryopy())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::testutils::*;
+    use endbasic_core::ast::Value;
+    use endbasic_core::exec::StopReason;
+    use futures_lite::future::block_on;
+    use std::f64::consts::{E, FRAC_PI_2, FRAC_PI_4, PI};
+
+    /// Evaluates `expr` and checks that the result is a double with the exact same bit pattern as
+    /// `exp_value`.
+    fn check_expr_bits(exp_value: f64, expr: &str) {
+        let mut t = Tester::default();
+        let result = block_on(t.get_machine().exec(&mut format!("result = {}", expr).as_bytes()));
+        assert_eq!(StopReason::Eof, result.expect("Execution failed"));
+        match t.get_machine().get_symbols().get_auto("result") {
+            Some(Symbol::Variable(Value::Double(d))) => {
+                assert_eq!(exp_value.to_bits(), d.to_bits())
+            }
+            s => panic!("Expected a double result but got {:?}", s),
+        }
+    }
+
+    #[test]
+    fn test_atan2_axes() {
+        check_expr_ok(0.0, "ATAN2(0, 0)");
+        check_expr_ok(0.0, "ATAN2(0, 1)");
+        check_expr_ok(FRAC_PI_2, "ATAN2(1, 0)");
+        check_expr_ok(-FRAC_PI_2, "ATAN2(-1, 0)");
+        check_expr_ok(PI, "ATAN2(0, -1)");
+        check_expr_ok(PI, "ATAN2(0.0, -3.5)");
+    }
+
+    #[test]
+    fn test_atan2_signed_zeros() {
+        check_expr_ok(PI, "ATAN2(0.0, -0.0)");
+        check_expr_ok(-PI, "ATAN2(-0.0, -1.0)");
+        check_expr_ok(0.0, "ATAN2(-0.0, 1.0)");
+        check_expr_ok(0.0, "ATAN2(0.0, 0.0)");
+    }
+
+    #[test]
+    fn test_atan2_quadrants() {
+        check_expr_approx(FRAC_PI_4, "ATAN2(1, 1)");
+        check_expr_approx(3.0 * FRAC_PI_4, "ATAN2(1, -1)");
+        check_expr_approx(-3.0 * FRAC_PI_4, "ATAN2(-1, -1)");
+        check_expr_approx(-FRAC_PI_4, "ATAN2(-1, 1)");
+        check_expr_approx(0.4636476090008061, "ATAN2(1.5, 3)");
+
+        check_expr_ok_with_vars(
+            FRAC_PI_4,
+            "ATAN2(y, x)",
+            [("y", 2.5f64.into()), ("x", 2.5f64.into())],
+        );
+    }
+
+    #[test]
+    fn test_atan2_degrees() {
+        let mut t = Tester::default();
+        t.run("DEG: result = ATAN2(1, 0)").expect_var("result", 90.0).check();
+        t.run("result = ATAN2(0, -1)").expect_var("result", 180.0).check();
+        t.run("RAD: result = ATAN2(1, 0)").expect_var("result", FRAC_PI_2).check();
+    }
+
+    #[test]
+    fn test_atan2_errors() {
+        check_expr_compilation_error("1:10: ATAN2 expected y#, x#", "ATAN2()");
+        check_expr_compilation_error("1:10: ATAN2 expected y#, x#", "ATAN2(1)");
+        check_expr_compilation_error("1:10: ATAN2 expected y#, x#", "ATAN2(1, 2, 3)");
+        check_expr_compilation_error("1:16: BOOLEAN is not a number", "ATAN2(FALSE, 1)");
+        check_expr_compilation_error("1:19: STRING is not a number", "ATAN2(1, \"a\")");
+    }
 
     #[test]
     fn test_atn() {
@@ -774,6 +2164,36 @@ mod tests {
         check_expr_compilation_error("1:10: ATN expected n#", "ATN(3, 4)");
     }
 
+    #[test]
+    fn test_ceil() {
+        check_expr_ok(5.0, "CEIL(4.1)");
+        check_expr_ok(5.0, "CEIL(4.9)");
+        check_expr_ok(4.0, "CEIL(4)");
+        check_expr_ok(-4.0, "CEIL(-4.1)");
+        check_expr_ok(-4.0, "CEIL(-4.9)");
+        check_expr_ok(0.0, "CEIL(-0.5)");
+        check_expr_ok_with_vars(1e20, "CEIL(d)", [("d", 1e20f64.into())]);
+
+        check_expr_ok_with_vars(1.0, "CEIL(d)", [("d", 0.2f64.into())]);
+
+        check_expr_compilation_error("1:10: CEIL expected expr#", "CEIL()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "CEIL(FALSE)");
+        check_expr_compilation_error("1:10: CEIL expected expr#", "CEIL(3.0, 4)");
+    }
+
+    #[test]
+    fn test_cdbl() {
+        check_expr_ok(0.0, "CDBL(0)");
+        check_expr_ok(-5.0, "CDBL(-5)");
+        check_expr_ok(2147483647.0, "CDBL(2147483647)");
+        check_expr_ok(1.5, "CDBL(1.5)");
+
+        check_expr_ok_with_vars(-2147483648.0, "CDBL(i)", [("i", i32::MIN.into())]);
+
+        check_expr_compilation_error("1:10: CDBL expected expr#", "CDBL()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "CDBL(TRUE)");
+    }
+
     #[test]
     fn test_cint() {
         check_expr_ok(0, "CINT(0.1)");
@@ -781,6 +2201,12 @@ mod tests {
         check_expr_ok(1, "CINT(0.9)");
         check_expr_ok(-1, "CINT(-0.9)");
 
+        check_expr_ok(0, "CINT(0.5)");
+        check_expr_ok(2, "CINT(1.5)");
+        check_expr_ok(2, "CINT(2.5)");
+        check_expr_ok(-2, "CINT(-2.5)");
+        check_expr_ok(-4, "CINT(-3.5)");
+
         check_expr_ok_with_vars(1, "CINT(d)", [("d", 0.9f64.into())]);
 
         check_expr_compilation_error("1:10: CINT expected expr#", "CINT()");
@@ -793,6 +2219,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_cint_overflow() {
+        check_expr_ok(i32::MAX, "CINT(2147483647.4)");
+        check_expr_ok(i32::MIN, "CINT(-2147483648.5)");
+        check_expr_error(
+            "1:15: Cannot cast 2147483648 to integer due to overflow",
+            "CINT(2147483647.5)",
+        );
+        check_expr_error(
+            "1:15: Cannot cast -2147483650 to integer due to overflow",
+            "CINT(-2147483649.5)",
+        );
+    }
+
+    #[test]
+    fn test_cint_fix_int_rounding() {
+        for (value, cint, fix, int) in [
+            ("2.5", 2, 2, 2),
+            ("3.5", 4, 3, 3),
+            ("2.7", 3, 2, 2),
+            ("-2.5", -2, -2, -3),
+            ("-3.5", -4, -3, -4),
+            ("-2.7", -3, -2, -3),
+            ("-0.2", 0, 0, -1),
+        ] {
+            check_expr_ok(cint, &format!("CINT({})", value));
+            check_expr_ok(fix, &format!("FIX({})", value));
+            check_expr_ok(int, &format!("INT({})", value));
+        }
+    }
+
+    #[test]
+    fn test_clamp() {
+        check_expr_ok(5, "CLAMP(5, 1, 10)");
+        check_expr_ok(1, "CLAMP(-3, 1, 10)");
+        check_expr_ok(10, "CLAMP(12, 1, 10)");
+        check_expr_ok(1, "CLAMP(1, 1, 10)");
+        check_expr_ok(10, "CLAMP(10, 1, 10)");
+        check_expr_ok(7, "CLAMP(7, 7, 7)");
+
+        check_expr_ok(5.5, "CLAMP(5.5, 1, 10)");
+        check_expr_ok(1.0, "CLAMP(-3, 1.0, 10)");
+        check_expr_ok(10.0, "CLAMP(12, 1, 10.0)");
+        check_expr_ok(0.5, "CLAMP(0.25, 0.5, 0.75)");
+
+        check_expr_ok_with_vars(
+            4,
+            "CLAMP(i, j, k)",
+            [("i", 2i32.into()), ("j", 4i32.into()), ("k", 6i32.into())],
+        );
+    }
+
+    #[test]
+    fn test_clamp_errors() {
+        check_expr_compilation_error("1:10: CLAMP expected value, lo, hi", "CLAMP()");
+        check_expr_compilation_error("1:10: CLAMP expected value, lo, hi", "CLAMP(1, 2)");
+        check_expr_compilation_error("1:10: CLAMP expected value, lo, hi", "CLAMP(1, 2, 3, 4)");
+        check_expr_compilation_error("1:19: BOOLEAN is not a number", "CLAMP(1, TRUE, 3)");
+        check_expr_error("1:19: lo must be less than or equal to hi", "CLAMP(5, 10, 1)");
+        check_expr_error("1:19: lo must be less than or equal to hi", "CLAMP(5, 1.5, 1)");
+    }
+
     #[test]
     fn test_cos() {
         check_expr_ok(123f64.cos(), "COS(123)");
@@ -805,6 +2293,18 @@ mod tests {
         check_expr_compilation_error("1:10: COS expected angle#", "COS(3, 4)");
     }
 
+    #[test]
+    fn test_cosh() {
+        check_expr_ok(1.0, "COSH(0)");
+        check_expr_approx(1.5430806348152437, "COSH(1)");
+        check_expr_approx(1.5430806348152437, "COSH(-1)");
+        check_expr_approx(3.7621956910836314, "COSH(2.0)");
+
+        check_expr_compilation_error("1:10: COSH expected n#", "COSH()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "COSH(FALSE)");
+        check_expr_compilation_error("1:10: COSH expected n#", "COSH(3, 4)");
+    }
+
     #[test]
     fn test_deg_rad_commands() {
         let mut t = Tester::default();
@@ -827,6 +2327,103 @@ mod tests {
         check_stmt_compilation_err("1:1: RAD expected no arguments", "RAD 1");
     }
 
+    #[test]
+    fn test_degrees() {
+        check_expr_ok(0.0, "DEGREES(0)");
+        check_expr_ok(180.0, "DEGREES(PI)");
+        check_expr_ok(-90.0, "DEGREES(-PI / 2)");
+        check_expr_bits(PI.to_degrees(), "DEGREES(PI)");
+
+        check_expr_compilation_error("1:10: DEGREES expected radians#", "DEGREES()");
+        check_expr_compilation_error("1:18: BOOLEAN is not a number", "DEGREES(FALSE)");
+    }
+
+    #[test]
+    fn test_degrees_radians_ignore_angle_mode() {
+        Tester::default()
+            .run("DEG: d = DEGREES(PI): r = RADIANS(180)")
+            .expect_var("d", 180.0)
+            .expect_var("r", PI)
+            .check();
+    }
+
+    #[test]
+    fn test_degrees_radians_round_trip() {
+        for angle in
+            [-720.5, -360.0, -180.0, -45.25, -0.001, 0.0, 0.001, 30.0, 90.0, 359.99, 1234.5]
+        {
+            check_expr_approx(angle, &format!("DEGREES(RADIANS({}))", angle));
+        }
+    }
+
+    #[test]
+    fn test_euler() {
+        check_expr_bits(E, "EULER");
+        check_expr_bits(E, "EULER#");
+
+        check_expr_compilation_error("1:10: EULER expected no arguments", "EULER()");
+    }
+
+    #[test]
+    fn test_fix() {
+        check_expr_ok(0, "FIX(0.1)");
+        check_expr_ok(0, "FIX(-0.1)");
+        check_expr_ok(0, "FIX(0.9)");
+        check_expr_ok(0, "FIX(-0.9)");
+        check_expr_ok(7, "FIX(7)");
+
+        check_expr_ok_with_vars(-4, "FIX(d)", [("d", (-4.6f64).into())]);
+
+        check_expr_compilation_error("1:10: FIX expected expr#", "FIX()");
+        check_expr_compilation_error("1:14: BOOLEAN is not a number", "FIX(FALSE)");
+        check_expr_compilation_error("1:10: FIX expected expr#", "FIX(3.0, 4)");
+    }
+
+    #[test]
+    fn test_fix_overflow() {
+        check_expr_ok(i32::MAX, "FIX(2147483647.9)");
+        check_expr_ok(i32::MIN, "FIX(-2147483648.9)");
+        check_expr_error(
+            "1:14: Cannot cast 2147483648 to integer due to overflow",
+            "FIX(2147483648.0)",
+        );
+        check_expr_error(
+            "1:14: Cannot cast -2147483649 to integer due to overflow",
+            "FIX(-2147483649.0)",
+        );
+    }
+
+    #[test]
+    fn test_floor() {
+        check_expr_ok(4.0, "FLOOR(4.1)");
+        check_expr_ok(4.0, "FLOOR(4.9)");
+        check_expr_ok(4.0, "FLOOR(4)");
+        check_expr_ok(-5.0, "FLOOR(-4.1)");
+        check_expr_ok(-5.0, "FLOOR(-4.9)");
+        check_expr_ok(-1.0, "FLOOR(-0.5)");
+        check_expr_ok_with_vars(-1e20, "FLOOR(d)", [("d", (-1e20f64).into())]);
+
+        check_expr_ok_with_vars(0.0, "FLOOR(d)", [("d", 0.2f64.into())]);
+
+        check_expr_compilation_error("1:10: FLOOR expected expr#", "FLOOR()");
+        check_expr_compilation_error("1:16: BOOLEAN is not a number", "FLOOR(FALSE)");
+        check_expr_compilation_error("1:10: FLOOR expected expr#", "FLOOR(3.0, 4)");
+    }
+
+    #[test]
+    fn test_floor_vs_int() {
+        // Both round towards negative infinity, but only INT converts the result to an integer.
+        check_expr_ok(-5.0, "FLOOR(-4.5)");
+        check_expr_ok(-5, "INT(-4.5)");
+        check_expr_ok(-4, "CINT(-4.4)");
+
+        check_expr_ok(-1234567890123456.0, "FLOOR(-1234567890123456.0)");
+        check_expr_error(
+            "1:14: Cannot cast -1234567890123456 to integer due to overflow",
+            "INT(-1234567890123456.0)",
+        );
+    }
+
     #[test]
     fn test_int() {
         check_expr_ok(0, "INT(0.1)");
@@ -847,69 +2444,122 @@ mod tests {
     }
 
     #[test]
-    fn test_max() {
-        check_expr_ok(0.0, "MAX(0)");
-        check_expr_ok(0.0, "MAX(0, 0)");
+    fn test_log10() {
+        check_expr_ok(0.0, "LOG10(1)");
+        check_expr_ok(3.0, "LOG10(1000)");
+        check_expr_ok(-2.0, "LOG10(0.01)");
+        check_expr_approx(0.6989700043360189, "LOG10(5)");
 
-        check_expr_ok(0.0, "MAX(0.0)");
+        check_expr_ok_with_vars(1.0, "LOG10(i)", [("i", 10i32.into())]);
+
+        check_expr_error("1:16: Cannot take logarithm of a non-positive number", "LOG10(0)");
+        check_expr_error("1:16: Cannot take logarithm of a non-positive number", "LOG10(-0.5)");
+
+        check_expr_compilation_error("1:10: LOG10 expected n#", "LOG10()");
+        check_expr_compilation_error("1:16: BOOLEAN is not a number", "LOG10(FALSE)");
+        check_expr_compilation_error("1:10: LOG10 expected n#", "LOG10(3, 4)");
+    }
+
+    #[test]
+    fn test_log2() {
+        check_expr_ok(0.0, "LOG2(1)");
+        check_expr_ok(10.0, "LOG2(1024)");
+        check_expr_ok(-1.0, "LOG2(0.5)");
+        check_expr_approx(1.584962500721156, "LOG2(3)");
+
+        check_expr_ok_with_vars(3.0, "LOG2(i)", [("i", 8i32.into())]);
+
+        check_expr_error("1:15: Cannot take logarithm of a non-positive number", "LOG2(0)");
+        check_expr_error("1:15: Cannot take logarithm of a non-positive number", "LOG2(-8)");
+
+        check_expr_compilation_error("1:10: LOG2 expected n#", "LOG2()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "LOG2(FALSE)");
+        check_expr_compilation_error("1:10: LOG2 expected n#", "LOG2(3, 4)");
+    }
+
+    #[test]
+    fn test_max() {
+        check_expr_ok(0, "MAX(0, 0)");
         check_expr_ok(0.0, "MAX(0.0, 0.0)");
 
-        check_expr_ok(1.0, "MAX(1)");
-        check_expr_ok(5.0, "MAX(5, 3, 4)");
-        check_expr_ok(-3.0, "MAX(-5, -3, -4)");
+        check_expr_ok(5, "MAX(5, 3, 4)");
+        check_expr_ok(-3, "MAX(-5, -3, -4)");
 
-        check_expr_ok(1.0, "MAX(1.0)");
         check_expr_ok(5.3, "MAX(5.3, 3.5, 4.2)");
         check_expr_ok(-3.5, "MAX(-5.3, -3.5, -4.2)");
 
         check_expr_ok(2.5, "MAX(1, 0.5, 2.5, 2)");
+        check_expr_ok(3.0, "MAX(3, 0.5)");
+        check_expr_ok(3.0, "MAX#(3, 2)");
 
         check_expr_ok_with_vars(
-            5.0,
+            5,
             "MAX(i, j, k)",
             [("i", 5i32.into()), ("j", 3i32.into()), ("k", 4i32.into())],
         );
 
-        check_expr_compilation_error("1:10: MAX expected expr1#[, .., exprN#]", "MAX()");
-        check_expr_compilation_error("1:14: BOOLEAN is not a number", "MAX(FALSE)");
+        check_expr_compilation_error("1:10: MAX expected expr0, expr1[, .., exprN]", "MAX()");
+        check_expr_compilation_error("1:10: MAX expected expr0, expr1[, .., exprN]", "MAX(1)");
+        check_expr_compilation_error("1:14: BOOLEAN is not a number", "MAX(FALSE, 1)");
+        check_expr_compilation_error("1:17: STRING is not a number", "MAX(1, \"a\")");
+        check_expr_compilation_error(
+            "1:10: Incompatible type annotation in MAX% reference",
+            "MAX%(1, 2)",
+        );
     }
 
     #[test]
     fn test_min() {
-        check_expr_ok(0.0, "MIN(0)");
-        check_expr_ok(0.0, "MIN(0, 0)");
-
-        check_expr_ok(0.0, "MIN(0.0)");
+        check_expr_ok(0, "MIN(0, 0)");
         check_expr_ok(0.0, "MIN(0.0, 0.0)");
 
-        check_expr_ok(1.0, "MIN(1)");
-        check_expr_ok(3.0, "MIN(5, 3, 4)");
-        check_expr_ok(-5.0, "MIN(-5, -3, -4)");
+        check_expr_ok(3, "MIN(5, 3, 4)");
+        check_expr_ok(-5, "MIN(-5, -3, -4)");
 
-        check_expr_ok(1.0, "MIN(1.0)");
         check_expr_ok(3.5, "MIN(5.3, 3.5, 4.2)");
         check_expr_ok(-5.3, "MIN(-5.3, -3.5, -4.2)");
 
         check_expr_ok(0.5, "MIN(1, 0.5, 2.5, 2)");
+        check_expr_ok(1.0, "MIN(1, 2.5)");
+        check_expr_ok(2.0, "MIN#(3, 2)");
 
         check_expr_ok_with_vars(
-            3.0,
+            3,
             "MIN(i, j, k)",
             [("i", 5i32.into()), ("j", 3i32.into()), ("k", 4i32.into())],
         );
 
-        check_expr_compilation_error("1:10: MIN expected expr1#[, .., exprN#]", "MIN()");
-        check_expr_compilation_error("1:14: BOOLEAN is not a number", "MIN(FALSE)");
+        check_expr_compilation_error("1:10: MIN expected expr0, expr1[, .., exprN]", "MIN()");
+        check_expr_compilation_error("1:10: MIN expected expr0, expr1[, .., exprN]", "MIN(1)");
+        check_expr_compilation_error("1:14: BOOLEAN is not a number", "MIN(FALSE, 1)");
+        check_expr_compilation_error("1:17: STRING is not a number", "MIN(1, \"a\")");
     }
 
     #[test]
     fn test_pi() {
-        check_expr_ok(std::f64::consts::PI, "PI");
+        check_expr_ok(PI, "PI");
 
         check_expr_compilation_error("1:10: PI expected no arguments", "PI()");
         check_expr_compilation_error("1:10: PI expected no arguments", "PI(3)");
     }
 
+    #[test]
+    fn test_pi_bits() {
+        check_expr_bits(PI, "PI");
+        check_expr_bits(PI, "PI#");
+    }
+
+    #[test]
+    fn test_radians() {
+        check_expr_ok(0.0, "RADIANS(0)");
+        check_expr_bits(PI, "RADIANS(180)");
+        check_expr_bits(-FRAC_PI_2, "RADIANS(-90)");
+        check_expr_bits(FRAC_PI_4, "RADIANS(45.0)");
+
+        check_expr_compilation_error("1:10: RADIANS expected degrees#", "RADIANS()");
+        check_expr_compilation_error("1:18: BOOLEAN is not a number", "RADIANS(FALSE)");
+    }
+
     #[test]
     fn test_randomize_and_rnd() {
         // These tests could lead to flakiness if the PRNG happens to yield the same number twice
@@ -939,6 +2589,312 @@ mod tests {
         check_stmt_compilation_err("1:11: BOOLEAN is not a number", "RANDOMIZE TRUE");
     }
 
+    #[test]
+    fn test_rndint_seeded_sequences() {
+        let mut t = Tester::default();
+        t.run("RANDOMIZE 10: a = RNDINT(1, 100): b = RNDINT(1, 100): c = RNDINT(1, 100)")
+            .expect_var("a", 29)
+            .expect_var("b", 45)
+            .expect_var("c", 4)
+            .check();
+
+        let mut t = Tester::default();
+        t.run("RANDOMIZE 10: a = RNDINT(1, 100): b = RNDINT(1, 100): c = RNDINT(1, 100)")
+            .expect_var("a", 29)
+            .expect_var("b", 45)
+            .expect_var("c", 4)
+            .check();
+
+        let mut t = Tester::default();
+        t.run("RANDOMIZE 11: a = RNDINT(1, 100): b = RNDINT(1, 100): c = RNDINT(1, 100)")
+            .expect_var("a", 90)
+            .expect_var("b", 97)
+            .expect_var("c", 46)
+            .check();
+    }
+
+    #[test]
+    fn test_rnd_survives_snapshot() {
+        let mut t = Tester::default();
+        t.run("RANDOMIZE 10: a = RNDINT(1, 100): DEG").expect_var("a", 29).check();
+        let snapshot = t.get_machine().snapshot();
+
+        t.run("b = RNDINT(1, 100): RAD").expect_var("a", 29).expect_var("b", 45).check();
+
+        t.get_machine().restore(snapshot).unwrap();
+        t.run("c = RNDINT(1, 100): d = SIN(90)")
+            .expect_clear()
+            .expect_var("a", 29)
+            .expect_var("c", 45)
+            .expect_var("d", 1.0)
+            .check();
+    }
+
+    #[test]
+    fn test_prng_save_restore() {
+        let mut prng = Prng::new_from_seed(5);
+        prng.next();
+        let state = prng.save();
+        let exp = prng.next();
+
+        let mut restored = Prng::restore(&state).unwrap();
+        assert_eq!(exp, restored.next());
+        assert_eq!(exp, restored.last());
+
+        match Prng::restore(&state[1..]) {
+            Ok(_) => panic!("Truncated state must be rejected"),
+            Err(e) => assert_eq!(io::ErrorKind::InvalidData, e.kind()),
+        }
+    }
+
+    #[test]
+    fn test_rndint_bounds() {
+        check_expr_ok(5, "RNDINT(5, 5)");
+        check_expr_ok(-3, "RNDINT(-3, -3)");
+
+        let mut prng = Prng::new_from_seed(1);
+        for _ in 0..1000 {
+            let n = prng.next_in_range(i32::MIN, i32::MAX);
+            assert!((i32::MIN..=i32::MAX).contains(&n));
+            let n = prng.next_in_range(i32::MAX - 1, i32::MAX);
+            assert!(n == i32::MAX - 1 || n == i32::MAX);
+        }
+    }
+
+    #[test]
+    fn test_rndint_coverage() {
+        let mut prng = Prng::new_from_seed(1);
+        let mut counts = [0; 3];
+        for _ in 0..3000 {
+            let n = prng.next_in_range(-1, 1);
+            counts[(n + 1) as usize] += 1;
+        }
+        for count in counts {
+            assert!((900..=1100).contains(&count), "Biased distribution: {:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_rndint_errors() {
+        check_expr_compilation_error("1:10: RNDINT expected lo%, hi%", "RNDINT(1)");
+        check_expr_compilation_error("1:10: RNDINT expected lo%, hi%", "RNDINT(1, 2, 3)");
+        check_expr_compilation_error("1:20: BOOLEAN is not a number", "RNDINT(1, TRUE)");
+        check_expr_error("1:17: lo% must be less than or equal to hi%", "RNDINT(10, 1)");
+    }
+
+    #[test]
+    fn test_rndchoice_seeded() {
+        const SCRIPT: &str = "DIM w(4) AS DOUBLE: w(0) = 1: w(2) = 3.5
+RANDOMIZE 7: a = RNDCHOICE(w): b = RNDCHOICE(w)";
+        for _ in 0..2 {
+            let mut t = Tester::default();
+            t.run(SCRIPT)
+                .expect_array_simple(
+                    "w",
+                    ExprType::Double,
+                    vec![1.0.into(), 0.0.into(), 3.5.into(), 0.0.into()],
+                )
+                .expect_var("a", 2)
+                .expect_var("b", 2)
+                .check();
+        }
+    }
+
+    #[test]
+    fn test_rndchoice_single_choice() {
+        Tester::default()
+            .run("DIM w(3) AS INTEGER: w(1) = 5: a = RNDCHOICE(w)")
+            .expect_array_simple("w", ExprType::Integer, vec![0.into(), 5.into(), 0.into()])
+            .expect_var("a", 1)
+            .check();
+    }
+
+    #[test]
+    fn test_rndchoice_errors() {
+        check_expr_compilation_error("1:10: RNDCHOICE expected weights", "RNDCHOICE()");
+        check_expr_compilation_error("1:20: Undefined symbol W", "RNDCHOICE(w)");
+
+        Tester::default()
+            .run("DIM w(3): w(1) = -1: a = RNDCHOICE(w)")
+            .expect_err("1:36: Weight at index 1 cannot be negative")
+            .expect_array_simple("w", ExprType::Integer, vec![0.into(), (-1).into(), 0.into()])
+            .check();
+
+        Tester::default()
+            .run("DIM w(3) AS INTEGER: a = RNDCHOICE(w)")
+            .expect_err("1:36: At least one weight must be positive")
+            .expect_array_simple("w", ExprType::Integer, vec![0.into(), 0.into(), 0.into()])
+            .check();
+
+        Tester::default()
+            .run("DIM w(2, 2): a = RNDCHOICE(w)")
+            .expect_err("1:28: W must be a one-dimensional array")
+            .expect_array("w", ExprType::Integer, &[2, 2], vec![])
+            .check();
+
+        Tester::default()
+            .run("DIM w(2) AS STRING: a = RNDCHOICE(w)")
+            .expect_err("1:35: W is not a numeric array")
+            .expect_array_simple("w", ExprType::Text, vec!["".into(), "".into()])
+            .check();
+    }
+
+    #[test]
+    fn test_rndchoice_proportions() {
+        let mut prng = Prng::new_from_seed(1);
+        let mut counts = [0; 3];
+        for _ in 0..10000 {
+            counts[prng.next_weighted(&[1.0, 0.0, 3.0])] += 1;
+        }
+        assert_eq!(0, counts[1]);
+        assert!((2300..=2700).contains(&counts[0]), "Biased distribution: {:?}", counts);
+        assert!((7300..=7700).contains(&counts[2]), "Biased distribution: {:?}", counts);
+    }
+
+    #[test]
+    fn test_rndexp_seeded() {
+        for _ in 0..2 {
+            let mut t = Tester::default();
+            t.run("RANDOMIZE 7: a = RNDEXP(0.5): b = RNDEXP(2)")
+                .expect_var("a", 0.7536226905495499)
+                .expect_var("b", 0.29328290027657555)
+                .check();
+        }
+    }
+
+    #[test]
+    fn test_rndexp_mean() {
+        let mut prng = Prng::new_from_seed(1);
+        let mut sum = 0.0;
+        for _ in 0..10000 {
+            let n = prng.next_exponential(0.5);
+            assert!(n >= 0.0);
+            sum += n;
+        }
+        let mean = sum / 10000.0;
+        assert!((mean - 2.0).abs() < 0.1, "Mean {} too far from 2", mean);
+    }
+
+    #[test]
+    fn test_rndexp_errors() {
+        check_expr_compilation_error("1:10: RNDEXP expected lambda#", "RNDEXP()");
+        check_expr_compilation_error("1:17: BOOLEAN is not a number", "RNDEXP(TRUE)");
+        check_expr_error("1:17: lambda# must be positive", "RNDEXP(0)");
+        check_expr_error("1:17: lambda# must be positive", "RNDEXP(-1.5)");
+    }
+
+    #[test]
+    fn test_rndnorm_seeded() {
+        for _ in 0..2 {
+            let mut t = Tester::default();
+            t.run("RANDOMIZE 7: a = RNDNORM(10, 2): b = RNDNORM(0, 0)")
+                .expect_var("a", 8.371025552145115)
+                .expect_var("b", 0.0)
+                .check();
+        }
+    }
+
+    #[test]
+    fn test_rndnorm_mean_and_stddev() {
+        let mut prng = Prng::new_from_seed(1);
+        let samples: Vec<f64> = (0..10000).map(|_| prng.next_normal(10.0, 2.0)).collect();
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        let variance =
+            samples.iter().map(|n| (n - mean) * (n - mean)).sum::<f64>() / samples.len() as f64;
+        assert!((mean - 10.0).abs() < 0.1, "Mean {} too far from 10", mean);
+        assert!((variance.sqrt() - 2.0).abs() < 0.1, "Stddev {} too far from 2", variance.sqrt());
+    }
+
+    #[test]
+    fn test_rndnorm_errors() {
+        check_expr_compilation_error("1:10: RNDNORM expected mean#, stddev#", "RNDNORM(1)");
+        check_expr_compilation_error("1:21: BOOLEAN is not a number", "RNDNORM(1, TRUE)");
+        check_expr_error("1:21: stddev# cannot be negative", "RNDNORM(1, -2)");
+    }
+
+    #[test]
+    fn test_round_no_digits() {
+        check_expr_ok(2.0, "ROUND(2.4)");
+        check_expr_ok(3.0, "ROUND(2.6)");
+        check_expr_ok(-2.0, "ROUND(-2.4)");
+        check_expr_ok(-3.0, "ROUND(-2.6)");
+        check_expr_ok(7.0, "ROUND(7)");
+
+        check_expr_ok_with_vars(1.0, "ROUND(d)", [("d", 0.5f64.into())]);
+    }
+
+    #[test]
+    fn test_round_ties() {
+        check_expr_ok(1.0, "ROUND(0.5)");
+        check_expr_ok(3.0, "ROUND(2.5)");
+        check_expr_ok(-1.0, "ROUND(-0.5)");
+        check_expr_ok(-3.0, "ROUND(-2.5)");
+        check_expr_ok(2.68, "ROUND(2.675, 2)");
+        check_expr_ok(1.01, "ROUND(1.005, 2)");
+        check_expr_ok(-1.01, "ROUND(-1.005, 2)");
+        check_expr_ok(0.29, "ROUND(0.285, 2)");
+        check_expr_ok(834.5, "ROUND(834.45, 1)");
+        check_expr_ok(0.13, "ROUND(0.125, 2)");
+    }
+
+    #[test]
+    fn test_round_positive_digits() {
+        check_expr_ok(1.23, "ROUND(1.23456, 2)");
+        check_expr_ok(1.2346, "ROUND(1.23456, 4)");
+        check_expr_ok(-1.235, "ROUND(-1.23456, 3)");
+        check_expr_ok(0.1, "ROUND(0.1, 20)");
+        check_expr_ok(5.0, "ROUND(5, 3)");
+    }
+
+    #[test]
+    fn test_round_negative_digits() {
+        check_expr_ok(1230.0, "ROUND(1234.5678, -1)");
+        check_expr_ok(1300.0, "ROUND(1250, -2)");
+        check_expr_ok(1200.0, "ROUND(1249.99, -2)");
+        check_expr_ok(-1300.0, "ROUND(-1250, -2)");
+        check_expr_ok(0.0, "ROUND(499, -3)");
+        check_expr_ok(1000.0, "ROUND(500, -3)");
+        check_expr_ok(0.0, "ROUND(123, -308)");
+    }
+
+    #[test]
+    fn test_round_large_magnitudes() {
+        check_expr_ok_with_vars(1.23e300, "ROUND(d, 2)", [("d", 1.23e300f64.into())]);
+        check_expr_ok_with_vars(-1.23e300, "ROUND(d, 308)", [("d", (-1.23e300f64).into())]);
+        check_expr_ok_with_vars(1e20, "ROUND(d)", [("d", 1e20f64.into())]);
+        check_expr_ok(123456800.0, "ROUND(123456789, -2)");
+        check_expr_ok(123456789012.35, "ROUND(123456789012.345, 2)");
+        check_expr_ok(1234567890123456.0, "ROUND(1234567890123456.0, 2)");
+    }
+
+    #[test]
+    fn test_round_errors() {
+        check_expr_compilation_error("1:10: ROUND expected <expr#> | <expr#, digits%>", "ROUND()");
+        check_expr_compilation_error(
+            "1:10: ROUND expected <expr#> | <expr#, digits%>",
+            "ROUND(1, 2, 3)",
+        );
+        check_expr_compilation_error("1:16: BOOLEAN is not a number", "ROUND(FALSE)");
+        check_expr_compilation_error("1:19: BOOLEAN is not a number", "ROUND(1, TRUE)");
+        check_expr_error("1:19: digits% must be between -308 and 308", "ROUND(1, 309)");
+        check_expr_error("1:19: digits% must be between -308 and 308", "ROUND(1, -309)");
+    }
+
+    #[test]
+    fn test_sgn() {
+        check_expr_ok(1, "SGN(5)");
+        check_expr_ok(-1, "SGN(-5)");
+        check_expr_ok(0, "SGN(0)");
+        check_expr_ok(1, "SGN(0.001)");
+        check_expr_ok(-1, "SGN(-0.001)");
+        check_expr_ok(0, "SGN(-0.0)");
+
+        check_expr_ok_with_vars(-1, "SGN(d)", [("d", (-2.5f64).into())]);
+
+        check_expr_compilation_error("1:10: SGN expected x#", "SGN()");
+        check_expr_compilation_error("1:14: BOOLEAN is not a number", "SGN(FALSE)");
+    }
+
     #[test]
     fn test_sin() {
         check_expr_ok(123f64.sin(), "SIN(123)");
@@ -951,6 +2907,18 @@ mod tests {
         check_expr_compilation_error("1:10: SIN expected angle#", "SIN(3, 4)");
     }
 
+    #[test]
+    fn test_sinh() {
+        check_expr_ok(0.0, "SINH(0)");
+        check_expr_approx(1.1752011936438014, "SINH(1)");
+        check_expr_approx(-1.1752011936438014, "SINH(-1)");
+        check_expr_approx(3.626860407847019, "SINH(2.0)");
+
+        check_expr_compilation_error("1:10: SINH expected n#", "SINH()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "SINH(FALSE)");
+        check_expr_compilation_error("1:10: SINH expected n#", "SINH(3, 4)");
+    }
+
     #[test]
     fn test_sqr() {
         check_expr_ok(0f64.sqrt(), "SQR(0)");
@@ -978,4 +2946,16 @@ mod tests {
         check_expr_compilation_error("1:14: BOOLEAN is not a number", "TAN(FALSE)");
         check_expr_compilation_error("1:10: TAN expected angle#", "TAN(3, 4)");
     }
+
+    #[test]
+    fn test_tanh() {
+        check_expr_ok(0.0, "TANH(0)");
+        check_expr_approx(0.7615941559557649, "TANH(1)");
+        check_expr_approx(-0.7615941559557649, "TANH(-1)");
+        check_expr_ok(1.0, "TANH(100)");
+
+        check_expr_compilation_error("1:10: TANH expected n#", "TANH()");
+        check_expr_compilation_error("1:15: BOOLEAN is not a number", "TANH(FALSE)");
+        check_expr_compilation_error("1:10: TANH expected n#", "TANH(3, 4)");
+    }
 }
@@ -0,0 +1,358 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Per-line execution profiler.
+
+use crate::console::Console;
+use crate::exec::CATEGORY;
+use async_trait::async_trait;
+use endbasic_core::ast::ExprType;
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Profiler, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::rc::Rc;
+use time::{Duration, OffsetDateTime};
+
+/// Type of the function used to query the current time.
+type NowFn = Box<dyn Fn() -> OffsetDateTime>;
+
+/// Execution statistics of a single line.
+#[derive(Clone, Copy, Default)]
+struct LineStats {
+    /// Number of times execution entered the line.
+    hits: u64,
+
+    /// Total wall-clock time spent on the line.
+    time: Duration,
+}
+
+/// Per-line execution statistics collected while profiling.
+struct Profile {
+    now_fn: NowFn,
+    lines: HashMap<usize, LineStats>,
+    current: Option<(usize, OffsetDateTime)>,
+}
+
+impl Profile {
+    /// Creates a new, empty profile that uses `now_fn` to query the current time.
+    fn new(now_fn: NowFn) -> Self {
+        Self { now_fn, lines: HashMap::default(), current: None }
+    }
+
+    /// Discards all collected statistics.
+    fn reset(&mut self) {
+        self.lines.clear();
+        self.current = None;
+    }
+
+    /// Accounts the time spent on the current line, if any, up to `now`.
+    fn close_line(&mut self, now: OffsetDateTime) {
+        if let Some((line, start)) = self.current.take() {
+            self.lines.entry(line).or_default().time += now - start;
+        }
+    }
+
+    /// Returns the collected statistics sorted by decreasing total time and increasing line
+    /// number.
+    fn sorted(&self) -> Vec<(usize, LineStats)> {
+        let mut lines = self.lines.iter().map(|(line, stats)| (*line, *stats)).collect::<Vec<_>>();
+        lines.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(&b.0)));
+        lines
+    }
+}
+
+impl Profiler for Profile {
+    fn enter_line(&mut self, line: usize) {
+        if let Some((current, _)) = self.current {
+            if current == line {
+                return;
+            }
+        }
+
+        let now = (self.now_fn)();
+        self.close_line(now);
+        self.lines.entry(line).or_default().hits += 1;
+        self.current = Some((line, now));
+    }
+
+    fn leave(&mut self) {
+        if self.current.is_some() {
+            let now = (self.now_fn)();
+            self.close_line(now);
+        }
+    }
+}
+
+/// Adapter to install a shared `Profile` into the machine.
+struct SharedProfile(Rc<RefCell<Profile>>);
+
+impl Profiler for SharedProfile {
+    fn enter_line(&mut self, line: usize) {
+        self.0.borrow_mut().enter_line(line)
+    }
+
+    fn leave(&mut self) {
+        self.0.borrow_mut().leave()
+    }
+}
+
+/// Resets the collected statistics when the machine is cleared.
+struct ProfileClearable(Rc<RefCell<Profile>>);
+
+impl Clearable for ProfileClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.borrow_mut().reset();
+    }
+}
+
+/// Prints the statistics collected in `profile` to the `console`.
+fn show_profile(console: &mut dyn Console, profile: &Profile) -> io::Result<()> {
+    let lines = profile.sorted();
+    if lines.is_empty() {
+        console.print("")?;
+        console.print("    No profiling data")?;
+        console.print("")?;
+        return Ok(());
+    }
+
+    let total = lines.iter().fold(Duration::ZERO, |total, (_line, stats)| total + stats.time);
+    console.print("")?;
+    console.print("      Line        Hits     Time (ms)       %")?;
+    for (line, stats) in lines {
+        let percent = if total.is_zero() {
+            0.0
+        } else {
+            stats.time.as_seconds_f64() * 100.0 / total.as_seconds_f64()
+        };
+        console.print(&format!(
+            "    {:>6}  {:>10}  {:>12}  {:>5.1}%",
+            line,
+            stats.hits,
+            stats.time.whole_milliseconds(),
+            percent
+        ))?;
+    }
+    console.print("")?;
+    Ok(())
+}
+
+/// The `PROFILE` command.
+pub struct ProfileCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    profile: Rc<RefCell<Profile>>,
+}
+
+impl ProfileCommand {
+    /// Creates a new `PROFILE` command that prints to `console` the statistics in `profile`.
+    fn new(console: Rc<RefCell<dyn Console>>, profile: Rc<RefCell<Profile>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PROFILE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("action"),
+                            vtype: ExprType::Text,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Measures how much time the program spends on each line.
+action$ can be \"ON\" to discard any previous measurements and start profiling, \"OFF\" to stop \
+profiling, or \"SHOW\" to print the measurements collected so far.
+While profiling is enabled, the interpreter counts how many times execution enters each line of \
+the program and how much wall-clock time it spends on it.  The report lists the lines sorted by \
+the total time spent on them, along with their hit counts and the percentage of the total time \
+they represent.  Time spent on statements that wait, such as SLEEP or INPUT, counts towards the \
+line that contains them.
+The measurements are also discarded by CLEAR.",
+                )
+                .build(),
+            console,
+            profile,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ProfileCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (action, pos) = scope.pop_string_with_pos();
+
+        match action.to_ascii_uppercase().as_str() {
+            "ON" => {
+                self.profile.borrow_mut().reset();
+                machine.set_profiler(Some(Box::from(SharedProfile(self.profile.clone()))));
+            }
+            "OFF" => {
+                if machine.set_profiler(None).is_some() {
+                    self.profile.borrow_mut().leave();
+                }
+            }
+            "SHOW" => {
+                let mut console = self.console.borrow_mut();
+                show_profile(&mut *console, &self.profile.borrow())
+                    .map_err(|e| scope.io_error(e))?;
+            }
+            _ => {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("Invalid profile action '{}'; must be ON, OFF, or SHOW", action),
+                ))
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Same as `add_all` but uses `now_fn` to query the current time.
+fn add_all_with_now_fn(machine: &mut Machine, console: Rc<RefCell<dyn Console>>, now_fn: NowFn) {
+    let profile = Rc::from(RefCell::from(Profile::new(now_fn)));
+    machine.add_clearable(Box::from(ProfileClearable(profile.clone())));
+    machine.add_callable(ProfileCommand::new(console, profile));
+}
+
+/// Adds all profiling-related commands to the `machine` and makes them write to `console`.
+pub fn add_all(machine: &mut Machine, console: Rc<RefCell<dyn Console>>) {
+    add_all_with_now_fn(machine, console, Box::from(OffsetDateTime::now_utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use std::cell::Cell;
+
+    /// Creates a tester for a machine with the profiling commands where the clock advances by one
+    /// millisecond every time it is queried.
+    fn tester_with_fake_clock() -> Tester {
+        let mut t = Tester::empty();
+        let console = t.get_console();
+        let now = Rc::from(Cell::new(OffsetDateTime::from_unix_timestamp(1_000_000).unwrap()));
+        let now_fn = Box::from(move || {
+            let current = now.get();
+            now.set(current + Duration::milliseconds(1));
+            current
+        });
+        add_all_with_now_fn(t.get_machine(), console, now_fn);
+        t
+    }
+
+    #[test]
+    fn test_profile_hot_loop() {
+        let program = r#"PROFILE "ON"
+a = 0
+FOR i = 1 TO 10
+    a = a + i
+NEXT
+PROFILE "OFF"
+PROFILE "SHOW"
+"#;
+        tester_with_fake_clock()
+            .run(program)
+            .expect_var("a", 55)
+            .expect_var("i", 11)
+            .expect_prints([
+                "",
+                "      Line        Hits     Time (ms)       %",
+                "         3          11            11   47.8%",
+                "         4          10            10   43.5%",
+                "         2           1             1    4.3%",
+                "         6           1             1    4.3%",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_profile_on_resets_counters() {
+        let program = r#"PROFILE "ON"
+FOR i = 1 TO 10: NEXT
+PROFILE "ON"
+a = 1
+PROFILE "OFF"
+PROFILE "SHOW"
+"#;
+        tester_with_fake_clock()
+            .run(program)
+            .expect_var("a", 1)
+            .expect_var("i", 11)
+            .expect_prints([
+                "",
+                "      Line        Hits     Time (ms)       %",
+                "         4           1             1   50.0%",
+                "         5           1             1   50.0%",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_profile_clear_resets_counters() {
+        let mut t = tester_with_fake_clock();
+        t.run(r#"PROFILE "ON": a = 1: PROFILE "OFF""#).expect_var("a", 1).check();
+        t.get_machine().clear();
+        t.run(r#"PROFILE "SHOW""#).expect_prints(["", "    No profiling data", ""]).check();
+    }
+
+    #[test]
+    fn test_profile_off_stops_collection() {
+        let program = r#"PROFILE "ON"
+PROFILE "OFF"
+a = 1
+PROFILE "SHOW"
+"#;
+        tester_with_fake_clock()
+            .run(program)
+            .expect_var("a", 1)
+            .expect_prints([
+                "",
+                "      Line        Hits     Time (ms)       %",
+                "         2           1             1  100.0%",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_profile_show_without_data() {
+        tester_with_fake_clock()
+            .run(r#"PROFILE "SHOW""#)
+            .expect_prints(["", "    No profiling data", ""])
+            .check();
+    }
+
+    #[test]
+    fn test_profile_errors() {
+        check_stmt_compilation_err("1:1: PROFILE expected action$", "PROFILE");
+        check_stmt_compilation_err("1:9: expected STRING but found INTEGER", "PROFILE 3");
+        check_stmt_err(
+            "1:9: Invalid profile action 'foo'; must be ON, OFF, or SHOW",
+            r#"PROFILE "foo""#,
+        );
+    }
+}
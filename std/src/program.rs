@@ -16,15 +16,28 @@
 //! Stored program manipulation.
 
 use crate::console::{read_line, Console, Pager};
-use crate::storage::Storage;
+use crate::storage::{Channels, Storage};
 use crate::strings::parse_boolean;
 use async_trait::async_trait;
-use endbasic_core::ast::ExprType;
-use endbasic_core::compiler::{compile, ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
-use endbasic_core::exec::{Machine, Result, Scope, StopReason};
-use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::bytecode::Image;
+use endbasic_core::compiler::{
+    self, compile, compile_preserving, ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax,
+    RequiredValueSyntax, SingularArgSyntax,
+};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope, StopReason};
+use endbasic_core::merger::merge;
+use endbasic_core::minifier::minify;
+use endbasic_core::refactor::{rename, renumber};
+use endbasic_core::stats::{compute, ProgramStats};
+use endbasic_core::syms::{
+    Callable, CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey, Symbols,
+};
 use std::borrow::Cow;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::io;
 use std::rc::Rc;
 use std::str;
@@ -47,7 +60,15 @@ from.";
 pub const BREAK_MSG: &str = "**** BREAK ****";
 
 /// Default extension to add to file names.
-const DEFAULT_EXTENSION: &str = "bas";
+pub(crate) const DEFAULT_EXTENSION: &str = "bas";
+
+/// First line of the programs generated by `PACKAGE`, used to recognize them.
+pub const PACKAGE_HEADER: &str = "' EndBASIC packaged program; LIST and EDIT are disabled";
+
+/// Returns true if the program `text` was generated by `PACKAGE`.
+fn is_packaged(text: &str) -> bool {
+    text.starts_with(PACKAGE_HEADER)
+}
 
 /// Representation of the single program that we can keep in memory.
 #[async_trait(?Send)]
@@ -69,10 +90,21 @@ pub trait Program {
     /// Resets the name of the program.  Used when saving it.
     fn set_name(&mut self, name: &str);
 
+    /// Replaces the contents of the stored program with the given `text` without changing its
+    /// name.  Unlike `load`, this marks the program as modified.
+    fn set_text(&mut self, text: &str);
+
     /// Gets the contents of the stored program as a single string.
     fn text(&self) -> String;
 }
 
+/// Hook to edit the stored program with an editor that lives outside of the interpreter.
+#[async_trait(?Send)]
+pub trait ExternalEditor {
+    /// Opens `text` in the editor, waits for the user to finish, and returns the edited text.
+    async fn edit(&self, text: &str) -> io::Result<String>;
+}
+
 /// Trivial implementation of a recorded program that doesn't support editing.
 #[derive(Default)]
 pub(crate) struct ImmutableProgram {
@@ -103,6 +135,10 @@ impl Program for ImmutableProgram {
         self.name = Some(name.to_owned());
     }
 
+    fn set_text(&mut self, text: &str) {
+        text.clone_into(&mut self.text);
+    }
+
     fn text(&self) -> String {
         self.text.clone()
     }
@@ -125,6 +161,228 @@ pub async fn continue_if_modified(
     Ok(parse_boolean(&answer).unwrap_or(false))
 }
 
+/// Prints the warnings raised by the operations on `storage` to `console`.
+fn print_storage_warnings(storage: &Storage, console: &mut dyn Console) -> io::Result<()> {
+    for warning in storage.take_warnings() {
+        console.print(&format!("Warning: {}", warning))?;
+    }
+    Ok(())
+}
+
+/// Reads the program stored in `pathname` from `storage` and returns its canonical name and its
+/// contents.
+async fn read_program(storage: &Storage, pathname: &str) -> io::Result<(String, String)> {
+    let full_name = storage.make_canonical_with_extension(pathname, DEFAULT_EXTENSION)?;
+    let content = storage.get(&full_name).await?;
+    match String::from_utf8(content) {
+        Ok(text) => Ok((full_name, text)),
+        Err(e) => {
+            Err(io::Error::new(io::ErrorKind::InvalidData, format!("Invalid file content: {}", e)))
+        }
+    }
+}
+
+/// Program validated by `CHAIN` that has to run next.
+struct ChainTarget {
+    /// Canonical name of the program.
+    name: String,
+
+    /// Source code of the program.
+    text: String,
+
+    /// Names of the variables and arrays that survive the transition into the program.
+    keep: HashSet<SymbolKey>,
+
+    /// Compiled version of the program.
+    image: Image,
+}
+
+/// The `CHAIN` command.
+pub struct ChainCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+    storage: Rc<RefCell<Storage>>,
+    common: Rc<RefCell<HashSet<SymbolKey>>>,
+    next: RefCell<Option<ChainTarget>>,
+    chaining: Cell<bool>,
+}
+
+impl ChainCommand {
+    /// Creates a new `CHAIN` command that loads programs from `storage` into `program` and that
+    /// preserves the variables registered in `common` across them.
+    pub fn new(
+        console: Rc<RefCell<dyn Console>>,
+        program: Rc<RefCell<dyn Program>>,
+        storage: Rc<RefCell<Storage>>,
+        common: Rc<RefCell<HashSet<SymbolKey>>>,
+    ) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CHAIN")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("filename"),
+                            vtype: ExprType::Text,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Replaces the running program with the program in filename$.
+This loads the program from filename$ in the same way LOAD does and runs it in place of the \
+current program, which never resumes.  Unlike RUN, the variables and arrays listed in COMMON \
+statements executed so far keep their values in the new program, while everything else is \
+cleared as CLEAR does.
+If the program cannot be loaded or contains syntax errors, CHAIN fails and the current program \
+stays intact.  CHAIN cannot be used within a FUNCTION or SUB.",
+                )
+                .build(),
+            console,
+            program,
+            storage,
+            common,
+            next: RefCell::from(None),
+            chaining: Cell::new(false),
+        })
+    }
+
+    /// Runs the programs queued in `next` until one of them terminates without chaining into
+    /// another, and returns how the last one stopped.
+    async fn run_chain(&self, machine: &mut Machine) -> Result<StopReason> {
+        loop {
+            let target = self.next.borrow_mut().take().expect("Must have a program to run");
+            self.program.borrow_mut().load(Some(&target.name), &target.text);
+            machine.set_args(vec![target.name]);
+            machine.clear_except(&target.keep);
+            let stop_reason = machine.exec_image(target.image).await?;
+            if self.next.borrow().is_none() {
+                return Ok(stop_reason);
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ChainCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (pathname, pathname_pos) = scope.pop_string_with_pos();
+
+        if machine.get_symbols().scopes_depth() > 1 {
+            return Err(scope.internal_error("CHAIN cannot be used within a FUNCTION or SUB"));
+        }
+
+        if !continue_if_modified(&*self.program.borrow(), &mut *self.console.borrow_mut())
+            .await
+            .map_err(|e| scope.io_error(e))?
+        {
+            self.console
+                .borrow_mut()
+                .print("CHAIN aborted; use SAVE to save your current changes.")
+                .map_err(|e| scope.io_error(e))?;
+            return Ok(());
+        }
+
+        let (name, text) =
+            read_program(&self.storage.borrow(), &pathname).await.map_err(|e| scope.io_error(e))?;
+        print_storage_warnings(&self.storage.borrow(), &mut *self.console.borrow_mut())
+            .map_err(|e| scope.io_error(e))?;
+
+        let keep = self.common.borrow().clone();
+        let image = compile_preserving(&mut text.as_bytes(), machine.get_symbols(), &keep)
+            .map_err(|e| Error::SyntaxError(pathname_pos, format!("{}: {}", name, e)))?;
+        *self.next.borrow_mut() = Some(ChainTarget { name, text, keep, image });
+
+        if self.chaining.get() {
+            // We are running within a program started by an outer CHAIN, so stop the current
+            // program and let the outer CHAIN run the new one to avoid nesting executions.
+            machine.request_stop(StopReason::Eof);
+            return Ok(());
+        }
+
+        self.chaining.set(true);
+        let result = self.run_chain(machine).await;
+        self.chaining.set(false);
+        self.next.borrow_mut().take();
+        machine.request_stop(result?);
+        Ok(())
+    }
+}
+
+/// Clearable for the registry of `COMMON` variables.
+struct ClearableCommon(Rc<RefCell<HashSet<SymbolKey>>>);
+
+impl Clearable for ClearableCommon {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.borrow_mut().clear();
+    }
+}
+
+/// The `COMMON` command.
+pub struct CommonCommand {
+    metadata: CallableMetadata,
+    common: Rc<RefCell<HashSet<SymbolKey>>>,
+}
+
+impl CommonCommand {
+    /// Creates a new `COMMON` command that registers variables in `common`.
+    pub fn new(common: Rc<RefCell<HashSet<SymbolKey>>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("COMMON")
+                .with_syntax(&[(
+                    &[],
+                    Some(&RepeatedSyntax {
+                        name: Cow::Borrowed("vref"),
+                        type_syn: RepeatedTypeSyntax::VariableOrArrayRef,
+                        sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                        require_one: true,
+                        allow_missing: false,
+                    }),
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Shares variables with the programs started by CHAIN.
+The variables and arrays listed in COMMON keep their values when the program uses CHAIN to \
+transfer control to another program.  Arrays must be defined with DIM before they can be listed \
+and must be suffixed with empty parenthesis, as in scores().  Variables that are not yet defined \
+are defined with their zero value.
+The list of shared variables is discarded by CLEAR, so each program must issue its own COMMON \
+statements to pass variables on to the next.",
+                )
+                .build(),
+            common,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CommonCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_ne!(0, scope.nargs());
+
+        let mut common = self.common.borrow_mut();
+        while scope.nargs() > 0 {
+            let (key, vtype) = scope.pop_varref();
+            if machine.get_symbols().get_auto(&key.to_string()).is_none() {
+                machine.get_mut_symbols().dim(key.clone(), vtype);
+            }
+            common.insert(key);
+        }
+        Ok(())
+    }
+}
+
 /// The `DISASM` command.
 pub struct DisasmCommand {
     metadata: CallableMetadata,
@@ -198,21 +456,72 @@ pub struct EditCommand {
     metadata: CallableMetadata,
     console: Rc<RefCell<dyn Console>>,
     program: Rc<RefCell<dyn Program>>,
+    editor: Option<Rc<dyn ExternalEditor>>,
 }
 
 impl EditCommand {
-    /// Creates a new `EDIT` command that edits the stored `program` in the `console`.
-    pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+    /// Creates a new `EDIT` command that edits the stored `program` in the `console` or, when
+    /// requested, in the external `editor` (if any).
+    pub fn new(
+        console: Rc<RefCell<dyn Console>>,
+        program: Rc<RefCell<dyn Program>>,
+        editor: Option<Rc<dyn ExternalEditor>>,
+    ) -> Rc<Self> {
         Rc::from(Self {
             metadata: CallableMetadataBuilder::new("EDIT")
-                .with_syntax(&[(&[], None)])
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("mode"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
                 .with_category(CATEGORY)
-                .with_description("Interactively edits the stored program.")
+                .with_description(
+                    "Interactively edits the stored program.
+With no arguments, EDIT opens the built-in full-screen editor.
+When mode$ is \"EXTERNAL\", EDIT saves the stored program to a temporary file and opens it in \
+the editor configured in the VISUAL or EDITOR environment variables, replacing the stored program \
+with the file's contents once the editor exits.  If the edited program has errors, EDIT reports \
+them but keeps the new contents so that you can fix them.  External editing is only available \
+on desktop builds that use the text console.",
+                )
                 .build(),
             console,
             program,
+            editor,
         })
     }
+
+    /// Edits the stored program with the external editor and warns about any errors it contains.
+    async fn edit_external(&self, scope: &Scope<'_>, machine: &Machine) -> Result<()> {
+        let editor = match self.editor.as_ref() {
+            Some(editor) => editor,
+            None => {
+                return Err(
+                    scope.internal_error("External editing is not supported on this platform")
+                )
+            }
+        };
+
+        let text = self.program.borrow().text();
+        let text = editor.edit(&text).await.map_err(|e| scope.io_error(e))?;
+        self.program.borrow_mut().set_text(&text);
+
+        if let Err(e) = compile(&mut text.as_bytes(), machine.get_symbols()) {
+            self.console
+                .borrow_mut()
+                .print(&format!("Warning: the edited program has errors: {}", e))
+                .map_err(|e| scope.io_error(e))?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
@@ -221,13 +530,33 @@ impl Callable for EditCommand {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        let external = if scope.nargs() == 0 {
+            false
+        } else {
+            debug_assert_eq!(1, scope.nargs());
+            let (mode, pos) = scope.pop_string_with_pos();
+            if !mode.eq_ignore_ascii_case("EXTERNAL") {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!("Invalid edit mode '{}'; must be EXTERNAL", mode),
+                ));
+            }
+            true
+        };
 
-        let mut console = self.console.borrow_mut();
-        let mut program = self.program.borrow_mut();
-        program.edit(&mut *console).await.map_err(|e| scope.io_error(e))?;
-        Ok(())
+        if is_packaged(&self.program.borrow().text()) {
+            return Err(scope.internal_error("Cannot EDIT a packaged program"));
+        }
+
+        if external {
+            self.edit_external(&scope, machine).await
+        } else {
+            let mut console = self.console.borrow_mut();
+            let mut program = self.program.borrow_mut();
+            program.edit(&mut *console).await.map_err(|e| scope.io_error(e))?;
+            Ok(())
+        }
     }
 }
 
@@ -243,9 +572,45 @@ impl ListCommand {
     pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
         Rc::from(Self {
             metadata: CallableMetadataBuilder::new("LIST")
-                .with_syntax(&[(&[], None)])
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("start"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("start"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("end"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
                 .with_category(CATEGORY)
-                .with_description("Prints the currently-loaded program.")
+                .with_description(
+                    "Prints the currently-loaded program.
+When start% is given, prints only the lines from start% to end%, both inclusive, or to the end \
+of the program if end% is not given.  Lines are counted from 1 in the same way as the positions \
+reported in error messages, regardless of any line numbers in the program itself.",
+                )
                 .build(),
             console,
             program,
@@ -259,12 +624,42 @@ impl Callable for ListCommand {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        let (start, end) = match scope.nargs() {
+            0 => (1, usize::MAX),
+            1 | 2 => {
+                let (start, start_pos) = scope.pop_integer_with_pos();
+                if start < 1 {
+                    return Err(Error::SyntaxError(
+                        start_pos,
+                        format!("Start line {} must be positive", start),
+                    ));
+                }
+                let end = if scope.nargs() > 0 {
+                    let (end, end_pos) = scope.pop_integer_with_pos();
+                    if end < start {
+                        return Err(Error::SyntaxError(
+                            end_pos,
+                            format!("End line {} must not be before start line {}", end, start),
+                        ));
+                    }
+                    end as usize
+                } else {
+                    usize::MAX
+                };
+                (start as usize, end)
+            }
+            _ => unreachable!("Syntax validated at compilation time"),
+        };
+
+        let text = self.program.borrow().text();
+        if is_packaged(&text) {
+            return Err(scope.internal_error("Cannot LIST a packaged program"));
+        }
 
         let mut console = self.console.borrow_mut();
         let mut pager = Pager::new(&mut *console).map_err(|e| scope.io_error(e))?;
-        for line in self.program.borrow().text().lines() {
+        for line in text.lines().take(end).skip(start - 1) {
             pager.print(line).await.map_err(|e| scope.io_error(e))?;
         }
         Ok(())
@@ -330,23 +725,11 @@ impl Callable for LoadCommand {
             .await
             .map_err(|e| scope.io_error(e))?
         {
-            let (full_name, content) = {
-                let storage = self.storage.borrow();
-                let full_name = storage
-                    .make_canonical_with_extension(&pathname, DEFAULT_EXTENSION)
-                    .map_err(|e| scope.io_error(e))?;
-                let content = storage.get(&full_name).await.map_err(|e| scope.io_error(e))?;
-                let content = match String::from_utf8(content) {
-                    Ok(text) => text,
-                    Err(e) => {
-                        return Err(scope.io_error(io::Error::new(
-                            io::ErrorKind::InvalidData,
-                            format!("Invalid file content: {}", e),
-                        )));
-                    }
-                };
-                (full_name, content)
-            };
+            let (full_name, content) = read_program(&self.storage.borrow(), &pathname)
+                .await
+                .map_err(|e| scope.io_error(e))?;
+            print_storage_warnings(&self.storage.borrow(), &mut *self.console.borrow_mut())
+                .map_err(|e| scope.io_error(e))?;
             self.program.borrow_mut().load(Some(&full_name), &content);
             machine.clear();
         } else {
@@ -359,6 +742,96 @@ impl Callable for LoadCommand {
     }
 }
 
+/// The `MERGE` command.
+pub struct MergeCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+    program: Rc<RefCell<dyn Program>>,
+}
+
+impl MergeCommand {
+    /// Creates a new `MERGE` command that merges programs from `storage` into `program`.
+    pub fn new(storage: Rc<RefCell<Storage>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MERGE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("filename"),
+                            vtype: ExprType::Text,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Merges the given program into the stored program.
+The filename must be a string and must be a valid EndBASIC path.  The .BAS extension is optional \
+but, if present, it must be .BAS.
+If both programs have line numbers at the beginning of all of their lines, the lines of the given \
+program are interleaved with those of the stored program by line number.  Otherwise, the given \
+program is appended to the end of the stored program.  This makes it possible to keep reusable \
+FUNCTIONs and SUBs in separate library files but, as these must be defined before they are used, \
+merge the libraries before writing the code that uses them.
+Unlike LOAD, MERGE does not clear any variables and keeps the name of the stored program, which \
+becomes modified and must be saved with SAVE to persist the result.
+MERGE fails without modifying the stored program if both programs define the same FUNCTION or \
+SUB or use the same line number, reporting the positions of all such conflicts.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+            program,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MergeCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let pathname = scope.pop_string();
+
+        let (full_name, content) = {
+            let storage = self.storage.borrow();
+            let full_name = storage
+                .make_canonical_with_extension(&pathname, DEFAULT_EXTENSION)
+                .map_err(|e| scope.io_error(e))?;
+            let content = storage.get(&full_name).await.map_err(|e| scope.io_error(e))?;
+            let content = match String::from_utf8(content) {
+                Ok(text) => text,
+                Err(e) => {
+                    return Err(scope.io_error(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid file content: {}", e),
+                    )));
+                }
+            };
+            (full_name, content)
+        };
+        if is_packaged(&content) {
+            return Err(scope.io_error(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot merge {} because it is a packaged program", full_name),
+            )));
+        }
+
+        let mut program = self.program.borrow_mut();
+        let text = {
+            let base_name = program.name().unwrap_or("(stored program)");
+            merge(&program.text(), base_name, &content, &full_name)
+                .map_err(|e| scope.io_error(e))?
+        };
+        program.set_text(&text);
+        Ok(())
+    }
+}
+
 /// The `NEW` command.
 pub struct NewCommand {
     metadata: CallableMetadata,
@@ -415,46 +888,482 @@ impl Callable for NewCommand {
     }
 }
 
-/// The `RUN` command.
-pub struct RunCommand {
+/// The `PACKAGE` command.
+pub struct PackageCommand {
     metadata: CallableMetadata,
     console: Rc<RefCell<dyn Console>>,
-    program: Rc<RefCell<dyn Program>>,
+    storage: Rc<RefCell<Storage>>,
 }
 
-impl RunCommand {
-    /// Creates a new `RUN` command that executes the `program`.
-    ///
-    /// Reports any non-successful return codes from the program to the console.
-    pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+impl PackageCommand {
+    /// Creates a new `PACKAGE` command that packages programs in `storage` and reports progress
+    /// to `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RUN")
-                .with_syntax(&[(&[], None)])
+            metadata: CallableMetadataBuilder::new("PACKAGE")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("src"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("dest"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Packages a program for sharing without its readable source code.
+Reads the program in src and writes a runnable copy of it into dest with all comments and \
+formatting removed and with all variable, array, function, subroutine, and label names replaced \
+by short meaningless names.  The packaged program can be loaded with LOAD and executed with \
+RUN as usual, and errors still report their line and column numbers, but LIST and EDIT refuse \
+to operate on it.
+The filenames must be strings and must be valid EndBASIC paths.  The .BAS extension is optional \
+but, if present, it must be .BAS.
+WARNING: Packaging is only a deterrent to casual snooping and does not provide any security: \
+the packaged program is still plain text that anyone can read with some effort.  Programs that \
+refer to their own names from within strings, such as via EXECUTE or ISDEFINED?, will not work \
+after packaging.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            console,
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PackageCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let src = scope.pop_string();
+        let dest = scope.pop_string();
+
+        let (src, dest) = {
+            let storage = self.storage.borrow();
+            let src = storage
+                .make_canonical_with_extension(&src, DEFAULT_EXTENSION)
+                .map_err(|e| scope.io_error(e))?;
+            let dest = storage
+                .make_canonical_with_extension(&dest, DEFAULT_EXTENSION)
+                .map_err(|e| scope.io_error(e))?;
+            (src, dest)
+        };
+
+        let content = self.storage.borrow().get(&src).await.map_err(|e| scope.io_error(e))?;
+        let symbols = machine.get_symbols();
+        let is_builtin = |name: &str| matches!(symbols.get_auto(name), Some(Symbol::Callable(_)));
+        let minified =
+            minify(&mut content.as_slice(), &is_builtin).map_err(|e| scope.io_error(e))?;
+
+        let packaged = format!("{}\n{}", PACKAGE_HEADER, minified);
+        self.storage
+            .borrow_mut()
+            .put(&dest, packaged.as_bytes())
+            .await
+            .map_err(|e| scope.io_error(e))?;
+
+        self.console
+            .borrow_mut()
+            .print(&format!("Packaged {} as {}", src, dest))
+            .map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `REFACTOR` command.
+pub struct RefactorCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+}
+
+impl RefactorCommand {
+    /// Creates a new `REFACTOR` command that modifies the `program` and reports the results to
+    /// `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("REFACTOR")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("action"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("old"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("new"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Runs the stored program.
+                    "Applies a transformation to the stored program.
+The only supported action is \"RENAME\", which renames all references to the variable, array, \
+function, or subroutine named old to new and reports how many references were changed.  \
+Variables local to a function or subroutine are not conflated with global variables of the same \
+name: old refers to a global symbol unless given as callable.name, in which case it refers to \
+the symbol local to the named function or subroutine.
+The rename is refused if new is a keyword, a built-in name, or a name that is already in use \
+where old is visible.  Comments and formatting are preserved, but names referenced from within \
+strings, such as via EXECUTE or ISDEFINED?, are not updated.",
+                )
+                .build(),
+            console,
+            program,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RefactorCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(3, scope.nargs());
+        let (action, apos) = scope.pop_string_with_pos();
+        let old = scope.pop_string();
+        let new = scope.pop_string();
+
+        if !action.eq_ignore_ascii_case("RENAME") {
+            return Err(Error::SyntaxError(
+                apos,
+                format!("Unknown refactoring action '{}'", action),
+            ));
+        }
+
+        let mut program = self.program.borrow_mut();
+        let text = program.text();
+        if is_packaged(&text) {
+            return Err(scope.internal_error("Cannot REFACTOR a packaged program"));
+        }
+
+        let symbols = machine.get_symbols();
+        let is_builtin = |name: &str| matches!(symbols.get_auto(name), Some(Symbol::Callable(_)));
+        let (text, count) =
+            rename(&mut text.as_bytes(), &old, &new, &is_builtin).map_err(|e| scope.io_error(e))?;
+        program.set_text(&text);
+
+        self.console
+            .borrow_mut()
+            .print(&format!("Renamed {} reference(s) to {}", count, new))
+            .map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// The `RENUM` command.
+pub struct RenumCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+}
+
+impl RenumCommand {
+    /// Creates a new `RENUM` command that renumbers the lines of the `program` and reports the
+    /// results to `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RENUM")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("start"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("step"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Renumbers the numbered lines of the stored program.
+The first numbered line gets the start% number and every subsequent numbered line gets a number \
+that is step% larger than the previous one.  If not specified, both start% and step% default to \
+10.  Lines without a number are left alone.
+All GOTO, GOSUB, and ON ERROR GOTO statements that target a line number are updated to match \
+the new numbers.  The renumbering is refused if any of these statements targets a line number \
+that does not exist.",
+                )
+                .build(),
+            console,
+            program,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RenumCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        let (start, step) = if scope.nargs() == 0 {
+            (10, 10)
+        } else {
+            debug_assert_eq!(2, scope.nargs());
+            (scope.pop_integer(), scope.pop_integer())
+        };
+
+        let mut program = self.program.borrow_mut();
+        let text = program.text();
+        if is_packaged(&text) {
+            return Err(scope.internal_error("Cannot RENUM a packaged program"));
+        }
+
+        let (text, count) =
+            renumber(&mut text.as_bytes(), start, step).map_err(|e| scope.io_error(e))?;
+        program.set_text(&text);
+
+        self.console
+            .borrow_mut()
+            .print(&format!("Renumbered {} line(s)", count))
+            .map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// Compiled version of the stored program that `RUN` reuses while the text of the program does
+/// not change.
+///
+/// The cache is keyed by the text of the program, so any modification to it, be it via an edit,
+/// a `LOAD` or a `NEW`, invalidates the cached image.  `RUN` always compiles the program right
+/// after clearing the machine, so the image only depends on the text of the program and on the
+/// callables registered in the machine, which never change.
+#[derive(Default)]
+struct CompilationCache {
+    /// Hash of `text`, used to quickly discard stale entries.
+    hash: u64,
+
+    /// Text of the program that was compiled into `image`.
+    text: String,
+
+    /// The compiled program, or `None` if nothing has been cached yet.
+    image: Option<Image>,
+}
+
+impl CompilationCache {
+    /// Computes the hash of the program `text`.
+    fn hash_text(text: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the image for the program `text`, compiling it against `syms` if the cached image
+    /// does not correspond to `text`.
+    fn get(&mut self, text: &str, syms: &Symbols) -> compiler::Result<Image> {
+        let hash = Self::hash_text(text);
+        if let Some(image) = self.image.as_ref() {
+            if self.hash == hash && self.text == text {
+                return Ok(image.clone());
+            }
+        }
+
+        self.image = None;
+        let image = compile(&mut text.as_bytes(), syms)?;
+        self.hash = hash;
+        text.clone_into(&mut self.text);
+        self.image = Some(image.clone());
+        Ok(image)
+    }
+}
+
+/// The `RUN` command.
+pub struct RunCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+    storage: Rc<RefCell<Storage>>,
+    channels: Rc<RefCell<Channels>>,
+    cache: RefCell<CompilationCache>,
+}
+
+impl RunCommand {
+    /// Creates a new `RUN` command that executes the `program`.
+    ///
+    /// Reports any non-successful return codes from the program to the console, and closes any
+    /// `channels` backed by `storage` that the program left open.
+    pub fn new(
+        console: Rc<RefCell<dyn Console>>,
+        program: Rc<RefCell<dyn Program>>,
+        storage: Rc<RefCell<Storage>>,
+        channels: Rc<RefCell<Channels>>,
+    ) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RUN")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        )],
+                        Some(&RepeatedSyntax {
+                            name: Cow::Borrowed("arg"),
+                            type_syn: RepeatedTypeSyntax::TypedValue(ExprType::Text),
+                            sep: ArgSepSyntax::Exactly(ArgSep::Long),
+                            require_one: true,
+                            allow_missing: false,
+                        }),
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Runs the stored program or the program in filename$.
+When given a filename$, this first loads the program from it in the same way LOAD does and then \
+runs it.  Any arg$ values that follow the filename$ are passed to the program, which can query \
+them with ARGC% and ARG$().  Argument 0 is always the name of the program.
 This issues a CLEAR operation before starting the program to prevent previous leftover state \
-from interfering with the new execution.",
+from interfering with the new execution.  The arguments are not affected by CLEAR.",
                 )
                 .build(),
             console,
             program,
+            storage,
+            channels,
+            cache: RefCell::from(CompilationCache::default()),
         })
     }
 }
 
+impl RunCommand {
+    /// Closes all channels left open by the program and warns about each of them.
+    async fn close_channels(&self, scope: &Scope<'_>) -> Result<()> {
+        if self.channels.borrow().is_empty() {
+            return Ok(());
+        }
+
+        let results = {
+            let mut storage = self.storage.borrow_mut();
+            self.channels.borrow_mut().close_all(&mut storage).await
+        };
+        let mut console = self.console.borrow_mut();
+        for (n, result) in results {
+            let message = match result {
+                Ok(()) => format!("Warning: channel #{} was not closed; closed it", n),
+                Err(e) => {
+                    format!("Warning: channel #{} was not closed and failed to close: {}", n, e)
+                }
+            };
+            console.print(&message).map_err(|e| scope.io_error(e))?;
+        }
+        Ok(())
+    }
+}
+
 #[async_trait(?Send)]
 impl Callable for RunCommand {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        if machine.is_poisoned() {
+            return Err(scope.internal_error(
+                "Cannot run programs after an internal error; save your work and restart",
+            ));
+        }
+
+        let args = if scope.nargs() == 0 {
+            vec![self.program.borrow().name().unwrap_or("").to_owned()]
+        } else {
+            let pathname = scope.pop_string();
+            let mut args = vec![];
+            while scope.nargs() > 0 {
+                args.push(scope.pop_string());
+            }
+
+            if !continue_if_modified(&*self.program.borrow(), &mut *self.console.borrow_mut())
+                .await
+                .map_err(|e| scope.io_error(e))?
+            {
+                self.console
+                    .borrow_mut()
+                    .print("RUN aborted; use SAVE to save your current changes.")
+                    .map_err(|e| scope.io_error(e))?;
+                return Ok(());
+            }
+
+            let (full_name, content) = read_program(&self.storage.borrow(), &pathname)
+                .await
+                .map_err(|e| scope.io_error(e))?;
+            print_storage_warnings(&self.storage.borrow(), &mut *self.console.borrow_mut())
+                .map_err(|e| scope.io_error(e))?;
+            self.program.borrow_mut().load(Some(&full_name), &content);
+
+            args.insert(0, full_name);
+            args
+        };
+        machine.set_args(args);
 
         machine.clear();
         let program = self.program.borrow().text();
-        let stop_reason = machine.exec(&mut program.as_bytes()).await?;
+        let image = self.cache.borrow_mut().get(&program, machine.get_symbols());
+        let result = match image {
+            Ok(image) => machine.exec_image(image).await,
+            Err(e) => Err(e.into()),
+        };
+        self.close_channels(&scope).await?;
+        let stop_reason = result?;
         match stop_reason {
             StopReason::Break => {
                 self.console.borrow_mut().print(BREAK_MSG).map_err(|e| scope.io_error(e))?
@@ -463,7 +1372,7 @@ impl Callable for RunCommand {
                 if stop_reason.as_exit_code() != 0 {
                     self.console
                         .borrow_mut()
-                        .print(&format!("Program exited with code {}", stop_reason.as_exit_code()))
+                        .print(&format!("Program ended with code {}", stop_reason.as_exit_code()))
                         .map_err(|e| scope.io_error(e))?;
                 }
             }
@@ -551,30 +1460,139 @@ impl Callable for SaveCommand {
             .map_err(|e| scope.io_error(e))?;
         self.program.borrow_mut().set_name(&full_name);
 
-        self.console
-            .borrow_mut()
-            .print(&format!("Saved as {}", full_name))
+        let mut console = self.console.borrow_mut();
+        print_storage_warnings(&self.storage.borrow(), &mut *console)
             .map_err(|e| scope.io_error(e))?;
+        console.print(&format!("Saved as {}", full_name)).map_err(|e| scope.io_error(e))?;
 
         Ok(())
     }
 }
 
+/// The `STATS` command.
+pub struct StatsCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    program: Rc<RefCell<dyn Program>>,
+}
+
+impl StatsCommand {
+    /// Creates a new `STATS` command that summarizes the `program` on the `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, program: Rc<RefCell<dyn Program>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("STATS")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Prints size and complexity metrics of the stored program.
+The report includes the number of lines and statements, the fraction of non-blank lines that only \
+contain comments, the number of distinct variables and arrays, the number of SUBs and FUNCTIONs, \
+the deepest nesting level of control flow statements, and the longest SUB or FUNCTION.
+The metrics are computed from the parsed program so the program must be syntactically valid, but \
+it does not need to run successfully.",
+                )
+                .build(),
+            console,
+            program,
+        })
+    }
+}
+
+/// Formats the `stats` of a program as rows of a table of labels and values.
+fn stats_rows(stats: &ProgramStats) -> Vec<(&'static str, String)> {
+    let non_blank = stats.code_lines + stats.comment_lines;
+    let comment_ratio = (stats.comment_lines * 100).checked_div(non_blank).unwrap_or(0);
+    let longest = match &stats.longest_callable {
+        Some((name, 1)) => format!("{} (1 line)", name),
+        Some((name, lines)) => format!("{} ({} lines)", name, lines),
+        None => "none".to_owned(),
+    };
+    vec![
+        ("Lines", stats.lines.to_string()),
+        ("Statements", stats.statements.to_string()),
+        ("Comment lines", format!("{} ({}%)", stats.comment_lines, comment_ratio)),
+        ("Variables", stats.variables.to_string()),
+        ("Arrays", stats.arrays.to_string()),
+        ("SUBs", stats.subs.to_string()),
+        ("FUNCTIONs", stats.functions.to_string()),
+        ("Deepest nesting", stats.max_nesting.to_string()),
+        ("Longest procedure", longest),
+    ]
+}
+
+#[async_trait(?Send)]
+impl Callable for StatsCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let stats = compute(&self.program.borrow().text()).map_err(compiler::Error::from)?;
+        let rows = stats_rows(&stats);
+        let width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+        let mut console = self.console.borrow_mut();
+        console.print("").map_err(|e| scope.io_error(e))?;
+        for (label, value) in rows {
+            console
+                .print(&format!("    {:width$}    {}", label, value, width = width))
+                .map_err(|e| scope.io_error(e))?;
+        }
+        console.print("").map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
 /// Adds all program editing commands against the stored `program` to the `machine`, using
-/// `console` for interactive editing and using `storage` as the on-disk storage for the programs.
+/// `console` for interactive editing, using `editor` (if any) for external editing, and using
+/// `storage` as the on-disk storage for the programs.
+///
+/// If `storage` is none, the commands that load and save programs are not added and `RUN` can only
+/// run the stored program.
 pub fn add_all(
     machine: &mut Machine,
     program: Rc<RefCell<dyn Program>>,
     console: Rc<RefCell<dyn Console>>,
-    storage: Rc<RefCell<Storage>>,
+    editor: Option<Rc<dyn ExternalEditor>>,
+    storage: Option<Rc<RefCell<Storage>>>,
+    channels: Rc<RefCell<Channels>>,
 ) {
+    let common = Rc::from(RefCell::from(HashSet::default()));
+    machine.add_clearable(Box::from(ClearableCommon(common.clone())));
+    if let Some(storage) = storage.as_ref() {
+        machine.add_callable(ChainCommand::new(
+            console.clone(),
+            program.clone(),
+            storage.clone(),
+            common.clone(),
+        ));
+    }
+    machine.add_callable(CommonCommand::new(common));
     machine.add_callable(DisasmCommand::new(console.clone(), program.clone()));
-    machine.add_callable(EditCommand::new(console.clone(), program.clone()));
+    machine.add_callable(EditCommand::new(console.clone(), program.clone(), editor));
     machine.add_callable(ListCommand::new(console.clone(), program.clone()));
-    machine.add_callable(LoadCommand::new(console.clone(), storage.clone(), program.clone()));
+    if let Some(storage) = storage.as_ref() {
+        machine.add_callable(LoadCommand::new(console.clone(), storage.clone(), program.clone()));
+        machine.add_callable(MergeCommand::new(storage.clone(), program.clone()));
+    }
     machine.add_callable(NewCommand::new(console.clone(), program.clone()));
-    machine.add_callable(RunCommand::new(console.clone(), program.clone()));
-    machine.add_callable(SaveCommand::new(console, storage, program));
+    if let Some(storage) = storage.as_ref() {
+        machine.add_callable(PackageCommand::new(console.clone(), storage.clone()));
+    }
+    machine.add_callable(RefactorCommand::new(console.clone(), program.clone()));
+    machine.add_callable(RenumCommand::new(console.clone(), program.clone()));
+    machine.add_callable(RunCommand::new(
+        console.clone(),
+        program.clone(),
+        storage.clone().unwrap_or_else(|| Rc::from(RefCell::from(Storage::default()))),
+        channels,
+    ));
+    if let Some(storage) = storage {
+        machine.add_callable(SaveCommand::new(console.clone(), storage, program.clone()));
+    }
+    machine.add_callable(StatsCommand::new(console, program));
 }
 
 #[cfg(test)]
@@ -582,12 +1600,156 @@ mod tests {
     use super::*;
     use crate::console::{CharsXY, Key};
     use crate::testutils::*;
+    use std::collections::HashMap;
 
     const NO_ANSWERS: &[&str] =
         &["n\n", "N\n", "no\n", "NO\n", "false\n", "FALSE\n", "xyz\n", "\n", "1\n"];
 
     const YES_ANSWERS: &[&str] = &["y\n", "yes\n", "Y\n", "YES\n", "true\n", "TRUE\n"];
 
+    #[test]
+    fn test_chain_preserves_common_variables() {
+        let menu =
+            "COMMON a, b$\na = 5\nb$ = \"text\"\nc = 7\nCHAIN \"sub\"\nPRINT \"not reached\"";
+        let sub = "PRINT a; b$\nd = a + 1";
+        Tester::default()
+            .write_file("menu.bas", menu)
+            .write_file("sub.bas", sub)
+            .run(r#"RUN "menu""#)
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 5 text"])
+            .expect_var("a", 5)
+            .expect_var("b", "text")
+            .expect_var("d", 6)
+            .expect_program(Some("MEMORY:sub.bas"), sub)
+            .expect_file("MEMORY:/menu.bas", menu)
+            .expect_file("MEMORY:/sub.bas", sub)
+            .check();
+    }
+
+    #[test]
+    fn test_chain_preserves_common_arrays() {
+        let menu = "DIM scores(3)\nDIM other(2)\nscores(1) = 10\nscores(2) = 20\nCOMMON scores()\nCHAIN \"sub\"";
+        let sub = "PRINT scores(1) + scores(2)";
+        Tester::default()
+            .write_file("menu.bas", menu)
+            .write_file("sub.bas", sub)
+            .run(r#"RUN "menu""#)
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 30"])
+            .expect_array_simple("scores", ExprType::Integer, vec![0.into(), 10.into(), 20.into()])
+            .expect_program(Some("MEMORY:sub.bas"), sub)
+            .expect_file("MEMORY:/menu.bas", menu)
+            .expect_file("MEMORY:/sub.bas", sub)
+            .check();
+    }
+
+    #[test]
+    fn test_chain_back_and_forth() {
+        let menu =
+            "COMMON n\nn = n + 1\nPRINT \"menu\"; n\nIF n < 3 THEN CHAIN \"sub\"\nPRINT \"done\"";
+        let sub = "COMMON n\nPRINT \"sub\"; n\nCHAIN \"menu\"\nPRINT \"not reached\"";
+        Tester::default()
+            .write_file("menu.bas", menu)
+            .write_file("sub.bas", sub)
+            .run(r#"RUN "menu""#)
+            .expect_clear()
+            .expect_prints(["menu 1"])
+            .expect_clear()
+            .expect_prints(["sub 1"])
+            .expect_clear()
+            .expect_prints(["menu 2"])
+            .expect_clear()
+            .expect_prints(["sub 2"])
+            .expect_clear()
+            .expect_prints(["menu 3", "done"])
+            .expect_var("n", 3)
+            .expect_program(Some("MEMORY:menu.bas"), menu)
+            .expect_file("MEMORY:/menu.bas", menu)
+            .expect_file("MEMORY:/sub.bas", sub)
+            .check();
+    }
+
+    #[test]
+    fn test_chain_propagates_exit_code() {
+        let sub = "PRINT ARGC; ARG(0)\nEND 4";
+        Tester::default()
+            .write_file("sub.bas", sub)
+            .run(r#"CHAIN "sub": PRINT "not reached""#)
+            .expect_clear()
+            .expect_prints([" 1 MEMORY:sub.bas"])
+            .expect_program(Some("MEMORY:sub.bas"), sub)
+            .expect_file("MEMORY:/sub.bas", sub)
+            .expect_ok(StopReason::Exited(4))
+            .check();
+    }
+
+    #[test]
+    fn test_chain_common_discarded_by_clear() {
+        let sub = "b = 2";
+        Tester::default()
+            .write_file("sub.bas", sub)
+            .run(r#"COMMON a: a = 1: CLEAR: a = 1: CHAIN "sub""#)
+            .expect_clear()
+            .expect_clear()
+            .expect_var("b", 2)
+            .expect_program(Some("MEMORY:sub.bas"), sub)
+            .expect_file("MEMORY:/sub.bas", sub)
+            .check();
+    }
+
+    #[test]
+    fn test_chain_errors_leave_program_intact() {
+        let program = "PRINT 1";
+        Tester::default()
+            .set_program(Some("current.bas"), program)
+            .write_file("bad.bas", "PRINT (")
+            .run(r#"a = 3: CHAIN "bad""#)
+            .expect_err("1:14: MEMORY:bad.bas: 1:8: Unexpected <<EOF>>")
+            .expect_var("a", 3)
+            .expect_program(Some("current.bas"), program)
+            .expect_file("MEMORY:/bad.bas", "PRINT (")
+            .check();
+
+        Tester::default()
+            .set_program(Some("current.bas"), program)
+            .run(r#"a = 3: CHAIN "missing""#)
+            .expect_err("1:8: Entry not found")
+            .expect_var("a", 3)
+            .expect_program(Some("current.bas"), program)
+            .check();
+    }
+
+    #[test]
+    fn test_chain_errors() {
+        check_stmt_compilation_err("1:1: CHAIN expected filename$", "CHAIN");
+        check_stmt_compilation_err("1:7: expected STRING but found INTEGER", "CHAIN 3");
+        check_stmt_err(
+            "2:1: CHAIN cannot be used within a FUNCTION or SUB",
+            "SUB s\nCHAIN \"x\"\nEND SUB\ns",
+        );
+    }
+
+    #[test]
+    fn test_common_defines_missing_variables() {
+        Tester::default()
+            .run("a = 3: COMMON a, b$, c?")
+            .expect_var("a", 3)
+            .expect_var("b", "")
+            .expect_var("c", false)
+            .check();
+    }
+
+    #[test]
+    fn test_common_errors() {
+        check_stmt_compilation_err("1:1: COMMON expected vref1[, .., vrefN]", "COMMON");
+        check_stmt_compilation_err("1:8: Requires a reference, not a value", "COMMON 3");
+        check_stmt_compilation_err("1:8: Undefined symbol A", "COMMON a()");
+        check_stmt_compilation_err("1:18: Requires a reference, not a value", "DIM a(3): COMMON a");
+    }
+
     #[test]
     fn test_disasm_nothing() {
         Tester::default().run("DISASM").expect_prints([""]).check();
@@ -654,9 +1816,90 @@ mod tests {
             .check();
     }
 
+    /// External editor that replaces the program with fixed contents or that fails.
+    struct MockExternalEditor {
+        result: io::Result<String>,
+        seen: RefCell<Option<String>>,
+    }
+
+    impl MockExternalEditor {
+        /// Creates a mock editor that returns `result` when invoked.
+        fn new(result: io::Result<String>) -> Rc<Self> {
+            Rc::from(Self { result, seen: RefCell::from(None) })
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl ExternalEditor for MockExternalEditor {
+        async fn edit(&self, text: &str) -> io::Result<String> {
+            *self.seen.borrow_mut() = Some(text.to_owned());
+            match &self.result {
+                Ok(text) => Ok(text.clone()),
+                Err(e) => Err(io::Error::new(e.kind(), e.to_string())),
+            }
+        }
+    }
+
+    /// Creates a tester with just the `EDIT` command configured to use `editor`.
+    fn tester_with_editor(editor: Option<Rc<MockExternalEditor>>) -> Tester {
+        let t = Tester::empty();
+        let console = t.get_console();
+        let program = t.get_program();
+        let editor = editor.map(|e| e as Rc<dyn ExternalEditor>);
+        t.add_callable(EditCommand::new(console, program, editor))
+    }
+
+    #[test]
+    fn test_edit_external_ok() {
+        let editor = MockExternalEditor::new(Ok("a = 1\nb = 2\n".to_owned()));
+        tester_with_editor(Some(editor.clone()))
+            .set_program(Some("foo.bas"), "a = 1\n")
+            .run(r#"EDIT "external""#)
+            .expect_program(Some("foo.bas"), "a = 1\nb = 2\n")
+            .check();
+        assert_eq!(Some("a = 1\n"), editor.seen.borrow().as_deref());
+    }
+
+    #[test]
+    fn test_edit_external_keeps_text_with_errors() {
+        let editor = MockExternalEditor::new(Ok("a = 1\nb = \n".to_owned()));
+        tester_with_editor(Some(editor))
+            .set_program(Some("foo.bas"), "a = 1\n")
+            .run(r#"EDIT "EXTERNAL""#)
+            .expect_prints([
+                "Warning: the edited program has errors: 2:5: Missing expression in assignment",
+            ])
+            .expect_program(Some("foo.bas"), "a = 1\nb = \n")
+            .check();
+    }
+
+    #[test]
+    fn test_edit_external_editor_fails() {
+        let editor =
+            MockExternalEditor::new(Err(io::Error::new(io::ErrorKind::Other, "Editor crashed")));
+        tester_with_editor(Some(editor))
+            .set_program(Some("foo.bas"), "a = 1\n")
+            .run(r#"EDIT "EXTERNAL""#)
+            .expect_err("1:1: Editor crashed")
+            .expect_program(Some("foo.bas"), "a = 1\n")
+            .check();
+    }
+
+    #[test]
+    fn test_edit_external_not_supported() {
+        tester_with_editor(None)
+            .set_program(Some("foo.bas"), "a = 1\n")
+            .run(r#"EDIT "EXTERNAL""#)
+            .expect_err("1:1: External editing is not supported on this platform")
+            .expect_program(Some("foo.bas"), "a = 1\n")
+            .check();
+    }
+
     #[test]
     fn test_edit_errors() {
-        check_stmt_compilation_err("1:1: EDIT expected no arguments", "EDIT 1");
+        check_stmt_compilation_err("1:1: EDIT expected <> | <mode$>", "EDIT 1, 2");
+        check_stmt_compilation_err("1:6: expected STRING but found INTEGER", "EDIT 1");
+        check_stmt_err("1:6: Invalid edit mode 'foo'; must be EXTERNAL", r#"EDIT "foo""#);
     }
 
     #[test]
@@ -684,9 +1927,34 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_list_range() {
+        let program = "one\n\nthree\n10 four\nfive\n";
+        for (args, exp) in [
+            ("1, 1", &["one"][..]),
+            ("2, 4", &["", "three", "10 four"][..]),
+            ("4", &["10 four", "five"][..]),
+            ("4, 100", &["10 four", "five"][..]),
+            ("6", &[][..]),
+        ] {
+            Tester::default()
+                .set_program(None, program)
+                .run(format!("LIST {}", args))
+                .expect_prints(exp.to_vec())
+                .expect_program(None as Option<&str>, program)
+                .check();
+        }
+    }
+
     #[test]
     fn test_list_errors() {
-        check_stmt_compilation_err("1:1: LIST expected no arguments", "LIST 2");
+        check_stmt_compilation_err(
+            "1:1: LIST expected <> | <start%> | <start%, end%>",
+            "LIST 1, 2, 3",
+        );
+        check_stmt_compilation_err("1:6: STRING is not a number", r#"LIST "a""#);
+        check_stmt_err("1:6: Start line 0 must be positive", "LIST 0");
+        check_stmt_err("1:9: End line 2 must not be before start line 3", "LIST 3, 2");
     }
 
     #[test]
@@ -715,6 +1983,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_load_mixed_case() {
+        Tester::default()
+            .write_file("game.bas", "the game\n")
+            .run(r#"LOAD "GAME.BAS""#)
+            .expect_clear()
+            .expect_program(Some("MEMORY:GAME.BAS"), "the game\n")
+            .expect_file("MEMORY:/game.bas", "the game\n")
+            .check();
+    }
+
+    #[test]
+    fn test_load_case_conflict_warns() {
+        Tester::default()
+            .write_file("GAME.BAS", "upper\n")
+            .write_file("game.bas", "lower\n")
+            .run(r#"LOAD "Game""#)
+            .expect_prints([
+                "Warning: MEMORY:Game.bas matches files that differ only in case; using GAME.BAS \
+                 and ignoring game.bas",
+            ])
+            .expect_clear()
+            .expect_program(Some("MEMORY:Game.bas"), "upper\n")
+            .expect_file("MEMORY:/GAME.BAS", "upper\n")
+            .expect_file("MEMORY:/game.bas", "lower\n")
+            .check();
+    }
+
     #[test]
     fn test_load_dirty_no_name_abort() {
         for answer in NO_ANSWERS {
@@ -800,31 +2096,110 @@ mod tests {
 
         Tester::default()
             .run(format!(r#"{} "a/b.bas""#, cmd))
-            .expect_err("1:1: Too many / separators in path 'a/b.bas'")
+            .expect_err("1:1: Directory of 'MEMORY:a/b.bas' does not exist")
+            .check();
+
+        Tester::default()
+            .run(format!(r#"{} "drive:""#, cmd))
+            .expect_err("1:1: Missing file name in path 'drive:'")
+            .check();
+    }
+
+    #[test]
+    fn test_load_errors() {
+        check_load_save_common_errors("LOAD");
+
+        Tester::default()
+            .run("LOAD")
+            .expect_compilation_err("1:1: LOAD expected filename$")
             .check();
 
+        check_stmt_err("1:1: Entry not found", r#"LOAD "missing-file""#);
+
+        Tester::default()
+            .write_file("mismatched-extension.bat", "")
+            .run(r#"LOAD "mismatched-extension""#)
+            .expect_err("1:1: Entry not found")
+            .expect_file("MEMORY:/mismatched-extension.bat", "")
+            .check();
+    }
+
+    #[test]
+    fn test_merge_function_library() {
+        let main = "' Main program\nn = 3";
+        let library = "FUNCTION square(n%)\n    square = n * n\nEND FUNCTION\n";
+        let code = "PRINT square(n)\n";
+        let merged = format!("{}\n{}{}", main, library, code);
+        Tester::default()
+            .write_file("lib.bas", library)
+            .write_file("code.bas", code)
+            .set_program(Some("MEMORY:/main.bas"), main)
+            .run(r#"a = 5: MERGE "lib": MERGE "code": PRINT a: RUN"#)
+            .expect_prints([" 5"])
+            .expect_clear()
+            .expect_prints([" 9"])
+            .expect_var("n", 3)
+            .expect_program(Some("MEMORY:/main.bas"), &merged)
+            .expect_file("MEMORY:/code.bas", code)
+            .expect_file("MEMORY:/lib.bas", library)
+            .check();
+    }
+
+    #[test]
+    fn test_merge_numbered() {
         Tester::default()
-            .run(format!(r#"{} "drive:""#, cmd))
-            .expect_err("1:1: Missing file name in path 'drive:'")
+            .write_file("lib.bas", "20 PRINT 2\n40 PRINT 4\n")
+            .set_program(None, "10 PRINT 1\n30 PRINT 3\n")
+            .run(r#"MERGE "lib.bas""#)
+            .expect_program(
+                None as Option<&str>,
+                "10 PRINT 1\n20 PRINT 2\n30 PRINT 3\n40 PRINT 4\n",
+            )
+            .expect_file("MEMORY:/lib.bas", "20 PRINT 2\n40 PRINT 4\n")
             .check();
     }
 
     #[test]
-    fn test_load_errors() {
-        check_load_save_common_errors("LOAD");
+    fn test_merge_marks_dirty() {
+        let mut t = Tester::default()
+            .write_file("lib.bas", "PRINT 2\n")
+            .set_program(Some("MEMORY:/main.bas"), "PRINT 1\n");
+        assert!(!t.get_program().borrow().is_dirty());
+        t.run(r#"MERGE "lib""#)
+            .expect_program(Some("MEMORY:/main.bas"), "PRINT 1\nPRINT 2\n")
+            .expect_file("MEMORY:/lib.bas", "PRINT 2\n")
+            .check();
+        assert!(t.get_program().borrow().is_dirty());
+    }
 
+    #[test]
+    fn test_merge_conflicts() {
+        let main = "SUB greet\n    PRINT \"main\"\nEND SUB\n";
+        let library = "' Library\nSUB Greet\n    PRINT \"lib\"\nEND SUB\n";
         Tester::default()
-            .run("LOAD")
-            .expect_compilation_err("1:1: LOAD expected filename$")
+            .write_file("lib.bas", library)
+            .set_program(Some("MEMORY:/main.bas"), main)
+            .run(r#"MERGE "lib""#)
+            .expect_err(
+                "1:1: SUB GREET at MEMORY:lib.bas:2:5 conflicts with SUB GREET at MEMORY:/main.bas:1:5",
+            )
+            .expect_program(Some("MEMORY:/main.bas"), main)
+            .expect_file("MEMORY:/lib.bas", library)
             .check();
+    }
 
-        check_stmt_err("1:1: Entry not found", r#"LOAD "missing-file""#);
+    #[test]
+    fn test_merge_errors() {
+        check_stmt_compilation_err("1:1: MERGE expected filename$", "MERGE");
+        check_stmt_compilation_err("1:7: expected STRING but found INTEGER", "MERGE 3");
+        check_stmt_err("1:1: Entry not found", r#"MERGE "missing-file""#);
 
+        let packaged = format!("{}\nPRINT 1\n", PACKAGE_HEADER);
         Tester::default()
-            .write_file("mismatched-extension.bat", "")
-            .run(r#"LOAD "mismatched-extension""#)
-            .expect_err("1:1: Entry not found")
-            .expect_file("MEMORY:/mismatched-extension.bat", "")
+            .write_file("lib.bas", &packaged)
+            .run(r#"MERGE "lib""#)
+            .expect_err("1:1: Cannot merge MEMORY:lib.bas because it is a packaged program")
+            .expect_file("MEMORY:/lib.bas", &packaged)
             .check();
     }
 
@@ -910,6 +2285,368 @@ mod tests {
         check_stmt_compilation_err("1:1: NEW expected no arguments", "NEW 10");
     }
 
+    /// Sample program to test packaging with.
+    const PACKAGE_INPUT: &str = "' Computes a square.
+FUNCTION square(n)
+    square = n * n ' Multiply.
+END FUNCTION
+
+total = square(3): IF total > 5 THEN PRINT \"Big:\"; total
+";
+
+    /// Expected output of packaging `PACKAGE_INPUT`.
+    const PACKAGE_OUTPUT: &str = "FUNCTION a(b)
+a=b*b
+END FUNCTION
+c=a(3):IF c>5 THEN PRINT \"Big:\";c
+";
+
+    #[test]
+    fn test_package_ok() {
+        let packaged = format!("{}\n{}", PACKAGE_HEADER, PACKAGE_OUTPUT);
+        Tester::default()
+            .write_file("square.bas", PACKAGE_INPUT)
+            .run(r#"PACKAGE "square", "packaged""#)
+            .expect_prints(["Packaged MEMORY:square.bas as MEMORY:packaged.bas"])
+            .expect_file("MEMORY:/square.bas", PACKAGE_INPUT)
+            .expect_file("MEMORY:/packaged.bas", &packaged)
+            .check();
+    }
+
+    #[test]
+    fn test_package_runs_identically() {
+        let mut t = Tester::default().set_program(Some("square.bas"), PACKAGE_INPUT);
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints(["Big: 9"])
+            .expect_var("total", 9)
+            .expect_program(Some("square.bas"), PACKAGE_INPUT)
+            .check();
+
+        let packaged = format!("{}\n{}", PACKAGE_HEADER, PACKAGE_OUTPUT);
+        let mut t = Tester::default().set_program(Some("packaged.bas"), &packaged);
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints(["Big: 9"])
+            .expect_var("c", 9)
+            .expect_program(Some("packaged.bas"), &packaged)
+            .check();
+    }
+
+    #[test]
+    fn test_package_refuses_list_and_edit() {
+        let packaged = format!("{}\n{}", PACKAGE_HEADER, PACKAGE_OUTPUT);
+        let mut t = Tester::default().set_program(Some("packaged.bas"), &packaged);
+        t.run("LIST")
+            .expect_err("1:1: Cannot LIST a packaged program")
+            .expect_program(Some("packaged.bas"), &packaged)
+            .check();
+        t.run("EDIT")
+            .expect_err("1:1: Cannot EDIT a packaged program")
+            .expect_program(Some("packaged.bas"), &packaged)
+            .check();
+    }
+
+    #[test]
+    fn test_package_reports_error_positions() {
+        let program = "' Fails on purpose.\n\nvalue = 3\nPRINT value / 0\n";
+        Tester::default()
+            .write_file("fails.bas", program)
+            .run(r#"PACKAGE "fails", "packaged": LOAD "packaged": RUN"#)
+            .expect_prints(["Packaged MEMORY:fails.bas as MEMORY:packaged.bas"])
+            .expect_clear()
+            .expect_clear()
+            .expect_err("3:8: Division by zero")
+            .expect_var("a", 3)
+            .expect_program(
+                Some("MEMORY:packaged.bas"),
+                format!("{}\na=3\nPRINT a/0\n", PACKAGE_HEADER),
+            )
+            .expect_file("MEMORY:/fails.bas", program)
+            .expect_file("MEMORY:/packaged.bas", format!("{}\na=3\nPRINT a/0\n", PACKAGE_HEADER))
+            .check();
+    }
+
+    #[test]
+    fn test_package_errors() {
+        check_stmt_compilation_err("1:1: PACKAGE expected src$, dest$", "PACKAGE");
+        check_stmt_compilation_err("1:1: PACKAGE expected src$, dest$", r#"PACKAGE "a""#);
+        check_stmt_compilation_err("1:9: expected STRING but found INTEGER", r#"PACKAGE 1, "a""#);
+
+        Tester::default()
+            .run(r#"PACKAGE "missing", "out""#)
+            .expect_err("1:1: Entry not found")
+            .check();
+
+        Tester::default()
+            .write_file("bad.bas", "a = 1.2.3")
+            .run(r#"PACKAGE "bad", "out""#)
+            .expect_err("1:1: 1:5: Too many dots in numeric literal")
+            .expect_file("MEMORY:/bad.bas", "a = 1.2.3")
+            .check();
+    }
+
+    #[test]
+    fn test_renum_defaults() {
+        let program = "1 PRINT \"a\"\n2 GOSUB 5: GOTO 9\n5 PRINT \"b\": RETURN\n9 END\n";
+        let renumbered = "10 PRINT \"a\"\n20 GOSUB 30: GOTO 40\n30 PRINT \"b\": RETURN\n40 END\n";
+        let mut t = Tester::default().set_program(Some("MEMORY:/main.bas"), program);
+        t.run("RENUM")
+            .expect_prints(["Renumbered 4 line(s)"])
+            .expect_program(Some("MEMORY:/main.bas"), renumbered)
+            .check();
+        assert!(t.get_program().borrow().is_dirty());
+    }
+
+    #[test]
+    fn test_renum_round_trip_runs_identically() {
+        let program = "' Sum and report\n\
+            ON ERROR GOTO 900\n\
+            100 i = 1: total = 0\n\
+            110 IF i > 4 THEN GOTO 150\n\
+            120 GOSUB 500: i = i + 1\n\
+            130 GOTO 110\n\
+            150 PRINT \"total\"; total\n\
+            160 a = 1 / 0\n\
+            170 END\n\
+            500 total = total + i: RETURN\n\
+            900 PRINT \"error\": END 2\n";
+        let renumbered = "' Sum and report\n\
+            ON ERROR GOTO 1056\n\
+            1000 i = 1: total = 0\n\
+            1007 IF i > 4 THEN GOTO 1028\n\
+            1014 GOSUB 1049: i = i + 1\n\
+            1021 GOTO 1007\n\
+            1028 PRINT \"total\"; total\n\
+            1035 a = 1 / 0\n\
+            1042 END\n\
+            1049 total = total + i: RETURN\n\
+            1056 PRINT \"error\": END 2\n";
+        Tester::default()
+            .set_program(None, program)
+            .run("RUN: RENUM 1000, 7: RUN")
+            .expect_clear()
+            .expect_prints(["total 10", "error", "Program ended with code 2"])
+            .expect_prints(["Renumbered 9 line(s)"])
+            .expect_clear()
+            .expect_prints(["total 10", "error", "Program ended with code 2"])
+            .expect_var("i", 5)
+            .expect_var("total", 10)
+            .expect_program(None as Option<&str>, renumbered)
+            .check();
+    }
+
+    #[test]
+    fn test_renum_refuses_unknown_references() {
+        let program = "10 PRINT 1\n20 GOTO 30\n";
+        Tester::default()
+            .set_program(None, program)
+            .run("RENUM")
+            .expect_err("1:1: 2:9: Reference to unknown line number 30")
+            .expect_program(None as Option<&str>, program)
+            .check();
+    }
+
+    #[test]
+    fn test_renum_errors() {
+        check_stmt_compilation_err("1:1: RENUM expected <> | <start%, step%>", "RENUM 10");
+        check_stmt_compilation_err("1:7: STRING is not a number", r#"RENUM "a", 1"#);
+        check_stmt_err("1:1: Line number step 0 must be positive", "RENUM 10, 0");
+
+        let packaged = format!("{}\n10 a=1\n", PACKAGE_HEADER);
+        Tester::default()
+            .set_program(None, &packaged)
+            .run("RENUM")
+            .expect_err("1:1: Cannot RENUM a packaged program")
+            .expect_program(None as Option<&str>, &packaged)
+            .check();
+    }
+
+    #[test]
+    fn test_refactor_rename_global() {
+        let program = "' Totals\ncount = 0\nFUNCTION count_up(count)\n    count_up = count + 1\nEND FUNCTION\ncount = count_up(count)  ' Bump\nPRINT count\n";
+        let renamed = "' Totals\ntally = 0\nFUNCTION count_up(count)\n    count_up = count + 1\nEND FUNCTION\ntally = count_up(tally)  ' Bump\nPRINT tally\n";
+        let mut t = Tester::default().set_program(Some("MEMORY:/main.bas"), program);
+        t.run(r#"REFACTOR "rename", "count", "tally""#)
+            .expect_prints(["Renamed 4 reference(s) to tally"])
+            .expect_program(Some("MEMORY:/main.bas"), renamed)
+            .check();
+        assert!(t.get_program().borrow().is_dirty());
+    }
+
+    #[test]
+    fn test_refactor_rename_local() {
+        let program =
+            "i = 5\nSUB show(n)\n    FOR i = 1 TO n\n        PRINT i\n    NEXT\nEND SUB\nshow i\n";
+        let renamed =
+            "i = 5\nSUB show(n)\n    FOR j = 1 TO n\n        PRINT j\n    NEXT\nEND SUB\nshow i\n";
+        Tester::default()
+            .set_program(None, program)
+            .run(r#"REFACTOR "RENAME", "show.i", "j": RUN"#)
+            .expect_prints(["Renamed 2 reference(s) to j"])
+            .expect_clear()
+            .expect_prints([" 1", " 2", " 3", " 4", " 5"])
+            .expect_var("i", 5)
+            .expect_program(None as Option<&str>, renamed)
+            .check();
+    }
+
+    #[test]
+    fn test_refactor_rename_refusals() {
+        let program = "a = 1\nb = 2\nPRINT a + b\n";
+        Tester::default()
+            .set_program(None, program)
+            .run(r#"REFACTOR "RENAME", "a", "b""#)
+            .expect_err("1:1: Cannot rename to 'b' because it is already in use")
+            .expect_program(None as Option<&str>, program)
+            .check();
+
+        Tester::default()
+            .set_program(None, program)
+            .run(r#"REFACTOR "RENAME", "a", "loop""#)
+            .expect_err("1:1: Cannot rename to 'loop' because it is a keyword")
+            .expect_program(None as Option<&str>, program)
+            .check();
+
+        Tester::default()
+            .set_program(None, program)
+            .run(r#"REFACTOR "RENAME", "a", "Len""#)
+            .expect_err("1:1: Cannot rename to 'Len' because it is a built-in name")
+            .expect_program(None as Option<&str>, program)
+            .check();
+    }
+
+    #[test]
+    fn test_refactor_errors() {
+        check_stmt_compilation_err("1:1: REFACTOR expected action$, old$, new$", "REFACTOR");
+        check_stmt_compilation_err(
+            "1:1: REFACTOR expected action$, old$, new$",
+            r#"REFACTOR "RENAME", "a""#,
+        );
+        check_stmt_compilation_err(
+            "1:20: expected STRING but found INTEGER",
+            r#"REFACTOR "RENAME", 1, "b""#,
+        );
+        check_stmt_err("1:10: Unknown refactoring action 'MOVE'", r#"REFACTOR "MOVE", "a", "b""#);
+        check_stmt_err("1:1: Symbol 'a' not found", r#"REFACTOR "RENAME", "a", "b""#);
+
+        let packaged = format!("{}\na=1\n", PACKAGE_HEADER);
+        Tester::default()
+            .set_program(None, &packaged)
+            .run(r#"REFACTOR "RENAME", "a", "b""#)
+            .expect_err("1:1: Cannot REFACTOR a packaged program")
+            .expect_program(None as Option<&str>, &packaged)
+            .check();
+    }
+
+    /// Creates a compilation cache that holds a bogus empty image for `text`.
+    fn fake_compilation_cache(text: &str) -> CompilationCache {
+        let image = Image { instrs: vec![], data: vec![], callables: HashMap::default() };
+        CompilationCache {
+            hash: CompilationCache::hash_text(text),
+            text: text.to_owned(),
+            image: Some(image),
+        }
+    }
+
+    #[test]
+    fn test_compilation_cache_hit() {
+        let mut cache = fake_compilation_cache("a = 1");
+        let image = cache.get("a = 1", &Symbols::default()).unwrap();
+        assert!(image.instrs.is_empty());
+    }
+
+    #[test]
+    fn test_compilation_cache_miss() {
+        let mut cache = fake_compilation_cache("a = 1");
+        let image = cache.get("a = 2", &Symbols::default()).unwrap();
+        assert!(!image.instrs.is_empty());
+        assert_eq!("a = 2", cache.text);
+        assert_eq!(CompilationCache::hash_text("a = 2"), cache.hash);
+
+        let again = cache.get("a = 2", &Symbols::default()).unwrap();
+        assert_eq!(image.instrs.len(), again.instrs.len());
+    }
+
+    #[test]
+    fn test_compilation_cache_errors_are_not_cached() {
+        let mut cache = fake_compilation_cache("a = 1");
+        assert!(cache.get("a = ", &Symbols::default()).is_err());
+        assert!(cache.image.is_none());
+        assert!(cache.get("a = ", &Symbols::default()).is_err());
+
+        let image = cache.get("a = 1", &Symbols::default()).unwrap();
+        assert!(!image.instrs.is_empty());
+    }
+
+    #[test]
+    fn test_run_cached_matches_uncached() {
+        let program =
+            "DIM a(3)\nFOR i = 0 TO 2: a(i) = i * 2: PRINT a(i): NEXT\nDATA 1, 2\nREAD x: PRINT x";
+        let mut t = Tester::default().set_program(Some("untouched.bas"), program);
+        let uncached = flatten_output(t.run("RUN").take_captured_out());
+        let cached = flatten_output(t.run("RUN").take_captured_out());
+        assert_eq!(uncached, cached);
+    }
+
+    #[test]
+    fn test_run_after_program_changes() {
+        let mut t = Tester::default().write_file("other.bas", "PRINT 2");
+        t.run("RUN").expect_clear().expect_file("MEMORY:/other.bas", "PRINT 2").check();
+
+        t.get_program().borrow_mut().load(Some("first.bas"), "PRINT 1");
+        t.run("RUN")
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_program(Some("first.bas"), "PRINT 1")
+            .expect_file("MEMORY:/other.bas", "PRINT 2")
+            .check();
+        t.run(r#"LOAD "other": RUN"#)
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 2"])
+            .expect_program(Some("MEMORY:other.bas"), "PRINT 2")
+            .expect_file("MEMORY:/other.bas", "PRINT 2")
+            .check();
+    }
+
+    #[test]
+    fn test_run_after_new() {
+        let mut t = Tester::default().set_program(None, "PRINT 1");
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_program(None as Option<&str>, "PRINT 1")
+            .check();
+        t.run("NEW: RUN")
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_clear()
+            .expect_clear()
+            .check();
+    }
+
+    #[test]
+    fn test_run_after_edit() {
+        let mut t = Tester::default().add_input_chars("PRINT 3\n").set_program(None, "PRINT 1\n");
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_program(None as Option<&str>, "PRINT 1\n")
+            .check();
+        t.run("EDIT: RUN")
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_clear()
+            .expect_prints([" 1", " 3"])
+            .expect_program(None as Option<&str>, "PRINT 1\nPRINT 3\n")
+            .check();
+    }
+
     #[test]
     fn test_run_nothing() {
         Tester::default().run("RUN").expect_clear().check();
@@ -932,6 +2669,24 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_run_resets_static_variables() {
+        let program = "SUB s\nSTATIC n\nn = n + 1\nPRINT n\nEND SUB\ns: s";
+        let mut t = Tester::default().set_program(Some("untouched.bas"), program);
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints([" 1", " 2"])
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+        t.run("RUN")
+            .expect_clear()
+            .expect_prints([" 1", " 2"])
+            .expect_clear()
+            .expect_prints([" 1", " 2"])
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+    }
+
     #[test]
     fn test_run_something_that_exits() {
         let program = "PRINT 5: END 1: PRINT 4";
@@ -939,14 +2694,131 @@ mod tests {
             .set_program(Some("untouched.bas"), program)
             .run(r#"RUN: PRINT "after""#)
             .expect_clear()
-            .expect_prints([" 5", "Program exited with code 1", "after"])
+            .expect_prints([" 5", "Program ended with code 1", "after"])
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_end_codes() {
+        let program = "PRINT 5: END 0: PRINT 4";
+        Tester::default()
+            .set_program(Some("untouched.bas"), program)
+            .run(r#"RUN: PRINT "after""#)
+            .expect_clear()
+            .expect_prints([" 5", "after"])
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+
+        let program = "FUNCTION f\n END 3\nEND FUNCTION\nGOSUB @sub\n@sub: a = f: RETURN";
+        Tester::default()
+            .set_program(Some("untouched.bas"), program)
+            .run(r#"RUN: PRINT "after""#)
+            .expect_clear()
+            .expect_prints(["Program ended with code 3", "after"])
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_poisoned_machine() {
+        let program = "PRINT 5";
+        let mut t = Tester::default().set_program(Some("untouched.bas"), program);
+        t.get_machine().poison();
+        t.run("RUN")
+            .expect_err(
+                "1:1: Cannot run programs after an internal error; save your work and restart",
+            )
+            .expect_program(Some("untouched.bas"), program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_sets_program_name_as_only_arg() {
+        let program = "PRINT ARGC: PRINT ARG(0)";
+        Tester::default()
+            .set_program(Some("untouched.bas"), program)
+            .run("RUN")
+            .expect_clear()
+            .expect_prints([" 1", "untouched.bas"])
             .expect_program(Some("untouched.bas"), program)
             .check();
     }
 
+    #[test]
+    fn test_run_file_with_args() {
+        let program = "FOR i = 0 TO ARGC - 1: PRINT i; ARG(i): NEXT";
+        Tester::default()
+            .write_file("script.bas", program)
+            .run(r#"RUN "script", "foo", "42""#)
+            .expect_clear()
+            .expect_var("i", 3)
+            .expect_prints([" 0 MEMORY:script.bas", " 1 foo", " 2 42"])
+            .expect_program(Some("MEMORY:script.bas"), program)
+            .expect_file("MEMORY:/script.bas", program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_file_without_args() {
+        let program = "PRINT ARGC";
+        Tester::default()
+            .write_file("script.bas", program)
+            .run(r#"RUN "script.bas""#)
+            .expect_clear()
+            .expect_prints([" 1"])
+            .expect_program(Some("MEMORY:script.bas"), program)
+            .expect_file("MEMORY:/script.bas", program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_args_survive_clear() {
+        let program = "CLEAR: PRINT ARGC; ARG(1)";
+        let mut t = Tester::default().write_file("script.bas", program);
+        t.run(r#"RUN "script", "foo""#)
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 2 foo"])
+            .expect_program(Some("MEMORY:script.bas"), program)
+            .expect_file("MEMORY:/script.bas", program)
+            .check();
+        t.run("CLEAR: PRINT ARGC; ARG(1)")
+            .expect_clear()
+            .expect_clear()
+            .expect_prints([" 2 foo"])
+            .expect_clear()
+            .expect_prints([" 2 foo"])
+            .expect_program(Some("MEMORY:script.bas"), program)
+            .expect_file("MEMORY:/script.bas", program)
+            .check();
+    }
+
+    #[test]
+    fn test_run_file_dirty_abort() {
+        Tester::default()
+            .add_input_chars("modified unnamed file\n")
+            .add_input_chars("n\n")
+            .write_file("other.bas", "PRINT 3")
+            .run(r#"EDIT: RUN "other""#)
+            .expect_program(None as Option<&str>, "modified unnamed file\n")
+            .expect_prints([
+                "Current program has unsaved changes and has never been saved!",
+                "RUN aborted; use SAVE to save your current changes.",
+            ])
+            .expect_file("MEMORY:/other.bas", "PRINT 3")
+            .check();
+    }
+
     #[test]
     fn test_run_errors() {
-        check_stmt_compilation_err("1:1: RUN expected no arguments", "RUN 10");
+        check_stmt_compilation_err(
+            "1:1: RUN expected <> | <filename$> | <filename$, arg1$[, .., argN$]>",
+            r#"RUN "a"; "b""#,
+        );
+        check_stmt_compilation_err("1:5: expected STRING but found INTEGER", "RUN 10");
+        check_stmt_compilation_err("1:10: expected STRING but found INTEGER", r#"RUN "a", 3"#);
+        check_stmt_err("1:1: Entry not found", r#"RUN "missing""#);
     }
 
     #[test]
@@ -980,6 +2852,18 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_save_mixed_case_overwrites() {
+        Tester::default()
+            .write_file("game.bas", "old\n")
+            .set_program(Some("before.bas"), "new\n")
+            .run(r#"SAVE "GAME.BAS""#)
+            .expect_program(Some("MEMORY:GAME.BAS"), "new\n")
+            .expect_prints(["Saved as MEMORY:GAME.BAS"])
+            .expect_file("MEMORY:/game.bas", "new\n")
+            .check();
+    }
+
     #[test]
     fn test_save_unnamed_error() {
         Tester::default()
@@ -999,4 +2883,77 @@ mod tests {
             .expect_compilation_err("1:1: SAVE expected <> | <filename$>")
             .check();
     }
+
+    #[test]
+    fn test_stats_empty() {
+        Tester::default()
+            .run("STATS")
+            .expect_prints([
+                "",
+                "    Lines                0",
+                "    Statements           0",
+                "    Comment lines        0 (0%)",
+                "    Variables            0",
+                "    Arrays               0",
+                "    SUBs                 0",
+                "    FUNCTIONs            0",
+                "    Deepest nesting      0",
+                "    Longest procedure    none",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_stats_ok() {
+        let program = "' Prints a few squares.
+DIM squares(5) AS INTEGER
+
+FUNCTION square(n%)
+    square = n * n
+END FUNCTION
+
+' Fill in the table and print the odd squares.
+FOR i = 0 TO 4
+    squares(i) = square(i)
+    IF squares(i) MOD 2 = 1 THEN
+        PRINT squares(i)
+    END IF
+NEXT
+count = 5: PRINT count
+";
+        Tester::default()
+            .set_program(Some("squares.bas"), program)
+            .run("STATS")
+            .expect_prints([
+                "",
+                "    Lines                15",
+                "    Statements           9",
+                "    Comment lines        2 (15%)",
+                "    Variables            3",
+                "    Arrays               1",
+                "    SUBs                 0",
+                "    FUNCTIONs            1",
+                "    Deepest nesting      2",
+                "    Longest procedure    SQUARE (3 lines)",
+                "",
+            ])
+            .expect_program(Some("squares.bas"), program)
+            .check();
+    }
+
+    #[test]
+    fn test_stats_code_errors() {
+        Tester::default()
+            .set_program(None, "PRINT 1\nIF a THEN\n")
+            .run("STATS")
+            .expect_err("2:1: IF without END IF")
+            .expect_program(None as Option<&str>, "PRINT 1\nIF a THEN\n")
+            .check();
+    }
+
+    #[test]
+    fn test_stats_errors() {
+        check_stmt_compilation_err("1:1: STATS expected no arguments", "STATS 2");
+    }
 }
@@ -0,0 +1,24 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! PWM abstractions for EndBASIC.
+
+use std::io;
+
+/// A trait abstracting access to a PWM channel.
+pub trait PwmChannel {
+    /// Sets the duty cycle of the channel to `duty`, which is a fraction between 0.0 and 1.0.
+    fn set_duty_cycle(&mut self, duty: f64) -> io::Result<()>;
+}
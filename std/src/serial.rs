@@ -0,0 +1,922 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Serial port access for EndBASIC.
+
+use crate::exec::{interruptible, seconds_to_duration};
+use crate::gpio::CATEGORY;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// Default number of bytes that `SERIALREAD` returns at most.
+pub const DEFAULT_READ_LENGTH: usize = 4096;
+
+/// Maximum number of bytes that `SERIALREAD` can return at once and maximum length of the lines
+/// that `SERIALREADLINE` returns.
+pub const MAX_READ_LENGTH: usize = 1024 * 1024;
+
+/// Parity checking mode of a serial port.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parity {
+    /// No parity bit.
+    None,
+
+    /// Even parity.
+    Even,
+
+    /// Odd parity.
+    Odd,
+}
+
+impl Parity {
+    /// Parses the parity `s` given at `pos`.
+    fn parse(s: &str, pos: LineCol) -> Result<Self> {
+        match s.to_ascii_uppercase().as_str() {
+            "N" | "NONE" => Ok(Parity::None),
+            "E" | "EVEN" => Ok(Parity::Even),
+            "O" | "ODD" => Ok(Parity::Odd),
+            _ => Err(Error::SyntaxError(pos, format!("Invalid parity {}", s))),
+        }
+    }
+}
+
+/// Configuration of a serial port.  Data is always transferred in 8-bit characters.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SerialConfig {
+    /// Speed of the port in bits per second.
+    pub baud: u32,
+
+    /// Parity checking mode.
+    pub parity: Parity,
+
+    /// Number of stop bits, which is either 1 or 2.
+    pub stop_bits: u8,
+}
+
+/// Abstract interface to an open serial port.
+///
+/// The port must be closed when this object is dropped.
+#[async_trait(?Send)]
+pub trait SerialPort {
+    /// Writes all of `data` to the port.
+    async fn write(&mut self, data: &[u8]) -> io::Result<()>;
+
+    /// Returns up to `max_length` bytes received by the port, waiting for at most `timeout` for
+    /// them to arrive if there are none.  Waits forever if `timeout` is `None` and does not wait at
+    /// all if `timeout` is zero.
+    ///
+    /// Returns an empty vector if no data arrived before the timeout expired.  Implementations
+    /// should report that the device disappeared with `io::ErrorKind::BrokenPipe`.
+    async fn read(&mut self, max_length: usize, timeout: Option<Duration>) -> io::Result<Vec<u8>>;
+}
+
+/// Abstract interface to open the serial ports of the machine.
+pub trait SerialPorts {
+    /// Opens the serial port backed by `device` with the given `config`.
+    fn open(&self, device: &str, config: SerialConfig) -> io::Result<Box<dyn SerialPort>>;
+}
+
+/// An open serial port and the data received from it that programs have not consumed yet.
+struct OpenPort {
+    /// The port to talk to the device.
+    port: Box<dyn SerialPort>,
+
+    /// Name of the device backing the port, for error reporting purposes.
+    device: String,
+
+    /// Data received from the port that has not been returned to the program yet.
+    pending: Vec<u8>,
+}
+
+impl OpenPort {
+    /// Returns the next line of text, without its line terminator, waiting for the device to send
+    /// it.  If `timeout` is not `None`, gives up when no data arrives for that long and returns the
+    /// partial line received so far.
+    async fn read_line(&mut self, timeout: Option<Duration>) -> io::Result<Vec<u8>> {
+        let mut searched = 0;
+        loop {
+            let newline = self.pending[searched..].iter().position(|b| *b == b'\n');
+            let length = newline.map(|i| searched + i).unwrap_or(self.pending.len());
+            if length > MAX_READ_LENGTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Line exceeds the maximum of {} bytes", MAX_READ_LENGTH),
+                ));
+            }
+
+            if newline.is_some() {
+                let mut line: Vec<u8> = self.pending.drain(..length + 1).collect();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return Ok(line);
+            }
+            searched = length;
+
+            let data = self.port.read(DEFAULT_READ_LENGTH, timeout).await?;
+            if data.is_empty() {
+                return Ok(self.pending.split_off(0));
+            }
+            self.pending.extend_from_slice(&data);
+        }
+    }
+
+    /// Returns up to `max_length` bytes of data that have already been received.
+    async fn read_available(&mut self, max_length: usize) -> io::Result<Vec<u8>> {
+        if self.pending.is_empty() {
+            return self.port.read(max_length, Some(Duration::ZERO)).await;
+        }
+        let n = std::cmp::min(max_length, self.pending.len());
+        Ok(self.pending.drain(..n).collect())
+    }
+}
+
+/// State shared by all the callables in this module.
+struct Serial {
+    /// The factory to open serial ports with, or `None` if serial ports are not supported.
+    ports: Option<Rc<dyn SerialPorts>>,
+
+    /// The currently-open port, if any.
+    open: RefCell<Option<OpenPort>>,
+}
+
+/// Returns the error raised when a callable needs an open port but there is none.
+fn not_open_error(scope: &Scope<'_>) -> Error {
+    scope.internal_error("No serial port is open")
+}
+
+/// Converts the I/O error `e` raised by the port in `open` into an error for `scope`.  If the error
+/// indicates that the device disappeared, the port is closed.
+fn port_error(open: &mut Option<OpenPort>, scope: &Scope<'_>, e: io::Error) -> Error {
+    let device = match open.as_ref() {
+        Some(open) => open.device.clone(),
+        None => return scope.io_error(e),
+    };
+    let message = if e.kind() == io::ErrorKind::BrokenPipe {
+        *open = None;
+        format!("Serial port {} disconnected: {}", device, e)
+    } else {
+        format!("Serial port {} failed: {}", device, e)
+    };
+    scope.io_error(io::Error::new(e.kind(), message))
+}
+
+/// Closes the open serial port, if any, when the machine is cleared.
+struct SerialClearable(Rc<Serial>);
+
+impl Clearable for SerialClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        *self.0.open.borrow_mut() = None;
+    }
+}
+
+/// The `SERIALCLOSE` command.
+pub struct SerialcloseCommand {
+    metadata: CallableMetadata,
+    serial: Rc<Serial>,
+}
+
+impl SerialcloseCommand {
+    /// Creates a new instance of the command.
+    fn new(serial: Rc<Serial>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SERIALCLOSE")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Closes the open serial port.
+Any data received from the port that was not read yet is discarded.",
+                )
+                .build(),
+            serial,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SerialcloseCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        match self.serial.open.borrow_mut().take() {
+            Some(_open) => Ok(()),
+            None => Err(not_open_error(&scope)),
+        }
+    }
+}
+
+/// The `SERIALOPEN` command.
+pub struct SerialopenCommand {
+    metadata: CallableMetadata,
+    serial: Rc<Serial>,
+}
+
+impl SerialopenCommand {
+    /// Creates a new instance of the command.
+    fn new(serial: Rc<Serial>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SERIALOPEN")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("device"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("baud"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("device"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("baud"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("parity"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("device"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("baud"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("parity"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("stopbits"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Opens a serial port.
+The device$ names the serial port to open, such as /dev/ttyUSB0, and baud% specifies its speed \
+in bits per second.  Data is transferred in 8-bit characters.  The parity$ can be N for none, E \
+for even or O for odd, and defaults to N.  The stopbits% can be 1 or 2, and defaults to 1.
+Only one serial port can be open at any given time: use SERIALCLOSE to close the open port \
+before opening a different one.
+Serial ports are only available on desktop systems.",
+                )
+                .build(),
+            serial,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SerialopenCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=4).contains(&scope.nargs()));
+        let (device, device_pos) = scope.pop_string_with_pos();
+        let baud = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            match u32::try_from(i) {
+                Ok(baud) if baud > 0 => baud,
+                _ => return Err(Error::SyntaxError(pos, format!("Invalid baud rate {}", i))),
+            }
+        };
+        let parity = if scope.nargs() > 0 {
+            let (s, pos) = scope.pop_string_with_pos();
+            Parity::parse(&s, pos)?
+        } else {
+            Parity::None
+        };
+        let stop_bits = if scope.nargs() > 0 {
+            let (i, pos) = scope.pop_integer_with_pos();
+            match i {
+                1 | 2 => i as u8,
+                _ => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("Invalid number of stop bits {}", i),
+                    ))
+                }
+            }
+        } else {
+            1
+        };
+
+        let ports = match self.serial.ports.as_ref() {
+            Some(ports) => ports,
+            None => {
+                return Err(scope.internal_error("Serial ports are not supported on this platform"))
+            }
+        };
+
+        let mut open = self.serial.open.borrow_mut();
+        if let Some(open) = open.as_ref() {
+            return Err(Error::SyntaxError(
+                device_pos,
+                format!("Serial port {} is already open", open.device),
+            ));
+        }
+
+        let config = SerialConfig { baud, parity, stop_bits };
+        let port = ports.open(&device, config).map_err(|e| {
+            scope.io_error(io::Error::new(
+                e.kind(),
+                format!("Cannot open serial port {}: {}", device, e),
+            ))
+        })?;
+        *open = Some(OpenPort { port, device, pending: vec![] });
+        Ok(())
+    }
+}
+
+/// The `SERIALREAD` function.
+pub struct SerialreadFunction {
+    metadata: CallableMetadata,
+    serial: Rc<Serial>,
+}
+
+impl SerialreadFunction {
+    /// Creates a new instance of the function.
+    fn new(serial: Rc<Serial>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SERIALREAD")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("maxbytes"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Reads the data received by the open serial port.
+Returns the data that has arrived so far without waiting for more, which means that the result is \
+empty if nothing has arrived.  Returns at most maxbytes% bytes, which must be between 1 and \
+1048576 and defaults to 4096.",
+                )
+                .build(),
+            serial,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SerialreadFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((0..=1).contains(&scope.nargs()));
+        let max_length = if scope.nargs() > 0 {
+            let (n, pos) = scope.pop_integer_with_pos();
+            match usize::try_from(n) {
+                Ok(n) if n > 0 && n <= MAX_READ_LENGTH => n,
+                _ => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("maxbytes% must be between 1 and {}", MAX_READ_LENGTH),
+                    ))
+                }
+            }
+        } else {
+            DEFAULT_READ_LENGTH
+        };
+
+        let mut open = self.serial.open.borrow_mut();
+        let port = match open.as_mut() {
+            Some(port) => port,
+            None => return Err(not_open_error(&scope)),
+        };
+        match port.read_available(max_length).await {
+            Ok(data) => scope.return_string(String::from_utf8_lossy(&data).into_owned()),
+            Err(e) => Err(port_error(&mut open, &scope, e)),
+        }
+    }
+}
+
+/// The `SERIALREADLINE` function.
+pub struct SerialreadlineFunction {
+    metadata: CallableMetadata,
+    serial: Rc<Serial>,
+}
+
+impl SerialreadlineFunction {
+    /// Creates a new instance of the function.
+    fn new(serial: Rc<Serial>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SERIALREADLINE")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("timeout"),
+                                vtype: ExprType::Double,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Reads a line of text from the open serial port.
+Waits until the device sends a full line and returns it without its line terminator, which can be \
+either LF or CRLF.  If timeout# is given, gives up waiting when no data arrives for timeout# \
+seconds and returns the partial line received so far, which may be empty.  If no timeout# is \
+given, waits forever.  Pressing CTRL+C interrupts the wait and stops the program.
+Lines cannot be longer than 1 MB.",
+                )
+                .build(),
+            serial,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SerialreadlineFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert!((0..=1).contains(&scope.nargs()));
+        let timeout = if scope.nargs() > 0 {
+            let (n, pos) = scope.pop_double_with_pos();
+            Some(seconds_to_duration(n, "Timeout", pos)?)
+        } else {
+            None
+        };
+
+        let mut open = self.serial.open.borrow_mut();
+        let port = match open.as_mut() {
+            Some(port) => port,
+            None => return Err(not_open_error(&scope)),
+        };
+        match interruptible(machine, port.read_line(timeout)).await {
+            Some(Ok(line)) => scope.return_string(String::from_utf8_lossy(&line).into_owned()),
+            Some(Err(e)) => Err(port_error(&mut open, &scope, e)),
+            None => scope.return_string(""),
+        }
+    }
+}
+
+/// The `SERIALWRITE` command.
+pub struct SerialwriteCommand {
+    metadata: CallableMetadata,
+    serial: Rc<Serial>,
+}
+
+impl SerialwriteCommand {
+    /// Creates a new instance of the command.
+    fn new(serial: Rc<Serial>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SERIALWRITE")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("data"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Writes data to the open serial port.
+The data$ is sent as is: no line terminator is appended to it, so protocols that work with lines \
+of text need to terminate them explicitly, for example with CHR(10).",
+                )
+                .build(),
+            serial,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SerialwriteCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let data = scope.pop_string();
+
+        let mut open = self.serial.open.borrow_mut();
+        let port = match open.as_mut() {
+            Some(port) => port,
+            None => return Err(not_open_error(&scope)),
+        };
+        match interruptible(machine, port.port.write(data.as_bytes())).await {
+            Some(Ok(())) | None => Ok(()),
+            Some(Err(e)) => Err(port_error(&mut open, &scope, e)),
+        }
+    }
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+///
+/// Serial ports are opened via `ports`.  If `ports` is `None`, the callables are still available but
+/// fail when used.
+pub fn add_all(machine: &mut Machine, ports: Option<Rc<dyn SerialPorts>>) {
+    let serial = Rc::from(Serial { ports, open: RefCell::from(None) });
+    machine.add_clearable(Box::from(SerialClearable(serial.clone())));
+    machine.add_callable(SerialcloseCommand::new(serial.clone()));
+    machine.add_callable(SerialopenCommand::new(serial.clone()));
+    machine.add_callable(SerialreadFunction::new(serial.clone()));
+    machine.add_callable(SerialreadlineFunction::new(serial.clone()));
+    machine.add_callable(SerialwriteCommand::new(serial));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use async_channel::Sender;
+    use endbasic_core::exec::{Signal, StopReason};
+    use futures_lite::future;
+    use std::collections::VecDeque;
+
+    /// State of the device behind a `LoopbackPort`.
+    #[derive(Default)]
+    struct LoopbackDevice {
+        /// Data pending to be read from the port, split in the packets in which it arrived.
+        incoming: VecDeque<Vec<u8>>,
+
+        /// Whether the device was unplugged, which causes all operations to fail.
+        unplugged: bool,
+
+        /// Whether the program closed the port.
+        closed: bool,
+
+        /// Timeouts requested by all the reads, in order.
+        timeouts: Vec<Option<Duration>>,
+    }
+
+    /// Serial port that receives back all data written to it.
+    struct LoopbackPort {
+        device: Rc<RefCell<LoopbackDevice>>,
+        signals_tx: Sender<Signal>,
+    }
+
+    impl Drop for LoopbackPort {
+        fn drop(&mut self) {
+            self.device.borrow_mut().closed = true;
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl SerialPort for LoopbackPort {
+        async fn write(&mut self, data: &[u8]) -> io::Result<()> {
+            let mut device = self.device.borrow_mut();
+            if device.unplugged {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "Device is gone"));
+            }
+            device.incoming.push_back(data.to_vec());
+            Ok(())
+        }
+
+        async fn read(
+            &mut self,
+            max_length: usize,
+            timeout: Option<Duration>,
+        ) -> io::Result<Vec<u8>> {
+            let mut device = self.device.borrow_mut();
+            device.timeouts.push(timeout);
+            if device.unplugged {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "Device is gone"));
+            }
+            match device.incoming.pop_front() {
+                Some(mut packet) => {
+                    if packet.len() > max_length {
+                        device.incoming.push_front(packet.split_off(max_length));
+                    }
+                    Ok(packet)
+                }
+                None if timeout.is_none() => {
+                    drop(device);
+                    self.signals_tx.try_send(Signal::Break).unwrap();
+                    future::pending().await
+                }
+                None => Ok(vec![]),
+            }
+        }
+    }
+
+    /// Factory of loopback ports that records how they were opened.
+    struct LoopbackPorts {
+        device: Rc<RefCell<LoopbackDevice>>,
+        opened: RefCell<Vec<(String, SerialConfig)>>,
+        signals_tx: Sender<Signal>,
+    }
+
+    impl SerialPorts for LoopbackPorts {
+        fn open(&self, device: &str, config: SerialConfig) -> io::Result<Box<dyn SerialPort>> {
+            if device == "missing" {
+                return Err(io::Error::new(io::ErrorKind::NotFound, "No such device"));
+            }
+            self.opened.borrow_mut().push((device.to_owned(), config));
+            let mut state = self.device.borrow_mut();
+            state.closed = false;
+            state.unplugged = false;
+            Ok(Box::from(LoopbackPort {
+                device: self.device.clone(),
+                signals_tx: self.signals_tx.clone(),
+            }))
+        }
+    }
+
+    /// Creates a tester backed by loopback serial ports.
+    fn tester_with_loopback() -> (Tester, Rc<LoopbackPorts>) {
+        let mut t = Tester::empty();
+        crate::strings::add_all(t.get_machine());
+        let ports = Rc::from(LoopbackPorts {
+            device: Rc::from(RefCell::from(LoopbackDevice::default())),
+            opened: RefCell::from(vec![]),
+            signals_tx: t.get_machine().get_signals_tx(),
+        });
+        add_all(t.get_machine(), Some(ports.clone()));
+        (t, ports)
+    }
+
+    #[test]
+    fn test_serialopen_config() {
+        let (mut t, ports) = tester_with_loopback();
+        t.run(
+            r#"SERIALOPEN "/dev/ttyUSB0", 9600: SERIALCLOSE
+            SERIALOPEN "/dev/ttyUSB1", 115200, "e": SERIALCLOSE
+            SERIALOPEN "COM3", 300, "Odd", 2"#,
+        )
+        .check();
+        assert_eq!(
+            vec![
+                (
+                    "/dev/ttyUSB0".to_owned(),
+                    SerialConfig { baud: 9600, parity: Parity::None, stop_bits: 1 }
+                ),
+                (
+                    "/dev/ttyUSB1".to_owned(),
+                    SerialConfig { baud: 115200, parity: Parity::Even, stop_bits: 1 }
+                ),
+                ("COM3".to_owned(), SerialConfig { baud: 300, parity: Parity::Odd, stop_bits: 2 }),
+            ],
+            *ports.opened.borrow()
+        );
+        assert!(!ports.device.borrow().closed);
+    }
+
+    #[test]
+    fn test_serialopen_already_open() {
+        let (mut t, ports) = tester_with_loopback();
+        t.run(r#"SERIALOPEN "first", 9600: SERIALOPEN "second", 9600"#)
+            .expect_err("1:38: Serial port first is already open")
+            .check();
+        assert_eq!(1, ports.opened.borrow().len());
+        assert!(!ports.device.borrow().closed);
+    }
+
+    #[test]
+    fn test_serialopen_errors() {
+        let (mut t, _ports) = tester_with_loopback();
+        t.run(r#"SERIALOPEN "missing", 9600"#)
+            .expect_err("1:1: Cannot open serial port missing: No such device")
+            .check();
+        t.run(r#"SERIALOPEN "dev", 0"#).expect_err("1:19: Invalid baud rate 0").check();
+        t.run(r#"SERIALOPEN "dev", -5"#).expect_err("1:19: Invalid baud rate -5").check();
+        t.run(r#"SERIALOPEN "dev", 9600, "X""#).expect_err("1:25: Invalid parity X").check();
+        t.run(r#"SERIALOPEN "dev", 9600, "N", 3"#)
+            .expect_err("1:30: Invalid number of stop bits 3")
+            .check();
+
+        check_stmt_compilation_err(
+            "1:1: SERIALOPEN expected <device$, baud%> | <device$, baud%, parity$> | \
+             <device$, baud%, parity$, stopbits%>",
+            r#"SERIALOPEN "dev""#,
+        );
+        check_stmt_compilation_err("1:19: STRING is not a number", r#"SERIALOPEN "dev", "9600""#);
+    }
+
+    #[test]
+    fn test_loopback_framing() {
+        let (mut t, ports) = tester_with_loopback();
+        t.run(
+            r#"SERIALOPEN "dev", 9600
+            SERIALWRITE "he"
+            SERIALWRITE "llo" + CHR(13) + CHR(10) + "wor"
+            SERIALWRITE "ld" + CHR(10) + CHR(10) + "rest"
+            a = SERIALREADLINE()
+            b = SERIALREADLINE(1)
+            c = SERIALREADLINE()
+            d = SERIALREAD(2)
+            e = SERIALREAD()
+            f = SERIALREAD()"#,
+        )
+        .expect_var("a", "hello")
+        .expect_var("b", "world")
+        .expect_var("c", "")
+        .expect_var("d", "re")
+        .expect_var("e", "st")
+        .expect_var("f", "")
+        .check();
+        let timeouts = ports.device.borrow().timeouts.clone();
+        assert_eq!(vec![None, None, Some(Duration::from_secs(1)), Some(Duration::ZERO)], timeouts);
+    }
+
+    #[test]
+    fn test_serialreadline_timeout_returns_partial_data() {
+        let (mut t, _ports) = tester_with_loopback();
+        t.run(
+            r#"SERIALOPEN "dev", 9600
+            SERIALWRITE "par": SERIALWRITE "tial"
+            a = SERIALREADLINE(0.5)
+            b = SERIALREADLINE(0.5)"#,
+        )
+        .expect_var("a", "partial")
+        .expect_var("b", "")
+        .check();
+    }
+
+    #[test]
+    fn test_serialreadline_interrupt() {
+        let (mut t, _ports) = tester_with_loopback();
+        t.run(
+            r#"SERIALOPEN "dev", 9600
+            SERIALWRITE "kept"
+            a = SERIALREADLINE()
+            b = 1"#,
+        )
+        .expect_ok(StopReason::Break)
+        .check();
+        t.run(r#"a = SERIALREADLINE(0)"#).expect_var("a", "kept").check();
+    }
+
+    #[test]
+    fn test_serialreadline_too_long() {
+        let (mut t, ports) = tester_with_loopback();
+        ports.device.borrow_mut().incoming.push_back(vec![b'x'; MAX_READ_LENGTH + 1]);
+        t.run(r#"SERIALOPEN "dev", 9600: a = SERIALREADLINE(0)"#)
+            .expect_err(format!(
+                "1:29: Serial port dev failed: Line exceeds the maximum of {} bytes",
+                MAX_READ_LENGTH
+            ))
+            .check();
+    }
+
+    #[test]
+    fn test_device_disappears() {
+        let (mut t, ports) = tester_with_loopback();
+        t.run(r#"SERIALOPEN "dev", 9600"#).check();
+        ports.device.borrow_mut().unplugged = true;
+        t.run(r#"a = SERIALREAD()"#)
+            .expect_err("1:5: Serial port dev disconnected: Device is gone")
+            .check();
+        assert!(ports.device.borrow().closed);
+        t.run(r#"SERIALCLOSE"#).expect_err("1:1: No serial port is open").check();
+
+        t.run(r#"SERIALOPEN "dev", 9600"#).check();
+        ports.device.borrow_mut().unplugged = true;
+        t.run(r#"SERIALWRITE "x""#)
+            .expect_err("1:1: Serial port dev disconnected: Device is gone")
+            .check();
+        assert!(ports.device.borrow().closed);
+
+        t.run(r#"SERIALOPEN "dev", 9600"#).check();
+        ports.device.borrow_mut().unplugged = true;
+        t.run(r#"a = SERIALREADLINE()"#)
+            .expect_err("1:5: Serial port dev disconnected: Device is gone")
+            .check();
+        assert!(ports.device.borrow().closed);
+    }
+
+    #[test]
+    fn test_clear_closes_port() {
+        let (mut t, ports) = tester_with_loopback();
+        t.run(r#"SERIALOPEN "dev", 9600"#).check();
+        t.get_machine().clear();
+        assert!(ports.device.borrow().closed);
+        t.run(r#"SERIALWRITE "x""#).expect_err("1:1: No serial port is open").check();
+    }
+
+    #[test]
+    fn test_no_open_port() {
+        let (mut t, _ports) = tester_with_loopback();
+        t.run(r#"SERIALCLOSE"#).expect_err("1:1: No serial port is open").check();
+        t.run(r#"SERIALWRITE "x""#).expect_err("1:1: No serial port is open").check();
+        t.run(r#"a = SERIALREAD()"#).expect_err("1:5: No serial port is open").check();
+        t.run(r#"a = SERIALREADLINE()"#).expect_err("1:5: No serial port is open").check();
+    }
+
+    #[test]
+    fn test_not_supported() {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), None);
+        t.run(r#"SERIALOPEN "/dev/ttyUSB0", 9600"#)
+            .expect_err("1:1: Serial ports are not supported on this platform")
+            .check();
+        t.run(r#"SERIALCLOSE"#).expect_err("1:1: No serial port is open").check();
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let (mut t, _ports) = tester_with_loopback();
+        for n in &["0", "-1", "1048577"] {
+            t.run(format!(r#"a = SERIALREAD({})"#, n))
+                .expect_err(format!("1:16: maxbytes% must be between 1 and {}", MAX_READ_LENGTH))
+                .check();
+        }
+        t.run(r#"a = SERIALREADLINE(-1)"#).expect_err("1:20: Timeout must be positive").check();
+
+        check_expr_compilation_error(
+            "1:10: SERIALREAD expected <> | <maxbytes%>",
+            r#"SERIALREAD(1, 2)"#,
+        );
+        check_expr_compilation_error(
+            "1:10: SERIALREADLINE expected <> | <timeout#>",
+            r#"SERIALREADLINE(1, 2)"#,
+        );
+        check_stmt_compilation_err("1:1: SERIALWRITE expected data$", r#"SERIALWRITE"#);
+        check_stmt_compilation_err("1:1: SERIALCLOSE expected no arguments", r#"SERIALCLOSE 1"#);
+    }
+}
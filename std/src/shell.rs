@@ -0,0 +1,397 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Commands to run external processes.
+
+use async_trait::async_trait;
+use endbasic_core::ast::ExprType;
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::io;
+use std::rc::Rc;
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "System
+These commands run other programs installed on the machine that runs the interpreter, which is \
+useful to automate tasks with system tools.  Commands are interpreted by the platform's shell, \
+which is sh on Unix systems and cmd on Windows.
+Running external programs is only possible when the interpreter runs on a terminal.  Other \
+environments, such as the web interface or the graphical consoles, reject these requests.";
+
+/// Maximum number of bytes that `SHELLOUT` captures from a command.
+pub const MAX_OUTPUT_LENGTH: usize = 1024 * 1024;
+
+/// Abstract interface to run external commands on behalf of programs.
+#[async_trait(?Send)]
+pub trait Shell {
+    /// Runs `command` through the platform's shell with its input and output attached to the
+    /// console, waits for it to finish, and returns its exit code.
+    async fn run(&self, command: &str) -> io::Result<i32>;
+
+    /// Runs `command` through the platform's shell, waits for it to finish, and returns its exit
+    /// code and the data it printed to its standard output.
+    ///
+    /// Implementations should stop reading the output once it grows beyond `max_length` bytes and
+    /// return the data read so far, so that callers can reject oversized outputs without waiting
+    /// for the command to finish.
+    async fn capture(&self, command: &str, max_length: usize) -> io::Result<(i32, Vec<u8>)>;
+}
+
+/// State shared by all the commands in this module.
+struct ShellState {
+    /// The shell to run commands with, or `None` if running commands is not supported.
+    shell: Option<Rc<dyn Shell>>,
+
+    /// Exit code of the last command, or zero if no command has run yet.
+    last_status: Cell<i32>,
+}
+
+impl ShellState {
+    /// Returns the shell to run commands with or an error if running them is not supported.
+    fn get_shell(&self, scope: &Scope<'_>) -> Result<&Rc<dyn Shell>> {
+        match self.shell.as_ref() {
+            Some(shell) => Ok(shell),
+            None => {
+                Err(scope
+                    .internal_error("Running external commands is not supported on this platform"))
+            }
+        }
+    }
+}
+
+/// Resets the status of the last command when the machine is cleared.
+struct ShellClearable(Rc<ShellState>);
+
+impl Clearable for ShellClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.last_status.set(0);
+    }
+}
+
+/// Syntax of the callables that take a single command to run.
+const COMMAND_SYNTAX: &[SingularArgSyntax] = &[SingularArgSyntax::RequiredValue(
+    RequiredValueSyntax { name: Cow::Borrowed("command"), vtype: ExprType::Text },
+    ArgSepSyntax::End,
+)];
+
+/// The `SHELL` command.
+pub struct ShellCommand {
+    metadata: CallableMetadata,
+    state: Rc<ShellState>,
+}
+
+impl ShellCommand {
+    /// Creates a new instance of the command.
+    fn new(state: Rc<ShellState>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SHELL")
+                .with_syntax(&[(COMMAND_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Runs an external command.
+The command$ is run through the platform's shell with its input and output attached to the \
+console, and the interpreter waits for it to finish.  Use SHELLSTATUS to know the exit code of \
+the command, which is not an error even if it indicates a failure.",
+                )
+                .build(),
+            state,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ShellCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let command = scope.pop_string();
+
+        let shell = self.state.get_shell(&scope)?;
+        let status = shell.run(&command).await.map_err(|e| scope.io_error(e))?;
+        self.state.last_status.set(status);
+        Ok(())
+    }
+}
+
+/// The `SHELLOUT` function.
+pub struct ShelloutFunction {
+    metadata: CallableMetadata,
+    state: Rc<ShellState>,
+}
+
+impl ShelloutFunction {
+    /// Creates a new instance of the function.
+    fn new(state: Rc<ShellState>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SHELLOUT")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(COMMAND_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Runs an external command and returns its output.
+The command$ is run through the platform's shell and the interpreter waits for it to finish.  \
+Returns what the command printed to its standard output, without any trailing line endings.  \
+The output cannot be longer than 1 MB.
+Use SHELLSTATUS to know the exit code of the command, which is not an error even if it indicates \
+a failure.",
+                )
+                .build(),
+            state,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ShelloutFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (command, pos) = scope.pop_string_with_pos();
+
+        let shell = self.state.get_shell(&scope)?;
+        let (status, output) =
+            shell.capture(&command, MAX_OUTPUT_LENGTH).await.map_err(|e| scope.io_error(e))?;
+        self.state.last_status.set(status);
+        if output.len() > MAX_OUTPUT_LENGTH {
+            return Err(Error::SyntaxError(
+                pos,
+                format!("Command output exceeds the maximum of {} bytes", MAX_OUTPUT_LENGTH),
+            ));
+        }
+
+        let output = String::from_utf8_lossy(&output);
+        scope.return_string(output.trim_end_matches(&['\r', '\n'][..]).to_owned())
+    }
+}
+
+/// The `SHELLSTATUS` function.
+pub struct ShellstatusFunction {
+    metadata: CallableMetadata,
+    state: Rc<ShellState>,
+}
+
+impl ShellstatusFunction {
+    /// Creates a new instance of the function.
+    fn new(state: Rc<ShellState>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SHELLSTATUS")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the exit code of the last external command.
+The exit code is 0 if the last command run by SHELL or SHELLOUT succeeded or if no command has \
+run yet.  Commands terminated by a signal on Unix systems report 128 plus the signal number, \
+like the shell does.",
+                )
+                .build(),
+            state,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ShellstatusFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+        scope.return_integer(self.state.last_status.get())
+    }
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+///
+/// The commands run external processes via `shell`.  If `shell` is `None`, the commands are still
+/// available but fail when used.
+pub fn add_all(machine: &mut Machine, shell: Option<Rc<dyn Shell>>) {
+    let state = Rc::from(ShellState { shell, last_status: Cell::new(0) });
+    machine.add_clearable(Box::from(ShellClearable(state.clone())));
+    machine.add_callable(ShellCommand::new(state.clone()));
+    machine.add_callable(ShelloutFunction::new(state.clone()));
+    machine.add_callable(ShellstatusFunction::new(state));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    /// Shell that returns canned results and records the commands it gets.
+    #[derive(Default)]
+    struct MockShell {
+        results: HashMap<String, (i32, String)>,
+        commands: RefCell<Vec<(String, bool)>>,
+    }
+
+    impl MockShell {
+        /// Registers the `status` and `output` that `command` yields.
+        fn add_result<S: Into<String>>(mut self, command: &str, status: i32, output: S) -> Self {
+            self.results.insert(command.to_owned(), (status, output.into()));
+            self
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Shell for MockShell {
+        async fn run(&self, command: &str) -> io::Result<i32> {
+            self.commands.borrow_mut().push((command.to_owned(), false));
+            match self.results.get(command) {
+                Some((status, _output)) => Ok(*status),
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "Cannot run shell")),
+            }
+        }
+
+        async fn capture(&self, command: &str, max_length: usize) -> io::Result<(i32, Vec<u8>)> {
+            self.commands.borrow_mut().push((command.to_owned(), true));
+            match self.results.get(command) {
+                Some((status, output)) => {
+                    let mut output = output.as_bytes().to_owned();
+                    output.truncate(max_length + 1);
+                    Ok((*status, output))
+                }
+                None => Err(io::Error::new(io::ErrorKind::NotFound, "Cannot run shell")),
+            }
+        }
+    }
+
+    /// Creates a tester that runs commands via `shell`.
+    fn tester_with_shell(shell: Rc<MockShell>) -> Tester {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), Some(shell));
+        t
+    }
+
+    #[test]
+    fn test_shell_ok() {
+        let shell = Rc::from(MockShell::default().add_result("ls -la", 0, "ignored"));
+        let mut t = tester_with_shell(shell.clone());
+        t.run(r#"SHELL "ls -la": s = SHELLSTATUS"#).expect_var("s", 0).check();
+        assert_eq!([("ls -la".to_owned(), false)], shell.commands.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_shell_status() {
+        let shell =
+            Rc::from(MockShell::default().add_result("fail", 3, "").add_result("ok", 0, ""));
+        let mut t = tester_with_shell(shell);
+        t.run(r#"a = SHELLSTATUS: SHELL "fail": b = SHELLSTATUS: SHELL "ok": c = SHELLSTATUS"#)
+            .expect_var("a", 0)
+            .expect_var("b", 3)
+            .expect_var("c", 0)
+            .check();
+        t.get_machine().clear();
+        t.run("d = SHELLSTATUS").expect_var("d", 0).check();
+    }
+
+    #[test]
+    fn test_shell_errors() {
+        let shell = Rc::from(MockShell::default());
+        let mut t = tester_with_shell(shell);
+        t.run(r#"SHELL "missing""#).expect_err("1:1: Cannot run shell").check();
+
+        check_stmt_compilation_err("1:1: SHELL expected command$", "SHELL");
+        check_stmt_compilation_err("1:7: expected STRING but found INTEGER", "SHELL 3");
+    }
+
+    #[test]
+    fn test_shell_not_supported() {
+        check_stmt_err(
+            "1:1: Running external commands is not supported on this platform",
+            "SHELL \"ls\"",
+        );
+        check_expr_error(
+            "1:10: Running external commands is not supported on this platform",
+            r#"SHELLOUT("ls")"#,
+        );
+        check_expr_ok(0, "SHELLSTATUS");
+    }
+
+    #[test]
+    fn test_shellout_ok() {
+        let shell = Rc::from(
+            MockShell::default()
+                .add_result("git rev-parse HEAD", 0, "0123abcd\n")
+                .add_result("printf", 0, "a\r\n\nb\r\n\r\n")
+                .add_result("false", 1, ""),
+        );
+        let mut t = tester_with_shell(shell.clone());
+        t.run(
+            r#"a = SHELLOUT("git rev-parse HEAD"): b = SHELLOUT("printf"): c = SHELLOUT("false")
+s = SHELLSTATUS"#,
+        )
+        .expect_var("a", "0123abcd")
+        .expect_var("b", "a\r\n\nb")
+        .expect_var("c", "")
+        .expect_var("s", 1)
+        .check();
+        assert_eq!(
+            [
+                ("git rev-parse HEAD".to_owned(), true),
+                ("printf".to_owned(), true),
+                ("false".to_owned(), true)
+            ],
+            shell.commands.borrow().as_slice()
+        );
+    }
+
+    #[test]
+    fn test_shellout_too_long() {
+        let fits = "x".repeat(MAX_OUTPUT_LENGTH);
+        let shell = Rc::from(MockShell::default().add_result("fits", 0, fits.clone()).add_result(
+            "long",
+            5,
+            "x".repeat(MAX_OUTPUT_LENGTH + 100),
+        ));
+        let mut t = tester_with_shell(shell);
+        t.run(r#"a = SHELLOUT("fits")"#).expect_var("a", fits.as_str()).check();
+        t.run(r#"b = SHELLOUT("long")"#)
+            .expect_var("a", fits.as_str())
+            .expect_err(format!(
+                "1:14: Command output exceeds the maximum of {} bytes",
+                MAX_OUTPUT_LENGTH
+            ))
+            .check();
+        t.run("s = SHELLSTATUS").expect_var("a", fits.as_str()).expect_var("s", 5).check();
+    }
+
+    #[test]
+    fn test_shellout_errors() {
+        let shell = Rc::from(MockShell::default());
+        let mut t = tester_with_shell(shell);
+        t.run(r#"a = SHELLOUT("missing")"#).expect_err("1:5: Cannot run shell").check();
+
+        check_expr_compilation_error("1:10: SHELLOUT expected command$", "SHELLOUT()");
+        check_expr_compilation_error("1:19: expected STRING but found INTEGER", "SHELLOUT(3)");
+    }
+
+    #[test]
+    fn test_shellstatus_errors() {
+        check_expr_compilation_error("1:10: SHELLSTATUS expected no arguments", "SHELLSTATUS(1)");
+    }
+}
@@ -0,0 +1,914 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! TCP client sockets for EndBASIC.
+
+use crate::exec::interruptible;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbols};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use std::io;
+use std::rc::Rc;
+
+/// Category description for all symbols provided by this module.
+const CATEGORY: &str = "Sockets
+The socket functions let programs talk to other machines over raw TCP connections, which is \
+useful to implement simple network protocols such as those of chat or time servers.
+SOCKOPEN% establishes a connection and returns a handle that identifies it in all other \
+operations.  Connections stay open until they are closed with SOCKCLOSE or until the machine is \
+cleared.  Receiving data waits until the remote end sends something, and these waits can be \
+interrupted by pressing CTRL+C.
+Data is sent as is and received data is interpreted as UTF-8 text.
+Sockets are only available when the interpreter runs on a desktop system.  Web browsers cannot \
+open raw TCP connections so the web interface rejects these requests.";
+
+/// Default number of bytes that `SOCKRECV` reads at once.
+pub const DEFAULT_RECV_LENGTH: usize = 4096;
+
+/// Maximum number of bytes that `SOCKRECV` can read at once and maximum length of the lines that
+/// `SOCKREADLINE` returns.
+pub const MAX_RECV_LENGTH: usize = 1024 * 1024;
+
+/// Abstract interface to an open connection.
+///
+/// The connection must be closed when the socket is dropped.
+#[async_trait(?Send)]
+pub trait Socket {
+    /// Sends all of `data` to the remote end.
+    async fn send(&mut self, data: &[u8]) -> io::Result<()>;
+
+    /// Waits until data is available and returns up to `max_length` bytes of it.
+    ///
+    /// Returns an empty vector if the remote end closed the connection.
+    async fn recv(&mut self, max_length: usize) -> io::Result<Vec<u8>>;
+}
+
+/// Abstract interface to establish TCP connections on behalf of programs.
+#[async_trait(?Send)]
+pub trait Connector {
+    /// Opens a connection to `port` on `host`.
+    async fn connect(&self, host: &str, port: u16) -> io::Result<Box<dyn Socket>>;
+}
+
+/// An open connection and the data received from it that programs have not consumed yet.
+struct Connection {
+    /// The socket to talk to the remote end.
+    socket: Box<dyn Socket>,
+
+    /// Address of the remote end, for error reporting purposes.
+    address: String,
+
+    /// Data received from the remote end that has not been returned to the program yet.
+    pending: Vec<u8>,
+
+    /// Whether the remote end closed the connection.
+    eof: bool,
+}
+
+impl Connection {
+    /// Waits for more data from the remote end and appends up to `max_length` bytes of it to the
+    /// pending data.  Returns false if the remote end closed the connection.
+    async fn fill(&mut self, max_length: usize) -> io::Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        match self.socket.recv(max_length).await {
+            Ok(data) if data.is_empty() => {
+                self.eof = true;
+                Ok(false)
+            }
+            Ok(data) => {
+                self.pending.extend_from_slice(&data);
+                Ok(true)
+            }
+            Err(e) => Err(io::Error::new(
+                e.kind(),
+                format!("Cannot receive from {}: {}", self.address, e),
+            )),
+        }
+    }
+
+    /// Returns up to `max_length` bytes of data, waiting for the remote end to send some if there
+    /// is none pending.  Returns an empty vector if the remote end closed the connection.
+    async fn recv(&mut self, max_length: usize) -> io::Result<Vec<u8>> {
+        if self.pending.is_empty() {
+            self.fill(max_length).await?;
+        }
+        let n = std::cmp::min(max_length, self.pending.len());
+        Ok(self.pending.drain(..n).collect())
+    }
+
+    /// Returns the next line of text, without its line terminator, waiting for the remote end to
+    /// send it.  A partial line is returned if the remote end closed the connection without
+    /// terminating it.
+    async fn read_line(&mut self) -> io::Result<Vec<u8>> {
+        let mut searched = 0;
+        loop {
+            let newline = self.pending[searched..].iter().position(|b| *b == b'\n');
+            let length = newline.map(|i| searched + i).unwrap_or(self.pending.len());
+            if length > MAX_RECV_LENGTH {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Line from {} exceeds the maximum of {} bytes",
+                        self.address, MAX_RECV_LENGTH
+                    ),
+                ));
+            }
+
+            if newline.is_some() {
+                let mut line: Vec<u8> = self.pending.drain(..length + 1).collect();
+                line.pop();
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                return Ok(line);
+            }
+            searched = length;
+
+            if !self.fill(DEFAULT_RECV_LENGTH).await? {
+                if self.pending.is_empty() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        format!("Connection to {} closed by peer", self.address),
+                    ));
+                }
+                return Ok(self.pending.split_off(0));
+            }
+        }
+    }
+}
+
+/// State shared by all the callables in this module.
+struct Sockets {
+    /// The connector to open new connections with, or `None` if sockets are not supported.
+    connector: Option<Rc<dyn Connector>>,
+
+    /// The open connections keyed by their handles.
+    connections: RefCell<BTreeMap<i32, Rc<RefCell<Connection>>>>,
+}
+
+impl Sockets {
+    /// Returns the connection for the `handle` given at `pos`.
+    fn get(&self, handle: i32, pos: LineCol) -> Result<Rc<RefCell<Connection>>> {
+        match self.connections.borrow().get(&handle) {
+            Some(conn) => Ok(conn.clone()),
+            None => Err(Error::SyntaxError(pos, format!("Invalid socket handle {}", handle))),
+        }
+    }
+
+    /// Registers a new `conn` and returns its handle, which is the lowest unused one.
+    fn insert(&self, conn: Connection) -> i32 {
+        let mut connections = self.connections.borrow_mut();
+        let mut handle = 1;
+        for used in connections.keys() {
+            if *used != handle {
+                break;
+            }
+            handle += 1;
+        }
+        connections.insert(handle, Rc::from(RefCell::from(conn)));
+        handle
+    }
+}
+
+/// Closes all open connections when the machine is cleared.
+struct SocketsClearable(Rc<Sockets>);
+
+impl Clearable for SocketsClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.0.connections.borrow_mut().clear();
+    }
+}
+
+/// Formats `host` and `port` as an address for error messages.
+fn format_address(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Syntax of the callables that only take a socket handle.
+const HANDLE_SYNTAX: &[SingularArgSyntax] = &[SingularArgSyntax::RequiredValue(
+    RequiredValueSyntax { name: Cow::Borrowed("handle"), vtype: ExprType::Integer },
+    ArgSepSyntax::End,
+)];
+
+/// The `SOCKCLOSE` command.
+pub struct SockcloseCommand {
+    metadata: CallableMetadata,
+    sockets: Rc<Sockets>,
+}
+
+impl SockcloseCommand {
+    /// Creates a new instance of the command.
+    fn new(sockets: Rc<Sockets>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOCKCLOSE")
+                .with_syntax(&[(HANDLE_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Closes a connection.
+The handle% is released and may be returned by a later SOCKOPEN%.  Any data received from the \
+remote end that was not read yet is discarded.",
+                )
+                .build(),
+            sockets,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SockcloseCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (handle, pos) = scope.pop_integer_with_pos();
+
+        match self.sockets.connections.borrow_mut().remove(&handle) {
+            Some(_conn) => Ok(()),
+            None => Err(Error::SyntaxError(pos, format!("Invalid socket handle {}", handle))),
+        }
+    }
+}
+
+/// The `SOCKOPEN` function.
+pub struct SockopenFunction {
+    metadata: CallableMetadata,
+    sockets: Rc<Sockets>,
+}
+
+impl SockopenFunction {
+    /// Creates a new instance of the function.
+    fn new(sockets: Rc<Sockets>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOCKOPEN")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("host"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("port"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Opens a TCP connection and returns its handle.
+The host$ can be a name or an IP address and port% must be between 1 and 65535.  Fails if the \
+connection cannot be established, such as when the remote end refuses it or does not respond.",
+                )
+                .build(),
+            sockets,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SockopenFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (host, host_pos) = scope.pop_string_with_pos();
+        let (port, port_pos) = scope.pop_integer_with_pos();
+
+        let connector = match self.sockets.connector.as_ref() {
+            Some(connector) => connector,
+            None => {
+                return Err(scope.internal_error("TCP sockets are not supported on this platform"))
+            }
+        };
+
+        if host.is_empty() {
+            return Err(Error::SyntaxError(host_pos, "Host cannot be empty".to_owned()));
+        }
+        let port = match u16::try_from(port) {
+            Ok(port) if port > 0 => port,
+            _ => return Err(Error::SyntaxError(port_pos, format!("Invalid port {}", port))),
+        };
+
+        let address = format_address(&host, port);
+        let socket = match interruptible(machine, connector.connect(&host, port)).await {
+            Some(Ok(socket)) => socket,
+            Some(Err(e)) => {
+                return Err(scope.io_error(io::Error::new(
+                    e.kind(),
+                    format!("Cannot connect to {}: {}", address, e),
+                )))
+            }
+            None => return scope.return_integer(0),
+        };
+
+        let handle =
+            self.sockets.insert(Connection { socket, address, pending: vec![], eof: false });
+        scope.return_integer(handle)
+    }
+}
+
+/// The `SOCKREADLINE` function.
+pub struct SockreadlineFunction {
+    metadata: CallableMetadata,
+    sockets: Rc<Sockets>,
+}
+
+impl SockreadlineFunction {
+    /// Creates a new instance of the function.
+    fn new(sockets: Rc<Sockets>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOCKREADLINE")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(HANDLE_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Receives a line of text from a connection.
+Waits until the remote end sends a full line and returns it without its line terminator, which \
+can be either LF or CRLF.  If the remote end closes the connection in the middle of a line, \
+returns the partial line.  Fails if the connection was closed and there is no more data to read.
+Lines cannot be longer than 1 MB.",
+                )
+                .build(),
+            sockets,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SockreadlineFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (handle, pos) = scope.pop_integer_with_pos();
+
+        let conn = self.sockets.get(handle, pos)?;
+        let mut conn = conn.borrow_mut();
+        match interruptible(machine, conn.read_line()).await {
+            Some(Ok(line)) => scope.return_string(String::from_utf8_lossy(&line).into_owned()),
+            Some(Err(e)) => Err(scope.io_error(e)),
+            None => scope.return_string(""),
+        }
+    }
+}
+
+/// The `SOCKRECV` function.
+pub struct SockrecvFunction {
+    metadata: CallableMetadata,
+    sockets: Rc<Sockets>,
+}
+
+impl SockrecvFunction {
+    /// Creates a new instance of the function.
+    fn new(sockets: Rc<Sockets>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOCKRECV")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[
+                    (HANDLE_SYNTAX, None),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("handle"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("maxbytes"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Receives data from a connection.
+Waits until the remote end sends some data and returns it, which may be less than what the remote \
+end sent in one go.  Returns at most maxbytes% bytes, which must be between 1 and 1048576 and \
+defaults to 4096.
+Returns an empty string if the remote end closed the connection.",
+                )
+                .build(),
+            sockets,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SockrecvFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert!((1..=2).contains(&scope.nargs()));
+        let (handle, handle_pos) = scope.pop_integer_with_pos();
+        let max_length = if scope.nargs() > 0 {
+            let (n, pos) = scope.pop_integer_with_pos();
+            match usize::try_from(n) {
+                Ok(n) if n > 0 && n <= MAX_RECV_LENGTH => n,
+                _ => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("maxbytes% must be between 1 and {}", MAX_RECV_LENGTH),
+                    ))
+                }
+            }
+        } else {
+            DEFAULT_RECV_LENGTH
+        };
+
+        let conn = self.sockets.get(handle, handle_pos)?;
+        let mut conn = conn.borrow_mut();
+        match interruptible(machine, conn.recv(max_length)).await {
+            Some(Ok(data)) => scope.return_string(String::from_utf8_lossy(&data).into_owned()),
+            Some(Err(e)) => Err(scope.io_error(e)),
+            None => scope.return_string(""),
+        }
+    }
+}
+
+/// The `SOCKSEND` command.
+pub struct SocksendCommand {
+    metadata: CallableMetadata,
+    sockets: Rc<Sockets>,
+}
+
+impl SocksendCommand {
+    /// Creates a new instance of the command.
+    fn new(sockets: Rc<Sockets>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SOCKSEND")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("handle"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("data"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Sends data over a connection.
+The data$ is sent as is: no line terminator is appended to it, so protocols that work with lines \
+of text need to terminate them explicitly, for example with CHR(10).",
+                )
+                .build(),
+            sockets,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SocksendCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (handle, pos) = scope.pop_integer_with_pos();
+        let data = scope.pop_string();
+
+        let conn = self.sockets.get(handle, pos)?;
+        let mut conn = conn.borrow_mut();
+        let conn = &mut *conn;
+        match interruptible(machine, conn.socket.send(data.as_bytes())).await {
+            Some(Ok(())) | None => Ok(()),
+            Some(Err(e)) => Err(scope.io_error(io::Error::new(
+                e.kind(),
+                format!("Cannot send to {}: {}", conn.address, e),
+            ))),
+        }
+    }
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+///
+/// New connections are established via `connector`.  If `connector` is `None`, the callables are
+/// still available but fail when used.
+pub fn add_all(machine: &mut Machine, connector: Option<Rc<dyn Connector>>) {
+    let sockets = Rc::from(Sockets { connector, connections: RefCell::from(BTreeMap::new()) });
+    machine.add_clearable(Box::from(SocketsClearable(sockets.clone())));
+    machine.add_callable(SockcloseCommand::new(sockets.clone()));
+    machine.add_callable(SockopenFunction::new(sockets.clone()));
+    machine.add_callable(SockreadlineFunction::new(sockets.clone()));
+    machine.add_callable(SockrecvFunction::new(sockets.clone()));
+    machine.add_callable(SocksendCommand::new(sockets));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use async_channel::Sender;
+    use endbasic_core::exec::{Signal, StopReason};
+    use futures_lite::future;
+    use std::collections::{HashMap, VecDeque};
+
+    /// Remote end of a connection established via the `MockConnector`.
+    #[derive(Default)]
+    struct MockPeer {
+        /// Data to send to the program, in order.  The peer closes the connection once it runs out
+        /// of data unless `hang` is true.
+        packets: VecDeque<Vec<u8>>,
+
+        /// Whether the peer sends back all the data it receives.
+        echo: bool,
+
+        /// Whether the peer never closes the connection, in which case waiting for more data
+        /// requests a break.
+        hang: bool,
+
+        /// Error to return when the program tries to receive data.
+        recv_error: Option<io::ErrorKind>,
+
+        /// Error to return when the program tries to send data.
+        send_error: Option<io::ErrorKind>,
+
+        /// All the data received from the program.
+        received: Vec<u8>,
+
+        /// Whether the program closed the connection.
+        closed: bool,
+    }
+
+    /// Socket connected to a `MockPeer`.
+    struct MockSocket {
+        peer: Rc<RefCell<MockPeer>>,
+        signals_tx: Sender<Signal>,
+    }
+
+    impl Drop for MockSocket {
+        fn drop(&mut self) {
+            self.peer.borrow_mut().closed = true;
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Socket for MockSocket {
+        async fn send(&mut self, data: &[u8]) -> io::Result<()> {
+            let mut peer = self.peer.borrow_mut();
+            if let Some(kind) = peer.send_error {
+                return Err(io::Error::new(kind, "Fake send error"));
+            }
+            peer.received.extend_from_slice(data);
+            if peer.echo {
+                peer.packets.push_back(data.to_vec());
+            }
+            Ok(())
+        }
+
+        async fn recv(&mut self, max_length: usize) -> io::Result<Vec<u8>> {
+            let mut peer = self.peer.borrow_mut();
+            if let Some(kind) = peer.recv_error {
+                return Err(io::Error::new(kind, "Fake recv error"));
+            }
+            match peer.packets.pop_front() {
+                Some(mut packet) => {
+                    if packet.len() > max_length {
+                        peer.packets.push_front(packet.split_off(max_length));
+                    }
+                    Ok(packet)
+                }
+                None if peer.hang => {
+                    drop(peer);
+                    self.signals_tx.try_send(Signal::Break).unwrap();
+                    future::pending().await
+                }
+                None => Ok(vec![]),
+            }
+        }
+    }
+
+    /// Connector that establishes connections against in-process peers.
+    struct MockConnector {
+        peers: HashMap<String, Rc<RefCell<MockPeer>>>,
+        signals_tx: Sender<Signal>,
+    }
+
+    #[async_trait(?Send)]
+    impl Connector for MockConnector {
+        async fn connect(&self, host: &str, port: u16) -> io::Result<Box<dyn Socket>> {
+            match self.peers.get(&format!("{}:{}", host, port)) {
+                Some(peer) => Ok(Box::from(MockSocket {
+                    peer: peer.clone(),
+                    signals_tx: self.signals_tx.clone(),
+                })),
+                None if host == "hang" => {
+                    self.signals_tx.try_send(Signal::Break).unwrap();
+                    future::pending().await
+                }
+                None => Err(io::Error::new(io::ErrorKind::ConnectionRefused, "Connection refused")),
+            }
+        }
+    }
+
+    /// Creates a tester whose connections reach the given `peers`, keyed by `host:port`.
+    fn tester_with_peers(peers: &[(&str, Rc<RefCell<MockPeer>>)]) -> Tester {
+        let mut t = Tester::empty();
+        crate::strings::add_all(t.get_machine());
+        let signals_tx = t.get_machine().get_signals_tx();
+        let peers = peers.iter().map(|(k, v)| ((*k).to_owned(), v.clone())).collect();
+        add_all(t.get_machine(), Some(Rc::from(MockConnector { peers, signals_tx })));
+        t
+    }
+
+    /// Creates a peer that sends the given `packets` and then closes the connection.
+    fn peer_with_packets(packets: &[&str]) -> Rc<RefCell<MockPeer>> {
+        let packets = packets.iter().map(|p| p.as_bytes().to_vec()).collect();
+        Rc::from(RefCell::from(MockPeer { packets, ..Default::default() }))
+    }
+
+    #[test]
+    fn test_sockopen_handles() {
+        let mut t = tester_with_peers(&[
+            ("localhost:10", peer_with_packets(&[])),
+            ("::1:80", peer_with_packets(&[])),
+        ]);
+        t.run(
+            r#"a = SOCKOPEN("localhost", 10): b = SOCKOPEN("::1", 80): SOCKCLOSE a
+            c = SOCKOPEN("localhost", 10): d = SOCKOPEN("localhost", 10)"#,
+        )
+        .expect_var("a", 1)
+        .expect_var("b", 2)
+        .expect_var("c", 1)
+        .expect_var("d", 3)
+        .check();
+    }
+
+    #[test]
+    fn test_echo_round_trip() {
+        let peer = Rc::from(RefCell::from(MockPeer { echo: true, ..Default::default() }));
+        let mut t = tester_with_peers(&[("echo:7", peer.clone())]);
+        t.run(
+            r#"h = SOCKOPEN("echo", 7)
+            SOCKSEND h, "Hello"
+            a = SOCKRECV(h)
+            SOCKSEND h, "first" + CHR(10) + "second" + CHR(13) + CHR(10)
+            b = SOCKREADLINE(h)
+            c = SOCKRECV(h, 3)
+            d = SOCKRECV(h)"#,
+        )
+        .expect_var("h", 1)
+        .expect_var("a", "Hello")
+        .expect_var("b", "first")
+        .expect_var("c", "sec")
+        .expect_var("d", "ond\r\n")
+        .check();
+        assert_eq!(b"Hellofirst\nsecond\r\n", peer.borrow().received.as_slice());
+        assert!(!peer.borrow().closed);
+    }
+
+    #[test]
+    fn test_sockreadline_split_across_packets() {
+        let peer = peer_with_packets(&["he", "llo", "\r", "\nwor", "ld\n\n", "partial"]);
+        let mut t = tester_with_peers(&[("lines:1", peer)]);
+        t.run(
+            r#"h = SOCKOPEN("lines", 1)
+            a = SOCKREADLINE(h)
+            b = SOCKREADLINE(h)
+            c = SOCKREADLINE(h)
+            d = SOCKREADLINE(h)
+            e = SOCKREADLINE(h)"#,
+        )
+        .expect_var("h", 1)
+        .expect_var("a", "hello")
+        .expect_var("b", "world")
+        .expect_var("c", "")
+        .expect_var("d", "partial")
+        .expect_err("6:17: Connection to lines:1 closed by peer")
+        .check();
+    }
+
+    #[test]
+    fn test_sockreadline_too_long() {
+        let packet = "x".repeat(MAX_RECV_LENGTH);
+        let peer = peer_with_packets(&[&packet, "yz\n"]);
+        let mut t = tester_with_peers(&[("long:1", peer)]);
+        t.run(r#"h = SOCKOPEN("long", 1): a = SOCKREADLINE(h)"#)
+            .expect_var("h", 1)
+            .expect_err(format!(
+                "1:30: Line from long:1 exceeds the maximum of {} bytes",
+                MAX_RECV_LENGTH
+            ))
+            .check();
+    }
+
+    #[test]
+    fn test_close_by_peer() {
+        let peer = peer_with_packets(&["bye"]);
+        let mut t = tester_with_peers(&[("srv:1", peer.clone())]);
+        t.run(
+            r#"h = SOCKOPEN("srv", 1)
+            a = SOCKRECV(h)
+            b = SOCKRECV(h)
+            c = SOCKRECV(h)
+            SOCKSEND h, "ignored""#,
+        )
+        .expect_var("h", 1)
+        .expect_var("a", "bye")
+        .expect_var("b", "")
+        .expect_var("c", "")
+        .check();
+        assert_eq!(b"ignored", peer.borrow().received.as_slice());
+        assert!(!peer.borrow().closed);
+    }
+
+    #[test]
+    fn test_close_by_program() {
+        let peer1 = peer_with_packets(&[]);
+        let peer2 = peer_with_packets(&[]);
+        let mut t = tester_with_peers(&[("one:1", peer1.clone()), ("two:2", peer2.clone())]);
+        t.run(r#"a = SOCKOPEN("one", 1): b = SOCKOPEN("two", 2): SOCKCLOSE a"#)
+            .expect_var("a", 1)
+            .expect_var("b", 2)
+            .check();
+        assert!(peer1.borrow().closed);
+        assert!(!peer2.borrow().closed);
+
+        for (stmt, err) in &[
+            ("SOCKCLOSE a", "1:11: Invalid socket handle 1"),
+            ("SOCKSEND a, \"x\"", "1:10: Invalid socket handle 1"),
+            ("c = SOCKRECV(a)", "1:14: Invalid socket handle 1"),
+            ("c = SOCKREADLINE(a)", "1:18: Invalid socket handle 1"),
+        ] {
+            t.run(*stmt).expect_var("a", 1).expect_var("b", 2).expect_err(*err).check();
+        }
+    }
+
+    #[test]
+    fn test_clear_closes_all() {
+        let peer = peer_with_packets(&[]);
+        let mut t = tester_with_peers(&[("srv:1", peer.clone())]);
+        t.run(r#"a = SOCKOPEN("srv", 1)"#).expect_var("a", 1).check();
+        assert!(!peer.borrow().closed);
+        t.get_machine().clear();
+        assert!(peer.borrow().closed);
+        t.run(r#"a = SOCKRECV(1)"#).expect_err("1:14: Invalid socket handle 1").check();
+    }
+
+    #[test]
+    fn test_io_errors() {
+        let mut t = tester_with_peers(&[
+            (
+                "reset:1",
+                Rc::from(RefCell::from(MockPeer {
+                    recv_error: Some(io::ErrorKind::ConnectionReset),
+                    ..Default::default()
+                })),
+            ),
+            (
+                "pipe:1",
+                Rc::from(RefCell::from(MockPeer {
+                    send_error: Some(io::ErrorKind::BrokenPipe),
+                    ..Default::default()
+                })),
+            ),
+        ]);
+        t.run(r#"a = SOCKOPEN("refused", 1234)"#)
+            .expect_err("1:5: Cannot connect to refused:1234: Connection refused")
+            .check();
+        t.run(r#"a = SOCKOPEN("::1", 1234)"#)
+            .expect_err("1:5: Cannot connect to [::1]:1234: Connection refused")
+            .check();
+        t.run(r#"h = SOCKOPEN("reset", 1): a = SOCKRECV(h)"#)
+            .expect_var("h", 1)
+            .expect_err("1:31: Cannot receive from reset:1: Fake recv error")
+            .check();
+        t.run(r#"h = SOCKOPEN("reset", 1): a = SOCKREADLINE(h)"#)
+            .expect_var("h", 2)
+            .expect_err("1:31: Cannot receive from reset:1: Fake recv error")
+            .check();
+        t.run(r#"h = SOCKOPEN("pipe", 1): SOCKSEND h, "x""#)
+            .expect_var("h", 3)
+            .expect_err("1:26: Cannot send to pipe:1: Fake send error")
+            .check();
+    }
+
+    #[test]
+    fn test_interrupt() {
+        let peer = Rc::from(RefCell::from(MockPeer { hang: true, ..Default::default() }));
+        let mut t = tester_with_peers(&[("slow:1", peer)]);
+        t.run(r#"h = SOCKOPEN("slow", 1): a = SOCKRECV(h): b = 1"#)
+            .expect_ok(StopReason::Break)
+            .expect_var("h", 1)
+            .check();
+        t.run(r#"a = SOCKREADLINE(1): b = 1"#)
+            .expect_ok(StopReason::Break)
+            .expect_var("h", 1)
+            .check();
+        t.run(r#"h = SOCKOPEN("hang", 1): b = 1"#)
+            .expect_ok(StopReason::Break)
+            .expect_var("h", 1)
+            .check();
+    }
+
+    #[test]
+    fn test_not_supported() {
+        let mut t = Tester::empty();
+        add_all(t.get_machine(), None);
+        t.run(r#"a = SOCKOPEN("localhost", 80)"#)
+            .expect_err("1:5: TCP sockets are not supported on this platform")
+            .check();
+        t.run(r#"SOCKCLOSE 1"#).expect_err("1:11: Invalid socket handle 1").check();
+    }
+
+    #[test]
+    fn test_sockopen_errors() {
+        let mut t = tester_with_peers(&[]);
+        t.run(r#"a = SOCKOPEN("", 80)"#).expect_err("1:14: Host cannot be empty").check();
+        t.run(r#"a = SOCKOPEN("x", 0)"#).expect_err("1:19: Invalid port 0").check();
+        t.run(r#"a = SOCKOPEN("x", 65536)"#).expect_err("1:19: Invalid port 65536").check();
+        t.run(r#"a = SOCKOPEN("x", -1)"#).expect_err("1:19: Invalid port -1").check();
+
+        check_expr_compilation_error("1:10: SOCKOPEN expected host$, port%", r#"SOCKOPEN("x")"#);
+        check_expr_compilation_error(
+            "1:19: expected STRING but found INTEGER",
+            r#"SOCKOPEN(1, 2)"#,
+        );
+    }
+
+    #[test]
+    fn test_sockrecv_errors() {
+        let mut t = tester_with_peers(&[("srv:1", peer_with_packets(&[]))]);
+        for n in &["0", "-1", "1048577"] {
+            t.run(format!(r#"h = SOCKOPEN("srv", 1): SOCKCLOSE h: a = SOCKRECV(h, {})"#, n))
+                .expect_var("h", 1)
+                .expect_err(format!("1:54: maxbytes% must be between 1 and {}", MAX_RECV_LENGTH))
+                .check();
+        }
+
+        check_expr_compilation_error(
+            "1:10: SOCKRECV expected <handle%> | <handle%, maxbytes%>",
+            r#"SOCKRECV()"#,
+        );
+        check_expr_compilation_error(
+            "1:10: SOCKRECV expected <handle%> | <handle%, maxbytes%>",
+            r#"SOCKRECV(1, 2, 3)"#,
+        );
+        check_expr_compilation_error("1:10: SOCKREADLINE expected handle%", r#"SOCKREADLINE()"#);
+    }
+
+    #[test]
+    fn test_sock_commands_errors() {
+        check_stmt_compilation_err("1:1: SOCKCLOSE expected handle%", r#"SOCKCLOSE"#);
+        check_stmt_compilation_err("1:1: SOCKSEND expected handle%, data$", r#"SOCKSEND 1"#);
+        check_stmt_compilation_err("1:13: expected STRING but found INTEGER", r#"SOCKSEND 1, 2"#);
+    }
+}
@@ -13,12 +13,25 @@
 // License for the specific language governing permissions and limitations
 // under the License.
 
-//! SPI bus abstractions for EndBASIC.
+//! SPI bus abstractions and commands for EndBASIC.
 
-use std::io::Write;
+use crate::gpio::CATEGORY;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType, VarRef};
+use endbasic_core::compiler::{
+    ArgSepSyntax, RequiredRefSyntax, RequiredValueSyntax, SingularArgSyntax,
+};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder, Symbol, Symbols};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::rc::Rc;
 
 /// Defines the SPI clock polarity and phase.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub enum SpiMode {
     /// CPOL 0, CPHA 0
     Mode0 = 0,
@@ -35,3 +48,575 @@ pub trait SpiBus: Write {
     /// Returns the maximum transfer size for the bus.
     fn max_size(&self) -> usize;
 }
+
+/// Writes all of `data` to the `spi_bus`.
+///
+/// The input data is chunked to respect the maximum write size accepted by the SPI bus.
+pub fn write_all<B: SpiBus>(spi_bus: &mut B, data: &[u8]) -> io::Result<()> {
+    // TODO(jmmv): Do we really need to chunk the data ourselves, or can we try to write it
+    // all to the bus and then expect the write to return partial results?
+    for chunk in data.chunks(spi_bus.max_size()) {
+        let mut i = 0;
+        loop {
+            let n = spi_bus.write(&chunk[i..])?;
+            if n == chunk.len() - i {
+                break;
+            }
+            i += n;
+        }
+    }
+    Ok(())
+}
+
+impl SpiMode {
+    /// Creates a new SPI mode from an EndBASIC integer value.
+    fn from_i32(i: i32, pos: LineCol) -> Result<Self> {
+        match i {
+            0 => Ok(SpiMode::Mode0),
+            1 => Ok(SpiMode::Mode1),
+            2 => Ok(SpiMode::Mode2),
+            3 => Ok(SpiMode::Mode3),
+            i => Err(Error::SyntaxError(pos, format!("SPI mode {} must be between 0 and 3", i))),
+        }
+    }
+}
+
+/// Identifier of an SPI device as a bus number and the chip select line within that bus.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SpiDevice {
+    /// The bus number.
+    pub bus: u8,
+
+    /// The chip select line.
+    pub cs: u8,
+}
+
+/// Converts an EndBASIC integer value to a bus or chip select number, using `what` to describe
+/// the value in error messages.
+fn u8_from_i32(i: i32, pos: LineCol, what: &str) -> Result<u8> {
+    if i < 0 {
+        return Err(Error::SyntaxError(pos, format!("{} {} cannot be negative", what, i)));
+    }
+    if i > u8::MAX as i32 {
+        return Err(Error::SyntaxError(pos, format!("{} {} is too large", what, i)));
+    }
+    Ok(i as u8)
+}
+
+/// Generic abstraction over the SPI devices that EndBASIC programs can talk to.
+pub trait Spi {
+    /// Opens the given `device` with the `speed_hz` clock and `mode` for future transfers.
+    ///
+    /// It is OK to open a device while another one is open: the previous one is closed first.
+    fn open(&mut self, device: SpiDevice, speed_hz: u32, mode: SpiMode) -> io::Result<()>;
+
+    /// Closes the currently-open device, if any.
+    fn close(&mut self) -> io::Result<()>;
+
+    /// Sends `tx` to the currently-open device while simultaneously receiving `rx`.
+    ///
+    /// Both buffers have the same length.
+    fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> io::Result<()>;
+}
+
+/// Stand-in implementation of the EndBASIC SPI operations that always returns an error.
+#[derive(Default)]
+pub(crate) struct NoopSpi {}
+
+impl Spi for NoopSpi {
+    fn open(&mut self, _device: SpiDevice, _speed_hz: u32, _mode: SpiMode) -> io::Result<()> {
+        Err(io::Error::other("SPI backend not compiled in"))
+    }
+
+    fn close(&mut self) -> io::Result<()> {
+        Err(io::Error::other("SPI backend not compiled in"))
+    }
+
+    fn transfer(&mut self, _tx: &[u8], _rx: &mut [u8]) -> io::Result<()> {
+        Err(io::Error::other("SPI backend not compiled in"))
+    }
+}
+
+/// Closes the open SPI device in a best-effort manner.
+struct SpiClearable {
+    spi: Rc<RefCell<dyn Spi>>,
+}
+
+impl SpiClearable {
+    /// Creates a new clearable for `spi`.
+    fn new(spi: Rc<RefCell<dyn Spi>>) -> Box<Self> {
+        Box::from(Self { spi })
+    }
+}
+
+impl Clearable for SpiClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        let _ = self.spi.borrow_mut().close();
+    }
+}
+
+/// The `SPI_OPEN` command.
+pub struct SpiOpenCommand {
+    metadata: CallableMetadata,
+    spi: Rc<RefCell<dyn Spi>>,
+    reserved: Vec<SpiDevice>,
+}
+
+impl SpiOpenCommand {
+    /// Creates a new instance of the command.  The `reserved` devices are in use by other parts
+    /// of the interpreter (such as an LCD console) and cannot be opened.
+    pub fn new(spi: Rc<RefCell<dyn Spi>>, reserved: Vec<SpiDevice>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SPI_OPEN")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("bus"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("cs"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("speedhz"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("mode"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Opens an SPI device for subsequent transfers.
+The device is identified by its bus% number and by the cs% chip select line within that bus.  \
+The clock of the bus is set to speedhz%, which must be positive, and mode% selects the clock \
+polarity and phase as a number between 0 and 3.
+Only one SPI device can be open at any given time, and opening a device closes the previously \
+open one.  Devices in use by the console, such as the bus of an LCD, cannot be opened.",
+                )
+                .build(),
+            spi,
+            reserved,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SpiOpenCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(4, scope.nargs());
+        let (bus, bus_pos) = scope.pop_integer_with_pos();
+        let bus = u8_from_i32(bus, bus_pos, "Bus number")?;
+        let cs = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            u8_from_i32(i, pos, "Chip select")?
+        };
+        let speed_hz = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            if i <= 0 {
+                return Err(Error::SyntaxError(pos, format!("SPI speed {} must be positive", i)));
+            }
+            i as u32
+        };
+        let mode = {
+            let (i, pos) = scope.pop_integer_with_pos();
+            SpiMode::from_i32(i, pos)?
+        };
+
+        let device = SpiDevice { bus, cs };
+        if self.reserved.contains(&device) {
+            return Err(Error::SyntaxError(
+                bus_pos,
+                format!("SPI bus {} chip select {} is in use by the console", bus, cs),
+            ));
+        }
+
+        self.spi.borrow_mut().open(device, speed_hz, mode).map_err(|e| scope.io_error(e))
+    }
+}
+
+/// The `SPI_XFER` command.
+pub struct SpiXferCommand {
+    metadata: CallableMetadata,
+    spi: Rc<RefCell<dyn Spi>>,
+}
+
+impl SpiXferCommand {
+    /// Creates a new instance of the command.
+    pub fn new(spi: Rc<RefCell<dyn Spi>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SPI_XFER")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredRef(
+                            RequiredRefSyntax {
+                                name: Cow::Borrowed("txarray"),
+                                require_array: true,
+                                define_undefined: false,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredRef(
+                            RequiredRefSyntax {
+                                name: Cow::Borrowed("rxarray"),
+                                require_array: true,
+                                define_undefined: false,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Performs a full-duplex transfer with the open SPI device.
+Sends the bytes in txarray to the device opened with SPI_OPEN while simultaneously receiving \
+the same number of bytes into rxarray.  Both arrays must be one-dimensional INTEGER arrays of \
+the same length, and all values in txarray must be between 0 and 255.
+It is OK for txarray and rxarray to be the same array, in which case the sent bytes are \
+replaced with the received ones.",
+                )
+                .build(),
+            spi,
+        })
+    }
+
+    /// Validates that `name` is a one-dimensional integer array and returns its length.
+    fn array_len(symbols: &Symbols, name: &str, vtype: ExprType, pos: LineCol) -> Result<usize> {
+        let array = match symbols
+            .get(&VarRef::new(name, Some(vtype)))
+            .map_err(|e| Error::SyntaxError(pos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!(),
+        };
+
+        if array.subtype() != ExprType::Integer {
+            return Err(Error::SyntaxError(pos, format!("{} must be an INTEGER array", name)));
+        }
+        if array.dimensions().len() != 1 {
+            return Err(Error::SyntaxError(
+                pos,
+                format!("{} must be a one-dimensional array", name),
+            ));
+        }
+        Ok(array.dimensions()[0])
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for SpiXferCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (txname, txtype, txpos) = scope.pop_varref_with_pos();
+        let (rxname, rxtype, rxpos) = scope.pop_varref_with_pos();
+        let (txname, rxname) = (txname.to_string(), rxname.to_string());
+
+        let txlen = SpiXferCommand::array_len(machine.get_symbols(), &txname, txtype, txpos)?;
+        let rxlen = SpiXferCommand::array_len(machine.get_symbols(), &rxname, rxtype, rxpos)?;
+        if txlen != rxlen {
+            return Err(Error::SyntaxError(
+                rxpos,
+                format!(
+                    "{} has {} elements but {} has {}; both arrays must have the same length",
+                    txname, txlen, rxname, rxlen
+                ),
+            ));
+        }
+
+        let mut tx = Vec::with_capacity(txlen);
+        if let Ok(Some(Symbol::Array(array))) =
+            machine.get_symbols().get(&VarRef::new(&txname, Some(txtype)))
+        {
+            let values = array.as_integers().expect("Array subtype was checked to be INTEGER");
+            for (i, b) in values.iter().enumerate() {
+                match u8::try_from(*b) {
+                    Ok(b) => tx.push(b),
+                    Err(_) => {
+                        return Err(Error::SyntaxError(
+                            txpos,
+                            format!("Value {} at index {} of {} is not a byte", b, i, txname),
+                        ))
+                    }
+                }
+            }
+        }
+
+        let mut rx = vec![0; rxlen];
+        self.spi.borrow_mut().transfer(&tx, &mut rx).map_err(|e| scope.io_error(e))?;
+
+        if let Ok(Some(Symbol::Array(array))) =
+            machine.get_mut_symbols().get_mut(&VarRef::new(&rxname, Some(rxtype)))
+        {
+            let values = array.as_integers_mut().expect("Array subtype was checked to be INTEGER");
+            for (value, b) in values.iter_mut().zip(rx) {
+                *value = i32::from(b);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds all symbols provided by this module to the given `machine`.
+///
+/// The `reserved` devices are in use by other parts of the interpreter and cannot be opened.
+pub fn add_all(machine: &mut Machine, spi: Rc<RefCell<dyn Spi>>, reserved: Vec<SpiDevice>) {
+    machine.add_clearable(SpiClearable::new(spi.clone()));
+    machine.add_callable(SpiOpenCommand::new(spi.clone(), reserved));
+    machine.add_callable(SpiXferCommand::new(spi));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use std::collections::VecDeque;
+
+    #[derive(Default)]
+    struct MockSpiBus {
+        max_size: usize,
+
+        writes: Vec<Vec<u8>>,
+    }
+
+    impl Write for MockSpiBus {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let partial = if buf.len() < self.max_size { buf } else { &buf[0..self.max_size] };
+            self.writes.push(partial.to_owned());
+            Ok(partial.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl SpiBus for MockSpiBus {
+        fn max_size(&self) -> usize {
+            self.max_size
+        }
+    }
+
+    /// Mock SPI implementation that records all operations and supplies canned received bytes.
+    #[derive(Default)]
+    struct RecordingSpi {
+        /// Parameters of the currently-open device, if any.
+        open: Option<(SpiDevice, u32, SpiMode)>,
+
+        /// Bytes sent by every transfer, in order.
+        txs: Vec<Vec<u8>>,
+
+        /// Bytes to return on future transfers.  Zeros are returned when this runs out.
+        rx_data: VecDeque<u8>,
+    }
+
+    impl Spi for RecordingSpi {
+        fn open(&mut self, device: SpiDevice, speed_hz: u32, mode: SpiMode) -> io::Result<()> {
+            self.open = Some((device, speed_hz, mode));
+            Ok(())
+        }
+
+        fn close(&mut self) -> io::Result<()> {
+            self.open = None;
+            Ok(())
+        }
+
+        fn transfer(&mut self, tx: &[u8], rx: &mut [u8]) -> io::Result<()> {
+            if self.open.is_none() {
+                return Err(io::Error::new(io::ErrorKind::NotConnected, "Device not open"));
+            }
+            self.txs.push(tx.to_owned());
+            for b in rx.iter_mut() {
+                *b = self.rx_data.pop_front().unwrap_or(0);
+            }
+            Ok(())
+        }
+    }
+
+    /// Creates a tester with the SPI commands backed by `spi` and with `reserved` devices.
+    fn tester(spi: Rc<RefCell<RecordingSpi>>, reserved: Vec<SpiDevice>) -> Tester {
+        Tester::empty()
+            .add_callable(SpiOpenCommand::new(spi.clone(), reserved))
+            .add_callable(SpiXferCommand::new(spi))
+    }
+
+    #[test]
+    fn test_spi_open_ok() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        tester(spi.clone(), vec![]).run("SPI_OPEN 1, 2, 500000, 3").check();
+        assert_eq!(Some((SpiDevice { bus: 1, cs: 2 }, 500000, SpiMode::Mode3)), spi.borrow().open);
+    }
+
+    #[test]
+    fn test_spi_open_reserved() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        let reserved = vec![SpiDevice { bus: 0, cs: 0 }];
+        tester(spi.clone(), reserved.clone()).run("SPI_OPEN 0, 1, 1000, 0").check();
+        tester(spi, reserved)
+            .run("SPI_OPEN 0, 0, 1000, 0")
+            .expect_err("1:10: SPI bus 0 chip select 0 is in use by the console")
+            .check();
+    }
+
+    #[test]
+    fn test_spi_open_errors() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        for (stmt, exp_err) in [
+            ("SPI_OPEN -1, 0, 1000, 0", "1:10: Bus number -1 cannot be negative"),
+            ("SPI_OPEN 256, 0, 1000, 0", "1:10: Bus number 256 is too large"),
+            ("SPI_OPEN 0, -1, 1000, 0", "1:13: Chip select -1 cannot be negative"),
+            ("SPI_OPEN 0, 0, 0, 0", "1:16: SPI speed 0 must be positive"),
+            ("SPI_OPEN 0, 0, -5, 0", "1:16: SPI speed -5 must be positive"),
+            ("SPI_OPEN 0, 0, 1000, -1", "1:22: SPI mode -1 must be between 0 and 3"),
+            ("SPI_OPEN 0, 0, 1000, 4", "1:22: SPI mode 4 must be between 0 and 3"),
+        ] {
+            tester(spi.clone(), vec![]).run(stmt).expect_err(exp_err).check();
+        }
+        assert_eq!(None, spi.borrow().open);
+
+        check_stmt_compilation_err(
+            "1:1: SPI_OPEN expected bus%, cs%, speedhz%, mode%",
+            "SPI_OPEN 0, 0, 1000",
+        );
+        check_stmt_compilation_err("1:10: STRING is not a number", "SPI_OPEN \"0\", 0, 1000, 0");
+    }
+
+    #[test]
+    fn test_spi_xfer_ok() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        spi.borrow_mut().rx_data.extend([0xa0, 0xb1, 0xc2]);
+        tester(spi.clone(), vec![])
+            .run(
+                "DIM tx(3)\nDIM rx(3)\ntx(0) = 1: tx(1) = 128: tx(2) = 255\n\
+                SPI_OPEN 0, 0, 1000, 0\nSPI_XFER tx, rx",
+            )
+            .expect_array_simple("tx", ExprType::Integer, vec![1.into(), 128.into(), 255.into()])
+            .expect_array_simple(
+                "rx",
+                ExprType::Integer,
+                vec![0xa0.into(), 0xb1.into(), 0xc2.into()],
+            )
+            .check();
+        assert_eq!(vec![vec![1, 128, 255]], spi.borrow().txs);
+    }
+
+    #[test]
+    fn test_spi_xfer_same_array() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        spi.borrow_mut().rx_data.extend([7, 8]);
+        tester(spi.clone(), vec![])
+            .run("DIM buf(2)\nbuf(0) = 3: buf(1) = 4\nSPI_OPEN 0, 0, 1000, 0\nSPI_XFER buf, buf")
+            .expect_array_simple("buf", ExprType::Integer, vec![7.into(), 8.into()])
+            .check();
+        assert_eq!(vec![vec![3, 4]], spi.borrow().txs);
+    }
+
+    #[test]
+    fn test_spi_xfer_length_mismatch() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        tester(spi.clone(), vec![])
+            .run("DIM tx(3)\nDIM rx(2)\nSPI_OPEN 0, 0, 1000, 0\nSPI_XFER tx, rx")
+            .expect_err(
+                "4:14: TX has 3 elements but RX has 2; both arrays must have the same length",
+            )
+            .expect_array("tx", ExprType::Integer, &[3], vec![])
+            .expect_array("rx", ExprType::Integer, &[2], vec![])
+            .check();
+        assert!(spi.borrow().txs.is_empty());
+    }
+
+    #[test]
+    fn test_spi_xfer_errors() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        tester(spi.clone(), vec![])
+            .run("DIM tx(2)\nDIM rx(2)\ntx(1) = 256\nSPI_OPEN 0, 0, 1000, 0\nSPI_XFER tx, rx")
+            .expect_err("5:10: Value 256 at index 1 of TX is not a byte")
+            .expect_array("tx", ExprType::Integer, &[2], vec![(&[1], 256.into())])
+            .expect_array("rx", ExprType::Integer, &[2], vec![])
+            .check();
+
+        tester(spi.clone(), vec![])
+            .run("DIM tx(2) AS DOUBLE\nDIM rx(2)\nSPI_XFER tx, rx")
+            .expect_err("3:10: TX must be an INTEGER array")
+            .expect_array("tx", ExprType::Double, &[2], vec![])
+            .expect_array("rx", ExprType::Integer, &[2], vec![])
+            .check();
+
+        tester(spi.clone(), vec![])
+            .run("DIM tx(2, 2)\nDIM rx(2)\nSPI_XFER tx, rx")
+            .expect_err("3:10: TX must be a one-dimensional array")
+            .expect_array("tx", ExprType::Integer, &[2, 2], vec![])
+            .expect_array("rx", ExprType::Integer, &[2], vec![])
+            .check();
+
+        assert!(spi.borrow().txs.is_empty());
+    }
+
+    #[test]
+    fn test_spi_xfer_not_open() {
+        let spi = Rc::from(RefCell::from(RecordingSpi::default()));
+        tester(spi.clone(), vec![])
+            .run("DIM tx(2)\nDIM rx(2)\nSPI_XFER tx, rx")
+            .expect_err("3:1: Device not open")
+            .expect_array("tx", ExprType::Integer, &[2], vec![])
+            .expect_array("rx", ExprType::Integer, &[2], vec![])
+            .check();
+
+        assert!(spi.borrow().txs.is_empty());
+    }
+
+    #[test]
+    fn test_spi_noop() {
+        check_stmt_err("1:1: SPI backend not compiled in", "SPI_OPEN 0, 0, 1000, 0");
+        Tester::default()
+            .run("DIM tx(1)\nSPI_XFER tx, tx")
+            .expect_err("2:1: SPI backend not compiled in")
+            .expect_array("tx", ExprType::Integer, &[1], vec![])
+            .check();
+    }
+
+    #[test]
+    fn test_write_all_shorter_than_max_size() {
+        let mut bus = MockSpiBus { max_size: 100, ..Default::default() };
+        write_all(&mut bus, &[0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(vec![vec![0, 1, 2, 3, 4]], bus.writes);
+    }
+
+    #[test]
+    fn test_write_all_equal_to_max_size() {
+        let mut bus = MockSpiBus { max_size: 3, ..Default::default() };
+        write_all(&mut bus, &[0, 1, 2]).unwrap();
+        assert_eq!(vec![vec![0, 1, 2]], bus.writes);
+    }
+
+    #[test]
+    fn test_write_all_greater_than_max_size() {
+        let mut bus = MockSpiBus { max_size: 6, ..Default::default() };
+        write_all(&mut bus, &[0, 1, 2, 3, 4, 5, 6]).unwrap();
+        assert_eq!(vec![vec![0, 1, 2, 3, 4, 5], vec![6]], bus.writes);
+    }
+}
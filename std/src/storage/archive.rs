@@ -0,0 +1,666 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Export and import of whole drives as single archive files.
+//!
+//! Archives start with the `ARCHIVE_MAGIC` bytes followed by a 16-bit format version and a 32-bit
+//! count of entries.  Each entry then carries a type byte, a 16-bit name length, the UTF-8 name
+//! of the entry relative to the root of the drive, a 64-bit modification time in seconds since
+//! the Unix epoch and, for files only, a 64-bit content length followed by the content.  All
+//! integers are little-endian.  Directories always precede the entries they contain.
+
+use super::cmds::CATEGORY;
+use crate::storage::Storage;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType};
+use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
+use endbasic_core::exec::{Machine, Result, Scope};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::convert::{TryFrom, TryInto};
+use std::io;
+use std::rc::Rc;
+use std::str;
+
+/// Bytes that identify an archive file.
+const ARCHIVE_MAGIC: &[u8] = b"EBDRIVE\0";
+
+/// Version of the archive format written by `encode`.
+const ARCHIVE_VERSION: u16 = 1;
+
+/// Type byte of directory entries.
+const TYPE_DIR: u8 = 1;
+
+/// Type byte of file entries.
+const TYPE_FILE: u8 = 2;
+
+/// A single entry of a drive archive.
+#[derive(Debug, PartialEq)]
+enum ArchiveEntry {
+    /// A directory with its path and modification time.
+    Dir(String, i64),
+
+    /// A file with its path, modification time and contents.
+    File(String, i64, Vec<u8>),
+}
+
+impl ArchiveEntry {
+    /// Returns the path of the entry relative to the root of the drive.
+    fn name(&self) -> &str {
+        match self {
+            ArchiveEntry::Dir(name, _) => name,
+            ArchiveEntry::File(name, _, _) => name,
+        }
+    }
+}
+
+/// Constructs the error returned when an archive cannot be decoded.
+fn corrupt_error<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Corrupt drive archive: {}", message.into()))
+}
+
+/// Serializes `entries` into an archive.
+fn encode(entries: &[ArchiveEntry]) -> io::Result<Vec<u8>> {
+    let too_big = || io::Error::new(io::ErrorKind::InvalidInput, "Drive too large to archive");
+
+    let mut data = ARCHIVE_MAGIC.to_vec();
+    data.extend_from_slice(&ARCHIVE_VERSION.to_le_bytes());
+    data.extend_from_slice(&u32::try_from(entries.len()).map_err(|_| too_big())?.to_le_bytes());
+    for entry in entries {
+        let (type_byte, name, date) = match entry {
+            ArchiveEntry::Dir(name, date) => (TYPE_DIR, name, date),
+            ArchiveEntry::File(name, date, _) => (TYPE_FILE, name, date),
+        };
+        data.push(type_byte);
+        data.extend_from_slice(&u16::try_from(name.len()).map_err(|_| too_big())?.to_le_bytes());
+        data.extend_from_slice(name.as_bytes());
+        data.extend_from_slice(&date.to_le_bytes());
+        if let ArchiveEntry::File(_, _, content) = entry {
+            data.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            data.extend_from_slice(content);
+        }
+    }
+    Ok(data)
+}
+
+/// Cursor over the raw bytes of an archive being decoded.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Consumes the next `n` bytes.
+    fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        match self.offset.checked_add(n) {
+            Some(end) if end <= self.data.len() => {
+                let bytes = &self.data[self.offset..end];
+                self.offset = end;
+                Ok(bytes)
+            }
+            _ => Err(corrupt_error("unexpected end of data")),
+        }
+    }
+
+    /// Consumes the next byte.
+    fn take_u8(&mut self) -> io::Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    /// Consumes the next little-endian 16-bit integer.
+    fn take_u16(&mut self) -> io::Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().expect("Took exactly 2 bytes")))
+    }
+
+    /// Consumes the next little-endian 32-bit integer.
+    fn take_u32(&mut self) -> io::Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().expect("Took exactly 4 bytes")))
+    }
+
+    /// Consumes the next little-endian 64-bit integer.
+    fn take_u64(&mut self) -> io::Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().expect("Took exactly 8 bytes")))
+    }
+}
+
+/// Returns true if `name` is a valid path for an archive entry.
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .split('/')
+            .all(|c| !c.is_empty() && c != "." && c != ".." && !c.contains([':', '\\']))
+}
+
+/// Deserializes and validates the archive in `data`.
+///
+/// Validation ensures that entry names are valid and unique and that the parent of every entry
+/// appears earlier in the archive, so that the entries can be recreated in order.
+fn decode(data: &[u8]) -> io::Result<Vec<ArchiveEntry>> {
+    let mut reader = Reader { data, offset: 0 };
+    if reader.take(ARCHIVE_MAGIC.len()).ok() != Some(ARCHIVE_MAGIC) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Not a drive archive"));
+    }
+    let version = reader.take_u16()?;
+    if version != ARCHIVE_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Unsupported drive archive version {}", version),
+        ));
+    }
+
+    let nentries = reader.take_u32()?;
+    let mut entries = vec![];
+    let mut dirs = HashSet::new();
+    let mut names = HashSet::new();
+    for _ in 0..nentries {
+        let type_byte = reader.take_u8()?;
+        let name_length = reader.take_u16()?;
+        let name = match str::from_utf8(reader.take(usize::from(name_length))?) {
+            Ok(name) if is_valid_name(name) => name.to_owned(),
+            _ => return Err(corrupt_error("invalid entry name")),
+        };
+        let date = reader.take_u64()? as i64;
+
+        if let Some((parent, _)) = name.rsplit_once('/') {
+            if !dirs.contains(parent) {
+                return Err(corrupt_error(format!("missing parent directory for '{}'", name)));
+            }
+        }
+        if !names.insert(name.clone()) {
+            return Err(corrupt_error(format!("duplicate entry '{}'", name)));
+        }
+
+        let entry = match type_byte {
+            TYPE_DIR => {
+                dirs.insert(name.clone());
+                ArchiveEntry::Dir(name, date)
+            }
+            TYPE_FILE => {
+                let length = usize::try_from(reader.take_u64()?)
+                    .map_err(|_| corrupt_error("unexpected end of data"))?;
+                ArchiveEntry::File(name, date, reader.take(length)?.to_vec())
+            }
+            _ => return Err(corrupt_error(format!("unknown entry type {}", type_byte))),
+        };
+        entries.push(entry);
+    }
+
+    if reader.offset != data.len() {
+        return Err(corrupt_error("trailing data"));
+    }
+    Ok(entries)
+}
+
+/// Normalizes a user-provided `drive` name, which may carry a trailing colon, into the location
+/// of its root directory.
+pub(super) fn drive_root(drive: &str) -> String {
+    format!("{}:/", drive.strip_suffix(':').unwrap_or(drive))
+}
+
+/// Returns the canonical form of `raw_location` as an absolute path within its drive.
+fn absolute_canonical(storage: &Storage, raw_location: &str) -> io::Result<String> {
+    let canonical = storage.make_canonical(raw_location)?;
+    match canonical.split_once(':') {
+        Some((drive, path)) if !path.starts_with('/') => Ok(format!("{}:/{}", drive, path)),
+        _ => Ok(canonical),
+    }
+}
+
+/// Collects all entries under the directory `dir` of the drive rooted at `root` into `entries`,
+/// skipping the file at the absolute canonical location `skip`.
+async fn collect_entries(
+    storage: &Storage,
+    root: &str,
+    dir: &str,
+    skip: &str,
+    entries: &mut Vec<ArchiveEntry>,
+) -> io::Result<()> {
+    let files = storage.enumerate(&format!("{}{}", root, dir)).await?;
+    for (name, metadata) in files.dirents() {
+        let name = format!("{}{}", dir, name);
+        let location = format!("{}{}", root, name);
+        if absolute_canonical(storage, &location)? == skip {
+            continue;
+        }
+        let content = storage.get(&location).await?;
+        entries.push(ArchiveEntry::File(name, metadata.date.unix_timestamp(), content));
+    }
+    for (name, metadata) in files.subdirs() {
+        let name = format!("{}{}", dir, name);
+        entries.push(ArchiveEntry::Dir(name.clone(), metadata.date.unix_timestamp()));
+        Box::pin(collect_entries(storage, root, &format!("{}/", name), skip, entries)).await?;
+    }
+    Ok(())
+}
+
+/// Writes all files and directories in `drive` into the archive file `filename`.
+///
+/// If the archive is stored within the exported drive, any previous version of it is excluded.
+pub async fn export_drive(storage: &mut Storage, drive: &str, filename: &str) -> io::Result<()> {
+    let root = drive_root(drive);
+    let skip = absolute_canonical(storage, filename)?;
+    let mut entries = vec![];
+    collect_entries(storage, &root, "", &skip, &mut entries).await?;
+    let data = encode(&entries)?;
+    storage.put(filename, &data).await
+}
+
+/// Recreates all files and directories in the archive file `filename` into `drive`.
+///
+/// The archive is fully validated before any changes are made to `drive`.  The import fails if
+/// any of the files in the archive already exist in `drive` unless `overwrite` is true.
+/// Modification times are not restored because drives do not support setting them.
+pub async fn import_drive(
+    storage: &mut Storage,
+    drive: &str,
+    filename: &str,
+    overwrite: bool,
+) -> io::Result<()> {
+    let root = drive_root(drive);
+    let entries = decode(&storage.get(filename).await?)?;
+
+    for entry in &entries {
+        let location = format!("{}{}", root, entry.name());
+        match entry {
+            ArchiveEntry::Dir(..) => {
+                if storage.exists(&location).await? {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("Cannot create directory {} over an existing file", location),
+                    ));
+                }
+            }
+            ArchiveEntry::File(..) => {
+                if !overwrite && storage.exists(&location).await? {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("File {} already exists", location),
+                    ));
+                }
+            }
+        }
+    }
+
+    for entry in &entries {
+        let location = format!("{}{}", root, entry.name());
+        match entry {
+            ArchiveEntry::Dir(..) => {
+                if storage.enumerate(&location).await.is_err() {
+                    storage.mkdir(&location).await?;
+                }
+            }
+            ArchiveEntry::File(_, _, content) => storage.put(&location, content).await?,
+        }
+    }
+    Ok(())
+}
+
+/// The `EXPORTDRIVE` command.
+pub struct ExportDriveCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl ExportDriveCommand {
+    /// Creates a new `EXPORTDRIVE` command that archives a whole drive into a file.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EXPORTDRIVE")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("drive"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Saves all files and directories of a drive into a single archive file.
+The drive$ is given without a colon at the end and the archive is written to filename$, which can \
+live in any drive.  The archive records the names, contents and modification dates of all files \
+and can be restored with IMPORTDRIVE, which makes it useful to back up a drive or to move its \
+contents between computers.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ExportDriveCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let drive = scope.pop_string();
+        let filename = scope.pop_string();
+
+        let mut storage = self.storage.borrow_mut();
+        export_drive(&mut storage, &drive, &filename).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `IMPORTDRIVE` command.
+pub struct ImportDriveCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl ImportDriveCommand {
+    /// Creates a new `IMPORTDRIVE` command that restores an archive created by `EXPORTDRIVE`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("IMPORTDRIVE")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("drive"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("filename"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("drive"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("filename"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("overwrite"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Restores the files and directories of an archive into a drive.
+The filename$ must be an archive created by EXPORTDRIVE and drive$ is given without a colon at \
+the end.  The whole archive is validated before making any changes to the drive, and the import \
+refuses to replace existing files unless overwrite? is TRUE.  Files in the drive that are not in \
+the archive are left untouched.
+Restored files get new modification dates.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ImportDriveCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let drive = scope.pop_string();
+        let filename = scope.pop_string();
+        let overwrite = if scope.nargs() == 0 { false } else { scope.pop_boolean() };
+
+        let mut storage = self.storage.borrow_mut();
+        import_drive(&mut storage, &drive, &filename, overwrite)
+            .await
+            .map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+    use futures_lite::future::block_on;
+
+    /// Creates a tester with a second memory drive `OTHER` and a few files in `MEMORY`.
+    fn setup() -> Tester {
+        let t = Tester::default()
+            .write_file("a.bas", "PRINT \"a\"")
+            .write_file_bytes("data.bin", &[0, 1, 255]);
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            block_on(storage.mount("other", "memory://", false)).unwrap();
+            block_on(storage.mkdir("MEMORY:/games")).unwrap();
+            block_on(storage.mkdir("MEMORY:/games/old")).unwrap();
+            block_on(storage.put("MEMORY:/games/pong.bas", b"PRINT \"pong\"")).unwrap();
+            block_on(storage.mkdir("MEMORY:/empty")).unwrap();
+        }
+        t
+    }
+
+    #[test]
+    fn test_encode_decode_round_trip() {
+        let entries = vec![
+            ArchiveEntry::File("a.bas".to_owned(), 1_000, b"abc".to_vec()),
+            ArchiveEntry::Dir("dir".to_owned(), -5),
+            ArchiveEntry::File("dir/empty".to_owned(), 0, vec![]),
+            ArchiveEntry::Dir("dir/sub".to_owned(), 2_000),
+            ArchiveEntry::File("dir/sub/b.bin".to_owned(), 3_000, vec![0, 255]),
+        ];
+        let data = encode(&entries).unwrap();
+        assert!(data.starts_with(ARCHIVE_MAGIC));
+        assert_eq!(entries, decode(&data).unwrap());
+
+        assert!(decode(&encode(&[]).unwrap()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let check = |exp_error: &str, data: &[u8]| {
+            let err = decode(data).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+            assert_eq!(exp_error, format!("{}", err));
+        };
+
+        check("Not a drive archive", b"");
+        check("Not a drive archive", b"PRINT \"Hello\"");
+
+        let mut data = encode(&[]).unwrap();
+        data[ARCHIVE_MAGIC.len()] = 2;
+        check("Unsupported drive archive version 2", &data);
+
+        let data = encode(&[ArchiveEntry::File("a".to_owned(), 0, b"abc".to_vec())]).unwrap();
+        check("Corrupt drive archive: unexpected end of data", &data[0..data.len() - 1]);
+        let mut extra = data.clone();
+        extra.push(0);
+        check("Corrupt drive archive: trailing data", &extra);
+        let mut bad_type = data.clone();
+        bad_type[ARCHIVE_MAGIC.len() + 6] = 7;
+        check("Corrupt drive archive: unknown entry type 7", &bad_type);
+
+        check(
+            "Corrupt drive archive: invalid entry name",
+            &encode(&[ArchiveEntry::Dir("../x".to_owned(), 0)]).unwrap(),
+        );
+        check(
+            "Corrupt drive archive: invalid entry name",
+            &encode(&[ArchiveEntry::File("C:x".to_owned(), 0, vec![])]).unwrap(),
+        );
+        check(
+            "Corrupt drive archive: missing parent directory for 'dir/a'",
+            &encode(&[ArchiveEntry::File("dir/a".to_owned(), 0, vec![])]).unwrap(),
+        );
+        check(
+            "Corrupt drive archive: duplicate entry 'a'",
+            &encode(&[
+                ArchiveEntry::File("a".to_owned(), 0, vec![]),
+                ArchiveEntry::Dir("a".to_owned(), 0),
+            ])
+            .unwrap(),
+        );
+    }
+
+    /// Returns the archive that `EXPORTDRIVE` should generate for the `MEMORY` drive in `setup`.
+    fn exp_archive() -> Vec<u8> {
+        let date = 1_588_757_875;
+        encode(&[
+            ArchiveEntry::File("a.bas".to_owned(), date, b"PRINT \"a\"".to_vec()),
+            ArchiveEntry::File("data.bin".to_owned(), date, vec![0, 1, 255]),
+            ArchiveEntry::Dir("empty".to_owned(), date),
+            ArchiveEntry::Dir("games".to_owned(), date),
+            ArchiveEntry::File("games/pong.bas".to_owned(), date, b"PRINT \"pong\"".to_vec()),
+            ArchiveEntry::Dir("games/old".to_owned(), date),
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exportdrive_importdrive_round_trip() {
+        let mut t = setup();
+        t.run(r#"EXPORTDRIVE "MEMORY", "OTHER:/backup.ebd""#)
+            .expect_file("MEMORY:/a.bas", "PRINT \"a\"")
+            .expect_file_bytes("MEMORY:/data.bin", &[0, 1, 255])
+            .expect_file("MEMORY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file_bytes("OTHER:/backup.ebd", &exp_archive())
+            .check();
+
+        t.run(r#"MOUNT "memory://" AS "copy": IMPORTDRIVE "COPY:", "OTHER:/backup.ebd""#)
+            .expect_file("MEMORY:/a.bas", "PRINT \"a\"")
+            .expect_file_bytes("MEMORY:/data.bin", &[0, 1, 255])
+            .expect_file("MEMORY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file("COPY:/a.bas", "PRINT \"a\"")
+            .expect_file_bytes("COPY:/data.bin", &[0, 1, 255])
+            .expect_file("COPY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file_bytes("OTHER:/backup.ebd", &exp_archive())
+            .check();
+
+        let storage = t.get_storage();
+        let storage = storage.borrow();
+        let subdirs = block_on(storage.enumerate("COPY:/")).unwrap().subdirs().clone();
+        assert_eq!(vec!["empty", "games"], subdirs.keys().collect::<Vec<&String>>());
+        assert!(block_on(storage.enumerate("COPY:/games/old")).is_ok());
+    }
+
+    #[test]
+    fn test_exportdrive_skips_own_archive() {
+        setup()
+            .run(r#"EXPORTDRIVE "MEMORY", "backup.ebd": EXPORTDRIVE "MEMORY", "backup.ebd""#)
+            .expect_file("MEMORY:/a.bas", "PRINT \"a\"")
+            .expect_file_bytes("MEMORY:/data.bin", &[0, 1, 255])
+            .expect_file("MEMORY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file_bytes("MEMORY:/backup.ebd", &exp_archive())
+            .check();
+    }
+
+    #[test]
+    fn test_importdrive_overwrite() {
+        let mut t = setup().write_file_bytes("OTHER:/backup.ebd", &exp_archive());
+        block_on(t.get_storage().borrow_mut().put("MEMORY:/a.bas", b"changed")).unwrap();
+
+        t.run(r#"IMPORTDRIVE "MEMORY", "OTHER:/backup.ebd""#)
+            .expect_err("1:1: File MEMORY:/a.bas already exists")
+            .expect_file("MEMORY:/a.bas", "changed")
+            .expect_file_bytes("MEMORY:/data.bin", &[0, 1, 255])
+            .expect_file("MEMORY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file_bytes("OTHER:/backup.ebd", &exp_archive())
+            .check();
+
+        t.run(r#"IMPORTDRIVE "MEMORY", "OTHER:/backup.ebd", TRUE"#)
+            .expect_file("MEMORY:/a.bas", "PRINT \"a\"")
+            .expect_file_bytes("MEMORY:/data.bin", &[0, 1, 255])
+            .expect_file("MEMORY:/games/pong.bas", "PRINT \"pong\"")
+            .expect_file_bytes("OTHER:/backup.ebd", &exp_archive())
+            .check();
+    }
+
+    #[test]
+    fn test_importdrive_corrupt_archive_writes_nothing() {
+        let mut data = encode(&[
+            ArchiveEntry::File("good.bas".to_owned(), 0, b"good".to_vec()),
+            ArchiveEntry::File("bad.bas".to_owned(), 0, b"bad".to_vec()),
+        ])
+        .unwrap();
+        data.truncate(data.len() - 1);
+
+        Tester::default()
+            .write_file_bytes("backup.ebd", &data)
+            .run(r#"MOUNT "memory://" AS "other": IMPORTDRIVE "OTHER", "backup.ebd""#)
+            .expect_err("1:31: Corrupt drive archive: unexpected end of data")
+            .expect_file_bytes("MEMORY:/backup.ebd", &data)
+            .check();
+    }
+
+    #[test]
+    fn test_importdrive_dir_over_file() {
+        let data = encode(&[ArchiveEntry::Dir("games".to_owned(), 0)]).unwrap();
+        Tester::default()
+            .write_file_bytes("backup.ebd", &data)
+            .write_file("games", "")
+            .run(r#"IMPORTDRIVE "MEMORY", "backup.ebd", TRUE"#)
+            .expect_err("1:1: Cannot create directory MEMORY:/games over an existing file")
+            .expect_file_bytes("MEMORY:/backup.ebd", &data)
+            .expect_file("MEMORY:/games", "")
+            .check();
+    }
+
+    #[test]
+    fn test_exportdrive_importdrive_errors() {
+        check_stmt_compilation_err("1:1: EXPORTDRIVE expected drive$, filename$", "EXPORTDRIVE");
+        check_stmt_compilation_err(
+            "1:1: IMPORTDRIVE expected <drive$, filename$> | <drive$, filename$, overwrite?>",
+            "IMPORTDRIVE \"a\"",
+        );
+        check_stmt_err("1:1: Drive 'X' is not mounted", r#"EXPORTDRIVE "X", "a.ebd""#);
+        check_stmt_err("1:1: Entry not found", r#"IMPORTDRIVE "MEMORY", "a.ebd""#);
+    }
+}
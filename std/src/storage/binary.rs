@@ -0,0 +1,440 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Binary file I/O via byte arrays.
+
+use super::channels::CATEGORY;
+use crate::storage::Storage;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType, VarRef};
+use endbasic_core::compiler::{
+    ArgSepSyntax, RequiredRefSyntax, RequiredValueSyntax, SingularArgSyntax,
+};
+use endbasic_core::exec::{Error, Machine, Result, Scope};
+use endbasic_core::syms::{
+    Array, Callable, CallableMetadata, CallableMetadataBuilder, Symbol, SymbolKey,
+};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::convert::TryFrom;
+use std::rc::Rc;
+
+/// Syntax of the commands that move bytes between a file and an array.
+const FILE_ARRAY_SYNTAX: &[SingularArgSyntax] = &[
+    SingularArgSyntax::RequiredValue(
+        RequiredValueSyntax { name: Cow::Borrowed("filename"), vtype: ExprType::Text },
+        ArgSepSyntax::Exactly(ArgSep::Long),
+    ),
+    SingularArgSyntax::RequiredRef(
+        RequiredRefSyntax {
+            name: Cow::Borrowed("array"),
+            require_array: true,
+            define_undefined: false,
+        },
+        ArgSepSyntax::End,
+    ),
+];
+
+/// Validates that the array `name` of type `vtype` referenced at `pos` can hold bytes and
+/// returns a reference to it to look up its symbol.
+fn byte_array_ref(name: SymbolKey, vtype: ExprType, pos: LineCol) -> Result<VarRef> {
+    if vtype != ExprType::Integer {
+        return Err(Error::SyntaxError(
+            pos,
+            format!("Array {} must be of type INTEGER but is {}", name, vtype),
+        ));
+    }
+    Ok(VarRef::new(name.to_string(), Some(vtype)))
+}
+
+/// Returns the number of elements of the byte `array` named `vref` referenced at `pos`, which
+/// must be one-dimensional.
+fn byte_array_len(array: &Array, vref: &VarRef, pos: LineCol) -> Result<usize> {
+    match array.dimensions() {
+        [n] => Ok(*n),
+        dims => Err(Error::SyntaxError(
+            pos,
+            format!("Array {} must have 1 dimension but has {}", vref.name(), dims.len()),
+        )),
+    }
+}
+
+/// The `BLOAD` command.
+pub struct BloadCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl BloadCommand {
+    /// Creates a new `BLOAD` command that loads files from `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("BLOAD")
+                .with_syntax(&[(FILE_ARRAY_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Loads the bytes of a file into an array.
+The array must be a one-dimensional INTEGER array.  The array is resized to have as many \
+elements as bytes are in the file and each element receives the value of one byte, in the \
+0 to 255 range.  Use UBOUND to know how many bytes were loaded.
+The file must not be empty.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for BloadCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (filename, filename_pos) = scope.pop_string_with_pos();
+        let (name, vtype, pos) = scope.pop_varref_with_pos();
+
+        let vref = byte_array_ref(name, vtype, pos)?;
+        let array = match machine
+            .get_mut_symbols()
+            .get_mut(&vref)
+            .map_err(|e| Error::SyntaxError(pos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!("Array existence guaranteed by the compiler"),
+        };
+        byte_array_len(array, &vref, pos)?;
+
+        let content = self.storage.borrow().get(&filename).await.map_err(|e| scope.io_error(e))?;
+        if content.is_empty() {
+            return Err(Error::SyntaxError(
+                filename_pos,
+                format!("Cannot load empty file {} into an array", filename),
+            ));
+        }
+
+        if i32::try_from(content.len()).is_err() {
+            return Err(scope.internal_error("File too large"));
+        }
+        let mut loaded = Array::new(ExprType::Integer, vec![content.len()]);
+        let values = loaded.as_integers_mut().expect("Array was created as INTEGER");
+        for (value, b) in values.iter_mut().zip(content) {
+            *value = i32::from(b);
+        }
+        machine
+            .get_mut_symbols()
+            .replace_array(&vref, loaded)
+            .map_err(|e| Error::EvalError(pos, format!("{}", e)))?;
+        Ok(())
+    }
+}
+
+/// The `BSAVE` command.
+pub struct BsaveCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl BsaveCommand {
+    /// Creates a new `BSAVE` command that saves files to `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("BSAVE")
+                .with_syntax(&[(FILE_ARRAY_SYNTAX, None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Saves the elements of an array as the bytes of a file.
+The array must be a one-dimensional INTEGER array and all of its elements must be in the 0 to \
+255 range.  Each element is written as one byte, and any previous contents of the file are \
+replaced.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for BsaveCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let filename = scope.pop_string();
+        let (name, vtype, pos) = scope.pop_varref_with_pos();
+
+        let vref = byte_array_ref(name, vtype, pos)?;
+        let array = match machine
+            .get_symbols()
+            .get(&vref)
+            .map_err(|e| Error::SyntaxError(pos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!("Array existence guaranteed by the compiler"),
+        };
+        let len = byte_array_len(array, &vref, pos)?;
+
+        let values = array.as_integers().expect("Array type validated above");
+        let mut content = Vec::with_capacity(len);
+        for (i, v) in values.iter().enumerate() {
+            match u8::try_from(*v) {
+                Ok(b) => content.push(b),
+                Err(_) => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!(
+                            "Value {} in {}({}) is out of range 0..255 for a byte",
+                            v,
+                            vref.name(),
+                            i
+                        ),
+                    ))
+                }
+            }
+        }
+
+        self.storage.borrow_mut().put(&filename, &content).await.map_err(|e| scope.io_error(e))
+    }
+}
+
+/// The `FILEREADB` function.
+pub struct FilereadbFunction {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl FilereadbFunction {
+    /// Creates a new `FILEREADB` function that reads files from `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FILEREADB")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("offset"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("length"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Reads a range of bytes from a file.
+Returns a string with one character per byte read, starting at the zero-based offset% and \
+reading at most length% bytes.  Each character's code, as returned by ASC, is the value of its \
+byte.
+If the range extends past the end of the file, this returns only the bytes that are available, \
+so the result may be shorter than requested or empty.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for FilereadbFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(3, scope.nargs());
+        let filename = scope.pop_string();
+        let (offset, offset_pos) = scope.pop_integer_with_pos();
+        let (length, length_pos) = scope.pop_integer_with_pos();
+
+        let offset = usize::try_from(offset).map_err(|_| {
+            Error::SyntaxError(offset_pos, format!("Offset {} must be positive", offset))
+        })?;
+        let length = usize::try_from(length).map_err(|_| {
+            Error::SyntaxError(length_pos, format!("Length {} must be positive", length))
+        })?;
+
+        let content = self.storage.borrow().get(&filename).await.map_err(|e| scope.io_error(e))?;
+        let text =
+            content.into_iter().skip(offset).take(length).map(char::from).collect::<String>();
+        scope.return_string(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutils::*;
+    use endbasic_core::ast::{ExprType, Value};
+
+    /// Generates `n` pseudo-random bytes from `seed` with a linear congruential generator.
+    fn random_bytes(seed: u32, n: usize) -> Vec<u8> {
+        let mut state = seed;
+        let mut bytes = Vec::with_capacity(n);
+        for _ in 0..n {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            bytes.push((state >> 16) as u8);
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_bsave_bload_round_trip() {
+        for (seed, n) in [(1, 1), (42, 17), (1234, 300)] {
+            let bytes = random_bytes(seed, n);
+
+            let mut t = Tester::default().write_file_bytes("in.bin", &bytes);
+            let values =
+                bytes.iter().map(|b| Value::Integer(i32::from(*b))).collect::<Vec<Value>>();
+            t.run("DIM a(1): BLOAD \"in.bin\", a: BSAVE \"out.bin\", a: n = UBOUND(a) + 1")
+                .expect_array_simple("a", ExprType::Integer, values)
+                .expect_var("n", n as i32)
+                .expect_file_bytes("MEMORY:/in.bin", &bytes)
+                .expect_file_bytes("MEMORY:/out.bin", &bytes)
+                .check();
+        }
+    }
+
+    #[test]
+    fn test_bload_accounts_memory() {
+        let bytes = random_bytes(1, 100);
+        let mut t = Tester::default().write_file_bytes("in.bin", &bytes);
+        t.run("SUB s: DIM a(1): BLOAD \"in.bin\", a: END SUB: s")
+            .expect_file_bytes("MEMORY:/in.bin", &bytes)
+            .check();
+        assert_eq!(0, t.get_machine().get_symbols().allocated());
+
+        t.get_machine().get_mut_symbols().set_memory_limit(Some(100));
+        t.run("DIM a(1): BLOAD \"in.bin\", a")
+            .expect_array_simple("a", ExprType::Integer, vec![0.into()])
+            .expect_file_bytes("MEMORY:/in.bin", &bytes)
+            .expect_err("1:27: Out of memory: limit is 100 bytes")
+            .check();
+    }
+
+    #[test]
+    fn test_bsave_ok() {
+        Tester::default()
+            .run("DIM a(4): a(0) = 72: a(1) = 105: a(2) = 33: a(3) = 10: BSAVE \"hi.txt\", a")
+            .expect_array_simple(
+                "a",
+                ExprType::Integer,
+                vec![72.into(), 105.into(), 33.into(), 10.into()],
+            )
+            .expect_file("MEMORY:/hi.txt", "Hi!\n")
+            .check();
+    }
+
+    #[test]
+    fn test_bsave_out_of_range() {
+        Tester::default()
+            .run("DIM a(4): a(1) = 256: a(3) = -1: BSAVE \"x.bin\", a")
+            .expect_array_simple(
+                "a",
+                ExprType::Integer,
+                vec![0.into(), 256.into(), 0.into(), (-1).into()],
+            )
+            .expect_err("1:49: Value 256 in A(1) is out of range 0..255 for a byte")
+            .check();
+    }
+
+    #[test]
+    fn test_bload_bsave_errors() {
+        check_stmt_compilation_err("1:1: BLOAD expected filename$, array", "BLOAD \"x.bin\"");
+        check_stmt_compilation_err(
+            "1:26: Requires a reference, not a value",
+            "DIM a(3): BSAVE \"x.bin\", 3",
+        );
+
+        Tester::default()
+            .run("DIM a(3) AS STRING: BLOAD \"x.bin\", a")
+            .expect_array("a", ExprType::Text, &[3], vec![])
+            .expect_err("1:36: Array A must be of type INTEGER but is STRING")
+            .check();
+        Tester::default()
+            .run("DIM a(2, 2): BSAVE \"x.bin\", a")
+            .expect_array("a", ExprType::Integer, &[2, 2], vec![])
+            .expect_err("1:29: Array A must have 1 dimension but has 2")
+            .check();
+        Tester::default()
+            .run("DIM a(2): BLOAD \"missing.bin\", a")
+            .expect_array("a", ExprType::Integer, &[2], vec![])
+            .expect_err("1:11: Entry not found")
+            .check();
+        Tester::default()
+            .write_file("empty.bin", "")
+            .run("DIM a(2): BLOAD \"empty.bin\", a")
+            .expect_array("a", ExprType::Integer, &[2], vec![])
+            .expect_err("1:17: Cannot load empty file empty.bin into an array")
+            .expect_file("MEMORY:/empty.bin", "")
+            .check();
+    }
+
+    #[test]
+    fn test_filereadb_ok() {
+        let mut t = Tester::default().write_file("data.bin", "0123456789");
+        for (offset, length, exp) in
+            [(0, 3, "012"), (4, 2, "45"), (8, 5, "89"), (10, 1, ""), (20, 1, ""), (3, 0, "")]
+        {
+            t.run(format!("d$ = FILEREADB(\"data.bin\", {}, {})", offset, length))
+                .expect_var("d", exp)
+                .expect_file("MEMORY:/data.bin", "0123456789")
+                .check();
+        }
+
+        let bytes = [0u8, 127, 128, 255];
+        let mut t = Tester::default().write_file_bytes("high.bin", &bytes);
+        t.run("d$ = FILEREADB$(\"high.bin\", 3, 10): a = ASC(d$)")
+            .expect_var("d", "\u{ff}")
+            .expect_var("a", 255)
+            .expect_file_bytes("MEMORY:/high.bin", &bytes)
+            .check();
+        t.run("d$ = FILEREADB$(\"high.bin\", 0, 3)")
+            .expect_var("d", "\u{0}\u{7f}\u{80}")
+            .expect_var("a", 255)
+            .expect_file_bytes("MEMORY:/high.bin", &bytes)
+            .check();
+    }
+
+    #[test]
+    fn test_filereadb_errors() {
+        check_expr_compilation_error(
+            "1:10: FILEREADB expected filename$, offset%, length%",
+            "FILEREADB(\"x\", 1)",
+        );
+        check_expr_error("1:25: Offset -1 must be positive", "FILEREADB(\"x\", -1, 1)");
+        check_expr_error("1:28: Length -2 must be positive", "FILEREADB(\"x\", 1, -2)");
+        check_expr_error("1:10: Entry not found", "FILEREADB(\"x\", 1, 2)");
+    }
+}
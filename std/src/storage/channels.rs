@@ -0,0 +1,767 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Sequential file I/O via numbered channels.
+
+use crate::storage::Storage;
+use crate::strings::{format_boolean, format_double, format_integer};
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType, Value, VarRef};
+use endbasic_core::compiler::{
+    ArgSepSyntax, RepeatedSyntax, RepeatedTypeSyntax, RequiredRefSyntax, RequiredValueSyntax,
+    SingularArgSyntax,
+};
+use endbasic_core::exec::{Error, Machine, Result, Scope, ValueTag};
+use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::LineCol;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::rc::Rc;
+use std::str;
+
+/// Category description for all symbols provided by this module and by the binary file I/O
+/// module.
+pub(super) const CATEGORY: &str = "File I/O
+These commands read and write the contents of files from within programs.
+Text files are read and written line by line via numbered channels.  A file is opened with OPEN, \
+which associates it with a channel number between 1 and 255, and all other commands refer to the \
+file via its channel number written as #channel.  Files are read fully into memory when opened \
+for INPUT or APPEND, and the contents written to a channel opened for OUTPUT or APPEND are \
+buffered in memory until the channel is closed with CLOSE.  Any channels left open by a program \
+are closed when RUN finishes executing it, but you should not rely on this.
+Binary files are read and written in one go with BLOAD and BSAVE, which move bytes between a \
+file and a one-dimensional INTEGER array holding one byte per element.  FILEREADB$ returns a \
+range of bytes from a binary file as a string.";
+
+/// Largest channel number that can be used.
+const MAX_CHANNEL: i32 = 255;
+
+/// Width of the zones used by `PRINT #` to align fields separated by commas.
+const ZONE_WIDTH: usize = 14;
+
+/// Mode in which a channel is opened.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelMode {
+    /// The channel reads lines from an existing file.
+    Input,
+
+    /// The channel writes lines to a new file, replacing any previous contents.
+    Output,
+
+    /// The channel writes lines at the end of a file, creating it if it does not exist.
+    Append,
+}
+
+impl fmt::Display for ChannelMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelMode::Input => write!(f, "INPUT"),
+            ChannelMode::Output => write!(f, "OUTPUT"),
+            ChannelMode::Append => write!(f, "APPEND"),
+        }
+    }
+}
+
+impl str::FromStr for ChannelMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "INPUT" => Ok(ChannelMode::Input),
+            "OUTPUT" => Ok(ChannelMode::Output),
+            "APPEND" => Ok(ChannelMode::Append),
+            _ => Err(format!("Invalid file mode '{}'; must be INPUT, OUTPUT, or APPEND", s)),
+        }
+    }
+}
+
+/// Contents of an open channel.
+enum Buffer {
+    /// Lines that have not been read yet from a channel opened for input.
+    Input(VecDeque<String>),
+
+    /// Text to write to the file when a channel opened for output or append is closed.
+    Output(String),
+}
+
+/// An open channel.
+struct Channel {
+    /// Canonical path of the file backing the channel.
+    path: String,
+
+    /// Mode in which the channel was opened.
+    mode: ChannelMode,
+
+    /// Contents of the channel.
+    buffer: Buffer,
+}
+
+/// Table of the channels that are open in a machine.
+#[derive(Default)]
+pub struct Channels {
+    channels: BTreeMap<i32, Channel>,
+}
+
+impl Channels {
+    /// Returns the channel `n`, which must be open.
+    fn get_mut(&mut self, n: i32) -> io::Result<&mut Channel> {
+        self.channels.get_mut(&n).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("Channel #{} is not open", n))
+        })
+    }
+
+    /// Returns the lines pending to be read from channel `n`, which must be open for input.
+    fn input_lines(&mut self, n: i32) -> io::Result<&mut VecDeque<String>> {
+        let channel = self.get_mut(n)?;
+        match &mut channel.buffer {
+            Buffer::Input(lines) => Ok(lines),
+            Buffer::Output(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot read from channel #{} opened for {}", n, channel.mode),
+            )),
+        }
+    }
+
+    /// Returns true if there are no open channels.
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// Opens the file at `path` in `storage` as channel `n` in the given `mode`.
+    pub async fn open(
+        &mut self,
+        storage: &mut Storage,
+        n: i32,
+        path: &str,
+        mode: ChannelMode,
+    ) -> io::Result<()> {
+        if self.channels.contains_key(&n) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Channel #{} is already open", n),
+            ));
+        }
+
+        let path = storage.make_canonical(path)?;
+        let buffer = match mode {
+            ChannelMode::Input => {
+                let content = String::from_utf8(storage.get(&path).await?).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid file content: {}", e),
+                    )
+                })?;
+                Buffer::Input(content.lines().map(str::to_owned).collect())
+            }
+            ChannelMode::Output => {
+                // Create the file right away so that problems with its path are reported early.
+                storage.put(&path, b"").await?;
+                Buffer::Output(String::new())
+            }
+            ChannelMode::Append => match storage.get(&path).await {
+                Ok(content) => Buffer::Output(String::from_utf8(content).map_err(|e| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Invalid file content: {}", e),
+                    )
+                })?),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Buffer::Output(String::new()),
+                Err(e) => return Err(e),
+            },
+        };
+
+        self.channels.insert(n, Channel { path, mode, buffer });
+        Ok(())
+    }
+
+    /// Closes channel `n` and writes its contents to `storage` if it was opened for writing.
+    pub async fn close(&mut self, storage: &mut Storage, n: i32) -> io::Result<()> {
+        let channel = self.channels.remove(&n).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("Channel #{} is not open", n))
+        })?;
+        match channel.buffer {
+            Buffer::Input(_) => Ok(()),
+            Buffer::Output(text) => storage.put(&channel.path, text.as_bytes()).await,
+        }
+    }
+
+    /// Closes all open channels and returns the number of each closed channel along with the
+    /// result of writing its contents to `storage`.
+    pub async fn close_all(&mut self, storage: &mut Storage) -> Vec<(i32, io::Result<()>)> {
+        let mut results = vec![];
+        let numbers = self.channels.keys().copied().collect::<Vec<i32>>();
+        for n in numbers {
+            results.push((n, self.close(storage, n).await));
+        }
+        results
+    }
+
+    /// Returns true if all lines of channel `n` have been read.
+    pub fn is_eof(&mut self, n: i32) -> io::Result<bool> {
+        Ok(self.input_lines(n)?.is_empty())
+    }
+
+    /// Reads the next line from channel `n`.
+    pub fn read_line(&mut self, n: i32) -> io::Result<String> {
+        self.input_lines(n)?.pop_front().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("Input past end of channel #{}", n),
+            )
+        })
+    }
+
+    /// Returns the text buffered for writing to channel `n`.
+    fn output_text(&mut self, n: i32) -> io::Result<&mut String> {
+        let channel = self.get_mut(n)?;
+        match &mut channel.buffer {
+            Buffer::Output(text) => Ok(text),
+            Buffer::Input(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot write to channel #{} opened for {}", n, channel.mode),
+            )),
+        }
+    }
+
+    /// Appends `text` to the contents of channel `n`.
+    pub fn write(&mut self, n: i32, text: &str) -> io::Result<()> {
+        self.output_text(n)?.push_str(text);
+        Ok(())
+    }
+}
+
+/// Pops a channel number from `scope` and validates it.
+fn pop_channel(scope: &mut Scope<'_>) -> Result<(i32, LineCol)> {
+    let (n, pos) = scope.pop_integer_with_pos();
+    if !(1..=MAX_CHANNEL).contains(&n) {
+        return Err(Error::SyntaxError(
+            pos,
+            format!("Channel number {} must be between 1 and {}", n, MAX_CHANNEL),
+        ));
+    }
+    Ok((n, pos))
+}
+
+/// The `CLOSE #` command.
+pub struct CloseCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+    channels: Rc<RefCell<Channels>>,
+}
+
+impl CloseCommand {
+    /// Creates a new `CLOSE #` command that closes channels in `channels` backed by `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>, channels: Rc<RefCell<Channels>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CLOSE#")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("channel"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Closes an open channel.
+Use as CLOSE #channel.  If the channel was opened for OUTPUT or APPEND, this writes all the \
+buffered contents to its file.",
+                )
+                .build(),
+            storage,
+            channels,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CloseCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, pos) = pop_channel(&mut scope)?;
+
+        let mut storage = self.storage.borrow_mut();
+        let mut channels = self.channels.borrow_mut();
+        channels.close(&mut storage, n).await.map_err(|e| Error::IoError(pos, e))
+    }
+}
+
+/// The `EOF` function.
+pub struct EofFunction {
+    metadata: CallableMetadata,
+    channels: Rc<RefCell<Channels>>,
+}
+
+impl EofFunction {
+    /// Creates a new `EOF` function that queries channels in `channels`.
+    pub fn new(channels: Rc<RefCell<Channels>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("EOF")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("channel"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Checks if all lines of a channel have been read.
+The channel must have been opened for INPUT.",
+                )
+                .build(),
+            channels,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for EofFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (n, pos) = pop_channel(&mut scope)?;
+
+        let eof = self.channels.borrow_mut().is_eof(n).map_err(|e| Error::IoError(pos, e))?;
+        scope.return_boolean(eof)
+    }
+}
+
+/// The `LINE INPUT #` command.
+pub struct LineInputCommand {
+    metadata: CallableMetadata,
+    channels: Rc<RefCell<Channels>>,
+}
+
+impl LineInputCommand {
+    /// Creates a new `LINE INPUT #` command that reads from channels in `channels`.
+    pub fn new(channels: Rc<RefCell<Channels>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("LINE INPUT#")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("channel"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredRef(
+                            RequiredRefSyntax {
+                                name: Cow::Borrowed("vref"),
+                                require_array: false,
+                                define_undefined: true,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Reads the next line from a channel into a string variable.
+Use as LINE INPUT #channel, vref$.  The channel must have been opened for INPUT and the line is \
+stored without its line terminator.  Use EOF to check if there are more lines to read.",
+                )
+                .build(),
+            channels,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for LineInputCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (n, npos) = pop_channel(&mut scope)?;
+        let (vname, vtype, vpos) = scope.pop_varref_with_pos();
+
+        if vtype != ExprType::Text {
+            return Err(Error::SyntaxError(
+                vpos,
+                format!("LINE INPUT requires a STRING variable but {} is {}", vname, vtype),
+            ));
+        }
+
+        let line = self.channels.borrow_mut().read_line(n).map_err(|e| Error::IoError(npos, e))?;
+        let vref = VarRef::new(vname.to_string(), Some(vtype));
+        machine
+            .get_mut_symbols()
+            .set_var(&vref, Value::Text(line))
+            .map_err(|e| Error::EvalError(vpos, format!("{}", e)))
+    }
+}
+
+/// The `OPEN` command.
+pub struct OpenCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+    channels: Rc<RefCell<Channels>>,
+}
+
+impl OpenCommand {
+    /// Creates a new `OPEN` command that opens files in `storage` as channels in `channels`.
+    pub fn new(storage: Rc<RefCell<Storage>>, channels: Rc<RefCell<Channels>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("OPEN")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("mode"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::As),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("channel"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Opens a file as a numbered channel.
+Use as OPEN filename$ FOR INPUT|OUTPUT|APPEND AS #channel.  INPUT reads lines from an existing \
+file; OUTPUT creates a new file or replaces the contents of an existing one; and APPEND adds lines \
+at the end of a file, creating it if it does not exist.
+The channel must be between 1 and 255 and must not be in use by another open file.",
+                )
+                .build(),
+            storage,
+            channels,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for OpenCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(3, scope.nargs());
+        let path = scope.pop_string();
+        let (mode, mode_pos) = scope.pop_string_with_pos();
+        let (n, npos) = pop_channel(&mut scope)?;
+
+        let mode = mode.parse::<ChannelMode>().map_err(|e| Error::SyntaxError(mode_pos, e))?;
+
+        let mut storage = self.storage.borrow_mut();
+        let mut channels = self.channels.borrow_mut();
+        channels.open(&mut storage, n, &path, mode).await.map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => Error::IoError(npos, e),
+            _ => scope.io_error(e),
+        })
+    }
+}
+
+/// The `PRINT #` command.
+pub struct PrintCommand {
+    metadata: CallableMetadata,
+    channels: Rc<RefCell<Channels>>,
+}
+
+impl PrintCommand {
+    /// Creates a new `PRINT #` command that writes to channels in `channels`.
+    pub fn new(channels: Rc<RefCell<Channels>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PRINT#")
+                .with_syntax(&[
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("channel"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("channel"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        )],
+                        Some(&RepeatedSyntax {
+                            name: Cow::Borrowed("expr"),
+                            type_syn: RepeatedTypeSyntax::AnyValue,
+                            sep: ArgSepSyntax::OneOf(ArgSep::Long, ArgSep::Short),
+                            require_one: true,
+                            allow_missing: true,
+                        }),
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Writes one or more values to a channel.
+Use as PRINT #channel, expr1 [...].  The channel must have been opened for OUTPUT or APPEND.  The \
+values are formatted in the same way as PRINT does, using print zones of 14 characters for the \
+fields separated by commas.",
+                )
+                .with_empty_values()
+                .build(),
+            channels,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PrintCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        let (n, npos) = pop_channel(&mut scope)?;
+
+        let mut channels = self.channels.borrow_mut();
+        let buffer = channels.output_text(n).map_err(|e| Error::IoError(npos, e))?;
+        let start = match buffer.rfind('\n') {
+            Some(i) => buffer[i + 1..].chars().count(),
+            None => buffer.chars().count(),
+        };
+
+        let mut text = String::new();
+        let mut nl = true;
+        while scope.nargs() > 0 {
+            let mut add_space = false;
+
+            match scope.pop_value_tag() {
+                ValueTag::Missing => {
+                    nl = false;
+                }
+                _ if scope.pop_if_empty() => {
+                    nl = true;
+                }
+                ValueTag::Boolean => {
+                    add_space = true;
+                    nl = true;
+                    text += format_boolean(scope.pop_boolean());
+                }
+                ValueTag::Double => {
+                    add_space = true;
+                    nl = true;
+                    text += &format_double(scope.pop_double());
+                }
+                ValueTag::Integer => {
+                    add_space = true;
+                    nl = true;
+                    text += &format_integer(scope.pop_integer());
+                }
+                ValueTag::Text => {
+                    nl = true;
+                    text += &scope.pop_string();
+                }
+            }
+
+            if scope.nargs() > 0 {
+                match scope.pop_sep_tag() {
+                    ArgSep::Short => {
+                        if add_space {
+                            text += " "
+                        }
+                    }
+                    ArgSep::Long => {
+                        let column = start + text.chars().count();
+                        let next = (column / ZONE_WIDTH + 1) * ZONE_WIDTH;
+                        text += &" ".repeat(next - column);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        if nl {
+            text.push('\n');
+        }
+        buffer.push_str(&text);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutils::*;
+
+    #[test]
+    fn test_channels_write_and_read() {
+        Tester::default()
+            .run(
+                r#"
+                OPEN "data.txt" FOR OUTPUT AS #1
+                PRINT #1, "first"; 2; TRUE
+                PRINT #1, "a", "b"
+                PRINT #1, "partial";
+                PRINT #1, " line"
+                PRINT #1
+                CLOSE #1
+                OPEN "data.txt" FOR INPUT AS #2
+                WHILE NOT EOF(2)
+                    LINE INPUT #2, l$
+                    PRINT "["; l$; "]"
+                WEND
+                CLOSE #2
+                "#,
+            )
+            .expect_prints(["[first 2 TRUE]", "[a             b]", "[partial line]", "[]"])
+            .expect_var("l", "")
+            .expect_file("MEMORY:/data.txt", "first 2 TRUE\na             b\npartial line\n\n")
+            .check();
+    }
+
+    #[test]
+    fn test_channels_output_buffered_until_close() {
+        let mut t = Tester::default().write_file("data.txt", "old");
+        t.run(r#"OPEN "data.txt" FOR OUTPUT AS #1: PRINT #1, "new""#)
+            .expect_file("MEMORY:/data.txt", "")
+            .check();
+        t.run("CLOSE #1").expect_file("MEMORY:/data.txt", "new\n").check();
+    }
+
+    #[test]
+    fn test_channels_append_across_runs() {
+        let program = r#"OPEN "log.txt" FOR APPEND AS #1: PRINT #1, "entry": CLOSE #1"#;
+        let mut t = Tester::default().set_program(Some("prog.bas"), program);
+        t.run("RUN")
+            .expect_clear()
+            .expect_program(Some("prog.bas"), program)
+            .expect_file("MEMORY:/log.txt", "entry\n")
+            .check();
+        t.run("RUN")
+            .expect_clear()
+            .expect_clear()
+            .expect_program(Some("prog.bas"), program)
+            .expect_file("MEMORY:/log.txt", "entry\nentry\n")
+            .check();
+    }
+
+    #[test]
+    fn test_channels_closed_at_end_of_run() {
+        let program =
+            r#"OPEN "out.txt" FOR OUTPUT AS #3: PRINT #3, "hi": OPEN "out.txt" FOR INPUT AS #1"#;
+        Tester::default()
+            .set_program(Some("prog.bas"), program)
+            .run(r#"RUN: OPEN "out.txt" FOR INPUT AS #3: LINE INPUT #3, l$: CLOSE #3"#)
+            .expect_clear()
+            .expect_prints([
+                "Warning: channel #1 was not closed; closed it",
+                "Warning: channel #3 was not closed; closed it",
+            ])
+            .expect_program(Some("prog.bas"), program)
+            .expect_var("l", "hi")
+            .expect_file("MEMORY:/out.txt", "hi\n")
+            .check();
+    }
+
+    #[test]
+    fn test_channels_closed_at_end_of_failed_run() {
+        let program = r#"OPEN "out.txt" FOR OUTPUT AS #1: PRINT #1, "hi": CLOSE #2"#;
+        Tester::default()
+            .set_program(Some("prog.bas"), program)
+            .run("RUN")
+            .expect_clear()
+            .expect_prints(["Warning: channel #1 was not closed; closed it"])
+            .expect_err("1:57: Channel #2 is not open")
+            .expect_program(Some("prog.bas"), program)
+            .expect_file("MEMORY:/out.txt", "hi\n")
+            .check();
+    }
+
+    #[test]
+    fn test_channels_errors() {
+        let mut t = Tester::default().write_file("in.txt", "only\n");
+        t.run(r#"OPEN "in.txt" FOR INPUT AS #1: OPEN "other.txt" FOR OUTPUT AS #1"#)
+            .expect_err("1:64: Channel #1 is already open")
+            .expect_file("MEMORY:/in.txt", "only\n")
+            .check();
+        t.run("LINE INPUT #1, a$: LINE INPUT #1, b$")
+            .expect_var("a", "only")
+            .expect_err("1:32: Input past end of channel #1")
+            .expect_file("MEMORY:/in.txt", "only\n")
+            .check();
+        t.run(r#"PRINT #1, "x""#)
+            .expect_err("1:8: Cannot write to channel #1 opened for INPUT")
+            .expect_var("a", "only")
+            .expect_file("MEMORY:/in.txt", "only\n")
+            .check();
+        t.run("CLOSE #1: CLOSE #1")
+            .expect_err("1:18: Channel #1 is not open")
+            .expect_var("a", "only")
+            .expect_file("MEMORY:/in.txt", "only\n")
+            .check();
+
+        let mut t = Tester::default();
+        t.run(r#"OPEN "out.txt" FOR OUTPUT AS #2: LINE INPUT #2, a$"#)
+            .expect_err("1:46: Cannot read from channel #2 opened for OUTPUT")
+            .expect_file("MEMORY:/out.txt", "")
+            .check();
+        t.run("a = EOF(2)")
+            .expect_err("1:9: Cannot read from channel #2 opened for OUTPUT")
+            .expect_file("MEMORY:/out.txt", "")
+            .check();
+
+        let mut t = Tester::default();
+        t.run(r#"OPEN "missing.txt" FOR INPUT AS #1"#).expect_err("1:1: Entry not found").check();
+        t.run(r#"OPEN "x.txt" FOR RANDOM AS #1"#)
+            .expect_err("1:18: Invalid file mode 'RANDOM'; must be INPUT, OUTPUT, or APPEND")
+            .check();
+        t.run(r#"OPEN "x.txt" FOR OUTPUT AS #0"#)
+            .expect_err("1:29: Channel number 0 must be between 1 and 255")
+            .check();
+        t.run("CLOSE #256").expect_err("1:8: Channel number 256 must be between 1 and 255").check();
+        t.run(r#"OPEN "x.txt" FOR OUTPUT AS #1: LINE INPUT #1, a"#)
+            .expect_err("1:47: LINE INPUT requires a STRING variable but A is INTEGER")
+            .expect_file("MEMORY:/x.txt", "")
+            .check();
+    }
+}
@@ -15,24 +15,35 @@
 
 //! File system interaction.
 
+use super::archive::drive_root;
 use super::time_format_error_to_io_error;
-use crate::console::{is_narrow, Console, Pager};
-use crate::storage::Storage;
+use crate::console::{is_narrow, Console, Pager, ScreenCell};
+use crate::program::DEFAULT_EXTENSION;
+use crate::storage::{
+    BloadCommand, BsaveCommand, Channels, CloseCommand, CsvClearable, CsvcolsFunction,
+    CsvfieldFunction, CsvloadCommand, DiskSpace, EofFunction, ExportDriveCommand,
+    FilereadbFunction, ImportDriveCommand, LineInputCommand, Metadata, OpenCommand, PrintCommand,
+    Storage,
+};
 use async_trait::async_trait;
-use endbasic_core::ast::{ArgSep, ExprType};
-use endbasic_core::compiler::{ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax};
-use endbasic_core::exec::{Machine, Result, Scope};
-use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
+use endbasic_core::ast::{ArgSep, ExprType, Value, VarRef};
+use endbasic_core::compiler::{
+    ArgSepSyntax, RepeatedSyntax, RequiredRefSyntax, RequiredValueSyntax, SingularArgSyntax,
+};
+use endbasic_core::exec::{Error, Machine, Result, Scope};
+use endbasic_core::syms::{Array, Callable, CallableMetadata, CallableMetadataBuilder, Symbol};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use std::cmp;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::io;
 use std::rc::Rc;
 use std::str;
 use time::format_description;
 
 /// Category description for all symbols provided by this module.
-const CATEGORY: &str = "File system
+pub(super) const CATEGORY: &str = "File system
 The EndBASIC storage subsystem is organized as a collection of drives, each identified by a \
 case-insensitive name.  Drives can be backed by a multitude of file systems with different \
 behaviors, and their targets are specified as URIs.  Special targets include: memory://, which \
@@ -41,20 +52,171 @@ sample programs.  Other targets may be available such as file:// to access a loc
 local:// to access web-local storage, depending on the context.  The output of the MOUNT command \
 can help to identify which targets are available.
 All commands that operate with files take a path.  Paths in EndBASIC can be of the form \
-FILENAME.EXT, in which case they refer to a file in the current drive; or DRIVE:/FILENAME.EXT and \
-DRIVE:FILENAME.EXT, in which case they refer to a file in the specified drive.  Note that the \
-slash before the file name is currently optional because EndBASIC does not support directories \
-yet.  Furthermore, if .EXT is missing, a .BAS extension is assumed.
+FILENAME.EXT, in which case they refer to a file in the current directory of the current drive; or \
+DRIVE:FILENAME.EXT, in which case they refer to a file in the current directory of the specified \
+drive.  Drives that support directories, such as memory:// and file://, accept paths with multiple \
+components separated by slashes like GAMES/PONG.BAS, and paths that start with a slash like \
+DRIVE:/GAMES/PONG.BAS are relative to the root of the drive instead of its current directory.  The \
+special . and .. components refer to the current and the parent directories.  Furthermore, if .EXT \
+is missing, a .BAS extension is assumed.
 Be aware that the commands below must be invoked using proper EndBASIC syntax.  In particular, \
 this means that path arguments must be double-quoted and multiple arguments have to be separated \
 by a comma (not a space).  If you have used commands like CD, DIR, or MOUNT in other contexts, \
 this is likely to confuse you.
 See the \"Stored program\" help topic for information on how to load, modify, and save programs.";
 
-/// Shows the contents of the given storage location.
-async fn show_dir(storage: &Storage, console: &mut dyn Console, path: &str) -> io::Result<()> {
+/// Criteria to sort the entries listed by `DIR`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum DirOrder {
+    /// Sort by name.
+    Name,
+
+    /// Sort by modification date, then by name.
+    Date,
+
+    /// Sort by size, then by name.
+    Size,
+}
+
+impl DirOrder {
+    /// Parses an `order` specification of the form `[-]NAME|DATE|SIZE`, returning the sorting
+    /// criteria and whether the sort is descending.
+    fn parse(order: &str) -> Option<(Self, bool)> {
+        let (key, descending) = match order.strip_prefix('-') {
+            Some(key) => (key, true),
+            None => (order, false),
+        };
+        let key = match key.to_ascii_uppercase().as_str() {
+            "NAME" => DirOrder::Name,
+            "DATE" => DirOrder::Date,
+            "SIZE" => DirOrder::Size,
+            _ => return None,
+        };
+        Some((key, descending))
+    }
+}
+
+/// Parses the `options` given to `DIR`, which consist of an optional sort order as accepted by
+/// `DirOrder::parse` and an optional `REFRESH` keyword, separated by whitespace.  Returns the
+/// sorting criteria, whether the sort is descending, and whether to discard cached data.
+fn parse_dir_options(options: &str) -> Option<(DirOrder, bool, bool)> {
+    let mut order = None;
+    let mut refresh = false;
+    for word in options.split_whitespace() {
+        if word.eq_ignore_ascii_case("REFRESH") && !refresh {
+            refresh = true;
+        } else if order.is_none() {
+            order = Some(DirOrder::parse(word)?);
+        } else {
+            return None;
+        }
+    }
+    if order.is_none() && !refresh {
+        return None;
+    }
+    let (order, descending) = order.unwrap_or((DirOrder::Name, false));
+    Some((order, descending, refresh))
+}
+
+/// Splits a `path` whose last component contains wildcards into the directory to list and the
+/// pattern to match its entries against.  Paths without wildcards refer to a whole directory.
+fn split_pattern(path: &str) -> (&str, Option<&str>) {
+    let start = path.rfind(['/', ':']).map(|i| i + 1).unwrap_or(0);
+    let leaf = &path[start..];
+    if !leaf.contains(['*', '?']) {
+        return (path, None);
+    }
+
+    let mut dir = &path[..start];
+    if dir.len() > 1 && dir.ends_with('/') && !dir.ends_with(":/") {
+        dir = &dir[..dir.len() - 1];
+    }
+    (dir, Some(leaf))
+}
+
+/// Returns true if `name` matches the wildcard `pattern`, where `*` matches any sequence of
+/// characters and `?` matches a single character.  Comparisons are case-insensitive.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_uppercase().chars().collect::<Vec<char>>();
+    let name = name.to_uppercase().chars().collect::<Vec<char>>();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack = None;
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            backtrack = Some((star_p, star_n + 1));
+            p = star_p + 1;
+            n = star_n + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Returns the `entries` that match the optional `pattern` sorted by `order`.
+fn sort_entries<'a>(
+    entries: &'a BTreeMap<String, Metadata>,
+    pattern: Option<&str>,
+    order: DirOrder,
+    descending: bool,
+) -> Vec<(&'a String, &'a Metadata)> {
+    let mut sorted = entries
+        .iter()
+        .filter(|(name, _)| pattern.map(|p| matches_pattern(p, name)).unwrap_or(true))
+        .collect::<Vec<(&String, &Metadata)>>();
+    sorted.sort_by(|(name1, md1), (name2, md2)| {
+        let ordering = match order {
+            DirOrder::Name => cmp::Ordering::Equal,
+            DirOrder::Date => md1.date.cmp(&md2.date),
+            DirOrder::Size => md1.length.cmp(&md2.length),
+        }
+        .then_with(|| name1.cmp(name2));
+        if descending {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    sorted
+}
+
+/// Formats a file `length` to fit in the size column of `DIR`, using K and M suffixes for sizes
+/// that have more than four digits.
+pub fn format_size(length: u64) -> String {
+    let mut size = length;
+    for suffix in ["", "K", "M", "G"] {
+        if size <= 9999 {
+            return format!("{}{}", size, suffix);
+        }
+        size = (size + 512) / 1024;
+    }
+    format!("{}T", size)
+}
+
+/// Shows the contents of the given storage location, which may end with a wildcard pattern, with
+/// its entries sorted by `order` in ascending or `descending` order.
+async fn show_dir(
+    storage: &Storage,
+    console: &mut dyn Console,
+    path: &str,
+    order: DirOrder,
+    descending: bool,
+) -> io::Result<()> {
+    let (path, pattern) = split_pattern(path);
     let canonical_path = storage.make_canonical(path)?;
     let files = storage.enumerate(path).await?;
+    let drive_name = canonical_path.split(':').next().expect("Canonical paths have a drive");
+    let read_only = storage.is_read_only(drive_name)?;
+
+    let subdirs = sort_entries(files.subdirs(), pattern, order, descending);
+    let dirents = sort_entries(files.dirents(), pattern, order, descending);
 
     let format = format_description::parse("[year]-[month]-[day] [hour]:[minute]")
         .expect("Hardcoded format must be valid");
@@ -62,15 +224,23 @@ async fn show_dir(storage: &Storage, console: &mut dyn Console, path: &str) -> i
 
     let mut pager = Pager::new(console)?;
     pager.print("").await?;
-    pager.print(&format!("    Directory of {}", canonical_path)).await?;
+    if read_only {
+        pager.print(&format!("    Directory of {} (read-only)", canonical_path)).await?;
+    } else {
+        pager.print(&format!("    Directory of {}", canonical_path)).await?;
+    }
     pager.print("").await?;
+    let total_dirs = subdirs.len();
     if show_narrow {
         let mut total_files = 0;
-        for name in files.dirents().keys() {
+        for (name, _details) in subdirs {
+            pager.print(&format!("    {}/", name)).await?;
+        }
+        for (name, _details) in dirents {
             pager.print(&format!("    {}", name,)).await?;
             total_files += 1;
         }
-        if total_files > 0 {
+        if total_files > 0 || total_dirs > 0 {
             pager.print("").await?;
         }
         pager.print(&format!("    {} file(s)", total_files)).await?;
@@ -78,19 +248,28 @@ async fn show_dir(storage: &Storage, console: &mut dyn Console, path: &str) -> i
         let mut total_files = 0;
         let mut total_bytes = 0;
         pager.print("    Modified              Size    Name").await?;
-        for (name, details) in files.dirents() {
+        for (name, details) in subdirs {
+            pager
+                .print(&format!(
+                    "    {}     <DIR>    {}",
+                    details.date.format(&format).map_err(time_format_error_to_io_error)?,
+                    name,
+                ))
+                .await?;
+        }
+        for (name, details) in dirents {
             pager
                 .print(&format!(
-                    "    {}    {:6}    {}",
+                    "    {}    {:>6}    {}",
                     details.date.format(&format).map_err(time_format_error_to_io_error)?,
-                    details.length,
+                    format_size(details.length),
                     name,
                 ))
                 .await?;
             total_files += 1;
             total_bytes += details.length;
         }
-        if total_files > 0 {
+        if total_files > 0 || total_dirs > 0 {
             pager.print("").await?;
         }
         pager.print(&format!("    {} file(s), {} bytes", total_files, total_bytes)).await?;
@@ -100,6 +279,9 @@ async fn show_dir(storage: &Storage, console: &mut dyn Console, path: &str) -> i
                 .await?;
         }
     }
+    if total_dirs > 0 {
+        pager.print(&format!("    {} dir(s)", total_dirs)).await?;
+    }
     pager.print("").await?;
     Ok(())
 }
@@ -115,7 +297,11 @@ fn show_drives(storage: &Storage, console: &mut dyn Console) -> io::Result<()> {
     let num_drives = drive_info.len();
     for (name, uri) in drive_info {
         let filler = " ".repeat(max_length - name.len());
-        console.print(&format!("    {}{}    {}", name, filler, uri))?;
+        if storage.is_read_only(name)? {
+            console.print(&format!("    {}{}    {} (read-only)", name, filler, uri))?;
+        } else {
+            console.print(&format!("    {}{}    {}", name, filler, uri))?;
+        }
     }
     console.print("")?;
     console.print(&format!("    {} drive(s)", num_drives))?;
@@ -123,6 +309,148 @@ fn show_drives(storage: &Storage, console: &mut dyn Console) -> io::Result<()> {
     Ok(())
 }
 
+/// Default number of values per `DATA` statement generated by `BINTODATA`.
+const DEFAULT_BYTES_PER_LINE: usize = 16;
+
+/// Maximum number of values per `DATA` statement generated by `BINTODATA`.
+const MAX_BYTES_PER_LINE: i32 = 255;
+
+/// Formats `content` as a program fragment of `DATA` statements with up to `bytes_per_line`
+/// values each, preceded by a comment that identifies the file `name` the bytes came from.
+fn bytes_to_data(name: &str, content: &[u8], bytes_per_line: usize) -> String {
+    let mut output = format!("' Generated by BINTODATA from {} ({} bytes)\n", name, content.len());
+    for chunk in content.chunks(bytes_per_line) {
+        let values = chunk.iter().map(|b| b.to_string()).collect::<Vec<String>>();
+        output.push_str("DATA ");
+        output.push_str(&values.join(", "));
+        output.push('\n');
+    }
+    output
+}
+
+/// The `BINTODATA` command.
+pub struct BintodataCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl BintodataCommand {
+    /// Creates a new `BINTODATA` command that converts files in `storage` to `DATA` statements.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("BINTODATA")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("src"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("dest"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("src"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("dest"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("bytesperline"),
+                                    vtype: ExprType::Integer,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Converts the contents of a file into DATA statements.
+Reads the file in src, which can contain arbitrary binary data such as sprites or music, and \
+writes into dest a program fragment with one DATA statement per bytesperline% bytes (16 by \
+default, up to 255).  Every byte is emitted as an integer between 0 and 255, and the fragment \
+starts with a comment that records the name and length of src.
+The resulting file can be pasted into a program or combined with it, and the bytes can be \
+recovered with READ in the same order in which they appeared in src.
+The src path is taken verbatim but, if dest lacks an extension, .BAS is assumed.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for BintodataCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let src = scope.pop_string();
+        let dest = scope.pop_string();
+        let bytes_per_line = if scope.nargs() == 0 {
+            DEFAULT_BYTES_PER_LINE
+        } else {
+            let (value, pos) = scope.pop_integer_with_pos();
+            if !(1..=MAX_BYTES_PER_LINE).contains(&value) {
+                return Err(Error::SyntaxError(
+                    pos,
+                    format!(
+                        "Bytes per line {} must be between 1 and {}",
+                        value, MAX_BYTES_PER_LINE
+                    ),
+                ));
+            }
+            value as usize
+        };
+
+        let (src, dest) = {
+            let storage = self.storage.borrow();
+            let src = storage.make_canonical(&src).map_err(|e| scope.io_error(e))?;
+            let dest = storage
+                .make_canonical_with_extension(&dest, DEFAULT_EXTENSION)
+                .map_err(|e| scope.io_error(e))?;
+            (src, dest)
+        };
+
+        let content = self.storage.borrow().get(&src).await.map_err(|e| scope.io_error(e))?;
+        let data = bytes_to_data(&src, &content, bytes_per_line);
+        self.storage
+            .borrow_mut()
+            .put(&dest, data.as_bytes())
+            .await
+            .map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
 /// The `CD` command.
 pub struct CdCommand {
     metadata: CallableMetadata,
@@ -142,7 +470,12 @@ impl CdCommand {
                     None,
                 )])
                 .with_category(CATEGORY)
-                .with_description("Changes the current path.")
+                .with_description(
+                    "Changes the current path.
+The path can name a drive, such as \"MEMORY:\", or a directory within a drive, such as \
+\"MEMORY:/GAMES\" or \"..\".  Each drive remembers its own current directory, which is used to \
+resolve relative paths that refer to that drive.",
+                )
                 .build(),
             storage,
         })
@@ -220,7 +553,99 @@ impl Callable for CopyCommand {
         let dest = scope.pop_string();
 
         let mut storage = self.storage.borrow_mut();
-        storage.copy(&src, &dest).await.map_err(|e| scope.io_error(e))?;
+        storage.copy(&src, &dest, true).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `COPYFILE` command.
+pub struct CopyFileCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl CopyFileCommand {
+    /// Creates a new `COPYFILE` command that copies a file without overwriting existing files
+    /// unless requested.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("COPYFILE")
+                .with_syntax(&[
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("src"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("dest"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("src"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("dest"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("overwrite"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Copies src to dest, possibly across drives.
+The contents of src are copied byte-for-byte.  If dest is a path without a name, the target file \
+given in dest will have the same name as the source file in src.
+Unlike COPY, this refuses to replace an existing dest unless overwrite? is TRUE.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CopyFileCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((2..=3).contains(&scope.nargs()));
+        let src = scope.pop_string();
+        let dest = scope.pop_string();
+        let overwrite = if scope.nargs() == 0 { false } else { scope.pop_boolean() };
+
+        let mut storage = self.storage.borrow_mut();
+        storage.copy(&src, &dest, overwrite).await.map_err(|e| scope.io_error(e))?;
 
         Ok(())
     }
@@ -250,9 +675,41 @@ impl DirCommand {
                         )],
                         None,
                     ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("path"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("order"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
                 ])
                 .with_category(CATEGORY)
-                .with_description("Displays the list of files on the current or given path.")
+                .with_description(
+                    "Displays the list of files on the current or given path.
+Subdirectories are listed before the files and are marked with <DIR>, or with a trailing slash on \
+narrow consoles.  Sizes larger than 9999 bytes are shown in kilobytes or megabytes with a K or M \
+suffix.
+The last component of path$ can be a pattern in which * matches any sequence of characters and ? \
+matches any single character, such as \"GAMES/*.BAS\", to only list the matching entries.  \
+Patterns are case-insensitive.  Use an empty path$ to refer to the current directory.
+By default, entries are sorted by name.  order$ can be NAME, DATE, or SIZE to sort by that \
+property instead, optionally prefixed by a minus sign as in -SIZE to sort in descending order.
+Some drives, like the cloud drive, remember their contents for a few seconds to avoid slow \
+queries.  Add REFRESH to order$, as in \"REFRESH\" or \"-DATE REFRESH\", to discard that \
+information and list the current contents.",
+                )
                 .build(),
             console,
             storage,
@@ -267,14 +724,30 @@ impl Callable for DirCommand {
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        let path = if scope.nargs() == 0 {
-            "".to_owned()
+        let path = if scope.nargs() == 0 { "".to_owned() } else { scope.pop_string() };
+
+        let (order, descending, refresh) = if scope.nargs() == 0 {
+            (DirOrder::Name, false, false)
         } else {
             debug_assert_eq!(1, scope.nargs());
-            scope.pop_string()
+            let (order, pos) = scope.pop_string_with_pos();
+            match parse_dir_options(&order) {
+                Some(options) => options,
+                None => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("Invalid order '{}'; must be NAME, DATE, or SIZE", order),
+                    ))
+                }
+            }
         };
 
-        show_dir(&self.storage.borrow(), &mut *self.console.borrow_mut(), &path)
+        if refresh {
+            let (dir, _pattern) = split_pattern(&path);
+            self.storage.borrow_mut().discard_cache(dir).map_err(|e| scope.io_error(e))?;
+        }
+
+        show_dir(&self.storage.borrow(), &mut *self.console.borrow_mut(), &path, order, descending)
             .await
             .map_err(|e| scope.io_error(e))?;
 
@@ -282,32 +755,45 @@ impl Callable for DirCommand {
     }
 }
 
-/// The `KILL` command.
-pub struct KillCommand {
+/// The `DIRARRAY` command.
+pub struct DirarrayCommand {
     metadata: CallableMetadata,
     storage: Rc<RefCell<Storage>>,
 }
 
-impl KillCommand {
-    /// Creates a new `KILL` command that deletes a file from `storage`.
+impl DirarrayCommand {
+    /// Creates a new `DIRARRAY` command that lists `storage` contents into an array.
     pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("KILL")
+            metadata: CallableMetadataBuilder::new("DIRARRAY")
                 .with_syntax(&[(
-                    &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax {
-                            name: Cow::Borrowed("filename"),
-                            vtype: ExprType::Text,
-                        },
-                        ArgSepSyntax::End,
-                    )],
+                    &[
+                        SingularArgSyntax::RequiredRef(
+                            RequiredRefSyntax {
+                                name: Cow::Borrowed("names"),
+                                require_array: true,
+                                define_undefined: false,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("pattern"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
                     None,
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Deletes the given file.
-The filename must be a string and must be a valid EndBASIC path.
-See the \"File system\" help topic for information on the path syntax.",
+                    "Stores the names of the files that match a pattern into an array.
+names must be a one-dimensional STRING array.  The array is resized to have as many elements as \
+files match pattern$, and receives their names sorted alphabetically.  Subdirectories are not \
+included.  If no files match, the array is resized to a single element holding an empty string.
+pattern$ follows the same rules as the path$ given to DIR: it can name a directory to get all of \
+its files, or end with a pattern that uses the * and ? wildcards.",
                 )
                 .build(),
             storage,
@@ -316,172 +802,280 @@ See the \"File system\" help topic for information on the path syntax.",
 }
 
 #[async_trait(?Send)]
-impl Callable for KillCommand {
+impl Callable for DirarrayCommand {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(1, scope.nargs());
-        let name = scope.pop_string();
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (name, vtype, pos) = scope.pop_varref_with_pos();
+        let path = scope.pop_string();
+
+        if vtype != ExprType::Text {
+            return Err(Error::SyntaxError(
+                pos,
+                format!("Array {} must be of type STRING but is {}", name, vtype),
+            ));
+        }
+        let vref = VarRef::new(name.to_string(), Some(vtype));
+        let array = match machine
+            .get_mut_symbols()
+            .get_mut(&vref)
+            .map_err(|e| Error::SyntaxError(pos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!("Array existence guaranteed by the compiler"),
+        };
+        if array.dimensions().len() != 1 {
+            return Err(Error::SyntaxError(
+                pos,
+                format!(
+                    "Array {} must have 1 dimension but has {}",
+                    vref.name(),
+                    array.dimensions().len()
+                ),
+            ));
+        }
 
-        self.storage.borrow_mut().delete(&name).await.map_err(|e| scope.io_error(e))?;
+        let (dir, pattern) = split_pattern(&path);
+        let files = self.storage.borrow().enumerate(dir).await.map_err(|e| scope.io_error(e))?;
+        let names = sort_entries(files.dirents(), pattern, DirOrder::Name, false);
 
+        let mut listed = Array::new(ExprType::Text, vec![cmp::max(1, names.len())]);
+        for (i, (name, _details)) in names.into_iter().enumerate() {
+            let i = i32::try_from(i).map_err(|_| scope.internal_error("Too many files"))?;
+            listed
+                .assign(&[i], Value::Text(name.clone()))
+                .map_err(|e| scope.internal_error(format!("{}", e)))?;
+        }
+        machine
+            .get_mut_symbols()
+            .replace_array(&vref, listed)
+            .map_err(|e| Error::EvalError(pos, format!("{}", e)))?;
         Ok(())
     }
 }
 
-/// The `MOUNT` command.
-pub struct MountCommand {
+/// Syntax of the functions that query the disk space of a drive.
+const DISK_SPACE_SYNTAX: &[(&[SingularArgSyntax], Option<&RepeatedSyntax>)] = &[(
+    &[SingularArgSyntax::RequiredValue(
+        RequiredValueSyntax { name: Cow::Borrowed("drive"), vtype: ExprType::Text },
+        ArgSepSyntax::End,
+    )],
+    None,
+)];
+
+/// Queries the quota of the drive named by the single argument in `scope` if `quota` is true, or
+/// its free space otherwise.  Returns `None` if the drive does not report this information.
+async fn get_disk_space(
+    storage: &Rc<RefCell<Storage>>,
+    scope: &mut Scope<'_>,
+    quota: bool,
+) -> Result<Option<DiskSpace>> {
+    debug_assert_eq!(1, scope.nargs());
+    let (drive, pos) = scope.pop_string_with_pos();
+
+    let files = storage
+        .borrow()
+        .enumerate(&drive_root(&drive))
+        .await
+        .map_err(|e| Error::IoError(pos, e))?;
+    if quota {
+        Ok(*files.disk_quota())
+    } else {
+        Ok(*files.disk_free())
+    }
+}
+
+/// The `DISKFREE` function.
+pub struct DiskfreeFunction {
     metadata: CallableMetadata,
-    console: Rc<RefCell<dyn Console>>,
     storage: Rc<RefCell<Storage>>,
 }
 
-impl MountCommand {
-    /// Creates a new `MOUNT` command.
-    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+impl DiskfreeFunction {
+    /// Creates a new `DISKFREE` function that queries drives in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("MOUNT")
-                .with_syntax(&[
-                    (&[], None),
-                    (
-                        &[
-                            SingularArgSyntax::RequiredValue(
-                                RequiredValueSyntax {
-                                    name: Cow::Borrowed("target"),
-                                    vtype: ExprType::Text,
-                                },
-                                ArgSepSyntax::Exactly(ArgSep::As),
-                            ),
-                            SingularArgSyntax::RequiredValue(
-                                RequiredValueSyntax {
-                                    name: Cow::Borrowed("drive_name"),
-                                    vtype: ExprType::Text,
-                                },
-                                ArgSepSyntax::End,
-                            ),
-                        ],
-                        None,
-                    ),
-                ])
+            metadata: CallableMetadataBuilder::new("DISKFREE")
+                .with_return_type(ExprType::Double)
+                .with_syntax(DISK_SPACE_SYNTAX)
                 .with_category(CATEGORY)
                 .with_description(
-                    "Lists the mounted drives or mounts a new drive.
-With no arguments, prints a list of mounted drives and their targets.
-With two arguments, mounts the drive_name$ to point to the target$.  Drive names are specified \
-without a colon at the end, and targets are given in the form of a URI.",
+                    "Returns the number of bytes that can still be stored in a drive.
+drive$ is the name of a mounted drive, with or without the trailing colon.  Returns -1 if the drive does not report this information.",
                 )
                 .build(),
-            console,
             storage,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for MountCommand {
+impl Callable for DiskfreeFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        if scope.nargs() == 0 {
-            show_drives(&self.storage.borrow_mut(), &mut *self.console.borrow_mut())
-                .map_err(|e| scope.io_error(e))?;
-            Ok(())
-        } else {
-            debug_assert_eq!(2, scope.nargs());
-            let target = scope.pop_string();
-            let name = scope.pop_string();
-
-            self.storage.borrow_mut().mount(&name, &target).map_err(|e| scope.io_error(e))?;
-            Ok(())
+        match get_disk_space(&self.storage, &mut scope, false).await? {
+            Some(space) => scope.return_double(space.bytes as f64),
+            None => scope.return_double(-1.0),
         }
     }
 }
 
-/// The `PWD` command.
-pub struct PwdCommand {
+/// The `DISKFREEFILES` function.
+pub struct DiskfreefilesFunction {
     metadata: CallableMetadata,
-    console: Rc<RefCell<dyn Console>>,
     storage: Rc<RefCell<Storage>>,
 }
 
-impl PwdCommand {
-    /// Creates a new `PWD` command that prints the current directory of `storage` to the `console`.
-    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+impl DiskfreefilesFunction {
+    /// Creates a new `DISKFREEFILES` function that queries drives in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("PWD")
-                .with_syntax(&[(&[], None)])
+            metadata: CallableMetadataBuilder::new("DISKFREEFILES")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(DISK_SPACE_SYNTAX)
                 .with_category(CATEGORY)
                 .with_description(
-                    "Prints the current working location.
-If the EndBASIC path representing the current location is backed by a real path that is accessible \
-by the underlying operating system, displays such path as well.",
+                    "Returns the number of files that can still be stored in a drive.
+drive$ is the name of a mounted drive, with or without the trailing colon.  Returns -1 if the drive does not report this information.",
                 )
                 .build(),
-            console,
             storage,
         })
     }
 }
 
 #[async_trait(?Send)]
-impl Callable for PwdCommand {
+impl Callable for DiskfreefilesFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
-    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(0, scope.nargs());
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        match get_disk_space(&self.storage, &mut scope, false).await? {
+            Some(space) => match i32::try_from(space.files) {
+                Ok(files) => scope.return_integer(files),
+                Err(_) => {
+                    Err(scope.internal_error(format!("File count {} is too large", space.files)))
+                }
+            },
+            None => scope.return_integer(-1),
+        }
+    }
+}
 
-        let storage = self.storage.borrow();
-        let cwd = storage.cwd();
-        let system_cwd = storage.system_path(&cwd).expect("cwd must return a valid path");
+/// The `DISKQUOTA` function.
+pub struct DiskquotaFunction {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
 
-        let console = &mut *self.console.borrow_mut();
-        console.print("").map_err(|e| scope.io_error(e))?;
-        console.print(&format!("    Working directory: {}", cwd)).map_err(|e| scope.io_error(e))?;
-        match system_cwd {
-            Some(path) => console
-                .print(&format!("    System location: {}", path.display()))
-                .map_err(|e| scope.io_error(e))?,
-            None => {
-                console.print("    No system location available").map_err(|e| scope.io_error(e))?
-            }
+impl DiskquotaFunction {
+    /// Creates a new `DISKQUOTA` function that queries drives in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DISKQUOTA")
+                .with_return_type(ExprType::Double)
+                .with_syntax(DISK_SPACE_SYNTAX)
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the maximum number of bytes that can be stored in a drive.
+drive$ is the name of a mounted drive, with or without the trailing colon.  Returns -1 if the drive does not report this information.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for DiskquotaFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        match get_disk_space(&self.storage, &mut scope, true).await? {
+            Some(space) => scope.return_double(space.bytes as f64),
+            None => scope.return_double(-1.0),
         }
-        console.print("").map_err(|e| scope.io_error(e))?;
+    }
+}
 
-        Ok(())
+/// The `DISKQUOTAFILES` function.
+pub struct DiskquotafilesFunction {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl DiskquotafilesFunction {
+    /// Creates a new `DISKQUOTAFILES` function that queries drives in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("DISKQUOTAFILES")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(DISK_SPACE_SYNTAX)
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the maximum number of files that can be stored in a drive.
+drive$ is the name of a mounted drive, with or without the trailing colon.  Returns -1 if the drive does not report this information.",
+                )
+                .build(),
+            storage,
+        })
     }
 }
 
-/// The `UNMOUNT` command.
-pub struct UnmountCommand {
+#[async_trait(?Send)]
+impl Callable for DiskquotafilesFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        match get_disk_space(&self.storage, &mut scope, true).await? {
+            Some(space) => match i32::try_from(space.files) {
+                Ok(files) => scope.return_integer(files),
+                Err(_) => {
+                    Err(scope.internal_error(format!("File count {} is too large", space.files)))
+                }
+            },
+            None => scope.return_integer(-1),
+        }
+    }
+}
+
+/// The `FILEDATE` function.
+pub struct FiledateFunction {
     metadata: CallableMetadata,
     storage: Rc<RefCell<Storage>>,
 }
 
-impl UnmountCommand {
-    /// Creates a new `UNMOUNT` command.
+impl FiledateFunction {
+    /// Creates a new `FILEDATE` function that queries files in `storage`.
     pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
         Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("UNMOUNT")
+            metadata: CallableMetadataBuilder::new("FILEDATE")
+                .with_return_type(ExprType::Text)
                 .with_syntax(&[(
                     &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax {
-                            name: Cow::Borrowed("drive_name"),
-                            vtype: ExprType::Text,
-                        },
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
                         ArgSepSyntax::End,
                     )],
                     None,
                 )])
                 .with_category(CATEGORY)
                 .with_description(
-                    "Unmounts the given drive.
-Drive names are specified without a colon at the end.",
+                    "Returns the modification date of a file.
+The date is formatted as YYYY-MM-DD HH:MM:SS, which is the same format used by DIR with the \
+addition of seconds.  Dates formatted this way sort in chronological order when compared as \
+strings.
+It is an error if the file does not exist.",
                 )
                 .build(),
             storage,
@@ -490,348 +1084,1780 @@ Drive names are specified without a colon at the end.",
 }
 
 #[async_trait(?Send)]
-impl Callable for UnmountCommand {
+impl Callable for FiledateFunction {
     fn metadata(&self) -> &CallableMetadata {
         &self.metadata
     }
 
     async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
         debug_assert_eq!(1, scope.nargs());
-        let drive = scope.pop_string();
-
-        self.storage.borrow_mut().unmount(&drive).map_err(|e| scope.io_error(e))?;
-
-        Ok(())
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let metadata =
+            self.storage.borrow().metadata(&name).await.map_err(|e| Error::IoError(pos, e))?;
+        let format = format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]")
+            .expect("Hardcoded format must be valid");
+        let date = metadata
+            .date
+            .format(&format)
+            .map_err(|e| Error::IoError(pos, time_format_error_to_io_error(e)))?;
+        scope.return_string(date)
     }
 }
 
-/// Adds all file system manipulation commands for `storage` to the `machine`, using `console` to
-/// display information.
-pub fn add_all(
-    machine: &mut Machine,
-    console: Rc<RefCell<dyn Console>>,
+/// The `FILEEXISTS` function.
+pub struct FileexistsFunction {
+    metadata: CallableMetadata,
     storage: Rc<RefCell<Storage>>,
-) {
-    machine.add_callable(CdCommand::new(storage.clone()));
-    machine.add_callable(CopyCommand::new(storage.clone()));
-    machine.add_callable(DirCommand::new(console.clone(), storage.clone()));
-    machine.add_callable(KillCommand::new(storage.clone()));
-    machine.add_callable(MountCommand::new(console.clone(), storage.clone()));
-    machine.add_callable(PwdCommand::new(console.clone(), storage.clone()));
-    machine.add_callable(UnmountCommand::new(storage));
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::console::{CharsXY, Key};
-    use crate::storage::{DirectoryDriveFactory, DiskSpace, Drive, InMemoryDrive};
-    use crate::testutils::*;
-    use futures_lite::future::block_on;
-    use std::collections::BTreeMap;
+impl FileexistsFunction {
+    /// Creates a new `FILEEXISTS` function that queries files in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FILEEXISTS")
+                .with_return_type(ExprType::Boolean)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Checks if a file exists.
+Returns FALSE if the file does not exist or if name$ refers to a directory.  It is still an \
+error if name$ is not a valid path or refers to a drive that is not mounted.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
 
-    #[test]
-    fn test_cd_ok() {
-        let mut t = Tester::default();
-        t.get_storage().borrow_mut().mount("other", "memory://").unwrap();
-        t.run("CD \"other:\"").check();
-        assert_eq!("OTHER:/", t.get_storage().borrow().cwd());
-        t.run("CD \"memory:/\"").check();
-        assert_eq!("MEMORY:/", t.get_storage().borrow().cwd());
+#[async_trait(?Send)]
+impl Callable for FileexistsFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
     }
 
-    #[test]
-    fn test_cd_errors() {
-        check_stmt_err("1:1: Drive 'A' is not mounted", "CD \"A:\"");
-        check_stmt_compilation_err("1:1: CD expected path$", "CD");
-        check_stmt_compilation_err("1:1: CD expected path$", "CD 2, 3");
-        check_stmt_compilation_err("1:4: expected STRING but found INTEGER", "CD 2");
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let exists =
+            self.storage.borrow().exists(&name).await.map_err(|e| Error::IoError(pos, e))?;
+        scope.return_boolean(exists)
     }
+}
 
-    #[test]
-    fn test_copy_ok() {
-        Tester::default()
-            .set_program(Some("foo.bas"), "Leave me alone")
-            .write_file("file1", "the content")
-            .run(r#"COPY "file1", "file2""#)
-            .expect_program(Some("foo.bas"), "Leave me alone")
-            .expect_file("MEMORY:/file1", "the content")
-            .expect_file("MEMORY:/file2", "the content")
-            .check();
+/// The `FILESIZE` function.
+pub struct FilesizeFunction {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl FilesizeFunction {
+    /// Creates a new `FILESIZE` function that queries files in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("FILESIZE")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("name"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the size of a file in bytes.
+It is an error if the file does not exist.",
+                )
+                .build(),
+            storage,
+        })
     }
+}
 
-    #[test]
-    fn test_copy_deduce_target_name() {
-        let t = Tester::default();
-        t.get_storage().borrow_mut().mount("other", "memory://").unwrap();
-        t.set_program(Some("foo.bas"), "Leave me alone")
-            .write_file("file1.x", "the content")
-            .run(r#"COPY "file1.x", "OTHER:/""#)
-            .expect_program(Some("foo.bas"), "Leave me alone")
-            .expect_file("MEMORY:/file1.x", "the content")
-            .expect_file("OTHER:/file1.x", "the content")
-            .check();
+#[async_trait(?Send)]
+impl Callable for FilesizeFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
     }
 
-    #[test]
-    fn test_copy_errors() {
-        Tester::default()
-            .run(r#"COPY "foo""#)
-            .expect_compilation_err("1:1: COPY expected src$, dest$")
-            .check();
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (name, pos) = scope.pop_string_with_pos();
+
+        let metadata =
+            self.storage.borrow().metadata(&name).await.map_err(|e| Error::IoError(pos, e))?;
+        match i32::try_from(metadata.length) {
+            Ok(length) => scope.return_integer(length),
+            Err(_) => Err(Error::IoError(
+                pos,
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("File size {} is too large", metadata.length),
+                ),
+            )),
+        }
+    }
+}
 
-        Tester::default()
-            .run(r#"COPY "memory:/", "foo.bar""#)
+/// The `KILL` command.
+pub struct KillCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl KillCommand {
+    /// Creates a new `KILL` command that deletes a file from `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("KILL")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("filename"),
+                            vtype: ExprType::Text,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Deletes the given file.
+The filename must be a string and must be a valid EndBASIC path.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for KillCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let name = scope.pop_string();
+
+        self.storage.borrow_mut().delete(&name).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `MKDIR` command.
+pub struct MkdirCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl MkdirCommand {
+    /// Creates a new `MKDIR` command that creates a directory in `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MKDIR")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("path"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Creates a new directory.
+The parent of the new directory must already exist.  Not all drives support directories.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MkdirCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let path = scope.pop_string();
+
+        self.storage.borrow_mut().mkdir(&path).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `MOUNT` command.
+pub struct MountCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl MountCommand {
+    /// Creates a new `MOUNT` command.
+    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("MOUNT")
+                .with_syntax(&[
+                    (&[], None),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("target"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::As),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("drive_name"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("target"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::As),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("drive_name"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("mode"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Lists the mounted drives or mounts a new drive.
+With no arguments, prints a list of mounted drives and their targets.  Drives mounted read-only \
+are marked as such.
+With two arguments, mounts the drive_name$ to point to the target$.  Drive names are specified \
+without a colon at the end, and targets are given in the form of a URI.
+The optional mode$ can be \"rw\", which is the default, to mount the drive read/write or \"ro\" \
+to mount it read-only.  Read-only drives reject any operation that would modify their contents, \
+which is useful to protect your files while running untrusted programs.  To change the mode of \
+an already-mounted drive, UNMOUNT it and MOUNT it again.",
+                )
+                .build(),
+            console,
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for MountCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        if scope.nargs() == 0 {
+            show_drives(&self.storage.borrow_mut(), &mut *self.console.borrow_mut())
+                .map_err(|e| scope.io_error(e))?;
+            Ok(())
+        } else {
+            debug_assert!((2..=3).contains(&scope.nargs()));
+            let target = scope.pop_string();
+            let name = scope.pop_string();
+            let read_only = if scope.nargs() > 0 {
+                let (mode, pos) = scope.pop_string_with_pos();
+                if mode.eq_ignore_ascii_case("ro") {
+                    true
+                } else if mode.eq_ignore_ascii_case("rw") {
+                    false
+                } else {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("Invalid mount mode '{}'; must be \"ro\" or \"rw\"", mode),
+                    ));
+                }
+            } else {
+                false
+            };
+
+            self.storage
+                .borrow_mut()
+                .mount(&name, &target, read_only)
+                .await
+                .map_err(|e| scope.io_error(e))?;
+            Ok(())
+        }
+    }
+}
+
+/// The `PWD` command.
+pub struct PwdCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl PwdCommand {
+    /// Creates a new `PWD` command that prints the current directory of `storage` to the `console`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("PWD")
+                .with_syntax(&[(&[], None)])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Prints the current working location.
+If the EndBASIC path representing the current location is backed by a real path that is accessible \
+by the underlying operating system, displays such path as well.",
+                )
+                .build(),
+            console,
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for PwdCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(0, scope.nargs());
+
+        let storage = self.storage.borrow();
+        let cwd = storage.cwd();
+        let system_cwd = storage.system_path(&cwd).expect("cwd must return a valid path");
+
+        let console = &mut *self.console.borrow_mut();
+        console.print("").map_err(|e| scope.io_error(e))?;
+        console.print(&format!("    Working directory: {}", cwd)).map_err(|e| scope.io_error(e))?;
+        match system_cwd {
+            Some(path) => console
+                .print(&format!("    System location: {}", path.display()))
+                .map_err(|e| scope.io_error(e))?,
+            None => {
+                console.print("    No system location available").map_err(|e| scope.io_error(e))?
+            }
+        }
+        console.print("").map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// Formats the `rows` of a text console as plain text.  If `ansi` is true, the text includes ANSI
+/// escape sequences to reproduce the colors of every cell.
+fn screen_to_text(rows: &[Vec<ScreenCell>], ansi: bool) -> String {
+    let mut output = String::new();
+    for row in rows {
+        let mut colors = (None, None);
+        for cell in row {
+            if ansi && (cell.fg, cell.bg) != colors {
+                let fg = match cell.fg {
+                    Some(color) => format!("38;5;{}", color),
+                    None => "39".to_owned(),
+                };
+                let bg = match cell.bg {
+                    Some(color) => format!("48;5;{}", color),
+                    None => "49".to_owned(),
+                };
+                output.push_str(&format!("\u{1b}[{};{}m", fg, bg));
+                colors = (cell.fg, cell.bg);
+            }
+            output.push(cell.ch);
+        }
+        if colors != (None, None) {
+            output.push_str("\u{1b}[0m");
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// The `RENAME` command.
+pub struct RenameCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl RenameCommand {
+    /// Creates a new `RENAME` command that renames or moves a file.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RENAME")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("old"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("new"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Renames the file old to new, possibly moving it across directories or drives.
+If new is a path without a name, the file keeps its name.  The file given in new must not exist.
+Moving a file to a different drive copies it and then deletes the original; if the original \
+cannot be deleted, the copy is removed and old is left untouched.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RenameCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let old = scope.pop_string();
+        let new = scope.pop_string();
+
+        let mut storage = self.storage.borrow_mut();
+        storage.rename(&old, &new).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `RMDIR` command.
+pub struct RmdirCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl RmdirCommand {
+    /// Creates a new `RMDIR` command that deletes a directory from `storage`.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("RMDIR")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax { name: Cow::Borrowed("path"), vtype: ExprType::Text },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Deletes the given directory.
+The directory must be empty and cannot be, or contain, the current directory of its drive.
+See the \"File system\" help topic for information on the path syntax.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for RmdirCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let path = scope.pop_string();
+
+        self.storage.borrow_mut().rmdir(&path).await.map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// The `SCREENDUMP` command.
+pub struct ScreenDumpCommand {
+    metadata: CallableMetadata,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl ScreenDumpCommand {
+    /// Creates a new `SCREENDUMP` command that saves the text in `console` to `storage`.
+    pub fn new(console: Rc<RefCell<dyn Console>>, storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("SCREENDUMP")
+                .with_syntax(&[
+                    (
+                        &[SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::End,
+                        )],
+                        None,
+                    ),
+                    (
+                        &[
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("filename"),
+                                    vtype: ExprType::Text,
+                                },
+                                ArgSepSyntax::Exactly(ArgSep::Long),
+                            ),
+                            SingularArgSyntax::RequiredValue(
+                                RequiredValueSyntax {
+                                    name: Cow::Borrowed("colors"),
+                                    vtype: ExprType::Boolean,
+                                },
+                                ArgSepSyntax::End,
+                            ),
+                        ],
+                        None,
+                    ),
+                ])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Saves the contents of the text console to a file.
+The file receives one line of plain text per row of the console, from the top, without trailing \
+blank space nor trailing blank rows.  Only text is saved: graphics drawn on the console are not \
+captured.
+If colors? is true, the file also includes ANSI escape sequences to reproduce the colors of the \
+text, which is useful to display the file in a terminal.
+The filename path is taken verbatim so no extension is assumed.
+See SCREENCHAR$ to query individual positions of the console.",
+                )
+                .build(),
+            console,
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for ScreenDumpCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert!((1..=2).contains(&scope.nargs()));
+        let filename = scope.pop_string();
+        let ansi = if scope.nargs() == 0 { false } else { scope.pop_boolean() };
+
+        let rows = self.console.borrow().read_screen().map_err(|e| scope.io_error(e))?;
+        let text = screen_to_text(&rows, ansi);
+
+        let mut storage = self.storage.borrow_mut();
+        let path = storage.make_canonical(&filename).map_err(|e| scope.io_error(e))?;
+        storage.put(&path, text.as_bytes()).await.map_err(|e| scope.io_error(e))?;
+        Ok(())
+    }
+}
+
+/// The `UNMOUNT` command.
+pub struct UnmountCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+}
+
+impl UnmountCommand {
+    /// Creates a new `UNMOUNT` command.
+    pub fn new(storage: Rc<RefCell<Storage>>) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("UNMOUNT")
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("drive_name"),
+                            vtype: ExprType::Text,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Unmounts the given drive.
+Drive names are specified without a colon at the end.",
+                )
+                .build(),
+            storage,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for UnmountCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let drive = scope.pop_string();
+
+        self.storage.borrow_mut().unmount(&drive).map_err(|e| scope.io_error(e))?;
+
+        Ok(())
+    }
+}
+
+/// Adds all file system manipulation commands for `storage` to the `machine`, using `console` to
+/// display information.
+pub fn add_all(
+    machine: &mut Machine,
+    console: Rc<RefCell<dyn Console>>,
+    storage: Rc<RefCell<Storage>>,
+    channels: Rc<RefCell<Channels>>,
+) {
+    machine.add_callable(BintodataCommand::new(storage.clone()));
+    machine.add_callable(BloadCommand::new(storage.clone()));
+    machine.add_callable(BsaveCommand::new(storage.clone()));
+    machine.add_callable(CdCommand::new(storage.clone()));
+    machine.add_callable(CloseCommand::new(storage.clone(), channels.clone()));
+    machine.add_callable(CopyCommand::new(storage.clone()));
+    machine.add_callable(CopyFileCommand::new(storage.clone()));
+    let csv_widths = Rc::from(RefCell::from(vec![]));
+    machine.add_clearable(CsvClearable::new(csv_widths.clone()));
+    machine.add_callable(CsvcolsFunction::new(csv_widths.clone()));
+    machine.add_callable(CsvfieldFunction::new());
+    machine.add_callable(CsvloadCommand::new(storage.clone(), csv_widths));
+    machine.add_callable(DirCommand::new(console.clone(), storage.clone()));
+    machine.add_callable(DirarrayCommand::new(storage.clone()));
+    machine.add_callable(DiskfreeFunction::new(storage.clone()));
+    machine.add_callable(DiskfreefilesFunction::new(storage.clone()));
+    machine.add_callable(DiskquotaFunction::new(storage.clone()));
+    machine.add_callable(DiskquotafilesFunction::new(storage.clone()));
+    machine.add_callable(EofFunction::new(channels.clone()));
+    machine.add_callable(ExportDriveCommand::new(storage.clone()));
+    machine.add_callable(FiledateFunction::new(storage.clone()));
+    machine.add_callable(FileexistsFunction::new(storage.clone()));
+    machine.add_callable(FilereadbFunction::new(storage.clone()));
+    machine.add_callable(FilesizeFunction::new(storage.clone()));
+    machine.add_callable(ImportDriveCommand::new(storage.clone()));
+    machine.add_callable(KillCommand::new(storage.clone()));
+    machine.add_callable(LineInputCommand::new(channels.clone()));
+    machine.add_callable(MkdirCommand::new(storage.clone()));
+    machine.add_callable(MountCommand::new(console.clone(), storage.clone()));
+    machine.add_callable(OpenCommand::new(storage.clone(), channels.clone()));
+    machine.add_callable(PrintCommand::new(channels));
+    machine.add_callable(PwdCommand::new(console.clone(), storage.clone()));
+    machine.add_callable(RenameCommand::new(storage.clone()));
+    machine.add_callable(RmdirCommand::new(storage.clone()));
+    machine.add_callable(ScreenDumpCommand::new(console.clone(), storage.clone()));
+    machine.add_callable(UnmountCommand::new(storage));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_size, matches_pattern};
+    use crate::console::{CharsXY, Key};
+    use crate::storage::{
+        DirectoryDriveFactory, DiskSpace, Drive, DriveFiles, InMemoryDrive, Metadata,
+    };
+    use crate::testutils::*;
+    use async_trait::async_trait;
+    use endbasic_core::ast::{ExprType, Value};
+    use futures_lite::future::block_on;
+    use std::collections::BTreeMap;
+    use std::io;
+
+    /// A read-only drive that reports a fixed set of files with known metadata.
+    struct MetadataDrive(BTreeMap<String, Metadata>);
+
+    impl MetadataDrive {
+        /// Creates a drive with files of various sizes and dates.
+        fn new() -> Self {
+            let mut files = BTreeMap::default();
+            for (name, timestamp, length) in [
+                ("big.dat", 1_600_000_000, 20_000_000),
+                ("edge.bin", 1_650_000_000, 9_999),
+                ("mid.bas", 1_500_000_000, 10_240),
+                ("small.bas", 1_550_000_000, 10),
+            ] {
+                let date = time::OffsetDateTime::from_unix_timestamp(timestamp).unwrap();
+                files.insert(name.to_owned(), Metadata { date, length });
+            }
+            Self(files)
+        }
+    }
+
+    #[async_trait(?Send)]
+    impl Drive for MetadataDrive {
+        async fn delete(&mut self, _name: &str) -> io::Result<()> {
+            unreachable!("Not used in tests");
+        }
+
+        async fn enumerate(&self) -> io::Result<DriveFiles> {
+            Ok(DriveFiles::new(self.0.clone(), None, None))
+        }
+
+        async fn get(&self, _name: &str) -> io::Result<Vec<u8>> {
+            unreachable!("Not used in tests");
+        }
+
+        async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+            unreachable!("Not used in tests");
+        }
+    }
+
+    /// Creates a tester with a `META` drive backed by a `MetadataDrive`.  Scripts run against it
+    /// must unmount the drive before checking the results.
+    fn tester_with_metadata_drive() -> Tester {
+        let t = Tester::default();
+        t.get_storage()
+            .borrow_mut()
+            .attach("meta", "meta://", Box::from(MetadataDrive::new()), false)
+            .unwrap();
+        t
+    }
+
+    /// Runs `DIR` on the `META` drive with the given `order` and checks that it lists the files
+    /// in the `exp_lines` order.
+    fn check_dir_order(order: &str, exp_lines: &[&'static str]) {
+        let mut prints =
+            vec!["", "    Directory of META:/", "", "    Modified              Size    Name"];
+        prints.extend(exp_lines);
+        prints.extend(["", "    4 file(s), 20020249 bytes", ""]);
+        tester_with_metadata_drive()
+            .run(format!(r#"DIR "meta:/", "{}": UNMOUNT "meta""#, order))
+            .expect_prints(prints)
+            .check();
+    }
+
+    /// Binary fixture for the `BINTODATA` tests with control characters and bytes above 127.
+    const BINTODATA_INPUT: &str = "\u{0}\u{1}A\n\"\u{e9}\u{7f}";
+
+    /// Expected output of `BINTODATA` for `BINTODATA_INPUT` with 3 bytes per line.
+    const BINTODATA_OUTPUT: &str = "' Generated by BINTODATA from MEMORY:sprite.bin (8 bytes)
+DATA 0, 1, 65
+DATA 10, 34, 195
+DATA 169, 127
+";
+
+    #[test]
+    fn test_bintodata_ok() {
+        Tester::default()
+            .write_file("sprite.bin", BINTODATA_INPUT)
+            .run(r#"BINTODATA "sprite.bin", "sprite", 3"#)
+            .expect_file("MEMORY:/sprite.bin", BINTODATA_INPUT)
+            .expect_file("MEMORY:/sprite.bas", BINTODATA_OUTPUT)
+            .check();
+    }
+
+    #[test]
+    fn test_bintodata_default_bytes_per_line() {
+        let input = (0..20).map(|i| char::from(b'a' + i)).collect::<String>();
+        Tester::default()
+            .write_file("letters.txt", &input)
+            .run(r#"BINTODATA "letters.txt", "other.dat""#)
+            .expect_file("MEMORY:/letters.txt", &input)
+            .expect_file(
+                "MEMORY:/other.dat",
+                "' Generated by BINTODATA from MEMORY:letters.txt (20 bytes)
+DATA 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112
+DATA 113, 114, 115, 116
+",
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_bintodata_empty() {
+        Tester::default()
+            .write_file("empty", "")
+            .run(r#"BINTODATA "empty", "empty.bas""#)
+            .expect_file("MEMORY:/empty", "")
+            .expect_file(
+                "MEMORY:/empty.bas",
+                "' Generated by BINTODATA from MEMORY:empty (0 bytes)\n",
+            )
+            .check();
+    }
+
+    #[test]
+    fn test_bintodata_reads_back() {
+        let mut t = Tester::default();
+        t.run(format!(
+            "{}DIM bytes(8)\nFOR i = 0 TO 7: READ b: bytes(i) = b: NEXT",
+            BINTODATA_OUTPUT
+        ))
+        .expect_var("b", 127)
+        .expect_var("i", 8)
+        .expect_array_simple(
+            "bytes",
+            ExprType::Integer,
+            BINTODATA_INPUT.bytes().map(|b| Value::Integer(i32::from(b))).collect(),
+        )
+        .check();
+    }
+
+    #[test]
+    fn test_bintodata_errors() {
+        check_stmt_compilation_err(
+            "1:1: BINTODATA expected <src$, dest$> | <src$, dest$, bytesperline%>",
+            r#"BINTODATA "a""#,
+        );
+        check_stmt_compilation_err(
+            "1:1: BINTODATA expected <src$, dest$> | <src$, dest$, bytesperline%>",
+            r#"BINTODATA "a", "b", 1, 2"#,
+        );
+        check_stmt_compilation_err("1:21: STRING is not a number", r#"BINTODATA "a", "b", "c""#);
+
+        for value in &["0", "-1", "256"] {
+            check_stmt_err(
+                format!("1:21: Bytes per line {} must be between 1 and 255", value),
+                &format!(r#"BINTODATA "a", "b", {}"#, value),
+            );
+        }
+
+        check_stmt_err("1:1: Entry not found", r#"BINTODATA "missing", "out""#);
+
+        Tester::default()
+            .write_file("in", "x")
+            .run(r#"BINTODATA "in", "missing:/out""#)
+            .expect_err("1:1: Drive 'MISSING' is not mounted")
+            .expect_file("MEMORY:/in", "x")
+            .check();
+    }
+
+    #[test]
+    fn test_cd_ok() {
+        let mut t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mount("other", "memory://", false)).unwrap();
+        t.run("CD \"other:\"").check();
+        assert_eq!("OTHER:/", t.get_storage().borrow().cwd());
+        t.run("CD \"memory:/\"").check();
+        assert_eq!("MEMORY:/", t.get_storage().borrow().cwd());
+    }
+
+    #[test]
+    fn test_cd_dirs() {
+        let mut t = Tester::default();
+        t.run(r#"MKDIR "games": CD "games": MKDIR "arcade": CD "arcade""#).check();
+        assert_eq!("MEMORY:/games/arcade", t.get_storage().borrow().cwd());
+        t.run(r#"CD "..""#).check();
+        assert_eq!("MEMORY:/games", t.get_storage().borrow().cwd());
+        t.run(r#"CD "/""#).check();
+        assert_eq!("MEMORY:/", t.get_storage().borrow().cwd());
+    }
+
+    #[test]
+    fn test_cd_errors() {
+        check_stmt_err("1:1: Drive 'A' is not mounted", "CD \"A:\"");
+        check_stmt_err("1:1: Location 'missing' is not a directory", "CD \"missing\"");
+        check_stmt_compilation_err("1:1: CD expected path$", "CD");
+        check_stmt_compilation_err("1:1: CD expected path$", "CD 2, 3");
+        check_stmt_compilation_err("1:4: expected STRING but found INTEGER", "CD 2");
+    }
+
+    #[test]
+    fn test_copy_ok() {
+        Tester::default()
+            .set_program(Some("foo.bas"), "Leave me alone")
+            .write_file("file1", "the content")
+            .run(r#"COPY "file1", "file2""#)
+            .expect_program(Some("foo.bas"), "Leave me alone")
+            .expect_file("MEMORY:/file1", "the content")
+            .expect_file("MEMORY:/file2", "the content")
+            .check();
+    }
+
+    #[test]
+    fn test_copy_deduce_target_name() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mount("other", "memory://", false)).unwrap();
+        t.set_program(Some("foo.bas"), "Leave me alone")
+            .write_file("file1.x", "the content")
+            .run(r#"COPY "file1.x", "OTHER:/""#)
+            .expect_program(Some("foo.bas"), "Leave me alone")
+            .expect_file("MEMORY:/file1.x", "the content")
+            .expect_file("OTHER:/file1.x", "the content")
+            .check();
+    }
+
+    #[test]
+    fn test_copy_errors() {
+        Tester::default()
+            .run(r#"COPY "foo""#)
+            .expect_compilation_err("1:1: COPY expected src$, dest$")
+            .check();
+
+        Tester::default()
+            .run(r#"COPY "memory:/", "foo.bar""#)
             .expect_err("1:1: Missing file name in copy source path 'memory:/'")
             .check();
 
         Tester::default()
-            .run(r#"COPY "missing.txt", "new.txt""#)
-            .expect_err("1:1: Entry not found")
+            .run(r#"COPY "missing.txt", "new.txt""#)
+            .expect_err("1:1: Entry not found")
+            .check();
+
+        Tester::default()
+            .write_file("foo", "irrelevant")
+            .run(r#"COPY "foo", "missing:/""#)
+            .expect_err("1:1: Drive 'MISSING' is not mounted")
+            .expect_file("MEMORY:/foo", "irrelevant")
+            .check();
+
+        //Tester::default()
+        //    .run(r#"KILL "a/b.bas""#)
+        //    .expect_err("1:1: Directory of 'a/b.bas' does not exist")
+        //    .check();
+
+        //Tester::default()
+        //    .run(r#"KILL "drive:""#)
+        //    .expect_err("1:1: Missing file name in path 'drive:'")
+        //    .check();
+
+        //Tester::default()
+        //    .run("KILL")
+        //    .expect_compilation_err("1:1: KILL expected filename$")
+        //    .check();
+
+        //check_stmt_err("1:1: Entry not found", r#"KILL "missing-file""#);
+
+        //Tester::default()
+        //    .write_file("no-automatic-extension.bas", "")
+        //    .run(r#"KILL "no-automatic-extension""#)
+        //    .expect_err("1:1: Entry not found")
+        //    .expect_file("MEMORY:/no-automatic-extension.bas", "")
+        //    .check();
+    }
+
+    #[test]
+    fn test_copyfile_across_drives() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mount("local", "memory://", false)).unwrap();
+        t.write_file("tour.bas", "PRINT \"tour\"\r\n\t' Trailing spaces  \n")
+            .run(
+                r#"COPYFILE "MEMORY:/tour.bas", "LOCAL:/TOUR2.BAS": COPYFILE "tour.bas", "local:""#,
+            )
+            .expect_file("MEMORY:/tour.bas", "PRINT \"tour\"\r\n\t' Trailing spaces  \n")
+            .expect_file("LOCAL:/TOUR2.BAS", "PRINT \"tour\"\r\n\t' Trailing spaces  \n")
+            .expect_file("LOCAL:/tour.bas", "PRINT \"tour\"\r\n\t' Trailing spaces  \n")
+            .check();
+    }
+
+    #[test]
+    fn test_copyfile_overwrite_guard() {
+        Tester::default()
+            .write_file("a.bas", "new")
+            .write_file("b.bas", "old")
+            .run(r#"COPYFILE "a.bas", "b.bas""#)
+            .expect_err("1:1: Destination MEMORY:b.bas already exists")
+            .expect_file("MEMORY:/a.bas", "new")
+            .expect_file("MEMORY:/b.bas", "old")
+            .check();
+
+        Tester::default()
+            .write_file("a.bas", "new")
+            .write_file("b.bas", "old")
+            .run(r#"COPYFILE "a.bas", "b.bas", FALSE"#)
+            .expect_err("1:1: Destination MEMORY:b.bas already exists")
+            .expect_file("MEMORY:/a.bas", "new")
+            .expect_file("MEMORY:/b.bas", "old")
+            .check();
+
+        Tester::default()
+            .write_file("a.bas", "new")
+            .write_file("b.bas", "old")
+            .run(r#"COPYFILE "a.bas", "b.bas", TRUE"#)
+            .expect_file("MEMORY:/a.bas", "new")
+            .expect_file("MEMORY:/b.bas", "new")
+            .check();
+    }
+
+    #[test]
+    fn test_copyfile_errors() {
+        check_stmt_compilation_err(
+            "1:1: COPYFILE expected <src$, dest$> | <src$, dest$, overwrite?>",
+            r#"COPYFILE "a""#,
+        );
+        check_stmt_compilation_err(
+            "1:20: expected BOOLEAN but found INTEGER",
+            r#"COPYFILE "a", "b", 1"#,
+        );
+        check_stmt_err("1:1: Entry not found", r#"COPYFILE "missing.bas", "new.bas""#);
+        check_stmt_err(
+            "1:1: Missing file name in copy source path 'memory:/'",
+            r#"COPYFILE "memory:/", "new.bas""#,
+        );
+
+        Tester::default()
+            .write_file("foo", "irrelevant")
+            .run(r#"COPYFILE "foo", "missing:/""#)
+            .expect_err("1:1: Drive 'MISSING' is not mounted")
+            .expect_file("MEMORY:/foo", "irrelevant")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_current_empty() {
+        Tester::default()
+            .run("DIR")
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    0 file(s), 0 bytes",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_dir_with_disk_free() {
+        let mut other = InMemoryDrive::default();
+        other.fake_disk_quota = Some(DiskSpace::new(456, 0));
+        other.fake_disk_free = Some(DiskSpace::new(123, 0));
+
+        let mut t = Tester::default();
+        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other), false).unwrap();
+
+        t.run("DIR \"OTHER:/\"")
+            .expect_prints([
+                "",
+                "    Directory of OTHER:/",
+                "",
+                "    Modified              Size    Name",
+                "    0 file(s), 0 bytes",
+                "    123 of 456 bytes free",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_dir_current_entries_are_sorted() {
+        Tester::default()
+            .write_file("empty.bas", "")
+            .write_file("some other file.bas", "not empty\n")
+            .write_file("00AAA.BAS", "first\nfile\n")
+            .write_file("not a bas.txt", "")
+            .run("DIR")
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37        11    00AAA.BAS",
+                "    2020-05-06 09:37         0    empty.bas",
+                "    2020-05-06 09:37         0    not a bas.txt",
+                "    2020-05-06 09:37        10    some other file.bas",
+                "",
+                "    4 file(s), 21 bytes",
+                "",
+            ])
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("MEMORY:/some other file.bas", "not empty\n")
+            .expect_file("MEMORY:/00AAA.BAS", "first\nfile\n")
+            .expect_file("MEMORY:/not a bas.txt", "")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_other_by_argument() {
+        let mut other = InMemoryDrive::default();
+        block_on(other.put("foo.bas", b"hello")).unwrap();
+
+        let mut t = Tester::default().write_file("empty.bas", "");
+        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other), false).unwrap();
+
+        let mut prints = vec![
+            "",
+            "    Directory of MEMORY:/",
+            "",
+            "    Modified              Size    Name",
+            "    2020-05-06 09:37         0    empty.bas",
+            "",
+            "    1 file(s), 0 bytes",
+            "",
+        ];
+        t.run("DIR \"memory:\"")
+            .expect_prints(prints.clone())
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("OTHER:/foo.bas", "hello")
+            .check();
+
+        prints.extend([
+            "",
+            "    Directory of OTHER:/",
+            "",
+            "    Modified              Size    Name",
+            "    2020-05-06 09:37         5    foo.bas",
+            "",
+            "    1 file(s), 5 bytes",
+            "",
+        ]);
+        t.run("DIR \"other:/\"")
+            .expect_prints(prints)
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("OTHER:/foo.bas", "hello")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_other_by_cwd() {
+        let mut other = InMemoryDrive::default();
+        block_on(other.put("foo.bas", b"hello")).unwrap();
+
+        let mut t = Tester::default().write_file("empty.bas", "");
+        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other), false).unwrap();
+
+        let mut prints = vec![
+            "",
+            "    Directory of MEMORY:/",
+            "",
+            "    Modified              Size    Name",
+            "    2020-05-06 09:37         0    empty.bas",
+            "",
+            "    1 file(s), 0 bytes",
+            "",
+        ];
+        t.run("DIR")
+            .expect_prints(prints.clone())
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("OTHER:/foo.bas", "hello")
+            .check();
+
+        t.get_storage().borrow_mut().cd("other:/").unwrap();
+        prints.extend([
+            "",
+            "    Directory of OTHER:/",
+            "",
+            "    Modified              Size    Name",
+            "    2020-05-06 09:37         5    foo.bas",
+            "",
+            "    1 file(s), 5 bytes",
+            "",
+        ]);
+        t.run("DIR")
+            .expect_prints(prints)
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("OTHER:/foo.bas", "hello")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_narrow_empty() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 1));
+        t.run("DIR")
+            .expect_prints(["", "    Directory of MEMORY:/", "", "    0 file(s)", ""])
+            .check();
+    }
+
+    #[test]
+    fn test_dir_narrow_some() {
+        let mut t = Tester::default().write_file("empty.bas", "");
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 1));
+        t.run("DIR")
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    empty.bas",
+                "",
+                "    1 file(s)",
+                "",
+            ])
+            .expect_file("MEMORY:/empty.bas", "")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_paging() {
+        let t = Tester::default();
+        t.get_console().borrow_mut().set_interactive(true);
+        t.get_console().borrow_mut().set_size_chars(CharsXY { x: 80, y: 7 });
+        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
+        t.write_file("0.bas", "")
+            .write_file("1.bas", "")
+            .write_file("2.bas", "")
+            .write_file("3.bas", "")
+            .run("DIR")
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37         0    0.bas",
+                "    2020-05-06 09:37         0    1.bas",
+                " << Press any key for more; ESC or Ctrl+C to stop >> ",
+                "    2020-05-06 09:37         0    2.bas",
+                "    2020-05-06 09:37         0    3.bas",
+                "",
+                "    4 file(s), 0 bytes",
+                "",
+            ])
+            .expect_file("MEMORY:/0.bas", "")
+            .expect_file("MEMORY:/1.bas", "")
+            .expect_file("MEMORY:/2.bas", "")
+            .expect_file("MEMORY:/3.bas", "")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_with_subdirs() {
+        Tester::default()
+            .write_file("top.bas", "top\n")
+            .run(
+                r#"MKDIR "games": MKDIR "games/arcade": MKDIR "zzz": COPY "top.bas", "games"
+                DIR: DIR "games""#,
+            )
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37     <DIR>    games",
+                "    2020-05-06 09:37     <DIR>    zzz",
+                "    2020-05-06 09:37         4    top.bas",
+                "",
+                "    1 file(s), 4 bytes",
+                "    2 dir(s)",
+                "",
+                "",
+                "    Directory of MEMORY:games",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37     <DIR>    arcade",
+                "    2020-05-06 09:37         4    top.bas",
+                "",
+                "    1 file(s), 4 bytes",
+                "    1 dir(s)",
+                "",
+            ])
+            .expect_file("MEMORY:/top.bas", "top\n")
+            .expect_file("MEMORY:/games/top.bas", "top\n")
+            .check();
+    }
+
+    #[test]
+    fn test_dir_narrow_with_subdirs() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 1));
+        t.run(r#"MKDIR "games": DIR"#)
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    games/",
+                "",
+                "    0 file(s)",
+                "    1 dir(s)",
+                "",
+            ])
+            .check();
+    }
+
+    #[test]
+    fn test_dir_with_subdirs_on_file_drive() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path().canonicalize().unwrap();
+        std::fs::create_dir(dir.join("sub")).unwrap();
+
+        let t = Tester::default();
+        {
+            let storage = t.get_storage();
+            let mut storage = storage.borrow_mut();
+            storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
+            block_on(storage.mount("disk", &format!("file://{}", dir.display()), false)).unwrap();
+        }
+        t.set_program(None, "PRINT 1\n")
+            .run(r#"MKDIR "disk:/sub/nested": CD "disk:sub/nested": SAVE "prog""#)
+            .expect_program(Some("DISK:/sub/nested/prog.bas"), "PRINT 1\n")
+            .expect_prints(["Saved as DISK:/sub/nested/prog.bas"])
+            .expect_file("DISK:/sub/nested/prog.bas", "PRINT 1\n")
             .check();
+        assert!(dir.join("sub/nested/prog.bas").is_file());
+    }
+
+    #[test]
+    fn test_dir_errors() {
+        check_stmt_compilation_err(
+            "1:1: DIR expected <> | <path$> | <path$, order$>",
+            "DIR 2, 3, 4",
+        );
+        check_stmt_compilation_err("1:5: expected STRING but found INTEGER", "DIR 2");
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("*", "anything.bas"));
+        assert!(matches_pattern("*.BAS", "game.bas"));
+        assert!(matches_pattern("g?me.*", "GAME.BAS"));
+        assert!(matches_pattern("*a*a*", "banana"));
+        assert!(!matches_pattern("*.bas", "game.bas.txt"));
+        assert!(!matches_pattern("?", ""));
+        assert!(!matches_pattern("a*b", "acbd"));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!("0", format_size(0));
+        assert_eq!("9999", format_size(9999));
+        assert_eq!("10K", format_size(10000));
+        assert_eq!("9999K", format_size(9999 * 1024));
+        assert_eq!("10M", format_size(10000 * 1024));
+        assert_eq!("2048M", format_size(2 * 1024 * 1024 * 1024));
+        assert_eq!("20G", format_size(20 * 1024 * 1024 * 1024));
+    }
+
+    const BIG: &str = "    2020-09-13 12:26       19M    big.dat";
+    const EDGE: &str = "    2022-04-15 05:20      9999    edge.bin";
+    const MID: &str = "    2017-07-14 02:40       10K    mid.bas";
+    const SMALL: &str = "    2019-02-12 19:33        10    small.bas";
+
+    #[test]
+    fn test_dir_order_name() {
+        check_dir_order("NAME", &[BIG, EDGE, MID, SMALL]);
+        check_dir_order("-name", &[SMALL, MID, EDGE, BIG]);
+    }
+
+    #[test]
+    fn test_dir_order_date() {
+        check_dir_order("DATE", &[MID, SMALL, BIG, EDGE]);
+        check_dir_order("-Date", &[EDGE, BIG, SMALL, MID]);
+    }
+
+    #[test]
+    fn test_dir_order_size() {
+        check_dir_order("SIZE", &[SMALL, EDGE, MID, BIG]);
+        check_dir_order("-SIZE", &[BIG, MID, EDGE, SMALL]);
+    }
 
+    #[test]
+    fn test_dir_order_with_refresh() {
+        check_dir_order("REFRESH", &[BIG, EDGE, MID, SMALL]);
+        check_dir_order("-size refresh", &[BIG, MID, EDGE, SMALL]);
+        check_dir_order(" Refresh  DATE ", &[MID, SMALL, BIG, EDGE]);
+    }
+
+    #[test]
+    fn test_dir_order_keeps_subdirs_first() {
         Tester::default()
-            .write_file("foo", "irrelevant")
-            .run(r#"COPY "foo", "missing:/""#)
-            .expect_err("1:1: Drive 'MISSING' is not mounted")
-            .expect_file("MEMORY:/foo", "irrelevant")
+            .write_file("a.bas", "12345")
+            .write_file("b.bas", "1")
+            .run(r#"MKDIR "sub": DIR "", "-SIZE""#)
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37     <DIR>    sub",
+                "    2020-05-06 09:37         5    a.bas",
+                "    2020-05-06 09:37         1    b.bas",
+                "",
+                "    2 file(s), 6 bytes",
+                "    1 dir(s)",
+                "",
+            ])
+            .expect_file("MEMORY:/a.bas", "12345")
+            .expect_file("MEMORY:/b.bas", "1")
             .check();
+    }
 
-        //Tester::default()
-        //    .run(r#"KILL "a/b.bas""#)
-        //    .expect_err("1:1: Too many / separators in path 'a/b.bas'")
-        //    .check();
+    #[test]
+    fn test_dir_pattern() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mkdir("games")).unwrap();
+        t.write_file("one.bas", "")
+            .write_file("two.BAS", "")
+            .write_file("three.txt", "")
+            .write_file("games/pong.bas", "")
+            .write_file("games/tetris.bas", "")
+            .run(r#"DIR "*.bas": DIR "memory:/games/p?ng.*""#)
+            .expect_prints([
+                "",
+                "    Directory of MEMORY:/",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37         0    one.bas",
+                "    2020-05-06 09:37         0    two.BAS",
+                "",
+                "    2 file(s), 0 bytes",
+                "",
+                "",
+                "    Directory of MEMORY:/games",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37         0    pong.bas",
+                "",
+                "    1 file(s), 0 bytes",
+                "",
+            ])
+            .expect_file("MEMORY:/one.bas", "")
+            .expect_file("MEMORY:/two.BAS", "")
+            .expect_file("MEMORY:/three.txt", "")
+            .expect_file("MEMORY:/games/pong.bas", "")
+            .expect_file("MEMORY:/games/tetris.bas", "")
+            .check();
+    }
 
-        //Tester::default()
-        //    .run(r#"KILL "drive:""#)
-        //    .expect_err("1:1: Missing file name in path 'drive:'")
-        //    .check();
+    #[test]
+    fn test_dir_order_errors() {
+        Tester::default()
+            .run(r#"DIR "", "BIGGEST""#)
+            .expect_err("1:9: Invalid order 'BIGGEST'; must be NAME, DATE, or SIZE")
+            .check();
+        Tester::default()
+            .run(r#"DIR "", "--SIZE""#)
+            .expect_err("1:9: Invalid order '--SIZE'; must be NAME, DATE, or SIZE")
+            .check();
+        Tester::default()
+            .run(r#"DIR "", "NAME SIZE""#)
+            .expect_err("1:9: Invalid order 'NAME SIZE'; must be NAME, DATE, or SIZE")
+            .check();
+        Tester::default()
+            .run(r#"DIR "", "REFRESH REFRESH""#)
+            .expect_err("1:9: Invalid order 'REFRESH REFRESH'; must be NAME, DATE, or SIZE")
+            .check();
+        check_stmt_compilation_err("1:9: expected STRING but found INTEGER", r#"DIR "", 3"#);
+    }
 
-        //Tester::default()
-        //    .run("KILL")
-        //    .expect_compilation_err("1:1: KILL expected filename$")
-        //    .check();
+    #[test]
+    fn test_dirarray_ok() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mkdir("sub")).unwrap();
+        t.write_file("b.bas", "")
+            .write_file("a.BAS", "")
+            .write_file("c.txt", "")
+            .write_file("sub/d.bas", "")
+            .run(r#"DIM names(1) AS STRING: DIRARRAY names, "*.bas": n = UBOUND(names)"#)
+            .expect_var("n", Value::Integer(1))
+            .expect_array_simple("names", ExprType::Text, vec!["a.BAS".into(), "b.bas".into()])
+            .expect_file("MEMORY:/a.BAS", "")
+            .expect_file("MEMORY:/b.bas", "")
+            .expect_file("MEMORY:/c.txt", "")
+            .expect_file("MEMORY:/sub/d.bas", "")
+            .check();
+    }
 
-        //check_stmt_err("1:1: Entry not found", r#"KILL "missing-file""#);
+    #[test]
+    fn test_dirarray_accounts_memory() {
+        let mut t = Tester::default();
+        for i in 0..20 {
+            t = t.write_file(&format!("f{:02}.bas", i), "");
+        }
+
+        let mut c = t.run(r#"SUB s: DIM a(1) AS STRING: DIRARRAY a, "*": END SUB: s"#);
+        for i in 0..20 {
+            c = c.expect_file(format!("MEMORY:/f{:02}.bas", i), "");
+        }
+        c.check();
+        assert_eq!(0, t.get_machine().get_symbols().allocated());
+
+        t.get_machine().get_mut_symbols().set_memory_limit(Some(100));
+        let mut c = t.run(r#"DIM a(1) AS STRING: DIRARRAY a, "*""#);
+        for i in 0..20 {
+            c = c.expect_file(format!("MEMORY:/f{:02}.bas", i), "");
+        }
+        c.expect_array_simple("a", ExprType::Text, vec!["".into()])
+            .expect_err("1:30: Out of memory: limit is 100 bytes")
+            .check();
+    }
+
+    #[test]
+    fn test_dirarray_whole_directory() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mkdir("sub")).unwrap();
+        block_on(t.get_storage().borrow_mut().mkdir("sub/nested")).unwrap();
+        t.write_file("top.bas", "")
+            .write_file("sub/x.bas", "")
+            .write_file("sub/y.txt", "")
+            .write_file("sub/nested/z.bas", "")
+            .run(r#"DIM names(5) AS STRING: DIRARRAY names, "memory:/sub""#)
+            .expect_array_simple("names", ExprType::Text, vec!["x.bas".into(), "y.txt".into()])
+            .expect_file("MEMORY:/top.bas", "")
+            .expect_file("MEMORY:/sub/x.bas", "")
+            .expect_file("MEMORY:/sub/y.txt", "")
+            .expect_file("MEMORY:/sub/nested/z.bas", "")
+            .check();
+    }
+
+    #[test]
+    fn test_dirarray_empty_match() {
+        Tester::default()
+            .write_file("a.txt", "")
+            .run(r#"DIM names(3) AS STRING: names(2) = "old": DIRARRAY names, "*.bas""#)
+            .expect_array_simple("names", ExprType::Text, vec!["".into()])
+            .expect_file("MEMORY:/a.txt", "")
+            .check();
+    }
+
+    #[test]
+    fn test_dirarray_errors() {
+        check_stmt_compilation_err("1:1: DIRARRAY expected names, pattern$", r#"DIRARRAY "*.bas""#);
+        Tester::default()
+            .run(r#"DIM a(1): DIRARRAY a, "*""#)
+            .expect_array("a", ExprType::Integer, &[1], vec![])
+            .expect_err("1:20: Array A must be of type STRING but is INTEGER")
+            .check();
+        Tester::default()
+            .run(r#"DIM a(1, 2) AS STRING: DIRARRAY a, "*""#)
+            .expect_array("a", ExprType::Text, &[1, 2], vec![])
+            .expect_err("1:33: Array A must have 1 dimension but has 2")
+            .check();
+        Tester::default()
+            .run(r#"DIM a(1) AS STRING: DIRARRAY a, "missing:/*""#)
+            .expect_array("a", ExprType::Text, &[1], vec![])
+            .expect_err("1:21: Drive 'MISSING' is not mounted")
+            .check();
+    }
+
+    #[test]
+    fn test_mkdir_rmdir_ok() {
+        let mut t = Tester::default();
+        t.run(r#"MKDIR "a": MKDIR "a/b": MKDIR "memory:/a/c""#).check();
+        assert_eq!(2, block_on(t.get_storage().borrow().enumerate("a")).unwrap().subdirs().len());
+        t.run(r#"RMDIR "a/b": RMDIR "memory:a/c": RMDIR "/a""#).check();
+        assert!(block_on(t.get_storage().borrow().enumerate("")).unwrap().subdirs().is_empty());
+    }
+
+    #[test]
+    fn test_mkdir_rmdir_errors() {
+        Tester::default()
+            .write_file("file.bas", "x")
+            .run(r#"MKDIR "file.bas""#)
+            .expect_err("1:1: Entry already exists")
+            .expect_file("MEMORY:/file.bas", "x")
+            .check();
+
+        Tester::default()
+            .run(r#"MKDIR "dir": RMDIR "dir/missing""#)
+            .expect_err("1:14: Directory not found")
+            .check();
+
+        check_stmt_err("1:1: Directory of 'a/b' does not exist", r#"MKDIR "a/b""#);
+        check_stmt_err("1:1: Cannot remove the root directory 'memory:'", r#"RMDIR "memory:""#);
 
-        //Tester::default()
-        //    .write_file("no-automatic-extension.bas", "")
-        //    .run(r#"KILL "no-automatic-extension""#)
-        //    .expect_err("1:1: Entry not found")
-        //    .expect_file("MEMORY:/no-automatic-extension.bas", "")
-        //    .check();
+        check_stmt_compilation_err("1:1: MKDIR expected path$", "MKDIR");
+        check_stmt_compilation_err("1:7: expected STRING but found INTEGER", "MKDIR 2");
+        check_stmt_compilation_err("1:1: RMDIR expected path$", "RMDIR");
+        check_stmt_compilation_err("1:7: expected STRING but found INTEGER", "RMDIR 2");
     }
 
     #[test]
-    fn test_dir_current_empty() {
+    fn test_rename_ok() {
         Tester::default()
-            .run("DIR")
-            .expect_prints([
-                "",
-                "    Directory of MEMORY:/",
-                "",
-                "    Modified              Size    Name",
-                "    0 file(s), 0 bytes",
-                "",
-            ])
+            .set_program(Some("foo.bas"), "Leave me alone")
+            .write_file("a.bas", "content")
+            .run(r#"MKDIR "dir": RENAME "a.bas", "b.bas": RENAME "b.bas", "dir""#)
+            .expect_program(Some("foo.bas"), "Leave me alone")
+            .expect_file("MEMORY:/dir/b.bas", "content")
             .check();
     }
 
     #[test]
-    fn test_dir_with_disk_free() {
-        let mut other = InMemoryDrive::default();
-        other.fake_disk_quota = Some(DiskSpace::new(456, 0));
-        other.fake_disk_free = Some(DiskSpace::new(123, 0));
+    fn test_rename_across_drives() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mount("other", "memory://", false)).unwrap();
+        t.write_file("a.bas", "content")
+            .write_file("keep.bas", "keep")
+            .run(r#"RENAME "a.bas", "OTHER:/b.bas""#)
+            .expect_file("MEMORY:/keep.bas", "keep")
+            .expect_file("OTHER:/b.bas", "content")
+            .check();
+    }
 
-        let mut t = Tester::default();
-        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other)).unwrap();
+    #[test]
+    fn test_rename_errors() {
+        check_stmt_compilation_err("1:1: RENAME expected old$, new$", r#"RENAME "a""#);
+        check_stmt_compilation_err("1:8: expected STRING but found INTEGER", r#"RENAME 1, "a""#);
+        check_stmt_err("1:1: Entry not found", r#"RENAME "missing.bas", "new.bas""#);
+        check_stmt_err("1:1: Missing file name in path 'memory:/'", r#"RENAME "memory:/", "a""#);
 
-        t.run("DIR \"OTHER:/\"")
-            .expect_prints([
-                "",
-                "    Directory of OTHER:/",
-                "",
-                "    Modified              Size    Name",
-                "    0 file(s), 0 bytes",
-                "    123 of 456 bytes free",
-                "",
-            ])
+        Tester::default()
+            .write_file("a.bas", "a")
+            .write_file("b.bas", "b")
+            .run(r#"RENAME "a.bas", "b.bas""#)
+            .expect_err("1:1: Destination MEMORY:b.bas already exists")
+            .expect_file("MEMORY:/a.bas", "a")
+            .expect_file("MEMORY:/b.bas", "b")
+            .check();
+
+        Tester::default()
+            .write_file("a.bas", "a")
+            .run(r#"RENAME "a.bas", "missing:""#)
+            .expect_err("1:1: Drive 'MISSING' is not mounted")
+            .expect_file("MEMORY:/a.bas", "a")
             .check();
     }
 
     #[test]
-    fn test_dir_current_entries_are_sorted() {
+    fn test_rmdir_non_empty() {
         Tester::default()
-            .write_file("empty.bas", "")
-            .write_file("some other file.bas", "not empty\n")
-            .write_file("00AAA.BAS", "first\nfile\n")
-            .write_file("not a bas.txt", "")
-            .run("DIR")
-            .expect_prints([
-                "",
-                "    Directory of MEMORY:/",
-                "",
-                "    Modified              Size    Name",
-                "    2020-05-06 09:37        11    00AAA.BAS",
-                "    2020-05-06 09:37         0    empty.bas",
-                "    2020-05-06 09:37         0    not a bas.txt",
-                "    2020-05-06 09:37        10    some other file.bas",
-                "",
-                "    4 file(s), 21 bytes",
-                "",
-            ])
-            .expect_file("MEMORY:/empty.bas", "")
-            .expect_file("MEMORY:/some other file.bas", "not empty\n")
-            .expect_file("MEMORY:/00AAA.BAS", "first\nfile\n")
-            .expect_file("MEMORY:/not a bas.txt", "")
+            .set_program(None, "PRINT 1\n")
+            .run(r#"MKDIR "dir": SAVE "dir/prog": RMDIR "dir""#)
+            .expect_err("1:31: Directory not empty")
+            .expect_program(Some("MEMORY:dir/prog.bas"), "PRINT 1\n")
+            .expect_prints(["Saved as MEMORY:dir/prog.bas"])
+            .expect_file("MEMORY:/dir/prog.bas", "PRINT 1\n")
             .check();
     }
 
     #[test]
-    fn test_dir_other_by_argument() {
+    fn test_disk_space_known() {
         let mut other = InMemoryDrive::default();
-        block_on(other.put("foo.bas", b"hello")).unwrap();
-
-        let mut t = Tester::default().write_file("empty.bas", "");
-        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other)).unwrap();
+        other.fake_disk_quota = Some(DiskSpace::new(456, 20));
+        other.fake_disk_free = Some(DiskSpace::new(123, 5));
 
-        let mut prints = vec![
-            "",
-            "    Directory of MEMORY:/",
-            "",
-            "    Modified              Size    Name",
-            "    2020-05-06 09:37         0    empty.bas",
-            "",
-            "    1 file(s), 0 bytes",
-            "",
-        ];
-        t.run("DIR \"memory:\"")
-            .expect_prints(prints.clone())
-            .expect_file("MEMORY:/empty.bas", "")
-            .expect_file("OTHER:/foo.bas", "hello")
-            .check();
+        let mut t = Tester::default();
+        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other), false).unwrap();
+        t.run(
+            r#"free = DISKFREE("other"): freefiles = DISKFREEFILES("OTHER:")
+            quota = DISKQUOTA("Other"): quotafiles = DISKQUOTAFILES("other:")"#,
+        )
+        .expect_var("free", Value::Double(123.0))
+        .expect_var("freefiles", Value::Integer(5))
+        .expect_var("quota", Value::Double(456.0))
+        .expect_var("quotafiles", Value::Integer(20))
+        .check();
+    }
 
-        prints.extend([
-            "",
-            "    Directory of OTHER:/",
-            "",
-            "    Modified              Size    Name",
-            "    2020-05-06 09:37         5    foo.bas",
-            "",
-            "    1 file(s), 5 bytes",
-            "",
-        ]);
-        t.run("DIR \"other:/\"")
-            .expect_prints(prints)
-            .expect_file("MEMORY:/empty.bas", "")
-            .expect_file("OTHER:/foo.bas", "hello")
+    #[test]
+    fn test_disk_space_unknown() {
+        Tester::default()
+            .run(
+                r#"free = DISKFREE("memory"): freefiles = DISKFREEFILES("memory")
+                quota = DISKQUOTA("memory:"): quotafiles = DISKQUOTAFILES("memory:")"#,
+            )
+            .expect_var("free", Value::Double(-1.0))
+            .expect_var("freefiles", Value::Integer(-1))
+            .expect_var("quota", Value::Double(-1.0))
+            .expect_var("quotafiles", Value::Integer(-1))
             .check();
     }
 
     #[test]
-    fn test_dir_other_by_cwd() {
-        let mut other = InMemoryDrive::default();
-        block_on(other.put("foo.bas", b"hello")).unwrap();
-
-        let mut t = Tester::default().write_file("empty.bas", "");
-        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other)).unwrap();
+    fn test_disk_space_errors() {
+        for name in ["DISKFREE", "DISKFREEFILES", "DISKQUOTA", "DISKQUOTAFILES"] {
+            check_expr_error(
+                format!("1:{}: Drive 'MISSING' is not mounted", 11 + name.len()),
+                &format!(r#"{}("missing")"#, name),
+            );
+            check_expr_compilation_error(
+                format!("1:10: {} expected drive$", name),
+                &format!("{}()", name),
+            );
+            check_expr_compilation_error(
+                format!("1:{}: expected STRING but found INTEGER", 11 + name.len()),
+                &format!("{}(3)", name),
+            );
+        }
+    }
 
-        let mut prints = vec![
-            "",
-            "    Directory of MEMORY:/",
-            "",
-            "    Modified              Size    Name",
-            "    2020-05-06 09:37         0    empty.bas",
-            "",
-            "    1 file(s), 0 bytes",
-            "",
-        ];
-        t.run("DIR")
-            .expect_prints(prints.clone())
-            .expect_file("MEMORY:/empty.bas", "")
-            .expect_file("OTHER:/foo.bas", "hello")
+    #[test]
+    fn test_filedate_ok() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mkdir("dir")).unwrap();
+        t.write_file("dir/file.bas", "abc")
+            .run(r#"CD "dir": d$ = FILEDATE("FILE.BAS")"#)
+            .expect_var("d", "2020-05-06 09:37:55")
+            .expect_file("MEMORY:/dir/file.bas", "abc")
             .check();
+    }
 
-        t.get_storage().borrow_mut().cd("other:/").unwrap();
-        prints.extend([
-            "",
-            "    Directory of OTHER:/",
-            "",
-            "    Modified              Size    Name",
-            "    2020-05-06 09:37         5    foo.bas",
-            "",
-            "    1 file(s), 5 bytes",
-            "",
-        ]);
-        t.run("DIR")
-            .expect_prints(prints)
-            .expect_file("MEMORY:/empty.bas", "")
-            .expect_file("OTHER:/foo.bas", "hello")
-            .check();
+    #[test]
+    fn test_filedate_errors() {
+        check_expr_compilation_error("1:10: FILEDATE expected name$", "FILEDATE()");
+        check_expr_compilation_error("1:19: expected STRING but found INTEGER", "FILEDATE(3)");
+        check_expr_error("1:19: Entry not found", r#"FILEDATE("x.bas")"#);
+        check_expr_error("1:19: Drive 'FOO' is not mounted", r#"FILEDATE("foo:x.bas")"#);
     }
 
     #[test]
-    fn test_dir_narrow_empty() {
-        let mut t = Tester::default();
-        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 1));
-        t.run("DIR")
-            .expect_prints(["", "    Directory of MEMORY:/", "", "    0 file(s)", ""])
+    fn test_fileexists_ok() {
+        let t = Tester::default();
+        block_on(t.get_storage().borrow_mut().mkdir("dir")).unwrap();
+        t.write_file("dir/file.bas", "abc")
+            .run(
+                r#"a = FILEEXISTS("dir/file.bas"): b = FILEEXISTS("memory:/dir/FILE.BAS")
+c = FILEEXISTS("dir/missing.bas"): d = FILEEXISTS("dir"): e = FILEEXISTS("x/y.bas")"#,
+            )
+            .expect_var("a", true)
+            .expect_var("b", true)
+            .expect_var("c", false)
+            .expect_var("d", false)
+            .expect_var("e", false)
+            .expect_file("MEMORY:/dir/file.bas", "abc")
             .check();
     }
 
     #[test]
-    fn test_dir_narrow_some() {
-        let mut t = Tester::default().write_file("empty.bas", "");
-        t.get_console().borrow_mut().set_size_chars(CharsXY::new(10, 1));
-        t.run("DIR")
-            .expect_prints([
-                "",
-                "    Directory of MEMORY:/",
-                "",
-                "    empty.bas",
-                "",
-                "    1 file(s)",
-                "",
-            ])
-            .expect_file("MEMORY:/empty.bas", "")
-            .check();
+    fn test_fileexists_errors() {
+        check_expr_compilation_error("1:10: FILEEXISTS expected name$", "FILEEXISTS()");
+        check_expr_compilation_error("1:21: expected STRING but found INTEGER", "FILEEXISTS(3)");
+        check_expr_error("1:21: Drive 'FOO' is not mounted", r#"FILEEXISTS("foo:x.bas")"#);
+        check_expr_error("1:21: Invalid path 'a:b\\c'", r#"FILEEXISTS("a:b\\c")"#);
     }
 
     #[test]
-    fn test_dir_paging() {
+    fn test_filesize_ok() {
         let t = Tester::default();
-        t.get_console().borrow_mut().set_interactive(true);
-        t.get_console().borrow_mut().set_size_chars(CharsXY { x: 80, y: 7 });
-        t.get_console().borrow_mut().add_input_keys(&[Key::NewLine]);
-        t.write_file("0.bas", "")
-            .write_file("1.bas", "")
-            .write_file("2.bas", "")
-            .write_file("3.bas", "")
-            .run("DIR")
-            .expect_prints([
-                "",
-                "    Directory of MEMORY:/",
-                "",
-                "    Modified              Size    Name",
-                "    2020-05-06 09:37         0    0.bas",
-                "    2020-05-06 09:37         0    1.bas",
-                " << Press any key for more; ESC or Ctrl+C to stop >> ",
-                "    2020-05-06 09:37         0    2.bas",
-                "    2020-05-06 09:37         0    3.bas",
-                "",
-                "    4 file(s), 0 bytes",
-                "",
-            ])
-            .expect_file("MEMORY:/0.bas", "")
-            .expect_file("MEMORY:/1.bas", "")
-            .expect_file("MEMORY:/2.bas", "")
-            .expect_file("MEMORY:/3.bas", "")
+        block_on(t.get_storage().borrow_mut().mkdir("dir")).unwrap();
+        t.write_file("empty.bas", "")
+            .write_file("dir/file.bas", "abc")
+            .run(r#"a = FILESIZE("empty.bas"): CD "dir": b = FILESIZE("File.Bas")"#)
+            .expect_var("a", 0)
+            .expect_var("b", 3)
+            .expect_file("MEMORY:/empty.bas", "")
+            .expect_file("MEMORY:/dir/file.bas", "abc")
             .check();
     }
 
     #[test]
-    fn test_dir_errors() {
-        check_stmt_compilation_err("1:1: DIR expected <> | <path$>", "DIR 2, 3");
-        check_stmt_compilation_err("1:5: expected STRING but found INTEGER", "DIR 2");
+    fn test_filesize_errors() {
+        check_expr_compilation_error("1:10: FILESIZE expected name$", "FILESIZE()");
+        check_expr_compilation_error("1:19: expected STRING but found INTEGER", "FILESIZE(3)");
+        check_expr_error("1:19: Entry not found", r#"FILESIZE("x.bas")"#);
     }
 
     #[test]
@@ -857,7 +2883,7 @@ mod tests {
 
         Tester::default()
             .run(r#"KILL "a/b.bas""#)
-            .expect_err("1:1: Too many / separators in path 'a/b.bas'")
+            .expect_err("1:1: Directory of 'a/b.bas' does not exist")
             .check();
 
         Tester::default()
@@ -884,7 +2910,7 @@ mod tests {
     fn test_mount_list() {
         let mut t = Tester::default();
         let other = InMemoryDrive::default();
-        t.get_storage().borrow_mut().attach("o", "origin://", Box::from(other)).unwrap();
+        t.get_storage().borrow_mut().attach("o", "origin://", Box::from(other), false).unwrap();
 
         let mut prints = vec![
             "",
@@ -923,10 +2949,13 @@ mod tests {
 
     #[test]
     fn test_mount_errors() {
-        check_stmt_compilation_err("1:1: MOUNT expected <> | <target$ AS drive_name$>", "MOUNT 1");
         check_stmt_compilation_err(
-            "1:1: MOUNT expected <> | <target$ AS drive_name$>",
-            "MOUNT 1, 2, 3",
+            "1:1: MOUNT expected <> | <target$ AS drive_name$> | <target$ AS drive_name$, mode$>",
+            "MOUNT 1",
+        );
+        check_stmt_compilation_err(
+            "1:1: MOUNT expected <> | <target$ AS drive_name$> | <target$ AS drive_name$, mode$>",
+            "MOUNT 1, 2, 3, 4",
         );
 
         check_stmt_compilation_err("1:14: expected STRING but found INTEGER", r#"MOUNT "a" AS 1"#);
@@ -938,6 +2967,88 @@ mod tests {
             r#"MOUNT "foo//bar" AS "a""#,
         );
         check_stmt_err("1:1: Unknown mount scheme 'foo'", r#"MOUNT "foo://bar" AS "a""#);
+
+        check_stmt_compilation_err(
+            "1:27: expected STRING but found INTEGER",
+            r#"MOUNT "memory://" AS "a", 1"#,
+        );
+        check_stmt_err(
+            "1:27: Invalid mount mode 'rx'; must be \"ro\" or \"rw\"",
+            r#"MOUNT "memory://" AS "a", "rx""#,
+        );
+    }
+
+    #[test]
+    fn test_mount_read_only_list_and_remount() {
+        let mut t = Tester::default();
+        t.run(r#"MOUNT "memory://" AS "x", "ro""#).check();
+        assert!(t.get_storage().borrow().is_read_only("x").unwrap());
+
+        t.run(r#"UNMOUNT "x": MOUNT "memory://" AS "x", "RW": MKDIR "x:/dir""#).check();
+        assert!(!t.get_storage().borrow().is_read_only("x").unwrap());
+
+        t.run(r#"UNMOUNT "x": MOUNT "memory://" AS "x": MKDIR "x:/dir""#).check();
+        assert!(!t.get_storage().borrow().is_read_only("x").unwrap());
+
+        t.run(r#"UNMOUNT "x": MOUNT "memory://" AS "x", "Ro": MOUNT"#)
+            .expect_prints([
+                "",
+                "    Name      Target",
+                "    MEMORY    memory://",
+                "    X         memory:// (read-only)",
+                "",
+                "    2 drive(s)",
+                "",
+            ])
+            .check();
+        assert!(t.get_storage().borrow().is_read_only("x").unwrap());
+    }
+
+    #[test]
+    fn test_mount_read_only_reads() {
+        let mut other = InMemoryDrive::default();
+        block_on(other.put("foo.bas", b"PRINT 1")).unwrap();
+
+        let mut t = Tester::default();
+        t.get_storage().borrow_mut().attach("other", "z://", Box::from(other), true).unwrap();
+
+        t.run(r#"DIR "other:/": LOAD "other:/foo.bas""#)
+            .expect_prints([
+                "",
+                "    Directory of OTHER:/ (read-only)",
+                "",
+                "    Modified              Size    Name",
+                "    2020-05-06 09:37         7    foo.bas",
+                "",
+                "    1 file(s), 7 bytes",
+                "",
+            ])
+            .expect_clear()
+            .expect_program(Some("OTHER:/foo.bas"), "PRINT 1")
+            .expect_file("OTHER:/foo.bas", "PRINT 1")
+            .check();
+    }
+
+    #[test]
+    fn test_mount_read_only_writes() {
+        for (cmd, err) in [
+            (r#"COPY "x:/foo.bas", "x:/bar.bas""#, "1:1: Drive is mounted read-only"),
+            (r#"KILL "x:/foo.bas""#, "1:1: Drive is mounted read-only"),
+            (r#"MKDIR "x:/dir""#, "1:1: Drive is mounted read-only"),
+            (r#"RENAME "x:/foo.bas", "x:/bar.bas""#, "1:1: Drive is mounted read-only"),
+            (r#"SAVE "x:/foo.bas""#, "1:1: Drive is mounted read-only"),
+        ] {
+            let mut drive = InMemoryDrive::default();
+            block_on(drive.put("foo.bas", b"original")).unwrap();
+
+            let mut t = Tester::default().set_program(None, "new");
+            t.get_storage().borrow_mut().attach("x", "z://", Box::from(drive), true).unwrap();
+            t.run(cmd)
+                .expect_program(None as Option<&str>, "new")
+                .expect_file("X:/foo.bas", "original")
+                .expect_err(err)
+                .check();
+        }
     }
 
     #[test]
@@ -964,7 +3075,7 @@ mod tests {
             let storage = t.get_storage();
             let storage = &mut *storage.borrow_mut();
             storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
-            storage.mount("other", &format!("file://{}", dir.display())).unwrap();
+            block_on(storage.mount("other", &format!("file://{}", dir.display()), false)).unwrap();
             storage.cd("other:/").unwrap();
         }
 
@@ -978,10 +3089,69 @@ mod tests {
             .check();
     }
 
+    #[test]
+    fn test_screendump_ok() {
+        let mut t = Tester::default();
+        t.get_console().borrow_mut().set_size_chars(CharsXY { x: 10, y: 5 });
+        t.run(
+            r#"PRINT "first": PRINT "": PRINT "long line wraps  "
+            SCREENDUMP "MEMORY:/screen.txt"
+            c$ = SCREENCHAR$(2, 2)"#,
+        )
+        .expect_prints(["first", "", "long line wraps  "])
+        .expect_file("MEMORY:/screen.txt", "first\n\nlong line\nwraps\n")
+        .expect_var("c", "n")
+        .check();
+    }
+
+    #[test]
+    fn test_screendump_colors() {
+        let mut t = Tester::default();
+        t.run(
+            r#"COLOR 1: PRINT "a";: COLOR 2, 3: PRINT "b": COLOR: PRINT "c": SCREENDUMP "s", TRUE"#,
+        )
+        .expect_output([
+            CapturedOut::SetColor(Some(1), None),
+            CapturedOut::Write("a".to_owned()),
+            CapturedOut::SetColor(Some(2), Some(3)),
+            CapturedOut::Print("b".to_owned()),
+            CapturedOut::SetColor(None, None),
+            CapturedOut::Print("c".to_owned()),
+        ])
+        .expect_file("MEMORY:/s", "\u{1b}[38;5;1;49ma\u{1b}[38;5;2;48;5;3mb\u{1b}[0m\nc\n")
+        .check();
+    }
+
+    #[test]
+    fn test_screendump_empty() {
+        Tester::default()
+            .run(r#"SCREENDUMP "empty.txt", FALSE"#)
+            .expect_file("MEMORY:/empty.txt", "")
+            .check();
+    }
+
+    #[test]
+    fn test_screendump_errors() {
+        check_stmt_compilation_err(
+            "1:1: SCREENDUMP expected <filename$> | <filename$, colors?>",
+            "SCREENDUMP",
+        );
+        check_stmt_compilation_err(
+            "1:1: SCREENDUMP expected <filename$> | <filename$, colors?>",
+            r#"SCREENDUMP "a", TRUE, 3"#,
+        );
+        check_stmt_compilation_err("1:12: expected STRING but found INTEGER", "SCREENDUMP 3");
+        check_stmt_compilation_err(
+            "1:17: expected BOOLEAN but found INTEGER",
+            r#"SCREENDUMP "a", 3"#,
+        );
+        check_stmt_err("1:1: Drive 'MISSING' is not mounted", r#"SCREENDUMP "missing:/a""#);
+    }
+
     #[test]
     fn test_unmount_ok() {
         let mut t = Tester::default();
-        t.get_storage().borrow_mut().mount("other", "memory://").unwrap();
+        block_on(t.get_storage().borrow_mut().mount("other", "memory://", false)).unwrap();
         t.get_storage().borrow_mut().cd("other:").unwrap();
         t.run("UNMOUNT \"memory\"").check();
 
@@ -0,0 +1,660 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Reading of comma-separated values files.
+
+use super::channels::CATEGORY;
+use crate::storage::Storage;
+use async_trait::async_trait;
+use endbasic_core::ast::{ArgSep, ExprType, Value, VarRef};
+use endbasic_core::compiler::{
+    ArgSepSyntax, RequiredRefSyntax, RequiredValueSyntax, SingularArgSyntax,
+};
+use endbasic_core::exec::{Clearable, Error, Machine, Result, Scope};
+use endbasic_core::syms::{
+    Array, Callable, CallableMetadata, CallableMetadataBuilder, Symbol, Symbols,
+};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::cmp;
+use std::convert::TryFrom;
+use std::fmt;
+use std::rc::Rc;
+
+/// Error while parsing CSV data.
+#[derive(Debug, PartialEq)]
+struct CsvError {
+    /// One-based number of the line where the error was detected.
+    line: usize,
+
+    /// Description of the error.
+    message: &'static str,
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} in line {}", self.message, self.line)
+    }
+}
+
+/// Parses `text` as CSV data as described in RFC 4180 and returns its records.
+///
+/// Records are separated by CRLF or LF line endings, and a line ending at the end of the text does
+/// not start a new record.  Fields may be enclosed in double quotes to hold commas, line endings
+/// and doubled quotes, which stand for a single quote.
+fn parse_csv(text: &str) -> std::result::Result<Vec<Vec<String>>, CsvError> {
+    let mut records = vec![];
+    let mut record = vec![];
+    let mut field = String::new();
+    let mut line = 1;
+    let mut quote_line = 0;
+    let mut in_quotes = false;
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            match ch {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                ch => {
+                    if ch == '\n' {
+                        line += 1;
+                    }
+                    field.push(ch);
+                }
+            }
+            continue;
+        }
+
+        match ch {
+            ',' => {
+                record.push(std::mem::take(&mut field));
+                quoted = false;
+            }
+            '\r' if chars.peek() == Some(&'\n') => (),
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                quoted = false;
+                line += 1;
+            }
+            _ if quoted => {
+                return Err(CsvError { line, message: "Unexpected character after closing quote" })
+            }
+            '"' if field.is_empty() => {
+                in_quotes = true;
+                quoted = true;
+                quote_line = line;
+            }
+            ch => field.push(ch),
+        }
+    }
+    if in_quotes {
+        return Err(CsvError { line: quote_line, message: "Unterminated quoted field" });
+    }
+    if !record.is_empty() || !field.is_empty() || quoted {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Number of fields in each row read by the last `CSVLOAD`.
+type RowWidths = Rc<RefCell<Vec<usize>>>;
+
+/// Forgets the rows read by the last `CSVLOAD` when the machine is cleared.
+pub struct CsvClearable {
+    widths: RowWidths,
+}
+
+impl CsvClearable {
+    /// Creates a new clearable for the row `widths` shared by the CSV callables.
+    pub fn new(widths: RowWidths) -> Box<Self> {
+        Box::from(Self { widths })
+    }
+}
+
+impl Clearable for CsvClearable {
+    fn reset_state(&self, _syms: &mut Symbols) {
+        self.widths.borrow_mut().clear();
+    }
+}
+
+/// The `CSVCOLS` function.
+pub struct CsvcolsFunction {
+    metadata: CallableMetadata,
+    widths: RowWidths,
+}
+
+impl CsvcolsFunction {
+    /// Creates a new `CSVCOLS` function that queries the row `widths` recorded by `CSVLOAD`.
+    pub fn new(widths: RowWidths) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CSVCOLS")
+                .with_return_type(ExprType::Integer)
+                .with_syntax(&[(
+                    &[SingularArgSyntax::RequiredValue(
+                        RequiredValueSyntax {
+                            name: Cow::Borrowed("row"),
+                            vtype: ExprType::Integer,
+                        },
+                        ArgSepSyntax::End,
+                    )],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Returns the number of fields in a row read by the last CSVLOAD.
+row% is the zero-based index of the row in the array loaded by CSVLOAD.  Rows with fewer fields \
+than the widest row are padded with empty strings in the array, and this function tells how \
+many of their fields were actually present in the file.",
+                )
+                .build(),
+            widths,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CsvcolsFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(1, scope.nargs());
+        let (row, pos) = scope.pop_integer_with_pos();
+
+        let width =
+            match usize::try_from(row).ok().and_then(|i| self.widths.borrow().get(i).copied()) {
+                Some(width) => width,
+                None => {
+                    return Err(Error::SyntaxError(
+                        pos,
+                        format!("Row {} was not read by the last CSVLOAD", row),
+                    ))
+                }
+            };
+        let width = i32::try_from(width).map_err(|_| scope.internal_error("Row too wide"))?;
+        scope.return_integer(width)
+    }
+}
+
+/// The `CSVFIELD` function.
+pub struct CsvfieldFunction {
+    metadata: CallableMetadata,
+}
+
+impl CsvfieldFunction {
+    /// Creates a new `CSVFIELD` function.
+    pub fn new() -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CSVFIELD")
+                .with_return_type(ExprType::Text)
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("line"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("n"),
+                                vtype: ExprType::Integer,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Extracts a field from a line of comma-separated values.
+line$ is parsed following the same rules as the files read by CSVLOAD, and n% indicates the \
+field to return and it is 1-indexed.  Returns an empty string if the line has fewer than n% \
+fields.
+This is useful to parse lines read with LINE INPUT#, but note that a quoted field that spans \
+multiple lines cannot be read this way.",
+                )
+                .build(),
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CsvfieldFunction {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (line, line_pos) = scope.pop_string_with_pos();
+        let (n, n_pos) = scope.pop_integer_with_pos();
+
+        if n < 1 {
+            return Err(Error::SyntaxError(n_pos, format!("Field number {} must be positive", n)));
+        }
+
+        let mut records = parse_csv(&line)
+            .map_err(|e| Error::SyntaxError(line_pos, format!("Invalid CSV line: {}", e)))?;
+        if records.len() > 1 {
+            return Err(Error::SyntaxError(
+                line_pos,
+                "Invalid CSV line: contains more than one record".to_owned(),
+            ));
+        }
+
+        let field = records
+            .pop()
+            .and_then(|mut record| {
+                let i = (n - 1) as usize;
+                if i < record.len() {
+                    Some(record.swap_remove(i))
+                } else {
+                    None
+                }
+            })
+            .unwrap_or_default();
+        scope.return_string(field)
+    }
+}
+
+/// The `CSVLOAD` command.
+pub struct CsvloadCommand {
+    metadata: CallableMetadata,
+    storage: Rc<RefCell<Storage>>,
+    widths: RowWidths,
+}
+
+impl CsvloadCommand {
+    /// Creates a new `CSVLOAD` command that loads files from `storage` and records the number of
+    /// fields of each row in `widths`.
+    pub fn new(storage: Rc<RefCell<Storage>>, widths: RowWidths) -> Rc<Self> {
+        Rc::from(Self {
+            metadata: CallableMetadataBuilder::new("CSVLOAD")
+                .with_syntax(&[(
+                    &[
+                        SingularArgSyntax::RequiredValue(
+                            RequiredValueSyntax {
+                                name: Cow::Borrowed("filename"),
+                                vtype: ExprType::Text,
+                            },
+                            ArgSepSyntax::Exactly(ArgSep::Long),
+                        ),
+                        SingularArgSyntax::RequiredRef(
+                            RequiredRefSyntax {
+                                name: Cow::Borrowed("target"),
+                                require_array: true,
+                                define_undefined: false,
+                            },
+                            ArgSepSyntax::End,
+                        ),
+                    ],
+                    None,
+                )])
+                .with_category(CATEGORY)
+                .with_description(
+                    "Loads a file of comma-separated values into an array.
+target must be a two-dimensional STRING array.  The array is resized to have as many rows as \
+records are in the file and as many columns as fields are in the widest record, so that \
+target(r, c) holds field c of record r, both zero-based.  Use UBOUND to know how many rows and \
+columns were loaded.  If the file is empty, the array is resized to a single element holding an \
+empty string.
+Records are separated by CRLF or LF line endings.  Fields may be enclosed in double quotes to \
+hold commas or line endings, and two double quotes in a row within a quoted field stand for a \
+single double quote.
+Records with fewer fields than the widest one are padded with empty strings.  Use CSVCOLS to \
+know how many fields each record really had.",
+                )
+                .build(),
+            storage,
+            widths,
+        })
+    }
+}
+
+#[async_trait(?Send)]
+impl Callable for CsvloadCommand {
+    fn metadata(&self) -> &CallableMetadata {
+        &self.metadata
+    }
+
+    async fn exec(&self, mut scope: Scope<'_>, machine: &mut Machine) -> Result<()> {
+        debug_assert_eq!(2, scope.nargs());
+        let (filename, filename_pos) = scope.pop_string_with_pos();
+        let (name, vtype, pos) = scope.pop_varref_with_pos();
+
+        if vtype != ExprType::Text {
+            return Err(Error::SyntaxError(
+                pos,
+                format!("Array {} must be of type STRING but is {}", name, vtype),
+            ));
+        }
+        let vref = VarRef::new(name.to_string(), Some(vtype));
+        let array = match machine
+            .get_mut_symbols()
+            .get_mut(&vref)
+            .map_err(|e| Error::SyntaxError(pos, format!("{}", e)))?
+        {
+            Some(Symbol::Array(array)) => array,
+            _ => unreachable!("Array existence guaranteed by the compiler"),
+        };
+        if array.dimensions().len() != 2 {
+            return Err(Error::SyntaxError(
+                pos,
+                format!(
+                    "Array {} must have 2 dimensions but has {}",
+                    vref.name(),
+                    array.dimensions().len()
+                ),
+            ));
+        }
+
+        let content = self.storage.borrow().get(&filename).await.map_err(|e| scope.io_error(e))?;
+        let text = String::from_utf8(content).map_err(|_| {
+            Error::SyntaxError(
+                filename_pos,
+                format!("Invalid CSV file {}: not valid UTF-8 text", filename),
+            )
+        })?;
+        let records = parse_csv(&text).map_err(|e| {
+            Error::SyntaxError(filename_pos, format!("Invalid CSV file {}: {}", filename, e))
+        })?;
+
+        let widths = records.iter().map(Vec::len).collect::<Vec<usize>>();
+        let ncols = widths.iter().copied().max().unwrap_or(1);
+        let mut loaded = Array::new(ExprType::Text, vec![cmp::max(1, records.len()), ncols]);
+        for (i, record) in records.into_iter().enumerate() {
+            let i = i32::try_from(i).map_err(|_| scope.internal_error("File too large"))?;
+            for (j, field) in record.into_iter().enumerate() {
+                let j = i32::try_from(j).map_err(|_| scope.internal_error("Record too large"))?;
+                loaded
+                    .assign(&[i, j], Value::Text(field))
+                    .map_err(|e| scope.internal_error(format!("{}", e)))?;
+            }
+        }
+        machine
+            .get_mut_symbols()
+            .replace_array(&vref, loaded)
+            .map_err(|e| Error::EvalError(pos, format!("{}", e)))?;
+        *self.widths.borrow_mut() = widths;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::*;
+
+    /// Syntactic sugar to build the records expected from `parse_csv`.
+    fn records(records: &[&[&str]]) -> Vec<Vec<String>> {
+        records.iter().map(|r| r.iter().map(|f| (*f).to_owned()).collect()).collect()
+    }
+
+    #[test]
+    fn test_parse_csv_ok() {
+        assert_eq!(records(&[]), parse_csv("").unwrap());
+        assert_eq!(records(&[&["a", "b", "c"]]), parse_csv("a,b,c").unwrap());
+        assert_eq!(records(&[&["a", "b"], &["c", "d"]]), parse_csv("a,b\nc,d\n").unwrap());
+        assert_eq!(records(&[&["a", "b"], &["c", "d"]]), parse_csv("a,b\r\nc,d\r\n").unwrap());
+        assert_eq!(records(&[&["a"], &[""], &["b"]]), parse_csv("a\n\nb").unwrap());
+        assert_eq!(records(&[&["a", "", ""]]), parse_csv("a,,\n").unwrap());
+        assert_eq!(records(&[&["", ""]]), parse_csv(",").unwrap());
+        assert_eq!(records(&[&[" a ", "b\rc"]]), parse_csv(" a ,b\rc").unwrap());
+    }
+
+    #[test]
+    fn test_parse_csv_quotes() {
+        assert_eq!(records(&[&["a,b", "c"]]), parse_csv("\"a,b\",c").unwrap());
+        assert_eq!(records(&[&["say \"hi\""]]), parse_csv("\"say \"\"hi\"\"\"").unwrap());
+        assert_eq!(records(&[&["", "x"]]), parse_csv("\"\",x\n").unwrap());
+        assert_eq!(records(&[&["a\r\nb", "c"], &["d"]]), parse_csv("\"a\r\nb\",c\r\nd").unwrap());
+        assert_eq!(records(&[&["a\"b"]]), parse_csv("a\"b").unwrap());
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        assert_eq!(
+            CsvError { line: 2, message: "Unterminated quoted field" },
+            parse_csv("a\n\"b\nc").unwrap_err()
+        );
+        assert_eq!(
+            CsvError { line: 3, message: "Unexpected character after closing quote" },
+            parse_csv("a\n\"b\nc\"d").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_csvload_ok() {
+        let content = "name,comment\r\n\"Smith, J.\",\"said \"\"hi\"\"\"\r\n\"multi\nline\",\r\n";
+        Tester::default()
+            .write_file("data.csv", content)
+            .run("DIM a(1, 1) AS STRING: CSVLOAD \"data.csv\", a: r = UBOUND(a, 1): c = UBOUND(a, 2)")
+            .expect_array(
+                "a",
+                ExprType::Text,
+                &[3, 2],
+                vec![
+                    (&[0, 0], "name".into()),
+                    (&[0, 1], "comment".into()),
+                    (&[1, 0], "Smith, J.".into()),
+                    (&[1, 1], "said \"hi\"".into()),
+                    (&[2, 0], "multi\nline".into()),
+                ],
+            )
+            .expect_var("r", 2)
+            .expect_var("c", 1)
+            .expect_file("MEMORY:/data.csv", content)
+            .check();
+    }
+
+    #[test]
+    fn test_csvload_accounts_memory() {
+        let content = "a,b\n".repeat(10);
+        let mut t = Tester::default().write_file("data.csv", &content);
+        t.run("SUB s: DIM a(1, 1) AS STRING: CSVLOAD \"data.csv\", a: END SUB: s")
+            .expect_file("MEMORY:/data.csv", &content)
+            .check();
+        assert_eq!(0, t.get_machine().get_symbols().allocated());
+
+        t.get_machine().get_mut_symbols().set_memory_limit(Some(100));
+        t.run("DIM a(1, 1) AS STRING: CSVLOAD \"data.csv\", a")
+            .expect_array("a", ExprType::Text, &[1, 1], vec![])
+            .expect_file("MEMORY:/data.csv", &content)
+            .expect_err("1:44: Out of memory: limit is 100 bytes")
+            .check();
+    }
+
+    #[test]
+    fn test_csvload_ragged() {
+        let content = "a,b,c\nd\n\ne,f\n";
+        let mut t = Tester::default().write_file("ragged.csv", content);
+        t.run("DIM a(1, 1) AS STRING: CSVLOAD \"ragged.csv\", a")
+            .expect_array(
+                "a",
+                ExprType::Text,
+                &[4, 3],
+                vec![
+                    (&[0, 0], "a".into()),
+                    (&[0, 1], "b".into()),
+                    (&[0, 2], "c".into()),
+                    (&[1, 0], "d".into()),
+                    (&[3, 0], "e".into()),
+                    (&[3, 1], "f".into()),
+                ],
+            )
+            .expect_file("MEMORY:/ragged.csv", content)
+            .check();
+        t.run("FOR i = 0 TO 3: PRINT CSVCOLS(i): NEXT")
+            .expect_prints([" 3", " 1", " 1", " 2"])
+            .expect_var("i", 4)
+            .expect_array(
+                "a",
+                ExprType::Text,
+                &[4, 3],
+                vec![
+                    (&[0, 0], "a".into()),
+                    (&[0, 1], "b".into()),
+                    (&[0, 2], "c".into()),
+                    (&[1, 0], "d".into()),
+                    (&[3, 0], "e".into()),
+                    (&[3, 1], "f".into()),
+                ],
+            )
+            .expect_file("MEMORY:/ragged.csv", content)
+            .check();
+    }
+
+    #[test]
+    fn test_csvload_empty() {
+        Tester::default()
+            .write_file("empty.csv", "")
+            .run("DIM a(3, 3) AS STRING: a(0, 0) = \"x\": CSVLOAD \"empty.csv\", a")
+            .expect_array("a", ExprType::Text, &[1, 1], vec![])
+            .expect_file("MEMORY:/empty.csv", "")
+            .check();
+    }
+
+    #[test]
+    fn test_csvload_large() {
+        let mut content = String::new();
+        let mut subscripts = vec![];
+        let mut values = vec![];
+        for i in 0..2000 {
+            content.push_str(&format!("{},\"row {}\",{}\n", i, i, i * 2));
+            subscripts.extend([[i, 0], [i, 1], [i, 2]]);
+            values.extend([i.to_string(), format!("row {}", i), (i * 2).to_string()]);
+        }
+        let contents = subscripts
+            .iter()
+            .zip(values)
+            .map(|(s, v)| (&s[..], Value::Text(v)))
+            .collect::<Vec<(&[i32], Value)>>();
+
+        Tester::default()
+            .write_file("large.csv", &content)
+            .run(
+                "DIM a(1, 1) AS STRING: CSVLOAD \"large.csv\", a
+r = UBOUND(a, 1): c = UBOUND(a, 2): x$ = a(1999, 1): n = CSVCOLS(1999)",
+            )
+            .expect_array("a", ExprType::Text, &[2000, 3], contents)
+            .expect_var("r", 1999)
+            .expect_var("c", 2)
+            .expect_var("x", "row 1999")
+            .expect_var("n", 3)
+            .expect_file("MEMORY:/large.csv", &content)
+            .check();
+    }
+
+    #[test]
+    fn test_csvload_errors() {
+        check_stmt_compilation_err("1:1: CSVLOAD expected filename$, target", "CSVLOAD \"x.csv\"");
+        check_stmt_compilation_err(
+            "1:38: Requires a reference, not a value",
+            "DIM a(3) AS STRING: CSVLOAD \"x.csv\", 3",
+        );
+
+        Tester::default()
+            .run("DIM a(3, 3): CSVLOAD \"x.csv\", a")
+            .expect_array("a", ExprType::Integer, &[3, 3], vec![])
+            .expect_err("1:31: Array A must be of type STRING but is INTEGER")
+            .check();
+        Tester::default()
+            .run("DIM a(3) AS STRING: CSVLOAD \"x.csv\", a")
+            .expect_array("a", ExprType::Text, &[3], vec![])
+            .expect_err("1:38: Array A must have 2 dimensions but has 1")
+            .check();
+        Tester::default()
+            .run("DIM a(2, 2) AS STRING: CSVLOAD \"missing.csv\", a")
+            .expect_array("a", ExprType::Text, &[2, 2], vec![])
+            .expect_err("1:24: Entry not found")
+            .check();
+        Tester::default()
+            .write_file("bad.csv", "a,b\n\"c,d\n")
+            .run("DIM a(2, 2) AS STRING: CSVLOAD \"bad.csv\", a")
+            .expect_array("a", ExprType::Text, &[2, 2], vec![])
+            .expect_err("1:32: Invalid CSV file bad.csv: Unterminated quoted field in line 2")
+            .expect_file("MEMORY:/bad.csv", "a,b\n\"c,d\n")
+            .check();
+        Tester::default()
+            .write_file_bytes("bin.csv", &[0xff, 0xfe])
+            .run("DIM a(2, 2) AS STRING: CSVLOAD \"bin.csv\", a")
+            .expect_array("a", ExprType::Text, &[2, 2], vec![])
+            .expect_err("1:32: Invalid CSV file bin.csv: not valid UTF-8 text")
+            .expect_file_bytes("MEMORY:/bin.csv", &[0xff, 0xfe])
+            .check();
+    }
+
+    #[test]
+    fn test_csvcols_errors() {
+        check_expr_error("1:18: Row 0 was not read by the last CSVLOAD", "CSVCOLS(0)");
+        check_expr_error("1:18: Row -1 was not read by the last CSVLOAD", "CSVCOLS(-1)");
+        check_expr_compilation_error("1:10: CSVCOLS expected row%", "CSVCOLS()");
+
+        let mut t = Tester::default().write_file("one.csv", "a,b\n");
+        t.run("DIM a(1, 1) AS STRING: CSVLOAD \"one.csv\", a: n = CSVCOLS(0)")
+            .expect_var("n", 2)
+            .expect_array(
+                "a",
+                ExprType::Text,
+                &[1, 2],
+                vec![(&[0, 0], "a".into()), (&[0, 1], "b".into())],
+            )
+            .expect_file("MEMORY:/one.csv", "a,b\n")
+            .check();
+        t.get_machine().clear();
+        t.run("n = CSVCOLS(0)")
+            .expect_clear()
+            .expect_err("1:13: Row 0 was not read by the last CSVLOAD")
+            .expect_file("MEMORY:/one.csv", "a,b\n")
+            .check();
+    }
+
+    #[test]
+    fn test_csvfield_ok() {
+        check_expr_ok("a", r#"CSVFIELD("a,b,c", 1)"#);
+        check_expr_ok("c", r#"CSVFIELD("a,b,c", 3)"#);
+        check_expr_ok("", r#"CSVFIELD("a,b,c", 4)"#);
+        check_expr_ok("", r#"CSVFIELD("a,,c", 2)"#);
+        check_expr_ok("", r#"CSVFIELD("a,b,", 3)"#);
+        check_expr_ok("", r#"CSVFIELD("", 1)"#);
+        check_expr_ok("x, y", r#"CSVFIELD("1,\"x, y\",2", 2)"#);
+        check_expr_ok("say \"hi\"", r#"CSVFIELD("\"say \"\"hi\"\"\",2", 1)"#);
+        check_expr_ok("2", r#"CSVFIELD("1,2" + CHR(13) + CHR(10), 2)"#);
+    }
+
+    #[test]
+    fn test_csvfield_errors() {
+        check_expr_compilation_error("1:10: CSVFIELD expected line$, n%", r#"CSVFIELD("a")"#);
+        check_expr_error("1:24: Field number 0 must be positive", r#"CSVFIELD("a", 0)"#);
+        check_expr_error(
+            "1:19: Invalid CSV line: Unterminated quoted field in line 1",
+            r#"CSVFIELD("\"a", 1)"#,
+        );
+        check_expr_error(
+            "1:33: Invalid CSV line: contains more than one record",
+            r#"CSVFIELD("a" + CHR(10) + "b", 1)"#,
+        );
+    }
+}
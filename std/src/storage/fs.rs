@@ -15,7 +15,7 @@
 
 //! File system-based implementation of the storage system.
 
-use crate::storage::{Drive, DriveFactory, DriveFiles, Metadata};
+use crate::storage::{Drive, DriveFactory, DriveFiles, Metadata, Storage};
 use async_trait::async_trait;
 use std::collections::BTreeMap;
 use std::fs::{self, File, OpenOptions};
@@ -59,14 +59,20 @@ impl Drive for DirectoryDrive {
     }
 
     async fn enumerate(&self) -> io::Result<DriveFiles> {
+        self.enumerate_dir("").await
+    }
+
+    async fn enumerate_dir(&self, dir: &str) -> io::Result<DriveFiles> {
         let mut entries = BTreeMap::default();
-        match fs::read_dir(&self.dir) {
+        let mut subdirs = BTreeMap::default();
+        match fs::read_dir(self.dir.join(dir)) {
             Ok(dirents) => {
                 for de in dirents {
                     let de = de?;
 
                     let file_type = de.file_type()?;
-                    if !file_type.is_file() && !file_type.is_symlink() {
+                    let is_dir = file_type.is_dir();
+                    if !file_type.is_file() && !file_type.is_symlink() && !is_dir {
                         // Silently ignore entries we cannot handle.
                         continue;
                     }
@@ -82,20 +88,22 @@ impl Drive for DirectoryDrive {
                     let date = time::OffsetDateTime::from(metadata.modified()?).to_offset(offset);
                     let length = metadata.len();
 
-                    entries.insert(
-                        de.file_name().to_string_lossy().to_string(),
-                        Metadata { date, length },
-                    );
+                    let name = de.file_name().to_string_lossy().to_string();
+                    if is_dir {
+                        subdirs.insert(name, Metadata { date, length: 0 });
+                    } else {
+                        entries.insert(name, Metadata { date, length });
+                    }
                 }
             }
             Err(e) => {
-                if e.kind() != io::ErrorKind::NotFound {
+                if e.kind() != io::ErrorKind::NotFound || !dir.is_empty() {
                     return Err(e);
                 }
             }
         }
         // TODO(jmmv): Calculate total and free disk space.
-        Ok(DriveFiles::new(entries, None, None))
+        Ok(DriveFiles::new(entries, None, None).with_subdirs(subdirs))
     }
 
     async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
@@ -106,6 +114,14 @@ impl Drive for DirectoryDrive {
         Ok(content)
     }
 
+    fn is_dir(&self, name: &str) -> bool {
+        self.dir.join(name).is_dir()
+    }
+
+    async fn mkdir(&mut self, name: &str) -> io::Result<()> {
+        fs::create_dir(self.dir.join(name))
+    }
+
     async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
         let path = self.dir.join(name);
         let mut output = OpenOptions::new().create(true).write(true).truncate(true).open(path)?;
@@ -113,6 +129,23 @@ impl Drive for DirectoryDrive {
         output.sync_all()
     }
 
+    async fn rename(&mut self, old: &str, new: &str) -> io::Result<()> {
+        let old = self.dir.join(old);
+        if fs::metadata(&old)?.is_dir() {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Entry is a directory"));
+        }
+        fs::rename(old, self.dir.join(new))
+    }
+
+    async fn rmdir(&mut self, name: &str) -> io::Result<()> {
+        let path = self.dir.join(name);
+        // Check for emptiness ourselves to return a consistent error across platforms.
+        if fs::read_dir(&path)?.next().is_some() {
+            return Err(io::Error::new(io::ErrorKind::Other, "Directory not empty"));
+        }
+        fs::remove_dir(path)
+    }
+
     fn system_path(&self, name: &str) -> Option<PathBuf> {
         Some(self.dir.join(name))
     }
@@ -122,8 +155,9 @@ impl Drive for DirectoryDrive {
 #[derive(Default)]
 pub struct DirectoryDriveFactory {}
 
+#[async_trait(?Send)]
 impl DriveFactory for DirectoryDriveFactory {
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
         if !target.is_empty() {
             Ok(Box::from(DirectoryDrive::new(target)?))
         } else {
@@ -220,11 +254,63 @@ mod tests {
     }
 
     #[test]
-    fn test_directorydrive_enumerate_ignores_non_files() {
+    fn test_directorydrive_enumerate_reports_dirs_separately() {
         let dir = tempfile::tempdir().unwrap();
-        fs::create_dir(dir.path().join("will-be-ignored")).unwrap();
+        fs::create_dir(dir.path().join("subdir")).unwrap();
         let drive = DirectoryDrive::new(dir.path()).unwrap();
-        assert!(block_on(drive.enumerate()).unwrap().dirents().is_empty());
+        let files = block_on(drive.enumerate()).unwrap();
+        assert!(files.dirents().is_empty());
+        assert_eq!(["subdir"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+    }
+
+    #[test]
+    fn test_directorydrive_nested_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut drive = DirectoryDrive::new(dir.path()).unwrap();
+        block_on(drive.mkdir("games")).unwrap();
+        block_on(drive.mkdir("games/arcade")).unwrap();
+        assert!(dir.path().join("games/arcade").is_dir());
+        assert!(drive.is_dir(""));
+        assert!(drive.is_dir("games/arcade"));
+        assert!(!drive.is_dir("missing"));
+
+        block_on(drive.put("games/arcade/pong.bas", b"pong\n")).unwrap();
+        check_file(&dir.path().join("games/arcade/pong.bas"), &["pong"]);
+        assert!(!drive.is_dir("games/arcade/pong.bas"));
+        assert_eq!(b"pong\n", block_on(drive.get("games/arcade/pong.bas")).unwrap().as_slice());
+
+        let files = block_on(drive.enumerate_dir("games")).unwrap();
+        assert!(files.dirents().is_empty());
+        assert_eq!(["arcade"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+        let files = block_on(drive.enumerate_dir("games/arcade")).unwrap();
+        assert_eq!(["pong.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+
+        block_on(drive.delete("games/arcade/pong.bas")).unwrap();
+        block_on(drive.rmdir("games/arcade")).unwrap();
+        assert!(!dir.path().join("games/arcade").exists());
+    }
+
+    #[test]
+    fn test_directorydrive_dir_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(&dir.path().join("file"), &[]);
+        fs::create_dir(dir.path().join("full")).unwrap();
+        write_file(&dir.path().join("full/file"), &[]);
+
+        let mut drive = DirectoryDrive::new(dir.path()).unwrap();
+        assert_eq!(io::ErrorKind::AlreadyExists, block_on(drive.mkdir("full")).unwrap_err().kind());
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            block_on(drive.mkdir("missing/sub")).unwrap_err().kind()
+        );
+        assert_eq!(
+            "Directory not empty",
+            format!("{}", block_on(drive.rmdir("full")).unwrap_err())
+        );
+        assert_eq!(io::ErrorKind::NotFound, block_on(drive.rmdir("missing")).unwrap_err().kind());
+        block_on(drive.enumerate_dir("missing")).unwrap_err();
+        assert!(dir.path().join("full/file").exists());
     }
 
     #[cfg(not(target_os = "windows"))]
@@ -281,6 +367,63 @@ mod tests {
         check_file(&dir.path().join("some file.bas"), &["a b c", "d e"]);
     }
 
+    #[test]
+    fn test_directorydrive_rename() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(&dir.path().join("a.bas"), &["content"]);
+        fs::create_dir(dir.path().join("sub")).unwrap();
+
+        let mut drive = DirectoryDrive::new(dir.path()).unwrap();
+        block_on(drive.rename("a.bas", "sub/b.bas")).unwrap();
+        assert!(!dir.path().join("a.bas").exists());
+        check_file(&dir.path().join("sub/b.bas"), &["content"]);
+
+        let err = block_on(drive.rename("a.bas", "c.bas")).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        let err = block_on(drive.rename("sub", "c.bas")).unwrap_err();
+        assert_eq!("Entry is a directory", format!("{}", err));
+        assert!(dir.path().join("sub").is_dir());
+    }
+
+    #[test]
+    fn test_directorydrive_names_are_case_insensitive() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut storage = Storage::default();
+        storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
+        block_on(storage.mount("disk", &format!("file://{}", dir.path().display()), false))
+            .unwrap();
+
+        block_on(storage.put("disk:/game.bas", b"first")).unwrap();
+        assert_eq!(b"first", block_on(storage.get("DISK:/GAME.BAS")).unwrap().as_slice());
+        block_on(storage.put("disk:/GAME.BAS", b"second")).unwrap();
+        check_file(&dir.path().join("game.bas"), &["second"]);
+        assert_eq!(1, fs::read_dir(dir.path()).unwrap().count());
+
+        block_on(storage.delete("disk:/Game.Bas")).unwrap();
+        assert_eq!(0, fs::read_dir(dir.path()).unwrap().count());
+        assert!(storage.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_directorydrive_names_with_case_conflicts() {
+        let dir = tempfile::tempdir().unwrap();
+        write_file(&dir.path().join("GAME.BAS"), &["upper"]);
+        write_file(&dir.path().join("game.bas"), &["lower"]);
+        if fs::read_dir(dir.path()).unwrap().count() != 2 {
+            // The host file system is case-insensitive so there can be no conflicts.
+            return;
+        }
+
+        let mut storage = Storage::default();
+        storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
+        block_on(storage.mount("disk", &format!("file://{}", dir.path().display()), false))
+            .unwrap();
+
+        assert_eq!(b"upper\n", block_on(storage.get("disk:/Game.bas")).unwrap().as_slice());
+        assert_eq!(1, storage.take_warnings().len());
+    }
+
     #[test]
     fn test_directorydrive_system_path() {
         let dir = tempfile::tempdir().unwrap();
@@ -15,16 +15,58 @@
 
 //! In-memory implementation of the storage system.
 
-use crate::storage::{DiskSpace, Drive, DriveFactory, DriveFiles, FileAcls, Metadata};
+use crate::storage::{DiskSpace, Drive, DriveFactory, DriveFiles, FileAcls, Metadata, Storage};
 use async_trait::async_trait;
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
 use std::str;
 
+/// ACLs of a file in an in-memory drive.
+#[derive(Default)]
+struct Acls {
+    /// Principals that are allowed to read the file.
+    readers: HashSet<String>,
+
+    /// Principals that are allowed to modify the file.
+    writers: HashSet<String>,
+}
+
+/// Contents of a directory in an in-memory drive.
+#[derive(Default)]
+struct Directory {
+    /// Files in this directory along with their ACLs.
+    programs: HashMap<String, (Vec<u8>, Acls)>,
+
+    /// Subdirectories of this directory.
+    subdirs: HashMap<String, Directory>,
+}
+
+impl Directory {
+    /// Returns true if this directory has no entries.
+    fn is_empty(&self) -> bool {
+        self.programs.is_empty() && self.subdirs.is_empty()
+    }
+
+    /// Returns true if `name` is an entry of this directory, be it a file or a subdirectory.
+    fn contains(&self, name: &str) -> bool {
+        self.programs.contains_key(name) || self.subdirs.contains_key(name)
+    }
+}
+
+/// Splits an entry `name` into the path of its parent directory and its leaf name.
+fn split_name(name: &str) -> (&str, &str) {
+    name.rsplit_once('/').unwrap_or(("", name))
+}
+
+/// Constructs the error returned when a directory does not exist.
+fn dir_not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "Directory not found")
+}
+
 /// A drive that records all data in memory only.
 #[derive(Default)]
 pub struct InMemoryDrive {
-    programs: HashMap<String, (Vec<u8>, HashSet<String>)>,
+    root: Directory,
 
     // TODO(jmmv): These fields are currently exposed only to allow testing for the consumers of
     // these details and are not enforced in the drive.  It might be nice to actually implement
@@ -33,69 +75,182 @@ pub struct InMemoryDrive {
     pub(crate) fake_disk_free: Option<DiskSpace>,
 }
 
+impl InMemoryDrive {
+    /// Returns the directory at path `name`, if it exists.
+    fn dir(&self, name: &str) -> Option<&Directory> {
+        let mut dir = &self.root;
+        if !name.is_empty() {
+            for component in name.split('/') {
+                dir = dir.subdirs.get(component)?;
+            }
+        }
+        Some(dir)
+    }
+
+    /// Returns the directory at path `name` for modification, if it exists.
+    fn dir_mut(&mut self, name: &str) -> Option<&mut Directory> {
+        let mut dir = &mut self.root;
+        if !name.is_empty() {
+            for component in name.split('/') {
+                dir = dir.subdirs.get_mut(component)?;
+            }
+        }
+        Some(dir)
+    }
+
+    /// Returns the file `name` and its ACLs, if it exists.
+    fn program(&self, name: &str) -> Option<&(Vec<u8>, Acls)> {
+        let (parent, leaf) = split_name(name);
+        self.dir(parent)?.programs.get(leaf)
+    }
+
+    /// Returns the file `name` and its ACLs for modification, if it exists.
+    fn program_mut(&mut self, name: &str) -> Option<&mut (Vec<u8>, Acls)> {
+        let (parent, leaf) = split_name(name);
+        self.dir_mut(parent)?.programs.get_mut(leaf)
+    }
+}
+
 #[async_trait(?Send)]
 impl Drive for InMemoryDrive {
     async fn delete(&mut self, name: &str) -> io::Result<()> {
-        match self.programs.remove(name) {
+        let (parent, leaf) = split_name(name);
+        match self.dir_mut(parent).and_then(|dir| dir.programs.remove(leaf)) {
             Some(_) => Ok(()),
             None => Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
         }
     }
 
     async fn enumerate(&self) -> io::Result<DriveFiles> {
+        self.enumerate_dir("").await
+    }
+
+    async fn enumerate_dir(&self, dir: &str) -> io::Result<DriveFiles> {
         let date = time::OffsetDateTime::from_unix_timestamp(1_588_757_875).unwrap();
 
+        let dir = self.dir(dir).ok_or_else(dir_not_found)?;
         let mut entries = BTreeMap::new();
-        for (name, (contents, _readers)) in &self.programs {
+        for (name, (contents, _acls)) in &dir.programs {
             entries.insert(name.clone(), Metadata { date, length: contents.len() as u64 });
         }
-        Ok(DriveFiles::new(entries, self.fake_disk_quota, self.fake_disk_free))
+        let mut subdirs = BTreeMap::new();
+        for name in dir.subdirs.keys() {
+            subdirs.insert(name.clone(), Metadata { date, length: 0 });
+        }
+        Ok(DriveFiles::new(entries, self.fake_disk_quota, self.fake_disk_free)
+            .with_subdirs(subdirs))
     }
 
     async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
-        match self.programs.get(name) {
-            Some((content, _readers)) => Ok(content.to_owned()),
+        match self.program(name) {
+            Some((content, _acls)) => Ok(content.to_owned()),
             None => Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
         }
     }
 
     async fn get_acls(&self, name: &str) -> io::Result<FileAcls> {
-        match self.programs.get(name) {
-            Some((_content, readers)) => {
-                let mut readers = readers.iter().map(String::to_owned).collect::<Vec<String>>();
+        match self.program(name) {
+            Some((_content, acls)) => {
+                let mut readers =
+                    acls.readers.iter().map(String::to_owned).collect::<Vec<String>>();
+                let mut writers =
+                    acls.writers.iter().map(String::to_owned).collect::<Vec<String>>();
                 // There is no need to sort the returned ACLs, but doing so simplifies testing...
                 // and this in-memory drive exists mostly for testing only.
                 readers.sort();
-                Ok(FileAcls::default().with_readers(readers))
+                writers.sort();
+                Ok(FileAcls::default().with_readers(readers).with_writers(writers))
             }
             None => Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
         }
     }
 
+    fn is_dir(&self, name: &str) -> bool {
+        self.dir(name).is_some()
+    }
+
+    async fn mkdir(&mut self, name: &str) -> io::Result<()> {
+        let (parent, leaf) = split_name(name);
+        let dir = self.dir_mut(parent).ok_or_else(dir_not_found)?;
+        if dir.contains(leaf) {
+            return Err(io::Error::new(io::ErrorKind::AlreadyExists, "Entry already exists"));
+        }
+        dir.subdirs.insert(leaf.to_owned(), Directory::default());
+        Ok(())
+    }
+
     async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
-        if let Some((prev_content, _readers)) = self.programs.get_mut(name) {
+        let (parent, leaf) = split_name(name);
+        let dir = self.dir_mut(parent).ok_or_else(dir_not_found)?;
+        if dir.subdirs.contains_key(leaf) {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Entry is a directory"));
+        }
+        if let Some((prev_content, _acls)) = dir.programs.get_mut(leaf) {
             content.clone_into(prev_content);
             return Ok(());
         };
-        self.programs.insert(name.to_owned(), (content.to_owned(), HashSet::new()));
+        dir.programs.insert(leaf.to_owned(), (content.to_owned(), Acls::default()));
+        Ok(())
+    }
+
+    async fn rename(&mut self, old: &str, new: &str) -> io::Result<()> {
+        let (new_parent, new_leaf) = split_name(new);
+        match self.dir(new_parent) {
+            Some(dir) if dir.subdirs.contains_key(new_leaf) => {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "Entry is a directory"));
+            }
+            Some(_) => (),
+            None => return Err(dir_not_found()),
+        }
+
+        let (old_parent, old_leaf) = split_name(old);
+        let entry = match self.dir_mut(old_parent).and_then(|dir| dir.programs.remove(old_leaf)) {
+            Some(entry) => entry,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
+        };
+        self.dir_mut(new_parent)
+            .expect("Target directory checked above")
+            .programs
+            .insert(new_leaf.to_owned(), entry);
         Ok(())
     }
 
+    async fn rmdir(&mut self, name: &str) -> io::Result<()> {
+        let (parent, leaf) = split_name(name);
+        let dir = self.dir_mut(parent).ok_or_else(dir_not_found)?;
+        match dir.subdirs.get(leaf) {
+            Some(subdir) if !subdir.is_empty() => {
+                Err(io::Error::new(io::ErrorKind::Other, "Directory not empty"))
+            }
+            Some(_) => {
+                dir.subdirs.remove(leaf);
+                Ok(())
+            }
+            None => Err(dir_not_found()),
+        }
+    }
+
     async fn update_acls(
         &mut self,
         name: &str,
         add: &FileAcls,
         remove: &FileAcls,
     ) -> io::Result<()> {
-        let readers = match self.programs.get_mut(name) {
-            Some((_content, readers)) => readers,
+        let acls = match self.program_mut(name) {
+            Some((_content, acls)) => acls,
             None => return Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
         };
         for reader in remove.readers() {
-            readers.remove(reader);
+            acls.readers.remove(reader);
         }
         for reader in add.readers() {
-            readers.insert(reader.to_owned());
+            acls.readers.insert(reader.to_owned());
+        }
+        for writer in remove.writers() {
+            acls.writers.remove(writer);
+        }
+        for writer in add.writers() {
+            acls.writers.insert(writer.to_owned());
         }
         Ok(())
     }
@@ -105,8 +260,9 @@ impl Drive for InMemoryDrive {
 #[derive(Default)]
 pub struct InMemoryDriveFactory {}
 
+#[async_trait(?Send)]
 impl DriveFactory for InMemoryDriveFactory {
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
         if target.is_empty() {
             Ok(Box::from(InMemoryDrive::default()))
         } else {
@@ -127,6 +283,11 @@ mod tests {
         FileAcls::default().with_readers(r.iter().map(|x| x.to_string()).collect::<Vec<String>>())
     }
 
+    /// Convenience function to instantiate a `FileAcls` with the `w` writers.
+    fn writers(w: &[&str]) -> FileAcls {
+        FileAcls::default().with_writers(w.iter().map(|x| x.to_string()).collect::<Vec<String>>())
+    }
+
     #[tokio::test]
     async fn test_inmemorydrive_put_respects_acls() {
         let mut drive = InMemoryDrive::default();
@@ -168,6 +329,108 @@ mod tests {
         assert_eq!(FileAcls::default(), drive.get_acls("untouched").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_inmemorydrive_get_update_writer_acls() {
+        let mut drive = InMemoryDrive::default();
+        drive.put("file", b"some content").await.unwrap();
+        drive.update_acls("file", &readers(&["r1"]), &FileAcls::default()).await.unwrap();
+
+        // Add some new writers and try to remove a non-existing one.
+        drive.update_acls("file", &writers(&["w1", "w2"]), &writers(&["w3"])).await.unwrap();
+        assert_eq!(
+            readers(&["r1"]).with_writers(["w1".to_owned(), "w2".to_owned()]),
+            drive.get_acls("file").await.unwrap()
+        );
+
+        // Remove a writer that is also a reader and make sure the reader stays.
+        drive.update_acls("file", &FileAcls::default(), &writers(&["w1", "r1"])).await.unwrap();
+        assert_eq!(
+            readers(&["r1"]).with_writers(["w2".to_owned()]),
+            drive.get_acls("file").await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_inmemorydrive_nested_dirs() {
+        let mut drive = InMemoryDrive::default();
+        drive.mkdir("games").await.unwrap();
+        drive.mkdir("games/arcade").await.unwrap();
+        drive.put("games/arcade/pong.bas", b"pong").await.unwrap();
+        drive.put("games/chess.bas", b"chess").await.unwrap();
+        drive.put("top.bas", b"top").await.unwrap();
+
+        assert!(drive.is_dir(""));
+        assert!(drive.is_dir("games"));
+        assert!(drive.is_dir("games/arcade"));
+        assert!(!drive.is_dir("games/chess.bas"));
+        assert!(!drive.is_dir("missing"));
+
+        let files = drive.enumerate().await.unwrap();
+        assert_eq!(["top.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+        assert_eq!(["games"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+
+        let files = drive.enumerate_dir("games").await.unwrap();
+        assert_eq!(["chess.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+        assert_eq!(["arcade"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+
+        assert_eq!(b"pong", drive.get("games/arcade/pong.bas").await.unwrap().as_slice());
+        drive.delete("games/arcade/pong.bas").await.unwrap();
+        assert!(drive.enumerate_dir("games/arcade").await.unwrap().dirents().is_empty());
+        drive.rmdir("games/arcade").await.unwrap();
+        assert!(!drive.is_dir("games/arcade"));
+    }
+
+    #[tokio::test]
+    async fn test_inmemorydrive_dir_errors() {
+        let mut drive = InMemoryDrive::default();
+        drive.mkdir("dir").await.unwrap();
+        drive.put("dir/file", b"").await.unwrap();
+
+        let err = drive.mkdir("dir").await.unwrap_err();
+        assert_eq!("Entry already exists", format!("{}", err));
+        let err = drive.mkdir("dir/file").await.unwrap_err();
+        assert_eq!("Entry already exists", format!("{}", err));
+        let err = drive.mkdir("missing/sub").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+
+        let err = drive.put("dir", b"").await.unwrap_err();
+        assert_eq!("Entry is a directory", format!("{}", err));
+        let err = drive.put("missing/file", b"").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+
+        let err = drive.rmdir("dir").await.unwrap_err();
+        assert_eq!("Directory not empty", format!("{}", err));
+        let err = drive.rmdir("dir/file").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        let err = drive.enumerate_dir("dir/file").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+
+        let err = drive.delete("dir").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        assert!(drive.is_dir("dir"));
+    }
+
+    #[tokio::test]
+    async fn test_inmemorydrive_rename() {
+        let mut drive = InMemoryDrive::default();
+        drive.mkdir("dir").await.unwrap();
+        drive.put("file", b"content").await.unwrap();
+        drive.update_acls("file", &readers(&["r1"]), &FileAcls::default()).await.unwrap();
+
+        drive.rename("file", "dir/moved").await.unwrap();
+        assert_eq!(io::ErrorKind::NotFound, drive.get("file").await.unwrap_err().kind());
+        assert_eq!(b"content", drive.get("dir/moved").await.unwrap().as_slice());
+        assert_eq!(readers(&["r1"]), drive.get_acls("dir/moved").await.unwrap());
+
+        let err = drive.rename("file", "other").await.unwrap_err();
+        assert_eq!("Entry not found", format!("{}", err));
+        let err = drive.rename("dir/moved", "dir").await.unwrap_err();
+        assert_eq!("Entry is a directory", format!("{}", err));
+        let err = drive.rename("dir/moved", "missing/file").await.unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        assert_eq!(b"content", drive.get("dir/moved").await.unwrap().as_slice());
+    }
+
     #[test]
     fn test_inmemorydrive_system_path() {
         let drive = InMemoryDrive::default();
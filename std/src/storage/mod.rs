@@ -16,6 +16,7 @@
 //! Storage-related abstractions and commands.
 
 use async_trait::async_trait;
+use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self};
 use std::io;
@@ -23,12 +24,24 @@ use std::path::PathBuf;
 use std::str;
 use time::error::Format;
 
+mod archive;
+pub use archive::*;
+mod binary;
+pub use binary::*;
+mod channels;
+pub use channels::*;
 mod cmds;
 pub use cmds::*;
+mod csv;
+pub use csv::*;
 mod fs;
 pub use fs::*;
 mod mem;
 pub use mem::*;
+mod ro;
+pub use ro::*;
+mod zip;
+pub use zip::*;
 
 /// Converts a time formatting error to an I/O error.
 pub(crate) fn time_format_error_to_io_error(e: Format) -> io::Error {
@@ -53,12 +66,15 @@ pub struct Metadata {
 pub struct FileAcls {
     /// List of principals that are allowed to read the file.
     pub readers: Vec<String>,
+
+    /// List of principals that are allowed to modify the file.
+    pub writers: Vec<String>,
 }
 
 impl FileAcls {
     /// Returns true if this group of ACLs is empty.
     pub fn is_empty(&self) -> bool {
-        self.readers.is_empty()
+        self.readers.is_empty() && self.writers.is_empty()
     }
 
     /// Extends this set of ACLs with the given `readers`.
@@ -67,15 +83,31 @@ impl FileAcls {
         self
     }
 
+    /// Extends this set of ACLs with the given `writers`.
+    pub fn with_writers<T: Into<Vec<String>>>(mut self, writers: T) -> Self {
+        self.writers.extend(writers.into());
+        self
+    }
+
     /// Gets the list of principals that are allowed to read the file.
     pub fn readers(&self) -> &[String] {
         &self.readers
     }
 
+    /// Gets the list of principals that are allowed to modify the file.
+    pub fn writers(&self) -> &[String] {
+        &self.writers
+    }
+
     /// Modifies the readers list by appending `reader` to it.
     pub fn add_reader<R: Into<String>>(&mut self, reader: R) {
         self.readers.push(reader.into());
     }
+
+    /// Modifies the writers list by appending `writer` to it.
+    pub fn add_writer<W: Into<String>>(&mut self, writer: W) {
+        self.writers.push(writer.into());
+    }
 }
 
 /// Representation of some amount of disk space.  Can be used to express both quotas and usage.
@@ -106,10 +138,11 @@ impl DiskSpace {
 }
 
 /// Collection of entries in the store and their metadata.  Used to represent the result of the
-/// `Drive::enumerate` call.
-#[derive(Debug)]
+/// `Drive::enumerate` and `Drive::enumerate_dir` calls.
+#[derive(Clone, Debug)]
 pub struct DriveFiles {
     dirents: BTreeMap<String, Metadata>,
+    subdirs: BTreeMap<String, Metadata>,
     disk_quota: Option<DiskSpace>,
     disk_free: Option<DiskSpace>,
 }
@@ -121,7 +154,13 @@ impl DriveFiles {
         disk_quota: Option<DiskSpace>,
         disk_free: Option<DiskSpace>,
     ) -> Self {
-        Self { dirents, disk_quota, disk_free }
+        Self { dirents, subdirs: BTreeMap::default(), disk_quota, disk_free }
+    }
+
+    /// Extends this collection with the given `subdirs`.
+    pub fn with_subdirs(mut self, subdirs: BTreeMap<String, Metadata>) -> Self {
+        self.subdirs.extend(subdirs);
+        self
     }
 
     /// Returns the collection of files in this result.
@@ -129,6 +168,11 @@ impl DriveFiles {
         &self.dirents
     }
 
+    /// Returns the collection of subdirectories in this result.
+    pub fn subdirs(&self) -> &BTreeMap<String, Metadata> {
+        &self.subdirs
+    }
+
     /// Returns the user's disk quota, if known.
     pub fn disk_quota(&self) -> &Option<DiskSpace> {
         &self.disk_quota
@@ -141,14 +185,42 @@ impl DriveFiles {
 }
 
 /// Abstract operations to load and store programs on some storage medium.
+///
+/// Entry names are paths relative to the root of the drive with their components separated by
+/// slashes, such as `GAMES/PONG.BAS`.  Drives that do not support directories only need to
+/// implement the flat operations: they never report any directory other than the root, so the
+/// storage subsystem never hands them names with more than one component.
 #[async_trait(?Send)]
 pub trait Drive {
+    /// Returns the canonical form of the entry `name`.
+    ///
+    /// The storage subsystem considers two names to refer to the same entry when their canonical
+    /// forms are equal, regardless of the behavior of the underlying medium.  The default makes
+    /// names case-insensitive, which is what users of all drives should see.
+    fn canonicalize_name(&self, name: &str) -> String {
+        name.to_uppercase()
+    }
+
     /// Deletes the program given by `name`.
     async fn delete(&mut self, name: &str) -> io::Result<()>;
 
-    /// Returns the entries in the store and their metadata.
+    /// Discards any information about the contents of the drive that the drive may have cached
+    /// so that subsequent operations query the underlying medium.
+    fn discard_cache(&mut self) {}
+
+    /// Returns the entries in the root of the store and their metadata.
     async fn enumerate(&self) -> io::Result<DriveFiles>;
 
+    /// Returns the entries in the `dir` directory and their metadata, where an empty `dir`
+    /// represents the root of the store.
+    async fn enumerate_dir(&self, dir: &str) -> io::Result<DriveFiles> {
+        if dir.is_empty() {
+            self.enumerate().await
+        } else {
+            Err(io::Error::new(io::ErrorKind::NotFound, "Directory not found"))
+        }
+    }
+
     /// Loads the contents of the program given by `name`.
     async fn get(&self, name: &str) -> io::Result<Vec<u8>>;
 
@@ -157,9 +229,39 @@ pub trait Drive {
         Err(io::Error::new(io::ErrorKind::Other, "Operation not supported by drive"))
     }
 
+    /// Returns true if `name` is an existing directory, where an empty `name` represents the root
+    /// of the store.
+    fn is_dir(&self, name: &str) -> bool {
+        name.is_empty()
+    }
+
+    /// Creates the `_name` directory.
+    async fn mkdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "Operation not supported by drive"))
+    }
+
     /// Saves the in-memory program given by `content` into `name`.
     async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()>;
 
+    /// Renames the program `old` to `new`, which must not exist yet.
+    ///
+    /// Drives without a native rename operation get a copy of the program followed by the deletion
+    /// of the original, which removes the copy again if the deletion fails.
+    async fn rename(&mut self, old: &str, new: &str) -> io::Result<()> {
+        let content = self.get(old).await?;
+        self.put(new, &content).await?;
+        if let Err(e) = self.delete(old).await {
+            let _ = self.delete(new).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Deletes the `_name` directory, which must be empty.
+    async fn rmdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(io::Error::new(io::ErrorKind::Other, "Operation not supported by drive"))
+    }
+
     /// Updates the ACLs of the file `_name` by extending them with the contents of `_add` and
     /// removing the existing entries listed in `_remove`.
     async fn update_acls(
@@ -208,7 +310,12 @@ impl fmt::Display for DriveKey {
     }
 }
 
-/// Representation of an EndBASIC path.
+/// Representation of an EndBASIC path as provided by the user.
+///
+/// Paths are of the form `[DRIVE:][/]COMPONENT/.../COMPONENT`.  Paths that start with a slash are
+/// absolute within their drive and all others are relative to the current directory of their
+/// drive.  The `.` and `..` components are only interpreted once the location is resolved against
+/// the current directory.
 ///
 /// This implementation is not as efficient as it could be, given that many times we don't have to
 /// clone the input string to break it into pieces.  However, owning the components here makes the
@@ -216,14 +323,15 @@ impl fmt::Display for DriveKey {
 #[derive(Debug)]
 struct Location {
     drive: Option<DriveKey>,
-    path: String,
+    absolute: bool,
+    components: Vec<String>,
 }
 
 impl Location {
     /// Constructs a new path from the contents of `s` after validating it.
     fn new(s: &str) -> io::Result<Self> {
         let fields = s.split(':').collect::<Vec<&str>>();
-        let (drive, mut path) = match fields.as_slice() {
+        let (drive, path) = match fields.as_slice() {
             [drive, path] => (Some(DriveKey::new(*drive)?), *path),
             [path] => (None, *path),
             _ => {
@@ -234,65 +342,79 @@ impl Location {
             }
         };
 
-        if path.is_empty() {
-            path = "/";
-        } else {
-            if !Location::is_path_valid(path)
-                || path == "."
-                || path == ".."
-                || path == "/."
-                || path == "/.."
-            {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("Invalid path '{}'", s),
-                ));
-            }
-            let slashes = path.chars().fold(0, |a, c| if c == '/' { a + 1 } else { a });
-            if (slashes == 1 && !path.starts_with('/')) || slashes > 1 {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("Too many / separators in path '{}'", s),
-                ));
-            }
+        let invalid_path =
+            || io::Error::new(io::ErrorKind::InvalidInput, format!("Invalid path '{}'", s));
+
+        if !Location::is_path_valid(path) {
+            return Err(invalid_path());
+        }
+        let (absolute, path) = match path.strip_prefix('/') {
+            Some(rest) => (true, rest),
+            None => (false, path),
+        };
+        if path.starts_with('/') {
+            return Err(invalid_path());
         }
+        let path = path.strip_suffix('/').unwrap_or(path);
 
-        Ok(Self { drive, path: path.to_owned() })
-    }
+        let mut components = vec![];
+        if !path.is_empty() {
+            for component in path.split('/') {
+                if component.is_empty() {
+                    return Err(invalid_path());
+                }
+                components.push(component.to_owned());
+            }
+        }
 
-    /// Convenience function to create a path to the root of a `drive`.
-    fn with_drive_root(drive: DriveKey) -> Self {
-        Self { drive: Some(drive), path: "/".to_owned() }
+        Ok(Self { drive, absolute, components })
     }
 
     /// Returns true if the given path is valid.
     fn is_path_valid(s: &str) -> bool {
-        !s.is_empty() && !s.chars().any(|c| c == ':' || c == '\\')
+        !s.chars().any(|c| c == ':' || c == '\\')
     }
+}
 
-    /// Returns the last component of this path, or none if there is no referenced file.
-    fn leaf_name(&self) -> Option<&str> {
-        if self.path == "/" {
-            None
-        } else if self.path.starts_with('/') {
-            Some(&self.path[1..])
-        } else {
-            Some(&self.path)
-        }
+/// Representation of an EndBASIC path after resolving it against the current directory of its
+/// drive.
+#[derive(Debug)]
+struct ResolvedLocation {
+    /// The drive the location belongs to.
+    drive: DriveKey,
+
+    /// Whether the location was given as relative to a current directory that is the root of the
+    /// drive, in which case its canonical form remains relative.
+    relative_to_root: bool,
+
+    /// Components of the path from the root of the drive.
+    components: Vec<String>,
+}
+
+impl ResolvedLocation {
+    /// Returns the path of this location within its drive, which is empty for the root.
+    fn name(&self) -> String {
+        self.components.join("/")
     }
 
-    /// Sets the leaf name of this path.
-    fn set_leaf_name(&mut self, name: &str) {
-        if self.path.starts_with('/') {
-            self.path.clear();
-            self.path.push('/');
-            self.path.push_str(name);
-        } else {
-            self.path.clear();
-            self.path.push_str(name);
+    /// Returns the path of the directory containing this location within its drive.
+    fn parent_name(&self) -> String {
+        match self.components.split_last() {
+            Some((_, parent)) => parent.join("/"),
+            None => String::new(),
         }
     }
 
+    /// Returns the last component of this path, or none if the path points to the root.
+    fn leaf_name(&self) -> Option<&str> {
+        self.components.last().map(String::as_str)
+    }
+
+    /// Appends the `name` component to this path.
+    fn push(&mut self, name: &str) {
+        self.components.push(name.to_owned());
+    }
+
     /// Sets the file name extension as long as this location corresponds to a file and not a
     /// directory and does not already have one.
     ///
@@ -300,11 +422,7 @@ impl Location {
     fn set_extension(&mut self, extension: &str) {
         debug_assert_eq!(extension, extension.to_ascii_lowercase());
 
-        if let Some(name) = self.leaf_name() {
-            if name.is_empty() {
-                return;
-            }
-
+        if let Some(name) = self.components.last_mut() {
             if name.rfind('.').is_some() {
                 return;
             }
@@ -320,29 +438,34 @@ impl Location {
                 }
             }
 
-            self.path.push('.');
+            name.push('.');
             if as_uppercase {
-                self.path.push_str(&extension.to_ascii_uppercase());
+                name.push_str(&extension.to_ascii_uppercase());
             } else {
-                self.path.push_str(extension);
+                name.push_str(extension);
             }
         }
     }
 }
 
-impl fmt::Display for Location {
+impl fmt::Display for ResolvedLocation {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.drive {
-            Some(drive) => write!(f, "{}:{}", drive.0, self.path),
-            None => self.path.fmt(f),
+        if self.relative_to_root && !self.components.is_empty() {
+            write!(f, "{}:{}", self.drive, self.name())
+        } else {
+            write!(f, "{}:/{}", self.drive, self.name())
         }
     }
 }
 
 /// Trait to instantiate drives of a given type.
+#[async_trait(?Send)]
 pub trait DriveFactory {
     /// Creates a new drive for `target`.
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>>;
+    ///
+    /// `storage` gives read access to the drives that are already mounted, which drives whose
+    /// contents live in other drives (such as archives) need to load their data.
+    async fn create(&self, target: &str, storage: &Storage) -> io::Result<Box<dyn Drive>>;
 }
 
 /// Given a mount URI, validates it and returns the `(scheme, path)` pair.
@@ -360,6 +483,12 @@ fn split_uri(uri: &str) -> io::Result<(&str, &str)> {
 struct MountedDrive {
     uri: String,
     drive: Box<dyn Drive>,
+
+    /// Whether the drive was mounted read-only, in which case `drive` rejects all modifications.
+    read_only: bool,
+
+    /// Components of the current directory of the drive, empty for the root.
+    cwd: Vec<String>,
 }
 
 /// Storage subsystem representation.
@@ -375,6 +504,9 @@ pub struct Storage {
 
     /// Name of the active drive, which must be present in `drives`.
     current: DriveKey,
+
+    /// Warnings raised by operations since the last call to `take_warnings`.
+    warnings: RefCell<Vec<String>>,
 }
 
 impl Default for Storage {
@@ -382,14 +514,16 @@ impl Default for Storage {
     fn default() -> Self {
         let mut factories: HashMap<String, Box<dyn DriveFactory>> = HashMap::default();
         factories.insert("memory".to_owned(), Box::from(InMemoryDriveFactory::default()));
+        factories.insert("zip".to_owned(), Box::from(ZipDriveFactory::default()));
 
         let drive: Box<dyn Drive> = Box::from(InMemoryDrive::default());
 
         let mut drives = HashMap::new();
         let key = DriveKey::new("MEMORY").expect("Hardcoded drive name must be valid");
-        let mounted_drive = MountedDrive { uri: "memory://".to_owned(), drive };
+        let mounted_drive =
+            MountedDrive { uri: "memory://".to_owned(), drive, read_only: false, cwd: vec![] };
         drives.insert(key.clone(), mounted_drive);
-        Self { factories, drives, current: key }
+        Self { factories, drives, current: key, warnings: RefCell::default() }
     }
 }
 
@@ -407,13 +541,39 @@ impl Storage {
         self.factories.contains_key(scheme)
     }
 
+    /// Parses `raw_location` and resolves it against the current directory of its drive.
+    ///
+    /// The drive needn't be mounted, in which case relative paths are resolved against its root.
+    fn resolve(&self, raw_location: &str) -> io::Result<ResolvedLocation> {
+        let location = Location::new(raw_location)?;
+        let drive = location.drive.unwrap_or_else(|| self.current.clone());
+
+        let mut components = match (location.absolute, self.drives.get(&drive)) {
+            (false, Some(mounted_drive)) => mounted_drive.cwd.clone(),
+            _ => vec![],
+        };
+        let relative_to_root = !location.absolute && components.is_empty();
+        for component in location.components {
+            match component.as_str() {
+                "." => (),
+                ".." => {
+                    if components.pop().is_none() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidInput,
+                            format!("Invalid path '{}'", raw_location),
+                        ));
+                    }
+                }
+                _ => components.push(component),
+            }
+        }
+
+        Ok(ResolvedLocation { drive, relative_to_root, components })
+    }
+
     /// Converts a `raw_location`, which needn't exist, to its canonical form.
     pub fn make_canonical(&self, raw_location: &str) -> io::Result<String> {
-        let mut location = Location::new(raw_location)?;
-        if location.drive.is_none() {
-            location.drive = Some(self.current.clone());
-        }
-        Ok(location.to_string())
+        Ok(self.resolve(raw_location)?.to_string())
     }
 
     /// Converts a `raw_location`, which needn't exist but must represent a file (not a directory),
@@ -425,10 +585,7 @@ impl Storage {
         raw_location: &str,
         extension: &str,
     ) -> io::Result<String> {
-        let mut location = Location::new(raw_location)?;
-        if location.drive.is_none() {
-            location.drive = Some(self.current.clone());
-        }
+        let mut location = self.resolve(raw_location)?;
         if location.leaf_name().is_none() {
             return Err(io::Error::new(
                 io::ErrorKind::NotFound,
@@ -439,10 +596,17 @@ impl Storage {
         Ok(location.to_string())
     }
 
-    /// Attaches a new `drive` with `name`, which was instantiated with `uri`.
+    /// Attaches a new `drive` with `name`, which was instantiated with `uri`.  If `read_only` is
+    /// true, the drive is wrapped so that all operations that would modify it fail.
     ///
     /// The `name` must be valid and must not yet have been registered.
-    fn attach(&mut self, name: &str, uri: &str, drive: Box<dyn Drive>) -> io::Result<()> {
+    fn attach(
+        &mut self,
+        name: &str,
+        uri: &str,
+        drive: Box<dyn Drive>,
+        read_only: bool,
+    ) -> io::Result<()> {
         let key = DriveKey::new(name)?;
         if self.drives.contains_key(&key) {
             return Err(io::Error::new(
@@ -450,18 +614,21 @@ impl Storage {
                 format!("Drive '{}' is already mounted", name),
             ));
         }
-        let mounted_drive = MountedDrive { uri: uri.to_owned(), drive };
+        let drive: Box<dyn Drive> =
+            if read_only { Box::from(ReadOnlyDrive::new(drive)) } else { drive };
+        let mounted_drive = MountedDrive { uri: uri.to_owned(), drive, read_only, cwd: vec![] };
         self.drives.insert(DriveKey::new(name)?, mounted_drive);
         Ok(())
     }
 
-    /// Instantiates and attaches a new `drive` with `name` that points to `uri`.
+    /// Instantiates and attaches a new `drive` with `name` that points to `uri`.  If `read_only` is
+    /// true, all operations that would modify the drive fail with a permission error.
     ///
     /// The `name` must be valid and must not yet have been registered.
-    pub fn mount(&mut self, name: &str, uri: &str) -> io::Result<()> {
+    pub async fn mount(&mut self, name: &str, uri: &str, read_only: bool) -> io::Result<()> {
         let (scheme, path) = split_uri(uri)?;
         let drive = match self.factories.get(&scheme.to_lowercase()) {
-            Some(factory) => factory.create(path)?,
+            Some(factory) => factory.create(path, self).await?,
             None => {
                 return Err(io::Error::new(
                     io::ErrorKind::InvalidInput,
@@ -469,7 +636,7 @@ impl Storage {
                 ))
             }
         };
-        self.attach(name, uri, drive)
+        self.attach(name, uri, drive, read_only)
     }
 
     /// Detaches an existing drive named `name`.
@@ -508,151 +675,316 @@ impl Storage {
         info
     }
 
-    /// Changes the current location.
-    ///
-    /// Given that we currently do not support directories, the location can only be of the forms
-    /// `DRIVE:` or `DRIVE:/`.
-    pub fn cd(&mut self, location: &str) -> io::Result<()> {
-        let location = Location::new(location)?;
-        if location.leaf_name().is_some() {
-            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Cannot cd to a file"));
+    /// Returns true if the drive `name` was mounted read-only.
+    pub fn is_read_only(&self, name: &str) -> io::Result<bool> {
+        let key = DriveKey::new(name)?;
+        match self.drives.get(&key) {
+            Some(mounted_drive) => Ok(mounted_drive.read_only),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Drive '{}' is not mounted", name),
+            )),
         }
+    }
 
-        match location.drive {
-            Some(drive) => {
-                if !self.drives.contains_key(&drive) {
-                    return Err(io::Error::new(
-                        io::ErrorKind::AlreadyExists,
-                        format!("Drive '{}' is not mounted", drive),
-                    ));
-                }
-                self.current = drive;
-                Ok(())
+    /// Changes the current location to the directory `raw_location`, which also becomes the
+    /// current directory of its drive.
+    pub fn cd(&mut self, raw_location: &str) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+
+        let mounted_drive = match self.drives.get_mut(&location.drive) {
+            Some(mounted_drive) => mounted_drive,
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::AlreadyExists,
+                    format!("Drive '{}' is not mounted", location.drive),
+                ))
             }
-            None => Ok(()),
+        };
+        if !mounted_drive.drive.is_dir(&location.name()) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Location '{}' is not a directory", raw_location),
+            ));
         }
+
+        mounted_drive.cwd = location.components;
+        self.current = location.drive;
+        Ok(())
     }
 
     /// Returns the current location, used to resolve relative paths.
     pub fn cwd(&self) -> String {
-        Location::with_drive_root(self.current.clone()).to_string()
+        let cwd = &self.drives.get(&self.current).expect("Current drive out of sync").cwd;
+        ResolvedLocation {
+            drive: self.current.clone(),
+            relative_to_root: false,
+            components: cwd.clone(),
+        }
+        .to_string()
     }
 
     /// Returns the drive referenced by `location`, or an error if it doesn't exist.
-    fn get_drive(&self, location: &Location) -> io::Result<&dyn Drive> {
-        match &location.drive {
-            Some(key) => match self.drives.get(key) {
-                Some(mounted_drive) => Ok(mounted_drive.drive.as_ref()),
-                None => Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Drive '{}' is not mounted", key),
-                )),
-            },
-            None => Ok(self
-                .drives
-                .get(&self.current)
-                .expect("Current drive out of sync")
-                .drive
-                .as_ref()),
+    fn get_drive(&self, location: &ResolvedLocation) -> io::Result<&dyn Drive> {
+        match self.drives.get(&location.drive) {
+            Some(mounted_drive) => Ok(mounted_drive.drive.as_ref()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Drive '{}' is not mounted", location.drive),
+            )),
         }
     }
 
     /// Returns the drive referenced by `location`, or an error if it doesn't exist.
-    fn get_drive_mut(&mut self, location: &Location) -> io::Result<&mut dyn Drive> {
-        match &location.drive {
-            Some(key) => match self.drives.get_mut(key) {
-                Some(mounted_drive) => Ok(mounted_drive.drive.as_mut()),
-                None => Err(io::Error::new(
-                    io::ErrorKind::NotFound,
-                    format!("Drive '{}' is not mounted", key),
-                )),
-            },
-            None => Ok(self
-                .drives
-                .get_mut(&self.current)
-                .expect("Current drive out of sync")
-                .drive
-                .as_mut()),
+    fn get_drive_mut(&mut self, location: &ResolvedLocation) -> io::Result<&mut dyn Drive> {
+        match self.drives.get_mut(&location.drive) {
+            Some(mounted_drive) => Ok(mounted_drive.drive.as_mut()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Drive '{}' is not mounted", location.drive),
+            )),
         }
     }
 
-    /// Deletes the program given by `raw_location`.
-    pub async fn delete(&mut self, raw_location: &str) -> io::Result<()> {
-        let location = Location::new(raw_location)?;
-        match location.leaf_name() {
-            Some(name) => self.get_drive_mut(&location)?.delete(name).await,
-            None => Err(io::Error::new(
+    /// Validates that `location` names an entry within an existing directory and returns its
+    /// name within the drive.  `raw_location` is the string that the user provided and is used for
+    /// error reporting.
+    fn entry_name(&self, raw_location: &str, location: &ResolvedLocation) -> io::Result<String> {
+        if location.leaf_name().is_none() {
+            return Err(io::Error::new(
                 io::ErrorKind::NotFound,
                 format!("Missing file name in path '{}'", raw_location),
-            )),
+            ));
+        }
+        if !self.get_drive(location)?.is_dir(&location.parent_name()) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Directory of '{}' does not exist", raw_location),
+            ));
         }
+        Ok(location.name())
+    }
+
+    /// Looks up the file `location` in the `files` of its directory by comparing the canonical
+    /// forms of the names, as defined by the drive, and returns the name of the matching entry
+    /// within the drive along with its metadata.  `raw_location` is the string that the user
+    /// provided and is used for error reporting.
+    ///
+    /// If there is no match, returns the name as given so that the caller can create the entry or
+    /// let the drive report the error.  If more than one entry matches, which can happen on media
+    /// that are case-sensitive, the first entry in sorted order wins and a warning is recorded.
+    fn match_entry(
+        &self,
+        raw_location: &str,
+        location: &ResolvedLocation,
+        files: &DriveFiles,
+    ) -> io::Result<(String, Option<Metadata>)> {
+        let name = self.entry_name(raw_location, location)?;
+        let leaf_name = location.leaf_name().expect("entry_name validated that the leaf exists");
+        let drive = self.get_drive(location)?;
+        let parent_name = location.parent_name();
+
+        let canonical_name = drive.canonicalize_name(leaf_name);
+        let mut matches = files
+            .dirents()
+            .iter()
+            .filter(|(name, _)| drive.canonicalize_name(name) == canonical_name);
+        let (first_name, metadata) = match matches.next() {
+            Some(entry) => entry,
+            None => return Ok((name, None)),
+        };
+        let others = matches.map(|(name, _)| name.as_str()).collect::<Vec<&str>>();
+        if !others.is_empty() {
+            self.warnings.borrow_mut().push(format!(
+                "{} matches files that differ only in case; using {} and ignoring {}",
+                raw_location,
+                first_name,
+                others.join(", ")
+            ));
+        }
+
+        let name = if parent_name.is_empty() {
+            first_name.to_owned()
+        } else {
+            format!("{}/{}", parent_name, first_name)
+        };
+        Ok((name, Some(metadata.clone())))
+    }
+
+    /// Same as `match_entry` but fetches the listing of the directory of `location` first.
+    ///
+    /// Errors while listing the directory are ignored and yield the name as given, leaving it to
+    /// the subsequent operation on the entry to report a meaningful error.
+    async fn lookup_entry(
+        &self,
+        raw_location: &str,
+        location: &ResolvedLocation,
+    ) -> io::Result<(String, Option<Metadata>)> {
+        let name = self.entry_name(raw_location, location)?;
+        let files = match self.get_drive(location)?.enumerate_dir(&location.parent_name()).await {
+            Ok(files) => files,
+            Err(_) => return Ok((name, None)),
+        };
+        self.match_entry(raw_location, location, &files)
+    }
+
+    /// Returns the warnings raised by operations since the last call to this function and clears
+    /// them.
+    pub fn take_warnings(&self) -> Vec<String> {
+        self.warnings.take()
+    }
+
+    /// Deletes the program given by `raw_location`.
+    pub async fn delete(&mut self, raw_location: &str) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+        let (name, _) = self.lookup_entry(raw_location, &location).await?;
+        self.get_drive_mut(&location)?.delete(&name).await
     }
 
     /// Returns a sorted list of the entries in `raw_location` and their metadata.
     pub async fn enumerate(&self, raw_location: &str) -> io::Result<DriveFiles> {
-        let location = Location::new(raw_location)?;
-        match location.leaf_name() {
-            Some(_) => Err(io::Error::new(
+        let location = self.resolve(raw_location)?;
+        let drive = self.get_drive(&location)?;
+        let name = location.name();
+        if !drive.is_dir(&name) {
+            return Err(io::Error::new(
                 io::ErrorKind::NotFound,
                 format!("Location '{}' is not a directory", raw_location),
-            )),
-            None => self.get_drive(&location)?.enumerate().await,
+            ));
         }
+        drive.enumerate_dir(&name).await
+    }
+
+    /// Discards the information cached by the drive referenced by `raw_location`, if any.
+    pub fn discard_cache(&mut self, raw_location: &str) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+        self.get_drive_mut(&location)?.discard_cache();
+        Ok(())
     }
 
     /// Loads the contents of the program given by `location`.  `raw_location` is the
     /// string that the user provided and is used for error reporting.
-    async fn get_location(&self, raw_location: &str, location: &Location) -> io::Result<Vec<u8>> {
-        match location.leaf_name() {
-            Some(name) => self.get_drive(location)?.get(name).await,
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Missing file name in path '{}'", raw_location),
-            )),
-        }
+    async fn get_location(
+        &self,
+        raw_location: &str,
+        location: &ResolvedLocation,
+    ) -> io::Result<Vec<u8>> {
+        let (name, _) = self.lookup_entry(raw_location, location).await?;
+        self.get_drive(location)?.get(&name).await
     }
 
     /// Loads the contents of the program given by `raw_location`.
     pub async fn get(&self, raw_location: &str) -> io::Result<Vec<u8>> {
-        let location = Location::new(raw_location)?;
+        let location = self.resolve(raw_location)?;
         self.get_location(raw_location, &location).await
     }
 
+    /// Loads the contents of the file `raw_location` without resolving its name against the
+    /// existing entries, which allows tests to inspect files that differ only in case.
+    pub(crate) async fn get_exact(&self, raw_location: &str) -> io::Result<Vec<u8>> {
+        let location = self.resolve(raw_location)?;
+        let name = self.entry_name(raw_location, &location)?;
+        self.get_drive(&location)?.get(&name).await
+    }
+
     /// Gets the ACLs of the file `raw_location`.
     pub async fn get_acls(&self, raw_location: &str) -> io::Result<FileAcls> {
-        let location = Location::new(raw_location)?;
-        match location.leaf_name() {
-            Some(name) => self.get_drive(&location)?.get_acls(name).await,
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Missing file name in path '{}'", raw_location),
-            )),
+        let location = self.resolve(raw_location)?;
+        let (name, _) = self.lookup_entry(raw_location, &location).await?;
+        self.get_drive(&location)?.get_acls(&name).await
+    }
+
+    /// Checks if the file `raw_location` exists.
+    ///
+    /// Missing files and missing parent directories yield false, but references to drives that
+    /// are not mounted or to invalid paths are still errors.
+    pub async fn exists(&self, raw_location: &str) -> io::Result<bool> {
+        let location = self.resolve(raw_location)?;
+        self.get_drive(&location)?;
+        match self.metadata(raw_location).await {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
         }
     }
 
+    /// Gets the metadata of the file `raw_location`.
+    ///
+    /// The file is looked up in the listing of its directory using the canonical names defined by
+    /// its drive.
+    pub async fn metadata(&self, raw_location: &str) -> io::Result<Metadata> {
+        let location = self.resolve(raw_location)?;
+        self.entry_name(raw_location, &location)?;
+        let files = self.get_drive(&location)?.enumerate_dir(&location.parent_name()).await?;
+        match self.match_entry(raw_location, &location, &files)? {
+            (_, Some(metadata)) => Ok(metadata),
+            (_, None) => Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
+        }
+    }
+
+    /// Creates the directory `raw_location`, whose parent must already exist.
+    pub async fn mkdir(&mut self, raw_location: &str) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+        if location.leaf_name().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Directory '{}' already exists", raw_location),
+            ));
+        }
+        let name = self.entry_name(raw_location, &location)?;
+        self.get_drive_mut(&location)?.mkdir(&name).await
+    }
+
     /// Saves the in-memory program given by `content` into `location`.  `raw_location` is the
     /// string that the user provided and is used for error reporting.
     async fn put_location(
         &mut self,
         raw_location: &str,
-        location: &Location,
+        location: &ResolvedLocation,
         content: &[u8],
     ) -> io::Result<()> {
-        match location.leaf_name() {
-            Some(name) => self.get_drive_mut(location)?.put(name, content).await,
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Missing file name in path '{}'", raw_location),
-            )),
-        }
+        let (name, _) = self.lookup_entry(raw_location, location).await?;
+        self.get_drive_mut(location)?.put(&name, content).await
     }
 
     /// Saves the in-memory program given by `content` into `raw_location`.
     pub async fn put(&mut self, raw_location: &str, content: &[u8]) -> io::Result<()> {
-        let location = Location::new(raw_location)?;
+        let location = self.resolve(raw_location)?;
         self.put_location(raw_location, &location, content).await
     }
 
+    /// Saves `content` into the file `raw_location` without resolving its name against the
+    /// existing entries, which allows tests to create files that differ only in case.
+    pub(crate) async fn put_exact(&mut self, raw_location: &str, content: &[u8]) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+        let name = self.entry_name(raw_location, &location)?;
+        self.get_drive_mut(&location)?.put(&name, content).await
+    }
+
+    /// Deletes the directory `raw_location`, which must be empty and cannot contain the current
+    /// directory of its drive.
+    pub async fn rmdir(&mut self, raw_location: &str) -> io::Result<()> {
+        let location = self.resolve(raw_location)?;
+        if location.leaf_name().is_none() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Cannot remove the root directory '{}'", raw_location),
+            ));
+        }
+        let name = self.entry_name(raw_location, &location)?;
+        let mounted_drive = self.drives.get(&location.drive).expect("Drive checked above");
+        if mounted_drive.cwd.starts_with(&location.components) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot remove '{}' because it contains the current directory",
+                    raw_location
+                ),
+            ));
+        }
+        self.get_drive_mut(&location)?.rmdir(&name).await
+    }
+
     /// Updates the ACLs of the file `raw_location` by extending them with the contents of `add` and
     /// removing the existing entries listed in `remove`.
     pub async fn update_acls(
@@ -661,28 +993,26 @@ impl Storage {
         add: &FileAcls,
         remove: &FileAcls,
     ) -> io::Result<()> {
-        let location = Location::new(raw_location)?;
-        match location.leaf_name() {
-            Some(name) => self.get_drive_mut(&location)?.update_acls(name, add, remove).await,
-            None => Err(io::Error::new(
-                io::ErrorKind::NotFound,
-                format!("Missing file name in path '{}'", raw_location),
-            )),
-        }
+        let location = self.resolve(raw_location)?;
+        let (name, _) = self.lookup_entry(raw_location, &location).await?;
+        self.get_drive_mut(&location)?.update_acls(&name, add, remove).await
     }
 
     /// Gets the system-addressable path of `raw_location`, if any.
     pub fn system_path(&self, raw_location: &str) -> io::Result<Option<PathBuf>> {
-        let location = Location::new(raw_location)?;
-        match location.leaf_name() {
-            Some(name) => Ok(self.get_drive(&location)?.system_path(name)),
-            None => Ok(self.get_drive(&location)?.system_path("")),
-        }
+        let location = self.resolve(raw_location)?;
+        Ok(self.get_drive(&location)?.system_path(&location.name()))
     }
 
-    /// Copies file `src` to `dest`.
-    pub async fn copy(&mut self, raw_src: &str, raw_dest: &str) -> io::Result<()> {
-        let src = Location::new(raw_src)?;
+    /// Resolves `raw_dest` as the target of an operation on the file `src`.  If `raw_dest` is a
+    /// directory, the target keeps the name of `src`.  `raw_src` is the string that the user
+    /// provided for `src` and is used for error reporting.
+    fn resolve_target(
+        &self,
+        raw_src: &str,
+        src: &ResolvedLocation,
+        raw_dest: &str,
+    ) -> io::Result<ResolvedLocation> {
         let src_name = match src.leaf_name() {
             Some(name) => name,
             None => {
@@ -693,20 +1023,90 @@ impl Storage {
             }
         };
 
-        let mut dest = Location::new(raw_dest)?;
-        if dest.leaf_name().is_none() {
-            dest.set_leaf_name(src_name);
+        let mut dest = self.resolve(raw_dest)?;
+        let dest_is_dir = match self.get_drive(&dest) {
+            Ok(drive) => drive.is_dir(&dest.name()),
+            Err(_) => dest.leaf_name().is_none(),
+        };
+        if dest_is_dir {
+            dest.push(src_name);
         }
+        Ok(dest)
+    }
+
+    /// Fails if the file `location` already exists.  `raw_location` is the string that the user
+    /// provided and is used for error reporting.
+    async fn check_not_exists(
+        &self,
+        raw_location: &str,
+        location: &ResolvedLocation,
+    ) -> io::Result<()> {
+        match self.get_location(raw_location, location).await {
+            Ok(_) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("Destination {} already exists", location),
+            )),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Copies file `src` to `dest`.  If `dest` is a directory, the copy keeps the name of `src`.
+    ///
+    /// The copy fails if `dest` already exists unless `overwrite` is true.
+    pub async fn copy(&mut self, raw_src: &str, raw_dest: &str, overwrite: bool) -> io::Result<()> {
+        let src = self.resolve(raw_src)?;
+        let dest = self.resolve_target(raw_src, &src, raw_dest)?;
 
         let content = self.get_location(raw_src, &src).await?;
+        if !overwrite {
+            self.check_not_exists(raw_dest, &dest).await?;
+        }
         self.put_location(raw_dest, &dest, &content).await
     }
+
+    /// Renames file `src` to `dest`, which must not exist yet.  If `dest` is a directory, the file
+    /// keeps its name.
+    ///
+    /// Renames across drives are implemented as a copy followed by the deletion of the source,
+    /// which removes the copy again if the deletion fails.
+    pub async fn rename(&mut self, raw_src: &str, raw_dest: &str) -> io::Result<()> {
+        let src = self.resolve(raw_src)?;
+        let (src_name, _) = self.lookup_entry(raw_src, &src).await?;
+        let dest = self.resolve_target(raw_src, &src, raw_dest)?;
+        let dest_name = self.entry_name(raw_dest, &dest)?;
+
+        if src.drive == dest.drive {
+            let (existing_dest_name, _) = self.lookup_entry(raw_dest, &dest).await?;
+            if src_name == existing_dest_name {
+                if src_name == dest_name {
+                    // Nothing to do other than validating that the file exists.
+                    return self.get_drive(&src)?.get(&src_name).await.map(|_| ());
+                }
+                // The names only differ in case, so this just changes how the file is stored.
+                return self.get_drive_mut(&src)?.rename(&src_name, &dest_name).await;
+            }
+            self.check_not_exists(raw_dest, &dest).await?;
+            return self.get_drive_mut(&src)?.rename(&src_name, &dest_name).await;
+        }
+
+        let content = self.get_drive(&src)?.get(&src_name).await?;
+        self.check_not_exists(raw_dest, &dest).await?;
+        self.get_drive_mut(&dest)?.put(&dest_name, &content).await?;
+        if let Err(e) = self.get_drive_mut(&src)?.delete(&src_name).await {
+            let _ = self.get_drive_mut(&dest)?.delete(&dest_name).await;
+            return Err(e);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use futures_lite::future::block_on;
+    use std::cell::Cell;
+    use std::rc::Rc;
 
     #[test]
     fn test_split_uri_ok() {
@@ -742,18 +1142,31 @@ mod tests {
 
     #[test]
     fn test_location_new_ok() {
-        fn check(exp_drive: Option<&str>, exp_path: &str, input: &str) {
+        fn check(
+            exp_drive: Option<&str>,
+            exp_absolute: bool,
+            exp_components: &[&str],
+            input: &str,
+        ) {
             let rp = Location::new(input).unwrap();
             assert_eq!(exp_drive.map(|s| DriveKey::new(s).unwrap()), rp.drive);
-            assert_eq!(exp_path, rp.path);
+            assert_eq!(exp_absolute, rp.absolute);
+            assert_eq!(exp_components, rp.components.as_slice());
         }
 
-        check(None, "/", "/");
-        check(None, "/foo.bas", "/foo.bas");
+        check(None, false, &[], "");
+        check(None, true, &[], "/");
+        check(None, true, &["foo.bas"], "/foo.bas");
+        check(None, false, &["foo.bas"], "foo.bas");
+        check(None, false, &["a", "b.bas"], "a/b.bas");
+        check(None, true, &["a", "b"], "/a/b/");
+        check(None, false, &["..", "x", "."], "../x/.");
 
-        check(Some("A"), "/", "a:");
-        check(Some("ABC"), "/foo.bas", "abc:/foo.bas");
-        check(Some("ABC"), "Foo.Bas", "abc:Foo.Bas");
+        check(Some("A"), false, &[], "a:");
+        check(Some("ABC"), true, &[], "abc:/");
+        check(Some("ABC"), true, &["foo.bas"], "abc:/foo.bas");
+        check(Some("ABC"), false, &["Foo.Bas"], "abc:Foo.Bas");
+        check(Some("ABC"), true, &["Dir", "Foo.Bas"], "abc:/Dir/Foo.Bas");
     }
 
     #[test]
@@ -772,63 +1185,62 @@ mod tests {
         check("Invalid drive name 'a.b'", "a.b:");
 
         check("Invalid path 'a:\\'", "a:\\");
-        check("Invalid path 'a:.'", "a:.");
-        check("Invalid path 'a:..'", "a:..");
-        check("Invalid path 'a:/.'", "a:/.");
-        check("Invalid path 'a:/..'", "a:/..");
-        check("Invalid path '.'", ".");
-        check("Invalid path '..'", "..");
-        check("Invalid path '/.'", "/.");
-        check("Invalid path '/..'", "/..");
-
-        check("Too many / separators in path 'a://.'", "a://.");
-        check("Too many / separators in path 'a:../'", "a:../");
-        check("Too many / separators in path 'a:b/c'", "a:b/c");
-        check("Too many / separators in path 'a:/b/c'", "a:/b/c");
+        check("Invalid path 'a:b\\c'", "a:b\\c");
+        check("Invalid path 'a://.'", "a://.");
+        check("Invalid path 'a:b//c'", "a:b//c");
+        check("Invalid path '//'", "//");
+    }
+
+    /// Syntactic sugar to instantiate a resolved location from its pieces.
+    fn resolved(drive: &str, relative_to_root: bool, components: &[&str]) -> ResolvedLocation {
+        ResolvedLocation {
+            drive: DriveKey::new(drive).unwrap(),
+            relative_to_root,
+            components: components.iter().map(|c| c.to_string()).collect(),
+        }
     }
 
     #[test]
-    fn test_location_leaf_name() {
-        assert_eq!(None, Location::new("drv:/").unwrap().leaf_name());
-        assert_eq!(None, Location::new("drv:").unwrap().leaf_name());
+    fn test_resolvedlocation_names() {
+        let location = resolved("drv", false, &[]);
+        assert_eq!("", location.name());
+        assert_eq!("", location.parent_name());
+        assert_eq!(None, location.leaf_name());
+
+        let location = resolved("drv", false, &["abc.txt"]);
+        assert_eq!("abc.txt", location.name());
+        assert_eq!("", location.parent_name());
+        assert_eq!(Some("abc.txt"), location.leaf_name());
 
-        assert_eq!(Some("abc.txt"), Location::new("a:/abc.txt").unwrap().leaf_name());
-        assert_eq!(Some("abc.txt"), Location::new("a:abc.txt").unwrap().leaf_name());
-        assert_eq!(Some("abc.txt"), Location::new("abc.txt").unwrap().leaf_name());
+        let mut location = resolved("drv", false, &["a", "b"]);
+        assert_eq!("a/b", location.name());
+        assert_eq!("a", location.parent_name());
+        assert_eq!(Some("b"), location.leaf_name());
+        location.push("c.bas");
+        assert_eq!("a/b/c.bas", location.name());
     }
 
     #[test]
-    fn test_location_set_leaf_name() {
-        let mut location = Location::new("drv:/").unwrap();
-        location.set_leaf_name("foo");
-        assert_eq!("DRV:/foo", format!("{}", location));
-
-        let mut location = Location::new("drv:").unwrap();
-        location.set_leaf_name("foo");
-        assert_eq!("DRV:/foo", format!("{}", location));
-
-        let mut location = Location::new("/bar").unwrap();
-        location.set_leaf_name("foo");
-        assert_eq!("/foo", format!("{}", location));
-
-        let mut location = Location::new("bar").unwrap();
-        location.set_leaf_name("foo");
-        assert_eq!("foo", format!("{}", location));
+    fn test_resolvedlocation_display() {
+        assert_eq!("DRV:/", resolved("drv", false, &[]).to_string());
+        assert_eq!("DRV:/", resolved("drv", true, &[]).to_string());
+        assert_eq!("DRV:/a/b", resolved("drv", false, &["a", "b"]).to_string());
+        assert_eq!("DRV:a/b", resolved("drv", true, &["a", "b"]).to_string());
     }
 
     #[test]
-    fn test_location_set_extension() {
-        for (exp_location, raw_location, extension) in [
-            ("DRV:/", "drv:", "bas"),
-            ("DRV:/", "drv:/", "bas"),
-            ("foo.bas", "foo", "bas"),
-            ("foo.bas", "foo.bas", "bas"),
-            ("Foo.bas", "Foo", "bas"),
-            ("FOO.BAS", "FOO", "bas"),
-            ("foo.", "foo.", "bas"),
-            ("foo.other", "foo.other", "bas"),
+    fn test_resolvedlocation_set_extension() {
+        for (exp_location, components, extension) in [
+            ("DRV:/", &[][..], "bas"),
+            ("DRV:foo.bas", &["foo"][..], "bas"),
+            ("DRV:foo.bas", &["foo.bas"][..], "bas"),
+            ("DRV:Foo.bas", &["Foo"][..], "bas"),
+            ("DRV:FOO.BAS", &["FOO"][..], "bas"),
+            ("DRV:foo.", &["foo."][..], "bas"),
+            ("DRV:foo.other", &["foo.other"][..], "bas"),
+            ("DRV:dir.x/foo.bas", &["dir.x", "foo"][..], "bas"),
         ] {
-            let mut location = Location::new(raw_location).unwrap();
+            let mut location = resolved("drv", true, components);
             location.set_extension(extension);
             assert_eq!(exp_location, location.to_string());
         }
@@ -850,7 +1262,7 @@ mod tests {
     #[test]
     fn test_storage_make_canonical_ok() {
         let mut storage = Storage::default();
-        storage.mount("some", "memory://").unwrap();
+        block_on(storage.mount("some", "memory://", false)).unwrap();
 
         assert_eq!("MEMORY:/", storage.make_canonical("memory:").unwrap());
 
@@ -878,7 +1290,7 @@ mod tests {
     #[test]
     fn test_storage_make_canonical_with_extension_ok() {
         let mut storage = Storage::default();
-        storage.mount("some", "memory://").unwrap();
+        block_on(storage.mount("some", "memory://", false)).unwrap();
 
         assert_eq!("MEMORY:foo.bas", storage.make_canonical_with_extension("foo", "bas").unwrap());
         assert_eq!(
@@ -914,8 +1326,8 @@ mod tests {
     #[test]
     fn test_storage_attach_ok() {
         let mut storage = Storage::default();
-        storage.attach("zzz1", "z://", Box::from(InMemoryDrive::default())).unwrap();
-        storage.attach("A4", "z://", Box::from(InMemoryDrive::default())).unwrap();
+        storage.attach("zzz1", "z://", Box::from(InMemoryDrive::default()), false).unwrap();
+        storage.attach("A4", "z://", Box::from(InMemoryDrive::default()), false).unwrap();
 
         assert_eq!("MEMORY:/", storage.cwd());
         assert_eq!(["A4", "MEMORY", "ZZZ1"], drive_names(&storage).as_slice());
@@ -928,7 +1340,9 @@ mod tests {
             "Invalid drive name 'a:b'",
             format!(
                 "{}",
-                storage.attach("a:b", "z://", Box::from(InMemoryDrive::default())).unwrap_err()
+                storage
+                    .attach("a:b", "z://", Box::from(InMemoryDrive::default()), false)
+                    .unwrap_err()
             )
         );
     }
@@ -940,16 +1354,20 @@ mod tests {
             "Drive 'memory' is already mounted",
             format!(
                 "{}",
-                storage.attach("memory", "z://", Box::from(InMemoryDrive::default())).unwrap_err()
+                storage
+                    .attach("memory", "z://", Box::from(InMemoryDrive::default()), false)
+                    .unwrap_err()
             )
         );
 
-        storage.attach("new", "z://", Box::from(InMemoryDrive::default())).unwrap();
+        storage.attach("new", "z://", Box::from(InMemoryDrive::default()), false).unwrap();
         assert_eq!(
             "Drive 'New' is already mounted",
             format!(
                 "{}",
-                storage.attach("New", "z://", Box::from(InMemoryDrive::default())).unwrap_err()
+                storage
+                    .attach("New", "z://", Box::from(InMemoryDrive::default()), false)
+                    .unwrap_err()
             )
         );
     }
@@ -966,8 +1384,8 @@ mod tests {
     fn test_storage_mount_ok() {
         let mut storage = Storage::default();
         storage.register_scheme("fake", Box::from(InMemoryDriveFactory::default()));
-        storage.mount("a", "memory://").unwrap();
-        storage.mount("z", "fAkE://").unwrap();
+        block_on(storage.mount("a", "memory://", false)).unwrap();
+        block_on(storage.mount("z", "fAkE://", false)).unwrap();
 
         assert_eq!(["A", "MEMORY", "Z"], drive_names(&storage).as_slice());
     }
@@ -980,8 +1398,8 @@ mod tests {
 
         let mut storage = Storage::default();
         storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
-        storage.mount("c", &format!("file://{}", dir1.display())).unwrap();
-        storage.mount("d", &format!("file://{}", dir2.display())).unwrap();
+        block_on(storage.mount("c", &format!("file://{}", dir1.display()), false)).unwrap();
+        block_on(storage.mount("d", &format!("file://{}", dir2.display()), false)).unwrap();
 
         block_on(storage.put("c:file1.txt", b"hi")).unwrap();
         block_on(storage.put("d:file2.txt", b"bye")).unwrap();
@@ -997,7 +1415,7 @@ mod tests {
         let mut storage = Storage::default();
         assert_eq!(
             "Unknown mount scheme 'fake'",
-            format!("{}", storage.mount("a", "fake://abc").unwrap_err())
+            format!("{}", block_on(storage.mount("a", "fake://abc", false)).unwrap_err())
         );
     }
 
@@ -1006,14 +1424,14 @@ mod tests {
         let mut storage = Storage::default();
         assert_eq!(
             "Cannot specify a path to mount an in-memory drive",
-            format!("{}", storage.mount("a", "memory://abc").unwrap_err())
+            format!("{}", block_on(storage.mount("a", "memory://abc", false)).unwrap_err())
         );
     }
 
     #[test]
     fn test_storage_unmount_ok() {
         let mut storage = Storage::default();
-        storage.mount("other", "memory://").unwrap();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
         assert_eq!("MEMORY:/", storage.cwd());
         assert_eq!(["MEMORY", "OTHER"], drive_names(&storage).as_slice());
 
@@ -1034,7 +1452,7 @@ mod tests {
     #[test]
     fn test_storage_unmount_current_drive_error() {
         let mut storage = Storage::default();
-        storage.mount("other", "memory://").unwrap();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
         assert_eq!(
             "Cannot unmount the current drive 'memory'",
             format!("{}", storage.unmount("memory").unwrap_err())
@@ -1049,7 +1467,7 @@ mod tests {
     fn test_storage_mounted() {
         let mut storage = Storage::default();
         storage.register_scheme("fake", Box::from(InMemoryDriveFactory::default()));
-        storage.mount("z", "fAkE://").unwrap();
+        block_on(storage.mount("z", "fAkE://", false)).unwrap();
 
         let mut exp_info = BTreeMap::default();
         exp_info.insert("MEMORY", "memory://");
@@ -1057,10 +1475,41 @@ mod tests {
         assert_eq!(exp_info, storage.mounted());
     }
 
+    #[test]
+    fn test_storage_mount_read_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("file.bas"), "content").unwrap();
+        let uri = format!("file://{}", dir.path().display());
+
+        let mut storage = Storage::default();
+        storage.register_scheme("file", Box::from(DirectoryDriveFactory::default()));
+        block_on(storage.mount("ro", &uri, true)).unwrap();
+        assert!(storage.is_read_only("RO").unwrap());
+        assert!(!storage.is_read_only("memory").unwrap());
+        assert_eq!(
+            "Drive 'foo' is not mounted",
+            format!("{}", storage.is_read_only("foo").unwrap_err())
+        );
+
+        assert_eq!(b"content", block_on(storage.get("ro:file.bas")).unwrap().as_slice());
+        let err = block_on(storage.put("ro:file.bas", b"new")).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert_eq!("Drive is mounted read-only", format!("{}", err));
+        let err = block_on(storage.delete("ro:file.bas")).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+        assert_eq!("content", std::fs::read_to_string(dir.path().join("file.bas")).unwrap());
+
+        storage.unmount("ro").unwrap();
+        block_on(storage.mount("ro", &uri, false)).unwrap();
+        assert!(!storage.is_read_only("ro").unwrap());
+        block_on(storage.put("ro:file.bas", b"new")).unwrap();
+        assert_eq!("new", std::fs::read_to_string(dir.path().join("file.bas")).unwrap());
+    }
+
     #[test]
     fn test_storage_cd_and_cwd_ok() {
         let mut storage = Storage::default();
-        storage.mount("other", "memory://").unwrap();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
         assert_eq!("MEMORY:/", storage.cwd());
         storage.cd("other:/").unwrap();
         assert_eq!("OTHER:/", storage.cwd());
@@ -1073,14 +1522,26 @@ mod tests {
         let mut storage = Storage::default();
         assert_eq!("Invalid drive name ''", format!("{}", storage.cd(":foo").unwrap_err()));
         assert_eq!("Invalid path 'a:b\\c'", format!("{}", storage.cd("a:b\\c").unwrap_err()));
-        assert_eq!("Cannot cd to a file", format!("{}", storage.cd("foo:bar.bas").unwrap_err()));
+        assert_eq!("Invalid path '..'", format!("{}", storage.cd("..").unwrap_err()));
         assert_eq!("Drive 'A' is not mounted", format!("{}", storage.cd("a:").unwrap_err()));
+        assert_eq!("Drive 'FOO' is not mounted", format!("{}", storage.cd("foo:bar").unwrap_err()));
+
+        block_on(storage.put("bar.bas", b"")).unwrap();
+        assert_eq!(
+            "Location 'bar.bas' is not a directory",
+            format!("{}", storage.cd("bar.bas").unwrap_err())
+        );
+        assert_eq!(
+            "Location 'missing' is not a directory",
+            format!("{}", storage.cd("missing").unwrap_err())
+        );
+        assert_eq!("MEMORY:/", storage.cwd());
     }
 
     #[test]
     fn test_storage_file_ops_with_absolute_paths() {
         let mut storage = Storage::default();
-        storage.mount("other", "memory://").unwrap();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
 
         block_on(storage.put("other:/f1", b"some text")).unwrap();
         block_on(storage.put("other:f2", b"other text")).unwrap();
@@ -1111,7 +1572,7 @@ mod tests {
     #[test]
     fn test_storage_file_ops_with_relative_paths() {
         let mut storage = Storage::default();
-        storage.mount("other", "memory://").unwrap();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
 
         block_on(storage.put("/f1", b"some text")).unwrap();
         block_on(storage.put("f2", b"other text")).unwrap();
@@ -1139,6 +1600,354 @@ mod tests {
         assert_eq!(0, block_on(storage.enumerate("other:")).unwrap().dirents().len());
     }
 
+    #[test]
+    fn test_storage_names_are_case_insensitive() {
+        let mut storage = Storage::default();
+        block_on(storage.mkdir("Dir")).unwrap();
+
+        block_on(storage.put("game.bas", b"first")).unwrap();
+        assert_eq!(b"first", block_on(storage.get("GAME.BAS")).unwrap().as_slice());
+        block_on(storage.put("Game.Bas", b"second")).unwrap();
+        assert_eq!(b"second", block_on(storage.get("game.BAS")).unwrap().as_slice());
+        block_on(storage.put("Dir/Other.bas", b"third")).unwrap();
+        assert_eq!(b"third", block_on(storage.get("Dir/OTHER.BAS")).unwrap().as_slice());
+
+        let files = block_on(storage.enumerate("")).unwrap();
+        assert_eq!(["game.bas"], files.dirents().keys().collect::<Vec<&String>>().as_slice());
+
+        block_on(storage.rename("GAME.BAS", "Game.bas")).unwrap();
+        let files = block_on(storage.enumerate("")).unwrap();
+        assert_eq!(["Game.bas"], files.dirents().keys().collect::<Vec<&String>>().as_slice());
+
+        block_on(storage.delete("GAME.bas")).unwrap();
+        block_on(storage.delete("dir/other.BAS")).unwrap_err();
+        block_on(storage.delete("Dir/other.BAS")).unwrap();
+        assert!(block_on(storage.enumerate("")).unwrap().dirents().is_empty());
+        assert!(block_on(storage.enumerate("Dir")).unwrap().dirents().is_empty());
+        assert!(storage.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_storage_names_with_case_conflicts() {
+        let mut storage = Storage::default();
+        block_on(storage.put_exact("game.bas", b"lower")).unwrap();
+        block_on(storage.put_exact("GAME.BAS", b"upper")).unwrap();
+
+        assert_eq!(b"upper", block_on(storage.get("Game.bas")).unwrap().as_slice());
+        assert_eq!(
+            ["Game.bas matches files that differ only in case; using GAME.BAS and ignoring \
+                 game.bas"],
+            storage.take_warnings().as_slice()
+        );
+        assert!(storage.take_warnings().is_empty());
+
+        block_on(storage.put("game.bas", b"new")).unwrap();
+        block_on(storage.delete("game.BAS")).unwrap();
+        assert_eq!(2, storage.take_warnings().len());
+        assert_eq!(b"lower", block_on(storage.get("GAME.BAS")).unwrap().as_slice());
+        assert!(storage.take_warnings().is_empty());
+    }
+
+    #[test]
+    fn test_storage_dirs_and_cwd() {
+        let mut storage = Storage::default();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
+
+        block_on(storage.mkdir("games")).unwrap();
+        block_on(storage.mkdir("/games/arcade")).unwrap();
+        block_on(storage.put("games/arcade/pong.bas", b"pong")).unwrap();
+
+        storage.cd("games").unwrap();
+        assert_eq!("MEMORY:/games", storage.cwd());
+        assert_eq!("MEMORY:/games/chess.bas", storage.make_canonical("chess.bas").unwrap());
+        assert_eq!("MEMORY:/top.bas", storage.make_canonical("../top.bas").unwrap());
+        assert_eq!("MEMORY:/top.bas", storage.make_canonical("/top.bas").unwrap());
+        block_on(storage.put("chess.bas", b"chess")).unwrap();
+        assert_eq!(b"pong", block_on(storage.get("arcade/pong.bas")).unwrap().as_slice());
+
+        let files = block_on(storage.enumerate("")).unwrap();
+        assert_eq!(["chess.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+        assert_eq!(["arcade"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+        let files = block_on(storage.enumerate("/")).unwrap();
+        assert!(files.dirents().is_empty());
+        assert_eq!(["games"], files.subdirs().keys().collect::<Vec<_>>().as_slice());
+
+        // Each drive keeps its own current directory.
+        storage.cd("other:").unwrap();
+        assert_eq!("OTHER:/", storage.cwd());
+        assert_eq!("MEMORY:/games/x.bas", storage.make_canonical("memory:x.bas").unwrap());
+        storage.cd("memory:").unwrap();
+        assert_eq!("MEMORY:/games", storage.cwd());
+
+        storage.cd("arcade/..").unwrap();
+        assert_eq!("MEMORY:/games", storage.cwd());
+        storage.cd("..").unwrap();
+        assert_eq!("MEMORY:/", storage.cwd());
+        assert_eq!("MEMORY:x.bas", storage.make_canonical("x.bas").unwrap());
+
+        block_on(storage.copy("games/chess.bas", "games/arcade", true)).unwrap();
+        assert_eq!(b"chess", block_on(storage.get("games/arcade/chess.bas")).unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_storage_mkdir_rmdir_errors() {
+        let mut storage = Storage::default();
+        block_on(storage.mkdir("dir")).unwrap();
+        block_on(storage.put("dir/file.bas", b"")).unwrap();
+
+        assert_eq!(
+            "Directory 'memory:' already exists",
+            format!("{}", block_on(storage.mkdir("memory:")).unwrap_err())
+        );
+        assert_eq!(
+            "Directory of 'a/b' does not exist",
+            format!("{}", block_on(storage.mkdir("a/b")).unwrap_err())
+        );
+        assert_eq!(
+            "Directory not empty",
+            format!("{}", block_on(storage.rmdir("dir")).unwrap_err())
+        );
+        assert_eq!(
+            "Cannot remove the root directory '/'",
+            format!("{}", block_on(storage.rmdir("/")).unwrap_err())
+        );
+
+        block_on(storage.delete("dir/file.bas")).unwrap();
+        storage.cd("dir").unwrap();
+        assert_eq!(
+            "Cannot remove '/dir' because it contains the current directory",
+            format!("{}", block_on(storage.rmdir("/dir")).unwrap_err())
+        );
+        storage.cd("/").unwrap();
+        block_on(storage.rmdir("dir")).unwrap();
+        assert!(block_on(storage.enumerate("")).unwrap().subdirs().is_empty());
+    }
+
+    /// A drive that does not implement any of the directory-aware operations.
+    #[derive(Default)]
+    struct FlatDrive(InMemoryDrive);
+
+    #[async_trait(?Send)]
+    impl Drive for FlatDrive {
+        async fn delete(&mut self, name: &str) -> io::Result<()> {
+            self.0.delete(name).await
+        }
+
+        async fn enumerate(&self) -> io::Result<DriveFiles> {
+            self.0.enumerate().await
+        }
+
+        async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+            self.0.get(name).await
+        }
+
+        async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
+            self.0.put(name, content).await
+        }
+    }
+
+    #[test]
+    fn test_storage_flat_drive() {
+        let mut storage = Storage::default();
+        storage.attach("flat", "flat://", Box::from(FlatDrive::default()), false).unwrap();
+
+        block_on(storage.put("flat:file.bas", b"data")).unwrap();
+        assert_eq!(1, block_on(storage.enumerate("flat:/")).unwrap().dirents().len());
+        assert_eq!(b"data", block_on(storage.get("flat:/file.bas")).unwrap().as_slice());
+
+        assert_eq!(
+            "Operation not supported by drive",
+            format!("{}", block_on(storage.mkdir("flat:dir")).unwrap_err())
+        );
+        assert_eq!(
+            "Directory of 'flat:dir/file.bas' does not exist",
+            format!("{}", block_on(storage.put("flat:dir/file.bas", b"")).unwrap_err())
+        );
+        assert_eq!(
+            "Location 'flat:dir' is not a directory",
+            format!("{}", storage.cd("flat:dir").unwrap_err())
+        );
+        storage.cd("flat:").unwrap();
+        assert_eq!("FLAT:/", storage.cwd());
+    }
+
+    #[test]
+    fn test_storage_flat_drive_rename() {
+        let mut storage = Storage::default();
+        storage.attach("flat", "flat://", Box::from(FlatDrive::default()), false).unwrap();
+
+        block_on(storage.put("flat:a.bas", b"data")).unwrap();
+        block_on(storage.rename("flat:a.bas", "flat:/b.bas")).unwrap();
+        let files = block_on(storage.enumerate("flat:")).unwrap();
+        assert_eq!(["b.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+        assert_eq!(b"data", block_on(storage.get("flat:b.bas")).unwrap().as_slice());
+    }
+
+    /// A drive that counts how many times its cache has been discarded.
+    #[derive(Default)]
+    struct CachingDrive {
+        drive: InMemoryDrive,
+        discards: Rc<Cell<usize>>,
+    }
+
+    #[async_trait(?Send)]
+    impl Drive for CachingDrive {
+        async fn delete(&mut self, name: &str) -> io::Result<()> {
+            self.drive.delete(name).await
+        }
+
+        fn discard_cache(&mut self) {
+            self.discards.set(self.discards.get() + 1);
+        }
+
+        async fn enumerate(&self) -> io::Result<DriveFiles> {
+            self.drive.enumerate().await
+        }
+
+        async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+            self.drive.get(name).await
+        }
+
+        async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
+            self.drive.put(name, content).await
+        }
+    }
+
+    #[test]
+    fn test_storage_discard_cache() {
+        let mut storage = Storage::default();
+        let (rw, ro) = (CachingDrive::default(), CachingDrive::default());
+        let (rw_discards, ro_discards) = (rw.discards.clone(), ro.discards.clone());
+        storage.attach("rw", "caching://", Box::from(rw), false).unwrap();
+        storage.attach("ro", "caching://", Box::from(ro), true).unwrap();
+
+        storage.discard_cache("rw:").unwrap();
+        storage.discard_cache("RW:/some/path").unwrap();
+        storage.discard_cache("ro:/").unwrap();
+        assert_eq!(2, rw_discards.get());
+        assert_eq!(1, ro_discards.get());
+
+        // Drives without a cache accept the request too.
+        storage.discard_cache("").unwrap();
+
+        assert_eq!(
+            "Drive 'X' is not mounted",
+            format!("{}", storage.discard_cache("x:").unwrap_err())
+        );
+    }
+
+    /// A drive whose contents cannot be modified.
+    #[derive(Default)]
+    struct ReadOnlyDrive(InMemoryDrive);
+
+    #[async_trait(?Send)]
+    impl Drive for ReadOnlyDrive {
+        async fn delete(&mut self, _name: &str) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Drive is read-only"))
+        }
+
+        async fn enumerate(&self) -> io::Result<DriveFiles> {
+            self.0.enumerate().await
+        }
+
+        async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+            self.0.get(name).await
+        }
+
+        async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+            Err(io::Error::new(io::ErrorKind::PermissionDenied, "Drive is read-only"))
+        }
+    }
+
+    #[test]
+    fn test_storage_copy_overwrite() {
+        let mut storage = Storage::default();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
+        block_on(storage.put("a.bas", b"first")).unwrap();
+        block_on(storage.put("other:/a.bas", b"second")).unwrap();
+
+        assert_eq!(
+            "Destination OTHER:a.bas already exists",
+            format!("{}", block_on(storage.copy("a.bas", "other:", false)).unwrap_err())
+        );
+        assert_eq!(b"second", block_on(storage.get("other:a.bas")).unwrap().as_slice());
+
+        block_on(storage.copy("a.bas", "other:", true)).unwrap();
+        assert_eq!(b"first", block_on(storage.get("other:a.bas")).unwrap().as_slice());
+
+        block_on(storage.copy("other:a.bas", "other:b.bas", false)).unwrap();
+        assert_eq!(b"first", block_on(storage.get("other:b.bas")).unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_storage_rename_across_drives() {
+        let mut storage = Storage::default();
+        block_on(storage.mount("other", "memory://", false)).unwrap();
+        block_on(storage.put("a.bas", b"content")).unwrap();
+
+        block_on(storage.rename("a.bas", "other:/")).unwrap();
+        assert!(block_on(storage.enumerate("memory:")).unwrap().dirents().is_empty());
+        assert_eq!(b"content", block_on(storage.get("other:a.bas")).unwrap().as_slice());
+
+        block_on(storage.put("a.bas", b"new")).unwrap();
+        assert_eq!(
+            "Destination MEMORY:a.bas already exists",
+            format!("{}", block_on(storage.rename("other:a.bas", "memory:a.bas")).unwrap_err())
+        );
+        assert_eq!(b"content", block_on(storage.get("other:a.bas")).unwrap().as_slice());
+        assert_eq!(b"new", block_on(storage.get("a.bas")).unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_storage_rename_rolls_back_on_failure() {
+        let mut ro = ReadOnlyDrive::default();
+        block_on(ro.0.put("ro.bas", b"read-only")).unwrap();
+
+        let mut storage = Storage::default();
+        storage.attach("ro", "ro://", Box::from(ro), false).unwrap();
+        block_on(storage.put("rw.bas", b"read-write")).unwrap();
+
+        assert_eq!(
+            "Drive is read-only",
+            format!("{}", block_on(storage.rename("ro:ro.bas", "memory:/")).unwrap_err())
+        );
+        assert_eq!(b"read-only", block_on(storage.get("ro:ro.bas")).unwrap().as_slice());
+        let files = block_on(storage.enumerate("memory:")).unwrap();
+        assert_eq!(["rw.bas"], files.dirents().keys().collect::<Vec<_>>().as_slice());
+
+        assert_eq!(
+            "Drive is read-only",
+            format!("{}", block_on(storage.rename("rw.bas", "ro:")).unwrap_err())
+        );
+        assert_eq!(b"read-write", block_on(storage.get("rw.bas")).unwrap().as_slice());
+        assert_eq!(1, block_on(storage.enumerate("ro:")).unwrap().dirents().len());
+    }
+
+    #[test]
+    fn test_storage_rename_errors() {
+        let mut storage = Storage::default();
+        assert_eq!(
+            "Entry not found",
+            format!("{}", block_on(storage.rename("missing.bas", "b.bas")).unwrap_err())
+        );
+        assert_eq!(
+            "Missing file name in path 'memory:'",
+            format!("{}", block_on(storage.rename("memory:", "b.bas")).unwrap_err())
+        );
+        assert_eq!(
+            "Drive 'FOO' is not mounted",
+            format!("{}", block_on(storage.rename("a.bas", "foo:b.bas")).unwrap_err())
+        );
+
+        block_on(storage.put("a.bas", b"")).unwrap();
+        block_on(storage.rename("a.bas", "A.bas")).unwrap();
+        block_on(storage.rename("A.bas", "A.bas")).unwrap();
+        assert_eq!(
+            "Entry not found",
+            format!("{}", block_on(storage.rename("b.bas", "b.bas")).unwrap_err())
+        );
+    }
+
     #[test]
     fn test_storage_delete_errors() {
         let mut storage = Storage::default();
@@ -1168,9 +1977,76 @@ mod tests {
             format!("{}", block_on(storage.enumerate("a:b\\c")).unwrap_err())
         );
         assert_eq!(
-            "Location 'a:/foo' is not a directory",
+            "Drive 'A' is not mounted",
             format!("{}", block_on(storage.enumerate("a:/foo")).unwrap_err())
         );
+        assert_eq!(
+            "Location 'memory:/foo' is not a directory",
+            format!("{}", block_on(storage.enumerate("memory:/foo")).unwrap_err())
+        );
+    }
+
+    #[test]
+    fn test_storage_metadata() {
+        let mut storage = Storage::default();
+        block_on(storage.put("file.bas", b"12345")).unwrap();
+        block_on(storage.mkdir("dir")).unwrap();
+        block_on(storage.put("dir/Other.bas", b"12")).unwrap();
+
+        let date = time::OffsetDateTime::from_unix_timestamp(1_588_757_875).unwrap();
+        assert_eq!(Metadata { date, length: 5 }, block_on(storage.metadata("file.bas")).unwrap());
+        assert_eq!(
+            Metadata { date, length: 2 },
+            block_on(storage.metadata("MEMORY:/dir/other.BAS")).unwrap()
+        );
+        storage.cd("dir").unwrap();
+        assert_eq!(Metadata { date, length: 2 }, block_on(storage.metadata("Other.bas")).unwrap());
+        assert_eq!(
+            Metadata { date, length: 5 },
+            block_on(storage.metadata("../file.bas")).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_storage_metadata_errors() {
+        let mut storage = Storage::default();
+        block_on(storage.mkdir("dir")).unwrap();
+        assert_eq!(
+            "Entry not found",
+            format!("{}", block_on(storage.metadata("missing.bas")).unwrap_err())
+        );
+        assert_eq!(
+            "Entry not found",
+            format!("{}", block_on(storage.metadata("dir")).unwrap_err())
+        );
+        assert_eq!(
+            "Directory of 'foo/bar.bas' does not exist",
+            format!("{}", block_on(storage.metadata("foo/bar.bas")).unwrap_err())
+        );
+        assert_eq!(
+            "Missing file name in path 'memory:'",
+            format!("{}", block_on(storage.metadata("memory:")).unwrap_err())
+        );
+    }
+
+    #[test]
+    fn test_storage_exists() {
+        let mut storage = Storage::default();
+        block_on(storage.put("file.bas", b"")).unwrap();
+        block_on(storage.mkdir("dir")).unwrap();
+        assert!(block_on(storage.exists("file.bas")).unwrap());
+        assert!(block_on(storage.exists("MEMORY:/FILE.BAS")).unwrap());
+        assert!(!block_on(storage.exists("missing.bas")).unwrap());
+        assert!(!block_on(storage.exists("dir")).unwrap());
+        assert!(!block_on(storage.exists("foo/bar.bas")).unwrap());
+        assert_eq!(
+            "Drive 'FOO' is not mounted",
+            format!("{}", block_on(storage.exists("foo:bar.bas")).unwrap_err())
+        );
+        assert_eq!(
+            "Invalid path 'a:b\\c'",
+            format!("{}", block_on(storage.exists("a:b\\c")).unwrap_err())
+        );
     }
 
     #[test]
@@ -1218,10 +2094,12 @@ mod tests {
                 "c",
                 &format!("file://{}", dir.display()),
                 Box::from(DirectoryDrive::new(dir.clone()).unwrap()),
+                false,
             )
             .unwrap();
 
         assert!(storage.system_path("memory:/foo").unwrap().is_none());
+        assert_eq!(dir.join("a/b"), storage.system_path("c:a/b").unwrap().unwrap());
         assert_eq!(dir.join("some name"), storage.system_path("c:/some name").unwrap().unwrap());
         assert_eq!(dir.join("xyz"), storage.system_path("c:xyz").unwrap().unwrap());
     }
@@ -1237,6 +2115,7 @@ mod tests {
                 "c",
                 &format!("file://{}", dir.display()),
                 Box::from(DirectoryDrive::new(dir.clone()).unwrap()),
+                false,
             )
             .unwrap();
 
@@ -1257,12 +2136,13 @@ mod tests {
                 "c",
                 &format!("file://{}", dir.display()),
                 Box::from(DirectoryDrive::new(dir).unwrap()),
+                false,
             )
             .unwrap();
 
         assert_eq!(
-            "Too many / separators in path 'c:a/b'",
-            format!("{}", storage.system_path("c:a/b").unwrap_err())
+            "Invalid path 'c:a//b'",
+            format!("{}", storage.system_path("c:a//b").unwrap_err())
         );
         assert_eq!("Invalid path 'c:..'", format!("{}", storage.system_path("c:..").unwrap_err()));
     }
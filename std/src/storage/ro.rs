@@ -0,0 +1,151 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Read-only adapter for arbitrary drives.
+
+use crate::storage::{Drive, DriveFiles, FileAcls};
+use async_trait::async_trait;
+use std::io;
+use std::path::PathBuf;
+
+/// Returns the error raised by all operations that would modify a read-only drive.
+fn read_only_error() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "Drive is mounted read-only")
+}
+
+/// A drive that wraps another drive and rejects all operations that would modify it.
+pub struct ReadOnlyDrive {
+    drive: Box<dyn Drive>,
+}
+
+impl ReadOnlyDrive {
+    /// Creates a new read-only view of `drive`.
+    pub fn new(drive: Box<dyn Drive>) -> Self {
+        Self { drive }
+    }
+}
+
+#[async_trait(?Send)]
+impl Drive for ReadOnlyDrive {
+    fn canonicalize_name(&self, name: &str) -> String {
+        self.drive.canonicalize_name(name)
+    }
+
+    async fn delete(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn discard_cache(&mut self) {
+        self.drive.discard_cache()
+    }
+
+    async fn enumerate(&self) -> io::Result<DriveFiles> {
+        self.drive.enumerate().await
+    }
+
+    async fn enumerate_dir(&self, dir: &str) -> io::Result<DriveFiles> {
+        self.drive.enumerate_dir(dir).await
+    }
+
+    async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        self.drive.get(name).await
+    }
+
+    async fn get_acls(&self, name: &str) -> io::Result<FileAcls> {
+        self.drive.get_acls(name).await
+    }
+
+    fn is_dir(&self, name: &str) -> bool {
+        self.drive.is_dir(name)
+    }
+
+    async fn mkdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rename(&mut self, _old: &str, _new: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rmdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn update_acls(
+        &mut self,
+        _name: &str,
+        _add: &FileAcls,
+        _remove: &FileAcls,
+    ) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    fn system_path(&self, name: &str) -> Option<PathBuf> {
+        self.drive.system_path(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::InMemoryDrive;
+    use futures_lite::future::block_on;
+
+    /// Creates a read-only drive backed by a memory drive with a file and a directory.
+    fn setup() -> ReadOnlyDrive {
+        let mut drive = InMemoryDrive::default();
+        block_on(drive.put("file.bas", b"content")).unwrap();
+        block_on(drive.mkdir("dir")).unwrap();
+        block_on(drive.put("dir/other.bas", b"more")).unwrap();
+        ReadOnlyDrive::new(Box::from(drive))
+    }
+
+    #[test]
+    fn test_read_only_drive_reads() {
+        let drive = setup();
+        assert_eq!(b"content", block_on(drive.get("file.bas")).unwrap().as_slice());
+        assert_eq!(b"more", block_on(drive.get("dir/other.bas")).unwrap().as_slice());
+        assert!(block_on(drive.enumerate()).unwrap().dirents().contains_key("file.bas"));
+        assert!(block_on(drive.enumerate_dir("dir")).unwrap().dirents().contains_key("other.bas"));
+        assert!(drive.is_dir("dir"));
+        assert!(!drive.is_dir("file.bas"));
+        assert!(drive.system_path("file.bas").is_none());
+    }
+
+    #[test]
+    fn test_read_only_drive_writes() {
+        let mut drive = setup();
+
+        let check = |r: io::Result<()>| {
+            let err = r.unwrap_err();
+            assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+            assert_eq!("Drive is mounted read-only", format!("{}", err));
+        };
+        check(block_on(drive.delete("file.bas")));
+        check(block_on(drive.mkdir("new")));
+        check(block_on(drive.put("file.bas", b"")));
+        check(block_on(drive.put("new.bas", b"")));
+        check(block_on(drive.rename("file.bas", "new.bas")));
+        check(block_on(drive.rmdir("dir")));
+        check(block_on(drive.update_acls("file.bas", &FileAcls::default(), &FileAcls::default())));
+
+        assert_eq!(b"content", block_on(drive.get("file.bas")).unwrap().as_slice());
+        assert_eq!(1, block_on(drive.enumerate()).unwrap().dirents().len());
+    }
+}
@@ -0,0 +1,736 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Read-only drive backed by a zip archive stored in another drive.
+
+use crate::storage::{DiskSpace, Drive, DriveFactory, DriveFiles, Metadata, Storage};
+use async_trait::async_trait;
+use flate2::read::DeflateDecoder;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::TryFrom;
+use std::io::{self, Read};
+
+/// Signature of the end of central directory record.
+const EOCD_SIGNATURE: u32 = 0x06054b50;
+
+/// Size of the end of central directory record without its trailing comment.
+const EOCD_SIZE: usize = 22;
+
+/// Signature of a central directory file header.
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x02014b50;
+
+/// Size of a central directory file header without its variable-length fields.
+const CENTRAL_HEADER_SIZE: usize = 46;
+
+/// Signature of a local file header.
+const LOCAL_HEADER_SIGNATURE: u32 = 0x04034b50;
+
+/// Size of a local file header without its variable-length fields.
+const LOCAL_HEADER_SIZE: usize = 30;
+
+/// Compression method for entries that are stored as is.
+const METHOD_STORED: u16 = 0;
+
+/// Compression method for entries that are compressed with deflate.
+const METHOD_DEFLATED: u16 = 8;
+
+/// General purpose flag that indicates that an entry is encrypted.
+const FLAG_ENCRYPTED: u16 = 0x0001;
+
+/// Constructs the error returned when the archive is malformed.
+fn corrupt_error<S: Into<String>>(message: S) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("Corrupt zip archive: {}", message.into()))
+}
+
+/// Constructs the error returned by all operations that would modify the drive.
+fn read_only_error() -> io::Error {
+    io::Error::new(io::ErrorKind::PermissionDenied, "Zip drives are read-only")
+}
+
+/// Constructs the error returned when a directory does not exist.
+fn dir_not_found() -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, "Directory not found")
+}
+
+/// Reads a little-endian 16-bit integer from `data` at `offset`, which must be in range.
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+/// Reads a little-endian 32-bit integer from `data` at `offset`, which must be in range.
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+}
+
+/// Converts an MS-DOS `date` and `time` pair, as stored in zip headers, to a timestamp.
+///
+/// Archives often carry bogus timestamps, so invalid values map to the start of the MS-DOS epoch
+/// instead of being treated as corruption.
+fn dos_datetime(date: u16, time: u16) -> time::OffsetDateTime {
+    let year = 1980 + i32::from(date >> 9);
+    let month = time::Month::try_from(((date >> 5) & 0x0f) as u8).unwrap_or(time::Month::January);
+    let day = (date & 0x1f) as u8;
+    let hour = (time >> 11) as u8;
+    let minute = ((time >> 5) & 0x3f) as u8;
+    let second = ((time & 0x1f) * 2) as u8;
+
+    let date = time::Date::from_calendar_date(year, month, day)
+        .unwrap_or_else(|_| time::Date::from_calendar_date(1980, time::Month::January, 1).unwrap());
+    let time = time::Time::from_hms(hour, minute, second).unwrap_or(time::Time::MIDNIGHT);
+    time::PrimitiveDateTime::new(date, time).assume_utc()
+}
+
+/// A file stored in the zip archive.
+struct Entry {
+    /// Name of the file as recorded in the archive, without its directory.
+    name: String,
+
+    /// Metadata of the uncompressed file.
+    metadata: Metadata,
+
+    /// Compression method used to store the file.
+    method: u16,
+
+    /// CRC-32 checksum of the uncompressed file.
+    crc: u32,
+
+    /// Offset of the compressed data within the archive.
+    offset: usize,
+
+    /// Length of the compressed data within the archive.
+    compressed_length: usize,
+}
+
+/// A directory in the zip archive.
+///
+/// Entries are keyed by their uppercase names so that lookups are case-insensitive.
+struct Directory {
+    /// Name of the directory as recorded in the archive, without its parent.
+    name: String,
+
+    /// Metadata of the directory.
+    metadata: Metadata,
+
+    /// Files in this directory.
+    files: HashMap<String, Entry>,
+
+    /// Subdirectories of this directory.
+    subdirs: HashMap<String, Directory>,
+}
+
+impl Directory {
+    /// Creates a new empty directory called `name` with a modification `date`.
+    fn new<S: Into<String>>(name: S, date: time::OffsetDateTime) -> Self {
+        Self {
+            name: name.into(),
+            metadata: Metadata { date, length: 0 },
+            files: HashMap::default(),
+            subdirs: HashMap::default(),
+        }
+    }
+
+    /// Returns the subdirectory `name` of this directory, creating it with a modification `date`
+    /// if it does not exist yet.
+    fn subdir_mut(&mut self, name: &str, date: time::OffsetDateTime) -> io::Result<&mut Directory> {
+        let key = name.to_uppercase();
+        if self.files.contains_key(&key) {
+            return Err(corrupt_error(format!("'{}' is both a file and a directory", name)));
+        }
+        Ok(self.subdirs.entry(key).or_insert_with(|| Directory::new(name, date)))
+    }
+}
+
+/// A read-only drive that exposes the contents of a zip archive.
+///
+/// The whole archive is held in memory and its central directory is validated upfront, so that a
+/// corrupt archive is detected when the drive is created and not when its files are accessed.
+pub struct ZipDrive {
+    /// Raw contents of the archive.
+    data: Vec<u8>,
+
+    /// Root directory of the archive.
+    root: Directory,
+
+    /// Total number of bytes of the uncompressed files.
+    bytes: u64,
+
+    /// Total number of files in the archive.
+    files: u64,
+}
+
+impl ZipDrive {
+    /// Creates a new drive from the raw contents of a zip archive in `data`.
+    pub fn new(data: Vec<u8>) -> io::Result<Self> {
+        let eocd = Self::find_eocd(&data)?;
+        let nentries = read_u16(&data, eocd + 10);
+        let cd_length = read_u32(&data, eocd + 12) as usize;
+        let cd_offset = read_u32(&data, eocd + 16) as usize;
+        if nentries == u16::MAX || cd_length == u32::MAX as usize || cd_offset == u32::MAX as usize
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "ZIP64 archives are not supported",
+            ));
+        }
+        match cd_offset.checked_add(cd_length) {
+            Some(end) if end <= eocd => (),
+            _ => return Err(corrupt_error("central directory out of bounds")),
+        }
+
+        let mut drive =
+            Self { data, root: Directory::new("", dos_datetime(0, 0)), bytes: 0, files: 0 };
+        let mut offset = cd_offset;
+        for _ in 0..nentries {
+            offset = drive.parse_central_header(offset, cd_offset + cd_length)?;
+        }
+        Ok(drive)
+    }
+
+    /// Locates the end of central directory record in `data` and returns its offset.
+    fn find_eocd(data: &[u8]) -> io::Result<usize> {
+        if data.len() < EOCD_SIZE {
+            return Err(corrupt_error("missing end of central directory"));
+        }
+        let last = data.len() - EOCD_SIZE;
+        let first = last.saturating_sub(u16::MAX as usize);
+        for offset in (first..=last).rev() {
+            if read_u32(data, offset) == EOCD_SIGNATURE
+                && offset + EOCD_SIZE + read_u16(data, offset + 20) as usize == data.len()
+            {
+                return Ok(offset);
+            }
+        }
+        Err(corrupt_error("missing end of central directory"))
+    }
+
+    /// Parses the central directory file header at `offset`, which must end before `limit`, and
+    /// records its entry.  Returns the offset of the next header.
+    fn parse_central_header(&mut self, offset: usize, limit: usize) -> io::Result<usize> {
+        let data = &self.data;
+        if offset + CENTRAL_HEADER_SIZE > limit
+            || read_u32(data, offset) != CENTRAL_HEADER_SIGNATURE
+        {
+            return Err(corrupt_error("bad central directory header"));
+        }
+        let flags = read_u16(data, offset + 8);
+        let method = read_u16(data, offset + 10);
+        let date = dos_datetime(read_u16(data, offset + 14), read_u16(data, offset + 12));
+        let crc = read_u32(data, offset + 16);
+        let compressed_length = read_u32(data, offset + 20) as usize;
+        let length = u64::from(read_u32(data, offset + 24));
+        let name_length = read_u16(data, offset + 28) as usize;
+        let extra_length = read_u16(data, offset + 30) as usize;
+        let comment_length = read_u16(data, offset + 32) as usize;
+        let local_offset = read_u32(data, offset + 42) as usize;
+
+        let next = offset + CENTRAL_HEADER_SIZE + name_length + extra_length + comment_length;
+        if next > limit {
+            return Err(corrupt_error("bad central directory header"));
+        }
+        let name_start = offset + CENTRAL_HEADER_SIZE;
+        let name = match std::str::from_utf8(&data[name_start..name_start + name_length]) {
+            Ok(name) => name.to_owned(),
+            Err(_) => return Err(corrupt_error("entry name is not valid UTF-8")),
+        };
+
+        if flags & FLAG_ENCRYPTED != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Cannot mount encrypted zip archive: entry '{}' is encrypted", name),
+            ));
+        }
+
+        if local_offset + LOCAL_HEADER_SIZE > data.len()
+            || read_u32(data, local_offset) != LOCAL_HEADER_SIGNATURE
+        {
+            return Err(corrupt_error(format!("bad local header for '{}'", name)));
+        }
+        let data_offset = local_offset
+            + LOCAL_HEADER_SIZE
+            + read_u16(data, local_offset + 26) as usize
+            + read_u16(data, local_offset + 28) as usize;
+        if data_offset + compressed_length > data.len() {
+            return Err(corrupt_error(format!("data for '{}' out of bounds", name)));
+        }
+
+        let (dir_name, is_dir) = match name.strip_suffix('/') {
+            Some(dir_name) => (dir_name, true),
+            None => (name.as_str(), false),
+        };
+        let mut components = vec![];
+        for component in dir_name.split('/') {
+            if component.is_empty()
+                || component == "."
+                || component == ".."
+                || component.contains([':', '\\'])
+            {
+                return Err(corrupt_error(format!("invalid entry name '{}'", name)));
+            }
+            components.push(component);
+        }
+
+        let mut dir = &mut self.root;
+        if is_dir {
+            for component in components {
+                dir = dir.subdir_mut(component, date)?;
+            }
+            dir.metadata.date = date;
+        } else {
+            let (leaf, parents) = components.split_last().expect("split always returns one item");
+            for component in parents {
+                dir = dir.subdir_mut(component, date)?;
+            }
+            let key = leaf.to_uppercase();
+            if dir.files.contains_key(&key) || dir.subdirs.contains_key(&key) {
+                return Err(corrupt_error(format!("duplicate entry '{}'", name)));
+            }
+            let entry = Entry {
+                name: (*leaf).to_owned(),
+                metadata: Metadata { date, length },
+                method,
+                crc,
+                offset: data_offset,
+                compressed_length,
+            };
+            dir.files.insert(key, entry);
+            self.bytes += length;
+            self.files += 1;
+        }
+
+        Ok(next)
+    }
+
+    /// Returns the directory at path `name`, if it exists.
+    fn dir(&self, name: &str) -> Option<&Directory> {
+        let mut dir = &self.root;
+        if !name.is_empty() {
+            for component in name.split('/') {
+                dir = dir.subdirs.get(&component.to_uppercase())?;
+            }
+        }
+        Some(dir)
+    }
+
+    /// Returns the file `name`, if it exists.
+    fn entry(&self, name: &str) -> Option<&Entry> {
+        let (parent, leaf) = name.rsplit_once('/').unwrap_or(("", name));
+        self.dir(parent)?.files.get(&leaf.to_uppercase())
+    }
+}
+
+#[async_trait(?Send)]
+impl Drive for ZipDrive {
+    async fn delete(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn enumerate(&self) -> io::Result<DriveFiles> {
+        self.enumerate_dir("").await
+    }
+
+    async fn enumerate_dir(&self, dir: &str) -> io::Result<DriveFiles> {
+        let dir = self.dir(dir).ok_or_else(dir_not_found)?;
+        let mut entries = BTreeMap::new();
+        for entry in dir.files.values() {
+            entries.insert(entry.name.clone(), entry.metadata.clone());
+        }
+        let mut subdirs = BTreeMap::new();
+        for subdir in dir.subdirs.values() {
+            subdirs.insert(subdir.name.clone(), subdir.metadata.clone());
+        }
+        let disk_quota = Some(DiskSpace::new(self.bytes, self.files));
+        let disk_free = Some(DiskSpace::new(0, 0));
+        Ok(DriveFiles::new(entries, disk_quota, disk_free).with_subdirs(subdirs))
+    }
+
+    async fn get(&self, name: &str) -> io::Result<Vec<u8>> {
+        let entry = match self.entry(name) {
+            Some(entry) => entry,
+            None => return Err(io::Error::new(io::ErrorKind::NotFound, "Entry not found")),
+        };
+
+        let raw = &self.data[entry.offset..entry.offset + entry.compressed_length];
+        let content = match entry.method {
+            METHOD_STORED => raw.to_owned(),
+            METHOD_DEFLATED => {
+                let mut content = Vec::with_capacity(entry.metadata.length as usize);
+                DeflateDecoder::new(raw)
+                    .read_to_end(&mut content)
+                    .map_err(|e| corrupt_error(format!("cannot inflate '{}': {}", name, e)))?;
+                content
+            }
+            method => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Unsupported compression method {} for '{}'", method, name),
+                ))
+            }
+        };
+
+        let mut crc = flate2::Crc::new();
+        crc.update(&content);
+        if content.len() as u64 != entry.metadata.length || crc.sum() != entry.crc {
+            return Err(corrupt_error(format!("checksum mismatch for '{}'", name)));
+        }
+        Ok(content)
+    }
+
+    fn is_dir(&self, name: &str) -> bool {
+        self.dir(name).is_some()
+    }
+
+    async fn mkdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn put(&mut self, _name: &str, _content: &[u8]) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rename(&mut self, _old: &str, _new: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+
+    async fn rmdir(&mut self, _name: &str) -> io::Result<()> {
+        Err(read_only_error())
+    }
+}
+
+/// Factory for zip drives.
+///
+/// Targets name the archive to open as an EndBASIC path, such as `LOCAL:/game.zip`.  Targets that
+/// start with a slash carry the drive name as their first component instead, which allows writing
+/// URIs like `zip:///LOCAL/game.zip`.
+#[derive(Default)]
+pub struct ZipDriveFactory {}
+
+#[async_trait(?Send)]
+impl DriveFactory for ZipDriveFactory {
+    async fn create(&self, target: &str, storage: &Storage) -> io::Result<Box<dyn Drive>> {
+        let location = match target.strip_prefix('/') {
+            Some(rest) => match rest.split_once('/') {
+                Some((drive, path)) if !drive.is_empty() && !path.is_empty() => {
+                    format!("{}:/{}", drive, path)
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Invalid zip archive location '{}'", target),
+                    ))
+                }
+            },
+            None if target.is_empty() => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Must specify the zip archive to mount",
+                ))
+            }
+            None => target.to_owned(),
+        };
+
+        let data = storage.get(&location).await?;
+        Ok(Box::from(ZipDrive::new(data)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use futures_lite::future::block_on;
+    use std::io::Write;
+
+    /// Builds a zip archive in memory with the given `(name, content, deflate)` entries.  Entries
+    /// with an empty content and whose name ends in a slash are directories.
+    fn build_zip(entries: &[(&str, &[u8], bool)], flags: u16) -> Vec<u8> {
+        let mut data = vec![];
+        let mut central = vec![];
+        for (name, content, deflate) in entries {
+            let (method, stored) = if *deflate {
+                let mut encoder = DeflateEncoder::new(vec![], Compression::default());
+                encoder.write_all(content).unwrap();
+                (METHOD_DEFLATED, encoder.finish().unwrap())
+            } else {
+                (METHOD_STORED, content.to_vec())
+            };
+            let mut crc = flate2::Crc::new();
+            crc.update(content);
+
+            // Common fields between the local and the central headers: version needed, flags,
+            // method, time, date, CRC, sizes, name length and extra length.
+            let mut common = vec![];
+            common.extend_from_slice(&20u16.to_le_bytes());
+            common.extend_from_slice(&flags.to_le_bytes());
+            common.extend_from_slice(&method.to_le_bytes());
+            common.extend_from_slice(&0x6000u16.to_le_bytes());
+            common.extend_from_slice(&0x5a8fu16.to_le_bytes());
+            common.extend_from_slice(&crc.sum().to_le_bytes());
+            common.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            common.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            common.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            common.extend_from_slice(&0u16.to_le_bytes());
+
+            let local_offset = data.len() as u32;
+            data.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&common);
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&stored);
+
+            central.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            central.extend_from_slice(&20u16.to_le_bytes());
+            central.extend_from_slice(&common);
+            central.extend_from_slice(&[0; 10]);
+            central.extend_from_slice(&local_offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+
+        let cd_offset = data.len() as u32;
+        data.extend_from_slice(&central);
+        data.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        data.extend_from_slice(&cd_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    /// Builds a zip archive with a few files and directories for testing.
+    fn fixture() -> Vec<u8> {
+        build_zip(
+            &[
+                ("Main.bas", b"PRINT \"main\"", false),
+                ("lib/", b"", false),
+                ("lib/util.bas", b"PRINT \"util\"\nPRINT \"util\"\nPRINT \"util\"", true),
+                ("assets/data/sprite.bin", &[1, 2, 3, 4], true),
+            ],
+            0,
+        )
+    }
+
+    /// Creates a storage with the zip archive `data` stored as `LOCAL:/game.zip`.
+    fn setup(data: &[u8]) -> Storage {
+        let mut storage = Storage::default();
+        block_on(storage.mount("local", "memory://", false)).unwrap();
+        block_on(storage.put("LOCAL:/game.zip", data)).unwrap();
+        storage
+    }
+
+    #[test]
+    fn test_dos_datetime() {
+        assert_eq!(
+            time::OffsetDateTime::from_unix_timestamp(1744718400).unwrap(),
+            dos_datetime(0x5a8f, 0x6000)
+        );
+        assert_eq!(
+            time::OffsetDateTime::from_unix_timestamp(315532800).unwrap(),
+            dos_datetime(0, 0)
+        );
+    }
+
+    #[test]
+    fn test_zip_drive_enumerate() {
+        let drive = ZipDrive::new(fixture()).unwrap();
+        let date = time::OffsetDateTime::from_unix_timestamp(1744718400).unwrap();
+
+        let files = block_on(drive.enumerate()).unwrap();
+        assert_eq!(
+            &BTreeMap::from([("Main.bas".to_owned(), Metadata { date, length: 12 })]),
+            files.dirents()
+        );
+        assert_eq!(
+            &BTreeMap::from([
+                ("assets".to_owned(), Metadata { date, length: 0 }),
+                ("lib".to_owned(), Metadata { date, length: 0 }),
+            ]),
+            files.subdirs()
+        );
+        assert_eq!(&Some(DiskSpace::new(54, 3)), files.disk_quota());
+        assert_eq!(&Some(DiskSpace::new(0, 0)), files.disk_free());
+
+        let files = block_on(drive.enumerate_dir("lib")).unwrap();
+        assert_eq!(
+            &BTreeMap::from([("util.bas".to_owned(), Metadata { date, length: 38 })]),
+            files.dirents()
+        );
+        assert!(files.subdirs().is_empty());
+
+        let files = block_on(drive.enumerate_dir("assets")).unwrap();
+        assert!(files.dirents().is_empty());
+        assert!(files.subdirs().contains_key("data"));
+
+        assert_eq!(
+            io::ErrorKind::NotFound,
+            block_on(drive.enumerate_dir("missing")).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn test_zip_drive_get() {
+        let drive = ZipDrive::new(fixture()).unwrap();
+        assert_eq!(b"PRINT \"main\"", block_on(drive.get("Main.bas")).unwrap().as_slice());
+        assert_eq!(
+            b"PRINT \"util\"\nPRINT \"util\"\nPRINT \"util\"",
+            block_on(drive.get("lib/util.bas")).unwrap().as_slice()
+        );
+        assert_eq!(
+            &[1, 2, 3, 4],
+            block_on(drive.get("assets/data/sprite.bin")).unwrap().as_slice()
+        );
+
+        let err = block_on(drive.get("missing.bas")).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+        let err = block_on(drive.get("lib")).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+    }
+
+    #[test]
+    fn test_zip_drive_case_insensitive() {
+        let drive = ZipDrive::new(fixture()).unwrap();
+        assert_eq!(b"PRINT \"main\"", block_on(drive.get("MAIN.BAS")).unwrap().as_slice());
+        assert_eq!(b"PRINT \"main\"", block_on(drive.get("main.bas")).unwrap().as_slice());
+        assert!(block_on(drive.get("LIB/UTIL.BAS")).is_ok());
+        assert!(drive.is_dir("Lib"));
+        assert!(drive.is_dir("ASSETS/Data"));
+        assert!(!drive.is_dir("main.bas"));
+        assert!(block_on(drive.enumerate_dir("LIB")).unwrap().dirents().contains_key("util.bas"));
+    }
+
+    #[test]
+    fn test_zip_drive_writes() {
+        let mut drive = ZipDrive::new(fixture()).unwrap();
+
+        let check = |r: io::Result<()>| {
+            let err = r.unwrap_err();
+            assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+            assert_eq!("Zip drives are read-only", format!("{}", err));
+        };
+        check(block_on(drive.delete("Main.bas")));
+        check(block_on(drive.mkdir("new")));
+        check(block_on(drive.put("Main.bas", b"")));
+        check(block_on(drive.put("new.bas", b"")));
+        check(block_on(drive.rename("Main.bas", "new.bas")));
+        check(block_on(drive.rmdir("lib")));
+
+        assert_eq!(b"PRINT \"main\"", block_on(drive.get("Main.bas")).unwrap().as_slice());
+    }
+
+    #[test]
+    fn test_zip_drive_empty_archive() {
+        let drive = ZipDrive::new(build_zip(&[], 0)).unwrap();
+        let files = block_on(drive.enumerate()).unwrap();
+        assert!(files.dirents().is_empty());
+        assert!(files.subdirs().is_empty());
+    }
+
+    #[test]
+    fn test_zip_drive_encrypted() {
+        let err = ZipDrive::new(build_zip(&[("secret.bas", b"x", false)], FLAG_ENCRYPTED))
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!(
+            "Cannot mount encrypted zip archive: entry 'secret.bas' is encrypted",
+            format!("{}", err)
+        );
+    }
+
+    #[test]
+    fn test_zip_drive_corrupt() {
+        let check = |exp_error: &str, data: Vec<u8>| {
+            let err = ZipDrive::new(data).map(|_| ()).unwrap_err();
+            assert_eq!(io::ErrorKind::InvalidData, err.kind());
+            assert_eq!(exp_error, format!("{}", err));
+        };
+
+        check("Corrupt zip archive: missing end of central directory", vec![]);
+        check("Corrupt zip archive: missing end of central directory", b"not a zip file".to_vec());
+
+        let mut data = fixture();
+        data.truncate(data.len() - 1);
+        check("Corrupt zip archive: missing end of central directory", data);
+
+        let mut data = fixture();
+        let eocd = ZipDrive::find_eocd(&data).unwrap();
+        data[eocd + 16] = 0xf0;
+        check("Corrupt zip archive: central directory out of bounds", data);
+
+        let mut data = fixture();
+        let eocd = ZipDrive::find_eocd(&data).unwrap();
+        let cd_offset = read_u32(&data, eocd + 16) as usize;
+        data[cd_offset] = b'X';
+        check("Corrupt zip archive: bad central directory header", data);
+
+        let mut data = fixture();
+        data[0] = b'X';
+        check("Corrupt zip archive: bad local header for 'Main.bas'", data);
+
+        check(
+            "Corrupt zip archive: duplicate entry 'A.BAS'",
+            build_zip(&[("a.bas", b"", false), ("A.BAS", b"", false)], 0),
+        );
+        check(
+            "Corrupt zip archive: invalid entry name '../a.bas'",
+            build_zip(&[("../a.bas", b"", false)], 0),
+        );
+    }
+
+    #[test]
+    fn test_zip_drive_bad_checksum() {
+        let mut data = build_zip(&[("a.bas", b"abc", false)], 0);
+        data[LOCAL_HEADER_SIZE + "a.bas".len()] = b'x';
+        let drive = ZipDrive::new(data).unwrap();
+        let err = block_on(drive.get("a.bas")).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert_eq!("Corrupt zip archive: checksum mismatch for 'a.bas'", format!("{}", err));
+    }
+
+    #[test]
+    fn test_zip_drive_factory_mount() {
+        let mut storage = setup(&fixture());
+        block_on(storage.mount("pkg", "zip:///LOCAL/game.zip", false)).unwrap();
+        block_on(storage.mount("pkg2", "zip://local:/game.zip", false)).unwrap();
+
+        assert_eq!(b"PRINT \"main\"", block_on(storage.get("PKG:/MAIN.BAS")).unwrap().as_slice());
+        assert_eq!(b"PRINT \"main\"", block_on(storage.get("PKG2:/main.bas")).unwrap().as_slice());
+        assert!(block_on(storage.get("PKG:/lib/util.bas")).is_ok());
+
+        let err = block_on(storage.put("PKG:/new.bas", b"")).unwrap_err();
+        assert_eq!(io::ErrorKind::PermissionDenied, err.kind());
+    }
+
+    #[test]
+    fn test_zip_drive_factory_errors() {
+        let mut storage = setup(b"garbage");
+
+        let err = block_on(storage.mount("pkg", "zip://", false)).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("Must specify the zip archive to mount", format!("{}", err));
+
+        let err = block_on(storage.mount("pkg", "zip:///LOCAL", false)).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("Invalid zip archive location '/LOCAL'", format!("{}", err));
+
+        let err = block_on(storage.mount("pkg", "zip:///LOCAL/missing.zip", false)).unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+
+        let err = block_on(storage.mount("pkg", "zip:///LOCAL/game.zip", false)).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+
+        assert!(!storage.mounted().contains_key("PKG"));
+    }
+}
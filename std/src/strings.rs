@@ -21,6 +21,7 @@ use endbasic_core::compiler::{
     AnyValueSyntax, ArgSepSyntax, RequiredValueSyntax, SingularArgSyntax,
 };
 use endbasic_core::exec::{Error, Machine, Result, Scope, ValueTag};
+use endbasic_core::simple::{SimpleCallable, SimpleError};
 use endbasic_core::syms::{Callable, CallableMetadata, CallableMetadataBuilder};
 use std::borrow::Cow;
 use std::cmp::min;
@@ -205,147 +206,45 @@ impl Callable for ChrFunction {
     }
 }
 
-/// The `LEFT` function.
-pub struct LeftFunction {
-    metadata: CallableMetadata,
-}
-
-impl LeftFunction {
-    /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
-        Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("LEFT")
-                .with_return_type(ExprType::Text)
-                .with_syntax(&[(
-                    &[
-                        SingularArgSyntax::RequiredValue(
-                            RequiredValueSyntax {
-                                name: Cow::Borrowed("expr"),
-                                vtype: ExprType::Text,
-                            },
-                            ArgSepSyntax::Exactly(ArgSep::Long),
-                        ),
-                        SingularArgSyntax::RequiredValue(
-                            RequiredValueSyntax {
-                                name: Cow::Borrowed("n"),
-                                vtype: ExprType::Integer,
-                            },
-                            ArgSepSyntax::End,
-                        ),
-                    ],
-                    None,
-                )])
-                .with_category(CATEGORY)
-                .with_description(
-                    "Returns a given number of characters from the left side of a string.
+/// Creates the `LEFT` function.
+pub fn left_function() -> Rc<SimpleCallable> {
+    SimpleCallable::function("LEFT")
+        .with_category(CATEGORY)
+        .with_description(
+            "Returns a given number of characters from the left side of a string.
 If n% is 0, returns an empty string.
 If n% is greater than or equal to the number of characters in expr$, returns expr$.",
-                )
-                .build(),
-        })
-    }
-}
-
-#[async_trait(?Send)]
-impl Callable for LeftFunction {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
-
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(2, scope.nargs());
-        let s = scope.pop_string();
-        let (n, npos) = scope.pop_integer_with_pos();
-
-        if n < 0 {
-            Err(Error::SyntaxError(npos, "n% cannot be negative".to_owned()))
-        } else {
-            let n = min(s.len(), n as usize);
-            scope.return_string(s[..n].to_owned())
-        }
-    }
-}
-
-/// The `LEN` function.
-pub struct LenFunction {
-    metadata: CallableMetadata,
-}
-
-impl LenFunction {
-    /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
-        Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("LEN")
-                .with_return_type(ExprType::Integer)
-                .with_syntax(&[(
-                    &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax { name: Cow::Borrowed("expr"), vtype: ExprType::Text },
-                        ArgSepSyntax::End,
-                    )],
-                    None,
-                )])
-                .with_category(CATEGORY)
-                .with_description("Returns the length of the string in expr$.")
-                .build(),
+        )
+        .arg("expr", ExprType::Text)
+        .arg("n", ExprType::Integer)
+        .handler(|args, _machine| {
+            let s = args.text(0);
+            match usize::try_from(args.integer(1)) {
+                Ok(n) => Ok(s[..min(s.len(), n)].to_owned()),
+                Err(_) => Err(SimpleError::Argument(1, "n% cannot be negative".to_owned())),
+            }
         })
-    }
 }
 
-#[async_trait(?Send)]
-impl Callable for LenFunction {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
-
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(1, scope.nargs());
-        let (s, spos) = scope.pop_string_with_pos();
-
-        if s.len() > i32::MAX as usize {
-            Err(Error::InternalError(spos, "String too long".to_owned()))
-        } else {
-            scope.return_integer(s.len() as i32)
-        }
-    }
-}
-
-/// The `LTRIM` function.
-pub struct LtrimFunction {
-    metadata: CallableMetadata,
-}
-
-impl LtrimFunction {
-    /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
-        Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("LTRIM")
-                .with_return_type(ExprType::Text)
-                .with_syntax(&[(
-                    &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax { name: Cow::Borrowed("expr"), vtype: ExprType::Text },
-                        ArgSepSyntax::End,
-                    )],
-                    None,
-                )])
-                .with_category(CATEGORY)
-                .with_description("Returns a copy of a string with leading whitespace removed.")
-                .build(),
+/// Creates the `LEN` function.
+pub fn len_function() -> Rc<SimpleCallable> {
+    SimpleCallable::function("LEN")
+        .with_category(CATEGORY)
+        .with_description("Returns the length of the string in expr$.")
+        .arg("expr", ExprType::Text)
+        .handler(|args, _machine| {
+            i32::try_from(args.text(0).len())
+                .map_err(|_| SimpleError::Internal("String too long".to_owned()))
         })
-    }
 }
 
-#[async_trait(?Send)]
-impl Callable for LtrimFunction {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
-
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(1, scope.nargs());
-        let s = scope.pop_string();
-
-        scope.return_string(s.trim_start().to_owned())
-    }
+/// Creates the `LTRIM` function.
+pub fn ltrim_function() -> Rc<SimpleCallable> {
+    SimpleCallable::function("LTRIM")
+        .with_category(CATEGORY)
+        .with_description("Returns a copy of a string with leading whitespace removed.")
+        .arg("expr", ExprType::Text)
+        .handler(|args, _machine| Ok(args.text(0).trim_start().to_owned()))
 }
 
 /// The `MID` function.
@@ -411,7 +310,8 @@ impl MidFunction {
                     "Returns a portion of a string.
 start% indicates the starting position of the substring to extract and it is 1-indexed.
 length% indicates the number of characters to extract and, if not specified, defaults to extracting
-until the end of the string.",
+until the end of the string.
+To modify a portion of a string variable in place, use the MID$ statement instead; see HELP \"VARIABLES\".",
                 )
                 .build(),
         })
@@ -449,104 +349,33 @@ impl Callable for MidFunction {
     }
 }
 
-/// The `RIGHT` function.
-pub struct RightFunction {
-    metadata: CallableMetadata,
-}
-
-impl RightFunction {
-    /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
-        Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RIGHT")
-                .with_return_type(ExprType::Text)
-                .with_syntax(&[(
-                    &[
-                        SingularArgSyntax::RequiredValue(
-                            RequiredValueSyntax {
-                                name: Cow::Borrowed("expr"),
-                                vtype: ExprType::Text,
-                            },
-                            ArgSepSyntax::Exactly(ArgSep::Long),
-                        ),
-                        SingularArgSyntax::RequiredValue(
-                            RequiredValueSyntax {
-                                name: Cow::Borrowed("n"),
-                                vtype: ExprType::Integer,
-                            },
-                            ArgSepSyntax::End,
-                        ),
-                    ],
-                    None,
-                )])
-                .with_category(CATEGORY)
-                .with_description(
-                    "Returns a given number of characters from the right side of a string.
+/// Creates the `RIGHT` function.
+pub fn right_function() -> Rc<SimpleCallable> {
+    SimpleCallable::function("RIGHT")
+        .with_category(CATEGORY)
+        .with_description(
+            "Returns a given number of characters from the right side of a string.
 If n% is 0, returns an empty string.
 If n% is greater than or equal to the number of characters in expr$, returns expr$.",
-                )
-                .build(),
-        })
-    }
-}
-
-#[async_trait(?Send)]
-impl Callable for RightFunction {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
-
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(2, scope.nargs());
-        let s = scope.pop_string();
-        let (n, npos) = scope.pop_integer_with_pos();
-
-        if n < 0 {
-            Err(Error::SyntaxError(npos, "n% cannot be negative".to_owned()))
-        } else {
-            let n = min(s.len(), n as usize);
-            scope.return_string(s[s.len() - n..].to_owned())
-        }
-    }
-}
-
-/// The `RTRIM` function.
-pub struct RtrimFunction {
-    metadata: CallableMetadata,
-}
-
-impl RtrimFunction {
-    /// Creates a new instance of the function.
-    pub fn new() -> Rc<Self> {
-        Rc::from(Self {
-            metadata: CallableMetadataBuilder::new("RTRIM")
-                .with_return_type(ExprType::Text)
-                .with_syntax(&[(
-                    &[SingularArgSyntax::RequiredValue(
-                        RequiredValueSyntax { name: Cow::Borrowed("expr"), vtype: ExprType::Text },
-                        ArgSepSyntax::End,
-                    )],
-                    None,
-                )])
-                .with_category(CATEGORY)
-                .with_description("Returns a copy of a string with trailing whitespace removed.")
-                .build(),
+        )
+        .arg("expr", ExprType::Text)
+        .arg("n", ExprType::Integer)
+        .handler(|args, _machine| {
+            let s = args.text(0);
+            match usize::try_from(args.integer(1)) {
+                Ok(n) => Ok(s[s.len() - min(s.len(), n)..].to_owned()),
+                Err(_) => Err(SimpleError::Argument(1, "n% cannot be negative".to_owned())),
+            }
         })
-    }
 }
 
-#[async_trait(?Send)]
-impl Callable for RtrimFunction {
-    fn metadata(&self) -> &CallableMetadata {
-        &self.metadata
-    }
-
-    async fn exec(&self, mut scope: Scope<'_>, _machine: &mut Machine) -> Result<()> {
-        debug_assert_eq!(1, scope.nargs());
-        let s = scope.pop_string();
-
-        scope.return_string(s.trim_end().to_owned())
-    }
+/// Creates the `RTRIM` function.
+pub fn rtrim_function() -> Rc<SimpleCallable> {
+    SimpleCallable::function("RTRIM")
+        .with_category(CATEGORY)
+        .with_description("Returns a copy of a string with trailing whitespace removed.")
+        .arg("expr", ExprType::Text)
+        .handler(|args, _machine| Ok(args.text(0).trim_end().to_owned()))
 }
 
 /// The `STR` function.
@@ -619,12 +448,12 @@ impl Callable for StrFunction {
 pub fn add_all(machine: &mut Machine) {
     machine.add_callable(AscFunction::new());
     machine.add_callable(ChrFunction::new());
-    machine.add_callable(LeftFunction::new());
-    machine.add_callable(LenFunction::new());
-    machine.add_callable(LtrimFunction::new());
+    machine.add_callable(left_function());
+    machine.add_callable(len_function());
+    machine.add_callable(ltrim_function());
     machine.add_callable(MidFunction::new());
-    machine.add_callable(RightFunction::new());
-    machine.add_callable(RtrimFunction::new());
+    machine.add_callable(right_function());
+    machine.add_callable(rtrim_function());
     machine.add_callable(StrFunction::new());
 }
 
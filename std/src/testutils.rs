@@ -16,8 +16,10 @@
 //! Test utilities for consumers of the EndBASIC interpreter.
 
 use crate::console::{
-    self, remove_control_chars, CharsXY, ClearType, Console, Key, PixelsXY, SizeInPixels,
+    self, remove_control_chars, CharsXY, ClearType, ClipRect, Console, Key, PixelsXY, ScreenCell,
+    ScreenRotation, ShadowScreen, SizeInPixels, TextRotation,
 };
+use crate::exec::{NowFn, SleepFn};
 use crate::gpio;
 use crate::program::Program;
 use crate::storage::Storage;
@@ -25,13 +27,17 @@ use async_trait::async_trait;
 use endbasic_core::ast::{ExprType, Value, VarRef};
 use endbasic_core::exec::{self, Machine, StopReason};
 use endbasic_core::syms::{Array, Callable, Symbol, SymbolKey};
-use futures_lite::future::block_on;
-use std::cell::RefCell;
+use endbasic_core::LineCol;
+use futures_lite::future::{block_on, BoxedLocal, FutureExt};
+use std::cell::{Cell, RefCell};
 use std::collections::{HashMap, VecDeque};
+use std::convert::TryFrom;
 use std::io;
 use std::rc::Rc;
 use std::result::Result;
 use std::str;
+use std::time::Duration;
+use time::OffsetDateTime;
 
 /// A captured command or messages sent to the mock console.
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -84,6 +90,27 @@ pub enum CapturedOut {
     /// Represents a call to `Console::draw_rect_filled`.
     DrawRectFilled(PixelsXY, PixelsXY),
 
+    /// Represents a call to `Console::draw_text`.
+    DrawText(PixelsXY, String, u16, TextRotation),
+
+    /// Represents a call to `Console::copy_pixels`.
+    CopyPixels(PixelsXY, SizeInPixels, PixelsXY),
+
+    /// Represents a call to `Console::scroll_pixels`.
+    ScrollPixels(i16, i16),
+
+    /// Represents a call to `Console::set_brightness`.
+    SetBrightness(u8),
+
+    /// Represents a call to `Console::set_clip`.
+    SetClip(Option<ClipRect>),
+
+    /// Represents a call to `Console::set_rotation`.
+    SetRotation(ScreenRotation),
+
+    /// Represents a call to `Console::set_text_font`.
+    SetTextFont(String),
+
     /// Represents a call to `Console::sync_now`.
     SyncNow,
 
@@ -107,6 +134,9 @@ pub struct MockConsole {
 
     /// Whether the console is interactive or not.
     interactive: bool,
+
+    /// Copy of the characters displayed on the mock text console.
+    shadow: ShadowScreen,
 }
 
 impl Default for MockConsole {
@@ -117,6 +147,7 @@ impl Default for MockConsole {
             size_chars: CharsXY::new(u16::MAX, u16::MAX),
             size_pixels: None,
             interactive: false,
+            shadow: ShadowScreen::new(CharsXY::new(u16::MAX, u16::MAX)),
         }
     }
 }
@@ -157,6 +188,7 @@ impl MockConsole {
     /// Sets the size of the mock text console.
     pub fn set_size_chars(&mut self, size: CharsXY) {
         self.size_chars = size;
+        self.shadow.resize(size);
     }
 
     /// Sets the size of the mock graphical console.
@@ -183,6 +215,7 @@ impl Drop for MockConsole {
 #[async_trait(?Send)]
 impl Console for MockConsole {
     fn clear(&mut self, how: ClearType) -> io::Result<()> {
+        self.shadow.clear(how);
         self.captured_out.push(CapturedOut::Clear(how));
         Ok(())
     }
@@ -197,11 +230,13 @@ impl Console for MockConsole {
     }
 
     fn set_color(&mut self, fg: Option<u8>, bg: Option<u8>) -> io::Result<()> {
+        self.shadow.set_color(fg, bg);
         self.captured_out.push(CapturedOut::SetColor(fg, bg));
         Ok(())
     }
 
     fn enter_alt(&mut self) -> io::Result<()> {
+        self.shadow.enter_alt();
         self.captured_out.push(CapturedOut::EnterAlt);
         Ok(())
     }
@@ -216,6 +251,7 @@ impl Console for MockConsole {
     }
 
     fn leave_alt(&mut self) -> io::Result<()> {
+        self.shadow.leave_alt();
         self.captured_out.push(CapturedOut::LeaveAlt);
         Ok(())
     }
@@ -223,11 +259,13 @@ impl Console for MockConsole {
     fn locate(&mut self, pos: CharsXY) -> io::Result<()> {
         assert!(pos.x < self.size_chars.x);
         assert!(pos.y < self.size_chars.y);
+        self.shadow.locate(pos);
         self.captured_out.push(CapturedOut::Locate(pos));
         Ok(())
     }
 
     fn move_within_line(&mut self, off: i16) -> io::Result<()> {
+        self.shadow.move_within_line(off);
         self.captured_out.push(CapturedOut::MoveWithinLine(off));
         Ok(())
     }
@@ -235,6 +273,7 @@ impl Console for MockConsole {
     fn print(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text.to_owned());
 
+        self.shadow.print(&text);
         self.captured_out.push(CapturedOut::Print(text));
         Ok(())
     }
@@ -272,10 +311,19 @@ impl Console for MockConsole {
     fn write(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text.to_owned());
 
+        self.shadow.write(&text);
         self.captured_out.push(CapturedOut::Write(text));
         Ok(())
     }
 
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        Ok(self.shadow.contents())
+    }
+
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        Ok(self.shadow.cursor())
+    }
+
     fn draw_circle(&mut self, xy: PixelsXY, r: u16) -> io::Result<()> {
         self.captured_out.push(CapturedOut::DrawCircle(xy, r));
         Ok(())
@@ -306,6 +354,52 @@ impl Console for MockConsole {
         Ok(())
     }
 
+    fn draw_text(
+        &mut self,
+        xy: PixelsXY,
+        text: &str,
+        scale: u16,
+        rotation: TextRotation,
+    ) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::DrawText(xy, text.to_owned(), scale, rotation));
+        Ok(())
+    }
+
+    fn copy_pixels(&mut self, src: PixelsXY, size: SizeInPixels, dst: PixelsXY) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::CopyPixels(src, size, dst));
+        Ok(())
+    }
+
+    fn scroll_pixels(&mut self, dx: i16, dy: i16) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::ScrollPixels(dx, dy));
+        Ok(())
+    }
+
+    fn set_clip(&mut self, clip: Option<ClipRect>) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::SetClip(clip));
+        Ok(())
+    }
+
+    fn set_brightness(&mut self, pct: u8) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::SetBrightness(pct));
+        Ok(())
+    }
+
+    fn set_rotation(&mut self, rotation: ScreenRotation) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::SetRotation(rotation));
+        Ok(())
+    }
+
+    fn set_text_font(&mut self, name: &str) -> io::Result<()> {
+        self.captured_out.push(CapturedOut::SetTextFont(name.to_owned()));
+        Ok(())
+    }
+
+    /// Computes the width of `text` assuming a fixed-width font of 8 pixels per character.
+    fn text_width(&self, text: &str) -> io::Result<u16> {
+        Ok(u16::try_from(text.chars().count() * 8).unwrap_or(u16::MAX))
+    }
+
     fn sync_now(&mut self) -> io::Result<()> {
         self.captured_out.push(CapturedOut::SyncNow);
         Ok(())
@@ -376,18 +470,103 @@ impl Program for RecordedProgram {
         self.dirty = false;
     }
 
+    fn set_text(&mut self, text: &str) {
+        text.clone_into(&mut self.content);
+        self.dirty = true;
+    }
+
     fn text(&self) -> String {
         self.content.clone()
     }
 }
 
+/// A fake clock that only moves forward when told to.
+///
+/// Copies of a clock share the same time, so a test can keep a copy to inspect or to advance the
+/// time seen by the machine that uses another copy.  Sleeps requested by the machine, such as those
+/// issued by `SLEEP` or `WAITFRAME`, advance the clock instead of pausing so that programs that wait
+/// for `TIMER` to reach a value terminate immediately in tests:
+///
+/// ```
+/// use endbasic_std::testutils::{MockClock, Tester};
+/// use std::time::Duration;
+///
+/// let clock = MockClock::default();
+/// let mut t = Tester::default().with_clock(clock.clone());
+/// t.run("WHILE TIMER < 10: SLEEP 3: WEND: t = TIMER")
+///     .expect_var("t", 12.0)
+///     .expect_elapsed(Duration::from_secs(12))
+///     .check();
+/// assert_eq!(Duration::from_secs(12), clock.elapsed());
+/// ```
+#[derive(Clone)]
+pub struct MockClock {
+    start: OffsetDateTime,
+    now: Rc<Cell<OffsetDateTime>>,
+}
+
+impl Default for MockClock {
+    /// Creates a new clock that starts at an arbitrary but fixed time.
+    fn default() -> Self {
+        Self::new(OffsetDateTime::from_unix_timestamp(1_000_000).unwrap())
+    }
+}
+
+impl MockClock {
+    /// Creates a new clock that starts at `start`.
+    pub fn new(start: OffsetDateTime) -> Self {
+        Self { start, now: Rc::from(Cell::new(start)) }
+    }
+
+    /// Returns the current time of the clock.
+    pub fn now(&self) -> OffsetDateTime {
+        self.now.get()
+    }
+
+    /// Moves the clock forward by `d`.
+    pub fn advance(&self, d: Duration) {
+        self.now.set(self.now.get() + d);
+    }
+
+    /// Returns how much the clock has moved forward since it was created.
+    pub fn elapsed(&self) -> Duration {
+        Duration::try_from(self.now.get() - self.start).expect("Clock never goes backwards")
+    }
+
+    /// Returns a function to query the current time of this clock, to be used in place of the
+    /// system clock.
+    pub fn now_fn(&self) -> NowFn {
+        let now = self.now.clone();
+        Box::from(move || now.get())
+    }
+
+    /// Returns a function that advances this clock by the requested duration, to be used in place
+    /// of the system sleep.
+    pub fn sleep_fn(&self) -> SleepFn {
+        let clock = self.clone();
+        Box::from(move |d: Duration, _pos: LineCol| -> BoxedLocal<exec::Result<()>> {
+            clock.advance(d);
+            async move { Ok(()) }.boxed_local()
+        })
+    }
+}
+
 /// Builder pattern to prepare an EndBASIC machine for testing purposes.
 #[must_use]
 pub struct Tester {
     console: Rc<RefCell<MockConsole>>,
     storage: Rc<RefCell<Storage>>,
     program: Rc<RefCell<RecordedProgram>>,
+    clock: Option<MockClock>,
+    rng_seed: Option<i32>,
     machine: Machine,
+
+    /// Whether the machine is fully-equipped and thus has callables that depend on the clock and
+    /// on the random number generator.
+    interactive: bool,
+
+    /// Whether the machine or its storage have been modified since the tester was created.
+    modified: bool,
 }
 
 impl Default for Tester {
@@ -395,7 +574,20 @@ impl Default for Tester {
     fn default() -> Self {
         let console = Rc::from(RefCell::from(MockConsole::default()));
         let program = Rc::from(RefCell::from(RecordedProgram::default()));
+        Self::new_interactive(console, program, None, None)
+    }
+}
 
+impl Tester {
+    /// Creates a new tester for a fully-equipped (interactive) machine attached to `console` and
+    /// `program`, which uses `clock` (if any) in place of the system clock and seeds the random
+    /// number generator with `rng_seed` (if any).
+    fn new_interactive(
+        console: Rc<RefCell<MockConsole>>,
+        program: Rc<RefCell<RecordedProgram>>,
+        clock: Option<MockClock>,
+        rng_seed: Option<i32>,
+    ) -> Self {
         // Default to the pins set that always returns errors.  We could have implemented a set of
         // fake pins here to track GPIO state changes in a nicer way, similar to how we track all
         // other machine state... but the GPIO module already implements its own mocking feature.
@@ -405,9 +597,14 @@ impl Default for Tester {
 
         let mut builder = crate::MachineBuilder::default()
             .with_console(console.clone())
-            .with_gpio_pins(gpio_pins)
-            .make_interactive()
-            .with_program(program.clone());
+            .with_gpio_pins(gpio_pins);
+        if let Some(clock) = clock.as_ref() {
+            builder = builder.with_now_fn(clock.now_fn()).with_sleep_fn(clock.sleep_fn());
+        }
+        if let Some(seed) = rng_seed {
+            builder = builder.with_rng_seed(seed);
+        }
+        let mut builder = builder.make_interactive().with_program(program.clone());
 
         // Grab access to the machine's storage subsystem before we lose track of it, as we will
         // need this to check its state.
@@ -415,11 +612,18 @@ impl Default for Tester {
 
         let machine = builder.build().unwrap();
 
-        Self { console, storage, program, machine }
+        Self {
+            console,
+            storage,
+            program,
+            clock,
+            rng_seed,
+            machine,
+            interactive: true,
+            modified: false,
+        }
     }
-}
 
-impl Tester {
     /// Creates a new tester with an empty `Machine`.
     pub fn empty() -> Self {
         let console = Rc::from(RefCell::from(MockConsole::default()));
@@ -428,11 +632,52 @@ impl Tester {
 
         let machine = Machine::default();
 
-        Self { console, storage, program, machine }
+        Self {
+            console,
+            storage,
+            program,
+            clock: None,
+            rng_seed: None,
+            machine,
+            interactive: false,
+            modified: false,
+        }
+    }
+
+    /// Makes the machine use `clock` in place of the system clock, which affects the callables
+    /// that query the time, like `TIMER` or `NOW`, and the callables that sleep, like `SLEEP`.
+    ///
+    /// For a fully-equipped machine, this must be called before modifying the machine or its
+    /// storage.  For an empty machine, the clock is only used to check the elapsed time, and the
+    /// callables added to the machine must be given the clock by the caller.
+    pub fn with_clock(mut self, clock: MockClock) -> Self {
+        self.clock = Some(clock);
+        self.rebuild()
+    }
+
+    /// Seeds the random number generator of the machine with `seed` so that functions like `RND`
+    /// return a reproducible sequence of numbers.
+    ///
+    /// For a fully-equipped machine, this must be called before modifying the machine or its
+    /// storage.  This has no effect on an empty machine.
+    pub fn with_rng_seed(mut self, seed: i32) -> Self {
+        self.rng_seed = Some(seed);
+        self.rebuild()
+    }
+
+    /// Recreates a fully-equipped machine so that it picks up the configured clock and random
+    /// number generator seed.  The console and the stored program are preserved.
+    fn rebuild(self) -> Self {
+        if !self.interactive {
+            return self;
+        }
+        assert!(!self.modified, "The clock and the seed must be set before modifying the tester");
+        Self::new_interactive(self.console, self.program, self.clock, self.rng_seed)
     }
 
     /// Registers the given builtin command into the machine, which must not yet be registered.
     pub fn add_callable(mut self, callable: Rc<dyn Callable>) -> Self {
+        self.modified = true;
         self.machine.add_callable(callable);
         self
     }
@@ -454,6 +699,7 @@ impl Tester {
     /// This method should generally not be used, except to run native methods that have
     /// side-effects on the machine that we'd like to validate later.
     pub fn get_machine(&mut self) -> &mut Machine {
+        self.modified = true;
         &mut self.machine
     }
 
@@ -483,6 +729,7 @@ impl Tester {
 
     /// Sets a variable to an initial value.
     pub fn set_var(mut self, name: &str, value: Value) -> Self {
+        self.modified = true;
         self.machine.get_mut_symbols().set_var(&VarRef::new(name, None), value).unwrap();
         self
     }
@@ -501,15 +748,23 @@ impl Tester {
 
     /// Creates or overwrites a file in the storage medium.
     pub fn write_file(self, name: &str, content: &str) -> Self {
-        block_on(self.storage.borrow_mut().put(name, content.as_bytes())).unwrap();
+        self.write_file_bytes(name, content.as_bytes())
+    }
+
+    /// Creates or overwrites a file in the storage medium with binary `content`.
+    pub fn write_file_bytes(mut self, name: &str, content: &[u8]) -> Self {
+        self.modified = true;
+        block_on(self.storage.borrow_mut().put_exact(name, content)).unwrap();
         self
     }
 
     /// Runs `script` in the configured machine and returns a `Checker` object to validate
     /// expectations about the execution.
     pub fn run<S: Into<String>>(&mut self, script: S) -> Checker {
+        self.modified = true;
+        let start = self.clock.as_ref().map(MockClock::now);
         let result = block_on(self.machine.exec(&mut script.into().as_bytes()));
-        Checker::new(self, result)
+        Checker::new(self, result, start)
     }
 
     /// Runs `scripts` in the configured machine and returns a `Checker` object to validate
@@ -521,6 +776,8 @@ impl Tester {
     /// This is useful when compared to `run` because `Machine::exec` compiles the script as one
     /// unit and thus compilation errors may prevent validating other operations later on.
     pub fn run_n(&mut self, scripts: &[&str]) -> Checker {
+        self.modified = true;
+        let start = self.clock.as_ref().map(MockClock::now);
         let mut result = Ok(StopReason::Eof);
         for script in scripts {
             result = block_on(self.machine.exec(&mut script.as_bytes()));
@@ -528,7 +785,7 @@ impl Tester {
                 break;
             }
         }
-        Checker::new(self, result)
+        Checker::new(self, result, start)
     }
 }
 
@@ -537,9 +794,11 @@ impl Tester {
 pub struct Checker<'a> {
     tester: &'a Tester,
     result: exec::Result<StopReason>,
+    elapsed: Option<Duration>,
     exp_result: Result<StopReason, String>,
+    exp_elapsed: Option<Duration>,
     exp_output: Vec<CapturedOut>,
-    exp_drives: HashMap<String, String>,
+    exp_drives: HashMap<String, Vec<u8>>,
     exp_program_name: Option<String>,
     exp_program_text: String,
     exp_arrays: HashMap<SymbolKey, Array>,
@@ -551,11 +810,25 @@ impl<'a> Checker<'a> {
     ///
     /// The default expectations are that the execution ran through completion and that it did not
     /// have any side-effects.
-    fn new(tester: &'a Tester, result: exec::Result<StopReason>) -> Self {
+    ///
+    /// `start` is the time of the tester's mock clock before the execution, if the tester has one.
+    fn new(
+        tester: &'a Tester,
+        result: exec::Result<StopReason>,
+        start: Option<OffsetDateTime>,
+    ) -> Self {
+        let elapsed = match (tester.clock.as_ref(), start) {
+            (Some(clock), Some(start)) => {
+                Some(Duration::try_from(clock.now() - start).expect("Clock never goes backwards"))
+            }
+            _ => None,
+        };
         Self {
             tester,
             result,
+            elapsed,
             exp_result: Ok(StopReason::Eof),
+            exp_elapsed: None,
             exp_output: vec![],
             exp_drives: HashMap::default(),
             exp_program_name: None,
@@ -637,6 +910,17 @@ impl<'a> Checker<'a> {
         self
     }
 
+    /// Expects the execution to have advanced the tester's mock clock by exactly `d`.
+    ///
+    /// The tester must have been configured with `Tester::with_clock`.  If not called, the time
+    /// spent by the execution is not validated.
+    pub fn expect_elapsed(mut self, d: Duration) -> Self {
+        assert!(self.elapsed.is_some(), "Elapsed time can only be checked with a mock clock");
+        assert!(self.exp_elapsed.is_none());
+        self.exp_elapsed = Some(d);
+        self
+    }
+
     /// Adds tracking for all the side-effects of a clear operation on the machine.
     pub fn expect_clear(mut self) -> Self {
         self.exp_output.append(&mut vec![
@@ -651,10 +935,18 @@ impl<'a> Checker<'a> {
     /// Adds a file to expect in the drive with a `name` and specific `content`.
     ///
     /// `name` must be the absolute path to the file that is expected, including the drive name.
-    pub fn expect_file<N: Into<String>, C: Into<String>>(mut self, name: N, content: C) -> Self {
+    pub fn expect_file<N: Into<String>, C: Into<String>>(self, name: N, content: C) -> Self {
+        let name = name.into();
+        self.expect_file_bytes(name, content.into().as_bytes())
+    }
+
+    /// Adds a file to expect in the drive with a `name` and specific binary `content`.
+    ///
+    /// `name` must be the absolute path to the file that is expected, including the drive name.
+    pub fn expect_file_bytes<N: Into<String>>(mut self, name: N, content: &[u8]) -> Self {
         let name = name.into();
         assert!(!self.exp_drives.contains_key(&name));
-        self.exp_drives.insert(name, content.into());
+        self.exp_drives.insert(name, content.to_vec());
         self
     }
 
@@ -717,7 +1009,7 @@ impl<'a> Checker<'a> {
 
         let mut arrays = HashMap::default();
         let mut vars = HashMap::default();
-        for (name, symbol) in self.tester.machine.get_symbols().locals() {
+        for (name, symbol) in self.tester.machine.get_symbols().locals().iter() {
             match symbol {
                 Symbol::Array(array) => {
                     // TODO(jmmv): This array.clone() call is a hack to simplify the equality check
@@ -744,12 +1036,17 @@ impl<'a> Checker<'a> {
                     continue;
                 }
 
-                let root = format!("{}:/", drive_name);
-                for name in block_on(storage.enumerate(&root)).unwrap().dirents().keys() {
-                    let path = format!("{}{}", root, name);
-                    let content = block_on(storage.get(&path)).unwrap();
-                    let content = String::from_utf8(content).unwrap();
-                    files.insert(path, content);
+                let mut pending = vec![format!("{}:/", drive_name)];
+                while let Some(dir) = pending.pop() {
+                    let dir_files = block_on(storage.enumerate(&dir)).unwrap();
+                    for name in dir_files.dirents().keys() {
+                        let path = format!("{}{}", dir, name);
+                        let content = block_on(storage.get_exact(&path)).unwrap();
+                        files.insert(path, content);
+                    }
+                    for name in dir_files.subdirs().keys() {
+                        pending.push(format!("{}{}/", dir, name));
+                    }
                 }
             }
             files
@@ -761,6 +1058,9 @@ impl<'a> Checker<'a> {
         assert_eq!(self.exp_program_name.as_deref(), self.tester.program.borrow().name());
         assert_eq!(self.exp_program_text, self.tester.program.borrow().text());
         assert_eq!(self.exp_drives, drive_contents);
+        if let Some(exp_elapsed) = self.exp_elapsed {
+            assert_eq!(Some(exp_elapsed), self.elapsed);
+        }
     }
 }
 
@@ -783,6 +1083,20 @@ pub fn check_expr_ok<V: Into<Value>>(exp_value: V, expr: &str) {
         .check();
 }
 
+/// Executes `expr` on a scripting interpreter and ensures that the result is a double that is
+/// within a small tolerance of `exp_value`.
+pub fn check_expr_approx(exp_value: f64, expr: &str) {
+    let mut t = Tester::default();
+    let result = block_on(t.get_machine().exec(&mut format!("result = {}", expr).as_bytes()));
+    assert_eq!(StopReason::Eof, result.expect("Execution failed"));
+    match t.get_machine().get_symbols().get_auto("result") {
+        Some(Symbol::Variable(Value::Double(d))) => {
+            assert!((d - exp_value).abs() < 1e-12, "{} is not close to {}", d, exp_value)
+        }
+        s => panic!("Expected a double result but got {:?}", s),
+    }
+}
+
 /// Executes `expr` on a scripting interpreter and ensures that the result is `exp_value`.
 ///
 /// Sets all `vars` before evaluating the expression so that the expression can contain variable
@@ -0,0 +1,136 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Tests for the time and randomness injection features of the test utilities, written as a
+//! downstream consumer of the crate would use them.
+
+// Keep these in sync with other top-level files.
+#![warn(anonymous_parameters, bad_style, missing_docs)]
+#![warn(unused, unused_extern_crates, unused_import_braces, unused_qualifications)]
+#![warn(unsafe_code)]
+
+use endbasic_core::ast::ExprType;
+use endbasic_core::simple::SimpleCallable;
+use endbasic_std::testutils::{MockClock, Tester};
+use std::convert::TryFrom;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+#[test]
+fn test_sleep_advances_clock() {
+    let clock = MockClock::default();
+    let mut t = Tester::default().with_clock(clock.clone());
+    t.run("SLEEP 1.5: SLEEP 0.25").expect_elapsed(Duration::from_millis(1750)).check();
+    assert_eq!(Duration::from_millis(1750), clock.elapsed());
+
+    t.run("SLEEP 2").expect_elapsed(Duration::from_secs(2)).check();
+    assert_eq!(Duration::from_millis(3750), clock.elapsed());
+}
+
+#[test]
+fn test_timer_follows_clock() {
+    let clock = MockClock::default();
+    let mut t = Tester::default().with_clock(clock.clone());
+    t.run("a = TIMER#").expect_var("a", 0.0).expect_elapsed(Duration::ZERO).check();
+
+    clock.advance(Duration::from_millis(500));
+    t.run("b = TIMER#: SLEEP 1: c = TIMER#")
+        .expect_var("a", 0.0)
+        .expect_var("b", 0.5)
+        .expect_var("c", 1.5)
+        .expect_elapsed(Duration::from_secs(1))
+        .check();
+}
+
+#[test]
+fn test_timer_loop_terminates() {
+    let clock = MockClock::default();
+    let mut t = Tester::default().with_clock(clock.clone());
+    t.run("n = 0: WHILE TIMER# < 60: SLEEP 7: n = n + 1: WEND")
+        .expect_var("n", 9)
+        .expect_elapsed(Duration::from_secs(63))
+        .check();
+}
+
+#[test]
+fn test_dates_follow_clock() {
+    let start = OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+    let clock = MockClock::new(start);
+    let mut t = Tester::default().with_clock(clock.clone());
+    t.run("d = DATE$: t1 = TIME$: SLEEP 65: t2 = TIME$")
+        .expect_var("d", "2023-11-14")
+        .expect_var("t1", "22:13:20")
+        .expect_var("t2", "22:14:25")
+        .check();
+    assert_eq!(start + Duration::from_secs(65), clock.now());
+}
+
+#[test]
+fn test_rnd_is_reproducible() {
+    for _ in 0..2 {
+        let mut t = Tester::default().with_rng_seed(10);
+        t.run("a = RND(1): b = RND(1): c = RND(0)")
+            .expect_var("a", 0.7097578208683426)
+            .expect_var("b", 0.2205558922655312)
+            .expect_var("c", 0.2205558922655312)
+            .check();
+    }
+
+    let mut t = Tester::default().with_rng_seed(11);
+    t.run("a = RND(1) <> 0.7097578208683426").expect_var("a", true).check();
+}
+
+#[test]
+fn test_rnd_matches_randomize() {
+    let mut t = Tester::default().with_rng_seed(10);
+    t.run("a = RNDINT(1, 100): b = RNDINT(1, 100): c = RNDINT(1, 100)")
+        .expect_var("a", 29)
+        .expect_var("b", 45)
+        .expect_var("c", 4)
+        .check();
+}
+
+#[test]
+fn test_clock_and_seed_combined() {
+    let clock = MockClock::default();
+    let mut t = Tester::default().with_rng_seed(10).with_clock(clock);
+    t.run("SLEEP RNDINT(1, 100) / 10.0").expect_elapsed(Duration::from_millis(2900)).check();
+}
+
+#[test]
+fn test_empty_with_clock_keeps_callables() {
+    let clock = MockClock::default();
+    let tick = {
+        let clock = clock.clone();
+        SimpleCallable::command("TICK")
+            .with_category("Testing")
+            .with_description("Advances the clock.")
+            .arg("secs", ExprType::Integer)
+            .handler(move |args, _| {
+                clock.advance(Duration::from_secs(u64::try_from(args.integer(0)).unwrap()));
+                Ok(())
+            })
+    };
+    let mut t = Tester::empty().add_callable(tick).with_clock(clock.clone()).with_rng_seed(1);
+    t.run("TICK 3").expect_elapsed(Duration::from_secs(3)).check();
+    t.run("SLEEP 1").expect_compilation_err("1:1: Undefined symbol SLEEP").check();
+    assert_eq!(Duration::from_secs(3), clock.elapsed());
+}
+
+#[test]
+#[should_panic(expected = "The clock and the seed must be set before modifying the tester")]
+fn test_with_clock_after_modification() {
+    let _ = Tester::default().write_file("a.bas", "").with_clock(MockClock::default());
+}
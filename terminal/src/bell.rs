@@ -0,0 +1,43 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Audio device that rings the terminal bell.
+
+use crossterm::tty::IsTty;
+use endbasic_std::audio::Audio;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Implementation of the EndBASIC audio operations that rings the terminal bell for every tone.
+///
+/// Terminals cannot play arbitrary tones, so the frequency and duration of the tones are ignored.
+/// The bell is only rung when stdout is a terminal so that redirected output stays clean.
+#[derive(Default)]
+pub struct TerminalBell {}
+
+impl Audio for TerminalBell {
+    fn tone(&mut self, _freq: f64, _duration: Duration) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        if !stdout.is_tty() {
+            return Ok(());
+        }
+        stdout.write_all(b"\x07")?;
+        stdout.flush()
+    }
+
+    fn rest(&mut self, _duration: Duration) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! External editor support for the terminal console.
+
+use crate::SuspendState;
+use async_trait::async_trait;
+use endbasic_std::program::ExternalEditor;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Counter to give a unique name to each temporary file created by `edit_with`.
+static NEXT_FILE_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Editor to use when neither `VISUAL` nor `EDITOR` are set.
+#[cfg(not(target_os = "windows"))]
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Editor to use when neither `VISUAL` nor `EDITOR` are set.
+#[cfg(target_os = "windows")]
+const DEFAULT_EDITOR: &str = "notepad";
+
+/// Returns the command line of the editor configured by the user, or the default editor.
+fn get_editor_command() -> String {
+    for var in ["VISUAL", "EDITOR"] {
+        if let Ok(command) = env::var(var) {
+            if !command.trim().is_empty() {
+                return command;
+            }
+        }
+    }
+    DEFAULT_EDITOR.to_owned()
+}
+
+/// Runs the editor given in `command` on the file at `path` and waits for it to exit.
+///
+/// `command` can contain arguments to pass to the editor, separated by whitespace, which are
+/// passed before the file name.
+fn run_editor(command: &str, path: &Path) -> io::Result<()> {
+    let mut words = command.split_whitespace();
+    let program = match words.next() {
+        Some(program) => program,
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "Empty editor command")),
+    };
+
+    let status =
+        Command::new(program).args(words).arg(path).status().map_err(|e| {
+            io::Error::new(e.kind(), format!("Cannot run editor '{}': {}", program, e))
+        })?;
+    if !status.success() {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("Editor '{}' failed with {}", program, status),
+        ));
+    }
+    Ok(())
+}
+
+/// Edits `text` with the editor given in `command` via a temporary file and returns the new text.
+fn edit_with(command: &str, text: &str) -> io::Result<String> {
+    let id = NEXT_FILE_ID.fetch_add(1, Ordering::SeqCst);
+    let path = env::temp_dir().join(format!("endbasic-edit-{}-{}.bas", process::id(), id));
+    fs::write(&path, text)?;
+    let result = run_editor(command, &path).and_then(|()| fs::read_to_string(&path));
+    let _ = fs::remove_file(&path);
+    result
+}
+
+/// External editor that runs the program configured in the `VISUAL` or `EDITOR` environment
+/// variables on the terminal.
+pub struct TerminalEditor {
+    /// Coordination with the console's key handler, if the console is attached to a TTY.
+    suspend: Option<Arc<SuspendState>>,
+}
+
+impl TerminalEditor {
+    /// Creates a new editor that suspends the console via `suspend` while the editor runs.
+    pub(crate) fn new(suspend: Option<Arc<SuspendState>>) -> Self {
+        Self { suspend }
+    }
+}
+
+#[async_trait(?Send)]
+impl ExternalEditor for TerminalEditor {
+    async fn edit(&self, text: &str) -> io::Result<String> {
+        let command = get_editor_command();
+        match self.suspend.as_ref() {
+            Some(suspend) => {
+                suspend.suspend()?;
+                let result = edit_with(&command, text);
+                suspend.resume()?;
+                result
+            }
+            None => edit_with(&command, text),
+        }
+    }
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Creates a fake editor in `dir` that runs the shell `script` with the file to edit as `$1`.
+    fn fake_editor(dir: &Path, script: &str) -> String {
+        let path = dir.join("editor.sh");
+        fs::write(&path, format!("#! /bin/sh\n{}\n", script)).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        path.to_str().unwrap().to_owned()
+    }
+
+    #[test]
+    fn test_edit_with_modifies_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = fake_editor(dir.path(), "echo 'PRINT \"new\"' >>\"$1\"");
+        assert_eq!(
+            "PRINT \"old\"\nPRINT \"new\"\n",
+            edit_with(&editor, "PRINT \"old\"\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_edit_with_passes_arguments() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = fake_editor(dir.path(), "echo \"$1\" >\"$2\"");
+        assert_eq!("-w\n", edit_with(&format!("{} -w", editor), "").unwrap());
+    }
+
+    #[test]
+    fn test_edit_with_editor_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let editor = fake_editor(dir.path(), "echo 'garbage' >\"$1\"; exit 3");
+        let e = edit_with(&editor, "original").unwrap_err();
+        assert!(format!("{}", e).contains("failed with exit status: 3"));
+    }
+
+    #[test]
+    fn test_edit_with_missing_editor() {
+        let e = edit_with("/nonexistent/editor", "").unwrap_err();
+        assert_eq!(io::ErrorKind::NotFound, e.kind());
+        assert!(format!("{}", e).starts_with("Cannot run editor '/nonexistent/editor'"));
+    }
+}
@@ -30,11 +30,67 @@ use crossterm::{cursor, style, terminal, QueueableCommand};
 use endbasic_core::exec::Signal;
 use endbasic_std::console::graphics::InputOps;
 use endbasic_std::console::{
-    get_env_var_as_u16, read_key_from_stdin, remove_control_chars, CharsXY, ClearType, Console, Key,
+    get_env_var_as_u16, read_key_from_stdin, remove_control_chars, CharsXY, ClearType, Console,
+    Key, ScreenCell, ShadowScreen,
 };
 use std::cmp::Ordering;
 use std::collections::VecDeque;
-use std::io::{self, StdoutLock, Write};
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+mod bell;
+pub use bell::TerminalBell;
+mod editor;
+pub use editor::TerminalEditor;
+#[cfg(unix)]
+mod serial;
+#[cfg(unix)]
+pub use serial::TtySerialPorts;
+mod shell;
+pub use shell::TerminalShell;
+
+/// Size of the shadow screen when the size of the terminal cannot be determined.
+const DEFAULT_SIZE_CHARS: CharsXY = CharsXY { x: 80, y: 24 };
+
+/// Maximum time the raw key handler waits for input before checking if it has to suspend.
+const SUSPEND_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// State shared between the console and its raw key handler so that the console can give up
+/// control of the terminal to a child process.
+#[derive(Default)]
+pub(crate) struct SuspendState {
+    /// Set by the console to ask the key handler to stop reading from the terminal.
+    requested: AtomicBool,
+
+    /// Set by the key handler once it has acknowledged a suspension request.
+    idle: AtomicBool,
+
+    /// Set by the key handler when it has terminated and will not read from the terminal anymore.
+    exited: AtomicBool,
+}
+
+impl SuspendState {
+    /// Stops the key handler from reading from the terminal and takes the terminal out of raw
+    /// mode.  Blocks until the key handler has acknowledged the request.
+    pub(crate) fn suspend(&self) -> io::Result<()> {
+        self.requested.store(true, AtomicOrdering::SeqCst);
+        while !self.idle.load(AtomicOrdering::SeqCst) && !self.exited.load(AtomicOrdering::SeqCst) {
+            thread::sleep(SUSPEND_POLL_INTERVAL / 5);
+        }
+        terminal::disable_raw_mode()
+    }
+
+    /// Puts the terminal back in raw mode and lets the key handler resume reading from it.
+    pub(crate) fn resume(&self) -> io::Result<()> {
+        let result = terminal::enable_raw_mode();
+        self.idle.store(false, AtomicOrdering::SeqCst);
+        self.requested.store(false, AtomicOrdering::SeqCst);
+        result
+    }
+}
 
 /// Implementation of the EndBASIC console to interact with stdin and stdout.
 pub struct TerminalConsole {
@@ -57,12 +113,30 @@ pub struct TerminalConsole {
     /// Whether video syncing is enabled or not.
     sync_enabled: bool,
 
+    /// Nesting level of the batches started via `start_batch` that have not ended yet.
+    batch_depth: usize,
+
+    /// Output that has not been sent to the terminal yet.
+    pending: Vec<u8>,
+
+    /// Destination of the output, which is stdout except in tests.
+    sink: Box<dyn Write>,
+
     /// Channel to receive key presses from the terminal.
     on_key_rx: Receiver<Key>,
+
+    /// Copy of the characters displayed on the terminal, as the terminal cannot be read back.
+    shadow: ShadowScreen,
+
+    /// Coordination with the raw key handler to suspend the console.  Only set when the console is
+    /// attached to a TTY.
+    suspend: Option<Arc<SuspendState>>,
 }
 
 impl Drop for TerminalConsole {
     fn drop(&mut self) {
+        // There is nothing we can do if we fail to flush the output at this point.
+        let _ = self.flush_pending();
         if self.is_tty {
             terminal::disable_raw_mode().unwrap();
         }
@@ -91,34 +165,88 @@ impl TerminalConsole {
 
         let is_tty = io::stdin().is_tty() && io::stdout().is_tty();
 
-        if is_tty {
+        let suspend = if is_tty {
             terminal::enable_raw_mode()?;
-            tokio::task::spawn(TerminalConsole::raw_key_handler(on_key_tx.clone(), signals_tx));
+            let suspend = Arc::from(SuspendState::default());
+            tokio::task::spawn(TerminalConsole::raw_key_handler(
+                on_key_tx.clone(),
+                signals_tx,
+                suspend.clone(),
+            ));
+            Some(suspend)
         } else {
             tokio::task::spawn(TerminalConsole::stdio_key_handler(on_key_tx.clone()));
+            None
+        };
+
+        let mut console = Self::new(is_tty, on_key_rx, suspend, Box::from(io::stdout()));
+        console.refresh_shadow_size();
+        Ok((console, on_key_tx))
+    }
+
+    /// Creates a new console that reads keys from `on_key_rx` and writes its output to `sink`.
+    fn new(
+        is_tty: bool,
+        on_key_rx: Receiver<Key>,
+        suspend: Option<Arc<SuspendState>>,
+        sink: Box<dyn Write>,
+    ) -> Self {
+        Self {
+            is_tty,
+            fg_color: None,
+            bg_color: None,
+            cursor_visible: true,
+            alt_active: false,
+            sync_enabled: true,
+            batch_depth: 0,
+            pending: vec![],
+            sink,
+            on_key_rx,
+            shadow: ShadowScreen::new(DEFAULT_SIZE_CHARS),
+            suspend,
         }
+    }
+
+    /// Returns an external editor that suspends this console while the editor runs.
+    pub fn external_editor(&self) -> TerminalEditor {
+        TerminalEditor::new(self.suspend.clone())
+    }
 
-        Ok((
-            Self {
-                is_tty,
-                fg_color: None,
-                bg_color: None,
-                cursor_visible: true,
-                alt_active: false,
-                sync_enabled: true,
-                on_key_rx,
-            },
-            on_key_tx,
-        ))
+    /// Returns a shell that suspends this console while external commands run.
+    pub fn shell(&self) -> TerminalShell {
+        TerminalShell::new(self.suspend.clone())
+    }
+
+    /// Updates the size of the shadow screen to match the terminal, which may have been resized
+    /// since the last time we checked.
+    fn refresh_shadow_size(&mut self) {
+        if let Ok(size) = self.size_chars() {
+            self.shadow.resize(size);
+        }
     }
 
     /// Async task to wait for key events on a raw terminal and translate them into events for the
     /// console or the machine.
-    async fn raw_key_handler(on_key_tx: Sender<Key>, signals_tx: Sender<Signal>) {
+    async fn raw_key_handler(
+        on_key_tx: Sender<Key>,
+        signals_tx: Sender<Signal>,
+        suspend: Arc<SuspendState>,
+    ) {
         use event::{KeyCode, KeyModifiers};
 
         let mut done = false;
         while !done {
+            // Poll with a timeout instead of blocking on a read so that we can notice suspension
+            // requests and leave the terminal alone while a child process owns it.
+            if suspend.requested.load(AtomicOrdering::SeqCst) {
+                suspend.idle.store(true, AtomicOrdering::SeqCst);
+                thread::sleep(SUSPEND_POLL_INTERVAL);
+                continue;
+            }
+            if let Ok(false) = event::poll(SUSPEND_POLL_INTERVAL) {
+                continue;
+            }
+
             let key = match event::read() {
                 Ok(event::Event::Key(ev)) => {
                     if ev.kind != KeyEventKind::Press {
@@ -155,6 +283,7 @@ impl TerminalConsole {
                             Key::ArrowDown
                         }
                         KeyCode::Char('p') if ev.modifiers == KeyModifiers::CONTROL => Key::ArrowUp,
+                        KeyCode::Char('r') if ev.modifiers == KeyModifiers::CONTROL => Key::Search,
                         KeyCode::Char(ch) => Key::Char(ch),
                         KeyCode::Enter => Key::NewLine,
                         _ => Key::Unknown,
@@ -189,6 +318,7 @@ impl TerminalConsole {
             let _ = on_key_tx.send(key).await;
         }
 
+        suspend.exited.store(true, AtomicOrdering::SeqCst);
         signals_tx.close();
         on_key_tx.close();
     }
@@ -225,10 +355,20 @@ impl TerminalConsole {
         on_key_tx.close();
     }
 
-    /// Flushes the console, which has already been written to via `lock`, if syncing is enabled.
-    fn maybe_flush(&self, mut lock: StdoutLock<'_>) -> io::Result<()> {
-        if self.sync_enabled {
-            lock.flush()
+    /// Sends all pending output to the terminal with a single write.
+    fn flush_pending(&mut self) -> io::Result<()> {
+        if !self.pending.is_empty() {
+            let result = self.sink.write_all(&self.pending);
+            self.pending.clear();
+            result?;
+        }
+        self.sink.flush()
+    }
+
+    /// Flushes the pending output if syncing is enabled and there is no batch in progress.
+    fn maybe_flush(&mut self) -> io::Result<()> {
+        if self.sync_enabled && self.batch_depth == 0 {
+            self.flush_pending()
         } else {
             Ok(())
         }
@@ -256,24 +396,22 @@ impl InputOps for TerminalConsole {
 #[async_trait(?Send)]
 impl Console for TerminalConsole {
     fn clear(&mut self, how: ClearType) -> io::Result<()> {
+        self.refresh_shadow_size();
+        self.shadow.clear(how);
         let how = match how {
             ClearType::All => terminal::ClearType::All,
             ClearType::CurrentLine => terminal::ClearType::CurrentLine,
             ClearType::PreviousChar => {
-                let stdout = io::stdout();
-                let mut stdout = stdout.lock();
-                stdout.write_all(b"\x08 \x08")?;
-                return self.maybe_flush(stdout);
+                self.pending.write_all(b"\x08 \x08")?;
+                return self.maybe_flush();
             }
             ClearType::UntilNewLine => terminal::ClearType::UntilNewLine,
         };
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
-        stdout.queue(terminal::Clear(how))?;
+        self.pending.queue(terminal::Clear(how))?;
         if how == terminal::ClearType::All {
-            stdout.queue(cursor::MoveTo(0, 0))?;
+            self.pending.queue(cursor::MoveTo(0, 0))?;
         }
-        self.maybe_flush(stdout)
+        self.maybe_flush()
     }
 
     fn color(&self) -> (Option<u8>, Option<u8>) {
@@ -285,14 +423,12 @@ impl Console for TerminalConsole {
             return Ok(());
         }
 
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
         if fg != self.fg_color {
             let ct_fg = match fg {
                 None => style::Color::Reset,
                 Some(color) => style::Color::AnsiValue(color),
             };
-            stdout.queue(style::SetForegroundColor(ct_fg))?;
+            self.pending.queue(style::SetForegroundColor(ct_fg))?;
             self.fg_color = fg;
         }
         if bg != self.bg_color {
@@ -300,19 +436,19 @@ impl Console for TerminalConsole {
                 None => style::Color::Reset,
                 Some(color) => style::Color::AnsiValue(color),
             };
-            stdout.queue(style::SetBackgroundColor(ct_bg))?;
+            self.pending.queue(style::SetBackgroundColor(ct_bg))?;
             self.bg_color = bg;
         }
-        self.maybe_flush(stdout)
+        self.shadow.set_color(fg, bg);
+        self.maybe_flush()
     }
 
     fn enter_alt(&mut self) -> io::Result<()> {
         if !self.alt_active {
-            let stdout = io::stdout();
-            let mut stdout = stdout.lock();
-            stdout.queue(terminal::EnterAlternateScreen)?;
+            self.pending.queue(terminal::EnterAlternateScreen)?;
             self.alt_active = true;
-            self.maybe_flush(stdout)
+            self.shadow.enter_alt();
+            self.maybe_flush()
         } else {
             Ok(())
         }
@@ -320,11 +456,9 @@ impl Console for TerminalConsole {
 
     fn hide_cursor(&mut self) -> io::Result<()> {
         if self.cursor_visible {
-            let stdout = io::stdout();
-            let mut stdout = stdout.lock();
-            stdout.queue(cursor::Hide)?;
+            self.pending.queue(cursor::Hide)?;
             self.cursor_visible = false;
-            self.maybe_flush(stdout)
+            self.maybe_flush()
         } else {
             Ok(())
         }
@@ -336,11 +470,10 @@ impl Console for TerminalConsole {
 
     fn leave_alt(&mut self) -> io::Result<()> {
         if self.alt_active {
-            let stdout = io::stdout();
-            let mut stdout = stdout.lock();
-            stdout.queue(terminal::LeaveAlternateScreen)?;
+            self.pending.queue(terminal::LeaveAlternateScreen)?;
             self.alt_active = false;
-            self.maybe_flush(stdout)
+            self.shadow.leave_alt();
+            self.maybe_flush()
         } else {
             Ok(())
         }
@@ -354,35 +487,36 @@ impl Console for TerminalConsole {
             assert!(pos.y < size.y);
         }
 
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
-        stdout.queue(cursor::MoveTo(pos.x, pos.y))?;
-        self.maybe_flush(stdout)
+        self.refresh_shadow_size();
+        self.shadow.locate(pos);
+
+        self.pending.queue(cursor::MoveTo(pos.x, pos.y))?;
+        self.maybe_flush()
     }
 
     fn move_within_line(&mut self, off: i16) -> io::Result<()> {
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
+        self.shadow.move_within_line(off);
+
         match off.cmp(&0) {
-            Ordering::Less => stdout.queue(cursor::MoveLeft(-off as u16)),
+            Ordering::Less => self.pending.queue(cursor::MoveLeft(-off as u16)),
             Ordering::Equal => return Ok(()),
-            Ordering::Greater => stdout.queue(cursor::MoveRight(off as u16)),
+            Ordering::Greater => self.pending.queue(cursor::MoveRight(off as u16)),
         }?;
-        self.maybe_flush(stdout)
+        self.maybe_flush()
     }
 
     fn print(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text.to_owned());
+        self.refresh_shadow_size();
+        self.shadow.print(&text);
 
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
-        stdout.write_all(text.as_bytes())?;
+        self.pending.write_all(text.as_bytes())?;
         if self.is_tty {
-            stdout.write_all(b"\r\n")?;
+            self.pending.write_all(b"\r\n")?;
         } else {
-            stdout.write_all(b"\n")?;
+            self.pending.write_all(b"\n")?;
         }
-        Ok(())
+        self.maybe_flush()
     }
 
     async fn poll_key(&mut self) -> io::Result<Option<Key>> {
@@ -395,11 +529,9 @@ impl Console for TerminalConsole {
 
     fn show_cursor(&mut self) -> io::Result<()> {
         if !self.cursor_visible {
-            let stdout = io::stdout();
-            let mut stdout = stdout.lock();
-            stdout.queue(cursor::Show)?;
+            self.pending.queue(cursor::Show)?;
             self.cursor_visible = true;
-            self.maybe_flush(stdout)
+            self.maybe_flush()
         } else {
             Ok(())
         }
@@ -423,27 +555,163 @@ impl Console for TerminalConsole {
 
     fn write(&mut self, text: &str) -> io::Result<()> {
         let text = remove_control_chars(text.to_owned());
+        self.refresh_shadow_size();
+        self.shadow.write(&text);
 
-        let stdout = io::stdout();
-        let mut stdout = stdout.lock();
-        stdout.write_all(text.as_bytes())?;
-        self.maybe_flush(stdout)
+        self.pending.write_all(text.as_bytes())?;
+        self.maybe_flush()
+    }
+
+    fn read_screen(&self) -> io::Result<Vec<Vec<ScreenCell>>> {
+        Ok(self.shadow.contents())
+    }
+
+    fn cursor_pos(&self) -> io::Result<CharsXY> {
+        Ok(self.shadow.cursor())
     }
 
     fn sync_now(&mut self) -> io::Result<()> {
-        if self.sync_enabled {
-            Ok(())
-        } else {
-            io::stdout().flush()
-        }
+        self.flush_pending()
     }
 
     fn set_sync(&mut self, enabled: bool) -> io::Result<bool> {
-        if !self.sync_enabled {
-            io::stdout().flush()?;
-        }
         let previous = self.sync_enabled;
         self.sync_enabled = enabled;
+        self.maybe_flush()?;
         Ok(previous)
     }
+
+    fn start_batch(&mut self) -> io::Result<()> {
+        self.batch_depth += 1;
+        Ok(())
+    }
+
+    fn end_batch(&mut self) -> io::Result<()> {
+        debug_assert!(self.batch_depth > 0, "Unbalanced end_batch call");
+        self.batch_depth = self.batch_depth.saturating_sub(1);
+        self.maybe_flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::rc::Rc;
+
+    /// Writer that records the data it receives and counts the calls to `write`, each of which
+    /// represents a system call on a real terminal.
+    #[derive(Clone, Default)]
+    struct CountingWriter {
+        writes: Rc<Cell<usize>>,
+        data: Rc<RefCell<Vec<u8>>>,
+    }
+
+    impl Write for CountingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.writes.set(self.writes.get() + 1);
+            self.data.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Creates a console that is not attached to a TTY and that writes to `sink`.
+    fn new_console(sink: &CountingWriter) -> TerminalConsole {
+        // Prevent the console from querying the size of the terminal, which we may not have.
+        std::env::set_var("COLUMNS", "80");
+        std::env::set_var("LINES", "24");
+        let (_on_key_tx, on_key_rx) = async_channel::unbounded();
+        TerminalConsole::new(false, on_key_rx, None, Box::from(sink.clone()))
+    }
+
+    /// Repaints a 10x10 area one cell at a time, like a text game would do.
+    fn redraw(console: &mut TerminalConsole) {
+        for y in 0..10 {
+            for x in 0..10 {
+                console.locate(CharsXY::new(x, y)).unwrap();
+                console.write("#").unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_batch_combines_writes() {
+        let unbatched = CountingWriter::default();
+        let mut console = new_console(&unbatched);
+        redraw(&mut console);
+        assert_eq!(200, unbatched.writes.get());
+
+        let batched = CountingWriter::default();
+        let mut console = new_console(&batched);
+        console.start_batch().unwrap();
+        redraw(&mut console);
+        assert_eq!(0, batched.writes.get());
+        console.end_batch().unwrap();
+        assert_eq!(1, batched.writes.get());
+
+        assert_eq!(*unbatched.data.borrow(), *batched.data.borrow());
+    }
+
+    #[test]
+    fn test_nested_batches_flush_at_outermost_end() {
+        let sink = CountingWriter::default();
+        let mut console = new_console(&sink);
+
+        console.start_batch().unwrap();
+        console.write("a").unwrap();
+        console.start_batch().unwrap();
+        console.print("b").unwrap();
+        console.end_batch().unwrap();
+        assert_eq!(0, sink.writes.get());
+        console.end_batch().unwrap();
+        assert_eq!(1, sink.writes.get());
+        assert_eq!(b"ab\n", sink.data.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_sync_disabled_holds_output() {
+        let sink = CountingWriter::default();
+        let mut console = new_console(&sink);
+
+        assert!(console.set_sync(false).unwrap());
+        redraw(&mut console);
+        console.start_batch().unwrap();
+        console.print("x").unwrap();
+        console.end_batch().unwrap();
+        assert_eq!(0, sink.writes.get());
+
+        console.sync_now().unwrap();
+        assert_eq!(1, sink.writes.get());
+
+        redraw(&mut console);
+        assert_eq!(1, sink.writes.get());
+        assert!(!console.set_sync(true).unwrap());
+        assert_eq!(2, sink.writes.get());
+    }
+
+    #[test]
+    fn test_print_is_a_single_write() {
+        let sink = CountingWriter::default();
+        let mut console = new_console(&sink);
+
+        console.print("Hello").unwrap();
+        assert_eq!(1, sink.writes.get());
+        assert_eq!(b"Hello\n", sink.data.borrow().as_slice());
+    }
+
+    #[test]
+    fn test_drop_flushes_pending_output() {
+        let sink = CountingWriter::default();
+        let mut console = new_console(&sink);
+
+        console.set_sync(false).unwrap();
+        console.write("pending").unwrap();
+        assert_eq!(0, sink.writes.get());
+        drop(console);
+        assert_eq!(b"pending", sink.data.borrow().as_slice());
+    }
 }
@@ -0,0 +1,283 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Serial port support for Unix systems.
+
+use async_trait::async_trait;
+use endbasic_std::serial::{Parity, SerialConfig, SerialPort, SerialPorts};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::io::AsRawFd;
+use std::time::Duration;
+use tokio::io::unix::AsyncFd;
+
+/// Converts a `baud` rate to its termios speed constant.
+fn baud_to_speed(baud: u32) -> io::Result<libc::speed_t> {
+    let speed = match baud {
+        300 => libc::B300,
+        600 => libc::B600,
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported baud rate {}", baud),
+            ))
+        }
+    };
+    Ok(speed)
+}
+
+/// Converts the result of a libc call into an `io::Result`.
+fn check_libc(result: libc::c_int) -> io::Result<()> {
+    if result == -1 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+/// Configures the terminal behind `file` for raw 8-bit transfers with the given `config`.
+#[allow(unsafe_code)]
+fn configure(file: &File, config: SerialConfig) -> io::Result<()> {
+    let speed = baud_to_speed(config.baud)?;
+    let fd = file.as_raw_fd();
+
+    // SAFETY: The file descriptor is valid for the lifetime of `file` and `tio` is a plain data
+    // structure that is fully initialized by `tcgetattr` before we modify it.
+    unsafe {
+        let mut tio: libc::termios = std::mem::zeroed();
+        check_libc(libc::tcgetattr(fd, &mut tio))?;
+        libc::cfmakeraw(&mut tio);
+        check_libc(libc::cfsetispeed(&mut tio, speed))?;
+        check_libc(libc::cfsetospeed(&mut tio, speed))?;
+
+        tio.c_cflag &= !(libc::CSIZE | libc::PARENB | libc::PARODD | libc::CSTOPB);
+        tio.c_cflag |= libc::CS8 | libc::CLOCAL | libc::CREAD;
+        match config.parity {
+            Parity::None => (),
+            Parity::Even => tio.c_cflag |= libc::PARENB,
+            Parity::Odd => tio.c_cflag |= libc::PARENB | libc::PARODD,
+        }
+        if config.stop_bits == 2 {
+            tio.c_cflag |= libc::CSTOPB;
+        }
+        // Reads without data must fail with EAGAIN instead of returning 0 bytes so that we can
+        // tell them apart from a device that went away.
+        tio.c_cc[libc::VMIN] = 1;
+        tio.c_cc[libc::VTIME] = 0;
+
+        check_libc(libc::tcsetattr(fd, libc::TCSANOW, &tio))
+    }
+}
+
+/// Converts an error from the device into the error kind that callers expect when the device
+/// disappears, if that is what the error represents.
+fn map_device_error(e: io::Error) -> io::Error {
+    match e.raw_os_error() {
+        Some(libc::EIO) | Some(libc::ENXIO) | Some(libc::ENODEV) => {
+            io::Error::new(io::ErrorKind::BrokenPipe, e.to_string())
+        }
+        _ => e,
+    }
+}
+
+/// Returns the error that represents a device that went away.
+fn device_gone() -> io::Error {
+    io::Error::new(io::ErrorKind::BrokenPipe, "Device disconnected")
+}
+
+/// Serial port backed by a Unix terminal device.
+struct TtySerialPort {
+    file: AsyncFd<File>,
+}
+
+impl TtySerialPort {
+    /// Reads up to `max_length` bytes of data that is already available without waiting.
+    fn try_read(&self, max_length: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; max_length];
+        match self.file.get_ref().read(&mut buffer) {
+            Ok(0) => Err(device_gone()),
+            Ok(n) => {
+                buffer.truncate(n);
+                Ok(buffer)
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => Ok(vec![]),
+            Err(e) => Err(map_device_error(e)),
+        }
+    }
+
+    /// Waits until data is available and reads up to `max_length` bytes of it.
+    async fn wait_and_read(&self, max_length: usize) -> io::Result<Vec<u8>> {
+        let mut buffer = vec![0; max_length];
+        loop {
+            let mut guard = self.file.readable().await?;
+            match guard.try_io(|file| file.get_ref().read(&mut buffer)) {
+                Ok(Ok(0)) => return Err(device_gone()),
+                Ok(Ok(n)) => {
+                    buffer.truncate(n);
+                    return Ok(buffer);
+                }
+                Ok(Err(e)) => return Err(map_device_error(e)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl SerialPort for TtySerialPort {
+    async fn write(&mut self, mut data: &[u8]) -> io::Result<()> {
+        while !data.is_empty() {
+            let mut guard = self.file.writable().await?;
+            match guard.try_io(|file| file.get_ref().write(data)) {
+                Ok(Ok(n)) => data = &data[n..],
+                Ok(Err(e)) => return Err(map_device_error(e)),
+                Err(_would_block) => continue,
+            }
+        }
+        Ok(())
+    }
+
+    async fn read(&mut self, max_length: usize, timeout: Option<Duration>) -> io::Result<Vec<u8>> {
+        match timeout {
+            Some(Duration::ZERO) => self.try_read(max_length),
+            Some(timeout) => {
+                match tokio::time::timeout(timeout, self.wait_and_read(max_length)).await {
+                    Ok(result) => result,
+                    Err(_elapsed) => Ok(vec![]),
+                }
+            }
+            None => self.wait_and_read(max_length).await,
+        }
+    }
+}
+
+/// Factory of serial ports backed by Unix terminal devices such as `/dev/ttyUSB0`.
+///
+/// This requires the program to run within a Tokio runtime with I/O and time support.
+#[derive(Default)]
+pub struct TtySerialPorts {}
+
+impl SerialPorts for TtySerialPorts {
+    fn open(&self, device: &str, config: SerialConfig) -> io::Result<Box<dyn SerialPort>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_NOCTTY | libc::O_NONBLOCK)
+            .open(device)?;
+        configure(&file, config)?;
+        Ok(Box::from(TtySerialPort { file: AsyncFd::new(file)? }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    /// Opens a new pseudo-terminal and returns its master side and the path to its slave side.
+    #[allow(unsafe_code)]
+    fn open_pty() -> (File, String) {
+        use std::os::unix::io::FromRawFd;
+
+        // SAFETY: The returned descriptor is checked for validity before being wrapped in a file
+        // that owns it, and `ptsname` returns a valid C string for a valid master descriptor.
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            check_libc(fd).unwrap();
+            let master = File::from_raw_fd(fd);
+            check_libc(libc::grantpt(fd)).unwrap();
+            check_libc(libc::unlockpt(fd)).unwrap();
+            let name = CStr::from_ptr(libc::ptsname(fd)).to_str().unwrap().to_owned();
+            (master, name)
+        }
+    }
+
+    /// Default configuration for the ports opened in the tests.
+    const CONFIG: SerialConfig = SerialConfig { baud: 9600, parity: Parity::None, stop_bits: 1 };
+
+    /// Creates a runtime to run the asynchronous operations of the ports.
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let (mut master, name) = open_pty();
+        let mut port = TtySerialPorts::default().open(&name, CONFIG).unwrap();
+
+        assert!(runtime.block_on(port.read(100, Some(Duration::ZERO))).unwrap().is_empty());
+
+        master.write_all(b"hello\n").unwrap();
+        let data = runtime.block_on(port.read(100, None)).unwrap();
+        assert_eq!(b"hello\n", data.as_slice());
+
+        runtime.block_on(port.write(b"ping")).unwrap();
+        let mut buffer = [0; 4];
+        master.read_exact(&mut buffer).unwrap();
+        assert_eq!(b"ping", &buffer);
+    }
+
+    #[test]
+    fn test_read_timeout() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let (_master, name) = open_pty();
+        let mut port = TtySerialPorts::default().open(&name, CONFIG).unwrap();
+
+        let data = runtime.block_on(port.read(100, Some(Duration::from_millis(10)))).unwrap();
+        assert!(data.is_empty());
+    }
+
+    #[test]
+    fn test_device_disappears() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let (master, name) = open_pty();
+        let mut port = TtySerialPorts::default().open(&name, CONFIG).unwrap();
+
+        drop(master);
+        let err = runtime.block_on(port.read(100, None)).unwrap_err();
+        assert_eq!(io::ErrorKind::BrokenPipe, err.kind());
+    }
+
+    #[test]
+    fn test_open_errors() {
+        let runtime = runtime();
+        let _guard = runtime.enter();
+        let (_master, name) = open_pty();
+
+        let config = SerialConfig { baud: 1234, ..CONFIG };
+        let err = TtySerialPorts::default().open(&name, config).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+        assert_eq!("Unsupported baud rate 1234", err.to_string());
+
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        let err = TtySerialPorts::default().open(missing.to_str().unwrap(), CONFIG).err().unwrap();
+        assert_eq!(io::ErrorKind::NotFound, err.kind());
+    }
+}
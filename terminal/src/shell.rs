@@ -0,0 +1,180 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! External command support for the terminal console.
+
+use crate::SuspendState;
+use async_trait::async_trait;
+use endbasic_std::shell::Shell;
+use std::convert::TryFrom;
+use std::io::{self, Read};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::Arc;
+
+/// Creates a command that runs `command` through the platform's shell.
+#[cfg(not(target_os = "windows"))]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("/bin/sh");
+    cmd.arg("-c").arg(command);
+    cmd
+}
+
+/// Creates a command that runs `command` through the platform's shell.
+#[cfg(target_os = "windows")]
+fn shell_command(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+/// Converts the `status` of a finished process into an exit code, reporting termination by a
+/// signal as 128 plus the signal number like the shell does.
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    status.code().or_else(|| status.signal().map(|signo| 128 + signo)).unwrap_or(-1)
+}
+
+/// Converts the `status` of a finished process into an exit code.
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(-1)
+}
+
+/// Constructs the error returned when the shell cannot be started.
+fn spawn_error(e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("Cannot run shell: {}", e))
+}
+
+/// Runs `command` with the standard input and outputs of the interpreter and returns its exit code.
+fn run_command(command: &str) -> io::Result<i32> {
+    let status = shell_command(command).status().map_err(spawn_error)?;
+    Ok(exit_code(status))
+}
+
+/// Runs `command` and returns its exit code and its standard output, which is truncated past
+/// `max_length` bytes.  The command is killed if its output grows beyond that size.
+fn capture_command(command: &str, max_length: usize) -> io::Result<(i32, Vec<u8>)> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(spawn_error)?;
+
+    let limit = u64::try_from(max_length).unwrap_or(u64::MAX).saturating_add(1);
+    let mut output = vec![];
+    let stdout = child.stdout.take().expect("stdout was configured as piped");
+    let result = stdout.take(limit).read_to_end(&mut output);
+    if result.is_err() || output.len() > max_length {
+        // Ignore errors: the process may have exited on its own already.
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    result?;
+    Ok((exit_code(status), output))
+}
+
+/// Shell that runs external commands attached to the terminal.
+pub struct TerminalShell {
+    /// Coordination with the console's key handler, if the console is attached to a TTY.
+    suspend: Option<Arc<SuspendState>>,
+}
+
+impl TerminalShell {
+    /// Creates a new shell that suspends the console via `suspend` while commands run.
+    pub(crate) fn new(suspend: Option<Arc<SuspendState>>) -> Self {
+        Self { suspend }
+    }
+
+    /// Runs `op` with the console suspended, if needed, so that the external command can freely
+    /// interact with the terminal.
+    fn suspended<T, F: FnOnce() -> io::Result<T>>(&self, op: F) -> io::Result<T> {
+        match self.suspend.as_ref() {
+            Some(suspend) => {
+                suspend.suspend()?;
+                let result = op();
+                suspend.resume()?;
+                result
+            }
+            None => op(),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl Shell for TerminalShell {
+    async fn run(&self, command: &str) -> io::Result<i32> {
+        self.suspended(|| run_command(command))
+    }
+
+    async fn capture(&self, command: &str, max_length: usize) -> io::Result<(i32, Vec<u8>)> {
+        self.suspended(|| capture_command(command, max_length))
+    }
+}
+
+#[cfg(all(test, not(target_os = "windows")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_command_exit_codes() {
+        assert_eq!(0, run_command("true").unwrap());
+        assert_eq!(5, run_command("exit 5").unwrap());
+        assert_eq!(128 + 15, run_command("kill -TERM $$").unwrap());
+    }
+
+    #[test]
+    fn test_run_command_has_environment() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.txt");
+        std::env::set_var("ENDBASIC_TEST_RUN_COMMAND", "the value");
+        let status =
+            run_command(&format!("echo \"$ENDBASIC_TEST_RUN_COMMAND\" >'{}'", path.display()))
+                .unwrap();
+        assert_eq!(0, status);
+        assert_eq!("the value\n", std::fs::read_to_string(path).unwrap());
+    }
+
+    #[test]
+    fn test_capture_command_output() {
+        assert_eq!(
+            (0, b"hello world\n".to_vec()),
+            capture_command("echo hello world", 100).unwrap()
+        );
+        assert_eq!((0, vec![]), capture_command("echo error >&2", 100).unwrap());
+        assert_eq!(
+            (3, b"partial".to_vec()),
+            capture_command("printf partial; exit 3", 100).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_capture_command_too_long() {
+        let (_status, output) = capture_command("while :; do echo xxxxxxxxxx; done", 100).unwrap();
+        assert_eq!(101, output.len());
+
+        let (status, output) = capture_command("printf 1234567890", 10).unwrap();
+        assert_eq!(0, status);
+        assert_eq!(b"1234567890", output.as_slice());
+    }
+
+    #[test]
+    fn test_terminal_shell_without_tty() {
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let shell = TerminalShell::new(None);
+        assert_eq!(2, runtime.block_on(shell.run("exit 2")).unwrap());
+        assert_eq!((0, b"x\n".to_vec()), runtime.block_on(shell.capture("echo x", 10)).unwrap());
+    }
+}
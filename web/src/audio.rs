@@ -0,0 +1,92 @@
+// EndBASIC
+// Copyright 2025 Julio Merino
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License.  You may obtain a copy
+// of the License at:
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.  See the
+// License for the specific language governing permissions and limitations
+// under the License.
+
+//! Implementation of the audio device using the browser's Web Audio API.
+
+use crate::canvas::js_value_to_io_error;
+use endbasic_std::audio::Audio;
+use std::io;
+use std::time::Duration;
+use web_sys::{AudioContext, OscillatorType};
+
+/// Amplitude of the generated tones as a fraction of the maximum volume.
+const TONE_VOLUME: f32 = 0.25;
+
+/// Implementation of the EndBASIC audio operations that schedules square waves on an audio
+/// context, one after the other.
+#[derive(Default)]
+pub(crate) struct WebAudio {
+    /// The audio context, lazily created on first use because browsers refuse to start audio
+    /// before the user has interacted with the page.
+    context: Option<AudioContext>,
+
+    /// Time, in the clock of the `context`, at which the last scheduled tone ends.
+    next_start: f64,
+}
+
+impl WebAudio {
+    /// Gets the audio context, creating it if necessary, or returns `None` if the browser does not
+    /// support audio.
+    fn get_context(&mut self) -> Option<&AudioContext> {
+        if self.context.is_none() {
+            match AudioContext::new() {
+                Ok(context) => self.context = Some(context),
+                Err(e) => {
+                    crate::log(&format!("Failed to create audio context: {:?}", e));
+                    return None;
+                }
+            }
+        }
+        self.context.as_ref()
+    }
+}
+
+impl Audio for WebAudio {
+    fn tone(&mut self, freq: f64, duration: Duration) -> io::Result<()> {
+        let next_start = self.next_start;
+        let context = match self.get_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+
+        let oscillator = context.create_oscillator().map_err(js_value_to_io_error)?;
+        oscillator.set_type(OscillatorType::Square);
+        oscillator.frequency().set_value(freq as f32);
+
+        let gain = context.create_gain().map_err(js_value_to_io_error)?;
+        gain.gain().set_value(TONE_VOLUME);
+
+        oscillator.connect_with_audio_node(&gain).map_err(js_value_to_io_error)?;
+        gain.connect_with_audio_node(&context.destination()).map_err(js_value_to_io_error)?;
+
+        let start = next_start.max(context.current_time());
+        let end = start + duration.as_secs_f64();
+        oscillator.start_with_when(start).map_err(js_value_to_io_error)?;
+        oscillator.stop_with_when(end).map_err(js_value_to_io_error)?;
+        self.next_start = end;
+        Ok(())
+    }
+
+    fn rest(&mut self, duration: Duration) -> io::Result<()> {
+        let next_start = self.next_start;
+        let context = match self.get_context() {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+
+        self.next_start = next_start.max(context.current_time()) + duration.as_secs_f64();
+        Ok(())
+    }
+}
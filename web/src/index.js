@@ -53,6 +53,11 @@ if (!isMobile) {
 }
 
 var wt = new endbasic_web.WebTerminal(terminal, __SERVICE_URL__);
+if (__QUIET__) {
+    wt.set_quiet();
+} else if (__BANNER__ !== null) {
+    wt.set_banner(__BANNER__);
+}
 
 var osk = wt.on_screen_keyboard();
 var mobileInput = document.getElementById('mobile-input');
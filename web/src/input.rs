@@ -65,6 +65,7 @@ fn on_key_event_into_key(dom_event: KeyboardEvent) -> Key {
         b'M' if dom_event.ctrl_key() => Key::NewLine,
         b'N' if dom_event.ctrl_key() => Key::ArrowDown,
         b'P' if dom_event.ctrl_key() => Key::ArrowUp,
+        b'R' if dom_event.ctrl_key() => Key::Search,
         _ => {
             let printable = !dom_event.alt_key() && !dom_event.ctrl_key() && !dom_event.meta_key();
             let chars = dom_event.key().chars().collect::<Vec<char>>();
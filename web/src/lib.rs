@@ -25,8 +25,12 @@
 use async_channel::{Receiver, Sender};
 use endbasic_core::exec::{Error, Result, Signal, YieldNowFn};
 use endbasic_core::LineCol;
+use endbasic_repl::autosave::{print_recovery_notice, Autosave, AutosaveCommand};
+use endbasic_repl::history::{History, DEFAULT_MAX_ENTRIES};
 use endbasic_std::console::{Console, GraphicsConsole};
+use endbasic_std::env::MemoryEnvironment;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::future::Future;
 use std::io;
 use std::pin::Pin;
@@ -43,12 +47,17 @@ use web_sys::HtmlCanvasElement;
 #[cfg(test)]
 wasm_bindgen_test_configure!(run_in_browser);
 
+/// Maximum number of bytes that programs can allocate for arrays and strings.
+const MEMORY_LIMIT: usize = 128 * 1024 * 1024;
+
+mod audio;
+use audio::WebAudio;
 mod canvas;
 use canvas::CanvasRasterOps;
 mod input;
 use input::{OnScreenKeyboard, WebInput, WebInputOps};
 mod store;
-use store::WebDriveFactory;
+use store::{WebDriveFactory, WebHistoryStore, WebTokenStore};
 
 #[wasm_bindgen]
 extern "C" {
@@ -194,11 +203,11 @@ impl Yielder {
 }
 
 /// Sets up the common storage drives.
-fn setup_storage(storage: &mut endbasic_std::storage::Storage) {
+async fn setup_storage(storage: &mut endbasic_std::storage::Storage) {
     storage.register_scheme("demos", Box::from(endbasic_repl::demos::DemoDriveFactory::default()));
-    storage.mount("demos", "demos://").expect("Demos drive shouldn't fail to mount");
+    storage.mount("demos", "demos://", false).await.expect("Demos drive shouldn't fail to mount");
     storage.register_scheme("local", Box::from(WebDriveFactory::default()));
-    storage.mount("local", "local://").expect("Web drive shouldn't fail to mount");
+    storage.mount("local", "local://", false).await.expect("Web drive shouldn't fail to mount");
     storage.cd("local:").expect("Local drive was just registered");
 }
 
@@ -210,6 +219,7 @@ pub struct WebTerminal {
     on_screen_keyboard: OnScreenKeyboard,
     service_url: String,
     signals_chan: (Sender<Signal>, Receiver<Signal>),
+    banner: endbasic_repl::Banner,
 }
 
 #[wasm_bindgen]
@@ -228,7 +238,18 @@ impl WebTerminal {
         let input_ops = WebInputOps(input);
         let console = GraphicsConsole::new(input_ops, raster_ops, None, None).unwrap();
 
-        Self { yielder, console, on_screen_keyboard, service_url, signals_chan }
+        let banner = endbasic_repl::Banner::Default;
+        Self { yielder, console, on_screen_keyboard, service_url, signals_chan, banner }
+    }
+
+    /// Replaces the welcome message shown at startup with the custom `text`.
+    pub fn set_banner(&mut self, text: String) {
+        self.banner = endbasic_repl::Banner::Custom(text);
+    }
+
+    /// Suppresses the welcome message shown at startup.
+    pub fn set_quiet(&mut self) {
+        self.banner = endbasic_repl::Banner::Quiet;
     }
 
     /// Generates a new `OnScreenKeyboard` that can inject key events into this terminal.
@@ -262,21 +283,44 @@ impl WebTerminal {
             None => log_and_panic!("Failed to get window"),
         };
 
+        // Let URL parameters such as `env.NAME=value` seed the environment variables that programs
+        // can query, as the browser offers no other environment to pull them from.
+        let mut env_vars = HashMap::default();
+        for (name, value) in location.query_pairs() {
+            if let Some(name) = name.strip_prefix("env.") {
+                env_vars.insert(name.to_owned(), value.into_owned());
+            }
+        }
+
         let yielder = self.yielder.clone();
 
         let console = Rc::from(RefCell::from(self.console));
         let mut builder = endbasic_std::MachineBuilder::default()
+            .with_audio(Rc::from(RefCell::from(WebAudio::default())))
             .with_console(console.clone())
+            .with_environment(Rc::from(RefCell::from(MemoryEnvironment::new(env_vars))))
+            .with_http_client(Rc::from(endbasic_client::ReqwestHttpClient::default()))
             .with_yield_now_fn(Yielder::new_yield_now_fn(self.yielder))
             .with_signals_chan(self.signals_chan)
+            .with_memory_limit(MEMORY_LIMIT)
             .with_sleep_fn(Box::from(move |d, pos| js_sleep(d, pos, yielder.clone())))
-            .make_interactive()
-            .with_program(Rc::from(RefCell::from(endbasic_repl::editor::Editor::default())));
+            .make_interactive();
+        let autosave = Rc::from(RefCell::from(Autosave::new(builder.get_storage())));
+        let editor = endbasic_repl::editor::Editor::default().with_autosave(autosave.clone());
+        let mut builder = builder.with_program(Rc::from(RefCell::from(editor)));
 
         let program = builder.get_program();
 
         let storage = builder.get_storage();
-        setup_storage(&mut storage.borrow_mut());
+        setup_storage(&mut storage.borrow_mut()).await;
+
+        let cloud = builder.is_feature_enabled(endbasic_std::Feature::Cloud);
+        let service =
+            Rc::from(RefCell::from(endbasic_client::CloudService::new(&self.service_url)?));
+        if cloud {
+            builder =
+                builder.with_doctor_check(endbasic_client::ServiceCheck::new(service.clone()));
+        }
 
         let mut machine = match builder.build() {
             Ok(machine) => machine,
@@ -288,17 +332,25 @@ impl WebTerminal {
             }
         };
 
-        let service =
-            Rc::from(RefCell::from(endbasic_client::CloudService::new(&self.service_url)?));
-        endbasic_client::add_all(
-            &mut machine,
-            service,
-            console.clone(),
-            storage.clone(),
-            format!("{}/", location.origin().unicode_serialization()),
-        );
+        machine.add_callable(AutosaveCommand::new(autosave.clone()));
+        if cloud {
+            let token_store: Rc<RefCell<dyn endbasic_client::TokenStore>> =
+                Rc::from(RefCell::from(WebTokenStore::from_window()));
+            endbasic_client::add_all(
+                &mut machine,
+                service.clone(),
+                console.clone(),
+                storage.clone(),
+                Some(token_store.clone()),
+                endbasic_client::RetryPolicy::default()
+                    .with_sleep_fn(Box::from(|d: Duration| do_sleep(d.as_millis() as i32, ()))),
+                format!("{}/", location.origin().unicode_serialization()),
+            );
+            endbasic_client::restore_session(service, storage.clone(), token_store).await;
+        }
 
-        endbasic_repl::print_welcome(console.clone())?;
+        endbasic_repl::print_banner(console.clone(), &self.banner)?;
+        print_recovery_notice(console.clone(), storage.clone()).await?;
 
         let mut auto_run = None;
         for (name, value) in location.query_pairs() {
@@ -325,10 +377,21 @@ impl WebTerminal {
             }
         }
 
-        endbasic_repl::try_load_autoexec(&mut machine, console.clone(), storage).await?;
+        endbasic_repl::try_load_autoexec(&mut machine, console.clone(), storage.clone()).await?;
+        let history = Rc::from(RefCell::from(History::new(
+            Box::from(WebHistoryStore::from_window()),
+            DEFAULT_MAX_ENTRIES,
+        )));
         loop {
-            let result =
-                endbasic_repl::run_repl_loop(&mut machine, console.clone(), program.clone()).await;
+            let result = endbasic_repl::run_repl_loop(
+                &mut machine,
+                console.clone(),
+                storage.clone(),
+                program.clone(),
+                Some(autosave.clone()),
+                Some(history.clone()),
+            )
+            .await;
             let mut console = console.borrow_mut();
             match result {
                 Ok(exit_code) => {
@@ -13,10 +13,13 @@
 // License for the specific language governing permissions and limitations
 // under the License.
 
-//! Implementation of a drive that uses the browser's local storage.
+//! Implementation of a drive, a token store, and a history store that use the browser's local
+//! storage.
 
 use async_trait::async_trait;
-use endbasic_std::storage::{Drive, DriveFactory, DriveFiles, Metadata};
+use endbasic_client::TokenStore;
+use endbasic_repl::history::HistoryStore;
+use endbasic_std::storage::{Drive, DriveFactory, DriveFiles, Metadata, Storage};
 use std::collections::BTreeMap;
 use std::io;
 use time::{OffsetDateTime, UtcOffset};
@@ -168,7 +171,7 @@ impl WebDrive {
             if let Some(key) = Key::parse(&key) {
                 let canonical = key.canonical();
                 if key != canonical {
-                    self.rename(&key, &canonical)?;
+                    self.rename_key(&key, &canonical)?;
                 }
             }
         }
@@ -176,7 +179,7 @@ impl WebDrive {
     }
 
     /// Renames a file stored with an `old` key to a `new` key.
-    fn rename(&self, old: &Key, new: &Key) -> io::Result<()> {
+    fn rename_key(&self, old: &Key, new: &Key) -> io::Result<()> {
         let old = old.serialized();
         let new = new.serialized();
 
@@ -286,6 +289,15 @@ impl Drive for WebDrive {
         Ok(entry.content)
     }
 
+    async fn rename(&mut self, old: &str, new: &str) -> io::Result<()> {
+        let old = Key::for_name(old);
+        let new = Key::for_name(new);
+        if old == new {
+            return self.get_entry(&old).map(|_| ());
+        }
+        self.rename_key(&old, &new)
+    }
+
     async fn put(&mut self, name: &str, content: &[u8]) -> io::Result<()> {
         let key = Key::for_name(name);
 
@@ -304,12 +316,83 @@ impl Drive for WebDrive {
     }
 }
 
+/// Token store that keeps the login session in the browser's local storage.
+pub struct WebTokenStore {
+    /// Instance of the window's local storage.
+    storage: web_sys::Storage,
+}
+
+impl WebTokenStore {
+    /// Key under which the session is stored.  Must not look like a program key.
+    const KEY: &'static str = "endbasic-session";
+
+    /// Creates a new token store for the current window.
+    pub fn from_window() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        Self { storage }
+    }
+}
+
+impl TokenStore for WebTokenStore {
+    fn load(&self) -> io::Result<Option<String>> {
+        self.storage.get(Self::KEY).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to get saved session: {:?}", e))
+        })
+    }
+
+    fn save(&mut self, data: &str) -> io::Result<()> {
+        self.storage.set(Self::KEY, data).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to save session: {:?}", e))
+        })
+    }
+
+    fn delete(&mut self) -> io::Result<()> {
+        self.storage.delete(Self::KEY).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to delete saved session: {:?}", e))
+        })
+    }
+}
+
+/// History store that keeps the lines entered in the REPL in the browser's local storage.
+pub struct WebHistoryStore {
+    /// Instance of the window's local storage.
+    storage: web_sys::Storage,
+}
+
+impl WebHistoryStore {
+    /// Key under which the history is stored.  Must not look like a program key.
+    const KEY: &'static str = "endbasic-history";
+
+    /// Creates a new history store for the current window.
+    pub fn from_window() -> Self {
+        let window = web_sys::window().unwrap();
+        let storage = window.local_storage().unwrap().unwrap();
+        Self { storage }
+    }
+}
+
+impl HistoryStore for WebHistoryStore {
+    fn load(&self) -> io::Result<Option<String>> {
+        self.storage.get(Self::KEY).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to get saved history: {:?}", e))
+        })
+    }
+
+    fn save(&mut self, data: &str) -> io::Result<()> {
+        self.storage.set(Self::KEY, data).map_err(|e| {
+            io::Error::new(io::ErrorKind::Other, format!("Failed to save history: {:?}", e))
+        })
+    }
+}
+
 /// Factory for web drives.
 #[derive(Default)]
 pub struct WebDriveFactory {}
 
+#[async_trait(?Send)]
 impl DriveFactory for WebDriveFactory {
-    fn create(&self, target: &str) -> io::Result<Box<dyn Drive>> {
+    async fn create(&self, target: &str, _storage: &Storage) -> io::Result<Box<dyn Drive>> {
         if target.is_empty() {
             Ok(Box::from(WebDrive::from_window()))
         } else {
@@ -483,6 +566,22 @@ mod tests {
         );
     }
 
+    #[wasm_bindgen_test]
+    async fn test_webdrive_rename() {
+        let mut webdrive = WebDrive::from_window();
+        webdrive.storage.clear().unwrap();
+        webdrive.storage.set("endbasic-program:OLD.BAS", "the entry").unwrap();
+
+        webdrive.rename("old.bas", "new.bas").await.unwrap();
+        assert!(webdrive.storage.get("endbasic-program:OLD.BAS").unwrap().is_none());
+        assert_eq!("the entry", webdrive.storage.get("endbasic-program:NEW.BAS").unwrap().unwrap());
+
+        assert_eq!(
+            "File not found",
+            format!("{}", webdrive.rename("old.bas", "other.bas").await.unwrap_err())
+        );
+    }
+
     #[wasm_bindgen_test]
     fn test_webdrive_system_path() {
         let webdrive = WebDrive::from_window();
@@ -1,3 +1,4 @@
+const fs = require("fs");
 const path = require("path");
 const CopyWebpackPlugin = require("copy-webpack-plugin");
 const HtmlWebpackPlugin = require('html-webpack-plugin');
@@ -20,6 +21,19 @@ function getServiceUrl() {
     }
 }
 
+function getBanner() {
+    var banner_file = process.env.BANNER_FILE;
+    if (banner_file === undefined || banner_file === "") {
+        return "null";
+    }
+    return JSON.stringify(fs.readFileSync(banner_file, "utf8"));
+}
+
+function getQuiet() {
+    var quiet = process.env.QUIET;
+    return JSON.stringify(quiet !== undefined && quiet !== "" && quiet !== "0");
+}
+
 module.exports = {
     mode: "production",
     entry: {
@@ -74,6 +88,8 @@ module.exports = {
         }),
 
         new DefinePlugin({
+            __BANNER__: getBanner(),
+            __QUIET__: getQuiet(),
             __SERVICE_URL__: getServiceUrl()
         })
     ],